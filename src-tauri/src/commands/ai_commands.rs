@@ -1,10 +1,19 @@
 use serde_json::{self, Value as JsonValue};
-use tauri::State;
+use tauri::{AppHandle, State};
 use tracing::{debug, warn};
 
 use crate::models::ai::{TaskParseRequest, TaskParseResponse};
-use crate::models::ai_types::AiStatusDto;
+use crate::models::ai_types::{
+    ActionItemExtractionDto, AiDebugEntry, AiStatusDto, TaskDecompositionDto,
+};
+use crate::models::memory::{ExportedFile, MemoryExportBundle};
 
+use crate::services::action_claims::UnverifiedAction;
+use crate::services::ai_agent_service::ChatAttachment;
+use crate::services::progress::ProgressReporter;
+use crate::services::project_service::resolve_parsed_project;
+
+use super::operations::start_operation;
 use super::{AppState, CommandError, CommandResult};
 
 pub(crate) async fn tasks_parse_ai_impl(
@@ -19,6 +28,17 @@ pub(crate) async fn tasks_parse_ai_impl(
         ));
     }
 
+    let mut request = request;
+    let projects = app_state
+        .projects()
+        .parse_options()
+        .map_err(CommandError::from)?;
+    if !projects.is_empty() {
+        let context = request.context.get_or_insert_with(Default::default);
+        context.projects.get_or_insert(projects);
+    }
+    let parse_context = request.context.clone();
+
     let has_context = request.context.is_some();
     debug!(
         target: "app::command",
@@ -28,7 +48,8 @@ pub(crate) async fn tasks_parse_ai_impl(
 
     let service = app_state.ai();
     match service.parse_task(request).await {
-        Ok(response) => {
+        Ok(mut response) => {
+            resolve_parsed_project(&mut response.payload, parse_context.as_ref());
             let correlation_id = response
                 .ai
                 .metadata
@@ -72,7 +93,11 @@ pub(crate) async fn ai_generate_recommendations_impl(
         Ok(dto) => {
             let count = dto.recommendations.len();
             let value = serde_json::to_value(dto).map_err(|err| {
-                CommandError::new("UNKNOWN", format!("推荐结果序列化失败: {err}"), None)
+                CommandError::new(
+                    "SERIALIZATION_ERROR",
+                    format!("推荐结果序列化失败: {err}"),
+                    None,
+                )
             })?;
             debug!(
                 target: "app::command",
@@ -103,7 +128,11 @@ pub(crate) async fn ai_plan_schedule_impl(
         Ok(dto) => {
             let count = dto.items.len();
             let value = serde_json::to_value(dto).map_err(|err| {
-                CommandError::new("UNKNOWN", format!("排程结果序列化失败: {err}"), None)
+                CommandError::new(
+                    "SERIALIZATION_ERROR",
+                    format!("排程结果序列化失败: {err}"),
+                    None,
+                )
             })?;
             debug!(
                 target: "app::command",
@@ -119,6 +148,87 @@ pub(crate) async fn ai_plan_schedule_impl(
     }
 }
 
+pub(crate) async fn ai_extract_action_items_impl(
+    app_state: &AppState,
+    text: String,
+) -> CommandResult<ActionItemExtractionDto> {
+    if text.trim().is_empty() {
+        return Err(CommandError::new(
+            "VALIDATION_ERROR",
+            "会议记录内容不能为空",
+            None,
+        ));
+    }
+
+    debug!(
+        target: "app::command",
+        text_len = text.len(),
+        "ai_extract_action_items invoked"
+    );
+
+    let service = app_state.ai();
+    match service.extract_action_items(&text).await {
+        Ok(extraction) => {
+            debug!(
+                target: "app::command",
+                item_count = extraction.items.len(),
+                duplicate_count = extraction.duplicate_count,
+                chunk_count = extraction.chunk_count,
+                "ai_extract_action_items completed"
+            );
+            Ok(extraction)
+        }
+        Err(error) => {
+            warn!(
+                target: "app::command",
+                error = %error,
+                "ai_extract_action_items failed"
+            );
+            Err(CommandError::from(error))
+        }
+    }
+}
+
+pub(crate) async fn tasks_decompose_ai_impl(
+    app_state: &AppState,
+    task_id: String,
+    max_subtasks: Option<usize>,
+) -> CommandResult<TaskDecompositionDto> {
+    if task_id.trim().is_empty() {
+        return Err(CommandError::new(
+            "VALIDATION_ERROR",
+            "任务 ID 不能为空",
+            None,
+        ));
+    }
+
+    debug!(
+        target: "app::command",
+        task_id = %task_id,
+        "tasks_decompose_ai invoked"
+    );
+
+    let service = app_state.ai();
+    match service.decompose_task(task_id.trim(), max_subtasks).await {
+        Ok(decomposition) => {
+            debug!(
+                target: "app::command",
+                subtask_count = decomposition.subtasks.len(),
+                "tasks_decompose_ai completed"
+            );
+            Ok(decomposition)
+        }
+        Err(error) => {
+            warn!(
+                target: "app::command",
+                error = %error,
+                "tasks_decompose_ai failed"
+            );
+            Err(CommandError::from(error))
+        }
+    }
+}
+
 pub(crate) async fn ai_status_impl(app_state: &AppState) -> CommandResult<AiStatusDto> {
     debug!(target: "app::command", "ai_status invoked");
 
@@ -136,6 +246,18 @@ pub(crate) async fn ai_status_impl(app_state: &AppState) -> CommandResult<AiStat
     }
 }
 
+pub(crate) async fn ai_debug_get_impl(
+    app_state: &AppState,
+    correlation_id: String,
+) -> CommandResult<AiDebugEntry> {
+    debug!(target: "app::command", correlation_id = %correlation_id, "ai_debug_get invoked");
+
+    let service = app_state.ai();
+    service
+        .debug_entry(&correlation_id)
+        .map_err(CommandError::from)
+}
+
 #[tauri::command]
 pub async fn tasks_parse_ai(
     state: State<'_, AppState>,
@@ -160,19 +282,53 @@ pub async fn ai_plan_schedule(
     ai_plan_schedule_impl(state.inner(), payload).await
 }
 
+#[tauri::command]
+pub async fn ai_extract_action_items(
+    state: State<'_, AppState>,
+    text: String,
+) -> CommandResult<ActionItemExtractionDto> {
+    ai_extract_action_items_impl(state.inner(), text).await
+}
+
+/// Proposed subtasks for review; creating them is left to the caller.
+#[tauri::command]
+pub async fn tasks_decompose_ai(
+    state: State<'_, AppState>,
+    task_id: String,
+    max_subtasks: Option<usize>,
+) -> CommandResult<TaskDecompositionDto> {
+    tasks_decompose_ai_impl(state.inner(), task_id, max_subtasks).await
+}
+
 #[tauri::command]
 pub async fn ai_status(state: State<'_, AppState>) -> CommandResult<AiStatusDto> {
     ai_status_impl(state.inner()).await
 }
 
+/// Redacted request/response of one AI call, recorded while the AI debug
+/// log setting is on.
+#[tauri::command]
+pub async fn ai_debug_get(
+    state: State<'_, AppState>,
+    correlation_id: String,
+) -> CommandResult<AiDebugEntry> {
+    ai_debug_get_impl(state.inner(), correlation_id).await
+}
+
 // Agent chat structures
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AgentChatRequest {
     pub conversation_id: String,
     pub message: String,
+    /// Tasks, goals or planning sessions the message refers to.
+    #[serde(default)]
+    pub attachments: Vec<ChatAttachment>,
+    /// Skip memory storage and retrieval for this conversation.
+    #[serde(default)]
+    pub incognito: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AgentChatResponse {
     pub message: String,
@@ -181,13 +337,18 @@ pub struct AgentChatResponse {
     pub metadata: AgentChatMetadata,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AgentChatMetadata {
     pub tokens_used: std::collections::HashMap<String, u64>,
     pub latency_ms: u128,
     pub memory_entries_used: usize,
     pub tools_executed: Vec<String>,
+    /// The reply was reused from an earlier identical question.
+    pub cached: bool,
+    /// Actions the reply claims but no tool call performed; the UI should
+    /// warn about them.
+    pub unverified_actions: Vec<UnverifiedAction>,
 }
 
 pub(crate) async fn ai_agent_chat_impl(
@@ -210,16 +371,35 @@ pub(crate) async fn ai_agent_chat_impl(
         ));
     }
 
+    if request
+        .attachments
+        .iter()
+        .any(|attachment| attachment.id.trim().is_empty())
+    {
+        return Err(CommandError::new(
+            "VALIDATION_ERROR",
+            "引用条目的ID不能为空",
+            None,
+        ));
+    }
+
     debug!(
         target: "app::command",
         conversation_id = %request.conversation_id,
         message_len = request.message.len(),
+        attachments = request.attachments.len(),
+        incognito = request.incognito,
         "ai_agent_chat invoked"
     );
 
     let agent_service = app_state.agent();
     match agent_service
-        .chat(&request.conversation_id, &request.message)
+        .chat_with_attachments(
+            &request.conversation_id,
+            &request.message,
+            &request.attachments,
+            request.incognito,
+        )
         .await
     {
         Ok(response) => {
@@ -254,6 +434,8 @@ pub(crate) async fn ai_agent_chat_impl(
                     latency_ms: response.metadata.latency_ms,
                     memory_entries_used: response.metadata.memory_entries_used,
                     tools_executed: response.metadata.tools_executed,
+                    cached: response.metadata.cached.unwrap_or(false),
+                    unverified_actions: response.metadata.unverified_actions,
                 },
             })
         }
@@ -274,12 +456,16 @@ pub async fn ai_agent_chat(
     state: State<'_, AppState>,
     conversation_id: String,
     message: String,
+    attachments: Option<Vec<ChatAttachment>>,
+    incognito: Option<bool>,
 ) -> CommandResult<AgentChatResponse> {
     ai_agent_chat_impl(
         state.inner(),
         AgentChatRequest {
             conversation_id,
             message,
+            attachments: attachments.unwrap_or_default(),
+            incognito: incognito.unwrap_or(false),
         },
     )
     .await
@@ -318,6 +504,23 @@ pub mod testing {
         ai_plan_schedule_impl(app_state, payload).await
     }
 
+    /// Internal helper exposed for integration testing of command logic.
+    pub async fn ai_extract_action_items(
+        app_state: &AppState,
+        text: String,
+    ) -> CommandResult<ActionItemExtractionDto> {
+        ai_extract_action_items_impl(app_state, text).await
+    }
+
+    /// Internal helper exposed for integration testing of command logic.
+    pub async fn tasks_decompose_ai(
+        app_state: &AppState,
+        task_id: String,
+        max_subtasks: Option<usize>,
+    ) -> CommandResult<TaskDecompositionDto> {
+        tasks_decompose_ai_impl(app_state, task_id, max_subtasks).await
+    }
+
     /// Internal helper exposed for integration testing of command logic.
     pub async fn ai_status(app_state: &AppState) -> CommandResult<AiStatusDto> {
         ai_status_impl(app_state).await
@@ -344,7 +547,7 @@ pub mod testing {
         app_state: &AppState,
         request: MemoryExportRequest,
     ) -> CommandResult<MemoryExportResponse> {
-        memory_export_impl(app_state, request).await
+        memory_export_impl(app_state, request, &ProgressReporter::silent()).await
     }
 
     /// Internal helper exposed for integration testing of memory clear logic.
@@ -356,6 +559,7 @@ pub mod testing {
     }
 }
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -363,7 +567,7 @@ pub struct ChatRequest {
     pub message: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ChatResponse {
     pub message: String,
     pub timestamp: String,
@@ -422,13 +626,13 @@ pub struct MemorySearchRequest {
     pub filters: Option<std::collections::HashMap<String, String>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct MemorySearchResponse {
     pub entries: Vec<MemoryEntryDto>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct MemoryEntryDto {
     pub id: String,
@@ -442,14 +646,21 @@ pub struct MemoryEntryDto {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MemoryExportRequest {
     pub path: String,
+    /// Writes one zip or `.tar.gz` file at `path` instead of a directory.
+    #[serde(default)]
+    pub bundle: Option<MemoryExportBundle>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct MemoryExportResponse {
     pub success: bool,
     pub path: String,
     pub message: String,
+    /// Written files with their SHA-256 checksums; empty when the export
+    /// failed.
+    #[serde(default)]
+    pub files: Vec<ExportedFile>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -457,7 +668,7 @@ pub struct MemoryClearRequest {
     pub conversation_id: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct MemoryClearResponse {
     pub success: bool,
@@ -578,6 +789,7 @@ fn parse_conversation_content(content: &str) -> (String, String) {
 pub(crate) async fn memory_export_impl(
     app_state: &AppState,
     request: MemoryExportRequest,
+    progress: &ProgressReporter,
 ) -> CommandResult<MemoryExportResponse> {
     if request.path.trim().is_empty() {
         return Err(CommandError::new(
@@ -599,19 +811,26 @@ pub(crate) async fn memory_export_impl(
         format: crate::models::memory::MemoryExportFormat::Archive,
         date_range: None,
         include_metadata: true,
+        bundle: request.bundle,
     };
 
-    match memory_service.export_memory_archive(&export_options).await {
-        Ok(()) => {
+    let result = memory_service
+        .export_memory_archive_with_progress(&export_options, progress)
+        .await;
+    app_state.operations().finish(progress, &result);
+    match result {
+        Ok(files) => {
             debug!(
                 target: "app::command",
                 path = %request.path,
+                files = files.len(),
                 "memory_export completed successfully"
             );
             Ok(MemoryExportResponse {
                 success: true,
                 path: request.path,
                 message: "记忆数据导出成功".to_string(),
+                files,
             })
         }
         Err(error) => {
@@ -625,6 +844,7 @@ pub(crate) async fn memory_export_impl(
                 success: false,
                 path: request.path,
                 message: format!("导出失败: {}", error),
+                files: Vec::new(),
             })
         }
     }
@@ -716,12 +936,23 @@ pub async fn memory_search(
     memory_search_impl(state.inner(), MemorySearchRequest { query, filters }).await
 }
 
+/// Pass `operation_id` to receive `operation://progress` events and to cancel
+/// through `operation_cancel`.
 #[tauri::command]
 pub async fn memory_export(
+    app: AppHandle,
     state: State<'_, AppState>,
     path: String,
+    bundle: Option<MemoryExportBundle>,
+    operation_id: Option<String>,
 ) -> CommandResult<MemoryExportResponse> {
-    memory_export_impl(state.inner(), MemoryExportRequest { path }).await
+    let progress = start_operation(&app, state.inner(), operation_id)?;
+    memory_export_impl(
+        state.inner(),
+        MemoryExportRequest { path, bundle },
+        &progress,
+    )
+    .await
 }
 
 #[tauri::command]
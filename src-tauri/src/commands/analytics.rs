@@ -1,12 +1,17 @@
-use tauri::{async_runtime, State};
+use tauri::{async_runtime, AppHandle, State};
 
 use crate::error::AppError;
 use crate::models::analytics::{
     AnalyticsExportParams, AnalyticsExportResult, AnalyticsHistoryResponse,
-    AnalyticsOverviewResponse, AnalyticsQueryParams,
+    AnalyticsOverviewResponse, AnalyticsQueryParams, ConfidenceCalibration, DayTimeline,
+    DefragmentationSuggestion, TodaySnapshot, YearInReview,
 };
-use crate::models::productivity::{ProductivityScoreHistoryResponse, ProductivityScoreRecord};
+use crate::models::productivity::{
+    ProductivityScoreHistoryResponse, ProductivityScoreRecord, ScoreStreak,
+};
+use crate::models::workload::{WorkloadForecastResponse, WorkloadGranularity, WorkloadHorizon};
 
+use super::operations::start_operation;
 use super::{AppState, CommandError, CommandResult};
 
 #[tauri::command]
@@ -87,28 +92,118 @@ pub async fn analytics_get_latest_productivity_score(
     run_blocking(move || app_state.productivity_score_service().get_latest_score()).await
 }
 
+/// Days in a row that reached the target score, and whether today is on
+/// track to extend them.
+#[tauri::command]
+pub async fn analytics_get_score_streak(state: State<'_, AppState>) -> CommandResult<ScoreStreak> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.analytics().score_streak()).await
+}
+
+/// Live metrics for today so far: completions, focus minutes and the
+/// planned time still ahead.
+#[tauri::command]
+pub async fn analytics_today_snapshot(state: State<'_, AppState>) -> CommandResult<TodaySnapshot> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.analytics().today_snapshot()).await
+}
+
+/// Hit rate of applied blocks per confidence bucket, the curve new plans
+/// are corrected with.
+#[tauri::command]
+pub async fn analytics_confidence_calibration(
+    state: State<'_, AppState>,
+) -> CommandResult<ConfidenceCalibration> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.analytics().confidence_calibration()).await
+}
+
+/// Long-form review of `year` as JSON plus a rendered Markdown version.
+#[tauri::command]
+pub async fn analytics_year_in_review(
+    state: State<'_, AppState>,
+    year: i32,
+) -> CommandResult<YearInReview> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.analytics().year_in_review(year)).await
+}
+
 #[tauri::command]
 pub async fn analytics_get_workload_forecast(
     state: State<'_, AppState>,
     capacity_threshold_hours: Option<f64>,
-) -> CommandResult<Vec<crate::models::workload::WorkloadForecastResponse>> {
+    horizon: Option<WorkloadHorizon>,
+    granularity: Option<WorkloadGranularity>,
+) -> CommandResult<Vec<WorkloadForecastResponse>> {
+    let app_state = state.inner().clone();
+    let horizons = match horizon {
+        Some(horizon) => vec![horizon],
+        None => WorkloadHorizon::ALL.to_vec(),
+    };
+
+    run_blocking(move || {
+        app_state.workload_forecast().generate_forecasts_with(
+            &horizons,
+            granularity.unwrap_or_default(),
+            capacity_threshold_hours,
+        )
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn analytics_get_latest_workload_forecasts(
+    state: State<'_, AppState>,
+    granularity: Option<WorkloadGranularity>,
+) -> CommandResult<Vec<WorkloadForecastResponse>> {
     let app_state = state.inner().clone();
 
     run_blocking(move || {
         app_state
             .workload_forecast()
-            .generate_forecasts(capacity_threshold_hours)
+            .get_all_latest_forecasts(granularity.unwrap_or_default())
     })
     .await
 }
 
 #[tauri::command]
-pub async fn analytics_get_latest_workload_forecasts(
+pub async fn analytics_get_day_timeline(
+    state: State<'_, AppState>,
+    date: String,
+) -> CommandResult<DayTimeline> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.analytics().day_timeline(date)).await
+}
+
+#[tauri::command]
+pub async fn analytics_defragmentation_suggestions(
     state: State<'_, AppState>,
-) -> CommandResult<Vec<crate::models::workload::WorkloadForecastResponse>> {
+    date: Option<String>,
+) -> CommandResult<Vec<DefragmentationSuggestion>> {
     let app_state = state.inner().clone();
+    run_blocking(move || app_state.analytics().suggest_defragmentation(date)).await
+}
 
-    run_blocking(move || app_state.workload_forecast().get_all_latest_forecasts()).await
+/// Recomputes daily snapshots in a date range, emitting
+/// `operation://progress` events; returns the number of days rebuilt.
+#[tauri::command]
+pub async fn analytics_snapshot_recompute(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    start_date: String,
+    end_date: String,
+    operation_id: Option<String>,
+) -> CommandResult<usize> {
+    let app_state = state.inner().clone();
+    let progress = start_operation(&app, &app_state, operation_id)?;
+    run_blocking(move || {
+        let result = app_state
+            .analytics()
+            .recompute_snapshots(&start_date, &end_date, &progress);
+        app_state.operations().finish(&progress, &result);
+        result
+    })
+    .await
 }
 
 async fn run_blocking<T: Send + 'static>(
@@ -116,6 +211,6 @@ async fn run_blocking<T: Send + 'static>(
 ) -> CommandResult<T> {
     async_runtime::spawn_blocking(task)
         .await
-        .map_err(|err| CommandError::new("UNKNOWN", format!("分析任务执行失败: {err}"), None))?
+        .map_err(|err| CommandError::internal(format!("分析任务执行失败: {err}")))?
         .map_err(CommandError::from)
 }
@@ -0,0 +1,8 @@
+use crate::commands::CommandResult;
+use crate::utils::appearance::{appearance_options as palette, AppearanceOptions};
+
+/// Colors and icons accepted on tasks and projects.
+#[tauri::command]
+pub async fn appearance_options() -> CommandResult<AppearanceOptions> {
+    Ok(palette())
+}
@@ -14,6 +14,6 @@ async fn run_blocking<T: Send + 'static>(
 ) -> CommandResult<T> {
     async_runtime::spawn_blocking(task)
         .await
-        .map_err(|err| CommandError::new("UNKNOWN", format!("缓存清除操作执行失败: {err}"), None))?
+        .map_err(|err| CommandError::internal(format!("缓存清除操作执行失败: {err}")))?
         .map_err(CommandError::from)
 }
@@ -0,0 +1,38 @@
+use tauri::{async_runtime, State};
+
+use crate::commands::{AppState, CommandError, CommandResult};
+use crate::error::AppError;
+use crate::models::caldav::{CalDavConnectInput, CalDavStatus, CalDavSyncReport};
+
+/// Connects a CalDAV account, discovering its task and event calendars
+/// unless they are given. Replaces any previously connected account.
+#[tauri::command]
+pub async fn caldav_connect(
+    state: State<'_, AppState>,
+    input: CalDavConnectInput,
+) -> CommandResult<CalDavStatus> {
+    let service = state.caldav();
+    service.connect(input).await.map_err(CommandError::from)
+}
+
+/// Runs one two-way sync of tasks and applied time blocks.
+#[tauri::command]
+pub async fn caldav_sync_now(state: State<'_, AppState>) -> CommandResult<CalDavSyncReport> {
+    let service = state.caldav();
+    service.sync_now().await.map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn caldav_status(state: State<'_, AppState>) -> CommandResult<CalDavStatus> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.caldav().status()).await
+}
+
+async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> CommandResult<T> {
+    async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|err| CommandError::internal(format!("读取 CalDAV 状态失败: {err}")))?
+        .map_err(CommandError::from)
+}
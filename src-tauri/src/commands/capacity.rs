@@ -0,0 +1,54 @@
+use tauri::{async_runtime, AppHandle, Emitter, State};
+use tracing::warn;
+
+use crate::commands::planning::PLANNING_GENERATED_EVENT;
+use crate::commands::{AppState, CommandError, CommandResult};
+use crate::error::AppError;
+use crate::models::capacity::{CapacityWizardAnswer, CapacityWizardResult, CapacityWizardState};
+
+/// Starts the weekly capacity wizard, prefilled with the saved commitments
+/// and focus target.
+#[tauri::command]
+pub async fn capacity_wizard_start(
+    state: State<'_, AppState>,
+) -> CommandResult<CapacityWizardState> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || app_state.capacity_wizard().start()).await
+}
+
+#[tauri::command]
+pub async fn capacity_wizard_answer(
+    state: State<'_, AppState>,
+    id: String,
+    answer: CapacityWizardAnswer,
+) -> CommandResult<CapacityWizardState> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || app_state.capacity_wizard().answer(&id, answer)).await
+}
+
+/// Saves the answers and generates the first weekly plan, which is left
+/// pending for the user to apply.
+#[tauri::command]
+pub async fn capacity_wizard_finish(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> CommandResult<CapacityWizardResult> {
+    let result = state.capacity_wizard().finish(&id).await?;
+
+    if let Err(error) = app.emit(PLANNING_GENERATED_EVENT, &result.plan) {
+        warn!(target: "app::command", event = PLANNING_GENERATED_EVENT, %error, "failed to emit planning event");
+    }
+    Ok(result)
+}
+
+async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> CommandResult<T> {
+    async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|err| CommandError::internal(format!("容量设置向导执行失败: {err}")))?
+        .map_err(CommandError::from)
+}
@@ -0,0 +1,31 @@
+use tauri::{async_runtime, State};
+
+use crate::commands::{AppState, CommandError, CommandResult};
+use crate::error::AppError;
+use crate::models::task::TaskRecord;
+use crate::services::clipboard_watcher::{
+    detect_actionable_text, ClipboardCandidate, ClipboardCaptureInput,
+};
+
+#[tauri::command]
+pub async fn clipboard_inspect(text: String) -> CommandResult<Option<ClipboardCandidate>> {
+    run_blocking(move || Ok(detect_actionable_text(&text))).await
+}
+
+#[tauri::command]
+pub async fn clipboard_capture(
+    state: State<'_, AppState>,
+    payload: ClipboardCaptureInput,
+) -> CommandResult<TaskRecord> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.clipboard().capture(payload)).await
+}
+
+async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> CommandResult<T> {
+    async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|err| CommandError::internal(format!("剪贴板操作执行失败: {err}")))?
+        .map_err(CommandError::from)
+}
@@ -1,5 +1,7 @@
 use crate::commands::AppState;
-use crate::services::community_service::{DetectedPlugin, ExportBundle, ProjectInfo};
+use crate::services::community_service::{
+    CommunityExportFormat, DetectedPlugin, ExportBundle, ProjectInfo,
+};
 use std::path::PathBuf;
 use tauri::{async_runtime, State};
 
@@ -42,6 +44,7 @@ pub async fn community_save_export_to_file(
     state: State<'_, AppState>,
     bundle_json: String,
     file_path: String,
+    format: Option<CommunityExportFormat>,
 ) -> Result<i64, String> {
     let bundle: ExportBundle =
         serde_json::from_str(&bundle_json).map_err(|e| format!("Failed to parse bundle: {}", e))?;
@@ -49,12 +52,29 @@ pub async fn community_save_export_to_file(
     let path = PathBuf::from(&file_path);
     let service = state.community_service.clone();
 
-    async_runtime::spawn(async move { service.save_export_to_file(&bundle, &path).await })
+    let format = format.unwrap_or_default();
+
+    async_runtime::spawn(async move { service.save_export_to_file(&bundle, &path, format).await })
         .await
         .map_err(|e| format!("Task join error: {}", e))?
         .map_err(|e| format!("Failed to save export: {}", e))
 }
 
+/// Read a zip export bundle, verifying its manifest and checksums
+#[tauri::command]
+pub async fn community_read_export_bundle(
+    state: State<'_, AppState>,
+    file_path: String,
+) -> Result<ExportBundle, String> {
+    let path = PathBuf::from(&file_path);
+    let service = state.community_service.clone();
+
+    async_runtime::spawn_blocking(move || service.read_export_bundle(&path))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+        .map_err(|e| format!("Failed to read export bundle: {}", e))
+}
+
 /// List previous exports
 #[tauri::command]
 pub async fn community_list_exports(
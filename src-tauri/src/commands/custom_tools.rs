@@ -0,0 +1,74 @@
+use tauri::{async_runtime, State};
+
+use crate::commands::{AppState, CommandError, CommandResult};
+use crate::error::AppError;
+use crate::models::custom_tool::{CustomToolDefinition, CustomToolRecord};
+use crate::services::tool_registry::ToolAllowlist;
+
+#[tauri::command]
+pub async fn tools_register_custom(
+    state: State<'_, AppState>,
+    payload: CustomToolDefinition,
+) -> CommandResult<CustomToolRecord> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || app_state.custom_tools().register(payload)).await
+}
+
+#[tauri::command]
+pub async fn tools_list_custom(state: State<'_, AppState>) -> CommandResult<Vec<CustomToolRecord>> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || app_state.custom_tools().list()).await
+}
+
+#[tauri::command]
+pub async fn tools_unregister_custom(
+    state: State<'_, AppState>,
+    name: String,
+) -> CommandResult<()> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || app_state.custom_tools().unregister(&name)).await
+}
+
+#[tauri::command]
+pub async fn tools_get_allowlist(state: State<'_, AppState>) -> CommandResult<ToolAllowlist> {
+    Ok(state.custom_tools().allowlist())
+}
+
+#[tauri::command]
+pub async fn tools_set_allowlist(
+    state: State<'_, AppState>,
+    allowlist: ToolAllowlist,
+) -> CommandResult<ToolAllowlist> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || app_state.custom_tools().set_allowlist(allowlist)).await
+}
+
+/// Passing no `tools` lifts the conversation's own restriction.
+#[tauri::command]
+pub async fn tools_set_conversation_allowlist(
+    state: State<'_, AppState>,
+    conversation_id: String,
+    tools: Option<Vec<String>>,
+) -> CommandResult<ToolAllowlist> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || {
+        app_state
+            .custom_tools()
+            .set_conversation_allowlist(&conversation_id, tools)
+    })
+    .await
+}
+
+async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> CommandResult<T> {
+    async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|err| CommandError::internal(format!("自定义工具任务执行失败: {err}")))?
+        .map_err(CommandError::from)
+}
@@ -0,0 +1,77 @@
+use tauri::{async_runtime, State};
+
+use crate::commands::{AppState, CommandError, CommandResult};
+use crate::error::AppError;
+use crate::models::analytics::TodaySnapshot;
+use crate::models::dashboard::{
+    DashboardWidget, GoalProgressSummary, UpcomingDeadline, WellnessWidgetState,
+};
+
+/// Today-so-far summary, cached for 30 seconds. `refresh` bypasses the cache.
+#[tauri::command]
+pub async fn dashboard_widget_today(
+    state: State<'_, AppState>,
+    refresh: Option<bool>,
+) -> CommandResult<DashboardWidget<TodaySnapshot>> {
+    let app_state = state.inner().clone();
+    run_blocking(move || {
+        app_state
+            .dashboard_widgets()
+            .today_summary(refresh.unwrap_or(false))
+    })
+    .await
+}
+
+/// Open tasks due in the next 7 days or overdue, cached for a minute.
+#[tauri::command]
+pub async fn dashboard_widget_deadlines(
+    state: State<'_, AppState>,
+    refresh: Option<bool>,
+) -> CommandResult<DashboardWidget<Vec<UpcomingDeadline>>> {
+    let app_state = state.inner().clone();
+    run_blocking(move || {
+        app_state
+            .dashboard_widgets()
+            .upcoming_deadlines(refresh.unwrap_or(false))
+    })
+    .await
+}
+
+/// Progress of the active top-level goals, cached for 5 minutes.
+#[tauri::command]
+pub async fn dashboard_widget_goals(
+    state: State<'_, AppState>,
+    refresh: Option<bool>,
+) -> CommandResult<DashboardWidget<Vec<GoalProgressSummary>>> {
+    let app_state = state.inner().clone();
+    run_blocking(move || {
+        app_state
+            .dashboard_widgets()
+            .goal_progress(refresh.unwrap_or(false))
+    })
+    .await
+}
+
+/// Focus session, pending nudge and idle adjustments, cached for 15 seconds.
+#[tauri::command]
+pub async fn dashboard_widget_wellness(
+    state: State<'_, AppState>,
+    refresh: Option<bool>,
+) -> CommandResult<DashboardWidget<WellnessWidgetState>> {
+    let app_state = state.inner().clone();
+    run_blocking(move || {
+        app_state
+            .dashboard_widgets()
+            .wellness_state(refresh.unwrap_or(false))
+    })
+    .await
+}
+
+async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> CommandResult<T> {
+    async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|err| CommandError::internal(format!("仪表盘数据加载失败: {err}")))?
+        .map_err(CommandError::from)
+}
@@ -0,0 +1,62 @@
+use tauri::{async_runtime, State};
+
+use crate::commands::{AppState, CommandError, CommandResult};
+use crate::error::AppError;
+use crate::models::data_export::{
+    DataEraseResult, DataExportResult, DataImportParams, DataImportReport,
+};
+
+/// Writes every user-owned record, settings without secrets and the memory
+/// files to one archive.
+#[tauri::command]
+pub async fn data_export_all(state: State<'_, AppState>) -> CommandResult<DataExportResult> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.data_export().export_all()).await
+}
+
+/// Wipes the database, memory files and reports. `confirmation` must be the
+/// exact phrase shown to the user.
+#[tauri::command]
+pub async fn data_erase_all(
+    state: State<'_, AppState>,
+    confirmation: String,
+) -> CommandResult<DataEraseResult> {
+    let app_state = state.inner().clone();
+    run_blocking(move || {
+        let result = app_state.data_export().erase_all(&confirmation)?;
+        let settings = app_state.settings();
+        settings.clear_sensitive()?;
+        settings.invalidate_cache();
+        app_state.analytics().invalidate_cache();
+        Ok(result)
+    })
+    .await
+}
+
+/// Restores the selected domains from an archive written by
+/// `data_export_all`. Run with `dry_run` first to see what would change.
+#[tauri::command]
+pub async fn data_import(
+    state: State<'_, AppState>,
+    params: DataImportParams,
+) -> CommandResult<DataImportReport> {
+    let app_state = state.inner().clone();
+    run_blocking(move || {
+        let report = app_state.data_export().import(params)?;
+        if !report.dry_run {
+            app_state.settings().invalidate_cache();
+            app_state.analytics().invalidate_cache();
+        }
+        Ok(report)
+    })
+    .await
+}
+
+async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> CommandResult<T> {
+    async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|err| CommandError::internal(format!("数据操作执行失败: {err}")))?
+        .map_err(CommandError::from)
+}
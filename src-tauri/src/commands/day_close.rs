@@ -0,0 +1,41 @@
+use tauri::{async_runtime, State};
+
+use crate::commands::{AppState, CommandError, CommandResult};
+use crate::error::AppError;
+use crate::models::day_log::{DayCloseInput, DayCloseResult, DayLogRecord};
+
+#[tauri::command]
+pub async fn day_close(
+    state: State<'_, AppState>,
+    payload: DayCloseInput,
+) -> CommandResult<DayCloseResult> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || app_state.day_close().close_day(payload)).await
+}
+
+#[tauri::command]
+pub async fn day_log_get(state: State<'_, AppState>, date: String) -> CommandResult<DayLogRecord> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || app_state.day_close().get_log(&date)).await
+}
+
+#[tauri::command]
+pub async fn day_log_list(
+    state: State<'_, AppState>,
+    limit: Option<usize>,
+) -> CommandResult<Vec<DayLogRecord>> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || app_state.day_close().list_logs(limit)).await
+}
+
+async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> CommandResult<T> {
+    async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|err| CommandError::internal(format!("每日结算任务执行失败: {err}")))?
+        .map_err(CommandError::from)
+}
@@ -1,7 +1,9 @@
 use tauri::State;
 
 use crate::commands::{AppState, CommandResult};
-use crate::models::dependency::{DependencyCreateInput, DependencyFilter, DependencyValidation};
+use crate::models::dependency::{
+    DependencyCreateInput, DependencyFilter, DependencyType, DependencyValidation,
+};
 
 #[tauri::command]
 pub async fn get_task_dependencies(
@@ -70,6 +72,20 @@ pub async fn add_dependency(
     Ok(dependency)
 }
 
+/// Links `task_ids` in order so each task depends on the previous one.
+#[tauri::command]
+pub async fn dependency_chain_create(
+    state: State<'_, AppState>,
+    task_ids: Vec<String>,
+    dependency_type: Option<DependencyType>,
+) -> CommandResult<Vec<crate::models::dependency::TaskDependency>> {
+    let service = state.dependency_service();
+    service
+        .add_dependency_chain(&task_ids, dependency_type)
+        .await
+        .map_err(Into::into)
+}
+
 #[tauri::command]
 pub async fn remove_dependency(
     state: State<'_, AppState>,
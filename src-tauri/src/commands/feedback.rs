@@ -3,7 +3,7 @@ use tauri::{async_runtime, State};
 
 use crate::commands::{AppState, CommandError, CommandResult};
 use crate::models::ai_feedback::AiFeedbackSurface;
-use crate::services::feedback_service::{FeedbackSubmission, WeeklyDigest};
+use crate::services::feedback_service::{FeedbackPrivacySummary, FeedbackSubmission, WeeklyDigest};
 
 /// Submit AI feedback
 #[tauri::command]
@@ -75,6 +75,18 @@ pub async fn feedback_check_opt_out(state: State<'_, AppState>) -> CommandResult
         .map_err(CommandError::from)
 }
 
+/// Describe what each feedback category stores and whether it is collected
+#[tauri::command]
+pub async fn feedback_get_privacy_summary(
+    state: State<'_, AppState>,
+) -> CommandResult<FeedbackPrivacySummary> {
+    let app_state = state.inner().clone();
+    async_runtime::spawn_blocking(move || app_state.feedback().get_privacy_summary())
+        .await
+        .map_err(|e| CommandError::new("INTERNAL", e.to_string(), None))?
+        .map_err(CommandError::from)
+}
+
 /// Purge all feedback data
 #[tauri::command]
 pub async fn feedback_purge_all(state: State<'_, AppState>) -> CommandResult<i64> {
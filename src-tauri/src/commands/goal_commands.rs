@@ -1,7 +1,12 @@
 use tauri::State;
 
 use crate::commands::{AppState, CommandResult};
-use crate::models::goal::{CreateGoalRequest, Goal, GoalTaskAssociation, GoalWithProgress, UpdateGoalRequest};
+use crate::models::goal::{
+    CreateGoalRequest, Goal, GoalCheckin, GoalTaskAssociation, GoalWithProgress, UpdateGoalRequest,
+};
+
+const DEFAULT_CHECKIN_HISTORY_LIMIT: usize = 20;
+const MAX_CHECKIN_HISTORY_LIMIT: usize = 200;
 
 #[tauri::command]
 pub async fn create_goal(
@@ -86,3 +91,27 @@ pub async fn get_goal_with_progress(
     let service = state.goals();
     service.get_goal_with_progress(&id).map_err(Into::into)
 }
+
+#[tauri::command]
+pub async fn goals_checkin(
+    state: State<'_, AppState>,
+    goal_id: String,
+    note: Option<String>,
+    progress: f64,
+) -> CommandResult<GoalCheckin> {
+    let service = state.goals();
+    service.checkin(&goal_id, note, progress).map_err(Into::into)
+}
+
+#[tauri::command]
+pub async fn goals_checkin_history(
+    state: State<'_, AppState>,
+    goal_id: String,
+    limit: Option<usize>,
+) -> CommandResult<Vec<GoalCheckin>> {
+    let service = state.goals();
+    let limit = limit
+        .unwrap_or(DEFAULT_CHECKIN_HISTORY_LIMIT)
+        .clamp(1, MAX_CHECKIN_HISTORY_LIMIT);
+    service.list_checkins(&goal_id, limit).map_err(Into::into)
+}
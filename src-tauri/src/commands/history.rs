@@ -0,0 +1,21 @@
+use tauri::State;
+
+use crate::commands::{AppState, CommandError, CommandResult};
+use crate::models::history::HistorySummary;
+
+/// Summarizes what got done between `start` and `end` (`YYYY-MM-DD`,
+/// inclusive). The narrative is AI-polished unless `polish` is `false`, no
+/// provider is configured or privacy mode is on.
+#[tauri::command]
+pub async fn history_summarize(
+    state: State<'_, AppState>,
+    start: String,
+    end: String,
+    polish: Option<bool>,
+) -> CommandResult<HistorySummary> {
+    state
+        .history()
+        .summarize(&start, &end, polish.unwrap_or(true))
+        .await
+        .map_err(CommandError::from)
+}
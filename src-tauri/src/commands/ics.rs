@@ -0,0 +1,32 @@
+use tauri::{async_runtime, State};
+
+use crate::commands::{AppState, CommandError, CommandResult};
+use crate::error::AppError;
+use crate::models::ics::{IcsExportInput, IcsExportResult, IcsImportInput, IcsImportResult};
+use crate::services::ics_service::import_events;
+
+/// Writes applied time blocks to an `.ics` file any calendar app can open.
+#[tauri::command]
+pub async fn ics_export(
+    state: State<'_, AppState>,
+    input: Option<IcsExportInput>,
+) -> CommandResult<IcsExportResult> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.ics().export(input.unwrap_or_default())).await
+}
+
+/// Reads an external `.ics` calendar into events for `existingEvents` in
+/// planning constraints. Nothing is stored.
+#[tauri::command]
+pub async fn ics_import(input: IcsImportInput) -> CommandResult<IcsImportResult> {
+    run_blocking(move || import_events(&input)).await
+}
+
+async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> CommandResult<T> {
+    async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|err| CommandError::internal(format!("日历文件处理失败: {err}")))?
+        .map_err(CommandError::from)
+}
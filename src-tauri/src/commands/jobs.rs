@@ -0,0 +1,38 @@
+use tauri::{async_runtime, State};
+
+use crate::commands::{AppState, CommandError, CommandResult};
+use crate::error::AppError;
+use crate::models::job::{BackgroundJobsOverview, OnDemandJobRun};
+
+/// Background jobs in the retry queue, newest first, optionally filtered by
+/// status (`pending`, `succeeded`, `failed`), along with the health of the
+/// supervised worker threads.
+#[tauri::command]
+pub async fn jobs_list(
+    state: State<'_, AppState>,
+    status: Option<String>,
+) -> CommandResult<BackgroundJobsOverview> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || app_state.jobs().overview(status)).await
+}
+
+/// Runs the scheduled jobs that are due when the runtime profile has no
+/// background threads (mobile). Fired reminders and due goal check-ins come
+/// back in the `output` of their runs. Returns nothing on desktop, where the
+/// jobs run on their own and emit events.
+#[tauri::command]
+pub async fn jobs_run_scheduled(state: State<'_, AppState>) -> CommandResult<Vec<OnDemandJobRun>> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || Ok(app_state.on_demand_jobs().run_due(chrono::Utc::now()))).await
+}
+
+async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> CommandResult<T> {
+    async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|err| CommandError::internal(format!("后台任务查询失败: {err}")))?
+        .map_err(CommandError::from)
+}
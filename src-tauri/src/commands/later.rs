@@ -0,0 +1,44 @@
+use tauri::{async_runtime, State};
+
+use crate::commands::{AppState, CommandError, CommandResult};
+use crate::error::AppError;
+use crate::models::later::{LaterItemCreateInput, LaterItemRecord};
+
+#[tauri::command]
+pub async fn later_add(
+    state: State<'_, AppState>,
+    payload: LaterItemCreateInput,
+) -> CommandResult<LaterItemRecord> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || app_state.later().add(payload)).await
+}
+
+#[tauri::command]
+pub async fn later_list(
+    state: State<'_, AppState>,
+    status: Option<String>,
+) -> CommandResult<Vec<LaterItemRecord>> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || app_state.later().list(status)).await
+}
+
+#[tauri::command]
+pub async fn later_complete(
+    state: State<'_, AppState>,
+    id: String,
+) -> CommandResult<LaterItemRecord> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || app_state.later().complete(&id)).await
+}
+
+async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> CommandResult<T> {
+    async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|err| CommandError::internal(format!("稍后清单任务执行失败: {err}")))?
+        .map_err(CommandError::from)
+}
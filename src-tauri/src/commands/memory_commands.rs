@@ -0,0 +1,80 @@
+use tauri::State;
+
+use crate::commands::{AppState, CommandResult};
+use crate::models::memory::{MemoryDocument, MemoryFact, MemoryFacts, MemoryTopicCount};
+
+#[tauri::command]
+pub async fn memory_topics_list(
+    state: State<'_, AppState>,
+) -> CommandResult<Vec<MemoryTopicCount>> {
+    Ok(state.memory().list_topics())
+}
+
+/// Returns how many memory documents were rewritten.
+#[tauri::command]
+pub async fn memory_topic_rename(
+    state: State<'_, AppState>,
+    from: String,
+    to: String,
+) -> CommandResult<usize> {
+    let service = state.memory();
+    service.rename_topic(&from, &to).await.map_err(Into::into)
+}
+
+#[tauri::command]
+pub async fn memory_topics_merge(
+    state: State<'_, AppState>,
+    sources: Vec<String>,
+    target: String,
+) -> CommandResult<usize> {
+    let service = state.memory();
+    service
+        .merge_topics(&sources, &target)
+        .await
+        .map_err(Into::into)
+}
+
+#[tauri::command]
+pub async fn memory_topic_delete(
+    state: State<'_, AppState>,
+    topic: String,
+) -> CommandResult<usize> {
+    let service = state.memory();
+    service.delete_topic(&topic).await.map_err(Into::into)
+}
+
+#[tauri::command]
+pub async fn memory_set_pinned(
+    state: State<'_, AppState>,
+    id: String,
+    pinned: bool,
+) -> CommandResult<MemoryDocument> {
+    let service = state.memory();
+    service.set_pinned(&id, pinned).await.map_err(Into::into)
+}
+
+#[tauri::command]
+pub async fn memory_set_ignored(
+    state: State<'_, AppState>,
+    id: String,
+    ignored: bool,
+) -> CommandResult<MemoryDocument> {
+    let service = state.memory();
+    service.set_ignored(&id, ignored).await.map_err(Into::into)
+}
+
+#[tauri::command]
+pub async fn memory_facts_get(state: State<'_, AppState>) -> CommandResult<MemoryFacts> {
+    let service = state.memory();
+    service.get_facts().map_err(Into::into)
+}
+
+/// Replaces the whole list of facts.
+#[tauri::command]
+pub async fn memory_facts_set(
+    state: State<'_, AppState>,
+    facts: Vec<MemoryFact>,
+) -> CommandResult<MemoryFacts> {
+    let service = state.memory();
+    service.set_facts(facts).map_err(Into::into)
+}
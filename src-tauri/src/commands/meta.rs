@@ -0,0 +1,659 @@
+//! Machine-readable description of every registered command and emitted
+//! event. The command catalog below mirrors `generate_handler!` in `lib.rs`;
+//! a test keeps the two in sync.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::JsonSchema;
+use serde_json::Value as JsonValue;
+
+use crate::commands::ai_commands::{
+    AgentChatResponse, ChatResponse, MemoryClearResponse, MemoryExportResponse,
+    MemorySearchResponse,
+};
+use crate::commands::planning::{PlanningPreferencesUpdatePayload, PreferenceProfileCreatePayload};
+use crate::commands::planning::{
+    PLANNING_APPLIED_EVENT, PLANNING_CONFLICTS_RESOLVED_EVENT, PLANNING_DISCARDED_EVENT,
+    PLANNING_GENERATED_EVENT, PLANNING_PREFERENCES_UPDATED_EVENT,
+};
+use crate::commands::recurring_commands::{
+    CreateRecurringTaskInput, RecurringTaskTemplateFilterInput, UpdateRecurringTaskInput,
+};
+use crate::commands::settings::{DashboardConfigUpdatePayload, SettingsUpdatePayload};
+use crate::commands::task::{TaskListFilters, TaskListResponse, TasksImportCommitPayload};
+use crate::commands::{CacheClearResult, CommandError, CommandResult};
+use crate::db::encryption::EncryptionStatus;
+use crate::db::DatabaseDiagnostics;
+use crate::error::AiErrorCode;
+use crate::models::ai::{TaskParseRequest, TaskParseResponse};
+use crate::models::ai_feedback::AiFeedback;
+use crate::models::ai_types::{
+    ActionItemExtractionDto, AiDebugEntry, AiStatusDto, TaskDecompositionDto,
+};
+use crate::models::analytics::AnalyticsAnomaly;
+use crate::models::analytics::{
+    AnalyticsExportParams, AnalyticsExportResult, AnalyticsHistoryResponse,
+    AnalyticsOverviewResponse, AnalyticsQueryParams, ConfidenceCalibration, DayTimeline,
+    DefragmentationSuggestion, TodaySnapshot, YearInReview,
+};
+use crate::models::caldav::{CalDavConnectInput, CalDavStatus, CalDavSyncReport};
+use crate::models::capacity::{CapacityWizardAnswer, CapacityWizardResult, CapacityWizardState};
+use crate::models::custom_tool::{CustomToolDefinition, CustomToolRecord};
+use crate::models::dashboard::{
+    DashboardWidget, GoalProgressSummary, UpcomingDeadline, WellnessWidgetState,
+};
+use crate::models::data_export::{
+    DataEraseResult, DataExportResult, DataImportParams, DataImportReport,
+};
+use crate::models::day_log::{DayCloseInput, DayCloseResult, DayLogRecord};
+use crate::models::dependency::{
+    DependencyCreateInput, DependencyFilter, DependencyGraph, DependencyType, DependencyValidation,
+    ReadyTask, TaskDependency,
+};
+use crate::models::goal::{
+    CreateGoalRequest, Goal, GoalCheckin, GoalTaskAssociation, GoalWithProgress, UpdateGoalRequest,
+};
+use crate::models::history::HistorySummary;
+use crate::models::ics::{IcsExportInput, IcsExportResult, IcsImportInput, IcsImportResult};
+use crate::models::job::{BackgroundJobsOverview, OnDemandJobRun};
+use crate::models::later::{LaterItemCreateInput, LaterItemRecord};
+use crate::models::memory::{
+    MemoryDocument, MemoryExportBundle, MemoryFact, MemoryFacts, MemoryTopicCount,
+};
+use crate::models::meta::{
+    ApiDescription, CommandDescriptor, CommandParamDescriptor, EmitFrequency, ErrorCodeDescriptor,
+    EventCatalog, EventDescriptor,
+};
+use crate::models::productivity::{
+    ProductivityScoreHistoryResponse, ProductivityScoreRecord, ScoreStreak,
+};
+use crate::models::project::{
+    ProjectCreateInput, ProjectProgress, ProjectRecord, ProjectUpdateInput,
+};
+use crate::models::prompt_template::{
+    PromptTemplateKey, PromptTemplateUpdateInput, PromptTemplateView,
+};
+use crate::models::query::{QueryResult, QueryTableDoc};
+use crate::models::recurring_task::{RecurringTaskStats, RecurringTaskTemplate, TaskInstance};
+use crate::models::reminder::BlockReminderRecord;
+use crate::models::retention::{RetentionPolicy, RetentionReport};
+use crate::models::settings::{
+    AppSettings, DashboardConfig, DelegationSettings, FeatureFlag, FeatureFlagState,
+    FeedbackOptOuts, IdleDetectionSettings, PowerPolicy, SleepSchedule,
+};
+use crate::models::suggestion::SuggestionRecord;
+use crate::models::sync::ChangeSet;
+use crate::models::task::TaskSnoozeEnded;
+use crate::models::task::{
+    SimilarTasksQuery, SimilarTasksResult, StaleTaskReport, TaskCreateInput, TaskMatrixSnapshot,
+    TaskRecord, TaskShiftDatesInput, TaskShiftDatesResult, TaskUpdateInput,
+};
+use crate::models::timesheet::{TimesheetExportParams, TimesheetExportResult};
+use crate::models::upgrade::AppUpgradeStatus;
+use crate::models::wellness::{FocusIdleAdjustment, FocusSession, WellnessEventRecord};
+use crate::models::workload::{WorkloadForecastResponse, WorkloadGranularity, WorkloadHorizon};
+use crate::services::ai_agent_service::ChatAttachment;
+use crate::services::analytics_service::ANALYTICS_ANOMALY_EVENT;
+use crate::services::behavior_learning::{
+    PreferencePreview, PreferenceProfile, PreferenceSnapshot,
+};
+use crate::services::clipboard_watcher::CLIPBOARD_ACTIONABLE_EVENT;
+use crate::services::clipboard_watcher::{ClipboardCandidate, ClipboardCaptureInput};
+use crate::services::community_service::{
+    CommunityExportFormat, DetectedPlugin, ExportBundle, ProjectInfo,
+};
+use crate::services::feedback_service::{FeedbackPrivacySummary, FeedbackSubmission, WeeklyDigest};
+use crate::services::goal_service::{CHECKIN_POLL_INTERVAL, GOAL_CHECKIN_DUE_EVENT};
+use crate::services::planning_service::{
+    AppliedPlan, ApplyPlanInput, GeneratePlanInput, PlanningSessionListFilter, PlanningSessionPage,
+    PlanningSessionView, ResolveConflictInput,
+};
+use crate::services::power_throttle::PowerStatus;
+use crate::services::progress::{ProgressEvent, OPERATION_PROGRESS_EVENT};
+use crate::services::reminder_service::REMINDER_DUE_EVENT;
+use crate::services::schedule_optimizer::ScheduleConflict;
+use crate::services::suggestion_service::SUGGESTIONS_EVENT;
+use crate::services::task_service::{
+    SNOOZE_POLL_INTERVAL, TASK_FOLLOW_UPS_CREATED_EVENT, TASK_SNOOZE_ENDED_EVENT,
+};
+use crate::services::tool_registry::ToolAllowlist;
+use crate::services::wellness_service::WeeklySummary;
+use crate::services::{clipboard_watcher, reminder_service, suggestion_service};
+use crate::utils::appearance::AppearanceOptions;
+use crate::utils::single_instance::{LaunchArgs, SECOND_INSTANCE_EVENT};
+
+/// Codes a `CommandError` can carry besides the AI ones.
+/// Stable error codes with their meaning and whether retrying may help.
+const ERROR_CODES: [(&str, &str, bool); 19] = [
+    (
+        "VALIDATION_ERROR",
+        "输入校验失败，details 中可能包含字段信息",
+        false,
+    ),
+    ("INVALID_INPUT", "参数格式不正确", false),
+    ("NOT_FOUND", "请求的资源不存在", false),
+    ("CONFLICT", "与现有数据冲突", false),
+    ("CANCELLED", "操作已被取消", true),
+    ("MEMORY_UNAVAILABLE", "记忆功能暂时不可用", true),
+    (
+        "TOOL_EXECUTION_FAILED",
+        "工具执行失败，details.toolName 为工具名",
+        false,
+    ),
+    (
+        "INVALID_TOOL_CALL",
+        "工具调用参数无效，details.toolName 为工具名",
+        false,
+    ),
+    (
+        "CONTEXT_TOO_LARGE",
+        "上下文超过模型限制，details 中包含 tokens 与 limit",
+        false,
+    ),
+    ("DATABASE_ERROR", "数据库操作失败", false),
+    ("DATABASE_BUSY", "数据库被其他操作占用，稍后重试即可", true),
+    (
+        "MIGRATION_FAILED",
+        "数据库迁移失败，details.version 为失败的版本号",
+        false,
+    ),
+    ("CRYPTO_ERROR", "加密、解密或系统密钥存储失败", false),
+    (
+        "EXTERNAL_INTEGRATION_FAILED",
+        "外部服务调用失败，details.service 为服务名",
+        true,
+    ),
+    (
+        "RATE_LIMITED",
+        "请求过于频繁，details.retryAfterSecs 为建议的等待秒数",
+        true,
+    ),
+    ("SERIALIZATION_ERROR", "数据序列化失败", false),
+    ("IO_ERROR", "文件读写失败", true),
+    ("INTERNAL", "内部错误", false),
+    ("UNKNOWN", "未分类的错误", false),
+];
+
+macro_rules! command_catalog {
+    ($($module:ident::$name:ident($($param:ident: $ty:ty),*) -> $output:ty;)*) => {
+        fn describe_commands(generator: &mut SchemaGenerator) -> Vec<CommandDescriptor> {
+            vec![$(CommandDescriptor {
+                name: stringify!($name).to_string(),
+                module: stringify!($module).to_string(),
+                params: vec![$(describe_param::<$ty>(generator, stringify!($param))),*],
+                output: generator.subschema_for::<$output>(),
+            }),*]
+        }
+    };
+}
+
+command_catalog! {
+    analytics::analytics_history_fetch(params: Option<AnalyticsQueryParams>) -> AnalyticsHistoryResponse;
+    analytics::analytics_overview_fetch(params: Option<AnalyticsQueryParams>) -> AnalyticsOverviewResponse;
+    analytics::analytics_report_export(params: AnalyticsExportParams) -> AnalyticsExportResult;
+    analytics::analytics_get_productivity_score(date: Option<String>) -> ProductivityScoreRecord;
+    analytics::analytics_get_productivity_score_history(start_date: String, end_date: String) -> ProductivityScoreHistoryResponse;
+    analytics::analytics_get_latest_productivity_score() -> Option<ProductivityScoreRecord>;
+    analytics::analytics_get_workload_forecast(capacity_threshold_hours: Option<f64>, horizon: Option<WorkloadHorizon>, granularity: Option<WorkloadGranularity>) -> Vec<WorkloadForecastResponse>;
+    analytics::analytics_get_latest_workload_forecasts(granularity: Option<WorkloadGranularity>) -> Vec<WorkloadForecastResponse>;
+    analytics::analytics_defragmentation_suggestions(date: Option<String>) -> Vec<DefragmentationSuggestion>;
+    analytics::analytics_get_day_timeline(date: String) -> DayTimeline;
+    analytics::analytics_snapshot_recompute(start_date: String, end_date: String, operation_id: Option<String>) -> usize;
+    analytics::analytics_get_score_streak() -> ScoreStreak;
+    analytics::analytics_today_snapshot() -> TodaySnapshot;
+    analytics::analytics_confidence_calibration() -> ConfidenceCalibration;
+    analytics::analytics_year_in_review(year: i32) -> YearInReview;
+    ai_commands::tasks_parse_ai(request: TaskParseRequest) -> TaskParseResponse;
+    ai_commands::ai_generate_recommendations(payload: JsonValue) -> JsonValue;
+    ai_commands::ai_plan_schedule(payload: JsonValue) -> JsonValue;
+    ai_commands::ai_extract_action_items(text: String) -> ActionItemExtractionDto;
+    ai_commands::tasks_decompose_ai(task_id: String, max_subtasks: Option<usize>) -> TaskDecompositionDto;
+    ai_commands::ai_status() -> AiStatusDto;
+    ai_commands::ai_debug_get(correlation_id: String) -> AiDebugEntry;
+    ai_commands::ai_chat(message: String) -> ChatResponse;
+    ai_commands::ai_agent_chat(conversation_id: String, message: String, attachments: Option<Vec<ChatAttachment>>, incognito: Option<bool>) -> AgentChatResponse;
+    ai_commands::memory_search(query: String, filters: Option<HashMap<String, String>>) -> MemorySearchResponse;
+    ai_commands::memory_export(path: String, bundle: Option<MemoryExportBundle>, operation_id: Option<String>) -> MemoryExportResponse;
+    ai_commands::memory_clear(conversation_id: String) -> MemoryClearResponse;
+    memory_commands::memory_topics_list() -> Vec<MemoryTopicCount>;
+    memory_commands::memory_topic_rename(from: String, to: String) -> usize;
+    memory_commands::memory_topics_merge(sources: Vec<String>, target: String) -> usize;
+    memory_commands::memory_topic_delete(topic: String) -> usize;
+    memory_commands::memory_set_pinned(id: String, pinned: bool) -> MemoryDocument;
+    memory_commands::memory_set_ignored(id: String, ignored: bool) -> MemoryDocument;
+    memory_commands::memory_facts_get() -> MemoryFacts;
+    memory_commands::memory_facts_set(facts: Vec<MemoryFact>) -> MemoryFacts;
+    custom_tools::tools_register_custom(payload: CustomToolDefinition) -> CustomToolRecord;
+    custom_tools::tools_list_custom() -> Vec<CustomToolRecord>;
+    custom_tools::tools_unregister_custom(name: String) -> ();
+    custom_tools::tools_get_allowlist() -> ToolAllowlist;
+    custom_tools::tools_set_allowlist(allowlist: ToolAllowlist) -> ToolAllowlist;
+    custom_tools::tools_set_conversation_allowlist(conversation_id: String, tools: Option<Vec<String>>) -> ToolAllowlist;
+    planning::planning_apply(payload: ApplyPlanInput) -> AppliedPlan;
+    planning::planning_generate(payload: GeneratePlanInput, operation_id: Option<String>) -> PlanningSessionView;
+    planning::planning_preferences_get(preference_id: Option<String>) -> PreferenceSnapshot;
+    planning::planning_preferences_update(payload: PlanningPreferencesUpdatePayload) -> ();
+    planning::planning_preferences_preview(snapshot: PreferenceSnapshot) -> PreferencePreview;
+    planning::planning_preference_profiles_list() -> Vec<PreferenceProfile>;
+    planning::planning_preference_profile_create(payload: PreferenceProfileCreatePayload) -> PreferenceProfile;
+    planning::planning_preference_profile_delete(id: String) -> ();
+    planning::planning_resolve_conflict(payload: ResolveConflictInput) -> PlanningSessionView;
+    planning::planning_session_discard(id: String) -> ();
+    planning::planning_session_get(id: String) -> PlanningSessionView;
+    planning::planning_sessions_list(filter: Option<PlanningSessionListFilter>) -> PlanningSessionPage;
+    task::tasks_list(filters: Option<TaskListFilters>) -> TaskListResponse;
+    task::tasks_create(payload: TaskCreateInput) -> TaskRecord;
+    task::tasks_update(id: String, payload: TaskUpdateInput) -> TaskRecord;
+    task::tasks_delete(id: String) -> ();
+    task::tasks_import_commit(payload: TasksImportCommitPayload, operation_id: Option<String>) -> Vec<TaskRecord>;
+    task::tasks_shift_dates(payload: TaskShiftDatesInput) -> TaskShiftDatesResult;
+    task::tasks_snooze(task_id: String, until: Option<String>, notify: Option<bool>) -> TaskRecord;
+    task::tasks_find_similar(payload: SimilarTasksQuery) -> SimilarTasksResult;
+    task::tasks_stale_report(min_days: Option<u32>) -> StaleTaskReport;
+    task::tasks_matrix_snapshot() -> TaskMatrixSnapshot;
+    settings::settings_get() -> AppSettings;
+    settings::settings_update(payload: SettingsUpdatePayload) -> AppSettings;
+    settings::settings_clear_api_key() -> AppSettings;
+    settings::dashboard_config_get() -> DashboardConfig;
+    settings::dashboard_config_update(payload: DashboardConfigUpdatePayload) -> DashboardConfig;
+    dashboard::dashboard_widget_today(refresh: Option<bool>) -> DashboardWidget<TodaySnapshot>;
+    dashboard::dashboard_widget_deadlines(refresh: Option<bool>) -> DashboardWidget<Vec<UpcomingDeadline>>;
+    dashboard::dashboard_widget_goals(refresh: Option<bool>) -> DashboardWidget<Vec<GoalProgressSummary>>;
+    dashboard::dashboard_widget_wellness(refresh: Option<bool>) -> DashboardWidget<WellnessWidgetState>;
+    settings::sleep_schedule_get() -> SleepSchedule;
+    settings::sleep_schedule_update(payload: SleepSchedule) -> SleepSchedule;
+    settings::retention_policy_get() -> RetentionPolicy;
+    settings::retention_policy_update(payload: RetentionPolicy) -> RetentionPolicy;
+    settings::power_policy_get() -> PowerPolicy;
+    settings::power_policy_update(payload: PowerPolicy) -> PowerPolicy;
+    settings::idle_detection_get() -> IdleDetectionSettings;
+    settings::idle_detection_update(payload: IdleDetectionSettings) -> IdleDetectionSettings;
+    settings::delegation_settings_get() -> DelegationSettings;
+    settings::delegation_settings_update(payload: DelegationSettings) -> DelegationSettings;
+    settings::power_status_get() -> PowerStatus;
+    settings::feedback_opt_outs_get() -> FeedbackOptOuts;
+    settings::feedback_opt_outs_update(payload: FeedbackOptOuts) -> FeedbackOptOuts;
+    settings::features_get() -> Vec<FeatureFlagState>;
+    settings::features_set(flag: FeatureFlag, enabled: bool) -> FeatureFlagState;
+    settings::retention_preview() -> RetentionReport;
+    settings::database_encryption_status() -> EncryptionStatus;
+    settings::database_encryption_enable(passphrase: String) -> EncryptionStatus;
+    settings::database_diagnostics() -> DatabaseDiagnostics;
+    cache::cache_clear_all() -> CacheClearResult;
+    data::data_export_all() -> DataExportResult;
+    data::data_erase_all(confirmation: String) -> DataEraseResult;
+    data::data_import(params: DataImportParams) -> DataImportReport;
+    query::query_run_readonly(sql: String, max_rows: Option<usize>) -> QueryResult;
+    query::query_schema() -> Vec<QueryTableDoc>;
+    clipboard::clipboard_inspect(text: String) -> Option<ClipboardCandidate>;
+    clipboard::clipboard_capture(payload: ClipboardCaptureInput) -> TaskRecord;
+    day_close::day_close(payload: DayCloseInput) -> DayCloseResult;
+    day_close::day_log_get(date: String) -> DayLogRecord;
+    day_close::day_log_list(limit: Option<usize>) -> Vec<DayLogRecord>;
+    operations::operation_cancel(operation_id: String) -> bool;
+    jobs::jobs_list(status: Option<String>) -> BackgroundJobsOverview;
+    jobs::jobs_run_scheduled() -> Vec<OnDemandJobRun>;
+    upgrade::app_upgrade_status() -> AppUpgradeStatus;
+    upgrade::app_changelog_acknowledge() -> AppUpgradeStatus;
+    capacity::capacity_wizard_start() -> CapacityWizardState;
+    capacity::capacity_wizard_answer(id: String, answer: CapacityWizardAnswer) -> CapacityWizardState;
+    capacity::capacity_wizard_finish(id: String) -> CapacityWizardResult;
+    later::later_add(payload: LaterItemCreateInput) -> LaterItemRecord;
+    later::later_list(status: Option<String>) -> Vec<LaterItemRecord>;
+    later::later_complete(id: String) -> LaterItemRecord;
+    prompts::prompt_templates_list() -> Vec<PromptTemplateView>;
+    prompts::prompt_template_update(payload: PromptTemplateUpdateInput) -> PromptTemplateView;
+    prompts::prompt_template_reset(key: PromptTemplateKey) -> PromptTemplateView;
+    wellness::wellness_check_nudge() -> Option<WellnessEventRecord>;
+    wellness::wellness_get_pending() -> Option<WellnessEventRecord>;
+    wellness::wellness_respond(id: i64, response: String) -> WellnessEventRecord;
+    wellness::wellness_focus_start(planned_minutes: Option<i64>, task_id: Option<String>) -> FocusSession;
+    wellness::wellness_focus_end() -> Option<WellnessEventRecord>;
+    wellness::wellness_focus_current() -> Option<FocusSession>;
+    wellness::wellness_idle_pending() -> Vec<FocusIdleAdjustment>;
+    wellness::wellness_idle_resolve(id: i64, confirm: bool) -> FocusIdleAdjustment;
+    wellness::wellness_get_weekly_summary() -> WeeklySummary;
+    feedback::feedback_submit(submission: FeedbackSubmission) -> i64;
+    feedback::feedback_get_recent(surface: String, limit: Option<i64>) -> Vec<AiFeedback>;
+    feedback::feedback_get_session(session_id: String) -> Vec<AiFeedback>;
+    feedback::feedback_get_weekly_digest() -> Option<WeeklyDigest>;
+    feedback::feedback_check_opt_out() -> bool;
+    feedback::feedback_get_privacy_summary() -> FeedbackPrivacySummary;
+    feedback::feedback_purge_all() -> i64;
+    feedback::feedback_get_stats(surface: Option<String>) -> JsonValue;
+    community::community_get_project_info() -> ProjectInfo;
+    community::community_detect_plugins() -> Vec<DetectedPlugin>;
+    community::community_generate_export_bundle(include_feedback: bool) -> ExportBundle;
+    community::community_save_export_to_file(bundle_json: String, file_path: String, format: Option<CommunityExportFormat>) -> i64;
+    community::community_read_export_bundle(file_path: String) -> ExportBundle;
+    community::community_list_exports() -> Vec<(i64, String, String)>;
+    goal_commands::create_goal(request: CreateGoalRequest) -> Goal;
+    goal_commands::get_goal(id: String) -> Goal;
+    goal_commands::list_goals(parent_goal_id: Option<String>) -> Vec<Goal>;
+    goal_commands::update_goal(id: String, request: UpdateGoalRequest) -> Goal;
+    goal_commands::delete_goal(id: String) -> ();
+    goal_commands::associate_task_with_goal(goal_id: String, task_id: String) -> GoalTaskAssociation;
+    goal_commands::dissociate_task_from_goal(goal_id: String, task_id: String) -> ();
+    goal_commands::get_goal_tasks(goal_id: String) -> Vec<String>;
+    goal_commands::get_goal_with_progress(id: String) -> GoalWithProgress;
+    goal_commands::goals_checkin(goal_id: String, note: Option<String>, progress: f64) -> GoalCheckin;
+    goal_commands::goals_checkin_history(goal_id: String, limit: Option<usize>) -> Vec<GoalCheckin>;
+    projects::projects_list(include_archived: Option<bool>) -> Vec<ProjectRecord>;
+    projects::projects_create(payload: ProjectCreateInput) -> ProjectRecord;
+    projects::projects_update(id: String, payload: ProjectUpdateInput) -> ProjectRecord;
+    projects::projects_archive(id: String) -> ProjectRecord;
+    projects::projects_progress(id: String) -> ProjectProgress;
+    reminders::reminders_dismiss(id: String) -> BlockReminderRecord;
+    reminders::reminders_list_upcoming(limit: Option<usize>) -> Vec<BlockReminderRecord>;
+    suggestions::suggestions_list(status: Option<String>, limit: Option<usize>) -> Vec<SuggestionRecord>;
+    suggestions::suggestion_accept(id: String, note: Option<String>) -> SuggestionRecord;
+    suggestions::suggestion_dismiss(id: String, note: Option<String>) -> SuggestionRecord;
+    history::history_summarize(start: String, end: String, polish: Option<bool>) -> HistorySummary;
+    timesheet::timesheet_export(params: TimesheetExportParams) -> TimesheetExportResult;
+    ics::ics_export(input: Option<IcsExportInput>) -> IcsExportResult;
+    ics::ics_import(input: IcsImportInput) -> IcsImportResult;
+    appearance::appearance_options() -> AppearanceOptions;
+    meta::meta_describe_api() -> JsonValue;
+    meta::meta_describe_events() -> JsonValue;
+    sync::sync_changes_since(revision: i64, limit: Option<usize>) -> ChangeSet;
+    caldav::caldav_connect(input: CalDavConnectInput) -> CalDavStatus;
+    caldav::caldav_sync_now() -> CalDavSyncReport;
+    caldav::caldav_status() -> CalDavStatus;
+    dependency_commands::get_task_dependencies(filter: Option<DependencyFilter>) -> Vec<TaskDependency>;
+    dependency_commands::get_dependency_graph(filter: Option<DependencyFilter>) -> DependencyGraph;
+    dependency_commands::get_ready_tasks() -> Vec<ReadyTask>;
+    dependency_commands::add_dependency(input: DependencyCreateInput) -> TaskDependency;
+    dependency_commands::dependency_chain_create(task_ids: Vec<String>, dependency_type: Option<DependencyType>) -> Vec<TaskDependency>;
+    dependency_commands::remove_dependency(dependency_id: String) -> ();
+    dependency_commands::update_dependency_type(dependency_id: String, dependency_type: DependencyType) -> ();
+    dependency_commands::validate_dependency(predecessor_id: String, successor_id: String) -> DependencyValidation;
+    recurring_commands::recurring_template_list(filters: Option<RecurringTaskTemplateFilterInput>) -> Vec<RecurringTaskTemplate>;
+    recurring_commands::recurring_template_create(input: CreateRecurringTaskInput) -> RecurringTaskTemplate;
+    recurring_commands::recurring_template_update(id: String, input: UpdateRecurringTaskInput) -> RecurringTaskTemplate;
+    recurring_commands::recurring_template_delete(id: String) -> ();
+    recurring_commands::recurring_template_get(id: String) -> RecurringTaskTemplate;
+    recurring_commands::recurring_template_generate_instances(id: String) -> Vec<TaskInstance>;
+    recurring_commands::recurring_template_instances(id: String, start_date: Option<String>, end_date: Option<String>) -> Vec<TaskInstance>;
+    recurring_commands::recurring_get_stats(rule_id: String) -> RecurringTaskStats;
+    recurring_commands::recurring_task_to_regular(instance_id: String) -> ();
+}
+
+/// Commands, their argument and result schemas, and the error codes they
+/// can fail with.
+#[tauri::command]
+pub async fn meta_describe_api() -> CommandResult<ApiDescription> {
+    Ok(describe_api())
+}
+
+pub fn describe_api() -> ApiDescription {
+    let mut generator = SchemaSettings::draft07().into_generator();
+    let commands = describe_commands(&mut generator);
+    // Failures share one shape regardless of the command
+    generator.subschema_for::<CommandError>();
+
+    let error_codes = ERROR_CODES
+        .iter()
+        .map(|(code, description, retryable)| {
+            (code.to_string(), description.to_string(), *retryable)
+        })
+        .chain(AiErrorCode::ALL.iter().map(|code| {
+            (
+                code.as_str().to_string(),
+                "AI 服务调用失败".to_string(),
+                code.is_retryable(),
+            )
+        }))
+        .map(|(code, description, retryable)| ErrorCodeDescriptor {
+            code,
+            description,
+            retryable,
+        })
+        .collect();
+
+    ApiDescription {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        schema_dialect: generator.settings().meta_schema.clone().unwrap_or_default(),
+        commands,
+        definitions: generator.take_definitions(),
+        error_codes,
+    }
+}
+
+/// Events emitted to the frontend, their payloads and how often they fire.
+#[tauri::command]
+pub async fn meta_describe_events() -> CommandResult<EventCatalog> {
+    Ok(describe_events())
+}
+
+pub fn describe_events() -> EventCatalog {
+    let mut generator = SchemaSettings::draft07().into_generator();
+    let gen = &mut generator;
+    let per_command = |commands: &[&str]| EmitFrequency::PerCommand {
+        commands: commands.iter().map(|command| command.to_string()).collect(),
+    };
+    let periodic = |interval: Duration| EmitFrequency::Periodic {
+        interval_secs: interval.as_secs(),
+    };
+
+    let events = vec![
+        event::<ProgressEvent>(
+            gen,
+            OPERATION_PROGRESS_EVENT,
+            "长时间操作的进度，按 operationId 区分；每个阶段发送一次",
+            per_command(&[
+                "analytics_snapshot_recompute",
+                "memory_export",
+                "planning_generate",
+                "tasks_import_commit",
+            ]),
+        ),
+        event::<PlanningSessionView>(
+            gen,
+            PLANNING_GENERATED_EVENT,
+            "生成了新的规划会话",
+            per_command(&["capacity_wizard_finish", "planning_generate"]),
+        ),
+        event::<AppliedPlan>(
+            gen,
+            PLANNING_APPLIED_EVENT,
+            "规划方案已应用到任务",
+            per_command(&["planning_apply"]),
+        ),
+        event::<Vec<ScheduleConflict>>(
+            gen,
+            PLANNING_CONFLICTS_RESOLVED_EVENT,
+            "冲突处理后剩余的冲突",
+            per_command(&["planning_resolve_conflict"]),
+        ),
+        event::<String>(
+            gen,
+            PLANNING_DISCARDED_EVENT,
+            "被丢弃的规划会话 ID",
+            per_command(&["planning_session_discard"]),
+        ),
+        event::<String>(
+            gen,
+            PLANNING_PREFERENCES_UPDATED_EVENT,
+            "被修改的偏好配置 ID",
+            per_command(&[
+                "planning_preferences_update",
+                "planning_preference_profile_create",
+                "planning_preference_profile_delete",
+            ]),
+        ),
+        event::<ClipboardCandidate>(
+            gen,
+            CLIPBOARD_ACTIONABLE_EVENT,
+            "剪贴板中出现了可转为任务的内容（需在设置中开启）",
+            periodic(clipboard_watcher::POLL_INTERVAL),
+        ),
+        event::<Vec<SuggestionRecord>>(
+            gen,
+            SUGGESTIONS_EVENT,
+            "当天新生成的主动建议",
+            periodic(suggestion_service::POLL_INTERVAL),
+        ),
+        event::<Vec<TaskSnoozeEnded>>(
+            gen,
+            TASK_SNOOZE_ENDED_EVENT,
+            "推迟结束、重新出现的任务",
+            periodic(SNOOZE_POLL_INTERVAL),
+        ),
+        event::<Vec<TaskRecord>>(
+            gen,
+            TASK_FOLLOW_UPS_CREATED_EVENT,
+            "为等待他人的任务生成的跟进任务",
+            periodic(SNOOZE_POLL_INTERVAL),
+        ),
+        event::<Vec<Goal>>(
+            gen,
+            GOAL_CHECKIN_DUE_EVENT,
+            "到了打卡时间的目标",
+            periodic(CHECKIN_POLL_INTERVAL),
+        ),
+        event::<Vec<BlockReminderRecord>>(
+            gen,
+            REMINDER_DUE_EVENT,
+            "即将开始的时间块提醒",
+            periodic(reminder_service::POLL_INTERVAL),
+        ),
+        event::<Vec<AnalyticsAnomaly>>(
+            gen,
+            ANALYTICS_ANOMALY_EVENT,
+            "夜间快照发现的异常指标",
+            periodic(Duration::from_secs(24 * 60 * 60)),
+        ),
+        event::<LaunchArgs>(
+            gen,
+            SECOND_INSTANCE_EVENT,
+            "再次启动应用时转发的启动参数",
+            EmitFrequency::External,
+        ),
+    ];
+
+    EventCatalog {
+        schema_dialect: generator.settings().meta_schema.clone().unwrap_or_default(),
+        events,
+        definitions: generator.take_definitions(),
+    }
+}
+
+fn event<T: JsonSchema>(
+    generator: &mut SchemaGenerator,
+    name: &str,
+    description: &str,
+    frequency: EmitFrequency,
+) -> EventDescriptor {
+    EventDescriptor {
+        name: name.to_string(),
+        description: description.to_string(),
+        payload: generator.subschema_for::<T>(),
+        frequency,
+    }
+}
+
+fn describe_param<T: JsonSchema>(
+    generator: &mut SchemaGenerator,
+    name: &str,
+) -> CommandParamDescriptor {
+    CommandParamDescriptor {
+        name: camel_case(name),
+        required: !std::any::type_name::<T>().starts_with("core::option::Option<"),
+        schema: generator.subschema_for::<T>(),
+    }
+}
+
+/// Tauri expects command arguments in camelCase.
+fn camel_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    for (index, part) in name.split('_').filter(|part| !part.is_empty()).enumerate() {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            if index == 0 {
+                result.push(first);
+            } else {
+                result.extend(first.to_uppercase());
+            }
+            result.push_str(chars.as_str());
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checked-in copy of `describe_events`, relative to the crate root.
+    const EVENT_CATALOG_ARTIFACT: &str = "gen/event-catalog.json";
+
+    #[test]
+    fn catalog_matches_registered_commands() {
+        let registered: Vec<&str> = include_str!("../lib.rs")
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("crate::commands::"))
+            .filter_map(|path| path.trim_end_matches(',').split("::").last())
+            .collect();
+        let description = describe_api();
+        let described: Vec<&str> = description
+            .commands
+            .iter()
+            .map(|command| command.name.as_str())
+            .collect();
+        assert_eq!(described, registered);
+
+        let json = serde_json::to_string(&description).unwrap();
+        for reference in json.split("\"$ref\":\"#/definitions/").skip(1) {
+            let name = &reference[..reference.find('"').unwrap()];
+            assert!(description.definitions.contains_key(name), "{name}");
+        }
+        assert!(description.definitions.contains_key("CommandError"));
+    }
+
+    /// Regenerate with `UPDATE_EVENT_CATALOG=1 cargo test event_catalog`.
+    #[test]
+    fn event_catalog_artifact_is_current() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(EVENT_CATALOG_ARTIFACT);
+        let catalog = describe_events();
+        let mut names: Vec<&str> = catalog
+            .events
+            .iter()
+            .map(|event| event.name.as_str())
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), catalog.events.len());
+
+        let generated = format!("{}\n", serde_json::to_string_pretty(&catalog).unwrap());
+        if std::env::var_os("UPDATE_EVENT_CATALOG").is_some() {
+            std::fs::write(&path, &generated).unwrap();
+        }
+        let current = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            current == generated,
+            "{EVENT_CATALOG_ARTIFACT} is stale; rerun with UPDATE_EVENT_CATALOG=1"
+        );
+    }
+
+    #[test]
+    fn params_use_ipc_names() {
+        let description = describe_api();
+        let snooze = description
+            .commands
+            .iter()
+            .find(|command| command.name == "tasks_snooze")
+            .unwrap();
+        let params: Vec<(&str, bool)> = snooze
+            .params
+            .iter()
+            .map(|param| (param.name.as_str(), param.required))
+            .collect();
+        assert_eq!(
+            params,
+            [("taskId", true), ("until", false), ("notify", false)]
+        );
+        assert_eq!(camel_case("_start_date"), "startDate");
+    }
+}
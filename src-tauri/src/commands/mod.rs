@@ -1,19 +1,43 @@
 pub mod ai;
 pub mod ai_commands;
 pub mod analytics;
+pub mod appearance;
 pub mod cache;
+pub mod caldav;
+pub mod capacity;
+pub mod clipboard;
 pub mod community;
+pub mod custom_tools;
+pub mod dashboard;
+pub mod data;
+pub mod day_close;
 pub mod dependency_commands;
 pub mod feedback;
 pub mod goal_commands;
+pub mod history;
+pub mod ics;
+pub mod jobs;
+pub mod later;
+pub mod memory_commands;
+pub mod meta;
+pub mod operations;
 pub mod planning;
+pub mod projects;
+pub mod prompts;
+pub mod query;
 pub mod recurring_commands;
+pub mod reminders;
 pub mod settings;
+pub mod suggestions;
+pub mod sync;
 pub mod task;
+pub mod timesheet;
+pub mod upgrade;
 pub mod wellness;
 
 use std::sync::Arc;
 
+use schemars::JsonSchema;
 use serde::Serialize;
 use serde_json::{Map as JsonMap, Value as JsonValue};
 use tracing::{error, warn};
@@ -23,18 +47,45 @@ use crate::error::{AppError, AppResult};
 use crate::services::ai_agent_service::AiAgentService;
 use crate::services::ai_service::AiService;
 use crate::services::analytics_service::AnalyticsService;
+use crate::services::cache_service::CacheService;
+use crate::services::caldav_sync_service::CalDavSyncService;
+use crate::services::capacity_wizard::CapacityWizardService;
+use crate::services::clipboard_watcher::ClipboardWatcher;
 use crate::services::community_service::CommunityService;
+use crate::services::custom_tool_service::CustomToolService;
+use crate::services::dashboard_widgets::DashboardWidgetService;
+use crate::services::data_export_service::DataExportService;
+use crate::services::day_close_service::DayCloseService;
+use crate::services::db_maintenance::DbMaintenanceService;
 use crate::services::dependency_service::DependencyService;
 use crate::services::feedback_service::FeedbackService;
 use crate::services::goal_service::GoalService;
+use crate::services::history_service::HistoryService;
+use crate::services::ics_service::IcsService;
+use crate::services::job_queue::{
+    JobQueueService, JOB_KIND_ANALYTICS_SNAPSHOT, JOB_KIND_WORKLOAD_FORECAST,
+};
+use crate::services::later_service::LaterService;
 use crate::services::memory_service::MemoryService;
+use crate::services::on_demand_jobs::OnDemandJobs;
 use crate::services::planning_service::PlanningService;
 use crate::services::productivity_score_service::ProductivityScoreService;
+use crate::services::progress::OperationRegistry;
+use crate::services::project_service::ProjectService;
+use crate::services::prompt_template_service::PromptTemplateService;
+use crate::services::query_console::QueryConsole;
+use crate::services::reminder_service::ReminderService;
+use crate::services::retention_service::RetentionService;
 use crate::services::settings_service::SettingsService;
+use crate::services::suggestion_service::SuggestionService;
+use crate::services::sync_service::SyncService;
 use crate::services::task_service::TaskService;
+use crate::services::timesheet_service::TimesheetService;
 use crate::services::tool_registry::ToolRegistry;
+use crate::services::upgrade_service::UpgradeService;
 use crate::services::wellness_service::WellnessService;
 use crate::services::workload_forecast_service::WorkloadForecastService;
+use crate::utils::runtime_profile::RuntimeProfile;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -52,21 +103,62 @@ pub struct AppState {
     dependency_service: Arc<DependencyService>,
     memory_service: Arc<MemoryService>,
     goal_service: Arc<GoalService>,
+    project_service: Arc<ProjectService>,
+    reminder_service: Arc<ReminderService>,
+    suggestion_service: Arc<SuggestionService>,
+    history_service: Arc<HistoryService>,
+    timesheet_service: Arc<TimesheetService>,
+    ics_service: Arc<IcsService>,
+    sync_service: Arc<SyncService>,
+    caldav_sync_service: Arc<CalDavSyncService>,
     recurring_task_service: Arc<crate::services::recurring_task_service::RecurringTaskService>,
+    clipboard_watcher: Arc<ClipboardWatcher>,
+    day_close_service: Arc<DayCloseService>,
+    later_service: Arc<LaterService>,
+    prompt_template_service: Arc<PromptTemplateService>,
+    retention_service: Arc<RetentionService>,
+    data_export_service: Arc<DataExportService>,
+    query_console: Arc<QueryConsole>,
+    dashboard_widgets: Arc<DashboardWidgetService>,
 
     tool_registry: Arc<ToolRegistry>,
+    custom_tool_service: Arc<CustomToolService>,
     agent_service: Arc<AiAgentService>,
+    operations: Arc<OperationRegistry>,
+    job_queue: Arc<JobQueueService>,
+    upgrade_service: Arc<UpgradeService>,
+    capacity_wizard: Arc<CapacityWizardService>,
+    on_demand_jobs: Arc<OnDemandJobs>,
+    runtime_profile: RuntimeProfile,
 }
 
 impl AppState {
     pub fn new(db_pool: DbPool, memory_base_dir: std::path::PathBuf) -> AppResult<Self> {
+        Self::with_profile(db_pool, memory_base_dir, RuntimeProfile::detect())
+    }
+
+    pub fn with_profile(
+        db_pool: DbPool,
+        memory_base_dir: std::path::PathBuf,
+        runtime_profile: RuntimeProfile,
+    ) -> AppResult<Self> {
         let task_service = Arc::new(TaskService::new(db_pool.clone()));
         let ai_service = Arc::new(AiService::new(db_pool.clone())?);
-        let planning_service = Arc::new(PlanningService::new(
-            db_pool.clone(),
-            Arc::clone(&task_service),
-            Arc::clone(&ai_service),
-        ));
+        // Initialize memory service with provided base directory
+        let memory_dir = memory_base_dir.join("memory");
+        let memory_service = Arc::new(MemoryService::new_with_index_limit(
+            memory_dir,
+            runtime_profile.memory_index_limit(),
+        )?);
+
+        let planning_service = Arc::new(
+            PlanningService::new(
+                db_pool.clone(),
+                Arc::clone(&task_service),
+                Arc::clone(&ai_service),
+            )
+            .with_memory_facts(Arc::clone(&memory_service)),
+        );
         let analytics_service = Arc::new(AnalyticsService::new(
             db_pool.clone(),
             Arc::clone(&task_service),
@@ -88,12 +180,25 @@ impl AppState {
         ));
         let community_service = CommunityService::new(db_pool.clone());
 
-        // Initialize memory service with provided base directory
-        let memory_dir = memory_base_dir.join("memory");
-        let memory_service = Arc::new(MemoryService::new(memory_dir)?);
-
         // Initialize goal service
         let goal_service = Arc::new(GoalService::new(db_pool.clone()));
+        let project_service = Arc::new(ProjectService::new(db_pool.clone()));
+        let reminder_service = Arc::new(ReminderService::new(db_pool.clone()));
+        let suggestion_service = Arc::new(SuggestionService::new(
+            db_pool.clone(),
+            Arc::clone(&feedback_service),
+        ));
+        let history_service = Arc::new(HistoryService::new(
+            db_pool.clone(),
+            Arc::clone(&ai_service),
+        ));
+        let timesheet_service = Arc::new(TimesheetService::new(db_pool.clone()));
+        let ics_service = Arc::new(IcsService::new(db_pool.clone()));
+        let sync_service = Arc::new(SyncService::new(db_pool.clone()));
+        let caldav_sync_service = Arc::new(CalDavSyncService::new(
+            db_pool.clone(),
+            Arc::clone(&task_service),
+        )?);
 
         // Initialize dependency service
         let dependency_service = Arc::new(DependencyService::new(db_pool.clone()));
@@ -103,6 +208,22 @@ impl AppState {
             crate::services::recurring_task_service::RecurringTaskService::new(db_pool.clone()),
         );
 
+        // Clipboard watcher stays idle until the user opts in via settings
+        let clipboard_watcher = Arc::new(ClipboardWatcher::new(
+            Arc::clone(&settings_service),
+            Arc::clone(&task_service),
+        ));
+
+        let day_close_service = Arc::new(DayCloseService::new(
+            db_pool.clone(),
+            Arc::clone(&task_service),
+            Arc::clone(&analytics_service),
+            Arc::clone(&productivity_score_service),
+        ));
+
+        let later_service = Arc::new(LaterService::new(db_pool.clone()));
+        let prompt_template_service = Arc::new(PromptTemplateService::new(db_pool.clone()));
+
         // Initialize tool registry and register tools
         let mut tool_registry = ToolRegistry::new();
 
@@ -130,18 +251,148 @@ impl AppState {
             Arc::clone(&recurring_task_service),
         )?;
 
+        // Register AI task decomposition tools
+        crate::tools::decomposition_tools::register_decomposition_tools(
+            &mut tool_registry,
+            Arc::clone(&ai_service),
+        )?;
+
         let tool_registry = Arc::new(tool_registry);
 
-        // Initialize AI agent service with memory
-        let agent_service = Arc::new(AiAgentService::new_with_memory(
-            Arc::clone(&ai_service),
+        // Reload user-defined declarative tools on top of the built-in ones
+        let custom_tool_service = Arc::new(CustomToolService::new(
+            db_pool.clone(),
             Arc::clone(&tool_registry),
+        ));
+        custom_tool_service.load_persisted()?;
+        custom_tool_service.load_allowlist()?;
+
+        // Initialize AI agent service with memory
+        let agent_service = Arc::new(
+            AiAgentService::new_with_memory(
+                Arc::clone(&ai_service),
+                Arc::clone(&tool_registry),
+                Arc::clone(&memory_service),
+            )
+            .with_task_history(Arc::clone(&task_service))
+            .with_attachment_sources(Arc::clone(&goal_service), Arc::clone(&planning_service))
+            .with_response_cache(CacheService::new(
+                db_pool.clone(),
+                chrono::Duration::hours(1),
+            )?),
+        );
+
+        // Failed nightly work is retried from a persistent queue
+        let job_queue = Arc::new(JobQueueService::new(db_pool.clone()));
+        {
+            let forecasts = Arc::clone(&workload_forecast_service);
+            job_queue.register(
+                JOB_KIND_WORKLOAD_FORECAST,
+                Arc::new(move |_| forecasts.generate_forecasts(None).map(|_| ())),
+            );
+            let analytics = Arc::clone(&analytics_service);
+            job_queue.register(
+                JOB_KIND_ANALYTICS_SNAPSHOT,
+                Arc::new(move |payload| {
+                    let date = payload
+                        .get("date")
+                        .and_then(JsonValue::as_str)
+                        .and_then(|value| chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").ok())
+                        .ok_or_else(|| AppError::validation("快照任务缺少有效日期"))?;
+                    analytics.capture_snapshot_for_date(date)
+                }),
+            );
+        }
+
+        let retention_service = Arc::new(RetentionService::new(
+            db_pool.clone(),
+            analytics_service.reports_dir().to_path_buf(),
+        ));
+        let data_export_service = Arc::new(DataExportService::new(
+            db_pool.clone(),
             Arc::clone(&memory_service),
+            analytics_service.reports_dir().to_path_buf(),
+        ));
+        let query_console = Arc::new(QueryConsole::new(db_pool.clone()));
+        let dashboard_widgets = Arc::new(DashboardWidgetService::new(
+            db_pool.clone(),
+            Arc::clone(&analytics_service),
+            Arc::clone(&goal_service),
+            Arc::clone(&wellness_service),
         ));
 
-        analytics_service.ensure_snapshot_job()?;
+        // Without background threads the same work runs when the frontend
+        // asks for it, e.g. each time the app returns to the foreground
+        let on_demand_jobs = Arc::new(OnDemandJobs::new());
+        let db_maintenance = Arc::new(DbMaintenanceService::new(db_pool.clone()));
+        if runtime_profile.runs_background_threads() {
+            analytics_service.ensure_snapshot_job()?;
+            planning_service.ensure_retention_job()?;
+            workload_forecast_service.ensure_nightly_job()?;
+            job_queue.ensure_worker()?;
+            retention_service.ensure_prune_job()?;
+            db_maintenance.ensure_checkpoint_job()?;
+        } else {
+            let analytics = Arc::clone(&analytics_service);
+            on_demand_jobs.register(
+                "analytics-snapshot",
+                chrono::Duration::hours(24),
+                move |_| analytics.capture_snapshot_for_previous_day(),
+            );
+            let forecasts = Arc::clone(&workload_forecast_service);
+            on_demand_jobs.register(
+                "workload-forecast",
+                chrono::Duration::hours(24),
+                move |_| forecasts.generate_forecasts(None).map(|_| ()),
+            );
+            let queue = Arc::clone(&job_queue);
+            on_demand_jobs.register("job-retry", chrono::Duration::zero(), move |now| {
+                queue.run_due(now).map(|_| ())
+            });
+            let planning = Arc::clone(&planning_service);
+            on_demand_jobs.register(
+                "planning-retention",
+                chrono::Duration::hours(24),
+                move |now| planning.expire_stale_sessions(now).map(|_| ()),
+            );
+            let retention = Arc::clone(&retention_service);
+            on_demand_jobs.register("retention-prune", chrono::Duration::days(7), move |_| {
+                retention.prune().map(|_| ())
+            });
+            let suggestions = Arc::clone(&suggestion_service);
+            on_demand_jobs.register(
+                "daily-suggestions",
+                chrono::Duration::hours(1),
+                move |now| suggestions.compose_daily(now).map(|_| ()),
+            );
+            // Woken tasks show up on the next task fetch; fired reminders and
+            // due check-ins are returned with the job runs instead of emitted
+            let tasks = Arc::clone(&task_service);
+            on_demand_jobs.register("task-snooze-waker", chrono::Duration::zero(), move |now| {
+                tasks.wake_snoozed(now)?;
+                tasks.generate_follow_ups(now).map(|_| ())
+            });
+            reminder_service.register_on_demand(&on_demand_jobs);
+            goal_service.register_on_demand(&on_demand_jobs);
+            on_demand_jobs.register(
+                "wal-checkpoint",
+                chrono::Duration::minutes(10),
+                move |now| db_maintenance.run_once(now).map(|_| ()),
+            );
+        }
         wellness_service.ensure_nudge_job()?;
-        workload_forecast_service.ensure_nightly_job()?;
+
+        // Post-upgrade data steps; failures are recorded and retried on the
+        // next launch instead of blocking startup
+        let upgrade_service = Arc::new(UpgradeService::new(db_pool.clone()));
+        if let Err(err) = upgrade_service.run_pending() {
+            warn!(target: "app::upgrade", error = %err, "failed to run upgrade steps");
+        }
+        let capacity_wizard = Arc::new(CapacityWizardService::new(
+            db_pool.clone(),
+            Arc::clone(&planning_service),
+            Arc::clone(&settings_service),
+        ));
 
         Ok(Self {
             db_pool,
@@ -158,10 +409,33 @@ impl AppState {
             dependency_service,
             memory_service,
             goal_service,
+            project_service,
+            reminder_service,
+            suggestion_service,
+            history_service,
+            timesheet_service,
+            ics_service,
+            sync_service,
+            caldav_sync_service,
             recurring_task_service,
+            clipboard_watcher,
+            day_close_service,
+            later_service,
+            prompt_template_service,
+            retention_service,
+            data_export_service,
+            query_console,
+            dashboard_widgets,
 
             tool_registry,
+            custom_tool_service,
             agent_service,
+            operations: Arc::new(OperationRegistry::new()),
+            job_queue,
+            upgrade_service,
+            capacity_wizard,
+            on_demand_jobs,
+            runtime_profile,
         })
     }
 
@@ -189,6 +463,22 @@ impl AppState {
         Arc::clone(&self.settings_service)
     }
 
+    pub fn retention(&self) -> Arc<RetentionService> {
+        Arc::clone(&self.retention_service)
+    }
+
+    pub fn data_export(&self) -> Arc<DataExportService> {
+        Arc::clone(&self.data_export_service)
+    }
+
+    pub fn query_console(&self) -> Arc<QueryConsole> {
+        Arc::clone(&self.query_console)
+    }
+
+    pub fn dashboard_widgets(&self) -> Arc<DashboardWidgetService> {
+        Arc::clone(&self.dashboard_widgets)
+    }
+
     pub fn wellness(&self) -> Arc<WellnessService> {
         Arc::clone(&self.wellness_service)
     }
@@ -217,10 +507,38 @@ impl AppState {
         Arc::clone(&self.tool_registry)
     }
 
+    pub fn custom_tools(&self) -> Arc<CustomToolService> {
+        Arc::clone(&self.custom_tool_service)
+    }
+
     pub fn agent(&self) -> Arc<AiAgentService> {
         Arc::clone(&self.agent_service)
     }
 
+    pub fn operations(&self) -> Arc<OperationRegistry> {
+        Arc::clone(&self.operations)
+    }
+
+    pub fn jobs(&self) -> Arc<JobQueueService> {
+        Arc::clone(&self.job_queue)
+    }
+
+    pub fn upgrades(&self) -> Arc<UpgradeService> {
+        Arc::clone(&self.upgrade_service)
+    }
+
+    pub fn capacity_wizard(&self) -> Arc<CapacityWizardService> {
+        Arc::clone(&self.capacity_wizard)
+    }
+
+    pub fn on_demand_jobs(&self) -> Arc<OnDemandJobs> {
+        Arc::clone(&self.on_demand_jobs)
+    }
+
+    pub fn runtime_profile(&self) -> RuntimeProfile {
+        self.runtime_profile
+    }
+
     pub fn memory(&self) -> Arc<MemoryService> {
         Arc::clone(&self.memory_service)
     }
@@ -229,10 +547,58 @@ impl AppState {
         Arc::clone(&self.goal_service)
     }
 
+    pub fn projects(&self) -> Arc<ProjectService> {
+        Arc::clone(&self.project_service)
+    }
+
+    pub fn reminders(&self) -> Arc<ReminderService> {
+        Arc::clone(&self.reminder_service)
+    }
+
+    pub fn suggestions(&self) -> Arc<SuggestionService> {
+        Arc::clone(&self.suggestion_service)
+    }
+
+    pub fn history(&self) -> Arc<HistoryService> {
+        Arc::clone(&self.history_service)
+    }
+
+    pub fn timesheets(&self) -> Arc<TimesheetService> {
+        Arc::clone(&self.timesheet_service)
+    }
+
+    pub fn ics(&self) -> Arc<IcsService> {
+        Arc::clone(&self.ics_service)
+    }
+
+    pub fn sync(&self) -> Arc<SyncService> {
+        Arc::clone(&self.sync_service)
+    }
+
+    pub fn caldav(&self) -> Arc<CalDavSyncService> {
+        Arc::clone(&self.caldav_sync_service)
+    }
+
     pub fn dependency_service(&self) -> Arc<DependencyService> {
         Arc::clone(&self.dependency_service)
     }
 
+    pub fn clipboard(&self) -> Arc<ClipboardWatcher> {
+        Arc::clone(&self.clipboard_watcher)
+    }
+
+    pub fn day_close(&self) -> Arc<DayCloseService> {
+        Arc::clone(&self.day_close_service)
+    }
+
+    pub fn later(&self) -> Arc<LaterService> {
+        Arc::clone(&self.later_service)
+    }
+
+    pub fn prompt_templates(&self) -> Arc<PromptTemplateService> {
+        Arc::clone(&self.prompt_template_service)
+    }
+
     /// Clear all cached data except settings
     pub fn clear_all_cache(&self) -> AppResult<CacheClearResult> {
         let mut result = CacheClearResult::default();
@@ -297,6 +663,7 @@ impl AppState {
 
             Ok(())
         })?;
+        self.dashboard_widgets.invalidate();
 
         Ok(result)
     }
@@ -304,7 +671,7 @@ impl AppState {
     // NOTE: additional AppState helpers remain above.
 }
 
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Default, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CacheClearResult {
     pub tasks_cleared: i64,
@@ -321,13 +688,15 @@ pub struct CacheClearResult {
 
 pub type CommandResult<T> = Result<T, CommandError>;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CommandError {
     pub code: String,
     pub message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub details: Option<JsonValue>,
+    /// Whether sending the same request again later may succeed.
+    pub retryable: bool,
 }
 
 impl CommandError {
@@ -340,23 +709,37 @@ impl CommandError {
             code: code.into(),
             message: message.into(),
             details,
+            retryable: false,
         }
     }
+
+    /// The blocking task behind a command panicked or was cancelled before
+    /// it returned.
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new("INTERNAL", message, None)
+    }
+
+    pub fn with_retryable(mut self, retryable: bool) -> Self {
+        self.retryable = retryable;
+        self
+    }
 }
 
 impl From<AppError> for CommandError {
     fn from(error: AppError) -> Self {
-        match error {
+        let code = error.code();
+        let retryable = error.is_retryable();
+        let command_error = match error {
             AppError::Validation {
                 message, details, ..
-            } => CommandError::new("VALIDATION_ERROR", message, details),
-            AppError::NotFound => CommandError::new("NOT_FOUND", "请求的资源不存在", None),
-            AppError::Conflict { message } => CommandError::new("CONFLICT", message, None),
+            } => CommandError::new(code, message, details),
+            AppError::NotFound => CommandError::new(code, "请求的资源不存在", None),
+            AppError::Conflict { message } => CommandError::new(code, message, None),
             AppError::Ai {
-                code,
                 message,
                 correlation_id,
                 details,
+                ..
             } => {
                 let mut merged = JsonMap::new();
                 if let Some(existing) = details {
@@ -379,20 +762,16 @@ impl From<AppError> for CommandError {
                 } else {
                     Some(JsonValue::Object(merged))
                 };
-                CommandError::new(code.as_str(), message, detail_value)
+                CommandError::new(code, message, detail_value)
             }
             AppError::MemoryUnavailable(message) => {
                 warn!(target: "app::command", %message, "memory unavailable in command");
-                CommandError::new(
-                    "MEMORY_UNAVAILABLE",
-                    format!("内存功能暂时不可用: {}", message),
-                    None,
-                )
+                CommandError::new(code, format!("内存功能暂时不可用: {}", message), None)
             }
             AppError::ToolExecutionFailed { tool_name, reason } => {
                 error!(target: "app::command", %tool_name, %reason, "tool execution failed in command");
                 CommandError::new(
-                    "TOOL_EXECUTION_FAILED",
+                    code,
                     format!("工具执行失败: {}", reason),
                     Some(serde_json::json!({ "toolName": tool_name })),
                 )
@@ -403,7 +782,7 @@ impl From<AppError> for CommandError {
             } => {
                 warn!(target: "app::command", %tool_name, %validation_error, "invalid tool call in command");
                 CommandError::new(
-                    "INVALID_TOOL_CALL",
+                    code,
                     format!("无效的工具调用: {}", validation_error),
                     Some(serde_json::json!({ "toolName": tool_name })),
                 )
@@ -411,27 +790,100 @@ impl From<AppError> for CommandError {
             AppError::ContextTooLarge { tokens, limit } => {
                 warn!(target: "app::command", tokens, limit, "context too large in command");
                 CommandError::new(
-                    "CONTEXT_TOO_LARGE",
+                    code,
                     format!("上下文过大 ({} tokens，限制 {} tokens)", tokens, limit),
                     Some(serde_json::json!({ "tokens": tokens, "limit": limit })),
                 )
             }
+            AppError::Cancelled => CommandError::new(code, "操作已取消", None),
+            AppError::Migration { version, message } => {
+                error!(target: "app::command", version, %message, "migration error in command");
+                CommandError::new(
+                    code,
+                    format!("数据库升级失败: {message}"),
+                    Some(serde_json::json!({ "version": version })),
+                )
+            }
+            AppError::Crypto(message) => {
+                error!(target: "app::command", %message, "crypto error in command");
+                CommandError::new(code, message, None)
+            }
+            AppError::ExternalIntegration { service, message } => {
+                warn!(target: "app::command", %service, %message, "external integration error in command");
+                CommandError::new(
+                    code,
+                    message,
+                    Some(serde_json::json!({ "service": service })),
+                )
+            }
+            AppError::RateLimited {
+                message,
+                retry_after_secs,
+            } => CommandError::new(
+                code,
+                message,
+                retry_after_secs.map(|secs| serde_json::json!({ "retryAfterSecs": secs })),
+            ),
             AppError::Database { message } => {
                 error!(target: "app::command", %message, "database error in command");
-                CommandError::new("UNKNOWN", message, None)
+                CommandError::new(code, message, None)
+            }
+            AppError::DatabaseBusy { message } => {
+                warn!(target: "app::command", %message, "database busy in command");
+                CommandError::new(code, "数据库正忙，请稍后重试", None)
             }
             AppError::Serialization(error) => {
                 error!(target: "app::command", error = %error, "serialization error in command");
-                CommandError::new("UNKNOWN", "序列化失败", None)
+                CommandError::new(code, "序列化失败", None)
             }
             AppError::Io(error) => {
                 error!(target: "app::command", error = %error, "io error in command");
-                CommandError::new("UNKNOWN", "文件系统读写失败", None)
+                CommandError::new(code, "文件系统读写失败", None)
             }
             AppError::Other(message) => {
                 error!(target: "app::command", %message, "unexpected error in command");
-                CommandError::new("UNKNOWN", message, None)
+                CommandError::new(code, message, None)
             }
-        }
+        };
+        command_error.with_retryable(retryable)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AiErrorCode;
+
+    #[test]
+    fn app_errors_map_to_stable_codes_with_retry_hints() {
+        let migration = CommandError::from(AppError::migration(12, "no such column"));
+        assert_eq!(migration.code, "MIGRATION_FAILED");
+        assert!(!migration.retryable);
+        assert_eq!(
+            migration.details,
+            Some(serde_json::json!({ "version": 12 }))
+        );
+
+        let limited = CommandError::from(AppError::rate_limited("慢一点", Some(30)));
+        assert_eq!(limited.code, "RATE_LIMITED");
+        assert!(limited.retryable);
+        assert_eq!(
+            limited.details,
+            Some(serde_json::json!({ "retryAfterSecs": 30 }))
+        );
+
+        let external = CommandError::from(AppError::external_integration("caldav", "超时"));
+        assert_eq!(external.code, "EXTERNAL_INTEGRATION_FAILED");
+        assert!(external.retryable);
+
+        let crypto = CommandError::from(AppError::crypto("解密失败"));
+        assert_eq!(crypto.code, "CRYPTO_ERROR");
+        assert!(!crypto.retryable);
+
+        let timeout = CommandError::from(AppError::ai(AiErrorCode::HttpTimeout, "超时"));
+        assert_eq!(timeout.code, AiErrorCode::HttpTimeout.as_str());
+        assert!(timeout.retryable);
+
+        assert!(!CommandError::from(AppError::not_found()).retryable);
     }
 }
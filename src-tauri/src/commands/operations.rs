@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use tauri::{AppHandle, Emitter, State};
+use tracing::warn;
+
+use crate::commands::{AppState, CommandResult};
+use crate::services::progress::{
+    ProgressEvent, ProgressReporter, ProgressSink, OPERATION_PROGRESS_EVENT,
+};
+
+/// Requests cancellation of a running operation. Returns false when no
+/// operation with this id is running.
+#[tauri::command]
+pub async fn operation_cancel(
+    state: State<'_, AppState>,
+    operation_id: String,
+) -> CommandResult<bool> {
+    Ok(state.operations().cancel(&operation_id))
+}
+
+/// Registers an operation whose progress is forwarded to the frontend as
+/// `operation://progress` events.
+pub(crate) fn start_operation(
+    app: &AppHandle,
+    state: &AppState,
+    operation_id: Option<String>,
+) -> CommandResult<ProgressReporter> {
+    let app = app.clone();
+    let sink: ProgressSink = Arc::new(move |event: &ProgressEvent| {
+        if let Err(error) = app.emit(OPERATION_PROGRESS_EVENT, event) {
+            warn!(target: "app::command", operation_id = %event.operation_id, %error, "failed to emit progress event");
+        }
+    });
+    Ok(state.operations().start(operation_id, Some(sink))?)
+}
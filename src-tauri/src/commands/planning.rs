@@ -1,11 +1,19 @@
+use chrono::{Days, Local, TimeZone};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tauri::{async_runtime, AppHandle, Emitter, State};
 use tracing::warn;
 
 use crate::error::AppError;
-use crate::services::behavior_learning::{BehaviorLearningService, PreferenceSnapshot};
+use crate::services::behavior_learning::{
+    preview_day, BehaviorLearningService, PreferencePreview, PreferenceProfile, PreferenceSnapshot,
+};
 use crate::services::planning_service::{
-    AppliedPlan, ApplyPlanInput, GeneratePlanInput, PlanningSessionView, ResolveConflictInput,
+    AppliedPlan, ApplyPlanInput, GeneratePlanInput, PlanningSessionListFilter, PlanningSessionPage,
+    PlanningSessionView, ResolveConflictInput,
+};
+use crate::services::settings_service::{
+    load_default_preference_id, load_sleep_schedule, load_workday_window,
 };
 // Removed: recommendation_orchestrator imports - feature deleted
 // use crate::services::recommendation_orchestrator::{
@@ -13,23 +21,36 @@ use crate::services::planning_service::{
 //     RecommendationOrchestrator, RecommendationResponse,
 // };
 
+use super::operations::start_operation;
 use super::{AppState, CommandError, CommandResult};
 
-const DEFAULT_PREFERENCE_ID: &str = "default";
+pub const PLANNING_GENERATED_EVENT: &str = "planning://generated";
+pub const PLANNING_APPLIED_EVENT: &str = "planning://applied";
+pub const PLANNING_CONFLICTS_RESOLVED_EVENT: &str = "planning://conflicts-resolved";
+pub const PLANNING_DISCARDED_EVENT: &str = "planning://discarded";
+pub const PLANNING_PREFERENCES_UPDATED_EVENT: &str = "planning://preferences-updated";
 
+/// Pass `operation_id` to receive `operation://progress` events and to cancel
+/// through `operation_cancel`.
 #[tauri::command]
 pub async fn planning_generate(
     app: AppHandle,
     state: State<'_, AppState>,
     payload: GeneratePlanInput,
+    operation_id: Option<String>,
 ) -> CommandResult<PlanningSessionView> {
     let state = state.inner().clone();
     let service = state.planning();
+    let progress = start_operation(&app, &state, operation_id)?;
 
     // generate_plan is now async, so we call it directly
-    let session = service.generate_plan(payload).await?;
+    let result = service
+        .generate_plan_with_progress(payload, &progress)
+        .await;
+    state.operations().finish(&progress, &result);
+    let session = result?;
 
-    emit_event(&app, "planning://generated", &session);
+    emit_event(&app, PLANNING_GENERATED_EVENT, &session);
     Ok(session)
 }
 
@@ -42,11 +63,15 @@ pub async fn planning_apply(
     let state = state.inner().clone();
     let applied = run_blocking(move || {
         let service = state.planning();
-        service.apply_option(payload)
+        let applied = service.apply_option(payload)?;
+        if let Err(err) = state.wellness().nudge_for_wind_down(&applied.option.blocks) {
+            warn!(target: "app::planning", error = %err, "failed to evaluate wind-down nudge");
+        }
+        Ok(applied)
     })
     .await?;
 
-    emit_event(&app, "planning://applied", &applied);
+    emit_event(&app, PLANNING_APPLIED_EVENT, &applied);
     Ok(applied)
 }
 
@@ -63,21 +88,57 @@ pub async fn planning_resolve_conflict(
     })
     .await?;
 
-    emit_event(&app, "planning://conflicts-resolved", &updated.conflicts);
+    emit_event(&app, PLANNING_CONFLICTS_RESOLVED_EVENT, &updated.conflicts);
     Ok(updated)
 }
 
+#[tauri::command]
+pub async fn planning_sessions_list(
+    state: State<'_, AppState>,
+    filter: Option<PlanningSessionListFilter>,
+) -> CommandResult<PlanningSessionPage> {
+    let state = state.inner().clone();
+    run_blocking(move || state.planning().list_sessions(filter.unwrap_or_default())).await
+}
+
+#[tauri::command]
+pub async fn planning_session_get(
+    state: State<'_, AppState>,
+    id: String,
+) -> CommandResult<PlanningSessionView> {
+    let state = state.inner().clone();
+    run_blocking(move || state.planning().get_session(&id)).await
+}
+
+/// Deletes a session that was never applied.
+#[tauri::command]
+pub async fn planning_session_discard(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> CommandResult<()> {
+    let state = state.inner().clone();
+    let session_id = id.clone();
+    run_blocking(move || state.planning().discard_session(&id)).await?;
+
+    emit_event(&app, PLANNING_DISCARDED_EVENT, &session_id);
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn planning_preferences_get(
     state: State<'_, AppState>,
     preference_id: Option<String>,
 ) -> CommandResult<PreferenceSnapshot> {
     let state = state.inner().clone();
-    let pref_id = preference_id.unwrap_or_else(|| DEFAULT_PREFERENCE_ID.to_string());
 
     run_blocking(move || {
         let pool = state.db();
         pool.with_connection(|conn| {
+            let pref_id = match preference_id {
+                Some(id) => id,
+                None => load_default_preference_id(conn)?,
+            };
             let service = BehaviorLearningService::new(conn);
             service.load_preferences(&pref_id)
         })
@@ -85,7 +146,7 @@ pub async fn planning_preferences_get(
     .await
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct PlanningPreferencesUpdatePayload {
     #[serde(default)]
@@ -100,25 +161,130 @@ pub async fn planning_preferences_update(
     payload: PlanningPreferencesUpdatePayload,
 ) -> CommandResult<()> {
     let state = state.inner().clone();
-    let pref_id = payload
-        .preference_id
-        .unwrap_or_else(|| DEFAULT_PREFERENCE_ID.to_string());
     let snapshot = payload.snapshot;
-    let pref_id_for_emit = pref_id.clone();
 
-    run_blocking(move || {
+    let pref_id = run_blocking(move || {
+        snapshot.validate()?;
         let pool = state.db();
         pool.with_connection(|conn| {
+            let pref_id = match payload.preference_id {
+                Some(id) => id,
+                None => load_default_preference_id(conn)?,
+            };
             let service = BehaviorLearningService::new(conn);
-            service.save_preferences(&pref_id, &snapshot)
+            service.save_preferences(&pref_id, &snapshot)?;
+            Ok(pref_id)
         })
     })
     .await?;
 
-    emit_event(&app, "planning://preferences-updated", &pref_id_for_emit);
+    emit_event(&app, PLANNING_PREFERENCES_UPDATED_EVENT, &pref_id);
     Ok(())
 }
 
+#[tauri::command]
+pub async fn planning_preference_profiles_list(
+    state: State<'_, AppState>,
+) -> CommandResult<Vec<PreferenceProfile>> {
+    let state = state.inner().clone();
+
+    run_blocking(move || {
+        state.db().with_connection(|conn| {
+            let default_id = load_default_preference_id(conn)?;
+            BehaviorLearningService::new(conn).list_profiles(&default_id)
+        })
+    })
+    .await
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PreferenceProfileCreatePayload {
+    pub name: String,
+    /// Copy the preferences of this profile instead of starting from the defaults.
+    #[serde(default)]
+    pub clone_from: Option<String>,
+}
+
+#[tauri::command]
+pub async fn planning_preference_profile_create(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    payload: PreferenceProfileCreatePayload,
+) -> CommandResult<PreferenceProfile> {
+    let state = state.inner().clone();
+
+    let profile = run_blocking(move || {
+        state.db().with_connection(|conn| {
+            let default_id = load_default_preference_id(conn)?;
+            BehaviorLearningService::new(conn).create_profile(
+                &payload.name,
+                payload.clone_from.as_deref(),
+                &default_id,
+            )
+        })
+    })
+    .await?;
+
+    emit_event(&app, PLANNING_PREFERENCES_UPDATED_EVENT, &profile.id);
+    Ok(profile)
+}
+
+#[tauri::command]
+pub async fn planning_preference_profile_delete(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> CommandResult<()> {
+    let state = state.inner().clone();
+    let profile_id = id.clone();
+
+    run_blocking(move || {
+        state.db().with_connection(|conn| {
+            let default_id = load_default_preference_id(conn)?;
+            BehaviorLearningService::new(conn).delete_profile(&id, &default_id)
+        })
+    })
+    .await?;
+
+    emit_event(&app, PLANNING_PREFERENCES_UPDATED_EVENT, &profile_id);
+    Ok(())
+}
+
+/// Shows how tomorrow would be windowed under `snapshot` without saving it.
+#[tauri::command]
+pub async fn planning_preferences_preview(
+    state: State<'_, AppState>,
+    snapshot: PreferenceSnapshot,
+) -> CommandResult<PreferencePreview> {
+    let state = state.inner().clone();
+
+    run_blocking(move || {
+        snapshot.validate()?;
+        let (workday, sleep_schedule) = state
+            .db()
+            .with_connection(|conn| Ok((load_workday_window(conn)?, load_sleep_schedule(conn)?)))?;
+
+        let tomorrow = Local::now()
+            .date_naive()
+            .checked_add_days(Days::new(1))
+            .ok_or_else(|| AppError::other("无法计算明天的日期"))?;
+        let midnight = Local
+            .from_local_datetime(&tomorrow.and_hms_opt(0, 0, 0).expect("midnight exists"))
+            .earliest()
+            .ok_or_else(|| AppError::other("无法计算明天的日期"))?
+            .fixed_offset();
+
+        Ok(preview_day(
+            &snapshot,
+            midnight,
+            workday,
+            sleep_schedule.as_ref(),
+        ))
+    })
+    .await
+}
+
 // Removed: recommendations commands - feature deleted
 // #[tauri::command]
 // pub async fn recommendations_generate(...) { ... }
@@ -130,7 +296,7 @@ async fn run_blocking<T: Send + 'static>(
 ) -> CommandResult<T> {
     async_runtime::spawn_blocking(task)
         .await
-        .map_err(|err| CommandError::new("UNKNOWN", format!("任务执行失败: {err}"), None))?
+        .map_err(|err| CommandError::internal(format!("任务执行失败: {err}")))?
         .map_err(CommandError::from)
 }
 
@@ -0,0 +1,63 @@
+use tauri::{async_runtime, State};
+
+use crate::commands::{AppState, CommandError, CommandResult};
+use crate::error::AppError;
+use crate::models::project::{
+    ProjectCreateInput, ProjectProgress, ProjectRecord, ProjectUpdateInput,
+};
+
+#[tauri::command]
+pub async fn projects_list(
+    state: State<'_, AppState>,
+    include_archived: Option<bool>,
+) -> CommandResult<Vec<ProjectRecord>> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.projects().list(include_archived.unwrap_or(false))).await
+}
+
+#[tauri::command]
+pub async fn projects_create(
+    state: State<'_, AppState>,
+    payload: ProjectCreateInput,
+) -> CommandResult<ProjectRecord> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.projects().create(payload)).await
+}
+
+#[tauri::command]
+pub async fn projects_update(
+    state: State<'_, AppState>,
+    id: String,
+    payload: ProjectUpdateInput,
+) -> CommandResult<ProjectRecord> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.projects().update(&id, payload)).await
+}
+
+/// Archives the project together with its unfinished tasks.
+#[tauri::command]
+pub async fn projects_archive(
+    state: State<'_, AppState>,
+    id: String,
+) -> CommandResult<ProjectRecord> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.projects().archive(&id)).await
+}
+
+#[tauri::command]
+pub async fn projects_progress(
+    state: State<'_, AppState>,
+    id: String,
+) -> CommandResult<ProjectProgress> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.projects().progress(&id)).await
+}
+
+async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> CommandResult<T> {
+    async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|err| CommandError::internal(format!("项目操作失败: {err}")))?
+        .map_err(CommandError::from)
+}
@@ -0,0 +1,45 @@
+use tauri::{async_runtime, State};
+
+use crate::commands::{AppState, CommandError, CommandResult};
+use crate::error::AppError;
+use crate::models::prompt_template::{
+    PromptTemplateKey, PromptTemplateUpdateInput, PromptTemplateView,
+};
+
+#[tauri::command]
+pub async fn prompt_templates_list(
+    state: State<'_, AppState>,
+) -> CommandResult<Vec<PromptTemplateView>> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || app_state.prompt_templates().list()).await
+}
+
+#[tauri::command]
+pub async fn prompt_template_update(
+    state: State<'_, AppState>,
+    payload: PromptTemplateUpdateInput,
+) -> CommandResult<PromptTemplateView> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || app_state.prompt_templates().update(payload)).await
+}
+
+#[tauri::command]
+pub async fn prompt_template_reset(
+    state: State<'_, AppState>,
+    key: PromptTemplateKey,
+) -> CommandResult<PromptTemplateView> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || app_state.prompt_templates().reset(key)).await
+}
+
+async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> CommandResult<T> {
+    async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|err| CommandError::internal(format!("提示词模板任务执行失败: {err}")))?
+        .map_err(CommandError::from)
+}
@@ -0,0 +1,34 @@
+use tauri::{async_runtime, State};
+
+use crate::commands::{AppState, CommandError, CommandResult};
+use crate::error::AppError;
+use crate::models::query::{QueryResult, QueryTableDoc};
+
+/// Runs a single SELECT statement against a read-only connection. Only the
+/// tables listed by `query_schema` can be read; results are capped at
+/// `max_rows` (200 by default, 1000 at most) and the query at 3 seconds.
+#[tauri::command]
+pub async fn query_run_readonly(
+    state: State<'_, AppState>,
+    sql: String,
+    max_rows: Option<usize>,
+) -> CommandResult<QueryResult> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.query_console().run(&sql, max_rows)).await
+}
+
+/// Tables available to `query_run_readonly`, with descriptions and columns.
+#[tauri::command]
+pub async fn query_schema(state: State<'_, AppState>) -> CommandResult<Vec<QueryTableDoc>> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.query_console().schema()).await
+}
+
+async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> CommandResult<T> {
+    async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|err| CommandError::internal(format!("查询执行失败: {err}")))?
+        .map_err(CommandError::from)
+}
@@ -1,16 +1,17 @@
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::Deserialize;
 use tauri::{async_runtime, State};
 
 use crate::commands::{AppState, CommandError, CommandResult};
 use crate::error::AppError;
 use crate::models::recurring_task::{
-    RecurringTaskTemplate, RecurringTaskTemplateCreate, RecurringTaskTemplateFilter,
-    RecurringTaskTemplateUpdate, TaskInstance,
+    RecurringTaskStats, RecurringTaskTemplate, RecurringTaskTemplateCreate,
+    RecurringTaskTemplateFilter, RecurringTaskTemplateUpdate, TaskInstance,
 };
 
 /// Filter parameters for recurring task templates
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct RecurringTaskTemplateFilterInput {
     pub is_active: Option<bool>,
@@ -21,7 +22,7 @@ pub struct RecurringTaskTemplateFilterInput {
 }
 
 /// Create recurring task template input
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase", default)]
 pub struct CreateRecurringTaskInput {
     pub title: String,
@@ -46,7 +47,7 @@ impl Default for CreateRecurringTaskInput {
 }
 
 /// Update recurring task template input
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "camelCase", default)]
 pub struct UpdateRecurringTaskInput {
     pub title: Option<String>,
@@ -235,6 +236,21 @@ pub async fn recurring_template_instances(
     .await
 }
 
+/// Streak, completion rate and average delay of a recurring rule
+#[tauri::command]
+pub async fn recurring_get_stats(
+    state: State<'_, AppState>,
+    rule_id: String,
+) -> CommandResult<RecurringTaskStats> {
+    let state = state.inner().clone();
+
+    run_blocking(move || {
+        let service = &state.recurring_task_service;
+        service.get_stats(&rule_id)
+    })
+    .await
+}
+
 /// Convert recurring instance to regular task
 #[tauri::command]
 pub async fn recurring_task_to_regular(
@@ -260,6 +276,6 @@ async fn run_blocking<T: Send + 'static>(
 ) -> CommandResult<T> {
     async_runtime::spawn_blocking(task)
         .await
-        .map_err(|err| CommandError::new("UNKNOWN", format!("任务执行失败: {err}"), None))?
+        .map_err(|err| CommandError::internal(format!("任务执行失败: {err}")))?
         .map_err(CommandError::from)
 }
@@ -0,0 +1,40 @@
+use tauri::{async_runtime, State};
+
+use crate::commands::{AppState, CommandError, CommandResult};
+use crate::error::AppError;
+use crate::models::reminder::BlockReminderRecord;
+
+const DEFAULT_UPCOMING_LIMIT: usize = 20;
+const MAX_UPCOMING_LIMIT: usize = 200;
+
+/// Scheduled start-of-block reminders, soonest first. Fired reminders arrive
+/// through `reminders://due`.
+#[tauri::command]
+pub async fn reminders_list_upcoming(
+    state: State<'_, AppState>,
+    limit: Option<usize>,
+) -> CommandResult<Vec<BlockReminderRecord>> {
+    let app_state = state.inner().clone();
+    let limit = limit
+        .unwrap_or(DEFAULT_UPCOMING_LIMIT)
+        .clamp(1, MAX_UPCOMING_LIMIT);
+    run_blocking(move || app_state.reminders().list_upcoming(limit)).await
+}
+
+#[tauri::command]
+pub async fn reminders_dismiss(
+    state: State<'_, AppState>,
+    id: String,
+) -> CommandResult<BlockReminderRecord> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.reminders().dismiss(&id)).await
+}
+
+async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> CommandResult<T> {
+    async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|err| CommandError::internal(format!("提醒操作失败: {err}")))?
+        .map_err(CommandError::from)
+}
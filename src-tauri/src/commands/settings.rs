@@ -1,10 +1,19 @@
 use std::collections::{BTreeMap, HashMap};
 
+use schemars::JsonSchema;
 use serde::Deserialize;
 use tauri::{async_runtime, State};
 
+use crate::db::encryption::EncryptionStatus;
+use crate::db::DatabaseDiagnostics;
 use crate::error::AppError;
-use crate::models::settings::{AppSettings, DashboardConfig};
+use crate::models::retention::{RetentionPolicy, RetentionReport};
+use crate::models::settings::{
+    AppSettings, DashboardConfig, DashboardMetric, DelegationSettings, FeatureFlag,
+    FeatureFlagState, FeedbackOptOuts, IdleDetectionSettings, PowerPolicy, SleepSchedule,
+};
+use crate::services::dashboard_metrics;
+use crate::services::power_throttle::{PowerStatus, PowerThrottle};
 use crate::services::settings_service::{DashboardConfigUpdateInput, SettingsUpdateInput};
 
 use super::{AppState, CommandError, CommandResult};
@@ -39,7 +48,11 @@ pub async fn settings_clear_api_key(state: State<'_, AppState>) -> CommandResult
 #[tauri::command]
 pub async fn dashboard_config_get(state: State<'_, AppState>) -> CommandResult<DashboardConfig> {
     let app_state = state.inner().clone();
-    run_blocking(move || app_state.settings().get_dashboard_config()).await
+    run_blocking(move || {
+        let config = app_state.settings().get_dashboard_config()?;
+        with_metric_values(&app_state, config)
+    })
+    .await
 }
 
 #[tauri::command]
@@ -49,10 +62,181 @@ pub async fn dashboard_config_update(
 ) -> CommandResult<DashboardConfig> {
     let app_state = state.inner().clone();
     let input = payload.into_input();
-    run_blocking(move || app_state.settings().update_dashboard_config(input)).await
+    run_blocking(move || {
+        let config = app_state.settings().update_dashboard_config(input)?;
+        with_metric_values(&app_state, config)
+    })
+    .await
+}
+
+/// Fills in today's values of the custom metrics; the snapshot is skipped
+/// when there are none.
+fn with_metric_values(
+    app_state: &AppState,
+    mut config: DashboardConfig,
+) -> Result<DashboardConfig, AppError> {
+    if !config.custom_metrics.is_empty() {
+        let snapshot = app_state.analytics().today_snapshot()?;
+        config.metric_values =
+            dashboard_metrics::evaluate_metrics(&config.custom_metrics, &snapshot);
+    }
+    Ok(config)
+}
+
+#[tauri::command]
+pub async fn sleep_schedule_get(state: State<'_, AppState>) -> CommandResult<SleepSchedule> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.settings().get_sleep_schedule()).await
+}
+
+#[tauri::command]
+pub async fn sleep_schedule_update(
+    state: State<'_, AppState>,
+    payload: SleepSchedule,
+) -> CommandResult<SleepSchedule> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.settings().update_sleep_schedule(payload)).await
+}
+
+#[tauri::command]
+pub async fn retention_policy_get(state: State<'_, AppState>) -> CommandResult<RetentionPolicy> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.settings().get_retention_policy()).await
+}
+
+#[tauri::command]
+pub async fn retention_policy_update(
+    state: State<'_, AppState>,
+    payload: RetentionPolicy,
+) -> CommandResult<RetentionPolicy> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.settings().update_retention_policy(payload)).await
 }
 
-#[derive(Debug, Deserialize)]
+#[tauri::command]
+pub async fn feedback_opt_outs_get(state: State<'_, AppState>) -> CommandResult<FeedbackOptOuts> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.settings().get_feedback_opt_outs()).await
+}
+
+#[tauri::command]
+pub async fn feedback_opt_outs_update(
+    state: State<'_, AppState>,
+    payload: FeedbackOptOuts,
+) -> CommandResult<FeedbackOptOuts> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.settings().update_feedback_opt_outs(payload)).await
+}
+
+#[tauri::command]
+pub async fn power_policy_get(state: State<'_, AppState>) -> CommandResult<PowerPolicy> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.settings().get_power_policy()).await
+}
+
+#[tauri::command]
+pub async fn power_policy_update(
+    state: State<'_, AppState>,
+    payload: PowerPolicy,
+) -> CommandResult<PowerPolicy> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.settings().update_power_policy(payload)).await
+}
+
+#[tauri::command]
+pub async fn idle_detection_get(
+    state: State<'_, AppState>,
+) -> CommandResult<IdleDetectionSettings> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.settings().get_idle_detection()).await
+}
+
+#[tauri::command]
+pub async fn idle_detection_update(
+    state: State<'_, AppState>,
+    payload: IdleDetectionSettings,
+) -> CommandResult<IdleDetectionSettings> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.settings().update_idle_detection(payload)).await
+}
+
+#[tauri::command]
+pub async fn delegation_settings_get(
+    state: State<'_, AppState>,
+) -> CommandResult<DelegationSettings> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.settings().get_delegation()).await
+}
+
+#[tauri::command]
+pub async fn delegation_settings_update(
+    state: State<'_, AppState>,
+    payload: DelegationSettings,
+) -> CommandResult<DelegationSettings> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.settings().update_delegation(payload)).await
+}
+
+/// Current power source and whether background jobs are held back by it.
+#[tauri::command]
+pub async fn power_status_get(state: State<'_, AppState>) -> CommandResult<PowerStatus> {
+    let app_state = state.inner().clone();
+    run_blocking(move || PowerThrottle::new(app_state.db()).status()).await
+}
+
+/// Experimental subsystems and whether each is currently on.
+#[tauri::command]
+pub async fn features_get(state: State<'_, AppState>) -> CommandResult<Vec<FeatureFlagState>> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.settings().get_feature_flags()).await
+}
+
+#[tauri::command]
+pub async fn features_set(
+    state: State<'_, AppState>,
+    flag: FeatureFlag,
+    enabled: bool,
+) -> CommandResult<FeatureFlagState> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.settings().set_feature_flag(flag, enabled)).await
+}
+
+/// Rows and report files the weekly prune would delete under the current
+/// policy. Nothing is deleted.
+#[tauri::command]
+pub async fn retention_preview(state: State<'_, AppState>) -> CommandResult<RetentionReport> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.retention().preview()).await
+}
+
+#[tauri::command]
+pub async fn database_encryption_status(
+    state: State<'_, AppState>,
+) -> CommandResult<EncryptionStatus> {
+    Ok(state.db().encryption_status())
+}
+
+/// Busy errors and retries seen by each kind of database access since the
+/// app started, plus file sizes and the latest WAL checkpoint.
+#[tauri::command]
+pub async fn database_diagnostics(
+    state: State<'_, AppState>,
+) -> CommandResult<DatabaseDiagnostics> {
+    Ok(state.db().diagnostics())
+}
+
+/// One-time switch to an encrypted database. The derived key is kept in the
+/// OS keychain, so the passphrase is not needed again on this machine.
+#[tauri::command]
+pub async fn database_encryption_enable(
+    state: State<'_, AppState>,
+    passphrase: String,
+) -> CommandResult<EncryptionStatus> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.db().enable_encryption(&passphrase)).await
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SettingsUpdatePayload {
     #[serde(default)]
@@ -67,6 +251,24 @@ pub struct SettingsUpdatePayload {
     theme: Option<String>,
     #[serde(default)]
     ai_feedback_opt_out: Option<bool>,
+    #[serde(default)]
+    clipboard_watch_enabled: Option<bool>,
+    #[serde(default)]
+    ai_privacy_mode: Option<bool>,
+    #[serde(default)]
+    ai_debug_log_enabled: Option<bool>,
+    #[serde(default)]
+    planning_session_retention_days: Option<u32>,
+    #[serde(default)]
+    proactive_suggestions_enabled: Option<bool>,
+    #[serde(default)]
+    anomaly_notifications_enabled: Option<bool>,
+    #[serde(default)]
+    plan_critique_enabled: Option<bool>,
+    #[serde(default)]
+    default_preference_id: Option<String>,
+    #[serde(default)]
+    productivity_score_target: Option<f64>,
 }
 
 impl SettingsUpdatePayload {
@@ -83,16 +285,27 @@ impl SettingsUpdatePayload {
             workday_end_minute: self.workday_end_minute,
             theme: self.theme,
             ai_feedback_opt_out: self.ai_feedback_opt_out,
+            clipboard_watch_enabled: self.clipboard_watch_enabled,
+            ai_privacy_mode: self.ai_privacy_mode,
+            ai_debug_log_enabled: self.ai_debug_log_enabled,
+            planning_session_retention_days: self.planning_session_retention_days,
+            proactive_suggestions_enabled: self.proactive_suggestions_enabled,
+            anomaly_notifications_enabled: self.anomaly_notifications_enabled,
+            plan_critique_enabled: self.plan_critique_enabled,
+            default_preference_id: self.default_preference_id,
+            productivity_score_target: self.productivity_score_target,
         }
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct DashboardConfigUpdatePayload {
     #[serde(default)]
     modules: Option<HashMap<String, bool>>,
     #[serde(default)]
+    custom_metrics: Option<Vec<DashboardMetric>>,
+    #[serde(default)]
     last_updated_at: Option<Option<String>>,
 }
 
@@ -102,6 +315,7 @@ impl DashboardConfigUpdatePayload {
             modules: self
                 .modules
                 .map(|modules| modules.into_iter().collect::<BTreeMap<_, _>>()),
+            custom_metrics: self.custom_metrics,
             last_updated_at: self.last_updated_at,
         }
     }
@@ -112,7 +326,7 @@ async fn run_blocking<T: Send + 'static>(
 ) -> CommandResult<T> {
     async_runtime::spawn_blocking(task)
         .await
-        .map_err(|err| CommandError::new("UNKNOWN", format!("设置操作执行失败: {err}"), None))?
+        .map_err(|err| CommandError::internal(format!("设置操作执行失败: {err}")))?
         .map_err(CommandError::from)
 }
 
@@ -131,6 +345,15 @@ mod tests {
             workday_end_minute: None,
             theme: None,
             ai_feedback_opt_out: None,
+            clipboard_watch_enabled: None,
+            ai_privacy_mode: None,
+            ai_debug_log_enabled: None,
+            planning_session_retention_days: None,
+            proactive_suggestions_enabled: None,
+            anomaly_notifications_enabled: None,
+            plan_critique_enabled: None,
+            default_preference_id: None,
+            productivity_score_target: None,
         };
 
         let input = payload.into_input();
@@ -147,6 +370,15 @@ mod tests {
             workday_end_minute: None,
             theme: None,
             ai_feedback_opt_out: None,
+            clipboard_watch_enabled: None,
+            ai_privacy_mode: None,
+            ai_debug_log_enabled: None,
+            planning_session_retention_days: None,
+            proactive_suggestions_enabled: None,
+            anomaly_notifications_enabled: None,
+            plan_critique_enabled: None,
+            default_preference_id: None,
+            productivity_score_target: None,
         };
 
         let input = payload.into_input();
@@ -166,6 +398,15 @@ mod tests {
             workday_end_minute: None,
             theme: None,
             ai_feedback_opt_out: None,
+            clipboard_watch_enabled: None,
+            ai_privacy_mode: None,
+            ai_debug_log_enabled: None,
+            planning_session_retention_days: None,
+            proactive_suggestions_enabled: None,
+            anomaly_notifications_enabled: None,
+            plan_critique_enabled: None,
+            default_preference_id: None,
+            productivity_score_target: None,
         };
 
         let input = payload.into_input();
@@ -183,6 +424,15 @@ mod tests {
             workday_end_minute: None,
             theme: None,
             ai_feedback_opt_out: None,
+            clipboard_watch_enabled: None,
+            ai_privacy_mode: None,
+            ai_debug_log_enabled: None,
+            planning_session_retention_days: None,
+            proactive_suggestions_enabled: None,
+            anomaly_notifications_enabled: None,
+            plan_critique_enabled: None,
+            default_preference_id: None,
+            productivity_score_target: None,
         };
 
         let input = payload.into_input();
@@ -195,6 +445,7 @@ mod tests {
         modules.insert("quick-actions".to_string(), false);
         let payload = DashboardConfigUpdatePayload {
             modules: Some(modules),
+            custom_metrics: None,
             last_updated_at: None,
         };
 
@@ -208,6 +459,7 @@ mod tests {
     fn test_dashboard_config_payload_null_timestamp() {
         let payload = DashboardConfigUpdatePayload {
             modules: None,
+            custom_metrics: None,
             last_updated_at: Some(None),
         };
 
@@ -0,0 +1,54 @@
+use tauri::{async_runtime, State};
+
+use crate::commands::{AppState, CommandError, CommandResult};
+use crate::error::AppError;
+use crate::models::suggestion::{SuggestionRecord, SuggestionStatus};
+
+const DEFAULT_LIST_LIMIT: usize = 20;
+const MAX_LIST_LIMIT: usize = 100;
+
+/// Suggestions with the given status, newest first; pending by default. New
+/// daily suggestions also arrive through `agent://suggestions`.
+#[tauri::command]
+pub async fn suggestions_list(
+    state: State<'_, AppState>,
+    status: Option<String>,
+    limit: Option<usize>,
+) -> CommandResult<Vec<SuggestionRecord>> {
+    let status = status
+        .map(|value| SuggestionStatus::try_from(value.as_str()))
+        .transpose()
+        .map_err(|err| CommandError::new("INVALID_INPUT", err, None))?;
+    let limit = limit.unwrap_or(DEFAULT_LIST_LIMIT).clamp(1, MAX_LIST_LIMIT);
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.suggestions().list(status, limit)).await
+}
+
+#[tauri::command]
+pub async fn suggestion_accept(
+    state: State<'_, AppState>,
+    id: String,
+    note: Option<String>,
+) -> CommandResult<SuggestionRecord> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.suggestions().accept(&id, note)).await
+}
+
+#[tauri::command]
+pub async fn suggestion_dismiss(
+    state: State<'_, AppState>,
+    id: String,
+    note: Option<String>,
+) -> CommandResult<SuggestionRecord> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.suggestions().dismiss(&id, note)).await
+}
+
+async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> CommandResult<T> {
+    async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|err| CommandError::internal(format!("建议操作失败: {err}")))?
+        .map_err(CommandError::from)
+}
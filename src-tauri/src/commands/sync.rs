@@ -0,0 +1,27 @@
+use tauri::{async_runtime, State};
+
+use crate::commands::{AppState, CommandError, CommandResult};
+use crate::error::AppError;
+use crate::models::sync::ChangeSet;
+
+/// Tasks, projects, goals and applied time blocks changed after `revision`,
+/// plus the ids of deleted ones. Pass the returned `revision` back on the
+/// next call.
+#[tauri::command]
+pub async fn sync_changes_since(
+    state: State<'_, AppState>,
+    revision: i64,
+    limit: Option<usize>,
+) -> CommandResult<ChangeSet> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.sync().changes_since(revision, limit)).await
+}
+
+async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> CommandResult<T> {
+    async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|err| CommandError::internal(format!("同步变更失败: {err}")))?
+        .map_err(CommandError::from)
+}
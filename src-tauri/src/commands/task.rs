@@ -1,19 +1,26 @@
 use std::collections::HashSet;
 
-use chrono::DateTime;
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use tauri::{async_runtime, State};
-use tracing::debug;
+use tauri::{async_runtime, AppHandle, State};
+use tracing::{debug, warn};
 
 use crate::error::AppError;
-use crate::models::task::{TaskCreateInput, TaskRecord, TaskUpdateInput};
+use crate::models::task::{
+    SimilarTasksQuery, SimilarTasksResult, StaleTaskReport, TaskCreateInput, TaskMatrixSnapshot,
+    TaskRecord, TaskShiftDatesInput, TaskShiftDatesResult, TaskUpdateInput,
+};
+use crate::services::task_service::is_snoozed;
 
+use super::operations::start_operation;
 use super::{AppState, CommandError, CommandResult};
 
 const DEFAULT_PAGE_SIZE: usize = 20;
 const MAX_PAGE_SIZE: usize = 200;
+const SIMILAR_MEMORY_NOTES: usize = 3;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase", default)]
 pub struct TaskListFilters {
     pub search: Option<String>,
@@ -22,6 +29,8 @@ pub struct TaskListFilters {
     pub tags: Option<Vec<String>>,
     pub owner_ids: Option<Vec<String>>,
     pub include_archived: Option<bool>,
+    /// Snoozed tasks are hidden unless this is set.
+    pub include_snoozed: Option<bool>,
     pub due_after: Option<String>,
     pub due_before: Option<String>,
     pub window_start: Option<String>,
@@ -43,6 +52,7 @@ impl Default for TaskListFilters {
             tags: None,
             owner_ids: None,
             include_archived: None,
+            include_snoozed: None,
             due_after: None,
             due_before: None,
             window_start: None,
@@ -57,7 +67,7 @@ impl Default for TaskListFilters {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskListResponse {
     pub items: Vec<TaskRecord>,
@@ -104,17 +114,129 @@ pub async fn tasks_delete(state: State<'_, AppState>, id: String) -> CommandResu
     run_blocking(move || service.tasks().delete_task(&id)).await
 }
 
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TasksImportCommitPayload {
+    pub tasks: Vec<TaskCreateInput>,
+}
+
+/// Creates a batch of tasks atomically, emitting `operation://progress`
+/// events; cancelling through `operation_cancel` discards the whole batch.
+#[tauri::command]
+pub async fn tasks_import_commit(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    payload: TasksImportCommitPayload,
+    operation_id: Option<String>,
+) -> CommandResult<Vec<TaskRecord>> {
+    let service = state.inner().clone();
+    let progress = start_operation(&app, &service, operation_id)?;
+    run_blocking(move || {
+        let result = service.tasks().import_tasks(payload.tasks, &progress);
+        service.operations().finish(&progress, &result);
+        result
+    })
+    .await
+}
+
+/// Shifts due dates by `deltaDays`, cascading to dependent tasks. With
+/// `preview: true` nothing is saved.
+#[tauri::command]
+pub async fn tasks_shift_dates(
+    state: State<'_, AppState>,
+    payload: TaskShiftDatesInput,
+) -> CommandResult<TaskShiftDatesResult> {
+    let service = state.inner().clone();
+    run_blocking(move || service.tasks().shift_dates(payload)).await
+}
+
+/// Hides the task until `until`; leaving `until` out ends the snooze. Woken
+/// tasks are reported through `tasks://snooze-ended`, flagged with `notify`
+/// when the user asked to be reminded.
+#[tauri::command]
+pub async fn tasks_snooze(
+    state: State<'_, AppState>,
+    task_id: String,
+    until: Option<String>,
+    notify: Option<bool>,
+) -> CommandResult<TaskRecord> {
+    let service = state.inner().clone();
+    run_blocking(move || {
+        service
+            .tasks()
+            .snooze_task(&task_id, until, notify.unwrap_or(false))
+    })
+    .await
+}
+
+/// Completed tasks resembling a task being created or planned, with how long
+/// they took and related notes from conversation memory.
+#[tauri::command]
+pub async fn tasks_find_similar(
+    state: State<'_, AppState>,
+    payload: SimilarTasksQuery,
+) -> CommandResult<SimilarTasksResult> {
+    let service = state.inner().clone();
+    let title = payload.title.trim().to_string();
+    let mut result = run_blocking(move || service.tasks().find_similar(payload)).await?;
+
+    if !title.is_empty() {
+        match state
+            .memory()
+            .search_memory(&title, SIMILAR_MEMORY_NOTES)
+            .await
+        {
+            Ok(context) => {
+                result.memory_notes = context
+                    .relevant_documents
+                    .into_iter()
+                    .map(|doc| doc.metadata.summary.trim().to_string())
+                    .filter(|summary| !summary.is_empty())
+                    .collect();
+            }
+            Err(err) => {
+                warn!(target: "app::command", error = %err, "memory lookup for similar tasks failed");
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Open tasks untouched for `min_days` or more (14 by default), grouped into
+/// age buckets with a suggestion to archive, re-estimate or break down each.
+#[tauri::command]
+pub async fn tasks_stale_report(
+    state: State<'_, AppState>,
+    min_days: Option<u32>,
+) -> CommandResult<StaleTaskReport> {
+    let service = state.inner().clone();
+    run_blocking(move || service.tasks().stale_report(min_days)).await
+}
+
+/// Open tasks sorted into the four urgent/important quadrants, for the
+/// priority matrix view.
+#[tauri::command]
+pub async fn tasks_matrix_snapshot(
+    state: State<'_, AppState>,
+) -> CommandResult<TaskMatrixSnapshot> {
+    let service = state.inner().clone();
+    run_blocking(move || service.tasks().matrix_snapshot()).await
+}
+
 async fn run_blocking<T: Send + 'static>(
     task: impl FnOnce() -> Result<T, AppError> + Send + 'static,
 ) -> CommandResult<T> {
     async_runtime::spawn_blocking(task)
         .await
-        .map_err(|err| CommandError::new("UNKNOWN", format!("任务执行失败: {err}"), None))?
+        .map_err(|err| CommandError::internal(format!("任务执行失败: {err}")))?
         .map_err(CommandError::from)
 }
 
 fn filter_and_paginate(records: Vec<TaskRecord>, filters: TaskListFilters) -> TaskListResponse {
     let include_archived = filters.include_archived.unwrap_or(false);
+    let include_snoozed = filters.include_snoozed.unwrap_or(false);
+    let now = Utc::now();
     let statuses = normalize_set(filters.statuses);
     let priorities = normalize_set(filters.priorities);
     let tags = normalize_set(filters.tags);
@@ -133,6 +255,7 @@ fn filter_and_paginate(records: Vec<TaskRecord>, filters: TaskListFilters) -> Ta
 
     let mut filtered: Vec<TaskRecord> = records
         .into_iter()
+        .filter(|task| include_snoozed || !is_snoozed(task, now))
         .filter(|task| {
             match_filters(
                 task,
@@ -381,9 +504,10 @@ fn status_rank(status: &str) -> usize {
         "todo" => 1,
         "in_progress" => 2,
         "blocked" => 3,
-        "done" => 4,
-        "archived" => 5,
-        _ => 6,
+        "waiting" => 4,
+        "done" => 5,
+        "archived" => 6,
+        _ => 7,
     }
 }
 
@@ -0,0 +1,25 @@
+use tauri::{async_runtime, State};
+
+use crate::commands::{AppState, CommandError, CommandResult};
+use crate::error::AppError;
+use crate::models::timesheet::{TimesheetExportParams, TimesheetExportResult};
+
+/// Exports worked time blocks in the range as a CSV (or JSON) timesheet,
+/// grouped by project or tag and rounded per entry.
+#[tauri::command]
+pub async fn timesheet_export(
+    state: State<'_, AppState>,
+    params: TimesheetExportParams,
+) -> CommandResult<TimesheetExportResult> {
+    let app_state = state.inner().clone();
+    run_blocking(move || app_state.timesheets().export(params)).await
+}
+
+async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> CommandResult<T> {
+    async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|err| CommandError::internal(format!("工时表导出失败: {err}")))?
+        .map_err(CommandError::from)
+}
@@ -0,0 +1,32 @@
+use tauri::{async_runtime, State};
+
+use crate::commands::{AppState, CommandError, CommandResult};
+use crate::error::AppError;
+use crate::models::upgrade::AppUpgradeStatus;
+
+/// Post-upgrade data steps with their state, plus changelog entries the user
+/// has not acknowledged yet.
+#[tauri::command]
+pub async fn app_upgrade_status(state: State<'_, AppState>) -> CommandResult<AppUpgradeStatus> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || app_state.upgrades().status()).await
+}
+
+#[tauri::command]
+pub async fn app_changelog_acknowledge(
+    state: State<'_, AppState>,
+) -> CommandResult<AppUpgradeStatus> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || app_state.upgrades().acknowledge_changelog()).await
+}
+
+async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> CommandResult<T> {
+    async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|err| CommandError::internal(format!("升级状态查询失败: {err}")))?
+        .map_err(CommandError::from)
+}
@@ -2,7 +2,9 @@ use tauri::{async_runtime, State};
 
 use crate::commands::{AppState, CommandError, CommandResult};
 use crate::error::AppError;
-use crate::models::wellness::{WellnessEventRecord, WellnessResponse};
+use crate::models::wellness::{
+    FocusIdleAdjustment, FocusSession, WellnessEventRecord, WellnessResponse,
+};
 use crate::services::wellness_service::WeeklySummary;
 
 #[tauri::command]
@@ -39,6 +41,64 @@ pub async fn wellness_respond(
     .await
 }
 
+/// Non-critical nudges are held back while the session runs.
+#[tauri::command]
+pub async fn wellness_focus_start(
+    state: State<'_, AppState>,
+    planned_minutes: Option<i64>,
+    task_id: Option<String>,
+) -> CommandResult<FocusSession> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || {
+        app_state
+            .wellness()
+            .start_focus_session(planned_minutes, task_id)
+    })
+    .await
+}
+
+/// Ends the session and returns the nudge it deferred, if one is due.
+#[tauri::command]
+pub async fn wellness_focus_end(
+    state: State<'_, AppState>,
+) -> CommandResult<Option<WellnessEventRecord>> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || app_state.wellness().end_focus_session()).await
+}
+
+#[tauri::command]
+pub async fn wellness_focus_current(
+    state: State<'_, AppState>,
+) -> CommandResult<Option<FocusSession>> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || app_state.wellness().current_focus_session()).await
+}
+
+/// Idle time detected in past focus sessions, awaiting confirmation.
+#[tauri::command]
+pub async fn wellness_idle_pending(
+    state: State<'_, AppState>,
+) -> CommandResult<Vec<FocusIdleAdjustment>> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || app_state.wellness().pending_idle_adjustments()).await
+}
+
+/// Confirmed idle time is subtracted from the recorded focus minutes.
+#[tauri::command]
+pub async fn wellness_idle_resolve(
+    state: State<'_, AppState>,
+    id: i64,
+    confirm: bool,
+) -> CommandResult<FocusIdleAdjustment> {
+    let app_state = state.inner().clone();
+
+    run_blocking(move || app_state.wellness().resolve_idle_adjustment(id, confirm)).await
+}
+
 #[tauri::command]
 pub async fn wellness_get_weekly_summary(
     state: State<'_, AppState>,
@@ -53,6 +113,6 @@ async fn run_blocking<T: Send + 'static>(
 ) -> CommandResult<T> {
     async_runtime::spawn_blocking(task)
         .await
-        .map_err(|err| CommandError::new("UNKNOWN", format!("健康提醒任务执行失败: {err}"), None))?
+        .map_err(|err| CommandError::internal(format!("健康提醒任务执行失败: {err}")))?
         .map_err(CommandError::from)
 }
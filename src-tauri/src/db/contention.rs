@@ -0,0 +1,291 @@
+//! How connections wait out `SQLITE_BUSY`. Every access is classified: an
+//! interactive read gives up quickly so the UI never hangs behind a
+//! background writer, an interactive write waits the usual few seconds, and
+//! a background write keeps retrying with jittered backoff because nobody is
+//! waiting on it. Busy errors and retries are counted for diagnostics.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::Serialize;
+use tracing::{debug, warn};
+
+use crate::error::AppResult;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbAccess {
+    InteractiveRead,
+    InteractiveWrite,
+    BackgroundWrite,
+}
+
+impl DbAccess {
+    pub const ALL: [DbAccess; 3] = [
+        DbAccess::InteractiveRead,
+        DbAccess::InteractiveWrite,
+        DbAccess::BackgroundWrite,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DbAccess::InteractiveRead => "interactive_read",
+            DbAccess::InteractiveWrite => "interactive_write",
+            DbAccess::BackgroundWrite => "background_write",
+        }
+    }
+
+    pub fn policy(self) -> RetryPolicy {
+        match self {
+            DbAccess::InteractiveRead => RetryPolicy {
+                busy_timeout: Duration::from_secs(1),
+                max_attempts: 1,
+                base_delay: Duration::ZERO,
+                max_delay: Duration::ZERO,
+            },
+            DbAccess::InteractiveWrite => RetryPolicy {
+                busy_timeout: Duration::from_secs(5),
+                max_attempts: 2,
+                base_delay: Duration::from_millis(50),
+                max_delay: Duration::from_millis(250),
+            },
+            DbAccess::BackgroundWrite => RetryPolicy {
+                busy_timeout: Duration::from_secs(10),
+                max_attempts: 6,
+                base_delay: Duration::from_millis(250),
+                max_delay: Duration::from_secs(8),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// SQLite's own wait for the lock before it reports busy.
+    pub busy_timeout: Duration,
+    /// Attempts including the first one.
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Wait before attempt `attempt + 1`. `jitter` in `[0, 1]` picks a point
+    /// between half and all of the exponential delay, so writers that
+    /// collided do not collide again on the next try.
+    pub fn backoff_delay(&self, attempt: u32, jitter: f64) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        let delay = self
+            .base_delay
+            .saturating_mul(1_u32 << exponent)
+            .min(self.max_delay);
+        delay.mul_f64(0.5 + 0.5 * jitter.clamp(0.0, 1.0))
+    }
+}
+
+/// Runs `operation` under the policy for `access`, trying again while it
+/// fails with a busy error. `operation` must be safe to re-run after a
+/// partial failure, e.g. a single upsert or its own transaction.
+pub fn retry_busy<T, F>(access: DbAccess, mut operation: F) -> AppResult<T>
+where
+    F: FnMut() -> AppResult<T>,
+{
+    let policy = access.policy();
+    let mut attempt = 1;
+    loop {
+        match operation() {
+            Err(err) if err.is_busy() => {
+                DB_CONTENTION_METRICS.record(access, ContentionEvent::Busy);
+                if attempt >= policy.max_attempts {
+                    DB_CONTENTION_METRICS.record(access, ContentionEvent::GaveUp);
+                    warn!(
+                        target: "app::database",
+                        access = access.as_str(),
+                        attempts = attempt,
+                        error = %err,
+                        "database still busy, giving up"
+                    );
+                    return Err(err);
+                }
+                let delay = policy.backoff_delay(attempt, rand::thread_rng().gen());
+                debug!(
+                    target: "app::database",
+                    access = access.as_str(),
+                    attempt,
+                    delay_ms = delay.as_millis() as u64,
+                    "database busy, retrying"
+                );
+                DB_CONTENTION_METRICS.record(access, ContentionEvent::Retried);
+                thread::sleep(delay);
+                attempt += 1;
+            }
+            result => {
+                if attempt > 1 && result.is_ok() {
+                    DB_CONTENTION_METRICS.record(access, ContentionEvent::Recovered);
+                }
+                return result;
+            }
+        }
+    }
+}
+
+/// Counts a busy failure from work that is not retried, such as an
+/// interactive callback that already ran once.
+pub fn observe<T>(access: DbAccess, result: AppResult<T>) -> AppResult<T> {
+    if result.as_ref().is_err_and(|err| err.is_busy()) {
+        DB_CONTENTION_METRICS.record(access, ContentionEvent::Busy);
+        DB_CONTENTION_METRICS.record(access, ContentionEvent::GaveUp);
+    }
+    result
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentionEvent {
+    Busy,
+    Retried,
+    Recovered,
+    GaveUp,
+}
+
+#[derive(Debug, Default)]
+struct AccessCounters {
+    busy: AtomicU64,
+    retried: AtomicU64,
+    recovered: AtomicU64,
+    gave_up: AtomicU64,
+}
+
+impl AccessCounters {
+    const fn new() -> Self {
+        Self {
+            busy: AtomicU64::new(0),
+            retried: AtomicU64::new(0),
+            recovered: AtomicU64::new(0),
+            gave_up: AtomicU64::new(0),
+        }
+    }
+}
+
+/// In-process contention counters per access class. They cover the current
+/// app session only and reset on restart.
+#[derive(Debug, Default)]
+pub struct DbContentionMetrics {
+    counters: [AccessCounters; 3],
+}
+
+impl DbContentionMetrics {
+    pub const fn new() -> Self {
+        Self {
+            counters: [
+                AccessCounters::new(),
+                AccessCounters::new(),
+                AccessCounters::new(),
+            ],
+        }
+    }
+
+    pub fn record(&self, access: DbAccess, event: ContentionEvent) {
+        let counters = &self.counters[access as usize];
+        let counter = match event {
+            ContentionEvent::Busy => &counters.busy,
+            ContentionEvent::Retried => &counters.retried,
+            ContentionEvent::Recovered => &counters.recovered,
+            ContentionEvent::GaveUp => &counters.gave_up,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> Vec<DbContentionStats> {
+        DbAccess::ALL
+            .iter()
+            .map(|access| {
+                let counters = &self.counters[*access as usize];
+                let policy = access.policy();
+                DbContentionStats {
+                    access: access.as_str().to_string(),
+                    busy_timeout_ms: policy.busy_timeout.as_millis() as u64,
+                    max_attempts: policy.max_attempts,
+                    busy_errors: counters.busy.load(Ordering::Relaxed),
+                    retries: counters.retried.load(Ordering::Relaxed),
+                    recovered: counters.recovered.load(Ordering::Relaxed),
+                    gave_up: counters.gave_up.load(Ordering::Relaxed),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Busy handling for one access class during this session.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DbContentionStats {
+    pub access: String,
+    pub busy_timeout_ms: u64,
+    pub max_attempts: u32,
+    /// Attempts that failed with `SQLITE_BUSY` or `SQLITE_LOCKED`.
+    pub busy_errors: u64,
+    pub retries: u64,
+    /// Operations that succeeded after at least one retry.
+    pub recovered: u64,
+    /// Operations that were still busy on their last attempt.
+    pub gave_up: u64,
+}
+
+/// Contention counters shared by every pool clone.
+pub static DB_CONTENTION_METRICS: DbContentionMetrics = DbContentionMetrics::new();
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AppError;
+
+    fn busy() -> AppError {
+        AppError::from(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY),
+            None,
+        ))
+    }
+
+    #[test]
+    fn backoff_grows_is_capped_and_jittered() {
+        let policy = DbAccess::BackgroundWrite.policy();
+        assert_eq!(policy.backoff_delay(1, 1.0), Duration::from_millis(250));
+        assert_eq!(policy.backoff_delay(3, 1.0), Duration::from_secs(1));
+        assert_eq!(policy.backoff_delay(3, 0.0), Duration::from_millis(500));
+        assert_eq!(policy.backoff_delay(12, 1.0), Duration::from_secs(8));
+    }
+
+    #[test]
+    fn interactive_reads_fail_fast_while_writes_retry() {
+        assert!(busy().is_busy());
+
+        let mut calls = 0;
+        let result: AppResult<()> = retry_busy(DbAccess::InteractiveRead, || {
+            calls += 1;
+            Err(busy())
+        });
+        assert!(result.is_err_and(|err| err.code() == "DATABASE_BUSY"));
+        assert_eq!(calls, 1);
+
+        let mut calls = 0;
+        let result = retry_busy(DbAccess::InteractiveWrite, || {
+            calls += 1;
+            if calls == 1 {
+                Err(busy())
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(result.unwrap(), 2);
+
+        let mut calls = 0;
+        let result: AppResult<()> = retry_busy(DbAccess::InteractiveWrite, || {
+            calls += 1;
+            Err(AppError::not_found())
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+}
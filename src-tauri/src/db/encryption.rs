@@ -0,0 +1,292 @@
+//! Opt-in at-rest encryption backed by SQLCipher (cargo feature `sqlcipher`).
+//!
+//! The raw key is derived from a user passphrase with PBKDF2 and kept in the
+//! OS keychain; a small sidecar file next to the database records the salt
+//! and iteration count so the key can be re-derived from the passphrase.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use base64::{engine::general_purpose::STANDARD as Base64, Engine as _};
+use keyring::Entry;
+use pbkdf2::pbkdf2_hmac;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use rusqlite::Connection;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use crate::error::{AppError, AppResult};
+
+pub const ENCRYPTION_SUPPORTED: bool = cfg!(feature = "sqlcipher");
+
+const KEYRING_SERVICE: &str = "cognical.db.key";
+const HEADER_SUFFIX: &str = ".keyinfo.json";
+const KDF_NAME: &str = "pbkdf2-sha256";
+const KDF_ITERATIONS: u32 = 256_000;
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const MIN_PASSPHRASE_CHARS: usize = 8;
+
+/// Raw SQLCipher key, hex encoded. Never printed.
+#[derive(Clone, PartialEq, Eq)]
+pub struct DatabaseKey(String);
+
+impl fmt::Debug for DatabaseKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DatabaseKey(***)")
+    }
+}
+
+impl DatabaseKey {
+    fn pragma_literal(&self) -> String {
+        format!("\"x'{}'\"", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct KeyHeader {
+    kdf: String,
+    iterations: u32,
+    salt: String,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptionStatus {
+    /// Whether this build ships SQLCipher.
+    pub supported: bool,
+    pub enabled: bool,
+}
+
+pub fn header_path(db_path: &Path) -> PathBuf {
+    with_suffix(db_path, HEADER_SUFFIX)
+}
+
+pub fn is_encrypted(db_path: &Path) -> bool {
+    header_path(db_path).exists()
+}
+
+/// Key for an encrypted database, read from the keychain. `None` when the
+/// database is plaintext.
+pub fn load_key(db_path: &Path) -> AppResult<Option<DatabaseKey>> {
+    if !is_encrypted(db_path) {
+        return Ok(None);
+    }
+    if !ENCRYPTION_SUPPORTED {
+        return Err(AppError::crypto(
+            "数据库已加密，但当前版本未启用数据库加密支持",
+        ));
+    }
+
+    match keyring_entry(db_path)?.get_password() {
+        Ok(hex) => Ok(Some(DatabaseKey(hex))),
+        Err(keyring::Error::NoEntry) => Err(AppError::crypto(
+            "数据库已加密，但系统钥匙串中缺少对应的密钥",
+        )),
+        Err(err) => Err(AppError::crypto(format!("无法访问系统密钥存储: {err}"))),
+    }
+}
+
+/// Unlocks `conn` and checks the key actually opens the database.
+pub fn apply_key(conn: &Connection, key: &DatabaseKey) -> AppResult<()> {
+    conn.execute_batch(&format!("PRAGMA key = {};", key.pragma_literal()))?;
+    conn.query_row("SELECT count(*) FROM sqlite_master", [], |row| {
+        row.get::<_, i64>(0)
+    })
+    .map_err(|_| AppError::crypto("数据库密钥不正确，无法打开数据库"))?;
+    Ok(())
+}
+
+/// One-time migration from the plaintext database at `db_path` to an
+/// encrypted copy. The plaintext file is only removed after the encrypted
+/// copy opens with the new key; on failure the original stays in place.
+pub fn encrypt_database(db_path: &Path, passphrase: &str) -> AppResult<DatabaseKey> {
+    if !ENCRYPTION_SUPPORTED {
+        return Err(AppError::validation("当前版本未启用数据库加密支持"));
+    }
+    if is_encrypted(db_path) {
+        return Err(AppError::conflict("数据库已处于加密状态"));
+    }
+    if passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+        return Err(AppError::validation(format!(
+            "加密口令至少需要 {} 个字符",
+            MIN_PASSPHRASE_CHARS
+        )));
+    }
+
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let header = KeyHeader {
+        kdf: KDF_NAME.to_string(),
+        iterations: KDF_ITERATIONS,
+        salt: Base64.encode(salt),
+    };
+    let key = derive_key(passphrase, &salt, header.iterations);
+
+    let encrypted_path = sibling_path(db_path, "encrypting");
+    export_encrypted_copy(db_path, &encrypted_path, &key)?;
+
+    keyring_entry(db_path)?
+        .set_password(&key.0)
+        .map_err(|err| AppError::crypto(format!("无法写入系统密钥存储: {err}")))?;
+    fs::write(header_path(db_path), serde_json::to_vec_pretty(&header)?)?;
+
+    if let Err(err) = swap_in_encrypted(db_path, &encrypted_path, &key) {
+        let _ = fs::remove_file(header_path(db_path));
+        let _ = keyring_entry(db_path).map(|entry| entry.delete_password());
+        return Err(err);
+    }
+
+    info!(target: "app::db", db_path = %db_path.display(), "database encrypted at rest");
+    Ok(key)
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> DatabaseKey {
+    let mut raw = [0u8; KEY_LEN];
+    pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, iterations, &mut raw);
+    DatabaseKey(raw.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+fn export_encrypted_copy(
+    plain_path: &Path,
+    encrypted_path: &Path,
+    key: &DatabaseKey,
+) -> AppResult<()> {
+    if encrypted_path.exists() {
+        fs::remove_file(encrypted_path)?;
+    }
+
+    let conn = Connection::open(plain_path)?;
+    let user_version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+    conn.execute(
+        &format!(
+            "ATTACH DATABASE ?1 AS encrypted KEY {}",
+            key.pragma_literal()
+        ),
+        [encrypted_path.to_string_lossy()],
+    )?;
+    let exported = conn
+        .query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))
+        .and_then(|_| {
+            conn.execute_batch(&format!("PRAGMA encrypted.user_version = {user_version};"))
+        });
+    conn.execute_batch("DETACH DATABASE encrypted;")?;
+    exported?;
+    Ok(())
+}
+
+fn swap_in_encrypted(db_path: &Path, encrypted_path: &Path, key: &DatabaseKey) -> AppResult<()> {
+    let backup_path = sibling_path(db_path, "plaintext-backup");
+    fs::rename(db_path, &backup_path)?;
+    for suffix in ["-wal", "-shm"] {
+        let _ = fs::remove_file(with_suffix(db_path, suffix));
+    }
+
+    let verified = fs::rename(encrypted_path, db_path)
+        .map_err(AppError::from)
+        .and_then(|_| {
+            let conn = Connection::open(db_path)?;
+            apply_key(&conn, key)
+        });
+    match verified {
+        Ok(()) => {
+            if let Err(err) = fs::remove_file(&backup_path) {
+                warn!(target: "app::db", error = %err, "failed to remove plaintext backup");
+            }
+            Ok(())
+        }
+        Err(err) => {
+            let _ = fs::remove_file(db_path);
+            fs::rename(&backup_path, db_path)?;
+            Err(err)
+        }
+    }
+}
+
+fn sibling_path(db_path: &Path, label: &str) -> PathBuf {
+    with_suffix(db_path, &format!(".{label}"))
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn keyring_entry(db_path: &Path) -> AppResult<Entry> {
+    let mut hasher = Sha256::new();
+    hasher.update(b"cognical.db.key.v1");
+    hasher.update(db_path.to_string_lossy().as_bytes());
+    let digest = hasher.finalize();
+    let account: String = digest[..16]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    Entry::new(KEYRING_SERVICE, &format!("db-{account}"))
+        .map_err(|err| AppError::crypto(format!("无法初始化系统密钥存储: {err}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derived_keys_depend_on_passphrase_and_salt() {
+        let first = derive_key("correct horse", &[1u8; SALT_LEN], 1_000);
+        assert_eq!(first, derive_key("correct horse", &[1u8; SALT_LEN], 1_000));
+        assert_ne!(first, derive_key("correct horse", &[2u8; SALT_LEN], 1_000));
+        assert_ne!(first, derive_key("battery staple", &[1u8; SALT_LEN], 1_000));
+        assert_eq!(first.0.len(), KEY_LEN * 2);
+        assert_eq!(format!("{first:?}"), "DatabaseKey(***)");
+    }
+
+    #[test]
+    fn header_lives_next_to_database() {
+        let path = Path::new("/data/cognical.sqlite");
+        assert_eq!(
+            header_path(path),
+            PathBuf::from("/data/cognical.sqlite.keyinfo.json")
+        );
+        assert!(!is_encrypted(path));
+    }
+
+    #[cfg(feature = "sqlcipher")]
+    #[test]
+    fn exported_copy_requires_the_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let plain = dir.path().join("plain.sqlite");
+        let encrypted = dir.path().join("encrypted.sqlite");
+        {
+            let conn = Connection::open(&plain).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE notes (body TEXT); INSERT INTO notes VALUES ('secret');
+                 PRAGMA user_version = 7;",
+            )
+            .unwrap();
+        }
+        let key = derive_key("passphrase", &[3u8; SALT_LEN], 1_000);
+        export_encrypted_copy(&plain, &encrypted, &key).unwrap();
+
+        let locked = Connection::open(&encrypted).unwrap();
+        assert!(locked
+            .query_row("SELECT count(*) FROM notes", [], |row| row.get::<_, i64>(0))
+            .is_err());
+
+        let conn = Connection::open(&encrypted).unwrap();
+        apply_key(&conn, &key).unwrap();
+        let body: String = conn
+            .query_row("SELECT body FROM notes", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(body, "secret");
+        let version: i64 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, 7);
+    }
+}
@@ -0,0 +1,175 @@
+//! Keeps the WAL file in check. Long-lived background jobs can hold readers
+//! open long enough that SQLite's automatic checkpoints never catch up, so a
+//! worker checkpoints passively whenever interactive commands have left the
+//! database alone for a while, and truncates the WAL once it grows past a
+//! threshold regardless of activity.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::RwLock;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use rusqlite::Connection;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::error::AppResult;
+
+/// WAL size past which a truncate checkpoint runs even while the app is busy.
+pub const WAL_TRUNCATE_THRESHOLD_BYTES: u64 = 64 * 1024 * 1024;
+/// Size the WAL file is cut back to after a checkpoint resets it.
+pub const WAL_SIZE_LIMIT_BYTES: i64 = 16 * 1024 * 1024;
+/// Quiet time without interactive commands before a passive checkpoint.
+pub const IDLE_BEFORE_CHECKPOINT_SECS: i64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckpointMode {
+    /// Copies what it can without waiting for readers or writers.
+    Passive,
+    /// Waits for readers, copies everything and empties the WAL file.
+    Truncate,
+}
+
+impl CheckpointMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CheckpointMode::Passive => "PASSIVE",
+            CheckpointMode::Truncate => "TRUNCATE",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointOutcome {
+    pub mode: CheckpointMode,
+    /// The checkpoint could not finish because another connection held a
+    /// lock; the rest is copied by a later one.
+    pub busy: bool,
+    pub wal_frames: i64,
+    pub checkpointed_frames: i64,
+    pub completed_at: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DbFileSizes {
+    pub database_bytes: u64,
+    pub wal_bytes: u64,
+    pub shm_bytes: u64,
+}
+
+impl DbFileSizes {
+    /// Sizes of the database file and its `-wal`/`-shm` companions; missing
+    /// files count as empty.
+    pub fn read(path: &Path) -> Self {
+        let size = |path: &Path| fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+        Self {
+            database_bytes: size(path),
+            wal_bytes: size(&companion(path, "-wal")),
+            shm_bytes: size(&companion(path, "-shm")),
+        }
+    }
+}
+
+fn companion(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Checkpoint due given the current file sizes and how long interactive
+/// commands have left the database alone.
+pub fn due_checkpoint(sizes: &DbFileSizes, idle_for: Duration) -> Option<CheckpointMode> {
+    if sizes.wal_bytes >= WAL_TRUNCATE_THRESHOLD_BYTES {
+        Some(CheckpointMode::Truncate)
+    } else if sizes.wal_bytes > 0 && idle_for >= Duration::seconds(IDLE_BEFORE_CHECKPOINT_SECS) {
+        Some(CheckpointMode::Passive)
+    } else {
+        None
+    }
+}
+
+pub fn run_checkpoint(conn: &Connection, mode: CheckpointMode) -> AppResult<CheckpointOutcome> {
+    let (busy, wal_frames, checkpointed_frames) = conn.query_row(
+        &format!("PRAGMA wal_checkpoint({})", mode.as_str()),
+        [],
+        |row| Ok((row.get::<_, i64>(0)? != 0, row.get(1)?, row.get(2)?)),
+    )?;
+    let outcome = CheckpointOutcome {
+        mode,
+        busy,
+        wal_frames,
+        checkpointed_frames,
+        completed_at: Utc::now().to_rfc3339(),
+    };
+    record_checkpoint(&outcome);
+    Ok(outcome)
+}
+
+/// Millisecond timestamp of the last interactive database access.
+static LAST_INTERACTIVE_ACCESS: AtomicI64 = AtomicI64::new(0);
+static LAST_CHECKPOINT: RwLock<Option<CheckpointOutcome>> = RwLock::new(None);
+
+/// Marks the database as in use by an interactive command.
+pub fn touch() {
+    LAST_INTERACTIVE_ACCESS.store(Utc::now().timestamp_millis(), Ordering::Relaxed);
+}
+
+/// Time since the last interactive database access.
+pub fn idle_for(now: DateTime<Utc>) -> Duration {
+    let last = LAST_INTERACTIVE_ACCESS.load(Ordering::Relaxed);
+    match Utc.timestamp_millis_opt(last).single() {
+        Some(last) => (now - last).max(Duration::zero()),
+        None => Duration::zero(),
+    }
+}
+
+fn record_checkpoint(outcome: &CheckpointOutcome) {
+    *LAST_CHECKPOINT
+        .write()
+        .unwrap_or_else(|err| err.into_inner()) = Some(outcome.clone());
+}
+
+/// Latest checkpoint run by this session.
+pub fn last_checkpoint() -> Option<CheckpointOutcome> {
+    LAST_CHECKPOINT
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checkpoints_when_idle_or_oversized() {
+        let small = DbFileSizes {
+            database_bytes: 1 << 20,
+            wal_bytes: 512 * 1024,
+            shm_bytes: 32 * 1024,
+        };
+        let huge = DbFileSizes {
+            wal_bytes: WAL_TRUNCATE_THRESHOLD_BYTES,
+            ..small
+        };
+        let empty = DbFileSizes {
+            wal_bytes: 0,
+            ..small
+        };
+
+        assert_eq!(due_checkpoint(&small, Duration::seconds(5)), None);
+        assert_eq!(
+            due_checkpoint(&small, Duration::minutes(2)),
+            Some(CheckpointMode::Passive)
+        );
+        assert_eq!(
+            due_checkpoint(&huge, Duration::zero()),
+            Some(CheckpointMode::Truncate)
+        );
+        assert_eq!(due_checkpoint(&empty, Duration::hours(1)), None);
+    }
+}
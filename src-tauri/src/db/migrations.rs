@@ -2,10 +2,10 @@ use rusqlite::{Connection, Row};
 use tracing::{info, warn};
 use chrono::{DateTime, Utc};
 
-use crate::error::AppResult;
+use crate::error::{AppError, AppResult};
 use crate::models::settings::DashboardConfig;
 
-const USER_VERSION: i32 = 9;
+const USER_VERSION: i32 = 36;
 const KEY_DASHBOARD_CONFIG: &str = "dashboard_config";
 
 #[derive(Debug)]
@@ -17,7 +17,18 @@ pub struct MigrationInfo {
 
 
 
+/// Brings the schema up to date. A failing step is reported as
+/// `AppError::Migration` carrying the version that could not be applied.
 pub fn run(conn: &Connection) -> AppResult<()> {
+    apply_pending(conn).map_err(|err| {
+        let reached: i32 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap_or(0);
+        AppError::migration(reached + 1, err.to_string())
+    })
+}
+
+fn apply_pending(conn: &Connection) -> AppResult<()> {
     // Ensure migration history table exists
     conn.execute_batch(
         r#"
@@ -118,6 +129,252 @@ pub fn run(conn: &Connection) -> AppResult<()> {
         ))?;
     }
 
+    if current_version < 10 {
+        info!(target: "app::db", version = current_version, "running migration v10");
+        migrate_to_v10(conn)?;
+        current_version = 10;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(
+            conn,
+            10,
+            "Add day logs for end-of-day close",
+            Some("DROP TABLE IF EXISTS day_logs;"),
+        )?;
+    }
+
+    if current_version < 11 {
+        info!(target: "app::db", version = current_version, "running migration v11");
+        migrate_to_v11(conn)?;
+        current_version = 11;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(
+            conn,
+            11,
+            "Add later list items",
+            Some("DROP TABLE IF EXISTS later_items;"),
+        )?;
+    }
+
+    if current_version < 12 {
+        info!(target: "app::db", version = current_version, "running migration v12");
+        migrate_to_v12(conn)?;
+        current_version = 12;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(
+            conn,
+            12,
+            "Add runtime-registered custom tools",
+            Some("DROP TABLE IF EXISTS custom_tools;"),
+        )?;
+    }
+
+    if current_version < 13 {
+        info!(target: "app::db", version = current_version, "running migration v13");
+        migrate_to_v13(conn)?;
+        current_version = 13;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(
+            conn,
+            13,
+            "Add user overrides for AI system prompts",
+            Some("DROP TABLE IF EXISTS prompt_overrides;"),
+        )?;
+    }
+
+    if current_version < 14 {
+        info!(target: "app::db", version = current_version, "running migration v14");
+        migrate_to_v14(conn)?;
+        current_version = 14;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(conn, 14, "Allow wind-down wellness nudges", None)?;
+    }
+
+    if current_version < 15 {
+        info!(target: "app::db", version = current_version, "running migration v15");
+        migrate_to_v15(conn)?;
+        current_version = 15;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(conn, 15, "Add context switch count to analytics snapshots", None)?;
+    }
+
+    if current_version < 16 {
+        info!(target: "app::db", version = current_version, "running migration v16");
+        migrate_to_v16(conn)?;
+        current_version = 16;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(conn, 16, "Add AI debug log", None)?;
+    }
+
+    if current_version < 17 {
+        info!(target: "app::db", version = current_version, "running migration v17");
+        migrate_to_v17(conn)?;
+        current_version = 17;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(conn, 17, "Add background job retry queue", None)?;
+    }
+
+    if current_version < 18 {
+        info!(target: "app::db", version = current_version, "running migration v18");
+        migrate_to_v18(conn)?;
+        current_version = 18;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(conn, 18, "Add projects grouping tasks and goals", None)?;
+    }
+
+    if current_version < 19 {
+        info!(target: "app::db", version = current_version, "running migration v19");
+        migrate_to_v19(conn)?;
+        current_version = 19;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(conn, 19, "Link planning sessions to projects", None)?;
+    }
+
+    if current_version < 20 {
+        info!(target: "app::db", version = current_version, "running migration v20");
+        migrate_to_v20(conn)?;
+        current_version = 20;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(conn, 20, "Add task snoozing", None)?;
+    }
+
+    if current_version < 21 {
+        info!(target: "app::db", version = current_version, "running migration v21");
+        migrate_to_v21(conn)?;
+        current_version = 21;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(conn, 21, "Add start-of-block reminders", None)?;
+    }
+
+    if current_version < 22 {
+        info!(target: "app::db", version = current_version, "running migration v22");
+        migrate_to_v22(conn)?;
+        current_version = 22;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(conn, 22, "Add proactive agent suggestions", None)?;
+    }
+
+    if current_version < 23 {
+        info!(target: "app::db", version = current_version, "running migration v23");
+        migrate_to_v23(conn)?;
+        current_version = 23;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(conn, 23, "Add task and project appearance", None)?;
+    }
+
+    if current_version < 24 {
+        info!(target: "app::db", version = current_version, "running migration v24");
+        migrate_to_v24(conn)?;
+        current_version = 24;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(conn, 24, "Add analytics anomaly alerts", None)?;
+    }
+
+    if current_version < 25 {
+        info!(target: "app::db", version = current_version, "running migration v25");
+        migrate_to_v25(conn)?;
+        current_version = 25;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(conn, 25, "Add entity change log for delta sync", None)?;
+    }
+
+    if current_version < 26 {
+        info!(target: "app::db", version = current_version, "running migration v26");
+        migrate_to_v26(conn)?;
+        current_version = 26;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(
+            conn,
+            26,
+            "Add materialized daily analytics aggregates",
+            None,
+        )?;
+    }
+
+    if current_version < 27 {
+        info!(target: "app::db", version = current_version, "running migration v27");
+        migrate_to_v27(conn)?;
+        current_version = 27;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(
+            conn,
+            27,
+            "Add forecast intervals and buckets to workload_forecasts",
+            None,
+        )?;
+    }
+
+    if current_version < 28 {
+        info!(target: "app::db", version = current_version, "running migration v28");
+        migrate_to_v28(conn)?;
+        current_version = 28;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(conn, 28, "Add goal check-in cadence and history", None)?;
+    }
+
+    if current_version < 29 {
+        info!(target: "app::db", version = current_version, "running migration v29");
+        migrate_to_v29(conn)?;
+        current_version = 29;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(conn, 29, "Add names to preference profiles", None)?;
+    }
+
+    if current_version < 30 {
+        info!(target: "app::db", version = current_version, "running migration v30");
+        migrate_to_v30(conn)?;
+        current_version = 30;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(conn, 30, "Add background worker run health", None)?;
+    }
+
+    if current_version < 31 {
+        info!(target: "app::db", version = current_version, "running migration v31");
+        migrate_to_v31(conn)?;
+        current_version = 31;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(conn, 31, "Add feature flags", None)?;
+    }
+
+    if current_version < 32 {
+        info!(target: "app::db", version = current_version, "running migration v32");
+        migrate_to_v32(conn)?;
+        current_version = 32;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(conn, 32, "Add post-upgrade data steps", None)?;
+    }
+
+    if current_version < 33 {
+        info!(target: "app::db", version = current_version, "running migration v33");
+        migrate_to_v33(conn)?;
+        current_version = 33;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(conn, 33, "Add focus idle adjustments", None)?;
+    }
+
+    if current_version < 34 {
+        info!(target: "app::db", version = current_version, "running migration v34");
+        migrate_to_v34(conn)?;
+        current_version = 34;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(conn, 34, "Add task delegation fields", None)?;
+    }
+
+    if current_version < 35 {
+        info!(target: "app::db", version = current_version, "running migration v35");
+        migrate_to_v35(conn)?;
+        current_version = 35;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(conn, 35, "Add prep notes to planning time blocks", None)?;
+    }
+
+    if current_version < 36 {
+        info!(target: "app::db", version = current_version, "running migration v36");
+        migrate_to_v36(conn)?;
+        current_version = 36;
+        conn.execute(&format!("PRAGMA user_version = {}", current_version), [])?;
+        record_migration(conn, 36, "Add CalDAV account and sync state", None)?;
+    }
+
     if current_version != USER_VERSION {
         conn.execute(&format!("PRAGMA user_version = {}", USER_VERSION), [])?;
     }
@@ -651,6 +908,27 @@ fn migrate_to_v9(conn: &Connection) -> AppResult<()> {
     Ok(())
 }
 
+fn migrate_to_v10(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        r#"
+        -- One row per closed day: reflection, roll-over decisions and score
+        CREATE TABLE IF NOT EXISTS day_logs (
+            log_date TEXT PRIMARY KEY,
+            closed_at TEXT NOT NULL,
+            reflection TEXT,
+            completed_tasks INTEGER NOT NULL DEFAULT 0,
+            unfinished_blocks INTEGER NOT NULL DEFAULT 0,
+            decisions TEXT NOT NULL DEFAULT '[]',
+            productivity_score REAL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        "#,
+    )?;
+
+    Ok(())
+}
+
 fn ensure_column(conn: &Connection, table: &str, column: &str, definition: &str) -> AppResult<()> {
     if !column_exists(conn, table, column)? {
         let sql = format!("ALTER TABLE {table} ADD COLUMN {column} {definition};");
@@ -677,3 +955,663 @@ fn equals_name(row: &Row<'_>, column: &str) -> Result<bool, rusqlite::Error> {
     let name: String = row.get(1)?;
     Ok(name.eq_ignore_ascii_case(column))
 }
+
+fn migrate_to_v11(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        r#"
+        -- Reading/watching queue kept outside the task priority ordering
+        CREATE TABLE IF NOT EXISTS later_items (
+            id TEXT PRIMARY KEY,
+            title TEXT NOT NULL,
+            url TEXT,
+            kind TEXT NOT NULL DEFAULT 'other',
+            estimated_minutes INTEGER NOT NULL,
+            notes TEXT,
+            status TEXT NOT NULL DEFAULT 'queued',
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            completed_at TEXT
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_later_items_status
+            ON later_items(status, created_at);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_to_v12(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        r#"
+        -- Declarative agent tools registered at runtime and reloaded on start
+        CREATE TABLE IF NOT EXISTS custom_tools (
+            name TEXT PRIMARY KEY,
+            description TEXT NOT NULL,
+            parameters TEXT NOT NULL,
+            target_tool TEXT NOT NULL,
+            fixed_arguments TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_to_v13(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        r#"
+        -- User-edited system prompts; absent rows fall back to built-in defaults
+        CREATE TABLE IF NOT EXISTS prompt_overrides (
+            prompt_key TEXT PRIMARY KEY,
+            template TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_to_v14(conn: &Connection) -> AppResult<()> {
+    // SQLite cannot alter a CHECK constraint, so the table is rebuilt
+    conn.execute_batch(
+        r#"
+        CREATE TABLE wellness_events_v14 (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            window_start TEXT NOT NULL,
+            trigger_reason TEXT NOT NULL CHECK(trigger_reason IN ('focus_streak', 'work_streak', 'wind_down')),
+            recommended_break_minutes INTEGER NOT NULL,
+            suggested_micro_task TEXT,
+            response TEXT CHECK(response IN ('completed', 'snoozed', 'ignored')),
+            response_at TEXT,
+            deferral_count INTEGER NOT NULL DEFAULT 0
+        );
+
+        INSERT INTO wellness_events_v14 (
+            id, window_start, trigger_reason, recommended_break_minutes,
+            suggested_micro_task, response, response_at, deferral_count
+        )
+        SELECT
+            id, window_start, trigger_reason, recommended_break_minutes,
+            suggested_micro_task, response, response_at, deferral_count
+        FROM wellness_events;
+
+        DROP TABLE wellness_events;
+        ALTER TABLE wellness_events_v14 RENAME TO wellness_events;
+
+        CREATE INDEX IF NOT EXISTS idx_wellness_events_window_start
+            ON wellness_events(window_start);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_to_v15(conn: &Connection) -> AppResult<()> {
+    ensure_column(
+        conn,
+        "analytics_snapshots",
+        "context_switches",
+        "INTEGER NOT NULL DEFAULT 0",
+    )?;
+
+    Ok(())
+}
+
+fn migrate_to_v16(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        r#"
+        -- Opt-in log of redacted AI requests and responses
+        CREATE TABLE IF NOT EXISTS ai_debug_log (
+            correlation_id TEXT PRIMARY KEY,
+            operation TEXT NOT NULL,
+            request TEXT NOT NULL,
+            response TEXT,
+            error TEXT,
+            latency_ms INTEGER,
+            created_at TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_ai_debug_log_created_at
+            ON ai_debug_log(created_at);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_to_v17(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        r#"
+        -- Failed background work retried with exponential backoff
+        CREATE TABLE IF NOT EXISTS background_jobs (
+            id TEXT PRIMARY KEY,
+            kind TEXT NOT NULL,
+            payload TEXT NOT NULL DEFAULT '{}',
+            status TEXT NOT NULL DEFAULT 'pending',
+            attempts INTEGER NOT NULL DEFAULT 0,
+            max_attempts INTEGER NOT NULL,
+            next_attempt_at TEXT NOT NULL,
+            last_error TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_background_jobs_due
+            ON background_jobs(status, next_attempt_at);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_to_v18(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        r#"
+        -- Projects grouping tasks and goals
+        CREATE TABLE IF NOT EXISTS projects (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'active',
+            color TEXT,
+            default_tags TEXT NOT NULL DEFAULT '[]',
+            archived_at TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        "#,
+    )?;
+
+    ensure_column(conn, "tasks", "project_id", "TEXT")?;
+    ensure_column(conn, "goals", "project_id", "TEXT")?;
+
+    conn.execute_batch(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_tasks_project_id ON tasks(project_id);
+        CREATE INDEX IF NOT EXISTS idx_goals_project_id ON goals(project_id);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_to_v19(conn: &Connection) -> AppResult<()> {
+    ensure_column(conn, "planning_sessions", "project_id", "TEXT")?;
+    conn.execute_batch(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_planning_sessions_project_id
+            ON planning_sessions(project_id, generated_at);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_to_v20(conn: &Connection) -> AppResult<()> {
+    ensure_column(conn, "tasks", "snoozed_until", "TEXT")?;
+    ensure_column(conn, "tasks", "snooze_notify", "INTEGER NOT NULL DEFAULT 0")?;
+    conn.execute_batch(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_tasks_snoozed_until ON tasks(snoozed_until);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_to_v21(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS block_reminders (
+            id TEXT PRIMARY KEY,
+            block_id TEXT NOT NULL UNIQUE,
+            task_id TEXT NOT NULL,
+            start_at TEXT NOT NULL,
+            remind_at TEXT NOT NULL,
+            lead_minutes INTEGER NOT NULL,
+            status TEXT NOT NULL DEFAULT 'scheduled',
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (block_id) REFERENCES planning_time_blocks(id) ON DELETE CASCADE,
+            FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_block_reminders_status_remind_at
+            ON block_reminders(status, remind_at);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_to_v22(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS agent_suggestions (
+            id TEXT PRIMARY KEY,
+            kind TEXT NOT NULL,
+            title TEXT NOT NULL,
+            reason TEXT NOT NULL,
+            task_id TEXT,
+            target_date TEXT,
+            payload TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending',
+            suggested_for TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            resolved_at TEXT,
+            FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_agent_suggestions_suggested_for
+            ON agent_suggestions(suggested_for);
+        CREATE INDEX IF NOT EXISTS idx_agent_suggestions_status
+            ON agent_suggestions(status);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_to_v23(conn: &Connection) -> AppResult<()> {
+    ensure_column(conn, "tasks", "color", "TEXT")?;
+    ensure_column(conn, "tasks", "icon", "TEXT")?;
+    ensure_column(conn, "projects", "icon", "TEXT")?;
+
+    Ok(())
+}
+
+fn migrate_to_v24(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS analytics_anomalies (
+            id TEXT PRIMARY KEY,
+            snapshot_date TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            observed REAL NOT NULL,
+            baseline REAL NOT NULL,
+            detail TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_analytics_anomalies_snapshot_date
+            ON analytics_anomalies(snapshot_date);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_to_v25(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS entity_changes (
+            revision INTEGER PRIMARY KEY AUTOINCREMENT,
+            entity TEXT NOT NULL,
+            entity_id TEXT NOT NULL,
+            deleted INTEGER NOT NULL DEFAULT 0,
+            changed_at TEXT NOT NULL,
+            UNIQUE(entity, entity_id)
+        );
+
+        CREATE TRIGGER IF NOT EXISTS trg_tasks_change_insert
+            AFTER INSERT ON tasks
+        BEGIN
+            DELETE FROM entity_changes WHERE entity = 'task' AND entity_id = NEW.id;
+            INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+            VALUES ('task', NEW.id, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS trg_tasks_change_update
+            AFTER UPDATE ON tasks
+        BEGIN
+            DELETE FROM entity_changes WHERE entity = 'task' AND entity_id = NEW.id;
+            INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+            VALUES ('task', NEW.id, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS trg_tasks_change_delete
+            AFTER DELETE ON tasks
+        BEGIN
+            DELETE FROM entity_changes WHERE entity = 'task' AND entity_id = OLD.id;
+            INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+            VALUES ('task', OLD.id, 1, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS trg_projects_change_insert
+            AFTER INSERT ON projects
+        BEGIN
+            DELETE FROM entity_changes WHERE entity = 'project' AND entity_id = NEW.id;
+            INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+            VALUES ('project', NEW.id, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS trg_projects_change_update
+            AFTER UPDATE ON projects
+        BEGIN
+            DELETE FROM entity_changes WHERE entity = 'project' AND entity_id = NEW.id;
+            INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+            VALUES ('project', NEW.id, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS trg_projects_change_delete
+            AFTER DELETE ON projects
+        BEGIN
+            DELETE FROM entity_changes WHERE entity = 'project' AND entity_id = OLD.id;
+            INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+            VALUES ('project', OLD.id, 1, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS trg_goals_change_insert
+            AFTER INSERT ON goals
+        BEGIN
+            DELETE FROM entity_changes WHERE entity = 'goal' AND entity_id = NEW.id;
+            INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+            VALUES ('goal', NEW.id, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS trg_goals_change_update
+            AFTER UPDATE ON goals
+        BEGIN
+            DELETE FROM entity_changes WHERE entity = 'goal' AND entity_id = NEW.id;
+            INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+            VALUES ('goal', NEW.id, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS trg_goals_change_delete
+            AFTER DELETE ON goals
+        BEGIN
+            DELETE FROM entity_changes WHERE entity = 'goal' AND entity_id = OLD.id;
+            INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+            VALUES ('goal', OLD.id, 1, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS trg_planning_time_blocks_change_insert
+            AFTER INSERT ON planning_time_blocks
+            WHEN NEW.applied_at IS NOT NULL
+        BEGIN
+            DELETE FROM entity_changes WHERE entity = 'time_block' AND entity_id = NEW.id;
+            INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+            VALUES ('time_block', NEW.id, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS trg_planning_time_blocks_change_update
+            AFTER UPDATE ON planning_time_blocks
+            WHEN NEW.applied_at IS NOT NULL OR OLD.applied_at IS NOT NULL
+        BEGIN
+            DELETE FROM entity_changes WHERE entity = 'time_block' AND entity_id = NEW.id;
+            INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+            VALUES ('time_block', NEW.id, NEW.applied_at IS NULL, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS trg_planning_time_blocks_change_delete
+            AFTER DELETE ON planning_time_blocks
+            WHEN OLD.applied_at IS NOT NULL
+        BEGIN
+            DELETE FROM entity_changes WHERE entity = 'time_block' AND entity_id = OLD.id;
+            INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+            VALUES ('time_block', OLD.id, 1, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+        END;
+
+        INSERT OR IGNORE INTO entity_changes (entity, entity_id, deleted, changed_at)
+            SELECT 'task', id, 0, updated_at FROM tasks;
+        INSERT OR IGNORE INTO entity_changes (entity, entity_id, deleted, changed_at)
+            SELECT 'project', id, 0, updated_at FROM projects;
+        INSERT OR IGNORE INTO entity_changes (entity, entity_id, deleted, changed_at)
+            SELECT 'goal', id, 0, updated_at FROM goals;
+        INSERT OR IGNORE INTO entity_changes (entity, entity_id, deleted, changed_at)
+            SELECT 'time_block', id, 0, applied_at FROM planning_time_blocks
+            WHERE applied_at IS NOT NULL;
+        "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_to_v26(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS analytics_daily_snapshots (
+            snapshot_date TEXT PRIMARY KEY,
+            completed INTEGER NOT NULL DEFAULT 0,
+            due INTEGER NOT NULL DEFAULT 0,
+            focus_minutes INTEGER NOT NULL DEFAULT 0,
+            overdue INTEGER NOT NULL DEFAULT 0,
+            stale INTEGER NOT NULL DEFAULT 0,
+            updated_at TEXT NOT NULL
+        );
+
+        CREATE TRIGGER IF NOT EXISTS trg_tasks_daily_aggregates_insert
+            AFTER INSERT ON tasks
+        BEGIN
+            UPDATE analytics_daily_snapshots SET stale = 1
+            WHERE snapshot_date >= MIN(COALESCE(date(NEW.due_at), '9999-12-31'), COALESCE(date(NEW.completed_at), '9999-12-31'));
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS trg_tasks_daily_aggregates_update
+            AFTER UPDATE ON tasks
+            WHEN OLD.due_at IS NOT NEW.due_at OR OLD.completed_at IS NOT NEW.completed_at
+        BEGIN
+            UPDATE analytics_daily_snapshots SET stale = 1
+            WHERE snapshot_date >= MIN(COALESCE(date(OLD.due_at), '9999-12-31'), COALESCE(date(OLD.completed_at), '9999-12-31'), COALESCE(date(NEW.due_at), '9999-12-31'), COALESCE(date(NEW.completed_at), '9999-12-31'));
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS trg_tasks_daily_aggregates_delete
+            AFTER DELETE ON tasks
+        BEGIN
+            UPDATE analytics_daily_snapshots SET stale = 1
+            WHERE snapshot_date >= MIN(COALESCE(date(OLD.due_at), '9999-12-31'), COALESCE(date(OLD.completed_at), '9999-12-31'));
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS trg_planning_time_blocks_daily_aggregates_insert
+            AFTER INSERT ON planning_time_blocks
+        BEGIN
+            UPDATE analytics_daily_snapshots SET stale = 1
+            WHERE snapshot_date = date(COALESCE(NEW.actual_start_at, NEW.start_at));
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS trg_planning_time_blocks_daily_aggregates_update
+            AFTER UPDATE ON planning_time_blocks
+            WHEN OLD.start_at IS NOT NEW.start_at OR OLD.end_at IS NOT NEW.end_at
+                OR OLD.actual_start_at IS NOT NEW.actual_start_at
+                OR OLD.actual_end_at IS NOT NEW.actual_end_at
+        BEGIN
+            UPDATE analytics_daily_snapshots SET stale = 1
+            WHERE snapshot_date IN (date(COALESCE(OLD.actual_start_at, OLD.start_at)), date(COALESCE(NEW.actual_start_at, NEW.start_at)));
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS trg_planning_time_blocks_daily_aggregates_delete
+            AFTER DELETE ON planning_time_blocks
+        BEGIN
+            UPDATE analytics_daily_snapshots SET stale = 1
+            WHERE snapshot_date = date(COALESCE(OLD.actual_start_at, OLD.start_at));
+        END;
+        "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_to_v27(conn: &Connection) -> AppResult<()> {
+    ensure_column(conn, "workload_forecasts", "low_hours", "REAL")?;
+    ensure_column(conn, "workload_forecasts", "high_hours", "REAL")?;
+    ensure_column(
+        conn,
+        "workload_forecasts",
+        "buckets",
+        "TEXT NOT NULL DEFAULT '[]'",
+    )?;
+
+    Ok(())
+}
+
+fn migrate_to_v28(conn: &Connection) -> AppResult<()> {
+    ensure_column(conn, "goals", "checkin_cadence", "TEXT")?;
+    ensure_column(conn, "goals", "next_checkin_at", "TEXT")?;
+    ensure_column(conn, "goals", "checkin_reminded_at", "TEXT")?;
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS goal_checkins (
+            id TEXT PRIMARY KEY,
+            goal_id TEXT NOT NULL,
+            note TEXT,
+            progress REAL NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (goal_id) REFERENCES goals(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_goal_checkins_goal_id_created_at
+            ON goal_checkins(goal_id, created_at);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_to_v29(conn: &Connection) -> AppResult<()> {
+    ensure_column(conn, "schedule_preferences", "name", "TEXT")?;
+
+    Ok(())
+}
+
+fn migrate_to_v30(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        r#"
+        -- Health of long-running background workers, updated when one panics
+        CREATE TABLE IF NOT EXISTS job_runs (
+            name TEXT PRIMARY KEY,
+            status TEXT NOT NULL DEFAULT 'running',
+            restarts INTEGER NOT NULL DEFAULT 0,
+            last_panic TEXT,
+            last_panic_at TEXT,
+            started_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_to_v31(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        r#"
+        -- Runtime toggles for experimental subsystems; missing rows use the
+        -- defaults compiled into the app
+        CREATE TABLE IF NOT EXISTS feature_flags (
+            name TEXT PRIMARY KEY,
+            enabled INTEGER NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_to_v32(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        r#"
+        -- Data upgrade steps that run once after an app update
+        CREATE TABLE IF NOT EXISTS app_upgrade_steps (
+            id TEXT PRIMARY KEY,
+            app_version TEXT NOT NULL,
+            status TEXT NOT NULL,
+            error TEXT,
+            attempted_at TEXT NOT NULL,
+            completed_at TEXT
+        );
+        "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_to_v33(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        r#"
+        -- Idle time detected during focus sessions; confirmed rows are
+        -- subtracted from the focus minutes of the day the session started
+        CREATE TABLE IF NOT EXISTS focus_idle_adjustments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_started_at TEXT NOT NULL,
+            session_ended_at TEXT NOT NULL,
+            task_id TEXT,
+            idle_minutes INTEGER NOT NULL,
+            periods TEXT NOT NULL DEFAULT '[]',
+            status TEXT NOT NULL DEFAULT 'pending',
+            created_at TEXT NOT NULL,
+            resolved_at TEXT
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_focus_idle_adjustments_status
+            ON focus_idle_adjustments(status, session_started_at);
+
+        CREATE TRIGGER IF NOT EXISTS trg_focus_idle_adjustments_daily_aggregates_update
+            AFTER UPDATE ON focus_idle_adjustments
+            WHEN OLD.status IS NOT NEW.status
+        BEGIN
+            UPDATE analytics_daily_snapshots SET stale = 1
+            WHERE snapshot_date = date(NEW.session_started_at);
+        END;
+        "#,
+    )?;
+
+    Ok(())
+}
+
+fn migrate_to_v34(conn: &Connection) -> AppResult<()> {
+    ensure_column(conn, "tasks", "delegated_to", "TEXT")?;
+    ensure_column(conn, "tasks", "waiting_on", "TEXT")?;
+    ensure_column(conn, "tasks", "waiting_since", "TEXT")?;
+    ensure_column(conn, "tasks", "follow_up_task_id", "TEXT")?;
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_tasks_waiting ON tasks(status, waiting_since);",
+    )?;
+
+    Ok(())
+}
+
+fn migrate_to_v35(conn: &Connection) -> AppResult<()> {
+    ensure_column(conn, "planning_time_blocks", "prep_note", "TEXT")?;
+
+    Ok(())
+}
+
+fn migrate_to_v36(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        r#"
+        -- The single connected CalDAV account; the password is encrypted
+        -- with the keychain-backed vault
+        CREATE TABLE IF NOT EXISTS caldav_account (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            server_url TEXT NOT NULL,
+            username TEXT NOT NULL,
+            password TEXT NOT NULL,
+            task_calendar_url TEXT,
+            event_calendar_url TEXT,
+            conflict_policy TEXT NOT NULL DEFAULT 'newest_wins',
+            last_sync_at TEXT,
+            last_error TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        -- Local task or time block mirrored to a calendar object, with the
+        -- state both sides had at the last sync
+        CREATE TABLE IF NOT EXISTS caldav_items (
+            kind TEXT NOT NULL,
+            local_id TEXT NOT NULL,
+            href TEXT NOT NULL UNIQUE,
+            etag TEXT,
+            local_hash TEXT NOT NULL,
+            synced_at TEXT NOT NULL,
+            PRIMARY KEY (kind, local_id)
+        );
+        "#,
+    )?;
+
+    Ok(())
+}
@@ -1,25 +1,58 @@
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
 use rusqlite::Connection;
+use schemars::JsonSchema;
+use serde::Serialize;
 use tracing::{debug, info};
 
 use crate::error::AppResult;
+use crate::utils::runtime_profile::RuntimeProfile;
 
+use self::contention::{DbAccess, DbContentionStats, DB_CONTENTION_METRICS};
+use self::encryption::{DatabaseKey, EncryptionStatus};
+use self::maintenance::{CheckpointMode, CheckpointOutcome, DbFileSizes};
+use self::pool::{ConnectionKind, ConnectionPool, PooledConnection};
+
+pub mod contention;
+pub mod encryption;
+pub mod maintenance;
 pub mod migrations;
+pub mod pool;
 
 pub mod repositories;
 
 const SCHEMA_SQL: &str = include_str!("schema.sql");
 
+/// Prepared statements kept per connection. Repositories use
+/// `prepare_cached` on hot paths, so this needs to cover the distinct SQL a
+/// single connection runs in one request.
+pub const STATEMENT_CACHE_CAPACITY: usize = 64;
+
+/// How long enabling encryption waits for writers in use to finish.
+const WRITER_QUIESCE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// WAL pages written before a checkpoint when writes are batched.
+const BATCHED_WAL_AUTOCHECKPOINT: i64 = 4000;
+
 #[derive(Clone, Debug)]
 pub struct DbPool {
     path: PathBuf,
+    /// Shared by all clones so enabling encryption applies to every service.
+    key: Arc<RwLock<Option<DatabaseKey>>>,
+    /// Relax fsync on commit so writes reach disk in batches at checkpoints.
+    batch_writes: bool,
+    connections: ConnectionPool,
 }
 
 impl DbPool {
     pub fn new<P: Into<PathBuf>>(path: P) -> AppResult<Self> {
+        Self::new_with_profile(path, RuntimeProfile::Desktop)
+    }
+
+    pub fn new_with_profile<P: Into<PathBuf>>(path: P, profile: RuntimeProfile) -> AppResult<Self> {
         let path = path.into();
         info!(db_path = %path.display(), "initializing database pool");
         if let Some(parent) = path.parent() {
@@ -28,19 +61,69 @@ impl DbPool {
             }
         }
 
-        let pool = Self { path };
-        {
-            pool.get_connection()?;
-        }
+        let key = encryption::load_key(&path)?;
+        let pool = Self {
+            path,
+            key: Arc::new(RwLock::new(key)),
+            batch_writes: profile.batches_writes(),
+            connections: ConnectionPool::default(),
+        };
+        pool.initialize()?;
 
         Ok(pool)
     }
 
-    pub fn get_connection(&self) -> AppResult<Connection> {
+    /// Creates the schema and runs pending migrations, once per pool rather
+    /// than on every connection.
+    fn initialize(&self) -> AppResult<()> {
+        let generation = self.connections.generation();
+        let conn = contention::retry_busy(DbAccess::InteractiveWrite, || {
+            let conn = self.open_connection(DbAccess::InteractiveWrite)?;
+            conn.execute_batch(SCHEMA_SQL)?;
+            migrations::run(&conn)?;
+            Ok(conn)
+        })?;
+        drop(
+            self.connections
+                .wrap(conn, ConnectionKind::Write, generation),
+        );
+        info!(db_path = %self.path.display(), "database schema ready");
+        Ok(())
+    }
+
+    /// A pooled connection for interactive commands. It goes back to the
+    /// pool when dropped.
+    pub fn get_connection(&self) -> AppResult<PooledConnection> {
+        contention::retry_busy(DbAccess::InteractiveWrite, || {
+            self.writer(DbAccess::InteractiveWrite)
+        })
+    }
+
+    fn writer(&self, access: DbAccess) -> AppResult<PooledConnection> {
+        let pass = self.connections.enter_write();
+        if let Some(conn) = self.connections.checkout(ConnectionKind::Write) {
+            conn.busy_timeout(access.policy().busy_timeout)?;
+            return Ok(conn.holding(pass));
+        }
+        let generation = self.connections.generation();
+        let conn = self.open_connection(access)?;
+        Ok(self
+            .connections
+            .wrap(conn, ConnectionKind::Write, generation)
+            .holding(pass))
+    }
+
+    fn open_connection(&self, access: DbAccess) -> AppResult<Connection> {
         let mut conn = Connection::open(&self.path)?;
-        configure_connection(&mut conn)?;
-        conn.execute_batch(SCHEMA_SQL)?;
-        migrations::run(&conn)?;
+        if let Some(key) = self
+            .key
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .as_ref()
+        {
+            encryption::apply_key(&conn, key)?;
+        }
+        configure_connection(&mut conn, access.policy().busy_timeout, self.batch_writes)?;
         debug!(db_path = %self.path.display(), "database connection ready");
         Ok(conn)
     }
@@ -49,18 +132,139 @@ impl DbPool {
     where
         F: FnOnce(&Connection) -> AppResult<T>,
     {
+        maintenance::touch();
         let conn = self.get_connection()?;
-        callback(&conn)
+        contention::observe(DbAccess::InteractiveWrite, callback(&conn))
+    }
+
+    /// Connection for writes made by background jobs. A busy database is
+    /// retried with backoff instead of failing the job, so `callback` may run
+    /// more than once and must leave no partial writes behind when it fails.
+    pub fn with_background_write<F, T>(&self, mut callback: F) -> AppResult<T>
+    where
+        F: FnMut(&Connection) -> AppResult<T>,
+    {
+        contention::retry_busy(DbAccess::BackgroundWrite, || {
+            let conn = self.writer(DbAccess::BackgroundWrite)?;
+            callback(&conn)
+        })
+    }
+
+    /// Connection for long read-only work such as analytics snapshots and
+    /// workload forecasts. It sets `query_only`, so under WAL it never
+    /// competes with interactive commands for the write lock, and gives up
+    /// quickly when the database is busy rather than stall the UI. Read
+    /// connections are pooled apart from writers.
+    pub fn get_read_connection(&self) -> AppResult<PooledConnection> {
+        if let Some(conn) = self.connections.checkout(ConnectionKind::Read) {
+            return Ok(conn);
+        }
+        let generation = self.connections.generation();
+        let conn = Connection::open(&self.path)?;
+        if let Some(key) = self
+            .key
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .as_ref()
+        {
+            encryption::apply_key(&conn, key)?;
+        }
+        conn.busy_timeout(DbAccess::InteractiveRead.policy().busy_timeout)?;
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+        conn.pragma_update(None, "query_only", 1)?;
+        debug!(db_path = %self.path.display(), "read connection ready");
+        Ok(self
+            .connections
+            .wrap(conn, ConnectionKind::Read, generation))
+    }
+
+    pub fn with_read_connection<F, T>(&self, callback: F) -> AppResult<T>
+    where
+        F: FnOnce(&Connection) -> AppResult<T>,
+    {
+        maintenance::touch();
+        let conn = self.get_read_connection()?;
+        contention::observe(DbAccess::InteractiveRead, callback(&conn))
     }
 
     pub fn path(&self) -> &Path {
         &self.path
     }
+
+    pub fn encryption_status(&self) -> EncryptionStatus {
+        EncryptionStatus {
+            supported: encryption::ENCRYPTION_SUPPORTED,
+            enabled: encryption::is_encrypted(&self.path),
+        }
+    }
+
+    pub fn file_sizes(&self) -> DbFileSizes {
+        DbFileSizes::read(&self.path)
+    }
+
+    /// Copies the WAL back into the database file. Runs as a background
+    /// write, so a busy database is retried rather than reported.
+    pub fn checkpoint(&self, mode: CheckpointMode) -> AppResult<CheckpointOutcome> {
+        self.with_background_write(|conn| maintenance::run_checkpoint(conn, mode))
+    }
+
+    pub fn diagnostics(&self) -> DatabaseDiagnostics {
+        DatabaseDiagnostics {
+            contention: DB_CONTENTION_METRICS.snapshot(),
+            storage: self.file_sizes(),
+            wal_truncate_threshold_bytes: maintenance::WAL_TRUNCATE_THRESHOLD_BYTES,
+            last_checkpoint: maintenance::last_checkpoint(),
+        }
+    }
+
+    /// Migrates the plaintext database to SQLCipher. Waits for the writers
+    /// in use to finish and holds back new ones, so no write lands in the
+    /// plaintext file after it was copied. Holds the key lock for the whole
+    /// migration so no connection opens the file halfway through, and closes
+    /// the pooled plaintext connections first.
+    pub fn enable_encryption(&self, passphrase: &str) -> AppResult<EncryptionStatus> {
+        let _quiesced = self.connections.quiesce_writers(WRITER_QUIESCE_TIMEOUT)?;
+        let mut key = self.key.write().unwrap_or_else(|err| err.into_inner());
+        self.connections.clear();
+        *key = Some(encryption::encrypt_database(&self.path, passphrase)?);
+        drop(key);
+        Ok(self.encryption_status())
+    }
 }
 
-fn configure_connection(conn: &mut Connection) -> AppResult<()> {
-    conn.busy_timeout(Duration::from_secs(5))?;
+/// Health of the database file and its connections during this session.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseDiagnostics {
+    /// Busy errors and retries per access class.
+    pub contention: Vec<DbContentionStats>,
+    /// Current size of the database, WAL and shared-memory files.
+    pub storage: DbFileSizes,
+    /// WAL size that triggers a truncate checkpoint.
+    pub wal_truncate_threshold_bytes: u64,
+    /// Latest checkpoint of this session, `None` until one has run.
+    pub last_checkpoint: Option<CheckpointOutcome>,
+}
+
+fn configure_connection(
+    conn: &mut Connection,
+    busy_timeout: Duration,
+    batch_writes: bool,
+) -> AppResult<()> {
+    conn.busy_timeout(busy_timeout)?;
+    conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
     conn.pragma_update(None, "foreign_keys", &1)?;
     conn.pragma_update(None, "journal_mode", &"WAL")?;
+    conn.pragma_update(
+        None,
+        "journal_size_limit",
+        maintenance::WAL_SIZE_LIMIT_BYTES,
+    )?;
+    if batch_writes {
+        // In WAL mode NORMAL still survives app crashes; only a power loss
+        // can drop the commits since the last checkpoint.
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        conn.pragma_update(None, "wal_autocheckpoint", BATCHED_WAL_AUTOCHECKPOINT)?;
+    }
     Ok(())
 }
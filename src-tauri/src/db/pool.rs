@@ -0,0 +1,338 @@
+//! Connections kept open between calls. Opening one costs a file open, the
+//! key setup on encrypted databases and the pragma setup, so a connection
+//! goes back to the pool when its [`PooledConnection`] drops instead of
+//! closing. Writers and `query_only` readers are pooled separately.
+
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+
+use rusqlite::Connection;
+use tracing::debug;
+
+use crate::db::STATEMENT_CACHE_CAPACITY;
+use crate::error::{AppError, AppResult};
+
+/// Idle connections kept per kind. More are opened under load and closed
+/// when handed back to a full pool.
+const MAX_IDLE_PER_KIND: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionKind {
+    Write,
+    Read,
+}
+
+#[derive(Debug, Default)]
+struct IdleConnections {
+    writers: Vec<Connection>,
+    readers: Vec<Connection>,
+    /// Bumped by [`ConnectionPool::clear`]; connections opened before are
+    /// closed when handed back.
+    generation: u64,
+}
+
+impl IdleConnections {
+    fn of(&mut self, kind: ConnectionKind) -> &mut Vec<Connection> {
+        match kind {
+            ConnectionKind::Write => &mut self.writers,
+            ConnectionKind::Read => &mut self.readers,
+        }
+    }
+}
+
+/// Writers currently handed out, and whether new ones are held back so the
+/// database file can be swapped underneath the pool.
+#[derive(Debug, Default)]
+struct WriteGate {
+    state: Mutex<WriteGateState>,
+    changed: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct WriteGateState {
+    active: usize,
+    closed: bool,
+}
+
+impl WriteGate {
+    fn lock(&self) -> MutexGuard<'_, WriteGateState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionPool {
+    idle: Arc<Mutex<IdleConnections>>,
+    gate: Arc<WriteGate>,
+}
+
+impl ConnectionPool {
+    /// An idle connection of `kind`, if one is available.
+    pub fn checkout(&self, kind: ConnectionKind) -> Option<PooledConnection> {
+        let mut idle = self.lock();
+        let generation = idle.generation;
+        let conn = idle.of(kind).pop()?;
+        Some(self.wrap(conn, kind, generation))
+    }
+
+    /// Admits one writer, waiting while writers are quiesced. Take it before
+    /// the connection and keep it with [`PooledConnection::holding`].
+    pub fn enter_write(&self) -> WritePass {
+        let mut state = self.gate.lock();
+        while state.closed {
+            state = self
+                .gate
+                .changed
+                .wait(state)
+                .unwrap_or_else(|err| err.into_inner());
+        }
+        state.active += 1;
+        WritePass {
+            gate: Arc::clone(&self.gate),
+        }
+    }
+
+    /// Holds back new writers and waits until the ones handed out are
+    /// returned. Writers keep being admitted again once the guard drops, or
+    /// right away when the wait times out.
+    pub fn quiesce_writers(&self, timeout: Duration) -> AppResult<WritersQuiesced> {
+        let gate = &self.gate;
+        let (mut state, _) = gate
+            .changed
+            .wait_timeout_while(gate.lock(), timeout, |state| state.closed)
+            .unwrap_or_else(|err| err.into_inner());
+        if state.closed {
+            return Err(AppError::database_busy("数据库正在进行维护，请稍后重试"));
+        }
+        state.closed = true;
+        let (mut state, _) = gate
+            .changed
+            .wait_timeout_while(state, timeout, |state| state.active > 0)
+            .unwrap_or_else(|err| err.into_inner());
+        if state.active > 0 {
+            state.closed = false;
+            gate.changed.notify_all();
+            return Err(AppError::database_busy("仍有写入未完成，请稍后重试"));
+        }
+        Ok(WritersQuiesced {
+            gate: Arc::clone(gate),
+        })
+    }
+
+    /// Current generation; read it before opening a connection to
+    /// [`ConnectionPool::wrap`].
+    pub fn generation(&self) -> u64 {
+        self.lock().generation
+    }
+
+    /// Hands a newly opened connection out through the pool, so it is kept
+    /// once dropped.
+    pub fn wrap(
+        &self,
+        conn: Connection,
+        kind: ConnectionKind,
+        generation: u64,
+    ) -> PooledConnection {
+        PooledConnection {
+            conn: Some(conn),
+            kind,
+            generation,
+            pool: self.clone(),
+            write_pass: None,
+        }
+    }
+
+    /// Closes the idle connections and keeps those still checked out from
+    /// coming back, e.g. before the database file is replaced.
+    pub fn clear(&self) -> usize {
+        let mut guard = self.lock();
+        let idle = &mut *guard;
+        idle.generation += 1;
+        let closed: Vec<Connection> = idle
+            .writers
+            .drain(..)
+            .chain(idle.readers.drain(..))
+            .collect();
+        drop(guard);
+        closed.len()
+    }
+
+    pub fn idle_count(&self, kind: ConnectionKind) -> usize {
+        self.lock().of(kind).len()
+    }
+
+    fn give_back(&self, conn: Connection, kind: ConnectionKind, generation: u64) {
+        if !conn.is_autocommit() {
+            debug!(target: "app::database", "closing connection left inside a transaction");
+            return;
+        }
+        let mut idle = self.lock();
+        if generation != idle.generation {
+            return;
+        }
+        let pooled = idle.of(kind);
+        if pooled.len() < MAX_IDLE_PER_KIND {
+            // A caller may have resized the statement cache; the next one
+            // gets the default again
+            conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+            pooled.push(conn);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, IdleConnections> {
+        self.idle.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// Admission of one writer through the pool's write gate, released on drop.
+#[derive(Debug)]
+pub struct WritePass {
+    gate: Arc<WriteGate>,
+}
+
+impl Drop for WritePass {
+    fn drop(&mut self) {
+        let mut state = self.gate.lock();
+        state.active = state.active.saturating_sub(1);
+        self.gate.changed.notify_all();
+    }
+}
+
+/// Keeps new writers waiting until dropped.
+#[derive(Debug)]
+pub struct WritersQuiesced {
+    gate: Arc<WriteGate>,
+}
+
+impl Drop for WritersQuiesced {
+    fn drop(&mut self) {
+        self.gate.lock().closed = false;
+        self.gate.changed.notify_all();
+    }
+}
+
+/// A connection borrowed from the pool; dereferences to [`Connection`].
+#[derive(Debug)]
+pub struct PooledConnection {
+    conn: Option<Connection>,
+    kind: ConnectionKind,
+    generation: u64,
+    pool: ConnectionPool,
+    /// Released after the connection went back to the pool.
+    write_pass: Option<WritePass>,
+}
+
+impl PooledConnection {
+    /// Keeps `pass` until this connection is returned.
+    pub fn holding(mut self, pass: WritePass) -> Self {
+        self.write_pass = Some(pass);
+        self
+    }
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn
+            .as_ref()
+            .expect("pooled connection already returned")
+    }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.conn
+            .as_mut()
+            .expect("pooled connection already returned")
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            self.pool.give_back(conn, self.kind, self.generation);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open() -> Connection {
+        Connection::open_in_memory().unwrap()
+    }
+
+    #[test]
+    fn connections_are_reused_until_cleared() {
+        let pool = ConnectionPool::default();
+        let conn = pool.wrap(open(), ConnectionKind::Write, pool.generation());
+        conn.execute_batch("CREATE TEMP TABLE marker (id INTEGER)")
+            .unwrap();
+        drop(conn);
+        assert_eq!(pool.idle_count(ConnectionKind::Write), 1);
+        assert!(pool.checkout(ConnectionKind::Read).is_none());
+
+        let conn = pool.checkout(ConnectionKind::Write).unwrap();
+        conn.execute("INSERT INTO marker VALUES (1)", []).unwrap();
+
+        let stale = pool.generation();
+        assert_eq!(pool.clear(), 0);
+        drop(conn);
+        drop(pool.wrap(open(), ConnectionKind::Write, stale));
+        assert_eq!(pool.idle_count(ConnectionKind::Write), 0);
+    }
+
+    #[test]
+    fn open_transactions_and_overflow_are_closed() {
+        let pool = ConnectionPool::default();
+        let mut conn = pool.wrap(open(), ConnectionKind::Write, pool.generation());
+        conn.execute_batch("BEGIN").unwrap();
+        drop(conn);
+        assert_eq!(pool.idle_count(ConnectionKind::Write), 0);
+
+        conn = pool.wrap(open(), ConnectionKind::Write, pool.generation());
+        conn.transaction().unwrap().commit().unwrap();
+        drop(conn);
+        assert_eq!(pool.idle_count(ConnectionKind::Write), 1);
+
+        let extra: Vec<_> = (0..MAX_IDLE_PER_KIND + 2)
+            .map(|_| pool.wrap(open(), ConnectionKind::Read, pool.generation()))
+            .collect();
+        drop(extra);
+        assert_eq!(pool.idle_count(ConnectionKind::Read), MAX_IDLE_PER_KIND);
+    }
+
+    #[test]
+    fn quiescing_waits_for_writers_and_holds_back_new_ones() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::thread;
+
+        let pool = ConnectionPool::default();
+        let pass = pool.enter_write();
+        assert!(pool.quiesce_writers(Duration::from_millis(20)).is_err());
+
+        let finishing = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            drop(pass);
+        });
+        let quiesced = pool.quiesce_writers(Duration::from_secs(5)).unwrap();
+        finishing.join().unwrap();
+
+        let admitted = Arc::new(AtomicBool::new(false));
+        let waiting = {
+            let (pool, admitted) = (pool.clone(), Arc::clone(&admitted));
+            thread::spawn(move || {
+                let _pass = pool.enter_write();
+                admitted.store(true, Ordering::SeqCst);
+            })
+        };
+        thread::sleep(Duration::from_millis(50));
+        assert!(!admitted.load(Ordering::SeqCst));
+        drop(quiesced);
+        waiting.join().unwrap();
+        assert!(admitted.load(Ordering::SeqCst));
+    }
+}
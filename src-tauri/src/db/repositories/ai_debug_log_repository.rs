@@ -0,0 +1,147 @@
+use std::convert::TryFrom;
+
+use rusqlite::{named_params, Connection, OptionalExtension, Row};
+
+use crate::error::AppResult;
+use crate::models::ai_types::AiDebugEntry;
+
+#[derive(Debug, Clone)]
+pub struct AiDebugLogRow {
+    pub correlation_id: String,
+    pub operation: String,
+    pub request: String,
+    pub response: Option<String>,
+    pub error: Option<String>,
+    pub latency_ms: Option<i64>,
+    pub created_at: String,
+}
+
+impl AiDebugLogRow {
+    pub fn from_record(record: &AiDebugEntry) -> AppResult<Self> {
+        Ok(Self {
+            correlation_id: record.correlation_id.clone(),
+            operation: record.operation.clone(),
+            request: serde_json::to_string(&record.request)?,
+            response: record
+                .response
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()?,
+            error: record.error.clone(),
+            latency_ms: record.latency_ms,
+            created_at: record.created_at.clone(),
+        })
+    }
+
+    pub fn into_record(self) -> AppResult<AiDebugEntry> {
+        Ok(AiDebugEntry {
+            correlation_id: self.correlation_id,
+            operation: self.operation,
+            request: serde_json::from_str(&self.request)?,
+            response: self
+                .response
+                .as_deref()
+                .map(serde_json::from_str)
+                .transpose()?,
+            error: self.error,
+            latency_ms: self.latency_ms,
+            created_at: self.created_at,
+        })
+    }
+}
+
+impl TryFrom<&Row<'_>> for AiDebugLogRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            correlation_id: row.get("correlation_id")?,
+            operation: row.get("operation")?,
+            request: row.get("request")?,
+            response: row.get("response")?,
+            error: row.get("error")?,
+            latency_ms: row.get("latency_ms")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+}
+
+pub struct AiDebugLogRepository;
+
+impl AiDebugLogRepository {
+    pub fn insert(conn: &Connection, row: &AiDebugLogRow) -> AppResult<()> {
+        conn.execute(
+            r#"
+                INSERT OR REPLACE INTO ai_debug_log (
+                    correlation_id,
+                    operation,
+                    request,
+                    response,
+                    error,
+                    latency_ms,
+                    created_at
+                ) VALUES (
+                    :correlation_id,
+                    :operation,
+                    :request,
+                    :response,
+                    :error,
+                    :latency_ms,
+                    :created_at
+                )
+            "#,
+            named_params! {
+                ":correlation_id": &row.correlation_id,
+                ":operation": &row.operation,
+                ":request": &row.request,
+                ":response": &row.response,
+                ":error": &row.error,
+                ":latency_ms": &row.latency_ms,
+                ":created_at": &row.created_at,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    pub fn find(conn: &Connection, correlation_id: &str) -> AppResult<Option<AiDebugLogRow>> {
+        let row = conn
+            .query_row(
+                r#"
+                    SELECT
+                        correlation_id,
+                        operation,
+                        request,
+                        response,
+                        error,
+                        latency_ms,
+                        created_at
+                    FROM ai_debug_log
+                    WHERE correlation_id = :correlation_id
+                "#,
+                named_params! {":correlation_id": correlation_id},
+                |row| AiDebugLogRow::try_from(row),
+            )
+            .optional()?;
+
+        Ok(row)
+    }
+
+    /// Keeps only the newest `keep` entries.
+    pub fn prune(conn: &Connection, keep: usize) -> AppResult<usize> {
+        let removed = conn.execute(
+            r#"
+                DELETE FROM ai_debug_log
+                WHERE correlation_id NOT IN (
+                    SELECT correlation_id
+                    FROM ai_debug_log
+                    ORDER BY created_at DESC
+                    LIMIT :keep
+                )
+            "#,
+            named_params! {":keep": keep as i64},
+        )?;
+
+        Ok(removed)
+    }
+}
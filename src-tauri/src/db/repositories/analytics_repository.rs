@@ -3,8 +3,8 @@ use std::convert::TryFrom;
 use chrono::NaiveDate;
 use rusqlite::{named_params, Connection, OptionalExtension, Row};
 
-use crate::error::AppResult;
-use crate::models::analytics::AnalyticsSnapshotRecord;
+use crate::error::{AppError, AppResult};
+use crate::models::analytics::{AnalyticsAnomaly, AnalyticsAnomalyKind, AnalyticsSnapshotRecord};
 
 #[derive(Debug, Clone)]
 pub struct AnalyticsSnapshotRow {
@@ -23,6 +23,7 @@ pub struct AnalyticsSnapshotRow {
     pub focus_consistency: f64,
     pub rest_balance: f64,
     pub capacity_risk: f64,
+    pub context_switches: i64,
     pub created_at: String,
 }
 
@@ -44,6 +45,7 @@ impl AnalyticsSnapshotRow {
             focus_consistency: record.focus_consistency,
             rest_balance: record.rest_balance,
             capacity_risk: record.capacity_risk,
+            context_switches: record.context_switches,
             created_at: record.created_at.clone(),
         }
     }
@@ -65,6 +67,7 @@ impl AnalyticsSnapshotRow {
             focus_consistency: self.focus_consistency,
             rest_balance: self.rest_balance,
             capacity_risk: self.capacity_risk,
+            context_switches: self.context_switches,
             created_at: self.created_at,
         }
     }
@@ -90,16 +93,101 @@ impl TryFrom<&Row<'_>> for AnalyticsSnapshotRow {
             focus_consistency: row.get("focus_consistency")?,
             rest_balance: row.get("rest_balance")?,
             capacity_risk: row.get("capacity_risk")?,
+            context_switches: row.get("context_switches")?,
             created_at: row.get("created_at")?,
         })
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct AnalyticsAnomalyRow {
+    pub id: String,
+    pub snapshot_date: String,
+    pub kind: String,
+    pub observed: f64,
+    pub baseline: f64,
+    pub detail: String,
+    pub created_at: String,
+}
+
+impl AnalyticsAnomalyRow {
+    pub fn from_record(record: &AnalyticsAnomaly) -> Self {
+        Self {
+            id: record.id.clone(),
+            snapshot_date: record.snapshot_date.clone(),
+            kind: record.kind.as_str().to_string(),
+            observed: record.observed,
+            baseline: record.baseline,
+            detail: record.detail.clone(),
+            created_at: record.created_at.clone(),
+        }
+    }
+
+    pub fn into_record(self) -> AppResult<AnalyticsAnomaly> {
+        let kind =
+            AnalyticsAnomalyKind::try_from(self.kind.as_str()).map_err(AppError::validation)?;
+        Ok(AnalyticsAnomaly {
+            id: self.id,
+            kind,
+            snapshot_date: self.snapshot_date,
+            observed: self.observed,
+            baseline: self.baseline,
+            detail: self.detail,
+            created_at: self.created_at,
+        })
+    }
+}
+
+impl TryFrom<&Row<'_>> for AnalyticsAnomalyRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.get("id")?,
+            snapshot_date: row.get("snapshot_date")?,
+            kind: row.get("kind")?,
+            observed: row.get("observed")?,
+            baseline: row.get("baseline")?,
+            detail: row.get("detail")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+}
+
+/// Whole-day counts the overview is built from. `stale` is set by triggers
+/// when a task or time block feeding the day changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalyticsDailyAggregateRow {
+    pub snapshot_date: String,
+    pub completed: i64,
+    pub due: i64,
+    pub focus_minutes: i64,
+    pub overdue: i64,
+    pub stale: bool,
+    pub updated_at: String,
+}
+
+impl TryFrom<&Row<'_>> for AnalyticsDailyAggregateRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            snapshot_date: row.get("snapshot_date")?,
+            completed: row.get("completed")?,
+            due: row.get("due")?,
+            focus_minutes: row.get("focus_minutes")?,
+            overdue: row.get("overdue")?,
+            stale: row.get::<_, i64>("stale")? != 0,
+            updated_at: row.get("updated_at")?,
+        })
+    }
+}
+
 pub struct AnalyticsRepository;
 
 impl AnalyticsRepository {
     pub fn upsert_snapshot(conn: &Connection, row: &AnalyticsSnapshotRow) -> AppResult<()> {
-        conn.execute(
+        conn.prepare_cached(
             r#"
                 INSERT INTO analytics_snapshots (
                     snapshot_date,
@@ -117,6 +205,7 @@ impl AnalyticsRepository {
                     focus_consistency,
                     rest_balance,
                     capacity_risk,
+                    context_switches,
                     created_at
                 ) VALUES (
                     :snapshot_date,
@@ -134,6 +223,7 @@ impl AnalyticsRepository {
                     :focus_consistency,
                     :rest_balance,
                     :capacity_risk,
+                    :context_switches,
                     :created_at
                 )
                 ON CONFLICT(snapshot_date) DO UPDATE SET
@@ -151,27 +241,29 @@ impl AnalyticsRepository {
                     focus_consistency = excluded.focus_consistency,
                     rest_balance = excluded.rest_balance,
                     capacity_risk = excluded.capacity_risk,
+                    context_switches = excluded.context_switches,
                     created_at = excluded.created_at
             "#,
-            named_params! {
-                ":snapshot_date": &row.snapshot_date,
-                ":total_tasks_completed": &row.total_tasks_completed,
-                ":completion_rate": &row.completion_rate,
-                ":overdue_tasks": &row.overdue_tasks,
-                ":total_focus_minutes": &row.total_focus_minutes,
-                ":productivity_score": &row.productivity_score,
-                ":efficiency_rating": &row.efficiency_rating,
-                ":time_spent_work": &row.time_spent_work,
-                ":time_spent_study": &row.time_spent_study,
-                ":time_spent_life": &row.time_spent_life,
-                ":time_spent_other": &row.time_spent_other,
-                ":on_time_ratio": &row.on_time_ratio,
-                ":focus_consistency": &row.focus_consistency,
-                ":rest_balance": &row.rest_balance,
-                ":capacity_risk": &row.capacity_risk,
-                ":created_at": &row.created_at,
-            },
-        )?;
+        )?
+        .execute(named_params! {
+            ":snapshot_date": &row.snapshot_date,
+            ":total_tasks_completed": &row.total_tasks_completed,
+            ":completion_rate": &row.completion_rate,
+            ":overdue_tasks": &row.overdue_tasks,
+            ":total_focus_minutes": &row.total_focus_minutes,
+            ":productivity_score": &row.productivity_score,
+            ":efficiency_rating": &row.efficiency_rating,
+            ":time_spent_work": &row.time_spent_work,
+            ":time_spent_study": &row.time_spent_study,
+            ":time_spent_life": &row.time_spent_life,
+            ":time_spent_other": &row.time_spent_other,
+            ":on_time_ratio": &row.on_time_ratio,
+            ":focus_consistency": &row.focus_consistency,
+            ":rest_balance": &row.rest_balance,
+            ":capacity_risk": &row.capacity_risk,
+            ":context_switches": &row.context_switches,
+            ":created_at": &row.created_at,
+        })?;
 
         Ok(())
     }
@@ -180,7 +272,7 @@ impl AnalyticsRepository {
         conn: &Connection,
         snapshot_date: &NaiveDate,
     ) -> AppResult<Option<AnalyticsSnapshotRow>> {
-        let mut stmt = conn.prepare(
+        let mut stmt = conn.prepare_cached(
             r#"
             SELECT
                 snapshot_date,
@@ -198,6 +290,7 @@ impl AnalyticsRepository {
                 focus_consistency,
                 rest_balance,
                 capacity_risk,
+                context_switches,
                 created_at
             FROM analytics_snapshots
             WHERE snapshot_date = ?1
@@ -235,6 +328,7 @@ impl AnalyticsRepository {
                 focus_consistency,
                 rest_balance,
                 capacity_risk,
+                context_switches,
                 created_at
             FROM analytics_snapshots
             ORDER BY snapshot_date DESC
@@ -249,11 +343,195 @@ impl AnalyticsRepository {
         Ok(rows)
     }
 
-    pub fn delete_before(conn: &Connection, cutoff: &NaiveDate) -> AppResult<usize> {
-        let deleted = conn.execute(
-            "DELETE FROM analytics_snapshots WHERE snapshot_date < ?1",
-            [cutoff.to_string()],
+    /// Re-flagging the same day replaces the earlier anomaly of that kind.
+    pub fn upsert_anomaly(conn: &Connection, row: &AnalyticsAnomalyRow) -> AppResult<()> {
+        conn.execute(
+            r#"
+                INSERT INTO analytics_anomalies (
+                    id,
+                    snapshot_date,
+                    kind,
+                    observed,
+                    baseline,
+                    detail,
+                    created_at
+                ) VALUES (
+                    :id,
+                    :snapshot_date,
+                    :kind,
+                    :observed,
+                    :baseline,
+                    :detail,
+                    :created_at
+                )
+                ON CONFLICT(id) DO UPDATE SET
+                    observed = excluded.observed,
+                    baseline = excluded.baseline,
+                    detail = excluded.detail,
+                    created_at = excluded.created_at
+            "#,
+            named_params! {
+                ":id": &row.id,
+                ":snapshot_date": &row.snapshot_date,
+                ":kind": &row.kind,
+                ":observed": &row.observed,
+                ":baseline": &row.baseline,
+                ":detail": &row.detail,
+                ":created_at": &row.created_at,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    /// Anomalies flagged for snapshot dates in `[start, end]`, newest first.
+    pub fn list_anomalies_between(
+        conn: &Connection,
+        start: &NaiveDate,
+        end: &NaiveDate,
+    ) -> AppResult<Vec<AnalyticsAnomalyRow>> {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, snapshot_date, kind, observed, baseline, detail, created_at
+            FROM analytics_anomalies
+            WHERE snapshot_date >= :start AND snapshot_date <= :end
+            ORDER BY snapshot_date DESC, kind ASC
+        "#,
+        )?;
+
+        let rows = stmt
+            .query_map(
+                named_params! {":start": start.to_string(), ":end": end.to_string()},
+                |row| AnalyticsAnomalyRow::try_from(row),
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    pub fn list_daily_aggregates(
+        conn: &Connection,
+        start: &NaiveDate,
+        end: &NaiveDate,
+    ) -> AppResult<Vec<AnalyticsDailyAggregateRow>> {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT snapshot_date, completed, due, focus_minutes, overdue, stale, updated_at
+            FROM analytics_daily_snapshots
+            WHERE snapshot_date >= :start AND snapshot_date <= :end
+            ORDER BY snapshot_date ASC
+        "#,
         )?;
-        Ok(deleted as usize)
+
+        let rows = stmt
+            .query_map(
+                named_params! {":start": start.to_string(), ":end": end.to_string()},
+                |row| AnalyticsDailyAggregateRow::try_from(row),
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// Recounts `date` from tasks and time blocks. Dates are taken in UTC,
+    /// focus minutes go to the day a block starts less the confirmed idle
+    /// minutes of focus sessions started that day, and overdue counts tasks
+    /// due by the end of the day and not completed by then.
+    pub fn compute_daily_aggregate(
+        conn: &Connection,
+        date: &NaiveDate,
+        now: &str,
+    ) -> AppResult<AnalyticsDailyAggregateRow> {
+        let row = conn
+            .prepare_cached(
+                r#"
+            SELECT
+                (SELECT COUNT(*) FROM tasks WHERE date(completed_at) = :date) AS completed,
+                (SELECT COUNT(*) FROM tasks WHERE date(due_at) = :date) AS due,
+                MAX((
+                    SELECT COALESCE(SUM(CAST(ROUND((
+                        julianday(COALESCE(actual_end_at, end_at))
+                        - julianday(COALESCE(actual_start_at, start_at))
+                    ) * 86400) AS INTEGER) / 60), 0)
+                    FROM planning_time_blocks
+                    WHERE date(COALESCE(actual_start_at, start_at)) = :date
+                      AND julianday(COALESCE(actual_end_at, end_at))
+                          > julianday(COALESCE(actual_start_at, start_at))
+                ) - (
+                    SELECT COALESCE(SUM(idle_minutes), 0)
+                    FROM focus_idle_adjustments
+                    WHERE status = 'confirmed' AND date(session_started_at) = :date
+                ), 0) AS focus_minutes,
+                (
+                    SELECT COUNT(*) FROM tasks
+                    WHERE julianday(due_at) <= julianday(:day_end)
+                      AND (completed_at IS NULL OR julianday(completed_at) > julianday(:day_end))
+                ) AS overdue
+            "#,
+            )?
+            .query_row(
+                named_params! {
+                    ":date": date.to_string(),
+                    ":day_end": format!("{date} 23:59:59"),
+                },
+                |row| {
+                    Ok(AnalyticsDailyAggregateRow {
+                        snapshot_date: date.to_string(),
+                        completed: row.get("completed")?,
+                        due: row.get("due")?,
+                        focus_minutes: row.get("focus_minutes")?,
+                        overdue: row.get("overdue")?,
+                        stale: false,
+                        updated_at: now.to_string(),
+                    })
+                },
+            )?;
+
+        Ok(row)
+    }
+
+    pub fn upsert_daily_aggregate(
+        conn: &Connection,
+        row: &AnalyticsDailyAggregateRow,
+    ) -> AppResult<()> {
+        conn.prepare_cached(
+            r#"
+                INSERT INTO analytics_daily_snapshots (
+                    snapshot_date,
+                    completed,
+                    due,
+                    focus_minutes,
+                    overdue,
+                    stale,
+                    updated_at
+                ) VALUES (
+                    :snapshot_date,
+                    :completed,
+                    :due,
+                    :focus_minutes,
+                    :overdue,
+                    :stale,
+                    :updated_at
+                )
+                ON CONFLICT(snapshot_date) DO UPDATE SET
+                    completed = excluded.completed,
+                    due = excluded.due,
+                    focus_minutes = excluded.focus_minutes,
+                    overdue = excluded.overdue,
+                    stale = excluded.stale,
+                    updated_at = excluded.updated_at
+            "#,
+        )?
+        .execute(named_params! {
+            ":snapshot_date": &row.snapshot_date,
+            ":completed": &row.completed,
+            ":due": &row.due,
+            ":focus_minutes": &row.focus_minutes,
+            ":overdue": &row.overdue,
+            ":stale": row.stale as i64,
+            ":updated_at": &row.updated_at,
+        })?;
+
+        Ok(())
     }
 }
@@ -0,0 +1,252 @@
+use std::convert::TryFrom;
+
+use rusqlite::{named_params, Connection, OptionalExtension, Row};
+
+use crate::error::AppResult;
+
+#[derive(Debug, Clone)]
+pub struct CalDavAccountRow {
+    pub server_url: String,
+    pub username: String,
+    /// Vault ciphertext, never the plain password.
+    pub password: String,
+    pub task_calendar_url: Option<String>,
+    pub event_calendar_url: Option<String>,
+    pub conflict_policy: String,
+    pub last_sync_at: Option<String>,
+    pub last_error: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl TryFrom<&Row<'_>> for CalDavAccountRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            server_url: row.get("server_url")?,
+            username: row.get("username")?,
+            password: row.get("password")?,
+            task_calendar_url: row.get("task_calendar_url")?,
+            event_calendar_url: row.get("event_calendar_url")?,
+            conflict_policy: row.get("conflict_policy")?,
+            last_sync_at: row.get("last_sync_at")?,
+            last_error: row.get("last_error")?,
+            created_at: row.get("created_at")?,
+            updated_at: row.get("updated_at")?,
+        })
+    }
+}
+
+/// A local item and the calendar object it is mirrored to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalDavItemRow {
+    pub kind: String,
+    pub local_id: String,
+    pub href: String,
+    /// `None` when the server did not return one after the last upload.
+    pub etag: Option<String>,
+    /// Hash of the local item as last synced.
+    pub local_hash: String,
+    pub synced_at: String,
+}
+
+impl TryFrom<&Row<'_>> for CalDavItemRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            kind: row.get("kind")?,
+            local_id: row.get("local_id")?,
+            href: row.get("href")?,
+            etag: row.get("etag")?,
+            local_hash: row.get("local_hash")?,
+            synced_at: row.get("synced_at")?,
+        })
+    }
+}
+
+pub struct CalDavRepository;
+
+impl CalDavRepository {
+    pub fn get_account(conn: &Connection) -> AppResult<Option<CalDavAccountRow>> {
+        let row = conn
+            .query_row(
+                r#"
+                SELECT
+                    server_url,
+                    username,
+                    password,
+                    task_calendar_url,
+                    event_calendar_url,
+                    conflict_policy,
+                    last_sync_at,
+                    last_error,
+                    created_at,
+                    updated_at
+                FROM caldav_account
+                WHERE id = 1
+            "#,
+                [],
+                |row| CalDavAccountRow::try_from(row),
+            )
+            .optional()?;
+
+        Ok(row)
+    }
+
+    /// Replaces the account. Mappings from a previous account are dropped
+    /// because their hrefs point at another server.
+    pub fn replace_account(conn: &Connection, row: &CalDavAccountRow) -> AppResult<()> {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM caldav_items", [])?;
+        tx.execute(
+            r#"
+                INSERT INTO caldav_account (
+                    id,
+                    server_url,
+                    username,
+                    password,
+                    task_calendar_url,
+                    event_calendar_url,
+                    conflict_policy,
+                    last_sync_at,
+                    last_error,
+                    created_at,
+                    updated_at
+                ) VALUES (
+                    1,
+                    :server_url,
+                    :username,
+                    :password,
+                    :task_calendar_url,
+                    :event_calendar_url,
+                    :conflict_policy,
+                    :last_sync_at,
+                    :last_error,
+                    :created_at,
+                    :updated_at
+                )
+                ON CONFLICT(id) DO UPDATE SET
+                    server_url = excluded.server_url,
+                    username = excluded.username,
+                    password = excluded.password,
+                    task_calendar_url = excluded.task_calendar_url,
+                    event_calendar_url = excluded.event_calendar_url,
+                    conflict_policy = excluded.conflict_policy,
+                    last_sync_at = excluded.last_sync_at,
+                    last_error = excluded.last_error,
+                    updated_at = excluded.updated_at
+            "#,
+            named_params! {
+                ":server_url": &row.server_url,
+                ":username": &row.username,
+                ":password": &row.password,
+                ":task_calendar_url": &row.task_calendar_url,
+                ":event_calendar_url": &row.event_calendar_url,
+                ":conflict_policy": &row.conflict_policy,
+                ":last_sync_at": &row.last_sync_at,
+                ":last_error": &row.last_error,
+                ":created_at": &row.created_at,
+                ":updated_at": &row.updated_at,
+            },
+        )?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    pub fn record_sync_result(
+        conn: &Connection,
+        synced_at: &str,
+        error: Option<&str>,
+    ) -> AppResult<()> {
+        conn.execute(
+            r#"
+                UPDATE caldav_account SET
+                    last_sync_at = :synced_at,
+                    last_error = :error,
+                    updated_at = :synced_at
+                WHERE id = 1
+            "#,
+            named_params! {
+                ":synced_at": synced_at,
+                ":error": error,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    pub fn list_items(conn: &Connection, kind: &str) -> AppResult<Vec<CalDavItemRow>> {
+        let mut stmt = conn.prepare_cached(
+            r#"
+            SELECT kind, local_id, href, etag, local_hash, synced_at
+            FROM caldav_items
+            WHERE kind = ?1
+            ORDER BY local_id ASC
+        "#,
+        )?;
+        let rows = stmt
+            .query_map([kind], |row| CalDavItemRow::try_from(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    pub fn count_items(conn: &Connection, kind: &str) -> AppResult<i64> {
+        let count = conn.query_row(
+            "SELECT COUNT(*) FROM caldav_items WHERE kind = ?1",
+            [kind],
+            |row| row.get(0),
+        )?;
+
+        Ok(count)
+    }
+
+    pub fn upsert_item(conn: &Connection, row: &CalDavItemRow) -> AppResult<()> {
+        conn.execute(
+            r#"
+                INSERT INTO caldav_items (
+                    kind,
+                    local_id,
+                    href,
+                    etag,
+                    local_hash,
+                    synced_at
+                ) VALUES (
+                    :kind,
+                    :local_id,
+                    :href,
+                    :etag,
+                    :local_hash,
+                    :synced_at
+                )
+                ON CONFLICT(kind, local_id) DO UPDATE SET
+                    href = excluded.href,
+                    etag = excluded.etag,
+                    local_hash = excluded.local_hash,
+                    synced_at = excluded.synced_at
+            "#,
+            named_params! {
+                ":kind": &row.kind,
+                ":local_id": &row.local_id,
+                ":href": &row.href,
+                ":etag": &row.etag,
+                ":local_hash": &row.local_hash,
+                ":synced_at": &row.synced_at,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    pub fn delete_item(conn: &Connection, kind: &str, local_id: &str) -> AppResult<()> {
+        conn.execute(
+            "DELETE FROM caldav_items WHERE kind = ?1 AND local_id = ?2",
+            [kind, local_id],
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+use std::convert::TryFrom;
+
+use rusqlite::{named_params, Connection, Row};
+
+use crate::error::AppResult;
+
+/// Latest revision of one entity in `entity_changes`. The rows are written
+/// by triggers, so there is no insert here.
+#[derive(Debug, Clone)]
+pub struct EntityChangeRow {
+    pub revision: i64,
+    pub entity: String,
+    pub entity_id: String,
+    pub deleted: bool,
+    pub changed_at: String,
+}
+
+impl TryFrom<&Row<'_>> for EntityChangeRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            revision: row.get("revision")?,
+            entity: row.get("entity")?,
+            entity_id: row.get("entity_id")?,
+            deleted: row.get::<_, i64>("deleted")? != 0,
+            changed_at: row.get("changed_at")?,
+        })
+    }
+}
+
+pub struct ChangeRepository;
+
+impl ChangeRepository {
+    /// Changes after `revision`, oldest first.
+    pub fn list_since(
+        conn: &Connection,
+        revision: i64,
+        limit: usize,
+    ) -> AppResult<Vec<EntityChangeRow>> {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT revision, entity, entity_id, deleted, changed_at
+            FROM entity_changes
+            WHERE revision > :revision
+            ORDER BY revision ASC
+            LIMIT :limit
+        "#,
+        )?;
+        let rows = stmt
+            .query_map(
+                named_params! {":revision": revision, ":limit": limit as i64},
+                |row| EntityChangeRow::try_from(row),
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    pub fn current_revision(conn: &Connection) -> AppResult<i64> {
+        let revision = conn.query_row(
+            "SELECT COALESCE(MAX(revision), 0) FROM entity_changes",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(revision)
+    }
+}
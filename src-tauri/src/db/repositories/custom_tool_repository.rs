@@ -0,0 +1,150 @@
+use std::convert::TryFrom;
+
+use rusqlite::{named_params, Connection, Row};
+
+use crate::error::{AppError, AppResult};
+use crate::models::custom_tool::{CustomToolDefinition, CustomToolRecord};
+
+#[derive(Debug, Clone)]
+pub struct CustomToolRow {
+    pub name: String,
+    pub description: String,
+    pub parameters: String,
+    pub target_tool: String,
+    pub fixed_arguments: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl CustomToolRow {
+    pub fn from_record(record: &CustomToolRecord) -> AppResult<Self> {
+        let definition = &record.definition;
+        Ok(Self {
+            name: definition.name.clone(),
+            description: definition.description.clone(),
+            parameters: serde_json::to_string(&definition.parameters)?,
+            target_tool: definition.target_tool.clone(),
+            fixed_arguments: definition
+                .fixed_arguments
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()?,
+            created_at: record.created_at.clone(),
+            updated_at: record.updated_at.clone(),
+        })
+    }
+
+    pub fn into_record(self) -> AppResult<CustomToolRecord> {
+        Ok(CustomToolRecord {
+            definition: CustomToolDefinition {
+                name: self.name,
+                description: self.description,
+                parameters: serde_json::from_str(&self.parameters)?,
+                target_tool: self.target_tool,
+                fixed_arguments: self
+                    .fixed_arguments
+                    .as_deref()
+                    .map(serde_json::from_str)
+                    .transpose()?,
+            },
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        })
+    }
+}
+
+impl TryFrom<&Row<'_>> for CustomToolRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            name: row.get("name")?,
+            description: row.get("description")?,
+            parameters: row.get("parameters")?,
+            target_tool: row.get("target_tool")?,
+            fixed_arguments: row.get("fixed_arguments")?,
+            created_at: row.get("created_at")?,
+            updated_at: row.get("updated_at")?,
+        })
+    }
+}
+
+pub struct CustomToolRepository;
+
+impl CustomToolRepository {
+    pub fn upsert(conn: &Connection, row: &CustomToolRow) -> AppResult<()> {
+        conn.execute(
+            r#"
+                INSERT INTO custom_tools (
+                    name,
+                    description,
+                    parameters,
+                    target_tool,
+                    fixed_arguments,
+                    created_at,
+                    updated_at
+                ) VALUES (
+                    :name,
+                    :description,
+                    :parameters,
+                    :target_tool,
+                    :fixed_arguments,
+                    :created_at,
+                    :updated_at
+                )
+                ON CONFLICT(name) DO UPDATE SET
+                    description = excluded.description,
+                    parameters = excluded.parameters,
+                    target_tool = excluded.target_tool,
+                    fixed_arguments = excluded.fixed_arguments,
+                    updated_at = excluded.updated_at
+            "#,
+            named_params! {
+                ":name": &row.name,
+                ":description": &row.description,
+                ":parameters": &row.parameters,
+                ":target_tool": &row.target_tool,
+                ":fixed_arguments": &row.fixed_arguments,
+                ":created_at": &row.created_at,
+                ":updated_at": &row.updated_at,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    pub fn delete(conn: &Connection, name: &str) -> AppResult<bool> {
+        let affected = conn.execute(
+            "DELETE FROM custom_tools WHERE name = :name",
+            named_params! {":name": name},
+        )?;
+        Ok(affected > 0)
+    }
+
+    pub fn list_all(conn: &Connection) -> AppResult<Vec<CustomToolRecord>> {
+        let mut stmt = conn.prepare(
+            r#"
+                SELECT
+                    name,
+                    description,
+                    parameters,
+                    target_tool,
+                    fixed_arguments,
+                    created_at,
+                    updated_at
+                FROM custom_tools
+                ORDER BY name ASC
+            "#,
+        )?;
+
+        let records = stmt
+            .query_map([], |row| CustomToolRow::try_from(row))?
+            .map(|row| {
+                row.map_err(AppError::from)
+                    .and_then(|row| row.into_record())
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(records)
+    }
+}
@@ -0,0 +1,198 @@
+use base64::{engine::general_purpose::STANDARD as Base64, Engine as _};
+use rusqlite::types::{Value, ValueRef};
+use rusqlite::{params_from_iter, Connection, OptionalExtension};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+use crate::error::AppResult;
+
+#[derive(Debug, Clone)]
+pub struct TableColumn {
+    pub name: String,
+    pub declared_type: String,
+    /// Position in the primary key, 0 when not part of it.
+    pub primary_key: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ForeignKey {
+    pub column: String,
+    pub parent_table: String,
+}
+
+/// A column value as JSON. Blobs are base64 encoded.
+pub fn json_value(value: ValueRef<'_>) -> JsonValue {
+    match value {
+        ValueRef::Null => JsonValue::Null,
+        ValueRef::Integer(value) => JsonValue::from(value),
+        ValueRef::Real(value) => JsonValue::from(value),
+        ValueRef::Text(value) => JsonValue::String(String::from_utf8_lossy(value).into_owned()),
+        ValueRef::Blob(value) => JsonValue::String(Base64.encode(value)),
+    }
+}
+
+/// Whole-table access for the full data export and erase. Table names come
+/// from `sqlite_master` or a fixed list, never from input.
+pub struct DataExportRepository;
+
+impl DataExportRepository {
+    /// Every table except SQLite internals, in name order.
+    pub fn list_tables(conn: &Connection) -> AppResult<Vec<String>> {
+        let mut stmt = conn.prepare(
+            r#"
+                SELECT name FROM sqlite_master
+                WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+                ORDER BY name
+            "#,
+        )?;
+        let tables = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(tables)
+    }
+
+    /// All rows of `table` as JSON objects keyed by column name. Blobs are
+    /// base64 encoded.
+    pub fn dump_table(
+        conn: &Connection,
+        table: &str,
+    ) -> AppResult<Vec<JsonMap<String, JsonValue>>> {
+        let mut stmt = conn.prepare(&format!("SELECT * FROM {table} ORDER BY rowid"))?;
+        let columns: Vec<String> = stmt
+            .column_names()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        let rows = stmt
+            .query_map([], |row| {
+                let mut object = JsonMap::with_capacity(columns.len());
+                for (index, column) in columns.iter().enumerate() {
+                    object.insert(column.clone(), json_value(row.get_ref(index)?));
+                }
+                Ok(object)
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    pub fn clear_table(conn: &Connection, table: &str) -> AppResult<usize> {
+        let deleted = conn.execute(&format!("DELETE FROM {table}"), [])?;
+        Ok(deleted)
+    }
+
+    pub fn table_columns(conn: &Connection, table: &str) -> AppResult<Vec<TableColumn>> {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+        let columns = stmt
+            .query_map([], |row| {
+                Ok(TableColumn {
+                    name: row.get("name")?,
+                    declared_type: row.get("type")?,
+                    primary_key: row.get("pk")?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(columns)
+    }
+
+    pub fn foreign_keys(conn: &Connection, table: &str) -> AppResult<Vec<ForeignKey>> {
+        let mut stmt = conn.prepare(&format!("PRAGMA foreign_key_list({table})"))?;
+        let keys = stmt
+            .query_map([], |row| {
+                Ok(ForeignKey {
+                    column: row.get("from")?,
+                    parent_table: row.get("table")?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(keys)
+    }
+
+    /// Rowid of the row whose primary key columns equal `values`.
+    pub fn find_rowid(
+        conn: &Connection,
+        table: &str,
+        key_columns: &[&str],
+        values: &[Value],
+    ) -> AppResult<Option<i64>> {
+        let filter = key_columns
+            .iter()
+            .map(|column| format!("{column} = ?"))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        let rowid = conn
+            .query_row(
+                &format!("SELECT rowid FROM {table} WHERE {filter}"),
+                params_from_iter(values),
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok(rowid)
+    }
+
+    /// Inserts one row and returns its rowid.
+    pub fn insert_row(
+        conn: &Connection,
+        table: &str,
+        columns: &[&str],
+        values: &[Value],
+    ) -> AppResult<i64> {
+        let placeholders = vec!["?"; columns.len()].join(", ");
+        conn.execute(
+            &format!(
+                "INSERT INTO {table} ({}) VALUES ({placeholders})",
+                columns.join(", ")
+            ),
+            params_from_iter(values),
+        )?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Updates the columns of the row at `rowid` in place, so rows that
+    /// reference it are left alone.
+    pub fn update_row(
+        conn: &Connection,
+        table: &str,
+        rowid: i64,
+        columns: &[&str],
+        values: &[Value],
+    ) -> AppResult<()> {
+        let assignments = columns
+            .iter()
+            .map(|column| format!("{column} = ?"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut params = values.to_vec();
+        params.push(Value::Integer(rowid));
+        conn.execute(
+            &format!("UPDATE {table} SET {assignments} WHERE rowid = ?"),
+            params_from_iter(params),
+        )?;
+
+        Ok(())
+    }
+
+    /// `(table, rowid)` of every row violating a foreign key.
+    pub fn foreign_key_violations(conn: &Connection) -> AppResult<Vec<(String, i64)>> {
+        let mut stmt = conn.prepare("PRAGMA foreign_key_check")?;
+        let violations = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Option<i64>>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(violations
+            .into_iter()
+            .filter_map(|(table, rowid)| Some((table, rowid?)))
+            .collect())
+    }
+
+    pub fn delete_rowid(conn: &Connection, table: &str, rowid: i64) -> AppResult<()> {
+        conn.execute(&format!("DELETE FROM {table} WHERE rowid = ?1"), [rowid])?;
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+use std::convert::TryFrom;
+
+use rusqlite::{named_params, Connection, OptionalExtension, Row};
+
+use crate::error::{AppError, AppResult};
+use crate::models::day_log::{DayLogRecord, RolloverDecision};
+
+#[derive(Debug, Clone)]
+pub struct DayLogRow {
+    pub log_date: String,
+    pub closed_at: String,
+    pub reflection: Option<String>,
+    pub completed_tasks: i64,
+    pub unfinished_blocks: i64,
+    pub decisions: String,
+    pub productivity_score: Option<f64>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl DayLogRow {
+    pub fn from_record(record: &DayLogRecord) -> AppResult<Self> {
+        Ok(Self {
+            log_date: record.log_date.clone(),
+            closed_at: record.closed_at.clone(),
+            reflection: record.reflection.clone(),
+            completed_tasks: record.completed_tasks,
+            unfinished_blocks: record.unfinished_blocks,
+            decisions: serde_json::to_string(&record.decisions)?,
+            productivity_score: record.productivity_score,
+            created_at: record.created_at.clone(),
+            updated_at: record.updated_at.clone(),
+        })
+    }
+
+    pub fn into_record(self) -> AppResult<DayLogRecord> {
+        let decisions: Vec<RolloverDecision> = serde_json::from_str(&self.decisions)?;
+
+        Ok(DayLogRecord {
+            log_date: self.log_date,
+            closed_at: self.closed_at,
+            reflection: self.reflection,
+            completed_tasks: self.completed_tasks,
+            unfinished_blocks: self.unfinished_blocks,
+            decisions,
+            productivity_score: self.productivity_score,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        })
+    }
+}
+
+impl TryFrom<&Row<'_>> for DayLogRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            log_date: row.get("log_date")?,
+            closed_at: row.get("closed_at")?,
+            reflection: row.get("reflection")?,
+            completed_tasks: row.get("completed_tasks")?,
+            unfinished_blocks: row.get("unfinished_blocks")?,
+            decisions: row.get("decisions")?,
+            productivity_score: row.get("productivity_score")?,
+            created_at: row.get("created_at")?,
+            updated_at: row.get("updated_at")?,
+        })
+    }
+}
+
+pub struct DayLogRepository;
+
+impl DayLogRepository {
+    pub fn upsert(conn: &Connection, row: &DayLogRow) -> AppResult<()> {
+        conn.execute(
+            r#"
+                INSERT INTO day_logs (
+                    log_date,
+                    closed_at,
+                    reflection,
+                    completed_tasks,
+                    unfinished_blocks,
+                    decisions,
+                    productivity_score,
+                    created_at,
+                    updated_at
+                ) VALUES (
+                    :log_date,
+                    :closed_at,
+                    :reflection,
+                    :completed_tasks,
+                    :unfinished_blocks,
+                    :decisions,
+                    :productivity_score,
+                    :created_at,
+                    :updated_at
+                )
+                ON CONFLICT(log_date) DO UPDATE SET
+                    closed_at = excluded.closed_at,
+                    reflection = excluded.reflection,
+                    completed_tasks = excluded.completed_tasks,
+                    unfinished_blocks = excluded.unfinished_blocks,
+                    decisions = excluded.decisions,
+                    productivity_score = excluded.productivity_score,
+                    updated_at = excluded.updated_at
+            "#,
+            named_params! {
+                ":log_date": &row.log_date,
+                ":closed_at": &row.closed_at,
+                ":reflection": &row.reflection,
+                ":completed_tasks": row.completed_tasks,
+                ":unfinished_blocks": row.unfinished_blocks,
+                ":decisions": &row.decisions,
+                ":productivity_score": row.productivity_score,
+                ":created_at": &row.created_at,
+                ":updated_at": &row.updated_at,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    pub fn find_by_date(conn: &Connection, log_date: &str) -> AppResult<Option<DayLogRecord>> {
+        let mut stmt = conn.prepare(
+            r#"
+                SELECT
+                    log_date,
+                    closed_at,
+                    reflection,
+                    completed_tasks,
+                    unfinished_blocks,
+                    decisions,
+                    productivity_score,
+                    created_at,
+                    updated_at
+                FROM day_logs
+                WHERE log_date = :log_date
+            "#,
+        )?;
+
+        let row = stmt
+            .query_row(named_params! {":log_date": log_date}, |row| {
+                DayLogRow::try_from(row)
+            })
+            .optional()?;
+
+        row.map(|row| row.into_record()).transpose()
+    }
+
+    pub fn list_recent(conn: &Connection, limit: usize) -> AppResult<Vec<DayLogRecord>> {
+        let mut stmt = conn.prepare(
+            r#"
+                SELECT
+                    log_date,
+                    closed_at,
+                    reflection,
+                    completed_tasks,
+                    unfinished_blocks,
+                    decisions,
+                    productivity_score,
+                    created_at,
+                    updated_at
+                FROM day_logs
+                ORDER BY log_date DESC
+                LIMIT :limit
+            "#,
+        )?;
+
+        let records = stmt
+            .query_map(named_params! {":limit": limit as i64}, |row| {
+                DayLogRow::try_from(row)
+            })?
+            .map(|row| {
+                row.map_err(AppError::from)
+                    .and_then(|row| row.into_record())
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(records)
+    }
+
+    /// Logs for `start..=end` (`YYYY-MM-DD`), oldest first.
+    pub fn list_between(conn: &Connection, start: &str, end: &str) -> AppResult<Vec<DayLogRecord>> {
+        let mut stmt = conn.prepare(
+            r#"
+                SELECT
+                    log_date,
+                    closed_at,
+                    reflection,
+                    completed_tasks,
+                    unfinished_blocks,
+                    decisions,
+                    productivity_score,
+                    created_at,
+                    updated_at
+                FROM day_logs
+                WHERE log_date >= :start AND log_date <= :end
+                ORDER BY log_date ASC
+            "#,
+        )?;
+
+        let records = stmt
+            .query_map(named_params! {":start": start, ":end": end}, |row| {
+                DayLogRow::try_from(row)
+            })?
+            .map(|row| {
+                row.map_err(AppError::from)
+                    .and_then(|row| row.into_record())
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(records)
+    }
+}
@@ -0,0 +1,70 @@
+use std::convert::TryFrom;
+
+use rusqlite::{named_params, Connection, OptionalExtension, Row};
+
+use crate::error::AppResult;
+
+#[derive(Debug, Clone)]
+pub struct FeatureFlagRow {
+    pub name: String,
+    pub enabled: bool,
+    pub updated_at: String,
+}
+
+impl TryFrom<&Row<'_>> for FeatureFlagRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            name: row.get("name")?,
+            enabled: row.get("enabled")?,
+            updated_at: row.get("updated_at")?,
+        })
+    }
+}
+
+pub struct FeatureFlagRepository;
+
+impl FeatureFlagRepository {
+    pub fn get(conn: &Connection, name: &str) -> AppResult<Option<FeatureFlagRow>> {
+        let row = conn
+            .query_row(
+                "SELECT name, enabled, updated_at FROM feature_flags WHERE name = ?1",
+                [name],
+                |row| FeatureFlagRow::try_from(row),
+            )
+            .optional()?;
+
+        Ok(row)
+    }
+
+    pub fn list(conn: &Connection) -> AppResult<Vec<FeatureFlagRow>> {
+        let mut stmt =
+            conn.prepare("SELECT name, enabled, updated_at FROM feature_flags ORDER BY name ASC")?;
+
+        let rows = stmt
+            .query_map([], |row| FeatureFlagRow::try_from(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    pub fn upsert(conn: &Connection, row: &FeatureFlagRow) -> AppResult<()> {
+        conn.execute(
+            r#"
+                INSERT INTO feature_flags (name, enabled, updated_at)
+                VALUES (:name, :enabled, :updated_at)
+                ON CONFLICT(name) DO UPDATE SET
+                    enabled = excluded.enabled,
+                    updated_at = excluded.updated_at
+            "#,
+            named_params! {
+                ":name": &row.name,
+                ":enabled": &row.enabled,
+                ":updated_at": &row.updated_at,
+            },
+        )?;
+
+        Ok(())
+    }
+}
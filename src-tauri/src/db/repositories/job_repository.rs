@@ -0,0 +1,333 @@
+use std::convert::TryFrom;
+
+use rusqlite::{named_params, Connection, OptionalExtension, Row};
+
+use crate::error::{AppError, AppResult};
+use crate::models::job::{BackgroundJobRecord, BackgroundJobStatus, JobRunRecord, JobRunStatus};
+
+#[derive(Debug, Clone)]
+pub struct BackgroundJobRow {
+    pub id: String,
+    pub kind: String,
+    pub payload: String,
+    pub status: String,
+    pub attempts: i64,
+    pub max_attempts: i64,
+    pub next_attempt_at: String,
+    pub last_error: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl BackgroundJobRow {
+    pub fn from_record(record: &BackgroundJobRecord) -> AppResult<Self> {
+        Ok(Self {
+            id: record.id.clone(),
+            kind: record.kind.clone(),
+            payload: serde_json::to_string(&record.payload)?,
+            status: record.status.as_str().to_string(),
+            attempts: record.attempts,
+            max_attempts: record.max_attempts,
+            next_attempt_at: record.next_attempt_at.clone(),
+            last_error: record.last_error.clone(),
+            created_at: record.created_at.clone(),
+            updated_at: record.updated_at.clone(),
+        })
+    }
+
+    pub fn into_record(self) -> AppResult<BackgroundJobRecord> {
+        let status =
+            BackgroundJobStatus::try_from(self.status.as_str()).map_err(AppError::validation)?;
+
+        Ok(BackgroundJobRecord {
+            id: self.id,
+            kind: self.kind,
+            payload: serde_json::from_str(&self.payload)?,
+            status,
+            attempts: self.attempts,
+            max_attempts: self.max_attempts,
+            next_attempt_at: self.next_attempt_at,
+            last_error: self.last_error,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        })
+    }
+}
+
+impl TryFrom<&Row<'_>> for BackgroundJobRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.get("id")?,
+            kind: row.get("kind")?,
+            payload: row.get("payload")?,
+            status: row.get("status")?,
+            attempts: row.get("attempts")?,
+            max_attempts: row.get("max_attempts")?,
+            next_attempt_at: row.get("next_attempt_at")?,
+            last_error: row.get("last_error")?,
+            created_at: row.get("created_at")?,
+            updated_at: row.get("updated_at")?,
+        })
+    }
+}
+
+const SELECT_COLUMNS: &str = r#"
+    SELECT
+        id,
+        kind,
+        payload,
+        status,
+        attempts,
+        max_attempts,
+        next_attempt_at,
+        last_error,
+        created_at,
+        updated_at
+    FROM background_jobs
+"#;
+
+pub struct JobRepository;
+
+impl JobRepository {
+    pub fn upsert(conn: &Connection, row: &BackgroundJobRow) -> AppResult<()> {
+        conn.execute(
+            r#"
+                INSERT INTO background_jobs (
+                    id,
+                    kind,
+                    payload,
+                    status,
+                    attempts,
+                    max_attempts,
+                    next_attempt_at,
+                    last_error,
+                    created_at,
+                    updated_at
+                ) VALUES (
+                    :id,
+                    :kind,
+                    :payload,
+                    :status,
+                    :attempts,
+                    :max_attempts,
+                    :next_attempt_at,
+                    :last_error,
+                    :created_at,
+                    :updated_at
+                )
+                ON CONFLICT(id) DO UPDATE SET
+                    status = excluded.status,
+                    attempts = excluded.attempts,
+                    next_attempt_at = excluded.next_attempt_at,
+                    last_error = excluded.last_error,
+                    updated_at = excluded.updated_at
+            "#,
+            named_params! {
+                ":id": &row.id,
+                ":kind": &row.kind,
+                ":payload": &row.payload,
+                ":status": &row.status,
+                ":attempts": &row.attempts,
+                ":max_attempts": &row.max_attempts,
+                ":next_attempt_at": &row.next_attempt_at,
+                ":last_error": &row.last_error,
+                ":created_at": &row.created_at,
+                ":updated_at": &row.updated_at,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    /// Pending job with the same kind and payload, used to avoid queueing the
+    /// same work twice.
+    pub fn find_pending(
+        conn: &Connection,
+        kind: &str,
+        payload: &str,
+    ) -> AppResult<Option<BackgroundJobRow>> {
+        let sql = format!(
+            "{SELECT_COLUMNS} WHERE status = 'pending' AND kind = :kind AND payload = :payload LIMIT 1"
+        );
+        let row = conn
+            .query_row(
+                &sql,
+                named_params! {":kind": kind, ":payload": payload},
+                |row| BackgroundJobRow::try_from(row),
+            )
+            .optional()?;
+
+        Ok(row)
+    }
+
+    pub fn list_due(
+        conn: &Connection,
+        now: &str,
+        limit: usize,
+    ) -> AppResult<Vec<BackgroundJobRow>> {
+        let sql = format!(
+            "{SELECT_COLUMNS} WHERE status = 'pending' AND next_attempt_at <= :now
+             ORDER BY next_attempt_at ASC LIMIT :limit"
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(named_params! {":now": now, ":limit": limit as i64}, |row| {
+                BackgroundJobRow::try_from(row)
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    pub fn list(conn: &Connection, status: Option<&str>) -> AppResult<Vec<BackgroundJobRow>> {
+        let sql = format!(
+            "{SELECT_COLUMNS} WHERE (:status IS NULL OR status = :status)
+             ORDER BY updated_at DESC"
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(named_params! {":status": status}, |row| {
+                BackgroundJobRow::try_from(row)
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct JobRunRow {
+    pub name: String,
+    pub status: String,
+    pub restarts: i64,
+    pub last_panic: Option<String>,
+    pub last_panic_at: Option<String>,
+    pub started_at: String,
+    pub updated_at: String,
+}
+
+impl JobRunRow {
+    pub fn from_record(record: &JobRunRecord) -> Self {
+        Self {
+            name: record.name.clone(),
+            status: record.status.as_str().to_string(),
+            restarts: record.restarts,
+            last_panic: record.last_panic.clone(),
+            last_panic_at: record.last_panic_at.clone(),
+            started_at: record.started_at.clone(),
+            updated_at: record.updated_at.clone(),
+        }
+    }
+
+    pub fn into_record(self) -> AppResult<JobRunRecord> {
+        let status = JobRunStatus::try_from(self.status.as_str()).map_err(AppError::validation)?;
+
+        Ok(JobRunRecord {
+            name: self.name,
+            status,
+            restarts: self.restarts,
+            last_panic: self.last_panic,
+            last_panic_at: self.last_panic_at,
+            started_at: self.started_at,
+            updated_at: self.updated_at,
+        })
+    }
+}
+
+impl TryFrom<&Row<'_>> for JobRunRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            name: row.get("name")?,
+            status: row.get("status")?,
+            restarts: row.get("restarts")?,
+            last_panic: row.get("last_panic")?,
+            last_panic_at: row.get("last_panic_at")?,
+            started_at: row.get("started_at")?,
+            updated_at: row.get("updated_at")?,
+        })
+    }
+}
+
+const SELECT_RUN_COLUMNS: &str = r#"
+    SELECT
+        name,
+        status,
+        restarts,
+        last_panic,
+        last_panic_at,
+        started_at,
+        updated_at
+    FROM job_runs
+"#;
+
+/// Health rows of the long-running background workers.
+pub struct JobRunRepository;
+
+impl JobRunRepository {
+    pub fn upsert(conn: &Connection, row: &JobRunRow) -> AppResult<()> {
+        conn.execute(
+            r#"
+                INSERT INTO job_runs (
+                    name,
+                    status,
+                    restarts,
+                    last_panic,
+                    last_panic_at,
+                    started_at,
+                    updated_at
+                ) VALUES (
+                    :name,
+                    :status,
+                    :restarts,
+                    :last_panic,
+                    :last_panic_at,
+                    :started_at,
+                    :updated_at
+                )
+                ON CONFLICT(name) DO UPDATE SET
+                    status = excluded.status,
+                    restarts = excluded.restarts,
+                    last_panic = excluded.last_panic,
+                    last_panic_at = excluded.last_panic_at,
+                    started_at = excluded.started_at,
+                    updated_at = excluded.updated_at
+            "#,
+            named_params! {
+                ":name": &row.name,
+                ":status": &row.status,
+                ":restarts": &row.restarts,
+                ":last_panic": &row.last_panic,
+                ":last_panic_at": &row.last_panic_at,
+                ":started_at": &row.started_at,
+                ":updated_at": &row.updated_at,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    pub fn find(conn: &Connection, name: &str) -> AppResult<Option<JobRunRow>> {
+        let sql = format!("{SELECT_RUN_COLUMNS} WHERE name = :name");
+        let row = conn
+            .query_row(&sql, named_params! {":name": name}, |row| {
+                JobRunRow::try_from(row)
+            })
+            .optional()?;
+
+        Ok(row)
+    }
+
+    pub fn list(conn: &Connection) -> AppResult<Vec<JobRunRow>> {
+        let sql = format!("{SELECT_RUN_COLUMNS} ORDER BY name ASC");
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map([], |row| JobRunRow::try_from(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+}
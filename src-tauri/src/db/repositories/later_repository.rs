@@ -0,0 +1,203 @@
+use std::convert::TryFrom;
+
+use rusqlite::{named_params, Connection, OptionalExtension, Row};
+
+use crate::error::{AppError, AppResult};
+use crate::models::later::{LaterItemKind, LaterItemRecord};
+
+#[derive(Debug, Clone)]
+pub struct LaterItemRow {
+    pub id: String,
+    pub title: String,
+    pub url: Option<String>,
+    pub kind: String,
+    pub estimated_minutes: i64,
+    pub notes: Option<String>,
+    pub status: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub completed_at: Option<String>,
+}
+
+impl LaterItemRow {
+    pub fn from_record(record: &LaterItemRecord) -> Self {
+        Self {
+            id: record.id.clone(),
+            title: record.title.clone(),
+            url: record.url.clone(),
+            kind: record.kind.as_str().to_string(),
+            estimated_minutes: record.estimated_minutes,
+            notes: record.notes.clone(),
+            status: record.status.clone(),
+            created_at: record.created_at.clone(),
+            updated_at: record.updated_at.clone(),
+            completed_at: record.completed_at.clone(),
+        }
+    }
+
+    pub fn into_record(self) -> AppResult<LaterItemRecord> {
+        let kind = LaterItemKind::try_from(self.kind.as_str()).map_err(AppError::validation)?;
+
+        Ok(LaterItemRecord {
+            id: self.id,
+            title: self.title,
+            url: self.url,
+            kind,
+            estimated_minutes: self.estimated_minutes,
+            notes: self.notes,
+            status: self.status,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            completed_at: self.completed_at,
+        })
+    }
+}
+
+impl TryFrom<&Row<'_>> for LaterItemRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.get("id")?,
+            title: row.get("title")?,
+            url: row.get("url")?,
+            kind: row.get("kind")?,
+            estimated_minutes: row.get("estimated_minutes")?,
+            notes: row.get("notes")?,
+            status: row.get("status")?,
+            created_at: row.get("created_at")?,
+            updated_at: row.get("updated_at")?,
+            completed_at: row.get("completed_at")?,
+        })
+    }
+}
+
+pub struct LaterRepository;
+
+impl LaterRepository {
+    pub fn insert(conn: &Connection, row: &LaterItemRow) -> AppResult<()> {
+        conn.execute(
+            r#"
+                INSERT INTO later_items (
+                    id,
+                    title,
+                    url,
+                    kind,
+                    estimated_minutes,
+                    notes,
+                    status,
+                    created_at,
+                    updated_at,
+                    completed_at
+                ) VALUES (
+                    :id,
+                    :title,
+                    :url,
+                    :kind,
+                    :estimated_minutes,
+                    :notes,
+                    :status,
+                    :created_at,
+                    :updated_at,
+                    :completed_at
+                )
+            "#,
+            named_params! {
+                ":id": &row.id,
+                ":title": &row.title,
+                ":url": &row.url,
+                ":kind": &row.kind,
+                ":estimated_minutes": row.estimated_minutes,
+                ":notes": &row.notes,
+                ":status": &row.status,
+                ":created_at": &row.created_at,
+                ":updated_at": &row.updated_at,
+                ":completed_at": &row.completed_at,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    pub fn mark_completed(conn: &Connection, id: &str, completed_at: &str) -> AppResult<()> {
+        let affected = conn.execute(
+            r#"
+                UPDATE later_items SET
+                    status = 'completed',
+                    completed_at = :completed_at,
+                    updated_at = :completed_at
+                WHERE id = :id
+            "#,
+            named_params! {
+                ":id": id,
+                ":completed_at": completed_at,
+            },
+        )?;
+
+        if affected == 0 {
+            return Err(AppError::not_found());
+        }
+
+        Ok(())
+    }
+
+    pub fn find_by_id(conn: &Connection, id: &str) -> AppResult<Option<LaterItemRecord>> {
+        let mut stmt = conn.prepare(
+            r#"
+                SELECT
+                    id,
+                    title,
+                    url,
+                    kind,
+                    estimated_minutes,
+                    notes,
+                    status,
+                    created_at,
+                    updated_at,
+                    completed_at
+                FROM later_items
+                WHERE id = :id
+            "#,
+        )?;
+
+        let row = stmt
+            .query_row(named_params! {":id": id}, |row| LaterItemRow::try_from(row))
+            .optional()?;
+
+        row.map(|row| row.into_record()).transpose()
+    }
+
+    /// Lists items oldest first so the queue is consumed in the order it was filled.
+    pub fn list(conn: &Connection, status: Option<&str>) -> AppResult<Vec<LaterItemRecord>> {
+        let mut stmt = conn.prepare(
+            r#"
+                SELECT
+                    id,
+                    title,
+                    url,
+                    kind,
+                    estimated_minutes,
+                    notes,
+                    status,
+                    created_at,
+                    updated_at,
+                    completed_at
+                FROM later_items
+                WHERE :status IS NULL OR status = :status
+                ORDER BY created_at ASC
+            "#,
+        )?;
+
+        let records = stmt
+            .query_map(named_params! {":status": status}, |row| {
+                LaterItemRow::try_from(row)
+            })?
+            .map(|row| {
+                row.map_err(AppError::from)
+                    .and_then(|row| row.into_record())
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(records)
+    }
+}
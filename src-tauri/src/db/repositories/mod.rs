@@ -1,11 +1,26 @@
+pub mod ai_debug_log_repository;
 pub mod ai_feedback_repository;
 pub mod ai_settings_repository;
 pub mod analytics_repository;
+pub mod caldav_repository;
+pub mod change_repository;
 pub mod community_export_repository;
+pub mod custom_tool_repository;
+pub mod data_export_repository;
+pub mod day_log_repository;
+pub mod feature_flag_repository;
+pub mod job_repository;
+pub mod later_repository;
 pub mod planning_repository;
 pub mod productivity_repository;
+pub mod project_repository;
+pub mod prompt_override_repository;
+pub mod reminder_repository;
+pub mod retention_repository;
 // pub mod recommendation_repository; // Removed - recommendation feature deleted
 pub mod settings_repository;
+pub mod suggestion_repository;
 pub mod task_repository;
+pub mod upgrade_repository;
 pub mod wellness_repository;
 pub mod workload_repository;
@@ -17,6 +17,7 @@ pub struct PlanningSessionRow {
     pub status: String,
     pub selected_option_id: Option<String>,
     pub personalization_snapshot: Option<String>,
+    pub project_id: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -31,6 +32,7 @@ impl PlanningSessionRow {
             status: record.status.clone(),
             selected_option_id: record.selected_option_id.clone(),
             personalization_snapshot: serialize_json(record.personalization_snapshot.as_ref())?,
+            project_id: record.project_id.clone(),
             created_at: record.created_at.clone(),
             updated_at: record.updated_at.clone(),
         })
@@ -45,6 +47,7 @@ impl PlanningSessionRow {
             status: self.status,
             selected_option_id: self.selected_option_id,
             personalization_snapshot: deserialize_json(self.personalization_snapshot)?,
+            project_id: self.project_id,
             created_at: self.created_at,
             updated_at: self.updated_at,
         })
@@ -63,6 +66,7 @@ impl TryFrom<&Row<'_>> for PlanningSessionRow {
             status: row.get("status")?,
             selected_option_id: row.get("selected_option_id")?,
             personalization_snapshot: row.get("personalization_snapshot")?,
+            project_id: row.get("project_id")?,
             created_at: row.get("created_at")?,
             updated_at: row.get("updated_at")?,
         })
@@ -144,6 +148,7 @@ pub struct PlanningTimeBlockRow {
     pub actual_start_at: Option<String>,
     pub actual_end_at: Option<String>,
     pub status: String,
+    pub prep_note: Option<String>,
 }
 
 impl PlanningTimeBlockRow {
@@ -161,6 +166,7 @@ impl PlanningTimeBlockRow {
             actual_start_at: record.actual_start_at.clone(),
             actual_end_at: record.actual_end_at.clone(),
             status: record.status.clone(),
+            prep_note: record.prep_note.clone(),
         })
     }
 
@@ -178,6 +184,7 @@ impl PlanningTimeBlockRow {
             actual_start_at: self.actual_start_at,
             actual_end_at: self.actual_end_at,
             status: self.status,
+            prep_note: self.prep_note,
         })
     }
 }
@@ -199,6 +206,7 @@ impl TryFrom<&Row<'_>> for PlanningTimeBlockRow {
             actual_start_at: row.get("actual_start_at")?,
             actual_end_at: row.get("actual_end_at")?,
             status: row.get("status")?,
+            prep_note: row.get("prep_note")?,
         })
     }
 }
@@ -240,6 +248,26 @@ impl TryFrom<&Row<'_>> for SchedulePreferencesRow {
     }
 }
 
+/// A preference profile without its data, for listing.
+#[derive(Debug, Clone)]
+pub struct PreferenceProfileRow {
+    pub id: String,
+    pub name: Option<String>,
+    pub updated_at: String,
+}
+
+impl TryFrom<&Row<'_>> for PreferenceProfileRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.get("id")?,
+            name: row.get("name")?,
+            updated_at: row.get("updated_at")?,
+        })
+    }
+}
+
 pub struct PlanningRepository;
 
 impl PlanningRepository {
@@ -254,6 +282,7 @@ impl PlanningRepository {
                     status,
                     selected_option_id,
                     personalization_snapshot,
+                    project_id,
                     created_at,
                     updated_at
                 ) VALUES (
@@ -264,6 +293,7 @@ impl PlanningRepository {
                     :status,
                     :selected_option_id,
                     :personalization_snapshot,
+                    :project_id,
                     :created_at,
                     :updated_at
                 )
@@ -276,6 +306,7 @@ impl PlanningRepository {
                 ":status": &row.status,
                 ":selected_option_id": &row.selected_option_id,
                 ":personalization_snapshot": &row.personalization_snapshot,
+                ":project_id": &row.project_id,
                 ":created_at": &row.created_at,
                 ":updated_at": &row.updated_at,
             },
@@ -294,6 +325,7 @@ impl PlanningRepository {
                     status = :status,
                     selected_option_id = :selected_option_id,
                     personalization_snapshot = :personalization_snapshot,
+                    project_id = :project_id,
                     updated_at = :updated_at
                 WHERE id = :id
             "#,
@@ -305,6 +337,7 @@ impl PlanningRepository {
                 ":status": &row.status,
                 ":selected_option_id": &row.selected_option_id,
                 ":personalization_snapshot": &row.personalization_snapshot,
+                ":project_id": &row.project_id,
                 ":updated_at": &row.updated_at,
             },
         )?;
@@ -338,6 +371,7 @@ impl PlanningRepository {
                 status,
                 selected_option_id,
                 personalization_snapshot,
+                project_id,
                 created_at,
                 updated_at
             FROM planning_sessions
@@ -352,9 +386,13 @@ impl PlanningRepository {
         Ok(row)
     }
 
-    pub fn list_recent_sessions(
+    /// Newest sessions first, optionally only those of one project and status.
+    pub fn list_sessions(
         conn: &Connection,
+        project_id: Option<&str>,
+        status: Option<&str>,
         limit: usize,
+        offset: usize,
     ) -> AppResult<Vec<PlanningSessionRow>> {
         let mut stmt = conn.prepare(
             r#"
@@ -366,21 +404,125 @@ impl PlanningRepository {
                 status,
                 selected_option_id,
                 personalization_snapshot,
+                project_id,
                 created_at,
                 updated_at
             FROM planning_sessions
+            WHERE (:project_id IS NULL OR project_id = :project_id)
+              AND (:status IS NULL OR status = :status)
             ORDER BY generated_at DESC
-            LIMIT ?1
+            LIMIT :limit OFFSET :offset
         "#,
         )?;
 
         let rows = stmt
-            .query_map([limit as i64], |row| PlanningSessionRow::try_from(row))?
+            .query_map(
+                named_params! {
+                    ":project_id": project_id,
+                    ":status": status,
+                    ":limit": limit as i64,
+                    ":offset": offset as i64,
+                },
+                |row| PlanningSessionRow::try_from(row),
+            )?
             .collect::<Result<Vec<_>, _>>()?;
 
         Ok(rows)
     }
 
+    pub fn count_sessions(
+        conn: &Connection,
+        project_id: Option<&str>,
+        status: Option<&str>,
+    ) -> AppResult<usize> {
+        let count: i64 = conn.query_row(
+            r#"
+            SELECT COUNT(*)
+            FROM planning_sessions
+            WHERE (:project_id IS NULL OR project_id = :project_id)
+              AND (:status IS NULL OR status = :status)
+        "#,
+            named_params! {":project_id": project_id, ":status": status},
+            |row| row.get(0),
+        )?;
+
+        Ok(count as usize)
+    }
+
+    /// Sessions generated within `[start, end]`, oldest first.
+    pub fn list_sessions_generated_between(
+        conn: &Connection,
+        start: &str,
+        end: &str,
+    ) -> AppResult<Vec<PlanningSessionRow>> {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT
+                id,
+                task_ids,
+                constraints,
+                generated_at,
+                status,
+                selected_option_id,
+                personalization_snapshot,
+                project_id,
+                created_at,
+                updated_at
+            FROM planning_sessions
+            WHERE generated_at >= ?1
+              AND generated_at <= ?2
+            ORDER BY generated_at ASC
+        "#,
+        )?;
+
+        let rows = stmt
+            .query_map([start, end], |row| PlanningSessionRow::try_from(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// Marks pending sessions generated before `cutoff` as expired and drops
+    /// their draft blocks and options. Returns how many sessions expired.
+    pub fn expire_pending_sessions(conn: &Connection, cutoff: &str, now: &str) -> AppResult<usize> {
+        conn.execute(
+            r#"
+            DELETE FROM planning_time_blocks
+            WHERE status = 'draft'
+              AND option_id IN (
+                  SELECT o.id
+                  FROM planning_options o
+                  JOIN planning_sessions s ON s.id = o.session_id
+                  WHERE s.status = 'pending' AND s.generated_at < :cutoff
+              )
+        "#,
+            named_params! {":cutoff": cutoff},
+        )?;
+        conn.execute(
+            r#"
+            DELETE FROM planning_options
+            WHERE session_id IN (
+                  SELECT id FROM planning_sessions
+                  WHERE status = 'pending' AND generated_at < :cutoff
+              )
+              AND NOT EXISTS (
+                  SELECT 1 FROM planning_time_blocks b WHERE b.option_id = planning_options.id
+              )
+        "#,
+            named_params! {":cutoff": cutoff},
+        )?;
+        let expired = conn.execute(
+            r#"
+            UPDATE planning_sessions
+            SET status = 'expired', selected_option_id = NULL, updated_at = :now
+            WHERE status = 'pending' AND generated_at < :cutoff
+        "#,
+            named_params! {":cutoff": cutoff, ":now": now},
+        )?;
+
+        Ok(expired)
+    }
+
     pub fn insert_option(conn: &Connection, row: &PlanningOptionRow) -> AppResult<()> {
         conn.execute(
             r#"
@@ -517,7 +659,7 @@ impl PlanningRepository {
     }
 
     pub fn insert_time_block(conn: &Connection, row: &PlanningTimeBlockRow) -> AppResult<()> {
-        conn.execute(
+        conn.prepare_cached(
             r#"
                 INSERT INTO planning_time_blocks (
                     id,
@@ -531,7 +673,8 @@ impl PlanningRepository {
                     applied_at,
                     actual_start_at,
                     actual_end_at,
-                    status
+                    status,
+                    prep_note
                 ) VALUES (
                     :id,
                     :option_id,
@@ -544,31 +687,34 @@ impl PlanningRepository {
                     :applied_at,
                     :actual_start_at,
                     :actual_end_at,
-                    :status
+                    :status,
+                    :prep_note
                 )
             "#,
-            named_params! {
-                ":id": &row.id,
-                ":option_id": &row.option_id,
-                ":task_id": &row.task_id,
-                ":start_at": &row.start_at,
-                ":end_at": &row.end_at,
-                ":flexibility": &row.flexibility,
-                ":confidence": &row.confidence,
-                ":conflict_flags": &row.conflict_flags,
-                ":applied_at": &row.applied_at,
-                ":actual_start_at": &row.actual_start_at,
-                ":actual_end_at": &row.actual_end_at,
-                ":status": &row.status,
-            },
-        )?;
+        )?
+        .execute(named_params! {
+            ":id": &row.id,
+            ":option_id": &row.option_id,
+            ":task_id": &row.task_id,
+            ":start_at": &row.start_at,
+            ":end_at": &row.end_at,
+            ":flexibility": &row.flexibility,
+            ":confidence": &row.confidence,
+            ":conflict_flags": &row.conflict_flags,
+            ":applied_at": &row.applied_at,
+            ":actual_start_at": &row.actual_start_at,
+            ":actual_end_at": &row.actual_end_at,
+            ":status": &row.status,
+            ":prep_note": &row.prep_note,
+        })?;
 
         Ok(())
     }
 
     pub fn update_time_block(conn: &Connection, row: &PlanningTimeBlockRow) -> AppResult<()> {
-        let affected = conn.execute(
-            r#"
+        let affected = conn
+            .prepare_cached(
+                r#"
                 UPDATE planning_time_blocks SET
                     option_id = :option_id,
                     task_id = :task_id,
@@ -580,10 +726,12 @@ impl PlanningRepository {
                     applied_at = :applied_at,
                     actual_start_at = :actual_start_at,
                     actual_end_at = :actual_end_at,
-                    status = :status
+                    status = :status,
+                    prep_note = :prep_note
                 WHERE id = :id
             "#,
-            named_params! {
+            )?
+            .execute(named_params! {
                 ":id": &row.id,
                 ":option_id": &row.option_id,
                 ":task_id": &row.task_id,
@@ -596,8 +744,8 @@ impl PlanningRepository {
                 ":actual_start_at": &row.actual_start_at,
                 ":actual_end_at": &row.actual_end_at,
                 ":status": &row.status,
-            },
-        )?;
+                ":prep_note": &row.prep_note,
+            })?;
 
         if affected == 0 {
             return Err(AppError::not_found());
@@ -618,7 +766,7 @@ impl PlanningRepository {
         conn: &Connection,
         option_id: &str,
     ) -> AppResult<Vec<PlanningTimeBlockRow>> {
-        let mut stmt = conn.prepare(
+        let mut stmt = conn.prepare_cached(
             r#"
             SELECT
                 id,
@@ -632,7 +780,8 @@ impl PlanningRepository {
                 applied_at,
                 actual_start_at,
                 actual_end_at,
-                status
+                status,
+                prep_note
             FROM planning_time_blocks
             WHERE option_id = ?1
             ORDER BY start_at ASC
@@ -646,11 +795,39 @@ impl PlanningRepository {
         Ok(rows)
     }
 
+    pub fn find_time_block(conn: &Connection, id: &str) -> AppResult<Option<PlanningTimeBlockRow>> {
+        let row = conn
+            .prepare_cached(
+                r#"
+                SELECT
+                    id,
+                    option_id,
+                    task_id,
+                    start_at,
+                    end_at,
+                    flexibility,
+                    confidence,
+                    conflict_flags,
+                    applied_at,
+                    actual_start_at,
+                    actual_end_at,
+                    status,
+                    prep_note
+                FROM planning_time_blocks
+                WHERE id = ?1
+            "#,
+            )?
+            .query_row([id], |row| PlanningTimeBlockRow::try_from(row))
+            .optional()?;
+
+        Ok(row)
+    }
+
     pub fn list_time_blocks_for_task(
         conn: &Connection,
         task_id: &str,
     ) -> AppResult<Vec<PlanningTimeBlockRow>> {
-        let mut stmt = conn.prepare(
+        let mut stmt = conn.prepare_cached(
             r#"
             SELECT
                 id,
@@ -664,7 +841,8 @@ impl PlanningRepository {
                 applied_at,
                 actual_start_at,
                 actual_end_at,
-                status
+                status,
+                prep_note
             FROM planning_time_blocks
             WHERE task_id = ?1
             ORDER BY start_at ASC
@@ -678,6 +856,115 @@ impl PlanningRepository {
         Ok(rows)
     }
 
+    /// Applied blocks whose planned start falls within `[start, end)`.
+    pub fn list_applied_time_blocks_between(
+        conn: &Connection,
+        start: &str,
+        end: &str,
+    ) -> AppResult<Vec<PlanningTimeBlockRow>> {
+        let mut stmt = conn.prepare_cached(
+            r#"
+            SELECT
+                id,
+                option_id,
+                task_id,
+                start_at,
+                end_at,
+                flexibility,
+                confidence,
+                conflict_flags,
+                applied_at,
+                actual_start_at,
+                actual_end_at,
+                status,
+                prep_note
+            FROM planning_time_blocks
+            WHERE applied_at IS NOT NULL
+              AND start_at >= ?1
+              AND start_at < ?2
+            ORDER BY start_at ASC
+        "#,
+        )?;
+
+        let rows = stmt
+            .query_map([start, end], |row| PlanningTimeBlockRow::try_from(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// Every applied block, oldest first.
+    pub fn list_applied_time_blocks(conn: &Connection) -> AppResult<Vec<PlanningTimeBlockRow>> {
+        let mut stmt = conn.prepare_cached(
+            r#"
+            SELECT
+                id,
+                option_id,
+                task_id,
+                start_at,
+                end_at,
+                flexibility,
+                confidence,
+                conflict_flags,
+                applied_at,
+                actual_start_at,
+                actual_end_at,
+                status,
+                prep_note
+            FROM planning_time_blocks
+            WHERE applied_at IS NOT NULL
+            ORDER BY start_at ASC
+        "#,
+        )?;
+
+        let rows = stmt
+            .query_map([], |row| PlanningTimeBlockRow::try_from(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// Applied blocks of any option of the session, oldest first.
+    pub fn list_applied_time_blocks_for_session(
+        conn: &Connection,
+        session_id: &str,
+    ) -> AppResult<Vec<PlanningTimeBlockRow>> {
+        let mut stmt = conn.prepare_cached(
+            r#"
+            SELECT
+                b.id,
+                b.option_id,
+                b.task_id,
+                b.start_at,
+                b.end_at,
+                b.flexibility,
+                b.confidence,
+                b.conflict_flags,
+                b.applied_at,
+                b.actual_start_at,
+                b.actual_end_at,
+                b.status,
+                b.prep_note
+            FROM planning_time_blocks b
+            JOIN planning_options o ON o.id = b.option_id
+            WHERE o.session_id = ?1
+              AND b.applied_at IS NOT NULL
+            ORDER BY b.start_at ASC
+        "#,
+        )?;
+
+        let rows = stmt
+            .query_map([session_id], |row| PlanningTimeBlockRow::try_from(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    pub fn delete_time_block(conn: &Connection, id: &str) -> AppResult<()> {
+        conn.execute("DELETE FROM planning_time_blocks WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
     pub fn delete_time_blocks_for_session(conn: &Connection, session_id: &str) -> AppResult<()> {
         conn.execute(
             r#"
@@ -731,6 +1018,37 @@ impl PlanningRepository {
 
         Ok(())
     }
+
+    /// Every preference profile, the built-in `default` one first.
+    pub fn list_preference_profiles(conn: &Connection) -> AppResult<Vec<PreferenceProfileRow>> {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, name, updated_at
+            FROM schedule_preferences
+            ORDER BY id = 'default' DESC, COALESCE(name, id) COLLATE NOCASE ASC
+        "#,
+        )?;
+        let rows = stmt
+            .query_map([], |row| PreferenceProfileRow::try_from(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    pub fn set_preference_profile_name(conn: &Connection, id: &str, name: &str) -> AppResult<()> {
+        conn.execute(
+            "UPDATE schedule_preferences SET name = :name WHERE id = :id",
+            named_params! {":id": id, ":name": name},
+        )?;
+
+        Ok(())
+    }
+
+    pub fn delete_schedule_preferences(conn: &Connection, id: &str) -> AppResult<usize> {
+        let deleted = conn.execute("DELETE FROM schedule_preferences WHERE id = ?1", [id])?;
+
+        Ok(deleted)
+    }
 }
 
 fn serialize_vec(values: &[String]) -> AppResult<String> {
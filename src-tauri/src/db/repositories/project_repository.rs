@@ -0,0 +1,260 @@
+use std::convert::TryFrom;
+
+use rusqlite::{named_params, Connection, OptionalExtension, Row};
+
+use crate::error::{AppError, AppResult};
+use crate::models::project::{ProjectRecord, ProjectStatus};
+
+#[derive(Debug, Clone)]
+pub struct ProjectRow {
+    pub id: String,
+    pub name: String,
+    pub status: String,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    pub default_tags: String,
+    pub archived_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl ProjectRow {
+    pub fn from_record(record: &ProjectRecord) -> AppResult<Self> {
+        Ok(Self {
+            id: record.id.clone(),
+            name: record.name.clone(),
+            status: record.status.as_str().to_string(),
+            color: record.color.clone(),
+            icon: record.icon.clone(),
+            default_tags: serde_json::to_string(&record.default_tags)?,
+            archived_at: record.archived_at.clone(),
+            created_at: record.created_at.clone(),
+            updated_at: record.updated_at.clone(),
+        })
+    }
+
+    pub fn into_record(self) -> AppResult<ProjectRecord> {
+        let status = ProjectStatus::try_from(self.status.as_str()).map_err(AppError::validation)?;
+
+        Ok(ProjectRecord {
+            id: self.id,
+            name: self.name,
+            status,
+            color: self.color,
+            icon: self.icon,
+            default_tags: serde_json::from_str(&self.default_tags)?,
+            archived_at: self.archived_at,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        })
+    }
+}
+
+impl TryFrom<&Row<'_>> for ProjectRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.get("id")?,
+            name: row.get("name")?,
+            status: row.get("status")?,
+            color: row.get("color")?,
+            icon: row.get("icon")?,
+            default_tags: row.get("default_tags")?,
+            archived_at: row.get("archived_at")?,
+            created_at: row.get("created_at")?,
+            updated_at: row.get("updated_at")?,
+        })
+    }
+}
+
+const SELECT_COLUMNS: &str = r#"
+    SELECT
+        id,
+        name,
+        status,
+        color,
+        icon,
+        default_tags,
+        archived_at,
+        created_at,
+        updated_at
+    FROM projects
+"#;
+
+/// Per-status task counts of one project.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectTaskCounts {
+    pub total: i64,
+    pub completed: i64,
+    pub in_progress: i64,
+    pub overdue: i64,
+}
+
+pub struct ProjectRepository;
+
+impl ProjectRepository {
+    pub fn upsert(conn: &Connection, row: &ProjectRow) -> AppResult<()> {
+        conn.execute(
+            r#"
+                INSERT INTO projects (
+                    id,
+                    name,
+                    status,
+                    color,
+                    icon,
+                    default_tags,
+                    archived_at,
+                    created_at,
+                    updated_at
+                ) VALUES (
+                    :id,
+                    :name,
+                    :status,
+                    :color,
+                    :icon,
+                    :default_tags,
+                    :archived_at,
+                    :created_at,
+                    :updated_at
+                )
+                ON CONFLICT(id) DO UPDATE SET
+                    name = excluded.name,
+                    status = excluded.status,
+                    color = excluded.color,
+                    icon = excluded.icon,
+                    default_tags = excluded.default_tags,
+                    archived_at = excluded.archived_at,
+                    updated_at = excluded.updated_at
+            "#,
+            named_params! {
+                ":id": &row.id,
+                ":name": &row.name,
+                ":status": &row.status,
+                ":color": &row.color,
+                ":icon": &row.icon,
+                ":default_tags": &row.default_tags,
+                ":archived_at": &row.archived_at,
+                ":created_at": &row.created_at,
+                ":updated_at": &row.updated_at,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    pub fn find(conn: &Connection, id: &str) -> AppResult<Option<ProjectRow>> {
+        let sql = format!("{SELECT_COLUMNS} WHERE id = :id");
+        let row = conn
+            .query_row(&sql, named_params! {":id": id}, |row| {
+                ProjectRow::try_from(row)
+            })
+            .optional()?;
+
+        Ok(row)
+    }
+
+    pub fn list(conn: &Connection, include_archived: bool) -> AppResult<Vec<ProjectRow>> {
+        let sql = format!(
+            "{SELECT_COLUMNS} WHERE (:include_archived = 1 OR status != 'archived')
+             ORDER BY name COLLATE NOCASE ASC"
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(
+                named_params! {":include_archived": include_archived as i64},
+                |row| ProjectRow::try_from(row),
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    pub fn delete(conn: &Connection, id: &str) -> AppResult<()> {
+        let affected = conn.execute("DELETE FROM projects WHERE id = ?1", [id])?;
+        if affected == 0 {
+            return Err(AppError::not_found());
+        }
+        Ok(())
+    }
+
+    /// Task counts ignoring archived tasks; `now` decides what is overdue.
+    pub fn task_counts(conn: &Connection, id: &str, now: &str) -> AppResult<ProjectTaskCounts> {
+        let counts = conn.query_row(
+            r#"
+                SELECT
+                    COUNT(*),
+                    COALESCE(SUM(CASE WHEN status = 'done' THEN 1 ELSE 0 END), 0),
+                    COALESCE(SUM(CASE WHEN status = 'in_progress' THEN 1 ELSE 0 END), 0),
+                    COALESCE(SUM(CASE WHEN status != 'done' AND due_at IS NOT NULL
+                        AND due_at < :now THEN 1 ELSE 0 END), 0)
+                FROM tasks
+                WHERE project_id = :id AND status != 'archived'
+            "#,
+            named_params! {":id": id, ":now": now},
+            |row| {
+                Ok(ProjectTaskCounts {
+                    total: row.get(0)?,
+                    completed: row.get(1)?,
+                    in_progress: row.get(2)?,
+                    overdue: row.get(3)?,
+                })
+            },
+        )?;
+
+        Ok(counts)
+    }
+
+    pub fn goal_count(conn: &Connection, id: &str) -> AppResult<i64> {
+        let count = conn.query_row(
+            "SELECT COUNT(*) FROM goals WHERE project_id = ?1",
+            [id],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    /// Open, unsnoozed tasks of the project that are not waiting on anyone
+    /// and whose predecessors are all finished, earliest due first.
+    pub fn list_unblocked_task_ids(
+        conn: &Connection,
+        id: &str,
+        now: &str,
+    ) -> AppResult<Vec<String>> {
+        let mut stmt = conn.prepare(
+            r#"
+                SELECT t.id
+                FROM tasks t
+                WHERE t.project_id = :id
+                  AND t.status NOT IN ('done', 'archived', 'blocked', 'waiting')
+                  AND (t.snoozed_until IS NULL OR t.snoozed_until <= :now)
+                  AND NOT EXISTS (
+                      SELECT 1
+                      FROM task_dependencies d
+                      JOIN tasks p ON p.id = d.predecessor_id
+                      WHERE d.successor_id = t.id
+                        AND p.status NOT IN ('done', 'archived')
+                  )
+                ORDER BY t.due_at IS NULL, t.due_at ASC, t.created_at ASC
+            "#,
+        )?;
+        let ids = stmt
+            .query_map(named_params! {":id": id, ":now": now}, |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+
+        Ok(ids)
+    }
+
+    /// Archives every open task of the project and returns how many changed.
+    pub fn archive_tasks(conn: &Connection, id: &str, now: &str) -> AppResult<usize> {
+        let affected = conn.execute(
+            r#"
+                UPDATE tasks
+                SET status = 'archived', updated_at = :now
+                WHERE project_id = :id AND status NOT IN ('done', 'archived')
+            "#,
+            named_params! {":id": id, ":now": now},
+        )?;
+        Ok(affected)
+    }
+}
@@ -0,0 +1,108 @@
+use std::convert::TryFrom;
+
+use rusqlite::{named_params, Connection, Row};
+
+use crate::error::{AppError, AppResult};
+use crate::models::prompt_template::{PromptOverrideRecord, PromptTemplateKey};
+
+#[derive(Debug, Clone)]
+pub struct PromptOverrideRow {
+    pub prompt_key: String,
+    pub template: String,
+    pub updated_at: String,
+}
+
+impl PromptOverrideRow {
+    pub fn from_record(record: &PromptOverrideRecord) -> Self {
+        Self {
+            prompt_key: record.key.as_str().to_string(),
+            template: record.template.clone(),
+            updated_at: record.updated_at.clone(),
+        }
+    }
+
+    pub fn into_record(self) -> AppResult<PromptOverrideRecord> {
+        let key =
+            PromptTemplateKey::try_from(self.prompt_key.as_str()).map_err(AppError::validation)?;
+
+        Ok(PromptOverrideRecord {
+            key,
+            template: self.template,
+            updated_at: self.updated_at,
+        })
+    }
+}
+
+impl TryFrom<&Row<'_>> for PromptOverrideRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            prompt_key: row.get("prompt_key")?,
+            template: row.get("template")?,
+            updated_at: row.get("updated_at")?,
+        })
+    }
+}
+
+pub struct PromptOverrideRepository;
+
+impl PromptOverrideRepository {
+    pub fn upsert(conn: &Connection, row: &PromptOverrideRow) -> AppResult<()> {
+        conn.execute(
+            r#"
+                INSERT INTO prompt_overrides (
+                    prompt_key,
+                    template,
+                    updated_at
+                ) VALUES (
+                    :prompt_key,
+                    :template,
+                    :updated_at
+                )
+                ON CONFLICT(prompt_key) DO UPDATE SET
+                    template = excluded.template,
+                    updated_at = excluded.updated_at
+            "#,
+            named_params! {
+                ":prompt_key": &row.prompt_key,
+                ":template": &row.template,
+                ":updated_at": &row.updated_at,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    pub fn delete(conn: &Connection, key: PromptTemplateKey) -> AppResult<bool> {
+        let affected = conn.execute(
+            "DELETE FROM prompt_overrides WHERE prompt_key = :prompt_key",
+            named_params! {":prompt_key": key.as_str()},
+        )?;
+        Ok(affected > 0)
+    }
+
+    /// Lists stored overrides. Rows with an unknown key (e.g. written by a newer
+    /// build) are skipped rather than failing every AI request.
+    pub fn list_all(conn: &Connection) -> AppResult<Vec<PromptOverrideRecord>> {
+        let mut stmt = conn.prepare(
+            r#"
+                SELECT
+                    prompt_key,
+                    template,
+                    updated_at
+                FROM prompt_overrides
+                ORDER BY prompt_key ASC
+            "#,
+        )?;
+
+        let rows = stmt
+            .query_map([], |row| PromptOverrideRow::try_from(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| row.into_record().ok())
+            .collect())
+    }
+}
@@ -0,0 +1,221 @@
+use std::convert::TryFrom;
+
+use rusqlite::{named_params, Connection, OptionalExtension, Row};
+
+use crate::error::{AppError, AppResult};
+use crate::models::reminder::{BlockReminderRecord, BlockReminderStatus};
+
+#[derive(Debug, Clone)]
+pub struct BlockReminderRow {
+    pub id: String,
+    pub block_id: String,
+    pub task_id: String,
+    pub start_at: String,
+    pub remind_at: String,
+    pub lead_minutes: i64,
+    pub status: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl BlockReminderRow {
+    pub fn from_record(record: &BlockReminderRecord) -> Self {
+        Self {
+            id: record.id.clone(),
+            block_id: record.block_id.clone(),
+            task_id: record.task_id.clone(),
+            start_at: record.start_at.clone(),
+            remind_at: record.remind_at.clone(),
+            lead_minutes: record.lead_minutes,
+            status: record.status.as_str().to_string(),
+            created_at: record.created_at.clone(),
+            updated_at: record.updated_at.clone(),
+        }
+    }
+
+    pub fn into_record(self) -> AppResult<BlockReminderRecord> {
+        let status =
+            BlockReminderStatus::try_from(self.status.as_str()).map_err(AppError::validation)?;
+
+        Ok(BlockReminderRecord {
+            id: self.id,
+            block_id: self.block_id,
+            task_id: self.task_id,
+            start_at: self.start_at,
+            remind_at: self.remind_at,
+            lead_minutes: self.lead_minutes,
+            status,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        })
+    }
+}
+
+impl TryFrom<&Row<'_>> for BlockReminderRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.get("id")?,
+            block_id: row.get("block_id")?,
+            task_id: row.get("task_id")?,
+            start_at: row.get("start_at")?,
+            remind_at: row.get("remind_at")?,
+            lead_minutes: row.get("lead_minutes")?,
+            status: row.get("status")?,
+            created_at: row.get("created_at")?,
+            updated_at: row.get("updated_at")?,
+        })
+    }
+}
+
+const SELECT_COLUMNS: &str = r#"
+    SELECT
+        id,
+        block_id,
+        task_id,
+        start_at,
+        remind_at,
+        lead_minutes,
+        status,
+        created_at,
+        updated_at
+    FROM block_reminders
+"#;
+
+/// An applied block that may need a reminder.
+#[derive(Debug, Clone)]
+pub struct ReminderCandidateRow {
+    pub block_id: String,
+    pub task_id: String,
+    pub start_at: String,
+    pub flexibility: Option<String>,
+}
+
+pub struct ReminderRepository;
+
+impl ReminderRepository {
+    pub fn upsert(conn: &Connection, row: &BlockReminderRow) -> AppResult<()> {
+        conn.execute(
+            r#"
+                INSERT INTO block_reminders (
+                    id,
+                    block_id,
+                    task_id,
+                    start_at,
+                    remind_at,
+                    lead_minutes,
+                    status,
+                    created_at,
+                    updated_at
+                ) VALUES (
+                    :id,
+                    :block_id,
+                    :task_id,
+                    :start_at,
+                    :remind_at,
+                    :lead_minutes,
+                    :status,
+                    :created_at,
+                    :updated_at
+                )
+                ON CONFLICT(id) DO UPDATE SET
+                    start_at = excluded.start_at,
+                    remind_at = excluded.remind_at,
+                    lead_minutes = excluded.lead_minutes,
+                    status = excluded.status,
+                    updated_at = excluded.updated_at
+            "#,
+            named_params! {
+                ":id": &row.id,
+                ":block_id": &row.block_id,
+                ":task_id": &row.task_id,
+                ":start_at": &row.start_at,
+                ":remind_at": &row.remind_at,
+                ":lead_minutes": row.lead_minutes,
+                ":status": &row.status,
+                ":created_at": &row.created_at,
+                ":updated_at": &row.updated_at,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    pub fn find(conn: &Connection, id: &str) -> AppResult<Option<BlockReminderRow>> {
+        let sql = format!("{SELECT_COLUMNS} WHERE id = :id");
+        let row = conn
+            .query_row(&sql, named_params! {":id": id}, |row| {
+                BlockReminderRow::try_from(row)
+            })
+            .optional()?;
+
+        Ok(row)
+    }
+
+    pub fn find_by_block(conn: &Connection, block_id: &str) -> AppResult<Option<BlockReminderRow>> {
+        let sql = format!("{SELECT_COLUMNS} WHERE block_id = :block_id");
+        let row = conn
+            .query_row(&sql, named_params! {":block_id": block_id}, |row| {
+                BlockReminderRow::try_from(row)
+            })
+            .optional()?;
+
+        Ok(row)
+    }
+
+    /// Reminders still waiting to fire, soonest first.
+    pub fn list_scheduled(conn: &Connection) -> AppResult<Vec<BlockReminderRow>> {
+        let sql = format!("{SELECT_COLUMNS} WHERE status = 'scheduled' ORDER BY remind_at ASC");
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map([], |row| BlockReminderRow::try_from(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// Scheduled reminders due at or before `now`.
+    pub fn list_due(conn: &Connection, now: &str) -> AppResult<Vec<BlockReminderRow>> {
+        let sql = format!(
+            "{SELECT_COLUMNS} WHERE status = 'scheduled' AND remind_at <= :now ORDER BY remind_at ASC"
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(named_params! {":now": now}, |row| {
+                BlockReminderRow::try_from(row)
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// Applied, open blocks of unfinished tasks starting after `since`. The
+    /// bound is a plain string comparison, so callers pass a generous one and
+    /// check the parsed times themselves.
+    pub fn list_candidates(conn: &Connection, since: &str) -> AppResult<Vec<ReminderCandidateRow>> {
+        let mut stmt = conn.prepare(
+            r#"
+                SELECT b.id, b.task_id, b.start_at, b.flexibility
+                FROM planning_time_blocks b
+                JOIN tasks t ON t.id = b.task_id
+                WHERE b.applied_at IS NOT NULL
+                  AND b.status IN ('planned', 'in_progress')
+                  AND t.status NOT IN ('done', 'archived')
+                  AND b.start_at >= :since
+            "#,
+        )?;
+        let rows = stmt
+            .query_map(named_params! {":since": since}, |row| {
+                Ok(ReminderCandidateRow {
+                    block_id: row.get(0)?,
+                    task_id: row.get(1)?,
+                    start_at: row.get(2)?,
+                    flexibility: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+}
@@ -0,0 +1,48 @@
+use rusqlite::{named_params, Connection};
+
+use crate::error::AppResult;
+
+/// Row-level access for retention pruning. `table` and `column` always come
+/// from the fixed target list in the retention service, never from input.
+pub struct RetentionRepository;
+
+impl RetentionRepository {
+    /// Cache tables are created lazily by the cache service, so they may not
+    /// exist yet.
+    pub fn table_exists(conn: &Connection, table: &str) -> AppResult<bool> {
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = :table",
+            named_params! {":table": table},
+            |row| row.get(0),
+        )?;
+
+        Ok(count > 0)
+    }
+
+    /// Rows whose `column` sorts before `cutoff`. Works for both `YYYY-MM-DD`
+    /// and RFC 3339 / SQLite timestamp columns.
+    pub fn count_before(
+        conn: &Connection,
+        table: &str,
+        column: &str,
+        cutoff: &str,
+    ) -> AppResult<usize> {
+        let sql = format!("SELECT COUNT(*) FROM {table} WHERE {column} < :cutoff");
+        let count: i64 =
+            conn.query_row(&sql, named_params! {":cutoff": cutoff}, |row| row.get(0))?;
+
+        Ok(count as usize)
+    }
+
+    pub fn delete_before(
+        conn: &Connection,
+        table: &str,
+        column: &str,
+        cutoff: &str,
+    ) -> AppResult<usize> {
+        let sql = format!("DELETE FROM {table} WHERE {column} < :cutoff");
+        let deleted = conn.execute(&sql, named_params! {":cutoff": cutoff})?;
+
+        Ok(deleted)
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::HashSet;
+use std::convert::TryFrom;
+
+use rusqlite::{named_params, Connection, OptionalExtension, Row};
+
+use crate::error::{AppError, AppResult};
+use crate::models::suggestion::{SuggestionKind, SuggestionRecord, SuggestionStatus};
+
+#[derive(Debug, Clone)]
+pub struct SuggestionRow {
+    pub id: String,
+    pub kind: String,
+    pub title: String,
+    pub reason: String,
+    pub task_id: Option<String>,
+    pub target_date: Option<String>,
+    pub payload: String,
+    pub status: String,
+    pub suggested_for: String,
+    pub created_at: String,
+    pub resolved_at: Option<String>,
+}
+
+impl SuggestionRow {
+    pub fn from_record(record: &SuggestionRecord) -> AppResult<Self> {
+        Ok(Self {
+            id: record.id.clone(),
+            kind: record.kind.as_str().to_string(),
+            title: record.title.clone(),
+            reason: record.reason.clone(),
+            task_id: record.task_id.clone(),
+            target_date: record.target_date.clone(),
+            payload: serde_json::to_string(&record.payload)?,
+            status: record.status.as_str().to_string(),
+            suggested_for: record.suggested_for.clone(),
+            created_at: record.created_at.clone(),
+            resolved_at: record.resolved_at.clone(),
+        })
+    }
+
+    pub fn into_record(self) -> AppResult<SuggestionRecord> {
+        let kind = SuggestionKind::try_from(self.kind.as_str()).map_err(AppError::validation)?;
+        let status =
+            SuggestionStatus::try_from(self.status.as_str()).map_err(AppError::validation)?;
+
+        Ok(SuggestionRecord {
+            id: self.id,
+            kind,
+            title: self.title,
+            reason: self.reason,
+            task_id: self.task_id,
+            target_date: self.target_date,
+            payload: serde_json::from_str(&self.payload)?,
+            status,
+            suggested_for: self.suggested_for,
+            created_at: self.created_at,
+            resolved_at: self.resolved_at,
+        })
+    }
+}
+
+impl TryFrom<&Row<'_>> for SuggestionRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.get("id")?,
+            kind: row.get("kind")?,
+            title: row.get("title")?,
+            reason: row.get("reason")?,
+            task_id: row.get("task_id")?,
+            target_date: row.get("target_date")?,
+            payload: row.get("payload")?,
+            status: row.get("status")?,
+            suggested_for: row.get("suggested_for")?,
+            created_at: row.get("created_at")?,
+            resolved_at: row.get("resolved_at")?,
+        })
+    }
+}
+
+const SELECT_COLUMNS: &str = r#"
+    SELECT
+        id,
+        kind,
+        title,
+        reason,
+        task_id,
+        target_date,
+        payload,
+        status,
+        suggested_for,
+        created_at,
+        resolved_at
+    FROM agent_suggestions
+"#;
+
+pub struct SuggestionRepository;
+
+impl SuggestionRepository {
+    pub fn upsert(conn: &Connection, row: &SuggestionRow) -> AppResult<()> {
+        conn.execute(
+            r#"
+                INSERT INTO agent_suggestions (
+                    id,
+                    kind,
+                    title,
+                    reason,
+                    task_id,
+                    target_date,
+                    payload,
+                    status,
+                    suggested_for,
+                    created_at,
+                    resolved_at
+                ) VALUES (
+                    :id,
+                    :kind,
+                    :title,
+                    :reason,
+                    :task_id,
+                    :target_date,
+                    :payload,
+                    :status,
+                    :suggested_for,
+                    :created_at,
+                    :resolved_at
+                )
+                ON CONFLICT(id) DO UPDATE SET
+                    status = excluded.status,
+                    resolved_at = excluded.resolved_at
+            "#,
+            named_params! {
+                ":id": &row.id,
+                ":kind": &row.kind,
+                ":title": &row.title,
+                ":reason": &row.reason,
+                ":task_id": &row.task_id,
+                ":target_date": &row.target_date,
+                ":payload": &row.payload,
+                ":status": &row.status,
+                ":suggested_for": &row.suggested_for,
+                ":created_at": &row.created_at,
+                ":resolved_at": &row.resolved_at,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    pub fn find(conn: &Connection, id: &str) -> AppResult<Option<SuggestionRow>> {
+        let sql = format!("{SELECT_COLUMNS} WHERE id = :id");
+        let row = conn
+            .query_row(&sql, named_params! {":id": id}, |row| {
+                SuggestionRow::try_from(row)
+            })
+            .optional()?;
+
+        Ok(row)
+    }
+
+    /// Suggestions composed for `date`, in the order they were composed.
+    pub fn list_for_date(conn: &Connection, date: &str) -> AppResult<Vec<SuggestionRow>> {
+        let sql = format!(
+            "{SELECT_COLUMNS} WHERE suggested_for = :date ORDER BY created_at ASC, rowid ASC"
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(named_params! {":date": date}, |row| {
+                SuggestionRow::try_from(row)
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    pub fn list_by_status(
+        conn: &Connection,
+        status: &str,
+        limit: usize,
+    ) -> AppResult<Vec<SuggestionRow>> {
+        let sql = format!(
+            "{SELECT_COLUMNS} WHERE status = :status ORDER BY created_at DESC, rowid DESC LIMIT :limit"
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(
+                named_params! {":status": status, ":limit": limit as i64},
+                |row| SuggestionRow::try_from(row),
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// Marks pending suggestions composed before `date` as expired.
+    pub fn expire_pending_before(conn: &Connection, date: &str, now: &str) -> AppResult<usize> {
+        let updated = conn.execute(
+            r#"
+                UPDATE agent_suggestions
+                SET status = 'expired', resolved_at = :now
+                WHERE status = 'pending' AND suggested_for < :date
+            "#,
+            named_params! {":date": date, ":now": now},
+        )?;
+
+        Ok(updated)
+    }
+
+    /// Tasks or days that already got a suggestion of `kind` composed on or
+    /// after `since`, so the same one is not suggested every day.
+    pub fn recent_targets(
+        conn: &Connection,
+        kind: &str,
+        since: &str,
+    ) -> AppResult<HashSet<String>> {
+        let mut stmt = conn.prepare(
+            r#"
+                SELECT DISTINCT COALESCE(task_id, target_date)
+                FROM agent_suggestions
+                WHERE kind = :kind
+                  AND suggested_for >= :since
+                  AND COALESCE(task_id, target_date) IS NOT NULL
+            "#,
+        )?;
+        let rows = stmt
+            .query_map(named_params! {":kind": kind, ":since": since}, |row| {
+                row.get::<_, String>(0)
+            })?
+            .collect::<Result<HashSet<_>, _>>()?;
+
+        Ok(rows)
+    }
+}
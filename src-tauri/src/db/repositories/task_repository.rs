@@ -43,6 +43,15 @@ const BASE_SELECT: &str = r#"
         ai_source,
         ai_generated_at,
         external_links,
+        project_id,
+        snoozed_until,
+        snooze_notify,
+        color,
+        icon,
+        delegated_to,
+        waiting_on,
+        waiting_since,
+        follow_up_task_id,
         created_at,
         updated_at
     FROM tasks
@@ -80,6 +89,15 @@ pub struct TaskRow {
     pub ai_source: Option<String>,
     pub ai_generated_at: Option<String>,
     pub external_links: Option<String>,
+    pub project_id: Option<String>,
+    pub snoozed_until: Option<String>,
+    pub snooze_notify: bool,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    pub delegated_to: Option<String>,
+    pub waiting_on: Option<String>,
+    pub waiting_since: Option<String>,
+    pub follow_up_task_id: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -129,6 +147,15 @@ impl TaskRow {
             ai_source: serialize_ai_source(record.ai.as_ref().and_then(|ai| ai.source)),
             ai_generated_at: record.ai.as_ref().and_then(|ai| ai.generated_at.clone()),
             external_links: serialize_vec(&record.external_links)?,
+            project_id: record.project_id.clone(),
+            snoozed_until: record.snoozed_until.clone(),
+            snooze_notify: record.snooze_notify,
+            color: record.color.clone(),
+            icon: record.icon.clone(),
+            delegated_to: record.delegated_to.clone(),
+            waiting_on: record.waiting_on.clone(),
+            waiting_since: record.waiting_since.clone(),
+            follow_up_task_id: record.follow_up_task_id.clone(),
             created_at: record.created_at.clone(),
             updated_at: record.updated_at.clone(),
         })
@@ -192,6 +219,15 @@ impl TaskRow {
             recurrence,
             ai,
             external_links: deserialize_vec(self.external_links)?,
+            project_id: self.project_id,
+            snoozed_until: self.snoozed_until,
+            snooze_notify: self.snooze_notify,
+            color: self.color,
+            icon: self.icon,
+            delegated_to: self.delegated_to,
+            waiting_on: self.waiting_on,
+            waiting_since: self.waiting_since,
+            follow_up_task_id: self.follow_up_task_id,
             created_at: self.created_at,
             updated_at: self.updated_at,
         })
@@ -233,6 +269,15 @@ impl TryFrom<&Row<'_>> for TaskRow {
             ai_source: row.get("ai_source")?,
             ai_generated_at: row.get("ai_generated_at")?,
             external_links: row.get("external_links")?,
+            project_id: row.get("project_id")?,
+            snoozed_until: row.get("snoozed_until")?,
+            snooze_notify: row.get::<_, i64>("snooze_notify")? != 0,
+            color: row.get("color")?,
+            icon: row.get("icon")?,
+            delegated_to: row.get("delegated_to")?,
+            waiting_on: row.get("waiting_on")?,
+            waiting_since: row.get("waiting_since")?,
+            follow_up_task_id: row.get("follow_up_task_id")?,
             created_at: row.get("created_at")?,
             updated_at: row.get("updated_at")?,
         })
@@ -243,7 +288,7 @@ pub struct TaskRepository;
 
 impl TaskRepository {
     pub fn insert(conn: &Connection, row: &TaskRow) -> AppResult<()> {
-        conn.execute(
+        conn.prepare_cached(
             r#"
                 INSERT INTO tasks (
                     id,
@@ -276,6 +321,15 @@ impl TaskRepository {
                     ai_source,
                     ai_generated_at,
                     external_links,
+                    project_id,
+                    snoozed_until,
+                    snooze_notify,
+                    color,
+                    icon,
+                    delegated_to,
+                    waiting_on,
+                    waiting_since,
+                    follow_up_task_id,
                     created_at,
                     updated_at
                 ) VALUES (
@@ -309,52 +363,71 @@ impl TaskRepository {
                     :ai_source,
                     :ai_generated_at,
                     :external_links,
+                    :project_id,
+                    :snoozed_until,
+                    :snooze_notify,
+                    :color,
+                    :icon,
+                    :delegated_to,
+                    :waiting_on,
+                    :waiting_since,
+                    :follow_up_task_id,
                     :created_at,
                     :updated_at
                 )
             "#,
-            named_params! {
-                ":id": &row.id,
-                ":title": &row.title,
-                ":description": &row.description,
-                ":status": &row.status,
-                ":priority": &row.priority,
-                ":planned_start_at": &row.planned_start_at,
-                ":start_at": &row.start_at,
-                ":due_at": &row.due_at,
-                ":completed_at": &row.completed_at,
-                ":estimated_minutes": &row.estimated_minutes,
-                ":estimated_hours": &row.estimated_hours,
-                ":tags": &row.tags,
-                ":owner_id": &row.owner_id,
-                ":task_type": &row.task_type,
-                ":is_recurring": row.is_recurring as i64,
-                ":recurrence_rule": &row.recurrence_rule,
-                ":recurrence_until": &row.recurrence_until,
-                ":ai_summary": &row.ai_summary,
-                ":ai_next_action": &row.ai_next_action,
-                ":ai_confidence": &row.ai_confidence,
-                ":ai_complexity_score": &row.ai_complexity_score,
-                ":ai_suggested_start_at": &row.ai_suggested_start_at,
-                ":ai_focus_mode": &row.ai_focus_mode,
-                ":ai_efficiency_prediction": &row.ai_efficiency_prediction,
-                ":ai_cot_steps": &row.ai_cot_steps,
-                ":ai_cot_summary": &row.ai_cot_summary,
-                ":ai_metadata": &row.ai_metadata,
-                ":ai_source": &row.ai_source,
-                ":ai_generated_at": &row.ai_generated_at,
-                ":external_links": &row.external_links,
-                ":created_at": &row.created_at,
-                ":updated_at": &row.updated_at,
-            },
-        )?;
+        )?
+        .execute(named_params! {
+            ":id": &row.id,
+            ":title": &row.title,
+            ":description": &row.description,
+            ":status": &row.status,
+            ":priority": &row.priority,
+            ":planned_start_at": &row.planned_start_at,
+            ":start_at": &row.start_at,
+            ":due_at": &row.due_at,
+            ":completed_at": &row.completed_at,
+            ":estimated_minutes": &row.estimated_minutes,
+            ":estimated_hours": &row.estimated_hours,
+            ":tags": &row.tags,
+            ":owner_id": &row.owner_id,
+            ":task_type": &row.task_type,
+            ":is_recurring": row.is_recurring as i64,
+            ":recurrence_rule": &row.recurrence_rule,
+            ":recurrence_until": &row.recurrence_until,
+            ":ai_summary": &row.ai_summary,
+            ":ai_next_action": &row.ai_next_action,
+            ":ai_confidence": &row.ai_confidence,
+            ":ai_complexity_score": &row.ai_complexity_score,
+            ":ai_suggested_start_at": &row.ai_suggested_start_at,
+            ":ai_focus_mode": &row.ai_focus_mode,
+            ":ai_efficiency_prediction": &row.ai_efficiency_prediction,
+            ":ai_cot_steps": &row.ai_cot_steps,
+            ":ai_cot_summary": &row.ai_cot_summary,
+            ":ai_metadata": &row.ai_metadata,
+            ":ai_source": &row.ai_source,
+            ":ai_generated_at": &row.ai_generated_at,
+            ":external_links": &row.external_links,
+            ":project_id": &row.project_id,
+            ":snoozed_until": &row.snoozed_until,
+            ":snooze_notify": row.snooze_notify as i64,
+            ":color": &row.color,
+            ":icon": &row.icon,
+            ":delegated_to": &row.delegated_to,
+            ":waiting_on": &row.waiting_on,
+            ":waiting_since": &row.waiting_since,
+            ":follow_up_task_id": &row.follow_up_task_id,
+            ":created_at": &row.created_at,
+            ":updated_at": &row.updated_at,
+        })?;
 
         Ok(())
     }
 
     pub fn update(conn: &Connection, row: &TaskRow) -> AppResult<()> {
-        let affected = conn.execute(
-            r#"
+        let affected = conn
+            .prepare_cached(
+                r#"
                 UPDATE tasks SET
                     title = :title,
                     description = :description,
@@ -385,10 +458,20 @@ impl TaskRepository {
                     ai_source = :ai_source,
                     ai_generated_at = :ai_generated_at,
                     external_links = :external_links,
+                    project_id = :project_id,
+                    snoozed_until = :snoozed_until,
+                    snooze_notify = :snooze_notify,
+                    color = :color,
+                    icon = :icon,
+                    delegated_to = :delegated_to,
+                    waiting_on = :waiting_on,
+                    waiting_since = :waiting_since,
+                    follow_up_task_id = :follow_up_task_id,
                     updated_at = :updated_at
                 WHERE id = :id
             "#,
-            named_params! {
+            )?
+            .execute(named_params! {
                 ":id": &row.id,
                 ":title": &row.title,
                 ":description": &row.description,
@@ -419,9 +502,17 @@ impl TaskRepository {
                 ":ai_source": &row.ai_source,
                 ":ai_generated_at": &row.ai_generated_at,
                 ":external_links": &row.external_links,
+                ":project_id": &row.project_id,
+                ":snoozed_until": &row.snoozed_until,
+                ":snooze_notify": row.snooze_notify as i64,
+                ":color": &row.color,
+                ":icon": &row.icon,
+                ":delegated_to": &row.delegated_to,
+                ":waiting_on": &row.waiting_on,
+                ":waiting_since": &row.waiting_since,
+                ":follow_up_task_id": &row.follow_up_task_id,
                 ":updated_at": &row.updated_at,
-            },
-        )?;
+            })?;
 
         if affected == 0 {
             return Err(AppError::not_found());
@@ -439,15 +530,88 @@ impl TaskRepository {
     }
 
     pub fn find_by_id(conn: &Connection, id: &str) -> AppResult<Option<TaskRow>> {
-        let mut stmt = conn.prepare(&format!("{} WHERE id = ?1", BASE_SELECT))?;
+        let mut stmt = conn.prepare_cached(&format!("{} WHERE id = ?1", BASE_SELECT))?;
         let row = stmt
             .query_row([id], |row| TaskRow::try_from(row))
             .optional()?;
         Ok(row)
     }
 
+    /// Tasks whose snooze ended at or before `now`.
+    pub fn list_snooze_ended(conn: &Connection, now: &str) -> AppResult<Vec<TaskRow>> {
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE snoozed_until IS NOT NULL AND snoozed_until <= ?1 ORDER BY snoozed_until ASC",
+            BASE_SELECT
+        ))?;
+        let rows = stmt
+            .query_map([now], |row| TaskRow::try_from(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Tasks in the `waiting` status, longest waiting first.
+    pub fn list_waiting(conn: &Connection) -> AppResult<Vec<TaskRow>> {
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE status = 'waiting' ORDER BY waiting_since IS NULL, waiting_since ASC",
+            BASE_SELECT
+        ))?;
+        let rows = stmt
+            .query_map([], |row| TaskRow::try_from(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Completed tasks, most recently completed first.
+    pub fn list_completed(conn: &Connection, limit: usize) -> AppResult<Vec<TaskRow>> {
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE status = 'done' ORDER BY COALESCE(completed_at, updated_at) DESC LIMIT ?1",
+            BASE_SELECT
+        ))?;
+        let rows = stmt
+            .query_map([limit as i64], |row| TaskRow::try_from(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Tasks completed within `[start, end)`, in completion order. Compared
+    /// as instants because stored timestamps keep their original offset.
+    pub fn list_completed_between(
+        conn: &Connection,
+        start: &str,
+        end: &str,
+    ) -> AppResult<Vec<TaskRow>> {
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE completed_at IS NOT NULL \
+             AND julianday(completed_at) >= julianday(?1) AND julianday(completed_at) < julianday(?2) \
+             AND status IN ('done', 'archived') ORDER BY julianday(completed_at) ASC",
+            BASE_SELECT
+        ))?;
+        let rows = stmt
+            .query_map([start, end], |row| TaskRow::try_from(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Open tasks due before `until`, overdue ones included, soonest first.
+    pub fn list_open_due_before(
+        conn: &Connection,
+        until: &str,
+        limit: usize,
+    ) -> AppResult<Vec<TaskRow>> {
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE due_at IS NOT NULL AND completed_at IS NULL \
+             AND status NOT IN ('done', 'archived') AND julianday(due_at) < julianday(?1) \
+             ORDER BY julianday(due_at) ASC LIMIT ?2",
+            BASE_SELECT
+        ))?;
+        let rows = stmt
+            .query_map((until, limit as i64), |row| TaskRow::try_from(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
     pub fn list_all(conn: &Connection) -> AppResult<Vec<TaskRow>> {
-        let mut stmt = conn.prepare(&format!("{} ORDER BY created_at DESC", BASE_SELECT))?;
+        let mut stmt = conn.prepare_cached(&format!("{} ORDER BY created_at DESC", BASE_SELECT))?;
         let rows = stmt
             .query_map([], |row| TaskRow::try_from(row))?
             .collect::<Result<Vec<_>, _>>()?;
@@ -0,0 +1,84 @@
+use std::convert::TryFrom;
+
+use rusqlite::{named_params, Connection, Row};
+
+use crate::error::AppResult;
+
+#[derive(Debug, Clone)]
+pub struct UpgradeStepRow {
+    pub id: String,
+    pub app_version: String,
+    pub status: String,
+    pub error: Option<String>,
+    pub attempted_at: String,
+    pub completed_at: Option<String>,
+}
+
+impl TryFrom<&Row<'_>> for UpgradeStepRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.get("id")?,
+            app_version: row.get("app_version")?,
+            status: row.get("status")?,
+            error: row.get("error")?,
+            attempted_at: row.get("attempted_at")?,
+            completed_at: row.get("completed_at")?,
+        })
+    }
+}
+
+pub struct UpgradeRepository;
+
+impl UpgradeRepository {
+    pub fn list(conn: &Connection) -> AppResult<Vec<UpgradeStepRow>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, app_version, status, error, attempted_at, completed_at
+             FROM app_upgrade_steps ORDER BY id ASC",
+        )?;
+
+        let rows = stmt
+            .query_map([], |row| UpgradeStepRow::try_from(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    pub fn upsert(conn: &Connection, row: &UpgradeStepRow) -> AppResult<()> {
+        conn.execute(
+            r#"
+                INSERT INTO app_upgrade_steps (
+                    id,
+                    app_version,
+                    status,
+                    error,
+                    attempted_at,
+                    completed_at
+                ) VALUES (
+                    :id,
+                    :app_version,
+                    :status,
+                    :error,
+                    :attempted_at,
+                    :completed_at
+                )
+                ON CONFLICT(id) DO UPDATE SET
+                    status = excluded.status,
+                    error = excluded.error,
+                    attempted_at = excluded.attempted_at,
+                    completed_at = excluded.completed_at
+            "#,
+            named_params! {
+                ":id": &row.id,
+                ":app_version": &row.app_version,
+                ":status": &row.status,
+                ":error": &row.error,
+                ":attempted_at": &row.attempted_at,
+                ":completed_at": &row.completed_at,
+            },
+        )?;
+
+        Ok(())
+    }
+}
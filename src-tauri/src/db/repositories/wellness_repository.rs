@@ -1,11 +1,12 @@
 use std::convert::TryFrom;
 
+use chrono::NaiveDate;
 use rusqlite::{named_params, Connection, OptionalExtension, Row};
 
 use crate::error::{AppError, AppResult};
 use crate::models::wellness::{
-    WellnessEventInsert, WellnessEventRecord, WellnessEventResponseUpdate, WellnessResponse,
-    WellnessTriggerReason,
+    FocusIdleAdjustment, IdleAdjustmentStatus, IdlePeriod, WellnessEventInsert,
+    WellnessEventRecord, WellnessEventResponseUpdate, WellnessResponse, WellnessTriggerReason,
 };
 
 #[derive(Debug, Clone)]
@@ -166,6 +167,43 @@ impl WellnessRepository {
         Ok(records)
     }
 
+    /// Events whose window starts within `[start, end)`, oldest first.
+    pub fn list_between(
+        conn: &Connection,
+        start: &str,
+        end: &str,
+    ) -> AppResult<Vec<WellnessEventRecord>> {
+        let mut stmt = conn.prepare(
+            r#"
+                SELECT
+                    id,
+                    window_start,
+                    trigger_reason,
+                    recommended_break_minutes,
+                    suggested_micro_task,
+                    response,
+                    response_at,
+                    deferral_count
+                FROM wellness_events
+                WHERE window_start >= :start
+                  AND window_start < :end
+                ORDER BY window_start ASC
+            "#,
+        )?;
+
+        let records = stmt
+            .query_map(named_params! {":start": start, ":end": end}, |row| {
+                WellnessEventRow::try_from(row)
+            })?
+            .map(|row| {
+                row.map_err(AppError::from)
+                    .and_then(|row| row.into_record())
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(records)
+    }
+
     pub fn list_pending(conn: &Connection, limit: usize) -> AppResult<Vec<WellnessEventRecord>> {
         let mut stmt = conn.prepare(
             r#"
@@ -226,3 +264,206 @@ impl WellnessRepository {
         Ok(())
     }
 }
+
+const IDLE_ADJUSTMENT_COLUMNS: &str = "id, session_started_at, session_ended_at, task_id, \
+     idle_minutes, periods, status, created_at, resolved_at";
+
+#[derive(Debug, Clone)]
+pub struct FocusIdleAdjustmentRow {
+    pub id: i64,
+    pub session_started_at: String,
+    pub session_ended_at: String,
+    pub task_id: Option<String>,
+    pub idle_minutes: i64,
+    pub periods: String,
+    pub status: String,
+    pub created_at: String,
+    pub resolved_at: Option<String>,
+}
+
+impl FocusIdleAdjustmentRow {
+    pub fn from_record(record: &FocusIdleAdjustment) -> AppResult<Self> {
+        Ok(Self {
+            id: record.id,
+            session_started_at: record.session_started_at.clone(),
+            session_ended_at: record.session_ended_at.clone(),
+            task_id: record.task_id.clone(),
+            idle_minutes: record.idle_minutes,
+            periods: serde_json::to_string(&record.periods)?,
+            status: record.status.as_str().to_string(),
+            created_at: record.created_at.clone(),
+            resolved_at: record.resolved_at.clone(),
+        })
+    }
+
+    pub fn into_record(self) -> AppResult<FocusIdleAdjustment> {
+        let periods = serde_json::from_str::<Vec<IdlePeriod>>(&self.periods)?;
+        let status =
+            IdleAdjustmentStatus::try_from(self.status.as_str()).map_err(AppError::validation)?;
+
+        Ok(FocusIdleAdjustment {
+            id: self.id,
+            session_started_at: self.session_started_at,
+            session_ended_at: self.session_ended_at,
+            task_id: self.task_id,
+            idle_minutes: self.idle_minutes,
+            periods,
+            status,
+            created_at: self.created_at,
+            resolved_at: self.resolved_at,
+        })
+    }
+}
+
+impl TryFrom<&Row<'_>> for FocusIdleAdjustmentRow {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &Row<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.get("id")?,
+            session_started_at: row.get("session_started_at")?,
+            session_ended_at: row.get("session_ended_at")?,
+            task_id: row.get("task_id")?,
+            idle_minutes: row.get("idle_minutes")?,
+            periods: row.get("periods")?,
+            status: row.get("status")?,
+            created_at: row.get("created_at")?,
+            resolved_at: row.get("resolved_at")?,
+        })
+    }
+}
+
+pub struct FocusIdleRepository;
+
+impl FocusIdleRepository {
+    pub fn insert(conn: &Connection, row: &FocusIdleAdjustmentRow) -> AppResult<i64> {
+        conn.execute(
+            r#"
+                INSERT INTO focus_idle_adjustments (
+                    session_started_at,
+                    session_ended_at,
+                    task_id,
+                    idle_minutes,
+                    periods,
+                    status,
+                    created_at,
+                    resolved_at
+                ) VALUES (
+                    :session_started_at,
+                    :session_ended_at,
+                    :task_id,
+                    :idle_minutes,
+                    :periods,
+                    :status,
+                    :created_at,
+                    :resolved_at
+                )
+            "#,
+            named_params! {
+                ":session_started_at": &row.session_started_at,
+                ":session_ended_at": &row.session_ended_at,
+                ":task_id": &row.task_id,
+                ":idle_minutes": row.idle_minutes,
+                ":periods": &row.periods,
+                ":status": &row.status,
+                ":created_at": &row.created_at,
+                ":resolved_at": &row.resolved_at,
+            },
+        )?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    pub fn find(conn: &Connection, id: i64) -> AppResult<Option<FocusIdleAdjustment>> {
+        let sql =
+            format!("SELECT {IDLE_ADJUSTMENT_COLUMNS} FROM focus_idle_adjustments WHERE id = ?1");
+        conn.query_row(&sql, [id], |row| FocusIdleAdjustmentRow::try_from(row))
+            .optional()?
+            .map(|row| row.into_record())
+            .transpose()
+    }
+
+    pub fn list_pending(conn: &Connection) -> AppResult<Vec<FocusIdleAdjustment>> {
+        let sql = format!(
+            "SELECT {IDLE_ADJUSTMENT_COLUMNS} FROM focus_idle_adjustments
+             WHERE status = 'pending' ORDER BY session_started_at ASC"
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let records = stmt
+            .query_map([], |row| FocusIdleAdjustmentRow::try_from(row))?
+            .map(|row| {
+                row.map_err(AppError::from)
+                    .and_then(|row| row.into_record())
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(records)
+    }
+
+    pub fn update_status(
+        conn: &Connection,
+        id: i64,
+        status: IdleAdjustmentStatus,
+        resolved_at: &str,
+    ) -> AppResult<()> {
+        let affected = conn.execute(
+            "UPDATE focus_idle_adjustments SET status = :status, resolved_at = :resolved_at
+             WHERE id = :id",
+            named_params! {
+                ":id": id,
+                ":status": status.as_str(),
+                ":resolved_at": resolved_at,
+            },
+        )?;
+
+        if affected == 0 {
+            return Err(AppError::not_found());
+        }
+
+        Ok(())
+    }
+
+    /// Confirmed idle minutes of sessions started within `[start, end)`.
+    pub fn confirmed_minutes_between(conn: &Connection, start: &str, end: &str) -> AppResult<i64> {
+        let minutes = conn.query_row(
+            r#"
+                SELECT COALESCE(SUM(idle_minutes), 0)
+                FROM focus_idle_adjustments
+                WHERE status = 'confirmed'
+                  AND julianday(session_started_at) >= julianday(?1)
+                  AND julianday(session_started_at) < julianday(?2)
+            "#,
+            [start, end],
+            |row| row.get(0),
+        )?;
+
+        Ok(minutes)
+    }
+
+    /// Confirmed idle minutes per UTC day (`YYYY-MM-DD`) between `start` and
+    /// `end`, inclusive.
+    pub fn confirmed_minutes_by_day(
+        conn: &Connection,
+        start: &NaiveDate,
+        end: &NaiveDate,
+    ) -> AppResult<Vec<(String, i64)>> {
+        let mut stmt = conn.prepare(
+            r#"
+                SELECT date(session_started_at) AS day, SUM(idle_minutes) AS minutes
+                FROM focus_idle_adjustments
+                WHERE status = 'confirmed'
+                  AND date(session_started_at) BETWEEN :start AND :end
+                GROUP BY day
+            "#,
+        )?;
+
+        let rows = stmt
+            .query_map(
+                named_params! {":start": start.to_string(), ":end": end.to_string()},
+                |row| Ok((row.get("day")?, row.get("minutes")?)),
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+}
@@ -15,6 +15,9 @@ pub struct WorkloadForecastRow {
     pub capacity_threshold: f64,
     pub contributing_tasks: String,
     pub confidence: f64,
+    pub low_hours: Option<f64>,
+    pub high_hours: Option<f64>,
+    pub buckets: String,
 }
 
 impl WorkloadForecastRow {
@@ -27,9 +30,14 @@ impl WorkloadForecastRow {
             capacity_threshold: record.capacity_threshold,
             contributing_tasks: serialize_json(&record.contributing_tasks)?,
             confidence: record.confidence,
+            low_hours: Some(record.low_hours),
+            high_hours: Some(record.high_hours),
+            buckets: serialize_json(&record.buckets)?,
         })
     }
 
+    /// Rows written before intervals were stored fall back to the point
+    /// estimate on both bounds.
     pub fn into_record(self) -> AppResult<WorkloadForecastRecord> {
         Ok(WorkloadForecastRecord {
             horizon: WorkloadHorizon::try_from(self.horizon.as_str())
@@ -41,6 +49,9 @@ impl WorkloadForecastRow {
             capacity_threshold: self.capacity_threshold,
             contributing_tasks: deserialize_json(&self.contributing_tasks)?,
             confidence: self.confidence,
+            low_hours: self.low_hours.unwrap_or(self.total_hours),
+            high_hours: self.high_hours.unwrap_or(self.total_hours),
+            buckets: deserialize_json(&self.buckets)?,
         })
     }
 }
@@ -57,6 +68,9 @@ impl TryFrom<&Row<'_>> for WorkloadForecastRow {
             capacity_threshold: row.get("capacity_threshold")?,
             contributing_tasks: row.get("contributing_tasks")?,
             confidence: row.get("confidence")?,
+            low_hours: row.get("low_hours")?,
+            high_hours: row.get("high_hours")?,
+            buckets: row.get("buckets")?,
         })
     }
 }
@@ -76,7 +90,10 @@ impl WorkloadRepository {
                     total_hours,
                     capacity_threshold,
                     contributing_tasks,
-                    confidence
+                    confidence,
+                    low_hours,
+                    high_hours,
+                    buckets
                 ) VALUES (
                     :horizon,
                     :generated_at,
@@ -84,14 +101,20 @@ impl WorkloadRepository {
                     :total_hours,
                     :capacity_threshold,
                     :contributing_tasks,
-                    :confidence
+                    :confidence,
+                    :low_hours,
+                    :high_hours,
+                    :buckets
                 )
                 ON CONFLICT(horizon, generated_at) DO UPDATE SET
                     risk_level = excluded.risk_level,
                     total_hours = excluded.total_hours,
                     capacity_threshold = excluded.capacity_threshold,
                     contributing_tasks = excluded.contributing_tasks,
-                    confidence = excluded.confidence
+                    confidence = excluded.confidence,
+                    low_hours = excluded.low_hours,
+                    high_hours = excluded.high_hours,
+                    buckets = excluded.buckets
             "#,
             named_params! {
                 ":horizon": &row.horizon,
@@ -101,6 +124,9 @@ impl WorkloadRepository {
                 ":capacity_threshold": &row.capacity_threshold,
                 ":contributing_tasks": &row.contributing_tasks,
                 ":confidence": &row.confidence,
+                ":low_hours": &row.low_hours,
+                ":high_hours": &row.high_hours,
+                ":buckets": &row.buckets,
             },
         )?;
 
@@ -120,7 +146,10 @@ impl WorkloadRepository {
                     total_hours,
                     capacity_threshold,
                     contributing_tasks,
-                    confidence
+                    confidence,
+                    low_hours,
+                    high_hours,
+                    buckets
                 FROM workload_forecasts
                 WHERE horizon = :horizon
                 ORDER BY generated_at DESC
@@ -151,7 +180,10 @@ impl WorkloadRepository {
                     total_hours,
                     capacity_threshold,
                     contributing_tasks,
-                    confidence
+                    confidence,
+                    low_hours,
+                    high_hours,
+                    buckets
                 FROM workload_forecasts
                 WHERE horizon = :horizon
                 ORDER BY generated_at DESC
@@ -29,6 +29,11 @@ CREATE TABLE IF NOT EXISTS tasks (
     ai_source TEXT,
     ai_generated_at TEXT,
     external_links TEXT,
+    project_id TEXT,
+    snoozed_until TEXT,
+    snooze_notify INTEGER NOT NULL DEFAULT 0,
+    color TEXT,
+    icon TEXT,
     created_at TEXT NOT NULL,
     updated_at TEXT NOT NULL
 );
@@ -58,6 +63,7 @@ CREATE TABLE IF NOT EXISTS planning_sessions (
     status TEXT NOT NULL,
     selected_option_id TEXT,
     personalization_snapshot TEXT,
+    project_id TEXT,
     created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
     updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
 );
@@ -102,6 +108,43 @@ CREATE INDEX IF NOT EXISTS idx_planning_time_blocks_option_id ON planning_time_b
 CREATE INDEX IF NOT EXISTS idx_planning_time_blocks_task_id ON planning_time_blocks(task_id);
 CREATE INDEX IF NOT EXISTS idx_planning_time_blocks_status ON planning_time_blocks(status);
 
+CREATE TABLE IF NOT EXISTS block_reminders (
+    id TEXT PRIMARY KEY,
+    block_id TEXT NOT NULL UNIQUE,
+    task_id TEXT NOT NULL,
+    start_at TEXT NOT NULL,
+    remind_at TEXT NOT NULL,
+    lead_minutes INTEGER NOT NULL,
+    status TEXT NOT NULL DEFAULT 'scheduled',
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (block_id) REFERENCES planning_time_blocks(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_block_reminders_status_remind_at
+    ON block_reminders(status, remind_at);
+
+CREATE TABLE IF NOT EXISTS agent_suggestions (
+    id TEXT PRIMARY KEY,
+    kind TEXT NOT NULL,
+    title TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    task_id TEXT,
+    target_date TEXT,
+    payload TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    suggested_for TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    resolved_at TEXT,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_agent_suggestions_suggested_for
+    ON agent_suggestions(suggested_for);
+CREATE INDEX IF NOT EXISTS idx_agent_suggestions_status
+    ON agent_suggestions(status);
+
 CREATE TABLE IF NOT EXISTS schedule_preferences (
     id TEXT PRIMARY KEY,
     data TEXT NOT NULL,
@@ -124,12 +167,85 @@ CREATE TABLE IF NOT EXISTS analytics_snapshots (
     focus_consistency REAL NOT NULL DEFAULT 0,
     rest_balance REAL NOT NULL DEFAULT 0,
     capacity_risk REAL NOT NULL DEFAULT 0,
+    context_switches INTEGER NOT NULL DEFAULT 0,
     created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
 );
 
 CREATE INDEX IF NOT EXISTS idx_analytics_snapshots_created_at
     ON analytics_snapshots(created_at);
 
+CREATE TABLE IF NOT EXISTS analytics_anomalies (
+    id TEXT PRIMARY KEY,
+    snapshot_date TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    observed REAL NOT NULL,
+    baseline REAL NOT NULL,
+    detail TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_analytics_anomalies_snapshot_date
+    ON analytics_anomalies(snapshot_date);
+
+-- Per-day completion, due, focus and overdue counts behind the overview.
+-- Triggers mark days stale when their inputs change; stale days are
+-- recomputed when next read.
+CREATE TABLE IF NOT EXISTS analytics_daily_snapshots (
+    snapshot_date TEXT PRIMARY KEY,
+    completed INTEGER NOT NULL DEFAULT 0,
+    due INTEGER NOT NULL DEFAULT 0,
+    focus_minutes INTEGER NOT NULL DEFAULT 0,
+    overdue INTEGER NOT NULL DEFAULT 0,
+    stale INTEGER NOT NULL DEFAULT 0,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TRIGGER IF NOT EXISTS trg_tasks_daily_aggregates_insert
+    AFTER INSERT ON tasks
+BEGIN
+    UPDATE analytics_daily_snapshots SET stale = 1
+    WHERE snapshot_date >= MIN(COALESCE(date(NEW.due_at), '9999-12-31'), COALESCE(date(NEW.completed_at), '9999-12-31'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_tasks_daily_aggregates_update
+    AFTER UPDATE ON tasks
+    WHEN OLD.due_at IS NOT NEW.due_at OR OLD.completed_at IS NOT NEW.completed_at
+BEGIN
+    UPDATE analytics_daily_snapshots SET stale = 1
+    WHERE snapshot_date >= MIN(COALESCE(date(OLD.due_at), '9999-12-31'), COALESCE(date(OLD.completed_at), '9999-12-31'), COALESCE(date(NEW.due_at), '9999-12-31'), COALESCE(date(NEW.completed_at), '9999-12-31'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_tasks_daily_aggregates_delete
+    AFTER DELETE ON tasks
+BEGIN
+    UPDATE analytics_daily_snapshots SET stale = 1
+    WHERE snapshot_date >= MIN(COALESCE(date(OLD.due_at), '9999-12-31'), COALESCE(date(OLD.completed_at), '9999-12-31'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_planning_time_blocks_daily_aggregates_insert
+    AFTER INSERT ON planning_time_blocks
+BEGIN
+    UPDATE analytics_daily_snapshots SET stale = 1
+    WHERE snapshot_date = date(COALESCE(NEW.actual_start_at, NEW.start_at));
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_planning_time_blocks_daily_aggregates_update
+    AFTER UPDATE ON planning_time_blocks
+    WHEN OLD.start_at IS NOT NEW.start_at OR OLD.end_at IS NOT NEW.end_at
+        OR OLD.actual_start_at IS NOT NEW.actual_start_at
+        OR OLD.actual_end_at IS NOT NEW.actual_end_at
+BEGIN
+    UPDATE analytics_daily_snapshots SET stale = 1
+    WHERE snapshot_date IN (date(COALESCE(OLD.actual_start_at, OLD.start_at)), date(COALESCE(NEW.actual_start_at, NEW.start_at)));
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_planning_time_blocks_daily_aggregates_delete
+    AFTER DELETE ON planning_time_blocks
+BEGIN
+    UPDATE analytics_daily_snapshots SET stale = 1
+    WHERE snapshot_date = date(COALESCE(OLD.actual_start_at, OLD.start_at));
+END;
+
 CREATE TABLE IF NOT EXISTS app_settings (
     key TEXT PRIMARY KEY,
     value TEXT NOT NULL,
@@ -184,6 +300,9 @@ CREATE TABLE IF NOT EXISTS workload_forecasts (
     capacity_threshold REAL NOT NULL,
     contributing_tasks TEXT NOT NULL,
     confidence REAL NOT NULL,
+    low_hours REAL,
+    high_hours REAL,
+    buckets TEXT NOT NULL DEFAULT '[]',
     PRIMARY KEY (horizon, generated_at)
 );
 
@@ -193,7 +312,7 @@ CREATE INDEX IF NOT EXISTS idx_workload_forecasts_generated_at
 CREATE TABLE IF NOT EXISTS wellness_events (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     window_start TEXT NOT NULL,
-    trigger_reason TEXT NOT NULL CHECK(trigger_reason IN ('focus_streak', 'work_streak')),
+    trigger_reason TEXT NOT NULL CHECK(trigger_reason IN ('focus_streak', 'work_streak', 'wind_down')),
     recommended_break_minutes INTEGER NOT NULL,
     suggested_micro_task TEXT,
     response TEXT CHECK(response IN ('completed', 'snoozed', 'ignored')),
@@ -347,8 +466,12 @@ CREATE TABLE IF NOT EXISTS goals (
     status TEXT DEFAULT 'not_started',
     priority TEXT DEFAULT 'medium',
     target_date TEXT,
+    project_id TEXT,
     created_at TEXT NOT NULL,
     updated_at TEXT NOT NULL,
+    checkin_cadence TEXT,
+    next_checkin_at TEXT,
+    checkin_reminded_at TEXT,
     FOREIGN KEY (parent_goal_id) REFERENCES goals(id) ON DELETE CASCADE
 );
 
@@ -377,3 +500,219 @@ CREATE INDEX IF NOT EXISTS idx_goal_task_associations_goal_id
     ON goal_task_associations(goal_id);
 CREATE INDEX IF NOT EXISTS idx_goal_task_associations_task_id 
     ON goal_task_associations(task_id);
+
+-- Progress check-ins recorded against a goal
+CREATE TABLE IF NOT EXISTS goal_checkins (
+    id TEXT PRIMARY KEY,
+    goal_id TEXT NOT NULL,
+    note TEXT,
+    progress REAL NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (goal_id) REFERENCES goals(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_goal_checkins_goal_id_created_at
+    ON goal_checkins(goal_id, created_at);
+
+-- Day logs written by the end-of-day close ritual
+CREATE TABLE IF NOT EXISTS day_logs (
+    log_date TEXT PRIMARY KEY,
+    closed_at TEXT NOT NULL,
+    reflection TEXT,
+    completed_tasks INTEGER NOT NULL DEFAULT 0,
+    unfinished_blocks INTEGER NOT NULL DEFAULT 0,
+    decisions TEXT NOT NULL DEFAULT '[]',
+    productivity_score REAL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+-- Later list: articles, videos and courses consumed in spare time
+CREATE TABLE IF NOT EXISTS later_items (
+    id TEXT PRIMARY KEY,
+    title TEXT NOT NULL,
+    url TEXT,
+    kind TEXT NOT NULL DEFAULT 'other',
+    estimated_minutes INTEGER NOT NULL,
+    notes TEXT,
+    status TEXT NOT NULL DEFAULT 'queued',
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    completed_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_later_items_status
+    ON later_items(status, created_at);
+
+-- Declarative agent tools registered at runtime
+CREATE TABLE IF NOT EXISTS custom_tools (
+    name TEXT PRIMARY KEY,
+    description TEXT NOT NULL,
+    parameters TEXT NOT NULL,
+    target_tool TEXT NOT NULL,
+    fixed_arguments TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+-- User overrides for AI system prompts
+CREATE TABLE IF NOT EXISTS prompt_overrides (
+    prompt_key TEXT PRIMARY KEY,
+    template TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+-- Opt-in log of redacted AI requests and responses
+CREATE TABLE IF NOT EXISTS ai_debug_log (
+    correlation_id TEXT PRIMARY KEY,
+    operation TEXT NOT NULL,
+    request TEXT NOT NULL,
+    response TEXT,
+    error TEXT,
+    latency_ms INTEGER,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_ai_debug_log_created_at
+    ON ai_debug_log(created_at);
+
+-- Failed background work retried with exponential backoff
+CREATE TABLE IF NOT EXISTS background_jobs (
+    id TEXT PRIMARY KEY,
+    kind TEXT NOT NULL,
+    payload TEXT NOT NULL DEFAULT '{}',
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL,
+    next_attempt_at TEXT NOT NULL,
+    last_error TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_background_jobs_due
+    ON background_jobs(status, next_attempt_at);
+
+-- Projects grouping tasks and goals
+CREATE TABLE IF NOT EXISTS projects (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'active',
+    color TEXT,
+    icon TEXT,
+    default_tags TEXT NOT NULL DEFAULT '[]',
+    archived_at TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+-- Latest revision per changed entity, kept by triggers for delta sync.
+-- Triggers delete then insert: an UPSERT in the outer statement would
+-- override INSERT OR REPLACE here.
+CREATE TABLE IF NOT EXISTS entity_changes (
+    revision INTEGER PRIMARY KEY AUTOINCREMENT,
+    entity TEXT NOT NULL,
+    entity_id TEXT NOT NULL,
+    deleted INTEGER NOT NULL DEFAULT 0,
+    changed_at TEXT NOT NULL,
+    UNIQUE(entity, entity_id)
+);
+
+CREATE TRIGGER IF NOT EXISTS trg_tasks_change_insert
+    AFTER INSERT ON tasks
+BEGIN
+    DELETE FROM entity_changes WHERE entity = 'task' AND entity_id = NEW.id;
+    INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+    VALUES ('task', NEW.id, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_tasks_change_update
+    AFTER UPDATE ON tasks
+BEGIN
+    DELETE FROM entity_changes WHERE entity = 'task' AND entity_id = NEW.id;
+    INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+    VALUES ('task', NEW.id, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_tasks_change_delete
+    AFTER DELETE ON tasks
+BEGIN
+    DELETE FROM entity_changes WHERE entity = 'task' AND entity_id = OLD.id;
+    INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+    VALUES ('task', OLD.id, 1, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_projects_change_insert
+    AFTER INSERT ON projects
+BEGIN
+    DELETE FROM entity_changes WHERE entity = 'project' AND entity_id = NEW.id;
+    INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+    VALUES ('project', NEW.id, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_projects_change_update
+    AFTER UPDATE ON projects
+BEGIN
+    DELETE FROM entity_changes WHERE entity = 'project' AND entity_id = NEW.id;
+    INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+    VALUES ('project', NEW.id, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_projects_change_delete
+    AFTER DELETE ON projects
+BEGIN
+    DELETE FROM entity_changes WHERE entity = 'project' AND entity_id = OLD.id;
+    INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+    VALUES ('project', OLD.id, 1, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_goals_change_insert
+    AFTER INSERT ON goals
+BEGIN
+    DELETE FROM entity_changes WHERE entity = 'goal' AND entity_id = NEW.id;
+    INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+    VALUES ('goal', NEW.id, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_goals_change_update
+    AFTER UPDATE ON goals
+BEGIN
+    DELETE FROM entity_changes WHERE entity = 'goal' AND entity_id = NEW.id;
+    INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+    VALUES ('goal', NEW.id, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_goals_change_delete
+    AFTER DELETE ON goals
+BEGIN
+    DELETE FROM entity_changes WHERE entity = 'goal' AND entity_id = OLD.id;
+    INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+    VALUES ('goal', OLD.id, 1, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_planning_time_blocks_change_insert
+    AFTER INSERT ON planning_time_blocks
+    WHEN NEW.applied_at IS NOT NULL
+BEGIN
+    DELETE FROM entity_changes WHERE entity = 'time_block' AND entity_id = NEW.id;
+    INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+    VALUES ('time_block', NEW.id, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_planning_time_blocks_change_update
+    AFTER UPDATE ON planning_time_blocks
+    WHEN NEW.applied_at IS NOT NULL OR OLD.applied_at IS NOT NULL
+BEGIN
+    DELETE FROM entity_changes WHERE entity = 'time_block' AND entity_id = NEW.id;
+    INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+    VALUES ('time_block', NEW.id, NEW.applied_at IS NULL, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_planning_time_blocks_change_delete
+    AFTER DELETE ON planning_time_blocks
+    WHEN OLD.applied_at IS NOT NULL
+BEGIN
+    DELETE FROM entity_changes WHERE entity = 'time_block' AND entity_id = OLD.id;
+    INSERT INTO entity_changes (entity, entity_id, deleted, changed_at)
+    VALUES ('time_block', OLD.id, 1, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
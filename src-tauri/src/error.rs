@@ -20,6 +20,17 @@ pub enum AiErrorCode {
 }
 
 impl AiErrorCode {
+    pub const ALL: [AiErrorCode; 8] = [
+        AiErrorCode::MissingApiKey,
+        AiErrorCode::Forbidden,
+        AiErrorCode::HttpTimeout,
+        AiErrorCode::RateLimited,
+        AiErrorCode::InvalidResponse,
+        AiErrorCode::InvalidRequest,
+        AiErrorCode::DeepseekUnavailable,
+        AiErrorCode::Unknown,
+    ];
+
     pub fn as_str(self) -> &'static str {
         match self {
             AiErrorCode::MissingApiKey => "MISSING_API_KEY",
@@ -32,6 +43,14 @@ impl AiErrorCode {
             AiErrorCode::Unknown => "UNKNOWN_AI_ERROR",
         }
     }
+
+    /// Whether the same request may succeed if sent again later.
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            AiErrorCode::HttpTimeout | AiErrorCode::RateLimited | AiErrorCode::DeepseekUnavailable
+        )
+    }
 }
 
 impl fmt::Display for AiErrorCode {
@@ -45,6 +64,9 @@ pub enum AppError {
     #[error("数据库错误: {message}")]
     Database { message: String },
 
+    #[error("数据库繁忙: {message}")]
+    DatabaseBusy { message: String },
+
     #[error("记录未找到")]
     NotFound,
 
@@ -82,6 +104,24 @@ pub enum AppError {
     #[error("上下文过大: {tokens} tokens (限制: {limit})")]
     ContextTooLarge { tokens: usize, limit: usize },
 
+    #[error("操作已取消")]
+    Cancelled,
+
+    #[error("数据库迁移失败 (v{version}): {message}")]
+    Migration { version: i32, message: String },
+
+    #[error("加密操作失败: {0}")]
+    Crypto(String),
+
+    #[error("外部服务 {service} 调用失败: {message}")]
+    ExternalIntegration { service: String, message: String },
+
+    #[error("请求过于频繁: {message}")]
+    RateLimited {
+        message: String,
+        retry_after_secs: Option<u64>,
+    },
+
     #[error("序列化错误: {0}")]
     Serialization(#[from] serde_json::Error),
 
@@ -197,6 +237,17 @@ impl AppError {
         AppError::Database { message }
     }
 
+    pub fn database_busy(message: impl Into<String>) -> Self {
+        let message = message.into();
+        warn!(target: "app::database", %message, "database busy");
+        AppError::DatabaseBusy { message }
+    }
+
+    /// Whether another connection held the lock past the busy timeout.
+    pub fn is_busy(&self) -> bool {
+        matches!(self, AppError::DatabaseBusy { .. })
+    }
+
     pub fn other(message: impl Into<String>) -> Self {
         let message = message.into();
         error!(target: "app::other", %message, "other error");
@@ -233,6 +284,87 @@ impl AppError {
         warn!(target: "app::context", tokens, limit, "context too large");
         AppError::ContextTooLarge { tokens, limit }
     }
+
+    pub fn cancelled() -> Self {
+        AppError::Cancelled
+    }
+
+    pub fn migration(version: i32, message: impl Into<String>) -> Self {
+        let message = message.into();
+        error!(target: "app::database", version, %message, "migration failed");
+        AppError::Migration { version, message }
+    }
+
+    pub fn crypto(message: impl Into<String>) -> Self {
+        let message = message.into();
+        error!(target: "app::crypto", %message, "crypto error");
+        AppError::Crypto(message)
+    }
+
+    pub fn external_integration(service: impl Into<String>, message: impl Into<String>) -> Self {
+        let service = service.into();
+        let message = message.into();
+        warn!(target: "app::integration", %service, %message, "external integration failed");
+        AppError::ExternalIntegration { service, message }
+    }
+
+    pub fn rate_limited(message: impl Into<String>, retry_after_secs: Option<u64>) -> Self {
+        let message = message.into();
+        warn!(target: "app::rate_limit", %message, ?retry_after_secs, "rate limited");
+        AppError::RateLimited {
+            message,
+            retry_after_secs,
+        }
+    }
+
+    /// Stable code reported to the frontend as `CommandError::code`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Database { .. } => "DATABASE_ERROR",
+            AppError::DatabaseBusy { .. } => "DATABASE_BUSY",
+            AppError::NotFound => "NOT_FOUND",
+            AppError::Conflict { .. } => "CONFLICT",
+            AppError::Validation { .. } => "VALIDATION_ERROR",
+            AppError::Ai { code, .. } => code.as_str(),
+            AppError::MemoryUnavailable(_) => "MEMORY_UNAVAILABLE",
+            AppError::ToolExecutionFailed { .. } => "TOOL_EXECUTION_FAILED",
+            AppError::InvalidToolCall { .. } => "INVALID_TOOL_CALL",
+            AppError::ContextTooLarge { .. } => "CONTEXT_TOO_LARGE",
+            AppError::Cancelled => "CANCELLED",
+            AppError::Migration { .. } => "MIGRATION_FAILED",
+            AppError::Crypto(_) => "CRYPTO_ERROR",
+            AppError::ExternalIntegration { .. } => "EXTERNAL_INTEGRATION_FAILED",
+            AppError::RateLimited { .. } => "RATE_LIMITED",
+            AppError::Serialization(_) => "SERIALIZATION_ERROR",
+            AppError::Io(_) => "IO_ERROR",
+            AppError::Other(_) => "UNKNOWN",
+        }
+    }
+
+    /// Whether retrying the same request later may succeed, so the frontend
+    /// can decide whether to offer a retry button.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            AppError::Ai { code, .. } => code.is_retryable(),
+            AppError::MemoryUnavailable(_)
+            | AppError::Cancelled
+            | AppError::ExternalIntegration { .. }
+            | AppError::RateLimited { .. }
+            | AppError::DatabaseBusy { .. }
+            | AppError::Io(_) => true,
+            AppError::Database { .. }
+            | AppError::NotFound
+            | AppError::Conflict { .. }
+            | AppError::Validation { .. }
+            | AppError::ToolExecutionFailed { .. }
+            | AppError::InvalidToolCall { .. }
+            | AppError::ContextTooLarge { .. }
+            | AppError::Migration { .. }
+            | AppError::Crypto(_)
+            | AppError::Serialization(_)
+            | AppError::Other(_) => false,
+        }
+    }
 }
 
 impl From<rusqlite::Error> for AppError {
@@ -245,6 +377,11 @@ impl From<rusqlite::Error> for AppError {
             SqliteFailure(err, _) if err.code == ErrorCode::ConstraintViolation => {
                 AppError::conflict("违反唯一性或约束限制")
             }
+            SqliteFailure(err, _)
+                if err.code == ErrorCode::DatabaseBusy || err.code == ErrorCode::DatabaseLocked =>
+            {
+                AppError::database_busy(error.to_string())
+            }
             _ => {
                 error!(target: "app::database", error = ?error, "sqlite error");
                 AppError::database(error.to_string())
@@ -16,8 +16,18 @@ pub fn run() {
 }
 
 fn try_run() -> Result<(), Box<dyn std::error::Error>> {
-    tauri::Builder::default()
+    let builder = tauri::Builder::default();
+
+    // Must be the first plugin: a second launch hands its arguments to the
+    // running instance and exits before touching the database.
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
+        crate::utils::single_instance::handle_second_instance(app, argv, cwd);
+    }));
+
+    builder
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .setup(|app| {
             let handle = app.handle();
 
@@ -32,7 +42,8 @@ fn try_run() -> Result<(), Box<dyn std::error::Error>> {
             std::fs::create_dir_all(&data_dir)?;
             data_dir.push("cognical.sqlite");
 
-            let pool = crate::db::DbPool::new(&data_dir)
+            let profile = crate::utils::runtime_profile::RuntimeProfile::detect();
+            let pool = crate::db::DbPool::new_with_profile(&data_dir, profile)
                 .map_err(|err| Box::new(err) as Box<dyn std::error::Error>)?;
 
             // Get app data directory for memory storage
@@ -41,8 +52,31 @@ fn try_run() -> Result<(), Box<dyn std::error::Error>> {
                 .app_data_dir()
                 .map_err(|err| Box::new(err) as Box<dyn std::error::Error>)?;
 
-            let state = crate::commands::AppState::new(pool, app_data_dir)
+            let state = crate::commands::AppState::with_profile(pool, app_data_dir, profile)
                 .map_err(|err| Box::new(err) as Box<dyn std::error::Error>)?;
+            if state.runtime_profile().runs_background_threads() {
+                state
+                    .clipboard()
+                    .ensure_started(handle.clone())
+                    .map_err(|err| Box::new(err) as Box<dyn std::error::Error>)?;
+                state
+                    .suggestions()
+                    .ensure_daily_job(handle.clone())
+                    .map_err(|err| Box::new(err) as Box<dyn std::error::Error>)?;
+                state
+                    .tasks()
+                    .ensure_snooze_waker(handle.clone())
+                    .map_err(|err| Box::new(err) as Box<dyn std::error::Error>)?;
+                state
+                    .reminders()
+                    .ensure_worker(handle.clone())
+                    .map_err(|err| Box::new(err) as Box<dyn std::error::Error>)?;
+                state
+                    .goals()
+                    .ensure_checkin_worker(handle.clone())
+                    .map_err(|err| Box::new(err) as Box<dyn std::error::Error>)?;
+            }
+            state.analytics().attach_notifier(handle.clone());
             app.manage(state);
 
             Ok(())
@@ -56,20 +90,51 @@ fn try_run() -> Result<(), Box<dyn std::error::Error>> {
             crate::commands::analytics::analytics_get_latest_productivity_score,
             crate::commands::analytics::analytics_get_workload_forecast,
             crate::commands::analytics::analytics_get_latest_workload_forecasts,
+            crate::commands::analytics::analytics_defragmentation_suggestions,
+            crate::commands::analytics::analytics_get_day_timeline,
+            crate::commands::analytics::analytics_snapshot_recompute,
+            crate::commands::analytics::analytics_get_score_streak,
+            crate::commands::analytics::analytics_today_snapshot,
+            crate::commands::analytics::analytics_confidence_calibration,
+            crate::commands::analytics::analytics_year_in_review,
             crate::commands::ai_commands::tasks_parse_ai,
             crate::commands::ai_commands::ai_generate_recommendations,
             crate::commands::ai_commands::ai_plan_schedule,
+            crate::commands::ai_commands::ai_extract_action_items,
+            crate::commands::ai_commands::tasks_decompose_ai,
             crate::commands::ai_commands::ai_status,
+            crate::commands::ai_commands::ai_debug_get,
             crate::commands::ai_commands::ai_chat,
             crate::commands::ai_commands::ai_agent_chat,
             crate::commands::ai_commands::memory_search,
             crate::commands::ai_commands::memory_export,
             crate::commands::ai_commands::memory_clear,
+            crate::commands::memory_commands::memory_topics_list,
+            crate::commands::memory_commands::memory_topic_rename,
+            crate::commands::memory_commands::memory_topics_merge,
+            crate::commands::memory_commands::memory_topic_delete,
+            crate::commands::memory_commands::memory_set_pinned,
+            crate::commands::memory_commands::memory_set_ignored,
+            crate::commands::memory_commands::memory_facts_get,
+            crate::commands::memory_commands::memory_facts_set,
+            crate::commands::custom_tools::tools_register_custom,
+            crate::commands::custom_tools::tools_list_custom,
+            crate::commands::custom_tools::tools_unregister_custom,
+            crate::commands::custom_tools::tools_get_allowlist,
+            crate::commands::custom_tools::tools_set_allowlist,
+            crate::commands::custom_tools::tools_set_conversation_allowlist,
             crate::commands::planning::planning_apply,
             crate::commands::planning::planning_generate,
             crate::commands::planning::planning_preferences_get,
             crate::commands::planning::planning_preferences_update,
+            crate::commands::planning::planning_preferences_preview,
+            crate::commands::planning::planning_preference_profiles_list,
+            crate::commands::planning::planning_preference_profile_create,
+            crate::commands::planning::planning_preference_profile_delete,
             crate::commands::planning::planning_resolve_conflict,
+            crate::commands::planning::planning_session_discard,
+            crate::commands::planning::planning_session_get,
+            crate::commands::planning::planning_sessions_list,
             // Removed: recommendations commands - feature deleted
             // crate::commands::planning::recommendations_generate,
             // crate::commands::planning::recommendations_record_decision,
@@ -77,27 +142,87 @@ fn try_run() -> Result<(), Box<dyn std::error::Error>> {
             crate::commands::task::tasks_create,
             crate::commands::task::tasks_update,
             crate::commands::task::tasks_delete,
+            crate::commands::task::tasks_import_commit,
+            crate::commands::task::tasks_shift_dates,
+            crate::commands::task::tasks_snooze,
+            crate::commands::task::tasks_find_similar,
+            crate::commands::task::tasks_stale_report,
+            crate::commands::task::tasks_matrix_snapshot,
             crate::commands::settings::settings_get,
             crate::commands::settings::settings_update,
             crate::commands::settings::settings_clear_api_key,
             crate::commands::settings::dashboard_config_get,
             crate::commands::settings::dashboard_config_update,
+            crate::commands::dashboard::dashboard_widget_today,
+            crate::commands::dashboard::dashboard_widget_deadlines,
+            crate::commands::dashboard::dashboard_widget_goals,
+            crate::commands::dashboard::dashboard_widget_wellness,
+            crate::commands::settings::sleep_schedule_get,
+            crate::commands::settings::sleep_schedule_update,
+            crate::commands::settings::retention_policy_get,
+            crate::commands::settings::retention_policy_update,
+            crate::commands::settings::power_policy_get,
+            crate::commands::settings::power_policy_update,
+            crate::commands::settings::idle_detection_get,
+            crate::commands::settings::idle_detection_update,
+            crate::commands::settings::delegation_settings_get,
+            crate::commands::settings::delegation_settings_update,
+            crate::commands::settings::power_status_get,
+            crate::commands::settings::feedback_opt_outs_get,
+            crate::commands::settings::feedback_opt_outs_update,
+            crate::commands::settings::features_get,
+            crate::commands::settings::features_set,
+            crate::commands::settings::retention_preview,
+            crate::commands::settings::database_encryption_status,
+            crate::commands::settings::database_encryption_enable,
+            crate::commands::settings::database_diagnostics,
             crate::commands::cache::cache_clear_all,
+            crate::commands::data::data_export_all,
+            crate::commands::data::data_erase_all,
+            crate::commands::data::data_import,
+            crate::commands::query::query_run_readonly,
+            crate::commands::query::query_schema,
+            crate::commands::clipboard::clipboard_inspect,
+            crate::commands::clipboard::clipboard_capture,
+            crate::commands::day_close::day_close,
+            crate::commands::day_close::day_log_get,
+            crate::commands::day_close::day_log_list,
+            crate::commands::operations::operation_cancel,
+            crate::commands::jobs::jobs_list,
+            crate::commands::jobs::jobs_run_scheduled,
+            crate::commands::upgrade::app_upgrade_status,
+            crate::commands::upgrade::app_changelog_acknowledge,
+            crate::commands::capacity::capacity_wizard_start,
+            crate::commands::capacity::capacity_wizard_answer,
+            crate::commands::capacity::capacity_wizard_finish,
+            crate::commands::later::later_add,
+            crate::commands::later::later_list,
+            crate::commands::later::later_complete,
+            crate::commands::prompts::prompt_templates_list,
+            crate::commands::prompts::prompt_template_update,
+            crate::commands::prompts::prompt_template_reset,
             crate::commands::wellness::wellness_check_nudge,
             crate::commands::wellness::wellness_get_pending,
             crate::commands::wellness::wellness_respond,
+            crate::commands::wellness::wellness_focus_start,
+            crate::commands::wellness::wellness_focus_end,
+            crate::commands::wellness::wellness_focus_current,
+            crate::commands::wellness::wellness_idle_pending,
+            crate::commands::wellness::wellness_idle_resolve,
             crate::commands::wellness::wellness_get_weekly_summary,
             crate::commands::feedback::feedback_submit,
             crate::commands::feedback::feedback_get_recent,
             crate::commands::feedback::feedback_get_session,
             crate::commands::feedback::feedback_get_weekly_digest,
             crate::commands::feedback::feedback_check_opt_out,
+            crate::commands::feedback::feedback_get_privacy_summary,
             crate::commands::feedback::feedback_purge_all,
             crate::commands::feedback::feedback_get_stats,
             crate::commands::community::community_get_project_info,
             crate::commands::community::community_detect_plugins,
             crate::commands::community::community_generate_export_bundle,
             crate::commands::community::community_save_export_to_file,
+            crate::commands::community::community_read_export_bundle,
             crate::commands::community::community_list_exports,
             crate::commands::goal_commands::create_goal,
             crate::commands::goal_commands::get_goal,
@@ -108,10 +233,34 @@ fn try_run() -> Result<(), Box<dyn std::error::Error>> {
             crate::commands::goal_commands::dissociate_task_from_goal,
             crate::commands::goal_commands::get_goal_tasks,
             crate::commands::goal_commands::get_goal_with_progress,
+            crate::commands::goal_commands::goals_checkin,
+            crate::commands::goal_commands::goals_checkin_history,
+            crate::commands::projects::projects_list,
+            crate::commands::projects::projects_create,
+            crate::commands::projects::projects_update,
+            crate::commands::projects::projects_archive,
+            crate::commands::projects::projects_progress,
+            crate::commands::reminders::reminders_dismiss,
+            crate::commands::reminders::reminders_list_upcoming,
+            crate::commands::suggestions::suggestions_list,
+            crate::commands::suggestions::suggestion_accept,
+            crate::commands::suggestions::suggestion_dismiss,
+            crate::commands::history::history_summarize,
+            crate::commands::timesheet::timesheet_export,
+            crate::commands::ics::ics_export,
+            crate::commands::ics::ics_import,
+            crate::commands::appearance::appearance_options,
+            crate::commands::meta::meta_describe_api,
+            crate::commands::meta::meta_describe_events,
+            crate::commands::sync::sync_changes_since,
+            crate::commands::caldav::caldav_connect,
+            crate::commands::caldav::caldav_sync_now,
+            crate::commands::caldav::caldav_status,
             crate::commands::dependency_commands::get_task_dependencies,
             crate::commands::dependency_commands::get_dependency_graph,
             crate::commands::dependency_commands::get_ready_tasks,
             crate::commands::dependency_commands::add_dependency,
+            crate::commands::dependency_commands::dependency_chain_create,
             crate::commands::dependency_commands::remove_dependency,
             crate::commands::dependency_commands::update_dependency_type,
             crate::commands::dependency_commands::validate_dependency,
@@ -123,6 +272,7 @@ fn try_run() -> Result<(), Box<dyn std::error::Error>> {
             crate::commands::recurring_commands::recurring_template_get,
             crate::commands::recurring_commands::recurring_template_generate_instances,
             crate::commands::recurring_commands::recurring_template_instances,
+            crate::commands::recurring_commands::recurring_get_stats,
             crate::commands::recurring_commands::recurring_task_to_regular,
         ])
         .run(tauri::generate_context!())?;
@@ -1,8 +1,9 @@
 use chrono::{Duration, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "camelCase", default)]
 pub struct TaskParseContext {
     pub timezone: Option<String>,
@@ -11,9 +12,22 @@ pub struct TaskParseContext {
     pub existing_task_id: Option<String>,
     pub metadata: Option<JsonValue>,
     pub user_preferences: Option<JsonValue>,
+    /// Project chosen by the user; parsed tasks always land there.
+    pub project_id: Option<String>,
+    /// Open projects the model may pick from when none is chosen.
+    pub projects: Option<Vec<TaskParseProjectOption>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskParseProjectOption {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub default_tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskParseRequest {
     pub input: String,
@@ -21,7 +35,7 @@ pub struct TaskParseRequest {
     pub context: Option<TaskParseContext>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ParsedTaskPayload {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -56,9 +70,11 @@ pub struct ParsedTaskPayload {
     pub task_type: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub external_links: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskParseAiResult {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -105,7 +121,7 @@ impl Default for TaskParseAiResult {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskFocusModeRecommendation {
     pub pomodoros: u32,
@@ -113,14 +129,14 @@ pub struct TaskFocusModeRecommendation {
     pub recommended_slots: Option<Vec<String>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskEfficiencyPrediction {
     pub expected_hours: f64,
     pub confidence: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskAiReasoningStep {
     #[serde(default)]
@@ -133,14 +149,14 @@ pub struct TaskAiReasoningStep {
     pub outcome: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, Copy, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum TaskAiSource {
     Live,
     Cache,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskParseResponse {
     pub payload: ParsedTaskPayload,
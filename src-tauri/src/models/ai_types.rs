@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value as JsonValue};
 
@@ -9,9 +10,10 @@ use crate::models::ai::{
     TaskAiSource, TaskEfficiencyPrediction, TaskFocusModeRecommendation, TaskParseAiResult,
     TaskParseResponse,
 };
+use crate::utils::tokens::TokenizerProfile;
 
 /// Common input context shared by AI operations.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "camelCase", default)]
 pub struct AiOperationContext {
     pub locale: Option<String>,
@@ -22,7 +24,7 @@ pub struct AiOperationContext {
 }
 
 /// Indicates where a provider response originated.
-#[derive(Debug, Clone, Serialize, Deserialize, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, Copy, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum AiResponseSource {
     Online,
@@ -37,7 +39,7 @@ impl Default for AiResponseSource {
 }
 
 /// Metadata describing the provider that produced a response.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "camelCase", default)]
 pub struct AiProviderMetadata {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -52,8 +54,42 @@ pub struct AiProviderMetadata {
     pub extra: Option<JsonValue>,
 }
 
+/// Limits of the configured chat model, used to budget prompts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AiModelProfile {
+    pub model: String,
+    pub context_window_tokens: usize,
+    pub max_output_tokens: usize,
+    pub tokenizer: TokenizerProfile,
+}
+
+impl AiModelProfile {
+    /// Known limits for DeepSeek models; unknown models get conservative values.
+    pub fn for_model(model: &str) -> Self {
+        let (context_window_tokens, max_output_tokens, tokenizer) = match model {
+            "deepseek-chat" => (65_536, 8_192, TokenizerProfile::DEEPSEEK),
+            "deepseek-reasoner" => (65_536, 32_768, TokenizerProfile::DEEPSEEK),
+            _ => (32_768, 4_096, TokenizerProfile::CONSERVATIVE),
+        };
+
+        Self {
+            model: model.to_string(),
+            context_window_tokens,
+            max_output_tokens,
+            tokenizer,
+        }
+    }
+
+    /// Tokens left for the prompt once room for the answer is reserved.
+    pub fn input_budget(&self) -> usize {
+        self.context_window_tokens
+            .saturating_sub(self.max_output_tokens)
+    }
+}
+
 /// Current connectivity status of the AI substrate.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "camelCase", default)]
 pub struct AiStatusDto {
     pub mode: AiResponseSource,
@@ -65,10 +101,25 @@ pub struct AiStatusDto {
     pub provider: Option<AiProviderMetadata>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub json_repair: Option<JsonRepairStats>,
+}
+
+/// How often malformed provider JSON was repaired during this session.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct JsonRepairStats {
+    pub total_responses: u64,
+    pub parsed_clean: u64,
+    pub repaired_locally: u64,
+    pub re_asked: u64,
+    pub recovered_by_re_ask: u64,
+    pub failed: u64,
+    pub repair_rate: f64,
 }
 
 /// Result type for parsing a natural language task.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ParsedTaskDto {
     pub payload: crate::models::ai::ParsedTaskPayload,
@@ -76,7 +127,7 @@ pub struct ParsedTaskDto {
     pub reasoning: ParsingReasoningDto,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "camelCase", default)]
 pub struct ParsingReasoningDto {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -108,7 +159,7 @@ pub struct ParsingReasoningDto {
 }
 
 /// Placeholder DTO for future recommendation responses.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "camelCase", default)]
 pub struct RecommendationDto {
     pub recommendations: Vec<JsonValue>,
@@ -117,7 +168,7 @@ pub struct RecommendationDto {
 }
 
 /// Placeholder DTO for future schedule planning responses.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "camelCase", default)]
 pub struct SchedulePlanDto {
     pub items: Vec<JsonValue>,
@@ -125,6 +176,135 @@ pub struct SchedulePlanDto {
     pub telemetry: Option<AiProviderMetadata>,
 }
 
+/// Redacted request/response pair kept by the opt-in AI debug log.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AiDebugEntry {
+    pub correlation_id: String,
+    pub operation: String,
+    pub request: JsonValue,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<JsonValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<i64>,
+    pub created_at: String,
+}
+
+/// Raw action item returned by the provider for one chunk of meeting notes.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExtractedActionItemDto {
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_hint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_excerpt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ActionItemsDto {
+    pub items: Vec<ExtractedActionItemDto>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub telemetry: Option<AiProviderMetadata>,
+}
+
+/// Existing task that an extracted action item appears to duplicate.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateTaskRef {
+    pub task_id: String,
+    pub title: String,
+    pub similarity: f64,
+}
+
+/// Reviewable action item; `selected` is pre-cleared for likely duplicates.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionItemCandidate {
+    #[serde(flatten)]
+    pub item: ExtractedActionItemDto,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<DuplicateTaskRef>,
+    pub selected: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionItemExtractionDto {
+    pub items: Vec<ActionItemCandidate>,
+    pub chunk_count: usize,
+    pub duplicate_count: usize,
+    pub generated_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub telemetry: Option<AiProviderMetadata>,
+}
+
+/// Proposed subtask of a decomposed task. `order` is 1-based; `dependsOn`
+/// lists the orders of subtasks that must be finished first.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProposedSubtaskDto {
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_minutes: Option<i64>,
+    pub order: usize,
+    pub depends_on: Vec<usize>,
+}
+
+/// Subtasks proposed for review; nothing is created until the user confirms.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TaskDecompositionDto {
+    pub task_id: String,
+    pub subtasks: Vec<ProposedSubtaskDto>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rationale: Option<String>,
+    /// Sum of the subtask estimates, in minutes.
+    pub total_estimated_minutes: i64,
+    pub generated_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub telemetry: Option<AiProviderMetadata>,
+}
+
+/// AI rewrite of a template-based activity summary.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistoryNarrativeDto {
+    pub narrative: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub telemetry: Option<AiProviderMetadata>,
+}
+
+/// Issues the AI found when reviewing a generated plan.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PlanCritiqueDto {
+    pub issues: Vec<PlanIssueDto>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub telemetry: Option<AiProviderMetadata>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PlanIssueDto {
+    /// `too_dense`, `ignores_constraint` or `other`.
+    pub kind: String,
+    pub message: String,
+}
+
 /// Shared provider contract to support online/offline execution.
 #[async_trait::async_trait]
 pub trait AiProvider: Send + Sync {
@@ -137,6 +317,14 @@ pub trait AiProvider: Send + Sync {
 
     async fn plan_schedule(&self, input: &JsonValue) -> AppResult<SchedulePlanDto>;
 
+    async fn extract_action_items(&self, input: &JsonValue) -> AppResult<ActionItemsDto>;
+
+    async fn decompose_task(&self, input: &JsonValue) -> AppResult<TaskDecompositionDto>;
+
+    async fn summarize_history(&self, input: &JsonValue) -> AppResult<HistoryNarrativeDto>;
+
+    async fn critique_plan(&self, input: &JsonValue) -> AppResult<PlanCritiqueDto>;
+
     async fn ping(&self) -> AppResult<AiProviderMetadata>;
 }
 
@@ -1,7 +1,11 @@
 use chrono::Duration;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+use crate::models::recurring_task::RecurringTaskStats;
+use crate::services::planning_service::ResolveConflictInput;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum AnalyticsGrouping {
     Day,
@@ -17,7 +21,7 @@ impl AnalyticsGrouping {
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
 pub enum AnalyticsRangeKey {
     #[serde(rename = "7d")]
     SevenDays,
@@ -51,7 +55,7 @@ impl Default for AnalyticsRangeKey {
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum AnalyticsExportFormat {
     Markdown,
@@ -73,7 +77,7 @@ impl Default for AnalyticsExportFormat {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AnalyticsQueryParams {
     #[serde(default)]
@@ -84,6 +88,13 @@ pub struct AnalyticsQueryParams {
     pub to: Option<String>,
     #[serde(default)]
     pub grouping: Option<AnalyticsGrouping>,
+    /// Restricts task and focus metrics to one project.
+    #[serde(default)]
+    pub project_id: Option<String>,
+    /// Rebuilds every day from tasks and time blocks instead of reading the
+    /// materialized daily aggregates, bypassing the overview cache.
+    #[serde(default)]
+    pub full_recompute: bool,
 }
 
 impl Default for AnalyticsQueryParams {
@@ -93,11 +104,13 @@ impl Default for AnalyticsQueryParams {
             from: None,
             to: None,
             grouping: None,
+            project_id: None,
+            full_recompute: false,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AnalyticsExportParams {
     pub range: AnalyticsRangeKey,
@@ -107,6 +120,8 @@ pub struct AnalyticsExportParams {
     pub from: Option<String>,
     #[serde(default)]
     pub to: Option<String>,
+    #[serde(default)]
+    pub project_id: Option<String>,
 }
 
 impl Default for AnalyticsExportParams {
@@ -116,11 +131,12 @@ impl Default for AnalyticsExportParams {
             format: AnalyticsExportFormat::Markdown,
             from: None,
             to: None,
+            project_id: None,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TrendPoint {
     pub date: String,
@@ -130,7 +146,7 @@ pub struct TrendPoint {
     pub focus_minutes: i64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TimeAllocationEntry {
     pub label: String,
@@ -138,7 +154,7 @@ pub struct TimeAllocationEntry {
     pub percentage: f64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TimeAllocationTypeEntry {
     #[serde(rename = "type")]
@@ -147,7 +163,7 @@ pub struct TimeAllocationTypeEntry {
     pub percentage: f64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TimeAllocationPriorityEntry {
     pub priority: String,
@@ -155,7 +171,7 @@ pub struct TimeAllocationPriorityEntry {
     pub percentage: f64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TimeAllocationBreakdown {
     #[serde(default)]
@@ -166,7 +182,7 @@ pub struct TimeAllocationBreakdown {
     pub by_status: Vec<TimeAllocationEntry>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct EfficiencySuggestion {
     pub id: String,
@@ -181,7 +197,7 @@ pub struct EfficiencySuggestion {
     pub category: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct InsightCard {
     pub id: String,
@@ -198,7 +214,7 @@ pub struct InsightCard {
     pub source: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AnalyticsEfficiency {
     pub estimate_accuracy: f64,
@@ -208,7 +224,7 @@ pub struct AnalyticsEfficiency {
     pub suggestions: Vec<EfficiencySuggestion>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AnalyticsSummary {
     pub total_completed: i64,
@@ -219,7 +235,7 @@ pub struct AnalyticsSummary {
     pub overdue_tasks: i64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ZeroStateMeta {
     pub is_empty: bool,
@@ -232,14 +248,122 @@ pub struct ZeroStateMeta {
     pub missing_configuration: Option<Vec<String>>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AnalyticsMeta {
     pub generated_at: String,
     pub is_demo: bool,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleStyle {
+    /// Long uninterrupted focus stretches dominate.
+    Maker,
+    Mixed,
+    /// Meetings and short gaps dominate.
+    Manager,
+}
+
+impl ScheduleStyle {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScheduleStyle::Maker => "maker",
+            ScheduleStyle::Mixed => "mixed",
+            ScheduleStyle::Manager => "manager",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MeetingLoadWeek {
+    /// Monday of the ISO week.
+    pub week_start: String,
+    pub meeting_hours: f64,
+    pub focus_hours: f64,
+    pub fragmented_gaps: i64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Default, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MeetingLoadBreakdown {
+    #[serde(default)]
+    pub weeks: Vec<MeetingLoadWeek>,
+    pub meeting_hours: f64,
+    pub focus_hours: f64,
+    /// Gaps shorter than 30 minutes between meetings or focus blocks.
+    pub fragmented_gaps: i64,
+    /// 0 (manager schedule) to 100 (maker schedule).
+    pub maker_score: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schedule_style: Option<ScheduleStyle>,
+}
+
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum DayTimelineEntryKind {
+    Block,
+    Actual,
+    Completion,
+    Nudge,
+    Note,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DayTimelineEntry {
+    pub kind: DayTimelineEntryKind,
+    /// Id of the underlying block, task, wellness event or day log.
+    pub source_id: String,
+    pub start_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_at: Option<String>,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Everything that happened on one day in chronological order.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DayTimeline {
+    pub date: String,
+    pub entries: Vec<DayTimelineEntry>,
+}
+
+/// A proposed block swap that groups interleaved work on the same task.
+/// `resolution` can be sent as-is to `planning_resolve_conflict`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DefragmentationSuggestion {
+    pub id: String,
+    pub date: String,
+    pub task_id: String,
+    pub switches_saved: i64,
+    pub summary: String,
+    pub resolution: ResolveConflictInput,
+}
+
+/// How reliably recurring tasks were completed in the overview range.
+#[derive(Debug, Clone, Serialize, PartialEq, Default, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HabitConsistency {
+    pub tracked_rules: i64,
+    /// Completed share of all due occurrences across rules.
+    pub completion_rate: f64,
+    pub longest_current_streak: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub average_delay_minutes: Option<f64>,
+    #[serde(default)]
+    pub rules: Vec<RecurringTaskStats>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AnalyticsOverview {
     pub range: AnalyticsRangeKey,
@@ -248,13 +372,15 @@ pub struct AnalyticsOverview {
     pub trend: Vec<TrendPoint>,
     pub time_allocation: TimeAllocationBreakdown,
     pub efficiency: AnalyticsEfficiency,
+    pub meeting_load: MeetingLoadBreakdown,
+    pub habits: HabitConsistency,
     #[serde(default)]
     pub insights: Vec<InsightCard>,
     pub zero_state: ZeroStateMeta,
     pub meta: AnalyticsMeta,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AnalyticsHistoryPoint {
     pub date: String,
@@ -265,7 +391,7 @@ pub struct AnalyticsHistoryPoint {
     pub overdue_tasks: i64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AnalyticsHistoryResponse {
     pub range: AnalyticsRangeKey,
@@ -274,7 +400,7 @@ pub struct AnalyticsHistoryResponse {
     pub points: Vec<AnalyticsHistoryPoint>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AnalyticsErrorSummary {
     pub code: String,
@@ -283,7 +409,7 @@ pub struct AnalyticsErrorSummary {
     pub hint: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AnalyticsOverviewResponse {
     pub overview: AnalyticsOverview,
@@ -292,10 +418,12 @@ pub struct AnalyticsOverviewResponse {
     pub error: Option<AnalyticsErrorSummary>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AnalyticsExportResult {
     pub file_path: String,
+    /// SHA-256 of the written file, hex encoded.
+    pub checksum: String,
     pub format: AnalyticsExportFormat,
     pub generated_at: String,
     pub is_demo: bool,
@@ -318,5 +446,188 @@ pub struct AnalyticsSnapshotRecord {
     pub focus_consistency: f64,
     pub rest_balance: f64,
     pub capacity_risk: f64,
+    /// Adjacent applied blocks that switch to an unrelated task.
+    pub context_switches: i64,
     pub created_at: String,
 }
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalyticsAnomalyKind {
+    /// Completion rate far below the trailing average.
+    CompletionDrop,
+    /// Overdue count well above the trailing average.
+    OverdueSpike,
+}
+
+impl AnalyticsAnomalyKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnalyticsAnomalyKind::CompletionDrop => "completion_drop",
+            AnalyticsAnomalyKind::OverdueSpike => "overdue_spike",
+        }
+    }
+
+    pub fn headline(&self) -> &'static str {
+        match self {
+            AnalyticsAnomalyKind::CompletionDrop => "完成率骤降",
+            AnalyticsAnomalyKind::OverdueSpike => "逾期任务激增",
+        }
+    }
+}
+
+impl TryFrom<&str> for AnalyticsAnomalyKind {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "completion_drop" => Ok(AnalyticsAnomalyKind::CompletionDrop),
+            "overdue_spike" => Ok(AnalyticsAnomalyKind::OverdueSpike),
+            other => Err(format!("unsupported anomaly kind: {other}")),
+        }
+    }
+}
+
+/// A daily snapshot that deviates sharply from the days before it, flagged
+/// by the nightly job. Payload of `analytics://anomaly`.
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyticsAnomaly {
+    pub id: String,
+    pub kind: AnalyticsAnomalyKind,
+    pub snapshot_date: String,
+    pub observed: f64,
+    /// Trailing average the snapshot was compared against.
+    pub baseline: f64,
+    pub detail: String,
+    pub created_at: String,
+}
+
+/// Summary of one calendar year, returned by `analytics_year_in_review`
+/// together with a Markdown rendering of the same data.
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct YearInReview {
+    pub year: i32,
+    pub totals: YearInReviewTotals,
+    /// Weeks with the most focus time, busiest first.
+    pub busiest_weeks: Vec<YearInReviewWeek>,
+    /// Projects with the most completed tasks.
+    pub top_projects: Vec<YearInReviewProject>,
+    /// Estimate accuracy (0-1) for each month with finished, planned work.
+    pub estimate_accuracy: Vec<YearInReviewMonthValue>,
+    pub wellness: Vec<YearInReviewWellnessMonth>,
+    /// Days with the most focus time and no meetings.
+    pub focus_days: Vec<YearInReviewFocusDay>,
+    pub markdown: String,
+    pub generated_at: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct YearInReviewTotals {
+    pub tasks_completed: i64,
+    pub focus_minutes: i64,
+    /// Days with at least one completed task or focus block.
+    pub active_days: i64,
+    /// Average over the days that still have a stored snapshot.
+    pub average_productivity_score: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct YearInReviewWeek {
+    /// Monday of the week, `YYYY-MM-DD`.
+    pub week_start: String,
+    pub focus_minutes: i64,
+    pub tasks_completed: i64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct YearInReviewProject {
+    pub project_id: String,
+    pub name: String,
+    pub tasks_completed: i64,
+    pub focus_minutes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct YearInReviewMonthValue {
+    pub month: u32,
+    pub value: f64,
+    pub samples: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct YearInReviewWellnessMonth {
+    pub month: u32,
+    pub nudges: i64,
+    pub breaks_taken: i64,
+    pub snoozed: i64,
+    pub ignored: i64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct YearInReviewFocusDay {
+    pub date: String,
+    pub focus_minutes: i64,
+}
+
+/// Live metrics for the current local day, computed on request rather than
+/// from the nightly snapshot.
+#[derive(Debug, Clone, Default, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TodaySnapshot {
+    /// Local date, `YYYY-MM-DD`.
+    pub date: String,
+    pub computed_at: String,
+    pub completed_so_far: i64,
+    /// Tasks due today that are not completed yet.
+    pub open_due_today: i64,
+    pub overdue: i64,
+    /// Elapsed minutes of today's applied blocks, less confirmed idle time.
+    pub focus_minutes_so_far: i64,
+    /// Minutes of open blocks still ahead today.
+    pub remaining_planned_minutes: i64,
+    pub remaining_blocks: i64,
+    /// Minutes into the block running now; 0 between blocks.
+    pub current_focus_minutes: i64,
+    /// Start of the first block that has begun today.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_focus_at: Option<String>,
+}
+
+/// Realized outcomes of applied blocks whose confidence fell in
+/// `[min_confidence, max_confidence)`.
+#[derive(Debug, Clone, Default, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CalibrationBucket {
+    pub min_confidence: f64,
+    pub max_confidence: f64,
+    pub samples: u32,
+    /// Blocks whose task was finished on time.
+    pub hits: u32,
+    pub mean_confidence: f64,
+    /// `None` until the bucket has samples.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hit_rate: Option<f64>,
+}
+
+/// How well block confidences predicted whether the planned work got done,
+/// over the settled blocks of the last `window_days`.
+#[derive(Debug, Clone, Default, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfidenceCalibration {
+    pub generated_at: String,
+    pub window_days: i64,
+    pub samples: u32,
+    pub hits: u32,
+    /// Mean squared error of confidence against outcome; lower is better.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub brier_score: Option<f64>,
+    pub buckets: Vec<CalibrationBucket>,
+}
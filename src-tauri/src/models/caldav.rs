@@ -0,0 +1,169 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Local entities mirrored to a CalDAV calendar.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CalDavItemKind {
+    /// Tasks, stored remotely as VTODO.
+    Task,
+    /// Applied planning blocks, stored remotely as VEVENT.
+    TimeBlock,
+}
+
+impl CalDavItemKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CalDavItemKind::Task => "task",
+            CalDavItemKind::TimeBlock => "time_block",
+        }
+    }
+
+    /// iCalendar component the kind is stored as.
+    pub fn component(&self) -> &'static str {
+        match self {
+            CalDavItemKind::Task => "VTODO",
+            CalDavItemKind::TimeBlock => "VEVENT",
+        }
+    }
+}
+
+impl fmt::Display for CalDavItemKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for CalDavItemKind {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "task" => Ok(CalDavItemKind::Task),
+            "time_block" => Ok(CalDavItemKind::TimeBlock),
+            other => Err(format!("unsupported caldav item kind: {other}")),
+        }
+    }
+}
+
+/// Which side wins when an item changed both locally and on the server
+/// since the last sync.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CalDavConflictPolicy {
+    /// The side modified last wins; remote wins when the local time is
+    /// unknown.
+    #[default]
+    NewestWins,
+    PreferLocal,
+    PreferRemote,
+}
+
+impl CalDavConflictPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CalDavConflictPolicy::NewestWins => "newest_wins",
+            CalDavConflictPolicy::PreferLocal => "prefer_local",
+            CalDavConflictPolicy::PreferRemote => "prefer_remote",
+        }
+    }
+}
+
+impl TryFrom<&str> for CalDavConflictPolicy {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "newest_wins" => Ok(CalDavConflictPolicy::NewestWins),
+            "prefer_local" => Ok(CalDavConflictPolicy::PreferLocal),
+            "prefer_remote" => Ok(CalDavConflictPolicy::PreferRemote),
+            other => Err(format!("unsupported caldav conflict policy: {other}")),
+        }
+    }
+}
+
+/// Account to connect. Calendars are discovered from `server_url` unless
+/// given explicitly.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CalDavConnectInput {
+    /// e.g. `https://cloud.example.com/remote.php/dav`,
+    /// `https://caldav.fastmail.com/dav/` or `https://caldav.icloud.com/`.
+    pub server_url: String,
+    pub username: String,
+    /// App password; stored encrypted with the system keychain.
+    pub password: String,
+    #[serde(default)]
+    pub task_calendar_url: Option<String>,
+    #[serde(default)]
+    pub event_calendar_url: Option<String>,
+    #[serde(default)]
+    pub conflict_policy: Option<CalDavConflictPolicy>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CalDavStatus {
+    pub connected: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Calendar receiving tasks; `None` when the server has none for VTODO.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_calendar_url: Option<String>,
+    /// Calendar receiving applied time blocks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_calendar_url: Option<String>,
+    pub conflict_policy: CalDavConflictPolicy,
+    pub syncing: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_sync_at: Option<String>,
+    /// Failure of the last sync, cleared by the next successful one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    pub synced_tasks: u32,
+    pub synced_time_blocks: u32,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CalDavSyncCounts {
+    /// Created or updated on the server.
+    pub pushed: u32,
+    /// Created or updated locally from the server.
+    pub pulled: u32,
+    pub deleted_remote: u32,
+    pub deleted_local: u32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CalDavConflictWinner {
+    Local,
+    Remote,
+}
+
+/// An item changed on both sides since the last sync.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CalDavConflict {
+    pub kind: CalDavItemKind,
+    pub local_id: String,
+    pub href: String,
+    pub winner: CalDavConflictWinner,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CalDavSyncReport {
+    pub started_at: String,
+    pub finished_at: String,
+    pub tasks: CalDavSyncCounts,
+    pub time_blocks: CalDavSyncCounts,
+    pub conflicts: Vec<CalDavConflict>,
+    /// Items skipped because the server rejected them; the rest still
+    /// synced.
+    pub errors: Vec<String>,
+}
@@ -0,0 +1,127 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::services::planning_service::PlanningSessionView;
+
+/// A fixed weekly commitment (class, standup, childcare) the planner must
+/// keep free. Times are local.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WeeklyCommitment {
+    pub title: String,
+    /// ISO weekday, 1 = Monday through 7 = Sunday.
+    pub weekday: u8,
+    pub start_minute: u32,
+    pub end_minute: u32,
+}
+
+impl WeeklyCommitment {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.title.trim().is_empty() {
+            return Err("固定安排需要名称".to_string());
+        }
+        if !(1..=7).contains(&self.weekday) {
+            return Err("星期需在 1（周一）到 7（周日）之间".to_string());
+        }
+        if self.start_minute >= self.end_minute || self.end_minute > 24 * 60 {
+            return Err(format!("「{}」的时间段无效", self.title.trim()));
+        }
+        Ok(())
+    }
+}
+
+/// Weekly capacity the user set up in the capacity wizard.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CapacityProfile {
+    #[serde(default)]
+    pub commitments: Vec<WeeklyCommitment>,
+    #[serde(default)]
+    pub weekly_focus_hours: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_updated_at: Option<String>,
+}
+
+/// Wizard steps in the order they are asked.
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum CapacityWizardStep {
+    Commitments,
+    FocusHours,
+    Priorities,
+    /// Every question is answered; `capacity_wizard_finish` writes the result.
+    Review,
+}
+
+impl CapacityWizardStep {
+    /// The question shown for this step.
+    pub fn prompt(&self) -> &'static str {
+        match self {
+            CapacityWizardStep::Commitments => "每周有哪些固定安排（课程、例会、接送等）？",
+            CapacityWizardStep::FocusHours => "每周希望投入多少小时专注工作？通常在什么时段？",
+            CapacityWizardStep::Priorities => "本周最重要的任务是哪些？按优先顺序选择",
+            CapacityWizardStep::Review => "确认以上信息后生成本周计划",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(tag = "step", rename_all = "snake_case")]
+pub enum CapacityWizardAnswer {
+    Commitments {
+        #[serde(default)]
+        commitments: Vec<WeeklyCommitment>,
+    },
+    #[serde(rename_all = "camelCase")]
+    FocusHours {
+        weekly_focus_hours: u32,
+        #[serde(default)]
+        focus_start_minute: Option<u32>,
+        #[serde(default)]
+        focus_end_minute: Option<u32>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Priorities { task_ids: Vec<String> },
+}
+
+impl CapacityWizardAnswer {
+    pub fn step(&self) -> CapacityWizardStep {
+        match self {
+            CapacityWizardAnswer::Commitments { .. } => CapacityWizardStep::Commitments,
+            CapacityWizardAnswer::FocusHours { .. } => CapacityWizardStep::FocusHours,
+            CapacityWizardAnswer::Priorities { .. } => CapacityWizardStep::Priorities,
+        }
+    }
+}
+
+/// Progress through the capacity wizard. Kept in memory until finished.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CapacityWizardState {
+    pub id: String,
+    pub step: CapacityWizardStep,
+    pub prompt: String,
+    pub commitments: Vec<WeeklyCommitment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weekly_focus_hours: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focus_start_minute: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focus_end_minute: Option<u32>,
+    pub priority_task_ids: Vec<String>,
+    pub started_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CapacityWizardResult {
+    pub preference_id: String,
+    pub profile: CapacityProfile,
+    /// Daily focus cap the plan was generated with.
+    pub max_focus_minutes_per_day: i64,
+    /// Pending planning session for the coming week; apply an option to
+    /// schedule it.
+    pub plan: PlanningSessionView,
+}
@@ -0,0 +1,28 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+/// Declarative tool registered at runtime. It exposes its own name and schema
+/// to the agent and delegates to an existing built-in tool.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomToolDefinition {
+    pub name: String,
+    pub description: String,
+    /// JSON Schema for the tool's own parameters
+    pub parameters: JsonValue,
+    /// Built-in tool the call is forwarded to
+    pub target_tool: String,
+    /// Arguments merged over the caller's arguments before forwarding
+    #[serde(default)]
+    pub fixed_arguments: Option<JsonValue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomToolRecord {
+    #[serde(flatten)]
+    pub definition: CustomToolDefinition,
+    pub created_at: String,
+    pub updated_at: String,
+}
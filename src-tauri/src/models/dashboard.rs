@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::models::goal::GoalStatus;
+use crate::models::wellness::{FocusSession, WellnessEventRecord};
+
+/// Data behind one dashboard widget, with how long it may be reused.
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardWidget<T> {
+    pub data: T,
+    pub generated_at: String,
+    pub ttl_seconds: u64,
+    /// Served from the cache rather than recomputed for this call.
+    pub cached: bool,
+}
+
+/// An open task due within the deadline window, or already overdue.
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpcomingDeadline {
+    pub task_id: String,
+    pub title: String,
+    pub due_at: String,
+    pub priority: String,
+    pub status: String,
+    pub overdue: bool,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GoalProgressSummary {
+    pub goal_id: String,
+    pub title: String,
+    pub status: GoalStatus,
+    pub progress_percentage: f32,
+    pub is_on_track: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_date: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub days_until_target: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WellnessWidgetState {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focus_session: Option<FocusSession>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending_nudge: Option<WellnessEventRecord>,
+    /// Idle periods from past focus sessions awaiting confirmation.
+    pub pending_idle_adjustments: usize,
+}
@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Version of the `cognical-data.json` layout inside a full export.
+pub const DATA_EXPORT_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DataExportResult {
+    /// The `.tar.gz` archive holding `cognical-data.json`, `README.md` and
+    /// the raw memory files under `memory/`.
+    pub file_path: String,
+    /// SHA-256 of the archive, hex encoded.
+    pub checksum: String,
+    pub format_version: u32,
+    /// Rows exported per table.
+    pub tables: BTreeMap<String, usize>,
+    pub memory_files: usize,
+    pub generated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DataEraseResult {
+    /// Rows deleted per table.
+    pub tables: BTreeMap<String, usize>,
+    pub memory_files: usize,
+    pub report_files: usize,
+    pub erased_at: String,
+}
+
+/// Group of related tables (or the memory files) restored together.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DataDomain {
+    /// Tasks, their dependencies and recurring templates.
+    Tasks,
+    Projects,
+    /// Goals, their task links and check-ins.
+    Goals,
+    /// Planning sessions, blocks, reminders and preference profiles.
+    Planning,
+    Later,
+    DayLogs,
+    Suggestions,
+    /// Snapshots, anomalies, scores and forecasts.
+    Analytics,
+    Wellness,
+    Feedback,
+    Conversations,
+    /// Custom tools and prompt overrides.
+    Customization,
+    Settings,
+    Memory,
+}
+
+impl DataDomain {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataDomain::Tasks => "tasks",
+            DataDomain::Projects => "projects",
+            DataDomain::Goals => "goals",
+            DataDomain::Planning => "planning",
+            DataDomain::Later => "later",
+            DataDomain::DayLogs => "day_logs",
+            DataDomain::Suggestions => "suggestions",
+            DataDomain::Analytics => "analytics",
+            DataDomain::Wellness => "wellness",
+            DataDomain::Feedback => "feedback",
+            DataDomain::Conversations => "conversations",
+            DataDomain::Customization => "customization",
+            DataDomain::Settings => "settings",
+            DataDomain::Memory => "memory",
+        }
+    }
+}
+
+/// What to do with an imported row whose primary key already exists.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportConflictMode {
+    /// Keep the existing row.
+    #[default]
+    Skip,
+    /// Replace the existing row's columns with the imported ones.
+    Overwrite,
+    /// Insert the imported row under a new id. Rows without an `id` key,
+    /// such as settings or daily snapshots, are skipped instead.
+    Duplicate,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DataImportParams {
+    /// A `.tar.gz` written by the full data export.
+    pub archive_path: String,
+    pub domains: Vec<DataDomain>,
+    #[serde(default)]
+    pub on_conflict: ImportConflictMode,
+    /// Runs the whole import and rolls it back, reporting what would happen.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DataImportEntry {
+    /// Table name, or `memory` for the memory files.
+    pub table: String,
+    pub rows: usize,
+    pub inserted: usize,
+    pub overwritten: usize,
+    pub duplicated: usize,
+    pub skipped: usize,
+    /// Rows that could not be written, including ones referencing data that
+    /// exists neither in the database nor in the import.
+    pub failed: usize,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DataImportReport {
+    pub dry_run: bool,
+    pub format_version: u32,
+    pub on_conflict: ImportConflictMode,
+    pub entries: Vec<DataImportEntry>,
+    /// First problems found, e.g. unknown columns or rows that failed.
+    pub warnings: Vec<String>,
+    pub imported_at: String,
+}
@@ -0,0 +1,98 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::models::productivity::ProductivityScoreRecord;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RolloverAction {
+    Reschedule,
+    Drop,
+    Park,
+}
+
+impl RolloverAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RolloverAction::Reschedule => "reschedule",
+            RolloverAction::Drop => "drop",
+            RolloverAction::Park => "park",
+        }
+    }
+}
+
+impl fmt::Display for RolloverAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for RolloverAction {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "reschedule" => Ok(RolloverAction::Reschedule),
+            "drop" => Ok(RolloverAction::Drop),
+            "park" => Ok(RolloverAction::Park),
+            other => Err(format!("unsupported rollover action: {other}")),
+        }
+    }
+}
+
+/// What the user decided to do with a task left unfinished at day close.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RolloverDecision {
+    pub task_id: String,
+    pub action: RolloverAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reschedule_to: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DayCloseInput {
+    pub date: String,
+    #[serde(default)]
+    pub decisions: Vec<RolloverDecision>,
+    #[serde(default)]
+    pub reflection: Option<String>,
+}
+
+/// Task with unfinished blocks on the closed day that still needs a decision.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RolloverCandidate {
+    pub task_id: String,
+    pub title: String,
+    pub status: String,
+    pub block_ids: Vec<String>,
+    pub planned_minutes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DayLogRecord {
+    pub log_date: String,
+    pub closed_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reflection: Option<String>,
+    pub completed_tasks: i64,
+    pub unfinished_blocks: i64,
+    pub decisions: Vec<RolloverDecision>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub productivity_score: Option<f64>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DayCloseResult {
+    pub log: DayLogRecord,
+    pub pending_rollovers: Vec<RolloverCandidate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<ProductivityScoreRecord>,
+}
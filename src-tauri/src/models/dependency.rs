@@ -1,7 +1,8 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskDependency {
     pub id: String,
@@ -11,7 +12,7 @@ pub struct TaskDependency {
     pub created_at: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum DependencyType {
     FinishToStart,  // A must finish before B can start (default)
@@ -51,7 +52,7 @@ impl std::str::FromStr for DependencyType {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct DependencyCreateInput {
     pub predecessor_id: String,
@@ -59,7 +60,7 @@ pub struct DependencyCreateInput {
     pub dependency_type: Option<DependencyType>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct DependencyValidation {
     pub is_valid: bool,
@@ -68,7 +69,7 @@ pub struct DependencyValidation {
     pub cycle_path: Option<Vec<String>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskNode {
     pub task_id: String,
@@ -78,7 +79,7 @@ pub struct TaskNode {
     pub is_ready: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct DependencyEdge {
     pub id: String,
@@ -87,7 +88,7 @@ pub struct DependencyEdge {
     pub dependency_type: DependencyType,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct DependencyGraph {
     pub nodes: HashMap<String, TaskNode>,
@@ -96,7 +97,7 @@ pub struct DependencyGraph {
     pub critical_path: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct DependencyFilter {
     pub task_ids: Option<Vec<String>>,
@@ -104,7 +105,7 @@ pub struct DependencyFilter {
     pub max_depth: Option<usize>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ReadyTask {
     pub id: String,
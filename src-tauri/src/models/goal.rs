@@ -1,7 +1,8 @@
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Goal {
     pub id: String,
     pub title: String,
@@ -10,11 +11,61 @@ pub struct Goal {
     pub status: GoalStatus,
     pub priority: String,
     pub target_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub project_id: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub checkin_cadence: Option<GoalCheckinCadence>,
+    /// When the next check-in is due; a reminder fires once it passes.
+    #[serde(default)]
+    pub next_checkin_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum GoalCheckinCadence {
+    Weekly,
+    Biweekly,
+}
+
+impl GoalCheckinCadence {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GoalCheckinCadence::Weekly => "weekly",
+            GoalCheckinCadence::Biweekly => "biweekly",
+        }
+    }
+
+    pub fn days(&self) -> i64 {
+        match self {
+            GoalCheckinCadence::Weekly => 7,
+            GoalCheckinCadence::Biweekly => 14,
+        }
+    }
+
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "weekly" => Ok(GoalCheckinCadence::Weekly),
+            "biweekly" => Ok(GoalCheckinCadence::Biweekly),
+            _ => Err(format!("Invalid check-in cadence: {}", s)),
+        }
+    }
+}
+
+/// A progress report on a goal. `progress` is the user's own estimate in
+/// percent, independent of linked task completion.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GoalCheckin {
+    pub id: String,
+    pub goal_id: String,
+    pub note: Option<String>,
+    pub progress: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum GoalStatus {
     NotStarted,
@@ -47,7 +98,7 @@ impl GoalStatus {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GoalTaskAssociation {
     pub id: String,
     pub goal_id: String,
@@ -55,7 +106,7 @@ pub struct GoalTaskAssociation {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct GoalWithProgress {
     #[serde(flatten)]
@@ -68,9 +119,12 @@ pub struct GoalWithProgress {
     pub child_goals: Vec<GoalWithProgress>,
     pub is_on_track: bool,
     pub days_until_target: Option<i64>,
+    pub latest_checkin: Option<GoalCheckin>,
+    /// Extrapolated from the pace between the first and latest check-in.
+    pub projected_completion_date: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GoalMilestone {
     pub goal_id: String,
     pub milestone_name: String,
@@ -79,20 +133,29 @@ pub struct GoalMilestone {
     pub achieved_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CreateGoalRequest {
     pub title: String,
     pub description: Option<String>,
     pub parent_goal_id: Option<String>,
     pub priority: String,
     pub target_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub project_id: Option<String>,
+    #[serde(default)]
+    pub checkin_cadence: Option<GoalCheckinCadence>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct UpdateGoalRequest {
     pub title: Option<String>,
     pub description: Option<String>,
     pub status: Option<GoalStatus>,
     pub priority: Option<String>,
     pub target_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub project_id: Option<String>,
+    /// `Some(None)` turns check-ins off.
+    #[serde(default)]
+    pub checkin_cadence: Option<Option<GoalCheckinCadence>>,
 }
@@ -0,0 +1,64 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Where the narrative of a [`HistorySummary`] came from.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryNarrativeSource {
+    #[default]
+    Template,
+    /// The template narrative rewritten by the AI provider.
+    Ai,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryCompletedTask {
+    pub task_id: String,
+    pub title: String,
+    pub completed_at: String,
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
+    /// Finished after its due date.
+    pub late: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryDayStat {
+    /// `YYYY-MM-DD`
+    pub date: String,
+    pub completed_count: usize,
+    pub focus_minutes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryTagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+/// What got done between two dates, for standups, timesheets and reviews.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HistorySummary {
+    /// `YYYY-MM-DD`, inclusive.
+    pub start_date: String,
+    /// `YYYY-MM-DD`, inclusive.
+    pub end_date: String,
+    pub completed_count: usize,
+    /// Most recent first; capped, see `completed_count` for the total.
+    pub completed_tasks: Vec<HistoryCompletedTask>,
+    /// Minutes in applied time blocks, using tracked times when present.
+    pub focus_minutes: i64,
+    pub days: Vec<HistoryDayStat>,
+    pub top_tags: Vec<HistoryTagCount>,
+    /// Finished goals, archived projects, busiest days and day-close
+    /// reflections within the range.
+    pub notable_events: Vec<String>,
+    pub narrative: String,
+    pub narrative_source: HistoryNarrativeSource,
+    pub generated_at: String,
+}
@@ -0,0 +1,46 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::services::schedule_optimizer::ExistingEvent;
+
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IcsExportInput {
+    /// Export the applied blocks of this session; every applied block when
+    /// omitted.
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IcsExportResult {
+    pub file_path: String,
+    /// SHA-256 of the written file, hex encoded.
+    pub checksum: String,
+    pub event_count: usize,
+    pub generated_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IcsImportInput {
+    /// Contents of the `.ics` file.
+    pub content: String,
+    /// Only keep events overlapping this range (RFC 3339). Recurring events
+    /// are kept when their series starts before `end_at`.
+    #[serde(default)]
+    pub start_at: Option<String>,
+    #[serde(default)]
+    pub end_at: Option<String>,
+}
+
+/// Events ready to be passed as `existingEvents` in planning constraints.
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IcsImportResult {
+    pub events: Vec<ExistingEvent>,
+    /// Events left out: all-day, free, cancelled, outside the range, or with
+    /// a recurrence the planner cannot expand.
+    pub skipped: usize,
+}
@@ -0,0 +1,130 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BackgroundJobStatus {
+    /// Waiting for `next_attempt_at`.
+    Pending,
+    Succeeded,
+    /// Gave up after `max_attempts`.
+    Failed,
+}
+
+impl BackgroundJobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BackgroundJobStatus::Pending => "pending",
+            BackgroundJobStatus::Succeeded => "succeeded",
+            BackgroundJobStatus::Failed => "failed",
+        }
+    }
+}
+
+impl fmt::Display for BackgroundJobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for BackgroundJobStatus {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "pending" => Ok(BackgroundJobStatus::Pending),
+            "succeeded" => Ok(BackgroundJobStatus::Succeeded),
+            "failed" => Ok(BackgroundJobStatus::Failed),
+            other => Err(format!("unsupported background job status: {other}")),
+        }
+    }
+}
+
+/// Background work that failed and is retried with exponential backoff.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BackgroundJobRecord {
+    pub id: String,
+    pub kind: String,
+    pub payload: JsonValue,
+    pub status: BackgroundJobStatus,
+    pub attempts: i64,
+    pub max_attempts: i64,
+    pub next_attempt_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobRunStatus {
+    Running,
+    /// Panicked and waiting out the restart backoff.
+    Restarting,
+    /// The worker loop returned and will not run again.
+    Stopped,
+}
+
+impl JobRunStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobRunStatus::Running => "running",
+            JobRunStatus::Restarting => "restarting",
+            JobRunStatus::Stopped => "stopped",
+        }
+    }
+}
+
+impl TryFrom<&str> for JobRunStatus {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "running" => Ok(JobRunStatus::Running),
+            "restarting" => Ok(JobRunStatus::Restarting),
+            "stopped" => Ok(JobRunStatus::Stopped),
+            other => Err(format!("unsupported job run status: {other}")),
+        }
+    }
+}
+
+/// Health of a long-running background worker such as the nightly forecast.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct JobRunRecord {
+    pub name: String,
+    pub status: JobRunStatus,
+    /// Times the worker was restarted after a panic.
+    pub restarts: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_panic: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_panic_at: Option<String>,
+    pub started_at: String,
+    pub updated_at: String,
+}
+
+/// Retry queue entries together with the health of the background workers.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BackgroundJobsOverview {
+    pub jobs: Vec<BackgroundJobRecord>,
+    pub workers: Vec<JobRunRecord>,
+}
+
+/// Outcome of one job started by `jobs_run_scheduled`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OnDemandJobRun {
+    pub name: String,
+    pub succeeded: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// What the job produced for the user, e.g. the reminders that fired.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<JsonValue>,
+}
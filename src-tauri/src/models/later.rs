@@ -0,0 +1,96 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LaterItemKind {
+    Article,
+    Video,
+    Course,
+    Podcast,
+    Other,
+}
+
+impl LaterItemKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LaterItemKind::Article => "article",
+            LaterItemKind::Video => "video",
+            LaterItemKind::Course => "course",
+            LaterItemKind::Podcast => "podcast",
+            LaterItemKind::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for LaterItemKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for LaterItemKind {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "article" => Ok(LaterItemKind::Article),
+            "video" => Ok(LaterItemKind::Video),
+            "course" => Ok(LaterItemKind::Course),
+            "podcast" => Ok(LaterItemKind::Podcast),
+            "other" => Ok(LaterItemKind::Other),
+            other => Err(format!("unsupported later item kind: {other}")),
+        }
+    }
+}
+
+/// Reading/watching queue entry kept apart from the prioritized task list.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LaterItemRecord {
+    pub id: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    pub kind: LaterItemKind,
+    pub estimated_minutes: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    pub status: String,
+    pub created_at: String,
+    pub updated_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LaterItemCreateInput {
+    pub title: String,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub kind: Option<LaterItemKind>,
+    pub estimated_minutes: i64,
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LaterSlotKind {
+    LowEnergy,
+    Slack,
+}
+
+/// A later-list item proposed for a gap in a generated plan.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LaterSlotSuggestion {
+    pub item_id: String,
+    pub title: String,
+    pub start_at: String,
+    pub end_at: String,
+    pub slot_kind: LaterSlotKind,
+}
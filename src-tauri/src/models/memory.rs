@@ -1,9 +1,10 @@
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MemoryDocument {
     pub id: String,
     pub file_path: PathBuf,
@@ -12,7 +13,7 @@ pub struct MemoryDocument {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MemoryMetadata {
     pub date: String,
     pub topics: Vec<String>,
@@ -20,9 +21,79 @@ pub struct MemoryMetadata {
     pub summary: String,
     pub relevance_score: f32,
     pub conversation_id: String,
+    /// Full creation timestamp. Documents written before it was recorded
+    /// only carry `date`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    /// Pinned memories are never archived or cleaned up.
+    #[serde(default)]
+    pub pinned: bool,
+    /// Ignored memories stay on disk but are left out of agent context.
+    #[serde(default)]
+    pub ignored: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryFactKind {
+    /// How the user likes to work, e.g. "deep work in the morning".
+    Preference,
+    /// Hard rules, e.g. "no meetings before 10am".
+    Constraint,
+    /// Who the user is, e.g. their role or the courses they take.
+    Profile,
+}
+
+impl MemoryFactKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MemoryFactKind::Preference => "Preference",
+            MemoryFactKind::Constraint => "Constraint",
+            MemoryFactKind::Profile => "About",
+        }
+    }
+}
+
+/// A user-written fact the assistant should always know, kept apart from
+/// retrieved conversation memories.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryFact {
+    pub kind: MemoryFactKind,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryFacts {
+    pub facts: Vec<MemoryFact>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl MemoryFacts {
+    /// Prompt section listing the facts, or `None` when there are none.
+    pub fn format_for_prompt(&self) -> Option<String> {
+        if self.facts.is_empty() {
+            return None;
+        }
+        let lines: Vec<String> = self
+            .facts
+            .iter()
+            .map(|fact| format!("- {}: {}", fact.kind.label(), fact.text))
+            .collect();
+        Some(lines.join("\n"))
+    }
+}
+
+/// A topic and how many memory documents carry it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryTopicCount {
+    pub topic: String,
+    pub document_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MemoryContext {
     pub relevant_documents: Vec<MemoryDocument>,
     pub total_context_length: usize,
@@ -35,7 +106,7 @@ pub struct MemoryContext {
     pub estimated_tokens: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ContextQuality {
     pub relevance_score: f32,
     pub diversity_score: f32,
@@ -43,7 +114,7 @@ pub struct ContextQuality {
     pub context_sufficiency: ContextSufficiency,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum ContextSufficiency {
     Insufficient,  // Not enough context for good responses
     Adequate,      // Enough context for basic responses
@@ -58,7 +129,7 @@ pub struct MemoryIndex {
     pub date_index: std::collections::BTreeMap<String, Vec<String>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ConversationSummary {
     pub conversation_id: String,
     pub user_message: String,
@@ -67,7 +138,7 @@ pub struct ConversationSummary {
     pub timestamp: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MemorySearchQuery {
     pub query: String,
     pub limit: usize,
@@ -76,22 +147,42 @@ pub struct MemorySearchQuery {
     pub topics: Option<Vec<String>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MemoryExportOptions {
     pub output_path: PathBuf,
     pub include_metadata: bool,
     pub date_range: Option<(DateTime<Utc>, DateTime<Utc>)>,
     pub format: MemoryExportFormat,
+    /// Packs the export into one compressed file at `output_path` instead of
+    /// writing a directory there.
+    #[serde(default)]
+    pub bundle: Option<MemoryExportBundle>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum MemoryExportFormat {
     Archive,
     Json,
     Markdown,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryExportBundle {
+    Zip,
+    TarGz,
+}
+
+impl MemoryExportBundle {
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            MemoryExportBundle::Zip => "zip",
+            MemoryExportBundle::TarGz => "tar.gz",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MemoryStats {
     pub total_documents: usize,
     pub total_topics: usize,
@@ -100,7 +191,7 @@ pub struct MemoryStats {
     pub newest_date: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MemoryValidationReport {
     pub total_checked: usize,
     pub valid_documents: usize,
@@ -109,20 +200,28 @@ pub struct MemoryValidationReport {
     pub corrupted_files: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A file written by a memory export.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportedFile {
+    pub path: PathBuf,
+    /// SHA-256 of the file, hex encoded.
+    pub checksum: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ExportInfo {
     pub export_date: DateTime<Utc>,
     pub total_documents: usize,
     pub date_range: (Option<DateTime<Utc>>, Option<DateTime<Utc>>),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct JsonExport {
     pub export_date: DateTime<Utc>,
     pub documents: Vec<MemoryDocument>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MemoryUsage {
     pub total_files: usize,
     pub total_size_bytes: u64,
@@ -214,7 +313,7 @@ impl Default for MemoryIndex {
 }
 
 /// Statistics about the search index state
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct IndexStatistics {
     pub total_documents: usize,
     pub total_topics: usize,
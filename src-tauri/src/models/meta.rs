@@ -0,0 +1,78 @@
+use schemars::schema::Schema;
+use schemars::Map;
+use serde::Serialize;
+
+/// Machine-readable description of the command API, for plugin authors and
+/// the web layer.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiDescription {
+    pub app_version: String,
+    /// JSON Schema dialect used by every schema in this description.
+    pub schema_dialect: String,
+    pub commands: Vec<CommandDescriptor>,
+    /// Every DTO referenced from `commands`, keyed by type name.
+    pub definitions: Map<String, Schema>,
+    pub error_codes: Vec<ErrorCodeDescriptor>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandDescriptor {
+    pub name: String,
+    pub module: String,
+    pub params: Vec<CommandParamDescriptor>,
+    /// Schema of the success value; failures are always a `CommandError`.
+    pub output: Schema,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandParamDescriptor {
+    /// Argument key as sent over IPC (camelCase).
+    pub name: String,
+    pub required: bool,
+    pub schema: Schema,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorCodeDescriptor {
+    pub code: String,
+    pub description: String,
+    /// Whether the same request may succeed if retried later.
+    pub retryable: bool,
+}
+
+/// How often the backend emits an event.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EmitFrequency {
+    /// Once per successful call of one of `commands`, or several times per
+    /// call for progress updates.
+    PerCommand { commands: Vec<String> },
+    /// From a background worker that checks every `interval_secs`; nothing is
+    /// emitted when a check finds nothing.
+    Periodic { interval_secs: u64 },
+    /// When another launch of the app is attempted.
+    External,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventDescriptor {
+    pub name: String,
+    pub description: String,
+    pub payload: Schema,
+    pub frequency: EmitFrequency,
+}
+
+/// Every event the backend emits to the frontend. Also written to
+/// `gen/event-catalog.json` for TypeScript codegen.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventCatalog {
+    pub schema_dialect: String,
+    pub events: Vec<EventDescriptor>,
+    pub definitions: Map<String, Schema>,
+}
@@ -2,15 +2,35 @@ pub mod ai;
 pub mod ai_feedback;
 pub mod ai_types;
 pub mod analytics;
+pub mod caldav;
+pub mod capacity;
 pub mod community_export;
+pub mod custom_tool;
+pub mod dashboard;
+pub mod data_export;
+pub mod day_log;
 pub mod dependency;
 pub mod goal;
+pub mod history;
+pub mod ics;
+pub mod job;
+pub mod later;
 pub mod memory;
+pub mod meta;
 pub mod planning;
 pub mod productivity;
+pub mod project;
+pub mod prompt_template;
+pub mod query;
 pub mod recurring_task;
+pub mod reminder;
+pub mod retention;
 // pub mod recommendation; // Removed - recommendation feature deleted
 pub mod settings;
+pub mod suggestion;
+pub mod sync;
 pub mod task;
+pub mod timesheet;
+pub mod upgrade;
 pub mod wellness;
 pub mod workload;
@@ -1,7 +1,8 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct PlanningSessionRecord {
     pub id: String,
@@ -14,11 +15,14 @@ pub struct PlanningSessionRecord {
     pub selected_option_id: Option<String>,
     #[serde(default)]
     pub personalization_snapshot: Option<JsonValue>,
+    /// Project the session was generated for, if any.
+    #[serde(default)]
+    pub project_id: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct PlanningOptionRecord {
     pub id: String,
@@ -36,7 +40,7 @@ pub struct PlanningOptionRecord {
     pub created_at: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct PlanningTimeBlockRecord {
     pub id: String,
@@ -57,9 +61,11 @@ pub struct PlanningTimeBlockRecord {
     #[serde(default)]
     pub actual_end_at: Option<String>,
     pub status: String,
+    #[serde(default)]
+    pub prep_note: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SchedulePreferencesRecord {
     pub id: String,
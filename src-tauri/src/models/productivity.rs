@@ -1,7 +1,9 @@
+use chrono::{Duration, NaiveDate};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ProductivityScoreRecord {
     pub snapshot_date: String,
@@ -15,7 +17,7 @@ pub struct ProductivityScoreRecord {
     pub created_at: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ProductivityScoreUpsert {
     pub snapshot_date: String,
@@ -26,7 +28,7 @@ pub struct ProductivityScoreUpsert {
     pub explanation: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ProductivityScoreHistoryResponse {
     pub scores: Vec<ProductivityScoreRecord>,
@@ -34,3 +36,78 @@ pub struct ProductivityScoreHistoryResponse {
     pub end_date: String,
     pub total_scores: usize,
 }
+
+/// Consecutive days whose composite productivity score reached the user's
+/// target. The nightly snapshot job records each finished day.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoreStreak {
+    /// Target the streak was counted against; `None` when no target is set.
+    #[serde(default)]
+    pub target: Option<f64>,
+    #[serde(default)]
+    pub current: u32,
+    #[serde(default)]
+    pub longest: u32,
+    /// `YYYY-MM-DD` of the last day that reached the target.
+    #[serde(default)]
+    pub last_met_date: Option<String>,
+    /// `YYYY-MM-DD` of the last day the nightly job recorded.
+    #[serde(default)]
+    pub last_evaluated_date: Option<String>,
+    /// Today's score so far; only filled in when the streak is read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub today_score: Option<f64>,
+    /// The streak is alive but today's score so far is below the target.
+    #[serde(default)]
+    pub at_risk: bool,
+}
+
+impl ScoreStreak {
+    /// Records the final score of `date`. Days at or before the last
+    /// recorded one are ignored, so re-running the job is harmless.
+    pub fn record_day(&mut self, date: NaiveDate, score: f64, target: f64) {
+        let date_key = date.to_string();
+        if self
+            .last_evaluated_date
+            .as_deref()
+            .is_some_and(|last| last >= date_key.as_str())
+        {
+            return;
+        }
+        if self.target != Some(target) {
+            self.current = 0;
+            self.target = Some(target);
+        }
+
+        if score >= target {
+            let previous = (date - Duration::days(1)).to_string();
+            let continues = self.last_met_date.as_deref() == Some(previous.as_str());
+            self.current = if continues { self.current + 1 } else { 1 };
+            self.longest = self.longest.max(self.current);
+            self.last_met_date = Some(date_key.clone());
+        } else {
+            self.current = 0;
+        }
+        self.last_evaluated_date = Some(date_key);
+    }
+
+    /// Fills in today's progress. A streak whose last good day is older than
+    /// the day before yesterday was broken by days the job never recorded.
+    pub fn refresh(&mut self, today: NaiveDate, today_score: f64, target: f64) {
+        if self.target != Some(target) {
+            self.current = 0;
+            self.target = Some(target);
+        }
+        let oldest_alive = (today - Duration::days(2)).to_string();
+        if self
+            .last_met_date
+            .as_deref()
+            .is_none_or(|date| date < oldest_alive.as_str())
+        {
+            self.current = 0;
+        }
+        self.today_score = Some(today_score);
+        self.at_risk = self.current > 0 && today_score < target;
+    }
+}
@@ -0,0 +1,105 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// System prompts that users may override.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptTemplateKey {
+    ParseTask,
+    Recommendations,
+    Schedule,
+    ActionItems,
+    DecomposeTask,
+    SummarizeHistory,
+    CritiquePlan,
+    Agent,
+}
+
+impl PromptTemplateKey {
+    pub const ALL: [PromptTemplateKey; 8] = [
+        PromptTemplateKey::ParseTask,
+        PromptTemplateKey::Recommendations,
+        PromptTemplateKey::Schedule,
+        PromptTemplateKey::ActionItems,
+        PromptTemplateKey::DecomposeTask,
+        PromptTemplateKey::SummarizeHistory,
+        PromptTemplateKey::CritiquePlan,
+        PromptTemplateKey::Agent,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PromptTemplateKey::ParseTask => "parse_task",
+            PromptTemplateKey::Recommendations => "recommendations",
+            PromptTemplateKey::Schedule => "schedule",
+            PromptTemplateKey::ActionItems => "action_items",
+            PromptTemplateKey::DecomposeTask => "decompose_task",
+            PromptTemplateKey::SummarizeHistory => "summarize_history",
+            PromptTemplateKey::CritiquePlan => "critique_plan",
+            PromptTemplateKey::Agent => "agent",
+        }
+    }
+
+    /// Whether the prompt drives a `response_format: json_object` request.
+    pub fn expects_json(&self) -> bool {
+        !matches!(self, PromptTemplateKey::Agent)
+    }
+}
+
+impl fmt::Display for PromptTemplateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for PromptTemplateKey {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "parse_task" => Ok(PromptTemplateKey::ParseTask),
+            "recommendations" => Ok(PromptTemplateKey::Recommendations),
+            "schedule" => Ok(PromptTemplateKey::Schedule),
+            "action_items" => Ok(PromptTemplateKey::ActionItems),
+            "decompose_task" => Ok(PromptTemplateKey::DecomposeTask),
+            "summarize_history" => Ok(PromptTemplateKey::SummarizeHistory),
+            "critique_plan" => Ok(PromptTemplateKey::CritiquePlan),
+            "agent" => Ok(PromptTemplateKey::Agent),
+            other => Err(format!("unsupported prompt template key: {other}")),
+        }
+    }
+}
+
+/// Stored override for one system prompt.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptOverrideRecord {
+    pub key: PromptTemplateKey,
+    pub template: String,
+    pub updated_at: String,
+}
+
+/// Default and overridden prompt as shown in settings.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptTemplateView {
+    pub key: PromptTemplateKey,
+    pub default_template: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub override_template: Option<String>,
+    /// True when an override exists and passes validation, i.e. it is in use.
+    pub override_active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validation_error: Option<String>,
+    pub variables: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptTemplateUpdateInput {
+    pub key: PromptTemplateKey,
+    pub template: String,
+}
@@ -0,0 +1,32 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    /// Values in column order; blobs are base64 encoded.
+    pub rows: Vec<Vec<JsonValue>>,
+    /// More rows matched than were returned.
+    pub truncated: bool,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryColumnDoc {
+    pub name: String,
+    /// Declared SQLite type, e.g. `TEXT` or `INTEGER`.
+    pub data_type: String,
+    pub primary_key: bool,
+}
+
+/// A table the query console may read.
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryTableDoc {
+    pub name: String,
+    pub description: String,
+    pub columns: Vec<QueryColumnDoc>,
+}
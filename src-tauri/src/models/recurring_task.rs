@@ -1,10 +1,11 @@
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::services::rrule_parser::RecurrenceRule;
 
 /// Recurring task template that defines how task instances are generated
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct RecurringTaskTemplate {
     pub id: String,
@@ -118,7 +119,7 @@ impl RecurringTaskTemplate {
 }
 
 /// Input for creating a new recurring task template
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct RecurringTaskTemplateCreate {
     pub title: String,
@@ -130,7 +131,7 @@ pub struct RecurringTaskTemplateCreate {
 }
 
 /// Input for updating a recurring task template
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct RecurringTaskTemplateUpdate {
     pub title: Option<String>,
@@ -143,7 +144,7 @@ pub struct RecurringTaskTemplateUpdate {
 }
 
 /// Task instance generated from a recurring template
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskInstance {
     pub id: String,
@@ -221,7 +222,7 @@ impl TaskInstance {
 }
 
 /// Input for updating a task instance
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskInstanceUpdate {
     pub title: Option<String>,
@@ -233,7 +234,7 @@ pub struct TaskInstanceUpdate {
 }
 
 /// Filter for querying recurring task templates
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct RecurringTaskTemplateFilter {
     pub is_active: Option<bool>,
@@ -244,7 +245,7 @@ pub struct RecurringTaskTemplateFilter {
 }
 
 /// Filter for querying task instances
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskInstanceFilter {
     pub template_id: Option<String>,
@@ -254,4 +255,25 @@ pub struct TaskInstanceFilter {
     pub due_after: Option<DateTime<Utc>>,
     pub due_before: Option<DateTime<Utc>>,
     pub is_exception: Option<bool>,
-}
\ No newline at end of file
+}
+
+/// Completion statistics for one recurring rule, computed from its
+/// materialized occurrences
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringTaskStats {
+    pub template_id: String,
+    pub title: String,
+    /// Occurrences already due, excluding cancelled ones and ones still
+    /// within the grace period
+    pub total_occurrences: i64,
+    pub completed_occurrences: i64,
+    pub completion_rate: f64,
+    /// Consecutive completed occurrences ending at the most recent one due
+    pub current_streak: i64,
+    pub longest_streak: i64,
+    /// Mean minutes between an occurrence's due time and its completion;
+    /// early completions count as zero
+    pub average_delay_minutes: Option<f64>,
+    pub last_completed_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,76 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockReminderStatus {
+    #[default]
+    Scheduled,
+    Fired,
+    /// The block had already started when the reminder came due.
+    Missed,
+    /// The block was moved away, checked off or its task finished.
+    Cancelled,
+    /// Dismissed by the user; kept so the reminder is not scheduled again
+    /// unless the block moves.
+    Dismissed,
+}
+
+impl BlockReminderStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BlockReminderStatus::Scheduled => "scheduled",
+            BlockReminderStatus::Fired => "fired",
+            BlockReminderStatus::Missed => "missed",
+            BlockReminderStatus::Cancelled => "cancelled",
+            BlockReminderStatus::Dismissed => "dismissed",
+        }
+    }
+}
+
+impl fmt::Display for BlockReminderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for BlockReminderStatus {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "scheduled" => Ok(BlockReminderStatus::Scheduled),
+            "fired" => Ok(BlockReminderStatus::Fired),
+            "missed" => Ok(BlockReminderStatus::Missed),
+            "cancelled" => Ok(BlockReminderStatus::Cancelled),
+            "dismissed" => Ok(BlockReminderStatus::Dismissed),
+            other => Err(format!("unsupported reminder status: {other}")),
+        }
+    }
+}
+
+/// Reminder ahead of an applied time block. There is at most one per block;
+/// `remind_at` is `start_at` minus `lead_minutes`, in UTC.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockReminderRecord {
+    pub id: String,
+    pub block_id: String,
+    pub task_id: String,
+    pub start_at: String,
+    pub remind_at: String,
+    pub lead_minutes: i64,
+    pub status: BlockReminderStatus,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// What a reconciliation pass changed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReminderSyncSummary {
+    pub scheduled: usize,
+    pub rescheduled: usize,
+    pub cancelled: usize,
+}
@@ -0,0 +1,151 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+pub const MAX_RETENTION_DAYS: u32 = 3650;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionCategory {
+    /// Nightly analytics snapshots and the anomalies flagged from them.
+    Snapshots,
+    /// Wellness nudges and the responses to them.
+    Nudges,
+    Feedback,
+    /// Cached AI and agent responses.
+    AiCache,
+    /// The opt-in log of redacted AI requests.
+    Audit,
+    /// Exported analytics report files.
+    Reports,
+}
+
+impl RetentionCategory {
+    pub const ALL: [RetentionCategory; 6] = [
+        RetentionCategory::Snapshots,
+        RetentionCategory::Nudges,
+        RetentionCategory::Feedback,
+        RetentionCategory::AiCache,
+        RetentionCategory::Audit,
+        RetentionCategory::Reports,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetentionCategory::Snapshots => "snapshots",
+            RetentionCategory::Nudges => "nudges",
+            RetentionCategory::Feedback => "feedback",
+            RetentionCategory::AiCache => "ai_cache",
+            RetentionCategory::Audit => "audit",
+            RetentionCategory::Reports => "reports",
+        }
+    }
+}
+
+/// Days each kind of derived data is kept. `0` keeps it forever.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionPolicy {
+    #[serde(default = "default_snapshots_days")]
+    pub snapshots_days: u32,
+    #[serde(default = "default_nudges_days")]
+    pub nudges_days: u32,
+    #[serde(default = "default_feedback_days")]
+    pub feedback_days: u32,
+    #[serde(default = "default_ai_cache_days")]
+    pub ai_cache_days: u32,
+    #[serde(default = "default_audit_days")]
+    pub audit_days: u32,
+    #[serde(default = "default_reports_days")]
+    pub reports_days: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_updated_at: Option<String>,
+}
+
+impl RetentionPolicy {
+    pub fn days_for(&self, category: RetentionCategory) -> u32 {
+        match category {
+            RetentionCategory::Snapshots => self.snapshots_days,
+            RetentionCategory::Nudges => self.nudges_days,
+            RetentionCategory::Feedback => self.feedback_days,
+            RetentionCategory::AiCache => self.ai_cache_days,
+            RetentionCategory::Audit => self.audit_days,
+            RetentionCategory::Reports => self.reports_days,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for category in RetentionCategory::ALL {
+            if self.days_for(category) > MAX_RETENTION_DAYS {
+                return Err(format!(
+                    "{} 的保留天数不能超过 {MAX_RETENTION_DAYS} 天",
+                    category.as_str()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Snapshots and nudges feed the year in review, so by default they cover
+/// the whole previous calendar year for as long as it can be reviewed.
+const YEAR_REVIEW_DAYS: u32 = 731;
+
+fn default_snapshots_days() -> u32 {
+    YEAR_REVIEW_DAYS
+}
+
+fn default_nudges_days() -> u32 {
+    YEAR_REVIEW_DAYS
+}
+
+fn default_feedback_days() -> u32 {
+    365
+}
+
+fn default_ai_cache_days() -> u32 {
+    30
+}
+
+fn default_audit_days() -> u32 {
+    30
+}
+
+fn default_reports_days() -> u32 {
+    90
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            snapshots_days: default_snapshots_days(),
+            nudges_days: default_nudges_days(),
+            feedback_days: default_feedback_days(),
+            ai_cache_days: default_ai_cache_days(),
+            audit_days: default_audit_days(),
+            reports_days: default_reports_days(),
+            last_updated_at: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionEntry {
+    pub category: RetentionCategory,
+    pub retention_days: u32,
+    /// `YYYY-MM-DD`; data older than this day is removed. `None` when the
+    /// category is kept forever.
+    pub cutoff: Option<String>,
+    /// Rows, or report files, that are (or would be) deleted.
+    pub rows: usize,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionReport {
+    /// `true` for a preview that deleted nothing.
+    pub dry_run: bool,
+    pub entries: Vec<RetentionEntry>,
+    pub total_rows: usize,
+    pub generated_at: String,
+}
@@ -1,5 +1,7 @@
 use std::collections::BTreeMap;
 
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, TimeZone, Weekday};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 pub const DASHBOARD_MODULE_DEFAULTS: [(&str, bool); 7] = [
@@ -20,11 +22,35 @@ fn default_dashboard_modules() -> BTreeMap<String, bool> {
     modules
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// A user-defined KPI: arithmetic over today's aggregates, e.g.
+/// `focus_minutes / (completed_tasks + 1)`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardMetric {
+    pub id: String,
+    pub label: String,
+    pub expression: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardMetricValue {
+    pub id: String,
+    /// `None` when the expression divides by zero.
+    pub value: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct DashboardConfig {
     #[serde(default)]
     pub modules: BTreeMap<String, bool>,
+    #[serde(default)]
+    pub custom_metrics: Vec<DashboardMetric>,
+    /// Values of `custom_metrics` for today; filled when the config is read
+    /// and never stored.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub metric_values: Vec<DashboardMetricValue>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub last_updated_at: Option<String>,
 }
@@ -51,12 +77,426 @@ impl Default for DashboardConfig {
     fn default() -> Self {
         Self {
             modules: default_dashboard_modules(),
+            custom_metrics: Vec::new(),
+            metric_values: Vec::new(),
             last_updated_at: None,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq)]
+/// Weekday keys for per-night overrides; a night belongs to the day it starts on.
+pub const SLEEP_WEEKDAY_KEYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+const DEFAULT_BED_MINUTE: i16 = 23 * 60;
+const DEFAULT_WAKE_MINUTE: i16 = 7 * 60;
+const DEFAULT_WIND_DOWN_MINUTES: i16 = 60;
+const MAX_WIND_DOWN_MINUTES: i16 = 180;
+const MAX_SLEEP_MINUTES: i16 = 16 * 60;
+
+fn default_wind_down_minutes() -> i16 {
+    DEFAULT_WIND_DOWN_MINUTES
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SleepWindow {
+    /// Minutes after midnight; a bedtime earlier than the wake time is
+    /// treated as falling after midnight.
+    pub bed_minute: i16,
+    pub wake_minute: i16,
+}
+
+impl SleepWindow {
+    fn duration_minutes(&self) -> i16 {
+        if self.bed_minute > self.wake_minute {
+            1440 - self.bed_minute + self.wake_minute
+        } else {
+            self.wake_minute - self.bed_minute
+        }
+    }
+}
+
+impl Default for SleepWindow {
+    fn default() -> Self {
+        Self {
+            bed_minute: DEFAULT_BED_MINUTE,
+            wake_minute: DEFAULT_WAKE_MINUTE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SleepSchedule {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub default_window: SleepWindow,
+    /// Overrides keyed by `SLEEP_WEEKDAY_KEYS`.
+    #[serde(default)]
+    pub weekdays: BTreeMap<String, SleepWindow>,
+    #[serde(default = "default_wind_down_minutes")]
+    pub wind_down_minutes: i16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_updated_at: Option<String>,
+}
+
+impl SleepSchedule {
+    pub fn normalize(mut self) -> Self {
+        self.weekdays = self
+            .weekdays
+            .into_iter()
+            .map(|(day, window)| (day.trim().to_lowercase(), window))
+            .filter(|(day, _)| SLEEP_WEEKDAY_KEYS.contains(&day.as_str()))
+            .collect();
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let windows = std::iter::once(("default", &self.default_window)).chain(
+            self.weekdays
+                .iter()
+                .map(|(day, window)| (day.as_str(), window)),
+        );
+        for (day, window) in windows {
+            if !(0..1440).contains(&window.bed_minute) || !(0..1440).contains(&window.wake_minute) {
+                return Err(format!("{day} 的作息时间必须在 0~1439 分钟之间"));
+            }
+            if window.bed_minute == window.wake_minute {
+                return Err(format!("{day} 的就寝时间与起床时间不能相同"));
+            }
+            if window.duration_minutes() > MAX_SLEEP_MINUTES {
+                return Err(format!("{day} 的睡眠时长不能超过 16 小时"));
+            }
+        }
+
+        if !(0..=MAX_WIND_DOWN_MINUTES).contains(&self.wind_down_minutes) {
+            return Err(format!(
+                "睡前缓冲时间必须在 0~{MAX_WIND_DOWN_MINUTES} 分钟之间"
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn window_for(&self, weekday: Weekday) -> SleepWindow {
+        let key = SLEEP_WEEKDAY_KEYS[weekday.num_days_from_monday() as usize];
+        self.weekdays
+            .get(key)
+            .copied()
+            .unwrap_or(self.default_window)
+    }
+
+    /// Sleep periods overlapping `[start, end)`, in `start`'s offset.
+    pub fn sleep_intervals(
+        &self,
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+    ) -> Vec<(DateTime<FixedOffset>, DateTime<FixedOffset>)> {
+        self.nights(start, end)
+            .into_iter()
+            .filter(|(bed, wake)| *bed < end && start < *wake)
+            .collect()
+    }
+
+    /// Wind-down periods just before bedtime overlapping `[start, end)`.
+    pub fn wind_down_intervals(
+        &self,
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+    ) -> Vec<(DateTime<FixedOffset>, DateTime<FixedOffset>)> {
+        if self.wind_down_minutes <= 0 {
+            return Vec::new();
+        }
+
+        self.nights(start, end)
+            .into_iter()
+            .map(|(bed, _)| (bed - Duration::minutes(self.wind_down_minutes as i64), bed))
+            .filter(|(from, bed)| *from < end && start < *bed)
+            .collect()
+    }
+
+    fn nights(
+        &self,
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+    ) -> Vec<(DateTime<FixedOffset>, DateTime<FixedOffset>)> {
+        let offset = *start.offset();
+        let local_end = end.with_timezone(&offset).date_naive();
+        let mut day = start.date_naive() - Duration::days(1);
+        let mut nights = Vec::new();
+
+        while day <= local_end {
+            let window = self.window_for(day.weekday());
+            let next_day = day + Duration::days(1);
+            let bed_day = if window.bed_minute > window.wake_minute {
+                day
+            } else {
+                next_day
+            };
+            nights.push((
+                at_minute(offset, bed_day, window.bed_minute),
+                at_minute(offset, next_day, window.wake_minute),
+            ));
+            day = next_day;
+        }
+
+        nights
+    }
+}
+
+fn at_minute(offset: FixedOffset, day: NaiveDate, minute: i16) -> DateTime<FixedOffset> {
+    let naive =
+        day.and_hms_opt(0, 0, 0).expect("midnight exists") + Duration::minutes(minute as i64);
+    offset
+        .from_local_datetime(&naive)
+        .single()
+        .expect("fixed offsets map local times uniquely")
+}
+
+impl Default for SleepSchedule {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            default_window: SleepWindow::default(),
+            weekdays: BTreeMap::new(),
+            wind_down_minutes: DEFAULT_WIND_DOWN_MINUTES,
+            last_updated_at: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedbackCategory {
+    /// Thumbs up/down prompts on AI scores, recommendations and forecasts.
+    AiQuality,
+    /// Wellness nudges and the responses to them.
+    WellnessCheckIns,
+    /// Anonymous usage counts in community export bundles.
+    UsageStats,
+}
+
+impl FeedbackCategory {
+    pub const ALL: [FeedbackCategory; 3] = [
+        FeedbackCategory::AiQuality,
+        FeedbackCategory::WellnessCheckIns,
+        FeedbackCategory::UsageStats,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeedbackCategory::AiQuality => "ai_quality",
+            FeedbackCategory::WellnessCheckIns => "wellness_check_ins",
+            FeedbackCategory::UsageStats => "usage_stats",
+        }
+    }
+}
+
+/// Feedback the user declined to give, per category. Everything is collected
+/// by default.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedbackOptOuts {
+    /// Same flag as `AppSettings::ai_feedback_opt_out`.
+    #[serde(default)]
+    pub ai_quality: bool,
+    #[serde(default)]
+    pub wellness_check_ins: bool,
+    #[serde(default)]
+    pub usage_stats: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_updated_at: Option<String>,
+}
+
+impl FeedbackOptOuts {
+    pub fn is_opted_out(&self, category: FeedbackCategory) -> bool {
+        match category {
+            FeedbackCategory::AiQuality => self.ai_quality,
+            FeedbackCategory::WellnessCheckIns => self.wellness_check_ins,
+            FeedbackCategory::UsageStats => self.usage_stats,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerThrottleMode {
+    /// Background jobs run regardless of the power source.
+    Off,
+    /// Defer while on battery or in power-saver mode.
+    #[default]
+    DeferOnBattery,
+    /// Defer only when the battery is below `low_battery_percent` or the
+    /// system is in power-saver mode.
+    DeferOnLowBattery,
+}
+
+/// When non-essential background jobs (snapshots, forecasts, pruning,
+/// retries) wait for AC power.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerPolicy {
+    #[serde(default)]
+    pub mode: PowerThrottleMode,
+    #[serde(default = "default_low_battery_percent")]
+    pub low_battery_percent: u8,
+    /// Longest a job is deferred before it runs anyway.
+    #[serde(default = "default_max_defer_hours")]
+    pub max_defer_hours: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_updated_at: Option<String>,
+}
+
+fn default_low_battery_percent() -> u8 {
+    30
+}
+
+fn default_max_defer_hours() -> u32 {
+    12
+}
+
+impl Default for PowerPolicy {
+    fn default() -> Self {
+        Self {
+            mode: PowerThrottleMode::default(),
+            low_battery_percent: default_low_battery_percent(),
+            max_defer_hours: default_max_defer_hours(),
+            last_updated_at: None,
+        }
+    }
+}
+
+impl PowerPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(5..=95).contains(&self.low_battery_percent) {
+            return Err("低电量阈值需在 5% 到 95% 之间".to_string());
+        }
+        if !(1..=48).contains(&self.max_defer_hours) {
+            return Err("最长推迟时间需在 1 到 48 小时之间".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Opt-in detection of idle stretches during focus sessions. Detected idle
+/// time is only subtracted from focus minutes once the user confirms it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IdleDetectionSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Shortest stretch without input that counts as idle.
+    #[serde(default = "default_idle_threshold_minutes")]
+    pub threshold_minutes: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_updated_at: Option<String>,
+}
+
+fn default_idle_threshold_minutes() -> u32 {
+    5
+}
+
+impl Default for IdleDetectionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold_minutes: default_idle_threshold_minutes(),
+            last_updated_at: None,
+        }
+    }
+}
+
+impl IdleDetectionSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=60).contains(&self.threshold_minutes) {
+            return Err("空闲阈值需在 1 到 60 分钟之间".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// When tasks waiting on someone else get a check-in task.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DelegationSettings {
+    /// Days a task waits before a follow-up is created, and between
+    /// follow-ups while it keeps waiting.
+    #[serde(default = "default_follow_up_after_days")]
+    pub follow_up_after_days: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_updated_at: Option<String>,
+}
+
+fn default_follow_up_after_days() -> u32 {
+    3
+}
+
+impl Default for DelegationSettings {
+    fn default() -> Self {
+        Self {
+            follow_up_after_days: default_follow_up_after_days(),
+            last_updated_at: None,
+        }
+    }
+}
+
+impl DelegationSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=90).contains(&self.follow_up_after_days) {
+            return Err("跟进间隔需在 1 到 90 天之间".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Experimental subsystems that can be switched off at runtime.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureFlag {
+    /// Insight cards in the analytics overview.
+    AiInsights,
+    /// The daily proactive suggestions from the agent.
+    ProactiveAgent,
+    /// Incremental change feed used by `sync_changes_since`.
+    Sync,
+}
+
+impl FeatureFlag {
+    pub const ALL: [FeatureFlag; 3] = [
+        FeatureFlag::AiInsights,
+        FeatureFlag::ProactiveAgent,
+        FeatureFlag::Sync,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeatureFlag::AiInsights => "ai_insights",
+            FeatureFlag::ProactiveAgent => "proactive_agent",
+            FeatureFlag::Sync => "sync",
+        }
+    }
+
+    /// Value used until the flag is set explicitly.
+    pub fn default_enabled(&self) -> bool {
+        match self {
+            FeatureFlag::AiInsights | FeatureFlag::ProactiveAgent | FeatureFlag::Sync => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FeatureFlagState {
+    pub flag: FeatureFlag,
+    pub enabled: bool,
+    pub default_enabled: bool,
+    /// When the flag was last set; absent while it follows the default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AppSettings {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -68,6 +508,35 @@ pub struct AppSettings {
     /// Privacy setting: Opt out of AI feedback collection
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ai_feedback_opt_out: Option<bool>,
+    /// Opt-in: watch the clipboard for text that looks like a task or meeting invite
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clipboard_watch_enabled: Option<bool>,
+    /// Privacy setting: send placeholders instead of task titles to AI providers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ai_privacy_mode: Option<bool>,
+    /// Opt-in: keep redacted AI requests and responses for `ai_debug_get`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ai_debug_log_enabled: Option<bool>,
+    /// Days before a pending planning session expires and its drafts are pruned
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub planning_session_retention_days: Option<u32>,
+    /// Opt-in: compose up to three daily suggestions in the background
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proactive_suggestions_enabled: Option<bool>,
+    /// Opt-in: notify when the nightly job flags an analytics anomaly
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anomaly_notifications_enabled: Option<bool>,
+    /// Opt-in: ask the AI provider to review optimizer plans for issues
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan_critique_enabled: Option<bool>,
+    /// Preference profile used when a planning run does not name one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_preference_id: Option<String>,
+    /// Composite productivity score a day must reach to extend the streak
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub productivity_score_target: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dashboard_config: Option<DashboardConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sleep_schedule: Option<SleepSchedule>,
 }
@@ -0,0 +1,107 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionKind {
+    /// An open task due soon with no applied block before its deadline.
+    RescheduleAtRisk,
+    /// A large task that would be easier to plan in smaller pieces.
+    BreakDownTask,
+    /// A day whose applied blocks exceed the workday.
+    FreeUpDay,
+}
+
+impl SuggestionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SuggestionKind::RescheduleAtRisk => "reschedule_at_risk",
+            SuggestionKind::BreakDownTask => "break_down_task",
+            SuggestionKind::FreeUpDay => "free_up_day",
+        }
+    }
+}
+
+impl fmt::Display for SuggestionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for SuggestionKind {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "reschedule_at_risk" => Ok(SuggestionKind::RescheduleAtRisk),
+            "break_down_task" => Ok(SuggestionKind::BreakDownTask),
+            "free_up_day" => Ok(SuggestionKind::FreeUpDay),
+            other => Err(format!("unsupported suggestion kind: {other}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionStatus {
+    #[default]
+    Pending,
+    Accepted,
+    Dismissed,
+    /// Left pending until the next day's suggestions replaced it.
+    Expired,
+}
+
+impl SuggestionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SuggestionStatus::Pending => "pending",
+            SuggestionStatus::Accepted => "accepted",
+            SuggestionStatus::Dismissed => "dismissed",
+            SuggestionStatus::Expired => "expired",
+        }
+    }
+}
+
+impl fmt::Display for SuggestionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for SuggestionStatus {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "pending" => Ok(SuggestionStatus::Pending),
+            "accepted" => Ok(SuggestionStatus::Accepted),
+            "dismissed" => Ok(SuggestionStatus::Dismissed),
+            "expired" => Ok(SuggestionStatus::Expired),
+            other => Err(format!("unsupported suggestion status: {other}")),
+        }
+    }
+}
+
+/// A suggestion composed by the daily proactive job. `payload` carries what
+/// the client needs to act on it, e.g. a proposed due date or the blocks
+/// that could be moved off an overloaded day.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestionRecord {
+    pub id: String,
+    pub kind: SuggestionKind,
+    pub title: String,
+    pub reason: String,
+    pub task_id: Option<String>,
+    /// `YYYY-MM-DD` of the day a `free_up_day` suggestion is about.
+    pub target_date: Option<String>,
+    pub payload: JsonValue,
+    pub status: SuggestionStatus,
+    /// `YYYY-MM-DD` of the day the suggestion was composed for.
+    pub suggested_for: String,
+    pub created_at: String,
+    pub resolved_at: Option<String>,
+}
@@ -0,0 +1,74 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::models::goal::Goal;
+use crate::models::planning::PlanningTimeBlockRecord;
+use crate::models::project::ProjectRecord;
+use crate::models::task::TaskRecord;
+
+/// Entities tracked by the change log.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncEntity {
+    Task,
+    Project,
+    Goal,
+    /// Applied time blocks only; drafts of pending plans are not synced.
+    TimeBlock,
+}
+
+impl SyncEntity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SyncEntity::Task => "task",
+            SyncEntity::Project => "project",
+            SyncEntity::Goal => "goal",
+            SyncEntity::TimeBlock => "time_block",
+        }
+    }
+}
+
+impl fmt::Display for SyncEntity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for SyncEntity {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "task" => Ok(SyncEntity::Task),
+            "project" => Ok(SyncEntity::Project),
+            "goal" => Ok(SyncEntity::Goal),
+            "time_block" => Ok(SyncEntity::TimeBlock),
+            other => Err(format!("unsupported sync entity: {other}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DeletedEntity {
+    pub entity: SyncEntity,
+    pub id: String,
+    pub revision: i64,
+}
+
+/// Entities changed after `since`, in their current state.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeSet {
+    pub since: i64,
+    /// Pass back as the next `revision`; covers everything returned here.
+    pub revision: i64,
+    /// More changes are waiting past `revision`.
+    pub has_more: bool,
+    pub tasks: Vec<TaskRecord>,
+    pub projects: Vec<ProjectRecord>,
+    pub goals: Vec<Goal>,
+    pub time_blocks: Vec<PlanningTimeBlockRecord>,
+    pub deleted: Vec<DeletedEntity>,
+}
@@ -1,3 +1,4 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 
@@ -5,7 +6,7 @@ use crate::models::ai::{
     TaskAiReasoningStep, TaskAiSource, TaskEfficiencyPrediction, TaskFocusModeRecommendation,
 };
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskRecord {
     pub id: String,
@@ -26,18 +27,42 @@ pub struct TaskRecord {
     pub recurrence: Option<TaskRecurrence>,
     pub ai: Option<TaskAiInsights>,
     pub external_links: Vec<String>,
+    pub project_id: Option<String>,
+    /// Hidden from default lists and planning until this time.
+    #[serde(default)]
+    pub snoozed_until: Option<String>,
+    /// Raise `tasks://snooze-ended` when the snooze ends.
+    #[serde(default)]
+    pub snooze_notify: bool,
+    /// Palette color, see `utils::appearance`.
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Person the task was handed to.
+    #[serde(default)]
+    pub delegated_to: Option<String>,
+    /// What or whom the task is blocked on while `waiting`.
+    #[serde(default)]
+    pub waiting_on: Option<String>,
+    /// When the task last entered the `waiting` status.
+    #[serde(default)]
+    pub waiting_since: Option<String>,
+    /// Latest check-in task generated while waiting.
+    #[serde(default)]
+    pub follow_up_task_id: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskRecurrence {
     pub rule: String,
     pub until: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskAiInsights {
     pub summary: Option<String>,
@@ -54,7 +79,7 @@ pub struct TaskAiInsights {
     pub generated_at: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskCreateInput {
     pub title: String,
@@ -90,9 +115,19 @@ pub struct TaskCreateInput {
     pub ai: Option<TaskAiInsights>,
     #[serde(default)]
     pub external_links: Option<Vec<String>>,
+    #[serde(default)]
+    pub project_id: Option<String>,
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub delegated_to: Option<String>,
+    #[serde(default)]
+    pub waiting_on: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskUpdateInput {
     #[serde(default)]
@@ -129,4 +164,194 @@ pub struct TaskUpdateInput {
     pub ai: Option<Option<TaskAiInsights>>,
     #[serde(default)]
     pub external_links: Option<Option<Vec<String>>>,
+    #[serde(default)]
+    pub project_id: Option<Option<String>>,
+    #[serde(default)]
+    pub color: Option<Option<String>>,
+    #[serde(default)]
+    pub icon: Option<Option<String>>,
+    #[serde(default)]
+    pub delegated_to: Option<Option<String>>,
+    #[serde(default)]
+    pub waiting_on: Option<Option<String>>,
+}
+
+/// Bulk due-date shift, addressed either by task ids or by a goal.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskShiftDatesInput {
+    #[serde(default)]
+    pub task_ids: Vec<String>,
+    #[serde(default)]
+    pub goal_id: Option<String>,
+    pub delta_days: i64,
+    /// Compute the result without writing anything.
+    #[serde(default)]
+    pub preview: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskDateShift {
+    pub task_id: String,
+    pub title: String,
+    /// Moved because it depends on a selected task, not selected itself.
+    pub cascaded: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_due_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_planned_start_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub planned_start_at: Option<String>,
+}
+
+/// Payload of `tasks://snooze-ended`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskSnoozeEnded {
+    pub task: TaskRecord,
+    /// The user asked to be notified when this snooze ends.
+    pub notify: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskShiftDatesResult {
+    pub preview: bool,
+    pub shifts: Vec<TaskDateShift>,
+    /// Applied time blocks whose conflict flags changed with the new dates.
+    pub flagged_block_ids: Vec<String>,
+}
+
+/// Looks up completed tasks resembling a task being created or planned.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarTasksQuery {
+    pub title: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Left out of the results, e.g. the task being edited.
+    #[serde(default)]
+    pub exclude_task_id: Option<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarTaskMatch {
+    pub task_id: String,
+    pub title: String,
+    pub tags: Vec<String>,
+    pub estimated_minutes: Option<i64>,
+    /// Minutes spent in applied time blocks, using tracked times when present.
+    pub actual_minutes: Option<i64>,
+    pub completed_at: Option<String>,
+    /// 0..=1 blend of title and tag overlap.
+    pub similarity: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarTasksResult {
+    pub matches: Vec<SimilarTaskMatch>,
+    /// Median of the known durations among the matches.
+    pub typical_minutes: Option<i64>,
+    /// Human-readable hint such as "上次类似任务耗时约 3 小时".
+    pub hint: Option<String>,
+    /// Summaries of past conversations that mention the task.
+    #[serde(default)]
+    pub memory_notes: Vec<String>,
+}
+
+/// What to do with a task that has not been touched in a while.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StaleTaskDisposition {
+    Archive,
+    Reestimate,
+    BreakDown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StaleTaskEntry {
+    pub task_id: String,
+    pub title: String,
+    pub status: String,
+    pub priority: String,
+    pub due_at: Option<String>,
+    /// When the task was last updated.
+    pub last_touched_at: String,
+    pub days_untouched: i64,
+    /// Label of the age bucket the task falls in.
+    pub bucket: String,
+    pub suggestion: StaleTaskDisposition,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StaleAgeBucket {
+    pub label: String,
+    pub min_days: i64,
+    /// Exclusive; `None` for the oldest bucket.
+    pub max_days: Option<i64>,
+    pub count: usize,
+}
+
+/// Open tasks untouched for at least `min_days`, oldest first.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StaleTaskReport {
+    pub min_days: u32,
+    pub generated_at: String,
+    pub buckets: Vec<StaleAgeBucket>,
+    pub tasks: Vec<StaleTaskEntry>,
+}
+
+/// Eisenhower quadrant of a task, in the order the planner takes them.
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum MatrixQuadrant {
+    /// Urgent and important.
+    DoFirst,
+    /// Important, not urgent.
+    Schedule,
+    /// Urgent, not important.
+    Delegate,
+    /// Neither urgent nor important.
+    Eliminate,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskMatrixEntry {
+    pub task_id: String,
+    pub title: String,
+    pub status: String,
+    pub priority: String,
+    pub due_at: Option<String>,
+    /// 0..=1, from how close the due date is.
+    pub urgency: f64,
+    /// 0..=1, from priority and whether an active goal includes the task.
+    pub importance: f64,
+    pub linked_to_goal: bool,
+    pub quadrant: MatrixQuadrant,
+}
+
+/// Open tasks grouped into the four quadrants, most urgent first within
+/// each.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskMatrixSnapshot {
+    pub generated_at: String,
+    pub do_first: Vec<TaskMatrixEntry>,
+    pub schedule: Vec<TaskMatrixEntry>,
+    pub delegate: Vec<TaskMatrixEntry>,
+    pub eliminate: Vec<TaskMatrixEntry>,
 }
@@ -0,0 +1,110 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TimesheetGrouping {
+    #[default]
+    Project,
+    /// By the task's first tag.
+    Tag,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TimesheetRoundingMode {
+    #[default]
+    Nearest,
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TimesheetFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+impl TimesheetFormat {
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            TimesheetFormat::Csv => "csv",
+            TimesheetFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TimesheetExportParams {
+    /// `YYYY-MM-DD`, inclusive.
+    pub start: String,
+    /// `YYYY-MM-DD`, inclusive.
+    pub end: String,
+    /// Each entry is rounded to a multiple of this many minutes; `1` keeps
+    /// exact minutes.
+    #[serde(default)]
+    pub rounding_minutes: Option<i64>,
+    #[serde(default)]
+    pub rounding_mode: TimesheetRoundingMode,
+    #[serde(default)]
+    pub group_by: TimesheetGrouping,
+    #[serde(default)]
+    pub format: TimesheetFormat,
+}
+
+/// One worked time block.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TimesheetEntry {
+    /// `YYYY-MM-DD` of the start, UTC.
+    pub date: String,
+    pub group: String,
+    pub task_id: String,
+    pub task_title: String,
+    pub start_at: String,
+    pub end_at: String,
+    /// Whether the times were tracked or taken from the plan.
+    pub tracked: bool,
+    pub minutes: i64,
+    pub billed_minutes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TimesheetGroupTotal {
+    pub group: String,
+    pub minutes: i64,
+    pub billed_minutes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Timesheet {
+    pub start_date: String,
+    pub end_date: String,
+    pub rounding_minutes: i64,
+    pub rounding_mode: TimesheetRoundingMode,
+    pub group_by: TimesheetGrouping,
+    /// Sorted by group, then start time.
+    pub entries: Vec<TimesheetEntry>,
+    pub groups: Vec<TimesheetGroupTotal>,
+    pub total_minutes: i64,
+    pub total_billed_minutes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TimesheetExportResult {
+    pub file_path: String,
+    /// SHA-256 of the written file, hex encoded.
+    pub checksum: String,
+    pub format: TimesheetFormat,
+    pub entry_count: usize,
+    pub groups: Vec<TimesheetGroupTotal>,
+    pub total_minutes: i64,
+    pub total_billed_minutes: i64,
+    pub generated_at: String,
+}
@@ -0,0 +1,66 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UpgradeStepState {
+    /// Not run yet on this database.
+    Pending,
+    Completed,
+    /// Ran and failed; retried on the next launch.
+    Failed,
+}
+
+impl UpgradeStepState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UpgradeStepState::Pending => "pending",
+            UpgradeStepState::Completed => "completed",
+            UpgradeStepState::Failed => "failed",
+        }
+    }
+}
+
+impl TryFrom<&str> for UpgradeStepState {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "pending" => Ok(UpgradeStepState::Pending),
+            "completed" => Ok(UpgradeStepState::Completed),
+            "failed" => Ok(UpgradeStepState::Failed),
+            other => Err(format!("unsupported upgrade step status: {other}")),
+        }
+    }
+}
+
+/// A post-upgrade data task and how far it got on this database.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpgradeStepStatus {
+    pub id: String,
+    /// App version that introduced the step.
+    pub app_version: String,
+    pub description: String,
+    pub state: UpgradeStepState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangelogEntry {
+    pub version: String,
+    pub notes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AppUpgradeStatus {
+    pub app_version: String,
+    pub steps: Vec<UpgradeStepStatus>,
+    /// Changelog entries the user has not acknowledged yet, newest first.
+    pub changelog: Vec<ChangelogEntry>,
+}
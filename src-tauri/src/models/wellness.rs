@@ -1,11 +1,14 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum WellnessTriggerReason {
     FocusStreak,
     WorkStreak,
+    /// Blocks were applied inside the pre-bedtime wind-down period.
+    WindDown,
 }
 
 impl WellnessTriggerReason {
@@ -13,8 +16,15 @@ impl WellnessTriggerReason {
         match self {
             WellnessTriggerReason::FocusStreak => "focus_streak",
             WellnessTriggerReason::WorkStreak => "work_streak",
+            WellnessTriggerReason::WindDown => "wind_down",
         }
     }
+
+    /// Critical nudges still surface during a focus session; the others
+    /// wait until it ends.
+    pub fn is_critical(&self) -> bool {
+        matches!(self, WellnessTriggerReason::WindDown)
+    }
 }
 
 impl fmt::Display for WellnessTriggerReason {
@@ -30,12 +40,13 @@ impl TryFrom<&str> for WellnessTriggerReason {
         match value {
             "focus_streak" => Ok(WellnessTriggerReason::FocusStreak),
             "work_streak" => Ok(WellnessTriggerReason::WorkStreak),
+            "wind_down" => Ok(WellnessTriggerReason::WindDown),
             other => Err(format!("unsupported wellness trigger: {other}")),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum WellnessResponse {
     Completed,
@@ -72,7 +83,7 @@ impl TryFrom<&str> for WellnessResponse {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct WellnessEventRecord {
     pub id: i64,
@@ -88,7 +99,7 @@ pub struct WellnessEventRecord {
     pub deferral_count: i64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct WellnessEventInsert {
     pub window_start: String,
@@ -98,7 +109,7 @@ pub struct WellnessEventInsert {
     pub suggested_micro_task: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct WellnessEventResponseUpdate {
     pub response: WellnessResponse,
@@ -106,3 +117,74 @@ pub struct WellnessEventResponseUpdate {
     #[serde(default)]
     pub deferral_count: i64,
 }
+
+/// The focus or pomodoro session the user is currently in. Kept in memory
+/// only; a session that outlives `ends_at` counts as ended.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusSession {
+    pub started_at: String,
+    pub ends_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum IdleAdjustmentStatus {
+    /// Waiting for the user to confirm or dismiss it.
+    Pending,
+    /// Subtracted from the day's focus minutes.
+    Confirmed,
+    Dismissed,
+}
+
+impl IdleAdjustmentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IdleAdjustmentStatus::Pending => "pending",
+            IdleAdjustmentStatus::Confirmed => "confirmed",
+            IdleAdjustmentStatus::Dismissed => "dismissed",
+        }
+    }
+}
+
+impl TryFrom<&str> for IdleAdjustmentStatus {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "pending" => Ok(IdleAdjustmentStatus::Pending),
+            "confirmed" => Ok(IdleAdjustmentStatus::Confirmed),
+            "dismissed" => Ok(IdleAdjustmentStatus::Dismissed),
+            other => Err(format!("unsupported idle adjustment status: {other}")),
+        }
+    }
+}
+
+/// A stretch without keyboard or mouse input inside a focus session.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IdlePeriod {
+    pub started_at: String,
+    pub ended_at: String,
+    pub minutes: i64,
+}
+
+/// Idle time detected during one focus session, proposed for removal from
+/// the recorded focus minutes.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusIdleAdjustment {
+    pub id: i64,
+    pub session_started_at: String,
+    pub session_ended_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<String>,
+    pub idle_minutes: i64,
+    pub periods: Vec<IdlePeriod>,
+    pub status: IdleAdjustmentStatus,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<String>,
+}
@@ -1,7 +1,8 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum WorkloadHorizon {
     #[serde(rename = "7d")]
@@ -13,6 +14,21 @@ pub enum WorkloadHorizon {
 }
 
 impl WorkloadHorizon {
+    /// Every horizon the nightly job stores a forecast for.
+    pub const ALL: [WorkloadHorizon; 3] = [
+        WorkloadHorizon::SevenDays,
+        WorkloadHorizon::FourteenDays,
+        WorkloadHorizon::ThirtyDays,
+    ];
+
+    pub fn days(&self) -> i64 {
+        match self {
+            WorkloadHorizon::SevenDays => 7,
+            WorkloadHorizon::FourteenDays => 14,
+            WorkloadHorizon::ThirtyDays => 30,
+        }
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             WorkloadHorizon::SevenDays => "7d",
@@ -41,7 +57,44 @@ impl TryFrom<&str> for WorkloadHorizon {
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+/// How forecast buckets are grouped. Weeks are consecutive seven-day spans
+/// starting on the day the forecast was generated.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum WorkloadGranularity {
+    #[default]
+    Day,
+    Week,
+}
+
+impl WorkloadGranularity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkloadGranularity::Day => "day",
+            WorkloadGranularity::Week => "week",
+        }
+    }
+}
+
+impl fmt::Display for WorkloadGranularity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for WorkloadGranularity {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "day" => Ok(WorkloadGranularity::Day),
+            "week" => Ok(WorkloadGranularity::Week),
+            other => Err(format!("unsupported workload granularity: {other}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum WorkloadRiskLevel {
     Ok,
@@ -78,7 +131,7 @@ impl TryFrom<&str> for WorkloadRiskLevel {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ContributingTaskSummary {
     pub task_id: String,
@@ -88,7 +141,19 @@ pub struct ContributingTaskSummary {
     pub priority: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Hours due within `start..=end` (`YYYY-MM-DD`), with a 90% interval
+/// around the point estimate.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkloadForecastBucket {
+    pub start: String,
+    pub end: String,
+    pub expected_hours: f64,
+    pub low_hours: f64,
+    pub high_hours: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct WorkloadForecastRecord {
     pub horizon: WorkloadHorizon,
@@ -98,9 +163,14 @@ pub struct WorkloadForecastRecord {
     pub capacity_threshold: f64,
     pub contributing_tasks: Vec<ContributingTaskSummary>,
     pub confidence: f64,
+    /// Lower and upper bounds of the 90% interval around `total_hours`.
+    pub low_hours: f64,
+    pub high_hours: f64,
+    /// Daily buckets; weekly views are rolled up from these on read.
+    pub buckets: Vec<WorkloadForecastBucket>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct WorkloadForecastResponse {
     pub horizon: String,
@@ -110,5 +180,9 @@ pub struct WorkloadForecastResponse {
     pub capacity_threshold: f64,
     pub contributing_tasks: Vec<ContributingTaskSummary>,
     pub confidence: f64,
+    pub low_hours: f64,
+    pub high_hours: f64,
+    pub granularity: WorkloadGranularity,
+    pub buckets: Vec<WorkloadForecastBucket>,
     pub recommendations: Vec<String>,
 }
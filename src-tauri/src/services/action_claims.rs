@@ -0,0 +1,306 @@
+//! Spots actions the agent says it took or is about to take ("I've added the
+//! review for Friday 3pm", "已为您创建任务") and checks them against the tool
+//! calls of the same turn, so the UI can warn when a reply claims something
+//! that never happened.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimedActionKind {
+    CreateTask,
+    /// Putting something on the calendar or giving a task a time.
+    Schedule,
+    Remind,
+    UpdateTask,
+    CompleteTask,
+    DeleteTask,
+    CreateGoal,
+}
+
+impl ClaimedActionKind {
+    /// Tools whose successful call backs up the claim.
+    fn backing_tools(&self) -> &'static [&'static str] {
+        match self {
+            ClaimedActionKind::CreateTask => &[
+                "create_task",
+                "create_recurring_task",
+                "generate_recurring_instances",
+            ],
+            ClaimedActionKind::Schedule => &[
+                "create_calendar_event",
+                "update_calendar_event",
+                "create_task",
+                "update_task",
+                "create_recurring_task",
+            ],
+            ClaimedActionKind::Remind => &[
+                "create_calendar_event",
+                "create_task",
+                "update_task",
+                "create_recurring_task",
+            ],
+            ClaimedActionKind::UpdateTask => &[
+                "update_task",
+                "update_recurring_task",
+                "update_calendar_event",
+                "add_task_dependency",
+                "create_dependency_chain",
+                "remove_task_dependency",
+                "associate_task_with_goal",
+            ],
+            ClaimedActionKind::CompleteTask => &["update_task"],
+            ClaimedActionKind::DeleteTask => &["delete_task"],
+            ClaimedActionKind::CreateGoal => &["create_goal"],
+        }
+    }
+
+    /// Lowercase keywords that name the action, English and Chinese.
+    fn keywords(&self) -> &'static [&'static str] {
+        match self {
+            ClaimedActionKind::CreateTask => &[
+                "create a task",
+                "create the task",
+                "created a task",
+                "created the task",
+                "add a task",
+                "add the task",
+                "added a task",
+                "added the task",
+                "创建任务",
+                "创建了任务",
+                "添加任务",
+                "添加了任务",
+                "新建任务",
+            ],
+            ClaimedActionKind::Schedule => &[
+                "schedule it",
+                "schedule the",
+                "schedule a",
+                "scheduled",
+                "put it on your calendar",
+                "added it to your calendar",
+                "block out",
+                "blocked out",
+                "安排在",
+                "安排到",
+                "安排了",
+                "为您安排",
+                "为你安排",
+                "加入日历",
+                "添加到日历",
+            ],
+            ClaimedActionKind::Remind => &["remind you", "set a reminder", "提醒您", "提醒你"],
+            ClaimedActionKind::UpdateTask => &[
+                "update the task",
+                "updated the task",
+                "moved the task",
+                "move the task",
+                "changed the due",
+                "change the due",
+                "更新了任务",
+                "更新任务",
+                "修改了",
+                "调整了",
+            ],
+            ClaimedActionKind::CompleteTask => &[
+                "mark it as done",
+                "marked it as done",
+                "mark it as complete",
+                "marked it as complete",
+                "marked the task",
+                "标记为完成",
+                "标记为已完成",
+            ],
+            ClaimedActionKind::DeleteTask => &[
+                "delete the task",
+                "deleted the task",
+                "remove the task",
+                "removed the task",
+                "删除了",
+                "删除任务",
+            ],
+            ClaimedActionKind::CreateGoal => &[
+                "create a goal",
+                "created a goal",
+                "set up a goal",
+                "创建目标",
+                "创建了目标",
+            ],
+        }
+    }
+
+    const ALL: [ClaimedActionKind; 7] = [
+        ClaimedActionKind::CreateTask,
+        ClaimedActionKind::Schedule,
+        ClaimedActionKind::Remind,
+        ClaimedActionKind::UpdateTask,
+        ClaimedActionKind::CompleteTask,
+        ClaimedActionKind::DeleteTask,
+        ClaimedActionKind::CreateGoal,
+    ];
+}
+
+/// Phrases in which the agent commits to an action itself, as opposed to
+/// suggesting one to the user.
+const COMMITMENT_MARKERS: [&str; 15] = [
+    "i'll ",
+    "i will ",
+    "i've ",
+    "i have ",
+    "i just ",
+    "i went ahead",
+    "i'm going to",
+    "我会",
+    "我将",
+    "我来",
+    "我已",
+    "已为您",
+    "已为你",
+    "已经为您",
+    "已经为你",
+];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UnverifiedReason {
+    /// No tool that could perform the action was called.
+    NoToolCall,
+    /// A matching tool was called but every such call failed.
+    ToolFailed,
+}
+
+/// An action the reply claims that no successful tool call backs up.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UnverifiedAction {
+    pub kind: ClaimedActionKind,
+    /// The sentence that makes the claim.
+    pub claim: String,
+    pub reason: UnverifiedReason,
+}
+
+/// A tool call made while answering, and whether it succeeded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutedTool {
+    pub name: String,
+    pub succeeded: bool,
+}
+
+/// Sentences of `reply` that commit to an action, with the actions named.
+/// Questions ("Shall I schedule it?") are not commitments.
+pub fn detect_claimed_actions(reply: &str) -> Vec<(ClaimedActionKind, String)> {
+    let mut claims = Vec::new();
+    for sentence in sentences(reply) {
+        let lower = sentence.to_lowercase();
+        if !COMMITMENT_MARKERS
+            .iter()
+            .any(|marker| lower.contains(marker))
+        {
+            continue;
+        }
+        for kind in ClaimedActionKind::ALL {
+            if kind
+                .keywords()
+                .iter()
+                .any(|keyword| lower.contains(keyword))
+            {
+                claims.push((kind, sentence.to_string()));
+            }
+        }
+    }
+    claims
+}
+
+/// Claims in `reply` not backed by a successful call in `executed`.
+pub fn find_unverified_actions(reply: &str, executed: &[ExecutedTool]) -> Vec<UnverifiedAction> {
+    detect_claimed_actions(reply)
+        .into_iter()
+        .filter_map(|(kind, claim)| {
+            let mut matching = executed
+                .iter()
+                .filter(|tool| kind.backing_tools().contains(&tool.name.as_str()))
+                .peekable();
+            let reason = if matching.peek().is_none() {
+                UnverifiedReason::NoToolCall
+            } else if matching.any(|tool| tool.succeeded) {
+                return None;
+            } else {
+                UnverifiedReason::ToolFailed
+            };
+            Some(UnverifiedAction {
+                kind,
+                claim,
+                reason,
+            })
+        })
+        .collect()
+}
+
+/// Splits on sentence ends and line breaks, dropping questions.
+fn sentences(text: &str) -> Vec<&str> {
+    let mut start = 0;
+    let mut parts = Vec::new();
+    for (index, c) in text.char_indices() {
+        if matches!(c, '.' | '!' | '?' | '。' | '！' | '？' | '\n') {
+            let end = index + c.len_utf8();
+            let is_question = matches!(c, '?' | '？');
+            if !is_question {
+                parts.push(&text[start..end]);
+            }
+            start = end;
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+        .into_iter()
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool(name: &str, succeeded: bool) -> ExecutedTool {
+        ExecutedTool {
+            name: name.to_string(),
+            succeeded,
+        }
+    }
+
+    #[test]
+    fn commitments_without_a_tool_call_are_flagged() {
+        let unverified = find_unverified_actions("Sure! I'll schedule the review Friday 3pm.", &[]);
+        assert_eq!(unverified.len(), 1);
+        assert_eq!(unverified[0].kind, ClaimedActionKind::Schedule);
+        assert_eq!(unverified[0].reason, UnverifiedReason::NoToolCall);
+        assert_eq!(unverified[0].claim, "I'll schedule the review Friday 3pm.");
+
+        let chinese = find_unverified_actions("好的，已为您创建任务「周报」。", &[]);
+        assert_eq!(chinese.len(), 1);
+        assert_eq!(chinese[0].kind, ClaimedActionKind::CreateTask);
+    }
+
+    #[test]
+    fn successful_tool_calls_back_the_claim() {
+        let reply = "I've added a task for the review and will remind you on Friday.";
+        assert!(find_unverified_actions(reply, &[tool("create_task", true)]).is_empty());
+
+        let failed = find_unverified_actions(reply, &[tool("create_task", false)]);
+        assert_eq!(failed.len(), 2);
+        assert!(failed
+            .iter()
+            .all(|action| action.reason == UnverifiedReason::ToolFailed));
+    }
+
+    #[test]
+    fn suggestions_and_questions_are_not_claims() {
+        assert!(detect_claimed_actions("You could create a task for this.").is_empty());
+        assert!(detect_claimed_actions("Should I schedule it for Friday?").is_empty());
+        assert!(detect_claimed_actions("需要我帮你安排时间吗？").is_empty());
+        assert!(detect_claimed_actions("I'll check your schedule first.").is_empty());
+    }
+}
@@ -1,14 +1,80 @@
+use crate::db::repositories::planning_repository::PlanningRepository;
 use crate::error::{AppError, AppResult};
+use crate::models::task::{TaskRecord, TaskUpdateInput};
+use crate::services::action_claims::{find_unverified_actions, ExecutedTool, UnverifiedAction};
 use crate::services::ai_service::AiService;
+use crate::services::cache_service::CacheService;
+use crate::services::goal_service::GoalService;
+use crate::services::planning_service::{GeneratePlanInput, PlanningService};
+use crate::services::schedule_optimizer::ScheduleConstraints;
+use crate::services::similar_tasks;
+use crate::services::slash_commands::{
+    self, PlanDay, SlashCommand, TaskMatch, DONE_USAGE, HELP_TEXT,
+};
+use crate::services::task_service::TaskService;
+use crate::services::today_snapshot;
 
 use crate::services::tool_registry::{ToolCall, ToolRegistry, ToolResult};
+use crate::utils::semantic::semantic_hash;
+use crate::utils::tokens::TokenizerProfile;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
-use std::sync::Arc;
-use std::time::Instant;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
 
+/// Upper bound for retrieved memory in the agent prompt, in tokens.
+const MAX_MEMORY_CONTEXT_TOKENS: usize = 2_000;
+/// Headings and instructions appended around memory and history.
+const PROMPT_OVERHEAD_TOKENS: usize = 256;
+/// Similar completed tasks quoted in the agent prompt.
+const SIMILAR_TASKS_IN_PROMPT: usize = 3;
+/// Referenced items hydrated into the prompt for a single message.
+const MAX_CHAT_ATTACHMENTS: usize = 10;
+/// Upper bound for today's schedule in the agent prompt, in tokens.
+const MAX_TODAY_CONTEXT_TOKENS: usize = 400;
+/// Due and overdue tasks listed next to today's blocks.
+const TODAY_TASKS_IN_PROMPT: usize = 5;
+/// How long a schedule summary is reused across messages.
+const TODAY_CONTEXT_TTL: Duration = Duration::from_secs(60);
+
+/// Kind of entity a chat message can reference
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatAttachmentKind {
+    Task,
+    Goal,
+    Session,
+}
+
+impl ChatAttachmentKind {
+    const ALL: [ChatAttachmentKind; 3] = [
+        ChatAttachmentKind::Task,
+        ChatAttachmentKind::Goal,
+        ChatAttachmentKind::Session,
+    ];
+
+    /// Tool argument that names an entity of this kind
+    fn id_argument(&self) -> &'static str {
+        match self {
+            ChatAttachmentKind::Task => "task_id",
+            ChatAttachmentKind::Goal => "goal_id",
+            ChatAttachmentKind::Session => "session_id",
+        }
+    }
+}
+
+/// A task, goal or planning session the user referenced in a chat message,
+/// e.g. the task they had open when asking "push this to Friday"
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatAttachment {
+    pub kind: ChatAttachmentKind,
+    pub id: String,
+}
+
 /// Context for an AI agent interaction
 #[derive(Debug, Clone, Serialize)]
 pub struct AgentContext {
@@ -32,7 +98,8 @@ pub struct AgentResponse {
     /// Tool calls that were executed (if any)
     #[serde(default)]
     pub tool_calls: Vec<ToolCall>,
-    /// Whether the conversation was stored in memory
+    /// Whether the conversation was stored in memory; always `false` for
+    /// incognito conversations
     pub memory_stored: bool,
     /// Metadata about the interaction
     pub metadata: AgentMetadata,
@@ -61,6 +128,12 @@ pub struct AgentMetadata {
     /// Performance breakdown by component
     #[serde(skip_serializing_if = "Option::is_none")]
     pub performance: Option<PerformanceMetrics>,
+    /// Whether the reply was served from the response cache
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached: Option<bool>,
+    /// Actions the reply claims that no successful tool call backs up
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unverified_actions: Vec<UnverifiedAction>,
 }
 
 /// Performance metrics for different components
@@ -106,6 +179,8 @@ impl Default for AgentMetadata {
             errors: None,
             memory_available: None,
             performance: None,
+            cached: None,
+            unverified_actions: Vec::new(),
         }
     }
 }
@@ -120,6 +195,26 @@ pub struct AiAgentService {
 
     /// Memory service for conversation context
     memory_service: Option<Arc<crate::services::memory_service::MemoryService>>,
+
+    /// Task history used to ground estimates in similar past tasks
+    task_service: Option<Arc<TaskService>>,
+
+    /// Goals and planning sessions that chat messages can reference
+    goal_service: Option<Arc<GoalService>>,
+    planning_service: Option<Arc<PlanningService>>,
+
+    /// Today's schedule summary, reused for a minute across messages
+    today_context: Mutex<Option<TodayContext>>,
+
+    /// Replies to repeated questions, valid until the data changes
+    response_cache: Option<CacheService>,
+}
+
+/// Cached summary of today's schedule for the system prompt
+struct TodayContext {
+    built_at: Instant,
+    day: chrono::NaiveDate,
+    summary: Option<String>,
 }
 
 impl AiAgentService {
@@ -134,6 +229,11 @@ impl AiAgentService {
             ai_service,
             tool_registry,
             memory_service: None,
+            task_service: None,
+            goal_service: None,
+            planning_service: None,
+            today_context: Mutex::new(None),
+            response_cache: None,
         }
     }
 
@@ -153,9 +253,49 @@ impl AiAgentService {
             ai_service,
             tool_registry,
             memory_service: Some(memory_service),
+            task_service: None,
+            goal_service: None,
+            planning_service: None,
+            today_context: Mutex::new(None),
+            response_cache: None,
         }
     }
 
+    /// Quote similar completed tasks in the prompt when the user's message
+    /// resembles them
+    ///
+    /// # Arguments
+    /// * `task_service` - Service used to look up completed tasks
+    pub fn with_task_history(mut self, task_service: Arc<TaskService>) -> Self {
+        self.task_service = Some(task_service);
+        self
+    }
+
+    /// Resolve goals and planning sessions attached to chat messages
+    ///
+    /// # Arguments
+    /// * `goal_service` - Service used to look up referenced goals
+    /// * `planning_service` - Service used to look up referenced sessions
+    pub fn with_attachment_sources(
+        mut self,
+        goal_service: Arc<GoalService>,
+        planning_service: Arc<PlanningService>,
+    ) -> Self {
+        self.goal_service = Some(goal_service);
+        self.planning_service = Some(planning_service);
+        self
+    }
+
+    /// Reuse replies to repeated questions until a task, project, goal or
+    /// time block changes or the cache entry expires
+    ///
+    /// # Arguments
+    /// * `cache` - Cache that stores the replies
+    pub fn with_response_cache(mut self, cache: CacheService) -> Self {
+        self.response_cache = Some(cache);
+        self
+    }
+
     /// Main chat method that orchestrates the full agent flow
     ///
     /// # Arguments
@@ -165,6 +305,52 @@ impl AiAgentService {
     /// # Returns
     /// * `AgentResponse` containing the AI's response and metadata
     pub async fn chat(&self, conversation_id: &str, message: &str) -> AppResult<AgentResponse> {
+        self.chat_with_attachments(conversation_id, message, &[], false)
+            .await
+    }
+
+    /// Chat about specific tasks, goals or planning sessions
+    ///
+    /// The referenced entities are described in the prompt, and tool calls
+    /// that leave out the matching id argument act on them.
+    ///
+    /// # Arguments
+    /// * `conversation_id` - Unique identifier for this conversation
+    /// * `message` - User's message
+    /// * `attachments` - Entities the message refers to
+    /// * `incognito` - Keep this conversation out of memory: nothing is
+    ///   retrieved for it or stored from it, for the rest of the session
+    pub async fn chat_with_attachments(
+        &self,
+        conversation_id: &str,
+        message: &str,
+        attachments: &[ChatAttachment],
+        incognito: bool,
+    ) -> AppResult<AgentResponse> {
+        if attachments.len() > MAX_CHAT_ATTACHMENTS {
+            return Err(AppError::validation(format!(
+                "每条消息最多引用 {MAX_CHAT_ATTACHMENTS} 个条目"
+            )));
+        }
+
+        // Once incognito, a conversation stays incognito
+        let incognito = match self.memory_service.as_ref() {
+            Some(memory_service) => {
+                if incognito {
+                    memory_service.mark_incognito(conversation_id);
+                }
+                memory_service.is_incognito(conversation_id)
+            }
+            None => incognito,
+        };
+
+        // Slash commands are answered directly, without the model
+        if let Some(command) = SlashCommand::parse(message) {
+            return self
+                .run_slash_command(conversation_id, command, attachments)
+                .await;
+        }
+
         let start_time = Instant::now();
         let correlation_id = uuid::Uuid::new_v4().to_string();
 
@@ -183,12 +369,16 @@ impl AiAgentService {
             conversation_id = conversation_id,
             correlation_id = %correlation_id,
             message_len = message.len(),
+            incognito = incognito,
             "Starting agent chat"
         );
 
         // Build context from memory and tools
         let context_start = Instant::now();
-        let context = match self.build_context(conversation_id, message).await {
+        let context = match self
+            .build_context(conversation_id, message, attachments, incognito)
+            .await
+        {
             Ok(ctx) => ctx,
             Err(e) => {
                 error!(
@@ -200,7 +390,7 @@ impl AiAgentService {
                 // Fallback to minimal context
                 AgentContext {
                     conversation_id: conversation_id.to_string(),
-                    available_tools: self.tool_registry.get_tool_schemas(),
+                    available_tools: self.tool_registry.tool_schemas_for(Some(conversation_id)),
                     system_prompt: "You are a helpful AI assistant.".to_string(),
                     history_messages: Vec::new(),
                 }
@@ -208,6 +398,16 @@ impl AiAgentService {
         };
         perf_metrics.context_building_ms = context_start.elapsed().as_millis();
 
+        // Repeated questions against unchanged data, prompt and facts reuse
+        // the earlier reply. Incognito replies are never written to the cache
+        let cache_key = self
+            .response_cache
+            .as_ref()
+            .filter(|_| !incognito)
+            .map(|_| self.response_cache_key(conversation_id, message, attachments));
+        let cached_reply = self.cached_response(cache_key.as_deref()).await;
+        let from_cache = cached_reply.is_some();
+
         // Check memory service availability
         let memory_available = self.memory_service.is_some();
         let mut error_details = Vec::new();
@@ -240,21 +440,33 @@ impl AiAgentService {
         let history_messages = &context.history_messages;
 
         // First AI call with tools
-        let ai_start = Instant::now();
-        let ai_response = self
-            .call_ai_with_tools(message, system_prompt, tool_schemas, history_messages)
-            .await?;
-        perf_metrics.ai_api_ms += ai_start.elapsed().as_millis();
+        let ai_response = match cached_reply {
+            Some(message) => AiResponse {
+                message,
+                tool_calls: Vec::new(),
+            },
+            None => {
+                let ai_start = Instant::now();
+                let response = self
+                    .call_ai_with_tools(message, system_prompt, tool_schemas, history_messages)
+                    .await?;
+                perf_metrics.ai_api_ms += ai_start.elapsed().as_millis();
+                response
+            }
+        };
 
         let mut final_message = ai_response.message.clone();
         let mut tool_calls_executed = Vec::new();
         let mut tools_used = Vec::new();
+        let mut executed_tools = Vec::new();
 
         // Check if AI wants to use tools
         if !ai_response.tool_calls.is_empty() {
+            let tool_calls = self.fill_attachment_ids(ai_response.tool_calls.clone(), attachments);
+
             debug!(
                 target: "ai_agent_service",
-                tool_count = ai_response.tool_calls.len(),
+                tool_count = tool_calls.len(),
                 correlation_id = %correlation_id,
                 "AI requested tool calls"
             );
@@ -262,13 +474,17 @@ impl AiAgentService {
             // Execute tool calls with error handling
             let tool_start = Instant::now();
             let tool_results = self
-                .execute_tool_calls_with_retry(ai_response.tool_calls.clone(), &correlation_id)
+                .execute_tool_calls_with_retry(tool_calls.clone(), conversation_id, &correlation_id)
                 .await;
             perf_metrics.tool_execution_ms = tool_start.elapsed().as_millis();
 
             // Track which tools were used and collect errors
-            for (tool_call, result) in ai_response.tool_calls.iter().zip(tool_results.iter()) {
+            for (tool_call, result) in tool_calls.iter().zip(tool_results.iter()) {
                 tools_used.push(tool_call.name.clone());
+                executed_tools.push(ExecutedTool {
+                    name: tool_call.name.clone(),
+                    succeeded: result.error.is_none(),
+                });
                 if let Some(ref error) = result.error {
                     let mut context_map = HashMap::new();
                     context_map.insert("tool_name".to_string(), tool_call.name.clone());
@@ -293,7 +509,8 @@ impl AiAgentService {
                 }
             }
 
-            tool_calls_executed = ai_response.tool_calls.clone();
+            tool_calls_executed = tool_calls;
+            self.invalidate_today_context();
 
             // Send tool results back to AI for final response
             let ai_start2 = Instant::now();
@@ -303,9 +520,36 @@ impl AiAgentService {
             perf_metrics.ai_api_ms += ai_start2.elapsed().as_millis();
         }
 
+        // Replies that promise an action the tools never took are flagged so
+        // the UI can warn instead of letting the user rely on it
+        let unverified_actions = find_unverified_actions(&final_message, &executed_tools);
+        if !unverified_actions.is_empty() {
+            warn!(
+                target: "ai_agent_service",
+                correlation_id = %correlation_id,
+                unverified = unverified_actions.len(),
+                "Reply claims actions no tool call performed"
+            );
+        }
+
+        // Only plain answers are cached; anything that ran a tool may have
+        // changed data and must reach the model again
+        if !from_cache && tool_calls_executed.is_empty() {
+            if let (Some(cache), Some(key)) = (self.response_cache.as_ref(), cache_key.as_deref()) {
+                if let Err(e) = cache.put_agent_response(key, &final_message).await {
+                    warn!(
+                        target: "ai_agent_service",
+                        error = %e,
+                        correlation_id = %correlation_id,
+                        "Failed to cache agent response"
+                    );
+                }
+            }
+        }
+
         // Store conversation in memory (with error handling)
         let storage_start = Instant::now();
-        let memory_stored = if memory_available {
+        let memory_stored = if memory_available && !incognito {
             match self
                 .store_conversation(
                     conversation_id,
@@ -324,6 +568,8 @@ impl AiAgentService {
                         },
                         memory_available: Some(memory_available),
                         performance: Some(perf_metrics.clone()),
+                        cached: Some(from_cache),
+                        unverified_actions: unverified_actions.clone(),
                     },
                 )
                 .await
@@ -380,19 +626,281 @@ impl AiAgentService {
                 },
                 memory_available: Some(memory_available),
                 performance: Some(perf_metrics),
+                cached: Some(from_cache),
+                unverified_actions,
             },
         })
     }
 
+    /// Key for the response cache: the message plus the inputs the user
+    /// controls that shape the reply, namely the agent prompt template and
+    /// the memory facts. The rolling history and the retrieved memory are
+    /// left out, as every reply adds to them and a repeated question would
+    /// never hit. Data changes are covered by the revision the cache stores
+    /// alongside each entry.
+    fn response_cache_key(
+        &self,
+        conversation_id: &str,
+        message: &str,
+        attachments: &[ChatAttachment],
+    ) -> String {
+        let mut tools: Vec<String> = self
+            .tool_registry
+            .tool_names()
+            .into_iter()
+            .filter(|name| {
+                self.tool_registry
+                    .is_tool_allowed(Some(conversation_id), name)
+            })
+            .collect();
+        tools.sort();
+        let prompt_template = self.ai_service.agent_prompt_template().ok();
+        let facts = self
+            .memory_service
+            .as_ref()
+            .and_then(|memory_service| memory_service.get_facts().ok())
+            .and_then(|facts| facts.format_for_prompt());
+        let context = serde_json::json!({
+            "day": chrono::Local::now().date_naive().to_string(),
+            "attachments": attachments,
+            "tools": tools,
+            "prompt_template": prompt_template,
+            "facts": facts,
+        });
+        semantic_hash(message, Some(&context))
+    }
+
+    async fn cached_response(&self, key: Option<&str>) -> Option<String> {
+        let (cache, key) = (self.response_cache.as_ref()?, key?);
+        match cache.get_agent_response(key).await {
+            Ok(hit) => hit,
+            Err(e) => {
+                warn!(
+                    target: "ai_agent_service",
+                    error = %e,
+                    "Failed to read agent response cache"
+                );
+                None
+            }
+        }
+    }
+
+    /// Answer a slash command by calling services directly
+    ///
+    /// The reply has the same shape as a model response, with the command
+    /// reported in `tools_executed`.
+    async fn run_slash_command(
+        &self,
+        conversation_id: &str,
+        command: SlashCommand,
+        attachments: &[ChatAttachment],
+    ) -> AppResult<AgentResponse> {
+        let start_time = Instant::now();
+        let correlation_id = uuid::Uuid::new_v4().to_string();
+        let name = command.name();
+
+        let message = match command {
+            SlashCommand::Done { query } => self.slash_done(&query, attachments)?,
+            SlashCommand::Today => self.slash_today()?,
+            SlashCommand::Plan { day } => self.slash_plan(day).await?,
+            SlashCommand::Help => HELP_TEXT.to_string(),
+            SlashCommand::Invalid { usage } => format!("{usage}\n\n{HELP_TEXT}"),
+            SlashCommand::Unknown { name } => format!("未知命令：/{name}\n\n{HELP_TEXT}"),
+        };
+
+        let latency_ms = start_time.elapsed().as_millis();
+        info!(
+            target: "ai_agent_service",
+            conversation_id = conversation_id,
+            correlation_id = %correlation_id,
+            command = %name,
+            latency_ms = latency_ms,
+            "Slash command completed"
+        );
+
+        Ok(AgentResponse {
+            message,
+            tool_calls: Vec::new(),
+            memory_stored: false,
+            metadata: AgentMetadata {
+                latency_ms,
+                tools_executed: vec![name],
+                correlation_id: Some(correlation_id),
+                memory_available: Some(self.memory_service.is_some()),
+                ..AgentMetadata::default()
+            },
+        })
+    }
+
+    fn slash_task_service(&self) -> AppResult<&Arc<TaskService>> {
+        self.task_service
+            .as_ref()
+            .ok_or_else(|| AppError::Other("任务服务不可用".to_string()))
+    }
+
+    /// `/done <task>`, or `/done` with exactly one attached task
+    fn slash_done(&self, query: &str, attachments: &[ChatAttachment]) -> AppResult<String> {
+        let task_service = self.slash_task_service()?;
+        let query = if query.is_empty() {
+            let mut attached = attachments
+                .iter()
+                .filter(|attachment| attachment.kind == ChatAttachmentKind::Task);
+            match (attached.next(), attached.next()) {
+                (Some(only), None) => only.id.clone(),
+                _ => return Ok(DONE_USAGE.to_string()),
+            }
+        } else {
+            query.to_string()
+        };
+
+        let tasks = task_service.list_tasks()?;
+        let task = match slash_commands::find_task(&tasks, &query) {
+            TaskMatch::One(task) => task,
+            TaskMatch::None => return Ok(format!("没有找到匹配“{query}”的未完成任务。")),
+            TaskMatch::Many(candidates) => {
+                let lines: Vec<String> = candidates
+                    .iter()
+                    .map(|task| slash_commands::format_task_line(task))
+                    .collect();
+                return Ok(format!(
+                    "有多个任务匹配“{query}”，请使用更完整的标题或任务ID：\n{}",
+                    lines.join("\n")
+                ));
+            }
+        };
+
+        if task.status == "done" {
+            return Ok(format!("任务“{}”已经完成。", task.title));
+        }
+
+        let updated = task_service.update_task(
+            &task.id,
+            TaskUpdateInput {
+                status: Some("done".to_string()),
+                completed_at: Some(Some(chrono::Utc::now().to_rfc3339())),
+                ..TaskUpdateInput::default()
+            },
+        )?;
+        self.invalidate_today_context();
+        Ok(format!("已将任务“{}”标记为完成。", updated.title))
+    }
+
+    /// `/today`: tasks due or planned today, plus overdue ones
+    fn slash_today(&self) -> AppResult<String> {
+        let tasks = self.slash_task_service()?.list_tasks()?;
+        let now = chrono::Utc::now();
+        let today = now.with_timezone(&chrono::Local).date_naive();
+        let agenda = slash_commands::agenda_for(&tasks, today, now);
+        Ok(slash_commands::format_agenda(&agenda))
+    }
+
+    /// `/plan [today|tomorrow]`: plans that day's open tasks with the
+    /// built-in optimizer
+    async fn slash_plan(&self, day: PlanDay) -> AppResult<String> {
+        let planning_service = self
+            .planning_service
+            .as_ref()
+            .ok_or_else(|| AppError::Other("规划服务不可用".to_string()))?;
+        let tasks = self.slash_task_service()?.list_tasks()?;
+
+        let now = chrono::Local::now();
+        let date = day.date(now.date_naive());
+        let agenda = slash_commands::agenda_for(&tasks, date, now.with_timezone(&chrono::Utc));
+        let task_ids: Vec<String> = agenda
+            .due
+            .iter()
+            .chain(&agenda.planned)
+            .chain(&agenda.overdue)
+            .map(|task| task.id.clone())
+            .collect();
+        if task_ids.is_empty() {
+            return Ok(format!("{}没有需要安排的任务。", day.label()));
+        }
+
+        let day_start = |date: chrono::NaiveDate| {
+            date.and_time(chrono::NaiveTime::MIN)
+                .and_local_timezone(chrono::Local)
+                .earliest()
+                .map(|start| start.to_rfc3339())
+        };
+        let planning_start_at = match day {
+            PlanDay::Today => Some(now.to_rfc3339()),
+            PlanDay::Tomorrow => day_start(date),
+        };
+        let planning_end_at = date.succ_opt().and_then(day_start);
+
+        let view = planning_service
+            .generate_plan(GeneratePlanInput {
+                task_ids,
+                project_id: None,
+                constraints: Some(ScheduleConstraints {
+                    planning_start_at,
+                    planning_end_at,
+                    ..ScheduleConstraints::default()
+                }),
+                preference_id: None,
+                seed: None,
+                include_later_items: false,
+                privacy_mode: None,
+                optimizer_only: true,
+                offline: true,
+            })
+            .await?;
+
+        let titles: HashMap<&str, &str> = tasks
+            .iter()
+            .map(|task| (task.id.as_str(), task.title.as_str()))
+            .collect();
+        let mut reply = format!(
+            "已为{}生成计划（{} 个方案，会话ID: {}）。",
+            day.label(),
+            view.options.len(),
+            view.session.id
+        );
+        if let Some(top) = view.options.first() {
+            for block in &top.blocks {
+                let time = |value: &str| {
+                    chrono::DateTime::parse_from_rfc3339(value)
+                        .map(|value| {
+                            value
+                                .with_timezone(&chrono::Local)
+                                .format("%H:%M")
+                                .to_string()
+                        })
+                        .unwrap_or_else(|_| value.to_string())
+                };
+                let title = titles
+                    .get(block.task_id.as_str())
+                    .copied()
+                    .unwrap_or(&block.task_id);
+                reply.push_str(&format!(
+                    "\n- {}–{} {}",
+                    time(&block.start_at),
+                    time(&block.end_at),
+                    title
+                ));
+            }
+        }
+        Ok(reply)
+    }
+
     /// Build context for the AI from memory and tool schemas
     ///
     /// # Arguments
     /// * `conversation_id` - Conversation identifier
     /// * `message` - User's current message
+    /// * `attachments` - Entities the message refers to
+    /// * `incognito` - Leave out facts, retrieved memory and history
     ///
     /// # Returns
     /// * `AgentContext` containing memory context, tool schemas, and system prompt
-    async fn build_context(&self, conversation_id: &str, message: &str) -> AppResult<AgentContext> {
+    async fn build_context(
+        &self,
+        conversation_id: &str,
+        message: &str,
+        attachments: &[ChatAttachment],
+        incognito: bool,
+    ) -> AppResult<AgentContext> {
         let start_time = std::time::Instant::now();
 
         debug!(
@@ -402,11 +910,64 @@ impl AiAgentService {
         );
 
         // Load tool schemas
-        let tool_schemas = self.tool_registry.get_tool_schemas();
+        let tool_schemas = self.tool_registry.tool_schemas_for(Some(conversation_id));
+
+        // Budget memory and history against the model's context window
+        let profile = self.ai_service.model_profile()?;
+        let tokenizer = profile.tokenizer;
+        let mut system_prompt = self.ai_service.agent_system_prompt()?;
+
+        // Facts the user wrote about themselves always come first, ahead of
+        // anything retrieved, and count against the fixed part of the budget
+        let memory_service = self.memory_service.as_ref().filter(|_| !incognito);
+        let facts_context =
+            memory_service.and_then(|memory_service| match memory_service.get_facts() {
+                Ok(facts) => facts.format_for_prompt(),
+                Err(e) => {
+                    warn!(
+                        target: "ai_agent_service",
+                        error = %e,
+                        "Failed to load memory facts"
+                    );
+                    None
+                }
+            });
+        if let Some(ref facts) = facts_context {
+            system_prompt.push_str("\n\n## About the User\n");
+            system_prompt.push_str(facts);
+            system_prompt.push_str("\n\nTreat these as standing facts about the user. Never propose plans that break a constraint.");
+        }
+
+        // A compact view of today answers "what's on today" without a tool call
+        if let Some(today) = self.today_schedule_context(&tokenizer) {
+            system_prompt.push_str("\n\n## Today's Schedule\n");
+            system_prompt.push_str(&today);
+        }
+
+        // Items the user attached to this message are what "this" and "it"
+        // refer to, so the model should not have to ask which one is meant
+        if let Some(referenced) = self.describe_attachments(attachments) {
+            system_prompt.push_str("\n\n## Referenced Items\n");
+            system_prompt.push_str(&referenced);
+            system_prompt.push_str("\n\nThe user's message is about these items. Act on them directly instead of asking which task, goal or plan is meant.");
+        }
+
+        let tools_json = serde_json::to_string(&tool_schemas).unwrap_or_default();
+        let fixed_tokens = tokenizer.estimate(&system_prompt)
+            + tokenizer.estimate(&tools_json)
+            + tokenizer.estimate(message)
+            + PROMPT_OVERHEAD_TOKENS;
+        let mut remaining_tokens = profile.input_budget().saturating_sub(fixed_tokens);
+        let memory_budget = (remaining_tokens / 4).min(MAX_MEMORY_CONTEXT_TOKENS);
 
         // Get memory context if available
-        let memory_context = if let Some(ref memory_service) = self.memory_service {
-            match memory_service.get_conversation_context(message, 2000).await {
+        let memory_context = if memory_budget == 0 {
+            None
+        } else if let Some(memory_service) = memory_service {
+            match memory_service
+                .get_conversation_context_with_tokenizer(message, memory_budget, &tokenizer)
+                .await
+            {
                 Ok(context) => {
                     if context.is_empty() {
                         None
@@ -429,12 +990,13 @@ impl AiAgentService {
 
         // Reconstruct recent conversation messages from this conversation_id
         let mut history_messages: Vec<ChatMessage> = Vec::new();
-        if let Some(ref memory_service) = self.memory_service {
+        if let Some(memory_service) = memory_service {
             if let Ok(mut docs) = memory_service
                 .search_by_conversation_id(conversation_id)
                 .await
             {
                 // Sort by created_at ascending, then take the last few exchanges to control size
+                docs.retain(|doc| !doc.metadata.ignored);
                 docs.sort_by(|a, b| a.created_at.cmp(&b.created_at));
 
                 // Limit to last 6 documents (≈ 6 exchanges)
@@ -456,68 +1018,56 @@ impl AiAgentService {
             }
         }
 
-        // Build system prompt with tool instructions and memory context
-        let current_date = chrono::Local::now().format("%Y-%m-%d").to_string();
-        let current_time = chrono::Local::now().format("%H:%M:%S").to_string();
-        let current_datetime = chrono::Local::now().to_rfc3339();
-
-        let mut system_prompt = format!(
-            r#"You are CogniCal, an intelligent AI assistant specialized in unified time management and productivity.
-
-## Current Information
-- **Current Date**: {}
-- **Current Time**: {}
-- **Current DateTime**: {}
-
-## Your Capabilities
-You have access to powerful tools for unified time management that combines tasks and calendar events as one cohesive system. You should:
-
-1. **Unified Time Management**: Tasks and calendar events are now unified - they're all "time-based items"
-2. **Be Proactive**: When users ask about schedule, calendar, tasks, or time management, immediately use the appropriate tools
-3. **Understand Context and User Intent**: 
-   - Pay attention to conversation flow and user intent
-   - If user initially asks to "create/安排/schedule" something and then provides details, they want to CREATE not SEARCH
-   - "recent", "latest", "upcoming" means future dates from today
-   - "past", "previous", "last week" means dates before today
-   - "today" means the current date: {}
-
-## CRITICAL: Tool Selection Rules
-**For Creating New Items:**
-- User says "创建/安排/schedule/建个/做个 + 时间 + 事情" → use `create_time_block`
-- User provides complete info after you ask for details → CREATE, not search
-- User says "快速安排 X 在 Y 时间" → use `quick_schedule`
-- If user gives you title + time, they want to CREATE
-
-**For Searching/Viewing Existing Items:**
-- User says "查找/搜索/找/看看 + 已有/现有的 + 时间安排" → use `search_time_items`
-- User says "查看/显示/列出 + 时间安排" → use `list_time_items`
-- User says "检查/确认 + 是否已安排" → use `search_time_items`
-
-**For Updating Items:**
-- User says "修改/更新/调整/重安排 + 已有的 + 时间" → use `update_time_item`
-
-## Tool Usage Rules
-- ALWAYS call tools when users ask for data (don't ask "what date is it")
-- Call tools FIRST, then format the results nicely for the user
-- If multiple tools are needed, call them in parallel when possible
-- Don't ask users for information you can infer from context
-- **Remember**: All time-based management is now unified - tasks and calendar events are the same thing!
-- **CRITICAL**: If user previously asked to create something and now provides the missing details, use CREATE tools!
-
-## Response Style
-- Be concise and actionable
-- Show results in clear, formatted lists
-- Use emojis appropriately for better UX (📅 for schedules, ⏰ for deadlines, 🕒 for time blocks)
-- Remember user preferences from conversation history"#,
-            current_date, current_time, current_datetime, current_date
-        );
+        // Completed tasks resembling the message, so estimates follow real durations
+        let similar_context =
+            self.task_service.as_ref().and_then(|task_service| {
+                match task_service.find_similar(crate::models::task::SimilarTasksQuery {
+                    title: message.to_string(),
+                    limit: Some(SIMILAR_TASKS_IN_PROMPT),
+                    ..Default::default()
+                }) {
+                    Ok(result) => similar_tasks::format_for_prompt(&result),
+                    Err(e) => {
+                        warn!(
+                            target: "ai_agent_service",
+                            error = %e,
+                            "Failed to look up similar past tasks"
+                        );
+                        None
+                    }
+                }
+            });
+
+        // Drop the oldest exchanges until history fits what is left
+        if let Some(ref context) = memory_context {
+            remaining_tokens = remaining_tokens.saturating_sub(tokenizer.estimate(context));
+        }
+        if let Some(ref context) = similar_context {
+            remaining_tokens = remaining_tokens.saturating_sub(tokenizer.estimate(context));
+        }
+        let mut history_tokens: usize = history_messages
+            .iter()
+            .map(|m| tokenizer.estimate(&m.content))
+            .sum();
+        while history_tokens > remaining_tokens && history_messages.len() >= 2 {
+            for dropped in history_messages.drain(..2) {
+                history_tokens -= tokenizer.estimate(&dropped.content);
+            }
+        }
 
+        // Append memory context to the system prompt (user override or default)
         if let Some(ref context) = memory_context {
             system_prompt.push_str("\n\n## Conversation History & Context\n");
             system_prompt.push_str(context);
             system_prompt.push_str("\n\nUse this history to provide personalized and context-aware responses. Remember user preferences and past interactions.");
         }
 
+        if let Some(ref context) = similar_context {
+            system_prompt.push_str("\n\n## Similar Past Tasks\n");
+            system_prompt.push_str(context);
+            system_prompt.push_str("\n\nWhen estimating or scheduling related work, mention how long these took (e.g. \"last time this took 3h\") and prefer the actual durations over the original estimates.");
+        }
+
         if !history_messages.is_empty() {
             system_prompt
                 .push_str("\n\n## Recent Conversation (historical messages provided separately)\n");
@@ -529,6 +1079,8 @@ You have access to powerful tools for unified time management that combines task
             target: "ai_agent_service",
             elapsed_ms = elapsed.as_millis(),
             memory_available = memory_context.is_some(),
+            history_messages = history_messages.len(),
+            context_window = profile.context_window_tokens,
             "Context building completed"
         );
 
@@ -553,6 +1105,7 @@ You have access to powerful tools for unified time management that combines task
 
         // Get API key from settings
         let api_key = self.ai_service.get_api_key()?;
+        let profile = self.ai_service.model_profile()?;
 
         // Build messages array with history
         let mut messages = vec![json!({"role": "system", "content": system_prompt})];
@@ -563,9 +1116,10 @@ You have access to powerful tools for unified time management that combines task
 
         // Build request body with tools
         let mut request_body = json!({
-            "model": "deepseek-chat",
+            "model": profile.model,
             "messages": messages,
             "temperature": 0.7,
+            "max_tokens": profile.max_output_tokens,
         });
 
         // Add tools if available
@@ -715,10 +1269,196 @@ You have access to powerful tools for unified time management that combines task
             })?
     }
 
+    /// Summarize today's applied blocks and most pressing tasks
+    ///
+    /// The summary is reused for a minute so a burst of messages does not
+    /// rescan every task; failures leave it out of the prompt.
+    fn today_schedule_context(&self, tokenizer: &TokenizerProfile) -> Option<String> {
+        let task_service = self.task_service.as_ref()?;
+        let now = chrono::Local::now();
+        let today = now.date_naive();
+
+        if let Ok(cache) = self.today_context.lock() {
+            if let Some(cached) = cache.as_ref().filter(|cached| {
+                cached.day == today && cached.built_at.elapsed() < TODAY_CONTEXT_TTL
+            }) {
+                return cached.summary.clone();
+            }
+        }
+
+        let summary = match summarize_today(task_service, now, tokenizer) {
+            Ok(summary) => summary,
+            Err(e) => {
+                warn!(
+                    target: "ai_agent_service",
+                    error = %e,
+                    "Failed to summarize today's schedule"
+                );
+                return None;
+            }
+        };
+
+        if let Ok(mut cache) = self.today_context.lock() {
+            *cache = Some(TodayContext {
+                built_at: Instant::now(),
+                day: today,
+                summary: summary.clone(),
+            });
+        }
+        summary
+    }
+
+    /// Drop the cached schedule after tools or commands may have changed it
+    fn invalidate_today_context(&self) {
+        if let Ok(mut cache) = self.today_context.lock() {
+            *cache = None;
+        }
+    }
+
+    /// Describe the attached entities for the prompt, one line each
+    ///
+    /// Entities that cannot be loaded are still listed so the model can tell
+    /// the user the reference is stale instead of guessing.
+    fn describe_attachments(&self, attachments: &[ChatAttachment]) -> Option<String> {
+        if attachments.is_empty() {
+            return None;
+        }
+
+        let lines: Vec<String> = attachments
+            .iter()
+            .map(|attachment| {
+                let described = match attachment.kind {
+                    ChatAttachmentKind::Task => self.describe_task(&attachment.id),
+                    ChatAttachmentKind::Goal => self.describe_goal(&attachment.id),
+                    ChatAttachmentKind::Session => self.describe_session(&attachment.id),
+                };
+                match described {
+                    Some(description) => format!("- {description}"),
+                    None => {
+                        warn!(
+                            target: "ai_agent_service",
+                            kind = ?attachment.kind,
+                            id = %attachment.id,
+                            "Referenced item could not be loaded"
+                        );
+                        format!(
+                            "- {} {} (not found; it may have been deleted)",
+                            attachment.kind.id_argument(),
+                            attachment.id
+                        )
+                    }
+                }
+            })
+            .collect();
+
+        Some(lines.join("\n"))
+    }
+
+    fn describe_task(&self, id: &str) -> Option<String> {
+        let task = self.task_service.as_ref()?.get_task(id).ok()?;
+        let mut description = format!(
+            "Task \"{}\" (task_id: {}, status: {}, priority: {}",
+            task.title, task.id, task.status, task.priority
+        );
+        if let Some(due_at) = task.due_at.as_deref() {
+            description.push_str(&format!(", due: {due_at}"));
+        }
+        if let Some(minutes) = task.estimated_minutes {
+            description.push_str(&format!(", estimate: {minutes} min"));
+        }
+        description.push(')');
+        Some(description)
+    }
+
+    fn describe_goal(&self, id: &str) -> Option<String> {
+        let goal = self.goal_service.as_ref()?.get_goal(id).ok()?;
+        let mut description = format!(
+            "Goal \"{}\" (goal_id: {}, status: {}, priority: {}",
+            goal.title,
+            goal.id,
+            goal.status.as_str(),
+            goal.priority
+        );
+        if let Some(target_date) = goal.target_date {
+            description.push_str(&format!(", target: {}", target_date.format("%Y-%m-%d")));
+        }
+        description.push(')');
+        Some(description)
+    }
+
+    fn describe_session(&self, id: &str) -> Option<String> {
+        let view = self.planning_service.as_ref()?.get_session(id).ok()?;
+        let session = &view.session;
+        let mut description = format!(
+            "Planning session (session_id: {}, status: {}, generated: {}, {} task(s), {} option(s)",
+            session.id,
+            session.status,
+            session.generated_at,
+            session.task_ids.len(),
+            view.options.len()
+        );
+        if let Some(selected) = session.selected_option_id.as_deref() {
+            description.push_str(&format!(", selected option: {selected}"));
+        }
+        description.push(')');
+        Some(description)
+    }
+
+    /// Fill in a missing `task_id`, `goal_id` or `session_id` argument when
+    /// the tool takes one and exactly one entity of that kind is attached
+    ///
+    /// Arguments the model did provide are never overwritten.
+    pub fn fill_attachment_ids(
+        &self,
+        mut tool_calls: Vec<ToolCall>,
+        attachments: &[ChatAttachment],
+    ) -> Vec<ToolCall> {
+        if attachments.is_empty() {
+            return tool_calls;
+        }
+
+        for call in &mut tool_calls {
+            let Some(parameters) = self.tool_registry.tool_parameters(&call.name) else {
+                continue;
+            };
+            if call.arguments.is_null() {
+                call.arguments = JsonValue::Object(Default::default());
+            }
+            let Some(arguments) = call.arguments.as_object_mut() else {
+                continue;
+            };
+
+            for kind in ChatAttachmentKind::ALL {
+                let key = kind.id_argument();
+                let declared = parameters
+                    .get("properties")
+                    .and_then(|properties| properties.get(key))
+                    .is_some();
+                if !declared || arguments.get(key).is_some_and(|value| !value.is_null()) {
+                    continue;
+                }
+
+                let mut matching = attachments.iter().filter(|a| a.kind == kind);
+                if let (Some(only), None) = (matching.next(), matching.next()) {
+                    debug!(
+                        target: "ai_agent_service",
+                        tool_name = %call.name,
+                        argument = key,
+                        "Filled tool argument from chat attachment"
+                    );
+                    arguments.insert(key.to_string(), JsonValue::String(only.id.clone()));
+                }
+            }
+        }
+
+        tool_calls
+    }
+
     /// Execute tool calls with retry logic for failed executions
     async fn execute_tool_calls_with_retry(
         &self,
         tool_calls: Vec<ToolCall>,
+        conversation_id: &str,
         correlation_id: &str,
     ) -> Vec<ToolResult> {
         info!(
@@ -729,12 +1469,19 @@ You have access to powerful tools for unified time management that combines task
         );
 
         // First attempt
-        let mut results = self.tool_registry.execute_tools(tool_calls.clone()).await;
+        let mut results = self
+            .tool_registry
+            .execute_tools_for(Some(conversation_id), tool_calls.clone())
+            .await;
 
-        // Identify failed tool calls
+        // Identify failed tool calls; refused ones would only be refused again
         let mut failed_indices = Vec::new();
         for (idx, result) in results.iter().enumerate() {
-            if result.error.is_some() {
+            if result.error.is_some()
+                && self
+                    .tool_registry
+                    .is_tool_allowed(Some(conversation_id), &tool_calls[idx].name)
+            {
                 failed_indices.push(idx);
             }
         }
@@ -758,7 +1505,10 @@ You have access to powerful tools for unified time management that combines task
                 .collect();
 
             // Execute retry
-            let retry_results = self.tool_registry.execute_tools(retry_calls).await;
+            let retry_results = self
+                .tool_registry
+                .execute_tools_for(Some(conversation_id), retry_calls)
+                .await;
 
             // Update results with retry outcomes
             for (failed_idx_pos, &original_idx) in failed_indices.iter().enumerate() {
@@ -950,6 +1700,117 @@ pub struct ChatMessage {
     pub content: String,
 }
 
+/// Today's progress so far, open applied blocks and up to
+/// `TODAY_TASKS_IN_PROMPT` due or overdue tasks, cut off at
+/// `MAX_TODAY_CONTEXT_TOKENS`. `None` when there
+/// is nothing scheduled or due.
+fn summarize_today(
+    task_service: &TaskService,
+    now: chrono::DateTime<chrono::Local>,
+    tokenizer: &TokenizerProfile,
+) -> AppResult<Option<String>> {
+    let today = now.date_naive();
+    let day_start = today
+        .and_time(chrono::NaiveTime::MIN)
+        .and_local_timezone(chrono::Local)
+        .earliest()
+        .unwrap_or(now)
+        .with_timezone(&chrono::Utc);
+    let day_end = day_start + chrono::Duration::days(1);
+
+    let tasks = task_service.list_tasks()?;
+    let blocks = task_service.pool().with_connection(|conn| {
+        PlanningRepository::list_applied_time_blocks_between(
+            conn,
+            &day_start.to_rfc3339(),
+            &day_end.to_rfc3339(),
+        )
+    })?;
+    let tasks_by_id: HashMap<&str, &TaskRecord> =
+        tasks.iter().map(|task| (task.id.as_str(), task)).collect();
+    let local_time = |value: &str, pattern: &str| {
+        chrono::DateTime::parse_from_rfc3339(value)
+            .map(|value| {
+                value
+                    .with_timezone(&chrono::Local)
+                    .format(pattern)
+                    .to_string()
+            })
+            .unwrap_or_else(|_| value.to_string())
+    };
+
+    let mut block_lines = Vec::new();
+    for block in blocks
+        .iter()
+        .filter(|block| matches!(block.status.as_str(), "planned" | "in_progress"))
+    {
+        let title = tasks_by_id
+            .get(block.task_id.as_str())
+            .map_or(block.task_id.as_str(), |task| task.title.as_str());
+        block_lines.push(format!(
+            "- {}-{} {} [{}]",
+            local_time(&block.start_at, "%H:%M"),
+            local_time(&block.end_at, "%H:%M"),
+            title,
+            block.status
+        ));
+    }
+
+    let agenda = slash_commands::agenda_for(&tasks, today, now.with_timezone(&chrono::Utc));
+    let task_lines: Vec<String> = agenda
+        .overdue
+        .iter()
+        .map(|task| (task, "overdue since"))
+        .chain(agenda.due.iter().map(|task| (task, "due")))
+        .take(TODAY_TASKS_IN_PROMPT)
+        .map(|(task, label)| {
+            let due = task.due_at.as_deref().unwrap_or_default();
+            format!(
+                "- {} (task_id: {}, priority: {}, {} {})",
+                task.title,
+                task.id,
+                task.priority,
+                label,
+                local_time(due, "%m-%d %H:%M")
+            )
+        })
+        .collect();
+
+    if block_lines.is_empty() && task_lines.is_empty() {
+        return Ok(None);
+    }
+
+    let progress = task_service
+        .pool()
+        .with_connection(|conn| today_snapshot::today_snapshot(conn, now.fixed_offset()))?;
+    let mut summary = format!(
+        "Now: {}\nSo far: {} completed, {} focus minutes, {} planned minutes left in {} blocks",
+        now.format("%Y-%m-%d %A %H:%M"),
+        progress.completed_so_far,
+        progress.focus_minutes_so_far,
+        progress.remaining_planned_minutes,
+        progress.remaining_blocks
+    );
+    for (heading, lines) in [("Blocks:", block_lines), ("Due or overdue:", task_lines)] {
+        if lines.is_empty() {
+            continue;
+        }
+        summary.push('\n');
+        summary.push_str(heading);
+        let total = lines.len();
+        for (index, line) in lines.into_iter().enumerate() {
+            let candidate = format!("{summary}\n{line}");
+            if tokenizer.estimate(&candidate) > MAX_TODAY_CONTEXT_TOKENS {
+                summary.push_str(&format!("\n- ...and {} more", total - index));
+                break;
+            }
+            summary = candidate;
+        }
+    }
+
+    Ok(Some(summary))
+}
+
 impl AiAgentService {
     /// Extract user/assistant messages from our stored markdown content
     /// Expected format (created by MemoryService::create_document_content):
@@ -991,3 +1852,47 @@ impl AiAgentService {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DbPool;
+    use crate::models::task::TaskCreateInput;
+
+    #[test]
+    fn today_summary_lists_due_and_overdue_tasks() {
+        let dir = tempfile::tempdir().unwrap();
+        let task_service = TaskService::new(DbPool::new(dir.path().join("agent.db")).unwrap());
+        let now = chrono::Local::now();
+        let tokenizer = TokenizerProfile::default();
+
+        assert_eq!(
+            summarize_today(&task_service, now, &tokenizer).unwrap(),
+            None
+        );
+
+        for (title, due_at) in [
+            ("Send invoice", now),
+            ("Renew passport", now - chrono::Duration::days(2)),
+            ("Plan offsite", now + chrono::Duration::days(7)),
+        ] {
+            task_service
+                .create_task(TaskCreateInput {
+                    title: title.to_string(),
+                    due_at: Some(due_at.to_rfc3339()),
+                    ..TaskCreateInput::default()
+                })
+                .unwrap();
+        }
+
+        let summary = summarize_today(&task_service, now, &tokenizer)
+            .unwrap()
+            .expect("summary");
+        assert!(summary.contains("Send invoice"));
+        assert!(summary.contains("Renew passport (task_id:"));
+        assert!(summary.contains("overdue since"));
+        assert!(!summary.contains("Plan offsite"));
+        assert!(summary.contains("So far: 0 completed"));
+        assert!(tokenizer.estimate(&summary) <= MAX_TODAY_CONTEXT_TOKENS);
+    }
+}
@@ -8,6 +8,7 @@ pub enum AiCacheOperation {
     ParseTask,
     Recommendations,
     Schedule,
+    AgentChat,
 }
 
 impl AiCacheOperation {
@@ -16,6 +17,7 @@ impl AiCacheOperation {
             AiCacheOperation::ParseTask => "parse",
             AiCacheOperation::Recommendations => "recommend",
             AiCacheOperation::Schedule => "schedule",
+            AiCacheOperation::AgentChat => "agent",
         }
     }
 }
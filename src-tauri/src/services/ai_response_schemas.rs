@@ -0,0 +1,304 @@
+use jsonschema::JSONSchema;
+use once_cell::sync::Lazy;
+use serde_json::{json, Value as JsonValue};
+
+/// Maximum number of validation errors reported back to the model.
+const MAX_REPORTED_ERRORS: usize = 10;
+
+/// Structured outputs whose shape is checked before deserialization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiResponseSchema {
+    ParsedTask,
+    SchedulePlan,
+    Recommendations,
+    TaskDecomposition,
+    HistoryNarrative,
+    PlanCritique,
+}
+
+static PARSED_TASK_SCHEMA: Lazy<JsonValue> = Lazy::new(|| {
+    let nullable_string = json!({ "type": ["string", "null"] });
+    let nullable_number = json!({ "type": ["number", "null"] });
+    let nullable_strings = json!({
+        "type": ["array", "null"],
+        "items": { "type": "string" }
+    });
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "ParsedTaskDto",
+        "type": "object",
+        "required": ["payload", "missingFields", "reasoning"],
+        "properties": {
+            "payload": {
+                "type": "object",
+                "properties": {
+                    "title": nullable_string,
+                    "description": nullable_string,
+                    "status": nullable_string,
+                    "priority": nullable_string,
+                    "plannedStartAt": nullable_string,
+                    "startAt": nullable_string,
+                    "dueAt": nullable_string,
+                    "completedAt": nullable_string,
+                    "estimatedMinutes": { "type": ["integer", "null"] },
+                    "estimatedHours": nullable_number,
+                    "tags": nullable_strings,
+                    "ownerId": nullable_string,
+                    "isRecurring": { "type": ["boolean", "null"] },
+                    "recurrence": { "type": ["object", "null"] },
+                    "taskType": nullable_string,
+                    "externalLinks": nullable_strings,
+                    "projectId": nullable_string
+                }
+            },
+            "missingFields": {
+                "type": "array",
+                "items": { "type": "string" }
+            },
+            "reasoning": {
+                "type": "object",
+                "properties": {
+                    "summary": nullable_string,
+                    "nextAction": nullable_string,
+                    "confidence": nullable_number,
+                    "cotSteps": {
+                        "type": ["array", "null"],
+                        "items": { "type": "object" }
+                    },
+                    "cotSummary": nullable_string,
+                    "complexityScore": nullable_number,
+                    "suggestedStartAt": nullable_string,
+                    "focusMode": {
+                        "type": ["object", "null"],
+                        "required": ["pomodoros"],
+                        "properties": {
+                            "pomodoros": { "type": "integer", "minimum": 0 },
+                            "recommendedSlots": nullable_strings
+                        }
+                    },
+                    "efficiencyPrediction": {
+                        "type": ["object", "null"],
+                        "required": ["expectedHours", "confidence"],
+                        "properties": {
+                            "expectedHours": { "type": "number" },
+                            "confidence": { "type": "number" }
+                        }
+                    },
+                    "metadata": { "type": ["object", "null"] }
+                }
+            }
+        }
+    })
+});
+
+static SCHEDULE_PLAN_SCHEMA: Lazy<JsonValue> = Lazy::new(|| {
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "SchedulePlanDto",
+        "type": "object",
+        "required": ["items"],
+        "properties": {
+            "items": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["title", "startAt", "endAt"],
+                    "properties": {
+                        "taskId": { "type": ["string", "null"] },
+                        "title": { "type": "string" },
+                        "startAt": { "type": "string" },
+                        "endAt": { "type": "string" },
+                        "confidence": { "type": ["number", "null"] },
+                        "notes": { "type": ["string", "null"] },
+                        "prepNote": { "type": ["string", "null"] }
+                    }
+                }
+            },
+            "telemetry": { "type": ["object", "null"] }
+        }
+    })
+});
+
+static RECOMMENDATIONS_SCHEMA: Lazy<JsonValue> = Lazy::new(|| {
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "RecommendationDto",
+        "type": "object",
+        "required": ["recommendations"],
+        "properties": {
+            "recommendations": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["title"],
+                    "properties": {
+                        "id": { "type": ["string", "null"] },
+                        "title": { "type": "string" },
+                        "detail": { "type": ["string", "null"] },
+                        "priority": { "type": ["string", "null"] },
+                        "impact": { "type": ["string", "null"] },
+                        "nextAction": { "type": ["string", "null"] }
+                    }
+                }
+            },
+            "telemetry": { "type": ["object", "null"] }
+        }
+    })
+});
+
+static TASK_DECOMPOSITION_SCHEMA: Lazy<JsonValue> = Lazy::new(|| {
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "TaskDecompositionDto",
+        "type": "object",
+        "required": ["subtasks"],
+        "properties": {
+            "subtasks": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["title"],
+                    "properties": {
+                        "title": { "type": "string" },
+                        "description": { "type": ["string", "null"] },
+                        "estimatedMinutes": { "type": ["integer", "null"] },
+                        "order": { "type": ["integer", "null"], "minimum": 1 },
+                        "dependsOn": {
+                            "type": ["array", "null"],
+                            "items": { "type": "integer" }
+                        }
+                    }
+                }
+            },
+            "rationale": { "type": ["string", "null"] },
+            "telemetry": { "type": ["object", "null"] }
+        }
+    })
+});
+
+static HISTORY_NARRATIVE_SCHEMA: Lazy<JsonValue> = Lazy::new(|| {
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "HistoryNarrativeDto",
+        "type": "object",
+        "required": ["narrative"],
+        "properties": {
+            "narrative": { "type": "string", "minLength": 1 },
+            "telemetry": { "type": ["object", "null"] }
+        }
+    })
+});
+
+static PLAN_CRITIQUE_SCHEMA: Lazy<JsonValue> = Lazy::new(|| {
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "PlanCritiqueDto",
+        "type": "object",
+        "required": ["issues"],
+        "properties": {
+            "issues": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["kind", "message"],
+                    "properties": {
+                        "kind": {
+                            "type": "string",
+                            "enum": ["too_dense", "ignores_constraint", "other"]
+                        },
+                        "message": { "type": "string", "minLength": 1 }
+                    }
+                }
+            },
+            "telemetry": { "type": ["object", "null"] }
+        }
+    })
+});
+
+static PARSED_TASK_VALIDATOR: Lazy<JSONSchema> = Lazy::new(|| compile(&PARSED_TASK_SCHEMA));
+static SCHEDULE_PLAN_VALIDATOR: Lazy<JSONSchema> = Lazy::new(|| compile(&SCHEDULE_PLAN_SCHEMA));
+static RECOMMENDATIONS_VALIDATOR: Lazy<JSONSchema> = Lazy::new(|| compile(&RECOMMENDATIONS_SCHEMA));
+static TASK_DECOMPOSITION_VALIDATOR: Lazy<JSONSchema> =
+    Lazy::new(|| compile(&TASK_DECOMPOSITION_SCHEMA));
+static HISTORY_NARRATIVE_VALIDATOR: Lazy<JSONSchema> =
+    Lazy::new(|| compile(&HISTORY_NARRATIVE_SCHEMA));
+static PLAN_CRITIQUE_VALIDATOR: Lazy<JSONSchema> = Lazy::new(|| compile(&PLAN_CRITIQUE_SCHEMA));
+
+fn compile(schema: &JsonValue) -> JSONSchema {
+    JSONSchema::compile(schema).expect("built-in AI response schema compiles")
+}
+
+impl AiResponseSchema {
+    pub fn definition(self) -> &'static JsonValue {
+        match self {
+            AiResponseSchema::ParsedTask => &PARSED_TASK_SCHEMA,
+            AiResponseSchema::SchedulePlan => &SCHEDULE_PLAN_SCHEMA,
+            AiResponseSchema::Recommendations => &RECOMMENDATIONS_SCHEMA,
+            AiResponseSchema::TaskDecomposition => &TASK_DECOMPOSITION_SCHEMA,
+            AiResponseSchema::HistoryNarrative => &HISTORY_NARRATIVE_SCHEMA,
+            AiResponseSchema::PlanCritique => &PLAN_CRITIQUE_SCHEMA,
+        }
+    }
+
+    /// Checks a provider response, returning readable `path: message` errors.
+    pub fn validate(self, value: &JsonValue) -> Result<(), Vec<String>> {
+        let validator: &JSONSchema = match self {
+            AiResponseSchema::ParsedTask => &PARSED_TASK_VALIDATOR,
+            AiResponseSchema::SchedulePlan => &SCHEDULE_PLAN_VALIDATOR,
+            AiResponseSchema::Recommendations => &RECOMMENDATIONS_VALIDATOR,
+            AiResponseSchema::TaskDecomposition => &TASK_DECOMPOSITION_VALIDATOR,
+            AiResponseSchema::HistoryNarrative => &HISTORY_NARRATIVE_VALIDATOR,
+            AiResponseSchema::PlanCritique => &PLAN_CRITIQUE_VALIDATOR,
+        };
+
+        match validator.validate(value) {
+            Ok(()) => Ok(()),
+            Err(errors) => Err(errors
+                .take(MAX_REPORTED_ERRORS)
+                .map(|error| {
+                    let path = error.instance_path.to_string();
+                    if path.is_empty() {
+                        error.to_string()
+                    } else {
+                        format!("{path}: {error}")
+                    }
+                })
+                .collect()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_minimal_parse_response() {
+        let value = json!({
+            "payload": {"title": "整理周报", "tags": null},
+            "missingFields": [],
+            "reasoning": {"summary": "ok", "source": "online"}
+        });
+
+        assert_eq!(AiResponseSchema::ParsedTask.validate(&value), Ok(()));
+    }
+
+    #[test]
+    fn reports_paths_of_invalid_fields() {
+        let value = json!({
+            "items": [{"title": "Deep work", "startAt": "2025-05-01T09:00:00Z"}]
+        });
+
+        let errors = AiResponseSchema::SchedulePlan
+            .validate(&value)
+            .expect_err("endAt is missing");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("/items/0"));
+
+        let errors = AiResponseSchema::Recommendations
+            .validate(&json!({"recommendations": "none"}))
+            .expect_err("recommendations must be an array");
+        assert!(errors[0].contains("/recommendations"));
+    }
+}
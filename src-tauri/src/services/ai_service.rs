@@ -1,28 +1,45 @@
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 use std::time::{Duration as StdDuration, Instant};
 
-use chrono::{Duration, Utc};
+use chrono::{Duration, Local, Utc};
 use serde_json::{json, Value as JsonValue};
 use tokio::time::sleep;
 use tracing::{debug, warn};
 
+use crate::db::repositories::ai_debug_log_repository::{AiDebugLogRepository, AiDebugLogRow};
 use crate::db::repositories::ai_settings_repository::AiSettingsRepository;
+use crate::db::repositories::task_repository::TaskRepository;
 use crate::db::DbPool;
 use crate::error::{AiErrorCode, AppError, AppResult};
 use crate::models::ai::{TaskParseRequest, TaskParseResponse};
 use crate::models::ai_types::{
-    AiProvider, AiProviderMetadata, AiResponseSource, AiStatusDto, ParsedTaskDto,
-    RecommendationDto, SchedulePlanDto,
+    ActionItemCandidate, ActionItemExtractionDto, ActionItemsDto, AiDebugEntry, AiModelProfile,
+    AiProvider, AiProviderMetadata, AiResponseSource, AiStatusDto, DuplicateTaskRef,
+    ExtractedActionItemDto, HistoryNarrativeDto, ParsedTaskDto, PlanCritiqueDto,
+    ProposedSubtaskDto, RecommendationDto, SchedulePlanDto, TaskDecompositionDto,
 };
+use crate::models::prompt_template::PromptTemplateKey;
+use crate::services::ai_response_schemas::AiResponseSchema;
 use crate::services::cache_service::CacheService;
+use crate::services::prompt_template_service::{load_effective_templates, render_template};
 use crate::services::prompt_templates::{
-    build_recommendations_payload, build_schedule_payload, build_task_parse_payload,
-    recommendations_system_prompt, schedule_planning_system_prompt, task_parsing_system_prompt,
+    build_action_items_payload, build_decompose_payload, build_history_summary_payload,
+    build_plan_critique_payload, build_recommendations_payload, build_schedule_payload,
+    build_task_parse_payload, default_system_prompt, json_completion_prompt,
+    schema_correction_prompt,
 };
+use crate::services::session_metrics::{JsonRepairOutcome, JSON_REPAIR_METRICS};
+use crate::services::settings_service::{
+    load_ai_debug_log_enabled, load_ai_privacy_mode, settings_version,
+};
+use crate::services::similar_tasks::find_similar_tasks;
 use crate::utils::crypto::CryptoVault;
+use crate::utils::json_repair::repair_json;
 use crate::utils::redact::redact_sensitive_data;
-use crate::utils::semantic::semantic_hash;
+use crate::utils::semantic::{semantic_hash, text_similarity};
+use crate::utils::tokens::chunk_by_tokens;
 use reqwest::StatusCode;
 use uuid::Uuid;
 
@@ -32,30 +49,76 @@ pub struct AiService {
     provider: Arc<RwLock<Option<Arc<DeepSeekProvider>>>>,
     cache: CacheService,
     config: Arc<RwLock<AiServiceConfig>>,
+    /// Settings version `config` was loaded at.
+    config_version: Arc<AtomicU64>,
+    /// Endpoint and key used instead of the configured ones; tests point
+    /// the service at a stub server with it.
+    #[cfg(feature = "test-support")]
+    endpoint_override: Option<(String, String)>,
 }
 
 const KEY_DEEPSEEK_API: &str = "deepseek_api_key";
 
+/// Notes are split so each request (prompt + chunk + answer) stays well inside the context window.
+const ACTION_ITEM_CHUNK_TOKENS: usize = 3_000;
+const ACTION_ITEM_MAX_CHUNKS: usize = 12;
+/// Titles at or above this similarity are treated as the same action item.
+const ACTION_ITEM_DUPLICATE_THRESHOLD: f64 = 0.8;
+/// Newest debug log entries kept when the AI debug log is enabled.
+const AI_DEBUG_LOG_LIMIT: usize = 200;
+const DEFAULT_MAX_SUBTASKS: usize = 6;
+const MAX_SUBTASKS: usize = 12;
+/// Completed look-alikes passed along so estimates follow real durations.
+const SIMILAR_TASKS_FOR_DECOMPOSE: usize = 3;
+const SUBTASK_MIN_MINUTES: i64 = 5;
+const SUBTASK_MAX_MINUTES: i64 = 8 * 60;
+const RATE_LIMITED_MESSAGE: &str = "DeepSeek 请求过于频繁，请稍后重试";
+/// Longest `Retry-After` waited out before retrying; longer ones go back to
+/// the caller with the hint.
+const MAX_RETRY_AFTER_WAIT_SECS: u64 = 10;
+
 #[derive(Debug, Clone)]
 struct AiServiceConfig {
     api_key: Option<String>,
     api_base_url: String,
     model: String,
+    model_profile: AiModelProfile,
     http_timeout: StdDuration,
     cache_ttl: Duration,
+    /// Effective system prompt templates (user overrides or defaults).
+    prompt_templates: HashMap<PromptTemplateKey, String>,
+    /// Opt-in: store redacted request/response pairs per correlation id.
+    debug_log_enabled: bool,
 }
 
 impl AiService {
     pub fn new(db_pool: DbPool) -> AppResult<Self> {
+        let version = settings_version();
         let config = AiServiceConfig::load(&db_pool)?;
+        Self::from_config(db_pool, config, version)
+    }
+
+    #[cfg(feature = "test-support")]
+    fn with_endpoint_override(db_pool: DbPool, endpoint: (String, String)) -> AppResult<Self> {
+        let version = settings_version();
+        let config = AiServiceConfig::load(&db_pool)?.with_endpoint(Some(&endpoint));
+        let mut service = Self::from_config(db_pool, config, version)?;
+        service.endpoint_override = Some(endpoint);
+        Ok(service)
+    }
+
+    fn from_config(db_pool: DbPool, config: AiServiceConfig, version: u64) -> AppResult<Self> {
         let cache = CacheService::new(db_pool.clone(), config.cache_ttl)?;
-        let provider = config.build_provider()?;
+        let provider = config.build_provider(&db_pool)?;
 
         Ok(Self {
             db_pool,
             provider: Arc::new(RwLock::new(provider)),
             cache,
             config: Arc::new(RwLock::new(config)),
+            config_version: Arc::new(AtomicU64::new(version)),
+            #[cfg(feature = "test-support")]
+            endpoint_override: None,
         })
     }
 
@@ -126,6 +189,192 @@ impl AiService {
         Ok(dto)
     }
 
+    /// Extract reviewable action items from (possibly long) meeting notes.
+    ///
+    /// Notes are chunked by token budget, each chunk is sent to the provider,
+    /// and the merged list is flagged against existing tasks so the caller can
+    /// bulk-create only the new ones.
+    pub async fn extract_action_items(&self, text: &str) -> AppResult<ActionItemExtractionDto> {
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            return Err(AppError::validation("会议记录内容不能为空"));
+        }
+
+        let chunks = chunk_by_tokens(trimmed, ACTION_ITEM_CHUNK_TOKENS);
+        if chunks.len() > ACTION_ITEM_MAX_CHUNKS {
+            return Err(AppError::validation_with_details(
+                "会议记录过长，请拆分后再提取",
+                json!({ "chunkCount": chunks.len(), "maxChunks": ACTION_ITEM_MAX_CHUNKS }),
+            ));
+        }
+
+        self.refresh_configuration()?;
+        let provider = self.current_provider()?;
+
+        let chunk_count = chunks.len();
+        let reference_date = Utc::now().to_rfc3339();
+        let mut extracted: Vec<ExtractedActionItemDto> = Vec::new();
+        let mut telemetry: Option<AiProviderMetadata> = None;
+
+        for (index, chunk) in chunks.into_iter().enumerate() {
+            debug!(
+                target: "app::ai",
+                chunk = index + 1,
+                chunk_count,
+                "extracting action items from notes chunk"
+            );
+            let input = json!({
+                "notes": chunk,
+                "chunkIndex": index + 1,
+                "chunkCount": chunk_count,
+                "referenceDate": reference_date,
+            });
+            let dto = provider.extract_action_items(&input).await?;
+            merge_action_items(&mut extracted, dto.items);
+            telemetry = accumulate_telemetry(telemetry, dto.telemetry);
+        }
+
+        let existing_tasks = self.db_pool.with_connection(|conn| {
+            TaskRepository::list_all(conn)?
+                .into_iter()
+                .map(|row| row.into_record())
+                .collect::<AppResult<Vec<_>>>()
+        })?;
+
+        let items: Vec<ActionItemCandidate> = extracted
+            .into_iter()
+            .map(|item| {
+                let duplicate_of = existing_tasks
+                    .iter()
+                    .filter(|task| task.status != "archived")
+                    .map(|task| (task, text_similarity(&item.title, &task.title)))
+                    .filter(|(_, score)| *score >= ACTION_ITEM_DUPLICATE_THRESHOLD)
+                    .max_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(task, score)| DuplicateTaskRef {
+                        task_id: task.id.clone(),
+                        title: task.title.clone(),
+                        similarity: score,
+                    });
+                ActionItemCandidate {
+                    selected: duplicate_of.is_none(),
+                    item,
+                    duplicate_of,
+                }
+            })
+            .collect();
+
+        let duplicate_count = items.iter().filter(|c| c.duplicate_of.is_some()).count();
+
+        Ok(ActionItemExtractionDto {
+            items,
+            chunk_count,
+            duplicate_count,
+            generated_at: Utc::now().to_rfc3339(),
+            telemetry,
+        })
+    }
+
+    /// Proposes ordered subtasks with estimates for a task. Nothing is
+    /// created; the caller reviews the proposal first.
+    pub async fn decompose_task(
+        &self,
+        task_id: &str,
+        max_subtasks: Option<usize>,
+    ) -> AppResult<TaskDecompositionDto> {
+        let (task, privacy_mode, similar) = self.db_pool.with_connection(|conn| {
+            let task = TaskRepository::find_by_id(conn, task_id)?
+                .ok_or_else(AppError::not_found)?
+                .into_record()?;
+            let similar = find_similar_tasks(
+                conn,
+                &task.title,
+                &task.tags,
+                Some(task_id),
+                SIMILAR_TASKS_FOR_DECOMPOSE,
+            )?;
+            Ok((task, load_ai_privacy_mode(conn)?, similar))
+        })?;
+        if matches!(task.status.as_str(), "done" | "archived") {
+            return Err(AppError::validation("已完成或已归档的任务无需拆分"));
+        }
+        if privacy_mode {
+            return Err(AppError::validation("隐私模式下无法使用 AI 拆分任务"));
+        }
+        let max_subtasks = max_subtasks
+            .unwrap_or(DEFAULT_MAX_SUBTASKS)
+            .clamp(2, MAX_SUBTASKS);
+
+        self.refresh_configuration()?;
+        let provider = self.current_provider()?;
+
+        debug!(target: "app::ai", task_id = %task_id, max_subtasks, "decomposing task");
+        let input = json!({
+            "task": {
+                "title": task.title,
+                "description": task.description,
+                "estimatedMinutes": task.estimated_minutes,
+                "dueAt": task.due_at,
+                "priority": task.priority,
+                "tags": task.tags,
+            },
+            "maxSubtasks": max_subtasks,
+            "similarPastTasks": similar.matches,
+            "referenceDate": Utc::now().to_rfc3339(),
+        });
+        let mut dto = provider.decompose_task(&input).await?;
+        dto.subtasks = normalize_subtasks(std::mem::take(&mut dto.subtasks), max_subtasks);
+        if dto.subtasks.is_empty() {
+            return Err(AppError::ai(
+                AiErrorCode::InvalidResponse,
+                "AI 未能给出可用的子任务",
+            ));
+        }
+        dto.task_id = task.id;
+        dto.total_estimated_minutes = dto
+            .subtasks
+            .iter()
+            .filter_map(|subtask| subtask.estimated_minutes)
+            .sum();
+        dto.generated_at = Utc::now().to_rfc3339();
+
+        Ok(dto)
+    }
+
+    /// Rewrites a template-based activity summary into a narrative. The
+    /// caller decides whether the summary may leave the device.
+    pub async fn summarize_history(&self, input: &JsonValue) -> AppResult<HistoryNarrativeDto> {
+        self.refresh_configuration()?;
+        let provider = self.current_provider()?;
+
+        debug!(target: "app::ai", "summarizing history");
+        let mut dto = provider.summarize_history(input).await?;
+        dto.narrative = dto.narrative.trim().to_string();
+        if dto.narrative.is_empty() {
+            return Err(AppError::ai(
+                AiErrorCode::InvalidResponse,
+                "AI 未能生成活动总结",
+            ));
+        }
+
+        Ok(dto)
+    }
+
+    /// Reviews a generated plan against the user's preferences. Issues
+    /// without a message are dropped.
+    pub async fn critique_plan(&self, input: &JsonValue) -> AppResult<PlanCritiqueDto> {
+        self.refresh_configuration()?;
+        let provider = self.current_provider()?;
+
+        debug!(target: "app::ai", "critiquing plan");
+        let mut dto = provider.critique_plan(input).await?;
+        dto.issues.retain_mut(|issue| {
+            issue.message = issue.message.trim().to_string();
+            !issue.message.is_empty()
+        });
+
+        Ok(dto)
+    }
+
     pub async fn status(&self) -> AppResult<AiStatusDto> {
         self.refresh_configuration()?;
 
@@ -143,6 +392,7 @@ impl AiService {
                 latency_ms: None,
                 provider: None,
                 message: Some("DeepSeek API Key 未配置".to_string()),
+                json_repair: Some(JSON_REPAIR_METRICS.snapshot()),
             });
         }
 
@@ -158,6 +408,7 @@ impl AiService {
                     latency_ms,
                     provider: Some(metadata),
                     message: None,
+                    json_repair: Some(JSON_REPAIR_METRICS.snapshot()),
                 })
             }
             Err(error) => {
@@ -180,15 +431,54 @@ impl AiService {
         provider.chat(&message).await
     }
 
+    /// Context window and output limits of the configured model.
+    pub fn model_profile(&self) -> AppResult<AiModelProfile> {
+        self.refresh_configuration()?;
+
+        let guard = self.config.read().expect("config lock poisoned");
+        Ok(guard.model_profile.clone())
+    }
+
+    /// Rendered system prompt for the agent, honouring a valid user override.
+    pub fn agent_system_prompt(&self) -> AppResult<String> {
+        Ok(render_template(
+            &self.agent_prompt_template()?,
+            Local::now(),
+        ))
+    }
+
+    /// The agent prompt, user override or default, before its date and time
+    /// variables are filled in.
+    pub fn agent_prompt_template(&self) -> AppResult<String> {
+        self.refresh_configuration()?;
+
+        let guard = self.config.read().expect("config lock poisoned");
+        Ok(guard
+            .prompt_templates
+            .get(&PromptTemplateKey::Agent)
+            .map(String::as_str)
+            .unwrap_or_else(|| default_system_prompt(PromptTemplateKey::Agent))
+            .to_string())
+    }
+
+    /// Reloads the configuration only when settings changed since the last
+    /// load. The version is read before loading so a write that lands during
+    /// the load triggers another reload on the next call.
     fn refresh_configuration(&self) -> AppResult<()> {
+        let version = settings_version();
+        if self.config_version.load(Ordering::Acquire) == version {
+            return Ok(());
+        }
         let config = AiServiceConfig::load(&self.db_pool)?;
+        #[cfg(feature = "test-support")]
+        let config = config.with_endpoint(self.endpoint_override.as_ref());
 
         let mut provider_update: Option<Option<Arc<DeepSeekProvider>>> = None;
 
         {
             let mut current = self.config.write().expect("config lock poisoned");
             if current.differs_from(&config) {
-                provider_update = Some(config.build_provider()?);
+                provider_update = Some(config.build_provider(&self.db_pool)?);
                 *current = config;
             } else {
                 *current = config;
@@ -199,6 +489,7 @@ impl AiService {
             let mut guard = self.provider.write().expect("provider lock poisoned");
             *guard = update;
         }
+        self.config_version.store(version, Ordering::Release);
 
         Ok(())
     }
@@ -259,6 +550,14 @@ impl AiService {
         parsed.reasoning.metadata = Some(metadata);
     }
 
+    /// Redacted request/response recorded by the AI debug log.
+    pub fn debug_entry(&self, correlation_id: &str) -> AppResult<AiDebugEntry> {
+        self.db_pool
+            .with_connection(|conn| AiDebugLogRepository::find(conn, correlation_id.trim()))?
+            .ok_or_else(AppError::not_found)?
+            .into_record()
+    }
+
     /// Get the API key for direct API calls
     pub fn get_api_key(&self) -> AppResult<String> {
         let config = self.config.read().unwrap();
@@ -271,6 +570,88 @@ impl AiService {
     }
 }
 
+/// Stores the redacted exchange for `ai_debug_get`. Failures here only cost
+/// the debug entry, never the AI call itself.
+fn record_debug_entry(
+    db_pool: &DbPool,
+    operation: &str,
+    correlation_id: &str,
+    payload: &JsonValue,
+    follow_up: &[JsonValue],
+    result: &AppResult<RawChatResult>,
+    elapsed: StdDuration,
+) {
+    let request = redact_sensitive_data(&json!({ "payload": payload, "followUp": follow_up }))
+        .unwrap_or(JsonValue::Null);
+    let (response, error, latency_ms) = match result {
+        Ok(raw) => {
+            let response = DeepSeekProvider::parse_content(&raw.content, correlation_id)
+                .ok()
+                .and_then(|(content, _)| redact_sensitive_data(&content).ok())
+                .unwrap_or_else(|| json!({ "unparsed": true, "length": raw.content.len() }));
+            (Some(response), None, raw.latency_ms)
+        }
+        Err(err) => (None, Some(err.to_string()), elapsed.as_millis()),
+    };
+
+    let entry = AiDebugEntry {
+        correlation_id: correlation_id.to_string(),
+        operation: operation.to_string(),
+        request,
+        response,
+        error,
+        latency_ms: Some(latency_ms as i64),
+        created_at: Utc::now().to_rfc3339(),
+    };
+    let stored = AiDebugLogRow::from_record(&entry).and_then(|row| {
+        db_pool.with_connection(|conn| {
+            AiDebugLogRepository::insert(conn, &row)?;
+            AiDebugLogRepository::prune(conn, AI_DEBUG_LOG_LIMIT)?;
+            Ok(())
+        })
+    });
+    if let Err(err) = stored {
+        warn!(
+            target: "app::ai",
+            correlation_id,
+            error = %err,
+            "failed to store AI debug log entry"
+        );
+    }
+}
+
+/// Limits for the configured model, optionally overridden for self-hosted or
+/// newer models via `COGNICAL_DEEPSEEK_CONTEXT_WINDOW` and
+/// `COGNICAL_DEEPSEEK_MAX_OUTPUT_TOKENS`.
+fn model_profile_from_env(model: &str) -> AiModelProfile {
+    let mut profile = AiModelProfile::for_model(model);
+    let read = |name: &str| {
+        std::env::var(name)
+            .ok()
+            .and_then(|value| value.trim().parse::<usize>().ok())
+            .filter(|value| *value > 0)
+    };
+
+    if let Some(context_window) = read("COGNICAL_DEEPSEEK_CONTEXT_WINDOW") {
+        profile.context_window_tokens = context_window;
+    }
+    if let Some(max_output) = read("COGNICAL_DEEPSEEK_MAX_OUTPUT_TOKENS") {
+        profile.max_output_tokens = max_output;
+    }
+    if profile.max_output_tokens >= profile.context_window_tokens {
+        warn!(
+            target: "app::ai",
+            model,
+            context_window = profile.context_window_tokens,
+            max_output = profile.max_output_tokens,
+            "max output tokens must be below the context window, using model defaults"
+        );
+        profile = AiModelProfile::for_model(model);
+    }
+
+    profile
+}
+
 impl AiServiceConfig {
     fn from_env() -> Self {
         let api_key = std::env::var("COGNICAL_DEEPSEEK_API_KEY").ok();
@@ -281,17 +662,24 @@ impl AiServiceConfig {
             .ok()
             .unwrap_or_else(|| "deepseek-chat".to_string());
 
+        let model_profile = model_profile_from_env(&model);
+
         Self {
             api_key,
             api_base_url,
             model,
+            model_profile,
             http_timeout: StdDuration::from_secs(30),
             cache_ttl: Duration::days(7),
+            prompt_templates: HashMap::new(),
+            debug_log_enabled: false,
         }
     }
 
     fn load(db_pool: &DbPool) -> AppResult<Self> {
         let mut config = Self::from_env();
+        config.prompt_templates = db_pool.with_connection(load_effective_templates)?;
+        config.debug_log_enabled = db_pool.with_connection(load_ai_debug_log_enabled)?;
 
         if config.api_key.is_none() {
             let vault = CryptoVault::from_database_path(db_pool.path())?;
@@ -337,18 +725,31 @@ impl AiServiceConfig {
         Ok(config)
     }
 
+    #[cfg(feature = "test-support")]
+    fn with_endpoint(mut self, endpoint: Option<&(String, String)>) -> Self {
+        if let Some((base_url, api_key)) = endpoint {
+            self.api_base_url = base_url.trim_end_matches('/').to_string();
+            self.api_key = Some(api_key.clone());
+        }
+        self
+    }
+
     fn differs_from(&self, other: &Self) -> bool {
         self.api_key != other.api_key
             || self.api_base_url != other.api_base_url
             || self.model != other.model
+            || self.model_profile != other.model_profile
             || self.http_timeout != other.http_timeout
             || self.cache_ttl != other.cache_ttl
+            || self.prompt_templates != other.prompt_templates
+            || self.debug_log_enabled != other.debug_log_enabled
     }
 
-    fn build_provider(&self) -> AppResult<Option<Arc<DeepSeekProvider>>> {
+    fn build_provider(&self, db_pool: &DbPool) -> AppResult<Option<Arc<DeepSeekProvider>>> {
         match &self.api_key {
             Some(api_key) => {
-                let provider = DeepSeekProvider::try_new(self, api_key.clone())?;
+                let mut provider = DeepSeekProvider::try_new(self, api_key.clone())?;
+                provider.debug_log = self.debug_log_enabled.then(|| db_pool.clone());
                 Ok(Some(Arc::new(provider)))
             }
             None => Ok(None),
@@ -362,6 +763,10 @@ struct DeepSeekProvider {
     base_url: String,
     endpoint: String,
     model: String,
+    max_output_tokens: usize,
+    prompt_templates: HashMap<PromptTemplateKey, String>,
+    /// Set when the AI debug log is enabled.
+    debug_log: Option<DbPool>,
 }
 
 #[derive(Clone, Copy)]
@@ -369,6 +774,10 @@ enum DeepSeekOperation {
     ParseTask,
     Recommendations,
     Schedule,
+    ActionItems,
+    DecomposeTask,
+    SummarizeHistory,
+    CritiquePlan,
 }
 
 impl DeepSeekOperation {
@@ -377,14 +786,34 @@ impl DeepSeekOperation {
             DeepSeekOperation::ParseTask => "parseTask",
             DeepSeekOperation::Recommendations => "generateRecommendations",
             DeepSeekOperation::Schedule => "planSchedule",
+            DeepSeekOperation::ActionItems => "extractActionItems",
+            DeepSeekOperation::DecomposeTask => "decomposeTask",
+            DeepSeekOperation::SummarizeHistory => "summarizeHistory",
+            DeepSeekOperation::CritiquePlan => "critiquePlan",
         }
     }
 
-    fn system_prompt(self) -> &'static str {
+    fn prompt_key(self) -> PromptTemplateKey {
         match self {
-            DeepSeekOperation::ParseTask => task_parsing_system_prompt(),
-            DeepSeekOperation::Recommendations => recommendations_system_prompt(),
-            DeepSeekOperation::Schedule => schedule_planning_system_prompt(),
+            DeepSeekOperation::ParseTask => PromptTemplateKey::ParseTask,
+            DeepSeekOperation::Recommendations => PromptTemplateKey::Recommendations,
+            DeepSeekOperation::Schedule => PromptTemplateKey::Schedule,
+            DeepSeekOperation::ActionItems => PromptTemplateKey::ActionItems,
+            DeepSeekOperation::DecomposeTask => PromptTemplateKey::DecomposeTask,
+            DeepSeekOperation::SummarizeHistory => PromptTemplateKey::SummarizeHistory,
+            DeepSeekOperation::CritiquePlan => PromptTemplateKey::CritiquePlan,
+        }
+    }
+
+    fn response_schema(self) -> Option<AiResponseSchema> {
+        match self {
+            DeepSeekOperation::ParseTask => Some(AiResponseSchema::ParsedTask),
+            DeepSeekOperation::Recommendations => Some(AiResponseSchema::Recommendations),
+            DeepSeekOperation::Schedule => Some(AiResponseSchema::SchedulePlan),
+            DeepSeekOperation::ActionItems => None,
+            DeepSeekOperation::DecomposeTask => Some(AiResponseSchema::TaskDecomposition),
+            DeepSeekOperation::SummarizeHistory => Some(AiResponseSchema::HistoryNarrative),
+            DeepSeekOperation::CritiquePlan => Some(AiResponseSchema::PlanCritique),
         }
     }
 
@@ -393,6 +822,10 @@ impl DeepSeekOperation {
             DeepSeekOperation::ParseTask => 0.2,
             DeepSeekOperation::Recommendations => 0.4,
             DeepSeekOperation::Schedule => 0.3,
+            DeepSeekOperation::ActionItems => 0.2,
+            DeepSeekOperation::DecomposeTask => 0.3,
+            DeepSeekOperation::SummarizeHistory => 0.5,
+            DeepSeekOperation::CritiquePlan => 0.2,
         }
     }
 }
@@ -404,6 +837,38 @@ struct ChatInvocationResult {
     correlation_id: String,
 }
 
+/// Reply as received, before JSON parsing and repair.
+struct RawChatResult {
+    content: String,
+    tokens_used: HashMap<String, u64>,
+    latency_ms: u128,
+    correlation_id: String,
+}
+
+impl RawChatResult {
+    fn into_invocation(self, content: JsonValue) -> ChatInvocationResult {
+        ChatInvocationResult {
+            content,
+            tokens_used: self.tokens_used,
+            latency_ms: self.latency_ms,
+            correlation_id: self.correlation_id,
+        }
+    }
+}
+
+/// Adds the usage of an earlier attempt to the result that is returned.
+fn accumulate_usage(
+    tokens_used: &mut HashMap<String, u64>,
+    latency_ms: &mut u128,
+    earlier_tokens: HashMap<String, u64>,
+    earlier_latency_ms: u128,
+) {
+    for (key, value) in earlier_tokens {
+        *tokens_used.entry(key).or_insert(0) += value;
+    }
+    *latency_ms += earlier_latency_ms;
+}
+
 impl DeepSeekProvider {
     fn try_new(config: &AiServiceConfig, api_key: String) -> AppResult<Self> {
         let client = reqwest::Client::builder()
@@ -422,21 +887,174 @@ impl DeepSeekProvider {
             base_url,
             endpoint,
             model: config.model.clone(),
+            max_output_tokens: config.model_profile.max_output_tokens,
+            prompt_templates: config.prompt_templates.clone(),
+            debug_log: None,
         })
     }
 
+    /// Invokes the operation and checks the reply against its response schema.
+    /// A reply that does not match is sent back once with the validation errors
+    /// so the model can correct itself.
+    async fn invoke_structured(
+        &self,
+        operation: DeepSeekOperation,
+        payload: JsonValue,
+    ) -> AppResult<ChatInvocationResult> {
+        let first = self.invoke_chat(operation, payload.clone(), &[]).await?;
+        let Some(schema) = operation.response_schema() else {
+            return Ok(first);
+        };
+
+        let errors = match schema.validate(&first.content) {
+            Ok(()) => return Ok(first),
+            Err(errors) => errors,
+        };
+        warn!(
+            target: "app::ai::deepseek",
+            operation = operation.as_str(),
+            correlation_id = %first.correlation_id,
+            error_count = errors.len(),
+            "DeepSeek response failed schema validation, requesting correction"
+        );
+
+        let previous = serde_json::to_string(&first.content).unwrap_or_default();
+        let follow_up = [
+            json!({ "role": "assistant", "content": previous }),
+            json!({ "role": "user", "content": schema_correction_prompt(&errors) }),
+        ];
+        let mut retry = self.invoke_chat(operation, payload, &follow_up).await?;
+
+        if let Err(errors) = schema.validate(&retry.content) {
+            return Err(AppError::ai_with_details(
+                AiErrorCode::InvalidResponse,
+                "DeepSeek 响应不符合预期结构",
+                Some(retry.correlation_id.as_str()),
+                Some(json!({
+                    "reason": "schema_validation_failed",
+                    "errors": errors,
+                    "initialCorrelationId": first.correlation_id,
+                })),
+            ));
+        }
+
+        accumulate_usage(
+            &mut retry.tokens_used,
+            &mut retry.latency_ms,
+            first.tokens_used,
+            first.latency_ms,
+        );
+        Ok(retry)
+    }
+
+    /// Sends the request and parses the reply as JSON, repairing it locally
+    /// when possible. Output that cannot be repaired is handed back to the
+    /// model once with a request to complete it.
     async fn invoke_chat(
         &self,
         operation: DeepSeekOperation,
         payload: JsonValue,
+        follow_up: &[JsonValue],
     ) -> AppResult<ChatInvocationResult> {
+        let first = self.send_chat(operation, &payload, follow_up).await?;
+        let parse_error = match Self::parse_content(&first.content, &first.correlation_id) {
+            Ok((content, repaired)) => {
+                JSON_REPAIR_METRICS.record(if repaired {
+                    JsonRepairOutcome::RepairedLocally
+                } else {
+                    JsonRepairOutcome::Clean
+                });
+                return Ok(first.into_invocation(content));
+            }
+            Err(err) => err,
+        };
+
+        JSON_REPAIR_METRICS.record(JsonRepairOutcome::ReAsked);
+        warn!(
+            target: "app::ai::deepseek",
+            operation = operation.as_str(),
+            correlation_id = %first.correlation_id,
+            content_len = first.content.len(),
+            "DeepSeek returned unrepairable JSON, re-asking with partial output"
+        );
+
+        let mut messages = follow_up.to_vec();
+        messages.push(json!({ "role": "assistant", "content": first.content }));
+        messages.push(json!({ "role": "user", "content": json_completion_prompt() }));
+
+        let retry = match self.send_chat(operation, &payload, &messages).await {
+            Ok(retry) => retry,
+            Err(err) => {
+                JSON_REPAIR_METRICS.record(JsonRepairOutcome::Failed);
+                warn!(
+                    target: "app::ai::deepseek",
+                    correlation_id = %first.correlation_id,
+                    error = %err,
+                    "JSON re-ask request failed"
+                );
+                return Err(parse_error);
+            }
+        };
+
+        match Self::parse_content(&retry.content, &retry.correlation_id) {
+            Ok((content, _)) => {
+                JSON_REPAIR_METRICS.record(JsonRepairOutcome::RecoveredByReAsk);
+                let mut result = retry.into_invocation(content);
+                accumulate_usage(
+                    &mut result.tokens_used,
+                    &mut result.latency_ms,
+                    first.tokens_used,
+                    first.latency_ms,
+                );
+                Ok(result)
+            }
+            Err(err) => {
+                JSON_REPAIR_METRICS.record(JsonRepairOutcome::Failed);
+                Err(err)
+            }
+        }
+    }
+
+    async fn send_chat(
+        &self,
+        operation: DeepSeekOperation,
+        payload: &JsonValue,
+        follow_up: &[JsonValue],
+    ) -> AppResult<RawChatResult> {
         let correlation_id = Uuid::new_v4().to_string();
-        let sanitized_payload = redact_sensitive_data(&payload)
+        let started = Instant::now();
+        let result = self
+            .send_chat_with_retries(operation, payload, follow_up, correlation_id.clone())
+            .await;
+
+        if let Some(db_pool) = self.debug_log.as_ref() {
+            record_debug_entry(
+                db_pool,
+                operation.as_str(),
+                &correlation_id,
+                payload,
+                follow_up,
+                &result,
+                started.elapsed(),
+            );
+        }
+
+        result
+    }
+
+    async fn send_chat_with_retries(
+        &self,
+        operation: DeepSeekOperation,
+        payload: &JsonValue,
+        follow_up: &[JsonValue],
+        correlation_id: String,
+    ) -> AppResult<RawChatResult> {
+        let sanitized_payload = redact_sensitive_data(payload)
             .unwrap_or_else(|_| JsonValue::String("<redacted>".to_string()));
         let sanitized_payload_str = serde_json::to_string(&sanitized_payload)
             .unwrap_or_else(|_| "\"<redacted>\"".to_string());
 
-        let request_body = self.build_request_body(operation, &payload);
+        let request_body = self.build_request_body(operation, payload, follow_up);
         let backoff_schedule = [
             StdDuration::from_secs(0),
             StdDuration::from_secs(1),
@@ -445,10 +1063,12 @@ impl DeepSeekProvider {
         ];
 
         let mut last_error: Option<AppError> = None;
+        let mut retry_after: Option<StdDuration> = None;
 
         for (attempt, delay) in backoff_schedule.iter().enumerate() {
-            if *delay > StdDuration::from_secs(0) {
-                sleep(*delay).await;
+            let delay = retry_after.take().map_or(*delay, |hint| hint.max(*delay));
+            if delay > StdDuration::from_secs(0) {
+                sleep(delay).await;
             }
 
             debug!(
@@ -511,18 +1131,18 @@ impl DeepSeekProvider {
                                     Some(json!({ "reason": "missing_message_content" })),
                                 )
                             })?;
-                        let content_value = Self::parse_content(content, &correlation_id)?;
                         let tokens_used = Self::extract_tokens(&body);
 
-                        return Ok(ChatInvocationResult {
-                            content: content_value,
+                        return Ok(RawChatResult {
+                            content: content.to_string(),
                             tokens_used,
                             latency_ms,
                             correlation_id,
                         });
                     }
 
-                    let (error, retryable) = Self::map_http_error(status, correlation_id.as_str());
+                    let (error, retryable) =
+                        Self::map_http_response(&resp, correlation_id.as_str());
                     warn!(
                         target: "app::ai::deepseek",
                         correlation_id = %correlation_id,
@@ -534,6 +1154,14 @@ impl DeepSeekProvider {
                     if !retryable || attempt == backoff_schedule.len() - 1 {
                         return Err(error);
                     }
+                    // Wait as long as the provider asks when that is short;
+                    // a long wait is left to the caller
+                    if let Some(secs) = Self::retry_after_secs(&error) {
+                        if secs > MAX_RETRY_AFTER_WAIT_SECS {
+                            return Err(error);
+                        }
+                        retry_after = Some(StdDuration::from_secs(secs));
+                    }
 
                     last_error = Some(error);
                     continue;
@@ -569,21 +1197,40 @@ impl DeepSeekProvider {
         }
     }
 
-    fn build_request_body(&self, operation: DeepSeekOperation, payload: &JsonValue) -> JsonValue {
+    fn build_request_body(
+        &self,
+        operation: DeepSeekOperation,
+        payload: &JsonValue,
+        follow_up: &[JsonValue],
+    ) -> JsonValue {
         let user_content = serde_json::to_string(payload).unwrap_or_else(|_| "{}".to_string());
+        let mut messages = vec![
+            json!({ "role": "system", "content": self.system_prompt(operation.prompt_key()) }),
+            json!({ "role": "user", "content": user_content }),
+        ];
+        messages.extend(follow_up.iter().cloned());
+
         json!({
             "model": self.model,
             "temperature": operation.temperature(),
             "top_p": 0.9,
+            "max_tokens": self.max_output_tokens,
             "response_format": { "type": "json_object" },
-            "messages": [
-                { "role": "system", "content": operation.system_prompt() },
-                { "role": "user", "content": user_content }
-            ]
+            "messages": messages
         })
     }
 
-    fn parse_content(content: &str, correlation_id: &str) -> AppResult<JsonValue> {
+    fn system_prompt(&self, key: PromptTemplateKey) -> String {
+        let template = self
+            .prompt_templates
+            .get(&key)
+            .map(String::as_str)
+            .unwrap_or_else(|| default_system_prompt(key));
+        render_template(template, Local::now())
+    }
+
+    /// Parses the reply, returning whether the JSON had to be repaired.
+    fn parse_content(content: &str, correlation_id: &str) -> AppResult<(JsonValue, bool)> {
         let trimmed = content.trim();
         let cleaned = if trimmed.starts_with("```") {
             let without_prefix = trimmed
@@ -596,14 +1243,26 @@ impl DeepSeekProvider {
             trimmed.to_string()
         };
 
-        serde_json::from_str(&cleaned).map_err(|err| {
-            AppError::ai_with_details(
-                AiErrorCode::InvalidResponse,
-                format!("DeepSeek 响应内容非 JSON: {err}"),
-                Some(correlation_id),
-                Some(json!({ "reason": "invalid_json" })),
-            )
-        })
+        let err = match serde_json::from_str(&cleaned) {
+            Ok(value) => return Ok((value, false)),
+            Err(err) => err,
+        };
+
+        if let Some(value) = repair_json(&cleaned) {
+            debug!(
+                target: "app::ai::deepseek",
+                correlation_id = %correlation_id,
+                "repaired malformed DeepSeek JSON"
+            );
+            return Ok((value, true));
+        }
+
+        Err(AppError::ai_with_details(
+            AiErrorCode::InvalidResponse,
+            format!("DeepSeek 响应内容非 JSON: {err}"),
+            Some(correlation_id),
+            Some(json!({ "reason": "invalid_json" })),
+        ))
     }
 
     fn extract_tokens(body: &JsonValue) -> HashMap<String, u64> {
@@ -681,6 +1340,36 @@ impl DeepSeekProvider {
         }
     }
 
+    /// Like [`Self::map_http_error`], keeping the `Retry-After` seconds of a
+    /// rate-limited response as `retryAfterSecs` in the error details.
+    fn map_http_response(resp: &reqwest::Response, correlation_id: &str) -> (AppError, bool) {
+        let retry_after_secs = resp
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .filter(|_| resp.status() == StatusCode::TOO_MANY_REQUESTS);
+        match retry_after_secs {
+            Some(secs) => (
+                AppError::ai_with_details(
+                    AiErrorCode::RateLimited,
+                    RATE_LIMITED_MESSAGE,
+                    Some(correlation_id),
+                    Some(json!({ "retryAfterSecs": secs })),
+                ),
+                true,
+            ),
+            None => Self::map_http_error(resp.status(), correlation_id),
+        }
+    }
+
+    fn retry_after_secs(error: &AppError) -> Option<u64> {
+        error
+            .ai_details()?
+            .get("retryAfterSecs")
+            .and_then(JsonValue::as_u64)
+    }
+
     fn map_http_error(status: StatusCode, correlation_id: &str) -> (AppError, bool) {
         match status {
             StatusCode::UNAUTHORIZED => (
@@ -704,7 +1393,7 @@ impl DeepSeekProvider {
             StatusCode::TOO_MANY_REQUESTS => (
                 AppError::ai_with_details(
                     AiErrorCode::RateLimited,
-                    "DeepSeek 请求过于频繁，请稍后重试",
+                    RATE_LIMITED_MESSAGE,
                     Some(correlation_id),
                     None,
                 ),
@@ -826,7 +1515,7 @@ impl DeepSeekProvider {
                 let latency_ms = start.elapsed().as_millis();
 
                 if !status.is_success() {
-                    let (error, _) = Self::map_http_error(status, correlation_id.as_str());
+                    let (error, _) = Self::map_http_response(&resp, correlation_id.as_str());
                     warn!(
                         target: "app::ai::deepseek",
                         correlation_id = %correlation_id,
@@ -877,6 +1566,113 @@ impl DeepSeekProvider {
     }
 }
 
+/// Fold newly extracted items into `merged`, filling gaps on near-duplicates
+/// that show up in more than one chunk.
+fn merge_action_items(
+    merged: &mut Vec<ExtractedActionItemDto>,
+    incoming: Vec<ExtractedActionItemDto>,
+) {
+    for mut item in incoming {
+        item.title = item.title.trim().to_string();
+        if item.title.is_empty() {
+            continue;
+        }
+
+        match merged.iter_mut().find(|existing| {
+            text_similarity(&existing.title, &item.title) >= ACTION_ITEM_DUPLICATE_THRESHOLD
+        }) {
+            Some(existing) => {
+                if existing.owner.is_none() {
+                    existing.owner = item.owner;
+                }
+                if existing.due_hint.is_none() {
+                    existing.due_hint = item.due_hint;
+                }
+                if existing.due_at.is_none() {
+                    existing.due_at = item.due_at;
+                }
+                if existing.priority.is_none() {
+                    existing.priority = item.priority;
+                }
+            }
+            None => merged.push(item),
+        }
+    }
+}
+
+/// Tidies proposed subtasks: drops untitled ones, keeps the first `max` in
+/// the proposed order, renumbers them from 1 and keeps only dependencies on
+/// earlier subtasks.
+fn normalize_subtasks(
+    mut subtasks: Vec<ProposedSubtaskDto>,
+    max: usize,
+) -> Vec<ProposedSubtaskDto> {
+    subtasks.retain(|subtask| !subtask.title.trim().is_empty());
+    // Unnumbered subtasks keep their position after the numbered ones.
+    subtasks.sort_by_key(|subtask| match subtask.order {
+        0 => usize::MAX,
+        order => order,
+    });
+    subtasks.truncate(max);
+
+    let renumbered: HashMap<usize, usize> = subtasks
+        .iter()
+        .enumerate()
+        .filter(|(_, subtask)| subtask.order > 0)
+        .map(|(index, subtask)| (subtask.order, index + 1))
+        .collect();
+
+    for (index, subtask) in subtasks.iter_mut().enumerate() {
+        let order = index + 1;
+        subtask.title = subtask.title.trim().to_string();
+        subtask.description = subtask
+            .description
+            .take()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+        subtask.estimated_minutes = subtask
+            .estimated_minutes
+            .filter(|minutes| *minutes > 0)
+            .map(|minutes| minutes.clamp(SUBTASK_MIN_MINUTES, SUBTASK_MAX_MINUTES));
+        let mut depends_on: Vec<usize> = subtask
+            .depends_on
+            .iter()
+            .filter_map(|dependency| renumbered.get(dependency).copied())
+            .filter(|dependency| *dependency < order)
+            .collect();
+        depends_on.sort_unstable();
+        depends_on.dedup();
+        subtask.depends_on = depends_on;
+        subtask.order = order;
+    }
+
+    subtasks
+}
+
+/// Sum token usage and latency across chunked provider calls.
+fn accumulate_telemetry(
+    total: Option<AiProviderMetadata>,
+    next: Option<AiProviderMetadata>,
+) -> Option<AiProviderMetadata> {
+    match (total, next) {
+        (None, next) => next,
+        (total, None) => total,
+        (Some(mut total), Some(next)) => {
+            total.latency_ms = match (total.latency_ms, next.latency_ms) {
+                (Some(a), Some(b)) => Some(a + b),
+                (a, b) => a.or(b),
+            };
+            if let Some(next_tokens) = next.tokens_used {
+                let tokens = total.tokens_used.get_or_insert_with(HashMap::new);
+                for (key, value) in next_tokens {
+                    *tokens.entry(key).or_insert(0) += value;
+                }
+            }
+            Some(total)
+        }
+    }
+}
+
 pub mod testing {
     use super::*;
     use std::time::Duration as StdDurationOverride;
@@ -895,12 +1691,41 @@ pub mod testing {
             api_key: Some("test-key".to_string()),
             api_base_url: base_url.trim_end_matches('/').to_string(),
             model: "deepseek-chat".to_string(),
+            model_profile: AiModelProfile::for_model("deepseek-chat"),
             http_timeout: timeout,
             cache_ttl: Duration::minutes(5),
+            prompt_templates: HashMap::new(),
+            debug_log_enabled: false,
         };
         let provider = DeepSeekProvider::try_new(&config, "test-key".to_string())?;
         provider.parse_task(&request).await
     }
+
+    /// A service whose provider talks to `base_url` with a test key,
+    /// whatever the stored settings say.
+    #[cfg(feature = "test-support")]
+    pub fn service_with_base_url(db_pool: DbPool, base_url: &str) -> AppResult<AiService> {
+        AiService::with_endpoint_override(db_pool, (base_url.to_string(), "test-key".to_string()))
+    }
+
+    /// Normalize proposed subtasks the same way `decompose_task` does.
+    pub fn normalize_subtasks(
+        subtasks: Vec<ProposedSubtaskDto>,
+        max: usize,
+    ) -> Vec<ProposedSubtaskDto> {
+        super::normalize_subtasks(subtasks, max)
+    }
+
+    /// Merge action items the same way chunked extraction does.
+    pub fn merge_action_items(
+        batches: Vec<Vec<ExtractedActionItemDto>>,
+    ) -> Vec<ExtractedActionItemDto> {
+        let mut merged = Vec::new();
+        for batch in batches {
+            super::merge_action_items(&mut merged, batch);
+        }
+        merged
+    }
 }
 
 #[async_trait::async_trait]
@@ -908,7 +1733,7 @@ impl AiProvider for DeepSeekProvider {
     async fn parse_task(&self, request: &TaskParseRequest) -> AppResult<ParsedTaskDto> {
         let payload = build_task_parse_payload(request);
         let result = self
-            .invoke_chat(DeepSeekOperation::ParseTask, payload)
+            .invoke_structured(DeepSeekOperation::ParseTask, payload)
             .await?;
 
         let ChatInvocationResult {
@@ -949,7 +1774,7 @@ impl AiProvider for DeepSeekProvider {
     async fn generate_recommendations(&self, input: &JsonValue) -> AppResult<RecommendationDto> {
         let payload = build_recommendations_payload(input);
         let result = self
-            .invoke_chat(DeepSeekOperation::Recommendations, payload)
+            .invoke_structured(DeepSeekOperation::Recommendations, payload)
             .await?;
 
         let ChatInvocationResult {
@@ -979,7 +1804,7 @@ impl AiProvider for DeepSeekProvider {
     async fn plan_schedule(&self, input: &JsonValue) -> AppResult<SchedulePlanDto> {
         let payload = build_schedule_payload(input);
         let result = self
-            .invoke_chat(DeepSeekOperation::Schedule, payload)
+            .invoke_structured(DeepSeekOperation::Schedule, payload)
             .await?;
 
         let ChatInvocationResult {
@@ -1006,6 +1831,128 @@ impl AiProvider for DeepSeekProvider {
         Ok(dto)
     }
 
+    async fn extract_action_items(&self, input: &JsonValue) -> AppResult<ActionItemsDto> {
+        let payload = build_action_items_payload(input);
+        let result = self
+            .invoke_chat(DeepSeekOperation::ActionItems, payload, &[])
+            .await?;
+
+        let ChatInvocationResult {
+            content,
+            tokens_used,
+            latency_ms,
+            correlation_id,
+        } = result;
+
+        let mut dto: ActionItemsDto = serde_json::from_value(content).map_err(|err| {
+            AppError::ai_with_details(
+                AiErrorCode::InvalidResponse,
+                format!("解析 DeepSeek 行动项响应失败: {err}"),
+                Some(correlation_id.as_str()),
+                None,
+            )
+        })?;
+
+        dto.items.retain(|item| !item.title.trim().is_empty());
+
+        let metadata =
+            self.build_provider_metadata(tokens_used, latency_ms, Some(correlation_id.as_str()));
+        let existing = dto.telemetry.take();
+        dto.telemetry = Self::merge_metadata(existing, metadata);
+
+        Ok(dto)
+    }
+
+    async fn decompose_task(&self, input: &JsonValue) -> AppResult<TaskDecompositionDto> {
+        let payload = build_decompose_payload(input);
+        let result = self
+            .invoke_structured(DeepSeekOperation::DecomposeTask, payload)
+            .await?;
+
+        let ChatInvocationResult {
+            content,
+            tokens_used,
+            latency_ms,
+            correlation_id,
+        } = result;
+
+        let mut dto: TaskDecompositionDto = serde_json::from_value(content).map_err(|err| {
+            AppError::ai_with_details(
+                AiErrorCode::InvalidResponse,
+                format!("解析 DeepSeek 任务拆分响应失败: {err}"),
+                Some(correlation_id.as_str()),
+                None,
+            )
+        })?;
+
+        let metadata =
+            self.build_provider_metadata(tokens_used, latency_ms, Some(correlation_id.as_str()));
+        let existing = dto.telemetry.take();
+        dto.telemetry = Self::merge_metadata(existing, metadata);
+
+        Ok(dto)
+    }
+
+    async fn summarize_history(&self, input: &JsonValue) -> AppResult<HistoryNarrativeDto> {
+        let payload = build_history_summary_payload(input);
+        let result = self
+            .invoke_structured(DeepSeekOperation::SummarizeHistory, payload)
+            .await?;
+
+        let ChatInvocationResult {
+            content,
+            tokens_used,
+            latency_ms,
+            correlation_id,
+        } = result;
+
+        let mut dto: HistoryNarrativeDto = serde_json::from_value(content).map_err(|err| {
+            AppError::ai_with_details(
+                AiErrorCode::InvalidResponse,
+                format!("解析 DeepSeek 活动总结响应失败: {err}"),
+                Some(correlation_id.as_str()),
+                None,
+            )
+        })?;
+
+        let metadata =
+            self.build_provider_metadata(tokens_used, latency_ms, Some(correlation_id.as_str()));
+        let existing = dto.telemetry.take();
+        dto.telemetry = Self::merge_metadata(existing, metadata);
+
+        Ok(dto)
+    }
+
+    async fn critique_plan(&self, input: &JsonValue) -> AppResult<PlanCritiqueDto> {
+        let payload = build_plan_critique_payload(input);
+        let result = self
+            .invoke_structured(DeepSeekOperation::CritiquePlan, payload)
+            .await?;
+
+        let ChatInvocationResult {
+            content,
+            tokens_used,
+            latency_ms,
+            correlation_id,
+        } = result;
+
+        let mut dto: PlanCritiqueDto = serde_json::from_value(content).map_err(|err| {
+            AppError::ai_with_details(
+                AiErrorCode::InvalidResponse,
+                format!("解析 DeepSeek 计划审查响应失败: {err}"),
+                Some(correlation_id.as_str()),
+                None,
+            )
+        })?;
+
+        let metadata =
+            self.build_provider_metadata(tokens_used, latency_ms, Some(correlation_id.as_str()));
+        let existing = dto.telemetry.take();
+        dto.telemetry = Self::merge_metadata(existing, metadata);
+
+        Ok(dto)
+    }
+
     async fn ping(&self) -> AppResult<AiProviderMetadata> {
         let url = format!("{}/v1/models", self.base_url);
         let start = Instant::now();
@@ -1028,7 +1975,7 @@ impl AiProvider for DeepSeekProvider {
                         Some(correlation_id.as_str()),
                     ))
                 } else {
-                    let (error, _) = Self::map_http_error(status, correlation_id.as_str());
+                    let (error, _) = Self::map_http_response(&resp, correlation_id.as_str());
                     warn!(
                         target: "app::ai::deepseek",
                         correlation_id = %correlation_id,
@@ -1050,3 +1997,65 @@ impl AiProvider for DeepSeekProvider {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_log_entries_are_redacted_and_retrievable() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_pool = DbPool::new(dir.path().join("ai-debug.sqlite")).unwrap();
+        let payload = json!({ "input": "x", "title": "给老板的辞职信", "estimatedMinutes": 30 });
+
+        let ok: AppResult<RawChatResult> = Ok(RawChatResult {
+            content: r#"{"title":"辞职信","priority":"high"}"#.to_string(),
+            tokens_used: HashMap::new(),
+            latency_ms: 42,
+            correlation_id: "corr-ok".to_string(),
+        });
+        record_debug_entry(
+            &db_pool,
+            "parseTask",
+            "corr-ok",
+            &payload,
+            &[],
+            &ok,
+            StdDuration::ZERO,
+        );
+        let failed: AppResult<RawChatResult> = Err(AppError::other("boom"));
+        record_debug_entry(
+            &db_pool,
+            "planSchedule",
+            "corr-err",
+            &payload,
+            &[],
+            &failed,
+            StdDuration::from_millis(7),
+        );
+
+        let entry = db_pool
+            .with_connection(|conn| AiDebugLogRepository::find(conn, "corr-ok"))
+            .unwrap()
+            .unwrap()
+            .into_record()
+            .unwrap();
+        assert_eq!(entry.operation, "parseTask");
+        assert_eq!(entry.request["payload"]["title"], "[REDACTED]");
+        assert_eq!(entry.request["payload"]["estimatedMinutes"], 30);
+        let response = entry.response.unwrap();
+        assert_eq!(response["title"], "[REDACTED]");
+        assert_eq!(response["priority"], "high");
+        assert_eq!(entry.latency_ms, Some(42));
+
+        let failed = db_pool
+            .with_connection(|conn| AiDebugLogRepository::find(conn, "corr-err"))
+            .unwrap()
+            .unwrap()
+            .into_record()
+            .unwrap();
+        assert!(failed.response.is_none());
+        assert!(failed.error.unwrap().contains("boom"));
+        assert_eq!(failed.latency_ms, Some(7));
+    }
+}
@@ -1,28 +1,62 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, OnceLock, RwLock};
 use std::thread;
 use std::time::Duration as StdDuration;
 
-use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
-use tracing::{debug, error};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone, Utc};
+use serde_json::json;
+use tauri::{AppHandle, Emitter};
+use tracing::{debug, error, info, warn};
 
-use crate::db::repositories::analytics_repository::{AnalyticsRepository, AnalyticsSnapshotRow};
-use crate::db::repositories::planning_repository::PlanningTimeBlockRow;
+use crate::db::repositories::analytics_repository::{
+    AnalyticsAnomalyRow, AnalyticsDailyAggregateRow, AnalyticsRepository, AnalyticsSnapshotRow,
+};
+use crate::db::repositories::day_log_repository::DayLogRepository;
+use crate::db::repositories::planning_repository::{PlanningRepository, PlanningTimeBlockRow};
+use crate::db::repositories::project_repository::ProjectRepository;
+use crate::db::repositories::wellness_repository::{FocusIdleRepository, WellnessRepository};
 use crate::db::DbPool;
 use crate::error::{AppError, AppResult};
 use crate::models::analytics::{
-    AnalyticsEfficiency, AnalyticsExportFormat, AnalyticsExportParams, AnalyticsExportResult,
-    AnalyticsGrouping, AnalyticsHistoryPoint, AnalyticsHistoryResponse, AnalyticsMeta,
-    AnalyticsOverview, AnalyticsOverviewResponse, AnalyticsQueryParams, AnalyticsRangeKey,
-    AnalyticsSnapshotRecord, AnalyticsSummary, EfficiencySuggestion, InsightCard,
-    TimeAllocationBreakdown, TimeAllocationEntry, TimeAllocationPriorityEntry,
-    TimeAllocationTypeEntry, TrendPoint, ZeroStateMeta,
+    AnalyticsAnomaly, AnalyticsAnomalyKind, AnalyticsEfficiency, AnalyticsExportFormat,
+    AnalyticsExportParams, AnalyticsExportResult, AnalyticsGrouping, AnalyticsHistoryPoint,
+    AnalyticsHistoryResponse, AnalyticsMeta, AnalyticsOverview, AnalyticsOverviewResponse,
+    AnalyticsQueryParams, AnalyticsRangeKey, AnalyticsSnapshotRecord, AnalyticsSummary,
+    ConfidenceCalibration, DayTimeline, DayTimelineEntry, DayTimelineEntryKind,
+    DefragmentationSuggestion, EfficiencySuggestion, HabitConsistency, InsightCard,
+    MeetingLoadBreakdown, MeetingLoadWeek, ScheduleStyle, TimeAllocationBreakdown,
+    TimeAllocationEntry, TimeAllocationPriorityEntry, TimeAllocationTypeEntry, TodaySnapshot,
+    TrendPoint, YearInReview, YearInReviewFocusDay, YearInReviewMonthValue, YearInReviewProject,
+    YearInReviewTotals, YearInReviewWeek, YearInReviewWellnessMonth, ZeroStateMeta,
 };
+use crate::models::day_log::DayLogRecord;
 use crate::models::planning::PlanningTimeBlockRecord;
+use crate::models::productivity::ScoreStreak;
+use crate::models::recurring_task::RecurringTaskStats;
+use crate::models::retention::MAX_RETENTION_DAYS;
+use crate::models::settings::FeatureFlag;
 use crate::models::task::TaskRecord;
+use crate::models::wellness::{WellnessEventRecord, WellnessResponse, WellnessTriggerReason};
+use crate::services::confidence_calibration;
+use crate::services::job_queue::{enqueue_retry, JOB_KIND_ANALYTICS_SNAPSHOT};
+use crate::services::job_supervisor::spawn_supervised;
+use crate::services::planning_service::{ResolveConflictInput, TimeBlockOverride};
+use crate::services::power_throttle::PowerThrottle;
+use crate::services::productivity_score_service::ProductivityScoreService;
+use crate::services::progress::{scaled_percent, ProgressReporter};
+use crate::services::recurring_task_service;
+use crate::services::schedule_optimizer::{expand_recurring_events, ScheduleConstraints};
+use crate::services::schedule_utils::intervals::{self, Interval};
+use crate::services::settings_service::{
+    load_anomaly_notifications_enabled, load_feature_enabled, load_productivity_score_target,
+    load_score_streak, save_score_streak,
+};
 use crate::services::task_service::TaskService;
+use crate::services::today_snapshot;
+use crate::utils::files::write_atomic;
+use crate::utils::paths::sanitize_file_name;
 
 const CACHE_TTL_SECONDS: i64 = 60;
 const MIN_ESTIMATED_MINUTES: i64 = 15;
@@ -31,8 +65,34 @@ const SNAPSHOT_JOB_HOUR: u32 = 1;
 const SNAPSHOT_JOB_MINUTE: u32 = 15;
 const SNAPSHOT_MIN_SLEEP_SECS: u64 = 60;
 const SNAPSHOT_FALLBACK_SLEEP_SECS: u64 = 3600;
-const SNAPSHOT_RETENTION_DAYS: i64 = 120;
+/// Longest range a single snapshot recompute may cover.
+const MAX_RECOMPUTE_DAYS: i64 = 120;
 const SNAPSHOT_LOOKBACK_DAYS: i64 = 7;
+/// Gaps shorter than this between commitments are too short for real work.
+const FRAGMENT_GAP_MINUTES: i64 = 30;
+/// Entries kept in each ranked list of the year in review.
+const YEAR_REVIEW_TOP_N: usize = 5;
+/// How far before the range a planning session may have recorded its events.
+const MEETING_SESSION_LOOKBACK_DAYS: i64 = 30;
+const MEETING_EVENT_TYPES: [&str; 5] = ["meeting", "call", "interview", "standup", "sync"];
+const MAKER_SCORE_THRESHOLD: f64 = 60.0;
+const MANAGER_SCORE_THRESHOLD: f64 = 40.0;
+/// Days ahead scanned for interleaved blocks that could be consolidated.
+const DEFRAG_HORIZON_DAYS: i64 = 7;
+/// Trailing snapshots an anomaly is measured against.
+const ANOMALY_BASELINE_DAYS: usize = 7;
+const ANOMALY_MIN_BASELINE_DAYS: usize = 3;
+/// Completion-rate drop below the trailing average that counts as sudden.
+const COMPLETION_DROP_THRESHOLD: f64 = 0.3;
+/// An overdue spike must reach this multiple of the trailing average and
+/// exceed it by at least `OVERDUE_SPIKE_MIN_INCREASE` tasks.
+const OVERDUE_SPIKE_RATIO: f64 = 2.0;
+const OVERDUE_SPIKE_MIN_INCREASE: f64 = 3.0;
+
+pub const ANALYTICS_ANOMALY_EVENT: &str = "analytics://anomaly";
+
+/// Goal ids per task id.
+type TaskGoals = HashMap<String, HashSet<String>>;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct CacheKey {
@@ -40,6 +100,7 @@ struct CacheKey {
     start_ts: i64,
     end_ts: i64,
     grouping: AnalyticsGrouping,
+    project_id: Option<String>,
 }
 
 #[derive(Clone)]
@@ -72,6 +133,7 @@ pub struct AnalyticsService {
     cache_ttl: Duration,
     reports_dir: PathBuf,
     snapshot_job_started: AtomicBool,
+    notifier: OnceLock<AppHandle>,
 }
 
 impl AnalyticsService {
@@ -85,9 +147,21 @@ impl AnalyticsService {
             cache_ttl: Duration::seconds(CACHE_TTL_SECONDS),
             reports_dir,
             snapshot_job_started: AtomicBool::new(false),
+            notifier: OnceLock::new(),
         })
     }
 
+    /// Where exported reports are written.
+    pub fn reports_dir(&self) -> &Path {
+        &self.reports_dir
+    }
+
+    /// Lets the nightly job emit [`ANALYTICS_ANOMALY_EVENT`] when the user
+    /// opted into anomaly notifications.
+    pub fn attach_notifier(&self, app: AppHandle) {
+        let _ = self.notifier.set(app);
+    }
+
     pub fn ensure_snapshot_job(self: &Arc<Self>) -> AppResult<()> {
         if self
             .snapshot_job_started
@@ -104,10 +178,9 @@ impl AnalyticsService {
             }
 
             let runner = Arc::clone(self);
-            if let Err(err) = thread::Builder::new()
-                .name("analytics-snapshot-job".to_string())
-                .spawn(move || {
-                    runner.run_snapshot_loop();
+            if let Err(err) =
+                spawn_supervised(self.db.clone(), "analytics-snapshot-job", move || {
+                    Arc::clone(&runner).run_snapshot_loop();
                 })
             {
                 self.snapshot_job_started.store(false, Ordering::SeqCst);
@@ -116,7 +189,7 @@ impl AnalyticsService {
                     error = %err,
                     "failed to start analytics snapshot thread"
                 );
-                return Err(AppError::other(format!("无法启动分析快照任务: {err}")));
+                return Err(err);
             }
         }
 
@@ -128,6 +201,11 @@ impl AnalyticsService {
         params: AnalyticsQueryParams,
     ) -> AppResult<AnalyticsOverviewResponse> {
         let resolved = self.resolve_query(params)?;
+        if resolved.params.full_recompute {
+            let response = self.compute_overview(&resolved)?;
+            self.insert_cache(resolved.cache_key, response.clone());
+            return Ok(response);
+        }
         if let Some(cached) = self.try_get_cache(&resolved.cache_key) {
             debug!(target: "app::analytics", range = %resolved.params.range.as_str(), "analytics cache hit");
             return Ok(cached);
@@ -150,12 +228,92 @@ impl AnalyticsService {
         Ok(overview.history)
     }
 
+    /// Applied blocks, actual work, completions, wellness nudges and the
+    /// day-close note for `date`, merged into one chronological list.
+    pub fn day_timeline(&self, date: String) -> AppResult<DayTimeline> {
+        let day = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+            .map_err(|_| AppError::validation("日期格式无效，应为 YYYY-MM-DD"))?;
+        let day_start = Utc.from_utc_datetime(&day.and_hms_opt(0, 0, 0).unwrap());
+        let day_end = day_start + Duration::days(1);
+        let (start, end) = (day_start.to_rfc3339(), day_end.to_rfc3339());
+
+        let tasks = self.task_service.list_tasks_readonly()?;
+        let (blocks, nudges, log) = self.db.with_read_connection(|conn| {
+            let blocks = PlanningRepository::list_applied_time_blocks_between(conn, &start, &end)?
+                .into_iter()
+                .map(|row| row.into_record())
+                .collect::<AppResult<Vec<_>>>()?;
+            let nudges = WellnessRepository::list_between(conn, &start, &end)?;
+            let log = DayLogRepository::find_by_date(conn, &day.to_string())?;
+            Ok((blocks, nudges, log))
+        })?;
+
+        Ok(DayTimeline {
+            date: day.to_string(),
+            entries: build_day_timeline(&blocks, &tasks, &nudges, log.as_ref(), day_start, day_end),
+        })
+    }
+
+    /// Proposes block swaps that put interleaved work on the same task next to
+    /// each other, scanning a week of planned blocks starting at `date`
+    /// (defaults to today).
+    pub fn suggest_defragmentation(
+        &self,
+        date: Option<String>,
+    ) -> AppResult<Vec<DefragmentationSuggestion>> {
+        let start_date = match date.as_deref().map(str::trim) {
+            Some(value) if !value.is_empty() => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map_err(|_| AppError::validation("日期格式无效，应为 YYYY-MM-DD"))?,
+            _ => Utc::now().date_naive(),
+        };
+        let start = Utc.from_utc_datetime(&start_date.and_hms_opt(0, 0, 0).unwrap());
+        let end = start + Duration::days(DEFRAG_HORIZON_DAYS);
+
+        let blocks = self.load_time_blocks(start, end)?;
+        let task_goals = self.load_task_goals()?;
+        let titles: HashMap<String, String> = self
+            .task_service
+            .list_tasks_readonly()?
+            .into_iter()
+            .map(|task| (task.id, task.title))
+            .collect();
+
+        let suggestions = build_defrag_suggestions(&blocks, &task_goals, &titles, Utc::now());
+        if suggestions.is_empty() {
+            return Ok(suggestions);
+        }
+
+        self.db.with_connection(|conn| {
+            let mut session_ids: HashMap<String, Option<String>> = HashMap::new();
+            let mut resolved = Vec::with_capacity(suggestions.len());
+            for mut suggestion in suggestions {
+                let option_id = suggestion.resolution.option_id.clone();
+                let session_id = match session_ids.get(&option_id) {
+                    Some(cached) => cached.clone(),
+                    None => {
+                        let found = PlanningRepository::find_option_by_id(conn, &option_id)?
+                            .map(|row| row.session_id);
+                        session_ids.insert(option_id, found.clone());
+                        found
+                    }
+                };
+                if let Some(session_id) = session_id {
+                    suggestion.resolution.session_id = session_id;
+                    resolved.push(suggestion);
+                }
+            }
+            Ok(resolved)
+        })
+    }
+
     pub fn export_report(&self, params: AnalyticsExportParams) -> AppResult<AnalyticsExportResult> {
         let query_params = AnalyticsQueryParams {
             range: params.range,
             from: params.from.clone(),
             to: params.to.clone(),
             grouping: None,
+            project_id: params.project_id.clone(),
+            full_recompute: false,
         };
         let overview = self.fetch_overview(query_params)?;
         self.generate_report_file(overview, params.format)
@@ -186,6 +344,7 @@ impl AnalyticsService {
             start_ts: start.timestamp(),
             end_ts: end.timestamp(),
             grouping,
+            project_id: params.project_id.clone(),
         };
 
         Ok(ResolvedQuery {
@@ -198,9 +357,25 @@ impl AnalyticsService {
     }
 
     fn compute_overview(&self, resolved: &ResolvedQuery) -> AppResult<AnalyticsOverviewResponse> {
-        let tasks = self.task_service.list_tasks()?;
-        let blocks = self.load_time_blocks(resolved.start, resolved.end)?;
-        let daily_stats = build_daily_stats(&tasks, &blocks, resolved.start, resolved.end);
+        let mut tasks = self.task_service.list_tasks_readonly()?;
+        let mut blocks = self.load_time_blocks(resolved.start, resolved.end)?;
+        if let Some(project_id) = resolved.params.project_id.as_deref() {
+            tasks.retain(|task| task.project_id.as_deref() == Some(project_id));
+            let task_ids: HashSet<&str> = tasks.iter().map(|task| task.id.as_str()).collect();
+            blocks.retain(|block| task_ids.contains(block.task_id.as_str()));
+        }
+        // Project filters and explicit recomputes need the exact range; the
+        // unfiltered overview is served from whole-day aggregates.
+        let daily_stats = if resolved.params.project_id.is_none() && !resolved.params.full_recompute
+        {
+            self.load_daily_aggregates(resolved.start.date_naive(), resolved.end.date_naive())?
+        } else {
+            let idle_minutes = self.load_confirmed_idle_minutes(
+                resolved.start.date_naive(),
+                resolved.end.date_naive(),
+            )?;
+            build_daily_stats(&tasks, &blocks, &idle_minutes, resolved.start, resolved.end)
+        };
         let history_points = build_history_points(&daily_stats, resolved.grouping);
 
         let total_completed: i64 = daily_stats.iter().map(|(_, stats)| stats.completed).sum();
@@ -236,13 +411,58 @@ impl AnalyticsService {
         let (efficiency, suggestions) =
             build_efficiency_metrics(&tasks, &blocks, total_focus_minutes, estimated_total);
 
-        let insights = build_insights(
-            total_completed,
-            completion_rate,
-            total_focus_minutes,
-            resolved.start,
-            resolved.end,
-        );
+        let meetings = self.load_meeting_intervals(resolved.start, resolved.end)?;
+        let focus_intervals = applied_block_intervals(&blocks);
+        let meeting_load =
+            build_meeting_load(&meetings, &focus_intervals, resolved.start, resolved.end);
+
+        let insights_enabled = self
+            .db
+            .with_read_connection(|conn| load_feature_enabled(conn, FeatureFlag::AiInsights))?;
+        let mut insights = Vec::new();
+        if insights_enabled {
+            // Anomalies are flagged on whole-day snapshots, so they only apply
+            // to the unfiltered overview.
+            insights = if resolved.params.project_id.is_none() {
+                self.load_anomaly_insights(resolved.start, resolved.end)?
+            } else {
+                Vec::new()
+            };
+            insights.extend(build_insights(
+                total_completed,
+                completion_rate,
+                total_focus_minutes,
+                resolved.start,
+                resolved.end,
+            ));
+            insights.extend(build_meeting_load_insight(&meeting_load));
+            if resolved.params.project_id.is_none() {
+                match self.score_streak() {
+                    Ok(streak) => insights.extend(score_streak_insight(&streak)),
+                    Err(err) => warn!(
+                        target: "app::analytics",
+                        error = %err,
+                        "failed to load productivity score streak"
+                    ),
+                }
+            }
+        }
+
+        // Recurring occurrences carry no project, so habits are only part of
+        // the unfiltered overview.
+        let habits = if resolved.params.project_id.is_none() {
+            let rules = self.db.with_read_connection(|conn| {
+                recurring_task_service::collect_stats(
+                    conn,
+                    None,
+                    Some(resolved.start),
+                    resolved.end,
+                )
+            })?;
+            build_habit_consistency(rules)
+        } else {
+            HabitConsistency::default()
+        };
 
         let zero_state = ZeroStateMeta {
             is_empty: tasks.is_empty(),
@@ -289,6 +509,8 @@ impl AnalyticsService {
                     / 1000.0,
                 suggestions,
             },
+            meeting_load,
+            habits,
             insights,
             zero_state,
             meta: AnalyticsMeta {
@@ -310,12 +532,67 @@ impl AnalyticsService {
         })
     }
 
+    /// Daily stats for `start..=end` from `analytics_daily_snapshots`,
+    /// recounting days that are missing or were marked stale by triggers.
+    fn load_daily_aggregates(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> AppResult<Vec<(NaiveDate, DailyStats)>> {
+        self.db.with_connection(|conn| {
+            let stored: HashMap<String, AnalyticsDailyAggregateRow> =
+                AnalyticsRepository::list_daily_aggregates(conn, &start, &end)?
+                    .into_iter()
+                    .map(|row| (row.snapshot_date.clone(), row))
+                    .collect();
+
+            let now = Utc::now().to_rfc3339();
+            let mut refreshed = 0usize;
+            let mut daily = Vec::new();
+            let mut date = start;
+            while date <= end {
+                let row = match stored.get(&date.to_string()) {
+                    Some(row) if !row.stale => row.clone(),
+                    _ => {
+                        let row = AnalyticsRepository::compute_daily_aggregate(conn, &date, &now)?;
+                        AnalyticsRepository::upsert_daily_aggregate(conn, &row)?;
+                        refreshed += 1;
+                        row
+                    }
+                };
+                daily.push((
+                    date,
+                    DailyStats {
+                        completed: row.completed,
+                        due: row.due,
+                        focus_minutes: row.focus_minutes,
+                        overdue: row.overdue,
+                    },
+                ));
+                date = match date.succ_opt() {
+                    Some(next) => next,
+                    None => break,
+                };
+            }
+
+            if refreshed > 0 {
+                debug!(
+                    target: "app::analytics",
+                    refreshed,
+                    "refreshed daily analytics aggregates"
+                );
+            }
+
+            Ok(daily)
+        })
+    }
+
     fn load_time_blocks(
         &self,
         start: DateTime<Utc>,
         end: DateTime<Utc>,
     ) -> AppResult<Vec<PlanningTimeBlockRecord>> {
-        self.db.with_connection(|conn| {
+        self.db.with_read_connection(|conn| {
             let mut stmt = conn.prepare(
                 r#"
                 SELECT
@@ -330,7 +607,8 @@ impl AnalyticsService {
                     applied_at,
                     actual_start_at,
                     actual_end_at,
-                    status
+                    status,
+                    prep_note
                 FROM planning_time_blocks
                 WHERE COALESCE(actual_end_at, end_at) >= :start
                   AND COALESCE(actual_start_at, start_at) <= :end
@@ -350,6 +628,97 @@ impl AnalyticsService {
         })
     }
 
+    /// Idle minutes the user confirmed for focus sessions, by the day the
+    /// session started.
+    fn load_confirmed_idle_minutes(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> AppResult<HashMap<NaiveDate, i64>> {
+        self.db.with_read_connection(|conn| {
+            Ok(
+                FocusIdleRepository::confirmed_minutes_by_day(conn, &start, &end)?
+                    .into_iter()
+                    .filter_map(|(day, minutes)| Some((day.parse::<NaiveDate>().ok()?, minutes)))
+                    .collect(),
+            )
+        })
+    }
+
+    /// Switching between tasks of the same goal does not count as a context
+    /// switch, so goal memberships are loaded alongside the blocks.
+    fn load_task_goals(&self) -> AppResult<TaskGoals> {
+        self.db.with_read_connection(|conn| {
+            let mut stmt = conn.prepare("SELECT task_id, goal_id FROM goal_task_associations")?;
+            let pairs = stmt
+                .query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let mut task_goals: TaskGoals = HashMap::new();
+            for (task_id, goal_id) in pairs {
+                task_goals.entry(task_id).or_default().insert(goal_id);
+            }
+            Ok(task_goals)
+        })
+    }
+
+    /// Meeting-type events recorded as existing commitments in planning
+    /// sessions. The same event may appear in several sessions, so events are
+    /// deduplicated by id and start time.
+    fn load_meeting_intervals(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> AppResult<Vec<(DateTime<Utc>, DateTime<Utc>)>> {
+        let sessions = self.db.with_read_connection(|conn| {
+            PlanningRepository::list_sessions_generated_between(
+                conn,
+                &(start - Duration::days(MEETING_SESSION_LOOKBACK_DAYS)).to_rfc3339(),
+                &end.to_rfc3339(),
+            )
+        })?;
+
+        let mut seen = std::collections::HashSet::new();
+        let mut intervals = Vec::new();
+        for session in sessions {
+            let Some(raw) = session.constraints.as_deref() else {
+                continue;
+            };
+            let Ok(constraints) = serde_json::from_str::<ScheduleConstraints>(raw) else {
+                continue;
+            };
+            let Ok(events) = expand_recurring_events(
+                &constraints.existing_events,
+                start.fixed_offset(),
+                end.fixed_offset(),
+            ) else {
+                continue;
+            };
+
+            for event in events {
+                if !is_meeting_event(event.event_type.as_deref()) {
+                    continue;
+                }
+                let (Some(event_start), Some(event_end)) = (
+                    parse_record_datetime(&Some(event.start_at.clone())),
+                    parse_record_datetime(&Some(event.end_at.clone())),
+                ) else {
+                    continue;
+                };
+                if event_end <= start || event_start >= end || event_end <= event_start {
+                    continue;
+                }
+                if seen.insert((event.id.clone(), event.start_at.clone())) {
+                    intervals.push((event_start.max(start), event_end.min(end)));
+                }
+            }
+        }
+
+        Ok(intervals)
+    }
+
     fn try_get_cache(&self, key: &CacheKey) -> Option<AnalyticsOverviewResponse> {
         let now = Utc::now();
         self.cache
@@ -377,6 +746,12 @@ impl AnalyticsService {
         }
     }
 
+    pub fn invalidate_cache(&self) {
+        if let Ok(mut guard) = self.cache.write() {
+            guard.clear();
+        }
+    }
+
     fn generate_report_file(
         &self,
         overview: AnalyticsOverviewResponse,
@@ -385,21 +760,17 @@ impl AnalyticsService {
         let timestamp = Utc::now().format("%Y%m%dT%H%M%SZ");
         let extension = format.file_extension();
         let filename = format!("{REPORT_PREFIX}-{}.{}", timestamp, extension);
-        let path = self.reports_dir.join(filename);
+        let path = self.reports_dir.join(sanitize_file_name(&filename));
 
-        match format {
-            AnalyticsExportFormat::Markdown => {
-                let content = render_markdown_report(&overview);
-                std::fs::write(&path, content)?;
-            }
-            AnalyticsExportFormat::Json => {
-                let json = serde_json::to_string_pretty(&overview)?;
-                std::fs::write(&path, json)?;
-            }
-        }
+        let content = match format {
+            AnalyticsExportFormat::Markdown => render_markdown_report(&overview),
+            AnalyticsExportFormat::Json => serde_json::to_string_pretty(&overview)?,
+        };
+        let checksum = write_atomic(&path, content.as_bytes())?;
 
         Ok(AnalyticsExportResult {
             file_path: path.to_string_lossy().to_string(),
+            checksum,
             format,
             generated_at: Utc::now().to_rfc3339(),
             is_demo: false,
@@ -407,11 +778,13 @@ impl AnalyticsService {
     }
 
     fn run_snapshot_loop(self: Arc<Self>) {
+        let throttle = PowerThrottle::new(self.db.clone());
         loop {
             let now = Utc::now();
             let next_run = Self::next_snapshot_run(now);
             let sleep_duration = duration_until(next_run, now);
             thread::sleep(sleep_duration);
+            throttle.wait_until_allowed("analytics-snapshot");
 
             if let Err(err) = self.capture_snapshot_for_previous_day() {
                 error!(
@@ -423,23 +796,283 @@ impl AnalyticsService {
         }
     }
 
-    fn capture_snapshot_for_previous_day(&self) -> AppResult<()> {
+    /// Captures yesterday's snapshot; a failure is queued for retry instead
+    /// of waiting for the next nightly run.
+    pub fn capture_snapshot_for_previous_day(&self) -> AppResult<()> {
         let today = Utc::now().date_naive();
         let target = today.pred_opt().unwrap_or(today);
-        self.capture_snapshot_for_date(target)
+        let result = self.capture_snapshot_for_date(target);
+
+        if result.is_ok() {
+            match self.flag_anomalies(target) {
+                Ok(anomalies) if !anomalies.is_empty() => self.notify_anomalies(&anomalies),
+                Ok(_) => {}
+                Err(err) => warn!(
+                    target: "app::analytics",
+                    error = %err,
+                    "analytics anomaly detection failed"
+                ),
+            }
+            if let Err(err) = self.record_score_streak(target) {
+                warn!(
+                    target: "app::analytics",
+                    error = %err,
+                    "failed to update productivity score streak"
+                );
+            }
+        }
+
+        if let Err(err) = &result {
+            let payload = json!({ "date": target.to_string() });
+            let queued = self.db.with_background_write(|conn| {
+                enqueue_retry(
+                    conn,
+                    JOB_KIND_ANALYTICS_SNAPSHOT,
+                    &payload,
+                    &err.to_string(),
+                    Utc::now(),
+                )
+            });
+            if let Err(queue_err) = queued {
+                warn!(
+                    target: "app::analytics",
+                    error = %queue_err,
+                    "failed to queue snapshot retry"
+                );
+            }
+        }
+
+        result
     }
 
-    fn capture_snapshot_for_date(&self, date: NaiveDate) -> AppResult<()> {
+    /// Builds and persists the analytics snapshot for `date` on demand, e.g.
+    /// when the user closes their day before the nightly job runs.
+    pub fn capture_snapshot_for_date(&self, date: NaiveDate) -> AppResult<()> {
         let record = self.build_snapshot_record(date)?;
-        let retention_cutoff = Self::retention_cutoff(date);
-        self.persist_snapshot(&record, retention_cutoff)
+        self.persist_snapshot(&record)
+    }
+
+    /// Rebuilds the stored snapshots for every day in `[start_date, end_date]`,
+    /// e.g. after importing history. Days finished before a cancellation keep
+    /// their new snapshot.
+    pub fn recompute_snapshots(
+        &self,
+        start_date: &str,
+        end_date: &str,
+        progress: &ProgressReporter,
+    ) -> AppResult<usize> {
+        let parse = |value: &str| {
+            NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+                .map_err(|_| AppError::validation("日期格式无效，应为 YYYY-MM-DD"))
+        };
+        let start = parse(start_date)?;
+        let end = parse(end_date)?;
+        if end < start {
+            return Err(AppError::validation("结束日期不能早于开始日期"));
+        }
+        let total = ((end - start).num_days() + 1) as usize;
+        if total as i64 > MAX_RECOMPUTE_DAYS {
+            return Err(AppError::validation(format!(
+                "单次最多重算 {} 天的快照",
+                MAX_RECOMPUTE_DAYS
+            )));
+        }
+
+        let mut date = start;
+        for done in 0..total {
+            progress.report("recomputing", scaled_percent(done, total, 0, 99))?;
+            self.capture_snapshot_for_date(date)?;
+            date = date.succ_opt().unwrap();
+        }
+        Ok(total)
+    }
+
+    /// Compares the snapshot for `date` with the days before it and stores
+    /// any anomalies found.
+    fn flag_anomalies(&self, date: NaiveDate) -> AppResult<Vec<AnalyticsAnomaly>> {
+        let anomalies = self.db.with_connection(|conn| {
+            let Some(latest) = AnalyticsRepository::find_by_date(conn, &date)? else {
+                return Ok(Vec::new());
+            };
+            let date_key = date.to_string();
+            let trailing: Vec<AnalyticsSnapshotRecord> =
+                AnalyticsRepository::list_recent_snapshots(conn, ANOMALY_BASELINE_DAYS + 2)?
+                    .into_iter()
+                    .filter(|row| row.snapshot_date < date_key)
+                    .take(ANOMALY_BASELINE_DAYS)
+                    .map(AnalyticsSnapshotRow::into_record)
+                    .collect();
+
+            let anomalies = detect_anomalies(&latest.into_record(), &trailing, Utc::now());
+            for anomaly in &anomalies {
+                AnalyticsRepository::upsert_anomaly(
+                    conn,
+                    &AnalyticsAnomalyRow::from_record(anomaly),
+                )?;
+            }
+            Ok(anomalies)
+        })?;
+
+        if !anomalies.is_empty() {
+            self.invalidate_cache();
+            info!(
+                target: "app::analytics",
+                date = %date,
+                count = anomalies.len(),
+                "analytics anomalies flagged"
+            );
+        }
+        Ok(anomalies)
+    }
+
+    /// Scores the snapshot for `date` and extends or resets the streak when
+    /// the user set a target score.
+    fn record_score_streak(&self, date: NaiveDate) -> AppResult<()> {
+        let Some(target) = self.db.with_connection(load_productivity_score_target)? else {
+            return Ok(());
+        };
+        let score = ProductivityScoreService::new(self.db.clone())
+            .calculate_score_for_date(&date.to_string())?
+            .composite_score;
+        self.db.with_connection(|conn| {
+            let mut streak = load_score_streak(conn)?;
+            streak.record_day(date, score, target);
+            save_score_streak(conn, &streak)
+        })?;
+        self.invalidate_cache();
+        Ok(())
+    }
+
+    /// The score streak with today's score so far. Without a target the
+    /// streak is empty.
+    pub fn score_streak(&self) -> AppResult<ScoreStreak> {
+        let (target, mut streak) = self.db.with_connection(|conn| {
+            Ok((
+                load_productivity_score_target(conn)?,
+                load_score_streak(conn)?,
+            ))
+        })?;
+        let Some(target) = target else {
+            return Ok(ScoreStreak::default());
+        };
+
+        let today = Utc::now().date_naive();
+        let snapshot = self.build_snapshot_record(today)?;
+        let today_score =
+            ProductivityScoreService::new(self.db.clone()).composite_score(&snapshot)?;
+        streak.refresh(today, today_score, target);
+        Ok(streak)
+    }
+
+    /// Live "today so far" metrics, computed on demand rather than from the
+    /// nightly snapshot.
+    pub fn today_snapshot(&self) -> AppResult<TodaySnapshot> {
+        let now = Local::now().fixed_offset();
+        self.db
+            .with_connection(|conn| today_snapshot::today_snapshot(conn, now))
+    }
+
+    /// How well block confidences matched what actually got done over the
+    /// last few months.
+    pub fn confidence_calibration(&self) -> AppResult<ConfidenceCalibration> {
+        self.db
+            .with_connection(|conn| confidence_calibration::load_calibration(conn, Utc::now()))
+    }
+
+    /// Assembles the review of `year` from daily aggregates, stored
+    /// snapshots, task history, applied blocks and wellness nudges. The
+    /// current year is covered up to today.
+    pub fn year_in_review(&self, year: i32) -> AppResult<YearInReview> {
+        let today = Utc::now().date_naive();
+        let (Some(first_day), Some(last_day)) = (
+            NaiveDate::from_ymd_opt(year, 1, 1),
+            NaiveDate::from_ymd_opt(year, 12, 31),
+        ) else {
+            return Err(AppError::validation("年份无效"));
+        };
+        if year < 2000 || first_day > today {
+            return Err(AppError::validation("年份无效"));
+        }
+        let last_day = last_day.min(today);
+        let start = Utc.from_utc_datetime(&first_day.and_hms_opt(0, 0, 0).unwrap());
+        let end = Utc.from_utc_datetime(&last_day.and_hms_opt(23, 59, 59).unwrap());
+
+        let daily = self.load_daily_aggregates(first_day, last_day)?;
+        let tasks = self.task_service.list_tasks_readonly()?;
+        let blocks = self.load_time_blocks(start, end)?;
+        let meetings = self.load_meeting_intervals(start, end)?;
+        let (project_names, wellness, snapshot_scores) = self.db.with_read_connection(|conn| {
+            let project_names = ProjectRepository::list(conn, true)?
+                .into_iter()
+                .map(|row| (row.id, row.name))
+                .collect::<HashMap<_, _>>();
+            let wellness =
+                WellnessRepository::list_between(conn, &start.to_rfc3339(), &end.to_rfc3339())?;
+            let prefix = format!("{year}-");
+            let snapshot_scores =
+                AnalyticsRepository::list_recent_snapshots(conn, MAX_RETENTION_DAYS as usize)?
+                    .into_iter()
+                    .filter(|row| row.snapshot_date.starts_with(&prefix))
+                    .map(|row| row.productivity_score)
+                    .collect::<Vec<_>>();
+            Ok((project_names, wellness, snapshot_scores))
+        })?;
+
+        let mut review = build_year_in_review(
+            year,
+            &YearInReviewSources {
+                daily: &daily,
+                tasks: &tasks,
+                blocks: &blocks,
+                meetings: &meetings,
+                project_names: &project_names,
+                wellness: &wellness,
+                snapshot_scores: &snapshot_scores,
+            },
+        );
+        review.markdown = render_year_in_review(&review);
+        Ok(review)
+    }
+
+    fn notify_anomalies(&self, anomalies: &[AnalyticsAnomaly]) {
+        let Some(app) = self.notifier.get() else {
+            return;
+        };
+        match self.db.with_connection(load_anomaly_notifications_enabled) {
+            Ok(true) => {
+                if let Err(err) = app.emit(ANALYTICS_ANOMALY_EVENT, anomalies) {
+                    warn!(target: "app::analytics", error = %err, "failed to emit anomaly event");
+                }
+            }
+            Ok(false) => {}
+            Err(err) => {
+                warn!(target: "app::analytics", error = %err, "failed to read anomaly notification setting")
+            }
+        }
+    }
+
+    fn load_anomaly_insights(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> AppResult<Vec<InsightCard>> {
+        let rows = self.db.with_connection(|conn| {
+            AnalyticsRepository::list_anomalies_between(
+                conn,
+                &start.date_naive(),
+                &end.date_naive(),
+            )
+        })?;
+        rows.into_iter()
+            .map(|row| row.into_record().map(|anomaly| anomaly_insight(&anomaly)))
+            .collect()
     }
 
     fn build_snapshot_record(&self, date: NaiveDate) -> AppResult<AnalyticsSnapshotRecord> {
         let day_start = Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap());
         let day_end = Utc.from_utc_datetime(&date.and_hms_opt(23, 59, 59).unwrap());
 
-        let tasks = self.task_service.list_tasks()?;
+        let tasks = self.task_service.list_tasks_readonly()?;
         let day_blocks = self.load_time_blocks(day_start, day_end)?;
 
         let lookback_start = if SNAPSHOT_LOOKBACK_DAYS > 1 {
@@ -454,7 +1087,14 @@ impl AnalyticsService {
             day_blocks.clone()
         };
 
-        let window_stats = build_daily_stats(&tasks, &lookback_blocks, lookback_start, day_end);
+        let idle_minutes = self.load_confirmed_idle_minutes(lookback_start.date_naive(), date)?;
+        let window_stats = build_daily_stats(
+            &tasks,
+            &lookback_blocks,
+            &idle_minutes,
+            lookback_start,
+            day_end,
+        );
         let day_stats = window_stats
             .iter()
             .find(|(day, _)| *day == date)
@@ -502,6 +1142,8 @@ impl AnalyticsService {
             day_stats.overdue,
             estimated_total_minutes,
         ));
+        let task_goals = self.load_task_goals()?;
+        let context_switches = count_context_switches(&day_blocks, &task_goals);
 
         Ok(AnalyticsSnapshotRecord {
             snapshot_date: date.to_string(),
@@ -519,23 +1161,16 @@ impl AnalyticsService {
             focus_consistency,
             rest_balance,
             capacity_risk,
+            context_switches,
             created_at: Utc::now().to_rfc3339(),
         })
     }
 
-    fn persist_snapshot(
-        &self,
-        record: &AnalyticsSnapshotRecord,
-        retention_cutoff: Option<NaiveDate>,
-    ) -> AppResult<()> {
+    /// Old snapshots are pruned by the retention job.
+    fn persist_snapshot(&self, record: &AnalyticsSnapshotRecord) -> AppResult<()> {
         let row = AnalyticsSnapshotRow::from_record(record);
-        self.db.with_connection(|conn| {
-            AnalyticsRepository::upsert_snapshot(conn, &row)?;
-            if let Some(cutoff) = retention_cutoff {
-                let _ = AnalyticsRepository::delete_before(conn, &cutoff)?;
-            }
-            Ok(())
-        })
+        self.db
+            .with_background_write(|conn| AnalyticsRepository::upsert_snapshot(conn, &row))
     }
 
     fn next_snapshot_run(now: DateTime<Utc>) -> DateTime<Utc> {
@@ -557,13 +1192,6 @@ impl AnalyticsService {
             Utc.from_utc_datetime(&next_target)
         }
     }
-
-    fn retention_cutoff(date: NaiveDate) -> Option<NaiveDate> {
-        if SNAPSHOT_RETENTION_DAYS <= 0 {
-            return None;
-        }
-        date.checked_sub_signed(Duration::days(SNAPSHOT_RETENTION_DAYS))
-    }
 }
 
 fn default_reports_dir(db_path: &Path) -> PathBuf {
@@ -676,6 +1304,39 @@ fn block_overlaps_range(
     }
 }
 
+fn build_habit_consistency(rules: Vec<RecurringTaskStats>) -> HabitConsistency {
+    let rules: Vec<RecurringTaskStats> = rules
+        .into_iter()
+        .filter(|rule| rule.total_occurrences > 0)
+        .collect();
+    let total: i64 = rules.iter().map(|rule| rule.total_occurrences).sum();
+    let completed: i64 = rules.iter().map(|rule| rule.completed_occurrences).sum();
+    let delays: Vec<f64> = rules
+        .iter()
+        .filter_map(|rule| {
+            rule.average_delay_minutes
+                .map(|delay| delay * rule.completed_occurrences as f64)
+        })
+        .collect();
+
+    HabitConsistency {
+        tracked_rules: rules.len() as i64,
+        completion_rate: if total > 0 {
+            round_ratio(completed as f64 / total as f64)
+        } else {
+            0.0
+        },
+        longest_current_streak: rules
+            .iter()
+            .map(|rule| rule.current_streak)
+            .max()
+            .unwrap_or(0),
+        average_delay_minutes: (completed > 0)
+            .then(|| (delays.iter().sum::<f64>() / completed as f64 * 10.0).round() / 10.0),
+        rules,
+    }
+}
+
 fn clamp_ratio(value: f64) -> f64 {
     if value.is_finite() {
         value.clamp(0.0, 1.0)
@@ -942,6 +1603,508 @@ fn build_insights(
     vec![completion, focus]
 }
 
+/// A nudge when today's score could still save an ongoing streak.
+fn score_streak_insight(streak: &ScoreStreak) -> Option<InsightCard> {
+    if !streak.at_risk {
+        return None;
+    }
+    let target = streak.target?;
+    let today_score = streak.today_score.unwrap_or(0.0);
+
+    Some(InsightCard {
+        id: "insight-score-streak".to_string(),
+        headline: format!("连续 {} 天达标，今天还差一点", streak.current),
+        detail: format!(
+            "今天目前的效率得分为 {today_score:.0}，目标是 {target:.0}。完成一两个小任务就能延续连续达标记录。"
+        ),
+        action_label: Some("查看任务".to_string()),
+        action_href: Some("/tasks".to_string()),
+        severity: "warning".to_string(),
+        related_ids: None,
+        generated_at: Utc::now().to_rfc3339(),
+        source: "rule".to_string(),
+    })
+}
+
+fn build_meeting_load_insight(load: &MeetingLoadBreakdown) -> Option<InsightCard> {
+    let style = load.schedule_style?;
+    let (headline, severity) = match style {
+        ScheduleStyle::Maker => ("创客型日程：专注时间充足", "success"),
+        ScheduleStyle::Mixed => ("会议与专注时间较为均衡", "info"),
+        ScheduleStyle::Manager => ("管理者型日程：会议占比偏高", "warning"),
+    };
+
+    Some(InsightCard {
+        id: "insight-meeting-load".to_string(),
+        headline: headline.to_string(),
+        detail: format!(
+            "会议 {:.1} 小时，专注 {:.1} 小时，{} 个不足 {} 分钟的碎片时段，创客指数 {:.0}。",
+            load.meeting_hours,
+            load.focus_hours,
+            load.fragmented_gaps,
+            FRAGMENT_GAP_MINUTES,
+            load.maker_score
+        ),
+        action_label: Some("查看日历".to_string()),
+        action_href: Some("/calendar".to_string()),
+        severity: severity.to_string(),
+        related_ids: None,
+        generated_at: Utc::now().to_rfc3339(),
+        source: "rule".to_string(),
+    })
+}
+
+/// Whether a snapshot shows no activity at all, which says nothing about
+/// the completion rate.
+fn is_idle_snapshot(snapshot: &AnalyticsSnapshotRecord) -> bool {
+    snapshot.total_tasks_completed == 0 && snapshot.overdue_tasks == 0
+}
+
+/// Flags a sudden completion-rate drop or overdue spike in `latest`
+/// compared with the `trailing` snapshots before it.
+fn detect_anomalies(
+    latest: &AnalyticsSnapshotRecord,
+    trailing: &[AnalyticsSnapshotRecord],
+    now: DateTime<Utc>,
+) -> Vec<AnalyticsAnomaly> {
+    let created_at = now.to_rfc3339();
+    let anomaly = |kind: AnalyticsAnomalyKind, observed: f64, baseline: f64, detail: String| {
+        AnalyticsAnomaly {
+            id: format!("anomaly-{}-{}", latest.snapshot_date, kind.as_str()),
+            kind,
+            snapshot_date: latest.snapshot_date.clone(),
+            observed,
+            baseline: (baseline * 1000.0).round() / 1000.0,
+            detail,
+            created_at: created_at.clone(),
+        }
+    };
+    let mut anomalies = Vec::new();
+
+    let active_rates: Vec<f64> = trailing
+        .iter()
+        .filter(|snapshot| !is_idle_snapshot(snapshot))
+        .map(|snapshot| snapshot.completion_rate)
+        .collect();
+    if !is_idle_snapshot(latest) && active_rates.len() >= ANOMALY_MIN_BASELINE_DAYS {
+        let baseline = mean(&active_rates);
+        if baseline - latest.completion_rate >= COMPLETION_DROP_THRESHOLD {
+            anomalies.push(anomaly(
+                AnalyticsAnomalyKind::CompletionDrop,
+                latest.completion_rate,
+                baseline,
+                format!(
+                    "{} 完成率 {:.0}%，明显低于此前 {} 天平均的 {:.0}%。",
+                    latest.snapshot_date,
+                    latest.completion_rate * 100.0,
+                    active_rates.len(),
+                    baseline * 100.0
+                ),
+            ));
+        }
+    }
+
+    if trailing.len() >= ANOMALY_MIN_BASELINE_DAYS {
+        let counts: Vec<f64> = trailing
+            .iter()
+            .map(|snapshot| snapshot.overdue_tasks as f64)
+            .collect();
+        let baseline = mean(&counts);
+        let observed = latest.overdue_tasks as f64;
+        if observed >= baseline * OVERDUE_SPIKE_RATIO
+            && observed - baseline >= OVERDUE_SPIKE_MIN_INCREASE
+        {
+            anomalies.push(anomaly(
+                AnalyticsAnomalyKind::OverdueSpike,
+                observed,
+                baseline,
+                format!(
+                    "{} 逾期任务增至 {} 个，此前 {} 天平均 {:.1} 个。",
+                    latest.snapshot_date,
+                    latest.overdue_tasks,
+                    trailing.len(),
+                    baseline
+                ),
+            ));
+        }
+    }
+
+    anomalies
+}
+
+fn anomaly_insight(anomaly: &AnalyticsAnomaly) -> InsightCard {
+    InsightCard {
+        id: format!("insight-{}", anomaly.id),
+        headline: anomaly.kind.headline().to_string(),
+        detail: anomaly.detail.clone(),
+        action_label: Some("查看任务".to_string()),
+        action_href: Some("/tasks".to_string()),
+        severity: "critical".to_string(),
+        related_ids: None,
+        generated_at: anomaly.created_at.clone(),
+        source: "rule".to_string(),
+    }
+}
+
+fn is_meeting_event(event_type: Option<&str>) -> bool {
+    match event_type {
+        None => true,
+        Some(kind) => MEETING_EVENT_TYPES
+            .iter()
+            .any(|meeting| kind.trim().eq_ignore_ascii_case(meeting)),
+    }
+}
+
+/// Only applied blocks count as focus; draft blocks of unchosen options do not.
+fn applied_block_intervals(
+    blocks: &[PlanningTimeBlockRecord],
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    blocks
+        .iter()
+        .filter(|block| block.applied_at.is_some())
+        .filter_map(|block| Some((parse_block_start(block)?, parse_block_end(block)?)))
+        .filter(|(start, end)| end > start)
+        .collect()
+}
+
+/// Sorts and merges overlapping intervals.
+fn merge_intervals(
+    spans: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    intervals::normalize(
+        spans
+            .into_iter()
+            .filter_map(|(start, end)| Interval::new(start, end)),
+    )
+    .into_iter()
+    .map(|span| (span.start, span.end))
+    .collect()
+}
+
+fn week_start_of(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+fn build_meeting_load(
+    meetings: &[(DateTime<Utc>, DateTime<Utc>)],
+    focus: &[(DateTime<Utc>, DateTime<Utc>)],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> MeetingLoadBreakdown {
+    let clamp = |intervals: &[(DateTime<Utc>, DateTime<Utc>)]| {
+        intervals
+            .iter()
+            .map(|(from, to)| ((*from).max(start), (*to).min(end)))
+            .filter(|(from, to)| to > from)
+            .collect::<Vec<_>>()
+    };
+    let meetings = merge_intervals(clamp(meetings));
+    let focus = merge_intervals(clamp(focus));
+
+    let mut weeks: std::collections::BTreeMap<NaiveDate, (i64, i64, i64)> =
+        std::collections::BTreeMap::new();
+    let mut week = week_start_of(start.date_naive());
+    while week <= end.date_naive() {
+        weeks.insert(week, (0, 0, 0));
+        week += Duration::days(7);
+    }
+    for (from, to) in &meetings {
+        weeks.entry(week_start_of(from.date_naive())).or_default().0 += (*to - *from).num_minutes();
+    }
+    for (from, to) in &focus {
+        weeks.entry(week_start_of(from.date_naive())).or_default().1 += (*to - *from).num_minutes();
+    }
+
+    // A fragment is a short gap between two commitments on the same day
+    let busy = merge_intervals(meetings.iter().chain(focus.iter()).copied().collect());
+    for pair in busy.windows(2) {
+        let (_, previous_end) = pair[0];
+        let (next_start, _) = pair[1];
+        let gap = (next_start - previous_end).num_minutes();
+        if previous_end.date_naive() == next_start.date_naive()
+            && gap > 0
+            && gap < FRAGMENT_GAP_MINUTES
+        {
+            weeks
+                .entry(week_start_of(previous_end.date_naive()))
+                .or_default()
+                .2 += 1;
+        }
+    }
+
+    let meeting_minutes: i64 = weeks.values().map(|(minutes, _, _)| minutes).sum();
+    let focus_minutes: i64 = weeks.values().map(|(_, minutes, _)| minutes).sum();
+    let fragmented_gaps: i64 = weeks.values().map(|(_, _, gaps)| gaps).sum();
+
+    let (maker_score, schedule_style) = if meeting_minutes + focus_minutes == 0 {
+        (0.0, None)
+    } else {
+        let focus_share = focus_minutes as f64 / (meeting_minutes + focus_minutes) as f64;
+        let week_count = weeks.len().max(1) as f64;
+        // Each weekly fragment costs two points, capped at thirty
+        let fragment_penalty = (fragmented_gaps as f64 / week_count * 2.0).min(30.0);
+        let score = (focus_share * 100.0 - fragment_penalty).clamp(0.0, 100.0);
+        let style = if score >= MAKER_SCORE_THRESHOLD {
+            ScheduleStyle::Maker
+        } else if score <= MANAGER_SCORE_THRESHOLD {
+            ScheduleStyle::Manager
+        } else {
+            ScheduleStyle::Mixed
+        };
+        ((score * 10.0).round() / 10.0, Some(style))
+    };
+
+    MeetingLoadBreakdown {
+        weeks: weeks
+            .into_iter()
+            .map(|(week, (meeting, focus, gaps))| MeetingLoadWeek {
+                week_start: date_to_iso(week),
+                meeting_hours: minutes_to_hours(meeting),
+                focus_hours: minutes_to_hours(focus),
+                fragmented_gaps: gaps,
+            })
+            .collect(),
+        meeting_hours: minutes_to_hours(meeting_minutes),
+        focus_hours: minutes_to_hours(focus_minutes),
+        fragmented_gaps,
+        maker_score,
+        schedule_style,
+    }
+}
+
+fn nudge_title(reason: WellnessTriggerReason) -> &'static str {
+    match reason {
+        WellnessTriggerReason::FocusStreak => "专注休息提醒",
+        WellnessTriggerReason::WorkStreak => "连续工作提醒",
+        WellnessTriggerReason::WindDown => "睡前放松提醒",
+    }
+}
+
+fn build_day_timeline(
+    blocks: &[PlanningTimeBlockRecord],
+    tasks: &[TaskRecord],
+    nudges: &[WellnessEventRecord],
+    log: Option<&DayLogRecord>,
+    day_start: DateTime<Utc>,
+    day_end: DateTime<Utc>,
+) -> Vec<DayTimelineEntry> {
+    let titles: HashMap<&str, &str> = tasks
+        .iter()
+        .map(|task| (task.id.as_str(), task.title.as_str()))
+        .collect();
+    let task_title = |task_id: &str| titles.get(task_id).copied().unwrap_or(task_id).to_string();
+    let mut entries = Vec::new();
+
+    for block in blocks {
+        entries.push(DayTimelineEntry {
+            kind: DayTimelineEntryKind::Block,
+            source_id: block.id.clone(),
+            start_at: block.start_at.clone(),
+            end_at: Some(block.end_at.clone()),
+            title: task_title(&block.task_id),
+            task_id: Some(block.task_id.clone()),
+            detail: Some(block.status.clone()),
+        });
+        if let Some(actual_start) = &block.actual_start_at {
+            entries.push(DayTimelineEntry {
+                kind: DayTimelineEntryKind::Actual,
+                source_id: block.id.clone(),
+                start_at: actual_start.clone(),
+                end_at: block.actual_end_at.clone(),
+                title: task_title(&block.task_id),
+                task_id: Some(block.task_id.clone()),
+                detail: None,
+            });
+        }
+    }
+
+    for task in tasks {
+        let Some(completed) = parse_record_datetime(&task.completed_at) else {
+            continue;
+        };
+        if completed >= day_start && completed < day_end {
+            entries.push(DayTimelineEntry {
+                kind: DayTimelineEntryKind::Completion,
+                source_id: task.id.clone(),
+                start_at: completed.to_rfc3339(),
+                end_at: None,
+                title: task.title.clone(),
+                task_id: Some(task.id.clone()),
+                detail: None,
+            });
+        }
+    }
+
+    for nudge in nudges {
+        entries.push(DayTimelineEntry {
+            kind: DayTimelineEntryKind::Nudge,
+            source_id: nudge.id.to_string(),
+            start_at: nudge.window_start.clone(),
+            end_at: None,
+            title: nudge_title(nudge.trigger_reason).to_string(),
+            task_id: None,
+            detail: nudge
+                .response
+                .map(|response| response.as_str().to_string())
+                .or_else(|| nudge.suggested_micro_task.clone()),
+        });
+    }
+
+    if let Some(log) = log {
+        entries.push(DayTimelineEntry {
+            kind: DayTimelineEntryKind::Note,
+            source_id: log.log_date.clone(),
+            start_at: log.closed_at.clone(),
+            end_at: None,
+            title: "当日复盘".to_string(),
+            task_id: None,
+            detail: log.reflection.clone(),
+        });
+    }
+
+    entries.sort_by_cached_key(|entry| {
+        (
+            DateTime::parse_from_rfc3339(&entry.start_at)
+                .map(|dt| dt.with_timezone(&Utc))
+                .ok(),
+            entry.kind,
+        )
+    });
+    entries
+}
+
+fn is_context_switch(previous: &str, next: &str, task_goals: &TaskGoals) -> bool {
+    if previous == next {
+        return false;
+    }
+    match (task_goals.get(previous), task_goals.get(next)) {
+        (Some(left), Some(right)) => left.is_disjoint(right),
+        _ => true,
+    }
+}
+
+fn count_switches(task_ids: &[&str], task_goals: &TaskGoals) -> i64 {
+    task_ids
+        .windows(2)
+        .filter(|pair| is_context_switch(pair[0], pair[1], task_goals))
+        .count() as i64
+}
+
+/// Counts adjacent applied blocks (ordered by start) that move to an unrelated
+/// task.
+fn count_context_switches(blocks: &[PlanningTimeBlockRecord], task_goals: &TaskGoals) -> i64 {
+    let mut applied: Vec<(DateTime<Utc>, &str)> = blocks
+        .iter()
+        .filter(|block| block.applied_at.is_some())
+        .filter_map(|block| Some((parse_block_start(block)?, block.task_id.as_str())))
+        .collect();
+    applied.sort_by_key(|(start, _)| *start);
+    let task_ids: Vec<&str> = applied.iter().map(|(_, task_id)| *task_id).collect();
+    count_switches(&task_ids, task_goals)
+}
+
+/// Looks for A-B-A runs within one option and day and proposes swapping the
+/// trailing pair so both A blocks sit together. The swapped blocks keep their
+/// durations and the gap between them, so the overall span is unchanged.
+/// Blocks that already started, lie in the past or are marked fixed stay put.
+fn build_defrag_suggestions(
+    blocks: &[PlanningTimeBlockRecord],
+    task_goals: &TaskGoals,
+    titles: &HashMap<String, String>,
+    now: DateTime<Utc>,
+) -> Vec<DefragmentationSuggestion> {
+    type Slot<'a> = (DateTime<Utc>, DateTime<Utc>, &'a PlanningTimeBlockRecord);
+
+    let mut groups: BTreeMap<(String, NaiveDate), Vec<Slot<'_>>> = BTreeMap::new();
+    for block in blocks {
+        let (Some(start), Some(end)) = (parse_block_start(block), parse_block_end(block)) else {
+            continue;
+        };
+        if end <= start {
+            continue;
+        }
+        groups
+            .entry((block.option_id.clone(), start.date_naive()))
+            .or_default()
+            .push((start, end, block));
+    }
+
+    let movable = |(start, _, block): &Slot<'_>| {
+        *start >= now
+            && block.actual_start_at.is_none()
+            && block.flexibility.as_deref() != Some("fixed")
+    };
+
+    let mut suggestions = Vec::new();
+    for ((option_id, date), mut slots) in groups {
+        slots.sort_by_key(|(start, _, _)| *start);
+        let task_ids: Vec<&str> = slots
+            .iter()
+            .map(|(_, _, block)| block.task_id.as_str())
+            .collect();
+        let baseline = count_switches(&task_ids, task_goals);
+
+        let mut index = 0;
+        while index + 2 < slots.len() {
+            let (first, middle, last) = (&slots[index], &slots[index + 1], &slots[index + 2]);
+            let task_id = first.2.task_id.as_str();
+            let candidate = last.2.task_id == task_id
+                && is_context_switch(task_id, &middle.2.task_id, task_goals)
+                && movable(middle)
+                && movable(last);
+            if !candidate {
+                index += 1;
+                continue;
+            }
+
+            let mut swapped = task_ids.clone();
+            swapped.swap(index + 1, index + 2);
+            let saved = baseline - count_switches(&swapped, task_goals);
+            if saved <= 0 {
+                index += 1;
+                continue;
+            }
+
+            let (middle_start, middle_end, middle_block) = *middle;
+            let (last_start, last_end, last_block) = *last;
+            let moved_last = (middle_start, middle_start + (last_end - last_start));
+            let moved_middle = (last_end - (middle_end - middle_start), last_end);
+            let title = titles.get(task_id).map(String::as_str).unwrap_or(task_id);
+
+            suggestions.push(DefragmentationSuggestion {
+                id: format!("defrag-{}-{}", middle_block.id, last_block.id),
+                date: date.to_string(),
+                task_id: task_id.to_string(),
+                switches_saved: saved,
+                summary: format!(
+                    "将「{}」的两段时间连续安排，可减少 {} 次上下文切换",
+                    title, saved
+                ),
+                resolution: ResolveConflictInput {
+                    session_id: String::new(),
+                    option_id: option_id.clone(),
+                    adjustments: vec![
+                        TimeBlockOverride {
+                            block_id: last_block.id.clone(),
+                            start_at: Some(moved_last.0.to_rfc3339()),
+                            end_at: Some(moved_last.1.to_rfc3339()),
+                            flexibility: None,
+                        },
+                        TimeBlockOverride {
+                            block_id: middle_block.id.clone(),
+                            start_at: Some(moved_middle.0.to_rfc3339()),
+                            end_at: Some(moved_middle.1.to_rfc3339()),
+                            flexibility: None,
+                        },
+                    ],
+                },
+            });
+            index += 3;
+        }
+    }
+    suggestions
+}
+
 fn predict_workload(tasks: &[TaskRecord]) -> i64 {
     let active_count = tasks
         .iter()
@@ -953,6 +2116,7 @@ fn predict_workload(tasks: &[TaskRecord]) -> i64 {
 fn build_daily_stats(
     tasks: &[TaskRecord],
     blocks: &[PlanningTimeBlockRecord],
+    idle_minutes: &HashMap<NaiveDate, i64>,
     start: DateTime<Utc>,
     end: DateTime<Utc>,
 ) -> Vec<(NaiveDate, DailyStats)> {
@@ -997,8 +2161,9 @@ fn build_daily_stats(
     }
 
     for (day, minutes) in focus_by_day {
+        let idle = idle_minutes.get(&day).copied().unwrap_or(0);
         let entry = stats.entry(day).or_default();
-        entry.focus_minutes += minutes;
+        entry.focus_minutes += (minutes - idle).max(0);
     }
 
     let mut ordered: Vec<(NaiveDate, DailyStats)> = stats.into_iter().collect();
@@ -1177,6 +2342,292 @@ fn compute_capacity_risk(tasks: &[TaskRecord], overdue_tasks: i64, estimated_min
     (backlog_pressure * 0.4 + utilization_pressure * 0.4 + overdue_pressure * 0.2).clamp(0.0, 1.0)
 }
 
+struct YearInReviewSources<'a> {
+    daily: &'a [(NaiveDate, DailyStats)],
+    tasks: &'a [TaskRecord],
+    blocks: &'a [PlanningTimeBlockRecord],
+    meetings: &'a [(DateTime<Utc>, DateTime<Utc>)],
+    project_names: &'a HashMap<String, String>,
+    wellness: &'a [WellnessEventRecord],
+    snapshot_scores: &'a [f64],
+}
+
+fn build_year_in_review(year: i32, sources: &YearInReviewSources<'_>) -> YearInReview {
+    let in_year = |time: &DateTime<Utc>| time.year() == year;
+
+    let totals = YearInReviewTotals {
+        tasks_completed: sources.daily.iter().map(|(_, stats)| stats.completed).sum(),
+        focus_minutes: sources
+            .daily
+            .iter()
+            .map(|(_, stats)| stats.focus_minutes)
+            .sum(),
+        active_days: sources
+            .daily
+            .iter()
+            .filter(|(_, stats)| stats.completed > 0 || stats.focus_minutes > 0)
+            .count() as i64,
+        average_productivity_score: (!sources.snapshot_scores.is_empty())
+            .then(|| (mean(sources.snapshot_scores) * 10.0).round() / 10.0),
+    };
+
+    let mut weeks: BTreeMap<NaiveDate, (i64, i64)> = BTreeMap::new();
+    for (date, stats) in sources.daily {
+        let week = weeks.entry(week_start_of(*date)).or_default();
+        week.0 += stats.focus_minutes;
+        week.1 += stats.completed;
+    }
+    let mut busiest_weeks: Vec<YearInReviewWeek> = weeks
+        .into_iter()
+        .filter(|(_, (focus, completed))| *focus > 0 || *completed > 0)
+        .map(
+            |(week_start, (focus_minutes, tasks_completed))| YearInReviewWeek {
+                week_start: week_start.to_string(),
+                focus_minutes,
+                tasks_completed,
+            },
+        )
+        .collect();
+    busiest_weeks.sort_by(|a, b| {
+        b.focus_minutes
+            .cmp(&a.focus_minutes)
+            .then_with(|| b.tasks_completed.cmp(&a.tasks_completed))
+            .then_with(|| a.week_start.cmp(&b.week_start))
+    });
+    busiest_weeks.truncate(YEAR_REVIEW_TOP_N);
+
+    let applied: Vec<(&PlanningTimeBlockRecord, DateTime<Utc>, DateTime<Utc>)> = sources
+        .blocks
+        .iter()
+        .filter(|block| block.applied_at.is_some())
+        .filter_map(|block| Some((block, parse_block_start(block)?, parse_block_end(block)?)))
+        .filter(|(_, start, end)| end > start && in_year(start))
+        .collect();
+    let completed: Vec<(&TaskRecord, DateTime<Utc>)> = sources
+        .tasks
+        .iter()
+        .filter_map(|task| Some((task, parse_record_datetime(&task.completed_at)?)))
+        .filter(|(_, done_at)| in_year(done_at))
+        .collect();
+
+    let task_projects: HashMap<&str, &str> = sources
+        .tasks
+        .iter()
+        .filter_map(|task| Some((task.id.as_str(), task.project_id.as_deref()?)))
+        .collect();
+    let mut project_totals: HashMap<&str, (i64, i64)> = HashMap::new();
+    for (task, _) in &completed {
+        if let Some(project_id) = task.project_id.as_deref() {
+            project_totals.entry(project_id).or_default().0 += 1;
+        }
+    }
+    for (block, start, end) in &applied {
+        if let Some(project_id) = task_projects.get(block.task_id.as_str()) {
+            project_totals.entry(project_id).or_default().1 += (*end - *start).num_minutes();
+        }
+    }
+    let mut top_projects: Vec<YearInReviewProject> = project_totals
+        .into_iter()
+        .map(
+            |(project_id, (tasks_completed, focus_minutes))| YearInReviewProject {
+                name: sources
+                    .project_names
+                    .get(project_id)
+                    .cloned()
+                    .unwrap_or_else(|| project_id.to_string()),
+                project_id: project_id.to_string(),
+                tasks_completed,
+                focus_minutes,
+            },
+        )
+        .collect();
+    top_projects.sort_by(|a, b| {
+        b.tasks_completed
+            .cmp(&a.tasks_completed)
+            .then_with(|| b.focus_minutes.cmp(&a.focus_minutes))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    top_projects.truncate(YEAR_REVIEW_TOP_N);
+
+    // Only tasks with applied blocks have actual time to compare against
+    let mut estimate_accuracy = Vec::new();
+    for month in 1..=12 {
+        let month_blocks: Vec<PlanningTimeBlockRecord> = applied
+            .iter()
+            .filter(|(block, _, _)| {
+                completed
+                    .iter()
+                    .any(|(task, done_at)| done_at.month() == month && task.id == block.task_id)
+            })
+            .map(|(block, _, _)| (*block).clone())
+            .collect();
+        let month_tasks: Vec<TaskRecord> = completed
+            .iter()
+            .filter(|(task, done_at)| {
+                done_at.month() == month
+                    && month_blocks.iter().any(|block| block.task_id == task.id)
+            })
+            .map(|(task, _)| (*task).clone())
+            .collect();
+        if month_tasks.is_empty() {
+            continue;
+        }
+        let estimated_total = month_tasks.iter().map(task_estimated_minutes).sum();
+        let (efficiency, _) =
+            build_efficiency_metrics(&month_tasks, &month_blocks, 0, estimated_total);
+        estimate_accuracy.push(YearInReviewMonthValue {
+            month,
+            value: round_ratio(efficiency.estimate_accuracy),
+            samples: month_tasks.len(),
+        });
+    }
+
+    let mut wellness_months: BTreeMap<u32, YearInReviewWellnessMonth> = BTreeMap::new();
+    for event in sources.wellness {
+        let Some(at) = parse_record_datetime(&Some(event.window_start.clone())) else {
+            continue;
+        };
+        if !in_year(&at) {
+            continue;
+        }
+        let month =
+            wellness_months
+                .entry(at.month())
+                .or_insert_with(|| YearInReviewWellnessMonth {
+                    month: at.month(),
+                    ..Default::default()
+                });
+        month.nudges += 1;
+        match event.response {
+            Some(WellnessResponse::Completed) => month.breaks_taken += 1,
+            Some(WellnessResponse::Snoozed) => month.snoozed += 1,
+            Some(WellnessResponse::Ignored) => month.ignored += 1,
+            None => {}
+        }
+    }
+
+    let meeting_days: HashSet<NaiveDate> = sources
+        .meetings
+        .iter()
+        .flat_map(|(start, end)| [start.date_naive(), end.date_naive()])
+        .collect();
+    let mut focus_by_day: BTreeMap<NaiveDate, i64> = BTreeMap::new();
+    for (_, start, end) in &applied {
+        *focus_by_day.entry(start.date_naive()).or_default() += (*end - *start).num_minutes();
+    }
+    let mut focus_days: Vec<YearInReviewFocusDay> = focus_by_day
+        .into_iter()
+        .filter(|(date, minutes)| *minutes > 0 && !meeting_days.contains(date))
+        .map(|(date, focus_minutes)| YearInReviewFocusDay {
+            date: date.to_string(),
+            focus_minutes,
+        })
+        .collect();
+    focus_days.sort_by(|a, b| {
+        b.focus_minutes
+            .cmp(&a.focus_minutes)
+            .then_with(|| a.date.cmp(&b.date))
+    });
+    focus_days.truncate(YEAR_REVIEW_TOP_N);
+
+    YearInReview {
+        year,
+        totals,
+        busiest_weeks,
+        top_projects,
+        estimate_accuracy,
+        wellness: wellness_months.into_values().collect(),
+        focus_days,
+        markdown: String::new(),
+        generated_at: Utc::now().to_rfc3339(),
+    }
+}
+
+fn render_year_in_review(review: &YearInReview) -> String {
+    const EMPTY: &str = "- 暂无数据\n";
+    let totals = &review.totals;
+    let mut content = format!("# {} 年度回顾\n\n", review.year);
+    content.push_str(&format!("生成时间：{}\n\n", review.generated_at));
+
+    content.push_str("## 全年概览\n");
+    content.push_str(&format!(
+        "- 完成任务：{}\n- 专注总时长：{:.1} 小时\n- 活跃天数：{}\n",
+        totals.tasks_completed,
+        totals.focus_minutes as f64 / 60.0,
+        totals.active_days
+    ));
+    match totals.average_productivity_score {
+        Some(score) => content.push_str(&format!(
+            "- 平均效率得分：{score:.1}（仅统计仍保留快照的日期）\n"
+        )),
+        None => content.push_str("- 平均效率得分：暂无快照\n"),
+    }
+    content.push('\n');
+
+    content.push_str("## 最忙碌的几周\n");
+    if review.busiest_weeks.is_empty() {
+        content.push_str(EMPTY);
+    }
+    for week in &review.busiest_weeks {
+        content.push_str(&format!(
+            "- {} 当周：专注 {} 分钟，完成 {} 个任务\n",
+            week.week_start, week.focus_minutes, week.tasks_completed
+        ));
+    }
+    content.push('\n');
+
+    content.push_str("## 投入最多的项目\n");
+    if review.top_projects.is_empty() {
+        content.push_str(EMPTY);
+    }
+    for project in &review.top_projects {
+        content.push_str(&format!(
+            "- {}：完成 {} 个任务，专注 {} 分钟\n",
+            project.name, project.tasks_completed, project.focus_minutes
+        ));
+    }
+    content.push('\n');
+
+    content.push_str("## 预估准确率变化\n");
+    if review.estimate_accuracy.is_empty() {
+        content.push_str(EMPTY);
+    }
+    for month in &review.estimate_accuracy {
+        content.push_str(&format!(
+            "- {} 月：{:.1}%（{} 个任务）\n",
+            month.month,
+            month.value * 100.0,
+            month.samples
+        ));
+    }
+    content.push('\n');
+
+    content.push_str("## 休息提醒\n");
+    if review.wellness.is_empty() {
+        content.push_str(EMPTY);
+    }
+    for month in &review.wellness {
+        content.push_str(&format!(
+            "- {} 月：提醒 {} 次，休息 {} 次，推迟 {} 次，忽略 {} 次\n",
+            month.month, month.nudges, month.breaks_taken, month.snoozed, month.ignored
+        ));
+    }
+    content.push('\n');
+
+    content.push_str("## 无会议的高专注日\n");
+    if review.focus_days.is_empty() {
+        content.push_str(EMPTY);
+    }
+    for day in &review.focus_days {
+        content.push_str(&format!(
+            "- {}：专注 {} 分钟\n",
+            day.date, day.focus_minutes
+        ));
+    }
+
+    content
+}
+
 fn render_markdown_report(overview: &AnalyticsOverviewResponse) -> String {
     let summary = &overview.overview.summary;
     let mut content = String::new();
@@ -1262,6 +2713,15 @@ mod tests {
             recurrence: None,
             ai: None,
             external_links: Vec::new(),
+            project_id: None,
+            snoozed_until: None,
+            snooze_notify: false,
+            color: None,
+            icon: None,
+            delegated_to: None,
+            waiting_on: None,
+            waiting_since: None,
+            follow_up_task_id: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
             updated_at: "2024-01-01T00:00:00Z".to_string(),
         }
@@ -1403,10 +2863,333 @@ mod tests {
         assert_eq!(second.productivity_score, 68.0);
     }
 
+    fn snapshot(date: &str, completed: i64, rate: f64, overdue: i64) -> AnalyticsSnapshotRecord {
+        AnalyticsSnapshotRecord {
+            snapshot_date: date.to_string(),
+            total_tasks_completed: completed,
+            completion_rate: rate,
+            overdue_tasks: overdue,
+            total_focus_minutes: 0,
+            productivity_score: 0.0,
+            efficiency_rating: 0.0,
+            time_spent_work: 0.0,
+            time_spent_study: 0.0,
+            time_spent_life: 0.0,
+            time_spent_other: 0.0,
+            on_time_ratio: 0.0,
+            focus_consistency: 0.0,
+            rest_balance: 0.0,
+            capacity_risk: 0.0,
+            context_switches: 0,
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn detect_anomalies_flags_completion_drop_and_overdue_spike() {
+        let now = Utc.with_ymd_and_hms(2025, 5, 9, 1, 15, 0).unwrap();
+        let trailing = vec![
+            snapshot("2025-05-07", 4, 0.8, 1),
+            snapshot("2025-05-06", 5, 0.9, 0),
+            snapshot("2025-05-05", 0, 0.0, 0),
+            snapshot("2025-05-04", 3, 0.75, 1),
+        ];
+
+        let calm = detect_anomalies(&snapshot("2025-05-08", 3, 0.7, 2), &trailing, now);
+        assert!(calm.is_empty());
+
+        let anomalies = detect_anomalies(&snapshot("2025-05-08", 1, 0.2, 5), &trailing, now);
+        let kinds: Vec<_> = anomalies.iter().map(|anomaly| anomaly.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                AnalyticsAnomalyKind::CompletionDrop,
+                AnalyticsAnomalyKind::OverdueSpike
+            ]
+        );
+        assert_eq!(anomalies[0].id, "anomaly-2025-05-08-completion_drop");
+        // The idle day is left out of the completion baseline.
+        assert!((anomalies[0].baseline - 0.817).abs() < 1e-9);
+        assert_eq!(anomaly_insight(&anomalies[1]).severity, "critical");
+
+        let idle = detect_anomalies(&snapshot("2025-05-08", 0, 0.0, 0), &trailing, now);
+        assert!(idle.is_empty());
+        assert!(
+            detect_anomalies(&snapshot("2025-05-08", 1, 0.2, 5), &trailing[..2], now).is_empty()
+        );
+    }
+
     #[test]
     fn completion_ratio_handles_zero_due_tasks() {
         assert_eq!(completion_ratio(0, 0), 0.0);
         assert_eq!(completion_ratio(3, 0), 1.0);
         assert_eq!(completion_ratio(1, 2), 0.5);
     }
+
+    #[test]
+    fn build_meeting_load_counts_hours_fragments_and_style() {
+        let at = |day: u32, hour: u32, minute: u32| {
+            Utc.with_ymd_and_hms(2025, 5, day, hour, minute, 0).unwrap()
+        };
+        let meetings = vec![
+            (at(5, 9, 0), at(5, 10, 0)),
+            (at(5, 9, 30), at(5, 10, 0)),
+            (at(5, 13, 0), at(5, 13, 30)),
+        ];
+        let focus = vec![(at(5, 10, 15), at(5, 12, 15))];
+
+        let load = build_meeting_load(&meetings, &focus, at(5, 0, 0), at(11, 23, 0));
+
+        assert_eq!(load.weeks.len(), 1);
+        assert_eq!(load.weeks[0].week_start, "2025-05-05T00:00:00+00:00");
+        assert_eq!(load.meeting_hours, 1.5);
+        assert_eq!(load.focus_hours, 2.0);
+        assert_eq!(load.fragmented_gaps, 1);
+        assert_eq!(load.maker_score, 55.1);
+        assert_eq!(load.schedule_style, Some(ScheduleStyle::Mixed));
+
+        let empty = build_meeting_load(&[], &[], at(5, 0, 0), at(11, 23, 0));
+        assert_eq!(empty.schedule_style, None);
+        assert!(build_meeting_load_insight(&empty).is_none());
+        assert!(!is_meeting_event(Some("personal")));
+        assert!(is_meeting_event(Some("Meeting")));
+    }
+
+    #[test]
+    fn context_switches_and_defrag_suggestions() {
+        let at = |hour: u32, minute: u32| {
+            Utc.with_ymd_and_hms(2025, 5, 6, hour, minute, 0)
+                .unwrap()
+                .to_rfc3339()
+        };
+        let block = |id: &str, task_id: &str, start: String, end: String| PlanningTimeBlockRecord {
+            id: id.to_string(),
+            option_id: "option-1".to_string(),
+            task_id: task_id.to_string(),
+            start_at: start,
+            end_at: end,
+            flexibility: None,
+            confidence: None,
+            conflict_flags: None,
+            applied_at: Some(at(8, 0)),
+            actual_start_at: None,
+            actual_end_at: None,
+            status: "planned".to_string(),
+            prep_note: None,
+        };
+        let blocks = vec![
+            block("b1", "write", at(9, 0), at(10, 0)),
+            block("b2", "email", at(10, 0), at(10, 30)),
+            block("b3", "write", at(10, 45), at(11, 45)),
+            block("b4", "review", at(13, 0), at(14, 0)),
+        ];
+
+        let mut task_goals = TaskGoals::new();
+        assert_eq!(count_context_switches(&blocks, &task_goals), 3);
+        task_goals.insert("write".to_string(), HashSet::from(["goal".to_string()]));
+        task_goals.insert("review".to_string(), HashSet::from(["goal".to_string()]));
+        assert_eq!(count_context_switches(&blocks, &task_goals), 2);
+
+        let titles = HashMap::from([("write".to_string(), "写报告".to_string())]);
+        let now = Utc.with_ymd_and_hms(2025, 5, 6, 7, 0, 0).unwrap();
+        let suggestions = build_defrag_suggestions(&blocks, &TaskGoals::new(), &titles, now);
+
+        assert_eq!(suggestions.len(), 1);
+        let suggestion = &suggestions[0];
+        assert_eq!(suggestion.switches_saved, 1);
+        assert_eq!(suggestion.date, "2025-05-06");
+        assert!(suggestion.summary.contains("写报告"));
+        let adjustments = &suggestion.resolution.adjustments;
+        assert_eq!(adjustments[0].block_id, "b3");
+        assert_eq!(adjustments[0].start_at.as_deref(), Some(at(10, 0).as_str()));
+        assert_eq!(adjustments[0].end_at.as_deref(), Some(at(11, 0).as_str()));
+        assert_eq!(adjustments[1].block_id, "b2");
+        assert_eq!(
+            adjustments[1].start_at.as_deref(),
+            Some(at(11, 15).as_str())
+        );
+        assert_eq!(adjustments[1].end_at.as_deref(), Some(at(11, 45).as_str()));
+
+        let later = Utc.with_ymd_and_hms(2025, 5, 6, 10, 15, 0).unwrap();
+        assert!(build_defrag_suggestions(&blocks, &TaskGoals::new(), &titles, later).is_empty());
+    }
+
+    #[test]
+    fn build_day_timeline_orders_entries_from_all_sources() {
+        let at = |hour: u32, minute: u32| {
+            Utc.with_ymd_and_hms(2025, 5, 6, hour, minute, 0)
+                .unwrap()
+                .to_rfc3339()
+        };
+        let block = PlanningTimeBlockRecord {
+            id: "block-1".to_string(),
+            option_id: "option-1".to_string(),
+            task_id: "task-1".to_string(),
+            start_at: at(9, 0),
+            end_at: at(10, 0),
+            flexibility: None,
+            confidence: None,
+            conflict_flags: None,
+            applied_at: Some(at(8, 0)),
+            actual_start_at: Some(at(9, 10)),
+            actual_end_at: Some(at(10, 5)),
+            status: "completed".to_string(),
+            prep_note: None,
+        };
+        let mut done = base_task("task-1");
+        done.completed_at = Some(at(10, 5));
+        let mut yesterday = base_task("task-2");
+        yesterday.completed_at = Some(at(0, 0).replace("2025-05-06", "2025-05-05"));
+        let nudge = WellnessEventRecord {
+            id: 7,
+            window_start: at(10, 30),
+            trigger_reason: WellnessTriggerReason::FocusStreak,
+            recommended_break_minutes: 5,
+            suggested_micro_task: Some("起身拉伸".to_string()),
+            response: None,
+            response_at: None,
+            deferral_count: 0,
+        };
+        let log = DayLogRecord {
+            log_date: "2025-05-06".to_string(),
+            closed_at: at(18, 0),
+            reflection: Some("上午效率不错".to_string()),
+            completed_tasks: 1,
+            unfinished_blocks: 0,
+            decisions: Vec::new(),
+            productivity_score: None,
+            created_at: at(18, 0),
+            updated_at: at(18, 0),
+        };
+        let day_start = Utc.with_ymd_and_hms(2025, 5, 6, 0, 0, 0).unwrap();
+
+        let entries = build_day_timeline(
+            &[block],
+            &[done, yesterday],
+            &[nudge],
+            Some(&log),
+            day_start,
+            day_start + Duration::days(1),
+        );
+
+        let kinds: Vec<DayTimelineEntryKind> = entries.iter().map(|entry| entry.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                DayTimelineEntryKind::Block,
+                DayTimelineEntryKind::Actual,
+                DayTimelineEntryKind::Completion,
+                DayTimelineEntryKind::Nudge,
+                DayTimelineEntryKind::Note,
+            ]
+        );
+        assert_eq!(entries[0].title, "Task task-1");
+        assert_eq!(entries[3].detail.as_deref(), Some("起身拉伸"));
+        assert_eq!(entries[4].detail.as_deref(), Some("上午效率不错"));
+    }
+
+    #[test]
+    fn build_year_in_review_ranks_weeks_projects_and_focus_days() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 3, d).unwrap();
+        let daily = vec![
+            (
+                day(3),
+                DailyStats {
+                    completed: 2,
+                    focus_minutes: 120,
+                    ..Default::default()
+                },
+            ),
+            (
+                day(10),
+                DailyStats {
+                    completed: 1,
+                    focus_minutes: 300,
+                    ..Default::default()
+                },
+            ),
+            (day(11), DailyStats::default()),
+        ];
+
+        let mut write = base_task("write");
+        write.project_id = Some("p-1".to_string());
+        write.estimated_minutes = Some(60);
+        write.completed_at = Some("2025-03-10T12:00:00Z".to_string());
+        let mut old = base_task("old");
+        old.project_id = Some("p-2".to_string());
+        old.completed_at = Some("2024-12-30T12:00:00Z".to_string());
+        let tasks = vec![write, old];
+
+        let block = |id: &str, start: &str, end: &str| PlanningTimeBlockRecord {
+            id: id.to_string(),
+            option_id: "option-1".to_string(),
+            task_id: "write".to_string(),
+            start_at: start.to_string(),
+            end_at: end.to_string(),
+            flexibility: None,
+            confidence: None,
+            conflict_flags: None,
+            applied_at: Some("2025-03-01T08:00:00Z".to_string()),
+            actual_start_at: None,
+            actual_end_at: None,
+            status: "planned".to_string(),
+            prep_note: None,
+        };
+        let blocks = vec![
+            block("b1", "2025-03-10T09:00:00Z", "2025-03-10T10:00:00Z"),
+            block("b2", "2025-03-11T09:00:00Z", "2025-03-11T09:30:00Z"),
+        ];
+        let meetings = vec![(
+            Utc.with_ymd_and_hms(2025, 3, 11, 14, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2025, 3, 11, 15, 0, 0).unwrap(),
+        )];
+        let project_names = HashMap::from([("p-1".to_string(), "写作".to_string())]);
+        let wellness = vec![WellnessEventRecord {
+            id: 1,
+            window_start: "2025-03-10T11:00:00Z".to_string(),
+            trigger_reason: WellnessTriggerReason::FocusStreak,
+            recommended_break_minutes: 5,
+            suggested_micro_task: None,
+            response: Some(WellnessResponse::Completed),
+            response_at: None,
+            deferral_count: 0,
+        }];
+
+        let review = build_year_in_review(
+            2025,
+            &YearInReviewSources {
+                daily: &daily,
+                tasks: &tasks,
+                blocks: &blocks,
+                meetings: &meetings,
+                project_names: &project_names,
+                wellness: &wellness,
+                snapshot_scores: &[70.0, 80.0],
+            },
+        );
+
+        assert_eq!(review.totals.tasks_completed, 3);
+        assert_eq!(review.totals.focus_minutes, 420);
+        assert_eq!(review.totals.active_days, 2);
+        assert_eq!(review.totals.average_productivity_score, Some(75.0));
+        let weeks: Vec<_> = review
+            .busiest_weeks
+            .iter()
+            .map(|week| week.week_start.as_str())
+            .collect();
+        assert_eq!(weeks, ["2025-03-10", "2025-03-03"]);
+        assert_eq!(review.top_projects.len(), 1);
+        assert_eq!(review.top_projects[0].name, "写作");
+        assert_eq!(review.top_projects[0].focus_minutes, 90);
+        assert_eq!(review.estimate_accuracy.len(), 1);
+        assert_eq!(review.estimate_accuracy[0].month, 3);
+        assert_eq!(review.wellness[0].breaks_taken, 1);
+        // The 11th had a meeting, so only the 10th counts as a focus day
+        assert_eq!(review.focus_days.len(), 1);
+        assert_eq!(review.focus_days[0].date, "2025-03-10");
+
+        let markdown = render_year_in_review(&review);
+        assert!(markdown.starts_with("# 2025 年度回顾"));
+        assert!(markdown.contains("- 写作：完成 1 个任务，专注 90 分钟"));
+    }
 }
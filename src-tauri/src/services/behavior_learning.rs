@@ -1,16 +1,36 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use chrono::{DateTime, Datelike, FixedOffset};
+use chrono::{DateTime, Datelike, Duration, FixedOffset};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value as JsonValue};
 use uuid::Uuid;
 
-use crate::db::repositories::planning_repository::{PlanningRepository, SchedulePreferencesRow};
-use crate::error::AppResult;
+use crate::db::repositories::planning_repository::{
+    PlanningRepository, PreferenceProfileRow, SchedulePreferencesRow,
+};
+use crate::error::{AppError, AppResult};
+use crate::models::memory::MemoryFact;
 use crate::models::planning::SchedulePreferencesRecord;
+use crate::models::settings::SleepSchedule;
 use crate::services::schedule_utils;
+use crate::services::schedule_utils::intervals::{self, Interval};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+/// Profile that always exists and is used until another one is chosen.
+pub const DEFAULT_PREFERENCE_ID: &str = "default";
+const DEFAULT_PROFILE_NAME: &str = "默认";
+const MAX_PROFILE_NAME_CHARS: usize = 40;
+const DEFAULT_BREAK_MINUTES: i64 = 10;
+const MINUTES_PER_DAY: u32 = 24 * 60;
+const MAX_BUFFER_MINUTES: i64 = 120;
+const MAX_AVOIDANCE_WINDOWS: usize = 50;
+const MIN_BREAK_AFTER_FOCUS_MINUTES: i64 = 15;
+const MAX_BREAK_AFTER_FOCUS_MINUTES: i64 = 480;
+const MAX_BREAK_MINUTES: i64 = 120;
+const DEFAULT_CONTEXT_SWITCH_WEIGHT: f64 = 1.0;
+const MAX_CONTEXT_SWITCH_WEIGHT: f64 = 5.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct PreferenceSnapshot {
     pub focus_start_minute: Option<u32>,
@@ -19,9 +39,204 @@ pub struct PreferenceSnapshot {
     pub prefer_compact_schedule: bool,
     #[serde(default)]
     pub avoidance_windows: Vec<AvoidanceWindow>,
+    /// Focus minutes in a row before the planner inserts a break; `None` disables breaks.
+    #[serde(default)]
+    pub break_after_focus_minutes: Option<i64>,
+    #[serde(default = "default_break_minutes")]
+    pub break_minutes: i64,
+    /// Focus window and buffer for specific weekdays, e.g. no deep work on
+    /// Fridays.
+    #[serde(default)]
+    pub weekday_overrides: Vec<WeekdayPreference>,
+    /// How strongly plans are pushed to keep same-project work together: 0
+    /// ignores task switches, higher values favour long single-project
+    /// stretches over variety.
+    #[serde(default = "default_context_switch_weight")]
+    pub context_switch_weight: f64,
+    /// Preferences and constraints from the user's memory facts. They are not
+    /// learned, so they are never written back with the rest of the snapshot.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub user_facts: Vec<MemoryFact>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+fn default_break_minutes() -> i64 {
+    DEFAULT_BREAK_MINUTES
+}
+
+fn default_context_switch_weight() -> f64 {
+    DEFAULT_CONTEXT_SWITCH_WEIGHT
+}
+
+impl PreferenceSnapshot {
+    /// Rejects values the planner cannot honour. The error names the
+    /// offending field in `details.field` so editors can highlight it.
+    pub fn validate(&self) -> AppResult<()> {
+        let invalid = |field: &str, message: String| {
+            Err(AppError::validation_with_details(
+                message,
+                json!({ "field": field }),
+            ))
+        };
+
+        if let Some((field, message)) =
+            focus_window_error(self.focus_start_minute, self.focus_end_minute)
+        {
+            return invalid(field, message);
+        }
+
+        if !(0..=MAX_BUFFER_MINUTES).contains(&self.buffer_minutes_between_blocks) {
+            return invalid(
+                "bufferMinutesBetweenBlocks",
+                format!("任务间缓冲时间需在 0 到 {MAX_BUFFER_MINUTES} 分钟之间"),
+            );
+        }
+
+        if self.avoidance_windows.len() > MAX_AVOIDANCE_WINDOWS {
+            return invalid(
+                "avoidanceWindows",
+                format!("规避时段最多 {MAX_AVOIDANCE_WINDOWS} 个"),
+            );
+        }
+        for window in &self.avoidance_windows {
+            if window.weekday > 6 {
+                return invalid(
+                    "avoidanceWindows",
+                    "规避时段的星期需在 0（周一）到 6（周日）之间".to_string(),
+                );
+            }
+            if window.end_minute > MINUTES_PER_DAY || window.start_minute >= window.end_minute {
+                return invalid(
+                    "avoidanceWindows",
+                    "规避时段的开始时间必须早于结束时间，且不能跨天".to_string(),
+                );
+            }
+        }
+
+        let mut seen_weekdays = HashSet::new();
+        for day in &self.weekday_overrides {
+            if day.weekday > 6 || !seen_weekdays.insert(day.weekday) {
+                return invalid(
+                    "weekdayOverrides",
+                    "每个星期（0 为周一，6 为周日）最多设置一条单独的偏好".to_string(),
+                );
+            }
+            if let Some((_, message)) =
+                focus_window_error(day.focus_start_minute, day.focus_end_minute)
+            {
+                return invalid("weekdayOverrides", message);
+            }
+            if day
+                .buffer_minutes_between_blocks
+                .is_some_and(|buffer| !(0..=MAX_BUFFER_MINUTES).contains(&buffer))
+            {
+                return invalid(
+                    "weekdayOverrides",
+                    format!("任务间缓冲时间需在 0 到 {MAX_BUFFER_MINUTES} 分钟之间"),
+                );
+            }
+        }
+
+        if let Some(after) = self.break_after_focus_minutes {
+            if !(MIN_BREAK_AFTER_FOCUS_MINUTES..=MAX_BREAK_AFTER_FOCUS_MINUTES).contains(&after) {
+                return invalid(
+                    "breakAfterFocusMinutes",
+                    format!(
+                        "连续专注时长需在 {MIN_BREAK_AFTER_FOCUS_MINUTES} 到 {MAX_BREAK_AFTER_FOCUS_MINUTES} 分钟之间"
+                    ),
+                );
+            }
+            if !(1..=MAX_BREAK_MINUTES).contains(&self.break_minutes) {
+                return invalid(
+                    "breakMinutes",
+                    format!("休息时长需在 1 到 {MAX_BREAK_MINUTES} 分钟之间"),
+                );
+            }
+        }
+
+        if !(0.0..=MAX_CONTEXT_SWITCH_WEIGHT).contains(&self.context_switch_weight) {
+            return invalid(
+                "contextSwitchWeight",
+                format!("任务切换惩罚权重需在 0 到 {MAX_CONTEXT_SWITCH_WEIGHT} 之间"),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Focus window on `weekday` (0 = Monday) in minutes after midnight.
+    pub fn focus_window_on(&self, weekday: u32) -> Option<(u32, u32)> {
+        focus_window_on(
+            (self.focus_start_minute, self.focus_end_minute),
+            &self.weekday_overrides,
+            weekday,
+        )
+    }
+
+    pub fn buffer_minutes_on(&self, weekday: u32) -> i64 {
+        buffer_minutes_on(
+            self.buffer_minutes_between_blocks,
+            &self.weekday_overrides,
+            weekday,
+        )
+    }
+}
+
+/// Field and message for a focus window the planner cannot use. Both ends
+/// unset is fine and means no focus window.
+fn focus_window_error(start: Option<u32>, end: Option<u32>) -> Option<(&'static str, String)> {
+    match (start, end) {
+        (None, None) => None,
+        (Some(_), Some(end)) if end > MINUTES_PER_DAY => Some((
+            "focusEndMinute",
+            format!("专注时段需在 0 到 {MINUTES_PER_DAY} 分钟之间"),
+        )),
+        (Some(start), Some(end)) if start >= end => Some((
+            "focusStartMinute",
+            "专注时段的开始时间必须早于结束时间".to_string(),
+        )),
+        (Some(_), Some(_)) => None,
+        (Some(_), None) => Some((
+            "focusEndMinute",
+            "专注时段的开始和结束时间需同时设置".to_string(),
+        )),
+        (None, Some(_)) => Some((
+            "focusStartMinute",
+            "专注时段的开始和结束时间需同时设置".to_string(),
+        )),
+    }
+}
+
+/// Focus window on `weekday`, taken from that day's override when there is
+/// one and from `default` otherwise.
+pub(crate) fn focus_window_on(
+    default: (Option<u32>, Option<u32>),
+    overrides: &[WeekdayPreference],
+    weekday: u32,
+) -> Option<(u32, u32)> {
+    let (start, end) = match overrides.iter().find(|day| day.weekday == weekday) {
+        Some(day) => (day.focus_start_minute, day.focus_end_minute),
+        None => default,
+    };
+    match (start, end) {
+        (Some(start), Some(end)) if start < end => Some((start, end)),
+        _ => None,
+    }
+}
+
+pub(crate) fn buffer_minutes_on(
+    default: i64,
+    overrides: &[WeekdayPreference],
+    weekday: u32,
+) -> i64 {
+    overrides
+        .iter()
+        .find(|day| day.weekday == weekday)
+        .and_then(|day| day.buffer_minutes_between_blocks)
+        .unwrap_or(default)
+        .max(0)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AvoidanceWindow {
     pub weekday: u32,
@@ -29,6 +244,55 @@ pub struct AvoidanceWindow {
     pub end_minute: u32,
 }
 
+/// A named set of preferences, e.g. "exam week", chosen per planning run
+/// through its `preference_id`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PreferenceProfile {
+    pub id: String,
+    pub name: String,
+    /// Whether planning runs without a `preference_id` use this profile.
+    pub is_default: bool,
+    pub updated_at: String,
+}
+
+impl PreferenceProfile {
+    fn from_row(row: PreferenceProfileRow, default_id: &str) -> Self {
+        let name = row
+            .name
+            .filter(|name| !name.trim().is_empty())
+            .unwrap_or_else(|| {
+                if row.id == DEFAULT_PREFERENCE_ID {
+                    DEFAULT_PROFILE_NAME.to_string()
+                } else {
+                    row.id.clone()
+                }
+            });
+        Self {
+            is_default: row.id == default_id,
+            id: row.id,
+            name,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+/// Replaces the focus window, and optionally the buffer, on one weekday.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WeekdayPreference {
+    /// 0 = Monday, as in [`AvoidanceWindow`].
+    pub weekday: u32,
+    /// Leaving both ends unset means no focus window on that day.
+    #[serde(default)]
+    pub focus_start_minute: Option<u32>,
+    #[serde(default)]
+    pub focus_end_minute: Option<u32>,
+    /// The default buffer applies when unset.
+    #[serde(default)]
+    pub buffer_minutes_between_blocks: Option<i64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct FeedbackEvent {
@@ -70,6 +334,72 @@ impl<'a> BehaviorLearningService<'a> {
         Ok(())
     }
 
+    /// Fails with `NotFound` when no profile has this id, unlike
+    /// `load_preferences` which falls back to the defaults.
+    pub fn load_profile_preferences(&self, preference_id: &str) -> AppResult<PreferenceSnapshot> {
+        let row = PlanningRepository::get_schedule_preferences(self.conn, preference_id)?
+            .ok_or_else(AppError::not_found)?;
+        Ok(self.parse_preferences(&row.into_record()?))
+    }
+
+    /// Profiles with `default_id` marked as the default.
+    pub fn list_profiles(&self, default_id: &str) -> AppResult<Vec<PreferenceProfile>> {
+        Ok(PlanningRepository::list_preference_profiles(self.conn)?
+            .into_iter()
+            .map(|row| PreferenceProfile::from_row(row, default_id))
+            .collect())
+    }
+
+    /// Creates a profile named `name`, copying the preferences of
+    /// `clone_from` or starting from the defaults.
+    pub fn create_profile(
+        &self,
+        name: &str,
+        clone_from: Option<&str>,
+        default_id: &str,
+    ) -> AppResult<PreferenceProfile> {
+        let name = name.trim();
+        if name.is_empty() || name.chars().count() > MAX_PROFILE_NAME_CHARS {
+            return Err(AppError::validation(format!(
+                "配置名称需为 1 到 {MAX_PROFILE_NAME_CHARS} 个字符"
+            )));
+        }
+        let existing = self.list_profiles(default_id)?;
+        if existing
+            .iter()
+            .any(|profile| profile.name.to_lowercase() == name.to_lowercase())
+        {
+            return Err(AppError::conflict(format!("已存在名为「{name}」的配置")));
+        }
+
+        let snapshot = match clone_from {
+            Some(source) => self.load_profile_preferences(source)?,
+            None => PreferenceSnapshot::default(),
+        };
+        let id = Uuid::new_v4().to_string();
+        self.save_preferences(&id, &snapshot)?;
+        PlanningRepository::set_preference_profile_name(self.conn, &id, name)?;
+
+        self.list_profiles(default_id)?
+            .into_iter()
+            .find(|profile| profile.id == id)
+            .ok_or_else(AppError::not_found)
+    }
+
+    /// The built-in profile and the current default cannot be deleted.
+    pub fn delete_profile(&self, preference_id: &str, default_id: &str) -> AppResult<()> {
+        if preference_id == DEFAULT_PREFERENCE_ID {
+            return Err(AppError::validation("内置的默认配置不能删除"));
+        }
+        if preference_id == default_id {
+            return Err(AppError::validation("该配置正被设为默认，请先切换默认配置"));
+        }
+        if PlanningRepository::delete_schedule_preferences(self.conn, preference_id)? == 0 {
+            return Err(AppError::not_found());
+        }
+        Ok(())
+    }
+
     pub fn snapshot_for_planning(&self, preference_id: &str) -> AppResult<JsonValue> {
         let snapshot = self.load_preferences(preference_id)?;
         Ok(json!({
@@ -78,6 +408,9 @@ impl<'a> BehaviorLearningService<'a> {
             "bufferMinutesBetweenBlocks": snapshot.buffer_minutes_between_blocks,
             "preferCompactSchedule": snapshot.prefer_compact_schedule,
             "avoidanceWindows": snapshot.avoidance_windows,
+            "breakAfterFocusMinutes": snapshot.break_after_focus_minutes,
+            "breakMinutes": snapshot.break_minutes,
+            "weekdayOverrides": snapshot.weekday_overrides,
         }))
     }
 
@@ -154,6 +487,32 @@ impl<'a> BehaviorLearningService<'a> {
                     .collect::<Vec<_>>()
             })
             .unwrap_or_default();
+        let break_after_focus_minutes = record
+            .data
+            .get("breakAfterFocusMinutes")
+            .and_then(|value| value.as_i64())
+            .filter(|minutes| *minutes > 0);
+        let break_minutes = record
+            .data
+            .get("breakMinutes")
+            .and_then(|value| value.as_i64())
+            .unwrap_or(DEFAULT_BREAK_MINUTES);
+        let weekday_overrides = record
+            .data
+            .get("weekdayOverrides")
+            .and_then(|value| value.as_array())
+            .map(|array| {
+                array
+                    .iter()
+                    .filter_map(|item| serde_json::from_value(item.clone()).ok())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let context_switch_weight = record
+            .data
+            .get("contextSwitchWeight")
+            .and_then(|value| value.as_f64())
+            .unwrap_or(DEFAULT_CONTEXT_SWITCH_WEIGHT);
 
         PreferenceSnapshot {
             focus_start_minute: focus_start,
@@ -161,6 +520,11 @@ impl<'a> BehaviorLearningService<'a> {
             buffer_minutes_between_blocks: buffer,
             prefer_compact_schedule: prefer_compact,
             avoidance_windows,
+            break_after_focus_minutes,
+            break_minutes,
+            weekday_overrides,
+            context_switch_weight,
+            user_facts: Vec::new(),
         }
     }
 
@@ -188,6 +552,10 @@ impl<'a> BehaviorLearningService<'a> {
             "bufferMinutesBetweenBlocks": snapshot.buffer_minutes_between_blocks,
             "preferCompactSchedule": snapshot.prefer_compact_schedule,
             "avoidanceWindows": avoidance,
+            "breakAfterFocusMinutes": snapshot.break_after_focus_minutes,
+            "breakMinutes": snapshot.break_minutes,
+            "weekdayOverrides": snapshot.weekday_overrides,
+            "contextSwitchWeight": snapshot.context_switch_weight,
         });
 
         SchedulePreferencesRecord {
@@ -308,7 +676,7 @@ impl<'a> BehaviorLearningService<'a> {
             new_windows.push(AvoidanceWindow {
                 weekday: *weekday,
                 start_minute: avg_start.saturating_sub(30),
-                end_minute: avg_end + 30,
+                end_minute: (avg_end + 30).min(MINUTES_PER_DAY),
             });
         }
 
@@ -320,6 +688,117 @@ impl<'a> BehaviorLearningService<'a> {
     }
 }
 
+/// How one day splits into plannable time under a set of preferences.
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PreferencePreview {
+    /// `YYYY-MM-DD` of the previewed day.
+    pub date: String,
+    pub windows: Vec<PreviewWindow>,
+    pub available_minutes: i64,
+    pub focus_minutes: i64,
+    /// Avoidance windows of that weekday, clipped to the workday.
+    pub avoided: Vec<PreviewWindow>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewWindow {
+    pub start_at: String,
+    pub end_at: String,
+    /// Inside the preferred focus window.
+    pub focus: bool,
+}
+
+/// Windows the planner would use on the day starting at `midnight`: the
+/// workday minus sleep and that weekday's avoidance windows, split at the
+/// focus window.
+pub fn preview_day(
+    snapshot: &PreferenceSnapshot,
+    midnight: DateTime<FixedOffset>,
+    workday: (i16, i16),
+    sleep_schedule: Option<&SleepSchedule>,
+) -> PreferencePreview {
+    let at = |minute: i64| midnight + Duration::minutes(minute);
+    let workday_spans: Vec<_> = Interval::new(at(workday.0 as i64), at(workday.1 as i64))
+        .into_iter()
+        .collect();
+
+    let mut blocked: Vec<Interval<DateTime<FixedOffset>>> = Vec::new();
+    if let Some(schedule) = sleep_schedule {
+        for span in &workday_spans {
+            blocked.extend(
+                schedule
+                    .sleep_intervals(span.start, span.end)
+                    .into_iter()
+                    .filter_map(|(bed, wake)| Interval::new(bed, wake)),
+            );
+        }
+    }
+    let weekday = midnight.weekday().num_days_from_monday();
+    let avoidance: Vec<_> = snapshot
+        .avoidance_windows
+        .iter()
+        .filter(|window| window.weekday == weekday)
+        .filter_map(|window| {
+            Interval::new(at(window.start_minute as i64), at(window.end_minute as i64))
+        })
+        .collect();
+    let avoided = intervals::intersect(&workday_spans, &avoidance);
+    blocked.extend(avoidance);
+
+    let available = intervals::subtract(&workday_spans, &blocked);
+    let focus_spans: Vec<_> = snapshot
+        .focus_window_on(weekday)
+        .and_then(|(start, end)| Interval::new(at(start as i64), at(end as i64)))
+        .into_iter()
+        .collect();
+    let focused = intervals::intersect(&available, &focus_spans);
+    let unfocused = intervals::subtract(&available, &focus_spans);
+
+    let mut spans: Vec<_> = focused
+        .iter()
+        .map(|span| (span, true))
+        .chain(unfocused.iter().map(|span| (span, false)))
+        .collect();
+    spans.sort_by_key(|(span, _)| span.start);
+
+    let mut warnings = Vec::new();
+    if available.is_empty() {
+        warnings.push("这一天没有可安排的时间".to_string());
+    } else if !focus_spans.is_empty() && focused.is_empty() {
+        warnings.push("专注时段与可用时间没有重叠，专注时段优先方案将无法生效".to_string());
+    }
+    let buffer_minutes = snapshot.buffer_minutes_on(weekday);
+    if buffer_minutes > 0 && intervals::total_minutes(&available) < buffer_minutes * 2 {
+        warnings.push("可用时间过短，扣除缓冲时间后难以安排任务".to_string());
+    }
+
+    PreferencePreview {
+        date: midnight.date_naive().to_string(),
+        available_minutes: intervals::total_minutes(&available),
+        focus_minutes: intervals::total_minutes(&focused),
+        windows: spans
+            .into_iter()
+            .map(|(span, focus)| preview_window(span, focus))
+            .collect(),
+        avoided: avoided
+            .iter()
+            .map(|span| preview_window(span, false))
+            .collect(),
+        warnings,
+    }
+}
+
+fn preview_window(span: &Interval<DateTime<FixedOffset>>, focus: bool) -> PreviewWindow {
+    PreviewWindow {
+        start_at: schedule_utils::format_datetime(span.start),
+        end_at: schedule_utils::format_datetime(span.end),
+        focus,
+    }
+}
+
 #[derive(Debug, Clone)]
 struct FeedbackMetric {
     completed: bool,
@@ -470,4 +949,130 @@ mod tests {
         assert!(avoidance.start_minute <= tuesday_start + 60);
         assert!(avoidance.end_minute >= tuesday_start + 90);
     }
+
+    #[test]
+    fn validate_rejects_inverted_focus_and_bad_windows() {
+        let valid = PreferenceSnapshot {
+            focus_start_minute: Some(9 * 60),
+            focus_end_minute: Some(12 * 60),
+            buffer_minutes_between_blocks: 15,
+            break_after_focus_minutes: Some(90),
+            break_minutes: 10,
+            ..Default::default()
+        };
+        assert!(valid.validate().is_ok());
+
+        let inverted = PreferenceSnapshot {
+            focus_start_minute: Some(12 * 60),
+            focus_end_minute: Some(9 * 60),
+            ..valid.clone()
+        };
+        assert!(inverted.validate().is_err());
+
+        let half_set = PreferenceSnapshot {
+            focus_end_minute: None,
+            ..valid.clone()
+        };
+        assert!(half_set.validate().is_err());
+
+        let bad_window = PreferenceSnapshot {
+            avoidance_windows: vec![AvoidanceWindow {
+                weekday: 7,
+                start_minute: 60,
+                end_minute: 120,
+            }],
+            ..valid.clone()
+        };
+        assert!(bad_window.validate().is_err());
+
+        let no_break = PreferenceSnapshot {
+            break_minutes: 0,
+            ..valid
+        };
+        assert!(no_break.validate().is_err());
+    }
+
+    #[test]
+    fn weekday_overrides_replace_focus_and_buffer() {
+        let friday = WeekdayPreference {
+            weekday: 4,
+            focus_start_minute: None,
+            focus_end_minute: None,
+            buffer_minutes_between_blocks: Some(30),
+        };
+        let snapshot = PreferenceSnapshot {
+            focus_start_minute: Some(9 * 60),
+            focus_end_minute: Some(12 * 60),
+            buffer_minutes_between_blocks: 10,
+            weekday_overrides: vec![friday.clone()],
+            ..Default::default()
+        };
+        assert!(snapshot.validate().is_ok());
+        assert_eq!(snapshot.focus_window_on(0), Some((9 * 60, 12 * 60)));
+        assert_eq!(snapshot.buffer_minutes_on(0), 10);
+        assert_eq!(snapshot.focus_window_on(4), None);
+        assert_eq!(snapshot.buffer_minutes_on(4), 30);
+
+        let duplicated = PreferenceSnapshot {
+            weekday_overrides: vec![friday.clone(), friday],
+            ..snapshot.clone()
+        };
+        assert!(duplicated.validate().is_err());
+
+        let inverted = PreferenceSnapshot {
+            weekday_overrides: vec![WeekdayPreference {
+                weekday: 2,
+                focus_start_minute: Some(14 * 60),
+                focus_end_minute: Some(13 * 60),
+                buffer_minutes_between_blocks: None,
+            }],
+            ..snapshot
+        };
+        assert!(inverted.validate().is_err());
+    }
+
+    #[test]
+    fn preview_day_removes_avoidance_and_marks_focus() {
+        let tz = FixedOffset::east_opt(0).unwrap();
+        // 2025-05-06 is a Tuesday
+        let midnight = tz.with_ymd_and_hms(2025, 5, 6, 0, 0, 0).unwrap();
+        let snapshot = PreferenceSnapshot {
+            focus_start_minute: Some(9 * 60),
+            focus_end_minute: Some(11 * 60),
+            buffer_minutes_between_blocks: 10,
+            avoidance_windows: vec![
+                AvoidanceWindow {
+                    weekday: 1,
+                    start_minute: 12 * 60,
+                    end_minute: 13 * 60,
+                },
+                AvoidanceWindow {
+                    weekday: 2,
+                    start_minute: 9 * 60,
+                    end_minute: 18 * 60,
+                },
+            ],
+            ..Default::default()
+        };
+
+        let preview = preview_day(&snapshot, midnight, (9 * 60, 18 * 60), None);
+        assert_eq!(preview.date, "2025-05-06");
+        assert_eq!(preview.available_minutes, 8 * 60);
+        assert_eq!(preview.focus_minutes, 2 * 60);
+        let shape: Vec<_> = preview
+            .windows
+            .iter()
+            .map(|window| (window.start_at.as_str(), window.focus))
+            .collect();
+        assert_eq!(
+            shape,
+            vec![
+                ("2025-05-06T09:00:00+00:00", true),
+                ("2025-05-06T11:00:00+00:00", false),
+                ("2025-05-06T13:00:00+00:00", false),
+            ]
+        );
+        assert_eq!(preview.avoided.len(), 1);
+        assert!(preview.warnings.is_empty());
+    }
 }
@@ -5,6 +5,7 @@ use rusqlite::{Connection, OptionalExtension};
 use tauri::async_runtime;
 use tracing::debug;
 
+use crate::db::repositories::change_repository::ChangeRepository;
 use crate::db::DbPool;
 use crate::error::{AppError, AppResult};
 use crate::models::ai::TaskParseResponse;
@@ -36,6 +37,17 @@ CREATE INDEX IF NOT EXISTS idx_ai_cache_semantic_hash
     ON ai_cache(semantic_hash);
 CREATE INDEX IF NOT EXISTS idx_ai_cache_expires_at
     ON ai_cache(expires_at);
+
+CREATE TABLE IF NOT EXISTS agent_response_cache (
+    cache_key TEXT PRIMARY KEY,
+    revision INTEGER NOT NULL,
+    response TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    hit_count INTEGER NOT NULL DEFAULT 0
+);
+CREATE INDEX IF NOT EXISTS idx_agent_response_cache_expires_at
+    ON agent_response_cache(expires_at);
 "#;
 
 #[derive(Debug, Clone)]
@@ -69,13 +81,110 @@ impl CacheService {
         self.put_task_response(key, raw_input, response).await
     }
 
+    /// Agent reply cached for `semantic_hash`, as long as no task, project,
+    /// goal or time block changed since it was stored.
+    pub async fn get_agent_response(&self, semantic_hash: &str) -> AppResult<Option<String>> {
+        let key = AiCacheKey::new(AiCacheOperation::AgentChat, semantic_hash.to_string());
+        let cache_key: String = (&key).into();
+        let db = Arc::clone(&self.db);
+
+        async_runtime::spawn_blocking(move || {
+            let conn = db.get_connection()?;
+            ensure_schema(&conn)?;
+
+            let revision = ChangeRepository::current_revision(&conn)?;
+            let now = Utc::now().to_rfc3339();
+            let response = conn
+                .query_row(
+                    "SELECT response FROM agent_response_cache WHERE cache_key = ?1 AND revision = ?2 AND expires_at > ?3",
+                    (&cache_key, revision, &now),
+                    |row| row.get::<_, String>(0),
+                )
+                .optional()?;
+
+            if response.is_some() {
+                conn.execute(
+                    "UPDATE agent_response_cache SET hit_count = hit_count + 1 WHERE cache_key = ?1",
+                    [&cache_key],
+                )?;
+                debug!(target: "app::ai::cache", cache_key = %cache_key, revision, "agent cache hit");
+            }
+
+            Ok(response)
+        })
+        .await
+        .map_err(|err| AppError::other(format!("缓存查询失败: {err}")))?
+    }
+
+    /// Caches an agent reply against the current data revision. Replies
+    /// cached against older revisions can no longer hit and are dropped.
+    pub async fn put_agent_response(&self, semantic_hash: &str, response: &str) -> AppResult<()> {
+        let key = AiCacheKey::new(AiCacheOperation::AgentChat, semantic_hash.to_string());
+        let cache_key: String = (&key).into();
+        let response = response.to_string();
+        let db = Arc::clone(&self.db);
+        let ttl = self.ttl;
+
+        async_runtime::spawn_blocking(move || {
+            let conn = db.get_connection()?;
+            ensure_schema(&conn)?;
+
+            let revision = ChangeRepository::current_revision(&conn)?;
+            let now = Utc::now();
+            let stale = conn.execute(
+                "DELETE FROM agent_response_cache WHERE revision < ?1",
+                [revision],
+            )?;
+            conn.execute(
+                r#"
+                INSERT INTO agent_response_cache (
+                    cache_key,
+                    revision,
+                    response,
+                    created_at,
+                    expires_at,
+                    hit_count
+                ) VALUES (?1, ?2, ?3, ?4, ?5, 0)
+                ON CONFLICT(cache_key) DO UPDATE SET
+                    revision = excluded.revision,
+                    response = excluded.response,
+                    created_at = excluded.created_at,
+                    expires_at = excluded.expires_at,
+                    hit_count = 0
+                "#,
+                (
+                    &cache_key,
+                    revision,
+                    &response,
+                    now.to_rfc3339(),
+                    (now + ttl).to_rfc3339(),
+                ),
+            )?;
+
+            debug!(
+                target: "app::ai::cache",
+                cache_key = %cache_key,
+                revision,
+                stale,
+                "cached agent response"
+            );
+            Ok(())
+        })
+        .await
+        .map_err(|err| AppError::other(format!("缓存写入失败: {err}")))?
+    }
+
     pub async fn purge_expired(&self) -> AppResult<()> {
         let db = Arc::clone(&self.db);
         async_runtime::spawn_blocking(move || {
             let conn = db.get_connection()?;
             ensure_schema(&conn)?;
             let now = Utc::now().to_rfc3339();
-            let deleted = conn.execute("DELETE FROM ai_cache WHERE expires_at <= ?1", [&now])?;
+            let deleted = conn.execute("DELETE FROM ai_cache WHERE expires_at <= ?1", [&now])?
+                + conn.execute(
+                    "DELETE FROM agent_response_cache WHERE expires_at <= ?1",
+                    [&now],
+                )?;
             if deleted > 0 {
                 debug!(target: "app::ai::cache", deleted, "purged expired cache entries");
             }
@@ -222,3 +331,35 @@ fn ensure_schema(conn: &Connection) -> AppResult<()> {
     conn.execute_batch(CACHE_SCHEMA)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::task::TaskCreateInput;
+    use crate::services::task_service::TaskService;
+
+    #[tokio::test]
+    async fn agent_responses_are_dropped_once_data_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = DbPool::new(dir.path().join("cache.sqlite")).unwrap();
+        let cache = CacheService::new(db.clone(), Duration::hours(1)).unwrap();
+
+        cache
+            .put_agent_response("hash-1", "Hi there!")
+            .await
+            .unwrap();
+        assert_eq!(
+            cache.get_agent_response("hash-1").await.unwrap().as_deref(),
+            Some("Hi there!")
+        );
+        assert_eq!(cache.get_agent_response("hash-2").await.unwrap(), None);
+
+        TaskService::new(db)
+            .create_task(TaskCreateInput {
+                title: "Write report".to_string(),
+                ..TaskCreateInput::default()
+            })
+            .unwrap();
+        assert_eq!(cache.get_agent_response("hash-1").await.unwrap(), None);
+    }
+}
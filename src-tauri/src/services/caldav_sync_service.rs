@@ -0,0 +1,1416 @@
+//! Two-way sync with a CalDAV server (Nextcloud, Fastmail, iCloud, ...).
+//! Tasks are stored as VTODO and applied time blocks as VEVENT. Every synced
+//! item remembers the object's href and ETag and a hash of the local item as
+//! last synced, so a pass can tell which side changed since; items changed
+//! on both sides are settled by the account's conflict policy. Events not
+//! created by the app are never touched.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::header::{HeaderMap, CONTENT_TYPE, ETAG, IF_MATCH, IF_NONE_MATCH};
+use reqwest::{Method, StatusCode, Url};
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use crate::db::repositories::caldav_repository::{
+    CalDavAccountRow, CalDavItemRow, CalDavRepository,
+};
+use crate::db::repositories::planning_repository::{PlanningRepository, PlanningTimeBlockRow};
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::caldav::{
+    CalDavConflict, CalDavConflictPolicy, CalDavConflictWinner, CalDavConnectInput, CalDavItemKind,
+    CalDavStatus, CalDavSyncCounts, CalDavSyncReport,
+};
+use crate::models::planning::PlanningTimeBlockRecord;
+use crate::models::task::{TaskCreateInput, TaskRecord, TaskUpdateInput};
+use crate::services::task_service::TaskService;
+use crate::utils::crypto::CryptoVault;
+use crate::utils::ical::{self, Component};
+
+const SERVICE: &str = "caldav";
+const HTTP_TIMEOUT: StdDuration = StdDuration::from_secs(30);
+/// UIDs of objects created by the app start with this, followed by the
+/// item kind and local id.
+const UID_PREFIX: &str = "cognical-";
+const UNTITLED_TASK: &str = "未命名任务";
+
+const CURRENT_USER_PRINCIPAL_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:"><d:prop><d:current-user-principal/></d:prop></d:propfind>"#;
+const CALENDAR_HOME_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav"><d:prop><c:calendar-home-set/></d:prop></d:propfind>"#;
+const CALENDARS_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav"><d:prop><d:resourcetype/><d:displayname/><c:supported-calendar-component-set/></d:prop></d:propfind>"#;
+
+static RESPONSE_PATTERN: Lazy<Regex> = Lazy::new(|| element_pattern("response"));
+static HREF_PATTERN: Lazy<Regex> = Lazy::new(|| element_pattern("href"));
+static ETAG_PATTERN: Lazy<Regex> = Lazy::new(|| element_pattern("getetag"));
+static CALENDAR_DATA_PATTERN: Lazy<Regex> = Lazy::new(|| element_pattern("calendar-data"));
+static PRINCIPAL_PATTERN: Lazy<Regex> = Lazy::new(|| element_pattern("current-user-principal"));
+static HOME_SET_PATTERN: Lazy<Regex> = Lazy::new(|| element_pattern("calendar-home-set"));
+static RESOURCETYPE_PATTERN: Lazy<Regex> = Lazy::new(|| element_pattern("resourcetype"));
+static COMPONENT_SET_PATTERN: Lazy<Regex> =
+    Lazy::new(|| element_pattern("supported-calendar-component-set"));
+static CALENDAR_TYPE_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"<(?:[\w.-]+:)?calendar(?:\s[^>]*)?/?>").unwrap());
+static COMP_NAME_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?i)comp\s+name\s*=\s*["']([a-z]+)["']"#).unwrap());
+
+fn element_pattern(name: &str) -> Regex {
+    Regex::new(&format!(
+        r"(?s)<(?:[\w.-]+:)?{name}(?:\s[^>]*)?>(.*?)</(?:[\w.-]+:)?{name}\s*>"
+    ))
+    .unwrap()
+}
+
+pub struct CalDavSyncService {
+    db: DbPool,
+    task_service: Arc<TaskService>,
+    vault: CryptoVault,
+    syncing: AtomicBool,
+}
+
+impl CalDavSyncService {
+    pub fn new(db: DbPool, task_service: Arc<TaskService>) -> AppResult<Self> {
+        let vault = CryptoVault::from_database_path(db.path())?;
+        Ok(Self {
+            db,
+            task_service,
+            vault,
+            syncing: AtomicBool::new(false),
+        })
+    }
+
+    pub fn status(&self) -> AppResult<CalDavStatus> {
+        let syncing = self.syncing.load(Ordering::SeqCst);
+        self.db.with_connection(|conn| {
+            let Some(account) = CalDavRepository::get_account(conn)? else {
+                return Ok(CalDavStatus {
+                    syncing,
+                    ..CalDavStatus::default()
+                });
+            };
+            let count = |kind: CalDavItemKind| {
+                CalDavRepository::count_items(conn, kind.as_str()).map(|count| count as u32)
+            };
+            Ok(CalDavStatus {
+                connected: true,
+                conflict_policy: parse_policy(&account.conflict_policy),
+                syncing,
+                synced_tasks: count(CalDavItemKind::Task)?,
+                synced_time_blocks: count(CalDavItemKind::TimeBlock)?,
+                server_url: Some(account.server_url),
+                username: Some(account.username),
+                task_calendar_url: account.task_calendar_url,
+                event_calendar_url: account.event_calendar_url,
+                last_sync_at: account.last_sync_at,
+                last_error: account.last_error,
+            })
+        })
+    }
+
+    /// Checks the credentials, finds the calendars for tasks and events and
+    /// stores the account, replacing any previous one.
+    pub async fn connect(&self, input: CalDavConnectInput) -> AppResult<CalDavStatus> {
+        let server_url = input.server_url.trim();
+        let username = input.username.trim();
+        if username.is_empty() || input.password.is_empty() {
+            return Err(AppError::validation("请填写 CalDAV 用户名和密码"));
+        }
+        let client = CalDavClient::new(server_url, username, &input.password)?;
+
+        let explicit = |value: Option<String>| {
+            value
+                .map(|url| url.trim().to_string())
+                .filter(|url| !url.is_empty())
+                .map(|url| client.resolve(&url).map(|url| url.to_string()))
+                .transpose()
+        };
+        let mut task_calendar_url = explicit(input.task_calendar_url)?;
+        let mut event_calendar_url = explicit(input.event_calendar_url)?;
+        if task_calendar_url.is_none() || event_calendar_url.is_none() {
+            let discovered = client.discover().await?;
+            task_calendar_url = task_calendar_url.or(discovered.tasks);
+            event_calendar_url = event_calendar_url.or(discovered.events);
+        }
+        if task_calendar_url.is_none() && event_calendar_url.is_none() {
+            return Err(AppError::validation(
+                "服务器上没有找到可存放任务或日程的日历",
+            ));
+        }
+
+        let now = Utc::now().to_rfc3339();
+        let row = CalDavAccountRow {
+            server_url: client.base.to_string(),
+            username: username.to_string(),
+            password: self.vault.encrypt(input.password.as_bytes())?,
+            task_calendar_url,
+            event_calendar_url,
+            conflict_policy: input
+                .conflict_policy
+                .unwrap_or_default()
+                .as_str()
+                .to_string(),
+            last_sync_at: None,
+            last_error: None,
+            created_at: now.clone(),
+            updated_at: now,
+        };
+        self.db
+            .with_connection(|conn| CalDavRepository::replace_account(conn, &row))?;
+        info!(target: "app::caldav", server = %row.server_url, "caldav account connected");
+        self.status()
+    }
+
+    /// One sync pass over both calendars. Items the server rejects are
+    /// listed in the report; failing to reach the server fails the pass.
+    pub async fn sync_now(&self) -> AppResult<CalDavSyncReport> {
+        if self
+            .syncing
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Err(AppError::conflict("CalDAV 同步正在进行中"));
+        }
+        let _guard = SyncGuard(&self.syncing);
+
+        let account = self
+            .db
+            .with_connection(CalDavRepository::get_account)?
+            .ok_or_else(|| AppError::validation("尚未连接 CalDAV 服务器"))?;
+        let result = self.run_sync(&account).await;
+
+        let finished_at = Utc::now().to_rfc3339();
+        let error = result.as_ref().err().map(|err| err.to_string());
+        self.db.with_connection(|conn| {
+            CalDavRepository::record_sync_result(conn, &finished_at, error.as_deref())
+        })?;
+        match &result {
+            Ok(report) => info!(
+                target: "app::caldav",
+                conflicts = report.conflicts.len(),
+                errors = report.errors.len(),
+                "caldav sync finished"
+            ),
+            Err(err) => warn!(target: "app::caldav", error = %err, "caldav sync failed"),
+        }
+        result
+    }
+
+    async fn run_sync(&self, account: &CalDavAccountRow) -> AppResult<CalDavSyncReport> {
+        let password = String::from_utf8(self.vault.decrypt(&account.password)?)
+            .map_err(|_| AppError::crypto("CalDAV 密码内容包含非法字符"))?;
+        let client = CalDavClient::new(&account.server_url, &account.username, &password)?;
+        let policy = parse_policy(&account.conflict_policy);
+
+        let mut report = CalDavSyncReport {
+            started_at: Utc::now().to_rfc3339(),
+            ..CalDavSyncReport::default()
+        };
+        if let Some(url) = account.task_calendar_url.as_deref() {
+            let counts = self
+                .sync_kind(&client, CalDavItemKind::Task, url, policy, &mut report)
+                .await?;
+            report.tasks = counts;
+        }
+        if let Some(url) = account.event_calendar_url.as_deref() {
+            let counts = self
+                .sync_kind(&client, CalDavItemKind::TimeBlock, url, policy, &mut report)
+                .await?;
+            report.time_blocks = counts;
+        }
+        report.finished_at = Utc::now().to_rfc3339();
+        Ok(report)
+    }
+
+    async fn sync_kind(
+        &self,
+        client: &CalDavClient,
+        kind: CalDavItemKind,
+        calendar_url: &str,
+        policy: CalDavConflictPolicy,
+        report: &mut CalDavSyncReport,
+    ) -> AppResult<CalDavSyncCounts> {
+        let calendar = client.resolve(calendar_url)?;
+        let remotes = client.list_objects(&calendar, kind.component()).await?;
+        let locals = self.load_locals(kind)?;
+        let mappings = self
+            .db
+            .with_connection(|conn| CalDavRepository::list_items(conn, kind.as_str()))?;
+        let plan = plan_sync(kind, &locals, &remotes, &mappings, policy);
+        report.conflicts.extend(plan.conflicts);
+
+        let locals: HashMap<&str, &LocalItem> =
+            locals.iter().map(|item| (item.id.as_str(), item)).collect();
+        let remotes: HashMap<&str, &RemoteItem> = remotes
+            .iter()
+            .map(|item| (item.href.as_str(), item))
+            .collect();
+        let mut counts = CalDavSyncCounts::default();
+
+        for action in plan.actions {
+            let outcome = match &action {
+                SyncAction::Push {
+                    local_id,
+                    href,
+                    etag,
+                } => match locals.get(local_id.as_str()) {
+                    Some(local) => self
+                        .push(
+                            client,
+                            kind,
+                            &calendar,
+                            local,
+                            href.as_deref(),
+                            etag.as_deref(),
+                        )
+                        .await
+                        .map(|_| counts.pushed += 1),
+                    None => Ok(()),
+                },
+                SyncAction::Pull { local_id, href } => match remotes.get(href.as_str()) {
+                    Some(remote) => self
+                        .pull(kind, local_id.as_deref(), remote)
+                        .map(|pulled| counts.pulled += u32::from(pulled)),
+                    None => Ok(()),
+                },
+                SyncAction::DeleteLocal { local_id } => self
+                    .delete_local(kind, local_id)
+                    .map(|_| counts.deleted_local += 1),
+                SyncAction::DeleteRemote {
+                    local_id,
+                    href,
+                    etag,
+                } => {
+                    let deleted = async {
+                        client
+                            .delete(&client.resolve(href)?, etag.as_deref())
+                            .await?;
+                        self.forget(kind, local_id)
+                    };
+                    deleted.await.map(|_| counts.deleted_remote += 1)
+                }
+                SyncAction::Forget { local_id } => self.forget(kind, local_id),
+            };
+            if let Err(err) = outcome {
+                warn!(target: "app::caldav", kind = %kind, ?action, error = %err, "caldav item failed to sync");
+                report.errors.push(format!("{kind}: {err}"));
+            }
+        }
+
+        Ok(counts)
+    }
+
+    fn load_locals(&self, kind: CalDavItemKind) -> AppResult<Vec<LocalItem>> {
+        let tasks = self.task_service.list_tasks_readonly()?;
+        match kind {
+            CalDavItemKind::Task => Ok(tasks.iter().map(local_task).collect()),
+            CalDavItemKind::TimeBlock => {
+                let titles: HashMap<&str, &str> = tasks
+                    .iter()
+                    .map(|task| (task.id.as_str(), task.title.as_str()))
+                    .collect();
+                let blocks = self.db.with_connection(|conn| {
+                    PlanningRepository::list_applied_time_blocks(conn)?
+                        .into_iter()
+                        .map(|row| row.into_record())
+                        .collect::<AppResult<Vec<_>>>()
+                })?;
+                Ok(blocks
+                    .iter()
+                    .filter_map(|block| {
+                        local_block(block, titles.get(block.task_id.as_str()).copied())
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    async fn push(
+        &self,
+        client: &CalDavClient,
+        kind: CalDavItemKind,
+        calendar: &Url,
+        local: &LocalItem,
+        href: Option<&str>,
+        etag: Option<&str>,
+    ) -> AppResult<()> {
+        let url = match href {
+            Some(href) => client.resolve(href)?,
+            None => calendar
+                .join(&format!("{}.ics", uid_for(kind, &local.id)))
+                .map_err(|err| AppError::validation(format!("日历地址无效: {err}")))?,
+        };
+        let mut component = local.component.clone();
+        component.push_datetime("DTSTAMP", Utc::now());
+        if let Some(modified_at) = local.modified_at {
+            component.push_datetime("LAST-MODIFIED", modified_at);
+        }
+        let body = ical::write_calendar(&[component]);
+        let new_etag = client.put(&url, body, etag, href.is_none()).await?;
+
+        let row = CalDavItemRow {
+            kind: kind.as_str().to_string(),
+            local_id: local.id.clone(),
+            href: url.path().to_string(),
+            etag: new_etag,
+            local_hash: local.hash.clone(),
+            synced_at: Utc::now().to_rfc3339(),
+        };
+        self.db
+            .with_connection(|conn| CalDavRepository::upsert_item(conn, &row))
+    }
+
+    /// Applies `remote` to the local item, creating a task when `local_id`
+    /// is `None`. Returns whether anything was written.
+    fn pull(
+        &self,
+        kind: CalDavItemKind,
+        local_id: Option<&str>,
+        remote: &RemoteItem,
+    ) -> AppResult<bool> {
+        let local = match kind {
+            CalDavItemKind::Task => {
+                let current = local_id
+                    .map(|id| self.task_service.get_task(id))
+                    .transpose()?;
+                let fields = TaskFields::from_vtodo(&remote.component, current.as_ref());
+                let task = match current {
+                    Some(task) => self
+                        .task_service
+                        .update_task(&task.id, fields.into_update())?,
+                    None => self.task_service.create_task(fields.into_create())?,
+                };
+                local_task(&task)
+            }
+            CalDavItemKind::TimeBlock => {
+                let Some(local_id) = local_id else {
+                    return Ok(false);
+                };
+                let (Some(start), Some(end)) = (
+                    remote.component.datetime("DTSTART"),
+                    remote.component.datetime("DTEND"),
+                ) else {
+                    return Err(AppError::validation("日程缺少开始或结束时间"));
+                };
+                if end <= start {
+                    return Err(AppError::validation("日程结束时间需晚于开始时间"));
+                }
+                let block = self.db.with_connection(|conn| {
+                    let mut block = PlanningRepository::find_time_block(conn, local_id)?
+                        .ok_or_else(AppError::not_found)?
+                        .into_record()?;
+                    block.start_at = start.to_rfc3339();
+                    block.end_at = end.to_rfc3339();
+                    PlanningRepository::update_time_block(
+                        conn,
+                        &PlanningTimeBlockRow::from_record(&block)?,
+                    )?;
+                    Ok(block)
+                })?;
+                let title = self
+                    .task_service
+                    .get_task(&block.task_id)
+                    .ok()
+                    .map(|task| task.title);
+                local_block(&block, title.as_deref()).ok_or_else(AppError::not_found)?
+            }
+        };
+
+        let row = CalDavItemRow {
+            kind: kind.as_str().to_string(),
+            local_id: local.id,
+            href: remote.href.clone(),
+            etag: remote.etag.clone(),
+            local_hash: local.hash,
+            synced_at: Utc::now().to_rfc3339(),
+        };
+        self.db
+            .with_connection(|conn| CalDavRepository::upsert_item(conn, &row))?;
+        Ok(true)
+    }
+
+    fn delete_local(&self, kind: CalDavItemKind, local_id: &str) -> AppResult<()> {
+        match kind {
+            CalDavItemKind::Task => match self.task_service.delete_task(local_id) {
+                Ok(()) | Err(AppError::NotFound) => {}
+                Err(err) => return Err(err),
+            },
+            CalDavItemKind::TimeBlock => self
+                .db
+                .with_connection(|conn| PlanningRepository::delete_time_block(conn, local_id))?,
+        }
+        self.forget(kind, local_id)
+    }
+
+    fn forget(&self, kind: CalDavItemKind, local_id: &str) -> AppResult<()> {
+        self.db
+            .with_connection(|conn| CalDavRepository::delete_item(conn, kind.as_str(), local_id))
+    }
+}
+
+struct SyncGuard<'a>(&'a AtomicBool);
+
+impl Drop for SyncGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+fn parse_policy(value: &str) -> CalDavConflictPolicy {
+    CalDavConflictPolicy::try_from(value).unwrap_or_default()
+}
+
+fn uid_for(kind: CalDavItemKind, local_id: &str) -> String {
+    format!("{UID_PREFIX}{}-{local_id}", kind.as_str())
+}
+
+/// Local id encoded in a UID created by the app for `kind`.
+fn local_id_from_uid(kind: CalDavItemKind, uid: &str) -> Option<&str> {
+    uid.strip_prefix(UID_PREFIX)?
+        .strip_prefix(kind.as_str())?
+        .strip_prefix('-')
+        .filter(|id| !id.is_empty())
+}
+
+fn parse_rfc3339(value: Option<&str>) -> Option<DateTime<Utc>> {
+    value
+        .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+        .map(|at| at.with_timezone(&Utc))
+}
+
+/// A local task or block in the form it is uploaded.
+#[derive(Debug, Clone)]
+struct LocalItem {
+    id: String,
+    component: Component,
+    /// Hash of `component`; differs from the mapping once the item changed.
+    hash: String,
+    modified_at: Option<DateTime<Utc>>,
+}
+
+impl LocalItem {
+    fn new(id: &str, component: Component, modified_at: Option<DateTime<Utc>>) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(component.content_lines().join("\n").as_bytes());
+        Self {
+            id: id.to_string(),
+            hash: format!("{:x}", hasher.finalize()),
+            component,
+            modified_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RemoteItem {
+    /// Path of the object on the server.
+    href: String,
+    etag: Option<String>,
+    uid: Option<String>,
+    modified_at: Option<DateTime<Utc>>,
+    component: Component,
+}
+
+fn local_task(task: &TaskRecord) -> LocalItem {
+    let mut todo = Component::new("VTODO");
+    todo.push_value("UID", uid_for(CalDavItemKind::Task, &task.id))
+        .push_text("SUMMARY", &task.title);
+    if let Some(description) = task.description.as_deref() {
+        todo.push_text("DESCRIPTION", description);
+    }
+    todo.push_value("STATUS", ical_status(&task.status))
+        .push_value("PRIORITY", ical_priority(&task.priority).to_string());
+    for (name, value) in [
+        ("DTSTART", task.start_at.as_deref()),
+        ("DUE", task.due_at.as_deref()),
+        ("COMPLETED", task.completed_at.as_deref()),
+    ] {
+        if let Some(at) = parse_rfc3339(value) {
+            todo.push_datetime(name, at);
+        }
+    }
+    if !task.tags.is_empty() {
+        let tags: Vec<String> = task.tags.iter().map(|tag| ical::escape_text(tag)).collect();
+        todo.push_value("CATEGORIES", tags.join(","));
+    }
+    LocalItem::new(&task.id, todo, parse_rfc3339(Some(&task.updated_at)))
+}
+
+/// `None` for blocks whose times cannot be parsed.
+fn local_block(block: &PlanningTimeBlockRecord, title: Option<&str>) -> Option<LocalItem> {
+    let event = block_event(block, title)?;
+    Some(LocalItem::new(&block.id, event, None))
+}
+
+/// The VEVENT an applied block is stored (and exported) as; `None` when
+/// its times cannot be parsed.
+pub(crate) fn block_event(
+    block: &PlanningTimeBlockRecord,
+    title: Option<&str>,
+) -> Option<Component> {
+    let start = parse_rfc3339(Some(&block.start_at))?;
+    let end = parse_rfc3339(Some(&block.end_at))?;
+    let mut event = Component::new("VEVENT");
+    event
+        .push_value("UID", uid_for(CalDavItemKind::TimeBlock, &block.id))
+        .push_text("SUMMARY", title.unwrap_or(UNTITLED_TASK))
+        .push_datetime("DTSTART", start)
+        .push_datetime("DTEND", end)
+        .push_value("TRANSP", "OPAQUE");
+    if let Some(note) = block.prep_note.as_deref() {
+        event.push_text("DESCRIPTION", note);
+    }
+    Some(event)
+}
+
+/// Whether `uid` belongs to a time block created by the app.
+pub(crate) fn is_block_uid(uid: &str) -> bool {
+    local_id_from_uid(CalDavItemKind::TimeBlock, uid).is_some()
+}
+
+fn ical_status(status: &str) -> &'static str {
+    match status {
+        "in_progress" => "IN-PROCESS",
+        "done" => "COMPLETED",
+        "archived" => "CANCELLED",
+        _ => "NEEDS-ACTION",
+    }
+}
+
+fn ical_priority(priority: &str) -> u8 {
+    match priority {
+        "urgent" => 1,
+        "high" => 3,
+        "low" => 9,
+        _ => 5,
+    }
+}
+
+/// Task fields read from a VTODO. Values the VTODO cannot express, such as
+/// `blocked` versus `todo`, keep the current task's value.
+#[derive(Debug, Clone, PartialEq)]
+struct TaskFields {
+    title: String,
+    description: Option<String>,
+    status: String,
+    priority: String,
+    start_at: Option<String>,
+    due_at: Option<String>,
+    completed_at: Option<String>,
+    tags: Vec<String>,
+}
+
+impl TaskFields {
+    fn from_vtodo(todo: &Component, current: Option<&TaskRecord>) -> Self {
+        let current_status = current.map(|task| task.status.as_str());
+        let remote_status = todo
+            .text("STATUS")
+            .unwrap_or_else(|| "NEEDS-ACTION".to_string())
+            .to_ascii_uppercase();
+        let status = match current_status {
+            Some(status) if ical_status(status) == remote_status => status.to_string(),
+            _ => match remote_status.as_str() {
+                "IN-PROCESS" => "in_progress",
+                "COMPLETED" => "done",
+                "CANCELLED" => "archived",
+                _ => "todo",
+            }
+            .to_string(),
+        };
+        let priority = match todo
+            .text("PRIORITY")
+            .and_then(|value| value.trim().parse::<u8>().ok())
+        {
+            Some(1..=2) => "urgent",
+            Some(3..=4) => "high",
+            Some(6..=9) => "low",
+            Some(5) => "medium",
+            _ => current.map_or("medium", |task| task.priority.as_str()),
+        }
+        .to_string();
+        let at = |name: &str| todo.datetime(name).map(|at| at.to_rfc3339());
+
+        Self {
+            title: todo
+                .text("SUMMARY")
+                .map(|title| title.trim().chars().take(160).collect())
+                .unwrap_or_else(|| UNTITLED_TASK.to_string()),
+            description: todo.text("DESCRIPTION"),
+            status,
+            priority,
+            start_at: at("DTSTART"),
+            due_at: at("DUE"),
+            completed_at: at("COMPLETED"),
+            tags: todo.text_list("CATEGORIES"),
+        }
+    }
+
+    fn into_update(self) -> TaskUpdateInput {
+        TaskUpdateInput {
+            title: Some(self.title),
+            description: Some(self.description),
+            status: Some(self.status),
+            priority: Some(self.priority),
+            start_at: Some(self.start_at),
+            due_at: Some(self.due_at),
+            completed_at: Some(self.completed_at),
+            tags: Some(Some(self.tags)),
+            ..TaskUpdateInput::default()
+        }
+    }
+
+    fn into_create(self) -> TaskCreateInput {
+        TaskCreateInput {
+            title: self.title,
+            description: self.description,
+            status: Some(self.status),
+            priority: Some(self.priority),
+            start_at: self.start_at,
+            due_at: self.due_at,
+            completed_at: self.completed_at,
+            tags: Some(self.tags),
+            ..TaskCreateInput::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SyncAction {
+    /// Upload the local item; `href` is `None` for a new object and `etag`
+    /// guards against overwriting a newer remote version.
+    Push {
+        local_id: String,
+        href: Option<String>,
+        etag: Option<String>,
+    },
+    /// Apply the remote object; `local_id` is `None` to create a task.
+    Pull {
+        local_id: Option<String>,
+        href: String,
+    },
+    DeleteLocal {
+        local_id: String,
+    },
+    DeleteRemote {
+        local_id: String,
+        href: String,
+        etag: Option<String>,
+    },
+    /// Drop the mapping and leave both sides alone.
+    Forget {
+        local_id: String,
+    },
+}
+
+#[derive(Debug, Default)]
+struct SyncPlan {
+    actions: Vec<SyncAction>,
+    conflicts: Vec<CalDavConflict>,
+}
+
+fn resolve_conflict(
+    policy: CalDavConflictPolicy,
+    local_at: Option<DateTime<Utc>>,
+    remote_at: Option<DateTime<Utc>>,
+) -> CalDavConflictWinner {
+    match policy {
+        CalDavConflictPolicy::PreferLocal => CalDavConflictWinner::Local,
+        CalDavConflictPolicy::PreferRemote => CalDavConflictWinner::Remote,
+        CalDavConflictPolicy::NewestWins => match (local_at, remote_at) {
+            (Some(local), Some(remote)) if local > remote => CalDavConflictWinner::Local,
+            (Some(_), None) => CalDavConflictWinner::Local,
+            _ => CalDavConflictWinner::Remote,
+        },
+    }
+}
+
+/// Decides what to do with every local item, remote object and existing
+/// mapping of `kind`. Remote objects of other apps are only imported for
+/// tasks; foreign events stay out of the planner.
+fn plan_sync(
+    kind: CalDavItemKind,
+    locals: &[LocalItem],
+    remotes: &[RemoteItem],
+    mappings: &[CalDavItemRow],
+    policy: CalDavConflictPolicy,
+) -> SyncPlan {
+    let locals_by_id: HashMap<&str, &LocalItem> =
+        locals.iter().map(|item| (item.id.as_str(), item)).collect();
+    let remotes_by_href: HashMap<&str, &RemoteItem> = remotes
+        .iter()
+        .map(|item| (item.href.as_str(), item))
+        .collect();
+    let mut plan = SyncPlan::default();
+    let mut mapped_locals: HashSet<&str> = HashSet::new();
+    let mut mapped_hrefs: HashSet<&str> = HashSet::new();
+    let conflict = |plan: &mut SyncPlan, mapping: &CalDavItemRow, winner| {
+        plan.conflicts.push(CalDavConflict {
+            kind,
+            local_id: mapping.local_id.clone(),
+            href: mapping.href.clone(),
+            winner,
+        });
+    };
+
+    for mapping in mappings {
+        mapped_locals.insert(mapping.local_id.as_str());
+        mapped_hrefs.insert(mapping.href.as_str());
+        let local = locals_by_id.get(mapping.local_id.as_str());
+        let remote = remotes_by_href.get(mapping.href.as_str());
+        let local_changed = local.is_some_and(|local| local.hash != mapping.local_hash);
+        let remote_changed =
+            remote.is_some_and(|remote| remote.etag.is_none() || remote.etag != mapping.etag);
+        let local_id = mapping.local_id.clone();
+
+        let action = match (local, remote) {
+            (None, None) => SyncAction::Forget { local_id },
+            (None, Some(remote)) => {
+                let winner = if remote_changed {
+                    let winner = resolve_conflict(policy, None, remote.modified_at);
+                    conflict(&mut plan, mapping, winner);
+                    winner
+                } else {
+                    CalDavConflictWinner::Local
+                };
+                match winner {
+                    CalDavConflictWinner::Local => SyncAction::DeleteRemote {
+                        local_id,
+                        href: mapping.href.clone(),
+                        etag: remote.etag.clone(),
+                    },
+                    // Without the mapping the object is imported again as
+                    // a new task on the next pass
+                    CalDavConflictWinner::Remote => SyncAction::Forget { local_id },
+                }
+            }
+            (Some(local), None) => {
+                let winner = if local_changed {
+                    let winner = resolve_conflict(policy, local.modified_at, None);
+                    conflict(&mut plan, mapping, winner);
+                    winner
+                } else {
+                    CalDavConflictWinner::Remote
+                };
+                match winner {
+                    CalDavConflictWinner::Local => SyncAction::Push {
+                        local_id,
+                        href: None,
+                        etag: None,
+                    },
+                    CalDavConflictWinner::Remote => SyncAction::DeleteLocal { local_id },
+                }
+            }
+            (Some(local), Some(remote)) => {
+                let winner = match (local_changed, remote_changed) {
+                    (false, false) => continue,
+                    (true, false) => CalDavConflictWinner::Local,
+                    (false, true) => CalDavConflictWinner::Remote,
+                    (true, true) => {
+                        let winner =
+                            resolve_conflict(policy, local.modified_at, remote.modified_at);
+                        conflict(&mut plan, mapping, winner);
+                        winner
+                    }
+                };
+                match winner {
+                    CalDavConflictWinner::Local => SyncAction::Push {
+                        local_id,
+                        href: Some(mapping.href.clone()),
+                        etag: remote.etag.clone(),
+                    },
+                    CalDavConflictWinner::Remote => SyncAction::Pull {
+                        local_id: Some(local_id),
+                        href: mapping.href.clone(),
+                    },
+                }
+            }
+        };
+        plan.actions.push(action);
+    }
+
+    // Objects the app uploaded before its mappings were lost are linked
+    // back to their local item instead of being duplicated
+    let orphans: HashMap<&str, &RemoteItem> = remotes
+        .iter()
+        .filter(|remote| !mapped_hrefs.contains(remote.href.as_str()))
+        .filter_map(|remote| {
+            let local_id = local_id_from_uid(kind, remote.uid.as_deref()?)?;
+            Some((local_id, remote))
+        })
+        .collect();
+    for local in locals {
+        if mapped_locals.contains(local.id.as_str()) {
+            continue;
+        }
+        let existing = orphans.get(local.id.as_str());
+        if let Some(remote) = existing {
+            mapped_hrefs.insert(remote.href.as_str());
+        }
+        plan.actions.push(SyncAction::Push {
+            local_id: local.id.clone(),
+            href: existing.map(|remote| remote.href.clone()),
+            etag: existing.and_then(|remote| remote.etag.clone()),
+        });
+    }
+
+    if kind == CalDavItemKind::Task {
+        for remote in remotes {
+            if !mapped_hrefs.contains(remote.href.as_str()) {
+                plan.actions.push(SyncAction::Pull {
+                    local_id: None,
+                    href: remote.href.clone(),
+                });
+            }
+        }
+    }
+
+    plan
+}
+
+#[derive(Debug, Default)]
+struct DiscoveredCalendars {
+    tasks: Option<String>,
+    events: Option<String>,
+}
+
+/// Minimal WebDAV/CalDAV client with basic auth.
+struct CalDavClient {
+    http: reqwest::Client,
+    base: Url,
+    username: String,
+    password: String,
+}
+
+impl CalDavClient {
+    fn new(server_url: &str, username: &str, password: &str) -> AppResult<Self> {
+        let mut base =
+            Url::parse(server_url).map_err(|_| AppError::validation("CalDAV 服务器地址无效"))?;
+        if !matches!(base.scheme(), "https" | "http") {
+            return Err(AppError::validation(
+                "CalDAV 服务器地址必须以 http(s):// 开头",
+            ));
+        }
+        if !base.path().ends_with('/') {
+            let path = format!("{}/", base.path());
+            base.set_path(&path);
+        }
+        let http = reqwest::Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .build()
+            .map_err(|err| AppError::other(format!("初始化 CalDAV HTTP 客户端失败: {err}")))?;
+        Ok(Self {
+            http,
+            base,
+            username: username.to_string(),
+            password: password.to_string(),
+        })
+    }
+
+    fn resolve(&self, href: &str) -> AppResult<Url> {
+        self.base
+            .join(href)
+            .map_err(|err| AppError::validation(format!("CalDAV 地址无效: {err}")))
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        url: &Url,
+        headers: HeaderMap,
+        body: Option<String>,
+    ) -> AppResult<(StatusCode, HeaderMap, String)> {
+        let mut request = self
+            .http
+            .request(method.clone(), url.clone())
+            .basic_auth(&self.username, Some(&self.password))
+            .headers(headers);
+        if let Some(body) = body {
+            request = request.body(body);
+        }
+        let response = request.send().await.map_err(|err| {
+            AppError::external_integration(SERVICE, format!("无法连接 CalDAV 服务器: {err}"))
+        })?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let text = response.text().await.unwrap_or_default();
+        match status {
+            // Retrying cannot fix credentials, so this is not reported as
+            // a transient integration failure
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                Err(AppError::validation("CalDAV 用户名或密码错误"))
+            }
+            StatusCode::PRECONDITION_FAILED => Err(AppError::conflict(format!(
+                "{} 已在服务器上被修改",
+                url.path()
+            ))),
+            status if status.is_success() || status == StatusCode::NOT_FOUND => {
+                Ok((status, headers, text))
+            }
+            status => Err(AppError::external_integration(
+                SERVICE,
+                format!("{method} {} 返回 {status}", url.path()),
+            )),
+        }
+    }
+
+    async fn dav(&self, method: &[u8], url: &Url, depth: &str, body: &str) -> AppResult<String> {
+        let method = Method::from_bytes(method).expect("valid WebDAV method");
+        let mut headers = HeaderMap::new();
+        headers.insert("Depth", depth.parse().expect("valid depth header"));
+        headers.insert(
+            CONTENT_TYPE,
+            "application/xml; charset=utf-8".parse().unwrap(),
+        );
+        let (status, _, text) = self
+            .send(method, url, headers, Some(body.to_string()))
+            .await?;
+        if status == StatusCode::NOT_FOUND {
+            return Err(AppError::external_integration(
+                SERVICE,
+                format!("{} 不存在", url.path()),
+            ));
+        }
+        Ok(text)
+    }
+
+    /// Follows the principal to the calendar home and picks the first
+    /// calendar accepting VTODO and the first accepting VEVENT.
+    async fn discover(&self) -> AppResult<DiscoveredCalendars> {
+        let root = self
+            .dav(b"PROPFIND", &self.base, "0", CURRENT_USER_PRINCIPAL_BODY)
+            .await?;
+        let principal = nested_href(&PRINCIPAL_PATTERN, &root)
+            .map(|href| self.resolve(&href))
+            .transpose()?
+            .unwrap_or_else(|| self.base.clone());
+        let principal_props = self
+            .dav(b"PROPFIND", &principal, "0", CALENDAR_HOME_BODY)
+            .await?;
+        let home = nested_href(&HOME_SET_PATTERN, &principal_props)
+            .ok_or_else(|| AppError::external_integration(SERVICE, "服务器未返回日历主目录"))
+            .and_then(|href| self.resolve(&href))?;
+        let listing = self.dav(b"PROPFIND", &home, "1", CALENDARS_BODY).await?;
+
+        let mut discovered = DiscoveredCalendars::default();
+        for (href, components) in parse_calendars(&listing) {
+            let url = self.resolve(&href)?.to_string();
+            let accepts =
+                |name: &str| components.is_empty() || components.iter().any(|c| c == name);
+            if discovered.tasks.is_none() && accepts("VTODO") {
+                discovered.tasks = Some(url.clone());
+            }
+            if discovered.events.is_none() && accepts("VEVENT") {
+                discovered.events = Some(url);
+            }
+        }
+        Ok(discovered)
+    }
+
+    async fn list_objects(&self, calendar: &Url, component: &str) -> AppResult<Vec<RemoteItem>> {
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav"><d:prop><d:getetag/><c:calendar-data/></d:prop><c:filter><c:comp-filter name="VCALENDAR"><c:comp-filter name="{component}"/></c:comp-filter></c:filter></c:calendar-query>"#
+        );
+        let listing = self.dav(b"REPORT", calendar, "1", &body).await?;
+        let mut items = Vec::new();
+        for (href, etag, data) in parse_objects(&listing) {
+            let roots = match ical::parse(&data) {
+                Ok(roots) => roots,
+                Err(err) => {
+                    warn!(target: "app::caldav", %href, error = %err, "skipping unreadable calendar object");
+                    continue;
+                }
+            };
+            let Some(component) = ical::find_components(&roots, component).first().cloned() else {
+                continue;
+            };
+            items.push(RemoteItem {
+                href: self.resolve(&href)?.path().to_string(),
+                etag,
+                uid: component.text("UID"),
+                modified_at: component
+                    .datetime("LAST-MODIFIED")
+                    .or_else(|| component.datetime("DTSTAMP")),
+                component: component.clone(),
+            });
+        }
+        Ok(items)
+    }
+
+    /// Uploads `body` and returns the new ETag when the server sends one.
+    async fn put(
+        &self,
+        url: &Url,
+        body: String,
+        etag: Option<&str>,
+        create: bool,
+    ) -> AppResult<Option<String>> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            CONTENT_TYPE,
+            "text/calendar; charset=utf-8".parse().unwrap(),
+        );
+        if create {
+            headers.insert(IF_NONE_MATCH, "*".parse().unwrap());
+        } else if let Some(etag) = etag.and_then(|etag| etag.parse().ok()) {
+            headers.insert(IF_MATCH, etag);
+        }
+        let (status, headers, _) = self.send(Method::PUT, url, headers, Some(body)).await?;
+        if status == StatusCode::NOT_FOUND {
+            return Err(AppError::external_integration(
+                SERVICE,
+                format!("{} 所在的日历不存在", url.path()),
+            ));
+        }
+        Ok(headers
+            .get(ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string))
+    }
+
+    /// Deletes the object; one that is already gone counts as deleted.
+    async fn delete(&self, url: &Url, etag: Option<&str>) -> AppResult<()> {
+        let mut headers = HeaderMap::new();
+        if let Some(etag) = etag.and_then(|etag| etag.parse().ok()) {
+            headers.insert(IF_MATCH, etag);
+        }
+        self.send(Method::DELETE, url, headers, None).await?;
+        Ok(())
+    }
+}
+
+fn xml_unescape(value: &str) -> String {
+    let value = value.trim();
+    if let Some(inner) = value
+        .strip_prefix("<![CDATA[")
+        .and_then(|inner| inner.strip_suffix("]]>"))
+    {
+        return inner.to_string();
+    }
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#13;", "\r")
+        .replace("&#xD;", "\r")
+        .replace("&#10;", "\n")
+        .replace("&amp;", "&")
+}
+
+fn first_element(pattern: &Regex, xml: &str) -> Option<String> {
+    pattern
+        .captures(xml)
+        .map(|captures| xml_unescape(&captures[1]))
+}
+
+/// The `href` inside the first element matched by `pattern`.
+fn nested_href(pattern: &Regex, xml: &str) -> Option<String> {
+    let inner = pattern.captures(xml)?;
+    first_element(&HREF_PATTERN, &inner[1]).filter(|href| !href.is_empty())
+}
+
+/// Calendar collections in a Depth 1 listing with the components they
+/// accept; an empty list means any component.
+fn parse_calendars(xml: &str) -> Vec<(String, Vec<String>)> {
+    RESPONSE_PATTERN
+        .captures_iter(xml)
+        .filter_map(|response| {
+            let response = &response[1];
+            let resource_type = RESOURCETYPE_PATTERN.captures(response)?;
+            if !CALENDAR_TYPE_PATTERN.is_match(&resource_type[1]) {
+                return None;
+            }
+            let href = first_element(&HREF_PATTERN, response)?;
+            let components = COMPONENT_SET_PATTERN
+                .captures(response)
+                .map(|set| {
+                    COMP_NAME_PATTERN
+                        .captures_iter(&set[1])
+                        .map(|comp| comp[1].to_ascii_uppercase())
+                        .collect()
+                })
+                .unwrap_or_default();
+            Some((href, components))
+        })
+        .collect()
+}
+
+/// `(href, etag, calendar data)` of every object in a calendar-query
+/// response.
+fn parse_objects(xml: &str) -> Vec<(String, Option<String>, String)> {
+    RESPONSE_PATTERN
+        .captures_iter(xml)
+        .filter_map(|response| {
+            let response = &response[1];
+            let href = first_element(&HREF_PATTERN, response)?;
+            let data = first_element(&CALENDAR_DATA_PATTERN, response)?;
+            let etag = first_element(&ETAG_PATTERN, response).filter(|etag| !etag.is_empty());
+            Some((href, etag, data))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(id: &str, hash: &str, modified_at: Option<&str>) -> LocalItem {
+        LocalItem {
+            id: id.to_string(),
+            component: Component::default(),
+            hash: hash.to_string(),
+            modified_at: parse_rfc3339(modified_at),
+        }
+    }
+
+    fn remote(href: &str, etag: &str, uid: Option<&str>, modified_at: Option<&str>) -> RemoteItem {
+        RemoteItem {
+            href: href.to_string(),
+            etag: Some(etag.to_string()),
+            uid: uid.map(str::to_string),
+            modified_at: parse_rfc3339(modified_at),
+            component: Component::default(),
+        }
+    }
+
+    fn mapping(local_id: &str, href: &str, etag: &str, hash: &str) -> CalDavItemRow {
+        CalDavItemRow {
+            kind: "task".to_string(),
+            local_id: local_id.to_string(),
+            href: href.to_string(),
+            etag: Some(etag.to_string()),
+            local_hash: hash.to_string(),
+            synced_at: "2026-03-01T00:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn plan_pushes_pulls_and_settles_conflicts_by_policy() {
+        let locals = vec![
+            local("same", "h1", None),
+            local("edited", "h2-new", None),
+            local("both", "h3-new", Some("2026-03-05T10:00:00Z")),
+            local("remote-gone", "h4", None),
+            local("new", "h5", None),
+        ];
+        let remotes = vec![
+            remote("/cal/same.ics", "e1", None, None),
+            remote("/cal/edited.ics", "e2", None, None),
+            remote(
+                "/cal/both.ics",
+                "e3-new",
+                None,
+                Some("2026-03-04T10:00:00Z"),
+            ),
+            remote("/cal/local-gone.ics", "e5", None, None),
+            remote("/cal/phone.ics", "e6", Some("from-phone"), None),
+        ];
+        let mappings = vec![
+            mapping("same", "/cal/same.ics", "e1", "h1"),
+            mapping("edited", "/cal/edited.ics", "e2", "h2"),
+            mapping("both", "/cal/both.ics", "e3", "h3"),
+            mapping("remote-gone", "/cal/remote-gone.ics", "e4", "h4"),
+            mapping("local-gone", "/cal/local-gone.ics", "e5", "h0"),
+        ];
+
+        let plan = plan_sync(
+            CalDavItemKind::Task,
+            &locals,
+            &remotes,
+            &mappings,
+            CalDavConflictPolicy::NewestWins,
+        );
+        assert_eq!(
+            plan.actions,
+            vec![
+                SyncAction::Push {
+                    local_id: "edited".to_string(),
+                    href: Some("/cal/edited.ics".to_string()),
+                    etag: Some("e2".to_string()),
+                },
+                SyncAction::Push {
+                    local_id: "both".to_string(),
+                    href: Some("/cal/both.ics".to_string()),
+                    etag: Some("e3-new".to_string()),
+                },
+                SyncAction::DeleteLocal {
+                    local_id: "remote-gone".to_string(),
+                },
+                SyncAction::DeleteRemote {
+                    local_id: "local-gone".to_string(),
+                    href: "/cal/local-gone.ics".to_string(),
+                    etag: Some("e5".to_string()),
+                },
+                SyncAction::Push {
+                    local_id: "new".to_string(),
+                    href: None,
+                    etag: None,
+                },
+                SyncAction::Pull {
+                    local_id: None,
+                    href: "/cal/phone.ics".to_string(),
+                },
+            ]
+        );
+        assert_eq!(plan.conflicts.len(), 1);
+        assert_eq!(plan.conflicts[0].winner, CalDavConflictWinner::Local);
+
+        let plan = plan_sync(
+            CalDavItemKind::Task,
+            &locals,
+            &remotes,
+            &mappings,
+            CalDavConflictPolicy::PreferRemote,
+        );
+        assert!(plan.actions.contains(&SyncAction::Pull {
+            local_id: Some("both".to_string()),
+            href: "/cal/both.ics".to_string(),
+        }));
+    }
+
+    #[test]
+    fn plan_links_own_orphans_and_ignores_foreign_events() {
+        let locals = vec![local("b1", "h1", None)];
+        let remotes = vec![
+            remote("/cal/x.ics", "e1", Some("cognical-time_block-b1"), None),
+            remote("/cal/meeting.ics", "e2", Some("meeting@corp"), None),
+        ];
+
+        let plan = plan_sync(
+            CalDavItemKind::TimeBlock,
+            &locals,
+            &remotes,
+            &[],
+            CalDavConflictPolicy::NewestWins,
+        );
+        assert_eq!(
+            plan.actions,
+            vec![SyncAction::Push {
+                local_id: "b1".to_string(),
+                href: Some("/cal/x.ics".to_string()),
+                etag: Some("e1".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn parses_multistatus_listings() {
+        let calendars = r#"<d:multistatus xmlns:d="DAV:" xmlns:cal="urn:ietf:params:xml:ns:caldav">
+            <d:response><d:href>/dav/calendars/me/</d:href><d:propstat><d:prop>
+              <d:resourcetype><d:collection/></d:resourcetype></d:prop></d:propstat></d:response>
+            <d:response><d:href>/dav/calendars/me/work/</d:href><d:propstat><d:prop>
+              <d:resourcetype><d:collection/><cal:calendar/></d:resourcetype>
+              <cal:supported-calendar-component-set><cal:comp name="VEVENT"/></cal:supported-calendar-component-set>
+            </d:prop></d:propstat></d:response>
+            <d:response><d:href>/dav/calendars/me/todo/</d:href><d:propstat><d:prop>
+              <d:resourcetype><d:collection/><cal:calendar/></d:resourcetype>
+              <cal:supported-calendar-component-set><cal:comp name="VTODO"/></cal:supported-calendar-component-set>
+            </d:prop></d:propstat></d:response>
+        </d:multistatus>"#;
+        assert_eq!(
+            parse_calendars(calendars),
+            vec![
+                (
+                    "/dav/calendars/me/work/".to_string(),
+                    vec!["VEVENT".to_string()]
+                ),
+                (
+                    "/dav/calendars/me/todo/".to_string(),
+                    vec!["VTODO".to_string()]
+                ),
+            ]
+        );
+
+        let objects = r#"<multistatus xmlns="DAV:"><response><href>/cal/a.ics</href><propstat><prop>
+            <getetag>&quot;abc&quot;</getetag>
+            <C:calendar-data xmlns:C="urn:ietf:params:xml:ns:caldav">BEGIN:VCALENDAR&#13;
+BEGIN:VTODO&#13;
+UID:a&#13;
+SUMMARY:Tom &amp; Jerry&#13;
+END:VTODO&#13;
+END:VCALENDAR&#13;
+</C:calendar-data></prop></propstat></response></multistatus>"#;
+        let parsed = parse_objects(objects);
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].1.as_deref(), Some("\"abc\""));
+        let roots = ical::parse(&parsed[0].2).unwrap();
+        let todo = ical::find_components(&roots, "VTODO")[0];
+        assert_eq!(todo.text("SUMMARY").as_deref(), Some("Tom & Jerry"));
+    }
+
+    #[test]
+    fn vtodo_fields_keep_local_detail_the_format_cannot_hold() {
+        let task = TaskRecord {
+            id: "t1".to_string(),
+            title: "Draft".to_string(),
+            description: None,
+            status: "blocked".to_string(),
+            priority: "high".to_string(),
+            planned_start_at: None,
+            start_at: None,
+            due_at: Some("2026-03-02T09:30:00+08:00".to_string()),
+            completed_at: None,
+            estimated_minutes: None,
+            estimated_hours: None,
+            tags: vec!["work".to_string()],
+            owner_id: None,
+            task_type: None,
+            is_recurring: false,
+            recurrence: None,
+            ai: None,
+            external_links: Vec::new(),
+            project_id: None,
+            snoozed_until: None,
+            snooze_notify: false,
+            color: None,
+            icon: None,
+            delegated_to: None,
+            waiting_on: None,
+            waiting_since: None,
+            follow_up_task_id: None,
+            created_at: "2026-03-01T00:00:00Z".to_string(),
+            updated_at: "2026-03-01T00:00:00Z".to_string(),
+        };
+        let todo = local_task(&task).component;
+        let fields = TaskFields::from_vtodo(&todo, Some(&task));
+        assert_eq!(fields.status, "blocked");
+        assert_eq!(fields.priority, "high");
+        assert_eq!(fields.tags, vec!["work".to_string()]);
+        assert_eq!(fields.due_at.as_deref(), Some("2026-03-02T01:30:00+00:00"));
+
+        let mut done = todo.clone();
+        done.properties.retain(|property| property.name != "STATUS");
+        done.push_value("STATUS", "COMPLETED");
+        assert_eq!(TaskFields::from_vtodo(&done, Some(&task)).status, "done");
+    }
+
+    #[tokio::test]
+    async fn rejected_credentials_are_not_retryable() {
+        use httpmock::prelude::*;
+
+        let server = MockServer::start_async().await;
+        for (path, status) in [("/denied/", 401), ("/forbidden/", 403)] {
+            server
+                .mock_async(|when, then| {
+                    when.path(path);
+                    then.status(status);
+                })
+                .await;
+            let client = CalDavClient::new(&server.url(path), "me", "wrong").unwrap();
+            let error = client
+                .send(Method::GET, &client.base, HeaderMap::new(), None)
+                .await
+                .unwrap_err();
+            assert!(!error.is_retryable(), "{status}");
+            assert!(error.to_string().contains("CalDAV 用户名或密码错误"));
+        }
+    }
+}
@@ -0,0 +1,305 @@
+//! Guided weekly capacity setup: asks for fixed commitments, a focus target
+//! and the week's priorities, then saves the planning preferences and the
+//! capacity profile and generates a first plan for the coming week.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, TimeZone, Utc};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::capacity::{
+    CapacityProfile, CapacityWizardAnswer, CapacityWizardResult, CapacityWizardState,
+    CapacityWizardStep, WeeklyCommitment,
+};
+use crate::services::behavior_learning::{BehaviorLearningService, PreferenceSnapshot};
+use crate::services::planning_service::{GeneratePlanInput, PlanningService};
+use crate::services::schedule_optimizer::{ExistingEvent, ScheduleConstraints};
+use crate::services::schedule_utils;
+use crate::services::settings_service::{load_default_preference_id, SettingsService};
+
+const PLAN_DAYS: i64 = 7;
+const WORKDAYS_PER_WEEK: i64 = 5;
+const MAX_WEEKLY_FOCUS_HOURS: u32 = 80;
+const MAX_COMMITMENTS: usize = 50;
+/// Unfinished wizards are dropped after this long.
+const WIZARD_TTL_HOURS: i64 = 24;
+
+pub struct CapacityWizardService {
+    db: DbPool,
+    planning: Arc<PlanningService>,
+    settings: Arc<SettingsService>,
+    wizards: Mutex<HashMap<String, CapacityWizardState>>,
+}
+
+impl CapacityWizardService {
+    pub fn new(db: DbPool, planning: Arc<PlanningService>, settings: Arc<SettingsService>) -> Self {
+        Self {
+            db,
+            planning,
+            settings,
+            wizards: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Starts a wizard prefilled with the saved capacity profile.
+    pub fn start(&self) -> AppResult<CapacityWizardState> {
+        let profile = self.settings.get_capacity_profile()?;
+        let now = Utc::now();
+        let state = CapacityWizardState {
+            id: Uuid::new_v4().to_string(),
+            step: CapacityWizardStep::Commitments,
+            prompt: CapacityWizardStep::Commitments.prompt().to_string(),
+            commitments: profile.commitments,
+            weekly_focus_hours: profile.weekly_focus_hours,
+            focus_start_minute: None,
+            focus_end_minute: None,
+            priority_task_ids: Vec::new(),
+            started_at: now.to_rfc3339(),
+        };
+
+        let mut wizards = self.lock()?;
+        wizards.retain(|_, wizard| {
+            DateTime::parse_from_rfc3339(&wizard.started_at).is_ok_and(|started| {
+                now - started.with_timezone(&Utc) < Duration::hours(WIZARD_TTL_HOURS)
+            })
+        });
+        wizards.insert(state.id.clone(), state.clone());
+        Ok(state)
+    }
+
+    /// Records the answer for one step and moves to the step after it. Steps
+    /// already answered can be answered again.
+    pub fn answer(&self, id: &str, answer: CapacityWizardAnswer) -> AppResult<CapacityWizardState> {
+        let mut wizards = self.lock()?;
+        let state = wizards.get_mut(id).ok_or_else(AppError::not_found)?;
+        if answer.step() > state.step {
+            return Err(AppError::validation("请先回答前面的问题"));
+        }
+
+        match answer {
+            CapacityWizardAnswer::Commitments { commitments } => {
+                if commitments.len() > MAX_COMMITMENTS {
+                    return Err(AppError::validation(format!(
+                        "固定安排最多 {MAX_COMMITMENTS} 项"
+                    )));
+                }
+                for commitment in &commitments {
+                    commitment.validate().map_err(AppError::validation)?;
+                }
+                state.commitments = commitments;
+                state.step = CapacityWizardStep::FocusHours;
+            }
+            CapacityWizardAnswer::FocusHours {
+                weekly_focus_hours,
+                focus_start_minute,
+                focus_end_minute,
+            } => {
+                if !(1..=MAX_WEEKLY_FOCUS_HOURS).contains(&weekly_focus_hours) {
+                    return Err(AppError::validation(format!(
+                        "每周专注时长需在 1 到 {MAX_WEEKLY_FOCUS_HOURS} 小时之间"
+                    )));
+                }
+                PreferenceSnapshot {
+                    focus_start_minute,
+                    focus_end_minute,
+                    ..PreferenceSnapshot::default()
+                }
+                .validate()?;
+                state.weekly_focus_hours = Some(weekly_focus_hours);
+                state.focus_start_minute = focus_start_minute;
+                state.focus_end_minute = focus_end_minute;
+                state.step = CapacityWizardStep::Priorities;
+            }
+            CapacityWizardAnswer::Priorities { task_ids } => {
+                let mut ordered: Vec<String> = Vec::new();
+                for task_id in task_ids {
+                    if !ordered.contains(&task_id) {
+                        ordered.push(task_id);
+                    }
+                }
+                if ordered.is_empty() {
+                    return Err(AppError::validation("请至少选择一个任务"));
+                }
+                let tasks = self.planning.get_task_service();
+                for task_id in &ordered {
+                    let task = tasks.get_task(task_id)?;
+                    if matches!(task.status.as_str(), "done" | "archived") {
+                        return Err(AppError::validation(format!(
+                            "任务「{}」已完成，无法加入计划",
+                            task.title
+                        )));
+                    }
+                }
+                state.priority_task_ids = ordered;
+                state.step = CapacityWizardStep::Review;
+            }
+        }
+
+        state.prompt = state.step.prompt().to_string();
+        Ok(state.clone())
+    }
+
+    /// Saves the focus window to the default preference profile, stores the
+    /// commitments and focus target, and generates a plan for the next
+    /// seven days. The wizard stays open if planning fails, so it can be
+    /// finished again.
+    pub async fn finish(&self, id: &str) -> AppResult<CapacityWizardResult> {
+        let state = self
+            .lock()?
+            .get(id)
+            .cloned()
+            .ok_or_else(AppError::not_found)?;
+        if state.step != CapacityWizardStep::Review {
+            return Err(AppError::validation("还有问题未回答，无法完成设置"));
+        }
+        let weekly_focus_hours = state
+            .weekly_focus_hours
+            .ok_or_else(|| AppError::validation("缺少每周专注时长"))?;
+
+        let preference_id = self.db.with_connection(|conn| {
+            let preference_id = load_default_preference_id(conn)?;
+            let behavior = BehaviorLearningService::new(conn);
+            let mut snapshot = behavior.load_preferences(&preference_id)?;
+            if state.focus_start_minute.is_some() {
+                snapshot.focus_start_minute = state.focus_start_minute;
+                snapshot.focus_end_minute = state.focus_end_minute;
+            }
+            snapshot.validate()?;
+            behavior.save_preferences(&preference_id, &snapshot)?;
+            Ok(preference_id)
+        })?;
+
+        let profile = self.settings.update_capacity_profile(CapacityProfile {
+            commitments: state.commitments.clone(),
+            weekly_focus_hours: Some(weekly_focus_hours),
+            last_updated_at: None,
+        })?;
+
+        let from = Local::now().fixed_offset();
+        let max_focus_minutes_per_day = daily_focus_cap(weekly_focus_hours);
+        let constraints = ScheduleConstraints {
+            planning_start_at: Some(schedule_utils::format_datetime(from)),
+            planning_end_at: Some(schedule_utils::format_datetime(
+                from + Duration::days(PLAN_DAYS),
+            )),
+            existing_events: commitment_events(&state.commitments, from, PLAN_DAYS),
+            max_focus_minutes_per_day: Some(max_focus_minutes_per_day),
+            ..ScheduleConstraints::default()
+        };
+        let plan = self
+            .planning
+            .generate_plan(GeneratePlanInput {
+                task_ids: state.priority_task_ids.clone(),
+                project_id: None,
+                constraints: Some(constraints),
+                preference_id: Some(preference_id.clone()),
+                seed: None,
+                include_later_items: false,
+                privacy_mode: None,
+                optimizer_only: false,
+                offline: false,
+            })
+            .await?;
+
+        self.lock()?.remove(id);
+        Ok(CapacityWizardResult {
+            preference_id,
+            profile,
+            max_focus_minutes_per_day,
+            plan,
+        })
+    }
+
+    fn lock(&self) -> AppResult<std::sync::MutexGuard<'_, HashMap<String, CapacityWizardState>>> {
+        self.wizards
+            .lock()
+            .map_err(|_| AppError::other("容量设置向导状态不可用"))
+    }
+}
+
+/// Weekly focus spread over the working days, rounded up.
+fn daily_focus_cap(weekly_focus_hours: u32) -> i64 {
+    (i64::from(weekly_focus_hours) * 60 + WORKDAYS_PER_WEEK - 1) / WORKDAYS_PER_WEEK
+}
+
+/// Occurrences of `commitments` in the `days` days starting on `from`'s
+/// date, as events the planner must not overlap.
+fn commitment_events(
+    commitments: &[WeeklyCommitment],
+    from: DateTime<FixedOffset>,
+    days: i64,
+) -> Vec<ExistingEvent> {
+    let offset = *from.offset();
+    let mut events = Vec::new();
+    for day in 0..days {
+        let date = from.date_naive() + Duration::days(day);
+        let weekday = date.weekday().number_from_monday() as u8;
+        for (index, commitment) in commitments.iter().enumerate() {
+            if commitment.weekday != weekday {
+                continue;
+            }
+            let midnight = date.and_hms_opt(0, 0, 0).expect("midnight exists");
+            let Some(start) = offset
+                .from_local_datetime(&midnight)
+                .single()
+                .map(|midnight| midnight + Duration::minutes(i64::from(commitment.start_minute)))
+            else {
+                continue;
+            };
+            let end = start
+                + Duration::minutes(i64::from(commitment.end_minute - commitment.start_minute));
+            events.push(ExistingEvent {
+                id: format!("commitment-{index}-{date}"),
+                start_at: schedule_utils::format_datetime(start),
+                end_at: schedule_utils::format_datetime(end),
+                event_type: Some("commitment".to_string()),
+                recurrence_rule: None,
+            });
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn commitments_expand_over_the_planning_week() {
+        let offset = FixedOffset::east_opt(8 * 3600).unwrap();
+        // A Wednesday
+        let from = offset
+            .from_local_datetime(
+                &NaiveDate::from_ymd_opt(2026, 3, 4)
+                    .unwrap()
+                    .and_hms_opt(10, 0, 0)
+                    .unwrap(),
+            )
+            .unwrap();
+        let standup = WeeklyCommitment {
+            title: "站会".to_string(),
+            weekday: 1,
+            start_minute: 9 * 60 + 30,
+            end_minute: 10 * 60,
+        };
+        let class = WeeklyCommitment {
+            title: "课程".to_string(),
+            weekday: 3,
+            start_minute: 14 * 60,
+            end_minute: 16 * 60,
+        };
+
+        let events = commitment_events(&[standup, class], from, PLAN_DAYS);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].start_at, "2026-03-04T14:00:00+08:00");
+        assert_eq!(events[0].end_at, "2026-03-04T16:00:00+08:00");
+        assert_eq!(events[1].start_at, "2026-03-09T09:30:00+08:00");
+
+        assert_eq!(daily_focus_cap(20), 240);
+        assert_eq!(daily_focus_cap(1), 12);
+    }
+}
@@ -0,0 +1,354 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tracing::{debug, error, warn};
+
+use crate::error::{AppError, AppResult};
+use crate::models::task::{TaskCreateInput, TaskRecord};
+use crate::services::job_supervisor::spawn_supervised;
+use crate::services::settings_service::SettingsService;
+use crate::services::task_service::TaskService;
+
+pub const CLIPBOARD_ACTIONABLE_EVENT: &str = "clipboard://actionable-detected";
+
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const MIN_TEXT_CHARS: usize = 6;
+const MAX_TEXT_CHARS: usize = 2_000;
+const PREVIEW_CHARS: usize = 160;
+const TITLE_CHARS: usize = 80;
+const INBOX_TAGS: [&str; 2] = ["inbox", "clipboard"];
+
+static DATE_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
+    [
+        r"\b\d{4}[-/.]\d{1,2}[-/.]\d{1,2}\b",
+        r"\b\d{1,2}/\d{1,2}\b",
+        r"\d{1,2}月\d{1,2}[日号]",
+        r"\b\d{1,2}:\d{2}\b",
+        r"(?i)\b\d{1,2}\s?(am|pm)\b",
+        r"(?i)\b(today|tonight|tomorrow|next week|this week|eod|eow)\b",
+        r"(?i)\b(mon|tues|wednes|thurs|fri|satur|sun)day\b",
+        r"(?i)\b(jan|feb|mar|apr|may|jun|jul|aug|sep|oct|nov|dec)[a-z]*\.? \d{1,2}\b",
+        r"今天|今晚|明天|后天|大后天|本周|下周|周[一二三四五六日天]|星期[一二三四五六日天]|月底|[上下]午\d{1,2}点",
+    ]
+    .iter()
+    .map(|pattern| Regex::new(pattern).expect("valid clipboard date pattern"))
+    .collect()
+});
+
+static ACTION_VERB_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)\b(review|send|submit|prepare|finish|complete|call|email|reply|fix|update|draft|write|schedule|book|follow up|remind|deliver|pay|renew|check)\b|完成|提交|准备|发送|回复|联系|安排|跟进|审核|修复|整理|确认|交付|汇报|提醒|预约|处理",
+    )
+    .expect("valid clipboard verb pattern")
+});
+
+static MEETING_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)\b(meeting|invite|invitation|call with|sync|standup|interview|webinar|agenda|zoom\.us|teams\.microsoft\.com|meet\.google\.com)\b|会议|开会|例会|面试|腾讯会议|飞书会议|钉钉会议|议程|邀请您参加",
+    )
+    .expect("valid clipboard meeting pattern")
+});
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardCandidateKind {
+    Task,
+    Meeting,
+}
+
+/// Clipboard text that looked actionable and is offered for capture.
+#[derive(Debug, Clone, Serialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipboardCandidate {
+    pub kind: ClipboardCandidateKind,
+    pub title_hint: String,
+    pub preview: String,
+    pub date_hints: Vec<String>,
+    pub matched_keywords: Vec<String>,
+    pub fingerprint: String,
+    pub detected_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipboardCaptureInput {
+    pub text: String,
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+/// Polls the system clipboard while the user has opted in and raises a local,
+/// AI-free suggestion when the copied text looks like a task or meeting invite.
+pub struct ClipboardWatcher {
+    settings: Arc<SettingsService>,
+    tasks: Arc<TaskService>,
+    running: AtomicBool,
+    last_fingerprint: Mutex<Option<String>>,
+}
+
+impl ClipboardWatcher {
+    pub fn new(settings: Arc<SettingsService>, tasks: Arc<TaskService>) -> Self {
+        Self {
+            settings,
+            tasks,
+            running: AtomicBool::new(false),
+            last_fingerprint: Mutex::new(None),
+        }
+    }
+
+    pub fn ensure_started(self: &Arc<Self>, app: AppHandle) -> AppResult<()> {
+        if self
+            .running
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Ok(());
+        }
+
+        let runner = Arc::clone(self);
+        let db = self.tasks.pool().clone();
+        if let Err(err) =
+            spawn_supervised(db, "clipboard-watcher", move || runner.run_poll_loop(&app))
+        {
+            self.running.store(false, Ordering::SeqCst);
+            error!(
+                target: "app::clipboard",
+                error = %err,
+                "failed to start clipboard watcher thread"
+            );
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    pub fn is_enabled(&self) -> AppResult<bool> {
+        Ok(self
+            .settings
+            .get()?
+            .clipboard_watch_enabled
+            .unwrap_or(false))
+    }
+
+    /// Runs detection on freshly copied text. Returns `None` when watching is
+    /// disabled, the text was already seen, or it does not look actionable.
+    pub fn observe(&self, text: &str) -> AppResult<Option<ClipboardCandidate>> {
+        if !self.is_enabled()? {
+            return Ok(None);
+        }
+
+        let fingerprint = fingerprint(text);
+        if let Ok(mut last) = self.last_fingerprint.lock() {
+            if last.as_deref() == Some(fingerprint.as_str()) {
+                return Ok(None);
+            }
+            *last = Some(fingerprint);
+        }
+
+        Ok(detect_actionable_text(text))
+    }
+
+    /// Stores accepted clipboard text as a backlog task tagged for the inbox.
+    pub fn capture(&self, input: ClipboardCaptureInput) -> AppResult<TaskRecord> {
+        let text = input.text.trim();
+        if text.is_empty() {
+            return Err(AppError::validation("剪贴板内容为空，无法收集"));
+        }
+
+        let title = input
+            .title
+            .map(|title| title.trim().to_string())
+            .filter(|title| !title.is_empty())
+            .unwrap_or_else(|| title_hint(text));
+
+        self.tasks.create_task(TaskCreateInput {
+            title,
+            description: Some(text.to_string()),
+            status: Some("backlog".to_string()),
+            tags: Some(INBOX_TAGS.iter().map(|tag| tag.to_string()).collect()),
+            ..Default::default()
+        })
+    }
+
+    fn run_poll_loop(&self, app: &AppHandle) {
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            match self.is_enabled() {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(err) => {
+                    warn!(target: "app::clipboard", error = %err, "failed to read clipboard setting");
+                    continue;
+                }
+            }
+
+            let text = match app.clipboard().read_text() {
+                Ok(text) => text,
+                Err(err) => {
+                    debug!(target: "app::clipboard", error = %err, "clipboard has no readable text");
+                    continue;
+                }
+            };
+
+            match self.observe(&text) {
+                Ok(Some(candidate)) => {
+                    debug!(
+                        target: "app::clipboard",
+                        kind = ?candidate.kind,
+                        "actionable clipboard text detected"
+                    );
+                    if let Err(err) = app.emit(CLIPBOARD_ACTIONABLE_EVENT, &candidate) {
+                        warn!(target: "app::clipboard", error = %err, "failed to emit clipboard event");
+                    }
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    warn!(target: "app::clipboard", error = %err, "clipboard detection failed");
+                }
+            }
+        }
+    }
+}
+
+/// Local heuristic: the text must mention a date/time and either an action
+/// verb or a meeting marker. No network or AI call is involved.
+pub fn detect_actionable_text(text: &str) -> Option<ClipboardCandidate> {
+    let trimmed = text.trim();
+    let char_count = trimmed.chars().count();
+    if !(MIN_TEXT_CHARS..=MAX_TEXT_CHARS).contains(&char_count) {
+        return None;
+    }
+
+    let mut date_hints: Vec<String> = Vec::new();
+    for pattern in DATE_PATTERNS.iter() {
+        for found in pattern.find_iter(trimmed) {
+            let hint = found.as_str().to_string();
+            if !date_hints.contains(&hint) {
+                date_hints.push(hint);
+            }
+        }
+    }
+    if date_hints.is_empty() {
+        return None;
+    }
+
+    let meeting_keywords = collect_matches(&MEETING_PATTERN, trimmed);
+    let verb_keywords = collect_matches(&ACTION_VERB_PATTERN, trimmed);
+    if meeting_keywords.is_empty() && verb_keywords.is_empty() {
+        return None;
+    }
+
+    let kind = if meeting_keywords.is_empty() {
+        ClipboardCandidateKind::Task
+    } else {
+        ClipboardCandidateKind::Meeting
+    };
+
+    let mut matched_keywords = meeting_keywords;
+    for keyword in verb_keywords {
+        if !matched_keywords.contains(&keyword) {
+            matched_keywords.push(keyword);
+        }
+    }
+
+    Some(ClipboardCandidate {
+        kind,
+        title_hint: title_hint(trimmed),
+        preview: truncate_chars(trimmed, PREVIEW_CHARS),
+        date_hints,
+        matched_keywords,
+        fingerprint: fingerprint(trimmed),
+        detected_at: Utc::now().to_rfc3339(),
+    })
+}
+
+fn collect_matches(pattern: &Regex, text: &str) -> Vec<String> {
+    let mut matches: Vec<String> = Vec::new();
+    for found in pattern.find_iter(text) {
+        let keyword = found.as_str().to_lowercase();
+        if !matches.contains(&keyword) {
+            matches.push(keyword);
+        }
+    }
+    matches
+}
+
+fn title_hint(text: &str) -> String {
+    let first_line = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    truncate_chars(first_line, TITLE_CHARS)
+}
+
+fn truncate_chars(text: &str, limit: usize) -> String {
+    if text.chars().count() <= limit {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(limit).collect();
+    truncated.push('…');
+    truncated
+}
+
+fn fingerprint(text: &str) -> String {
+    let digest = Sha256::digest(text.trim().as_bytes());
+    digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_task_with_date_and_verb() {
+        let candidate =
+            detect_actionable_text("Please review the Q3 budget draft by Friday 5pm").unwrap();
+        assert_eq!(candidate.kind, ClipboardCandidateKind::Task);
+        assert!(candidate.date_hints.iter().any(|hint| hint == "Friday"));
+        assert!(candidate.matched_keywords.contains(&"review".to_string()));
+    }
+
+    #[test]
+    fn detects_chinese_meeting_invite() {
+        let candidate = detect_actionable_text(
+            "邀请您参加腾讯会议\n会议时间：明天 14:00-15:00\n议程：周报同步",
+        )
+        .unwrap();
+        assert_eq!(candidate.kind, ClipboardCandidateKind::Meeting);
+        assert_eq!(candidate.title_hint, "邀请您参加腾讯会议");
+        assert!(candidate.date_hints.contains(&"明天".to_string()));
+    }
+
+    #[test]
+    fn ignores_text_without_dates_or_actions() {
+        assert!(detect_actionable_text("Please review the attached document").is_none());
+        assert!(detect_actionable_text("The release shipped on 2024-05-01.").is_none());
+        assert!(detect_actionable_text("ok").is_none());
+    }
+
+    #[test]
+    fn ignores_oversized_text() {
+        let long_text = format!("review tomorrow {}", "x".repeat(MAX_TEXT_CHARS));
+        assert!(detect_actionable_text(&long_text).is_none());
+    }
+
+    #[test]
+    fn title_hint_is_truncated_by_chars() {
+        let line = "准".repeat(TITLE_CHARS + 10);
+        let hint = title_hint(&line);
+        assert_eq!(hint.chars().count(), TITLE_CHARS + 1);
+        assert!(hint.ends_with('…'));
+    }
+}
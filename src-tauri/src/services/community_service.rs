@@ -2,10 +2,16 @@ use crate::db::repositories::community_export_repository::CommunityExportReposit
 use crate::db::DbPool;
 use crate::error::{AppError, AppResult};
 use crate::models::community_export::CommunityExportCreate;
+use crate::models::settings::FeedbackCategory;
+use crate::services::settings_service::load_feedback_opt_outs;
+use crate::utils::bundle::BundleWriter;
+use crate::utils::files::{write_atomic, write_atomic_with};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use sha2::{Digest, Sha256};
-use std::fs;
+use std::fs::File;
+use std::io::Read;
 use std::path::Path;
 
 const PROJECT_NAME: &str = "CogniCal";
@@ -16,7 +22,14 @@ const PROJECT_DOCS: &str = "https://github.com/cognical/cognical#readme";
 const PROJECT_CONTRIBUTING: &str = "https://github.com/cognical/cognical/blob/main/CONTRIBUTING.md";
 const PROJECT_COMMUNITY: &str = "https://github.com/cognical/cognical/discussions";
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Bumped whenever the layout of a zip bundle changes. Readers accept every
+/// version up to this one.
+pub const COMMUNITY_BUNDLE_SCHEMA_VERSION: u32 = 1;
+const BUNDLE_MANIFEST_FILE: &str = "manifest.json";
+const BUNDLE_DATA_FILE: &str = "bundle.json";
+const BUNDLE_README_FILE: &str = "README.md";
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ProjectInfo {
     pub name: String,
@@ -30,7 +43,7 @@ pub struct ProjectInfo {
     pub features_always_free: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct DetectedPlugin {
     pub name: String,
@@ -40,7 +53,7 @@ pub struct DetectedPlugin {
     pub permissions: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SystemInfo {
     pub os: String,
@@ -49,7 +62,7 @@ pub struct SystemInfo {
     pub timestamp: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AnonymizedMetrics {
     pub total_tasks: i64,
@@ -61,7 +74,7 @@ pub struct AnonymizedMetrics {
     pub wellness_events_count: i64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct FeedbackSummary {
     pub total_feedback_count: i64,
@@ -71,16 +84,47 @@ pub struct FeedbackSummary {
     pub most_common_issues: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ExportBundle {
     pub system_info: SystemInfo,
-    pub metrics: AnonymizedMetrics,
+    /// `None` when the user opted out of anonymous usage statistics.
+    pub metrics: Option<AnonymizedMetrics>,
     pub feedback_summary: Option<FeedbackSummary>,
     pub plugins: Vec<DetectedPlugin>,
     pub checksum: String,
 }
 
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CommunityExportFormat {
+    /// A zip holding `manifest.json`, `bundle.json` and `README.md`.
+    #[default]
+    Zip,
+    /// The readable report only, as one Markdown file.
+    Markdown,
+}
+
+/// `manifest.json` of a zip bundle; lists every other file with its hash.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleManifest {
+    pub schema_version: u32,
+    pub app_version: String,
+    pub generated_at: String,
+    /// Checksum of the bundle data, as in [`ExportBundle::checksum`].
+    pub bundle_checksum: String,
+    pub files: Vec<BundleFileEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleFileEntry {
+    pub path: String,
+    /// SHA-256 of the file, hex encoded.
+    pub sha256: String,
+}
+
 impl ExportBundle {
     pub fn to_markdown(&self) -> String {
         let mut md = String::new();
@@ -102,40 +146,41 @@ impl ExportBundle {
 
         // Anonymized Metrics
         md.push_str("## Anonymized Usage Metrics\n\n");
-        md.push_str(&format!(
-            "- **Total Tasks**: {}\n",
-            self.metrics.total_tasks
-        ));
-        md.push_str(&format!(
-            "- **Completed Tasks**: {}\n",
-            self.metrics.completed_tasks
-        ));
-        if let Some(avg_time) = self.metrics.average_completion_time_minutes {
+        if let Some(ref metrics) = self.metrics {
+            md.push_str(&format!("- **Total Tasks**: {}\n", metrics.total_tasks));
             md.push_str(&format!(
-                "- **Avg Completion Time**: {:.1} minutes\n",
-                avg_time
+                "- **Completed Tasks**: {}\n",
+                metrics.completed_tasks
             ));
-        }
-        md.push_str(&format!(
-            "- **Total Sessions**: {}\n",
-            self.metrics.total_sessions
-        ));
-        md.push_str(&format!(
-            "- **Productivity Score**: {}\n",
-            if self.metrics.productivity_score_available {
-                "Available"
-            } else {
-                "Not Available"
+            if let Some(avg_time) = metrics.average_completion_time_minutes {
+                md.push_str(&format!(
+                    "- **Avg Completion Time**: {:.1} minutes\n",
+                    avg_time
+                ));
             }
-        ));
-        md.push_str(&format!(
-            "- **Workload Forecasts**: {}\n",
-            self.metrics.workload_forecasts_count
-        ));
-        md.push_str(&format!(
-            "- **Wellness Events**: {}\n\n",
-            self.metrics.wellness_events_count
-        ));
+            md.push_str(&format!(
+                "- **Total Sessions**: {}\n",
+                metrics.total_sessions
+            ));
+            md.push_str(&format!(
+                "- **Productivity Score**: {}\n",
+                if metrics.productivity_score_available {
+                    "Available"
+                } else {
+                    "Not Available"
+                }
+            ));
+            md.push_str(&format!(
+                "- **Workload Forecasts**: {}\n",
+                metrics.workload_forecasts_count
+            ));
+            md.push_str(&format!(
+                "- **Wellness Events**: {}\n\n",
+                metrics.wellness_events_count
+            ));
+        } else {
+            md.push_str("Not included: anonymous usage statistics are turned off.\n\n");
+        }
 
         // Feedback Summary
         if let Some(ref feedback) = self.feedback_summary {
@@ -379,7 +424,15 @@ impl CommunityService {
     /// Generate complete export bundle
     pub async fn generate_export_bundle(&self, include_feedback: bool) -> AppResult<ExportBundle> {
         let system_info = self.generate_system_info();
-        let metrics = self.collect_anonymized_metrics().await?;
+        let usage_stats_opted_out = {
+            let conn = self.db_pool.get_connection()?;
+            load_feedback_opt_outs(&conn)?.is_opted_out(FeedbackCategory::UsageStats)
+        };
+        let metrics = if usage_stats_opted_out {
+            None
+        } else {
+            Some(self.collect_anonymized_metrics().await?)
+        };
         let feedback_summary = if include_feedback {
             self.collect_feedback_summary().await?
         } else {
@@ -409,22 +462,28 @@ impl CommunityService {
         &self,
         bundle: &ExportBundle,
         file_path: &Path,
+        format: CommunityExportFormat,
     ) -> AppResult<i64> {
-        // Generate Markdown content
-        let markdown_content = bundle.to_markdown();
-
         // Write to file
-        fs::write(file_path, markdown_content)
-            .map_err(|e| AppError::validation(format!("Failed to write export file: {}", e)))?;
+        let written = match format {
+            CommunityExportFormat::Zip => Self::write_zip_bundle(bundle, file_path),
+            CommunityExportFormat::Markdown => {
+                write_atomic(file_path, bundle.to_markdown().as_bytes())
+            }
+        };
+        written.map_err(|e| AppError::validation(format!("Failed to write export file: {}", e)))?;
 
         // Record in database
         let conn = self.db_pool.get_connection()?;
 
-        let metrics_summary = json!({
-            "total_tasks": bundle.metrics.total_tasks,
-            "completed_tasks": bundle.metrics.completed_tasks,
-            "total_sessions": bundle.metrics.total_sessions,
-        });
+        let metrics_summary = match &bundle.metrics {
+            Some(metrics) => json!({
+                "total_tasks": metrics.total_tasks,
+                "completed_tasks": metrics.completed_tasks,
+                "total_sessions": metrics.total_sessions,
+            }),
+            None => json!({}),
+        };
 
         let create_input = CommunityExportCreate::new(
             file_path.to_string_lossy().to_string(),
@@ -438,6 +497,109 @@ impl CommunityService {
         Ok(export_id)
     }
 
+    /// Writes `bundle.json` and `README.md`, then the manifest with both
+    /// files' hashes.
+    fn write_zip_bundle(bundle: &ExportBundle, file_path: &Path) -> AppResult<String> {
+        let data = serde_json::to_vec_pretty(bundle)?;
+        let readme = bundle.to_markdown();
+
+        write_atomic_with(file_path, |file| {
+            let mut writer = BundleWriter::zip(file);
+            let mut files = Vec::new();
+            for (name, bytes) in [
+                (BUNDLE_DATA_FILE, data.as_slice()),
+                (BUNDLE_README_FILE, readme.as_bytes()),
+            ] {
+                let sha256 = writer.add_entry(Path::new(name), |out| {
+                    out.write_all(bytes)?;
+                    Ok(())
+                })?;
+                files.push(BundleFileEntry {
+                    path: name.to_string(),
+                    sha256,
+                });
+            }
+
+            let manifest = BundleManifest {
+                schema_version: COMMUNITY_BUNDLE_SCHEMA_VERSION,
+                app_version: PROJECT_VERSION.to_string(),
+                generated_at: bundle.system_info.timestamp.clone(),
+                bundle_checksum: bundle.checksum.clone(),
+                files,
+            };
+            writer.add_entry(Path::new(BUNDLE_MANIFEST_FILE), |out| {
+                serde_json::to_writer_pretty(out, &manifest)?;
+                Ok(())
+            })?;
+            writer
+                .finish()?
+                .ok_or_else(|| AppError::other("Bundle file was not created"))
+        })
+    }
+
+    /// Reads a zip bundle and verifies its schema version, every file hash in
+    /// the manifest and the bundle checksum.
+    pub fn read_export_bundle(&self, file_path: &Path) -> AppResult<ExportBundle> {
+        let invalid = |e: zip::result::ZipError| {
+            AppError::validation(format!("Invalid community bundle: {}", e))
+        };
+        let mut archive = zip::ZipArchive::new(File::open(file_path)?).map_err(invalid)?;
+        let mut read_entry = |name: &str| -> AppResult<Vec<u8>> {
+            let mut bytes = Vec::new();
+            archive
+                .by_name(name)
+                .map_err(invalid)?
+                .read_to_end(&mut bytes)?;
+            Ok(bytes)
+        };
+
+        let manifest: BundleManifest =
+            serde_json::from_slice(&read_entry(BUNDLE_MANIFEST_FILE)?)
+                .map_err(|e| AppError::validation(format!("Invalid bundle manifest: {}", e)))?;
+        if !(1..=COMMUNITY_BUNDLE_SCHEMA_VERSION).contains(&manifest.schema_version) {
+            return Err(AppError::validation(format!(
+                "Unsupported bundle schema version {}",
+                manifest.schema_version
+            )));
+        }
+
+        let mut data = None;
+        for entry in &manifest.files {
+            let bytes = read_entry(&entry.path)?;
+            if format!("{:x}", Sha256::digest(&bytes)) != entry.sha256 {
+                return Err(AppError::validation(format!(
+                    "Checksum mismatch for {}",
+                    entry.path
+                )));
+            }
+            if entry.path == BUNDLE_DATA_FILE {
+                data = Some(bytes);
+            }
+        }
+        let data = data.ok_or_else(|| {
+            AppError::validation(format!(
+                "Bundle manifest does not list {}",
+                BUNDLE_DATA_FILE
+            ))
+        })?;
+
+        let bundle: ExportBundle = serde_json::from_slice(&data)
+            .map_err(|e| AppError::validation(format!("Invalid bundle data: {}", e)))?;
+        let unsigned = serde_json::to_string(&ExportBundle {
+            checksum: String::new(),
+            ..bundle.clone()
+        })?;
+        if bundle.checksum != manifest.bundle_checksum
+            || Self::calculate_checksum(&unsigned) != bundle.checksum
+        {
+            return Err(AppError::validation(
+                "Bundle checksum does not match its data",
+            ));
+        }
+
+        Ok(bundle)
+    }
+
     /// Get list of previous exports
     pub fn list_exports(&self) -> AppResult<Vec<(i64, String, String)>> {
         let conn = self.db_pool.get_connection()?;
@@ -459,6 +621,7 @@ impl CommunityService {
 mod tests {
     use super::*;
     use crate::db::DbPool;
+    use std::fs;
     use tempfile::TempDir;
 
     fn setup_test_service() -> AppResult<(CommunityService, TempDir)> {
@@ -537,7 +700,7 @@ mod tests {
         let file_path = temp_dir.path().join("export.md");
 
         let export_id = service
-            .save_export_to_file(&bundle, &file_path)
+            .save_export_to_file(&bundle, &file_path, CommunityExportFormat::Markdown)
             .await
             .expect("Should save export to file");
 
@@ -547,4 +710,78 @@ mod tests {
         let content = fs::read_to_string(&file_path).expect("Should read file");
         assert!(content.contains("CogniCal"));
     }
+
+    #[tokio::test]
+    async fn test_usage_stats_opt_out_leaves_metrics_out() {
+        let (service, _temp_dir) = setup_test_service().expect("Failed to setup test service");
+        let settings =
+            crate::services::settings_service::SettingsService::new(service.db_pool.clone())
+                .expect("Should create settings service");
+        settings
+            .update_feedback_opt_outs(crate::models::settings::FeedbackOptOuts {
+                usage_stats: true,
+                ..Default::default()
+            })
+            .expect("Should opt out");
+
+        let bundle = service
+            .generate_export_bundle(false)
+            .await
+            .expect("Should generate export bundle");
+
+        assert!(bundle.metrics.is_none());
+        assert!(bundle.to_markdown().contains("statistics are turned off"));
+    }
+
+    #[tokio::test]
+    async fn test_zip_bundle_round_trips_and_detects_tampering() {
+        let (service, temp_dir) = setup_test_service().expect("Failed to setup test service");
+        let bundle = service
+            .generate_export_bundle(false)
+            .await
+            .expect("Should generate export bundle");
+
+        let file_path = temp_dir.path().join("export.zip");
+        service
+            .save_export_to_file(&bundle, &file_path, CommunityExportFormat::Zip)
+            .await
+            .expect("Should save zip bundle");
+
+        let read = service
+            .read_export_bundle(&file_path)
+            .expect("Should read zip bundle");
+        assert_eq!(read.checksum, bundle.checksum);
+        assert_eq!(
+            read.metrics.map(|m| m.total_tasks),
+            bundle.metrics.as_ref().map(|m| m.total_tasks)
+        );
+
+        // Rewrite the bundle with altered data but the original manifest
+        let mut archive = zip::ZipArchive::new(File::open(&file_path).unwrap()).unwrap();
+        let mut manifest = String::new();
+        archive
+            .by_name(BUNDLE_MANIFEST_FILE)
+            .unwrap()
+            .read_to_string(&mut manifest)
+            .unwrap();
+        let tampered_path = temp_dir.path().join("tampered.zip");
+        let mut writer = BundleWriter::zip(File::create(&tampered_path).unwrap());
+        let mut tampered = bundle.clone();
+        tampered.metrics.as_mut().unwrap().total_tasks += 1;
+        writer
+            .add_entry(Path::new(BUNDLE_DATA_FILE), |out| {
+                serde_json::to_writer(out, &tampered)?;
+                Ok(())
+            })
+            .unwrap();
+        writer
+            .add_entry(Path::new(BUNDLE_MANIFEST_FILE), |out| {
+                out.write_all(manifest.as_bytes())?;
+                Ok(())
+            })
+            .unwrap();
+        writer.finish().unwrap();
+
+        assert!(service.read_export_bundle(&tampered_path).is_err());
+    }
 }
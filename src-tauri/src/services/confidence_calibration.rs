@@ -0,0 +1,277 @@
+//! Checks block confidences against what actually happened and corrects new
+//! ones. An applied block counts as a hit when it was not left unfinished,
+//! started roughly when planned, and its task was done soon after the task's
+//! last block ended.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::Connection;
+
+use crate::db::repositories::planning_repository::PlanningRepository;
+use crate::db::repositories::task_repository::TaskRepository;
+use crate::error::AppResult;
+use crate::models::analytics::{CalibrationBucket, ConfidenceCalibration};
+use crate::models::planning::PlanningTimeBlockRecord;
+use crate::models::task::TaskRecord;
+use crate::services::schedule_optimizer::PlanOption;
+
+const CALIBRATION_WINDOW_DAYS: i64 = 90;
+/// Blocks settle once this long has passed since they ended.
+const SETTLE_HOURS: i64 = 24;
+/// Starting further than this from the planned start counts as moved.
+const MOVED_MINUTES: i64 = 60;
+const BUCKET_BOUNDS: [f64; 7] = [0.0, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0];
+/// Weight of the raw confidence when blending in observed hit rates, in
+/// samples; small buckets barely move it.
+const PRIOR_SAMPLES: f64 = 10.0;
+const MIN_CONFIDENCE: f32 = 0.05;
+const MAX_CONFIDENCE: f32 = 0.99;
+const UNFINISHED_STATUS: &str = "unfinished";
+
+/// Calibration over the applied blocks that settled in the last
+/// `CALIBRATION_WINDOW_DAYS` before `now`.
+pub fn load_calibration(conn: &Connection, now: DateTime<Utc>) -> AppResult<ConfidenceCalibration> {
+    let blocks = PlanningRepository::list_applied_time_blocks_between(
+        conn,
+        &(now - Duration::days(CALIBRATION_WINDOW_DAYS)).to_rfc3339(),
+        &now.to_rfc3339(),
+    )?
+    .into_iter()
+    .map(|row| row.into_record())
+    .collect::<AppResult<Vec<_>>>()?;
+    let tasks = TaskRepository::list_all(conn)?
+        .into_iter()
+        .map(|row| row.into_record())
+        .collect::<AppResult<Vec<_>>>()?;
+
+    Ok(build_calibration(&blocks, &tasks, now))
+}
+
+fn parse(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|at| at.with_timezone(&Utc))
+}
+
+pub fn build_calibration(
+    blocks: &[PlanningTimeBlockRecord],
+    tasks: &[TaskRecord],
+    now: DateTime<Utc>,
+) -> ConfidenceCalibration {
+    let tasks: HashMap<&str, &TaskRecord> =
+        tasks.iter().map(|task| (task.id.as_str(), task)).collect();
+    let mut last_end: HashMap<&str, DateTime<Utc>> = HashMap::new();
+    for block in blocks {
+        if let Some(end) = parse(&block.end_at) {
+            let last = last_end.entry(block.task_id.as_str()).or_insert(end);
+            *last = (*last).max(end);
+        }
+    }
+
+    let mut buckets: Vec<CalibrationBucket> = BUCKET_BOUNDS
+        .windows(2)
+        .map(|bounds| CalibrationBucket {
+            min_confidence: bounds[0],
+            max_confidence: bounds[1],
+            ..CalibrationBucket::default()
+        })
+        .collect();
+    let mut squared_error = 0.0;
+    let mut calibration = ConfidenceCalibration {
+        generated_at: now.to_rfc3339(),
+        window_days: CALIBRATION_WINDOW_DAYS,
+        ..ConfidenceCalibration::default()
+    };
+
+    for block in blocks {
+        let (Some(confidence), Some(start), Some(end)) = (
+            block.confidence,
+            parse(&block.start_at),
+            parse(&block.end_at),
+        ) else {
+            continue;
+        };
+        let Some(task) = tasks.get(block.task_id.as_str()) else {
+            continue;
+        };
+        let Some(deadline) = last_end
+            .get(block.task_id.as_str())
+            .map(|last| *last + Duration::hours(SETTLE_HOURS))
+        else {
+            continue;
+        };
+        let completed_at = task.completed_at.as_deref().and_then(parse);
+        if end + Duration::hours(SETTLE_HOURS) > now && completed_at.is_none() {
+            continue;
+        }
+
+        let moved = block
+            .actual_start_at
+            .as_deref()
+            .and_then(parse)
+            .is_some_and(|actual| (actual - start).num_minutes().abs() > MOVED_MINUTES);
+        let hit = block.status != UNFINISHED_STATUS
+            && !moved
+            && completed_at.is_some_and(|at| at <= deadline);
+
+        let confidence = confidence.clamp(0.0, 1.0);
+        let index = buckets
+            .iter()
+            .rposition(|bucket| confidence >= bucket.min_confidence)
+            .unwrap_or(0);
+        let bucket = &mut buckets[index];
+        bucket.samples += 1;
+        bucket.mean_confidence += confidence;
+        let outcome = if hit { 1.0 } else { 0.0 };
+        if hit {
+            bucket.hits += 1;
+            calibration.hits += 1;
+        }
+        calibration.samples += 1;
+        squared_error += (confidence - outcome).powi(2);
+    }
+
+    for bucket in &mut buckets {
+        if bucket.samples > 0 {
+            let samples = f64::from(bucket.samples);
+            bucket.mean_confidence = round3(bucket.mean_confidence / samples);
+            bucket.hit_rate = Some(round3(f64::from(bucket.hits) / samples));
+        }
+    }
+    if calibration.samples > 0 {
+        calibration.brier_score = Some(round3(squared_error / f64::from(calibration.samples)));
+    }
+    calibration.buckets = buckets;
+    calibration
+}
+
+/// `confidence` moved towards the hit rate observed for similar blocks.
+pub fn calibrate(confidence: f32, calibration: &ConfidenceCalibration) -> f32 {
+    let raw = f64::from(confidence.clamp(0.0, 1.0));
+    let Some((bucket, hit_rate)) = calibration
+        .buckets
+        .iter()
+        .rev()
+        .find(|bucket| raw >= bucket.min_confidence)
+        .and_then(|bucket| Some((bucket, bucket.hit_rate?)))
+    else {
+        return confidence;
+    };
+    let samples = f64::from(bucket.samples);
+    let shift = (hit_rate - bucket.mean_confidence) * samples / (samples + PRIOR_SAMPLES);
+    ((raw + shift) as f32).clamp(MIN_CONFIDENCE, MAX_CONFIDENCE)
+}
+
+pub fn calibrate_options(options: &mut [PlanOption], calibration: &ConfidenceCalibration) {
+    if calibration.samples == 0 {
+        return;
+    }
+    for block in options
+        .iter_mut()
+        .flat_map(|option| option.blocks.iter_mut())
+    {
+        block.confidence = calibrate(block.confidence, calibration);
+    }
+}
+
+fn round3(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: &str, completed_at: Option<&str>) -> TaskRecord {
+        TaskRecord {
+            id: id.to_string(),
+            title: id.to_string(),
+            description: None,
+            status: completed_at.map_or("todo", |_| "done").to_string(),
+            priority: "medium".to_string(),
+            planned_start_at: None,
+            start_at: None,
+            due_at: None,
+            completed_at: completed_at.map(str::to_string),
+            estimated_minutes: None,
+            estimated_hours: None,
+            tags: Vec::new(),
+            owner_id: None,
+            task_type: None,
+            is_recurring: false,
+            recurrence: None,
+            ai: None,
+            external_links: Vec::new(),
+            project_id: None,
+            snoozed_until: None,
+            snooze_notify: false,
+            color: None,
+            icon: None,
+            delegated_to: None,
+            waiting_on: None,
+            waiting_since: None,
+            follow_up_task_id: None,
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            updated_at: "2026-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    fn block(task_id: &str, day: u32, confidence: f64) -> PlanningTimeBlockRecord {
+        PlanningTimeBlockRecord {
+            id: format!("{task_id}-{day}"),
+            option_id: "option".to_string(),
+            task_id: task_id.to_string(),
+            start_at: format!("2026-03-{day:02}T09:00:00Z"),
+            end_at: format!("2026-03-{day:02}T10:00:00Z"),
+            flexibility: None,
+            confidence: Some(confidence),
+            conflict_flags: None,
+            applied_at: Some("2026-03-01T08:00:00Z".to_string()),
+            actual_start_at: None,
+            actual_end_at: None,
+            status: "planned".to_string(),
+            prep_note: None,
+        }
+    }
+
+    #[test]
+    fn outcomes_build_a_curve_that_corrects_overconfidence() {
+        let now = DateTime::parse_from_rfc3339("2026-03-20T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let tasks = vec![
+            task("on-time", Some("2026-03-02T16:00:00Z")),
+            task("late", Some("2026-03-09T16:00:00Z")),
+            task("never", None),
+            task("pending", None),
+        ];
+        let mut unfinished = block("on-time", 3, 0.85);
+        unfinished.status = "unfinished".to_string();
+        let mut moved = block("on-time", 2, 0.85);
+        moved.actual_start_at = Some("2026-03-02T13:00:00Z".to_string());
+        let blocks = vec![
+            block("on-time", 1, 0.85),
+            moved,
+            unfinished,
+            block("late", 2, 0.85),
+            block("never", 4, 0.55),
+            // Ended less than a day ago and not done yet
+            block("pending", 20, 0.85),
+        ];
+
+        let calibration = build_calibration(&blocks, &tasks, now);
+        assert_eq!((calibration.samples, calibration.hits), (5, 1));
+        let high = &calibration.buckets[4];
+        assert_eq!((high.samples, high.hits), (4, 1));
+        assert_eq!(high.hit_rate, Some(0.25));
+        assert_eq!(calibration.buckets[1].hit_rate, Some(0.0));
+        assert_eq!(calibration.buckets[0].hit_rate, None);
+
+        // 0.85 + (0.25 - 0.85) * 4 / 14
+        let adjusted = calibrate(0.85, &calibration);
+        assert!((adjusted - 0.679).abs() < 0.001, "{adjusted}");
+        // No data below 0.5, so those stay as they are
+        assert_eq!(calibrate(0.3, &calibration), 0.3);
+    }
+}
@@ -52,6 +52,10 @@ impl CotEngine {
             recurrence: None,
             task_type: self.derive_task_type(trimmed_input),
             external_links: Some(Vec::new()),
+            project_id: request
+                .context
+                .as_ref()
+                .and_then(|ctx| ctx.project_id.clone()),
         };
 
         let complexity_score = self.estimate_complexity(trimmed_input, &tags);
@@ -0,0 +1,390 @@
+use std::collections::BTreeSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value as JsonValue;
+use tracing::{info, warn};
+
+use crate::db::repositories::custom_tool_repository::{CustomToolRepository, CustomToolRow};
+use crate::db::repositories::settings_repository::SettingsRepository;
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::custom_tool::{CustomToolDefinition, CustomToolRecord};
+use crate::services::settings_service::notify_settings_changed;
+use crate::services::tool_registry::{ToolAllowlist, ToolHandler, ToolOrigin, ToolRegistry};
+
+const MAX_DESCRIPTION_CHARS: usize = 1_024;
+const KEY_TOOL_ALLOWLIST: &str = "agent_tool_allowlist";
+
+static TOOL_NAME_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[a-z][a-z0-9_]{2,63}$").expect("valid tool name pattern"));
+
+/// Persists declarative tools and keeps the shared `ToolRegistry` in sync, so
+/// advanced users and plugins can add agent tools without recompiling.
+pub struct CustomToolService {
+    db: DbPool,
+    registry: Arc<ToolRegistry>,
+}
+
+impl CustomToolService {
+    pub fn new(db: DbPool, registry: Arc<ToolRegistry>) -> Self {
+        Self { db, registry }
+    }
+
+    /// Registers (or replaces) a declarative tool and stores it for the next start.
+    pub fn register(&self, definition: CustomToolDefinition) -> AppResult<CustomToolRecord> {
+        let definition = normalize_definition(definition);
+        self.validate(&definition)?;
+
+        let existing = self.db.with_connection(CustomToolRepository::list_all)?;
+        let created_at = existing
+            .iter()
+            .find(|record| record.definition.name == definition.name)
+            .map(|record| record.created_at.clone());
+
+        if self.registry.tool_origin(&definition.name) == Some(ToolOrigin::Runtime) {
+            self.registry.unregister_runtime_tool(&definition.name)?;
+        }
+        self.install(&definition)?;
+
+        let now = Utc::now().to_rfc3339();
+        let record = CustomToolRecord {
+            definition,
+            created_at: created_at.unwrap_or_else(|| now.clone()),
+            updated_at: now,
+        };
+        let row = CustomToolRow::from_record(&record)?;
+        if let Err(err) = self
+            .db
+            .with_connection(|conn| CustomToolRepository::upsert(conn, &row))
+        {
+            let _ = self
+                .registry
+                .unregister_runtime_tool(&record.definition.name);
+            return Err(err);
+        }
+
+        info!(target: "app::custom_tools", tool_name = %record.definition.name, "custom tool registered");
+        Ok(record)
+    }
+
+    pub fn list(&self) -> AppResult<Vec<CustomToolRecord>> {
+        self.db.with_connection(CustomToolRepository::list_all)
+    }
+
+    pub fn unregister(&self, name: &str) -> AppResult<()> {
+        let removed = self
+            .db
+            .with_connection(|conn| CustomToolRepository::delete(conn, name))?;
+
+        match self.registry.unregister_runtime_tool(name) {
+            Ok(()) => Ok(()),
+            Err(AppError::NotFound) if removed => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Re-registers persisted tools at startup. Definitions that no longer
+    /// validate (e.g. their target tool was removed) are skipped, not fatal.
+    pub fn load_persisted(&self) -> AppResult<usize> {
+        let records = self.db.with_connection(CustomToolRepository::list_all)?;
+        let mut loaded = 0;
+
+        for record in records {
+            let name = record.definition.name.clone();
+            match self
+                .validate(&record.definition)
+                .and_then(|_| self.install(&record.definition))
+            {
+                Ok(()) => loaded += 1,
+                Err(err) => {
+                    warn!(
+                        target: "app::custom_tools",
+                        tool_name = %name,
+                        error = %err,
+                        "skipping invalid persisted custom tool"
+                    );
+                }
+            }
+        }
+
+        Ok(loaded)
+    }
+
+    pub fn allowlist(&self) -> ToolAllowlist {
+        self.registry.allowlist()
+    }
+
+    /// Restricts which tools the agent may call, e.g. read-only tools for a
+    /// demo, and stores the lists for the next start.
+    pub fn set_allowlist(&self, allowlist: ToolAllowlist) -> AppResult<ToolAllowlist> {
+        let allowlist = normalize_allowlist(allowlist);
+        let unknown = allowlist
+            .global
+            .iter()
+            .chain(allowlist.conversations.values())
+            .flatten()
+            .find(|name| !self.registry.has_tool(name));
+        if let Some(name) = unknown {
+            return Err(AppError::validation(format!("工具 '{name}' 不存在")));
+        }
+
+        let serialized = serde_json::to_string(&allowlist)?;
+        self.db.with_connection(|conn| {
+            SettingsRepository::upsert(conn, KEY_TOOL_ALLOWLIST, &serialized)
+        })?;
+        notify_settings_changed();
+        self.registry.set_allowlist(allowlist.clone());
+
+        info!(
+            target: "app::custom_tools",
+            global = ?allowlist.global.as_ref().map(BTreeSet::len),
+            conversations = allowlist.conversations.len(),
+            "tool allowlist updated"
+        );
+        Ok(allowlist)
+    }
+
+    /// Sets or, with `None`, clears the allowlist of one conversation.
+    pub fn set_conversation_allowlist(
+        &self,
+        conversation_id: &str,
+        tools: Option<Vec<String>>,
+    ) -> AppResult<ToolAllowlist> {
+        let conversation_id = conversation_id.trim();
+        if conversation_id.is_empty() {
+            return Err(AppError::validation("会话ID不能为空"));
+        }
+
+        let mut allowlist = self.registry.allowlist();
+        match tools {
+            Some(tools) => {
+                allowlist
+                    .conversations
+                    .insert(conversation_id.to_string(), tools.into_iter().collect());
+            }
+            None => {
+                allowlist.conversations.remove(conversation_id);
+            }
+        }
+        self.set_allowlist(allowlist)
+    }
+
+    /// Restores the stored allowlist at startup. Names of tools that are gone
+    /// are kept; they simply match nothing.
+    pub fn load_allowlist(&self) -> AppResult<()> {
+        let stored = self
+            .db
+            .with_connection(|conn| SettingsRepository::get(conn, KEY_TOOL_ALLOWLIST))?;
+        let Some(row) = stored else {
+            return Ok(());
+        };
+
+        match serde_json::from_str::<ToolAllowlist>(&row.value) {
+            Ok(allowlist) => self.registry.set_allowlist(normalize_allowlist(allowlist)),
+            Err(err) => {
+                warn!(
+                    target: "app::custom_tools",
+                    error = %err,
+                    "ignoring unreadable tool allowlist"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn validate(&self, definition: &CustomToolDefinition) -> AppResult<()> {
+        if !TOOL_NAME_PATTERN.is_match(&definition.name) {
+            return Err(AppError::validation(
+                "工具名称需以小写字母开头，仅包含小写字母、数字和下划线，长度 3-64",
+            ));
+        }
+        if self.registry.tool_origin(&definition.name) == Some(ToolOrigin::BuiltIn) {
+            return Err(AppError::validation(format!(
+                "工具名称 '{}' 与内置工具冲突",
+                definition.name
+            )));
+        }
+
+        let description_len = definition.description.chars().count();
+        if description_len == 0 || description_len > MAX_DESCRIPTION_CHARS {
+            return Err(AppError::validation(format!(
+                "工具描述不能为空且不超过 {MAX_DESCRIPTION_CHARS} 个字符"
+            )));
+        }
+
+        if definition
+            .parameters
+            .get("type")
+            .and_then(JsonValue::as_str)
+            != Some("object")
+        {
+            return Err(AppError::validation(
+                "工具参数必须是 type 为 object 的 JSON Schema",
+            ));
+        }
+        if let Err(err) = jsonschema::JSONSchema::compile(&definition.parameters) {
+            return Err(AppError::validation(format!("工具参数 Schema 无效: {err}")));
+        }
+
+        if let Some(fixed) = &definition.fixed_arguments {
+            if !fixed.is_object() {
+                return Err(AppError::validation("固定参数必须是 JSON 对象"));
+            }
+        }
+
+        if self.registry.tool_origin(&definition.target_tool) != Some(ToolOrigin::BuiltIn) {
+            return Err(AppError::validation(format!(
+                "目标工具 '{}' 不存在或不是内置工具",
+                definition.target_tool
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn install(&self, definition: &CustomToolDefinition) -> AppResult<()> {
+        let target = self
+            .registry
+            .tool_handler(&definition.target_tool)
+            .ok_or_else(AppError::not_found)?;
+        let target_schema = self
+            .registry
+            .tool_parameters(&definition.target_tool)
+            .ok_or_else(AppError::not_found)?;
+
+        let handler = forwarding_handler(
+            definition.target_tool.clone(),
+            target,
+            target_schema,
+            definition.fixed_arguments.clone(),
+        );
+
+        self.registry.register_runtime_tool(
+            definition.name.clone(),
+            definition.description.clone(),
+            definition.parameters.clone(),
+            handler,
+        )
+    }
+}
+
+fn normalize_definition(mut definition: CustomToolDefinition) -> CustomToolDefinition {
+    definition.name = definition.name.trim().to_string();
+    definition.description = definition.description.trim().to_string();
+    definition.target_tool = definition.target_tool.trim().to_string();
+    if definition
+        .fixed_arguments
+        .as_ref()
+        .is_some_and(JsonValue::is_null)
+    {
+        definition.fixed_arguments = None;
+    }
+    definition
+}
+
+fn normalize_allowlist(allowlist: ToolAllowlist) -> ToolAllowlist {
+    let normalize_names = |names: BTreeSet<String>| -> BTreeSet<String> {
+        names
+            .into_iter()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect()
+    };
+
+    ToolAllowlist {
+        global: allowlist.global.map(normalize_names),
+        conversations: allowlist
+            .conversations
+            .into_iter()
+            .map(|(id, names)| (id.trim().to_string(), normalize_names(names)))
+            .filter(|(id, _)| !id.is_empty())
+            .collect(),
+    }
+}
+
+/// Builds a handler that merges fixed arguments over the caller's, checks the
+/// result against the target tool's schema and forwards the call.
+fn forwarding_handler(
+    target_name: String,
+    target: ToolHandler,
+    target_schema: JsonValue,
+    fixed_arguments: Option<JsonValue>,
+) -> ToolHandler {
+    Arc::new(move |args: JsonValue| {
+        let target_name = target_name.clone();
+        let target = Arc::clone(&target);
+        let target_schema = target_schema.clone();
+        let fixed_arguments = fixed_arguments.clone();
+        Box::pin(async move {
+            let merged = merge_arguments(args, fixed_arguments.as_ref());
+            validate_forwarded(&target_name, &target_schema, &merged)?;
+            target(merged).await
+        }) as Pin<Box<dyn Future<Output = AppResult<JsonValue>> + Send>>
+    })
+}
+
+fn merge_arguments(args: JsonValue, fixed: Option<&JsonValue>) -> JsonValue {
+    let mut merged = match args {
+        JsonValue::Object(map) => map,
+        _ => serde_json::Map::new(),
+    };
+    if let Some(JsonValue::Object(fixed)) = fixed {
+        for (key, value) in fixed {
+            merged.insert(key.clone(), value.clone());
+        }
+    }
+    JsonValue::Object(merged)
+}
+
+fn validate_forwarded(target_name: &str, schema: &JsonValue, args: &JsonValue) -> AppResult<()> {
+    let compiled = jsonschema::JSONSchema::compile(schema).map_err(|err| {
+        AppError::tool_execution_failed(target_name, format!("目标工具 Schema 无效: {err}"))
+    })?;
+
+    if let Err(errors) = compiled.validate(args) {
+        let messages: Vec<String> = errors.map(|err| err.to_string()).collect();
+        return Err(AppError::invalid_tool_call(
+            target_name,
+            messages.join("; "),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn fixed_arguments_override_caller_arguments() {
+        let merged = merge_arguments(
+            json!({"title": "Read RFC 9110", "tags": ["misc"]}),
+            Some(&json!({"tags": ["reading"], "priority": "low"})),
+        );
+
+        assert_eq!(
+            merged,
+            json!({"title": "Read RFC 9110", "tags": ["reading"], "priority": "low"})
+        );
+        assert_eq!(merge_arguments(json!("oops"), None), json!({}));
+    }
+
+    #[test]
+    fn forwarded_arguments_are_checked_against_target_schema() {
+        let schema = json!({
+            "type": "object",
+            "properties": {"title": {"type": "string"}},
+            "required": ["title"]
+        });
+
+        assert!(validate_forwarded("create_task", &schema, &json!({"title": "ok"})).is_ok());
+        assert!(validate_forwarded("create_task", &schema, &json!({})).is_err());
+    }
+}
@@ -0,0 +1,298 @@
+//! Custom dashboard KPIs. A metric is a small arithmetic expression (`+ - * /`,
+//! parentheses, numbers) over the aggregates of [`TodaySnapshot`], evaluated
+//! here so the dashboard only renders the numbers.
+
+use std::collections::HashSet;
+
+use crate::error::{AppError, AppResult};
+use crate::models::analytics::TodaySnapshot;
+use crate::models::settings::{DashboardMetric, DashboardMetricValue};
+
+pub const MAX_CUSTOM_METRICS: usize = 12;
+const MAX_LABEL_CHARS: usize = 40;
+const MAX_EXPRESSION_CHARS: usize = 200;
+/// Nesting limit for parentheses and unary minus.
+const MAX_DEPTH: usize = 16;
+
+/// Names an expression may use.
+pub const METRIC_VARIABLES: [&str; 7] = [
+    "completed_tasks",
+    "open_due_today",
+    "overdue",
+    "focus_minutes",
+    "current_focus_minutes",
+    "remaining_planned_minutes",
+    "remaining_blocks",
+];
+
+fn variable(snapshot: &TodaySnapshot, name: &str) -> Option<f64> {
+    let value = match name {
+        "completed_tasks" => snapshot.completed_so_far,
+        "open_due_today" => snapshot.open_due_today,
+        "overdue" => snapshot.overdue,
+        "focus_minutes" => snapshot.focus_minutes_so_far,
+        "current_focus_minutes" => snapshot.current_focus_minutes,
+        "remaining_planned_minutes" => snapshot.remaining_planned_minutes,
+        "remaining_blocks" => snapshot.remaining_blocks,
+        _ => return None,
+    };
+    Some(value as f64)
+}
+
+/// Checks and tidies metric definitions before they are stored.
+pub fn validate_metrics(metrics: Vec<DashboardMetric>) -> AppResult<Vec<DashboardMetric>> {
+    if metrics.len() > MAX_CUSTOM_METRICS {
+        return Err(AppError::validation(format!(
+            "自定义指标不能超过 {MAX_CUSTOM_METRICS} 个"
+        )));
+    }
+    let mut seen = HashSet::new();
+    metrics
+        .into_iter()
+        .map(|metric| {
+            let id = metric.id.trim().to_lowercase();
+            if id.is_empty() {
+                return Err(AppError::validation("指标 ID 不能为空"));
+            }
+            if !seen.insert(id.clone()) {
+                return Err(AppError::validation(format!("指标 ID 重复: {id}")));
+            }
+            let label = metric.label.trim().to_string();
+            if label.is_empty() || label.chars().count() > MAX_LABEL_CHARS {
+                return Err(AppError::validation(format!(
+                    "指标名称需为 1-{MAX_LABEL_CHARS} 个字符"
+                )));
+            }
+            let expression = metric.expression.trim().to_string();
+            if expression.chars().count() > MAX_EXPRESSION_CHARS {
+                return Err(AppError::validation(format!(
+                    "指标表达式不能超过 {MAX_EXPRESSION_CHARS} 个字符"
+                )));
+            }
+            parse(&expression).map_err(|message| {
+                AppError::validation(format!("指标 {id} 的表达式无效: {message}"))
+            })?;
+            Ok(DashboardMetric {
+                id,
+                label,
+                expression,
+            })
+        })
+        .collect()
+}
+
+/// Today's value of each metric. Definitions that no longer parse are
+/// skipped rather than failing the whole dashboard.
+pub fn evaluate_metrics(
+    metrics: &[DashboardMetric],
+    snapshot: &TodaySnapshot,
+) -> Vec<DashboardMetricValue> {
+    metrics
+        .iter()
+        .filter_map(|metric| {
+            let expr = parse(&metric.expression).ok()?;
+            let value = expr.eval(snapshot).filter(|value| value.is_finite());
+            Some(DashboardMetricValue {
+                id: metric.id.clone(),
+                value,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Variable(&'static str),
+    Negate(Box<Expr>),
+    Binary(Box<Expr>, char, Box<Expr>),
+}
+
+impl Expr {
+    fn eval(&self, snapshot: &TodaySnapshot) -> Option<f64> {
+        match self {
+            Expr::Number(value) => Some(*value),
+            Expr::Variable(name) => variable(snapshot, name),
+            Expr::Negate(inner) => inner.eval(snapshot).map(|value| -value),
+            Expr::Binary(left, op, right) => {
+                let (left, right) = (left.eval(snapshot)?, right.eval(snapshot)?);
+                match op {
+                    '+' => Some(left + right),
+                    '-' => Some(left - right),
+                    '*' => Some(left * right),
+                    _ if right == 0.0 => None,
+                    _ => Some(left / right),
+                }
+            }
+        }
+    }
+}
+
+fn parse(expression: &str) -> Result<Expr, String> {
+    let mut parser = Parser {
+        chars: expression.chars().collect(),
+        index: 0,
+        depth: 0,
+    };
+    let expr = parser.sum()?;
+    parser.skip_whitespace();
+    match parser.peek() {
+        None => Ok(expr),
+        Some(ch) => Err(format!("多余的字符 '{ch}'")),
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    index: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.index += 1;
+        }
+    }
+
+    /// The next operator among `ops`, consumed.
+    fn operator(&mut self, ops: &[char]) -> Option<char> {
+        self.skip_whitespace();
+        let op = self.peek().filter(|ch| ops.contains(ch))?;
+        self.index += 1;
+        Some(op)
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut expr = self.product()?;
+        while let Some(op) = self.operator(&['+', '-']) {
+            expr = Expr::Binary(Box::new(expr), op, Box::new(self.product()?));
+        }
+        Ok(expr)
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        while let Some(op) = self.operator(&['*', '/']) {
+            expr = Expr::Binary(Box::new(expr), op, Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err("嵌套层级过深".to_string());
+        }
+        let expr = if self.operator(&['-']).is_some() {
+            Expr::Negate(Box::new(self.unary()?))
+        } else {
+            self.atom()?
+        };
+        self.depth -= 1;
+        Ok(expr)
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        self.skip_whitespace();
+        let start = self.index;
+        match self.peek() {
+            Some('(') => {
+                self.index += 1;
+                let expr = self.sum()?;
+                self.operator(&[')'])
+                    .map(|_| expr)
+                    .ok_or_else(|| "缺少右括号".to_string())
+            }
+            Some(ch) if ch.is_ascii_digit() || ch == '.' => {
+                while self
+                    .peek()
+                    .is_some_and(|ch| ch.is_ascii_digit() || ch == '.')
+                {
+                    self.index += 1;
+                }
+                let literal: String = self.chars[start..self.index].iter().collect();
+                literal
+                    .parse()
+                    .map(Expr::Number)
+                    .map_err(|_| format!("无效的数字 '{literal}'"))
+            }
+            Some(ch) if ch.is_ascii_alphabetic() || ch == '_' => {
+                while self
+                    .peek()
+                    .is_some_and(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+                {
+                    self.index += 1;
+                }
+                let name: String = self.chars[start..self.index].iter().collect();
+                METRIC_VARIABLES
+                    .into_iter()
+                    .find(|known| known.eq_ignore_ascii_case(&name))
+                    .map(Expr::Variable)
+                    .ok_or_else(|| format!("未知的变量 '{name}'"))
+            }
+            Some(ch) => Err(format!("意外的字符 '{ch}'")),
+            None => Err("表达式不完整".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metric(id: &str, expression: &str) -> DashboardMetric {
+        DashboardMetric {
+            id: id.to_string(),
+            label: id.to_string(),
+            expression: expression.to_string(),
+        }
+    }
+
+    #[test]
+    fn evaluates_expressions_over_today_aggregates() {
+        let snapshot = TodaySnapshot {
+            completed_so_far: 3,
+            overdue: 2,
+            focus_minutes_so_far: 90,
+            ..TodaySnapshot::default()
+        };
+        let metrics = [
+            metric("per-task", "focus_minutes / (completed_tasks + 1)"),
+            metric("net", "-overdue + 2 * completed_tasks - 1.5"),
+            metric("ratio", "completed_tasks / remaining_blocks"),
+        ];
+
+        let values = evaluate_metrics(&metrics, &snapshot);
+        assert_eq!(values[0].value, Some(22.5));
+        assert_eq!(values[1].value, Some(2.5));
+        assert_eq!(values[2].value, None);
+    }
+
+    #[test]
+    fn rejects_invalid_definitions() {
+        let valid = validate_metrics(vec![metric(" Focus ", "FOCUS_MINUTES / 60")]).unwrap();
+        assert_eq!(valid[0].id, "focus");
+
+        for expression in [
+            "",
+            "focus_minutes +",
+            "(overdue",
+            "overdue)",
+            "streak * 2",
+            "1..2",
+            "overdue; 1",
+        ] {
+            assert!(
+                validate_metrics(vec![metric("m", expression)]).is_err(),
+                "{expression}"
+            );
+        }
+        assert!(validate_metrics(vec![metric("m", "1"), metric("M", "2")]).is_err());
+        assert!(validate_metrics(vec![metric("m", &format!("{}1", "-".repeat(40)))]).is_err());
+    }
+}
@@ -0,0 +1,302 @@
+//! Data for the individual dashboard widgets. Each widget is computed on its
+//! own and cached for its own TTL, so a refresh tick only recomputes what
+//! went stale instead of the whole analytics overview. Cached data is also
+//! dropped as soon as tasks, goals or blocks change.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+
+use crate::db::repositories::change_repository::ChangeRepository;
+use crate::db::repositories::task_repository::TaskRepository;
+use crate::db::DbPool;
+use crate::error::AppResult;
+use crate::models::analytics::TodaySnapshot;
+use crate::models::dashboard::{
+    DashboardWidget, GoalProgressSummary, UpcomingDeadline, WellnessWidgetState,
+};
+use crate::models::goal::GoalStatus;
+use crate::models::task::TaskRecord;
+use crate::services::analytics_service::AnalyticsService;
+use crate::services::goal_service::GoalService;
+use crate::services::wellness_service::WellnessService;
+
+const TODAY_TTL: Duration = Duration::from_secs(30);
+const DEADLINES_TTL: Duration = Duration::from_secs(60);
+const GOALS_TTL: Duration = Duration::from_secs(5 * 60);
+/// Short because focus sessions live in memory and do not bump the data
+/// revision.
+const WELLNESS_TTL: Duration = Duration::from_secs(15);
+
+const DEADLINE_WINDOW_DAYS: i64 = 7;
+const MAX_DEADLINES: usize = 10;
+const MAX_GOALS: usize = 6;
+
+pub struct DashboardWidgetService {
+    db: DbPool,
+    analytics: Arc<AnalyticsService>,
+    goals: Arc<GoalService>,
+    wellness: Arc<WellnessService>,
+    today: WidgetCache<TodaySnapshot>,
+    deadlines: WidgetCache<Vec<UpcomingDeadline>>,
+    goal_progress: WidgetCache<Vec<GoalProgressSummary>>,
+    wellness_state: WidgetCache<WellnessWidgetState>,
+}
+
+impl DashboardWidgetService {
+    pub fn new(
+        db: DbPool,
+        analytics: Arc<AnalyticsService>,
+        goals: Arc<GoalService>,
+        wellness: Arc<WellnessService>,
+    ) -> Self {
+        Self {
+            db,
+            analytics,
+            goals,
+            wellness,
+            today: WidgetCache::new(TODAY_TTL),
+            deadlines: WidgetCache::new(DEADLINES_TTL),
+            goal_progress: WidgetCache::new(GOALS_TTL),
+            wellness_state: WidgetCache::new(WELLNESS_TTL),
+        }
+    }
+
+    pub fn today_summary(&self, refresh: bool) -> AppResult<DashboardWidget<TodaySnapshot>> {
+        let revision = self.revision()?;
+        self.today
+            .get_or_load(revision, refresh, || self.analytics.today_snapshot())
+    }
+
+    pub fn upcoming_deadlines(
+        &self,
+        refresh: bool,
+    ) -> AppResult<DashboardWidget<Vec<UpcomingDeadline>>> {
+        let revision = self.revision()?;
+        self.deadlines.get_or_load(revision, refresh, || {
+            let now = Utc::now();
+            let until = (now + ChronoDuration::days(DEADLINE_WINDOW_DAYS)).to_rfc3339();
+            let tasks = self
+                .db
+                .with_read_connection(|conn| {
+                    TaskRepository::list_open_due_before(conn, &until, MAX_DEADLINES)
+                })?
+                .into_iter()
+                .map(|row| row.into_record())
+                .collect::<AppResult<Vec<_>>>()?;
+            Ok(deadlines(tasks, now))
+        })
+    }
+
+    pub fn goal_progress(
+        &self,
+        refresh: bool,
+    ) -> AppResult<DashboardWidget<Vec<GoalProgressSummary>>> {
+        let revision = self.revision()?;
+        self.goal_progress.get_or_load(revision, refresh, || {
+            let mut goals: Vec<_> = self
+                .goals
+                .list_goals(None)?
+                .into_iter()
+                .filter(|goal| {
+                    matches!(goal.status, GoalStatus::NotStarted | GoalStatus::InProgress)
+                })
+                .collect();
+            goals.sort_by_key(|goal| (goal.target_date.is_none(), goal.target_date));
+            goals
+                .into_iter()
+                .take(MAX_GOALS)
+                .map(|goal| {
+                    let progress = self.goals.get_goal_with_progress(&goal.id)?;
+                    Ok(GoalProgressSummary {
+                        goal_id: goal.id,
+                        title: goal.title,
+                        status: goal.status,
+                        progress_percentage: progress.progress_percentage,
+                        is_on_track: progress.is_on_track,
+                        target_date: goal.target_date,
+                        days_until_target: progress.days_until_target,
+                    })
+                })
+                .collect()
+        })
+    }
+
+    pub fn wellness_state(&self, refresh: bool) -> AppResult<DashboardWidget<WellnessWidgetState>> {
+        let revision = self.revision()?;
+        self.wellness_state.get_or_load(revision, refresh, || {
+            Ok(WellnessWidgetState {
+                focus_session: self.wellness.current_focus_session()?,
+                pending_nudge: self.wellness.get_pending_nudge()?,
+                pending_idle_adjustments: self.wellness.pending_idle_adjustments()?.len(),
+            })
+        })
+    }
+
+    /// Drops every cached widget, e.g. after data was wiped directly.
+    pub fn invalidate(&self) {
+        self.today.clear();
+        self.deadlines.clear();
+        self.goal_progress.clear();
+        self.wellness_state.clear();
+    }
+
+    fn revision(&self) -> AppResult<i64> {
+        self.db
+            .with_read_connection(ChangeRepository::current_revision)
+    }
+}
+
+fn deadlines(tasks: Vec<TaskRecord>, now: DateTime<Utc>) -> Vec<UpcomingDeadline> {
+    tasks
+        .into_iter()
+        .filter_map(|task| {
+            let due_at = task.due_at?;
+            let overdue = DateTime::parse_from_rfc3339(&due_at).is_ok_and(|due| due < now);
+            Some(UpcomingDeadline {
+                task_id: task.id,
+                title: task.title,
+                due_at,
+                priority: task.priority,
+                status: task.status,
+                overdue,
+            })
+        })
+        .collect()
+}
+
+/// One widget's latest data, reused until its TTL runs out or the data
+/// revision moves on.
+struct WidgetCache<T> {
+    ttl: Duration,
+    entry: Mutex<Option<CachedWidget<T>>>,
+}
+
+struct CachedWidget<T> {
+    data: T,
+    revision: i64,
+    built_at: Instant,
+    generated_at: String,
+}
+
+impl<T: Clone> WidgetCache<T> {
+    fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entry: Mutex::new(None),
+        }
+    }
+
+    /// The lock is held while loading so concurrent refreshes of the same
+    /// widget compute it once.
+    fn get_or_load(
+        &self,
+        revision: i64,
+        refresh: bool,
+        load: impl FnOnce() -> AppResult<T>,
+    ) -> AppResult<DashboardWidget<T>> {
+        let mut entry = self.entry.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(cached) = entry.as_ref().filter(|cached| {
+            !refresh && cached.revision == revision && cached.built_at.elapsed() < self.ttl
+        }) {
+            return Ok(self.widget(cached, true));
+        }
+
+        let cached = entry.insert(CachedWidget {
+            data: load()?,
+            revision,
+            built_at: Instant::now(),
+            generated_at: Utc::now().to_rfc3339(),
+        });
+        Ok(self.widget(cached, false))
+    }
+
+    fn widget(&self, cached: &CachedWidget<T>, hit: bool) -> DashboardWidget<T> {
+        DashboardWidget {
+            data: cached.data.clone(),
+            generated_at: cached.generated_at.clone(),
+            ttl_seconds: self.ttl.as_secs(),
+            cached: hit,
+        }
+    }
+
+    fn clear(&self) {
+        *self.entry.lock().unwrap_or_else(|err| err.into_inner()) = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AppError;
+    use crate::models::task::TaskCreateInput;
+    use crate::services::task_service::TaskService;
+
+    #[test]
+    fn widget_cache_reloads_on_refresh_revision_or_expiry() {
+        let cache = WidgetCache::new(Duration::from_secs(60));
+        let mut loads = 0;
+        let mut load = |revision: i64, refresh: bool| {
+            cache
+                .get_or_load(revision, refresh, || {
+                    loads += 1;
+                    Ok(loads)
+                })
+                .unwrap()
+        };
+
+        let first = load(1, false);
+        assert_eq!((first.data, first.cached), (1, false));
+        let hit = load(1, false);
+        assert_eq!((hit.data, hit.cached), (1, true));
+        assert_eq!(hit.generated_at, first.generated_at);
+        assert_eq!(load(2, false).data, 2);
+        assert_eq!(load(2, true).data, 3);
+
+        let expired = WidgetCache::new(Duration::ZERO);
+        expired.get_or_load(1, false, || Ok(1)).unwrap();
+        assert!(!expired.get_or_load(1, false, || Ok(2)).unwrap().cached);
+
+        let err = expired.get_or_load(1, true, || Err(AppError::validation("boom")));
+        assert!(err.is_err());
+        expired.clear();
+        assert!(!expired.get_or_load(1, false, || Ok(3)).unwrap().cached);
+    }
+
+    #[test]
+    fn deadlines_list_open_tasks_in_the_window() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = DbPool::new(dir.path().join("widgets.sqlite")).unwrap();
+        let tasks = TaskService::new(db.clone());
+        let now = Utc::now();
+        for (title, status, due_in_hours) in [
+            ("later", "todo", 24 * 30),
+            ("soon", "todo", 3),
+            ("late", "in_progress", -2),
+            ("finished", "done", 1),
+        ] {
+            tasks
+                .create_task(TaskCreateInput {
+                    title: title.to_string(),
+                    status: Some(status.to_string()),
+                    due_at: Some((now + ChronoDuration::hours(due_in_hours)).to_rfc3339()),
+                    ..TaskCreateInput::default()
+                })
+                .unwrap();
+        }
+
+        let until = (now + ChronoDuration::days(DEADLINE_WINDOW_DAYS)).to_rfc3339();
+        let records = db
+            .with_read_connection(|conn| TaskRepository::list_open_due_before(conn, &until, 10))
+            .unwrap()
+            .into_iter()
+            .map(|row| row.into_record().unwrap())
+            .collect();
+        let entries = deadlines(records, now);
+        let titles: Vec<_> = entries.iter().map(|entry| entry.title.as_str()).collect();
+        assert_eq!(titles, vec!["late", "soon"]);
+        assert!(entries[0].overdue);
+        assert!(!entries[1].overdue);
+    }
+}
@@ -0,0 +1,945 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::Utc;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rusqlite::types::Value;
+use rusqlite::Connection;
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::db::repositories::data_export_repository::{
+    DataExportRepository, ForeignKey, TableColumn,
+};
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::data_export::{
+    DataDomain, DataEraseResult, DataExportResult, DataImportEntry, DataImportParams,
+    DataImportReport, ImportConflictMode, DATA_EXPORT_FORMAT_VERSION,
+};
+use crate::services::memory_service::MemoryService;
+use crate::services::settings_service::{notify_settings_changed, SECRET_SETTING_KEYS};
+use crate::utils::files::write_atomic_with;
+use crate::utils::paths::{sanitize_file_name, sanitize_relative_path};
+
+const EXPORT_PREFIX: &str = "cognical-export";
+const DATA_FILE: &str = "cognical-data.json";
+const README_FILE: &str = "README.md";
+const MEMORY_ARCHIVE_DIR: &str = "memory";
+/// Typed exactly by the user before `erase_all` deletes anything.
+pub const ERASE_CONFIRMATION: &str = "DELETE ALL MY DATA";
+/// Schema bookkeeping kept by an erase so migrations do not run again.
+const ERASE_KEPT_TABLES: [&str; 1] = ["migration_history"];
+
+/// User-owned tables in export (and import) order with their domain and the
+/// description written to the archive README. Parents come before the
+/// tables referencing them. Caches, job queues and change logs are left out.
+const EXPORT_TABLES: [(&str, DataDomain, &str); 29] = [
+    ("projects", DataDomain::Projects, "项目"),
+    ("tasks", DataDomain::Tasks, "任务"),
+    ("goals", DataDomain::Goals, "目标"),
+    (
+        "goal_task_associations",
+        DataDomain::Goals,
+        "目标与任务的关联",
+    ),
+    ("goal_checkins", DataDomain::Goals, "目标打卡记录"),
+    ("task_dependencies", DataDomain::Tasks, "任务依赖关系"),
+    (
+        "recurring_task_templates",
+        DataDomain::Tasks,
+        "重复任务模板",
+    ),
+    ("task_instances", DataDomain::Tasks, "重复任务生成的实例"),
+    ("later_items", DataDomain::Later, "稍后处理清单"),
+    ("day_logs", DataDomain::DayLogs, "每日收尾记录"),
+    ("planning_sessions", DataDomain::Planning, "规划会话"),
+    ("planning_options", DataDomain::Planning, "规划方案"),
+    ("planning_time_blocks", DataDomain::Planning, "规划时间块"),
+    ("block_reminders", DataDomain::Planning, "时间块提醒"),
+    ("schedule_preferences", DataDomain::Planning, "排程偏好配置"),
+    ("agent_suggestions", DataDomain::Suggestions, "主动建议"),
+    ("analytics_snapshots", DataDomain::Analytics, "每日分析快照"),
+    ("analytics_anomalies", DataDomain::Analytics, "分析异常记录"),
+    ("productivity_scores", DataDomain::Analytics, "效率得分"),
+    ("wellness_events", DataDomain::Wellness, "休息提醒及响应"),
+    (
+        "focus_idle_adjustments",
+        DataDomain::Wellness,
+        "专注空闲时长调整",
+    ),
+    ("workload_forecasts", DataDomain::Analytics, "工作量预测"),
+    ("ai_feedback", DataDomain::Feedback, "AI 反馈"),
+    ("community_exports", DataDomain::Feedback, "社区导出记录"),
+    ("conversations", DataDomain::Conversations, "对话记录"),
+    ("custom_tools", DataDomain::Customization, "自定义工具"),
+    (
+        "prompt_overrides",
+        DataDomain::Customization,
+        "自定义提示词",
+    ),
+    (
+        SETTINGS_TABLE,
+        DataDomain::Settings,
+        "应用设置（不含 API 密钥）",
+    ),
+    ("feature_flags", DataDomain::Settings, "功能开关"),
+];
+const SETTINGS_TABLE: &str = "app_settings";
+/// References added after their tables existed and therefore not declared
+/// as foreign keys, as (table, column, parent table). Duplicated parents are
+/// remapped in these columns too.
+const UNDECLARED_REFERENCES: [(&str, &str, &str); 3] = [
+    ("tasks", "project_id", "projects"),
+    ("goals", "project_id", "projects"),
+    ("planning_sessions", "project_id", "projects"),
+];
+const MAX_IMPORT_WARNINGS: usize = 20;
+/// Removing an orphaned row can orphan the rows referencing it in turn.
+const MAX_ORPHAN_PASSES: usize = 5;
+
+/// User-owned tables and their descriptions, in export order.
+pub fn user_tables() -> impl Iterator<Item = (&'static str, &'static str)> {
+    EXPORT_TABLES
+        .iter()
+        .map(|(table, _, description)| (*table, *description))
+}
+
+pub struct DataExportService {
+    db: DbPool,
+    memory: Arc<MemoryService>,
+    reports_dir: PathBuf,
+    exports_dir: PathBuf,
+}
+
+impl DataExportService {
+    pub fn new(db: DbPool, memory: Arc<MemoryService>, reports_dir: PathBuf) -> Self {
+        let exports_dir = default_exports_dir(db.path());
+        Self {
+            db,
+            memory,
+            reports_dir,
+            exports_dir,
+        }
+    }
+
+    /// Packages every user-owned record, settings without secrets and the
+    /// memory files into one `.tar.gz` archive in the exports directory.
+    pub fn export_all(&self) -> AppResult<DataExportResult> {
+        let now = Utc::now();
+        let (tables, counts) = self.db.with_read_connection(|conn| {
+            let existing = DataExportRepository::list_tables(conn)?;
+            let mut tables = JsonMap::new();
+            let mut counts = BTreeMap::new();
+            let names = EXPORT_TABLES.iter().map(|(table, _, _)| *table);
+            for table in names.filter(|table| existing.iter().any(|name| name == table)) {
+                let mut rows = DataExportRepository::dump_table(conn, table)?;
+                if table == SETTINGS_TABLE {
+                    rows.retain(|row| {
+                        row.get("key")
+                            .and_then(JsonValue::as_str)
+                            .is_none_or(|key| !SECRET_SETTING_KEYS.contains(&key))
+                    });
+                }
+                counts.insert(table.to_string(), rows.len());
+                tables.insert(table.to_string(), JsonValue::from(rows));
+            }
+            Ok((tables, counts))
+        })?;
+
+        let data = json!({
+            "formatVersion": DATA_EXPORT_FORMAT_VERSION,
+            "appVersion": env!("CARGO_PKG_VERSION"),
+            "generatedAt": now.to_rfc3339(),
+            "tables": tables,
+        });
+        let memory_dir = self.memory.memory_dir().to_path_buf();
+        let memory_files = self.memory.data_files()?;
+
+        fs::create_dir_all(&self.exports_dir)?;
+        let path = self.exports_dir.join(sanitize_file_name(&format!(
+            "{EXPORT_PREFIX}-{}.tar.gz",
+            now.format("%Y%m%d-%H%M%S")
+        )));
+        let checksum = write_atomic_with(&path, |file| {
+            let mut archive = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+            append_bytes(&mut archive, DATA_FILE, &serde_json::to_vec_pretty(&data)?)?;
+            append_bytes(
+                &mut archive,
+                README_FILE,
+                render_readme(&counts, memory_files.len()).as_bytes(),
+            )?;
+            for file in &memory_files {
+                let relative = file.strip_prefix(&memory_dir).unwrap_or(file);
+                archive
+                    .append_path_with_name(file, Path::new(MEMORY_ARCHIVE_DIR).join(relative))?;
+            }
+            let mut file = archive.into_inner()?.finish()?;
+            file.flush()?;
+            Ok(file)
+        })?;
+
+        info!(
+            target: "app::data_export",
+            path = %path.display(),
+            tables = counts.len(),
+            memory_files = memory_files.len(),
+            "full data export written"
+        );
+
+        Ok(DataExportResult {
+            file_path: path.to_string_lossy().into_owned(),
+            checksum,
+            format_version: DATA_EXPORT_FORMAT_VERSION,
+            tables: counts,
+            memory_files: memory_files.len(),
+            generated_at: now.to_rfc3339(),
+        })
+    }
+
+    /// Restores the chosen domains from a full export. All rows are written
+    /// in one transaction; a dry run performs the same work, reports it and
+    /// rolls everything back.
+    pub fn import(&self, params: DataImportParams) -> AppResult<DataImportReport> {
+        if params.domains.is_empty() {
+            return Err(AppError::validation("请至少选择一个要导入的数据类别"));
+        }
+        let archive = read_archive(Path::new(&params.archive_path))?;
+        let format_version = archive
+            .data
+            .get("formatVersion")
+            .and_then(JsonValue::as_u64)
+            .and_then(|version| u32::try_from(version).ok())
+            .filter(|version| (1..=DATA_EXPORT_FORMAT_VERSION).contains(version))
+            .ok_or_else(|| AppError::validation("不支持的导出文件格式版本"))?;
+        let Some(tables) = archive.data.get("tables").and_then(JsonValue::as_object) else {
+            return Err(AppError::validation("导出文件中缺少数据表"));
+        };
+        let domains: HashSet<DataDomain> = params.domains.iter().copied().collect();
+
+        let (mut entries, mut warnings) = self.db.with_connection(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            tx.pragma_update(None, "defer_foreign_keys", true)?;
+            let existing = DataExportRepository::list_tables(&tx)?;
+            let mut importer = TableImporter::new(&tx, params.on_conflict);
+            let mut entries = Vec::new();
+            for (table, domain, _) in EXPORT_TABLES {
+                if !domains.contains(&domain) {
+                    continue;
+                }
+                let Some(rows) = tables.get(table).and_then(JsonValue::as_array) else {
+                    continue;
+                };
+                if !existing.iter().any(|name| name == table) {
+                    importer.warn(format!("当前数据库中没有 {table} 表，已跳过"));
+                    continue;
+                }
+                entries.push(importer.import_table(table, rows)?);
+            }
+            importer.drop_orphans(&mut entries)?;
+            let warnings = importer.warnings;
+
+            if params.dry_run {
+                tx.rollback()?;
+            } else {
+                tx.commit()?;
+            }
+            Ok((entries, warnings))
+        })?;
+        // Imported settings and prompt overrides must reach cached configuration
+        if !params.dry_run {
+            notify_settings_changed();
+        }
+
+        if domains.contains(&DataDomain::Memory) {
+            entries.push(self.import_memory(
+                &archive.memory_files,
+                params.on_conflict,
+                params.dry_run,
+                &mut warnings,
+            )?);
+        }
+
+        if !params.dry_run {
+            info!(
+                target: "app::data_export",
+                domains = domains.len(),
+                rows = entries.iter().map(|entry| entry.rows).sum::<usize>(),
+                "data imported"
+            );
+        }
+
+        Ok(DataImportReport {
+            dry_run: params.dry_run,
+            format_version,
+            on_conflict: params.on_conflict,
+            entries,
+            warnings,
+            imported_at: Utc::now().to_rfc3339(),
+        })
+    }
+
+    /// Memory documents carry their id inside the file, so duplicate mode
+    /// skips files that already exist instead of copying them.
+    fn import_memory(
+        &self,
+        files: &[(PathBuf, Vec<u8>)],
+        mode: ImportConflictMode,
+        dry_run: bool,
+        warnings: &mut Vec<String>,
+    ) -> AppResult<DataImportEntry> {
+        let memory_dir = self.memory.memory_dir();
+        let mut entry = DataImportEntry {
+            table: MEMORY_ARCHIVE_DIR.to_string(),
+            rows: files.len(),
+            ..Default::default()
+        };
+        let mut written = 0;
+        for (relative, bytes) in files {
+            let target = memory_dir.join(relative);
+            let overwrite = target.exists();
+            if overwrite && mode != ImportConflictMode::Overwrite {
+                entry.skipped += 1;
+                continue;
+            }
+            if !dry_run {
+                let result = target
+                    .parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|_| fs::write(&target, bytes));
+                if let Err(err) = result {
+                    entry.failed += 1;
+                    push_warning(
+                        warnings,
+                        format!("记忆文件 {} 写入失败: {err}", relative.display()),
+                    );
+                    continue;
+                }
+                written += 1;
+            }
+            if overwrite {
+                entry.overwritten += 1;
+            } else {
+                entry.inserted += 1;
+            }
+        }
+        if written > 0 {
+            self.memory.rebuild_index()?;
+        }
+        Ok(entry)
+    }
+
+    /// Deletes every database row, memory file and report. Earlier full
+    /// exports are kept so the user can erase right after exporting.
+    pub fn erase_all(&self, confirmation: &str) -> AppResult<DataEraseResult> {
+        if confirmation.trim() != ERASE_CONFIRMATION {
+            return Err(AppError::validation(format!(
+                "请输入“{ERASE_CONFIRMATION}”以确认删除全部数据"
+            )));
+        }
+
+        let tables = self.db.with_connection(|conn| {
+            let names: Vec<String> = DataExportRepository::list_tables(conn)?
+                .into_iter()
+                .filter(|table| !ERASE_KEPT_TABLES.contains(&table.as_str()))
+                .collect();
+            let tx = conn.unchecked_transaction()?;
+            tx.pragma_update(None, "defer_foreign_keys", true)?;
+            let mut deleted = BTreeMap::new();
+            for table in &names {
+                deleted.insert(
+                    table.clone(),
+                    DataExportRepository::clear_table(&tx, table)?,
+                );
+            }
+            // Triggers write change-log and staleness rows while other
+            // tables are cleared; a second pass removes those.
+            for table in &names {
+                DataExportRepository::clear_table(&tx, table)?;
+            }
+            tx.commit()?;
+            Ok(deleted)
+        })?;
+        notify_settings_changed();
+
+        let memory_files = self.memory.erase_all()?;
+        let report_files = remove_files_in(&self.reports_dir);
+
+        warn!(
+            target: "app::data_export",
+            rows = tables.values().sum::<usize>(),
+            memory_files,
+            report_files,
+            "all user data erased"
+        );
+
+        Ok(DataEraseResult {
+            tables,
+            memory_files,
+            report_files,
+            erased_at: Utc::now().to_rfc3339(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ImportOutcome {
+    Inserted,
+    Overwritten,
+    Duplicated,
+}
+
+/// Writes exported rows into one transaction, remembering which rows it
+/// wrote and the new ids given to duplicated rows so that rows referencing
+/// them later in the import follow along.
+struct TableImporter<'a> {
+    conn: &'a Connection,
+    mode: ImportConflictMode,
+    /// New id of each duplicated row, keyed by table and original id.
+    remapped: HashMap<(String, String), Value>,
+    /// Rowids written by this import and how, per table.
+    written: HashMap<String, HashMap<i64, ImportOutcome>>,
+    warnings: Vec<String>,
+}
+
+impl<'a> TableImporter<'a> {
+    fn new(conn: &'a Connection, mode: ImportConflictMode) -> Self {
+        Self {
+            conn,
+            mode,
+            remapped: HashMap::new(),
+            written: HashMap::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    fn warn(&mut self, message: String) {
+        push_warning(&mut self.warnings, message);
+    }
+
+    fn import_table(&mut self, table: &str, rows: &[JsonValue]) -> AppResult<DataImportEntry> {
+        let columns = DataExportRepository::table_columns(self.conn, table)?;
+        let mut foreign_keys = DataExportRepository::foreign_keys(self.conn, table)?;
+        foreign_keys.extend(
+            UNDECLARED_REFERENCES
+                .iter()
+                .filter(|(child, _, _)| *child == table)
+                .map(|(_, column, parent_table)| ForeignKey {
+                    column: column.to_string(),
+                    parent_table: parent_table.to_string(),
+                }),
+        );
+        let mut key_columns: Vec<&TableColumn> = columns
+            .iter()
+            .filter(|column| column.primary_key > 0)
+            .collect();
+        key_columns.sort_by_key(|column| column.primary_key);
+        let key_names: Vec<&str> = key_columns
+            .iter()
+            .map(|column| column.name.as_str())
+            .collect();
+        let id_column = match key_columns.as_slice() {
+            [column] if column.name == "id" => Some(*column),
+            _ => None,
+        };
+
+        let mut entry = DataImportEntry {
+            table: table.to_string(),
+            rows: rows.len(),
+            ..Default::default()
+        };
+        let mut unknown_columns = BTreeSet::new();
+        for row in rows {
+            let Some(object) = row.as_object() else {
+                entry.failed += 1;
+                continue;
+            };
+            let mut names = Vec::with_capacity(object.len());
+            let mut values = Vec::with_capacity(object.len());
+            for (name, value) in object {
+                if !columns.iter().any(|column| &column.name == name) {
+                    unknown_columns.insert(name.clone());
+                    continue;
+                }
+                let mut value = json_to_sql(value);
+                if let Some(key) = foreign_keys.iter().find(|key| &key.column == name) {
+                    let original = (key.parent_table.clone(), sql_key(&value));
+                    if let Some(new_id) = self.remapped.get(&original) {
+                        value = new_id.clone();
+                    }
+                }
+                names.push(name.as_str());
+                values.push(value);
+            }
+
+            let key_values: Option<Vec<Value>> = key_names
+                .iter()
+                .map(|key| {
+                    names
+                        .iter()
+                        .position(|name| name == key)
+                        .map(|index| values[index].clone())
+                })
+                .collect();
+            let existing = match key_values {
+                Some(key_values) if !key_names.is_empty() => {
+                    DataExportRepository::find_rowid(self.conn, table, &key_names, &key_values)?
+                }
+                _ => None,
+            };
+
+            let result = match (existing, self.mode, id_column) {
+                (None, _, _) => DataExportRepository::insert_row(self.conn, table, &names, &values)
+                    .map(|rowid| Some((rowid, ImportOutcome::Inserted))),
+                (Some(rowid), ImportConflictMode::Overwrite, _) => {
+                    DataExportRepository::update_row(self.conn, table, rowid, &names, &values)
+                        .map(|_| Some((rowid, ImportOutcome::Overwritten)))
+                }
+                (Some(_), ImportConflictMode::Duplicate, Some(id_column)) => self
+                    .duplicate(table, id_column, &mut names, &mut values)
+                    .map(|rowid| Some((rowid, ImportOutcome::Duplicated))),
+                (Some(_), _, _) => Ok(None),
+            };
+
+            match result {
+                Ok(Some((rowid, outcome))) => {
+                    self.written
+                        .entry(table.to_string())
+                        .or_default()
+                        .insert(rowid, outcome);
+                    *outcome_counter(&mut entry, outcome) += 1;
+                }
+                Ok(None) => entry.skipped += 1,
+                Err(err) => {
+                    entry.failed += 1;
+                    self.warn(format!("{table} 中有一行导入失败: {err}"));
+                }
+            }
+        }
+
+        if !unknown_columns.is_empty() {
+            let unknown = unknown_columns.into_iter().collect::<Vec<_>>().join(", ");
+            self.warn(format!("{table} 中的未知列已忽略: {unknown}"));
+        }
+        Ok(entry)
+    }
+
+    /// Inserts the row under a new id: the next rowid for integer keys, a
+    /// new UUID otherwise.
+    fn duplicate(
+        &mut self,
+        table: &str,
+        id_column: &TableColumn,
+        names: &mut Vec<&str>,
+        values: &mut Vec<Value>,
+    ) -> AppResult<i64> {
+        let Some(index) = names.iter().position(|name| *name == id_column.name) else {
+            return Err(AppError::validation("缺少主键"));
+        };
+        let original = sql_key(&values[index]);
+        let integer_key = id_column.declared_type.to_uppercase().contains("INT");
+        if integer_key {
+            names.remove(index);
+            values.remove(index);
+        } else {
+            values[index] = Value::Text(Uuid::new_v4().to_string());
+        }
+
+        let rowid = DataExportRepository::insert_row(self.conn, table, names, values)?;
+        let new_id = if integer_key {
+            Value::Integer(rowid)
+        } else {
+            values[index].clone()
+        };
+        self.remapped.insert((table.to_string(), original), new_id);
+        Ok(rowid)
+    }
+
+    /// Rows written by this import that reference data found neither in the
+    /// database nor in the import are removed again and counted as failed.
+    fn drop_orphans(&mut self, entries: &mut [DataImportEntry]) -> AppResult<()> {
+        for _ in 0..MAX_ORPHAN_PASSES {
+            let violations = DataExportRepository::foreign_key_violations(self.conn)?;
+            if violations.is_empty() {
+                return Ok(());
+            }
+            let mut dropped = HashSet::new();
+            for (table, rowid) in violations {
+                if !dropped.insert((table.clone(), rowid)) {
+                    continue;
+                }
+                let Some(outcome) = self
+                    .written
+                    .get_mut(&table)
+                    .and_then(|rows| rows.remove(&rowid))
+                else {
+                    return Err(AppError::validation(format!(
+                        "导入后 {table} 中存在无法解析的引用"
+                    )));
+                };
+                DataExportRepository::delete_rowid(self.conn, &table, rowid)?;
+                if let Some(entry) = entries.iter_mut().find(|entry| entry.table == table) {
+                    *outcome_counter(entry, outcome) -= 1;
+                    entry.failed += 1;
+                }
+            }
+            self.warn(format!("{} 行引用了不存在的数据，已跳过", dropped.len()));
+        }
+        Err(AppError::validation("导入数据中的引用关系无法解析"))
+    }
+}
+
+fn outcome_counter(entry: &mut DataImportEntry, outcome: ImportOutcome) -> &mut usize {
+    match outcome {
+        ImportOutcome::Inserted => &mut entry.inserted,
+        ImportOutcome::Overwritten => &mut entry.overwritten,
+        ImportOutcome::Duplicated => &mut entry.duplicated,
+    }
+}
+
+fn push_warning(warnings: &mut Vec<String>, message: String) {
+    if warnings.len() < MAX_IMPORT_WARNINGS {
+        warnings.push(message);
+    }
+}
+
+fn json_to_sql(value: &JsonValue) -> Value {
+    match value {
+        JsonValue::Null => Value::Null,
+        JsonValue::Bool(flag) => Value::Integer(i64::from(*flag)),
+        JsonValue::Number(number) => match number.as_i64() {
+            Some(integer) => Value::Integer(integer),
+            None => Value::Real(number.as_f64().unwrap_or_default()),
+        },
+        JsonValue::String(text) => Value::Text(text.clone()),
+        other => Value::Text(other.to_string()),
+    }
+}
+
+/// Key used to look up remapped ids, matching integer and text ids alike.
+fn sql_key(value: &Value) -> String {
+    match value {
+        Value::Integer(integer) => integer.to_string(),
+        Value::Real(real) => real.to_string(),
+        Value::Text(text) => text.clone(),
+        Value::Null | Value::Blob(_) => String::new(),
+    }
+}
+
+struct ImportArchive {
+    data: JsonValue,
+    /// Paths relative to the memory directory.
+    memory_files: Vec<(PathBuf, Vec<u8>)>,
+}
+
+fn read_archive(path: &Path) -> AppResult<ImportArchive> {
+    let invalid = |_| AppError::validation("导出文件已损坏或不是有效的 tar.gz 文件");
+    let file = File::open(path).map_err(|_| AppError::validation("无法打开导出文件"))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+
+    let mut data = None;
+    let mut memory_files = Vec::new();
+    for entry in archive.entries().map_err(invalid)? {
+        let mut entry = entry.map_err(invalid)?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let entry_path = entry.path().map_err(invalid)?.into_owned();
+        // Entries must stay inside the archive root
+        if !entry_path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(AppError::validation("导出文件包含无效路径"));
+        }
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes).map_err(invalid)?;
+
+        if entry_path == Path::new(DATA_FILE) {
+            data = Some(
+                serde_json::from_slice(&bytes)
+                    .map_err(|_| AppError::validation("导出数据文件无法解析"))?,
+            );
+        } else if let Ok(relative) = entry_path.strip_prefix(MEMORY_ARCHIVE_DIR) {
+            // The archive may come from another platform
+            memory_files.push((sanitize_relative_path(relative), bytes));
+        }
+    }
+
+    let data = data.ok_or_else(|| AppError::validation(format!("导出文件中缺少 {DATA_FILE}")))?;
+    Ok(ImportArchive { data, memory_files })
+}
+
+fn append_bytes<W: Write>(
+    archive: &mut tar::Builder<W>,
+    name: &str,
+    bytes: &[u8],
+) -> AppResult<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(bytes.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    archive.append_data(&mut header, name, bytes)?;
+    Ok(())
+}
+
+fn render_readme(counts: &BTreeMap<String, usize>, memory_files: usize) -> String {
+    let mut content = String::from("# CogniCal 数据导出\n\n");
+    content.push_str(&format!(
+        "- `{DATA_FILE}`：全部数据。`formatVersion` 为格式版本（当前为 {DATA_EXPORT_FORMAT_VERSION}），`tables` 下每个键是一张表，值为该表所有行，每行是以列名为键的对象；二进制列为 Base64 编码。\n"
+    ));
+    content.push_str(&format!(
+        "- `{MEMORY_ARCHIVE_DIR}/`：AI 记忆文档（Markdown）及 `facts.json`，共 {memory_files} 个文件。\n\n"
+    ));
+    content.push_str("## 数据表\n\n");
+    for (table, _, description) in EXPORT_TABLES {
+        if let Some(count) = counts.get(table) {
+            content.push_str(&format!("- `{table}`：{description}，{count} 行\n"));
+        }
+    }
+    content
+}
+
+/// Removes the regular files directly inside `dir` and returns how many
+/// were removed. Failures are logged and skipped.
+fn remove_files_in(dir: &Path) -> usize {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    let mut removed = 0;
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if !path.is_file() {
+            continue;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => removed += 1,
+            Err(err) => warn!(
+                target: "app::data_export",
+                path = %path.display(),
+                error = %err,
+                "failed to remove report"
+            ),
+        }
+    }
+    removed
+}
+
+fn default_exports_dir(db_path: &Path) -> PathBuf {
+    db_path
+        .parent()
+        .map(|dir| dir.join("exports"))
+        .unwrap_or_else(|| std::env::temp_dir().join("cognical"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::repositories::settings_repository::SettingsRepository;
+    use crate::models::project::ProjectCreateInput;
+    use crate::models::task::TaskCreateInput;
+    use crate::services::project_service::ProjectService;
+    use crate::services::settings_service::settings_version;
+    use crate::services::task_service::TaskService;
+
+    #[test]
+    fn export_leaves_out_secrets_and_erase_requires_confirmation() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = DbPool::new(dir.path().join("export.sqlite")).unwrap();
+        let memory = Arc::new(MemoryService::new(dir.path().join("memory")).unwrap());
+        fs::write(dir.path().join("memory").join("note.md"), "# Note").unwrap();
+        let reports_dir = dir.path().join("reports");
+        fs::create_dir_all(&reports_dir).unwrap();
+        fs::write(reports_dir.join("report.md"), "report").unwrap();
+        let service = DataExportService::new(db.clone(), memory, reports_dir.clone());
+
+        TaskService::new(db.clone())
+            .create_task(TaskCreateInput {
+                title: "Write report".to_string(),
+                ..TaskCreateInput::default()
+            })
+            .unwrap();
+        db.with_connection(|conn| {
+            SettingsRepository::upsert(conn, "theme", "dark")?;
+            SettingsRepository::upsert(conn, SECRET_SETTING_KEYS[0], "secret")
+        })
+        .unwrap();
+
+        let export = service.export_all().unwrap();
+        assert_eq!(export.tables.get("tasks"), Some(&1));
+        assert_eq!(export.memory_files, 1);
+        assert_eq!(
+            export.checksum,
+            crate::utils::files::file_checksum(Path::new(&export.file_path)).unwrap()
+        );
+
+        let mut archive = tar::Archive::new(GzDecoder::new(File::open(&export.file_path).unwrap()));
+        let mut names = Vec::new();
+        let mut data = String::new();
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let name = entry.path().unwrap().to_string_lossy().into_owned();
+            if name == DATA_FILE {
+                entry.read_to_string(&mut data).unwrap();
+            }
+            names.push(name);
+        }
+        assert!(names.contains(&README_FILE.to_string()));
+        assert!(names.contains(&"memory/note.md".to_string()));
+        let data: JsonValue = serde_json::from_str(&data).unwrap();
+        let settings = data["tables"][SETTINGS_TABLE].as_array().unwrap();
+        assert!(settings.iter().any(|row| row["key"] == "theme"));
+        assert!(!data.to_string().contains("secret"));
+
+        assert!(service.erase_all("yes").is_err());
+        let version = settings_version();
+        let erased = service.erase_all(ERASE_CONFIRMATION).unwrap();
+        assert!(settings_version() > version);
+        assert_eq!(erased.tables.get("tasks"), Some(&1));
+        assert_eq!(erased.memory_files, 1);
+        assert_eq!(erased.report_files, 1);
+        assert!(TaskService::new(db)
+            .list_tasks_readonly()
+            .unwrap()
+            .is_empty());
+        assert!(Path::new(&export.file_path).exists());
+    }
+
+    #[test]
+    fn import_dry_run_writes_nothing_and_duplicate_remaps_references() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = DbPool::new(dir.path().join("import.sqlite")).unwrap();
+        let memory = Arc::new(MemoryService::new(dir.path().join("memory")).unwrap());
+        let service = DataExportService::new(db.clone(), memory, dir.path().join("reports"));
+        let tasks = TaskService::new(db.clone());
+
+        let first = tasks
+            .create_task(TaskCreateInput {
+                title: "Draft".to_string(),
+                ..TaskCreateInput::default()
+            })
+            .unwrap();
+        let second = tasks
+            .create_task(TaskCreateInput {
+                title: "Review".to_string(),
+                ..TaskCreateInput::default()
+            })
+            .unwrap();
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO task_dependencies (id, predecessor_id, successor_id, created_at) VALUES ('dep-1', ?1, ?2, '2026-01-01T00:00:00Z')",
+                rusqlite::params![first.id, second.id],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+        let export = service.export_all().unwrap();
+        service.erase_all(ERASE_CONFIRMATION).unwrap();
+
+        let params = |on_conflict, dry_run| DataImportParams {
+            archive_path: export.file_path.clone(),
+            domains: vec![DataDomain::Tasks],
+            on_conflict,
+            dry_run,
+        };
+        let preview = service
+            .import(params(ImportConflictMode::Skip, true))
+            .unwrap();
+        let task_entry = preview.entries.iter().find(|e| e.table == "tasks").unwrap();
+        assert_eq!(task_entry.inserted, 2);
+        assert!(tasks.list_tasks_readonly().unwrap().is_empty());
+
+        let version = settings_version();
+        service
+            .import(params(ImportConflictMode::Skip, false))
+            .unwrap();
+        assert_eq!(tasks.list_tasks_readonly().unwrap().len(), 2);
+        assert!(settings_version() > version);
+
+        let skipped = service
+            .import(params(ImportConflictMode::Skip, false))
+            .unwrap();
+        let task_entry = skipped.entries.iter().find(|e| e.table == "tasks").unwrap();
+        assert_eq!(task_entry.skipped, 2);
+
+        let duplicated = service
+            .import(params(ImportConflictMode::Duplicate, false))
+            .unwrap();
+        let dependency_entry = duplicated
+            .entries
+            .iter()
+            .find(|e| e.table == "task_dependencies")
+            .unwrap();
+        assert_eq!(dependency_entry.duplicated, 1);
+        assert_eq!(tasks.list_tasks_readonly().unwrap().len(), 4);
+        let original_links: i64 = db
+            .with_read_connection(|conn| {
+                Ok(conn.query_row(
+                    "SELECT COUNT(*) FROM task_dependencies WHERE predecessor_id = ?1",
+                    [&first.id],
+                    |row| row.get(0),
+                )?)
+            })
+            .unwrap();
+        assert_eq!(original_links, 1);
+
+        assert!(service
+            .import(DataImportParams {
+                domains: Vec::new(),
+                ..params(ImportConflictMode::Skip, true)
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn duplicate_import_remaps_undeclared_project_references() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = DbPool::new(dir.path().join("projects.sqlite")).unwrap();
+        let memory = Arc::new(MemoryService::new(dir.path().join("memory")).unwrap());
+        let service = DataExportService::new(db.clone(), memory, dir.path().join("reports"));
+        let project = ProjectService::new(db.clone())
+            .create(ProjectCreateInput {
+                name: "Launch".to_string(),
+                ..ProjectCreateInput::default()
+            })
+            .unwrap();
+        TaskService::new(db.clone())
+            .create_task(TaskCreateInput {
+                title: "Announce".to_string(),
+                project_id: Some(project.id.clone()),
+                ..TaskCreateInput::default()
+            })
+            .unwrap();
+        let export = service.export_all().unwrap();
+
+        service
+            .import(DataImportParams {
+                archive_path: export.file_path,
+                domains: vec![DataDomain::Projects, DataDomain::Tasks],
+                on_conflict: ImportConflictMode::Duplicate,
+                dry_run: false,
+            })
+            .unwrap();
+
+        let links: Vec<(String, i64)> = db
+            .with_read_connection(|conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT p.id, COUNT(t.id) FROM projects p LEFT JOIN tasks t ON t.project_id = p.id GROUP BY p.id",
+                )?;
+                let rows = stmt
+                    .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+            .unwrap();
+        assert_eq!(links.len(), 2);
+        assert!(links.iter().all(|(_, tasks)| *tasks == 1), "{links:?}");
+        assert!(links.iter().any(|(id, _)| *id != project.id));
+    }
+}
@@ -0,0 +1,411 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use tracing::{info, warn};
+
+use crate::db::repositories::day_log_repository::{DayLogRepository, DayLogRow};
+use crate::db::repositories::planning_repository::{PlanningRepository, PlanningTimeBlockRow};
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::day_log::{
+    DayCloseInput, DayCloseResult, DayLogRecord, RolloverAction, RolloverCandidate,
+    RolloverDecision,
+};
+use crate::models::productivity::ProductivityScoreRecord;
+use crate::models::task::{TaskRecord, TaskUpdateInput};
+use crate::services::analytics_service::AnalyticsService;
+use crate::services::productivity_score_service::ProductivityScoreService;
+use crate::services::task_service::TaskService;
+
+const MAX_REFLECTION_CHARS: usize = 500;
+const DEFAULT_LOG_LIMIT: usize = 30;
+const MAX_LOG_LIMIT: usize = 365;
+const UNFINISHED_STATUS: &str = "unfinished";
+const OPEN_BLOCK_STATUSES: [&str; 3] = ["planned", "in_progress", UNFINISHED_STATUS];
+const CLOSED_TASK_STATUSES: [&str; 2] = ["done", "archived"];
+const INBOX_TAG: &str = "inbox";
+
+/// End-of-day shutdown ritual: marks unfinished blocks, applies roll-over
+/// decisions, scores the day right away and keeps a `day_logs` entry.
+pub struct DayCloseService {
+    db: DbPool,
+    task_service: Arc<TaskService>,
+    analytics_service: Arc<AnalyticsService>,
+    productivity_score_service: Arc<ProductivityScoreService>,
+}
+
+impl DayCloseService {
+    pub fn new(
+        db: DbPool,
+        task_service: Arc<TaskService>,
+        analytics_service: Arc<AnalyticsService>,
+        productivity_score_service: Arc<ProductivityScoreService>,
+    ) -> Self {
+        Self {
+            db,
+            task_service,
+            analytics_service,
+            productivity_score_service,
+        }
+    }
+
+    /// Closes `input.date`. Calling it again for the same day is allowed: new
+    /// decisions are merged into the stored log and the score is recomputed.
+    pub fn close_day(&self, input: DayCloseInput) -> AppResult<DayCloseResult> {
+        let date = parse_log_date(&input.date)?;
+        if date > Utc::now().date_naive() {
+            return Err(AppError::validation("不能结算未来的日期"));
+        }
+        let reflection = normalize_reflection(input.reflection)?;
+        for decision in &input.decisions {
+            validate_decision(decision, date)?;
+        }
+
+        let (day_start, day_end) = day_window(date);
+        let unfinished = self.mark_unfinished_blocks(day_start, day_end)?;
+
+        for decision in &input.decisions {
+            self.apply_decision(decision, date)?;
+        }
+
+        let existing = self
+            .db
+            .with_connection(|conn| DayLogRepository::find_by_date(conn, &input.date))?;
+        let decisions = merge_decisions(
+            existing
+                .as_ref()
+                .map(|log| log.decisions.clone())
+                .unwrap_or_default(),
+            input.decisions,
+        );
+
+        let tasks = self.task_service.list_tasks()?;
+        let pending_rollovers = build_rollover_candidates(&tasks, &unfinished, &decisions);
+        let completed_tasks = count_completed_on(&tasks, day_start, day_end);
+
+        let score = self.score_day(date)?;
+
+        let now = Utc::now().to_rfc3339();
+        let log = DayLogRecord {
+            log_date: date.to_string(),
+            closed_at: now.clone(),
+            reflection: reflection
+                .or_else(|| existing.as_ref().and_then(|log| log.reflection.clone())),
+            completed_tasks,
+            unfinished_blocks: unfinished.len() as i64,
+            decisions,
+            productivity_score: score.as_ref().map(|record| record.composite_score),
+            created_at: existing
+                .as_ref()
+                .map(|log| log.created_at.clone())
+                .unwrap_or_else(|| now.clone()),
+            updated_at: now,
+        };
+
+        let row = DayLogRow::from_record(&log)?;
+        self.db
+            .with_connection(|conn| DayLogRepository::upsert(conn, &row))?;
+
+        info!(
+            target: "app::day_close",
+            date = %log.log_date,
+            unfinished = log.unfinished_blocks,
+            pending = pending_rollovers.len(),
+            "day closed"
+        );
+
+        Ok(DayCloseResult {
+            log,
+            pending_rollovers,
+            score,
+        })
+    }
+
+    pub fn get_log(&self, date: &str) -> AppResult<DayLogRecord> {
+        let date = parse_log_date(date)?;
+        self.db
+            .with_connection(|conn| DayLogRepository::find_by_date(conn, &date.to_string()))?
+            .ok_or_else(AppError::not_found)
+    }
+
+    pub fn list_logs(&self, limit: Option<usize>) -> AppResult<Vec<DayLogRecord>> {
+        let limit = limit.unwrap_or(DEFAULT_LOG_LIMIT).clamp(1, MAX_LOG_LIMIT);
+        self.db
+            .with_connection(|conn| DayLogRepository::list_recent(conn, limit))
+    }
+
+    /// Flags applied blocks of the day whose task is still open. Returns every
+    /// unfinished block, including ones marked by an earlier close.
+    fn mark_unfinished_blocks(
+        &self,
+        day_start: DateTime<Utc>,
+        day_end: DateTime<Utc>,
+    ) -> AppResult<Vec<PlanningTimeBlockRow>> {
+        let blocks = self.db.with_connection(|conn| {
+            PlanningRepository::list_applied_time_blocks_between(
+                conn,
+                &day_start.to_rfc3339(),
+                &day_end.to_rfc3339(),
+            )
+        })?;
+
+        let mut unfinished = Vec::new();
+        for mut block in blocks {
+            if !OPEN_BLOCK_STATUSES.contains(&block.status.as_str()) {
+                continue;
+            }
+            let task = match self.task_service.get_task(&block.task_id) {
+                Ok(task) => task,
+                Err(AppError::NotFound) => continue,
+                Err(err) => return Err(err),
+            };
+            if task.status == "done" {
+                continue;
+            }
+
+            if block.status != UNFINISHED_STATUS {
+                block.status = UNFINISHED_STATUS.to_string();
+                self.db
+                    .with_connection(|conn| PlanningRepository::update_time_block(conn, &block))?;
+            }
+            unfinished.push(block);
+        }
+
+        Ok(unfinished)
+    }
+
+    fn apply_decision(
+        &self,
+        decision: &RolloverDecision,
+        date: NaiveDate,
+    ) -> AppResult<TaskRecord> {
+        let task = self.task_service.get_task(&decision.task_id)?;
+
+        let update = match decision.action {
+            RolloverAction::Reschedule => {
+                let target = decision.reschedule_to.clone().unwrap_or_default();
+                // Keep the task from turning overdue when its deadline was the closed day.
+                let due_at = task
+                    .due_at
+                    .as_deref()
+                    .and_then(parse_datetime)
+                    .filter(|due| due.date_naive() <= date)
+                    .map(|_| Some(target.clone()));
+                TaskUpdateInput {
+                    planned_start_at: Some(Some(target)),
+                    due_at,
+                    ..Default::default()
+                }
+            }
+            RolloverAction::Drop => TaskUpdateInput {
+                status: Some("archived".to_string()),
+                ..Default::default()
+            },
+            RolloverAction::Park => {
+                let mut tags = task.tags.clone();
+                if !tags.iter().any(|tag| tag == INBOX_TAG) {
+                    tags.push(INBOX_TAG.to_string());
+                }
+                TaskUpdateInput {
+                    status: Some("backlog".to_string()),
+                    planned_start_at: Some(None),
+                    tags: Some(Some(tags)),
+                    ..Default::default()
+                }
+            }
+        };
+
+        self.task_service.update_task(&task.id, update)
+    }
+
+    fn score_day(&self, date: NaiveDate) -> AppResult<Option<ProductivityScoreRecord>> {
+        self.analytics_service.capture_snapshot_for_date(date)?;
+
+        match self
+            .productivity_score_service
+            .calculate_score_for_date(&date.to_string())
+        {
+            Ok(record) => Ok(Some(record)),
+            Err(AppError::NotFound) => {
+                warn!(target: "app::day_close", date = %date, "no snapshot available to score day");
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+fn parse_log_date(value: &str) -> AppResult<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map_err(|_| AppError::validation("日期格式无效，应为 YYYY-MM-DD"))
+}
+
+fn parse_datetime(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+fn day_window(date: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+    let start = Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap());
+    (start, start + Duration::days(1))
+}
+
+fn normalize_reflection(reflection: Option<String>) -> AppResult<Option<String>> {
+    let reflection = reflection
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty());
+
+    if let Some(text) = &reflection {
+        if text.chars().count() > MAX_REFLECTION_CHARS {
+            return Err(AppError::validation(format!(
+                "复盘记录不能超过 {MAX_REFLECTION_CHARS} 个字符"
+            )));
+        }
+    }
+
+    Ok(reflection)
+}
+
+fn validate_decision(decision: &RolloverDecision, date: NaiveDate) -> AppResult<()> {
+    if decision.task_id.trim().is_empty() {
+        return Err(AppError::validation("顺延决定缺少任务 ID"));
+    }
+
+    if decision.action == RolloverAction::Reschedule {
+        let target = decision
+            .reschedule_to
+            .as_deref()
+            .ok_or_else(|| AppError::validation("改期需要提供新的开始时间"))?;
+        let target = parse_datetime(target)
+            .ok_or_else(|| AppError::validation("改期时间格式无效，应为 RFC3339"))?;
+        if target.date_naive() <= date {
+            return Err(AppError::validation("改期时间必须晚于结算日期"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Later decisions for the same task replace earlier ones.
+fn merge_decisions(
+    existing: Vec<RolloverDecision>,
+    incoming: Vec<RolloverDecision>,
+) -> Vec<RolloverDecision> {
+    let mut merged = existing;
+    for decision in incoming {
+        match merged
+            .iter_mut()
+            .find(|entry| entry.task_id == decision.task_id)
+        {
+            Some(entry) => *entry = decision,
+            None => merged.push(decision),
+        }
+    }
+    merged
+}
+
+fn build_rollover_candidates(
+    tasks: &[TaskRecord],
+    unfinished: &[PlanningTimeBlockRow],
+    decisions: &[RolloverDecision],
+) -> Vec<RolloverCandidate> {
+    let mut grouped: BTreeMap<&str, (Vec<String>, i64)> = BTreeMap::new();
+    for block in unfinished {
+        let minutes = match (
+            parse_datetime(&block.start_at),
+            parse_datetime(&block.end_at),
+        ) {
+            (Some(start), Some(end)) => (end - start).num_minutes().max(0),
+            _ => 0,
+        };
+        let entry = grouped.entry(block.task_id.as_str()).or_default();
+        entry.0.push(block.id.clone());
+        entry.1 += minutes;
+    }
+
+    grouped
+        .into_iter()
+        .filter(|(task_id, _)| {
+            !decisions
+                .iter()
+                .any(|decision| decision.task_id == *task_id)
+        })
+        .filter_map(|(task_id, (block_ids, planned_minutes))| {
+            let task = tasks.iter().find(|task| task.id == task_id)?;
+            if CLOSED_TASK_STATUSES.contains(&task.status.as_str()) {
+                return None;
+            }
+            Some(RolloverCandidate {
+                task_id: task.id.clone(),
+                title: task.title.clone(),
+                status: task.status.clone(),
+                block_ids,
+                planned_minutes,
+            })
+        })
+        .collect()
+}
+
+fn count_completed_on(tasks: &[TaskRecord], start: DateTime<Utc>, end: DateTime<Utc>) -> i64 {
+    tasks
+        .iter()
+        .filter_map(|task| task.completed_at.as_deref().and_then(parse_datetime))
+        .filter(|completed| *completed >= start && *completed < end)
+        .count() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decision(task_id: &str, action: RolloverAction) -> RolloverDecision {
+        RolloverDecision {
+            task_id: task_id.to_string(),
+            action,
+            reschedule_to: None,
+        }
+    }
+
+    #[test]
+    fn merge_decisions_replaces_by_task() {
+        let merged = merge_decisions(
+            vec![
+                decision("a", RolloverAction::Park),
+                decision("b", RolloverAction::Drop),
+            ],
+            vec![
+                decision("a", RolloverAction::Drop),
+                decision("c", RolloverAction::Park),
+            ],
+        );
+
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0].action, RolloverAction::Drop);
+        assert_eq!(merged[2].task_id, "c");
+    }
+
+    #[test]
+    fn reschedule_requires_future_target() {
+        let date = NaiveDate::from_ymd_opt(2024, 5, 10).unwrap();
+        let mut reschedule = decision("a", RolloverAction::Reschedule);
+        assert!(validate_decision(&reschedule, date).is_err());
+
+        reschedule.reschedule_to = Some("2024-05-10T18:00:00Z".to_string());
+        assert!(validate_decision(&reschedule, date).is_err());
+
+        reschedule.reschedule_to = Some("2024-05-11T09:00:00+08:00".to_string());
+        assert!(validate_decision(&reschedule, date).is_ok());
+    }
+
+    #[test]
+    fn reflection_is_trimmed_and_capped() {
+        assert_eq!(normalize_reflection(Some("   ".to_string())).unwrap(), None);
+        assert_eq!(
+            normalize_reflection(Some(" 今天专注度不错 ".to_string())).unwrap(),
+            Some("今天专注度不错".to_string())
+        );
+        assert!(normalize_reflection(Some("字".repeat(MAX_REFLECTION_CHARS + 1))).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Utc};
+use tracing::{debug, info, warn};
+
+use crate::db::maintenance::{self, CheckpointMode, CheckpointOutcome};
+use crate::db::DbPool;
+use crate::error::AppResult;
+use crate::services::job_supervisor::spawn_supervised;
+
+const CHECK_INTERVAL: StdDuration = StdDuration::from_secs(60);
+
+/// Schedules WAL checkpoints; see [`crate::db::maintenance`] for when they
+/// run.
+pub struct DbMaintenanceService {
+    db: DbPool,
+    job_started: AtomicBool,
+}
+
+impl DbMaintenanceService {
+    pub fn new(db: DbPool) -> Self {
+        Self {
+            db,
+            job_started: AtomicBool::new(false),
+        }
+    }
+
+    /// Checks the WAL once and checkpoints if one is due. Returns the
+    /// checkpoint that ran, if any.
+    pub fn run_once(&self, now: DateTime<Utc>) -> AppResult<Option<CheckpointOutcome>> {
+        let sizes = self.db.file_sizes();
+        let Some(mode) = maintenance::due_checkpoint(&sizes, maintenance::idle_for(now)) else {
+            return Ok(None);
+        };
+
+        let outcome = self.db.checkpoint(mode)?;
+        let after = self.db.file_sizes();
+        if mode == CheckpointMode::Truncate {
+            info!(
+                target: "app::database",
+                wal_bytes_before = sizes.wal_bytes,
+                wal_bytes_after = after.wal_bytes,
+                busy = outcome.busy,
+                "truncated oversized WAL"
+            );
+        } else {
+            debug!(
+                target: "app::database",
+                wal_frames = outcome.wal_frames,
+                checkpointed = outcome.checkpointed_frames,
+                busy = outcome.busy,
+                "passive WAL checkpoint"
+            );
+        }
+        Ok(Some(outcome))
+    }
+
+    /// Checks the WAL every minute on a supervised background thread.
+    pub fn ensure_checkpoint_job(self: &Arc<Self>) -> AppResult<()> {
+        if self
+            .job_started
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Ok(());
+        }
+
+        let runner = Arc::clone(self);
+        if let Err(err) = spawn_supervised(self.db.clone(), "wal-checkpoint-job", move || loop {
+            thread::sleep(CHECK_INTERVAL);
+            if let Err(err) = runner.run_once(Utc::now()) {
+                warn!(target: "app::database", error = %err, "WAL checkpoint failed");
+            }
+        }) {
+            self.job_started.store(false, Ordering::SeqCst);
+            return Err(err);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idle_database_gets_checkpointed() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = DbPool::new(dir.path().join("wal.sqlite")).unwrap();
+        // SQLite removes the WAL when the last connection closes
+        let _open = db.get_read_connection().unwrap();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "CREATE TABLE scratch (value TEXT); INSERT INTO scratch VALUES ('a'), ('b');",
+            )?;
+            Ok(())
+        })
+        .unwrap();
+        assert!(db.file_sizes().wal_bytes > 0);
+
+        let service = DbMaintenanceService::new(db.clone());
+        let later = Utc::now() + chrono::Duration::minutes(5);
+        let outcome = service.run_once(later).unwrap().unwrap();
+        assert_eq!(outcome.mode, CheckpointMode::Passive);
+        assert!(!outcome.busy);
+        assert_eq!(outcome.wal_frames, outcome.checkpointed_frames);
+        assert_eq!(
+            db.diagnostics().last_checkpoint.map(|last| last.mode),
+            Some(CheckpointMode::Passive)
+        );
+    }
+}
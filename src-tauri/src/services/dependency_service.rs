@@ -84,6 +84,97 @@ impl DependencyService {
         Ok(dependency_id)
     }
 
+    /// Chain `task_ids` in order, each task depending on the one before it.
+    /// The whole chain is checked for cycles against the existing graph and
+    /// written in one transaction; links that already exist are kept as is.
+    pub async fn add_dependency_chain(
+        &self,
+        task_ids: &[String],
+        dependency_type: Option<DependencyType>,
+    ) -> AppResult<Vec<TaskDependency>> {
+        if task_ids.len() < 2 {
+            return Err(crate::error::AppError::validation(
+                "A dependency chain needs at least two tasks",
+            ));
+        }
+        let mut seen = HashSet::new();
+        if let Some(duplicate) = task_ids.iter().find(|id| !seen.insert(id.as_str())) {
+            return Err(crate::error::AppError::validation(format!(
+                "Task {} appears more than once in the chain",
+                duplicate
+            )));
+        }
+
+        {
+            let conn = self.db_pool.get_connection()?;
+            let mut stmt = conn.prepare("SELECT 1 FROM tasks WHERE id = ?1")?;
+            for task_id in task_ids {
+                if !stmt.exists(params![task_id])? {
+                    return Err(crate::error::AppError::validation(format!(
+                        "Task {} not found",
+                        task_id
+                    )));
+                }
+            }
+        }
+
+        let links: Vec<(&str, &str)> = task_ids
+            .windows(2)
+            .map(|pair| (pair[0].as_str(), pair[1].as_str()))
+            .collect();
+
+        let graph = self.get_dependency_graph(None).await?;
+        if let Some(path) = self.detect_cycle_with_new_edges(&graph, &links) {
+            return Err(crate::error::AppError::validation(format!(
+                "Adding this chain would create a circular dependency: {}",
+                path.join(" -> ")
+            )));
+        }
+
+        let dependency_type = dependency_type.unwrap_or_default();
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut created = Vec::new();
+
+        let mut conn = self.db_pool.get_connection()?;
+        let tx = conn.transaction()?;
+        for (predecessor_id, successor_id) in links {
+            let exists = tx
+                .prepare(
+                    "SELECT 1 FROM task_dependencies WHERE predecessor_id = ?1 AND successor_id = ?2",
+                )?
+                .exists(params![predecessor_id, successor_id])?;
+            if exists {
+                continue;
+            }
+
+            let dependency = TaskDependency {
+                id: Uuid::new_v4().to_string(),
+                predecessor_id: predecessor_id.to_string(),
+                successor_id: successor_id.to_string(),
+                dependency_type: dependency_type.clone(),
+                created_at: now.clone(),
+            };
+            tx.execute(
+                "INSERT INTO task_dependencies (id, predecessor_id, successor_id, dependency_type, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    dependency.id,
+                    dependency.predecessor_id,
+                    dependency.successor_id,
+                    dependency.dependency_type.to_string(),
+                    dependency.created_at
+                ],
+            )?;
+            created.push(dependency);
+        }
+        tx.commit()?;
+
+        // Invalidate cache after modification
+        self.invalidate_cache();
+
+        Ok(created)
+    }
+
     /// Remove a dependency relationship
     pub async fn remove_dependency(&self, dependency_id: &str) -> AppResult<()> {
         let conn = self.db_pool.get_connection()?;
@@ -502,14 +593,23 @@ impl DependencyService {
         new_predecessor: &str,
         new_successor: &str,
     ) -> Option<Vec<String>> {
-        // Create a temporary graph with the new edge
+        self.detect_cycle_with_new_edges(graph, &[(new_predecessor, new_successor)])
+    }
+
+    /// Detect if adding several new edges at once would create a cycle
+    fn detect_cycle_with_new_edges(
+        &self,
+        graph: &DependencyGraph,
+        new_edges: &[(&str, &str)],
+    ) -> Option<Vec<String>> {
+        // Create a temporary graph with the new edges
         let mut temp_edges = graph.edges.clone();
-        temp_edges.push(DependencyEdge {
+        temp_edges.extend(new_edges.iter().map(|(source, target)| DependencyEdge {
             id: "temp".to_string(),
-            source: new_predecessor.to_string(),
-            target: new_successor.to_string(),
+            source: source.to_string(),
+            target: target.to_string(),
             dependency_type: DependencyType::FinishToStart,
-        });
+        }));
 
         // Build adjacency list
         let mut adj_list: HashMap<String, Vec<String>> = HashMap::new();
@@ -529,7 +629,9 @@ impl DependencyService {
         let mut rec_stack = HashSet::new();
         let mut path = Vec::new();
 
-        for node_id in graph.nodes.keys() {
+        let mut start_nodes: Vec<&String> = adj_list.keys().collect();
+        start_nodes.sort();
+        for node_id in start_nodes {
             if !visited.contains(node_id) {
                 if let Some(cycle_path) = self.dfs_cycle_detection(
                     node_id,
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use chrono::{DateTime, Duration, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use tracing::{debug, info};
@@ -11,7 +12,9 @@ use crate::error::{AppError, AppResult};
 use crate::models::ai_feedback::{
     AiFeedback, AiFeedbackCreate, AiFeedbackSentiment, AiFeedbackSurface,
 };
-use crate::services::settings_service::SettingsService;
+use crate::models::retention::RetentionCategory;
+use crate::models::settings::{FeedbackCategory, FeedbackOptOuts};
+use crate::services::settings_service::{load_retention_policy, SettingsService};
 use crate::utils::redact::redact_sensitive_data;
 
 const MIN_FEEDBACK_FOR_DIGEST: usize = 5; // Minimum feedback entries to generate digest
@@ -23,7 +26,7 @@ pub struct FeedbackService {
     settings_service: Arc<SettingsService>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct FeedbackSubmission {
     pub surface: AiFeedbackSurface,
@@ -34,7 +37,7 @@ pub struct FeedbackSubmission {
     pub context_snapshot: JsonValue,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct WeeklyDigest {
     pub period_start: String,
@@ -47,7 +50,7 @@ pub struct WeeklyDigest {
     pub adjustments_made: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SurfaceDigest {
     pub surface: String,
@@ -57,6 +60,31 @@ pub struct SurfaceDigest {
     pub sample_notes: Vec<String>,
 }
 
+/// What each feedback category keeps on this device.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedbackPrivacySummary {
+    pub opt_outs: FeedbackOptOuts,
+    pub categories: Vec<FeedbackCategorySummary>,
+    /// Nothing listed here is sent anywhere unless the user shares it.
+    pub stored_locally_only: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedbackCategorySummary {
+    pub category: FeedbackCategory,
+    pub opted_out: bool,
+    pub description: String,
+    /// Fields stored for every record.
+    pub stored_fields: Vec<String>,
+    /// Records currently stored for the category.
+    pub stored_records: i64,
+    /// Days records are kept under the retention policy; `0` keeps them
+    /// forever, `None` when the policy does not cover the category.
+    pub retention_days: Option<u32>,
+}
+
 impl FeedbackService {
     pub fn new(db: DbPool, settings_service: Arc<SettingsService>) -> Self {
         Self {
@@ -67,8 +95,95 @@ impl FeedbackService {
 
     /// Check if user has opted out of AI feedback collection
     pub fn is_opted_out(&self) -> AppResult<bool> {
-        let settings = self.settings_service.get()?;
-        Ok(settings.ai_feedback_opt_out.unwrap_or(false))
+        self.is_category_opted_out(FeedbackCategory::AiQuality)
+    }
+
+    /// Check if user has opted out of one feedback category
+    pub fn is_category_opted_out(&self, category: FeedbackCategory) -> AppResult<bool> {
+        Ok(self
+            .settings_service
+            .get_feedback_opt_outs()?
+            .is_opted_out(category))
+    }
+
+    /// Describe what every feedback category stores and whether it is on
+    pub fn get_privacy_summary(&self) -> AppResult<FeedbackPrivacySummary> {
+        let opt_outs = self.settings_service.get_feedback_opt_outs()?;
+        let conn = self.db.get_connection()?;
+        let retention = load_retention_policy(&conn)?;
+        let count = |table: &str| -> AppResult<i64> {
+            Ok(
+                conn.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                    row.get(0)
+                })?,
+            )
+        };
+
+        let categories = FeedbackCategory::ALL
+            .into_iter()
+            .map(|category| {
+                let (description, stored_fields, table, retention_days) = match category {
+                    FeedbackCategory::AiQuality => (
+                        "Thumbs up/down ratings on AI scores, recommendations and forecasts, \
+                         used for the weekly feedback digest.",
+                        &[
+                            "surface",
+                            "sentiment",
+                            "note",
+                            "prompt_snapshot",
+                            "context_snapshot (redacted when a note or thumbs down is given)",
+                            "created_at",
+                        ][..],
+                        "ai_feedback",
+                        Some(retention.days_for(RetentionCategory::Feedback)),
+                    ),
+                    FeedbackCategory::WellnessCheckIns => (
+                        "Break reminders raised after long focus or work streaks, and whether \
+                         they were taken, snoozed or ignored.",
+                        &[
+                            "window_start",
+                            "trigger_reason",
+                            "recommended_break_minutes",
+                            "response",
+                            "response_at",
+                        ][..],
+                        "wellness_events",
+                        Some(retention.days_for(RetentionCategory::Nudges)),
+                    ),
+                    FeedbackCategory::UsageStats => (
+                        "Task, session and forecast counts added to community export bundles. \
+                         No titles or content are included.",
+                        &[
+                            "generated_at",
+                            "payload_path",
+                            "metrics_summary (task and session counts)",
+                            "includes_feedback",
+                            "checksum",
+                        ][..],
+                        "community_exports",
+                        None,
+                    ),
+                };
+
+                Ok(FeedbackCategorySummary {
+                    category,
+                    opted_out: opt_outs.is_opted_out(category),
+                    description: description.to_string(),
+                    stored_fields: stored_fields
+                        .iter()
+                        .map(|field| field.to_string())
+                        .collect(),
+                    stored_records: count(table)?,
+                    retention_days,
+                })
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(FeedbackPrivacySummary {
+            opt_outs,
+            categories,
+            stored_locally_only: true,
+        })
     }
 
     /// Submit feedback with optional anonymization
@@ -468,6 +583,32 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_category_opt_outs_are_independent() -> AppResult<()> {
+        let (service, settings_service, _temp_dir) = setup_test_service()?;
+
+        settings_service.update_feedback_opt_outs(FeedbackOptOuts {
+            wellness_check_ins: true,
+            ..Default::default()
+        })?;
+        assert!(!service.is_opted_out()?);
+        assert!(service.is_category_opted_out(FeedbackCategory::WellnessCheckIns)?);
+
+        // The legacy switch still controls AI quality prompts
+        settings_service.update(crate::services::settings_service::SettingsUpdateInput {
+            ai_feedback_opt_out: Some(true),
+            ..Default::default()
+        })?;
+        let summary = service.get_privacy_summary()?;
+        assert!(summary.opt_outs.ai_quality);
+        assert!(summary.opt_outs.wellness_check_ins);
+        assert!(!summary.opt_outs.usage_stats);
+        assert_eq!(summary.categories.len(), FeedbackCategory::ALL.len());
+        assert!(summary.categories.iter().all(|c| c.stored_records == 0));
+
+        Ok(())
+    }
+
     #[test]
     fn test_purge_feedback() -> AppResult<()> {
         let (service, _, _temp_dir) = setup_test_service()?;
@@ -0,0 +1,106 @@
+//! Turns periodic idle-time samples taken during a focus session into the
+//! idle stretches that are proposed for removal from its focus minutes.
+
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::models::wellness::IdlePeriod;
+
+pub struct IdleTracker {
+    session_started_at: DateTime<Utc>,
+    threshold: Duration,
+    /// Start of the idle stretch in progress.
+    open: Option<DateTime<Utc>>,
+    periods: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+impl IdleTracker {
+    pub fn new(session_started_at: DateTime<Utc>, threshold_minutes: u32) -> Self {
+        Self {
+            session_started_at,
+            threshold: Duration::minutes(i64::from(threshold_minutes)),
+            open: None,
+            periods: Vec::new(),
+        }
+    }
+
+    pub fn session_started_at(&self) -> DateTime<Utc> {
+        self.session_started_at
+    }
+
+    /// Records a sample taken at `now`. Samples where idle time could not be
+    /// read are skipped.
+    pub fn observe(&mut self, now: DateTime<Utc>, idle: Option<StdDuration>) {
+        let Some(idle) = idle.and_then(|idle| Duration::from_std(idle).ok()) else {
+            return;
+        };
+        // Input before the session started does not make the session idle
+        let last_input = (now - idle).max(self.session_started_at);
+        if idle >= self.threshold {
+            self.open.get_or_insert(last_input);
+        } else {
+            self.close(last_input);
+        }
+    }
+
+    /// Closes tracking at `ended_at`; a stretch still open then runs to the
+    /// end of the session.
+    pub fn finish(mut self, ended_at: DateTime<Utc>) -> Vec<IdlePeriod> {
+        self.close(ended_at);
+        self.periods
+            .into_iter()
+            .map(|(started_at, ended_at)| IdlePeriod {
+                started_at: started_at.to_rfc3339(),
+                ended_at: ended_at.to_rfc3339(),
+                minutes: (ended_at - started_at).num_minutes(),
+            })
+            .collect()
+    }
+
+    fn close(&mut self, at: DateTime<Utc>) {
+        if let Some(started_at) = self.open.take() {
+            if at - started_at >= self.threshold {
+                self.periods.push((started_at, at));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn idle_stretches_over_the_threshold_are_collected() {
+        let start = Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+        let at = |minutes: i64| start + Duration::minutes(minutes);
+        let idle = |minutes: u64| Some(StdDuration::from_secs(minutes * 60));
+        let mut tracker = IdleTracker::new(start, 5);
+
+        // Short pauses never open a stretch
+        tracker.observe(at(3), idle(2));
+        // Away from 10:00 until input at 10:30
+        tracker.observe(at(65), idle(5));
+        tracker.observe(at(80), idle(20));
+        tracker.observe(at(90), None);
+        tracker.observe(at(91), idle(1));
+        // Idle again when the session ends
+        tracker.observe(at(115), idle(10));
+
+        let periods = tracker.finish(at(120));
+        assert_eq!(periods.len(), 2);
+        assert_eq!(periods[0].started_at, at(60).to_rfc3339());
+        assert_eq!(periods[0].ended_at, at(90).to_rfc3339());
+        assert_eq!(periods[0].minutes, 30);
+        assert_eq!(periods[1].minutes, 15);
+
+        // Idle since before the session counts from its start
+        let mut early = IdleTracker::new(start, 5);
+        early.observe(at(10), idle(30));
+        let periods = early.finish(at(20));
+        assert_eq!(periods[0].started_at, start.to_rfc3339());
+        assert_eq!(periods[0].minutes, 20);
+    }
+}
@@ -1,20 +1,43 @@
-use chrono::Utc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, Utc};
 use rusqlite::params;
+use tauri::{AppHandle, Emitter};
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
 use crate::db::DbPool;
 use crate::error::{AppError, AppResult};
 use crate::models::goal::{
-    CreateGoalRequest, Goal, GoalStatus, GoalTaskAssociation, GoalWithProgress, UpdateGoalRequest,
+    CreateGoalRequest, Goal, GoalCheckin, GoalCheckinCadence, GoalStatus, GoalTaskAssociation,
+    GoalWithProgress, UpdateGoalRequest,
 };
+use crate::services::job_supervisor::spawn_supervised;
+use crate::services::on_demand_jobs::OnDemandJobs;
+
+pub const GOAL_CHECKIN_DUE_EVENT: &str = "goals://checkin-due";
+
+/// Column order expected by [`GoalService::map_goal_row`].
+pub(crate) const GOAL_COLUMNS: &str = "id, title, description, parent_goal_id, status, priority, \
+    target_date, created_at, updated_at, project_id, checkin_cadence, next_checkin_at";
+
+pub(crate) const CHECKIN_POLL_INTERVAL: StdDuration = StdDuration::from_secs(300);
+const MAX_CHECKIN_NOTE_CHARS: usize = 2000;
 
 pub struct GoalService {
     db: DbPool,
+    checkin_worker_started: AtomicBool,
 }
 
 impl GoalService {
     pub fn new(db: DbPool) -> Self {
-        Self { db }
+        Self {
+            db,
+            checkin_worker_started: AtomicBool::new(false),
+        }
     }
 
     pub fn create_goal(&self, request: CreateGoalRequest) -> AppResult<Goal> {
@@ -36,11 +59,14 @@ impl GoalService {
                 )));
             }
         }
+        if let Some(ref project_id) = request.project_id {
+            ensure_project_exists(conn, project_id)?;
+        }
 
         conn.execute(
             r#"
-            INSERT INTO goals (id, title, description, parent_goal_id, status, priority, target_date, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO goals (id, title, description, parent_goal_id, status, priority, target_date, project_id, created_at, updated_at, checkin_cadence, next_checkin_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
             params![
                 &id,
@@ -50,8 +76,13 @@ impl GoalService {
                 "not_started",
                 &request.priority,
                 request.target_date.map(|d| d.to_rfc3339()),
+                &request.project_id,
                 now.to_rfc3339(),
                 now.to_rfc3339(),
+                request.checkin_cadence.map(|cadence| cadence.as_str()),
+                request
+                    .checkin_cadence
+                    .map(|cadence| (now + Duration::days(cadence.days())).to_rfc3339()),
             ],
         )?;
 
@@ -63,39 +94,20 @@ impl GoalService {
 
     pub fn get_goal(&self, id: &str) -> AppResult<Goal> {
         self.db.with_connection(|conn| {
-        Ok(conn.query_row(
-            r#"
-            SELECT id, title, description, parent_goal_id, status, priority, target_date, created_at, updated_at
-            FROM goals
-            WHERE id = ?
-            "#,
-            params![id],
-            |row| {
-                Ok(Goal {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    description: row.get(2)?,
-                    parent_goal_id: row.get(3)?,
-                    status: GoalStatus::from_str(&row.get::<_, String>(4)?).unwrap_or_else(|_| GoalStatus::NotStarted),
-                    priority: row.get(5)?,
-                    target_date: row.get::<_, Option<String>>(6)?.and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok().map(|dt| dt.with_timezone(&Utc))),
-                    created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(7)?).unwrap().with_timezone(&Utc),
-                    updated_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(8)?).unwrap().with_timezone(&Utc),
-                })
-            },
-        )?)
+            let sql = format!("SELECT {GOAL_COLUMNS} FROM goals WHERE id = ?");
+            Ok(conn.query_row(&sql, params![id], Self::map_goal_row)?)
         })
     }
 
     pub fn list_goals(&self, parent_goal_id: Option<String>) -> AppResult<Vec<Goal>> {
         self.db.with_connection(|conn| {
         let query = if parent_goal_id.is_some() {
-            "SELECT id, title, description, parent_goal_id, status, priority, target_date, created_at, updated_at FROM goals WHERE parent_goal_id = ? ORDER BY created_at DESC"
+            format!("SELECT {GOAL_COLUMNS} FROM goals WHERE parent_goal_id = ? ORDER BY created_at DESC")
         } else {
-            "SELECT id, title, description, parent_goal_id, status, priority, target_date, created_at, updated_at FROM goals WHERE parent_goal_id IS NULL ORDER BY created_at DESC"
+            format!("SELECT {GOAL_COLUMNS} FROM goals WHERE parent_goal_id IS NULL ORDER BY created_at DESC")
         };
 
-        let mut stmt = conn.prepare(query)?;
+        let mut stmt = conn.prepare(&query)?;
         let goals = if let Some(parent_id) = parent_goal_id {
             stmt.query_map(params![parent_id], Self::map_goal_row)?
         } else {
@@ -134,6 +146,21 @@ impl GoalService {
                 updates.push("target_date = ?");
                 params_vec.push(Box::new(target_date.to_rfc3339()));
             }
+            if let Some(project_id) = request.project_id {
+                ensure_project_exists(conn, &project_id)?;
+                updates.push("project_id = ?");
+                params_vec.push(Box::new(project_id));
+            }
+            if let Some(cadence) = request.checkin_cadence {
+                // A new cadence restarts the schedule from today.
+                updates.push("checkin_cadence = ?");
+                params_vec.push(Box::new(cadence.map(|c| c.as_str().to_string())));
+                updates.push("next_checkin_at = ?");
+                params_vec.push(Box::new(
+                    cadence.map(|c| (now + Duration::days(c.days())).to_rfc3339()),
+                ));
+                updates.push("checkin_reminded_at = NULL");
+            }
 
             if updates.is_empty() {
                 return Ok(());
@@ -234,6 +261,148 @@ impl GoalService {
         })
     }
 
+    /// Record a check-in and move the next one a full cadence from now.
+    pub fn checkin(
+        &self,
+        goal_id: &str,
+        note: Option<String>,
+        progress: f64,
+    ) -> AppResult<GoalCheckin> {
+        if !progress.is_finite() || !(0.0..=100.0).contains(&progress) {
+            return Err(AppError::validation("进度需在 0 到 100 之间"));
+        }
+        let note = note
+            .map(|note| note.trim().to_string())
+            .filter(|note| !note.is_empty());
+        if note
+            .as_ref()
+            .is_some_and(|note| note.chars().count() > MAX_CHECKIN_NOTE_CHARS)
+        {
+            return Err(AppError::validation(format!(
+                "备注不能超过 {MAX_CHECKIN_NOTE_CHARS} 个字符"
+            )));
+        }
+
+        let goal = self.get_goal(goal_id)?;
+        let now = Utc::now();
+        let checkin = GoalCheckin {
+            id: Uuid::new_v4().to_string(),
+            goal_id: goal.id.clone(),
+            note,
+            progress,
+            created_at: now,
+        };
+
+        self.db.with_connection(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            tx.execute(
+                "INSERT INTO goal_checkins (id, goal_id, note, progress, created_at) VALUES (?, ?, ?, ?, ?)",
+                params![
+                    &checkin.id,
+                    &checkin.goal_id,
+                    &checkin.note,
+                    checkin.progress,
+                    now.to_rfc3339(),
+                ],
+            )?;
+            tx.execute(
+                "UPDATE goals SET next_checkin_at = ?, checkin_reminded_at = NULL, updated_at = ? WHERE id = ?",
+                params![
+                    goal.checkin_cadence
+                        .map(|cadence| (now + Duration::days(cadence.days())).to_rfc3339()),
+                    now.to_rfc3339(),
+                    &goal.id,
+                ],
+            )?;
+            tx.commit()?;
+            Ok(())
+        })?;
+
+        info!(goal_id = %goal.id, progress, "goal check-in recorded");
+        Ok(checkin)
+    }
+
+    /// Check-ins for a goal, newest first.
+    pub fn list_checkins(&self, goal_id: &str, limit: usize) -> AppResult<Vec<GoalCheckin>> {
+        self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, goal_id, note, progress, created_at FROM goal_checkins WHERE goal_id = ? ORDER BY created_at DESC LIMIT ?",
+            )?;
+            let rows = stmt.query_map(params![goal_id, limit as i64], map_checkin_row)?;
+            Ok(rows.collect::<Result<Vec<_>, _>>()?)
+        })
+    }
+
+    /// Open goals whose check-in came due and has not been reminded yet.
+    /// Each is returned once; the next check-in re-arms the reminder.
+    pub fn fire_due_checkins(&self, now: DateTime<Utc>) -> AppResult<Vec<Goal>> {
+        self.db.with_connection(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            let sql = format!(
+                "SELECT {GOAL_COLUMNS} FROM goals \
+                 WHERE next_checkin_at IS NOT NULL AND checkin_reminded_at IS NULL \
+                   AND julianday(next_checkin_at) <= julianday(?) \
+                   AND status NOT IN ('completed', 'cancelled')"
+            );
+            let due = {
+                let mut stmt = tx.prepare(&sql)?;
+                let rows = stmt.query_map(params![now.to_rfc3339()], Self::map_goal_row)?;
+                rows.collect::<Result<Vec<_>, _>>()?
+            };
+            for goal in &due {
+                tx.execute(
+                    "UPDATE goals SET checkin_reminded_at = ? WHERE id = ?",
+                    params![now.to_rfc3339(), &goal.id],
+                )?;
+            }
+            tx.commit()?;
+            Ok(due)
+        })
+    }
+
+    /// Mobile counterpart of
+    /// [`ensure_checkin_worker`](Self::ensure_checkin_worker): the goals that
+    /// came due are returned from `jobs_run_scheduled` instead of emitted.
+    pub fn register_on_demand(self: &Arc<Self>, jobs: &OnDemandJobs) {
+        let runner = Arc::clone(self);
+        jobs.register_with_output("goal-checkins", Duration::zero(), move |now| {
+            runner.fire_due_checkins(now)
+        });
+    }
+
+    /// Polls for due check-ins every few minutes, emitting
+    /// [`GOAL_CHECKIN_DUE_EVENT`] with the goals that came due.
+    pub fn ensure_checkin_worker(self: &Arc<Self>, app: AppHandle) -> AppResult<()> {
+        if self
+            .checkin_worker_started
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Ok(());
+        }
+
+        let runner = Arc::clone(self);
+        if let Err(err) = spawn_supervised(self.db.clone(), "goal-checkin-worker", move || loop {
+            match runner.fire_due_checkins(Utc::now()) {
+                Ok(due) if !due.is_empty() => {
+                    if let Err(err) = app.emit(GOAL_CHECKIN_DUE_EVENT, &due) {
+                        warn!(target: "app::goals", error = %err, "failed to emit check-in event");
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    error!(target: "app::goals", error = %err, "goal check-in poll failed")
+                }
+            }
+            thread::sleep(CHECKIN_POLL_INTERVAL);
+        }) {
+            self.checkin_worker_started.store(false, Ordering::SeqCst);
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
     pub fn get_goal_with_progress(&self, id: &str) -> AppResult<GoalWithProgress> {
         let goal = self.get_goal(id)?;
         let task_ids = self.get_goal_tasks(id)?;
@@ -284,9 +453,19 @@ impl GoalService {
             (target - now).num_days()
         });
 
+        let mut checkins = self.list_checkins(id, usize::MAX)?;
+        checkins.reverse();
+        let projected_completion_date = project_completion(&checkins);
+        let latest_checkin = checkins.pop();
+
         // Determine if goal is on track
-        // A goal is on track if progress is proportional to time elapsed
-        let is_on_track = if let Some(target) = goal.target_date {
+        // With check-ins, the projected finish has to land by the target;
+        // otherwise progress should be proportional to time elapsed
+        let is_on_track = if let (Some(target), Some(projected)) =
+            (goal.target_date, projected_completion_date)
+        {
+            projected <= target
+        } else if let Some(target) = goal.target_date {
             let now = Utc::now();
             let total_duration = (target - goal.created_at).num_days() as f32;
             let elapsed_duration = (now - goal.created_at).num_days() as f32;
@@ -318,10 +497,12 @@ impl GoalService {
             child_goals: child_goals_with_progress,
             is_on_track,
             days_until_target,
+            latest_checkin,
+            projected_completion_date,
         })
     }
 
-    fn map_goal_row(row: &rusqlite::Row) -> Result<Goal, rusqlite::Error> {
+    pub(crate) fn map_goal_row(row: &rusqlite::Row) -> Result<Goal, rusqlite::Error> {
         Ok(Goal {
             id: row.get(0)?,
             title: row.get(1)?,
@@ -341,6 +522,137 @@ impl GoalService {
             updated_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(8)?)
                 .unwrap()
                 .with_timezone(&Utc),
+            project_id: row.get(9)?,
+            checkin_cadence: row
+                .get::<_, Option<String>>(10)?
+                .and_then(|s| GoalCheckinCadence::from_str(&s).ok()),
+            next_checkin_at: row.get::<_, Option<String>>(11)?.and_then(|s| {
+                chrono::DateTime::parse_from_rfc3339(&s)
+                    .ok()
+                    .map(|dt| dt.with_timezone(&Utc))
+            }),
         })
     }
 }
+
+fn map_checkin_row(row: &rusqlite::Row) -> Result<GoalCheckin, rusqlite::Error> {
+    Ok(GoalCheckin {
+        id: row.get(0)?,
+        goal_id: row.get(1)?,
+        note: row.get(2)?,
+        progress: row.get(3)?,
+        created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(4)?)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+    })
+}
+
+/// Extrapolates when progress reaches 100% from the pace between the first
+/// and latest check-in (oldest first). `None` without forward progress.
+fn project_completion(checkins: &[GoalCheckin]) -> Option<DateTime<Utc>> {
+    let latest = checkins.last()?;
+    if latest.progress >= 100.0 {
+        return Some(latest.created_at);
+    }
+    let first = checkins.first()?;
+    let elapsed_days = (latest.created_at - first.created_at).num_seconds() as f64 / 86_400.0;
+    let gained = latest.progress - first.progress;
+    if elapsed_days <= 0.0 || gained <= 0.0 {
+        return None;
+    }
+
+    let remaining_days = (100.0 - latest.progress) * elapsed_days / gained;
+    Some(latest.created_at + Duration::seconds((remaining_days * 86_400.0).round() as i64))
+}
+
+fn ensure_project_exists(conn: &rusqlite::Connection, project_id: &str) -> AppResult<()> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM projects WHERE id = ?)",
+        params![project_id],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        return Err(AppError::validation("所选项目不存在"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn checkin_at(day: u32, progress: f64) -> GoalCheckin {
+        GoalCheckin {
+            id: format!("c{day}"),
+            goal_id: "g".to_string(),
+            note: None,
+            progress,
+            created_at: Utc.with_ymd_and_hms(2025, 3, day, 9, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn projects_completion_from_checkin_pace() {
+        let checkins = vec![
+            checkin_at(1, 10.0),
+            checkin_at(8, 20.0),
+            checkin_at(15, 30.0),
+        ];
+        let projected = project_completion(&checkins).expect("projection");
+        // 20 points in 14 days leaves 70 points, i.e. 49 more days.
+        assert_eq!(projected, checkins[2].created_at + Duration::days(49));
+
+        assert!(project_completion(&[checkin_at(1, 40.0), checkin_at(8, 30.0)]).is_none());
+        assert!(project_completion(&[checkin_at(1, 40.0)]).is_none());
+        assert_eq!(
+            project_completion(&[checkin_at(1, 100.0)]),
+            Some(checkin_at(1, 100.0).created_at)
+        );
+    }
+
+    #[test]
+    fn checkins_rearm_the_cadence_reminder() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = GoalService::new(DbPool::new(dir.path().join("goals.sqlite")).unwrap());
+        let goal = service
+            .create_goal(CreateGoalRequest {
+                title: "Ship v2".to_string(),
+                description: None,
+                parent_goal_id: None,
+                priority: "high".to_string(),
+                target_date: None,
+                project_id: None,
+                checkin_cadence: Some(GoalCheckinCadence::Weekly),
+            })
+            .unwrap();
+        let next = goal.next_checkin_at.expect("next check-in scheduled");
+
+        assert!(service
+            .fire_due_checkins(next - Duration::hours(1))
+            .unwrap()
+            .is_empty());
+        let due = service.fire_due_checkins(next).unwrap();
+        assert_eq!(due.len(), 1);
+        assert!(service.fire_due_checkins(next).unwrap().is_empty());
+
+        let checkin = service
+            .checkin(&goal.id, Some("  halfway  ".to_string()), 50.0)
+            .unwrap();
+        assert_eq!(checkin.note.as_deref(), Some("halfway"));
+        assert!(service.checkin(&goal.id, None, 120.0).is_err());
+        assert!(matches!(
+            service.checkin("missing", None, 10.0),
+            Err(AppError::NotFound)
+        ));
+
+        let rescheduled = service.get_goal(&goal.id).unwrap().next_checkin_at.unwrap();
+        assert_eq!(rescheduled, checkin.created_at + Duration::days(7));
+        assert_eq!(service.fire_due_checkins(rescheduled).unwrap().len(), 1);
+
+        let history = service.list_checkins(&goal.id, 10).unwrap();
+        assert_eq!(history, vec![checkin.clone()]);
+        let progress = service.get_goal_with_progress(&goal.id).unwrap();
+        assert_eq!(progress.latest_checkin, Some(checkin));
+    }
+}
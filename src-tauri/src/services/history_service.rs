@@ -0,0 +1,461 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use rusqlite::Connection;
+use serde_json::{json, Value as JsonValue};
+use tracing::{debug, warn};
+
+use crate::db::repositories::day_log_repository::DayLogRepository;
+use crate::db::repositories::planning_repository::PlanningRepository;
+use crate::db::repositories::task_repository::TaskRepository;
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::history::{
+    HistoryCompletedTask, HistoryDayStat, HistoryNarrativeSource, HistorySummary, HistoryTagCount,
+};
+use crate::services::ai_service::AiService;
+use crate::services::settings_service::load_ai_privacy_mode;
+
+const MAX_RANGE_DAYS: i64 = 366;
+const MAX_LISTED_TASKS: usize = 100;
+const TOP_TAG_COUNT: usize = 5;
+/// Task titles quoted in the template narrative.
+const NARRATIVE_TITLE_COUNT: usize = 5;
+/// Task titles passed to the AI for polishing.
+const POLISH_TITLE_COUNT: usize = 30;
+const MAX_REFLECTION_EVENTS: usize = 3;
+const REFLECTION_PREVIEW_CHARS: usize = 80;
+const UNFINISHED_BLOCK_STATUS: &str = "unfinished";
+
+pub struct HistoryService {
+    db: DbPool,
+    ai: Arc<AiService>,
+}
+
+impl HistoryService {
+    pub fn new(db: DbPool, ai: Arc<AiService>) -> Self {
+        Self { db, ai }
+    }
+
+    /// Summarizes `start..=end` (`YYYY-MM-DD`). With `polish` the template
+    /// narrative is rewritten by the AI provider when one is configured and
+    /// privacy mode is off; any AI failure keeps the template narrative.
+    pub async fn summarize(
+        &self,
+        start: &str,
+        end: &str,
+        polish: bool,
+    ) -> AppResult<HistorySummary> {
+        let (start, end) = parse_range(start, end)?;
+        let now = Utc::now();
+        let (mut summary, privacy_mode) = self.db.with_connection(|conn| {
+            Ok((
+                compose_history_summary(conn, start, end, now)?,
+                load_ai_privacy_mode(conn)?,
+            ))
+        })?;
+
+        let has_content = summary.completed_count > 0
+            || summary.focus_minutes > 0
+            || !summary.notable_events.is_empty();
+        if polish && !privacy_mode && has_content {
+            match self.ai.summarize_history(&polish_input(&summary)).await {
+                Ok(dto) => {
+                    summary.narrative = dto.narrative;
+                    summary.narrative_source = HistoryNarrativeSource::Ai;
+                }
+                Err(err) => {
+                    warn!(
+                        target: "app::history",
+                        error = %err,
+                        "history polish failed; keeping template narrative"
+                    );
+                }
+            }
+        }
+
+        debug!(
+            target: "app::history",
+            start = %summary.start_date,
+            end = %summary.end_date,
+            completed = summary.completed_count,
+            source = ?summary.narrative_source,
+            "history summarized"
+        );
+        Ok(summary)
+    }
+}
+
+/// Aggregates completed tasks, focus time and notable events of
+/// `start..=end` (UTC days) into a template-based summary.
+pub fn compose_history_summary(
+    conn: &Connection,
+    start: NaiveDate,
+    end: NaiveDate,
+    now: DateTime<Utc>,
+) -> AppResult<HistorySummary> {
+    let window_start = Utc.from_utc_datetime(&start.and_hms_opt(0, 0, 0).unwrap());
+    let window_end = Utc.from_utc_datetime(&end.and_hms_opt(0, 0, 0).unwrap()) + Duration::days(1);
+
+    let mut days: BTreeMap<NaiveDate, HistoryDayStat> = BTreeMap::new();
+    let mut date = start;
+    while date <= end {
+        days.insert(
+            date,
+            HistoryDayStat {
+                date: date.format("%Y-%m-%d").to_string(),
+                completed_count: 0,
+                focus_minutes: 0,
+            },
+        );
+        date = date.succ_opt().unwrap();
+    }
+
+    let mut completed = Vec::new();
+    let mut tag_counts: HashMap<String, usize> = HashMap::new();
+    let rows = TaskRepository::list_completed_between(
+        conn,
+        &window_start.to_rfc3339(),
+        &window_end.to_rfc3339(),
+    )?;
+    for row in rows {
+        let task = row.into_record()?;
+        let Some(completed_at) = task.completed_at.as_deref().and_then(parse_datetime) else {
+            continue;
+        };
+        if let Some(day) = days.get_mut(&completed_at.date_naive()) {
+            day.completed_count += 1;
+        }
+        for tag in &task.tags {
+            *tag_counts.entry(tag.trim().to_lowercase()).or_insert(0) += 1;
+        }
+        let late = task
+            .due_at
+            .as_deref()
+            .and_then(parse_datetime)
+            .is_some_and(|due_at| completed_at > due_at);
+        completed.push(HistoryCompletedTask {
+            task_id: task.id,
+            title: task.title,
+            completed_at: completed_at.to_rfc3339(),
+            tags: task.tags,
+            project_id: task.project_id,
+            late,
+        });
+    }
+
+    let mut focus_minutes = 0;
+    let blocks = PlanningRepository::list_applied_time_blocks_between(
+        conn,
+        &window_start.to_rfc3339(),
+        &window_end.to_rfc3339(),
+    )?;
+    for block in blocks {
+        if block.status == UNFINISHED_BLOCK_STATUS {
+            continue;
+        }
+        let start_at = block
+            .actual_start_at
+            .as_deref()
+            .or(Some(block.start_at.as_str()))
+            .and_then(parse_datetime);
+        let end_at = block
+            .actual_end_at
+            .as_deref()
+            .or(Some(block.end_at.as_str()))
+            .and_then(parse_datetime);
+        let (Some(start_at), Some(end_at)) = (start_at, end_at) else {
+            continue;
+        };
+        // Planned time that has not happened yet is not focus time.
+        let end_at = end_at.min(now);
+        if end_at <= start_at {
+            continue;
+        }
+        let minutes = (end_at - start_at).num_minutes();
+        focus_minutes += minutes;
+        if let Some(day) = days.get_mut(&start_at.date_naive()) {
+            day.focus_minutes += minutes;
+        }
+    }
+
+    let mut top_tags: Vec<HistoryTagCount> = tag_counts
+        .into_iter()
+        .filter(|(tag, _)| !tag.is_empty())
+        .map(|(tag, count)| HistoryTagCount { tag, count })
+        .collect();
+    top_tags.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+    top_tags.truncate(TOP_TAG_COUNT);
+
+    let days: Vec<HistoryDayStat> = days.into_values().collect();
+    let notable_events = notable_events(conn, &completed, &days, window_start, window_end)?;
+
+    let completed_count = completed.len();
+    completed.reverse();
+    completed.truncate(MAX_LISTED_TASKS);
+
+    let mut summary = HistorySummary {
+        start_date: start.format("%Y-%m-%d").to_string(),
+        end_date: end.format("%Y-%m-%d").to_string(),
+        completed_count,
+        completed_tasks: completed,
+        focus_minutes,
+        days,
+        top_tags,
+        notable_events,
+        narrative: String::new(),
+        narrative_source: HistoryNarrativeSource::Template,
+        generated_at: now.to_rfc3339(),
+    };
+    summary.narrative = template_narrative(&summary);
+    Ok(summary)
+}
+
+fn notable_events(
+    conn: &Connection,
+    completed: &[HistoryCompletedTask],
+    days: &[HistoryDayStat],
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+) -> AppResult<Vec<String>> {
+    let start = window_start.to_rfc3339();
+    let end = window_end.to_rfc3339();
+    let mut events = Vec::new();
+
+    let mut stmt = conn.prepare(
+        "SELECT title FROM goals WHERE status = 'completed' \
+         AND julianday(updated_at) >= julianday(?1) AND julianday(updated_at) < julianday(?2) \
+         ORDER BY julianday(updated_at) ASC",
+    )?;
+    for title in stmt.query_map([&start, &end], |row| row.get::<_, String>(0))? {
+        events.push(format!("达成目标「{}」", title?));
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT name FROM projects WHERE archived_at IS NOT NULL \
+         AND julianday(archived_at) >= julianday(?1) AND julianday(archived_at) < julianday(?2) \
+         ORDER BY julianday(archived_at) ASC",
+    )?;
+    for name in stmt.query_map([&start, &end], |row| row.get::<_, String>(0))? {
+        events.push(format!("归档项目「{}」", name?));
+    }
+
+    if days.len() > 1 {
+        if let Some(busiest) = days
+            .iter()
+            .filter(|day| day.completed_count >= 2)
+            .max_by_key(|day| day.completed_count)
+        {
+            events.push(format!(
+                "{} 完成任务最多（{} 项）",
+                busiest.date, busiest.completed_count
+            ));
+        }
+        if let Some(focused) = days
+            .iter()
+            .filter(|day| day.focus_minutes > 0)
+            .max_by_key(|day| day.focus_minutes)
+        {
+            events.push(format!(
+                "{} 专注时间最长（{}）",
+                focused.date,
+                format_minutes(focused.focus_minutes)
+            ));
+        }
+    }
+
+    let late = completed.iter().filter(|task| task.late).count();
+    if late > 0 {
+        events.push(format!("{late} 项任务晚于截止时间完成"));
+    }
+
+    let first = days
+        .first()
+        .map(|day| day.date.as_str())
+        .unwrap_or_default();
+    let last = days.last().map(|day| day.date.as_str()).unwrap_or_default();
+    let reflections: Vec<(String, String)> = DayLogRepository::list_between(conn, first, last)?
+        .into_iter()
+        .filter_map(|log| {
+            let reflection = log.reflection?.trim().to_string();
+            (!reflection.is_empty()).then_some((log.log_date, reflection))
+        })
+        .collect();
+    let skip = reflections.len().saturating_sub(MAX_REFLECTION_EVENTS);
+    for (date, reflection) in reflections.into_iter().skip(skip) {
+        let mut preview: String = reflection.chars().take(REFLECTION_PREVIEW_CHARS).collect();
+        if preview.chars().count() < reflection.chars().count() {
+            preview.push('…');
+        }
+        events.push(format!("{date} 复盘：{preview}"));
+    }
+
+    Ok(events)
+}
+
+fn template_narrative(summary: &HistorySummary) -> String {
+    let range = if summary.start_date == summary.end_date {
+        summary.start_date.clone()
+    } else {
+        format!("{} 至 {}", summary.start_date, summary.end_date)
+    };
+    if summary.completed_count == 0
+        && summary.focus_minutes == 0
+        && summary.notable_events.is_empty()
+    {
+        return format!("{range} 期间没有记录到已完成的任务或专注时间。");
+    }
+
+    let mut parts = vec![format!(
+        "{range}：完成 {} 项任务，专注 {}。",
+        summary.completed_count,
+        format_minutes(summary.focus_minutes)
+    )];
+
+    if !summary.completed_tasks.is_empty() {
+        let titles: Vec<String> = summary
+            .completed_tasks
+            .iter()
+            .take(NARRATIVE_TITLE_COUNT)
+            .map(|task| format!("「{}」", task.title))
+            .collect();
+        let suffix = if summary.completed_count > NARRATIVE_TITLE_COUNT {
+            " 等"
+        } else {
+            ""
+        };
+        parts.push(format!("主要完成：{}{suffix}。", titles.join("、")));
+    }
+
+    if !summary.top_tags.is_empty() {
+        let tags: Vec<String> = summary
+            .top_tags
+            .iter()
+            .map(|tag| format!("#{}（{}）", tag.tag, tag.count))
+            .collect();
+        parts.push(format!("重点领域：{}。", tags.join("、")));
+    }
+
+    if !summary.notable_events.is_empty() {
+        parts.push(format!("值得关注：{}。", summary.notable_events.join("；")));
+    }
+
+    parts.join("\n")
+}
+
+fn polish_input(summary: &HistorySummary) -> JsonValue {
+    let titles: Vec<&str> = summary
+        .completed_tasks
+        .iter()
+        .take(POLISH_TITLE_COUNT)
+        .map(|task| task.title.as_str())
+        .collect();
+    json!({
+        "startDate": summary.start_date,
+        "endDate": summary.end_date,
+        "completedCount": summary.completed_count,
+        "completedTasks": titles,
+        "focusMinutes": summary.focus_minutes,
+        "topTags": summary.top_tags,
+        "notableEvents": summary.notable_events,
+        "draft": summary.narrative,
+    })
+}
+
+/// Parses an inclusive `YYYY-MM-DD` range of at most a year.
+pub(crate) fn parse_range(start: &str, end: &str) -> AppResult<(NaiveDate, NaiveDate)> {
+    let parse = |value: &str| {
+        NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+            .map_err(|_| AppError::validation("日期格式应为 YYYY-MM-DD"))
+    };
+    let (start, end) = (parse(start)?, parse(end)?);
+    if start > end {
+        return Err(AppError::validation("开始日期不能晚于结束日期"));
+    }
+    if (end - start).num_days() >= MAX_RANGE_DAYS {
+        return Err(AppError::validation(format!(
+            "时间范围最长为 {MAX_RANGE_DAYS} 天"
+        )));
+    }
+    Ok((start, end))
+}
+
+fn parse_datetime(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+fn format_minutes(minutes: i64) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, rest) => format!("{rest} 分钟"),
+        (hours, 0) => format!("{hours} 小时"),
+        (hours, rest) => format!("{hours} 小时 {rest} 分钟"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::task::TaskCreateInput;
+    use crate::services::task_service::TaskService;
+    use tempfile::tempdir;
+
+    #[test]
+    fn compose_counts_completed_tasks_in_range() {
+        let dir = tempdir().expect("temp dir");
+        let pool = DbPool::new(dir.path().join("history.sqlite")).expect("db pool");
+        let tasks = TaskService::new(pool.clone());
+        for (title, completed_at, due_at) in [
+            ("整理周报", "2026-03-02T10:00:00+08:00", None),
+            (
+                "修复登录问题",
+                "2026-03-03T09:00:00Z",
+                Some("2026-03-02T18:00:00Z"),
+            ),
+            ("上个月的任务", "2026-02-20T09:00:00Z", None),
+        ] {
+            tasks
+                .create_task(TaskCreateInput {
+                    title: title.into(),
+                    status: Some("done".into()),
+                    completed_at: Some(completed_at.into()),
+                    due_at: due_at.map(Into::into),
+                    tags: Some(vec!["work".into()]),
+                    ..Default::default()
+                })
+                .expect("create task");
+        }
+
+        let start = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2026, 3, 7).unwrap();
+        let now = Utc.with_ymd_and_hms(2026, 3, 8, 0, 0, 0).unwrap();
+        let summary = pool
+            .with_connection(|conn| compose_history_summary(conn, start, end, now))
+            .expect("compose");
+
+        assert_eq!(summary.completed_count, 2);
+        assert_eq!(summary.days.len(), 7);
+        assert_eq!(summary.completed_tasks[0].title, "修复登录问题");
+        assert!(summary.completed_tasks[0].late);
+        assert_eq!(summary.top_tags[0].tag, "work");
+        assert_eq!(summary.top_tags[0].count, 2);
+        assert!(summary
+            .notable_events
+            .iter()
+            .any(|event| event == "1 项任务晚于截止时间完成"));
+        assert!(summary
+            .narrative
+            .starts_with("2026-03-01 至 2026-03-07：完成 2 项任务"));
+        assert_eq!(summary.narrative_source, HistoryNarrativeSource::Template);
+    }
+
+    #[test]
+    fn parse_range_rejects_inverted_and_oversized_ranges() {
+        assert!(parse_range("2026-03-02", "2026-03-01").is_err());
+        assert!(parse_range("2025-01-01", "2026-03-01").is_err());
+        assert!(parse_range("2026/03/01", "2026-03-02").is_err());
+        assert!(parse_range("2026-03-01", "2026-03-01").is_ok());
+    }
+}
@@ -0,0 +1,446 @@
+//! `.ics` files for calendars outside CalDAV: applied time blocks are
+//! exported as VEVENTs, and external calendars (a university timetable, a
+//! shared team calendar) are read into `existingEvents` for planning.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, Utc};
+use tracing::info;
+
+use crate::db::repositories::planning_repository::{PlanningRepository, PlanningTimeBlockRow};
+use crate::db::repositories::task_repository::TaskRepository;
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::ics::{IcsExportInput, IcsExportResult, IcsImportInput, IcsImportResult};
+use crate::services::caldav_sync_service::{block_event, is_block_uid};
+use crate::services::rrule_parser::RRuleParser;
+use crate::services::schedule_optimizer::{expand_recurring_events, ExistingEvent};
+use crate::services::schedule_utils;
+use crate::utils::files::write_atomic;
+use crate::utils::ical::{self, Component};
+use crate::utils::paths::sanitize_file_name;
+
+const ICS_PREFIX: &str = "cognical-plan";
+/// Event type of imported events without categories.
+const IMPORTED_EVENT_TYPE: &str = "imported";
+/// How far a series with exceptions is expanded when the import range has
+/// no end.
+const SERIES_HORIZON_DAYS: i64 = 366;
+
+pub struct IcsService {
+    db: DbPool,
+    exports_dir: PathBuf,
+}
+
+impl IcsService {
+    pub fn new(db: DbPool) -> Self {
+        let exports_dir = default_exports_dir(db.path());
+        Self { db, exports_dir }
+    }
+
+    /// Writes the applied blocks of the session (or all of them) to an
+    /// `.ics` file in the reports directory.
+    pub fn export(&self, input: IcsExportInput) -> AppResult<IcsExportResult> {
+        let now = Utc::now();
+        let session_id = input
+            .session_id
+            .as_deref()
+            .map(str::trim)
+            .filter(|id| !id.is_empty());
+
+        let (blocks, titles) = self.db.with_read_connection(|conn| {
+            let rows = match session_id {
+                Some(id) => {
+                    if PlanningRepository::find_session_by_id(conn, id)?.is_none() {
+                        return Err(AppError::not_found());
+                    }
+                    PlanningRepository::list_applied_time_blocks_for_session(conn, id)?
+                }
+                None => PlanningRepository::list_applied_time_blocks(conn)?,
+            };
+            let mut titles = HashMap::new();
+            for row in &rows {
+                if !titles.contains_key(&row.task_id) {
+                    let title =
+                        TaskRepository::find_by_id(conn, &row.task_id)?.map(|task| task.title);
+                    titles.insert(row.task_id.clone(), title);
+                }
+            }
+            let blocks = rows
+                .into_iter()
+                .map(PlanningTimeBlockRow::into_record)
+                .collect::<AppResult<Vec<_>>>()?;
+            Ok((blocks, titles))
+        })?;
+
+        let events: Vec<Component> = blocks
+            .iter()
+            .filter_map(|block| {
+                let title = titles
+                    .get(&block.task_id)
+                    .and_then(|title| title.as_deref());
+                block_event(block, title)
+            })
+            .collect();
+
+        std::fs::create_dir_all(&self.exports_dir)?;
+        let filename = match session_id {
+            Some(id) => format!("{ICS_PREFIX}-{id}.ics"),
+            None => format!("{ICS_PREFIX}-{}.ics", now.format("%Y%m%d-%H%M%S")),
+        };
+        let path = self.exports_dir.join(sanitize_file_name(&filename));
+        let checksum = write_atomic(&path, ical::write_calendar(&events).as_bytes())?;
+
+        info!(
+            target: "app::ics",
+            events = events.len(),
+            session_id = session_id.unwrap_or("all"),
+            "time blocks exported as ics"
+        );
+        Ok(IcsExportResult {
+            file_path: path.to_string_lossy().to_string(),
+            checksum,
+            event_count: events.len(),
+            generated_at: now.to_rfc3339(),
+        })
+    }
+}
+
+/// Reads the busy VEVENTs of an `.ics` file as planning constraints.
+/// Times are given in the local offset so recurring events keep their wall
+/// clock time when expanded.
+pub fn import_events(input: &IcsImportInput) -> AppResult<IcsImportResult> {
+    let range_start = parse_bound(input.start_at.as_deref())?;
+    let range_end = parse_bound(input.end_at.as_deref())?;
+    if let (Some(start), Some(end)) = (range_start, range_end) {
+        if end <= start {
+            return Err(AppError::validation("结束时间必须晚于开始时间"));
+        }
+    }
+
+    let roots = ical::parse(&input.content)?;
+    let components = ical::find_components(&roots, "VEVENT");
+    let mut exceptions: HashMap<String, SeriesExceptions> = HashMap::new();
+    for component in &components {
+        if let Some(uid) = component.text("UID") {
+            exceptions.entry(uid).or_default().collect(component);
+        }
+    }
+
+    let mut events = Vec::new();
+    let mut skipped = 0;
+    for component in components {
+        let event = match read_event(component) {
+            Some(event) if event.in_range(range_start, range_end) => event,
+            _ => {
+                skipped += 1;
+                continue;
+            }
+        };
+        // Excluded or moved occurrences only show up once the series is
+        // expanded, so those series are imported occurrence by occurrence.
+        let series_exceptions = exceptions
+            .get(&event.id)
+            .filter(|series| event.recurrence_rule.is_some() && !series.is_empty());
+        let Some(series_exceptions) = series_exceptions else {
+            events.push(event.into_existing());
+            continue;
+        };
+        let window_start = range_start.unwrap_or(event.start);
+        let window_end = range_end.unwrap_or(window_start + Duration::days(SERIES_HORIZON_DAYS));
+        let Ok(occurrences) = expand_recurring_events(
+            &[event.into_existing()],
+            window_start.fixed_offset(),
+            window_end.fixed_offset(),
+        ) else {
+            skipped += 1;
+            continue;
+        };
+        for occurrence in occurrences {
+            match schedule_utils::parse_datetime(&occurrence.start_at) {
+                Ok(start) if !series_exceptions.covers(start) => events.push(occurrence),
+                _ => skipped += 1,
+            }
+        }
+    }
+    events.sort_by(|a, b| a.start_at.cmp(&b.start_at));
+
+    Ok(IcsImportResult { events, skipped })
+}
+
+struct ImportedEvent {
+    id: String,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    event_type: String,
+    recurrence_rule: Option<String>,
+}
+
+impl ImportedEvent {
+    fn in_range(&self, start: Option<DateTime<Utc>>, end: Option<DateTime<Utc>>) -> bool {
+        let starts_before_end = end.is_none_or(|end| self.start < end);
+        if self.recurrence_rule.is_some() {
+            return starts_before_end;
+        }
+        starts_before_end && start.is_none_or(|start| self.end > start)
+    }
+
+    fn into_existing(self) -> ExistingEvent {
+        let local = |at: DateTime<Utc>| {
+            schedule_utils::format_datetime(at.with_timezone(&Local).fixed_offset())
+        };
+        ExistingEvent {
+            id: self.id,
+            start_at: local(self.start),
+            end_at: local(self.end),
+            event_type: Some(self.event_type),
+            recurrence_rule: self.recurrence_rule,
+        }
+    }
+}
+
+/// Occurrences of a series that are not imported from its master: EXDATEs
+/// and the RECURRENCE-IDs of instances that were moved or cancelled.
+#[derive(Default)]
+struct SeriesExceptions {
+    times: HashSet<DateTime<Utc>>,
+    dates: HashSet<NaiveDate>,
+}
+
+impl SeriesExceptions {
+    fn collect(&mut self, event: &Component) {
+        let properties = event.properties.iter().filter(|property| {
+            property.name.eq_ignore_ascii_case("EXDATE")
+                || property.name.eq_ignore_ascii_case("RECURRENCE-ID")
+        });
+        for property in properties {
+            for value in property.value.split(',').map(str::trim) {
+                if let Ok(date) = NaiveDate::parse_from_str(value, "%Y%m%d") {
+                    self.dates.insert(date);
+                } else if let Some(at) = ical::parse_datetime(value, property.param("TZID")) {
+                    self.times.insert(at);
+                }
+            }
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.times.is_empty() && self.dates.is_empty()
+    }
+
+    fn covers(&self, start: DateTime<FixedOffset>) -> bool {
+        self.times.contains(&start.with_timezone(&Utc)) || self.dates.contains(&start.date_naive())
+    }
+}
+
+/// `None` for events that do not block time or cannot be read.
+fn read_event(event: &Component) -> Option<ImportedEvent> {
+    let uid = event.text("UID")?;
+    if is_block_uid(&uid) {
+        return None;
+    }
+    let status = event.text("STATUS").unwrap_or_default();
+    let transparency = event.text("TRANSP").unwrap_or_default();
+    if status.eq_ignore_ascii_case("CANCELLED") || transparency.eq_ignore_ascii_case("TRANSPARENT")
+    {
+        return None;
+    }
+
+    let dtstart = event.property("DTSTART")?;
+    let is_date = dtstart
+        .param("VALUE")
+        .is_some_and(|value| value.eq_ignore_ascii_case("DATE"))
+        || dtstart.value.trim().len() == 8;
+    if is_date {
+        return None;
+    }
+    let start = event.datetime("DTSTART")?;
+    let end = match event.datetime("DTEND") {
+        Some(end) => end,
+        None => start + parse_duration(&event.text("DURATION")?)?,
+    };
+    if end <= start {
+        return None;
+    }
+
+    let recurrence_rule = match event.text("RRULE") {
+        Some(rule) => {
+            RRuleParser::parse(&rule).ok()?;
+            Some(rule)
+        }
+        None => None,
+    };
+    // Moved occurrences of a series share its UID.
+    let id = match event.property("RECURRENCE-ID") {
+        Some(recurrence_id) => format!("{uid}@{}", recurrence_id.value.trim()),
+        None => uid,
+    };
+    let event_type = event
+        .text_list("CATEGORIES")
+        .into_iter()
+        .next()
+        .map(|category| category.trim().to_lowercase())
+        .unwrap_or_else(|| IMPORTED_EVENT_TYPE.to_string());
+
+    Some(ImportedEvent {
+        id,
+        start,
+        end,
+        event_type,
+        recurrence_rule,
+    })
+}
+
+/// RFC 5545 durations such as `PT1H30M` or `P1W`; negative ones are
+/// rejected.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim().strip_prefix('+').unwrap_or(value.trim());
+    let rest = value.strip_prefix('P')?;
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+    for ch in rest.chars() {
+        match ch {
+            'T' if !in_time && number.is_empty() => in_time = true,
+            digit if digit.is_ascii_digit() => number.push(digit),
+            unit => {
+                let amount: i64 = number.parse().ok()?;
+                number.clear();
+                total += match (unit, in_time) {
+                    ('W', false) => Duration::weeks(amount),
+                    ('D', false) => Duration::days(amount),
+                    ('H', true) => Duration::hours(amount),
+                    ('M', true) => Duration::minutes(amount),
+                    ('S', true) => Duration::seconds(amount),
+                    _ => return None,
+                };
+            }
+        }
+    }
+    (number.is_empty() && total > Duration::zero()).then_some(total)
+}
+
+fn parse_bound(value: Option<&str>) -> AppResult<Option<DateTime<Utc>>> {
+    value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| schedule_utils::parse_datetime(value).map(|at| at.with_timezone(&Utc)))
+        .transpose()
+}
+
+fn default_exports_dir(db_path: &Path) -> PathBuf {
+    db_path
+        .parent()
+        .map(|dir| dir.join("reports"))
+        .unwrap_or_else(|| std::env::temp_dir().join("cognical"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMETABLE: &str = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+        BEGIN:VEVENT\r\nUID:course-1\r\nSUMMARY:线性代数\r\n\
+        DTSTART;TZID=Asia/Shanghai:20260907T080000\r\n\
+        DTEND;TZID=Asia/Shanghai:20260907T094000\r\n\
+        RRULE:FREQ=WEEKLY;UNTIL=20270110T000000Z\r\nCATEGORIES:class\r\nEND:VEVENT\r\n\
+        BEGIN:VEVENT\r\nUID:meeting-1\r\nDTSTART:20260910T060000Z\r\n\
+        DURATION:PT1H30M\r\nEND:VEVENT\r\n\
+        BEGIN:VEVENT\r\nUID:holiday\r\nDTSTART;VALUE=DATE:20261001\r\nEND:VEVENT\r\n\
+        BEGIN:VEVENT\r\nUID:free\r\nDTSTART:20260910T100000Z\r\n\
+        DTEND:20260910T110000Z\r\nTRANSP:TRANSPARENT\r\nEND:VEVENT\r\n\
+        BEGIN:VEVENT\r\nUID:cognical-time_block-b1\r\nDTSTART:20260910T120000Z\r\n\
+        DTEND:20260910T130000Z\r\nEND:VEVENT\r\n\
+        BEGIN:VEVENT\r\nUID:late\r\nDTSTART:20261210T060000Z\r\n\
+        DTEND:20261210T070000Z\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+
+    #[test]
+    fn imports_busy_timed_events_within_range() {
+        let result = import_events(&IcsImportInput {
+            content: TIMETABLE.to_string(),
+            start_at: Some("2026-09-08T00:00:00Z".to_string()),
+            end_at: Some("2026-09-15T00:00:00Z".to_string()),
+        })
+        .unwrap();
+
+        assert_eq!(result.skipped, 4);
+        let ids: Vec<&str> = result
+            .events
+            .iter()
+            .map(|event| event.id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["course-1", "meeting-1"]);
+
+        let course = &result.events[0];
+        assert_eq!(course.event_type.as_deref(), Some("class"));
+        assert_eq!(
+            course.recurrence_rule.as_deref(),
+            Some("FREQ=WEEKLY;UNTIL=20270110T000000Z")
+        );
+        let start = schedule_utils::parse_datetime(&course.start_at).unwrap();
+        assert_eq!(
+            start.with_timezone(&Utc).to_rfc3339(),
+            "2026-09-07T00:00:00+00:00"
+        );
+
+        let meeting = &result.events[1];
+        let start = schedule_utils::parse_datetime(&meeting.start_at).unwrap();
+        let end = schedule_utils::parse_datetime(&meeting.end_at).unwrap();
+        assert_eq!(end - start, Duration::minutes(90));
+        assert_eq!(meeting.event_type.as_deref(), Some(IMPORTED_EVENT_TYPE));
+    }
+
+    #[test]
+    fn drops_excluded_and_moved_occurrences_of_a_series() {
+        let content = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+            BEGIN:VEVENT\r\nUID:course-1\r\n\
+            DTSTART;TZID=Asia/Shanghai:20260907T080000\r\n\
+            DTEND;TZID=Asia/Shanghai:20260907T094000\r\n\
+            RRULE:FREQ=WEEKLY;UNTIL=20270110T000000Z\r\n\
+            EXDATE;TZID=Asia/Shanghai:20260914T080000\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nUID:course-1\r\n\
+            RECURRENCE-ID;TZID=Asia/Shanghai:20260921T080000\r\n\
+            DTSTART;TZID=Asia/Shanghai:20260922T100000\r\n\
+            DTEND;TZID=Asia/Shanghai:20260922T114000\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        let result = import_events(&IcsImportInput {
+            content: content.to_string(),
+            start_at: Some("2026-09-08T00:00:00Z".to_string()),
+            end_at: Some("2026-10-01T00:00:00Z".to_string()),
+        })
+        .unwrap();
+
+        assert_eq!(result.skipped, 2);
+        let starts: Vec<String> = result
+            .events
+            .iter()
+            .map(|event| {
+                assert!(event.recurrence_rule.is_none());
+                let start = schedule_utils::parse_datetime(&event.start_at).unwrap();
+                start.with_timezone(&Utc).to_rfc3339()
+            })
+            .collect();
+        assert_eq!(
+            starts,
+            vec!["2026-09-22T02:00:00+00:00", "2026-09-28T00:00:00+00:00"]
+        );
+        assert_eq!(result.events[0].id, "course-1@20260921T080000");
+        assert!(result.events[1].id.starts_with("course-1@"));
+    }
+
+    #[test]
+    fn parses_durations_and_rejects_bad_ranges() {
+        assert_eq!(parse_duration("PT45M"), Some(Duration::minutes(45)));
+        assert_eq!(parse_duration("P1DT2H"), Some(Duration::hours(26)));
+        assert_eq!(parse_duration("P1W"), Some(Duration::weeks(1)));
+        assert_eq!(parse_duration("-PT1H"), None);
+        assert_eq!(parse_duration("PT"), None);
+
+        let err = import_events(&IcsImportInput {
+            content: TIMETABLE.to_string(),
+            start_at: Some("2026-09-15T00:00:00Z".to_string()),
+            end_at: Some("2026-09-08T00:00:00Z".to_string()),
+        });
+        assert!(err.is_err());
+    }
+}
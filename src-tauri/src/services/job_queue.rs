@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::Connection;
+use serde_json::Value as JsonValue;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::db::repositories::job_repository::{BackgroundJobRow, JobRepository, JobRunRepository};
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::job::{BackgroundJobRecord, BackgroundJobStatus, BackgroundJobsOverview};
+use crate::services::job_supervisor::spawn_supervised;
+use crate::services::power_throttle::PowerThrottle;
+
+pub const JOB_KIND_WORKLOAD_FORECAST: &str = "workload_forecast";
+pub const JOB_KIND_ANALYTICS_SNAPSHOT: &str = "analytics_snapshot";
+
+const DEFAULT_MAX_ATTEMPTS: i64 = 8;
+const BACKOFF_BASE_SECONDS: i64 = 5 * 60;
+const BACKOFF_MAX_SECONDS: i64 = 6 * 60 * 60;
+const POLL_INTERVAL: StdDuration = StdDuration::from_secs(60);
+/// Jobs attempted per poll, so a backlog does not hammer a service that has
+/// just come back.
+const JOBS_PER_POLL: usize = 3;
+
+/// Re-runs the work described by a job's payload.
+pub type JobHandler = Arc<dyn Fn(&JsonValue) -> AppResult<()> + Send + Sync>;
+
+/// Delay before the next attempt once `attempts` attempts have failed.
+pub fn backoff_delay(attempts: i64) -> Duration {
+    let exponent = attempts.clamp(0, 16) as u32;
+    let seconds = BACKOFF_BASE_SECONDS.saturating_mul(1_i64 << exponent);
+    Duration::seconds(seconds.min(BACKOFF_MAX_SECONDS))
+}
+
+/// Queues `kind` for a retry after it failed in the background. Queuing the
+/// same kind and payload again only refreshes the recorded error.
+pub fn enqueue_retry(
+    conn: &Connection,
+    kind: &str,
+    payload: &JsonValue,
+    failure: &str,
+    now: DateTime<Utc>,
+) -> AppResult<BackgroundJobRecord> {
+    let serialized = serde_json::to_string(payload)?;
+    let timestamp = now.to_rfc3339();
+
+    let record = match JobRepository::find_pending(conn, kind, &serialized)? {
+        Some(existing) => {
+            let mut record = existing.into_record()?;
+            record.last_error = Some(failure.to_string());
+            record.updated_at = timestamp;
+            record
+        }
+        None => BackgroundJobRecord {
+            id: Uuid::new_v4().to_string(),
+            kind: kind.to_string(),
+            payload: payload.clone(),
+            status: BackgroundJobStatus::Pending,
+            attempts: 0,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            next_attempt_at: (now + backoff_delay(0)).to_rfc3339(),
+            last_error: Some(failure.to_string()),
+            created_at: timestamp.clone(),
+            updated_at: timestamp,
+        },
+    };
+
+    JobRepository::upsert(conn, &BackgroundJobRow::from_record(&record)?)?;
+    info!(target: "app::jobs", kind, job_id = %record.id, "background job queued for retry");
+    Ok(record)
+}
+
+/// Persistent retry queue for background work that failed, e.g. the nightly
+/// forecast. A worker thread re-runs due jobs through the registered handlers.
+pub struct JobQueueService {
+    db: DbPool,
+    handlers: RwLock<HashMap<String, JobHandler>>,
+    worker_started: AtomicBool,
+}
+
+impl JobQueueService {
+    pub fn new(db: DbPool) -> Self {
+        Self {
+            db,
+            handlers: RwLock::new(HashMap::new()),
+            worker_started: AtomicBool::new(false),
+        }
+    }
+
+    pub fn register(&self, kind: &str, handler: JobHandler) {
+        if let Ok(mut handlers) = self.handlers.write() {
+            handlers.insert(kind.to_string(), handler);
+        }
+    }
+
+    pub fn list(&self, status: Option<String>) -> AppResult<Vec<BackgroundJobRecord>> {
+        let status = status
+            .map(|value| {
+                BackgroundJobStatus::try_from(value.trim()).map_err(|_| {
+                    AppError::validation("任务状态仅支持 pending、succeeded 或 failed")
+                })
+            })
+            .transpose()?;
+
+        self.db.with_connection(|conn| {
+            JobRepository::list(conn, status.as_ref().map(BackgroundJobStatus::as_str))?
+                .into_iter()
+                .map(BackgroundJobRow::into_record)
+                .collect()
+        })
+    }
+
+    /// Queue entries plus the health of every supervised worker thread.
+    pub fn overview(&self, status: Option<String>) -> AppResult<BackgroundJobsOverview> {
+        let jobs = self.list(status)?;
+        let workers = self.db.with_connection(|conn| {
+            JobRunRepository::list(conn)?
+                .into_iter()
+                .map(|row| row.into_record())
+                .collect()
+        })?;
+
+        Ok(BackgroundJobsOverview { jobs, workers })
+    }
+
+    /// Attempts up to [`JOBS_PER_POLL`] due jobs and returns how many ran.
+    pub fn run_due(&self, now: DateTime<Utc>) -> AppResult<usize> {
+        let due = self.db.with_connection(|conn| {
+            JobRepository::list_due(conn, &now.to_rfc3339(), JOBS_PER_POLL)?
+                .into_iter()
+                .map(BackgroundJobRow::into_record)
+                .collect::<AppResult<Vec<_>>>()
+        })?;
+
+        for mut job in due.iter().cloned() {
+            let handler = self
+                .handlers
+                .read()
+                .ok()
+                .and_then(|handlers| handlers.get(&job.kind).cloned());
+            let outcome = match handler {
+                Some(handler) => handler(&job.payload),
+                None => Err(AppError::other(format!(
+                    "未注册的后台任务类型: {}",
+                    job.kind
+                ))),
+            };
+
+            job.attempts += 1;
+            job.updated_at = now.to_rfc3339();
+            match outcome {
+                Ok(()) => {
+                    job.status = BackgroundJobStatus::Succeeded;
+                    job.last_error = None;
+                    info!(target: "app::jobs", kind = %job.kind, job_id = %job.id, attempts = job.attempts, "background job succeeded");
+                }
+                Err(err) => {
+                    job.last_error = Some(err.to_string());
+                    if job.attempts >= job.max_attempts {
+                        job.status = BackgroundJobStatus::Failed;
+                        error!(target: "app::jobs", kind = %job.kind, job_id = %job.id, error = %err, "background job gave up");
+                    } else {
+                        job.next_attempt_at = (now + backoff_delay(job.attempts)).to_rfc3339();
+                        warn!(target: "app::jobs", kind = %job.kind, job_id = %job.id, error = %err, "background job failed, retry scheduled");
+                    }
+                }
+            }
+
+            let row = BackgroundJobRow::from_record(&job)?;
+            self.db
+                .with_background_write(|conn| JobRepository::upsert(conn, &row))?;
+        }
+
+        Ok(due.len())
+    }
+
+    pub fn ensure_worker(self: &Arc<Self>) -> AppResult<()> {
+        if self
+            .worker_started
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+        {
+            let runner = Arc::clone(self);
+            if let Err(err) = spawn_supervised(self.db.clone(), "job-retry-worker", move || {
+                runner.run_worker_loop()
+            }) {
+                self.worker_started.store(false, Ordering::SeqCst);
+                return Err(err);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn run_worker_loop(&self) {
+        let throttle = PowerThrottle::new(self.db.clone());
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            throttle.wait_until_allowed("job-retry-worker");
+            match self.run_due(Utc::now()) {
+                Ok(0) => {}
+                Ok(count) => debug!(target: "app::jobs", count, "background jobs attempted"),
+                Err(err) => error!(target: "app::jobs", error = %err, "background job poll failed"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn failed_jobs_back_off_until_they_succeed() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = DbPool::new(dir.path().join("jobs.sqlite")).unwrap();
+        let queue = JobQueueService::new(db.clone());
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        queue.register(
+            JOB_KIND_ANALYTICS_SNAPSHOT,
+            Arc::new(move |payload| {
+                assert_eq!(payload["date"], "2026-03-01");
+                match counter.fetch_add(1, Ordering::SeqCst) {
+                    0 => Err(AppError::other("AI 服务不可用")),
+                    _ => Ok(()),
+                }
+            }),
+        );
+
+        let now = Utc::now();
+        let payload = json!({ "date": "2026-03-01" });
+        let queued = db
+            .with_connection(|conn| {
+                enqueue_retry(conn, JOB_KIND_ANALYTICS_SNAPSHOT, &payload, "boom", now)?;
+                enqueue_retry(
+                    conn,
+                    JOB_KIND_ANALYTICS_SNAPSHOT,
+                    &payload,
+                    "boom again",
+                    now,
+                )
+            })
+            .unwrap();
+        assert_eq!(queue.list(None).unwrap().len(), 1);
+        assert_eq!(queued.last_error.as_deref(), Some("boom again"));
+
+        assert_eq!(queue.run_due(now).unwrap(), 0);
+
+        let first_try = now + backoff_delay(0);
+        assert_eq!(queue.run_due(first_try).unwrap(), 1);
+        let job = &queue.list(Some("pending".to_string())).unwrap()[0];
+        assert_eq!(job.attempts, 1);
+        assert_eq!(
+            job.next_attempt_at,
+            (first_try + backoff_delay(1)).to_rfc3339()
+        );
+
+        assert_eq!(queue.run_due(first_try + backoff_delay(1)).unwrap(), 1);
+        let done = queue.list(Some("succeeded".to_string())).unwrap();
+        assert_eq!(done.len(), 1);
+        assert!(done[0].last_error.is_none());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        assert_eq!(backoff_delay(1), Duration::minutes(10));
+        assert_eq!(backoff_delay(20), Duration::hours(6));
+        assert!(queue.list(Some("bogus".to_string())).is_err());
+    }
+}
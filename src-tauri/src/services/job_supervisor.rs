@@ -0,0 +1,148 @@
+//! Keeps long-running background workers alive. A worker body that panics is
+//! caught, logged, recorded in `job_runs` and restarted after a backoff, so one
+//! bad night does not silently stop the forecast or snapshot job for good.
+
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+use std::time::Duration as StdDuration;
+
+use chrono::Utc;
+use tracing::{error, info, warn};
+
+use crate::db::repositories::job_repository::{JobRunRepository, JobRunRow};
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::job::{JobRunRecord, JobRunStatus};
+
+const RESTART_BASE: StdDuration = StdDuration::from_secs(5);
+const RESTART_MAX: StdDuration = StdDuration::from_secs(10 * 60);
+
+/// Delay before restarting a worker that has panicked `restarts` times.
+pub fn restart_delay(base: StdDuration, restarts: i64) -> StdDuration {
+    let exponent = (restarts - 1).clamp(0, 16) as u32;
+    base.saturating_mul(1_u32 << exponent).min(RESTART_MAX)
+}
+
+/// Spawns `body` on a named thread under supervision. `body` is the worker
+/// loop; returning from it ends supervision.
+pub fn spawn_supervised<F>(db: DbPool, name: &str, body: F) -> AppResult<()>
+where
+    F: Fn() + Send + 'static,
+{
+    let worker = name.to_string();
+    thread::Builder::new()
+        .name(worker.clone())
+        .spawn(move || supervise(&db, &worker, RESTART_BASE, body))
+        .map(|_| ())
+        .map_err(|err| AppError::other(format!("无法启动后台任务 {name}: {err}")))
+}
+
+/// Runs `body` until it returns, restarting it after each panic.
+fn supervise<F: Fn()>(db: &DbPool, name: &str, restart_base: StdDuration, body: F) {
+    let mut run = JobRunRecord {
+        name: name.to_string(),
+        status: JobRunStatus::Running,
+        restarts: 0,
+        last_panic: None,
+        last_panic_at: None,
+        started_at: Utc::now().to_rfc3339(),
+        updated_at: Utc::now().to_rfc3339(),
+    };
+    if let Ok(Some(previous)) = db.with_connection(|conn| JobRunRepository::find(conn, name)) {
+        if let Ok(previous) = previous.into_record() {
+            run.last_panic = previous.last_panic;
+            run.last_panic_at = previous.last_panic_at;
+        }
+    }
+    record(db, &run);
+
+    loop {
+        match panic::catch_unwind(AssertUnwindSafe(&body)) {
+            Ok(()) => {
+                info!(target: "app::jobs", worker = name, "background worker stopped");
+                run.status = JobRunStatus::Stopped;
+                run.updated_at = Utc::now().to_rfc3339();
+                record(db, &run);
+                return;
+            }
+            Err(payload) => {
+                let message = panic_message(payload.as_ref());
+                run.restarts += 1;
+                let delay = restart_delay(restart_base, run.restarts);
+                error!(
+                    target: "app::jobs",
+                    worker = name,
+                    restarts = run.restarts,
+                    panic = %message,
+                    retry_in_secs = delay.as_secs(),
+                    "background worker panicked, restarting"
+                );
+                let now = Utc::now().to_rfc3339();
+                run.status = JobRunStatus::Restarting;
+                run.last_panic = Some(message);
+                run.last_panic_at = Some(now.clone());
+                run.updated_at = now;
+                record(db, &run);
+
+                thread::sleep(delay);
+
+                run.status = JobRunStatus::Running;
+                run.started_at = Utc::now().to_rfc3339();
+                run.updated_at = run.started_at.clone();
+                record(db, &run);
+            }
+        }
+    }
+}
+
+fn record(db: &DbPool, run: &JobRunRecord) {
+    let row = JobRunRow::from_record(run);
+    if let Err(err) = db.with_background_write(|conn| JobRunRepository::upsert(conn, &row)) {
+        warn!(target: "app::jobs", worker = %run.name, error = %err, "failed to record worker health");
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn panicking_workers_are_restarted_and_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = DbPool::new(dir.path().join("jobs.sqlite")).unwrap();
+
+        let calls = AtomicUsize::new(0);
+        supervise(&db, "forecast-job", StdDuration::ZERO, || {
+            if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                panic!("forecast exploded");
+            }
+        });
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let run = db
+            .with_connection(|conn| JobRunRepository::find(conn, "forecast-job"))
+            .unwrap()
+            .unwrap()
+            .into_record()
+            .unwrap();
+        assert_eq!(run.status, JobRunStatus::Stopped);
+        assert_eq!(run.restarts, 2);
+        assert_eq!(run.last_panic.as_deref(), Some("forecast exploded"));
+        assert!(run.last_panic_at.is_some());
+
+        let base = StdDuration::from_secs(5);
+        assert_eq!(restart_delay(base, 1), base);
+        assert_eq!(restart_delay(base, 3), StdDuration::from_secs(20));
+        assert_eq!(restart_delay(base, 40), RESTART_MAX);
+    }
+}
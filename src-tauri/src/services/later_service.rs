@@ -0,0 +1,341 @@
+use chrono::{DateTime, Datelike, FixedOffset, Utc};
+use uuid::Uuid;
+
+use crate::db::repositories::later_repository::{LaterItemRow, LaterRepository};
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::later::{
+    LaterItemCreateInput, LaterItemKind, LaterItemRecord, LaterSlotKind, LaterSlotSuggestion,
+};
+use crate::models::planning::PlanningTimeBlockRecord;
+use crate::services::schedule_optimizer::SchedulingPreferences;
+use crate::services::schedule_utils;
+
+pub const LATER_STATUS_QUEUED: &str = "queued";
+pub const LATER_STATUS_COMPLETED: &str = "completed";
+
+const MAX_ESTIMATED_MINUTES: i64 = 8 * 60;
+const MIN_SLOT_MINUTES: i64 = 15;
+// Post-lunch dip used when the user has not configured a focus window.
+const DEFAULT_LOW_ENERGY_START_MINUTE: i64 = 13 * 60;
+const DEFAULT_LOW_ENERGY_END_MINUTE: i64 = 15 * 60;
+
+/// "Later list" of articles, videos and courses. Items never enter the task
+/// priority ordering; the planner only offers them for leftover gaps.
+pub struct LaterService {
+    db: DbPool,
+}
+
+impl LaterService {
+    pub fn new(db: DbPool) -> Self {
+        Self { db }
+    }
+
+    pub fn add(&self, input: LaterItemCreateInput) -> AppResult<LaterItemRecord> {
+        let title = input.title.trim().to_string();
+        if title.is_empty() {
+            return Err(AppError::validation("标题不能为空"));
+        }
+        if input.estimated_minutes <= 0 || input.estimated_minutes > MAX_ESTIMATED_MINUTES {
+            return Err(AppError::validation(format!(
+                "预计时长需在 1 到 {MAX_ESTIMATED_MINUTES} 分钟之间"
+            )));
+        }
+
+        let now = Utc::now().to_rfc3339();
+        let record = LaterItemRecord {
+            id: Uuid::new_v4().to_string(),
+            title,
+            url: trim_optional(input.url),
+            kind: input.kind.unwrap_or(LaterItemKind::Other),
+            estimated_minutes: input.estimated_minutes,
+            notes: trim_optional(input.notes),
+            status: LATER_STATUS_QUEUED.to_string(),
+            created_at: now.clone(),
+            updated_at: now,
+            completed_at: None,
+        };
+
+        let row = LaterItemRow::from_record(&record);
+        self.db
+            .with_connection(|conn| LaterRepository::insert(conn, &row))?;
+
+        Ok(record)
+    }
+
+    pub fn list(&self, status: Option<String>) -> AppResult<Vec<LaterItemRecord>> {
+        let status = status.filter(|value| !value.trim().is_empty());
+        if let Some(value) = status.as_deref() {
+            if value != LATER_STATUS_QUEUED && value != LATER_STATUS_COMPLETED {
+                return Err(AppError::validation(format!("不支持的状态: {value}")));
+            }
+        }
+
+        self.db
+            .with_connection(|conn| LaterRepository::list(conn, status.as_deref()))
+    }
+
+    pub fn complete(&self, id: &str) -> AppResult<LaterItemRecord> {
+        self.db.with_connection(|conn| {
+            let existing =
+                LaterRepository::find_by_id(conn, id)?.ok_or_else(AppError::not_found)?;
+            if existing.status == LATER_STATUS_COMPLETED {
+                return Ok(existing);
+            }
+
+            LaterRepository::mark_completed(conn, id, &Utc::now().to_rfc3339())?;
+            LaterRepository::find_by_id(conn, id)?.ok_or_else(AppError::not_found)
+        })
+    }
+}
+
+/// Fits queued later items into the gaps between planned blocks. Low-energy
+/// gaps are filled first; items keep their queue (FIFO) order and each item is
+/// suggested at most once.
+pub fn suggest_later_slots(
+    blocks: &[PlanningTimeBlockRecord],
+    items: &[LaterItemRecord],
+    preferences: &SchedulingPreferences,
+) -> Vec<LaterSlotSuggestion> {
+    let mut spans: Vec<(DateTime<FixedOffset>, DateTime<FixedOffset>)> = blocks
+        .iter()
+        .filter_map(|block| {
+            let start = schedule_utils::parse_datetime(&block.start_at).ok()?;
+            let end = schedule_utils::parse_datetime(&block.end_at).ok()?;
+            (end > start).then_some((start, end))
+        })
+        .collect();
+    spans.sort_by_key(|(start, _)| *start);
+
+    let mut gaps = Vec::new();
+    for pair in spans.windows(2) {
+        let (_, prev_end) = pair[0];
+        let (next_start, _) = pair[1];
+        if !schedule_utils::same_day(prev_end, next_start) {
+            continue;
+        }
+        let buffer = preferences.buffer_minutes_on(prev_end.weekday().num_days_from_monday());
+        let (Ok(gap_start), Ok(gap_end)) = (
+            schedule_utils::add_minutes(prev_end, buffer),
+            schedule_utils::add_minutes(next_start, -buffer),
+        ) else {
+            continue;
+        };
+        let minutes = gap_end.signed_duration_since(gap_start).num_minutes();
+        if minutes >= MIN_SLOT_MINUTES {
+            gaps.push((
+                classify_gap(gap_start, gap_end, preferences),
+                gap_start,
+                gap_end,
+            ));
+        }
+    }
+    gaps.sort_by_key(|(kind, start, _)| (*kind != LaterSlotKind::LowEnergy, *start));
+
+    let mut queue: Vec<&LaterItemRecord> = items
+        .iter()
+        .filter(|item| item.status == LATER_STATUS_QUEUED)
+        .collect();
+    let mut suggestions = Vec::new();
+
+    for (kind, gap_start, gap_end) in gaps {
+        let mut cursor = gap_start;
+        let mut index = 0;
+        while index < queue.len() {
+            let item = queue[index];
+            let remaining = gap_end.signed_duration_since(cursor).num_minutes();
+            if item.estimated_minutes > remaining {
+                index += 1;
+                continue;
+            }
+            let Ok(end) = schedule_utils::add_minutes(cursor, item.estimated_minutes) else {
+                index += 1;
+                continue;
+            };
+            suggestions.push(LaterSlotSuggestion {
+                item_id: item.id.clone(),
+                title: item.title.clone(),
+                start_at: schedule_utils::format_datetime(cursor),
+                end_at: schedule_utils::format_datetime(end),
+                slot_kind: kind,
+            });
+            cursor = end;
+            queue.remove(index);
+        }
+    }
+
+    suggestions.sort_by(|a, b| a.start_at.cmp(&b.start_at));
+    suggestions
+}
+
+fn classify_gap(
+    start: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+    preferences: &SchedulingPreferences,
+) -> LaterSlotKind {
+    let start_minute = schedule_utils::midnight_minutes_of(start);
+    let end_minute = schedule_utils::midnight_minutes_of(end);
+
+    let focus_window = preferences.focus_window_on(start.weekday().num_days_from_monday());
+    let is_slack = match focus_window {
+        Some((focus_start, focus_end)) => {
+            start_minute >= focus_start as i64 && end_minute <= focus_end as i64
+        }
+        None => {
+            !(start_minute < DEFAULT_LOW_ENERGY_END_MINUTE
+                && end_minute > DEFAULT_LOW_ENERGY_START_MINUTE)
+        }
+    };
+
+    if is_slack {
+        LaterSlotKind::Slack
+    } else {
+        LaterSlotKind::LowEnergy
+    }
+}
+
+fn trim_optional(value: Option<String>) -> Option<String> {
+    value
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn block(id: &str, start_at: &str, end_at: &str) -> PlanningTimeBlockRecord {
+        PlanningTimeBlockRecord {
+            id: id.to_string(),
+            option_id: "option".to_string(),
+            task_id: format!("task-{id}"),
+            start_at: start_at.to_string(),
+            end_at: end_at.to_string(),
+            flexibility: None,
+            confidence: None,
+            conflict_flags: None,
+            applied_at: None,
+            actual_start_at: None,
+            actual_end_at: None,
+            status: "draft".to_string(),
+            prep_note: None,
+        }
+    }
+
+    fn item(id: &str, minutes: i64) -> LaterItemRecord {
+        LaterItemRecord {
+            id: id.to_string(),
+            title: format!("Item {id}"),
+            url: None,
+            kind: LaterItemKind::Article,
+            estimated_minutes: minutes,
+            notes: None,
+            status: LATER_STATUS_QUEUED.to_string(),
+            created_at: "2025-05-01T00:00:00+00:00".to_string(),
+            updated_at: "2025-05-01T00:00:00+00:00".to_string(),
+            completed_at: None,
+        }
+    }
+
+    #[test]
+    fn fills_low_energy_gap_before_slack() {
+        let blocks = vec![
+            block(
+                "a",
+                "2025-05-01T09:00:00+00:00",
+                "2025-05-01T10:00:00+00:00",
+            ),
+            block(
+                "b",
+                "2025-05-01T10:30:00+00:00",
+                "2025-05-01T12:30:00+00:00",
+            ),
+            block(
+                "c",
+                "2025-05-01T13:30:00+00:00",
+                "2025-05-01T15:00:00+00:00",
+            ),
+        ];
+        let items = vec![item("long", 50), item("short", 25)];
+
+        let suggestions = suggest_later_slots(&blocks, &items, &SchedulingPreferences::default());
+
+        assert_eq!(suggestions.len(), 2);
+        assert_eq!(suggestions[0].item_id, "short");
+        assert_eq!(suggestions[0].slot_kind, LaterSlotKind::Slack);
+        assert_eq!(suggestions[1].item_id, "long");
+        assert_eq!(suggestions[1].slot_kind, LaterSlotKind::LowEnergy);
+        assert_eq!(suggestions[1].start_at, "2025-05-01T12:30:00+00:00");
+    }
+
+    #[test]
+    fn respects_buffer_and_skips_completed_items() {
+        let blocks = vec![
+            block(
+                "a",
+                "2025-05-01T09:00:00+00:00",
+                "2025-05-01T10:00:00+00:00",
+            ),
+            block(
+                "b",
+                "2025-05-01T10:40:00+00:00",
+                "2025-05-01T11:00:00+00:00",
+            ),
+        ];
+        let mut done = item("done", 10);
+        done.status = LATER_STATUS_COMPLETED.to_string();
+        let items = vec![done, item("fits", 20), item("too-long", 30)];
+        let preferences = SchedulingPreferences {
+            buffer_minutes_between_blocks: 10,
+            ..Default::default()
+        };
+
+        let suggestions = suggest_later_slots(&blocks, &items, &preferences);
+
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].item_id, "fits");
+        assert_eq!(suggestions[0].start_at, "2025-05-01T10:10:00+00:00");
+    }
+
+    #[test]
+    fn add_list_and_complete_round_trip() {
+        let dir = tempdir().expect("temp dir");
+        let service = LaterService::new(DbPool::new(dir.path().join("later.sqlite")).unwrap());
+
+        assert!(service
+            .add(LaterItemCreateInput {
+                title: "  ".into(),
+                url: None,
+                kind: None,
+                estimated_minutes: 10,
+                notes: None,
+            })
+            .is_err());
+
+        let added = service
+            .add(LaterItemCreateInput {
+                title: "Rust async book, chapter 3".into(),
+                url: Some(" https://rust-lang.github.io/async-book/ ".into()),
+                kind: Some(LaterItemKind::Course),
+                estimated_minutes: 40,
+                notes: None,
+            })
+            .unwrap();
+        assert_eq!(
+            added.url.as_deref(),
+            Some("https://rust-lang.github.io/async-book/")
+        );
+
+        let completed = service.complete(&added.id).unwrap();
+        assert_eq!(completed.status, LATER_STATUS_COMPLETED);
+        assert!(completed.completed_at.is_some());
+
+        assert!(service
+            .list(Some(LATER_STATUS_QUEUED.into()))
+            .unwrap()
+            .is_empty());
+        assert_eq!(service.list(None).unwrap().len(), 1);
+        assert!(service.list(Some("archived".into())).is_err());
+    }
+}
@@ -3,20 +3,27 @@ use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
-use std::time::Instant;
+use std::time::{Instant, SystemTime};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use serde_yaml;
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
 use crate::error::{AppError, AppResult};
 use crate::models::memory::{
-    ContextSufficiency, ConversationSummary, ExportInfo, IndexStatistics, JsonExport,
-    MemoryContext, MemoryDocument, MemoryExportFormat, MemoryExportOptions, MemoryIndex,
-    MemoryMetadata, MemorySearchQuery, MemoryStats, MemoryUsage, MemoryValidationReport,
+    ContextSufficiency, ConversationSummary, ExportInfo, ExportedFile, IndexStatistics,
+    MemoryContext, MemoryDocument, MemoryExportBundle, MemoryExportFormat, MemoryExportOptions,
+    MemoryFact, MemoryFacts, MemoryIndex, MemoryMetadata, MemorySearchQuery, MemoryStats,
+    MemoryTopicCount, MemoryUsage, MemoryValidationReport,
 };
+use crate::services::progress::{scaled_percent, ProgressReporter};
+use crate::utils::bundle::BundleWriter;
+use crate::utils::files::write_atomic_with;
+use crate::utils::paths::sanitize_relative_path;
+use crate::utils::tokens::TokenizerProfile;
 
 /// Search result cache for frequently accessed queries
 #[derive(Clone)]
@@ -220,16 +227,119 @@ impl InvertedIndex {
     }
 }
 
+/// Sidecar in the memory directory caching parsed documents and the inverted
+/// index, so startup only re-reads markdown files that changed since.
+const INDEX_SNAPSHOT_FILE: &str = ".index-snapshot.json";
+/// Structured "facts about me", stored apart from conversation documents.
+const FACTS_FILE: &str = "facts.json";
+const MAX_FACTS: usize = 50;
+const MAX_FACT_CHARS: usize = 500;
+
+/// Version 1 snapshots stored the load time as `created_at`; they are
+/// migrated on load.
+const INDEX_SNAPSHOT_VERSION: u32 = 2;
+
+#[derive(Serialize, Deserialize, Default)]
+struct IndexSnapshot {
+    version: u32,
+    /// Keyed by path relative to the memory directory.
+    files: HashMap<String, SnapshotEntry>,
+    word_to_docs: HashMap<String, HashSet<String>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotEntry {
+    modified: Option<SystemTime>,
+    len: u64,
+    document: MemoryDocument,
+}
+
+/// A memory document picked for export.
+struct ExportEntry {
+    id: String,
+    file_path: PathBuf,
+    created_at: DateTime<Utc>,
+}
+
+/// Reports `exporting` progress once per percent rather than per document,
+/// checking for cancellation before each one.
+struct ExportProgress<'a> {
+    progress: &'a ProgressReporter,
+    total: usize,
+    done: usize,
+    last_percent: Option<u8>,
+}
+
+impl<'a> ExportProgress<'a> {
+    fn new(progress: &'a ProgressReporter, total: usize) -> Self {
+        Self {
+            progress,
+            total,
+            done: 0,
+            last_percent: None,
+        }
+    }
+
+    fn advance(&mut self) -> AppResult<()> {
+        let percent = scaled_percent(self.done, self.total, 5, 95);
+        self.done += 1;
+        if self.last_percent == Some(percent) {
+            return self.progress.checkpoint();
+        }
+        self.last_percent = Some(percent);
+        self.progress.report("exporting", percent)
+    }
+}
+
+impl SnapshotEntry {
+    fn is_fresh(&self, metadata: &fs::Metadata) -> bool {
+        self.modified.is_some()
+            && self.modified == metadata.modified().ok()
+            && self.len == metadata.len()
+    }
+}
+
+/// When a document was written: the frontmatter timestamp if present,
+/// otherwise its `date` (at the file's modification time when that falls on
+/// the same day, else midnight UTC), otherwise the modification time.
+fn document_created_at(metadata: &MemoryMetadata, modified: Option<SystemTime>) -> DateTime<Utc> {
+    if let Some(created_at) = metadata.created_at {
+        return created_at;
+    }
+
+    let modified = modified.map(DateTime::<Utc>::from);
+    match NaiveDate::parse_from_str(&metadata.date, "%Y-%m-%d") {
+        Ok(date) => match modified {
+            Some(modified) if modified.date_naive() == date => modified,
+            _ => date.and_time(NaiveTime::MIN).and_utc(),
+        },
+        Err(_) => modified.unwrap_or_else(Utc::now),
+    }
+}
+
 #[derive(Clone)]
 pub struct MemoryService {
     memory_dir: PathBuf,
     search_index: Arc<RwLock<MemoryIndex>>,
     search_cache: SearchCache,
     inverted_index: InvertedIndex,
+    /// Conversations the user started in incognito mode. Nothing from them is
+    /// stored or read back for the rest of the app session.
+    incognito_conversations: Arc<RwLock<HashSet<String>>>,
+    /// Most documents kept in the search index; the oldest unpinned ones
+    /// beyond it are dropped from the index but stay on disk.
+    index_limit: Option<usize>,
 }
 
 impl MemoryService {
     pub fn new(memory_dir: PathBuf) -> AppResult<Self> {
+        Self::new_with_index_limit(memory_dir, None)
+    }
+
+    pub fn new_with_index_limit(
+        memory_dir: PathBuf,
+        index_limit: Option<usize>,
+    ) -> AppResult<Self> {
         // Ensure memory directory exists
         if !memory_dir.exists() {
             fs::create_dir_all(&memory_dir).map_err(|e| {
@@ -245,6 +355,8 @@ impl MemoryService {
             search_index: Arc::new(RwLock::new(MemoryIndex::new())),
             search_cache: SearchCache::new(),
             inverted_index: InvertedIndex::new(),
+            incognito_conversations: Arc::new(RwLock::new(HashSet::new())),
+            index_limit,
         };
 
         // Load existing memory documents into index
@@ -253,7 +365,23 @@ impl MemoryService {
         Ok(service)
     }
 
+    /// Keep `conversation_id` out of memory for the rest of the session
+    pub fn mark_incognito(&self, conversation_id: &str) {
+        if let Ok(mut conversations) = self.incognito_conversations.write() {
+            conversations.insert(conversation_id.to_string());
+        }
+    }
+
+    pub fn is_incognito(&self, conversation_id: &str) -> bool {
+        self.incognito_conversations
+            .read()
+            .map(|conversations| conversations.contains(conversation_id))
+            .unwrap_or(false)
+    }
+
     /// Store a conversation as a memory document
+    ///
+    /// Fails for incognito conversations.
     pub async fn store_conversation(
         &self,
         conversation_id: &str,
@@ -261,6 +389,10 @@ impl MemoryService {
         ai_response: &str,
         topics: Vec<String>,
     ) -> AppResult<String> {
+        if self.is_incognito(conversation_id) {
+            return Err(AppError::validation("无痕会话不会保存到记忆中"));
+        }
+
         let now = Utc::now();
         let doc_id = Uuid::new_v4().to_string();
 
@@ -282,6 +414,9 @@ impl MemoryService {
             summary,
             relevance_score: 1.0, // Initial score, will be updated based on usage
             conversation_id: conversation_id.to_string(),
+            created_at: Some(now),
+            pinned: false,
+            ignored: false,
         };
 
         // Create document content
@@ -311,6 +446,7 @@ impl MemoryService {
         {
             let mut index = self.search_index.write().unwrap();
             index.add_document(document.clone());
+            self.enforce_index_limit(&mut index);
         }
 
         // Add to inverted index for fast search
@@ -404,6 +540,10 @@ impl MemoryService {
                 }
             }
 
+            if document.metadata.ignored {
+                continue;
+            }
+
             if let Some(ref topics) = search_query.topics {
                 if !document.metadata.topics.iter().any(|t| topics.contains(t)) {
                     continue;
@@ -638,18 +778,19 @@ impl MemoryService {
         documents: &[MemoryDocument],
         conversations: &[ConversationSummary],
     ) -> u32 {
-        let mut total_chars = 0;
+        let tokenizer = TokenizerProfile::default();
+        let mut total_tokens = 0;
 
         for doc in documents {
-            total_chars += doc.content.len();
+            total_tokens += tokenizer.estimate(&doc.content);
         }
 
         for conv in conversations {
-            total_chars += conv.user_message.len() + conv.ai_response.len();
+            total_tokens +=
+                tokenizer.estimate(&conv.user_message) + tokenizer.estimate(&conv.ai_response);
         }
 
-        // Rough estimate: 1 token ≈ 4 characters
-        (total_chars / 4) as u32
+        total_tokens as u32
     }
 
     /// Determine context sufficiency level
@@ -696,167 +837,249 @@ impl MemoryService {
         Ok(recent_docs)
     }
 
-    /// Export memory archive with options
-    pub async fn export_memory_archive(&self, options: &MemoryExportOptions) -> AppResult<()> {
-        // Clone documents to avoid holding lock across await
-        let documents_to_export: Vec<MemoryDocument> = {
-            let index = self.search_index.read().unwrap();
+    pub fn memory_dir(&self) -> &Path {
+        &self.memory_dir
+    }
 
-            // Filter documents by date range if specified
-            let docs: Vec<MemoryDocument> = if let Some((start, end)) = options.date_range {
-                index
-                    .documents
-                    .values()
-                    .filter(|doc| doc.created_at >= start && doc.created_at <= end)
-                    .cloned()
-                    .collect()
-            } else {
-                index.documents.values().cloned().collect()
-            };
+    /// User-owned files in the memory directory: every document, archived
+    /// ones included, plus the facts file. The index snapshot is left out.
+    pub fn data_files(&self) -> AppResult<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        self.scan_directory(&self.memory_dir, &mut paths)?;
+        let facts = self.memory_dir.join(FACTS_FILE);
+        if facts.is_file() {
+            paths.push(facts);
+        }
+        paths.sort();
+        Ok(paths)
+    }
 
-            docs
-        }; // Lock is released here
+    /// Deletes the whole memory directory and starts over with an empty
+    /// index. Returns how many user files were removed.
+    pub fn erase_all(&self) -> AppResult<usize> {
+        let removed = self.data_files()?.len();
+        if self.memory_dir.exists() {
+            fs::remove_dir_all(&self.memory_dir)?;
+        }
+        fs::create_dir_all(&self.memory_dir)?;
+        self.rebuild_index()?;
+        info!("Erased {} memory files", removed);
+        Ok(removed)
+    }
 
-        // Create output directory
-        fs::create_dir_all(&options.output_path)?;
+    /// Export memory archive with options. Documents are streamed one at a
+    /// time and every file is written atomically; the written files are
+    /// returned with their checksums.
+    pub async fn export_memory_archive(
+        &self,
+        options: &MemoryExportOptions,
+    ) -> AppResult<Vec<ExportedFile>> {
+        self.export_memory_archive_with_progress(options, &ProgressReporter::silent())
+            .await
+    }
 
-        match options.format {
-            MemoryExportFormat::Archive => {
-                self.export_as_archive(
-                    &documents_to_export,
-                    &options.output_path,
-                    options.include_metadata,
-                )
-                .await?;
-            }
-            MemoryExportFormat::Json => {
-                self.export_as_json(&documents_to_export, &options.output_path)
-                    .await?;
+    /// Like [`Self::export_memory_archive`], reporting progress per document
+    /// and stopping between documents once `progress` is cancelled.
+    pub async fn export_memory_archive_with_progress(
+        &self,
+        options: &MemoryExportOptions,
+        progress: &ProgressReporter,
+    ) -> AppResult<Vec<ExportedFile>> {
+        progress.report("collecting", 0)?;
+        let entries = self.export_entries(options.date_range);
+
+        let exported = match options.bundle {
+            None => {
+                let mut writer = BundleWriter::directory(&options.output_path)?;
+                self.write_export(&mut writer, &entries, options, progress)?
+                    .into_iter()
+                    .map(|(relative, checksum)| ExportedFile {
+                        path: options.output_path.join(relative),
+                        checksum,
+                    })
+                    .collect()
             }
-            MemoryExportFormat::Markdown => {
-                self.export_as_markdown(&documents_to_export, &options.output_path)
-                    .await?;
+            Some(bundle) => {
+                if let Some(parent) = options.output_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                let mut staging = options.output_path.clone().into_os_string();
+                staging.push(".entry");
+                let checksum = write_atomic_with(&options.output_path, |file| {
+                    let mut writer = match bundle {
+                        MemoryExportBundle::Zip => BundleWriter::zip(file),
+                        MemoryExportBundle::TarGz => BundleWriter::tar_gz(file, staging.into()),
+                    };
+                    self.write_export(&mut writer, &entries, options, progress)?;
+                    writer
+                        .finish()?
+                        .ok_or_else(|| AppError::other("压缩文件未生成"))
+                })?;
+                vec![ExportedFile {
+                    path: options.output_path.clone(),
+                    checksum,
+                }]
             }
-        }
+        };
 
         info!(
             "Exported {} memory documents to {:?}",
-            documents_to_export.len(),
+            entries.len(),
             options.output_path
         );
-        Ok(())
+        Ok(exported)
     }
 
-    /// Export as file archive (preserving directory structure)
-    async fn export_as_archive(
+    /// Documents to export, oldest first. Only ids and paths are collected;
+    /// the documents themselves are cloned one at a time while writing.
+    fn export_entries(
         &self,
-        documents: &[MemoryDocument],
-        output_path: &Path,
-        include_metadata: bool,
-    ) -> AppResult<()> {
-        // Copy all memory documents preserving structure
-        for document in documents {
-            let relative_path = document
-                .file_path
-                .strip_prefix(&self.memory_dir)
-                .map_err(|_| AppError::Other("Invalid file path".to_string()))?;
-
-            let dest_path = output_path.join(relative_path);
-
-            if let Some(parent) = dest_path.parent() {
-                fs::create_dir_all(parent)?;
-            }
-
-            fs::copy(&document.file_path, &dest_path)?;
-        }
-
-        if include_metadata {
-            // Create metadata index
-            let metadata_path = output_path.join("metadata.json");
-            let metadata: Vec<&MemoryMetadata> =
-                documents.iter().map(|doc| &doc.metadata).collect();
-            let metadata_json = serde_json::to_string_pretty(&metadata)?;
-            fs::write(metadata_path, metadata_json)?;
-
-            // Create export info
-            let export_info = ExportInfo {
-                export_date: Utc::now(),
-                total_documents: documents.len(),
-                date_range: documents.iter().map(|doc| doc.created_at).fold(
-                    (None, None),
-                    |acc, date| {
-                        let min = acc.0.map_or(Some(date), |min| Some(min.min(date)));
-                        let max = acc.1.map_or(Some(date), |max| Some(max.max(date)));
-                        (min, max)
-                    },
-                ),
-            };
-
-            let info_path = output_path.join("export_info.json");
-            let info_json = serde_json::to_string_pretty(&export_info)?;
-            fs::write(info_path, info_json)?;
-        }
+        date_range: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    ) -> Vec<ExportEntry> {
+        let index = self.search_index.read().unwrap();
+        let mut entries: Vec<ExportEntry> = index
+            .documents
+            .values()
+            .filter(|doc| {
+                date_range
+                    .is_none_or(|(start, end)| doc.created_at >= start && doc.created_at <= end)
+            })
+            .map(|doc| ExportEntry {
+                id: doc.id.clone(),
+                file_path: doc.file_path.clone(),
+                created_at: doc.created_at,
+            })
+            .collect();
+        entries.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        entries
+    }
 
-        Ok(())
+    /// Documents removed since the entries were collected are skipped.
+    fn export_document(&self, id: &str) -> Option<MemoryDocument> {
+        self.search_index.read().unwrap().documents.get(id).cloned()
     }
 
-    /// Export as single JSON file
-    async fn export_as_json(
+    /// Writes `entries` in the requested format and returns each written
+    /// entry's relative path and checksum.
+    fn write_export(
         &self,
-        documents: &[MemoryDocument],
-        output_path: &Path,
-    ) -> AppResult<()> {
-        let export_data = JsonExport {
-            export_date: Utc::now(),
-            documents: documents.to_vec(),
-        };
-
-        let json_path = output_path.join("memory_export.json");
-        let json_data = serde_json::to_string_pretty(&export_data)?;
-        fs::write(json_path, json_data)?;
+        writer: &mut BundleWriter,
+        entries: &[ExportEntry],
+        options: &MemoryExportOptions,
+        progress: &ProgressReporter,
+    ) -> AppResult<Vec<(PathBuf, String)>> {
+        let mut written = Vec::new();
+        let mut tracker = ExportProgress::new(progress, entries.len());
 
-        Ok(())
-    }
+        match options.format {
+            MemoryExportFormat::Archive => {
+                // Copy all memory documents preserving structure
+                for entry in entries {
+                    tracker.advance()?;
+                    let relative = entry
+                        .file_path
+                        .strip_prefix(&self.memory_dir)
+                        .map_err(|_| AppError::Other("Invalid file path".to_string()))?;
+                    let relative = sanitize_relative_path(relative);
+                    let checksum = writer.add_file(&relative, &entry.file_path)?;
+                    written.push((relative, checksum));
+                }
 
-    /// Export as single Markdown file
-    async fn export_as_markdown(
-        &self,
-        documents: &[MemoryDocument],
-        output_path: &Path,
-    ) -> AppResult<()> {
-        let mut markdown_content = String::new();
-        markdown_content.push_str("# Memory Export\n\n");
-        markdown_content.push_str(&format!(
-            "Export Date: {}\n",
-            Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
-        ));
-        markdown_content.push_str(&format!("Total Documents: {}\n\n", documents.len()));
-
-        // Sort documents by date
-        let mut sorted_docs = documents.to_vec();
-        sorted_docs.sort_by(|a, b| a.created_at.cmp(&b.created_at));
-
-        for document in sorted_docs {
-            markdown_content.push_str(&format!(
-                "## {} ({})\n\n",
-                document.metadata.summary, document.metadata.date
-            ));
-            markdown_content.push_str(&format!(
-                "**Topics:** {}\n",
-                document.metadata.topics.join(", ")
-            ));
-            markdown_content.push_str(&format!(
-                "**Conversation ID:** {}\n\n",
-                document.metadata.conversation_id
-            ));
-            markdown_content.push_str(&document.content);
-            markdown_content.push_str("\n\n---\n\n");
+                if options.include_metadata {
+                    let relative = PathBuf::from("metadata.json");
+                    let checksum = writer.add_entry(&relative, |out| {
+                        out.write_all(b"[")?;
+                        let documents = entries.iter().filter_map(|e| self.export_document(&e.id));
+                        for (position, document) in documents.enumerate() {
+                            if position > 0 {
+                                out.write_all(b",")?;
+                            }
+                            serde_json::to_writer_pretty(&mut *out, &document.metadata)?;
+                        }
+                        out.write_all(b"]")?;
+                        Ok(())
+                    })?;
+                    written.push((relative, checksum));
+
+                    let export_info = ExportInfo {
+                        export_date: Utc::now(),
+                        total_documents: entries.len(),
+                        date_range: (
+                            entries.first().map(|entry| entry.created_at),
+                            entries.last().map(|entry| entry.created_at),
+                        ),
+                    };
+                    let relative = PathBuf::from("export_info.json");
+                    let checksum = writer.add_entry(&relative, |out| {
+                        serde_json::to_writer_pretty(out, &export_info)?;
+                        Ok(())
+                    })?;
+                    written.push((relative, checksum));
+                }
+            }
+            MemoryExportFormat::Json => {
+                // Same shape as `JsonExport`, written document by document
+                let relative = PathBuf::from("memory_export.json");
+                let checksum = writer.add_entry(&relative, |out| {
+                    write!(
+                        out,
+                        "{{\"export_date\":{},\"documents\":[",
+                        serde_json::to_string(&Utc::now())?
+                    )?;
+                    let mut first = true;
+                    for entry in entries {
+                        tracker.advance()?;
+                        let Some(document) = self.export_document(&entry.id) else {
+                            continue;
+                        };
+                        if !first {
+                            out.write_all(b",")?;
+                        }
+                        first = false;
+                        serde_json::to_writer_pretty(&mut *out, &document)?;
+                    }
+                    out.write_all(b"]}")?;
+                    Ok(())
+                })?;
+                written.push((relative, checksum));
+            }
+            MemoryExportFormat::Markdown => {
+                let relative = PathBuf::from("memory_export.md");
+                let checksum = writer.add_entry(&relative, |out| {
+                    writeln!(out, "# Memory Export\n")?;
+                    writeln!(
+                        out,
+                        "Export Date: {}",
+                        Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
+                    )?;
+                    writeln!(out, "Total Documents: {}\n", entries.len())?;
+
+                    for entry in entries {
+                        tracker.advance()?;
+                        let Some(document) = self.export_document(&entry.id) else {
+                            continue;
+                        };
+                        writeln!(
+                            out,
+                            "## {} ({})\n",
+                            document.metadata.summary, document.metadata.date
+                        )?;
+                        writeln!(out, "**Topics:** {}", document.metadata.topics.join(", "))?;
+                        writeln!(
+                            out,
+                            "**Conversation ID:** {}\n",
+                            document.metadata.conversation_id
+                        )?;
+                        out.write_all(document.content.as_bytes())?;
+                        out.write_all(b"\n\n---\n\n")?;
+                    }
+                    Ok(())
+                })?;
+                written.push((relative, checksum));
+            }
         }
 
-        let markdown_path = output_path.join("memory_export.md");
-        fs::write(markdown_path, markdown_content)?;
-
-        Ok(())
+        Ok(written)
     }
 
     /// Archive old memories (move to archive directory)
@@ -873,7 +1096,7 @@ impl MemoryService {
         let docs_to_archive: Vec<MemoryDocument> = index
             .documents
             .values()
-            .filter(|doc| doc.created_at < cutoff_date)
+            .filter(|doc| doc.created_at < cutoff_date && !doc.metadata.pinned)
             .cloned()
             .collect();
 
@@ -1038,7 +1261,7 @@ impl MemoryService {
         let docs_to_remove: Vec<String> = index
             .documents
             .values()
-            .filter(|doc| doc.created_at < cutoff_date)
+            .filter(|doc| doc.created_at < cutoff_date && !doc.metadata.pinned)
             .map(|doc| doc.id.clone())
             .collect();
 
@@ -1067,7 +1290,9 @@ impl MemoryService {
         Ok(removed_count)
     }
 
-    /// Rebuild the search index from existing files
+    /// Rebuild the search index from existing files. Files whose size and
+    /// modification time match the on-disk snapshot are taken from it; only
+    /// new or changed files are read and parsed again.
     pub fn rebuild_index(&self) -> AppResult<()> {
         let mut index = self.search_index.write().unwrap();
         *index = MemoryIndex::new();
@@ -1076,17 +1301,106 @@ impl MemoryService {
         self.inverted_index.clear();
         self.search_cache.clear();
 
-        self.scan_directory(&self.memory_dir, &mut index)?;
+        let mut snapshot = self.load_index_snapshot();
+        let migrated = snapshot.version != INDEX_SNAPSHOT_VERSION && !snapshot.files.is_empty();
+        let mut paths = Vec::new();
+        self.scan_directory(&self.memory_dir, &mut paths)?;
+
+        let mut files = HashMap::with_capacity(paths.len());
+        let mut stale_ids = Vec::new();
+        let mut fresh_ids = Vec::new();
+        for path in paths {
+            let Ok(metadata) = fs::metadata(&path) else {
+                continue;
+            };
+            let key = self.snapshot_key(&path);
+
+            match snapshot.files.remove(&key) {
+                Some(mut entry) if entry.is_fresh(&metadata) => {
+                    entry.document.file_path = path;
+                    files.insert(key, entry);
+                }
+                previous => {
+                    if let Some(previous) = previous {
+                        stale_ids.push(previous.document.id);
+                    }
+                    if let Ok(document) = self.load_document_from_file(&path) {
+                        fresh_ids.push(document.id.clone());
+                        files.insert(
+                            key,
+                            SnapshotEntry {
+                                modified: metadata.modified().ok(),
+                                len: metadata.len(),
+                                document,
+                            },
+                        );
+                    }
+                }
+            }
+        }
+        // Whatever is left in the snapshot was deleted or moved away
+        stale_ids.extend(snapshot.files.into_values().map(|entry| entry.document.id));
+        let changed = migrated || !stale_ids.is_empty() || !fresh_ids.is_empty();
+
+        *self.inverted_index.word_to_docs.write().unwrap() = snapshot.word_to_docs;
+        for doc_id in &stale_ids {
+            self.inverted_index.remove_document(doc_id);
+        }
+        for entry in files.values() {
+            index.add_document(entry.document.clone());
+        }
+        for doc_id in &fresh_ids {
+            if let Some(document) = index.documents.get(doc_id) {
+                self.inverted_index
+                    .add_document(&document.id, &document.content);
+            }
+        }
+
+        if changed {
+            self.save_index_snapshot(files);
+        }
+        self.enforce_index_limit(&mut index);
 
         info!(
-            "Rebuilt memory index with {} documents",
-            index.documents.len()
+            "Rebuilt memory index with {} documents ({} re-read)",
+            index.documents.len(),
+            fresh_ids.len()
         );
         Ok(())
     }
 
-    /// Recursively scan directory for memory documents
-    fn scan_directory(&self, dir: &Path, index: &mut MemoryIndex) -> AppResult<()> {
+    /// Drops the oldest unpinned documents from the index until it fits
+    /// `index_limit`.
+    fn enforce_index_limit(&self, index: &mut MemoryIndex) {
+        let Some(limit) = self.index_limit else {
+            return;
+        };
+        let excess = index.documents.len().saturating_sub(limit);
+        if excess == 0 {
+            return;
+        }
+
+        let mut candidates = index
+            .documents
+            .values()
+            .filter(|document| !document.metadata.pinned)
+            .map(|document| (document.created_at, document.id.clone()))
+            .collect::<Vec<_>>();
+        candidates.sort();
+        for (_, doc_id) in candidates.into_iter().take(excess) {
+            index.remove_document(&doc_id);
+            self.inverted_index.remove_document(&doc_id);
+        }
+        self.search_cache.clear();
+        debug!(
+            limit,
+            dropped = excess,
+            "Trimmed memory index to its size limit"
+        );
+    }
+
+    /// Recursively collect memory document paths
+    fn scan_directory(&self, dir: &Path, paths: &mut Vec<PathBuf>) -> AppResult<()> {
         if !dir.exists() {
             return Ok(());
         }
@@ -1096,24 +1410,77 @@ impl MemoryService {
             let path = entry.path();
 
             if path.is_dir() {
-                self.scan_directory(&path, index)?;
+                self.scan_directory(&path, paths)?;
             } else if path.extension().and_then(|s| s.to_str()) == Some("md") {
-                if let Ok(document) = self.load_document_from_file(&path) {
-                    // Add to inverted index for fast search
-                    self.inverted_index
-                        .add_document(&document.id, &document.content);
-                    index.add_document(document);
-                }
+                paths.push(path);
             }
         }
 
         Ok(())
     }
 
+    fn snapshot_key(&self, path: &Path) -> String {
+        path.strip_prefix(&self.memory_dir)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// A missing, unreadable or outdated snapshot is treated as empty, which
+    /// makes the next rebuild read every file.
+    fn load_index_snapshot(&self) -> IndexSnapshot {
+        let path = self.memory_dir.join(INDEX_SNAPSHOT_FILE);
+        let Ok(raw) = fs::read(&path) else {
+            return IndexSnapshot::default();
+        };
+
+        match serde_json::from_slice::<IndexSnapshot>(&raw) {
+            Ok(snapshot) if snapshot.version == INDEX_SNAPSHOT_VERSION => snapshot,
+            Ok(mut snapshot) if snapshot.version == 1 => {
+                for entry in snapshot.files.values_mut() {
+                    entry.document.created_at =
+                        document_created_at(&entry.document.metadata, entry.modified);
+                }
+                snapshot
+            }
+            Ok(_) => {
+                debug!("Ignoring memory index snapshot from an older version");
+                IndexSnapshot::default()
+            }
+            Err(e) => {
+                warn!("Failed to parse memory index snapshot: {}", e);
+                IndexSnapshot::default()
+            }
+        }
+    }
+
+    /// Writes the snapshot next to the documents. Failures only cost a full
+    /// re-read on the next start, so they are logged rather than returned.
+    fn save_index_snapshot(&self, files: HashMap<String, SnapshotEntry>) {
+        let snapshot = IndexSnapshot {
+            version: INDEX_SNAPSHOT_VERSION,
+            files,
+            word_to_docs: self.inverted_index.word_to_docs.read().unwrap().clone(),
+        };
+        let path = self.memory_dir.join(INDEX_SNAPSHOT_FILE);
+        let temp_path = path.with_extension("json.tmp");
+
+        let result = serde_json::to_vec(&snapshot)
+            .map_err(AppError::from)
+            .and_then(|raw| fs::write(&temp_path, raw).map_err(AppError::from))
+            .and_then(|_| fs::rename(&temp_path, &path).map_err(AppError::from));
+        if let Err(e) = result {
+            warn!("Failed to save memory index snapshot: {}", e);
+        }
+    }
+
     /// Load a memory document from a file
     fn load_document_from_file(&self, file_path: &Path) -> AppResult<MemoryDocument> {
         let content = fs::read_to_string(file_path)?;
         let (metadata, body) = self.parse_document_content(&content)?;
+        let modified = fs::metadata(file_path)
+            .and_then(|meta| meta.modified())
+            .ok();
 
         let doc_id = file_path
             .file_stem()
@@ -1124,16 +1491,16 @@ impl MemoryService {
         Ok(MemoryDocument {
             id: doc_id,
             file_path: file_path.to_path_buf(),
+            created_at: document_created_at(&metadata, modified),
             metadata,
             content: body,
-            created_at: Utc::now(), // Will be overridden by metadata date if available
         })
     }
 
     /// Parse document content to extract metadata and body
     fn parse_document_content(&self, content: &str) -> AppResult<(MemoryMetadata, String)> {
         // Look for YAML frontmatter
-        let frontmatter_regex = Regex::new(r"^---\n(.*?)\n---\n(.*)$").unwrap();
+        let frontmatter_regex = Regex::new(r"(?s)^---\n(.*?)\n---\n(.*)$").unwrap();
 
         if let Some(captures) = frontmatter_regex.captures(content) {
             let yaml_content = captures.get(1).unwrap().as_str();
@@ -1281,6 +1648,23 @@ impl MemoryService {
         query: &str,
         limit: usize,
         context_limit_tokens: Option<usize>,
+    ) -> AppResult<MemoryContext> {
+        self.semantic_search_with_tokenizer(
+            query,
+            limit,
+            context_limit_tokens,
+            &TokenizerProfile::default(),
+        )
+        .await
+    }
+
+    /// Same as `semantic_search`, counting tokens with the given model tokenizer.
+    pub async fn semantic_search_with_tokenizer(
+        &self,
+        query: &str,
+        limit: usize,
+        context_limit_tokens: Option<usize>,
+        tokenizer: &TokenizerProfile,
     ) -> AppResult<MemoryContext> {
         let search_query = MemorySearchQuery {
             query: query.to_string(),
@@ -1294,20 +1678,24 @@ impl MemoryService {
 
         // Apply token limit if specified
         if let Some(token_limit) = context_limit_tokens {
-            self.limit_context_by_tokens(&mut context, token_limit);
+            self.limit_context_by_tokens(&mut context, token_limit, tokenizer);
         }
 
         Ok(context)
     }
 
     /// Limit memory context by token count with safe string handling
-    fn limit_context_by_tokens(&self, context: &mut MemoryContext, token_limit: usize) {
+    fn limit_context_by_tokens(
+        &self,
+        context: &mut MemoryContext,
+        token_limit: usize,
+        tokenizer: &TokenizerProfile,
+    ) {
         let mut total_tokens = 0;
         let mut limited_docs = Vec::new();
 
         for doc in &context.relevant_documents {
-            // Rough token estimation: 1 token ≈ 4 characters
-            let doc_tokens = doc.content.len() / 4;
+            let doc_tokens = tokenizer.estimate(&doc.content);
 
             if total_tokens + doc_tokens <= token_limit {
                 total_tokens += doc_tokens;
@@ -1315,22 +1703,19 @@ impl MemoryService {
             } else {
                 // If adding this document would exceed the limit, try to add a truncated version
                 let remaining_tokens = token_limit - total_tokens;
-                if remaining_tokens > 0 {
-                    let remaining_chars = remaining_tokens * 4;
-                    if remaining_chars > 10 {
-                        // Only add if meaningful amount of content
-                        let mut truncated_doc = doc.clone();
-                        truncated_doc.content =
-                            self.safe_truncate_content(&doc.content, remaining_chars);
-                        limited_docs.push(truncated_doc);
-                    }
+                let truncated = tokenizer.truncate(&doc.content, remaining_tokens);
+                if truncated.chars().count() > 10 {
+                    // Only add if meaningful amount of content
+                    let mut truncated_doc = doc.clone();
+                    truncated_doc.content = truncated.to_string();
+                    limited_docs.push(truncated_doc);
                 }
                 break;
             }
         }
 
+        context.total_context_length = limited_docs.iter().map(|doc| doc.content.len()).sum();
         context.relevant_documents = limited_docs;
-        context.total_context_length = total_tokens * 4; // Convert back to characters
     }
 
     /// Safely truncate content at character boundary
@@ -1358,6 +1743,10 @@ impl MemoryService {
         &self,
         conversation_id: &str,
     ) -> AppResult<Vec<MemoryDocument>> {
+        if self.is_incognito(conversation_id) {
+            return Ok(Vec::new());
+        }
+
         let index = self.search_index.read().unwrap();
         let documents: Vec<MemoryDocument> = index
             .documents
@@ -1421,9 +1810,24 @@ impl MemoryService {
         &self,
         query: &str,
         max_context_tokens: usize,
+    ) -> AppResult<String> {
+        self.get_conversation_context_with_tokenizer(
+            query,
+            max_context_tokens,
+            &TokenizerProfile::default(),
+        )
+        .await
+    }
+
+    /// Memory context for a specific model, budgeted with its tokenizer.
+    pub async fn get_conversation_context_with_tokenizer(
+        &self,
+        query: &str,
+        max_context_tokens: usize,
+        tokenizer: &TokenizerProfile,
     ) -> AppResult<String> {
         let context = self
-            .semantic_search(query, 5, Some(max_context_tokens))
+            .semantic_search_with_tokenizer(query, 5, Some(max_context_tokens), tokenizer)
             .await?;
 
         if context.relevant_documents.is_empty() {
@@ -1522,6 +1926,215 @@ impl MemoryService {
         index.topic_index.keys().cloned().collect()
     }
 
+    /// The user's facts, empty until they have saved some.
+    pub fn get_facts(&self) -> AppResult<MemoryFacts> {
+        let path = self.memory_dir.join(FACTS_FILE);
+        if !path.exists() {
+            return Ok(MemoryFacts::default());
+        }
+        let raw = fs::read(&path)?;
+        Ok(serde_json::from_slice(&raw)?)
+    }
+
+    /// Replaces the user's facts. Blank and repeated facts are dropped.
+    pub fn set_facts(&self, facts: Vec<MemoryFact>) -> AppResult<MemoryFacts> {
+        let mut cleaned: Vec<MemoryFact> = Vec::with_capacity(facts.len());
+        for fact in facts {
+            let text = fact.text.trim();
+            if text.is_empty() {
+                continue;
+            }
+            if text.chars().count() > MAX_FACT_CHARS {
+                return Err(AppError::validation(format!(
+                    "单条事实不能超过 {} 个字符",
+                    MAX_FACT_CHARS
+                )));
+            }
+            if cleaned
+                .iter()
+                .any(|existing| existing.kind == fact.kind && existing.text == text)
+            {
+                continue;
+            }
+            cleaned.push(MemoryFact {
+                kind: fact.kind,
+                text: text.to_string(),
+            });
+        }
+        if cleaned.len() > MAX_FACTS {
+            return Err(AppError::validation(format!(
+                "最多只能保存 {} 条事实",
+                MAX_FACTS
+            )));
+        }
+
+        let facts = MemoryFacts {
+            facts: cleaned,
+            updated_at: Some(Utc::now()),
+        };
+        let path = self.memory_dir.join(FACTS_FILE);
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, serde_json::to_vec_pretty(&facts)?)?;
+        fs::rename(&temp_path, &path)?;
+
+        info!("Saved {} memory facts", facts.facts.len());
+        Ok(facts)
+    }
+
+    /// Topics with the number of documents carrying each, most used first.
+    pub fn list_topics(&self) -> Vec<MemoryTopicCount> {
+        let index = self.search_index.read().unwrap();
+        let mut topics: Vec<MemoryTopicCount> = index
+            .topic_index
+            .iter()
+            .map(|(topic, doc_ids)| MemoryTopicCount {
+                topic: topic.clone(),
+                document_count: doc_ids.len(),
+            })
+            .collect();
+        topics.sort_by(|a, b| {
+            b.document_count
+                .cmp(&a.document_count)
+                .then_with(|| a.topic.cmp(&b.topic))
+        });
+        topics
+    }
+
+    /// Renames a topic in every document. Renaming onto a topic that already
+    /// exists merges the two.
+    pub async fn rename_topic(&self, from: &str, to: &str) -> AppResult<usize> {
+        self.merge_topics(&[from.to_string()], to).await
+    }
+
+    /// Replaces each of `sources` with `target` in every document, returning
+    /// how many documents were rewritten.
+    pub async fn merge_topics(&self, sources: &[String], target: &str) -> AppResult<usize> {
+        let target = target.trim();
+        if target.is_empty() {
+            return Err(AppError::validation("目标主题不能为空"));
+        }
+        let sources: HashSet<&str> = sources
+            .iter()
+            .map(|topic| topic.trim())
+            .filter(|topic| !topic.is_empty() && *topic != target)
+            .collect();
+        if sources.is_empty() {
+            return Err(AppError::validation("至少需要一个与目标不同的来源主题"));
+        }
+
+        let updated = self.rewrite_topics(&sources, |topics| {
+            let mut merged: Vec<String> = Vec::with_capacity(topics.len());
+            for topic in topics.drain(..) {
+                let topic = if sources.contains(topic.as_str()) {
+                    target.to_string()
+                } else {
+                    topic
+                };
+                if !merged.contains(&topic) {
+                    merged.push(topic);
+                }
+            }
+            *topics = merged;
+        })?;
+
+        info!(
+            "Merged {:?} into topic {} across {} memory documents",
+            sources, target, updated
+        );
+        Ok(updated)
+    }
+
+    /// Removes a topic from every document that carries it.
+    pub async fn delete_topic(&self, topic: &str) -> AppResult<usize> {
+        let topic = topic.trim();
+        if topic.is_empty() {
+            return Err(AppError::validation("主题不能为空"));
+        }
+
+        let updated = self.rewrite_topics(&HashSet::from([topic]), |topics| {
+            topics.retain(|existing| existing != topic);
+        })?;
+
+        info!("Deleted topic {} from {} memory documents", topic, updated);
+        Ok(updated)
+    }
+
+    /// Pins or unpins a memory so archival and cleanup leave it alone.
+    pub async fn set_pinned(&self, doc_id: &str, pinned: bool) -> AppResult<MemoryDocument> {
+        self.rewrite_metadata(doc_id, |metadata| metadata.pinned = pinned)
+    }
+
+    /// Marks a memory as ignored so it is no longer offered as agent context.
+    pub async fn set_ignored(&self, doc_id: &str, ignored: bool) -> AppResult<MemoryDocument> {
+        self.rewrite_metadata(doc_id, |metadata| metadata.ignored = ignored)
+    }
+
+    fn rewrite_topics(
+        &self,
+        topics: &HashSet<&str>,
+        mut update: impl FnMut(&mut Vec<String>),
+    ) -> AppResult<usize> {
+        let doc_ids: HashSet<String> = {
+            let index = self.search_index.read().unwrap();
+            topics
+                .iter()
+                .filter_map(|topic| index.topic_index.get(*topic))
+                .flatten()
+                .cloned()
+                .collect()
+        };
+
+        for doc_id in &doc_ids {
+            self.rewrite_metadata(doc_id, |metadata| update(&mut metadata.topics))?;
+        }
+        Ok(doc_ids.len())
+    }
+
+    /// Rewrites a document's frontmatter in place, keeping its body, and
+    /// refreshes the indices.
+    fn rewrite_metadata(
+        &self,
+        doc_id: &str,
+        update: impl FnOnce(&mut MemoryMetadata),
+    ) -> AppResult<MemoryDocument> {
+        let document = {
+            let index = self.search_index.read().unwrap();
+            index.documents.get(doc_id).cloned()
+        }
+        .ok_or(AppError::NotFound)?;
+
+        let raw = fs::read_to_string(&document.file_path)?;
+        let (mut metadata, body) = self.parse_document_content(&raw)?;
+        update(&mut metadata);
+        let yaml_metadata = serde_yaml::to_string(&metadata)
+            .map_err(|e| AppError::Other(format!("Failed to serialize metadata: {}", e)))?;
+        let rewritten = format!("---\n{}---\n{}", yaml_metadata, body);
+        fs::write(&document.file_path, &rewritten)?;
+
+        // Documents stored in this session keep their frontmatter in `content`
+        let content = if document.content.starts_with("---\n") {
+            rewritten
+        } else {
+            body
+        };
+        let old_content = document.content.clone();
+        let updated = MemoryDocument {
+            metadata,
+            content,
+            ..document
+        };
+        {
+            let mut index = self.search_index.write().unwrap();
+            index.remove_document(doc_id);
+            index.add_document(updated.clone());
+        }
+        self.inverted_index
+            .update_document(doc_id, &updated.content, &old_content);
+        self.search_cache.clear();
+
+        Ok(updated)
+    }
+
     /// Get search performance metrics
     pub fn get_search_performance_metrics(&self) -> HashMap<String, usize> {
         let cache = self.search_cache.cache.read().unwrap();
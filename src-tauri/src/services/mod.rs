@@ -1,19 +1,47 @@
+pub mod action_claims;
 pub mod ai_agent_service;
 pub mod ai_cache;
+pub mod ai_response_schemas;
 pub mod ai_service;
 pub mod analytics_service;
 pub mod behavior_learning;
 pub mod cache_service;
+pub mod caldav_sync_service;
+pub mod capacity_wizard;
+pub mod clipboard_watcher;
 pub mod community_service;
+pub mod confidence_calibration;
+pub mod custom_tool_service;
+pub mod dashboard_metrics;
+pub mod dashboard_widgets;
+pub mod data_export_service;
+pub mod day_close_service;
+pub mod db_maintenance;
 pub mod dependency_service;
 pub mod feedback_service;
+pub mod focus_idle;
 pub mod goal_service;
+pub mod history_service;
+pub mod ics_service;
 pub mod instance_generator;
+pub mod job_queue;
+pub mod job_supervisor;
+pub mod later_service;
 pub mod memory_service;
+pub mod on_demand_jobs;
 pub mod planning_service;
+pub mod power_throttle;
+pub mod prep_notes;
+pub mod priority_matrix;
 pub mod productivity_score_service;
+pub mod progress;
+pub mod project_service;
+pub mod prompt_template_service;
 pub mod prompt_templates;
+pub mod query_console;
 pub mod recurring_task_service;
+pub mod reminder_service;
+pub mod retention_service;
 // pub mod recommendation_orchestrator; // Removed - recommendation feature deleted
 pub mod rrule_parser;
 pub mod schedule_optimizer;
@@ -21,9 +49,17 @@ pub mod schedule_service;
 pub mod schedule_utils;
 pub mod session_metrics;
 pub mod settings_service;
+pub mod similar_tasks;
+pub mod slash_commands;
+pub mod stale_tasks;
 pub mod streaming;
+pub mod suggestion_service;
+pub mod sync_service;
 pub mod task_instance_service;
 pub mod task_service;
+pub mod timesheet_service;
+pub mod today_snapshot;
 pub mod tool_registry;
+pub mod upgrade_service;
 pub mod wellness_service;
 pub mod workload_forecast_service;
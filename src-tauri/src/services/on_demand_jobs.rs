@@ -0,0 +1,151 @@
+//! Scheduled work for profiles without background threads. The frontend
+//! calls `jobs_run_scheduled` when the app comes to the foreground and every
+//! job whose interval has passed runs once, on the calling thread.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use tracing::{info, warn};
+
+use crate::error::AppResult;
+use crate::models::job::OnDemandJobRun;
+
+type JobBody = Box<dyn Fn(DateTime<Utc>) -> AppResult<Option<JsonValue>> + Send + Sync>;
+
+struct OnDemandJob {
+    name: &'static str,
+    interval: Duration,
+    run: JobBody,
+}
+
+#[derive(Default)]
+pub struct OnDemandJobs {
+    jobs: RwLock<Vec<OnDemandJob>>,
+    last_runs: Mutex<HashMap<&'static str, DateTime<Utc>>>,
+}
+
+impl OnDemandJobs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `run` to execute at most once per `interval`. It runs on
+    /// the first call after registration.
+    pub fn register(
+        &self,
+        name: &'static str,
+        interval: Duration,
+        run: impl Fn(DateTime<Utc>) -> AppResult<()> + Send + Sync + 'static,
+    ) {
+        self.push(name, interval, Box::new(move |now| run(now).map(|()| None)));
+    }
+
+    /// Like [`register`](Self::register), for jobs whose result the user has
+    /// to see, such as fired reminders. Without background threads nothing
+    /// emits an event for them, so the result is returned in the job's run.
+    pub fn register_with_output<T: Serialize>(
+        &self,
+        name: &'static str,
+        interval: Duration,
+        run: impl Fn(DateTime<Utc>) -> AppResult<T> + Send + Sync + 'static,
+    ) {
+        self.push(
+            name,
+            interval,
+            Box::new(move |now| Ok(Some(serde_json::to_value(run(now)?)?))),
+        );
+    }
+
+    fn push(&self, name: &'static str, interval: Duration, run: JobBody) {
+        if let Ok(mut jobs) = self.jobs.write() {
+            jobs.push(OnDemandJob {
+                name,
+                interval,
+                run,
+            });
+        }
+    }
+
+    /// Runs every job that is due at `now`. A failing job is retried on the
+    /// next call instead of waiting out its interval.
+    pub fn run_due(&self, now: DateTime<Utc>) -> Vec<OnDemandJobRun> {
+        let Ok(jobs) = self.jobs.read() else {
+            return Vec::new();
+        };
+
+        let mut runs = Vec::new();
+        for job in jobs.iter() {
+            let last_run = self
+                .last_runs
+                .lock()
+                .ok()
+                .and_then(|last_runs| last_runs.get(job.name).copied());
+            if last_run.is_some_and(|last_run| now - last_run < job.interval) {
+                continue;
+            }
+
+            let outcome = (job.run)(now);
+            match &outcome {
+                Ok(_) => {
+                    info!(target: "app::jobs", job = job.name, "on-demand job ran");
+                    if let Ok(mut last_runs) = self.last_runs.lock() {
+                        last_runs.insert(job.name, now);
+                    }
+                }
+                Err(err) => {
+                    warn!(target: "app::jobs", job = job.name, error = %err, "on-demand job failed")
+                }
+            }
+            let (output, error) = match outcome {
+                Ok(output) => (output, None),
+                Err(err) => (None, Some(err.to_string())),
+            };
+            runs.push(OnDemandJobRun {
+                name: job.name.to_string(),
+                succeeded: error.is_none(),
+                error,
+                output,
+            });
+        }
+
+        runs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AppError;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn jobs_run_once_per_interval_and_failures_retry() {
+        let jobs = OnDemandJobs::new();
+        let snapshots = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&snapshots);
+        jobs.register("analytics-snapshot", Duration::hours(24), move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        });
+        jobs.register("broken", Duration::hours(24), |_| {
+            Err(AppError::other("不可用"))
+        });
+
+        let now = Utc::now();
+        let runs = jobs.run_due(now);
+        assert_eq!(runs.len(), 2);
+        assert!(runs[0].succeeded);
+        assert_eq!(runs[1].error.as_deref(), Some("不可用"));
+
+        let later = jobs.run_due(now + Duration::hours(1));
+        assert_eq!(later.len(), 1);
+        assert_eq!(later[0].name, "broken");
+
+        jobs.run_due(now + Duration::hours(25));
+        assert_eq!(snapshots.load(Ordering::SeqCst), 2);
+    }
+}
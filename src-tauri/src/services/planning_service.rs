@@ -1,34 +1,62 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::thread;
+use std::time::Duration as StdDuration;
 
-use chrono::{DateTime, FixedOffset, Utc};
+use chrono::{DateTime, Duration, FixedOffset, Utc};
 use rusqlite::Connection;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use tracing::{debug, info, warn};
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+use crate::db::repositories::later_repository::LaterRepository;
 use crate::db::repositories::planning_repository::{
     PlanningOptionRow, PlanningRepository, PlanningSessionRow, PlanningTimeBlockRow,
 };
+use crate::db::repositories::project_repository::ProjectRepository;
 use crate::db::repositories::task_repository::TaskRepository;
 use crate::db::DbPool;
 use crate::error::{AppError, AppResult};
+use crate::models::ai_types::{PlanCritiqueDto, SchedulePlanDto};
+use crate::models::later::LaterSlotSuggestion;
+use crate::models::memory::{MemoryFact, MemoryFactKind};
 use crate::models::planning::{
     PlanningOptionRecord, PlanningSessionRecord, PlanningTimeBlockRecord,
 };
+use crate::models::project::ProjectStatus;
+use crate::models::settings::SleepSchedule;
 use crate::models::task::TaskRecord;
 use crate::services::ai_service::AiService;
 use crate::services::behavior_learning::{BehaviorLearningService, PreferenceSnapshot};
+use crate::services::confidence_calibration::{calibrate_options, load_calibration};
+use crate::services::job_supervisor::spawn_supervised;
+use crate::services::later_service::{suggest_later_slots, LATER_STATUS_QUEUED};
+use crate::services::memory_service::MemoryService;
+use crate::services::prep_notes::{attach_prep_notes, load_predecessors};
+use crate::services::priority_matrix::{self, load_goal_linked_task_ids};
+use crate::services::progress::ProgressReporter;
+use crate::services::reminder_service::sync_block_reminders;
 use crate::services::schedule_optimizer::{
-    detect_conflicts, PlanOption, PlanRationaleStep, SchedulableTask, ScheduleConflict,
+    detect_conflicts, detect_sleep_conflicts, BreakBlock, ConflictKind, ConflictSeverity,
+    ExistingEvent, PlanOption, PlanRationaleStep, SchedulableTask, ScheduleConflict,
     ScheduleConstraints, ScheduleOptimizer, SchedulingPreferences, TimeBlockCandidate,
 };
 use crate::services::schedule_utils;
-use crate::services::task_service::TaskService;
+use crate::services::settings_service::{
+    load_ai_privacy_mode, load_default_preference_id, load_plan_critique_enabled,
+    load_planning_session_retention_days, load_sleep_schedule,
+};
+use crate::services::task_service::{TaskService, WAITING_STATUS};
+use crate::utils::redact::PlaceholderMap;
 
-const DEFAULT_PREFERENCE_ID: &str = "default";
+const DEFAULT_SESSION_PAGE_SIZE: usize = 20;
+const MAX_SESSION_PAGE_SIZE: usize = 100;
+const RETENTION_INTERVAL: StdDuration = StdDuration::from_secs(24 * 60 * 60);
+const CRITIQUE_NOTE_PREFIX: &str = "AI 审查：";
 
 #[derive(Clone)]
 pub struct PlanningService {
@@ -36,21 +64,40 @@ pub struct PlanningService {
     task_service: Arc<TaskService>,
     #[allow(dead_code)]
     ai_service: Arc<AiService>,
+    retention_job_started: Arc<AtomicBool>,
+    /// Source of the user's preference and constraint facts, if wired up.
+    memory_service: Option<Arc<MemoryService>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct GeneratePlanInput {
+    #[serde(default)]
     pub task_ids: Vec<String>,
+    /// Plans the project's unblocked tasks in addition to `task_ids`.
+    #[serde(default)]
+    pub project_id: Option<String>,
     #[serde(default)]
     pub constraints: Option<ScheduleConstraints>,
     #[serde(default)]
     pub preference_id: Option<String>,
     #[serde(default)]
     pub seed: Option<u64>,
+    /// Offer queued later-list items for low-energy and slack gaps.
+    #[serde(default)]
+    pub include_later_items: bool,
+    /// Overrides the `ai_privacy_mode` setting for this call.
+    #[serde(default)]
+    pub privacy_mode: Option<bool>,
+    /// Uses the built-in optimizer even when an AI provider is configured.
+    #[serde(default)]
+    pub optimizer_only: bool,
+    /// Never contacts the AI provider, not even for the plan critique.
+    #[serde(default)]
+    pub offline: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TimeBlockOverride {
     pub block_id: String,
@@ -62,7 +109,7 @@ pub struct TimeBlockOverride {
     pub flexibility: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ApplyPlanInput {
     pub session_id: String,
@@ -71,7 +118,7 @@ pub struct ApplyPlanInput {
     pub overrides: Vec<TimeBlockOverride>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ResolveConflictInput {
     pub session_id: String,
@@ -80,7 +127,7 @@ pub struct ResolveConflictInput {
     pub adjustments: Vec<TimeBlockOverride>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct PlanningSessionView {
     pub session: PlanningSessionRecord,
@@ -89,18 +136,76 @@ pub struct PlanningSessionView {
     pub conflicts: Vec<ScheduleConflict>,
     #[serde(default)]
     pub preference_snapshot: Option<PreferenceSnapshot>,
+    #[serde(default)]
+    pub later_suggestions: Vec<LaterSlotSuggestion>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct PlanningOptionView {
     pub option: PlanningOptionRecord,
     pub blocks: Vec<PlanningTimeBlockRecord>,
     #[serde(default)]
+    pub breaks: Vec<BreakBlock>,
+    #[serde(default)]
     pub conflicts: Vec<ScheduleConflict>,
+    #[serde(default)]
+    pub display_hints: PlanningDisplayHints,
+}
+
+/// Severity rollups so clients can color blocks and days without parsing
+/// `conflict_flags` themselves.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanningDisplayHints {
+    #[serde(default)]
+    pub blocks: Vec<BlockDisplayHint>,
+    #[serde(default)]
+    pub days: Vec<DayDisplayHint>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockDisplayHint {
+    pub block_id: String,
+    /// Highest severity among `kinds`; `None` for a conflict-free block.
+    #[serde(default)]
+    pub severity: Option<ConflictSeverity>,
+    #[serde(default)]
+    pub kinds: Vec<ConflictKind>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DayDisplayHint {
+    /// `YYYY-MM-DD` in the blocks' own offset.
+    pub date: String,
+    #[serde(default)]
+    pub severity: Option<ConflictSeverity>,
+    pub block_count: usize,
+    pub conflicted_blocks: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PlanningSessionListFilter {
+    pub project_id: Option<String>,
+    /// `pending`, `applied` or `expired`.
+    pub status: Option<String>,
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanningSessionPage {
+    pub items: Vec<PlanningSessionView>,
+    pub total: usize,
+    pub page: usize,
+    pub page_size: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AppliedPlan {
     pub session: PlanningSessionRecord,
@@ -116,6 +221,9 @@ struct OptionRiskMetadata {
     notes: Vec<String>,
     #[serde(default)]
     conflicts: Vec<ScheduleConflict>,
+    /// Break blocks have no task, so they live here rather than in `planning_time_blocks`.
+    #[serde(default)]
+    breaks: Vec<BreakBlock>,
 }
 
 impl PlanningService {
@@ -124,6 +232,34 @@ impl PlanningService {
             db,
             task_service,
             ai_service,
+            retention_job_started: Arc::new(AtomicBool::new(false)),
+            memory_service: None,
+        }
+    }
+
+    /// Adds the user's preference and constraint facts to every plan's
+    /// preference snapshot.
+    pub fn with_memory_facts(mut self, memory_service: Arc<MemoryService>) -> Self {
+        self.memory_service = Some(memory_service);
+        self
+    }
+
+    /// Preference and constraint facts for planning. Profile facts are left
+    /// out; they say nothing about when work can happen.
+    fn planning_facts(&self) -> Vec<MemoryFact> {
+        let Some(memory_service) = self.memory_service.as_ref() else {
+            return Vec::new();
+        };
+        match memory_service.get_facts() {
+            Ok(facts) => facts
+                .facts
+                .into_iter()
+                .filter(|fact| fact.kind != MemoryFactKind::Profile)
+                .collect(),
+            Err(err) => {
+                warn!(target: "app::planning", error = %err, "failed to load memory facts");
+                Vec::new()
+            }
         }
     }
 
@@ -133,15 +269,49 @@ impl PlanningService {
     }
 
     pub async fn generate_plan(&self, input: GeneratePlanInput) -> AppResult<PlanningSessionView> {
-        if input.task_ids.is_empty() {
+        self.generate_plan_with_progress(input, &ProgressReporter::silent())
+            .await
+    }
+
+    /// Same as [`generate_plan`](Self::generate_plan), reporting stages to
+    /// `progress`. Cancelling before the session is committed leaves nothing
+    /// behind.
+    pub async fn generate_plan_with_progress(
+        &self,
+        input: GeneratePlanInput,
+        progress: &ProgressReporter,
+    ) -> AppResult<PlanningSessionView> {
+        let project_id = input
+            .project_id
+            .as_deref()
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(str::to_string);
+        if input.task_ids.is_empty() && project_id.is_none() {
             return Err(AppError::validation("生成计划时至少需要一个任务"));
         }
 
+        progress.report("loading", 5)?;
         let conn = self.db.get_connection()?;
-        let has_ai_key = self.ai_service.has_configured_provider(&conn)?;
+        let has_provider = !input.offline && self.ai_service.has_configured_provider(&conn)?;
+        let has_ai_key = !input.optimizer_only && has_provider;
         let seed = input.seed;
 
-        let tasks = self.fetch_tasks(&input.task_ids)?;
+        let task_ids = match project_id.as_deref() {
+            Some(project_id) => project_task_ids(&conn, project_id, &input.task_ids)?,
+            None => input.task_ids.clone(),
+        };
+        let (waiting, tasks): (Vec<_>, Vec<_>) = self
+            .fetch_tasks(&task_ids)?
+            .into_iter()
+            .partition(|task| task.status == WAITING_STATUS);
+        if !waiting.is_empty() {
+            debug!(target: "app::planning", skipped = waiting.len(), "skipping tasks waiting on others");
+            if tasks.is_empty() {
+                return Err(AppError::validation("所选任务都在等待他人，暂时无需排期"));
+            }
+        }
+        let task_ids: Vec<String> = tasks.iter().map(|task| task.id.clone()).collect();
         let tasks_by_id = tasks
             .iter()
             .map(|task| (task.id.clone(), task.clone()))
@@ -152,20 +322,30 @@ impl PlanningService {
             debug!(target: "app::planning", "constraints without explicit windows, relying on optimizer fallback");
         }
 
-        let preference_id = input
-            .preference_id
-            .as_deref()
-            .unwrap_or(DEFAULT_PREFERENCE_ID)
-            .to_string();
-
         // Load preferences and close connection before async call
-        let preference_snapshot = {
+        let mut preference_snapshot = {
             let behavior = BehaviorLearningService::new(&conn);
-            behavior.load_preferences(&preference_id)?
+            match input.preference_id.as_deref() {
+                Some(preference_id) => behavior.load_profile_preferences(preference_id)?,
+                None => behavior.load_preferences(&load_default_preference_id(&conn)?)?,
+            }
         };
+        preference_snapshot.user_facts = self.planning_facts();
         let personalization_json = serde_json::to_value(&preference_snapshot)?;
 
-        let scheduling_preferences = scheduling_preferences_from(&preference_snapshot);
+        let sleep_schedule = load_sleep_schedule(&conn)?;
+        let goal_linked = load_goal_linked_task_ids(&conn)?;
+        let predecessors = load_predecessors(&conn)?;
+        let calibration = load_calibration(&conn, Utc::now())?;
+        let privacy_mode = match input.privacy_mode {
+            Some(enabled) => enabled,
+            None => load_ai_privacy_mode(&conn)?,
+        };
+        let scheduling_preferences =
+            scheduling_preferences_from(&preference_snapshot, sleep_schedule);
+        // Optimizer plans can get a second look from the provider when one
+        // is configured and the caller did not ask to stay offline
+        let critique_plan = !has_ai_key && has_provider && load_plan_critique_enabled(&conn)?;
 
         // Clone data needed for AI call (so we can drop conn)
         let tasks_for_ai = tasks.clone();
@@ -174,14 +354,16 @@ impl PlanningService {
         // Drop connection before async operations
         drop(conn);
 
-        let options = if has_ai_key {
-            let generated = self
-                .generate_with_ai(
+        progress.report("generating", 20)?;
+        let mut options = if has_ai_key {
+            let generated = progress
+                .cancellable(self.generate_with_ai(
                     &tasks_for_ai,
                     &constraints_for_ai,
                     &scheduling_preferences,
                     &preference_snapshot,
-                )
+                    privacy_mode,
+                ))
                 .await?;
             info!(target: "app::planning", "Successfully generated plan options using DeepSeek AI");
             generated
@@ -191,10 +373,37 @@ impl PlanningService {
                 &tasks_for_ai,
                 &constraints_for_ai,
                 &scheduling_preferences,
+                &goal_linked,
                 seed,
             )?
         };
 
+        if critique_plan {
+            if let Some(top_option) = options.iter_mut().min_by_key(|option| option.rank) {
+                progress.report("reviewing", 70)?;
+                let critique = self.critique_option(
+                    top_option,
+                    &tasks_by_id,
+                    &constraints_for_ai,
+                    &scheduling_preferences,
+                    &preference_snapshot,
+                    privacy_mode,
+                );
+                match progress.cancellable(critique).await {
+                    Ok(notes) => top_option.risk_notes.extend(notes),
+                    Err(err) => {
+                        progress.checkpoint()?;
+                        warn!(target: "app::planning", error = %err, "plan critique skipped");
+                    }
+                }
+            }
+        }
+
+        attach_prep_notes(&mut options, &tasks_by_id, &predecessors)?;
+        calibrate_options(&mut options, &calibration);
+
+        progress.report("saving", 85)?;
+
         // Reconnect for database operations
         let mut conn = self.db.get_connection()?;
 
@@ -204,12 +413,13 @@ impl PlanningService {
 
         let session_record = PlanningSessionRecord {
             id: session_id.clone(),
-            task_ids: input.task_ids,
+            task_ids,
             constraints: Some(serde_json::to_value(&constraints)?),
             generated_at: generated_at.clone(),
             status: "pending".to_string(),
             selected_option_id: None,
             personalization_snapshot: Some(personalization_json),
+            project_id,
             created_at: now.clone(),
             updated_at: now.clone(),
         };
@@ -225,6 +435,7 @@ impl PlanningService {
             let metadata = OptionRiskMetadata {
                 notes: option.risk_notes.clone(),
                 conflicts: option.conflicts.clone(),
+                breaks: option.breaks.clone(),
             };
 
             let option_record = PlanningOptionRecord {
@@ -262,6 +473,7 @@ impl PlanningService {
                     actual_start_at: None,
                     actual_end_at: None,
                     status: "draft".to_string(),
+                    prep_note: block.prep_note.clone(),
                 };
 
                 let block_row = PlanningTimeBlockRow::from_record(&block_record)?;
@@ -273,7 +485,16 @@ impl PlanningService {
 
         info!(target: "app::planning", session_id = %session_record.id, options = options.len(), "planning session generated");
 
-        self.load_session_view(&session_record.id, &conn)
+        let mut view = self.load_session_view(&session_record.id, &conn)?;
+        if input.include_later_items {
+            if let Some(top_option) = view.options.first() {
+                let items = LaterRepository::list(&conn, Some(LATER_STATUS_QUEUED))?;
+                view.later_suggestions =
+                    suggest_later_slots(&top_option.blocks, &items, &scheduling_preferences);
+            }
+        }
+
+        Ok(view)
     }
 
     pub fn apply_option(&self, input: ApplyPlanInput) -> AppResult<AppliedPlan> {
@@ -286,6 +507,9 @@ impl PlanningService {
         if session_row.status == "applied" {
             return Err(AppError::conflict("该规划会话已完成应用"));
         }
+        if session_row.status == "expired" {
+            return Err(AppError::conflict("该规划会话已过期，请重新生成计划"));
+        }
 
         let mut session_row_for_update = session_row.clone();
         let session_record = session_row.into_record()?;
@@ -363,6 +587,7 @@ impl PlanningService {
                 warn!(target: "app::planning", task_id = %task_id, "skipping task update because record not found");
             }
         }
+        sync_block_reminders(tx_conn, Utc::now())?;
 
         tx.commit()?;
 
@@ -388,6 +613,9 @@ impl PlanningService {
 
         let session_row = PlanningRepository::find_session_by_id(tx_conn, &input.session_id)?
             .ok_or_else(AppError::not_found)?;
+        if session_row.status == "expired" {
+            return Err(AppError::conflict("该规划会话已过期，请重新生成计划"));
+        }
         let mut session_row_for_update = session_row.clone();
         let session_record = session_row.into_record()?;
 
@@ -443,12 +671,127 @@ impl PlanningService {
 
         session_row_for_update.updated_at = Utc::now().to_rfc3339();
         PlanningRepository::update_session(tx_conn, &session_row_for_update)?;
+        sync_block_reminders(tx_conn, Utc::now())?;
 
         tx.commit()?;
 
         self.load_session_view(&input.session_id, &conn)
     }
 
+    /// One page of sessions, newest first, with their options and blocks.
+    pub fn list_sessions(
+        &self,
+        filter: PlanningSessionListFilter,
+    ) -> AppResult<PlanningSessionPage> {
+        let project_id = filter
+            .project_id
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty());
+        let status = filter
+            .status
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty());
+        let page = filter.page.unwrap_or(1).max(1);
+        let page_size = filter
+            .page_size
+            .unwrap_or(DEFAULT_SESSION_PAGE_SIZE)
+            .clamp(1, MAX_SESSION_PAGE_SIZE);
+
+        let conn = self.db.get_connection()?;
+        let total = PlanningRepository::count_sessions(&conn, project_id, status)?;
+        let rows = PlanningRepository::list_sessions(
+            &conn,
+            project_id,
+            status,
+            page_size,
+            (page - 1) * page_size,
+        )?;
+        let items = rows
+            .iter()
+            .map(|row| self.load_session_view(&row.id, &conn))
+            .collect::<AppResult<Vec<_>>>()?;
+        debug!(target: "app::planning", total, page, page_size, returned = items.len(), "planning sessions listed");
+
+        Ok(PlanningSessionPage {
+            items,
+            total,
+            page,
+            page_size,
+        })
+    }
+
+    pub fn get_session(&self, session_id: &str) -> AppResult<PlanningSessionView> {
+        let conn = self.db.get_connection()?;
+        self.load_session_view(session_id, &conn)
+    }
+
+    /// Expires pending sessions older than the configured retention and
+    /// prunes their draft blocks. Returns how many sessions expired.
+    pub fn expire_stale_sessions(&self, now: DateTime<Utc>) -> AppResult<usize> {
+        let mut conn = self.db.get_connection()?;
+        let retention_days = load_planning_session_retention_days(&conn)?;
+        let cutoff = (now - Duration::days(i64::from(retention_days))).to_rfc3339();
+
+        let tx = conn.transaction()?;
+        let expired = PlanningRepository::expire_pending_sessions(&tx, &cutoff, &now.to_rfc3339())?;
+        tx.commit()?;
+
+        if expired > 0 {
+            info!(target: "app::planning", expired, retention_days, "stale planning sessions expired");
+        }
+        Ok(expired)
+    }
+
+    /// Expires stale sessions now and then once a day on a background thread.
+    pub fn ensure_retention_job(self: &Arc<Self>) -> AppResult<()> {
+        if self
+            .retention_job_started
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Ok(());
+        }
+
+        let runner = Arc::clone(self);
+        if let Err(err) = spawn_supervised(
+            self.db.clone(),
+            "planning-retention-job",
+            move || loop {
+                if let Err(err) = runner.expire_stale_sessions(Utc::now()) {
+                    error!(target: "app::planning", error = %err, "planning session retention failed");
+                }
+                thread::sleep(RETENTION_INTERVAL);
+            },
+        ) {
+            self.retention_job_started.store(false, Ordering::SeqCst);
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    /// Deletes a session that was never applied, along with its options and
+    /// draft blocks.
+    pub fn discard_session(&self, session_id: &str) -> AppResult<()> {
+        let mut conn = self.db.get_connection()?;
+        let tx = conn.transaction()?;
+        let session_row = PlanningRepository::find_session_by_id(&tx, session_id)?
+            .ok_or_else(AppError::not_found)?;
+        if session_row.status == "applied" {
+            return Err(AppError::conflict("已应用的规划会话无法丢弃"));
+        }
+
+        PlanningRepository::delete_time_blocks_for_session(&tx, session_id)?;
+        PlanningRepository::delete_options_for_session(&tx, session_id)?;
+        PlanningRepository::delete_session(&tx, session_id)?;
+        tx.commit()?;
+
+        info!(target: "app::planning", session_id, "planning session discarded");
+        Ok(())
+    }
+
     fn fetch_tasks(&self, ids: &[String]) -> AppResult<Vec<TaskRecord>> {
         let mut results = Vec::new();
         for id in ids {
@@ -458,56 +801,157 @@ impl PlanningService {
         Ok(results)
     }
 
-    /// Generate plan options using DeepSeek AI service
+    /// Generate plan options using DeepSeek AI service.
+    ///
+    /// In privacy mode task and event ids are replaced by placeholders and
+    /// titles are left out, so only durations and constraints leave the
+    /// device; the placeholders are mapped back once the response arrives.
     async fn generate_with_ai(
         &self,
         tasks: &[TaskRecord],
         constraints: &ScheduleConstraints,
-        _preferences: &SchedulingPreferences,
+        preferences: &SchedulingPreferences,
         preference_snapshot: &PreferenceSnapshot,
+        privacy_mode: bool,
     ) -> AppResult<Vec<PlanOption>> {
+        let mut placeholders = privacy_mode.then(PlaceholderMap::new);
+
         // Build AI request payload
         let task_items: Vec<serde_json::Value> = tasks
             .iter()
             .map(|task| {
-                json!({
-                    "id": task.id,
-                    "title": task.title,
-                    "priority": task.priority,
-                    "estimatedMinutes": task.estimated_minutes.or_else(|| task.estimated_hours.map(|h| (h * 60.0) as i64)),
-                    "dueAt": task.due_at.as_ref(),
-                    "startAt": task.start_at.as_ref().or(task.planned_start_at.as_ref()),
-                })
+                let estimated_minutes = task
+                    .estimated_minutes
+                    .or_else(|| task.estimated_hours.map(|h| (h * 60.0) as i64));
+                let start_at = task.start_at.as_ref().or(task.planned_start_at.as_ref());
+                match placeholders.as_mut() {
+                    Some(map) => json!({
+                        "id": map.alias("T", &task.id, Some(&task.title)),
+                        "priority": task.priority,
+                        "estimatedMinutes": estimated_minutes,
+                        "dueAt": task.due_at.as_ref(),
+                        "startAt": start_at,
+                    }),
+                    None => json!({
+                        "id": task.id,
+                        "title": task.title,
+                        "priority": task.priority,
+                        "estimatedMinutes": estimated_minutes,
+                        "dueAt": task.due_at.as_ref(),
+                        "startAt": start_at,
+                    }),
+                }
             })
             .collect();
 
+        let existing_events: Vec<ExistingEvent> = match placeholders.as_mut() {
+            Some(map) => constraints
+                .existing_events
+                .iter()
+                .map(|event| ExistingEvent {
+                    id: map.alias("E", &event.id, None),
+                    ..event.clone()
+                })
+                .collect(),
+            None => constraints.existing_events.clone(),
+        };
+
         let ai_payload = json!({
             "tasks": task_items,
             "constraints": {
                 "planningStartAt": constraints.planning_start_at,
                 "planningEndAt": constraints.planning_end_at,
                 "availableWindows": constraints.available_windows,
-                "existingEvents": constraints.existing_events,
+                "existingEvents": existing_events,
                 "maxFocusMinutesPerDay": constraints.max_focus_minutes_per_day,
             },
-            "preferences": {
-                "focusStartMinute": preference_snapshot.focus_start_minute,
-                "focusEndMinute": preference_snapshot.focus_end_minute,
-                "bufferMinutesBetweenBlocks": preference_snapshot.buffer_minutes_between_blocks,
-                "preferCompactSchedule": preference_snapshot.prefer_compact_schedule,
-                "avoidanceWindows": preference_snapshot.avoidance_windows,
-            },
+            "preferences": preferences_payload(preferences, preference_snapshot),
             "context": {
                 "source": "planning_service",
                 "timestamp": Utc::now().to_rfc3339(),
+                "privacyMode": privacy_mode,
             }
         });
 
         // Call AI service
-        let schedule_dto = self.ai_service.plan_schedule(ai_payload).await?;
+        let mut schedule_dto = self.ai_service.plan_schedule(ai_payload).await?;
+        if let Some(map) = placeholders.as_ref() {
+            resolve_plan_placeholders(&mut schedule_dto, map)?;
+        }
 
         // Convert AI response to PlanOption format
-        self.convert_ai_response_to_plan_options(schedule_dto, tasks)
+        let mut options = self.convert_ai_response_to_plan_options(schedule_dto, tasks)?;
+
+        // The model may ignore the sleep schedule, so surface any overlap
+        if let Some(schedule) = preferences.sleep_schedule.as_ref() {
+            for option in options.iter_mut() {
+                let sleep_conflicts = detect_sleep_conflicts(&option.blocks, schedule)?;
+                if !sleep_conflicts.is_empty() {
+                    option.risk_notes.push(format!(
+                        "{} 个时间块落在睡眠时间内，请调整后再应用",
+                        sleep_conflicts.len()
+                    ));
+                    option.conflicts.splice(0..0, sleep_conflicts);
+                }
+            }
+        }
+
+        Ok(options)
+    }
+
+    /// Asks the provider to review `option` against the user's preferences
+    /// and returns its findings as risk notes. Privacy mode hides task titles
+    /// the same way plan generation does.
+    async fn critique_option(
+        &self,
+        option: &PlanOption,
+        tasks: &HashMap<String, TaskRecord>,
+        constraints: &ScheduleConstraints,
+        preferences: &SchedulingPreferences,
+        preference_snapshot: &PreferenceSnapshot,
+        privacy_mode: bool,
+    ) -> AppResult<Vec<String>> {
+        let mut placeholders = privacy_mode.then(PlaceholderMap::new);
+        let blocks: Vec<serde_json::Value> = option
+            .blocks
+            .iter()
+            .map(|block| {
+                let title = tasks.get(&block.task_id).map(|task| task.title.as_str());
+                let (task_id, title) = match placeholders.as_mut() {
+                    Some(map) => (map.alias("T", &block.task_id, title), None),
+                    None => (block.task_id.clone(), title),
+                };
+                json!({
+                    "taskId": task_id,
+                    "title": title,
+                    "startAt": block.start_at,
+                    "endAt": block.end_at,
+                })
+            })
+            .collect();
+
+        let input = json!({
+            "plan": {
+                "blocks": blocks,
+                "breaks": option.breaks,
+                "riskNotes": option.risk_notes,
+            },
+            "constraints": {
+                "planningStartAt": constraints.planning_start_at,
+                "planningEndAt": constraints.planning_end_at,
+                "availableWindows": constraints.available_windows,
+                "maxFocusMinutesPerDay": constraints.max_focus_minutes_per_day,
+            },
+            "preferences": preferences_payload(preferences, preference_snapshot),
+            "context": {
+                "source": "planning_service",
+                "timestamp": Utc::now().to_rfc3339(),
+                "privacyMode": privacy_mode,
+            }
+        });
+
+        let critique = self.ai_service.critique_plan(&input).await?;
+        Ok(critique_notes(critique, placeholders.as_ref()))
     }
 
     fn generate_with_optimizer(
@@ -515,12 +959,14 @@ impl PlanningService {
         tasks: &[TaskRecord],
         constraints: &ScheduleConstraints,
         preferences: &SchedulingPreferences,
+        goal_linked: &HashSet<String>,
         seed: Option<u64>,
     ) -> AppResult<Vec<PlanOption>> {
         let optimizer = ScheduleOptimizer::new(seed);
+        let now = Utc::now();
         let schedulable_tasks = tasks
             .iter()
-            .map(Self::map_schedulable_task)
+            .map(|task| Self::map_schedulable_task(task, goal_linked, now))
             .collect::<Vec<_>>();
 
         optimizer.generate_plan_options(schedulable_tasks, constraints.clone(), preferences.clone())
@@ -564,6 +1010,10 @@ impl PlanningService {
                 .unwrap_or(0.75) as f32;
 
             let notes = item.get("notes").and_then(|v| v.as_str()).unwrap_or("");
+            let prep_note = item
+                .get("prepNote")
+                .and_then(|v| v.as_str())
+                .map(str::to_string);
 
             blocks.push(TimeBlockCandidate {
                 id: Uuid::new_v4().to_string(),
@@ -573,6 +1023,7 @@ impl PlanningService {
                 flexibility: Some("moderate".to_string()),
                 confidence,
                 conflict_flags: Vec::new(),
+                prep_note,
             });
 
             if !notes.is_empty() {
@@ -598,6 +1049,7 @@ impl PlanningService {
             score: 90.0, // High score for AI-generated plan
             is_fallback: false,
             blocks,
+            breaks: Vec::new(),
             rationale: rationale_steps,
             conflicts: conflicts.clone(),
             risk_notes: if conflicts.is_empty() {
@@ -644,10 +1096,13 @@ impl PlanningService {
             let metadata = parse_risk_metadata(&option_row);
             merge_conflicts(&mut aggregated_conflicts, &metadata.conflicts);
 
+            let display_hints = build_display_hints(&blocks, &metadata.conflicts);
             options.push(PlanningOptionView {
                 option: option_record,
                 blocks,
+                breaks: metadata.breaks,
                 conflicts: metadata.conflicts,
+                display_hints,
             });
         }
 
@@ -658,12 +1113,18 @@ impl PlanningService {
             options,
             conflicts,
             preference_snapshot,
+            later_suggestions: Vec::new(),
         })
     }
 }
 
 impl PlanningService {
-    fn map_schedulable_task(task: &TaskRecord) -> SchedulableTask {
+    fn map_schedulable_task(
+        task: &TaskRecord,
+        goal_linked: &HashSet<String>,
+        now: DateTime<Utc>,
+    ) -> SchedulableTask {
+        let matrix = priority_matrix::classify(task, goal_linked.contains(&task.id), now);
         SchedulableTask {
             id: task.id.clone(),
             title: task.title.clone(),
@@ -681,8 +1142,99 @@ impl PlanningService {
             is_parallelizable: task.tags.iter().any(|tag| {
                 tag.eq_ignore_ascii_case("parallel") || tag.eq_ignore_ascii_case("parallelizable")
             }),
+            quadrant: Some(matrix.quadrant),
+            project_id: task.project_id.clone(),
+        }
+    }
+}
+
+/// The explicitly requested tasks followed by the project's unblocked ones.
+fn project_task_ids(
+    conn: &Connection,
+    project_id: &str,
+    requested: &[String],
+) -> AppResult<Vec<String>> {
+    let project = ProjectRepository::find(conn, project_id)?
+        .ok_or_else(|| AppError::validation("所选项目不存在"))?
+        .into_record()?;
+    if project.status == ProjectStatus::Archived {
+        return Err(AppError::validation("项目已归档，无法生成计划"));
+    }
+
+    let mut seen = HashSet::new();
+    let task_ids = requested
+        .iter()
+        .cloned()
+        .chain(ProjectRepository::list_unblocked_task_ids(
+            conn,
+            project_id,
+            &Utc::now().to_rfc3339(),
+        )?)
+        .filter(|id| seen.insert(id.clone()))
+        .collect::<Vec<_>>();
+    if task_ids.is_empty() {
+        return Err(AppError::validation("项目中没有可规划的任务"));
+    }
+    debug!(target: "app::planning", project_id, count = task_ids.len(), "planning project tasks");
+    Ok(task_ids)
+}
+
+/// Maps placeholder task ids in an AI schedule back to local ids and expands
+/// placeholders in free text with the local titles.
+fn resolve_plan_placeholders(dto: &mut SchedulePlanDto, map: &PlaceholderMap) -> AppResult<()> {
+    for item in dto.items.iter_mut() {
+        let Some(fields) = item.as_object_mut() else {
+            continue;
+        };
+        if let Some(placeholder) = fields.get("taskId").and_then(|value| value.as_str()) {
+            let task_id = map
+                .resolve_id(placeholder)
+                .ok_or_else(|| AppError::validation("AI 返回了未知的任务占位符"))?
+                .to_string();
+            fields.insert("taskId".to_string(), json!(task_id));
+        }
+        for key in ["title", "notes", "prepNote"] {
+            if let Some(text) = fields.get(key).and_then(|value| value.as_str()) {
+                let resolved = map.resolve_text(text);
+                fields.insert(key.to_string(), json!(resolved));
+            }
         }
     }
+    Ok(())
+}
+
+fn preferences_payload(
+    preferences: &SchedulingPreferences,
+    preference_snapshot: &PreferenceSnapshot,
+) -> serde_json::Value {
+    json!({
+        "focusStartMinute": preference_snapshot.focus_start_minute,
+        "focusEndMinute": preference_snapshot.focus_end_minute,
+        "bufferMinutesBetweenBlocks": preference_snapshot.buffer_minutes_between_blocks,
+        "preferCompactSchedule": preference_snapshot.prefer_compact_schedule,
+        "avoidanceWindows": preference_snapshot.avoidance_windows,
+        "weekdayOverrides": preference_snapshot.weekday_overrides,
+        "userFacts": preference_snapshot.user_facts,
+        "breakAfterFocusMinutes": preferences.break_after_focus_minutes,
+        "breakMinutes": preferences.break_minutes,
+        "sleepSchedule": preferences.sleep_schedule,
+    })
+}
+
+/// Risk notes for the issues a critique found, with privacy placeholders
+/// mapped back to task titles.
+fn critique_notes(critique: PlanCritiqueDto, placeholders: Option<&PlaceholderMap>) -> Vec<String> {
+    critique
+        .issues
+        .into_iter()
+        .map(|issue| {
+            let message = match placeholders {
+                Some(map) => map.resolve_text(&issue.message),
+                None => issue.message,
+            };
+            format!("{CRITIQUE_NOTE_PREFIX}{message}")
+        })
+        .collect()
 }
 
 fn priority_weight(priority: &str) -> f32 {
@@ -695,12 +1247,20 @@ fn priority_weight(priority: &str) -> f32 {
     }
 }
 
-fn scheduling_preferences_from(snapshot: &PreferenceSnapshot) -> SchedulingPreferences {
+fn scheduling_preferences_from(
+    snapshot: &PreferenceSnapshot,
+    sleep_schedule: Option<SleepSchedule>,
+) -> SchedulingPreferences {
     SchedulingPreferences {
         focus_start_minute: snapshot.focus_start_minute,
         focus_end_minute: snapshot.focus_end_minute,
         buffer_minutes_between_blocks: snapshot.buffer_minutes_between_blocks,
         prefer_compact_schedule: snapshot.prefer_compact_schedule,
+        sleep_schedule,
+        break_after_focus_minutes: snapshot.break_after_focus_minutes,
+        break_minutes: snapshot.break_minutes,
+        weekday_overrides: snapshot.weekday_overrides.clone(),
+        context_switch_weight: snapshot.context_switch_weight,
     }
 }
 
@@ -757,6 +1317,7 @@ fn time_block_to_candidate(block: &PlanningTimeBlockRecord) -> AppResult<TimeBlo
         flexibility: block.flexibility.clone(),
         confidence: block.confidence.unwrap_or(0.75) as f32,
         conflict_flags: flags,
+        prep_note: block.prep_note.clone(),
     })
 }
 
@@ -843,6 +1404,91 @@ fn update_block_conflict_flags(
     Ok(())
 }
 
+fn build_display_hints(
+    blocks: &[PlanningTimeBlockRecord],
+    conflicts: &[ScheduleConflict],
+) -> PlanningDisplayHints {
+    let mut block_kinds: HashMap<&str, BTreeSet<ConflictKind>> = HashMap::new();
+    for block in blocks {
+        let kinds = block_kinds.entry(block.id.as_str()).or_default();
+        let flags = block
+            .conflict_flags
+            .as_ref()
+            .and_then(|value| serde_json::from_value::<Vec<String>>(value.clone()).ok())
+            .unwrap_or_default();
+        kinds.extend(
+            flags
+                .iter()
+                .filter_map(|flag| ConflictKind::from_flag(flag)),
+        );
+    }
+
+    let mut day_severity: BTreeMap<String, Option<ConflictSeverity>> = BTreeMap::new();
+    for conflict in conflicts {
+        let Some(kind) = ConflictKind::from_flag(&conflict.conflict_type) else {
+            continue;
+        };
+        if let Some(kinds) = conflict
+            .related_block_id
+            .as_deref()
+            .and_then(|id| block_kinds.get_mut(id))
+        {
+            kinds.insert(kind);
+        }
+        if let Some(date) = conflict.date.as_ref() {
+            let entry = day_severity.entry(date.clone()).or_default();
+            *entry = (*entry).max(Some(conflict.severity));
+        }
+    }
+
+    let mut day_counts: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    let mut block_hints = Vec::with_capacity(blocks.len());
+    for block in blocks {
+        let kinds: Vec<ConflictKind> = block_kinds
+            .remove(block.id.as_str())
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        let severity = kinds.iter().map(ConflictKind::severity).max();
+
+        if let Ok(start) = schedule_utils::parse_datetime(&block.start_at) {
+            let date = start.date_naive().to_string();
+            let counts = day_counts.entry(date.clone()).or_default();
+            counts.0 += 1;
+            if severity.is_some() {
+                counts.1 += 1;
+            }
+            let entry = day_severity.entry(date).or_default();
+            *entry = (*entry).max(severity);
+        }
+
+        block_hints.push(BlockDisplayHint {
+            block_id: block.id.clone(),
+            severity,
+            kinds,
+        });
+    }
+
+    let days = day_severity
+        .into_iter()
+        .map(|(date, severity)| {
+            let (block_count, conflicted_blocks) =
+                day_counts.get(&date).copied().unwrap_or_default();
+            DayDisplayHint {
+                date,
+                severity,
+                block_count,
+                conflicted_blocks,
+            }
+        })
+        .collect();
+
+    PlanningDisplayHints {
+        blocks: block_hints,
+        days,
+    }
+}
+
 fn parse_risk_metadata(row: &PlanningOptionRow) -> OptionRiskMetadata {
     row.risk_notes
         .as_ref()
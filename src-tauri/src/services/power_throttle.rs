@@ -0,0 +1,133 @@
+//! Holds back non-essential background jobs while the laptop runs on
+//! battery, according to the user's [`PowerPolicy`].
+
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration as StdDuration, Instant};
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use tracing::{debug, warn};
+
+use crate::db::DbPool;
+use crate::error::AppResult;
+use crate::models::settings::{PowerPolicy, PowerThrottleMode};
+use crate::services::settings_service::load_power_policy;
+use crate::utils::power::{PowerSource, PowerState, SystemPowerSource};
+
+const RECHECK_INTERVAL: StdDuration = StdDuration::from_secs(15 * 60);
+
+/// Whether a job should wait under `policy` given the current power state.
+pub fn should_defer(policy: &PowerPolicy, state: &PowerState) -> bool {
+    let PowerState::Battery {
+        percent,
+        power_saver,
+    } = state
+    else {
+        return false;
+    };
+    match policy.mode {
+        PowerThrottleMode::Off => false,
+        PowerThrottleMode::DeferOnBattery => true,
+        PowerThrottleMode::DeferOnLowBattery => {
+            *power_saver || percent.is_some_and(|percent| percent < policy.low_battery_percent)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerStatus {
+    pub state: PowerState,
+    /// Whether background jobs are currently being deferred.
+    pub deferring: bool,
+}
+
+pub struct PowerThrottle {
+    db: DbPool,
+    source: Arc<dyn PowerSource>,
+}
+
+impl PowerThrottle {
+    pub fn new(db: DbPool) -> Self {
+        Self::with_source(db, Arc::new(SystemPowerSource::default()))
+    }
+
+    pub fn with_source(db: DbPool, source: Arc<dyn PowerSource>) -> Self {
+        Self { db, source }
+    }
+
+    pub fn status(&self) -> AppResult<PowerStatus> {
+        let policy = self.db.with_read_connection(load_power_policy)?;
+        let state = self.source.state();
+        Ok(PowerStatus {
+            deferring: should_defer(&policy, &state),
+            state,
+        })
+    }
+
+    /// Blocks the calling job thread until the policy lets `job` run, or
+    /// until it has waited `max_defer_hours`.
+    pub fn wait_until_allowed(&self, job: &str) {
+        let started = Instant::now();
+        loop {
+            let policy = self
+                .db
+                .with_read_connection(load_power_policy)
+                .unwrap_or_default();
+            if !should_defer(&policy, &self.source.state()) {
+                return;
+            }
+
+            let max_defer = StdDuration::from_secs(u64::from(policy.max_defer_hours) * 60 * 60);
+            let waited = started.elapsed();
+            if waited >= max_defer {
+                warn!(target: "app::power", job, "deferred job runs on battery after waiting too long");
+                return;
+            }
+            debug!(target: "app::power", job, "deferring background job until on AC power");
+            thread::sleep(RECHECK_INTERVAL.min(max_defer - waited));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn battery_defers_according_to_the_policy() {
+        let low = PowerState::Battery {
+            percent: Some(20),
+            power_saver: false,
+        };
+        let charged = PowerState::Battery {
+            percent: Some(80),
+            power_saver: false,
+        };
+        let default = PowerPolicy::default();
+        assert!(should_defer(&default, &charged));
+        assert!(!should_defer(&default, &PowerState::Ac));
+        assert!(!should_defer(&default, &PowerState::Unknown));
+
+        let low_only = PowerPolicy {
+            mode: PowerThrottleMode::DeferOnLowBattery,
+            ..PowerPolicy::default()
+        };
+        assert!(should_defer(&low_only, &low));
+        assert!(!should_defer(&low_only, &charged));
+        assert!(should_defer(
+            &low_only,
+            &PowerState::Battery {
+                percent: Some(80),
+                power_saver: true
+            }
+        ));
+
+        let off = PowerPolicy {
+            mode: PowerThrottleMode::Off,
+            ..PowerPolicy::default()
+        };
+        assert!(!should_defer(&off, &low));
+    }
+}
@@ -0,0 +1,210 @@
+//! Short "what to have ready" notes for planned blocks, built from the task
+//! description and from prerequisite tasks that finish just before. Notes the
+//! provider already wrote for a block are kept.
+
+use std::collections::HashMap;
+
+use chrono::Duration;
+use rusqlite::Connection;
+
+use crate::error::AppResult;
+use crate::models::task::TaskRecord;
+use crate::services::schedule_optimizer::PlanOption;
+use crate::services::schedule_utils;
+
+const MAX_PREP_NOTE_CHARS: usize = 120;
+const MAX_DESCRIPTION_CHARS: usize = 60;
+/// A prerequisite block ending this close to the start is a hand-off.
+const HANDOFF_MINUTES: i64 = 60;
+
+/// Predecessor task ids keyed by successor id.
+pub fn load_predecessors(conn: &Connection) -> AppResult<HashMap<String, Vec<String>>> {
+    let mut stmt = conn.prepare("SELECT predecessor_id, successor_id FROM task_dependencies")?;
+    let mut predecessors: HashMap<String, Vec<String>> = HashMap::new();
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+    for row in rows {
+        let (predecessor, successor) = row?;
+        predecessors.entry(successor).or_default().push(predecessor);
+    }
+    Ok(predecessors)
+}
+
+/// Fills in `prep_note` on every block of `options` that has none yet.
+pub fn attach_prep_notes(
+    options: &mut [PlanOption],
+    tasks: &HashMap<String, TaskRecord>,
+    predecessors: &HashMap<String, Vec<String>>,
+) -> AppResult<()> {
+    for option in options.iter_mut() {
+        let mut ends = Vec::with_capacity(option.blocks.len());
+        for block in &option.blocks {
+            ends.push((
+                block.task_id.clone(),
+                schedule_utils::parse_datetime(&block.end_at)?,
+            ));
+        }
+
+        for block in option.blocks.iter_mut() {
+            if let Some(note) = block.prep_note.take() {
+                let note = note.trim();
+                if !note.is_empty() {
+                    block.prep_note = Some(truncate(note, MAX_PREP_NOTE_CHARS));
+                    continue;
+                }
+            }
+
+            let start = schedule_utils::parse_datetime(&block.start_at)?;
+            let mut parts = Vec::new();
+            let handoffs = predecessors
+                .get(&block.task_id)
+                .into_iter()
+                .flatten()
+                .filter(|predecessor| {
+                    ends.iter().any(|(task_id, end)| {
+                        task_id == *predecessor
+                            && *end <= start
+                            && start - *end <= Duration::minutes(HANDOFF_MINUTES)
+                    })
+                })
+                .filter_map(|predecessor| tasks.get(predecessor));
+            for predecessor in handoffs {
+                parts.push(format!("接上「{}」的产出", predecessor.title));
+            }
+            if let Some(line) = tasks
+                .get(&block.task_id)
+                .and_then(|task| task.description.as_deref())
+                .and_then(|description| {
+                    description
+                        .lines()
+                        .map(str::trim)
+                        .find(|line| !line.is_empty())
+                })
+            {
+                parts.push(format!("准备：{}", truncate(line, MAX_DESCRIPTION_CHARS)));
+            }
+
+            if !parts.is_empty() {
+                block.prep_note = Some(truncate(&parts.join("；"), MAX_PREP_NOTE_CHARS));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn truncate(text: &str, limit: usize) -> String {
+    if text.chars().count() <= limit {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(limit - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::schedule_optimizer::TimeBlockCandidate;
+
+    fn task(id: &str, description: Option<&str>) -> TaskRecord {
+        TaskRecord {
+            id: id.to_string(),
+            title: id.to_string(),
+            description: description.map(str::to_string),
+            status: "todo".to_string(),
+            priority: "medium".to_string(),
+            planned_start_at: None,
+            start_at: None,
+            due_at: None,
+            completed_at: None,
+            estimated_minutes: Some(60),
+            estimated_hours: None,
+            tags: Vec::new(),
+            owner_id: None,
+            task_type: None,
+            is_recurring: false,
+            recurrence: None,
+            ai: None,
+            external_links: Vec::new(),
+            project_id: None,
+            snoozed_until: None,
+            snooze_notify: false,
+            color: None,
+            icon: None,
+            delegated_to: None,
+            waiting_on: None,
+            waiting_since: None,
+            follow_up_task_id: None,
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            updated_at: "2026-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    fn block(task_id: &str, start_at: &str, end_at: &str) -> TimeBlockCandidate {
+        TimeBlockCandidate {
+            id: format!("block-{task_id}"),
+            task_id: task_id.to_string(),
+            start_at: start_at.to_string(),
+            end_at: end_at.to_string(),
+            flexibility: None,
+            confidence: 0.8,
+            conflict_flags: Vec::new(),
+            prep_note: None,
+        }
+    }
+
+    #[test]
+    fn notes_come_from_descriptions_handoffs_and_the_provider() -> AppResult<()> {
+        let tasks: HashMap<_, _> = [
+            task("draft", None),
+            task("review", Some("\n  打开评审清单和上周的反馈\n其余说明")),
+            task("publish", None),
+            task("later", None),
+        ]
+        .into_iter()
+        .map(|task| (task.id.clone(), task))
+        .collect();
+        let predecessors = HashMap::from([
+            ("review".to_string(), vec!["draft".to_string()]),
+            ("later".to_string(), vec!["draft".to_string()]),
+        ]);
+        let mut provided = block("publish", "2026-03-02T14:00:00Z", "2026-03-02T15:00:00Z");
+        provided.prep_note = Some("  带上发布账号  ".to_string());
+        let mut options = vec![PlanOption {
+            id: "option".to_string(),
+            label: "方案".to_string(),
+            rank: 1,
+            score: 100.0,
+            is_fallback: false,
+            blocks: vec![
+                block("draft", "2026-03-02T09:00:00Z", "2026-03-02T10:00:00Z"),
+                block("review", "2026-03-02T10:15:00Z", "2026-03-02T11:00:00Z"),
+                provided,
+                block("later", "2026-03-02T16:00:00Z", "2026-03-02T17:00:00Z"),
+            ],
+            breaks: Vec::new(),
+            rationale: Vec::new(),
+            conflicts: Vec::new(),
+            risk_notes: Vec::new(),
+        }];
+
+        attach_prep_notes(&mut options, &tasks, &predecessors)?;
+        let notes: Vec<_> = options[0]
+            .blocks
+            .iter()
+            .map(|block| block.prep_note.as_deref())
+            .collect();
+        assert_eq!(
+            notes,
+            [
+                None,
+                Some("接上「draft」的产出；准备：打开评审清单和上周的反馈"),
+                Some("带上发布账号"),
+                // The prerequisite ended hours earlier
+                None,
+            ]
+        );
+        Ok(())
+    }
+}
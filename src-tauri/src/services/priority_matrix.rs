@@ -0,0 +1,160 @@
+//! Eisenhower classification of open tasks: urgency comes from how close the
+//! due date is, importance from the priority and whether an active goal
+//! includes the task.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use rusqlite::Connection;
+
+use crate::error::AppResult;
+use crate::models::task::{MatrixQuadrant, TaskMatrixEntry, TaskMatrixSnapshot, TaskRecord};
+use crate::services::task_service::is_snoozed;
+
+/// Urgency rises linearly over the last week before the due date.
+const URGENCY_HORIZON_HOURS: f64 = 7.0 * 24.0;
+/// Due within two days, or overdue, counts as urgent.
+const URGENT_WITHIN_HOURS: f64 = 48.0;
+const IMPORTANT_AT: f64 = 0.6;
+/// Added to the importance of tasks that move an active goal forward.
+const GOAL_BONUS: f64 = 0.25;
+
+/// 0 for tasks without a due date or due more than a week out, 1 once due.
+pub fn urgency(due_at: Option<&str>, now: DateTime<Utc>) -> f64 {
+    let Some(due) = due_at.and_then(|due| DateTime::parse_from_rfc3339(due).ok()) else {
+        return 0.0;
+    };
+    let hours = (due.with_timezone(&Utc) - now).num_minutes() as f64 / 60.0;
+    (1.0 - hours / URGENCY_HORIZON_HOURS).clamp(0.0, 1.0)
+}
+
+pub fn importance(priority: &str, linked_to_goal: bool) -> f64 {
+    let base = match priority {
+        "urgent" => 1.0,
+        "high" => 0.8,
+        "medium" => 0.5,
+        "low" => 0.2,
+        _ => 0.4,
+    };
+    let bonus = if linked_to_goal { GOAL_BONUS } else { 0.0 };
+    (base + bonus).min(1.0)
+}
+
+pub fn quadrant(urgency: f64, importance: f64) -> MatrixQuadrant {
+    let urgent = urgency >= 1.0 - URGENT_WITHIN_HOURS / URGENCY_HORIZON_HOURS;
+    match (urgent, importance >= IMPORTANT_AT) {
+        (true, true) => MatrixQuadrant::DoFirst,
+        (false, true) => MatrixQuadrant::Schedule,
+        (true, false) => MatrixQuadrant::Delegate,
+        (false, false) => MatrixQuadrant::Eliminate,
+    }
+}
+
+pub fn classify(task: &TaskRecord, linked_to_goal: bool, now: DateTime<Utc>) -> TaskMatrixEntry {
+    let urgency = urgency(task.due_at.as_deref(), now);
+    let importance = importance(&task.priority, linked_to_goal);
+    TaskMatrixEntry {
+        task_id: task.id.clone(),
+        title: task.title.clone(),
+        status: task.status.clone(),
+        priority: task.priority.clone(),
+        due_at: task.due_at.clone(),
+        urgency: round2(urgency),
+        importance: round2(importance),
+        linked_to_goal,
+        quadrant: quadrant(urgency, importance),
+    }
+}
+
+/// Tasks linked to a goal that is neither completed nor cancelled.
+pub fn load_goal_linked_task_ids(conn: &Connection) -> AppResult<HashSet<String>> {
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT DISTINCT a.task_id
+            FROM goal_task_associations a
+            JOIN goals g ON g.id = a.goal_id
+            WHERE COALESCE(g.status, 'not_started') NOT IN ('completed', 'cancelled')
+        "#,
+    )?;
+    let ids = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<HashSet<String>, _>>()?;
+    Ok(ids)
+}
+
+/// Open, unsnoozed tasks sorted into quadrants.
+pub fn build_matrix_snapshot(
+    tasks: &[TaskRecord],
+    goal_linked: &HashSet<String>,
+    now: DateTime<Utc>,
+) -> TaskMatrixSnapshot {
+    let mut entries: Vec<TaskMatrixEntry> = tasks
+        .iter()
+        .filter(|task| !matches!(task.status.as_str(), "done" | "archived"))
+        .filter(|task| !is_snoozed(task, now))
+        .map(|task| classify(task, goal_linked.contains(&task.id), now))
+        .collect();
+    entries.sort_by(|a, b| {
+        b.urgency
+            .total_cmp(&a.urgency)
+            .then_with(|| b.importance.total_cmp(&a.importance))
+    });
+
+    let mut snapshot = TaskMatrixSnapshot {
+        generated_at: now.to_rfc3339(),
+        ..TaskMatrixSnapshot::default()
+    };
+    for entry in entries {
+        match entry.quadrant {
+            MatrixQuadrant::DoFirst => snapshot.do_first.push(entry),
+            MatrixQuadrant::Schedule => snapshot.schedule.push(entry),
+            MatrixQuadrant::Delegate => snapshot.delegate.push(entry),
+            MatrixQuadrant::Eliminate => snapshot.eliminate.push(entry),
+        }
+    }
+    snapshot
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn quadrants_follow_due_proximity_priority_and_goals() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+        let due_in = |hours: i64| (now + Duration::hours(hours)).to_rfc3339();
+
+        assert_eq!(urgency(None, now), 0.0);
+        assert_eq!(urgency(Some(&due_in(-3)), now), 1.0);
+        assert_eq!(urgency(Some(&due_in(24 * 14)), now), 0.0);
+
+        let soon = urgency(Some(&due_in(24)), now);
+        let later = urgency(Some(&due_in(24 * 5)), now);
+        assert_eq!(
+            quadrant(soon, importance("high", false)),
+            MatrixQuadrant::DoFirst
+        );
+        assert_eq!(
+            quadrant(later, importance("high", false)),
+            MatrixQuadrant::Schedule
+        );
+        assert_eq!(
+            quadrant(soon, importance("low", false)),
+            MatrixQuadrant::Delegate
+        );
+        assert_eq!(
+            quadrant(later, importance("medium", false)),
+            MatrixQuadrant::Eliminate
+        );
+        // A goal makes a medium task worth scheduling
+        assert_eq!(
+            quadrant(later, importance("medium", true)),
+            MatrixQuadrant::Schedule
+        );
+    }
+}
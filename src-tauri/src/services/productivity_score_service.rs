@@ -76,6 +76,14 @@ impl ProductivityScoreService {
         Ok(created)
     }
 
+    /// Composite score of `snapshot` without persisting it, e.g. for the
+    /// day still in progress.
+    pub fn composite_score(&self, snapshot: &AnalyticsSnapshotRecord) -> AppResult<f64> {
+        let dimension_scores = self.calculate_dimension_scores(snapshot)?;
+        let weight_breakdown = self.calculate_weights(&dimension_scores)?;
+        self.calculate_composite_score(&dimension_scores, &weight_breakdown)
+    }
+
     /// Get productivity score history for a date range
     pub fn get_score_history(
         &self,
@@ -308,6 +316,7 @@ pub struct DimensionWeights {
 mod tests {
     use super::*;
     use crate::models::analytics::AnalyticsSnapshotRecord;
+    use crate::models::productivity::ScoreStreak;
     use tempfile::tempdir;
 
     #[test]
@@ -329,6 +338,7 @@ mod tests {
             focus_consistency: 0.7,
             rest_balance: 0.6,
             capacity_risk: 0.2,
+            context_switches: 0,
             created_at: Utc::now().to_rfc3339(),
         };
 
@@ -388,6 +398,7 @@ mod tests {
             focus_consistency: 0.4,
             rest_balance: 0.3,
             capacity_risk: 0.1,
+            context_switches: 0,
             created_at: Utc::now().to_rfc3339(),
         };
 
@@ -409,6 +420,7 @@ mod tests {
             focus_consistency: 0.7,
             rest_balance: 0.6,
             capacity_risk: 0.2,
+            context_switches: 0,
             created_at: Utc::now().to_rfc3339(),
         };
 
@@ -421,4 +433,28 @@ mod tests {
         let pool = DbPool::new(db_path).expect("create db pool");
         (ProductivityScoreService::new(pool), dir)
     }
+
+    fn day(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn streak_counts_consecutive_days_at_target() {
+        let mut streak = ScoreStreak::default();
+        streak.record_day(day("2026-03-01"), 82.0, 75.0);
+        streak.record_day(day("2026-03-02"), 76.0, 75.0);
+        streak.record_day(day("2026-03-02"), 10.0, 75.0);
+        assert_eq!((streak.current, streak.longest), (2, 2));
+
+        streak.refresh(day("2026-03-03"), 40.0, 75.0);
+        assert!(streak.at_risk);
+
+        streak.record_day(day("2026-03-03"), 40.0, 75.0);
+        streak.record_day(day("2026-03-04"), 90.0, 75.0);
+        assert_eq!((streak.current, streak.longest), (1, 2));
+
+        streak.refresh(day("2026-03-08"), 90.0, 75.0);
+        assert_eq!(streak.current, 0);
+        assert!(!streak.at_risk);
+    }
 }
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+
+pub const OPERATION_PROGRESS_EVENT: &str = "operation://progress";
+
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressEvent {
+    pub operation_id: String,
+    pub stage: String,
+    pub percent: u8,
+    pub status: OperationStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Receives every progress update; commands forward them as Tauri events.
+pub type ProgressSink = Arc<dyn Fn(&ProgressEvent) + Send + Sync>;
+
+/// Handle passed into long-running service calls. Reporting a stage doubles
+/// as a cancellation checkpoint, so services stop at the next stage boundary
+/// once the user cancels.
+#[derive(Clone)]
+pub struct ProgressReporter {
+    operation_id: String,
+    cancelled: Arc<AtomicBool>,
+    sink: Option<ProgressSink>,
+    tracked: bool,
+}
+
+impl ProgressReporter {
+    /// Reporter for callers that neither listen to progress nor cancel.
+    pub fn silent() -> Self {
+        Self {
+            operation_id: Uuid::new_v4().to_string(),
+            cancelled: Arc::new(AtomicBool::new(false)),
+            sink: None,
+            tracked: false,
+        }
+    }
+
+    pub fn operation_id(&self) -> &str {
+        &self.operation_id
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    pub fn checkpoint(&self) -> AppResult<()> {
+        if self.is_cancelled() {
+            Err(AppError::cancelled())
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn report(&self, stage: &str, percent: u8) -> AppResult<()> {
+        self.checkpoint()?;
+        self.emit(stage, percent.min(100), OperationStatus::Running, None);
+        Ok(())
+    }
+
+    /// Runs `future` until it resolves or the operation is cancelled,
+    /// whichever comes first.
+    pub async fn cancellable<T>(&self, future: impl Future<Output = AppResult<T>>) -> AppResult<T> {
+        if !self.tracked {
+            return future.await;
+        }
+        self.checkpoint()?;
+
+        let cancelled = Arc::clone(&self.cancelled);
+        tokio::select! {
+            result = future => result,
+            _ = async move {
+                while !cancelled.load(Ordering::SeqCst) {
+                    tokio::time::sleep(CANCEL_POLL_INTERVAL).await;
+                }
+            } => Err(AppError::cancelled()),
+        }
+    }
+
+    fn emit(&self, stage: &str, percent: u8, status: OperationStatus, message: Option<String>) {
+        if let Some(sink) = &self.sink {
+            sink(&ProgressEvent {
+                operation_id: self.operation_id.clone(),
+                stage: stage.to_string(),
+                percent,
+                status,
+                message,
+            });
+        }
+    }
+}
+
+/// Operations currently running, keyed by operation id, so a separate
+/// command can cancel them.
+#[derive(Default)]
+pub struct OperationRegistry {
+    operations: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl OperationRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers an operation. Callers may supply their own id so they can
+    /// subscribe to events and cancel before the command returns.
+    pub fn start(
+        &self,
+        operation_id: Option<String>,
+        sink: Option<ProgressSink>,
+    ) -> AppResult<ProgressReporter> {
+        let operation_id = operation_id
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        let mut operations = self.lock();
+        if operations.contains_key(&operation_id) {
+            return Err(AppError::conflict("同一操作 ID 正在执行中"));
+        }
+        operations.insert(operation_id.clone(), Arc::clone(&cancelled));
+        debug!(target: "app::progress", %operation_id, "operation started");
+
+        Ok(ProgressReporter {
+            operation_id,
+            cancelled,
+            sink,
+            tracked: true,
+        })
+    }
+
+    /// Requests cancellation; returns false when the operation is unknown or
+    /// already finished.
+    pub fn cancel(&self, operation_id: &str) -> bool {
+        match self.lock().get(operation_id) {
+            Some(flag) => {
+                flag.store(true, Ordering::SeqCst);
+                debug!(target: "app::progress", %operation_id, "operation cancellation requested");
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Emits the final event for `reporter` and forgets the operation.
+    pub fn finish<T>(&self, reporter: &ProgressReporter, result: &AppResult<T>) {
+        self.lock().remove(&reporter.operation_id);
+        match result {
+            Ok(_) => reporter.emit("done", 100, OperationStatus::Completed, None),
+            Err(AppError::Cancelled) => {
+                reporter.emit("cancelled", 100, OperationStatus::Cancelled, None)
+            }
+            Err(err) => reporter.emit(
+                "failed",
+                100,
+                OperationStatus::Failed,
+                Some(err.to_string()),
+            ),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Arc<AtomicBool>>> {
+        self.operations
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Integer percentage of `done` out of `total`, scaled into `[from, to]`.
+pub fn scaled_percent(done: usize, total: usize, from: u8, to: u8) -> u8 {
+    if total == 0 {
+        return to;
+    }
+    let span = to.saturating_sub(from) as usize;
+    from + (span * done.min(total) / total) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_progress_and_honours_cancellation() {
+        let events: Arc<Mutex<Vec<ProgressEvent>>> = Arc::new(Mutex::new(Vec::new()));
+        let captured = Arc::clone(&events);
+        let sink: ProgressSink =
+            Arc::new(move |event| captured.lock().unwrap().push(event.clone()));
+
+        let registry = OperationRegistry::new();
+        let reporter = registry
+            .start(Some("op-1".to_string()), Some(sink))
+            .unwrap();
+        assert!(registry.start(Some("op-1".to_string()), None).is_err());
+
+        reporter.report("loading", 10).unwrap();
+        assert!(registry.cancel("op-1"));
+        let result = reporter.report("saving", 80);
+        assert!(matches!(result, Err(AppError::Cancelled)));
+        registry.finish(&reporter, &result);
+
+        assert!(!registry.cancel("op-1"));
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].stage, "loading");
+        assert_eq!(events[0].status, OperationStatus::Running);
+        assert_eq!(events[1].status, OperationStatus::Cancelled);
+        assert_eq!(scaled_percent(1, 4, 20, 60), 30);
+        assert_eq!(scaled_percent(0, 0, 20, 60), 60);
+    }
+}
@@ -0,0 +1,333 @@
+use std::collections::HashSet;
+
+use chrono::Utc;
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::db::repositories::project_repository::{ProjectRepository, ProjectRow};
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::ai::{ParsedTaskPayload, TaskParseContext, TaskParseProjectOption};
+use crate::models::project::{
+    ProjectCreateInput, ProjectProgress, ProjectRecord, ProjectStatus, ProjectUpdateInput,
+};
+use crate::services::reminder_service::sync_block_reminders;
+use crate::utils::appearance::{normalize_color, normalize_icon};
+
+const MAX_NAME_CHARS: usize = 64;
+const MAX_DEFAULT_TAGS: usize = 10;
+const MAX_TAG_CHARS: usize = 32;
+
+pub struct ProjectService {
+    db: DbPool,
+}
+
+impl ProjectService {
+    pub fn new(db: DbPool) -> Self {
+        Self { db }
+    }
+
+    pub fn list(&self, include_archived: bool) -> AppResult<Vec<ProjectRecord>> {
+        self.db.with_connection(|conn| {
+            ProjectRepository::list(conn, include_archived)?
+                .into_iter()
+                .map(ProjectRow::into_record)
+                .collect()
+        })
+    }
+
+    pub fn get(&self, id: &str) -> AppResult<ProjectRecord> {
+        self.db
+            .with_connection(|conn| ProjectRepository::find(conn, id))?
+            .ok_or_else(AppError::not_found)?
+            .into_record()
+    }
+
+    pub fn create(&self, input: ProjectCreateInput) -> AppResult<ProjectRecord> {
+        let now = Utc::now().to_rfc3339();
+        let record = ProjectRecord {
+            id: Uuid::new_v4().to_string(),
+            name: normalize_name(&input.name)?,
+            status: ProjectStatus::Active,
+            color: normalize_color(input.color)?,
+            icon: normalize_icon(input.icon)?,
+            default_tags: normalize_tags(input.default_tags.unwrap_or_default())?,
+            archived_at: None,
+            created_at: now.clone(),
+            updated_at: now,
+        };
+
+        let row = ProjectRow::from_record(&record)?;
+        self.db
+            .with_connection(|conn| ProjectRepository::upsert(conn, &row))?;
+        info!(target: "app::projects", project_id = %record.id, "project created");
+        Ok(record)
+    }
+
+    /// Updates the project. Moving it to `archived` goes through
+    /// [`ProjectService::archive`] so its tasks are archived too.
+    pub fn update(&self, id: &str, input: ProjectUpdateInput) -> AppResult<ProjectRecord> {
+        let mut record = self.get(id)?;
+        if let Some(name) = input.name {
+            record.name = normalize_name(&name)?;
+        }
+        if let Some(color) = input.color {
+            record.color = normalize_color(color)?;
+        }
+        if let Some(icon) = input.icon {
+            record.icon = normalize_icon(icon)?;
+        }
+        if let Some(tags) = input.default_tags {
+            record.default_tags = normalize_tags(tags)?;
+        }
+
+        let archive = match input.status {
+            Some(ProjectStatus::Archived) => record.status != ProjectStatus::Archived,
+            Some(status) => {
+                record.status = status;
+                record.archived_at = None;
+                false
+            }
+            None => false,
+        };
+        record.updated_at = Utc::now().to_rfc3339();
+
+        let row = ProjectRow::from_record(&record)?;
+        self.db
+            .with_connection(|conn| ProjectRepository::upsert(conn, &row))?;
+        if archive {
+            return self.archive(id);
+        }
+        info!(target: "app::projects", project_id = %id, "project updated");
+        Ok(record)
+    }
+
+    /// Archives the project and every task in it that is not done yet, in
+    /// one transaction.
+    pub fn archive(&self, id: &str) -> AppResult<ProjectRecord> {
+        let mut record = self.get(id)?;
+        let now = Utc::now().to_rfc3339();
+        record.status = ProjectStatus::Archived;
+        record.archived_at = Some(now.clone());
+        record.updated_at = now.clone();
+
+        let mut conn = self.db.get_connection()?;
+        let tx = conn.transaction()?;
+        ProjectRepository::upsert(&tx, &ProjectRow::from_record(&record)?)?;
+        let archived_tasks = ProjectRepository::archive_tasks(&tx, id, &now)?;
+        sync_block_reminders(&tx, Utc::now())?;
+        tx.commit()?;
+
+        info!(target: "app::projects", project_id = %id, archived_tasks, "project archived");
+        Ok(record)
+    }
+
+    pub fn progress(&self, id: &str) -> AppResult<ProjectProgress> {
+        let project = self.get(id)?;
+        let now = Utc::now().to_rfc3339();
+        let (counts, goal_count) = self.db.with_connection(|conn| {
+            Ok((
+                ProjectRepository::task_counts(conn, id, &now)?,
+                ProjectRepository::goal_count(conn, id)?,
+            ))
+        })?;
+
+        let progress_percentage = if counts.total > 0 {
+            (counts.completed as f64 / counts.total as f64 * 1000.0).round() / 10.0
+        } else {
+            0.0
+        };
+        debug!(target: "app::projects", project_id = %id, total = counts.total, "project progress computed");
+
+        Ok(ProjectProgress {
+            project,
+            total_tasks: counts.total,
+            completed_tasks: counts.completed,
+            in_progress_tasks: counts.in_progress,
+            overdue_tasks: counts.overdue,
+            goal_count,
+            progress_percentage,
+        })
+    }
+
+    /// Open projects offered to the task parser.
+    pub fn parse_options(&self) -> AppResult<Vec<TaskParseProjectOption>> {
+        Ok(self
+            .list(false)?
+            .into_iter()
+            .filter(|project| project.status != ProjectStatus::Completed)
+            .map(|project| TaskParseProjectOption {
+                id: project.id,
+                name: project.name,
+                default_tags: project.default_tags,
+            })
+            .collect())
+    }
+}
+
+/// Settles the project of a parsed task: a project chosen in the context
+/// wins; otherwise the model's pick is kept only if it names an offered
+/// project, by id or by name.
+pub fn resolve_parsed_project(payload: &mut ParsedTaskPayload, context: Option<&TaskParseContext>) {
+    if let Some(selected) = context
+        .and_then(|ctx| ctx.project_id.as_deref())
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+    {
+        payload.project_id = Some(selected.to_string());
+        return;
+    }
+
+    let options = context
+        .and_then(|ctx| ctx.projects.as_deref())
+        .unwrap_or_default();
+    payload.project_id = payload.project_id.take().and_then(|candidate| {
+        let candidate = candidate.trim();
+        options
+            .iter()
+            .find(|option| option.id == candidate)
+            .or_else(|| {
+                options
+                    .iter()
+                    .find(|option| option.name.eq_ignore_ascii_case(candidate))
+            })
+            .map(|option| option.id.clone())
+    });
+}
+
+fn normalize_name(name: &str) -> AppResult<String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err(AppError::validation("项目名称不能为空"));
+    }
+    if trimmed.chars().count() > MAX_NAME_CHARS {
+        return Err(AppError::validation(format!(
+            "项目名称不能超过 {} 个字符",
+            MAX_NAME_CHARS
+        )));
+    }
+    Ok(trimmed.to_string())
+}
+
+fn normalize_tags(tags: Vec<String>) -> AppResult<Vec<String>> {
+    let mut seen = HashSet::new();
+    let mut result = Vec::new();
+    for tag in tags {
+        let trimmed = tag.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if trimmed.chars().count() > MAX_TAG_CHARS {
+            return Err(AppError::validation("单个标签长度需小于 32 字符"));
+        }
+        if seen.insert(trimmed.to_lowercase()) {
+            result.push(trimmed.to_string());
+        }
+    }
+    if result.len() > MAX_DEFAULT_TAGS {
+        return Err(AppError::validation(format!(
+            "项目默认标签最多 {} 个",
+            MAX_DEFAULT_TAGS
+        )));
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::task::TaskCreateInput;
+    use crate::services::task_service::TaskService;
+
+    #[test]
+    fn archiving_a_project_archives_its_open_tasks() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = DbPool::new(dir.path().join("projects.sqlite")).unwrap();
+        let projects = ProjectService::new(db.clone());
+        let tasks = TaskService::new(db);
+
+        let project = projects
+            .create(ProjectCreateInput {
+                name: " 发布 2.0 ".into(),
+                color: Some("#3B82F6".into()),
+                icon: Some("rocket".into()),
+                default_tags: Some(vec!["release".into(), "Release".into()]),
+            })
+            .unwrap();
+        assert_eq!(project.name, "发布 2.0");
+        assert_eq!(project.color.as_deref(), Some("#3b82f6"));
+        assert_eq!(project.icon.as_deref(), Some("rocket"));
+        assert_eq!(project.default_tags, vec!["release"]);
+
+        let open = tasks
+            .create_task(TaskCreateInput {
+                title: "写发布说明".into(),
+                tags: Some(vec!["docs".into()]),
+                project_id: Some(project.id.clone()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(open.tags, vec!["docs", "release"]);
+        tasks
+            .create_task(TaskCreateInput {
+                title: "冻结分支".into(),
+                status: Some("done".into()),
+                project_id: Some(project.id.clone()),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let progress = projects.progress(&project.id).unwrap();
+        assert_eq!(progress.total_tasks, 2);
+        assert_eq!(progress.progress_percentage, 50.0);
+
+        projects.archive(&project.id).unwrap();
+        assert_eq!(tasks.get_task(&open.id).unwrap().status, "archived");
+        assert!(projects.list(false).unwrap().is_empty());
+        assert!(tasks
+            .create_task(TaskCreateInput {
+                title: "新任务".into(),
+                project_id: Some(project.id.clone()),
+                ..Default::default()
+            })
+            .is_err());
+        assert!(projects
+            .create(ProjectCreateInput {
+                name: "坏颜色".into(),
+                color: Some("blue".into()),
+                icon: None,
+                default_tags: None,
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn parsed_project_must_be_offered_or_selected() {
+        let context = TaskParseContext {
+            projects: Some(vec![TaskParseProjectOption {
+                id: "p-1".into(),
+                name: "Website".into(),
+                default_tags: Vec::new(),
+            }]),
+            ..Default::default()
+        };
+        let resolve = |candidate: Option<&str>, context: &TaskParseContext| {
+            let mut payload = ParsedTaskPayload {
+                project_id: candidate.map(str::to_string),
+                ..Default::default()
+            };
+            resolve_parsed_project(&mut payload, Some(context));
+            payload.project_id
+        };
+
+        assert_eq!(resolve(Some("p-1"), &context).as_deref(), Some("p-1"));
+        assert_eq!(resolve(Some("website"), &context).as_deref(), Some("p-1"));
+        assert_eq!(resolve(Some("p-404"), &context), None);
+
+        let selected = TaskParseContext {
+            project_id: Some("p-2".into()),
+            ..context.clone()
+        };
+        assert_eq!(resolve(Some("p-1"), &selected).as_deref(), Some("p-2"));
+    }
+}
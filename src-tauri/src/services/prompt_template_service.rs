@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Local, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use rusqlite::Connection;
+use tracing::{info, warn};
+
+use crate::db::repositories::prompt_override_repository::{
+    PromptOverrideRepository, PromptOverrideRow,
+};
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::prompt_template::{
+    PromptOverrideRecord, PromptTemplateKey, PromptTemplateUpdateInput, PromptTemplateView,
+};
+use crate::services::prompt_templates::default_system_prompt;
+use crate::services::settings_service::notify_settings_changed;
+
+const MAX_TEMPLATE_CHARS: usize = 20_000;
+
+/// Variables every template may reference as `{{name}}`.
+pub const TEMPLATE_VARIABLES: [&str; 3] = ["current_date", "current_time", "current_datetime"];
+
+static PLACEHOLDER_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{\{\s*([^{}]*?)\s*\}\}").expect("valid placeholder pattern"));
+
+/// Stores per-user overrides of the AI system prompts. Overrides are validated
+/// on save and again on load, so a broken one falls back to the default.
+pub struct PromptTemplateService {
+    db: DbPool,
+}
+
+impl PromptTemplateService {
+    pub fn new(db: DbPool) -> Self {
+        Self { db }
+    }
+
+    pub fn list(&self) -> AppResult<Vec<PromptTemplateView>> {
+        let overrides = self
+            .db
+            .with_connection(PromptOverrideRepository::list_all)?;
+
+        Ok(PromptTemplateKey::ALL
+            .iter()
+            .map(|key| {
+                let record = overrides.iter().find(|record| record.key == *key);
+                build_view(*key, record)
+            })
+            .collect())
+    }
+
+    pub fn update(&self, input: PromptTemplateUpdateInput) -> AppResult<PromptTemplateView> {
+        let template = input.template.trim().to_string();
+        validate_template(input.key, &template)
+            .map_err(|reason| AppError::validation(format!("提示词模板无效: {reason}")))?;
+
+        let record = PromptOverrideRecord {
+            key: input.key,
+            template,
+            updated_at: Utc::now().to_rfc3339(),
+        };
+        let row = PromptOverrideRow::from_record(&record);
+        self.db
+            .with_connection(|conn| PromptOverrideRepository::upsert(conn, &row))?;
+        notify_settings_changed();
+
+        info!(target: "app::ai::prompts", prompt_key = %record.key, "prompt override saved");
+        Ok(build_view(record.key, Some(&record)))
+    }
+
+    /// Drops the override so the built-in prompt is used again.
+    pub fn reset(&self, key: PromptTemplateKey) -> AppResult<PromptTemplateView> {
+        let removed = self
+            .db
+            .with_connection(|conn| PromptOverrideRepository::delete(conn, key))?;
+        if removed {
+            notify_settings_changed();
+            info!(target: "app::ai::prompts", prompt_key = %key, "prompt override reset");
+        }
+
+        Ok(build_view(key, None))
+    }
+}
+
+/// Effective (unrendered) template per key, substituting the default for
+/// missing or invalid overrides.
+pub fn load_effective_templates(
+    conn: &Connection,
+) -> AppResult<HashMap<PromptTemplateKey, String>> {
+    let overrides = PromptOverrideRepository::list_all(conn)?;
+
+    Ok(PromptTemplateKey::ALL
+        .iter()
+        .map(|key| {
+            let stored = overrides
+                .iter()
+                .find(|record| record.key == *key)
+                .map(|record| record.template.as_str());
+            (*key, resolve_template(*key, stored).to_string())
+        })
+        .collect())
+}
+
+/// Returns the override when it validates, otherwise the default.
+pub fn resolve_template(key: PromptTemplateKey, stored: Option<&str>) -> &str {
+    match stored {
+        Some(template) => match validate_template(key, template) {
+            Ok(()) => template,
+            Err(reason) => {
+                warn!(
+                    target: "app::ai::prompts",
+                    prompt_key = %key,
+                    reason = %reason,
+                    "ignoring invalid prompt override, using default"
+                );
+                default_system_prompt(key)
+            }
+        },
+        None => default_system_prompt(key),
+    }
+}
+
+pub fn validate_template(key: PromptTemplateKey, template: &str) -> Result<(), String> {
+    let length = template.trim().chars().count();
+    if length == 0 {
+        return Err("模板不能为空".to_string());
+    }
+    if length > MAX_TEMPLATE_CHARS {
+        return Err(format!("模板长度不能超过 {MAX_TEMPLATE_CHARS} 个字符"));
+    }
+
+    for captures in PLACEHOLDER_PATTERN.captures_iter(template) {
+        let name = captures.get(1).map(|m| m.as_str()).unwrap_or_default();
+        if !TEMPLATE_VARIABLES.contains(&name) {
+            return Err(format!("未知的模板变量 '{{{{{name}}}}}'"));
+        }
+    }
+    let remainder = PLACEHOLDER_PATTERN.replace_all(template, "");
+    if remainder.contains("{{") || remainder.contains("}}") {
+        return Err("模板变量的花括号不匹配".to_string());
+    }
+
+    // Requests for these keys use `response_format: json_object`, which the
+    // API rejects unless the prompt itself asks for JSON.
+    if key.expects_json() && !template.to_ascii_lowercase().contains("json") {
+        return Err("该模板必须要求模型以 JSON 格式输出".to_string());
+    }
+
+    Ok(())
+}
+
+/// Fills the template variables. Call only on validated templates.
+pub fn render_template(template: &str, now: DateTime<Local>) -> String {
+    PLACEHOLDER_PATTERN
+        .replace_all(template, |captures: &regex::Captures<'_>| {
+            match captures.get(1).map(|m| m.as_str()) {
+                Some("current_date") => now.format("%Y-%m-%d").to_string(),
+                Some("current_time") => now.format("%H:%M:%S").to_string(),
+                Some("current_datetime") => now.to_rfc3339(),
+                _ => String::new(),
+            }
+        })
+        .into_owned()
+}
+
+fn build_view(key: PromptTemplateKey, record: Option<&PromptOverrideRecord>) -> PromptTemplateView {
+    let validation_error = record.and_then(|record| validate_template(key, &record.template).err());
+
+    PromptTemplateView {
+        key,
+        default_template: default_system_prompt(key).to_string(),
+        override_template: record.map(|record| record.template.clone()),
+        override_active: record.is_some() && validation_error.is_none(),
+        validation_error,
+        variables: TEMPLATE_VARIABLES
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+        updated_at: record.map(|record| record.updated_at.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::tempdir;
+
+    #[test]
+    fn defaults_pass_validation() {
+        for key in PromptTemplateKey::ALL {
+            assert_eq!(validate_template(key, default_system_prompt(key)), Ok(()));
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_variables_and_missing_json_instruction() {
+        let key = PromptTemplateKey::Schedule;
+
+        assert!(validate_template(key, "Return JSON for {{ current_date }}").is_ok());
+        assert!(validate_template(key, "Return JSON for {{user_name}}").is_err());
+        assert!(validate_template(key, "Return JSON for {{current_date}").is_err());
+        assert!(validate_template(key, "Plan my day").is_err());
+        assert!(validate_template(PromptTemplateKey::Agent, "Be brief.").is_ok());
+
+        assert_eq!(
+            resolve_template(key, Some("Plan my day")),
+            default_system_prompt(key)
+        );
+    }
+
+    #[test]
+    fn renders_date_variables() {
+        let now = Local.with_ymd_and_hms(2025, 5, 1, 9, 30, 0).unwrap();
+
+        let rendered = render_template("Today is {{current_date}} at {{ current_time }}.", now);
+
+        assert_eq!(rendered, "Today is 2025-05-01 at 09:30:00.");
+    }
+
+    #[test]
+    fn update_list_and_reset_round_trip() {
+        let dir = tempdir().expect("temp dir");
+        let pool = DbPool::new(dir.path().join("prompts.sqlite")).unwrap();
+        let service = PromptTemplateService::new(pool.clone());
+
+        assert!(service
+            .update(PromptTemplateUpdateInput {
+                key: PromptTemplateKey::Recommendations,
+                template: "Give me tips".into(),
+            })
+            .is_err());
+
+        let view = service
+            .update(PromptTemplateUpdateInput {
+                key: PromptTemplateKey::Recommendations,
+                template: "  Reply in JSON with at most three recommendations.  ".into(),
+            })
+            .unwrap();
+        assert!(view.override_active);
+
+        let effective = pool.with_connection(load_effective_templates).unwrap();
+        assert_eq!(
+            effective[&PromptTemplateKey::Recommendations],
+            "Reply in JSON with at most three recommendations."
+        );
+        assert_eq!(
+            effective[&PromptTemplateKey::ParseTask],
+            default_system_prompt(PromptTemplateKey::ParseTask)
+        );
+
+        let view = service.reset(PromptTemplateKey::Recommendations).unwrap();
+        assert!(!view.override_active);
+        assert!(service
+            .list()
+            .unwrap()
+            .iter()
+            .all(|view| view.override_template.is_none()));
+    }
+}
@@ -1,6 +1,7 @@
 use serde_json::{json, Value as JsonValue};
 
 use crate::models::ai::TaskParseRequest;
+use crate::models::prompt_template::PromptTemplateKey;
 
 /// System prompt guiding DeepSeek when parsing natural language tasks.
 pub fn task_parsing_system_prompt() -> &'static str {
@@ -24,7 +25,8 @@ valid UTF-8 JSON. Do not wrap the response in markdown code blocks. The schema i
     "isRecurring": boolean|null,
     "recurrence": object|null,
     "taskType": string|null,
-    "externalLinks": string[]|null
+    "externalLinks": string[]|null,
+    "projectId": string|null
   },
   "missingFields": string[],
   "reasoning": {
@@ -41,6 +43,11 @@ valid UTF-8 JSON. Do not wrap the response in markdown code blocks. The schema i
   }
 }
 Use ISO-8601 timestamps in UTC. Provide clear, concise reasoning entries.
+The request names the input "language" (zh-CN, en or ja) and carries "examples" in that language.
+Resolve relative dates such as "下周二", "next Tuesday" or "来週の火曜" against context.referenceDate
+the way a native speaker would, and keep the title in the input language.
+When context.projects lists projects, set payload.projectId to the id of the project the task
+clearly belongs to, or null when unsure. Never invent project ids.
 
 Example response:
 {
@@ -108,14 +115,185 @@ pub fn schedule_planning_system_prompt() -> &'static str {
      "startAt": string,
      "endAt": string,
      "confidence": number|null,
-     "notes": string|null
+     "notes": string|null,
+     "prepNote": string|null
   }],
   "telemetry": object|null
 }
 Ensure times are ISO-8601 UTC and sorted by startAt."
+"prepNote" is one short sentence on what to have ready when the block starts, or null.
+On weekdays listed in preferences.weekdayOverrides (weekday 0 is Monday), use that day's focus
+window and buffer instead of the defaults; a day with no focus window gets no deep work blocks.
     "#
 }
 
+/// System prompt for extracting action items from meeting notes.
+pub fn action_items_system_prompt() -> &'static str {
+    r#"You are Cognical's meeting assistant. Read one chunk of meeting notes and extract every
+concrete action item. Respond with JSON following:
+{
+  "items": [{
+     "title": string,
+     "owner": string|null,
+     "dueHint": string|null,
+     "dueAt": string|null,
+     "priority": string|null,
+     "sourceExcerpt": string|null,
+     "confidence": number|null
+  }],
+  "telemetry": object|null
+}
+Titles start with a verb and keep the language of the notes. "owner" is the person responsible as
+written in the notes. "dueHint" quotes the deadline wording; "dueAt" is ISO-8601 UTC only when the
+date can be resolved from the reference date. "priority" is one of low, medium, high, urgent.
+Skip decisions, status updates and discussion that do not require follow-up. Return an empty list
+when the chunk has no action items."
+    "#
+}
+
+/// System prompt template for the tool-calling agent. Placeholders are filled
+/// by `prompt_template_service::render_template`.
+pub fn agent_system_prompt_template() -> &'static str {
+    r#"You are CogniCal, an intelligent AI assistant specialized in unified time management and productivity.
+
+## Current Information
+- **Current Date**: {{current_date}}
+- **Current Time**: {{current_time}}
+- **Current DateTime**: {{current_datetime}}
+
+## Your Capabilities
+You have access to powerful tools for unified time management that combines tasks and calendar events as one cohesive system. You should:
+
+1. **Unified Time Management**: Tasks and calendar events are now unified - they're all "time-based items"
+2. **Be Proactive**: When users ask about schedule, calendar, tasks, or time management, immediately use the appropriate tools
+3. **Understand Context and User Intent**: 
+   - Pay attention to conversation flow and user intent
+   - If user initially asks to "create/安排/schedule" something and then provides details, they want to CREATE not SEARCH
+   - "recent", "latest", "upcoming" means future dates from today
+   - "past", "previous", "last week" means dates before today
+   - "today" means the current date: {{current_date}}
+
+## CRITICAL: Tool Selection Rules
+**For Creating New Items:**
+- User says "创建/安排/schedule/建个/做个 + 时间 + 事情" → use `create_time_block`
+- User provides complete info after you ask for details → CREATE, not search
+- User says "快速安排 X 在 Y 时间" → use `quick_schedule`
+- If user gives you title + time, they want to CREATE
+
+**For Searching/Viewing Existing Items:**
+- User says "查找/搜索/找/看看 + 已有/现有的 + 时间安排" → use `search_time_items`
+- User says "查看/显示/列出 + 时间安排" → use `list_time_items`
+- User says "检查/确认 + 是否已安排" → use `search_time_items`
+
+**For Updating Items:**
+- User says "修改/更新/调整/重安排 + 已有的 + 时间" → use `update_time_item`
+
+## Tool Usage Rules
+- ALWAYS call tools when users ask for data (don't ask "what date is it")
+- Call tools FIRST, then format the results nicely for the user
+- If multiple tools are needed, call them in parallel when possible
+- Don't ask users for information you can infer from context
+- **Remember**: All time-based management is now unified - tasks and calendar events are the same thing!
+- **CRITICAL**: If user previously asked to create something and now provides the missing details, use CREATE tools!
+
+## Response Style
+- Be concise and actionable
+- Show results in clear, formatted lists
+- Use emojis appropriately for better UX (📅 for schedules, ⏰ for deadlines, 🕒 for time blocks)
+- Remember user preferences from conversation history"#
+}
+
+/// Built-in system prompt for each overridable template.
+pub fn default_system_prompt(key: PromptTemplateKey) -> &'static str {
+    match key {
+        PromptTemplateKey::ParseTask => task_parsing_system_prompt(),
+        PromptTemplateKey::Recommendations => recommendations_system_prompt(),
+        PromptTemplateKey::Schedule => schedule_planning_system_prompt(),
+        PromptTemplateKey::ActionItems => action_items_system_prompt(),
+        PromptTemplateKey::DecomposeTask => task_decomposition_system_prompt(),
+        PromptTemplateKey::SummarizeHistory => history_summary_system_prompt(),
+        PromptTemplateKey::CritiquePlan => plan_critique_system_prompt(),
+        PromptTemplateKey::Agent => agent_system_prompt_template(),
+    }
+}
+
+/// Follow-up message asking the model to finish a truncated or malformed reply.
+pub fn json_completion_prompt() -> &'static str {
+    "Your previous response was truncated or was not valid JSON. Return the complete JSON object \
+only, following the schema from the system prompt. Do not add explanations or markdown."
+}
+
+/// Follow-up message asking the model to fix a reply that failed schema validation.
+pub fn schema_correction_prompt(errors: &[String]) -> String {
+    let mut prompt = String::from(
+        "Your previous response did not match the required JSON schema. Validation errors:\n",
+    );
+    for error in errors {
+        prompt.push_str("- ");
+        prompt.push_str(error);
+        prompt.push('\n');
+    }
+    prompt.push_str(
+        "Return the complete corrected JSON object only, following the schema from the system prompt. Do not add explanations or markdown.",
+    );
+    prompt
+}
+
+/// System prompt for splitting a large task into ordered subtasks.
+pub fn task_decomposition_system_prompt() -> &'static str {
+    r#"You are Cognical's planning assistant. Break the given task into concrete, ordered subtasks
+that can each be finished in one sitting. Respond with JSON following:
+{
+  "subtasks": [{
+     "title": string,
+     "description": string|null,
+     "estimatedMinutes": integer|null,
+     "order": integer,
+     "dependsOn": [integer]
+  }],
+  "rationale": string|null,
+  "telemetry": object|null
+}
+Titles start with a verb and keep the language of the task. "order" starts at 1 and follows the
+order the work should be done in; "dependsOn" lists the orders of subtasks that must be finished
+first. Keep estimates between 15 and 240 minutes and, when the task has an estimate, make them add
+up to roughly that estimate. When "similarPastTasks" lists completed look-alikes, size the
+subtasks by their actualMinutes rather than the original estimates. Return no more subtasks than
+"maxSubtasks".
+    "#
+}
+
+/// System prompt for polishing a template-based activity summary.
+pub fn history_summary_system_prompt() -> &'static str {
+    r#"You are Cognical's reporting assistant. Rewrite the given activity summary of a date range into
+a short, readable narrative suitable for a standup, timesheet or performance review. Respond with
+JSON following:
+{
+  "narrative": string,
+  "telemetry": object|null
+}
+Use only the facts in the payload: never invent tasks, numbers or events. Keep the language of the
+"draft", lead with the most important outcomes, group related tasks, and stay under 200 words."#
+}
+
+/// System prompt for reviewing an optimizer plan against the user's preferences.
+pub fn plan_critique_system_prompt() -> &'static str {
+    r#"You are Cognical's planning reviewer. Check the proposed plan against the user's preferences,
+constraints and stated facts, and report only real problems. Respond with JSON following:
+{
+  "issues": [{
+     "kind": "too_dense"|"ignores_constraint"|"other",
+     "message": string
+  }],
+  "telemetry": object|null
+}
+Use "too_dense" for days packed with back-to-back blocks, no breaks or more focus than the daily
+limit, and "ignores_constraint" when a block contradicts a focus window, avoidance window, sleep
+schedule or user fact; quote the constraint in the message. Write each message as one short
+sentence in Simplified Chinese. Return at most "maxIssues" issues, and an empty list when the plan
+looks fine."#
+}
+
 /// Build the user payload for task parsing requests.
 pub fn build_task_parse_payload(request: &TaskParseRequest) -> JsonValue {
     let mut payload = serde_json::Map::new();
@@ -128,10 +306,15 @@ pub fn build_task_parse_payload(request: &TaskParseRequest) -> JsonValue {
         }
     }
 
+    let language = resolve_parse_language(request);
+    payload.insert("language".to_string(), json!(language.as_str()));
+    payload.insert("examples".to_string(), json!(task_parse_examples(language)));
+
     payload.insert(
         "expectations".to_string(),
         json!({
-            "languages": ["zh-CN", "en"],
+            "languages": ParseLanguage::ALL.iter().map(|lang| lang.as_str()).collect::<Vec<_>>(),
+            "keepInputLanguage": true,
             "mustReturnAllFields": true,
             "timezoneFallback": "UTC",
             "minConfidence": 0.5
@@ -141,6 +324,133 @@ pub fn build_task_parse_payload(request: &TaskParseRequest) -> JsonValue {
     JsonValue::Object(payload)
 }
 
+/// Languages with dedicated parsing examples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseLanguage {
+    ZhCn,
+    En,
+    Ja,
+}
+
+impl ParseLanguage {
+    pub const ALL: [ParseLanguage; 3] = [ParseLanguage::ZhCn, ParseLanguage::En, ParseLanguage::Ja];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ParseLanguage::ZhCn => "zh-CN",
+            ParseLanguage::En => "en",
+            ParseLanguage::Ja => "ja",
+        }
+    }
+
+    /// Maps a BCP 47 locale such as `ja-JP` or `zh-Hans`; unknown locales
+    /// return `None` so detection can take over.
+    pub fn from_locale(locale: &str) -> Option<Self> {
+        let primary = locale.trim().split(['-', '_']).next()?.to_ascii_lowercase();
+        match primary.as_str() {
+            "zh" => Some(ParseLanguage::ZhCn),
+            "en" => Some(ParseLanguage::En),
+            "ja" => Some(ParseLanguage::Ja),
+            _ => None,
+        }
+    }
+
+    /// Guesses the language from the script: any kana means Japanese, other
+    /// CJK ideographs mean Chinese, everything else is treated as English.
+    pub fn detect(text: &str) -> Self {
+        let mut has_han = false;
+        for ch in text.chars() {
+            match ch {
+                '\u{3040}'..='\u{30FF}' | '\u{31F0}'..='\u{31FF}' | '\u{FF66}'..='\u{FF9F}' => {
+                    return ParseLanguage::Ja;
+                }
+                '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}' => has_han = true,
+                _ => {}
+            }
+        }
+        if has_han {
+            ParseLanguage::ZhCn
+        } else {
+            ParseLanguage::En
+        }
+    }
+}
+
+/// Language used for the parse prompt: the context locale when the user
+/// picked one, otherwise detected from the input.
+pub fn resolve_parse_language(request: &TaskParseRequest) -> ParseLanguage {
+    request
+        .context
+        .as_ref()
+        .and_then(|context| context.locale.as_deref())
+        .and_then(ParseLanguage::from_locale)
+        .unwrap_or_else(|| ParseLanguage::detect(&request.input))
+}
+
+/// Few-shot examples for relative dates and durations in `language`. The
+/// reference date in every example is Wednesday 2025-10-15, UTC.
+fn task_parse_examples(language: ParseLanguage) -> JsonValue {
+    match language {
+        ParseLanguage::ZhCn => json!([
+            {
+                "input": "下周二下午三点前交季度报告，大概两小时",
+                "referenceDate": "2025-10-15T02:00:00Z",
+                "payload": {
+                    "title": "交季度报告",
+                    "dueAt": "2025-10-21T15:00:00Z",
+                    "estimatedMinutes": 120
+                }
+            },
+            {
+                "input": "明早和设计团队过一遍原型",
+                "referenceDate": "2025-10-15T02:00:00Z",
+                "payload": {
+                    "title": "和设计团队过一遍原型",
+                    "startAt": "2025-10-16T09:00:00Z"
+                }
+            }
+        ]),
+        ParseLanguage::En => json!([
+            {
+                "input": "Submit the expense report by next Tuesday 3pm, about 2 hours",
+                "referenceDate": "2025-10-15T02:00:00Z",
+                "payload": {
+                    "title": "Submit the expense report",
+                    "dueAt": "2025-10-21T15:00:00Z",
+                    "estimatedMinutes": 120
+                }
+            },
+            {
+                "input": "Call the dentist tomorrow morning",
+                "referenceDate": "2025-10-15T02:00:00Z",
+                "payload": {
+                    "title": "Call the dentist",
+                    "startAt": "2025-10-16T09:00:00Z"
+                }
+            }
+        ]),
+        ParseLanguage::Ja => json!([
+            {
+                "input": "来週の火曜15時までに見積書を送る、2時間くらい",
+                "referenceDate": "2025-10-15T02:00:00Z",
+                "payload": {
+                    "title": "見積書を送る",
+                    "dueAt": "2025-10-21T15:00:00Z",
+                    "estimatedMinutes": 120
+                }
+            },
+            {
+                "input": "明後日の朝に部長と1on1",
+                "referenceDate": "2025-10-15T02:00:00Z",
+                "payload": {
+                    "title": "部長と1on1",
+                    "startAt": "2025-10-17T09:00:00Z"
+                }
+            }
+        ]),
+    }
+}
+
 /// Build the user payload for recommendation requests.
 pub fn build_recommendations_payload(input: &JsonValue) -> JsonValue {
     json!({
@@ -165,3 +475,53 @@ pub fn build_schedule_payload(input: &JsonValue) -> JsonValue {
         }
     })
 }
+
+/// Build the user payload for action item extraction over one notes chunk.
+pub fn build_action_items_payload(input: &JsonValue) -> JsonValue {
+    json!({
+        "operation": "extractActionItems",
+        "context": input,
+        "expectations": {
+            "languages": ["zh-CN", "en"],
+            "timezoneFallback": "UTC",
+            "includeOwners": true
+        }
+    })
+}
+
+/// Build the user payload for decomposing one task into subtasks.
+pub fn build_decompose_payload(input: &JsonValue) -> JsonValue {
+    json!({
+        "operation": "decomposeTask",
+        "context": input,
+        "expectations": {
+            "languages": ["zh-CN", "en"],
+            "includeEstimates": true,
+            "includeDependencies": true
+        }
+    })
+}
+
+/// Build the user payload for reviewing a generated plan.
+pub fn build_plan_critique_payload(input: &JsonValue) -> JsonValue {
+    json!({
+        "operation": "critiquePlan",
+        "context": input,
+        "expectations": {
+            "maxIssues": 3,
+            "language": "zh-CN"
+        }
+    })
+}
+
+/// Build the user payload for polishing an activity summary.
+pub fn build_history_summary_payload(input: &JsonValue) -> JsonValue {
+    json!({
+        "operation": "summarizeHistory",
+        "context": input,
+        "expectations": {
+            "format": "narrative",
+            "maxWords": 200
+        }
+    })
+}
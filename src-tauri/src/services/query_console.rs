@@ -0,0 +1,338 @@
+//! Read-only SQL for power users building their own reports. Statements run
+//! on a `query_only` connection whose authorizer allows nothing but reading
+//! the user-owned tables, and are cut off after a row and time limit.
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use rusqlite::hooks::{AuthAction, AuthContext, Authorization};
+use rusqlite::Connection;
+
+use crate::db::repositories::data_export_repository::{json_value, DataExportRepository};
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::query::{QueryColumnDoc, QueryResult, QueryTableDoc};
+use crate::services::data_export_service::user_tables;
+
+const DEFAULT_MAX_ROWS: usize = 200;
+const MAX_ROWS: usize = 1000;
+const MAX_SQL_CHARS: usize = 10_000;
+const QUERY_TIMEOUT: Duration = Duration::from_secs(3);
+/// Virtual machine instructions between deadline checks.
+const PROGRESS_INTERVAL_OPS: i32 = 10_000;
+/// User-owned tables the console does not expose; settings may hold the
+/// API key.
+const HIDDEN_TABLES: [&str; 1] = ["app_settings"];
+const STATEMENT_KEYWORDS: [&str; 2] = ["select", "with"];
+
+pub struct QueryConsole {
+    db: DbPool,
+}
+
+impl QueryConsole {
+    pub fn new(db: DbPool) -> Self {
+        Self { db }
+    }
+
+    /// Runs one SELECT (or WITH ... SELECT) statement and returns up to
+    /// `max_rows` rows, 200 by default and at most 1000.
+    pub fn run(&self, sql: &str, max_rows: Option<usize>) -> AppResult<QueryResult> {
+        let sql = validate_statement(sql)?;
+        let max_rows = max_rows.unwrap_or(DEFAULT_MAX_ROWS).clamp(1, MAX_ROWS);
+        self.db
+            .with_read_connection(|conn| run_query(conn, sql, max_rows, QUERY_TIMEOUT))
+    }
+
+    /// Tables the console can read, with their columns.
+    pub fn schema(&self) -> AppResult<Vec<QueryTableDoc>> {
+        self.db.with_read_connection(|conn| {
+            let existing = DataExportRepository::list_tables(conn)?;
+            queryable_tables()
+                .filter(|(table, _)| existing.iter().any(|name| name == table))
+                .map(|(table, description)| {
+                    let columns = DataExportRepository::table_columns(conn, table)?
+                        .into_iter()
+                        .map(|column| QueryColumnDoc {
+                            name: column.name,
+                            data_type: column.declared_type,
+                            primary_key: column.primary_key > 0,
+                        })
+                        .collect();
+                    Ok(QueryTableDoc {
+                        name: table.to_string(),
+                        description: description.to_string(),
+                        columns,
+                    })
+                })
+                .collect()
+        })
+    }
+}
+
+fn queryable_tables() -> impl Iterator<Item = (&'static str, &'static str)> {
+    user_tables().filter(|(table, _)| !HIDDEN_TABLES.contains(table))
+}
+
+/// Runs an already validated statement with the console's authorizer and
+/// deadline installed, removing both afterwards.
+fn run_query(
+    conn: &Connection,
+    sql: &str,
+    max_rows: usize,
+    timeout: Duration,
+) -> AppResult<QueryResult> {
+    let allowed: HashSet<&'static str> = queryable_tables().map(|(table, _)| table).collect();
+    conn.authorizer(Some(move |context: AuthContext<'_>| {
+        authorize(&allowed, context)
+    }));
+    let started = Instant::now();
+    let deadline = started + timeout;
+    let timed_out = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&timed_out);
+    conn.progress_handler(
+        PROGRESS_INTERVAL_OPS,
+        Some(move || {
+            let expired = Instant::now() >= deadline;
+            flag.store(expired, Ordering::Relaxed);
+            expired
+        }),
+    );
+
+    let result = collect_rows(conn, sql, max_rows);
+
+    conn.authorizer(None::<fn(AuthContext<'_>) -> Authorization>);
+    conn.progress_handler(0, None::<fn() -> bool>);
+
+    let (columns, rows, truncated) = result.map_err(|err| {
+        if timed_out.load(Ordering::Relaxed) {
+            AppError::validation(format!("查询超过 {} 秒，已中止", timeout.as_secs().max(1)))
+        } else {
+            AppError::validation(format!("查询失败: {err}"))
+        }
+    })?;
+    Ok(QueryResult {
+        columns,
+        rows,
+        truncated,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+type CollectedRows = (Vec<String>, Vec<Vec<serde_json::Value>>, bool);
+
+fn collect_rows(conn: &Connection, sql: &str, max_rows: usize) -> rusqlite::Result<CollectedRows> {
+    let mut stmt = conn.prepare(sql)?;
+    if !stmt.readonly() {
+        return Err(rusqlite::Error::InvalidQuery);
+    }
+    let columns: Vec<String> = stmt
+        .column_names()
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+
+    let mut rows = Vec::new();
+    let mut truncated = false;
+    let mut cursor = stmt.query([])?;
+    while let Some(row) = cursor.next()? {
+        if rows.len() == max_rows {
+            truncated = true;
+            break;
+        }
+        let values = (0..columns.len())
+            .map(|index| row.get_ref(index).map(json_value))
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        rows.push(values);
+    }
+    Ok((columns, rows, truncated))
+}
+
+fn authorize(allowed: &HashSet<&'static str>, context: AuthContext<'_>) -> Authorization {
+    match context.action {
+        AuthAction::Select | AuthAction::Recursive => Authorization::Allow,
+        AuthAction::Function { function_name } => {
+            if function_name.eq_ignore_ascii_case("load_extension") {
+                Authorization::Deny
+            } else {
+                Authorization::Allow
+            }
+        }
+        AuthAction::Read { table_name, .. } => {
+            let listed = allowed
+                .iter()
+                .any(|table| table.eq_ignore_ascii_case(table_name));
+            match context.database_name {
+                // Reads of common table expressions come without a database.
+                None => Authorization::Allow,
+                Some("main") if listed => Authorization::Allow,
+                Some(_) => Authorization::Deny,
+            }
+        }
+        _ => Authorization::Deny,
+    }
+}
+
+/// The single SELECT statement in `sql` without its trailing semicolon.
+fn validate_statement(sql: &str) -> AppResult<&str> {
+    if sql.chars().count() > MAX_SQL_CHARS {
+        return Err(AppError::validation(format!(
+            "查询语句不能超过 {MAX_SQL_CHARS} 个字符"
+        )));
+    }
+    let statement = match statement_end(sql) {
+        Some(end) => {
+            let mut rest = &sql[end..];
+            while let Some(next) = skip_trivia(rest).strip_prefix(';') {
+                rest = next;
+            }
+            if !skip_trivia(rest).is_empty() {
+                return Err(AppError::validation("一次只能执行一条查询语句"));
+            }
+            &sql[..end]
+        }
+        None => sql,
+    };
+
+    let keyword: String = skip_trivia(statement)
+        .chars()
+        .take_while(char::is_ascii_alphabetic)
+        .collect::<String>()
+        .to_ascii_lowercase();
+    if keyword.is_empty() {
+        return Err(AppError::validation("查询语句不能为空"));
+    }
+    if !STATEMENT_KEYWORDS.contains(&keyword.as_str()) {
+        return Err(AppError::validation("只支持 SELECT 查询"));
+    }
+    Ok(statement.trim())
+}
+
+/// Byte offset of the first `;` outside literals, quoted names and
+/// comments.
+fn statement_end(sql: &str) -> Option<usize> {
+    let bytes = sql.as_bytes();
+    let closing = |from: usize, quote: u8| {
+        bytes[from..]
+            .iter()
+            .position(|byte| *byte == quote)
+            .map_or(bytes.len(), |offset| from + offset + 1)
+    };
+    let mut index = 0;
+    while index < bytes.len() {
+        index = match bytes[index] {
+            quote @ (b'\'' | b'"' | b'`') => closing(index + 1, quote),
+            b'[' => closing(index + 1, b']'),
+            b'-' if bytes.get(index + 1) == Some(&b'-') => closing(index + 2, b'\n'),
+            b'/' if bytes.get(index + 1) == Some(&b'*') => sql[index + 2..]
+                .find("*/")
+                .map_or(bytes.len(), |offset| index + 2 + offset + 2),
+            b';' => return Some(index),
+            _ => index + 1,
+        };
+    }
+    None
+}
+
+fn skip_trivia(mut sql: &str) -> &str {
+    loop {
+        let trimmed = sql.trim_start();
+        if let Some(rest) = trimmed.strip_prefix("--") {
+            sql = rest.split_once('\n').map_or("", |(_, rest)| rest);
+        } else if let Some(rest) = trimmed.strip_prefix("/*") {
+            sql = rest.split_once("*/").map_or("", |(_, rest)| rest);
+        } else {
+            return trimmed;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::repositories::settings_repository::SettingsRepository;
+    use crate::models::task::TaskCreateInput;
+    use crate::services::task_service::TaskService;
+
+    #[test]
+    fn reads_user_tables_within_the_row_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = DbPool::new(dir.path().join("query.sqlite")).unwrap();
+        let tasks = TaskService::new(db.clone());
+        for title in ["a", "b", "c"] {
+            tasks
+                .create_task(TaskCreateInput {
+                    title: title.to_string(),
+                    ..TaskCreateInput::default()
+                })
+                .unwrap();
+        }
+        let console = QueryConsole::new(db);
+
+        let result = console
+            .run(
+                "-- titles\nSELECT title, 1.5 AS weight FROM tasks ORDER BY title;",
+                Some(2),
+            )
+            .unwrap();
+        assert_eq!(result.columns, vec!["title", "weight"]);
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![serde_json::json!("a"), serde_json::json!(1.5)],
+                vec![serde_json::json!("b"), serde_json::json!(1.5)],
+            ]
+        );
+        assert!(result.truncated);
+
+        let counted = console
+            .run(
+                "WITH done AS (SELECT * FROM tasks WHERE status = 'done') \
+                 SELECT count(*) FROM done",
+                None,
+            )
+            .unwrap();
+        assert_eq!(counted.rows, vec![vec![serde_json::json!(0)]]);
+
+        let schema = console.schema().unwrap();
+        let table = schema.iter().find(|table| table.name == "tasks").unwrap();
+        assert!(table.columns.iter().any(|column| column.name == "title"));
+        assert!(schema.iter().all(|table| table.name != "app_settings"));
+    }
+
+    #[test]
+    fn rejects_writes_hidden_tables_and_long_queries() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = DbPool::new(dir.path().join("query.sqlite")).unwrap();
+        db.with_connection(|conn| SettingsRepository::upsert(conn, "theme", "dark"))
+            .unwrap();
+        let console = QueryConsole::new(db.clone());
+
+        for sql in [
+            "DELETE FROM tasks",
+            "SELECT 1; DROP TABLE tasks",
+            "SELECT value FROM app_settings",
+            "SELECT * FROM tasks JOIN caldav_account",
+            "SELECT name FROM sqlite_master",
+            "PRAGMA table_info(tasks)",
+            "",
+        ] {
+            assert!(console.run(sql, None).is_err(), "{sql}");
+        }
+        assert!(console.run("SELECT ';' AS semi; -- done", None).is_ok());
+
+        let err = db
+            .with_read_connection(|conn| {
+                run_query(
+                    conn,
+                    "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n) \
+                     SELECT count(*) FROM n",
+                    1,
+                    Duration::from_millis(50),
+                )
+            })
+            .unwrap_err();
+        assert!(err.to_string().contains("已中止"), "{err}");
+    }
+}
@@ -1,12 +1,13 @@
-use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{named_params, Connection};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, RwLock};
 
 use crate::db::DbPool;
 use crate::error::{AppError, AppResult};
 use crate::models::recurring_task::{
     RecurringTaskTemplate, RecurringTaskTemplateCreate, RecurringTaskTemplateFilter,
-    RecurringTaskTemplateUpdate, TaskInstance,
+    RecurringTaskStats, RecurringTaskTemplateUpdate, TaskInstance,
 };
 use crate::services::instance_generator::{GenerationConfig, InstanceGenerator};
 use crate::services::rrule_parser::RRuleParser;
@@ -66,6 +67,10 @@ impl InstanceCache {
     }
 }
 
+/// Open occurrences due less than this long ago are still pending: they
+/// neither count as missed nor break a streak.
+const PENDING_GRACE_HOURS: i64 = 24;
+
 /// Service for managing recurring task templates and instances
 #[derive(Clone)]
 pub struct RecurringTaskService {
@@ -582,6 +587,20 @@ impl RecurringTaskService {
 
 
 
+    /// Streak, completion rate and average delay for one recurring rule
+    /// across all of its materialized occurrences
+    pub fn get_stats(&self, template_id: &str) -> AppResult<RecurringTaskStats> {
+        let template = self.get_template(template_id)?;
+        let now = Utc::now();
+        let stats = self
+            .db
+            .with_read_connection(|conn| collect_stats(conn, Some(template_id), None, now))?
+            .pop()
+            .unwrap_or_else(|| summarize_occurrences(&template.id, &template.title, &[], now));
+
+        Ok(stats)
+    }
+
     /// Get database pool reference
     pub fn pool(&self) -> &DbPool {
         &self.db
@@ -616,6 +635,157 @@ impl RecurringTaskService {
     }
 }
 
+/// One materialized occurrence, reduced to what the statistics need
+#[derive(Debug, Clone)]
+struct OccurrenceOutcome {
+    instance_date: DateTime<Utc>,
+    due_at: Option<DateTime<Utc>>,
+    status: String,
+    completed_at: Option<DateTime<Utc>>,
+}
+
+impl OccurrenceOutcome {
+    fn is_completed(&self) -> bool {
+        self.completed_at.is_some() || self.status == "completed"
+    }
+
+    fn due(&self) -> DateTime<Utc> {
+        self.due_at.unwrap_or(self.instance_date)
+    }
+}
+
+/// Per-rule statistics for occurrences dated in `[since, until]`, one entry
+/// per rule that has any. Regenerating instances can leave several rows for
+/// the same date, so occurrences are merged by date and a completed row wins.
+pub(crate) fn collect_stats(
+    conn: &Connection,
+    template_id: Option<&str>,
+    since: Option<DateTime<Utc>>,
+    until: DateTime<Utc>,
+) -> AppResult<Vec<RecurringTaskStats>> {
+    let mut stmt = conn.prepare(
+        r#"
+            SELECT i.template_id, t.title, i.instance_date, i.due_at, i.status, i.completed_at
+            FROM task_instances i
+            JOIN recurring_task_templates t ON t.id = i.template_id
+            WHERE (:template_id IS NULL OR i.template_id = :template_id)
+              AND (:since IS NULL OR i.instance_date >= :since)
+              AND i.instance_date <= :until
+        "#,
+    )?;
+    let rows = stmt
+        .query_map(
+            named_params! {
+                ":template_id": template_id,
+                ":since": since.map(|value| value.to_rfc3339()),
+                ":until": until.to_rfc3339(),
+            },
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, Option<String>>(5)?,
+                ))
+            },
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut by_template: BTreeMap<String, (String, BTreeMap<DateTime<Utc>, OccurrenceOutcome>)> =
+        BTreeMap::new();
+    for (id, title, instance_date, due_at, status, completed_at) in rows {
+        let Some(instance_date) = parse_timestamp(Some(instance_date)) else {
+            continue;
+        };
+        let outcome = OccurrenceOutcome {
+            instance_date,
+            due_at: parse_timestamp(due_at),
+            status: status.unwrap_or_else(|| "todo".to_string()),
+            completed_at: parse_timestamp(completed_at),
+        };
+        let (_, occurrences) = by_template
+            .entry(id)
+            .or_insert_with(|| (title, BTreeMap::new()));
+        match occurrences.get(&instance_date) {
+            Some(existing) if existing.is_completed() || !outcome.is_completed() => {}
+            _ => {
+                occurrences.insert(instance_date, outcome);
+            }
+        }
+    }
+
+    Ok(by_template
+        .into_iter()
+        .map(|(id, (title, occurrences))| {
+            let occurrences: Vec<OccurrenceOutcome> = occurrences.into_values().collect();
+            summarize_occurrences(&id, &title, &occurrences, until)
+        })
+        .collect())
+}
+
+/// Folds date-ordered occurrences into statistics as of `now`.
+fn summarize_occurrences(
+    template_id: &str,
+    title: &str,
+    occurrences: &[OccurrenceOutcome],
+    now: DateTime<Utc>,
+) -> RecurringTaskStats {
+    let grace_cutoff = now - Duration::hours(PENDING_GRACE_HOURS);
+    let mut total = 0;
+    let mut completed = 0;
+    let mut streak = 0;
+    let mut longest_streak = 0;
+    let mut delay_total = 0.0;
+    let mut last_completed_at: Option<DateTime<Utc>> = None;
+
+    for occurrence in occurrences {
+        if occurrence.status == "cancelled" {
+            continue;
+        }
+        if occurrence.is_completed() {
+            total += 1;
+            completed += 1;
+            streak += 1;
+            longest_streak = longest_streak.max(streak);
+            if let Some(completed_at) = occurrence.completed_at {
+                delay_total += (completed_at - occurrence.due()).num_minutes().max(0) as f64;
+                last_completed_at = last_completed_at.max(Some(completed_at));
+            }
+        } else if occurrence.due() <= grace_cutoff {
+            total += 1;
+            streak = 0;
+        }
+    }
+
+    let completion_rate = if total > 0 {
+        (completed as f64 / total as f64 * 1000.0).round() / 1000.0
+    } else {
+        0.0
+    };
+    let average_delay_minutes =
+        (completed > 0).then(|| (delay_total / completed as f64 * 10.0).round() / 10.0);
+
+    RecurringTaskStats {
+        template_id: template_id.to_string(),
+        title: title.to_string(),
+        total_occurrences: total,
+        completed_occurrences: completed,
+        completion_rate,
+        current_streak: streak,
+        longest_streak,
+        average_delay_minutes,
+        last_completed_at,
+    }
+}
+
+fn parse_timestamp(value: Option<String>) -> Option<DateTime<Utc>> {
+    value
+        .and_then(|raw| DateTime::parse_from_rfc3339(&raw).ok())
+        .map(|value| value.with_timezone(&Utc))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -769,4 +939,95 @@ mod tests {
         
         assert!(service.create_template(input).is_err());
     }
-}
\ No newline at end of file
+
+    fn occurrence(day: u32, status: &str, completed_hours_late: Option<i64>) -> OccurrenceOutcome {
+        use chrono::TimeZone;
+        let instance_date = Utc.with_ymd_and_hms(2025, 3, day, 9, 0, 0).unwrap();
+        OccurrenceOutcome {
+            instance_date,
+            due_at: None,
+            status: status.to_string(),
+            completed_at: completed_hours_late.map(|hours| instance_date + Duration::hours(hours)),
+        }
+    }
+
+    #[test]
+    fn test_summarize_occurrences_streaks_and_delay() {
+        use chrono::TimeZone;
+        let now = Utc.with_ymd_and_hms(2025, 3, 8, 12, 0, 0).unwrap();
+        let occurrences = vec![
+            occurrence(1, "completed", Some(0)),
+            occurrence(2, "completed", Some(2)),
+            occurrence(3, "completed", Some(-1)),
+            occurrence(4, "todo", None),
+            occurrence(5, "cancelled", None),
+            occurrence(6, "completed", Some(1)),
+            occurrence(7, "completed", Some(0)),
+            // Due this morning and still open: pending, not missed
+            occurrence(8, "todo", None),
+        ];
+
+        let stats = summarize_occurrences("rule", "Daily Standup", &occurrences, now);
+
+        assert_eq!(stats.total_occurrences, 6);
+        assert_eq!(stats.completed_occurrences, 5);
+        assert_eq!(stats.completion_rate, 0.833);
+        assert_eq!(stats.current_streak, 2);
+        assert_eq!(stats.longest_streak, 3);
+        // 0 + 120 + 0 (early) + 60 + 0 over five completions
+        assert_eq!(stats.average_delay_minutes, Some(36.0));
+        assert_eq!(stats.last_completed_at, occurrences[6].completed_at);
+    }
+
+    #[test]
+    fn test_get_stats_merges_regenerated_occurrences() {
+        let (service, _dir) = setup_service();
+
+        let input = RecurringTaskTemplateCreate {
+            title: "Journal".to_string(),
+            description: None,
+            recurrence_rule_string: "FREQ=DAILY".to_string(),
+            priority: None,
+            tags: None,
+            estimated_minutes: None,
+        };
+        let template = service.create_template(input).unwrap();
+        service
+            .db
+            .with_connection(|conn| {
+                conn.execute(
+                    "DELETE FROM task_instances WHERE template_id = ?1",
+                    [&template.id],
+                )?;
+                Ok(())
+            })
+            .unwrap();
+
+        let now = Utc::now();
+        let mut instances = Vec::new();
+        for days_ago in 1..=3 {
+            let mut instance =
+                TaskInstance::from_template(&template, now - Duration::days(days_ago));
+            instance.status = "completed".to_string();
+            instance.completed_at = Some(instance.instance_date + Duration::minutes(30));
+            instances.push(instance);
+        }
+        // A regenerated, still-open copy of an occurrence that was completed
+        instances.push(TaskInstance::from_template(
+            &template,
+            instances[0].instance_date,
+        ));
+        service.batch_store_instances(&instances).unwrap();
+
+        let stats = service.get_stats(&template.id).unwrap();
+        assert_eq!(stats.total_occurrences, 3);
+        assert_eq!(stats.completed_occurrences, 3);
+        assert_eq!(stats.current_streak, 3);
+        assert_eq!(stats.average_delay_minutes, Some(30.0));
+
+        assert!(matches!(
+            service.get_stats("missing"),
+            Err(AppError::NotFound)
+        ));
+    }
+}
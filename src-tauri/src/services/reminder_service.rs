@@ -0,0 +1,397 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::Connection;
+use tauri::{AppHandle, Emitter};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::db::repositories::reminder_repository::{BlockReminderRow, ReminderRepository};
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::reminder::{BlockReminderRecord, BlockReminderStatus, ReminderSyncSummary};
+use crate::services::job_supervisor::spawn_supervised;
+use crate::services::on_demand_jobs::OnDemandJobs;
+
+pub const REMINDER_DUE_EVENT: &str = "reminders://due";
+
+const DEFAULT_LEAD_MINUTES: i64 = 10;
+/// Fixed blocks cannot slide, so the heads-up comes earlier.
+const FIXED_LEAD_MINUTES: i64 = 15;
+const FLEXIBLE_LEAD_MINUTES: i64 = 5;
+const MAX_LEAD_MINUTES: i64 = 120;
+/// Block start times are compared as strings when picking candidates; this
+/// margin covers any UTC offset they were written with.
+const CANDIDATE_MARGIN_HOURS: i64 = 24;
+pub(crate) const POLL_INTERVAL: StdDuration = StdDuration::from_secs(60);
+
+/// Minutes between the reminder and the block start. Travel time is added
+/// on top once the block has one.
+pub fn reminder_lead_minutes(flexibility: Option<&str>, travel_minutes: Option<i64>) -> i64 {
+    let base = match flexibility {
+        Some("fixed") => FIXED_LEAD_MINUTES,
+        Some("flexible") => FLEXIBLE_LEAD_MINUTES,
+        _ => DEFAULT_LEAD_MINUTES,
+    };
+    (base + travel_minutes.unwrap_or(0).max(0)).min(MAX_LEAD_MINUTES)
+}
+
+/// Brings `block_reminders` in line with the applied blocks: upcoming open
+/// blocks get a scheduled reminder, moved blocks are rescheduled and
+/// reminders whose block was moved into the past, checked off or whose task
+/// finished are cancelled. Every change to blocks goes through here, so no
+/// reminder outlives its block.
+pub fn sync_block_reminders(
+    conn: &Connection,
+    now: DateTime<Utc>,
+) -> AppResult<ReminderSyncSummary> {
+    let mut summary = ReminderSyncSummary::default();
+    let timestamp = now.to_rfc3339();
+    let since = (now - Duration::hours(CANDIDATE_MARGIN_HOURS)).to_rfc3339();
+
+    let mut wanted = HashSet::new();
+    for candidate in ReminderRepository::list_candidates(conn, &since)? {
+        let Some(start) = DateTime::parse_from_rfc3339(&candidate.start_at)
+            .ok()
+            .map(|value| value.with_timezone(&Utc))
+            .filter(|start| *start > now)
+        else {
+            continue;
+        };
+        wanted.insert(candidate.block_id.clone());
+
+        let lead_minutes = reminder_lead_minutes(candidate.flexibility.as_deref(), None);
+        let start_at = start.to_rfc3339();
+        let remind_at = (start - Duration::minutes(lead_minutes)).to_rfc3339();
+
+        let mut record = match ReminderRepository::find_by_block(conn, &candidate.block_id)? {
+            None => {
+                summary.scheduled += 1;
+                BlockReminderRecord {
+                    id: Uuid::new_v4().to_string(),
+                    block_id: candidate.block_id,
+                    task_id: candidate.task_id,
+                    start_at,
+                    remind_at,
+                    lead_minutes,
+                    status: BlockReminderStatus::Scheduled,
+                    created_at: timestamp.clone(),
+                    updated_at: timestamp.clone(),
+                }
+            }
+            Some(existing) => {
+                let mut record = existing.into_record()?;
+                let moved = record.start_at != start_at;
+                let changed = moved
+                    || (record.status == BlockReminderStatus::Scheduled
+                        && record.lead_minutes != lead_minutes);
+                let revived = record.status == BlockReminderStatus::Cancelled;
+                if !changed && !revived {
+                    continue;
+                }
+                record.status = BlockReminderStatus::Scheduled;
+                summary.rescheduled += 1;
+                record.start_at = start_at;
+                record.remind_at = remind_at;
+                record.lead_minutes = lead_minutes;
+                record
+            }
+        };
+        record.updated_at = timestamp.clone();
+        ReminderRepository::upsert(conn, &BlockReminderRow::from_record(&record))?;
+    }
+
+    for row in ReminderRepository::list_scheduled(conn)? {
+        if wanted.contains(&row.block_id) {
+            continue;
+        }
+        let mut record = row.into_record()?;
+        record.status = BlockReminderStatus::Cancelled;
+        record.updated_at = timestamp.clone();
+        ReminderRepository::upsert(conn, &BlockReminderRow::from_record(&record))?;
+        summary.cancelled += 1;
+    }
+
+    if summary != ReminderSyncSummary::default() {
+        debug!(
+            target: "app::reminders",
+            scheduled = summary.scheduled,
+            rescheduled = summary.rescheduled,
+            cancelled = summary.cancelled,
+            "block reminders synced"
+        );
+    }
+    Ok(summary)
+}
+
+/// Owns block reminders: reconciliation, firing and dismissal.
+pub struct ReminderService {
+    db: DbPool,
+    worker_started: AtomicBool,
+}
+
+impl ReminderService {
+    pub fn new(db: DbPool) -> Self {
+        Self {
+            db,
+            worker_started: AtomicBool::new(false),
+        }
+    }
+
+    pub fn sync(&self, now: DateTime<Utc>) -> AppResult<ReminderSyncSummary> {
+        let mut conn = self.db.get_connection()?;
+        let tx = conn.transaction()?;
+        let summary = sync_block_reminders(&tx, now)?;
+        tx.commit()?;
+        Ok(summary)
+    }
+
+    pub fn list_upcoming(&self, limit: usize) -> AppResult<Vec<BlockReminderRecord>> {
+        self.db.with_connection(|conn| {
+            ReminderRepository::list_scheduled(conn)?
+                .into_iter()
+                .take(limit)
+                .map(BlockReminderRow::into_record)
+                .collect()
+        })
+    }
+
+    /// Stops a reminder from firing. It comes back only if its block moves.
+    pub fn dismiss(&self, id: &str) -> AppResult<BlockReminderRecord> {
+        let mut record = self
+            .db
+            .with_connection(|conn| ReminderRepository::find(conn, id))?
+            .ok_or_else(AppError::not_found)?
+            .into_record()?;
+        record.status = BlockReminderStatus::Dismissed;
+        record.updated_at = Utc::now().to_rfc3339();
+
+        let row = BlockReminderRow::from_record(&record);
+        self.db
+            .with_connection(|conn| ReminderRepository::upsert(conn, &row))?;
+        info!(target: "app::reminders", reminder_id = %id, "block reminder dismissed");
+        Ok(record)
+    }
+
+    /// Marks due reminders as fired and returns them. Reminders whose block
+    /// already started, e.g. while the app was closed, are marked missed.
+    pub fn fire_due(&self, now: DateTime<Utc>) -> AppResult<Vec<BlockReminderRecord>> {
+        let mut conn = self.db.get_connection()?;
+        let tx = conn.transaction()?;
+        let mut fired = Vec::new();
+        for row in ReminderRepository::list_due(&tx, &now.to_rfc3339())? {
+            let mut record = row.into_record()?;
+            let started = DateTime::parse_from_rfc3339(&record.start_at)
+                .map(|start| start.with_timezone(&Utc) <= now)
+                .unwrap_or(true);
+            record.status = if started {
+                BlockReminderStatus::Missed
+            } else {
+                BlockReminderStatus::Fired
+            };
+            record.updated_at = now.to_rfc3339();
+            ReminderRepository::upsert(&tx, &BlockReminderRow::from_record(&record))?;
+            if !started {
+                fired.push(record);
+            }
+        }
+        tx.commit()?;
+        Ok(fired)
+    }
+
+    /// Mobile counterpart of [`ensure_worker`](Self::ensure_worker): the
+    /// reminders that fired are returned from `jobs_run_scheduled` instead
+    /// of emitted.
+    pub fn register_on_demand(self: &Arc<Self>, jobs: &OnDemandJobs) {
+        let runner = Arc::clone(self);
+        jobs.register_with_output("block-reminders", Duration::zero(), move |now| {
+            runner.sync(now)?;
+            runner.fire_due(now)
+        });
+    }
+
+    /// Syncs and fires reminders once a minute, emitting
+    /// [`REMINDER_DUE_EVENT`] for the ones that fired.
+    pub fn ensure_worker(self: &Arc<Self>, app: AppHandle) -> AppResult<()> {
+        if self
+            .worker_started
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Ok(());
+        }
+
+        let runner = Arc::clone(self);
+        if let Err(err) = spawn_supervised(self.db.clone(), "block-reminder-worker", move || loop {
+            let now = Utc::now();
+            let outcome = runner.sync(now).and_then(|_| runner.fire_due(now));
+            match outcome {
+                Ok(fired) if !fired.is_empty() => {
+                    if let Err(err) = app.emit(REMINDER_DUE_EVENT, &fired) {
+                        warn!(target: "app::reminders", error = %err, "failed to emit reminder event");
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    error!(target: "app::reminders", error = %err, "block reminder poll failed")
+                }
+            }
+            thread::sleep(POLL_INTERVAL);
+        }) {
+            self.worker_started.store(false, Ordering::SeqCst);
+            return Err(err);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::task::{TaskCreateInput, TaskUpdateInput};
+    use crate::services::task_service::TaskService;
+    use rusqlite::params;
+
+    fn insert_block(pool: &DbPool, task_id: &str, start: DateTime<Utc>, flexibility: &str) {
+        pool.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO planning_sessions (id, task_ids, generated_at, status) VALUES ('s-1', '[]', ?1, 'applied')",
+                params![start.to_rfc3339()],
+            )?;
+            conn.execute(
+                "INSERT INTO planning_options (id, session_id, rank) VALUES ('o-1', 's-1', 1)",
+                [],
+            )?;
+            conn.execute(
+                "INSERT INTO planning_time_blocks (id, option_id, task_id, start_at, end_at, flexibility, applied_at) \
+                 VALUES ('b-1', 'o-1', ?1, ?2, ?3, ?4, ?2)",
+                params![
+                    task_id,
+                    start.to_rfc3339(),
+                    (start + Duration::hours(1)).to_rfc3339(),
+                    flexibility
+                ],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn lead_time_follows_flexibility_and_travel() {
+        assert_eq!(reminder_lead_minutes(Some("fixed"), None), 15);
+        assert_eq!(reminder_lead_minutes(Some("flexible"), None), 5);
+        assert_eq!(reminder_lead_minutes(None, Some(20)), 30);
+        assert_eq!(
+            reminder_lead_minutes(Some("fixed"), Some(600)),
+            MAX_LEAD_MINUTES
+        );
+    }
+
+    #[test]
+    fn reminders_follow_their_block() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = DbPool::new(dir.path().join("reminders.sqlite")).unwrap();
+        let tasks = TaskService::new(db.clone());
+        let reminders = ReminderService::new(db.clone());
+
+        let task = tasks
+            .create_task(TaskCreateInput {
+                title: "评审设计稿".into(),
+                ..Default::default()
+            })
+            .unwrap();
+        let now = Utc::now();
+        let start = now + Duration::hours(2);
+        insert_block(&db, &task.id, start, "fixed");
+
+        assert_eq!(reminders.sync(now).unwrap().scheduled, 1);
+        let upcoming = reminders.list_upcoming(10).unwrap();
+        assert_eq!(upcoming.len(), 1);
+        assert_eq!(upcoming[0].lead_minutes, 15);
+        assert_eq!(reminders.sync(now).unwrap(), ReminderSyncSummary::default());
+
+        let moved = start + Duration::hours(1);
+        db.with_connection(|conn| {
+            conn.execute(
+                "UPDATE planning_time_blocks SET start_at = ?1 WHERE id = 'b-1'",
+                params![moved.to_rfc3339()],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(reminders.sync(now).unwrap().rescheduled, 1);
+        let reminder = &reminders.list_upcoming(10).unwrap()[0];
+        assert_eq!(
+            reminder.remind_at,
+            (moved - Duration::minutes(15)).to_rfc3339()
+        );
+
+        assert!(reminders.fire_due(now).unwrap().is_empty());
+        let fired = reminders.fire_due(moved - Duration::minutes(10)).unwrap();
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].status, BlockReminderStatus::Fired);
+        assert_eq!(reminders.sync(now).unwrap(), ReminderSyncSummary::default());
+
+        db.with_connection(|conn| {
+            conn.execute(
+                "UPDATE planning_time_blocks SET start_at = ?1 WHERE id = 'b-1'",
+                params![start.to_rfc3339()],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+        reminders.sync(now).unwrap();
+        tasks
+            .update_task(
+                &task.id,
+                TaskUpdateInput {
+                    status: Some("done".into()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert!(reminders.list_upcoming(10).unwrap().is_empty());
+        let cancelled = db
+            .with_connection(|conn| ReminderRepository::find_by_block(conn, "b-1"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(cancelled.status, "cancelled");
+    }
+
+    #[test]
+    fn on_demand_runs_return_the_reminders_they_fire() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = DbPool::new(dir.path().join("reminders.sqlite")).unwrap();
+        let tasks = TaskService::new(db.clone());
+        let reminders = Arc::new(ReminderService::new(db.clone()));
+        let jobs = OnDemandJobs::new();
+        reminders.register_on_demand(&jobs);
+
+        let task = tasks
+            .create_task(TaskCreateInput {
+                title: "组会".into(),
+                ..Default::default()
+            })
+            .unwrap();
+        let now = Utc::now();
+        insert_block(&db, &task.id, now + Duration::minutes(10), "fixed");
+
+        let runs = jobs.run_due(now);
+        assert_eq!(runs.len(), 1);
+        assert!(runs[0].succeeded);
+        let fired: Vec<BlockReminderRecord> =
+            serde_json::from_value(runs[0].output.clone().unwrap()).unwrap();
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].block_id, "b-1");
+        assert_eq!(fired[0].status, BlockReminderStatus::Fired);
+
+        let again = jobs.run_due(now + Duration::minutes(1));
+        assert_eq!(again[0].output, Some(serde_json::json!([])));
+    }
+}
@@ -0,0 +1,271 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration as StdDuration, SystemTime};
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use tracing::{error, info, warn};
+
+use crate::db::repositories::retention_repository::RetentionRepository;
+use crate::db::DbPool;
+use crate::error::AppResult;
+use crate::models::retention::{RetentionCategory, RetentionEntry, RetentionReport};
+use crate::services::job_supervisor::spawn_supervised;
+use crate::services::power_throttle::PowerThrottle;
+use crate::services::settings_service::load_retention_policy;
+
+const PRUNE_INTERVAL: StdDuration = StdDuration::from_secs(7 * 24 * 60 * 60);
+
+/// Tables and the date column compared against the cutoff for each
+/// database-backed category.
+fn table_targets(category: RetentionCategory) -> &'static [(&'static str, &'static str)] {
+    match category {
+        RetentionCategory::Snapshots => &[
+            ("analytics_snapshots", "snapshot_date"),
+            ("analytics_anomalies", "snapshot_date"),
+        ],
+        RetentionCategory::Nudges => &[("wellness_events", "window_start")],
+        RetentionCategory::Feedback => &[("ai_feedback", "created_at")],
+        RetentionCategory::AiCache => &[
+            ("ai_cache", "created_at"),
+            ("agent_response_cache", "created_at"),
+        ],
+        RetentionCategory::Audit => &[("ai_debug_log", "created_at")],
+        RetentionCategory::Reports => &[],
+    }
+}
+
+pub struct RetentionService {
+    db: DbPool,
+    reports_dir: PathBuf,
+    prune_job_started: AtomicBool,
+}
+
+impl RetentionService {
+    pub fn new(db: DbPool, reports_dir: PathBuf) -> Self {
+        Self {
+            db,
+            reports_dir,
+            prune_job_started: AtomicBool::new(false),
+        }
+    }
+
+    /// Counts what the next prune would delete without touching anything.
+    pub fn preview(&self) -> AppResult<RetentionReport> {
+        self.run(Utc::now(), true)
+    }
+
+    /// Deletes everything older than the configured retention.
+    pub fn prune(&self) -> AppResult<RetentionReport> {
+        let report = self.run(Utc::now(), false)?;
+        if report.total_rows > 0 {
+            info!(
+                target: "app::retention",
+                deleted = report.total_rows,
+                "expired data pruned"
+            );
+        }
+        Ok(report)
+    }
+
+    /// Prunes now and then once a week on a background thread.
+    pub fn ensure_prune_job(self: &Arc<Self>) -> AppResult<()> {
+        if self
+            .prune_job_started
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Ok(());
+        }
+
+        let runner = Arc::clone(self);
+        let throttle = PowerThrottle::new(self.db.clone());
+        if let Err(err) = spawn_supervised(self.db.clone(), "retention-prune-job", move || loop {
+            throttle.wait_until_allowed("retention-prune");
+            if let Err(err) = runner.prune() {
+                error!(target: "app::retention", error = %err, "retention pruning failed");
+            }
+            thread::sleep(PRUNE_INTERVAL);
+        }) {
+            self.prune_job_started.store(false, Ordering::SeqCst);
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    fn run(&self, now: DateTime<Utc>, dry_run: bool) -> AppResult<RetentionReport> {
+        let today = now.date_naive();
+        let policy = self.db.with_read_connection(load_retention_policy)?;
+
+        let mut entries = Vec::with_capacity(RetentionCategory::ALL.len());
+        for category in RetentionCategory::ALL {
+            let retention_days = policy.days_for(category);
+            let cutoff = retention_cutoff(today, retention_days);
+            let rows = match cutoff {
+                None => 0,
+                Some(cutoff) if category == RetentionCategory::Reports => {
+                    self.prune_reports(cutoff, dry_run)
+                }
+                Some(cutoff) => self.prune_tables(category, cutoff, dry_run)?,
+            };
+            entries.push(RetentionEntry {
+                category,
+                retention_days,
+                cutoff: cutoff.map(|date| date.to_string()),
+                rows,
+            });
+        }
+
+        Ok(RetentionReport {
+            dry_run,
+            total_rows: entries.iter().map(|entry| entry.rows).sum(),
+            entries,
+            generated_at: now.to_rfc3339(),
+        })
+    }
+
+    fn prune_tables(
+        &self,
+        category: RetentionCategory,
+        cutoff: NaiveDate,
+        dry_run: bool,
+    ) -> AppResult<usize> {
+        let cutoff = cutoff.to_string();
+        let targets = table_targets(category);
+        if dry_run {
+            return self.db.with_read_connection(|conn| {
+                let mut count = 0;
+                for (table, column) in targets {
+                    if RetentionRepository::table_exists(conn, table)? {
+                        count += RetentionRepository::count_before(conn, table, column, &cutoff)?;
+                    }
+                }
+                Ok(count)
+            });
+        }
+
+        self.db.with_connection(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            let mut deleted = 0;
+            for (table, column) in targets {
+                if RetentionRepository::table_exists(&tx, table)? {
+                    deleted += RetentionRepository::delete_before(&tx, table, column, &cutoff)?;
+                }
+            }
+            tx.commit()?;
+            Ok(deleted)
+        })
+    }
+
+    /// Report files last modified before `cutoff`. Files that cannot be read
+    /// or removed are skipped.
+    fn prune_reports(&self, cutoff: NaiveDate, dry_run: bool) -> usize {
+        let Ok(entries) = fs::read_dir(&self.reports_dir) else {
+            return 0;
+        };
+        let cutoff: SystemTime = cutoff.and_hms_opt(0, 0, 0).unwrap().and_utc().into();
+
+        let mut count = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let expired = entry
+                .metadata()
+                .ok()
+                .filter(|metadata| metadata.is_file())
+                .and_then(|metadata| metadata.modified().ok())
+                .is_some_and(|modified| modified < cutoff);
+            if !expired {
+                continue;
+            }
+            if dry_run {
+                count += 1;
+                continue;
+            }
+            match fs::remove_file(&path) {
+                Ok(()) => count += 1,
+                Err(err) => warn!(
+                    target: "app::retention",
+                    path = %path.display(),
+                    error = %err,
+                    "failed to remove expired report"
+                ),
+            }
+        }
+        count
+    }
+}
+
+fn retention_cutoff(today: NaiveDate, retention_days: u32) -> Option<NaiveDate> {
+    if retention_days == 0 {
+        return None;
+    }
+    today.checked_sub_signed(Duration::days(i64::from(retention_days)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::retention::RetentionPolicy;
+    use crate::services::settings_service::SettingsService;
+    use rusqlite::params;
+
+    #[test]
+    fn preview_counts_and_prune_deletes_expired_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = DbPool::new(dir.path().join("retention.sqlite")).unwrap();
+        let reports_dir = dir.path().join("reports");
+        fs::create_dir_all(&reports_dir).unwrap();
+        let service = RetentionService::new(db.clone(), reports_dir);
+
+        let now = Utc::now();
+        let old = (now - Duration::days(60)).to_rfc3339();
+        let recent = now.to_rfc3339();
+        db.with_connection(|conn| {
+            for window_start in [&old, &recent] {
+                conn.execute(
+                    "INSERT INTO wellness_events (window_start, trigger_reason, recommended_break_minutes) VALUES (?1, 'focus_streak', 5)",
+                    params![window_start],
+                )?;
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        SettingsService::new(db.clone())
+            .unwrap()
+            .update_retention_policy(RetentionPolicy {
+                nudges_days: 30,
+                feedback_days: 0,
+                ..RetentionPolicy::default()
+            })
+            .unwrap();
+
+        let preview = service.preview().unwrap();
+        assert!(preview.dry_run);
+        let nudges = preview
+            .entries
+            .iter()
+            .find(|entry| entry.category == RetentionCategory::Nudges)
+            .unwrap();
+        assert_eq!(nudges.rows, 1);
+        let feedback = preview
+            .entries
+            .iter()
+            .find(|entry| entry.category == RetentionCategory::Feedback)
+            .unwrap();
+        assert_eq!(feedback.cutoff, None);
+
+        let pruned = service.prune().unwrap();
+        assert_eq!(pruned.total_rows, 1);
+        assert_eq!(service.preview().unwrap().total_rows, 0);
+        let remaining: i64 = db
+            .with_connection(|conn| {
+                Ok(conn.query_row("SELECT COUNT(*) FROM wellness_events", [], |row| row.get(0))?)
+            })
+            .unwrap();
+        assert_eq!(remaining, 1);
+    }
+}
@@ -2,6 +2,7 @@
 #[allow(unused_imports)]
 use chrono::Datelike;
 use chrono::{DateTime, NaiveDate, Utc, Weekday};
+use schemars::JsonSchema;
 use serde::de::{self, MapAccess, Visitor};
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
@@ -12,7 +13,7 @@ use std::str::FromStr;
 use crate::error::{AppError, AppResult};
 
 /// Frequency values for RRULE as defined in RFC 5545
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub enum Frequency {
     Daily,
     Weekly,
@@ -46,7 +47,7 @@ impl std::fmt::Display for Frequency {
 }
 
 /// Represents a BYDAY entry with optional ordinal position (e.g. 1MO, -1FR)
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, JsonSchema)]
 pub struct ByDayEntry {
     pub weekday: Weekday,
     pub position: Option<i8>,
@@ -160,7 +161,7 @@ fn format_by_day_entry(entry: &ByDayEntry) -> String {
 }
 
 /// Parsed recurrence rule following RFC 5545 RRULE standard
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct RecurrenceRule {
     pub freq: Frequency,
     pub interval: Option<u32>,
@@ -1,11 +1,20 @@
 use std::cmp::Ordering;
 
-use chrono::{offset::LocalResult, DateTime, Duration, FixedOffset, NaiveTime, TimeZone};
+use chrono::{
+    offset::LocalResult, DateTime, Datelike, Duration, FixedOffset, NaiveTime, TimeZone, Utc,
+};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::error::{AppError, AppResult};
+use crate::models::settings::SleepSchedule;
+use crate::models::task::MatrixQuadrant;
+use crate::services::behavior_learning::{self, WeekdayPreference};
+use crate::services::instance_generator::InstanceGenerator;
+use crate::services::rrule_parser::RRuleParser;
 use crate::services::schedule_utils;
+use crate::services::schedule_utils::intervals::{self, Interval};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -21,16 +30,22 @@ pub struct SchedulableTask {
     pub priority_weight: f32,
     #[serde(default)]
     pub is_parallelizable: bool,
+    /// Eisenhower quadrant; priority-first plans take tasks quadrant by
+    /// quadrant before falling back to the priority weight.
+    #[serde(default)]
+    pub quadrant: Option<MatrixQuadrant>,
+    #[serde(default)]
+    pub project_id: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TimeWindow {
     pub start_at: String,
     pub end_at: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ExistingEvent {
     pub id: String,
@@ -38,6 +53,11 @@ pub struct ExistingEvent {
     pub end_at: String,
     #[serde(default)]
     pub event_type: Option<String>,
+    /// RRULE for commitments such as weekly standups or classes. `start_at`
+    /// and `end_at` describe the first occurrence; later occurrences keep
+    /// its local time of day and duration.
+    #[serde(default)]
+    pub recurrence_rule: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
@@ -51,9 +71,51 @@ pub struct SchedulingPreferences {
     pub buffer_minutes_between_blocks: i64,
     #[serde(default)]
     pub prefer_compact_schedule: bool,
+    /// Sleep periods are removed from every window before blocks are placed.
+    #[serde(default)]
+    pub sleep_schedule: Option<SleepSchedule>,
+    /// Insert a break after this many minutes of back-to-back focus.
+    #[serde(default)]
+    pub break_after_focus_minutes: Option<i64>,
+    #[serde(default)]
+    pub break_minutes: i64,
+    /// Replace the focus window and buffer above on specific weekdays.
+    #[serde(default)]
+    pub weekday_overrides: Vec<WeekdayPreference>,
+    /// Scales the penalty for switching tasks between adjacent blocks and the
+    /// bonus for keeping a project's blocks together; 0 disables both.
+    #[serde(default)]
+    pub context_switch_weight: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+impl SchedulingPreferences {
+    /// Focus window on `weekday` (0 = Monday) in minutes after midnight.
+    pub fn focus_window_on(&self, weekday: u32) -> Option<(u32, u32)> {
+        behavior_learning::focus_window_on(
+            (self.focus_start_minute, self.focus_end_minute),
+            &self.weekday_overrides,
+            weekday,
+        )
+    }
+
+    pub fn buffer_minutes_on(&self, weekday: u32) -> i64 {
+        behavior_learning::buffer_minutes_on(
+            self.buffer_minutes_between_blocks,
+            &self.weekday_overrides,
+            weekday,
+        )
+    }
+
+    pub fn has_focus_window(&self) -> bool {
+        (0..7).any(|weekday| self.focus_window_on(weekday).is_some())
+    }
+}
+
+fn weekday_of(time: DateTime<FixedOffset>) -> u32 {
+    time.weekday().num_days_from_monday()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ScheduleConstraints {
     #[serde(default)]
@@ -79,6 +141,20 @@ pub struct TimeBlockCandidate {
     pub confidence: f32,
     #[serde(default)]
     pub conflict_flags: Vec<String>,
+    /// What to have ready when the block starts.
+    #[serde(default)]
+    pub prep_note: Option<String>,
+}
+
+/// Rest period placed between focus blocks. Breaks belong to no task and do
+/// not count towards `max_focus_minutes_per_day`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakBlock {
+    pub id: String,
+    pub start_at: String,
+    pub end_at: String,
+    pub duration_minutes: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -90,7 +166,7 @@ pub struct PlanRationaleStep {
     pub result: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ScheduleConflict {
     pub conflict_type: String,
@@ -100,9 +176,14 @@ pub struct ScheduleConflict {
     pub related_block_id: Option<String>,
     #[serde(default)]
     pub related_event_id: Option<String>,
+    /// `YYYY-MM-DD` of day-level conflicts such as `daily-overload`.
+    #[serde(default)]
+    pub date: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, JsonSchema,
+)]
 #[serde(rename_all = "camelCase")]
 pub enum ConflictSeverity {
     Low,
@@ -110,6 +191,74 @@ pub enum ConflictSeverity {
     High,
 }
 
+/// Normalized conflict types. `as_str` is the spelling stored in block
+/// `conflict_flags` and in `ScheduleConflict::conflict_type`.
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, JsonSchema,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictKind {
+    /// Overlaps an existing calendar event.
+    CalendarOverlap,
+    /// Falls inside the sleep schedule.
+    SleepWindow,
+    /// Ends after the task's deadline.
+    DeadlineRisk,
+    /// Moved past the task's due date by date shifting.
+    PastDue,
+    /// Starts before the task's planned start.
+    BeforePlannedStart,
+    /// The day exceeds `max_focus_minutes_per_day`.
+    DailyOverload,
+    /// Continues a task that was split across blocks.
+    SplitTask,
+    /// Longer than two hours without a break.
+    LongSession,
+}
+
+impl ConflictKind {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            ConflictKind::CalendarOverlap => "calendar-overlap",
+            ConflictKind::SleepWindow => "sleep-window",
+            ConflictKind::DeadlineRisk => "deadline-risk",
+            ConflictKind::PastDue => "past-due",
+            ConflictKind::BeforePlannedStart => "before-planned-start",
+            ConflictKind::DailyOverload => "daily-overload",
+            ConflictKind::SplitTask => "split-task",
+            ConflictKind::LongSession => "long-session",
+        }
+    }
+
+    /// Unknown spellings, e.g. from AI-generated plans, yield `None`.
+    pub fn from_flag(value: &str) -> Option<Self> {
+        match value {
+            "calendar-overlap" => Some(ConflictKind::CalendarOverlap),
+            "sleep-window" => Some(ConflictKind::SleepWindow),
+            "deadline-risk" => Some(ConflictKind::DeadlineRisk),
+            "past-due" => Some(ConflictKind::PastDue),
+            "before-planned-start" => Some(ConflictKind::BeforePlannedStart),
+            "daily-overload" => Some(ConflictKind::DailyOverload),
+            "split-task" => Some(ConflictKind::SplitTask),
+            "long-session" => Some(ConflictKind::LongSession),
+            _ => None,
+        }
+    }
+
+    pub fn severity(&self) -> ConflictSeverity {
+        match self {
+            ConflictKind::CalendarOverlap
+            | ConflictKind::SleepWindow
+            | ConflictKind::DeadlineRisk
+            | ConflictKind::PastDue => ConflictSeverity::High,
+            ConflictKind::BeforePlannedStart | ConflictKind::DailyOverload => {
+                ConflictSeverity::Medium
+            }
+            ConflictKind::SplitTask | ConflictKind::LongSession => ConflictSeverity::Low,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct PlanOption {
@@ -119,6 +268,8 @@ pub struct PlanOption {
     pub score: f64,
     pub is_fallback: bool,
     pub blocks: Vec<TimeBlockCandidate>,
+    #[serde(default)]
+    pub breaks: Vec<BreakBlock>,
     pub rationale: Vec<PlanRationaleStep>,
     pub conflicts: Vec<ScheduleConflict>,
     pub risk_notes: Vec<String>,
@@ -151,21 +302,21 @@ impl ScheduleOptimizer {
             return Err(AppError::validation("没有可用于规划的任务"));
         }
 
-        let parsed_windows = self.prepare_windows(&tasks, &constraints)?;
+        let parsed_windows = self.prepare_windows(&tasks, &constraints, &preferences)?;
         let planning_start = parsed_windows
             .first()
             .map(|w| w.start)
             .ok_or_else(|| AppError::validation("未找到可用时间窗口"))?;
 
         let mut variants = vec![PlanVariant::DeadlineFirst, PlanVariant::PriorityFirst];
-        if preferences.focus_start_minute.is_some() || preferences.focus_end_minute.is_some() {
+        if preferences.has_focus_window() {
             variants.push(PlanVariant::FocusAligned);
         }
 
         let mut options = Vec::new();
         for (idx, variant) in variants.iter().enumerate() {
             let plan_id = Uuid::new_v4().to_string();
-            let (blocks, rationale, risk_notes, fallback) = self.build_blocks_for_variant(
+            let (blocks, breaks, rationale, risk_notes, fallback) = self.build_blocks_for_variant(
                 &tasks,
                 variant,
                 &parsed_windows,
@@ -188,6 +339,7 @@ impl ScheduleOptimizer {
                 score,
                 is_fallback: fallback,
                 blocks,
+                breaks,
                 rationale,
                 conflicts,
                 risk_notes,
@@ -211,6 +363,7 @@ impl ScheduleOptimizer {
         preferences: &SchedulingPreferences,
     ) -> AppResult<(
         Vec<TimeBlockCandidate>,
+        Vec<BreakBlock>,
         Vec<PlanRationaleStep>,
         Vec<String>,
         bool,
@@ -224,9 +377,14 @@ impl ScheduleOptimizer {
         });
 
         let mut blocks = Vec::new();
+        let mut breaks = Vec::new();
         let mut risk_notes = Vec::new();
         let mut fallback = false;
-        let buffer_minutes = preferences.buffer_minutes_between_blocks.max(0);
+        let break_after = preferences
+            .break_after_focus_minutes
+            .filter(|limit| *limit > 0 && preferences.break_minutes > 0);
+        let mut focus_streak = 0;
+        let mut last_focus_end: Option<DateTime<FixedOffset>> = None;
 
         let mut cursor_window_idx = 0;
         let mut cursor_time = planning_start;
@@ -264,7 +422,8 @@ impl ScheduleOptimizer {
                 if cursor_time >= current_window.end {
                     cursor_window_idx += 1;
                     if cursor_window_idx < windows.len() {
-                        cursor_time = windows[cursor_window_idx].start;
+                        // Never move the cursor back past a buffer or an earliest start
+                        cursor_time = cursor_time.max(windows[cursor_window_idx].start);
                         continue;
                     } else {
                         fallback = true;
@@ -280,7 +439,7 @@ impl ScheduleOptimizer {
                 if available_minutes <= 0 {
                     cursor_window_idx += 1;
                     if cursor_window_idx < windows.len() {
-                        cursor_time = windows[cursor_window_idx].start;
+                        cursor_time = cursor_time.max(windows[cursor_window_idx].start);
                         continue;
                     } else {
                         fallback = true;
@@ -288,17 +447,50 @@ impl ScheduleOptimizer {
                     }
                 }
 
-                let block_minutes = available_minutes.min(remaining);
+                let mut block_minutes = available_minutes.min(remaining);
+                if let Some(limit) = break_after {
+                    // Only a gap no longer than the buffer keeps the streak going
+                    let continues = last_focus_end
+                        .map(|end| {
+                            (aligned_start - end).num_minutes()
+                                <= preferences.buffer_minutes_on(weekday_of(aligned_start))
+                        })
+                        .unwrap_or(false);
+                    if !continues {
+                        focus_streak = 0;
+                    }
+
+                    if focus_streak >= limit {
+                        let break_end =
+                            schedule_utils::add_minutes(aligned_start, preferences.break_minutes)?
+                                .min(current_window.end);
+                        breaks.push(BreakBlock {
+                            id: Uuid::new_v4().to_string(),
+                            start_at: schedule_utils::format_datetime(aligned_start),
+                            end_at: schedule_utils::format_datetime(break_end),
+                            duration_minutes: schedule_utils::duration_minutes(
+                                aligned_start,
+                                break_end,
+                            )?,
+                        });
+                        focus_streak = 0;
+                        last_focus_end = None;
+                        cursor_time = break_end;
+                        continue;
+                    }
+
+                    block_minutes = block_minutes.min(limit - focus_streak);
+                }
                 let end_time = schedule_utils::add_minutes(aligned_start, block_minutes)?;
 
                 let mut flags = Vec::new();
                 if !first_block {
-                    flags.push("split-task".to_string());
+                    flags.push(ConflictKind::SplitTask.as_str().to_string());
                 }
 
                 if let Some(due) = due_at {
                     if end_time > due {
-                        flags.push("deadline-risk".to_string());
+                        flags.push(ConflictKind::DeadlineRisk.as_str().to_string());
                         risk_notes.push(format!(
                             "任务 {} 规划结束时间超出截止时间 {}",
                             task.title,
@@ -314,7 +506,7 @@ impl ScheduleOptimizer {
                 }
 
                 if !preferences.prefer_compact_schedule && block_minutes > 120 {
-                    flags.push("long-session".to_string());
+                    flags.push(ConflictKind::LongSession.as_str().to_string());
                 }
 
                 let block_id = Uuid::new_v4().to_string();
@@ -328,12 +520,22 @@ impl ScheduleOptimizer {
                     } else {
                         "fixed".to_string()
                     }),
-                    confidence: self.estimate_confidence(block_minutes, &preferences, &flags),
+                    confidence: self.estimate_confidence(
+                        block_minutes,
+                        preferences.buffer_minutes_on(weekday_of(aligned_start)),
+                        &flags,
+                    ),
                     conflict_flags: flags,
+                    prep_note: None,
                 });
 
                 remaining -= block_minutes;
-                cursor_time = schedule_utils::add_minutes(end_time, buffer_minutes)?;
+                focus_streak += block_minutes;
+                last_focus_end = Some(end_time);
+                cursor_time = schedule_utils::add_minutes(
+                    end_time,
+                    preferences.buffer_minutes_on(weekday_of(end_time)),
+                )?;
                 first_block = false;
 
                 if remaining > 0 {
@@ -357,10 +559,18 @@ impl ScheduleOptimizer {
         rationale.push(PlanRationaleStep {
             step: rationale.len() + 1,
             thought: "完成时间块生成".to_string(),
-            result: Some(format!("共生成 {} 个时间块", blocks.len())),
+            result: Some(if breaks.is_empty() {
+                format!("共生成 {} 个时间块", blocks.len())
+            } else {
+                format!(
+                    "共生成 {} 个时间块，穿插 {} 次休息",
+                    blocks.len(),
+                    breaks.len()
+                )
+            }),
         });
 
-        Ok((blocks, rationale, risk_notes, fallback))
+        Ok((blocks, breaks, rationale, risk_notes, fallback))
     }
 
     fn order_tasks(
@@ -377,9 +587,13 @@ impl ScheduleOptimizer {
             }
             PlanVariant::PriorityFirst => {
                 tasks.sort_by(|a, b| {
-                    b.priority_weight
-                        .partial_cmp(&a.priority_weight)
-                        .unwrap_or(Ordering::Equal)
+                    quadrant_rank(a)
+                        .cmp(&quadrant_rank(b))
+                        .then_with(|| {
+                            b.priority_weight
+                                .partial_cmp(&a.priority_weight)
+                                .unwrap_or(Ordering::Equal)
+                        })
                         .then_with(|| compare_datetime_opt(&a.due_at, &b.due_at))
                         .then_with(|| self.tie_breaker(a, b))
                 });
@@ -404,6 +618,7 @@ impl ScheduleOptimizer {
         &self,
         tasks: &[SchedulableTask],
         constraints: &ScheduleConstraints,
+        preferences: &SchedulingPreferences,
     ) -> AppResult<Vec<ParsedWindow>> {
         let mut windows = Vec::new();
         for window in &constraints.available_windows {
@@ -449,8 +664,15 @@ impl ScheduleOptimizer {
             }
         }
 
-        windows.sort_by_key(|w| w.start);
-        Ok(windows)
+        if let Some(schedule) = preferences
+            .sleep_schedule
+            .as_ref()
+            .filter(|schedule| schedule.enabled)
+        {
+            windows = exclude_sleep(windows, schedule);
+        }
+
+        Ok(intervals::normalize(windows))
     }
 
     fn score_option(
@@ -482,21 +704,27 @@ impl ScheduleOptimizer {
             })
             .sum();
 
-        let focus_bonus = if let (Some(start), Some(end)) =
-            (preferences.focus_start_minute, preferences.focus_end_minute)
-        {
-            let preferred_range = start..end;
+        let focus_bonus = if preferences.has_focus_window() {
             let mut aligned_minutes = 0.0;
             let mut total_minutes = 0.0;
             for block in blocks {
                 let start_time = schedule_utils::parse_datetime(&block.start_at)?;
                 let end_time = schedule_utils::parse_datetime(&block.end_at)?;
+                // Blocks on days without a focus window neither help nor hurt
+                let Some((start, end)) = preferences.focus_window_on(weekday_of(start_time)) else {
+                    continue;
+                };
                 let block_minutes = schedule_utils::duration_minutes(start_time, end_time)? as f64;
                 total_minutes += block_minutes;
+                let preferred_range = Interval::new(start, end);
 
                 let start_minute = schedule_utils::midnight_minutes_of(start_time) as u32;
                 let end_minute = schedule_utils::midnight_minutes_of(end_time) as u32;
-                let overlap = overlap_in_minutes(start_minute, end_minute, &preferred_range);
+                let overlap = Interval::new(start_minute, end_minute)
+                    .zip(preferred_range)
+                    .and_then(|(block, preferred)| block.intersection(&preferred))
+                    .map(|common| common.end - common.start)
+                    .unwrap_or(0);
                 aligned_minutes += overlap as f64;
             }
 
@@ -526,29 +754,38 @@ impl ScheduleOptimizer {
             base -= compact_penalty * 0.05;
         }
 
+        if preferences.context_switch_weight > 0.0 {
+            base += preferences.context_switch_weight * context_switch_score(blocks, tasks)?;
+        }
+
         Ok(base.max(0.0))
     }
 
     fn estimate_confidence(
         &self,
         block_minutes: i64,
-        preferences: &SchedulingPreferences,
+        buffer_minutes: i64,
         flags: &[String],
     ) -> f32 {
         let mut confidence: f32 = 0.85;
         if block_minutes > 120 {
             confidence -= 0.1;
         }
-        if preferences.buffer_minutes_between_blocks < 10 {
+        if buffer_minutes < 10 {
             confidence -= 0.05;
         }
-        if flags.iter().any(|f| f == "deadline-risk") {
+        if flags
+            .iter()
+            .any(|f| f == ConflictKind::DeadlineRisk.as_str())
+        {
             confidence -= 0.2;
         }
         confidence.clamp(0.0, 1.0)
     }
 }
 
+/// Recurring events are expanded over the span of `blocks`, so callers can
+/// pass a weekly commitment once instead of every occurrence.
 pub fn detect_conflicts(
     blocks: &[TimeBlockCandidate],
     existing_events: &[ExistingEvent],
@@ -556,24 +793,41 @@ pub fn detect_conflicts(
 ) -> AppResult<Vec<ScheduleConflict>> {
     let mut conflicts = Vec::new();
 
+    let mut span: Option<(DateTime<FixedOffset>, DateTime<FixedOffset>)> = None;
+    for block in blocks {
+        let start = schedule_utils::parse_datetime(&block.start_at)?;
+        let end = schedule_utils::parse_datetime(&block.end_at)?;
+        span = Some(match span {
+            Some((low, high)) => (low.min(start), high.max(end)),
+            None => (start, end),
+        });
+    }
+    let existing_events = match span {
+        Some((window_start, window_end)) => {
+            expand_recurring_events(existing_events, window_start, window_end)?
+        }
+        None => Vec::new(),
+    };
+
     for block in blocks {
         let block_start = schedule_utils::parse_datetime(&block.start_at)?;
         let block_end = schedule_utils::parse_datetime(&block.end_at)?;
 
-        for event in existing_events {
+        for event in &existing_events {
             let event_start = schedule_utils::parse_datetime(&event.start_at)?;
             let event_end = schedule_utils::parse_datetime(&event.end_at)?;
 
             if schedule_utils::overlaps(block_start, block_end, event_start, event_end)? {
                 conflicts.push(ScheduleConflict {
-                    conflict_type: "calendar-overlap".to_string(),
-                    severity: ConflictSeverity::High,
+                    conflict_type: ConflictKind::CalendarOverlap.as_str().to_string(),
+                    severity: ConflictKind::CalendarOverlap.severity(),
                     message: format!(
                         "时间块 [{} - {}] 与事件 {} 冲突",
                         block.start_at, block.end_at, event.id
                     ),
                     related_block_id: Some(block.id.clone()),
                     related_event_id: Some(event.id.clone()),
+                    date: None,
                 });
             }
         }
@@ -592,11 +846,12 @@ pub fn detect_conflicts(
         for (day, minutes) in day_totals {
             if minutes > limit {
                 conflicts.push(ScheduleConflict {
-                    conflict_type: "daily-overload".to_string(),
-                    severity: ConflictSeverity::Medium,
+                    conflict_type: ConflictKind::DailyOverload.as_str().to_string(),
+                    severity: ConflictKind::DailyOverload.severity(),
                     message: format!("{} 当日排程 {} 分钟，超过上限 {} 分钟", day, minutes, limit),
                     related_block_id: None,
                     related_event_id: None,
+                    date: Some(day.to_string()),
                 });
             }
         }
@@ -615,6 +870,124 @@ pub fn detect_conflicts(
     Ok(conflicts)
 }
 
+/// Upper bound on occurrences expanded per recurring event, so a daily rule
+/// against a far-off window cannot run away.
+const MAX_RECURRING_OCCURRENCES: usize = 1000;
+
+/// Replaces each recurring event with its occurrences that overlap
+/// `[window_start, window_end)`. Occurrence ids get the date appended so
+/// conflicts still point at a specific occurrence. Non-recurring events are
+/// passed through unchanged.
+pub fn expand_recurring_events(
+    events: &[ExistingEvent],
+    window_start: DateTime<FixedOffset>,
+    window_end: DateTime<FixedOffset>,
+) -> AppResult<Vec<ExistingEvent>> {
+    let mut expanded = Vec::new();
+
+    for event in events {
+        let Some(raw_rule) = event
+            .recurrence_rule
+            .as_deref()
+            .filter(|raw| !raw.trim().is_empty())
+        else {
+            expanded.push(event.clone());
+            continue;
+        };
+
+        let rule = RRuleParser::parse(raw_rule)?;
+        let first_start = schedule_utils::parse_datetime(&event.start_at)?;
+        let first_end = schedule_utils::parse_datetime(&event.end_at)?;
+        schedule_utils::ensure_window(first_start, first_end)?;
+        let duration = first_end - first_start;
+        let offset = *first_start.offset();
+        let time_of_day = first_start.time();
+        let last_date = window_end.with_timezone(&offset).date_naive();
+        let max_count = rule
+            .count
+            .map(|count| count as usize)
+            .unwrap_or(MAX_RECURRING_OCCURRENCES)
+            .min(MAX_RECURRING_OCCURRENCES);
+
+        let mut date = first_start.date_naive();
+        for _ in 0..max_count {
+            let LocalResult::Single(start) =
+                offset.from_local_datetime(&date.and_time(time_of_day))
+            else {
+                break;
+            };
+            if rule
+                .until
+                .is_some_and(|until| start.with_timezone(&Utc) > until)
+            {
+                break;
+            }
+            let end = start + duration;
+            if end > window_start && start < window_end {
+                expanded.push(ExistingEvent {
+                    id: format!("{}@{}", event.id, date),
+                    start_at: schedule_utils::format_datetime(start),
+                    end_at: schedule_utils::format_datetime(end),
+                    event_type: event.event_type.clone(),
+                    recurrence_rule: None,
+                });
+            }
+
+            let from = Utc.from_utc_datetime(&date.and_time(NaiveTime::MIN));
+            match InstanceGenerator::calculate_next_occurrence(&rule, from)? {
+                Some(next) if next.date_naive() > date && next.date_naive() <= last_date => {
+                    date = next.date_naive();
+                }
+                _ => break,
+            }
+        }
+    }
+
+    Ok(expanded)
+}
+
+/// Flags blocks that overlap the sleep schedule, e.g. in AI-generated plans
+/// that did not go through window preparation.
+pub fn detect_sleep_conflicts(
+    blocks: &[TimeBlockCandidate],
+    schedule: &SleepSchedule,
+) -> AppResult<Vec<ScheduleConflict>> {
+    let mut conflicts = Vec::new();
+    if !schedule.enabled {
+        return Ok(conflicts);
+    }
+
+    for block in blocks {
+        let block_start = schedule_utils::parse_datetime(&block.start_at)?;
+        let block_end = schedule_utils::parse_datetime(&block.end_at)?;
+
+        if !schedule.sleep_intervals(block_start, block_end).is_empty() {
+            conflicts.push(ScheduleConflict {
+                conflict_type: ConflictKind::SleepWindow.as_str().to_string(),
+                severity: ConflictKind::SleepWindow.severity(),
+                message: format!(
+                    "时间块 [{} - {}] 落在睡眠时间内",
+                    block.start_at, block.end_at
+                ),
+                related_block_id: Some(block.id.clone()),
+                related_event_id: None,
+                date: None,
+            });
+        }
+    }
+
+    Ok(conflicts)
+}
+
+fn exclude_sleep(windows: Vec<ParsedWindow>, schedule: &SleepSchedule) -> Vec<ParsedWindow> {
+    let sleep: Vec<ParsedWindow> = windows
+        .iter()
+        .flat_map(|window| schedule.sleep_intervals(window.start, window.end))
+        .filter_map(|(bed, wake)| Interval::new(bed, wake))
+        .collect();
+    intervals::subtract(&windows, &sleep)
+}
+
 fn compare_datetime_opt(a: &Option<String>, b: &Option<String>) -> Ordering {
     match (a, b) {
         (Some(a), Some(b)) => match (
@@ -630,6 +1003,49 @@ fn compare_datetime_opt(a: &Option<String>, b: &Option<String>) -> Ordering {
     }
 }
 
+/// Score lost for each switch to a different task between back-to-back
+/// blocks on the same day, at a context switch weight of 1.
+const CONTEXT_SWITCH_PENALTY: f64 = 4.0;
+/// Score gained when the next block stays within the same project.
+const SAME_PROJECT_BONUS: f64 = 2.0;
+
+/// Bonus for same-project neighbours minus the penalty for task switches,
+/// taken over adjacent blocks on the same day.
+fn context_switch_score(
+    blocks: &[TimeBlockCandidate],
+    tasks: &[SchedulableTask],
+) -> AppResult<f64> {
+    let project_of = |task_id: &str| {
+        tasks
+            .iter()
+            .find(|task| task.id == task_id)
+            .and_then(|task| task.project_id.as_deref())
+    };
+    let mut ordered = blocks
+        .iter()
+        .map(|block| Ok((schedule_utils::parse_datetime(&block.start_at)?, block)))
+        .collect::<AppResult<Vec<_>>>()?;
+    ordered.sort_by_key(|(start, _)| *start);
+
+    let mut score = 0.0;
+    for pair in ordered.windows(2) {
+        let ((prev_start, prev), (next_start, next)) = (pair[0], pair[1]);
+        if prev_start.date_naive() != next_start.date_naive() || prev.task_id == next.task_id {
+            continue;
+        }
+        match (project_of(&prev.task_id), project_of(&next.task_id)) {
+            (Some(a), Some(b)) if a == b => score += SAME_PROJECT_BONUS,
+            _ => score -= CONTEXT_SWITCH_PENALTY,
+        }
+    }
+    Ok(score)
+}
+
+/// Unclassified tasks sort after every quadrant.
+fn quadrant_rank(task: &SchedulableTask) -> u8 {
+    task.quadrant.map_or(u8::MAX, |quadrant| quadrant as u8)
+}
+
 fn earliest_task_time(tasks: &[SchedulableTask]) -> AppResult<Option<DateTime<FixedOffset>>> {
     let mut earliest: Option<DateTime<FixedOffset>> = None;
     for task in tasks {
@@ -660,21 +1076,9 @@ fn current_fixed_offset(seed: u64) -> DateTime<FixedOffset> {
     base + Duration::seconds(adjustment)
 }
 
-fn overlap_in_minutes(start: u32, end: u32, range: &std::ops::Range<u32>) -> u32 {
-    if start >= range.end || end <= range.start {
-        0
-    } else {
-        let effective_start = start.max(range.start);
-        let effective_end = end.min(range.end);
-        effective_end.saturating_sub(effective_start)
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
-struct ParsedWindow {
-    start: DateTime<FixedOffset>,
-    end: DateTime<FixedOffset>,
-}
+/// Windows are plain intervals, so overlapping or touching windows from the
+/// caller merge instead of double-booking the shared time.
+type ParsedWindow = Interval<DateTime<FixedOffset>>;
 
 impl PlanVariant {
     fn label(&self) -> String {
@@ -721,6 +1125,7 @@ mod tests {
     use super::*;
     use crate::error::AppResult;
     use chrono::{Duration, NaiveDate, TimeZone};
+    use proptest::prelude::*;
 
     fn dt(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<FixedOffset> {
         let offset = FixedOffset::east_opt(0).expect("offset");
@@ -750,6 +1155,8 @@ mod tests {
                 estimated_minutes: Some(150),
                 priority_weight: 0.9,
                 is_parallelizable: false,
+                quadrant: None,
+                project_id: None,
             },
             SchedulableTask {
                 id: "task-2".to_string(),
@@ -759,6 +1166,8 @@ mod tests {
                 estimated_minutes: Some(120),
                 priority_weight: 0.7,
                 is_parallelizable: true,
+                quadrant: None,
+                project_id: None,
             },
             SchedulableTask {
                 id: "task-3".to_string(),
@@ -768,6 +1177,8 @@ mod tests {
                 estimated_minutes: Some(120),
                 priority_weight: 0.5,
                 is_parallelizable: false,
+                quadrant: None,
+                project_id: None,
             },
         ];
 
@@ -781,6 +1192,7 @@ mod tests {
                 start_at: iso(2025, 5, 1, 10, 0),
                 end_at: iso(2025, 5, 1, 11, 0),
                 event_type: Some("meeting".to_string()),
+                recurrence_rule: None,
             }],
             max_focus_minutes_per_day: Some(210),
             ..Default::default()
@@ -791,6 +1203,7 @@ mod tests {
             focus_end_minute: Some(12 * 60 + 30),
             buffer_minutes_between_blocks: 15,
             prefer_compact_schedule: true,
+            ..Default::default()
         };
 
         let options = optimizer.generate_plan_options(tasks, constraints, preferences)?;
@@ -822,6 +1235,7 @@ mod tests {
             flexibility: None,
             confidence: 0.8,
             conflict_flags: Vec::new(),
+            prep_note: None,
         };
 
         let overlapping = ExistingEvent {
@@ -829,16 +1243,423 @@ mod tests {
             start_at: schedule_utils::format_datetime(start + Duration::minutes(30)),
             end_at: schedule_utils::format_datetime(start + Duration::minutes(90)),
             event_type: None,
+            recurrence_rule: None,
         };
 
         let conflicts = detect_conflicts(&[block.clone()], &[overlapping], Some(60))?;
         assert_eq!(conflicts.len(), 2);
         assert_eq!(conflicts[0].conflict_type, "calendar-overlap");
         assert_eq!(conflicts[0].severity, ConflictSeverity::High);
-        assert!(conflicts
+        let overload = conflicts
             .iter()
-            .any(|conflict| conflict.conflict_type == "daily-overload"));
+            .find(|conflict| conflict.conflict_type == "daily-overload")
+            .expect("daily overload");
+        assert_eq!(overload.date.as_deref(), Some("2025-05-02"));
+        assert_eq!(
+            ConflictKind::from_flag(&overload.conflict_type),
+            Some(ConflictKind::DailyOverload)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn detect_conflicts_expands_recurring_events() -> AppResult<()> {
+        let block = |id: &str, start: DateTime<FixedOffset>| TimeBlockCandidate {
+            id: id.to_string(),
+            task_id: "task-1".to_string(),
+            start_at: schedule_utils::format_datetime(start),
+            end_at: schedule_utils::format_datetime(start + Duration::minutes(60)),
+            flexibility: None,
+            confidence: 0.8,
+            conflict_flags: Vec::new(),
+            prep_note: None,
+        };
+        // Thursday 2025-05-01 is the first standup; the plan covers the next two weeks.
+        let standup = ExistingEvent {
+            id: "standup".to_string(),
+            start_at: iso(2025, 5, 1, 9, 30),
+            end_at: iso(2025, 5, 1, 10, 0),
+            event_type: Some("standup".to_string()),
+            recurrence_rule: Some("FREQ=WEEKLY;BYDAY=TH".to_string()),
+        };
+        let blocks = vec![
+            block("on-standup", dt(2025, 5, 8, 9, 0)),
+            block("after-standup", dt(2025, 5, 8, 10, 0)),
+            block("other-day", dt(2025, 5, 14, 9, 0)),
+            block("next-standup", dt(2025, 5, 15, 9, 45)),
+        ];
+
+        let conflicts = detect_conflicts(&blocks, &[standup.clone()], None)?;
+        let mut hits: Vec<(String, String)> = conflicts
+            .iter()
+            .map(|conflict| {
+                (
+                    conflict.related_block_id.clone().unwrap_or_default(),
+                    conflict.related_event_id.clone().unwrap_or_default(),
+                )
+            })
+            .collect();
+        hits.sort();
+        assert_eq!(
+            hits,
+            vec![
+                ("next-standup".to_string(), "standup@2025-05-15".to_string()),
+                ("on-standup".to_string(), "standup@2025-05-08".to_string()),
+            ]
+        );
+
+        let limited = ExistingEvent {
+            recurrence_rule: Some("FREQ=WEEKLY;COUNT=2".to_string()),
+            ..standup
+        };
+        let conflicts = detect_conflicts(&blocks, &[limited], None)?;
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(
+            conflicts[0].related_event_id.as_deref(),
+            Some("standup@2025-05-08")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn priority_first_takes_quadrants_before_weight() -> AppResult<()> {
+        let optimizer = ScheduleOptimizer::new(Some(1));
+        let task = |id: &str, weight: f32, quadrant: Option<MatrixQuadrant>| SchedulableTask {
+            id: id.to_string(),
+            title: id.to_string(),
+            due_at: None,
+            earliest_start_at: None,
+            estimated_minutes: Some(30),
+            priority_weight: weight,
+            is_parallelizable: false,
+            quadrant,
+            project_id: None,
+        };
+        let tasks = vec![
+            task("unclassified", 1.0, None),
+            task("schedule", 0.9, Some(MatrixQuadrant::Schedule)),
+            task("do-first", 0.5, Some(MatrixQuadrant::DoFirst)),
+            task("eliminate", 0.2, Some(MatrixQuadrant::Eliminate)),
+            task("schedule-high", 1.0, Some(MatrixQuadrant::Schedule)),
+        ];
 
+        let ordered = optimizer.order_tasks(&tasks, &PlanVariant::PriorityFirst)?;
+        let ids: Vec<_> = ordered.iter().map(|task| task.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            [
+                "do-first",
+                "schedule-high",
+                "schedule",
+                "eliminate",
+                "unclassified"
+            ]
+        );
         Ok(())
     }
+
+    #[test]
+    fn sleep_schedule_is_excluded_from_windows() -> AppResult<()> {
+        let optimizer = ScheduleOptimizer::new(Some(3));
+        let tasks = vec![SchedulableTask {
+            id: "task-1".to_string(),
+            title: "Migration".to_string(),
+            due_at: None,
+            earliest_start_at: None,
+            estimated_minutes: Some(300),
+            priority_weight: 0.8,
+            is_parallelizable: false,
+            quadrant: None,
+            project_id: None,
+        }];
+        let constraints = ScheduleConstraints {
+            available_windows: vec![TimeWindow {
+                start_at: iso(2025, 5, 1, 20, 0),
+                end_at: iso(2025, 5, 2, 12, 0),
+            }],
+            ..Default::default()
+        };
+        let schedule = SleepSchedule {
+            enabled: true,
+            ..Default::default()
+        };
+        let preferences = SchedulingPreferences {
+            sleep_schedule: Some(schedule.clone()),
+            ..Default::default()
+        };
+
+        let options = optimizer.generate_plan_options(tasks, constraints, preferences)?;
+        for option in &options {
+            assert!(detect_sleep_conflicts(&option.blocks, &schedule)?.is_empty());
+            let last = option.blocks.last().expect("blocks scheduled");
+            assert_eq!(last.start_at, iso(2025, 5, 2, 7, 0));
+            assert_eq!(last.end_at, iso(2025, 5, 2, 9, 0));
+        }
+
+        let late_block = TimeBlockCandidate {
+            id: "block-late".to_string(),
+            task_id: "task-1".to_string(),
+            start_at: iso(2025, 5, 1, 22, 30),
+            end_at: iso(2025, 5, 1, 23, 30),
+            flexibility: None,
+            confidence: 0.7,
+            conflict_flags: Vec::new(),
+            prep_note: None,
+        };
+        let conflicts = detect_sleep_conflicts(&[late_block], &schedule)?;
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].conflict_type, "sleep-window");
+
+        Ok(())
+    }
+
+    #[test]
+    fn inserts_breaks_after_long_focus_streaks() -> AppResult<()> {
+        let optimizer = ScheduleOptimizer::new(Some(11));
+        let tasks = vec![SchedulableTask {
+            id: "task-1".to_string(),
+            title: "Quarterly report".to_string(),
+            due_at: None,
+            earliest_start_at: None,
+            estimated_minutes: Some(180),
+            priority_weight: 0.8,
+            is_parallelizable: false,
+            quadrant: None,
+            project_id: None,
+        }];
+        let constraints = ScheduleConstraints {
+            available_windows: vec![TimeWindow {
+                start_at: iso(2025, 5, 1, 9, 0),
+                end_at: iso(2025, 5, 1, 17, 0),
+            }],
+            max_focus_minutes_per_day: Some(180),
+            ..Default::default()
+        };
+        let preferences = SchedulingPreferences {
+            buffer_minutes_between_blocks: 5,
+            break_after_focus_minutes: Some(90),
+            break_minutes: 15,
+            ..Default::default()
+        };
+
+        let options = optimizer.generate_plan_options(tasks, constraints, preferences)?;
+        let option = &options[0];
+
+        assert_eq!(option.blocks.len(), 2);
+        assert_eq!(option.blocks[0].end_at, iso(2025, 5, 1, 10, 30));
+        assert_eq!(option.breaks.len(), 1);
+        assert_eq!(option.breaks[0].start_at, iso(2025, 5, 1, 10, 35));
+        assert_eq!(option.breaks[0].duration_minutes, 15);
+        assert_eq!(option.blocks[1].start_at, iso(2025, 5, 1, 10, 50));
+        // Break minutes do not count towards the daily focus limit
+        assert!(option
+            .conflicts
+            .iter()
+            .all(|conflict| conflict.conflict_type != "daily-overload"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn context_switch_weight_rewards_grouped_projects() -> AppResult<()> {
+        let optimizer = ScheduleOptimizer::new(Some(5));
+        let task = |id: &str, project: &str| SchedulableTask {
+            id: id.to_string(),
+            title: id.to_string(),
+            due_at: None,
+            earliest_start_at: None,
+            estimated_minutes: Some(60),
+            priority_weight: 0.5,
+            is_parallelizable: false,
+            quadrant: None,
+            project_id: Some(project.to_string()),
+        };
+        let tasks = vec![
+            task("a", "thesis"),
+            task("b", "thesis"),
+            task("c", "chores"),
+        ];
+        let plan = |ids: [&str; 3]| -> Vec<TimeBlockCandidate> {
+            ids.iter()
+                .enumerate()
+                .map(|(hour, id)| block_at(id, dt(2025, 5, 1, 9 + hour as u32, 0), 60))
+                .collect()
+        };
+        let grouped = plan(["a", "b", "c"]);
+        let interleaved = plan(["a", "c", "b"]);
+
+        assert_eq!(context_switch_score(&grouped, &tasks)?, -2.0);
+        assert_eq!(context_switch_score(&interleaved, &tasks)?, -8.0);
+
+        let score = |blocks: &[TimeBlockCandidate], weight: f64| {
+            let preferences = SchedulingPreferences {
+                context_switch_weight: weight,
+                ..Default::default()
+            };
+            optimizer.score_option(blocks, &tasks, &preferences, &[])
+        };
+        assert_eq!(score(&grouped, 0.0)?, score(&interleaved, 0.0)?);
+        assert!(score(&grouped, 2.0)? > score(&interleaved, 2.0)?);
+        Ok(())
+    }
+
+    /// Minutes after 2025-05-01 00:00 UTC, in quarter hours so windows,
+    /// buffers and estimates line up the way real plans do.
+    fn at_quarter(quarters: u32) -> DateTime<FixedOffset> {
+        dt(2025, 5, 1, 0, 0) + Duration::minutes(quarters as i64 * 15)
+    }
+
+    fn block_at(id: &str, start: DateTime<FixedOffset>, minutes: i64) -> TimeBlockCandidate {
+        TimeBlockCandidate {
+            id: id.to_string(),
+            task_id: id.to_string(),
+            start_at: schedule_utils::format_datetime(start),
+            end_at: schedule_utils::format_datetime(start + Duration::minutes(minutes)),
+            flexibility: None,
+            confidence: 0.8,
+            conflict_flags: Vec::new(),
+            prep_note: None,
+        }
+    }
+
+    fn event_at(id: &str, start: DateTime<FixedOffset>, minutes: i64) -> ExistingEvent {
+        ExistingEvent {
+            id: id.to_string(),
+            start_at: schedule_utils::format_datetime(start),
+            end_at: schedule_utils::format_datetime(start + Duration::minutes(minutes)),
+            event_type: None,
+            recurrence_rule: None,
+        }
+    }
+
+    fn plan_inputs() -> impl Strategy<
+        Value = (
+            Vec<(u32, u32)>,
+            Vec<(i64, Option<u32>, Option<u32>)>,
+            i64,
+            Option<i64>,
+        ),
+    > {
+        (
+            prop::collection::vec((0u32..12 * 4, 1u32..5 * 4), 1..5),
+            prop::collection::vec(
+                (
+                    1i64..300,
+                    prop::option::of(0u32..16 * 4),
+                    prop::option::of(0u32..16 * 4),
+                ),
+                1..6,
+            ),
+            0i64..45,
+            prop::option::of(30i64..120),
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn generated_blocks_stay_inside_windows_and_keep_buffers(
+            (windows, tasks, buffer, break_after) in plan_inputs()
+        ) {
+            let available: Vec<ParsedWindow> = windows
+                .iter()
+                .map(|(start, len)| ParsedWindow {
+                    start: at_quarter(*start),
+                    end: at_quarter(start + len),
+                })
+                .collect();
+            let tasks: Vec<SchedulableTask> = tasks
+                .iter()
+                .enumerate()
+                .map(|(idx, (minutes, earliest, due))| SchedulableTask {
+                    id: format!("task-{idx}"),
+                    title: format!("Task {idx}"),
+                    due_at: due
+                        .map(|quarters| schedule_utils::format_datetime(at_quarter(quarters))),
+                    earliest_start_at: earliest
+                        .map(|quarters| schedule_utils::format_datetime(at_quarter(quarters))),
+                    estimated_minutes: Some(*minutes),
+                    priority_weight: 0.5,
+                    is_parallelizable: false,
+                    quadrant: None,
+                    project_id: None,
+                })
+                .collect();
+            let constraints = ScheduleConstraints {
+                available_windows: available
+                    .iter()
+                    .map(|window| TimeWindow {
+                        start_at: schedule_utils::format_datetime(window.start),
+                        end_at: schedule_utils::format_datetime(window.end),
+                    })
+                    .collect(),
+                ..Default::default()
+            };
+            let preferences = SchedulingPreferences {
+                focus_start_minute: Some(9 * 60),
+                focus_end_minute: Some(12 * 60),
+                buffer_minutes_between_blocks: buffer,
+                break_after_focus_minutes: break_after,
+                break_minutes: 10,
+                ..Default::default()
+            };
+
+            let options = ScheduleOptimizer::new(Some(7))
+                .generate_plan_options(tasks.clone(), constraints, preferences)
+                .expect("plan options");
+            for option in &options {
+                let mut focus = Vec::new();
+                for block in &option.blocks {
+                    let start = schedule_utils::parse_datetime(&block.start_at).unwrap();
+                    let end = schedule_utils::parse_datetime(&block.end_at).unwrap();
+                    prop_assert!(start < end);
+                    let span = ParsedWindow { start, end };
+                    // Overlapping windows merge, so a block may span two of them
+                    prop_assert!(
+                        intervals::subtract(&[span], &available).is_empty(),
+                        "block {:?} is outside every window", block
+                    );
+                    let task = tasks.iter().find(|task| task.id == block.task_id).unwrap();
+                    if let Some(earliest) = &task.earliest_start_at {
+                        prop_assert!(start >= schedule_utils::parse_datetime(earliest).unwrap());
+                    }
+                    focus.push(span);
+                }
+                for pair in focus.windows(2) {
+                    prop_assert!(
+                        (pair[1].start - pair[0].end).num_minutes() >= buffer,
+                        "blocks {:?} and {:?} are closer than the {} minute buffer",
+                        pair[0], pair[1], buffer
+                    );
+                }
+                for rest in &option.breaks {
+                    let start = schedule_utils::parse_datetime(&rest.start_at).unwrap();
+                    let end = schedule_utils::parse_datetime(&rest.end_at).unwrap();
+                    let rest = ParsedWindow { start, end };
+                    prop_assert!(focus.iter().all(|block| !block.overlaps(&rest)));
+                }
+            }
+        }
+
+        #[test]
+        fn calendar_conflicts_are_symmetric(
+            a_start in 0u32..96,
+            a_len in 1i64..240,
+            b_start in 0u32..96,
+            b_len in 1i64..240,
+        ) {
+            let a = at_quarter(a_start);
+            let b = at_quarter(b_start);
+            let forward =
+                detect_conflicts(&[block_at("a", a, a_len)], &[event_at("b", b, b_len)], None)
+                    .expect("conflicts");
+            let backward =
+                detect_conflicts(&[block_at("b", b, b_len)], &[event_at("a", a, a_len)], None)
+                    .expect("conflicts");
+            let overlaps = a < b + Duration::minutes(b_len) && b < a + Duration::minutes(a_len);
+            prop_assert_eq!(forward.len(), backward.len());
+            prop_assert_eq!(forward.len(), usize::from(overlaps));
+        }
+    }
 }
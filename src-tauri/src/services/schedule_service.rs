@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::error::{AppError, AppResult};
 use crate::models::task::{TaskRecord, TaskUpdateInput};
+use crate::services::schedule_utils::intervals::Interval;
 use crate::services::task_service::TaskService;
 use tracing::{debug, info};
 
@@ -93,6 +94,11 @@ impl ScheduleService {
             task_type: Some("time_block".to_string()),
             ai: None,
             external_links: None,
+            project_id: None,
+            color: None,
+            icon: None,
+            delegated_to: None,
+            waiting_on: None,
         };
 
         let task_record = self.task_service.create_task(task_input)?;
@@ -226,34 +232,40 @@ impl ScheduleService {
         let end_date_parsed = NaiveDate::parse_from_str(end_date, "%Y-%m-%d")
             .map_err(|e| AppError::validation(format!("Invalid end date format: {}", e)))?;
 
+        // The range covers whole days: [start 00:00, day after end 00:00)
+        let range = Interval {
+            start: start_date_parsed.and_hms_opt(0, 0, 0).unwrap().and_utc(),
+            end: (end_date_parsed + Duration::days(1))
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+                .and_utc(),
+        };
+        let in_range = |instant: DateTime<Utc>| range.start <= instant && instant < range.end;
+
         // Check if the task's time range overlaps with the requested range
         if let (Some(start_at), Some(end_at)) = (&item.start_at, &item.end_at) {
             let task_start = DateTime::parse_from_rfc3339(start_at)
-                .map_err(|e| AppError::validation(format!("Invalid task start time: {}", e)))?;
+                .map_err(|e| AppError::validation(format!("Invalid task start time: {}", e)))?
+                .with_timezone(&Utc);
             let task_end = DateTime::parse_from_rfc3339(end_at)
-                .map_err(|e| AppError::validation(format!("Invalid task end time: {}", e)))?;
-
-            let range_start = start_date_parsed.and_hms_opt(0, 0, 0).unwrap().and_utc();
-            let range_end = end_date_parsed.and_hms_opt(23, 59, 59).unwrap().and_utc();
+                .map_err(|e| AppError::validation(format!("Invalid task end time: {}", e)))?
+                .with_timezone(&Utc);
 
-            // Check for overlap
-            Ok(task_start <= range_end && task_end >= range_start)
+            // A zero-length block is treated as an instant
+            Ok(match Interval::new(task_start, task_end) {
+                Some(task) => task.overlaps(&range),
+                None => in_range(task_start),
+            })
         } else if let Some(end_at) = &item.end_at {
             let task_due = DateTime::parse_from_rfc3339(end_at)
                 .map_err(|e| AppError::validation(format!("Invalid task due time: {}", e)))?;
 
-            let range_start = start_date_parsed.and_hms_opt(0, 0, 0).unwrap().and_utc();
-            let range_end = end_date_parsed.and_hms_opt(23, 59, 59).unwrap().and_utc();
-
-            Ok(task_due >= range_start && task_due <= range_end)
+            Ok(in_range(task_due.with_timezone(&Utc)))
         } else if let Some(start_at) = &item.start_at {
             let task_start = DateTime::parse_from_rfc3339(start_at)
                 .map_err(|e| AppError::validation(format!("Invalid task start time: {}", e)))?;
 
-            let range_start = start_date_parsed.and_hms_opt(0, 0, 0).unwrap().and_utc();
-            let range_end = end_date_parsed.and_hms_opt(23, 59, 59).unwrap().and_utc();
-
-            Ok(task_start >= range_start && task_start <= range_end)
+            Ok(in_range(task_start.with_timezone(&Utc)))
         } else {
             Ok(false)
         }
@@ -0,0 +1,226 @@
+//! Interval arithmetic over half-open `[start, end)` spans.
+//!
+//! Every operation drops empty spans and returns its result sorted and
+//! merged, so touching spans such as `[9, 10)` and `[10, 11)` become one.
+
+use chrono::{DateTime, TimeZone};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T: Ord + Copy> Interval<T> {
+    /// `None` when `end` is not after `start`.
+    pub fn new(start: T, end: T) -> Option<Self> {
+        (start < end).then_some(Self { start, end })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.end <= self.start
+    }
+
+    /// Shares at least one instant. Touching spans do not overlap, and an
+    /// empty span overlaps nothing.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        !self.is_empty() && !other.is_empty() && self.start < other.end && other.start < self.end
+    }
+
+    pub fn contains(&self, other: &Self) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        Self::new(self.start.max(other.start), self.end.min(other.end))
+    }
+}
+
+impl<Tz: TimeZone> Interval<DateTime<Tz>> {
+    pub fn duration_minutes(&self) -> i64 {
+        (self.end.clone() - self.start.clone()).num_minutes().max(0)
+    }
+}
+
+/// Sorts `intervals`, drops empty ones and merges overlapping or touching
+/// ones.
+pub fn normalize<T: Ord + Copy>(
+    intervals: impl IntoIterator<Item = Interval<T>>,
+) -> Vec<Interval<T>> {
+    let mut sorted: Vec<Interval<T>> = intervals
+        .into_iter()
+        .filter(|interval| !interval.is_empty())
+        .collect();
+    sorted.sort_by_key(|interval| (interval.start, interval.end));
+
+    let mut merged: Vec<Interval<T>> = Vec::with_capacity(sorted.len());
+    for interval in sorted {
+        match merged.last_mut() {
+            Some(last) if interval.start <= last.end => last.end = last.end.max(interval.end),
+            _ => merged.push(interval),
+        }
+    }
+    merged
+}
+
+pub fn union<T: Ord + Copy>(a: &[Interval<T>], b: &[Interval<T>]) -> Vec<Interval<T>> {
+    normalize(a.iter().chain(b.iter()).copied())
+}
+
+/// Parts of `a` that are also covered by `b`.
+pub fn intersect<T: Ord + Copy>(a: &[Interval<T>], b: &[Interval<T>]) -> Vec<Interval<T>> {
+    let a = normalize(a.iter().copied());
+    let b = normalize(b.iter().copied());
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if let Some(common) = a[i].intersection(&b[j]) {
+            result.push(common);
+        }
+        if a[i].end <= b[j].end {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    result
+}
+
+/// Parts of `from` not covered by any of `remove`, e.g. available windows
+/// minus busy time.
+pub fn subtract<T: Ord + Copy>(from: &[Interval<T>], remove: &[Interval<T>]) -> Vec<Interval<T>> {
+    let remove = normalize(remove.iter().copied());
+    let mut result = Vec::new();
+    for interval in normalize(from.iter().copied()) {
+        let mut cursor = interval.start;
+        for cut in remove
+            .iter()
+            .skip_while(|cut| cut.end <= interval.start)
+            .take_while(|cut| cut.start < interval.end)
+        {
+            if cut.start > cursor {
+                result.push(Interval {
+                    start: cursor,
+                    end: cut.start,
+                });
+            }
+            cursor = cursor.max(cut.end);
+        }
+        if cursor < interval.end {
+            result.push(Interval {
+                start: cursor,
+                end: interval.end,
+            });
+        }
+    }
+    result
+}
+
+/// Minutes covered by `intervals`, counting shared time once.
+pub fn total_minutes<Tz: TimeZone>(intervals: &[Interval<DateTime<Tz>>]) -> i64
+where
+    DateTime<Tz>: Copy,
+{
+    normalize(intervals.iter().copied())
+        .iter()
+        .map(Interval::duration_minutes)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn span(start: i64, end: i64) -> Interval<i64> {
+        Interval { start, end }
+    }
+
+    #[test]
+    fn normalize_merges_overlapping_and_touching_spans() {
+        let merged = normalize(vec![
+            span(5, 7),
+            span(1, 3),
+            span(3, 4),
+            span(6, 9),
+            span(8, 8),
+        ]);
+        assert_eq!(merged, vec![span(1, 4), span(5, 9)]);
+    }
+
+    #[test]
+    fn subtract_handles_nested_and_boundary_cuts() {
+        let windows = vec![span(9, 12), span(13, 18)];
+        let busy = vec![
+            span(10, 11),
+            span(11, 11),
+            span(12, 14),
+            span(17, 20),
+            span(0, 9),
+        ];
+        assert_eq!(
+            subtract(&windows, &busy),
+            vec![span(9, 10), span(11, 12), span(14, 17)]
+        );
+        assert!(subtract(&windows, &[span(0, 24)]).is_empty());
+        assert_eq!(subtract(&windows, &[]), windows);
+    }
+
+    #[test]
+    fn intersect_and_union_agree_with_subtract() {
+        let a = vec![span(0, 10), span(20, 30)];
+        let b = vec![span(5, 25)];
+        assert_eq!(intersect(&a, &b), vec![span(5, 10), span(20, 25)]);
+        assert_eq!(union(&a, &b), vec![span(0, 30)]);
+        assert_eq!(
+            union(&intersect(&a, &b), &subtract(&a, &b)),
+            normalize(a.clone())
+        );
+        assert!(!span(0, 5).overlaps(&span(5, 10)));
+        assert_eq!(span(0, 5).intersection(&span(5, 10)), None);
+    }
+
+    fn spans() -> impl Strategy<Value = Vec<Interval<i64>>> {
+        prop::collection::vec((0i64..200, 0i64..40), 0..8).prop_map(|raw| {
+            raw.into_iter()
+                .map(|(start, len)| span(start, start + len))
+                .collect()
+        })
+    }
+
+    fn covered(intervals: &[Interval<i64>]) -> i64 {
+        normalize(intervals.iter().copied())
+            .iter()
+            .map(|interval| interval.end - interval.start)
+            .sum()
+    }
+
+    proptest! {
+        #[test]
+        fn operations_return_normalized_spans(a in spans(), b in spans()) {
+            for result in [union(&a, &b), intersect(&a, &b), subtract(&a, &b)] {
+                prop_assert_eq!(normalize(result.clone()), result);
+            }
+        }
+
+        #[test]
+        fn subtract_and_intersect_partition_the_source(a in spans(), b in spans()) {
+            let kept = subtract(&a, &b);
+            let shared = intersect(&a, &b);
+            prop_assert!(kept.iter().all(|left| b.iter().all(|cut| !left.overlaps(cut))));
+            prop_assert!(intersect(&kept, &shared).is_empty());
+            prop_assert_eq!(covered(&kept) + covered(&shared), covered(&a));
+            prop_assert_eq!(union(&kept, &shared), normalize(a.clone()));
+        }
+
+        #[test]
+        fn intersect_and_union_are_symmetric(a in spans(), b in spans()) {
+            prop_assert_eq!(intersect(&a, &b), intersect(&b, &a));
+            prop_assert_eq!(union(&a, &b), union(&b, &a));
+            prop_assert_eq!(
+                covered(&union(&a, &b)) + covered(&intersect(&a, &b)),
+                covered(&a) + covered(&b)
+            );
+        }
+    }
+}
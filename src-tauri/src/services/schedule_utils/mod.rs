@@ -3,6 +3,8 @@ use serde_json::json;
 
 use crate::error::{AppError, AppResult};
 
+pub mod intervals;
+
 pub fn parse_datetime(value: &str) -> AppResult<DateTime<FixedOffset>> {
     DateTime::parse_from_rfc3339(value).map_err(|err| {
         AppError::validation_with_details(
@@ -0,0 +1,96 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::models::ai_types::JsonRepairStats;
+
+/// In-process counters for how often provider output needed repair. They
+/// cover the current app session only and reset on restart.
+#[derive(Debug, Default)]
+pub struct JsonRepairMetrics {
+    parsed_clean: AtomicU64,
+    repaired_locally: AtomicU64,
+    re_asked: AtomicU64,
+    recovered_by_re_ask: AtomicU64,
+    failed: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonRepairOutcome {
+    Clean,
+    RepairedLocally,
+    ReAsked,
+    RecoveredByReAsk,
+    Failed,
+}
+
+impl JsonRepairMetrics {
+    pub const fn new() -> Self {
+        Self {
+            parsed_clean: AtomicU64::new(0),
+            repaired_locally: AtomicU64::new(0),
+            re_asked: AtomicU64::new(0),
+            recovered_by_re_ask: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record(&self, outcome: JsonRepairOutcome) {
+        let counter = match outcome {
+            JsonRepairOutcome::Clean => &self.parsed_clean,
+            JsonRepairOutcome::RepairedLocally => &self.repaired_locally,
+            JsonRepairOutcome::ReAsked => &self.re_asked,
+            JsonRepairOutcome::RecoveredByReAsk => &self.recovered_by_re_ask,
+            JsonRepairOutcome::Failed => &self.failed,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> JsonRepairStats {
+        let parsed_clean = self.parsed_clean.load(Ordering::Relaxed);
+        let repaired_locally = self.repaired_locally.load(Ordering::Relaxed);
+        let re_asked = self.re_asked.load(Ordering::Relaxed);
+        let recovered_by_re_ask = self.recovered_by_re_ask.load(Ordering::Relaxed);
+        let failed = self.failed.load(Ordering::Relaxed);
+
+        // Every response ends in exactly one of clean, repaired or failed.
+        let total_responses = parsed_clean + repaired_locally + recovered_by_re_ask + failed;
+        let repair_rate = if total_responses == 0 {
+            0.0
+        } else {
+            (repaired_locally + recovered_by_re_ask) as f64 / total_responses as f64
+        };
+
+        JsonRepairStats {
+            total_responses,
+            parsed_clean,
+            repaired_locally,
+            re_asked,
+            recovered_by_re_ask,
+            failed,
+            repair_rate,
+        }
+    }
+}
+
+/// Repair counters shared by every DeepSeek provider instance.
+pub static JSON_REPAIR_METRICS: JsonRepairMetrics = JsonRepairMetrics::new();
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_reports_repair_rate() {
+        let metrics = JsonRepairMetrics::new();
+        metrics.record(JsonRepairOutcome::Clean);
+        metrics.record(JsonRepairOutcome::Clean);
+        metrics.record(JsonRepairOutcome::RepairedLocally);
+        metrics.record(JsonRepairOutcome::ReAsked);
+        metrics.record(JsonRepairOutcome::RecoveredByReAsk);
+
+        let stats = metrics.snapshot();
+
+        assert_eq!(stats.total_responses, 4);
+        assert_eq!(stats.re_asked, 1);
+        assert!((stats.repair_rate - 0.5).abs() < f64::EPSILON);
+    }
+}
@@ -1,17 +1,29 @@
 use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::RwLock;
 
 use base64::{engine::general_purpose::STANDARD as Base64, Engine as _};
 use chrono::Utc;
+use rusqlite::Connection;
 use sha2::{Digest, Sha256};
 use tracing::warn;
 
 use crate::db::repositories::ai_settings_repository::AiSettingsRepository;
+use crate::db::repositories::feature_flag_repository::{FeatureFlagRepository, FeatureFlagRow};
+use crate::db::repositories::planning_repository::PlanningRepository;
 use crate::db::repositories::settings_repository::{AppSettingRow, SettingsRepository};
 use crate::db::DbPool;
 use crate::error::{AppError, AppResult};
-use crate::models::settings::{AppSettings, DashboardConfig};
+use crate::models::capacity::CapacityProfile;
+use crate::models::productivity::ScoreStreak;
+use crate::models::retention::RetentionPolicy;
+use crate::models::settings::{
+    AppSettings, DashboardConfig, DashboardMetric, DelegationSettings, FeatureFlag,
+    FeatureFlagState, FeedbackOptOuts, IdleDetectionSettings, PowerPolicy, SleepSchedule,
+};
+use crate::services::behavior_learning::DEFAULT_PREFERENCE_ID;
+use crate::services::dashboard_metrics;
 use crate::utils::crypto::CryptoVault;
 
 const KEY_DEEPSEEK_API: &str = "deepseek_api_key";
@@ -20,12 +32,50 @@ const KEY_WORKDAY_END: &str = "workday_end_minute";
 const KEY_THEME: &str = "theme";
 const KEY_AI_FEEDBACK_OPT_OUT: &str = "ai_feedback_opt_out";
 const KEY_DASHBOARD_CONFIG: &str = "dashboard_config";
+const KEY_CLIPBOARD_WATCH: &str = "clipboard_watch_enabled";
+const KEY_SLEEP_SCHEDULE: &str = "sleep_schedule";
+const KEY_AI_PRIVACY_MODE: &str = "ai_privacy_mode";
+const KEY_AI_DEBUG_LOG: &str = "ai_debug_log_enabled";
+const KEY_PLANNING_SESSION_RETENTION: &str = "planning_session_retention_days";
+const KEY_PROACTIVE_SUGGESTIONS: &str = "proactive_suggestions_enabled";
+const KEY_ANOMALY_NOTIFICATIONS: &str = "anomaly_notifications_enabled";
+const KEY_PLAN_CRITIQUE: &str = "plan_critique_enabled";
+const KEY_DEFAULT_PREFERENCE: &str = "default_preference_id";
+const KEY_SCORE_TARGET: &str = "productivity_score_target";
+const KEY_SCORE_STREAK: &str = "productivity_score_streak";
+const KEY_RETENTION_POLICY: &str = "retention_policy";
+const KEY_POWER_POLICY: &str = "power_policy";
+const KEY_IDLE_DETECTION: &str = "idle_detection";
+const KEY_CAPACITY_PROFILE: &str = "capacity_profile";
+const KEY_DELEGATION: &str = "delegation";
+/// Opt-outs other than AI quality, which keeps its own key for settings saved
+/// before opt-outs were per category.
+const KEY_FEEDBACK_OPT_OUTS: &str = "feedback_opt_outs";
+
+/// Settings never written to a data export.
+pub const SECRET_SETTING_KEYS: [&str; 1] = [KEY_DEEPSEEK_API];
 
 const DEFAULT_WORKDAY_START: i16 = 9 * 60;
 const DEFAULT_WORKDAY_END: i16 = 18 * 60;
+pub const DEFAULT_PLANNING_SESSION_RETENTION_DAYS: u32 = 14;
+const MAX_PLANNING_SESSION_RETENTION_DAYS: u32 = 365;
 const DEFAULT_THEME: &str = "system";
 const THEME_OPTIONS: [&str; 3] = ["system", "light", "dark"];
 
+/// Bumped after every committed change to settings, the stored API key or
+/// prompt overrides. Services that derive configuration from them compare
+/// versions instead of re-reading and re-decrypting on every call.
+static SETTINGS_VERSION: AtomicU64 = AtomicU64::new(0);
+
+pub fn settings_version() -> u64 {
+    SETTINGS_VERSION.load(Ordering::Acquire)
+}
+
+/// Call after the write is committed, never inside a transaction.
+pub fn notify_settings_changed() {
+    SETTINGS_VERSION.fetch_add(1, Ordering::AcqRel);
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct SettingsUpdateInput {
     pub deepseek_api_key: Option<Option<String>>,
@@ -33,11 +83,23 @@ pub struct SettingsUpdateInput {
     pub workday_end_minute: Option<i16>,
     pub theme: Option<String>,
     pub ai_feedback_opt_out: Option<bool>,
+    pub clipboard_watch_enabled: Option<bool>,
+    pub ai_privacy_mode: Option<bool>,
+    pub ai_debug_log_enabled: Option<bool>,
+    pub planning_session_retention_days: Option<u32>,
+    pub proactive_suggestions_enabled: Option<bool>,
+    pub anomaly_notifications_enabled: Option<bool>,
+    pub plan_critique_enabled: Option<bool>,
+    pub default_preference_id: Option<String>,
+    /// 0 turns the score streak off.
+    pub productivity_score_target: Option<f64>,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct DashboardConfigUpdateInput {
     pub modules: Option<BTreeMap<String, bool>>,
+    /// Replaces the whole list when present.
+    pub custom_metrics: Option<Vec<DashboardMetric>>,
     pub last_updated_at: Option<Option<String>>,
 }
 
@@ -82,8 +144,14 @@ impl SettingsService {
             .normalize())
     }
 
+    pub fn get_sleep_schedule(&self) -> AppResult<SleepSchedule> {
+        let settings = self.get()?;
+        Ok(settings.sleep_schedule.unwrap_or_default())
+    }
+
     pub fn update(&self, input: SettingsUpdateInput) -> AppResult<AppSettings> {
-        let mut current = self.get()?;
+        let previous = self.get()?;
+        let mut current = previous.clone();
 
         if let Some(workday_start) = input.workday_start_minute {
             ensure_valid_minute(workday_start)?;
@@ -116,6 +184,57 @@ impl SettingsService {
             current.ai_feedback_opt_out = Some(opt_out);
         }
 
+        if let Some(enabled) = input.clipboard_watch_enabled {
+            current.clipboard_watch_enabled = Some(enabled);
+        }
+
+        if let Some(enabled) = input.ai_privacy_mode {
+            current.ai_privacy_mode = Some(enabled);
+        }
+
+        if let Some(enabled) = input.ai_debug_log_enabled {
+            current.ai_debug_log_enabled = Some(enabled);
+        }
+
+        if let Some(days) = input.planning_session_retention_days {
+            if !(1..=MAX_PLANNING_SESSION_RETENTION_DAYS).contains(&days) {
+                return Err(AppError::validation(format!(
+                    "规划会话保留天数需在 1 到 {} 之间",
+                    MAX_PLANNING_SESSION_RETENTION_DAYS
+                )));
+            }
+            current.planning_session_retention_days = Some(days);
+        }
+
+        if let Some(enabled) = input.proactive_suggestions_enabled {
+            current.proactive_suggestions_enabled = Some(enabled);
+        }
+
+        if let Some(enabled) = input.anomaly_notifications_enabled {
+            current.anomaly_notifications_enabled = Some(enabled);
+        }
+
+        if let Some(enabled) = input.plan_critique_enabled {
+            current.plan_critique_enabled = Some(enabled);
+        }
+
+        if let Some(preference_id) = input.default_preference_id.as_ref() {
+            let exists = self.db.with_connection(|conn| {
+                Ok(PlanningRepository::get_schedule_preferences(conn, preference_id)?.is_some())
+            })?;
+            if !exists {
+                return Err(AppError::validation("偏好配置不存在"));
+            }
+            current.default_preference_id = Some(preference_id.clone());
+        }
+
+        if let Some(target) = input.productivity_score_target {
+            if !(0.0..=100.0).contains(&target) {
+                return Err(AppError::validation("目标效率得分需在 0 到 100 之间"));
+            }
+            current.productivity_score_target = Some(target);
+        }
+
         let api_key_instruction = self.prepare_api_key_instruction(&input)?;
         if let Some(masked) = api_key_instruction.masked.clone() {
             current.deepseek_api_key = Some(masked);
@@ -123,9 +242,16 @@ impl SettingsService {
             current.deepseek_api_key = None;
         }
 
+        // Repeated saves of the same values (e.g. from a form autosave) skip
+        // the write so nothing downstream reloads.
+        if current == previous && matches!(api_key_instruction.action, ApiKeyAction::NoChange) {
+            return Ok(current);
+        }
+
         let now = Utc::now().to_rfc3339();
         self.persist_changes(&input, &api_key_instruction)?;
         current.updated_at = now;
+        notify_settings_changed();
 
         if let Ok(mut guard) = self.cache.write() {
             *guard = Some(current.clone());
@@ -139,6 +265,7 @@ impl SettingsService {
         input: DashboardConfigUpdateInput,
     ) -> AppResult<DashboardConfig> {
         let mut current = self.get_dashboard_config()?;
+        current.metric_values.clear();
         if let Some(overrides) = input.modules {
             for (module, enabled) in overrides {
                 let normalized = module.to_lowercase();
@@ -147,6 +274,9 @@ impl SettingsService {
                 }
             }
         }
+        if let Some(metrics) = input.custom_metrics {
+            current.custom_metrics = dashboard_metrics::validate_metrics(metrics)?;
+        }
 
         let now = Utc::now().to_rfc3339();
         current.last_updated_at = match input.last_updated_at {
@@ -155,6 +285,7 @@ impl SettingsService {
         };
 
         self.persist_dashboard_config(&current)?;
+        notify_settings_changed();
 
         if let Ok(mut guard) = self.cache.write() {
             if let Some(settings) = guard.as_mut() {
@@ -169,12 +300,235 @@ impl SettingsService {
         Ok(current)
     }
 
+    pub fn update_sleep_schedule(&self, schedule: SleepSchedule) -> AppResult<SleepSchedule> {
+        let mut schedule = schedule.normalize();
+        schedule
+            .validate()
+            .map_err(|reason| AppError::validation(format!("作息时间设置无效: {reason}")))?;
+
+        let now = Utc::now().to_rfc3339();
+        schedule.last_updated_at = Some(now.clone());
+
+        let serialized = serde_json::to_string(&schedule)?;
+        self.db.with_connection(|conn| {
+            SettingsRepository::upsert(conn, KEY_SLEEP_SCHEDULE, &serialized)?;
+            Ok(())
+        })?;
+        notify_settings_changed();
+
+        if let Ok(mut guard) = self.cache.write() {
+            if let Some(settings) = guard.as_mut() {
+                settings.sleep_schedule = Some(schedule.clone());
+                settings.updated_at = now;
+            }
+        }
+
+        Ok(schedule)
+    }
+
+    pub fn get_retention_policy(&self) -> AppResult<RetentionPolicy> {
+        self.db.with_read_connection(load_retention_policy)
+    }
+
+    pub fn update_retention_policy(&self, policy: RetentionPolicy) -> AppResult<RetentionPolicy> {
+        let mut policy = policy;
+        policy
+            .validate()
+            .map_err(|reason| AppError::validation(format!("数据保留设置无效: {reason}")))?;
+        policy.last_updated_at = Some(Utc::now().to_rfc3339());
+
+        let serialized = serde_json::to_string(&policy)?;
+        self.db.with_connection(|conn| {
+            SettingsRepository::upsert(conn, KEY_RETENTION_POLICY, &serialized)?;
+            Ok(())
+        })?;
+        notify_settings_changed();
+
+        Ok(policy)
+    }
+
+    pub fn get_power_policy(&self) -> AppResult<PowerPolicy> {
+        self.db.with_read_connection(load_power_policy)
+    }
+
+    pub fn update_power_policy(&self, policy: PowerPolicy) -> AppResult<PowerPolicy> {
+        let mut policy = policy;
+        policy
+            .validate()
+            .map_err(|reason| AppError::validation(format!("省电设置无效: {reason}")))?;
+        policy.last_updated_at = Some(Utc::now().to_rfc3339());
+
+        let serialized = serde_json::to_string(&policy)?;
+        self.db.with_connection(|conn| {
+            SettingsRepository::upsert(conn, KEY_POWER_POLICY, &serialized)?;
+            Ok(())
+        })?;
+        notify_settings_changed();
+
+        Ok(policy)
+    }
+
+    pub fn get_idle_detection(&self) -> AppResult<IdleDetectionSettings> {
+        self.db.with_read_connection(load_idle_detection)
+    }
+
+    pub fn update_idle_detection(
+        &self,
+        settings: IdleDetectionSettings,
+    ) -> AppResult<IdleDetectionSettings> {
+        let mut settings = settings;
+        settings
+            .validate()
+            .map_err(|reason| AppError::validation(format!("空闲检测设置无效: {reason}")))?;
+        settings.last_updated_at = Some(Utc::now().to_rfc3339());
+
+        let serialized = serde_json::to_string(&settings)?;
+        self.db.with_connection(|conn| {
+            SettingsRepository::upsert(conn, KEY_IDLE_DETECTION, &serialized)?;
+            Ok(())
+        })?;
+        notify_settings_changed();
+
+        Ok(settings)
+    }
+
+    pub fn get_delegation(&self) -> AppResult<DelegationSettings> {
+        self.db.with_read_connection(load_delegation)
+    }
+
+    pub fn update_delegation(&self, settings: DelegationSettings) -> AppResult<DelegationSettings> {
+        let mut settings = settings;
+        settings
+            .validate()
+            .map_err(|reason| AppError::validation(format!("委派设置无效: {reason}")))?;
+        settings.last_updated_at = Some(Utc::now().to_rfc3339());
+
+        let serialized = serde_json::to_string(&settings)?;
+        self.db.with_connection(|conn| {
+            SettingsRepository::upsert(conn, KEY_DELEGATION, &serialized)?;
+            Ok(())
+        })?;
+        notify_settings_changed();
+
+        Ok(settings)
+    }
+
+    pub fn get_capacity_profile(&self) -> AppResult<CapacityProfile> {
+        self.db.with_read_connection(load_capacity_profile)
+    }
+
+    pub fn update_capacity_profile(&self, profile: CapacityProfile) -> AppResult<CapacityProfile> {
+        let mut profile = profile;
+        for commitment in &profile.commitments {
+            commitment
+                .validate()
+                .map_err(|reason| AppError::validation(format!("固定安排无效: {reason}")))?;
+        }
+        profile.last_updated_at = Some(Utc::now().to_rfc3339());
+
+        let serialized = serde_json::to_string(&profile)?;
+        self.db.with_connection(|conn| {
+            SettingsRepository::upsert(conn, KEY_CAPACITY_PROFILE, &serialized)?;
+            Ok(())
+        })?;
+        notify_settings_changed();
+
+        Ok(profile)
+    }
+
+    /// Every feature flag with its effective value.
+    pub fn get_feature_flags(&self) -> AppResult<Vec<FeatureFlagState>> {
+        self.db.with_read_connection(|conn| {
+            let stored = FeatureFlagRepository::list(conn)?;
+            Ok(FeatureFlag::ALL
+                .iter()
+                .map(|flag| {
+                    let row = stored.iter().find(|row| row.name == flag.as_str());
+                    FeatureFlagState {
+                        flag: *flag,
+                        enabled: row.map_or(flag.default_enabled(), |row| row.enabled),
+                        default_enabled: flag.default_enabled(),
+                        updated_at: row.map(|row| row.updated_at.clone()),
+                    }
+                })
+                .collect())
+        })
+    }
+
+    pub fn set_feature_flag(
+        &self,
+        flag: FeatureFlag,
+        enabled: bool,
+    ) -> AppResult<FeatureFlagState> {
+        let row = FeatureFlagRow {
+            name: flag.as_str().to_string(),
+            enabled,
+            updated_at: Utc::now().to_rfc3339(),
+        };
+        self.db
+            .with_connection(|conn| FeatureFlagRepository::upsert(conn, &row))?;
+        notify_settings_changed();
+
+        Ok(FeatureFlagState {
+            flag,
+            enabled,
+            default_enabled: flag.default_enabled(),
+            updated_at: Some(row.updated_at),
+        })
+    }
+
+    pub fn get_feedback_opt_outs(&self) -> AppResult<FeedbackOptOuts> {
+        self.db.with_read_connection(load_feedback_opt_outs)
+    }
+
+    pub fn update_feedback_opt_outs(
+        &self,
+        opt_outs: FeedbackOptOuts,
+    ) -> AppResult<FeedbackOptOuts> {
+        let mut opt_outs = opt_outs;
+        let now = Utc::now().to_rfc3339();
+        opt_outs.last_updated_at = Some(now.clone());
+
+        let serialized = serde_json::to_string(&opt_outs)?;
+        self.db.with_connection(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            SettingsRepository::upsert(
+                &tx,
+                KEY_AI_FEEDBACK_OPT_OUT,
+                &opt_outs.ai_quality.to_string(),
+            )?;
+            SettingsRepository::upsert(&tx, KEY_FEEDBACK_OPT_OUTS, &serialized)?;
+            tx.commit()?;
+            Ok(())
+        })?;
+        notify_settings_changed();
+
+        if let Ok(mut guard) = self.cache.write() {
+            if let Some(settings) = guard.as_mut() {
+                settings.ai_feedback_opt_out = Some(opt_outs.ai_quality);
+                settings.updated_at = now;
+            }
+        }
+
+        Ok(opt_outs)
+    }
+
+    /// Drops the cached settings so the next read goes to the database,
+    /// e.g. after all data was erased.
+    pub fn invalidate_cache(&self) {
+        if let Ok(mut guard) = self.cache.write() {
+            *guard = None;
+        }
+        notify_settings_changed();
+    }
+
     pub fn clear_sensitive(&self) -> AppResult<()> {
         self.db.with_connection(|conn| {
             AiSettingsRepository::delete(conn, KEY_DEEPSEEK_API)?;
             SettingsRepository::delete(conn, KEY_DEEPSEEK_API)?;
             Ok(())
         })?;
+        notify_settings_changed();
 
         if let Err(err) = self.vault.clear_master_secret() {
             warn!(
@@ -206,6 +560,15 @@ impl SettingsService {
             .as_ref()
             .map(|value| value.trim().to_lowercase());
         let ai_feedback_opt_out = input.ai_feedback_opt_out;
+        let clipboard_watch_enabled = input.clipboard_watch_enabled;
+        let ai_privacy_mode = input.ai_privacy_mode;
+        let ai_debug_log_enabled = input.ai_debug_log_enabled;
+        let planning_session_retention_days = input.planning_session_retention_days;
+        let proactive_suggestions_enabled = input.proactive_suggestions_enabled;
+        let anomaly_notifications_enabled = input.anomaly_notifications_enabled;
+        let plan_critique_enabled = input.plan_critique_enabled;
+        let default_preference_id = input.default_preference_id.as_deref();
+        let productivity_score_target = input.productivity_score_target;
 
         self.db.with_connection(|conn| {
             match api_instr.action {
@@ -238,6 +601,46 @@ impl SettingsService {
                 SettingsRepository::upsert(conn, KEY_AI_FEEDBACK_OPT_OUT, &value.to_string())?;
             }
 
+            if let Some(value) = clipboard_watch_enabled {
+                SettingsRepository::upsert(conn, KEY_CLIPBOARD_WATCH, &value.to_string())?;
+            }
+
+            if let Some(value) = ai_privacy_mode {
+                SettingsRepository::upsert(conn, KEY_AI_PRIVACY_MODE, &value.to_string())?;
+            }
+
+            if let Some(value) = ai_debug_log_enabled {
+                SettingsRepository::upsert(conn, KEY_AI_DEBUG_LOG, &value.to_string())?;
+            }
+
+            if let Some(value) = planning_session_retention_days {
+                SettingsRepository::upsert(
+                    conn,
+                    KEY_PLANNING_SESSION_RETENTION,
+                    &value.to_string(),
+                )?;
+            }
+
+            if let Some(value) = proactive_suggestions_enabled {
+                SettingsRepository::upsert(conn, KEY_PROACTIVE_SUGGESTIONS, &value.to_string())?;
+            }
+
+            if let Some(value) = anomaly_notifications_enabled {
+                SettingsRepository::upsert(conn, KEY_ANOMALY_NOTIFICATIONS, &value.to_string())?;
+            }
+
+            if let Some(value) = plan_critique_enabled {
+                SettingsRepository::upsert(conn, KEY_PLAN_CRITIQUE, &value.to_string())?;
+            }
+
+            if let Some(value) = default_preference_id {
+                SettingsRepository::upsert(conn, KEY_DEFAULT_PREFERENCE, value)?;
+            }
+
+            if let Some(value) = productivity_score_target {
+                SettingsRepository::upsert(conn, KEY_SCORE_TARGET, &value.to_string())?;
+            }
+
             Ok(())
         })
     }
@@ -266,6 +669,12 @@ impl SettingsService {
         }
     }
 
+    fn extract_sleep_schedule(map: &mut HashMap<String, AppSettingRow>) -> SleepSchedule {
+        map.remove(KEY_SLEEP_SCHEDULE)
+            .map(|row| parse_sleep_schedule(&row.value))
+            .unwrap_or_default()
+    }
+
     fn prepare_api_key_instruction(
         &self,
         input: &SettingsUpdateInput,
@@ -398,7 +807,43 @@ impl SettingsService {
                 .get(KEY_AI_FEEDBACK_OPT_OUT)
                 .and_then(|row| row.value.parse::<bool>().ok());
 
+            let clipboard_watch_enabled = map
+                .get(KEY_CLIPBOARD_WATCH)
+                .and_then(|row| row.value.parse::<bool>().ok());
+
+            let ai_privacy_mode = map
+                .get(KEY_AI_PRIVACY_MODE)
+                .and_then(|row| row.value.parse::<bool>().ok());
+
+            let ai_debug_log_enabled = map
+                .get(KEY_AI_DEBUG_LOG)
+                .and_then(|row| row.value.parse::<bool>().ok());
+
+            let planning_session_retention_days = map
+                .get(KEY_PLANNING_SESSION_RETENTION)
+                .and_then(|row| row.value.parse::<u32>().ok());
+
+            let proactive_suggestions_enabled = map
+                .get(KEY_PROACTIVE_SUGGESTIONS)
+                .and_then(|row| row.value.parse::<bool>().ok());
+
+            let anomaly_notifications_enabled = map
+                .get(KEY_ANOMALY_NOTIFICATIONS)
+                .and_then(|row| row.value.parse::<bool>().ok());
+
+            let plan_critique_enabled = map
+                .get(KEY_PLAN_CRITIQUE)
+                .and_then(|row| row.value.parse::<bool>().ok());
+
+            let default_preference_id =
+                map.get(KEY_DEFAULT_PREFERENCE).map(|row| row.value.clone());
+
+            let productivity_score_target = map
+                .get(KEY_SCORE_TARGET)
+                .and_then(|row| row.value.parse::<f64>().ok());
+
             let dashboard_config = Self::extract_dashboard_config(&mut map);
+            let sleep_schedule = Self::extract_sleep_schedule(&mut map);
 
             let updated_at = latest_updated_at.unwrap_or_else(|| Utc::now().to_rfc3339());
 
@@ -417,7 +862,17 @@ impl SettingsService {
                 theme,
                 updated_at,
                 ai_feedback_opt_out,
+                clipboard_watch_enabled,
+                ai_privacy_mode,
+                ai_debug_log_enabled,
+                planning_session_retention_days,
+                proactive_suggestions_enabled,
+                anomaly_notifications_enabled,
+                plan_critique_enabled,
+                default_preference_id,
+                productivity_score_target,
                 dashboard_config: Some(dashboard_config),
+                sleep_schedule: Some(sleep_schedule),
             })
         })
     }
@@ -471,6 +926,179 @@ fn mask_api_key(value: &str) -> String {
     format!("{}{}", masked_prefix, visible)
 }
 
+/// Reads the stored sleep schedule for services that do not hold a
+/// `SettingsService`. Returns `None` unless the schedule is enabled.
+pub fn load_sleep_schedule(conn: &Connection) -> AppResult<Option<SleepSchedule>> {
+    let schedule = SettingsRepository::get(conn, KEY_SLEEP_SCHEDULE)?
+        .map(|row| parse_sleep_schedule(&row.value))
+        .unwrap_or_default();
+    Ok(Some(schedule).filter(|schedule| schedule.enabled))
+}
+
+/// Whether prompts to external providers must use placeholders instead of
+/// task titles and descriptions.
+pub fn load_ai_privacy_mode(conn: &Connection) -> AppResult<bool> {
+    Ok(SettingsRepository::get(conn, KEY_AI_PRIVACY_MODE)?
+        .and_then(|row| row.value.parse::<bool>().ok())
+        .unwrap_or(false))
+}
+
+/// Whether redacted AI requests and responses are kept for debugging.
+pub fn load_ai_debug_log_enabled(conn: &Connection) -> AppResult<bool> {
+    Ok(SettingsRepository::get(conn, KEY_AI_DEBUG_LOG)?
+        .and_then(|row| row.value.parse::<bool>().ok())
+        .unwrap_or(false))
+}
+
+/// Days a pending planning session is kept before it expires.
+pub fn load_planning_session_retention_days(conn: &Connection) -> AppResult<u32> {
+    Ok(
+        SettingsRepository::get(conn, KEY_PLANNING_SESSION_RETENTION)?
+            .and_then(|row| row.value.parse::<u32>().ok())
+            .filter(|days| *days > 0)
+            .unwrap_or(DEFAULT_PLANNING_SESSION_RETENTION_DAYS),
+    )
+}
+
+/// Whether the daily proactive suggestion job is enabled.
+pub fn load_proactive_suggestions_enabled(conn: &Connection) -> AppResult<bool> {
+    Ok(SettingsRepository::get(conn, KEY_PROACTIVE_SUGGESTIONS)?
+        .and_then(|row| row.value.parse::<bool>().ok())
+        .unwrap_or(false))
+}
+
+/// Workday bounds in minutes after midnight, falling back to the defaults
+/// when unset or inverted.
+pub fn load_workday_window(conn: &Connection) -> AppResult<(i16, i16)> {
+    let read = |key: &str| -> AppResult<Option<i16>> {
+        Ok(SettingsRepository::get(conn, key)?.and_then(|row| row.value.parse::<i16>().ok()))
+    };
+    let start = read(KEY_WORKDAY_START)?.unwrap_or(DEFAULT_WORKDAY_START);
+    let end = read(KEY_WORKDAY_END)?.unwrap_or(DEFAULT_WORKDAY_END);
+    if start < end {
+        Ok((start, end))
+    } else {
+        Ok((DEFAULT_WORKDAY_START, DEFAULT_WORKDAY_END))
+    }
+}
+
+/// Whether flagged analytics anomalies raise a notification.
+pub fn load_anomaly_notifications_enabled(conn: &Connection) -> AppResult<bool> {
+    Ok(SettingsRepository::get(conn, KEY_ANOMALY_NOTIFICATIONS)?
+        .and_then(|row| row.value.parse::<bool>().ok())
+        .unwrap_or(false))
+}
+
+/// Whether optimizer plans get a second look from the AI provider.
+pub fn load_plan_critique_enabled(conn: &Connection) -> AppResult<bool> {
+    Ok(SettingsRepository::get(conn, KEY_PLAN_CRITIQUE)?
+        .and_then(|row| row.value.parse::<bool>().ok())
+        .unwrap_or(false))
+}
+
+/// Preference profile used when a planning run does not name one, falling
+/// back to the built-in profile when the chosen one was deleted.
+pub fn load_default_preference_id(conn: &Connection) -> AppResult<String> {
+    let stored = SettingsRepository::get(conn, KEY_DEFAULT_PREFERENCE)?.map(|row| row.value);
+    match stored {
+        Some(id) if PlanningRepository::get_schedule_preferences(conn, &id)?.is_some() => Ok(id),
+        _ => Ok(DEFAULT_PREFERENCE_ID.to_string()),
+    }
+}
+
+/// Composite score a day must reach to extend the streak, or `None` when
+/// the user has not set a target.
+pub fn load_productivity_score_target(conn: &Connection) -> AppResult<Option<f64>> {
+    Ok(SettingsRepository::get(conn, KEY_SCORE_TARGET)?
+        .and_then(|row| row.value.parse::<f64>().ok())
+        .filter(|target| *target > 0.0))
+}
+
+/// Streak state kept by the nightly analytics job.
+pub fn load_score_streak(conn: &Connection) -> AppResult<ScoreStreak> {
+    Ok(SettingsRepository::get(conn, KEY_SCORE_STREAK)?
+        .and_then(|row| serde_json::from_str(&row.value).ok())
+        .unwrap_or_default())
+}
+
+pub fn save_score_streak(conn: &Connection, streak: &ScoreStreak) -> AppResult<()> {
+    SettingsRepository::upsert(conn, KEY_SCORE_STREAK, &serde_json::to_string(streak)?)?;
+    Ok(())
+}
+
+/// Per-category retention, falling back to the defaults when unset or
+/// unreadable.
+pub fn load_retention_policy(conn: &Connection) -> AppResult<RetentionPolicy> {
+    Ok(SettingsRepository::get(conn, KEY_RETENTION_POLICY)?
+        .and_then(|row| serde_json::from_str::<RetentionPolicy>(&row.value).ok())
+        .filter(|policy| policy.validate().is_ok())
+        .unwrap_or_default())
+}
+
+pub fn load_power_policy(conn: &Connection) -> AppResult<PowerPolicy> {
+    Ok(SettingsRepository::get(conn, KEY_POWER_POLICY)?
+        .and_then(|row| serde_json::from_str::<PowerPolicy>(&row.value).ok())
+        .filter(|policy| policy.validate().is_ok())
+        .unwrap_or_default())
+}
+
+pub fn load_idle_detection(conn: &Connection) -> AppResult<IdleDetectionSettings> {
+    Ok(SettingsRepository::get(conn, KEY_IDLE_DETECTION)?
+        .and_then(|row| serde_json::from_str::<IdleDetectionSettings>(&row.value).ok())
+        .filter(|settings| settings.validate().is_ok())
+        .unwrap_or_default())
+}
+
+pub fn load_delegation(conn: &Connection) -> AppResult<DelegationSettings> {
+    Ok(SettingsRepository::get(conn, KEY_DELEGATION)?
+        .and_then(|row| serde_json::from_str::<DelegationSettings>(&row.value).ok())
+        .filter(|settings| settings.validate().is_ok())
+        .unwrap_or_default())
+}
+
+pub fn load_capacity_profile(conn: &Connection) -> AppResult<CapacityProfile> {
+    Ok(SettingsRepository::get(conn, KEY_CAPACITY_PROFILE)?
+        .and_then(|row| serde_json::from_str::<CapacityProfile>(&row.value).ok())
+        .unwrap_or_default())
+}
+
+/// Whether `flag` is on, falling back to its compiled-in default.
+pub fn load_feature_enabled(conn: &Connection, flag: FeatureFlag) -> AppResult<bool> {
+    Ok(FeatureFlagRepository::get(conn, flag.as_str())?
+        .map_or(flag.default_enabled(), |row| row.enabled))
+}
+
+/// Per-category feedback opt-outs. AI quality always follows
+/// `ai_feedback_opt_out`, so either setting can change it.
+pub fn load_feedback_opt_outs(conn: &Connection) -> AppResult<FeedbackOptOuts> {
+    let mut opt_outs = SettingsRepository::get(conn, KEY_FEEDBACK_OPT_OUTS)?
+        .and_then(|row| serde_json::from_str::<FeedbackOptOuts>(&row.value).ok())
+        .unwrap_or_default();
+    opt_outs.ai_quality = SettingsRepository::get(conn, KEY_AI_FEEDBACK_OPT_OUT)?
+        .and_then(|row| row.value.parse::<bool>().ok())
+        .unwrap_or(false);
+    Ok(opt_outs)
+}
+
+fn parse_sleep_schedule(raw: &str) -> SleepSchedule {
+    let parsed = serde_json::from_str::<SleepSchedule>(raw)
+        .map_err(|err| err.to_string())
+        .map(SleepSchedule::normalize)
+        .and_then(|schedule| schedule.validate().map(|_| schedule));
+
+    match parsed {
+        Ok(schedule) => schedule,
+        Err(reason) => {
+            warn!(
+                target: "app::settings",
+                reason = %reason,
+                "failed to load stored sleep schedule, falling back to defaults"
+            );
+            SleepSchedule::default()
+        }
+    }
+}
+
 fn ensure_valid_minute(value: i16) -> AppResult<()> {
     if !(0..=1440).contains(&value) {
         return Err(AppError::validation("工作时间必须在 0~1440 分钟之间"));
@@ -521,6 +1149,7 @@ enum ApiKeyAction {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::settings::SleepWindow;
     use tempfile::TempDir;
 
     fn setup_service() -> (SettingsService, TempDir) {
@@ -531,6 +1160,55 @@ mod tests {
         (service, temp_dir)
     }
 
+    #[test]
+    fn feature_flags_fall_back_to_defaults_until_set() {
+        let (service, _guard) = setup_service();
+        let flags = service.get_feature_flags().unwrap();
+        assert_eq!(flags.len(), FeatureFlag::ALL.len());
+        assert!(flags
+            .iter()
+            .all(|state| state.enabled == state.default_enabled && state.updated_at.is_none()));
+
+        let before = settings_version();
+        let sync = service.set_feature_flag(FeatureFlag::Sync, false).unwrap();
+        assert!(!sync.enabled);
+        assert!(sync.default_enabled);
+        assert!(settings_version() > before);
+
+        let enabled = service
+            .db
+            .with_read_connection(|conn| {
+                Ok((
+                    load_feature_enabled(conn, FeatureFlag::Sync)?,
+                    load_feature_enabled(conn, FeatureFlag::AiInsights)?,
+                ))
+            })
+            .unwrap();
+        assert_eq!(enabled, (false, true));
+    }
+
+    #[test]
+    fn changes_bump_the_settings_version_and_repeats_skip_the_write() {
+        let (service, _guard) = setup_service();
+        let before = settings_version();
+        let changed = service
+            .update(SettingsUpdateInput {
+                theme: Some("dark".to_string()),
+                ..SettingsUpdateInput::default()
+            })
+            .unwrap();
+        assert!(settings_version() > before);
+
+        let repeated = service
+            .update(SettingsUpdateInput {
+                theme: Some("Dark".to_string()),
+                ..SettingsUpdateInput::default()
+            })
+            .unwrap();
+        assert_eq!(repeated.updated_at, changed.updated_at);
+        assert_eq!(repeated.theme, "dark");
+    }
+
     #[test]
     fn defaults_are_returned_when_no_settings_exist() {
         let (service, _guard) = setup_service();
@@ -553,6 +1231,15 @@ mod tests {
             workday_end_minute: Some(17 * 60),
             theme: Some("dark".to_string()),
             ai_feedback_opt_out: None,
+            clipboard_watch_enabled: None,
+            ai_privacy_mode: None,
+            ai_debug_log_enabled: None,
+            planning_session_retention_days: None,
+            proactive_suggestions_enabled: None,
+            anomaly_notifications_enabled: None,
+            plan_critique_enabled: None,
+            default_preference_id: None,
+            productivity_score_target: None,
         };
 
         let updated = service.update(input).unwrap();
@@ -649,6 +1336,7 @@ mod tests {
         let updated = service
             .update_dashboard_config(DashboardConfigUpdateInput {
                 modules: Some(overrides),
+                custom_metrics: None,
                 last_updated_at: None,
             })
             .unwrap();
@@ -664,6 +1352,7 @@ mod tests {
         service
             .update_dashboard_config(DashboardConfigUpdateInput {
                 modules: None,
+                custom_metrics: None,
                 last_updated_at: Some(None),
             })
             .unwrap();
@@ -671,4 +1360,84 @@ mod tests {
         let reset = service.get_dashboard_config().unwrap();
         assert!(reset.last_updated_at.is_none());
     }
+
+    #[test]
+    fn dashboard_custom_metrics_are_validated_and_persisted() {
+        let (service, _guard) = setup_service();
+        let metric = |expression: &str| DashboardMetric {
+            id: "Focus-Per-Task".to_string(),
+            label: "每项专注".to_string(),
+            expression: expression.to_string(),
+        };
+
+        let err = service
+            .update_dashboard_config(DashboardConfigUpdateInput {
+                custom_metrics: Some(vec![metric("focus_minutes / streak")]),
+                ..DashboardConfigUpdateInput::default()
+            })
+            .unwrap_err();
+        assert!(matches!(err, AppError::Validation { .. }));
+        assert!(service
+            .get_dashboard_config()
+            .unwrap()
+            .custom_metrics
+            .is_empty());
+
+        service
+            .update_dashboard_config(DashboardConfigUpdateInput {
+                custom_metrics: Some(vec![metric("focus_minutes / (completed_tasks + 1)")]),
+                ..DashboardConfigUpdateInput::default()
+            })
+            .unwrap();
+        service.invalidate_cache();
+        let stored = service.get_dashboard_config().unwrap();
+        assert_eq!(stored.custom_metrics.len(), 1);
+        assert_eq!(stored.custom_metrics[0].id, "focus-per-task");
+        assert!(stored.metric_values.is_empty());
+    }
+
+    #[test]
+    fn sleep_schedule_is_validated_and_persisted() {
+        let (service, _guard) = setup_service();
+        assert!(!service.get_sleep_schedule().unwrap().enabled);
+
+        let mut schedule = SleepSchedule {
+            enabled: true,
+            ..Default::default()
+        };
+        schedule.weekdays.insert(
+            "FRI".to_string(),
+            SleepWindow {
+                bed_minute: 60,
+                wake_minute: 9 * 60,
+            },
+        );
+        schedule.weekdays.insert(
+            "holiday".to_string(),
+            SleepWindow {
+                bed_minute: 0,
+                wake_minute: 600,
+            },
+        );
+
+        let saved = service.update_sleep_schedule(schedule).unwrap();
+        assert_eq!(saved.weekdays.keys().collect::<Vec<_>>(), vec!["fri"]);
+        assert_eq!(saved.window_for(chrono::Weekday::Fri).bed_minute, 60);
+
+        let stored = service
+            .db
+            .with_connection(load_sleep_schedule)
+            .unwrap()
+            .expect("enabled schedule is returned");
+        assert_eq!(stored.weekdays, saved.weekdays);
+
+        let invalid = SleepSchedule {
+            default_window: SleepWindow {
+                bed_minute: 22 * 60,
+                wake_minute: 22 * 60,
+            },
+            ..saved
+        };
+        assert!(service.update_sleep_schedule(invalid).is_err());
+    }
 }
@@ -0,0 +1,300 @@
+//! Finds completed tasks that resemble a new one, so estimates and AI prompts
+//! can lean on how long similar work actually took.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use rusqlite::Connection;
+
+use crate::db::repositories::planning_repository::PlanningRepository;
+use crate::db::repositories::task_repository::TaskRepository;
+use crate::error::AppResult;
+use crate::models::task::{SimilarTaskMatch, SimilarTasksResult};
+
+/// How many recently completed tasks are compared against.
+const CANDIDATE_LIMIT: usize = 500;
+pub const DEFAULT_SIMILAR_LIMIT: usize = 5;
+pub const MAX_SIMILAR_LIMIT: usize = 20;
+/// Matches below this score are noise, e.g. a single shared character pair.
+const MIN_SIMILARITY: f64 = 0.3;
+const TITLE_WEIGHT: f64 = 0.75;
+const TAG_WEIGHT: f64 = 0.25;
+
+/// Completed tasks resembling `title`/`tags`, best match first.
+pub fn find_similar_tasks(
+    conn: &Connection,
+    title: &str,
+    tags: &[String],
+    exclude_task_id: Option<&str>,
+    limit: usize,
+) -> AppResult<SimilarTasksResult> {
+    let title_tokens = tokenize(title);
+    let tag_set = normalize_tags(tags);
+    if title_tokens.is_empty() && tag_set.is_empty() {
+        return Ok(SimilarTasksResult::default());
+    }
+
+    let mut scored = Vec::new();
+    for row in TaskRepository::list_completed(conn, CANDIDATE_LIMIT)? {
+        if exclude_task_id == Some(row.id.as_str()) {
+            continue;
+        }
+        let task = row.into_record()?;
+        let similarity = score(&title_tokens, &tag_set, &task.title, &task.tags);
+        if similarity >= MIN_SIMILARITY {
+            scored.push((similarity, task));
+        }
+    }
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored.truncate(limit);
+
+    let mut matches = Vec::with_capacity(scored.len());
+    for (similarity, task) in scored {
+        let actual_minutes = tracked_minutes(conn, &task.id)?;
+        matches.push(SimilarTaskMatch {
+            task_id: task.id,
+            title: task.title,
+            tags: task.tags,
+            estimated_minutes: task.estimated_minutes,
+            actual_minutes,
+            completed_at: task.completed_at,
+            similarity: (similarity * 100.0).round() / 100.0,
+        });
+    }
+
+    let typical_minutes = typical_minutes(&matches);
+    let hint = duration_hint(&matches);
+    Ok(SimilarTasksResult {
+        matches,
+        typical_minutes,
+        hint,
+        memory_notes: Vec::new(),
+    })
+}
+
+/// Prompt section describing the matches, or `None` when there are none.
+pub fn format_for_prompt(result: &SimilarTasksResult) -> Option<String> {
+    if result.matches.is_empty() {
+        return None;
+    }
+    let mut lines = Vec::with_capacity(result.matches.len());
+    for item in &result.matches {
+        let mut line = format!("- \"{}\"", item.title);
+        if let Some(minutes) = item.actual_minutes {
+            line.push_str(&format!(", took {minutes} min"));
+        }
+        if let Some(minutes) = item.estimated_minutes {
+            line.push_str(&format!(", estimated {minutes} min"));
+        }
+        if let Some(completed_at) = item.completed_at.as_deref() {
+            line.push_str(&format!(
+                ", completed {}",
+                completed_at.get(..10).unwrap_or(completed_at)
+            ));
+        }
+        lines.push(line);
+    }
+    Some(lines.join("\n"))
+}
+
+/// Blends token overlap of the titles with overlap of the tags. Tags only
+/// count when at least one side has some.
+pub fn score(
+    title_tokens: &HashSet<String>,
+    tags: &HashSet<String>,
+    candidate_title: &str,
+    candidate_tags: &[String],
+) -> f64 {
+    let title_score = jaccard(title_tokens, &tokenize(candidate_title));
+    let candidate_tags = normalize_tags(candidate_tags);
+    if tags.is_empty() && candidate_tags.is_empty() {
+        return title_score;
+    }
+    title_score * TITLE_WEIGHT + jaccard(tags, &candidate_tags) * TAG_WEIGHT
+}
+
+/// Lowercased words for alphabetic scripts and character pairs for CJK text,
+/// which has no spaces to split on.
+pub fn tokenize(text: &str) -> HashSet<String> {
+    let mut tokens = HashSet::new();
+    let mut word = String::new();
+    let mut cjk: Vec<char> = Vec::new();
+
+    let flush_word = |word: &mut String, tokens: &mut HashSet<String>| {
+        if word.chars().count() >= 2 {
+            tokens.insert(std::mem::take(word));
+        }
+        word.clear();
+    };
+    let flush_cjk = |cjk: &mut Vec<char>, tokens: &mut HashSet<String>| {
+        match cjk.len() {
+            0 => {}
+            1 => {
+                tokens.insert(cjk[0].to_string());
+            }
+            _ => {
+                for pair in cjk.windows(2) {
+                    tokens.insert(pair.iter().collect());
+                }
+            }
+        }
+        cjk.clear();
+    };
+
+    for ch in text.chars() {
+        if is_cjk(ch) {
+            flush_word(&mut word, &mut tokens);
+            cjk.push(ch);
+        } else if ch.is_alphanumeric() {
+            flush_cjk(&mut cjk, &mut tokens);
+            word.extend(ch.to_lowercase());
+        } else {
+            flush_word(&mut word, &mut tokens);
+            flush_cjk(&mut cjk, &mut tokens);
+        }
+    }
+    flush_word(&mut word, &mut tokens);
+    flush_cjk(&mut cjk, &mut tokens);
+
+    tokens
+}
+
+fn is_cjk(ch: char) -> bool {
+    matches!(ch,
+        '\u{4E00}'..='\u{9FFF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{3040}'..='\u{30FF}'
+        | '\u{AC00}'..='\u{D7AF}')
+}
+
+fn normalize_tags(tags: &[String]) -> HashSet<String> {
+    tags.iter()
+        .map(|tag| tag.trim().to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let shared = a.intersection(b).count();
+    shared as f64 / (a.len() + b.len() - shared) as f64
+}
+
+/// Minutes spent in the task's applied blocks; `None` if it was never
+/// scheduled.
+fn tracked_minutes(conn: &Connection, task_id: &str) -> AppResult<Option<i64>> {
+    let mut total = None;
+    for block in PlanningRepository::list_time_blocks_for_task(conn, task_id)? {
+        if block.applied_at.is_none() {
+            continue;
+        }
+        let start = block.actual_start_at.as_deref().unwrap_or(&block.start_at);
+        let end = block.actual_end_at.as_deref().unwrap_or(&block.end_at);
+        let (Ok(start), Ok(end)) = (
+            DateTime::parse_from_rfc3339(start),
+            DateTime::parse_from_rfc3339(end),
+        ) else {
+            continue;
+        };
+        let minutes = (end.with_timezone(&Utc) - start.with_timezone(&Utc)).num_minutes();
+        if minutes > 0 {
+            *total.get_or_insert(0) += minutes;
+        }
+    }
+    Ok(total)
+}
+
+/// Median of the best-known duration of each match: tracked time, or the
+/// estimate when the task was never scheduled.
+fn typical_minutes(matches: &[SimilarTaskMatch]) -> Option<i64> {
+    let mut durations: Vec<i64> = matches
+        .iter()
+        .filter_map(|item| item.actual_minutes.or(item.estimated_minutes))
+        .collect();
+    if durations.is_empty() {
+        return None;
+    }
+    durations.sort_unstable();
+    Some(durations[durations.len() / 2])
+}
+
+fn duration_hint(matches: &[SimilarTaskMatch]) -> Option<String> {
+    let latest = matches
+        .iter()
+        .filter(|item| item.actual_minutes.is_some())
+        .max_by(|a, b| a.completed_at.cmp(&b.completed_at))?;
+    let minutes = latest.actual_minutes?;
+    Some(format!(
+        "上次类似任务「{}」耗时约 {}",
+        latest.title,
+        format_minutes(minutes)
+    ))
+}
+
+fn format_minutes(minutes: i64) -> String {
+    if minutes < 60 {
+        return format!("{minutes} 分钟");
+    }
+    let hours = minutes as f64 / 60.0;
+    if (hours - hours.round()).abs() < 0.1 {
+        format!("{} 小时", hours.round() as i64)
+    } else {
+        format!("{:.1} 小时", hours)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_splits_words_and_cjk_pairs() {
+        let tokens = tokenize("Write 季度报告 v2!");
+        assert!(tokens.contains("write"));
+        assert!(tokens.contains("v2"));
+        assert!(tokens.contains("季度"));
+        assert!(tokens.contains("度报"));
+        assert!(tokens.contains("报告"));
+        assert_eq!(tokens.len(), 5);
+    }
+
+    #[test]
+    fn score_prefers_shared_title_and_tags() {
+        let title = tokenize("准备季度报告");
+        let tags: HashSet<String> = ["report".to_string()].into_iter().collect();
+
+        let close = score(&title, &tags, "季度报告初稿", &["Report".to_string()]);
+        let title_only = score(&title, &tags, "季度报告初稿", &[]);
+        let unrelated = score(&title, &tags, "Book dentist", &[]);
+
+        assert!(close > title_only);
+        assert!(title_only >= MIN_SIMILARITY);
+        assert_eq!(unrelated, 0.0);
+    }
+
+    #[test]
+    fn duration_hint_uses_latest_tracked_match() {
+        let item = |title: &str, actual: Option<i64>, completed: &str| SimilarTaskMatch {
+            task_id: title.to_string(),
+            title: title.to_string(),
+            tags: Vec::new(),
+            estimated_minutes: Some(60),
+            actual_minutes: actual,
+            completed_at: Some(completed.to_string()),
+            similarity: 0.5,
+        };
+        let matches = vec![
+            item("old", Some(90), "2026-01-01T00:00:00Z"),
+            item("new", Some(180), "2026-03-01T00:00:00Z"),
+            item("untracked", None, "2026-04-01T00:00:00Z"),
+        ];
+
+        assert_eq!(
+            duration_hint(&matches).as_deref(),
+            Some("上次类似任务「new」耗时约 3 小时")
+        );
+        assert_eq!(typical_minutes(&matches), Some(90));
+    }
+}
@@ -0,0 +1,340 @@
+//! Deterministic `/command` inputs for the agent chat. They are answered by
+//! calling services directly instead of the model, so they are fast and work
+//! without an API key.
+
+use chrono::{DateTime, Local, NaiveDate, Utc};
+
+use crate::models::task::TaskRecord;
+use crate::services::task_service::is_snoozed;
+
+pub const HELP_TEXT: &str = "可用命令：\n\
+/done <任务> — 将任务标记为完成（任务标题或ID）\n\
+/today — 查看今天到期或计划的任务\n\
+/plan [today|tomorrow] — 为今天或明天生成计划，默认明天\n\
+/help — 显示此帮助";
+
+pub const DONE_USAGE: &str = "用法：/done <任务标题或ID>";
+const TODAY_USAGE: &str = "用法：/today";
+const PLAN_USAGE: &str = "用法：/plan [today|tomorrow]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanDay {
+    Today,
+    Tomorrow,
+}
+
+impl PlanDay {
+    pub fn date(&self, today: NaiveDate) -> NaiveDate {
+        match self {
+            PlanDay::Today => today,
+            PlanDay::Tomorrow => today.succ_opt().unwrap_or(today),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PlanDay::Today => "今天",
+            PlanDay::Tomorrow => "明天",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlashCommand {
+    /// Marks the task matching `query` done; an empty query falls back to
+    /// the single attached task.
+    Done {
+        query: String,
+    },
+    /// Open tasks due or planned today, plus overdue ones.
+    Today,
+    /// Generates a plan for the day with the built-in optimizer.
+    Plan {
+        day: PlanDay,
+    },
+    Help,
+    /// A known command called with arguments it does not take.
+    Invalid {
+        usage: &'static str,
+    },
+    Unknown {
+        name: String,
+    },
+}
+
+impl SlashCommand {
+    /// Name reported in `tools_executed` for this command.
+    pub fn name(&self) -> String {
+        match self {
+            SlashCommand::Done { .. } => "/done".to_string(),
+            SlashCommand::Today => "/today".to_string(),
+            SlashCommand::Plan { .. } => "/plan".to_string(),
+            SlashCommand::Help | SlashCommand::Invalid { .. } => "/help".to_string(),
+            SlashCommand::Unknown { name } => format!("/{name}"),
+        }
+    }
+
+    /// Parses `message` as a slash command. Returns `None` for ordinary chat,
+    /// including messages that merely start with a path such as `/usr/bin`.
+    pub fn parse(message: &str) -> Option<Self> {
+        let rest = message.trim().strip_prefix('/')?;
+        let (name, args) = match rest.split_once(char::is_whitespace) {
+            Some((name, args)) => (name, args.trim()),
+            None => (rest, ""),
+        };
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphabetic()) {
+            return None;
+        }
+
+        let command = match name.to_ascii_lowercase().as_str() {
+            "done" => SlashCommand::Done {
+                query: args.to_string(),
+            },
+            "today" if args.is_empty() => SlashCommand::Today,
+            "today" => SlashCommand::Invalid { usage: TODAY_USAGE },
+            "plan" => match args.to_lowercase().as_str() {
+                "" | "tomorrow" | "明天" => SlashCommand::Plan {
+                    day: PlanDay::Tomorrow,
+                },
+                "today" | "今天" => SlashCommand::Plan {
+                    day: PlanDay::Today,
+                },
+                _ => SlashCommand::Invalid { usage: PLAN_USAGE },
+            },
+            "help" => SlashCommand::Help,
+            other => SlashCommand::Unknown {
+                name: other.to_string(),
+            },
+        };
+        Some(command)
+    }
+}
+
+#[derive(Debug)]
+pub enum TaskMatch<'a> {
+    One(&'a TaskRecord),
+    Many(Vec<&'a TaskRecord>),
+    None,
+}
+
+/// Finds the open task `query` refers to: an exact id, then an exact title,
+/// then a title containing the query, all case-insensitive.
+pub fn find_task<'a>(tasks: &'a [TaskRecord], query: &str) -> TaskMatch<'a> {
+    let query = query.trim().to_lowercase();
+    if let Some(task) = tasks.iter().find(|task| task.id.to_lowercase() == query) {
+        return TaskMatch::One(task);
+    }
+
+    let open = tasks.iter().filter(|task| is_open(task));
+    let exact: Vec<_> = open
+        .clone()
+        .filter(|task| task.title.trim().to_lowercase() == query)
+        .collect();
+    let candidates = if exact.is_empty() {
+        open.filter(|task| task.title.to_lowercase().contains(&query))
+            .collect()
+    } else {
+        exact
+    };
+
+    match candidates.len() {
+        0 => TaskMatch::None,
+        1 => TaskMatch::One(candidates[0]),
+        _ => TaskMatch::Many(candidates),
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DayAgenda<'a> {
+    pub due: Vec<&'a TaskRecord>,
+    pub planned: Vec<&'a TaskRecord>,
+    pub overdue: Vec<&'a TaskRecord>,
+}
+
+/// Open, unsnoozed tasks due on `day`, planned to start on `day`, or due
+/// before it, each listed once.
+pub fn agenda_for(tasks: &[TaskRecord], day: NaiveDate, now: DateTime<Utc>) -> DayAgenda<'_> {
+    let mut agenda = DayAgenda::default();
+    for task in tasks
+        .iter()
+        .filter(|task| is_open(task) && !is_snoozed(task, now))
+    {
+        let due = task.due_at.as_deref().and_then(local_date);
+        let planned = task
+            .planned_start_at
+            .as_deref()
+            .or(task.start_at.as_deref())
+            .and_then(local_date);
+        match due {
+            Some(date) if date == day => agenda.due.push(task),
+            Some(date) if date < day => agenda.overdue.push(task),
+            _ if planned == Some(day) => agenda.planned.push(task),
+            _ => {}
+        }
+    }
+    for list in [&mut agenda.due, &mut agenda.planned, &mut agenda.overdue] {
+        list.sort_by(|a, b| a.due_at.cmp(&b.due_at).then_with(|| a.title.cmp(&b.title)));
+    }
+    agenda
+}
+
+pub fn format_agenda(agenda: &DayAgenda<'_>) -> String {
+    if agenda.due.is_empty() && agenda.planned.is_empty() && agenda.overdue.is_empty() {
+        return "今天没有到期或计划的任务。".to_string();
+    }
+
+    let mut sections = Vec::new();
+    for (heading, tasks) in [
+        ("今天到期", &agenda.due),
+        ("今天计划", &agenda.planned),
+        ("已逾期", &agenda.overdue),
+    ] {
+        if tasks.is_empty() {
+            continue;
+        }
+        let lines: Vec<String> = tasks.iter().map(|task| format_task_line(task)).collect();
+        sections.push(format!(
+            "{heading}（{}）：\n{}",
+            tasks.len(),
+            lines.join("\n")
+        ));
+    }
+    sections.join("\n\n")
+}
+
+pub fn format_task_line(task: &TaskRecord) -> String {
+    let due = task
+        .due_at
+        .as_deref()
+        .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+        .map(|value| {
+            value
+                .with_timezone(&Local)
+                .format("%m-%d %H:%M")
+                .to_string()
+        });
+    match due {
+        Some(due) => format!("- {}（截止 {due}，ID: {}）", task.title, task.id),
+        None => format!("- {}（ID: {}）", task.title, task.id),
+    }
+}
+
+fn is_open(task: &TaskRecord) -> bool {
+    !matches!(task.status.as_str(), "done" | "archived")
+}
+
+fn local_date(value: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|value| value.with_timezone(&Local).date_naive())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: &str, title: &str, status: &str, due_at: Option<&str>) -> TaskRecord {
+        TaskRecord {
+            id: id.to_string(),
+            title: title.to_string(),
+            description: None,
+            status: status.to_string(),
+            priority: "medium".to_string(),
+            planned_start_at: None,
+            start_at: None,
+            due_at: due_at.map(str::to_string),
+            completed_at: None,
+            estimated_minutes: None,
+            estimated_hours: None,
+            tags: Vec::new(),
+            owner_id: None,
+            task_type: None,
+            is_recurring: false,
+            recurrence: None,
+            ai: None,
+            external_links: Vec::new(),
+            project_id: None,
+            snoozed_until: None,
+            snooze_notify: false,
+            color: None,
+            icon: None,
+            delegated_to: None,
+            waiting_on: None,
+            waiting_since: None,
+            follow_up_task_id: None,
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            updated_at: "2026-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_commands_and_leaves_plain_chat_alone() {
+        assert_eq!(
+            SlashCommand::parse("  /done  Write report "),
+            Some(SlashCommand::Done {
+                query: "Write report".to_string()
+            })
+        );
+        assert_eq!(SlashCommand::parse("/TODAY"), Some(SlashCommand::Today));
+        assert_eq!(
+            SlashCommand::parse("/plan"),
+            Some(SlashCommand::Plan {
+                day: PlanDay::Tomorrow
+            })
+        );
+        assert_eq!(
+            SlashCommand::parse("/plan today"),
+            Some(SlashCommand::Plan {
+                day: PlanDay::Today
+            })
+        );
+        assert_eq!(
+            SlashCommand::parse("/plan next week"),
+            Some(SlashCommand::Invalid { usage: PLAN_USAGE })
+        );
+        assert_eq!(
+            SlashCommand::parse("/snooze 1h"),
+            Some(SlashCommand::Unknown {
+                name: "snooze".to_string()
+            })
+        );
+        assert_eq!(SlashCommand::parse("what is due today?"), None);
+        assert_eq!(SlashCommand::parse("/usr/bin/env is missing"), None);
+        assert_eq!(SlashCommand::parse("/"), None);
+    }
+
+    #[test]
+    fn find_task_prefers_exact_matches_over_partial_ones() {
+        let tasks = vec![
+            task("t-1", "Report", "todo", None),
+            task("t-2", "Report review", "todo", None),
+            task("t-3", "Old report", "done", None),
+        ];
+
+        assert!(matches!(find_task(&tasks, "report"), TaskMatch::One(t) if t.id == "t-1"));
+        assert!(matches!(find_task(&tasks, "T-2"), TaskMatch::One(t) if t.id == "t-2"));
+        assert!(matches!(find_task(&tasks, "rep"), TaskMatch::Many(found) if found.len() == 2));
+        assert!(matches!(find_task(&tasks, "invoice"), TaskMatch::None));
+    }
+
+    #[test]
+    fn agenda_splits_due_and_overdue_tasks() {
+        let now = Utc::now();
+        let today = now.with_timezone(&Local).date_naive();
+        let due_today = Local::now().to_rfc3339();
+        let overdue = (Local::now() - chrono::Duration::days(2)).to_rfc3339();
+        let tasks = vec![
+            task("t-1", "Due today", "todo", Some(&due_today)),
+            task("t-2", "Overdue", "in_progress", Some(&overdue)),
+            task("t-3", "Finished", "done", Some(&due_today)),
+            task("t-4", "Someday", "todo", None),
+        ];
+
+        let agenda = agenda_for(&tasks, today, now);
+        assert_eq!(agenda.due.len(), 1);
+        assert_eq!(agenda.due[0].id, "t-1");
+        assert_eq!(agenda.overdue.len(), 1);
+        assert_eq!(agenda.overdue[0].id, "t-2");
+        assert!(agenda.planned.is_empty());
+    }
+}
@@ -0,0 +1,198 @@
+//! Task aging: open tasks nobody has touched in a while, grouped by age with
+//! a suggested way to clear each one out of the backlog.
+
+use chrono::{DateTime, Utc};
+
+use crate::models::task::{
+    StaleAgeBucket, StaleTaskDisposition, StaleTaskEntry, StaleTaskReport, TaskRecord,
+};
+use crate::services::task_service::is_snoozed;
+
+pub const DEFAULT_STALE_DAYS: u32 = 14;
+pub const MAX_STALE_DAYS: u32 = 3650;
+/// Lower bounds of the age buckets, in days.
+const BUCKET_BOUNDS: [i64; 5] = [0, 7, 30, 90, 180];
+/// Low-stakes tasks this old are more likely dead than deferred.
+const ARCHIVE_AFTER_DAYS: i64 = 90;
+/// Estimates above this are hard to start in one sitting.
+const BREAK_DOWN_MINUTES: i64 = 240;
+
+/// Open, unsnoozed tasks last updated at least `min_days` before `now`.
+pub fn build_stale_report(
+    tasks: &[TaskRecord],
+    min_days: u32,
+    now: DateTime<Utc>,
+) -> StaleTaskReport {
+    let mut buckets: Vec<StaleAgeBucket> = BUCKET_BOUNDS
+        .iter()
+        .enumerate()
+        .map(|(index, &min)| {
+            let max = BUCKET_BOUNDS.get(index + 1).copied();
+            StaleAgeBucket {
+                label: match max {
+                    Some(max) => format!("{min}-{} 天", max - 1),
+                    None => format!("{min} 天以上"),
+                },
+                min_days: min,
+                max_days: max,
+                count: 0,
+            }
+        })
+        .filter(|bucket| bucket.max_days.is_none_or(|max| max > i64::from(min_days)))
+        .collect();
+
+    let mut entries = Vec::new();
+    for task in tasks {
+        if matches!(task.status.as_str(), "done" | "archived") || is_snoozed(task, now) {
+            continue;
+        }
+        let Ok(updated_at) = DateTime::parse_from_rfc3339(&task.updated_at) else {
+            continue;
+        };
+        let days = (now - updated_at.with_timezone(&Utc)).num_days();
+        if days < i64::from(min_days) {
+            continue;
+        }
+
+        let Some(bucket) = buckets
+            .iter_mut()
+            .rev()
+            .find(|bucket| days >= bucket.min_days)
+        else {
+            continue;
+        };
+        bucket.count += 1;
+        let (suggestion, reason) = suggest(task, days, now);
+        entries.push(StaleTaskEntry {
+            task_id: task.id.clone(),
+            title: task.title.clone(),
+            status: task.status.clone(),
+            priority: task.priority.clone(),
+            due_at: task.due_at.clone(),
+            last_touched_at: task.updated_at.clone(),
+            days_untouched: days,
+            bucket: bucket.label.clone(),
+            suggestion,
+            reason,
+        });
+    }
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.days_untouched));
+
+    StaleTaskReport {
+        min_days,
+        generated_at: now.to_rfc3339(),
+        buckets,
+        tasks: entries,
+    }
+}
+
+fn suggest(task: &TaskRecord, days: i64, now: DateTime<Utc>) -> (StaleTaskDisposition, String) {
+    let past_due = task
+        .due_at
+        .as_deref()
+        .and_then(|due| DateTime::parse_from_rfc3339(due).ok())
+        .is_some_and(|due| due < now);
+    let estimated_minutes = task.estimated_minutes.or_else(|| {
+        task.estimated_hours
+            .map(|hours| (hours * 60.0).round() as i64)
+    });
+
+    if days >= ARCHIVE_AFTER_DAYS && matches!(task.priority.as_str(), "low" | "medium") {
+        return (
+            StaleTaskDisposition::Archive,
+            format!("{days} 天未更新且优先级不高，可能已不再需要"),
+        );
+    }
+    if estimated_minutes.is_some_and(|minutes| minutes > BREAK_DOWN_MINUTES) {
+        return (
+            StaleTaskDisposition::BreakDown,
+            "预计耗时超过 4 小时，拆分成小步骤更容易开始".to_string(),
+        );
+    }
+    let reason = if past_due {
+        "已过截止时间，需要重新评估时间和截止日期".to_string()
+    } else if estimated_minutes.is_none() {
+        "缺少时间预估，补充后才能排进计划".to_string()
+    } else {
+        format!("{days} 天未更新，建议重新评估优先级和预估")
+    };
+    (StaleTaskDisposition::Reestimate, reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn task(id: &str, priority: &str, days_ago: i64, now: DateTime<Utc>) -> TaskRecord {
+        let updated_at = (now - Duration::days(days_ago)).to_rfc3339();
+        TaskRecord {
+            id: id.to_string(),
+            title: id.to_string(),
+            description: None,
+            status: "todo".to_string(),
+            priority: priority.to_string(),
+            planned_start_at: None,
+            start_at: None,
+            due_at: None,
+            completed_at: None,
+            estimated_minutes: Some(60),
+            estimated_hours: None,
+            tags: Vec::new(),
+            owner_id: None,
+            task_type: None,
+            is_recurring: false,
+            recurrence: None,
+            ai: None,
+            external_links: Vec::new(),
+            project_id: None,
+            snoozed_until: None,
+            snooze_notify: false,
+            color: None,
+            icon: None,
+            delegated_to: None,
+            waiting_on: None,
+            waiting_since: None,
+            follow_up_task_id: None,
+            created_at: updated_at.clone(),
+            updated_at,
+        }
+    }
+
+    #[test]
+    fn stale_tasks_are_bucketed_with_suggestions() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+        let mut big = task("big", "high", 40, now);
+        big.estimated_hours = Some(6.0);
+        big.estimated_minutes = None;
+        let mut done = task("done", "low", 200, now);
+        done.status = "done".to_string();
+        let mut snoozed = task("snoozed", "low", 200, now);
+        snoozed.snoozed_until = Some((now + Duration::days(3)).to_rfc3339());
+        let tasks = vec![
+            task("fresh", "medium", 3, now),
+            task("old", "low", 120, now),
+            task("urgent", "urgent", 120, now),
+            big,
+            task("recent", "medium", 20, now),
+            done,
+            snoozed,
+        ];
+
+        let report = build_stale_report(&tasks, 14, now);
+        let ids: Vec<_> = report.tasks.iter().map(|t| t.task_id.as_str()).collect();
+        assert_eq!(ids, ["old", "urgent", "big", "recent"]);
+        assert_eq!(report.tasks[0].suggestion, StaleTaskDisposition::Archive);
+        assert_eq!(report.tasks[1].suggestion, StaleTaskDisposition::Reestimate);
+        assert_eq!(report.tasks[2].suggestion, StaleTaskDisposition::BreakDown);
+        assert_eq!(report.tasks[3].bucket, "7-29 天");
+
+        // Buckets entirely below the threshold are left out
+        let counts: Vec<_> = report
+            .buckets
+            .iter()
+            .map(|bucket| (bucket.min_days, bucket.count))
+            .collect();
+        assert_eq!(counts, [(7, 1), (30, 1), (90, 2), (180, 0)]);
+    }
+}
@@ -0,0 +1,572 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use rusqlite::Connection;
+use serde_json::json;
+use tauri::{AppHandle, Emitter};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::db::repositories::planning_repository::{PlanningRepository, PlanningTimeBlockRow};
+use crate::db::repositories::suggestion_repository::{SuggestionRepository, SuggestionRow};
+use crate::db::repositories::task_repository::TaskRepository;
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::ai_feedback::{AiFeedbackSentiment, AiFeedbackSurface};
+use crate::models::settings::FeatureFlag;
+use crate::models::suggestion::{SuggestionKind, SuggestionRecord, SuggestionStatus};
+use crate::models::task::TaskRecord;
+use crate::services::feedback_service::{FeedbackService, FeedbackSubmission};
+use crate::services::job_supervisor::spawn_supervised;
+use crate::services::settings_service::{
+    load_feature_enabled, load_proactive_suggestions_enabled, load_workday_window,
+};
+use crate::services::task_service::is_snoozed;
+
+pub const SUGGESTIONS_EVENT: &str = "agent://suggestions";
+
+/// A task due within this window without an applied block is at risk.
+const AT_RISK_WINDOW_HOURS: i64 = 48;
+/// How far an at-risk deadline is proposed to move.
+const RESCHEDULE_SHIFT_DAYS: i64 = 2;
+const BIG_TASK_MINUTES: i64 = 240;
+const BREAKDOWN_PART_MINUTES: i64 = 90;
+const OVERLOAD_LOOKAHEAD_DAYS: i64 = 7;
+/// Tasks and days suggested this recently are not suggested again.
+const REPEAT_COOLDOWN_DAYS: i64 = 3;
+const OPEN_BLOCK_STATUSES: [&str; 2] = ["planned", "in_progress"];
+pub(crate) const POLL_INTERVAL: StdDuration = StdDuration::from_secs(60 * 60);
+
+/// Composes today's suggestions: at most one of each kind, so at most three.
+/// Returns nothing when today's suggestions already exist.
+pub fn compose_suggestions(
+    conn: &Connection,
+    now: DateTime<Utc>,
+    today: NaiveDate,
+) -> AppResult<Vec<SuggestionRecord>> {
+    let today_key = today.to_string();
+    if !SuggestionRepository::list_for_date(conn, &today_key)?.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let timestamp = now.to_rfc3339();
+    SuggestionRepository::expire_pending_before(conn, &today_key, &timestamp)?;
+
+    let tasks = TaskRepository::list_all(conn)?
+        .into_iter()
+        .map(|row| row.into_record())
+        .collect::<AppResult<Vec<_>>>()?
+        .into_iter()
+        .filter(|task| !matches!(task.status.as_str(), "done" | "archived"))
+        .filter(|task| !is_snoozed(task, now))
+        .collect::<Vec<_>>();
+    let blocks = PlanningRepository::list_applied_time_blocks_between(
+        conn,
+        &(now - Duration::days(1)).to_rfc3339(),
+        &(now + Duration::days(OVERLOAD_LOOKAHEAD_DAYS + 1)).to_rfc3339(),
+    )?
+    .into_iter()
+    .filter(|block| OPEN_BLOCK_STATUSES.contains(&block.status.as_str()))
+    .collect::<Vec<_>>();
+    let cooldown_since = (today - Duration::days(REPEAT_COOLDOWN_DAYS)).to_string();
+    let (workday_start, workday_end) = load_workday_window(conn)?;
+
+    let mut drafts = Vec::new();
+    let recent = SuggestionRepository::recent_targets(
+        conn,
+        SuggestionKind::RescheduleAtRisk.as_str(),
+        &cooldown_since,
+    )?;
+    drafts.extend(at_risk_suggestion(&tasks, &blocks, &recent, now));
+    let recent = SuggestionRepository::recent_targets(
+        conn,
+        SuggestionKind::BreakDownTask.as_str(),
+        &cooldown_since,
+    )?;
+    drafts.extend(breakdown_suggestion(&tasks, &recent));
+    let recent = SuggestionRepository::recent_targets(
+        conn,
+        SuggestionKind::FreeUpDay.as_str(),
+        &cooldown_since,
+    )?;
+    let capacity = i64::from(workday_end - workday_start);
+    drafts.extend(overload_suggestion(
+        &tasks, &blocks, &recent, today, capacity,
+    ));
+
+    let mut composed = Vec::new();
+    for draft in drafts {
+        let record = SuggestionRecord {
+            id: Uuid::new_v4().to_string(),
+            kind: draft.kind,
+            title: draft.title,
+            reason: draft.reason,
+            task_id: draft.task_id,
+            target_date: draft.target_date,
+            payload: draft.payload,
+            status: SuggestionStatus::Pending,
+            suggested_for: today_key.clone(),
+            created_at: timestamp.clone(),
+            resolved_at: None,
+        };
+        SuggestionRepository::upsert(conn, &SuggestionRow::from_record(&record)?)?;
+        composed.push(record);
+    }
+
+    debug!(target: "app::suggestions", count = composed.len(), date = %today_key, "daily suggestions composed");
+    Ok(composed)
+}
+
+struct SuggestionDraft {
+    kind: SuggestionKind,
+    title: String,
+    reason: String,
+    task_id: Option<String>,
+    target_date: Option<String>,
+    payload: serde_json::Value,
+}
+
+fn parse_utc(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|value| value.with_timezone(&Utc))
+}
+
+fn estimate_minutes(task: &TaskRecord) -> Option<i64> {
+    task.estimated_minutes
+        .or_else(|| {
+            task.estimated_hours
+                .map(|hours| (hours * 60.0).round() as i64)
+        })
+        .filter(|minutes| *minutes > 0)
+}
+
+fn priority_rank(priority: &str) -> u8 {
+    match priority {
+        "urgent" => 3,
+        "high" => 2,
+        "medium" => 1,
+        _ => 0,
+    }
+}
+
+fn block_minutes(block: &PlanningTimeBlockRow) -> i64 {
+    match (parse_utc(&block.start_at), parse_utc(&block.end_at)) {
+        (Some(start), Some(end)) => (end - start).num_minutes().max(0),
+        _ => 0,
+    }
+}
+
+/// The most pressing open task that is due soon, or overdue, with no applied
+/// block before its deadline.
+fn at_risk_suggestion(
+    tasks: &[TaskRecord],
+    blocks: &[PlanningTimeBlockRow],
+    recent: &HashSet<String>,
+    now: DateTime<Utc>,
+) -> Option<SuggestionDraft> {
+    let horizon = now + Duration::hours(AT_RISK_WINDOW_HOURS);
+    let (task, due) = tasks
+        .iter()
+        .filter(|task| !recent.contains(&task.id))
+        .filter_map(|task| Some((task, parse_utc(task.due_at.as_deref()?)?)))
+        .filter(|(_, due)| *due <= horizon)
+        .filter(|(task, due)| {
+            !blocks.iter().any(|block| {
+                block.task_id == task.id
+                    && parse_utc(&block.start_at).is_some_and(|start| start < *due)
+            })
+        })
+        .min_by(|(a, a_due), (b, b_due)| {
+            a_due
+                .cmp(b_due)
+                .then(priority_rank(&b.priority).cmp(&priority_rank(&a.priority)))
+        })?;
+
+    let reason = if due <= now {
+        "任务已逾期，且还没有安排时间块".to_string()
+    } else {
+        format!(
+            "任务将在 {} 小时内到期，但还没有安排时间块",
+            (due - now).num_hours().max(1)
+        )
+    };
+    let proposed_due = due.max(now) + Duration::days(RESCHEDULE_SHIFT_DAYS);
+
+    Some(SuggestionDraft {
+        kind: SuggestionKind::RescheduleAtRisk,
+        title: format!("重新安排「{}」", task.title),
+        reason,
+        task_id: Some(task.id.clone()),
+        target_date: None,
+        payload: json!({
+            "dueAt": task.due_at,
+            "estimatedMinutes": estimate_minutes(task),
+            "proposedDueAt": proposed_due.to_rfc3339(),
+        }),
+    })
+}
+
+/// The largest open task estimated at more than [`BIG_TASK_MINUTES`].
+fn breakdown_suggestion(tasks: &[TaskRecord], recent: &HashSet<String>) -> Option<SuggestionDraft> {
+    let (task, minutes) = tasks
+        .iter()
+        .filter(|task| !task.is_recurring && !recent.contains(&task.id))
+        .filter_map(|task| Some((task, estimate_minutes(task)?)))
+        .filter(|(_, minutes)| *minutes >= BIG_TASK_MINUTES)
+        .max_by_key(|(task, minutes)| (*minutes, priority_rank(&task.priority)))?;
+
+    let parts = (minutes + BREAKDOWN_PART_MINUTES - 1) / BREAKDOWN_PART_MINUTES;
+    Some(SuggestionDraft {
+        kind: SuggestionKind::BreakDownTask,
+        title: format!("拆分「{}」", task.title),
+        reason: format!(
+            "预计需要 {:.1} 小时，拆成 {} 个小任务更容易安排",
+            minutes as f64 / 60.0,
+            parts
+        ),
+        task_id: Some(task.id.clone()),
+        target_date: None,
+        payload: json!({
+            "estimatedMinutes": minutes,
+            "suggestedParts": parts,
+            "partMinutes": BREAKDOWN_PART_MINUTES,
+        }),
+    })
+}
+
+/// The first upcoming day whose open applied blocks exceed the workday,
+/// with the movable blocks that would bring it back under, lowest priority
+/// first.
+fn overload_suggestion(
+    tasks: &[TaskRecord],
+    blocks: &[PlanningTimeBlockRow],
+    recent: &HashSet<String>,
+    today: NaiveDate,
+    capacity_minutes: i64,
+) -> Option<SuggestionDraft> {
+    let last_day = today + Duration::days(OVERLOAD_LOOKAHEAD_DAYS - 1);
+    let mut days: BTreeMap<NaiveDate, Vec<&PlanningTimeBlockRow>> = BTreeMap::new();
+    for block in blocks {
+        let Ok(start) = DateTime::parse_from_rfc3339(&block.start_at) else {
+            continue;
+        };
+        let day = start.date_naive();
+        if day >= today && day <= last_day {
+            days.entry(day).or_default().push(block);
+        }
+    }
+
+    let priorities: HashMap<&str, u8> = tasks
+        .iter()
+        .map(|task| (task.id.as_str(), priority_rank(&task.priority)))
+        .collect();
+    let (day, day_blocks, scheduled) = days
+        .into_iter()
+        .filter(|(day, _)| !recent.contains(&day.to_string()))
+        .map(|(day, day_blocks)| {
+            let scheduled = day_blocks
+                .iter()
+                .map(|block| block_minutes(block))
+                .sum::<i64>();
+            (day, day_blocks, scheduled)
+        })
+        .find(|(_, _, scheduled)| *scheduled > capacity_minutes)?;
+
+    let mut movable = day_blocks
+        .into_iter()
+        .filter(|block| block.flexibility.as_deref() != Some("fixed"))
+        .collect::<Vec<_>>();
+    movable.sort_by_key(|block| {
+        (
+            priorities.get(block.task_id.as_str()).copied().unwrap_or(0),
+            std::cmp::Reverse(block_minutes(block)),
+        )
+    });
+    let overflow = scheduled - capacity_minutes;
+    let mut freed = 0;
+    let mut block_ids = Vec::new();
+    for block in movable {
+        if freed >= overflow {
+            break;
+        }
+        freed += block_minutes(block);
+        block_ids.push(block.id.clone());
+    }
+    if block_ids.is_empty() {
+        return None;
+    }
+
+    Some(SuggestionDraft {
+        kind: SuggestionKind::FreeUpDay,
+        title: format!("减轻 {} 的安排", day),
+        reason: format!(
+            "当天已安排 {:.1} 小时，超出工作时长 {:.1} 小时",
+            scheduled as f64 / 60.0,
+            overflow as f64 / 60.0
+        ),
+        task_id: None,
+        target_date: Some(day.to_string()),
+        payload: json!({
+            "scheduledMinutes": scheduled,
+            "capacityMinutes": capacity_minutes,
+            "blockIds": block_ids,
+        }),
+    })
+}
+
+/// Opt-in proactive agent: composes daily suggestions and records how the
+/// user responds to them.
+pub struct SuggestionService {
+    db: DbPool,
+    feedback: Arc<FeedbackService>,
+    job_started: AtomicBool,
+}
+
+impl SuggestionService {
+    pub fn new(db: DbPool, feedback: Arc<FeedbackService>) -> Self {
+        Self {
+            db,
+            feedback,
+            job_started: AtomicBool::new(false),
+        }
+    }
+
+    /// Composes today's suggestions if the proactive mode is on and they
+    /// were not composed yet. Returns only newly composed suggestions.
+    pub fn compose_daily(&self, now: DateTime<Utc>) -> AppResult<Vec<SuggestionRecord>> {
+        let mut conn = self.db.get_connection()?;
+        if !load_feature_enabled(&conn, FeatureFlag::ProactiveAgent)?
+            || !load_proactive_suggestions_enabled(&conn)?
+        {
+            return Ok(Vec::new());
+        }
+        let today = now.with_timezone(&Local).date_naive();
+        let tx = conn.transaction()?;
+        let composed = compose_suggestions(&tx, now, today)?;
+        tx.commit()?;
+        Ok(composed)
+    }
+
+    /// Suggestions with the given status, newest first. Defaults to pending.
+    pub fn list(
+        &self,
+        status: Option<SuggestionStatus>,
+        limit: usize,
+    ) -> AppResult<Vec<SuggestionRecord>> {
+        let status = status.unwrap_or_default();
+        self.db.with_connection(|conn| {
+            SuggestionRepository::list_by_status(conn, status.as_str(), limit)?
+                .into_iter()
+                .map(SuggestionRow::into_record)
+                .collect()
+        })
+    }
+
+    pub fn accept(&self, id: &str, note: Option<String>) -> AppResult<SuggestionRecord> {
+        self.resolve(
+            id,
+            SuggestionStatus::Accepted,
+            AiFeedbackSentiment::Up,
+            note,
+        )
+    }
+
+    pub fn dismiss(&self, id: &str, note: Option<String>) -> AppResult<SuggestionRecord> {
+        self.resolve(
+            id,
+            SuggestionStatus::Dismissed,
+            AiFeedbackSentiment::Down,
+            note,
+        )
+    }
+
+    fn resolve(
+        &self,
+        id: &str,
+        status: SuggestionStatus,
+        sentiment: AiFeedbackSentiment,
+        note: Option<String>,
+    ) -> AppResult<SuggestionRecord> {
+        let mut record = self
+            .db
+            .with_connection(|conn| SuggestionRepository::find(conn, id))?
+            .ok_or_else(AppError::not_found)?
+            .into_record()?;
+        if record.status != SuggestionStatus::Pending {
+            return Err(AppError::validation("该建议已处理，无法重复操作"));
+        }
+        record.status = status;
+        record.resolved_at = Some(Utc::now().to_rfc3339());
+
+        let row = SuggestionRow::from_record(&record)?;
+        self.db
+            .with_connection(|conn| SuggestionRepository::upsert(conn, &row))?;
+        info!(target: "app::suggestions", suggestion_id = %id, status = %status, "suggestion resolved");
+
+        if !self.feedback.is_opted_out()? {
+            let submission = FeedbackSubmission {
+                surface: AiFeedbackSurface::Recommendation,
+                session_id: Some(record.id.clone()),
+                sentiment,
+                note: note
+                    .map(|value| value.trim().to_string())
+                    .filter(|value| !value.is_empty()),
+                prompt_snapshot: record.title.clone(),
+                context_snapshot: json!({
+                    "kind": record.kind,
+                    "reason": record.reason,
+                    "payload": record.payload,
+                }),
+            };
+            if let Err(err) = self.feedback.submit_feedback(&submission) {
+                warn!(target: "app::suggestions", error = %err, "failed to record suggestion feedback");
+            }
+        }
+
+        Ok(record)
+    }
+
+    /// Checks hourly whether today's suggestions are due and emits
+    /// [`SUGGESTIONS_EVENT`] with the ones it composed.
+    pub fn ensure_daily_job(self: &Arc<Self>, app: AppHandle) -> AppResult<()> {
+        if self
+            .job_started
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Ok(());
+        }
+
+        let runner = Arc::clone(self);
+        if let Err(err) = spawn_supervised(self.db.clone(), "proactive-suggestions", move || loop {
+            match runner.compose_daily(Utc::now()) {
+                Ok(composed) if !composed.is_empty() => {
+                    if let Err(err) = app.emit(SUGGESTIONS_EVENT, &composed) {
+                        warn!(target: "app::suggestions", error = %err, "failed to emit suggestions event");
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    error!(target: "app::suggestions", error = %err, "composing daily suggestions failed")
+                }
+            }
+            thread::sleep(POLL_INTERVAL);
+        }) {
+            self.job_started.store(false, Ordering::SeqCst);
+            return Err(err);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::task::TaskCreateInput;
+    use crate::services::settings_service::{SettingsService, SettingsUpdateInput};
+    use crate::services::task_service::TaskService;
+    use rusqlite::params;
+
+    #[test]
+    fn daily_suggestions_cover_each_kind_once_and_feed_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = DbPool::new(dir.path().join("suggestions.sqlite")).unwrap();
+        let settings = Arc::new(SettingsService::new(db.clone()).unwrap());
+        let feedback = Arc::new(FeedbackService::new(db.clone(), Arc::clone(&settings)));
+        let tasks = TaskService::new(db.clone());
+        let service = SuggestionService::new(db.clone(), Arc::clone(&feedback));
+
+        let now = Utc::now();
+        assert!(service.compose_daily(now).unwrap().is_empty());
+        settings
+            .update(SettingsUpdateInput {
+                proactive_suggestions_enabled: Some(true),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let urgent = tasks
+            .create_task(TaskCreateInput {
+                title: "提交报销".into(),
+                due_at: Some((now + Duration::hours(10)).to_rfc3339()),
+                ..Default::default()
+            })
+            .unwrap();
+        let big = tasks
+            .create_task(TaskCreateInput {
+                title: "重写同步模块".into(),
+                estimated_minutes: Some(360),
+                ..Default::default()
+            })
+            .unwrap();
+        let today = now.date_naive();
+        let tomorrow = today + Duration::days(1);
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO planning_sessions (id, task_ids, generated_at, status) VALUES ('s-1', '[]', ?1, 'applied')",
+                params![now.to_rfc3339()],
+            )?;
+            conn.execute(
+                "INSERT INTO planning_options (id, session_id, rank) VALUES ('o-1', 's-1', 1)",
+                [],
+            )?;
+            for (id, hour) in [("b-1", 9), ("b-2", 14)] {
+                let start = tomorrow.and_hms_opt(hour, 0, 0).unwrap().and_utc();
+                conn.execute(
+                    "INSERT INTO planning_time_blocks (id, option_id, task_id, start_at, end_at, applied_at) \
+                     VALUES (?1, 'o-1', ?2, ?3, ?4, ?3)",
+                    params![
+                        id,
+                        big.id,
+                        start.to_rfc3339(),
+                        (start + Duration::hours(5)).to_rfc3339()
+                    ],
+                )?;
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        let compose = |now: DateTime<Utc>, today: NaiveDate| {
+            db.with_connection(|conn| compose_suggestions(conn, now, today))
+                .unwrap()
+        };
+        let composed = compose(now, today);
+        let kinds = composed.iter().map(|s| s.kind).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                SuggestionKind::RescheduleAtRisk,
+                SuggestionKind::BreakDownTask,
+                SuggestionKind::FreeUpDay
+            ]
+        );
+        assert_eq!(composed[0].task_id.as_deref(), Some(urgent.id.as_str()));
+        assert_eq!(composed[1].payload["suggestedParts"], 4);
+        assert_eq!(composed[2].target_date, Some(tomorrow.to_string()));
+        assert!(compose(now, today).is_empty());
+
+        let accepted = service.accept(&composed[0].id, None).unwrap();
+        assert_eq!(accepted.status, SuggestionStatus::Accepted);
+        assert!(service.accept(&composed[0].id, None).is_err());
+        service
+            .dismiss(&composed[1].id, Some("已经拆过了".into()))
+            .unwrap();
+        let recorded = feedback
+            .get_recent_feedback(AiFeedbackSurface::Recommendation, None)
+            .unwrap();
+        assert_eq!(recorded.len(), 2);
+
+        // The next day the leftover is expired and nothing repeats.
+        assert!(compose(now + Duration::days(1), tomorrow).is_empty());
+        let expired = service.list(Some(SuggestionStatus::Expired), 10).unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].kind, SuggestionKind::FreeUpDay);
+    }
+}
@@ -0,0 +1,194 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use tracing::debug;
+
+use crate::db::repositories::change_repository::ChangeRepository;
+use crate::db::repositories::planning_repository::PlanningRepository;
+use crate::db::repositories::project_repository::ProjectRepository;
+use crate::db::repositories::task_repository::TaskRepository;
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::goal::Goal;
+use crate::models::settings::FeatureFlag;
+use crate::models::sync::{ChangeSet, DeletedEntity, SyncEntity};
+use crate::services::goal_service::{GoalService, GOAL_COLUMNS};
+use crate::services::settings_service::load_feature_enabled;
+
+pub const DEFAULT_CHANGE_LIMIT: usize = 500;
+pub const MAX_CHANGE_LIMIT: usize = 2000;
+
+/// Serves incremental refreshes from the trigger-maintained change log, so
+/// the frontend does not re-fetch whole lists after every command.
+pub struct SyncService {
+    db: DbPool,
+}
+
+impl SyncService {
+    pub fn new(db: DbPool) -> Self {
+        Self { db }
+    }
+
+    /// Entities changed after `revision`. Start from 0 for a full load;
+    /// keep calling while `has_more` is set.
+    pub fn changes_since(&self, revision: i64, limit: Option<usize>) -> AppResult<ChangeSet> {
+        if revision < 0 {
+            return Err(AppError::validation("修订号不能为负数"));
+        }
+        if !self
+            .db
+            .with_read_connection(|conn| load_feature_enabled(conn, FeatureFlag::Sync))?
+        {
+            return Err(AppError::validation("增量同步功能已关闭"));
+        }
+        let limit = limit
+            .unwrap_or(DEFAULT_CHANGE_LIMIT)
+            .clamp(1, MAX_CHANGE_LIMIT);
+
+        let changes = self
+            .db
+            .with_connection(|conn| collect_changes(conn, revision, limit))?;
+        debug!(
+            target: "app::sync",
+            since = revision,
+            revision = changes.revision,
+            has_more = changes.has_more,
+            "change set built"
+        );
+        Ok(changes)
+    }
+}
+
+fn collect_changes(conn: &Connection, since: i64, limit: usize) -> AppResult<ChangeSet> {
+    let current = ChangeRepository::current_revision(conn)?;
+    // One extra row tells whether another page is waiting.
+    let mut rows = ChangeRepository::list_since(conn, since, limit + 1)?;
+    let has_more = rows.len() > limit;
+    rows.truncate(limit);
+
+    let mut changes = ChangeSet {
+        since,
+        revision: if has_more {
+            rows.last().map(|row| row.revision).unwrap_or(since)
+        } else {
+            current.max(since)
+        },
+        has_more,
+        tasks: Vec::new(),
+        projects: Vec::new(),
+        goals: Vec::new(),
+        time_blocks: Vec::new(),
+        deleted: Vec::new(),
+    };
+
+    for row in rows {
+        let entity = SyncEntity::try_from(row.entity.as_str()).map_err(AppError::validation)?;
+        let found = !row.deleted
+            && match entity {
+                SyncEntity::Task => TaskRepository::find_by_id(conn, &row.entity_id)?
+                    .map(|task| task.into_record())
+                    .transpose()?
+                    .map(|task| changes.tasks.push(task))
+                    .is_some(),
+                SyncEntity::Project => ProjectRepository::find(conn, &row.entity_id)?
+                    .map(|project| project.into_record())
+                    .transpose()?
+                    .map(|project| changes.projects.push(project))
+                    .is_some(),
+                SyncEntity::Goal => find_goal(conn, &row.entity_id)?
+                    .map(|goal| changes.goals.push(goal))
+                    .is_some(),
+                SyncEntity::TimeBlock => PlanningRepository::find_time_block(conn, &row.entity_id)?
+                    .map(|block| block.into_record())
+                    .transpose()?
+                    .map(|block| changes.time_blocks.push(block))
+                    .is_some(),
+            };
+        // A row removed after its change was logged counts as deleted.
+        if !found {
+            changes.deleted.push(DeletedEntity {
+                entity,
+                id: row.entity_id,
+                revision: row.revision,
+            });
+        }
+    }
+
+    Ok(changes)
+}
+
+fn find_goal(conn: &Connection, id: &str) -> AppResult<Option<Goal>> {
+    let sql = format!("SELECT {GOAL_COLUMNS} FROM goals WHERE id = ?");
+    let goal = conn
+        .query_row(&sql, params![id], GoalService::map_goal_row)
+        .optional()?;
+    Ok(goal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::project::ProjectCreateInput;
+    use crate::models::task::{TaskCreateInput, TaskUpdateInput};
+    use crate::services::project_service::ProjectService;
+    use crate::services::task_service::TaskService;
+    use tempfile::tempdir;
+
+    #[test]
+    fn changes_since_returns_updates_and_deletions_after_revision() {
+        let dir = tempdir().unwrap();
+        let pool = DbPool::new(dir.path().join("sync.sqlite")).unwrap();
+        let tasks = TaskService::new(pool.clone());
+        let projects = ProjectService::new(pool.clone());
+        let service = SyncService::new(pool);
+
+        let first = tasks
+            .create_task(TaskCreateInput {
+                title: "整理发票".into(),
+                ..Default::default()
+            })
+            .unwrap();
+        let second = tasks
+            .create_task(TaskCreateInput {
+                title: "回复邮件".into(),
+                ..Default::default()
+            })
+            .unwrap();
+        let initial = service.changes_since(0, None).unwrap();
+        assert_eq!(initial.tasks.len(), 2);
+        assert!(!initial.has_more);
+
+        tasks
+            .update_task(
+                &first.id,
+                TaskUpdateInput {
+                    status: Some("done".into()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        tasks.delete_task(&second.id).unwrap();
+        let project = projects
+            .create(ProjectCreateInput {
+                name: "报销".into(),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let delta = service.changes_since(initial.revision, None).unwrap();
+        assert_eq!(delta.tasks.len(), 1);
+        assert_eq!(delta.tasks[0].status, "done");
+        assert_eq!(delta.projects[0].id, project.id);
+        assert_eq!(delta.deleted.len(), 1);
+        assert_eq!(delta.deleted[0].entity, SyncEntity::Task);
+        assert_eq!(delta.deleted[0].id, second.id);
+
+        let paged = service.changes_since(initial.revision, Some(1)).unwrap();
+        assert!(paged.has_more);
+        assert_eq!(paged.tasks.len() + paged.deleted.len(), 1);
+        assert!(service
+            .changes_since(delta.revision, None)
+            .unwrap()
+            .tasks
+            .is_empty());
+        assert!(service.changes_since(-1, None).is_err());
+    }
+}
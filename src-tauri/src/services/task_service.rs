@@ -1,34 +1,76 @@
-use std::collections::HashSet;
-
-use chrono::{DateTime, Utc};
-
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::Connection;
+use serde_json::json;
+
+use crate::db::repositories::planning_repository::{PlanningRepository, PlanningTimeBlockRow};
+use crate::db::repositories::project_repository::ProjectRepository;
 use crate::db::repositories::task_repository::{TaskRepository, TaskRow};
 use crate::db::DbPool;
 use crate::error::{AppError, AppResult};
+use crate::models::project::{ProjectRecord, ProjectStatus};
 use crate::models::task::{
-    TaskAiInsights, TaskCreateInput, TaskRecord, TaskRecurrence, TaskUpdateInput,
+    SimilarTasksQuery, SimilarTasksResult, StaleTaskReport, TaskAiInsights, TaskCreateInput,
+    TaskDateShift, TaskMatrixSnapshot, TaskRecord, TaskRecurrence, TaskShiftDatesInput,
+    TaskShiftDatesResult, TaskSnoozeEnded, TaskUpdateInput,
 };
-use tracing::{debug, info};
+use crate::services::job_supervisor::spawn_supervised;
+use crate::services::priority_matrix::{build_matrix_snapshot, load_goal_linked_task_ids};
+use crate::services::progress::{scaled_percent, ProgressReporter};
+use crate::services::reminder_service::sync_block_reminders;
+use crate::services::schedule_optimizer::ConflictKind;
+use crate::services::settings_service::load_delegation;
+use crate::services::similar_tasks::{
+    find_similar_tasks, DEFAULT_SIMILAR_LIMIT, MAX_SIMILAR_LIMIT,
+};
+use crate::services::stale_tasks::{build_stale_report, DEFAULT_STALE_DAYS, MAX_STALE_DAYS};
+use crate::utils::appearance::{normalize_color, normalize_icon};
+use tauri::{AppHandle, Emitter};
+use tracing::{debug, error, info, warn};
+
+pub const TASK_SNOOZE_ENDED_EVENT: &str = "tasks://snooze-ended";
+pub const TASK_FOLLOW_UPS_CREATED_EVENT: &str = "tasks://follow-ups-created";
+/// Tasks handed off or blocked on someone else; the planner leaves them out.
+pub const WAITING_STATUS: &str = "waiting";
 
 const VALID_STATUSES: &[&str] = &[
     "backlog",
     "todo",
     "in_progress",
     "blocked",
+    WAITING_STATUS,
     "done",
     "archived",
 ];
 
 const VALID_PRIORITIES: &[&str] = &["low", "medium", "high", "urgent"];
 
+/// Conflict flags owned by date shifting; other flags on a block are kept.
+const FLAG_PAST_DUE: &str = ConflictKind::PastDue.as_str();
+const FLAG_BEFORE_PLANNED_START: &str = ConflictKind::BeforePlannedStart.as_str();
+const MAX_SHIFT_DAYS: i64 = 3650;
+const MAX_SNOOZE_DAYS: i64 = 365;
+const MAX_PARTY_CHARS: usize = 80;
+const FOLLOW_UP_MINUTES: i64 = 15;
+pub(crate) const SNOOZE_POLL_INTERVAL: StdDuration = StdDuration::from_secs(60);
+
 #[derive(Clone)]
 pub struct TaskService {
     db: DbPool,
+    snooze_waker_started: Arc<AtomicBool>,
 }
 
 impl TaskService {
     pub fn new(db: DbPool) -> Self {
-        Self { db }
+        Self {
+            db,
+            snooze_waker_started: Arc::new(AtomicBool::new(false)),
+        }
     }
 
     pub fn create_task(&self, input: TaskCreateInput) -> AppResult<TaskRecord> {
@@ -36,26 +78,98 @@ impl TaskService {
         let now = Utc::now().to_rfc3339();
         record.id = uuid::Uuid::new_v4().to_string();
         record.created_at = now.clone();
+        sync_waiting(&mut record, &now);
         record.updated_at = now;
 
         validate_record(&record)?;
 
-        let row = TaskRow::from_record(&record)?;
-        self.db
-            .with_connection(|conn| TaskRepository::insert(conn, &row))?;
+        self.db.with_connection(|conn| {
+            apply_project_defaults(conn, &mut record)?;
+            TaskRepository::insert(conn, &TaskRow::from_record(&record)?)
+        })?;
         info!(task_id = %record.id, "task created");
         Ok(record)
     }
 
+    /// Creates `inputs` in a single transaction. Every task is validated
+    /// before anything is written, and cancelling rolls the whole batch back.
+    pub fn import_tasks(
+        &self,
+        inputs: Vec<TaskCreateInput>,
+        progress: &ProgressReporter,
+    ) -> AppResult<Vec<TaskRecord>> {
+        if inputs.is_empty() {
+            return Err(AppError::validation("没有可导入的任务"));
+        }
+
+        progress.report("validating", 0)?;
+        let total = inputs.len();
+        let now = Utc::now().to_rfc3339();
+        let mut records = Vec::with_capacity(total);
+        for (index, input) in inputs.into_iter().enumerate() {
+            let prepared = build_record_from_create(input).and_then(|mut record| {
+                record.id = uuid::Uuid::new_v4().to_string();
+                record.created_at = now.clone();
+                record.updated_at = now.clone();
+                sync_waiting(&mut record, &now);
+                validate_record(&record)?;
+                Ok(record)
+            });
+            let record = prepared.map_err(|err| match err {
+                AppError::Validation { message, .. } => {
+                    AppError::validation(format!("第 {} 个任务无效: {}", index + 1, message))
+                }
+                other => other,
+            })?;
+            records.push(record);
+        }
+
+        let mut conn = self.db.get_connection()?;
+        let tx = conn.transaction()?;
+        let mut last_percent = None;
+        for (index, record) in records.iter_mut().enumerate() {
+            let percent = scaled_percent(index, total, 10, 95);
+            if last_percent != Some(percent) {
+                progress.report("importing", percent)?;
+                last_percent = Some(percent);
+            } else {
+                progress.checkpoint()?;
+            }
+            apply_project_defaults(&tx, record)?;
+            TaskRepository::insert(&tx, &TaskRow::from_record(record)?)?;
+        }
+        progress.checkpoint()?;
+        tx.commit()?;
+
+        info!(count = total, "tasks imported");
+        Ok(records)
+    }
+
     pub fn update_task(&self, id: &str, update: TaskUpdateInput) -> AppResult<TaskRecord> {
         let mut existing = self.get_task(id)?;
+        let previous_project = existing.project_id.clone();
+        let previous_status = existing.status.clone();
         apply_update(&mut existing, update)?;
-        existing.updated_at = Utc::now().to_rfc3339();
+        let update_project = existing
+            .project_id
+            .clone()
+            .filter(|project_id| previous_project.as_ref() != Some(project_id));
+        let now = Utc::now().to_rfc3339();
+        sync_waiting(&mut existing, &now);
+        existing.updated_at = now;
         validate_record(&existing)?;
 
         let row = TaskRow::from_record(&existing)?;
-        self.db
-            .with_connection(|conn| TaskRepository::update(conn, &row))?;
+        self.db.with_connection(|conn| {
+            if let Some(project_id) = update_project.as_deref() {
+                ensure_project_open(conn, project_id)?;
+            }
+            TaskRepository::update(conn, &row)?;
+            if existing.status != previous_status {
+                sync_block_reminders(conn, Utc::now())?;
+            }
+            Ok(())
+        })?;
         info!(task_id = %existing.id, "task updated");
         Ok(existing)
     }
@@ -90,11 +204,446 @@ impl TaskService {
         Ok(tasks)
     }
 
+    /// [`TaskService::list_tasks`] on a read-only connection, for analytics
+    /// and forecasts that scan every task.
+    pub fn list_tasks_readonly(&self) -> AppResult<Vec<TaskRecord>> {
+        let rows = self.db.with_read_connection(TaskRepository::list_all)?;
+        rows.into_iter().map(|row| row.into_record()).collect()
+    }
+
+    /// Hides the task from default lists and project planning until `until`;
+    /// `None` ends the snooze right away.
+    pub fn snooze_task(
+        &self,
+        id: &str,
+        until: Option<String>,
+        notify: bool,
+    ) -> AppResult<TaskRecord> {
+        let mut record = self.get_task(id)?;
+        let now = Utc::now();
+        match normalize_datetime_opt(until)? {
+            Some(until) => {
+                if record.status == "done" || record.status == "archived" {
+                    return Err(AppError::validation("已完成或已归档的任务无法暂缓"));
+                }
+                let until = DateTime::parse_from_rfc3339(&until)
+                    .map_err(|_| AppError::validation("时间格式非法"))?
+                    .with_timezone(&Utc);
+                if until <= now {
+                    return Err(AppError::validation("暂缓时间必须晚于当前时间"));
+                }
+                if until - now > Duration::days(MAX_SNOOZE_DAYS) {
+                    return Err(AppError::validation(format!(
+                        "最多暂缓 {} 天",
+                        MAX_SNOOZE_DAYS
+                    )));
+                }
+                record.snoozed_until = Some(until.to_rfc3339());
+                record.snooze_notify = notify;
+            }
+            None => {
+                record.snoozed_until = None;
+                record.snooze_notify = false;
+            }
+        }
+        record.updated_at = now.to_rfc3339();
+
+        let row = TaskRow::from_record(&record)?;
+        self.db
+            .with_connection(|conn| TaskRepository::update(conn, &row))?;
+        info!(task_id = %record.id, snoozed_until = ?record.snoozed_until, "task snooze updated");
+        Ok(record)
+    }
+
+    /// Clears every snooze that ended by `now` and returns the woken tasks.
+    pub fn wake_snoozed(&self, now: DateTime<Utc>) -> AppResult<Vec<TaskSnoozeEnded>> {
+        let mut conn = self.db.get_connection()?;
+        let tx = conn.transaction()?;
+        let mut woken = Vec::new();
+        for row in TaskRepository::list_snooze_ended(&tx, &now.to_rfc3339())? {
+            let mut task = row.into_record()?;
+            let notify = task.snooze_notify;
+            task.snoozed_until = None;
+            task.snooze_notify = false;
+            task.updated_at = now.to_rfc3339();
+            TaskRepository::update(&tx, &TaskRow::from_record(&task)?)?;
+            woken.push(TaskSnoozeEnded { task, notify });
+        }
+        tx.commit()?;
+
+        if !woken.is_empty() {
+            info!(count = woken.len(), "snoozed tasks woken");
+        }
+        Ok(woken)
+    }
+
+    /// Creates a check-in task for every waiting task that has waited
+    /// `follow_up_after_days` since it started waiting or since its last
+    /// check-in was finished. A waiting task with an open check-in is left
+    /// alone.
+    pub fn generate_follow_ups(&self, now: DateTime<Utc>) -> AppResult<Vec<TaskRecord>> {
+        let mut conn = self.db.get_connection()?;
+        let tx = conn.transaction()?;
+        let after = Duration::days(i64::from(load_delegation(&tx)?.follow_up_after_days));
+        let now_str = now.to_rfc3339();
+        let mut created = Vec::new();
+        for row in TaskRepository::list_waiting(&tx)? {
+            let mut task = row.into_record()?;
+            let previous = match task.follow_up_task_id.as_deref() {
+                Some(id) => TaskRepository::find_by_id(&tx, id)?
+                    .map(TaskRow::into_record)
+                    .transpose()?,
+                None => None,
+            };
+            let since = match &previous {
+                Some(previous) if !matches!(previous.status.as_str(), "done" | "archived") => {
+                    continue
+                }
+                Some(previous) => previous
+                    .completed_at
+                    .clone()
+                    .unwrap_or_else(|| previous.updated_at.clone()),
+                None => match task.waiting_since.clone() {
+                    Some(since) => since,
+                    None => continue,
+                },
+            };
+            let Ok(since) = DateTime::parse_from_rfc3339(&since) else {
+                continue;
+            };
+            if now - since.with_timezone(&Utc) < after {
+                continue;
+            }
+
+            let mut follow_up = build_follow_up(&task, now)?;
+            follow_up.id = uuid::Uuid::new_v4().to_string();
+            follow_up.created_at = now_str.clone();
+            follow_up.updated_at = now_str.clone();
+            TaskRepository::insert(&tx, &TaskRow::from_record(&follow_up)?)?;
+
+            task.follow_up_task_id = Some(follow_up.id.clone());
+            task.updated_at = now_str.clone();
+            TaskRepository::update(&tx, &TaskRow::from_record(&task)?)?;
+            created.push(follow_up);
+        }
+        tx.commit()?;
+
+        if !created.is_empty() {
+            info!(count = created.len(), "follow-up tasks created");
+        }
+        Ok(created)
+    }
+
+    /// Once a minute wakes snoozed tasks and creates follow-ups for waiting
+    /// ones, emitting [`TASK_SNOOZE_ENDED_EVENT`] and
+    /// [`TASK_FOLLOW_UPS_CREATED_EVENT`] for them.
+    pub fn ensure_snooze_waker(self: &Arc<Self>, app: AppHandle) -> AppResult<()> {
+        if self
+            .snooze_waker_started
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Ok(());
+        }
+
+        let runner = Arc::clone(self);
+        if let Err(err) = spawn_supervised(self.db.clone(), "task-snooze-waker", move || loop {
+            match runner.wake_snoozed(Utc::now()) {
+                Ok(woken) if !woken.is_empty() => {
+                    if let Err(err) = app.emit(TASK_SNOOZE_ENDED_EVENT, &woken) {
+                        warn!(error = %err, "failed to emit snooze-ended event");
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => error!(error = %err, "waking snoozed tasks failed"),
+            }
+            match runner.generate_follow_ups(Utc::now()) {
+                Ok(created) if !created.is_empty() => {
+                    if let Err(err) = app.emit(TASK_FOLLOW_UPS_CREATED_EVENT, &created) {
+                        warn!(error = %err, "failed to emit follow-ups-created event");
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => error!(error = %err, "creating follow-up tasks failed"),
+            }
+            thread::sleep(SNOOZE_POLL_INTERVAL);
+        }) {
+            self.snooze_waker_started.store(false, Ordering::SeqCst);
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    /// Moves due dates and planned starts of the selected tasks (or all tasks
+    /// of a goal) by `delta_days`. When pushing dates later, unfinished
+    /// successors in the dependency graph move by the same amount. Applied
+    /// time blocks of every moved task are re-flagged against the new dates.
+    /// Everything is written in one transaction; `preview` rolls it back.
+    pub fn shift_dates(&self, input: TaskShiftDatesInput) -> AppResult<TaskShiftDatesResult> {
+        if input.delta_days == 0 {
+            return Err(AppError::validation("delta_days 不能为 0"));
+        }
+        if input.delta_days.abs() > MAX_SHIFT_DAYS {
+            return Err(AppError::validation(format!(
+                "单次最多平移 {} 天",
+                MAX_SHIFT_DAYS
+            )));
+        }
+        let delta = Duration::days(input.delta_days);
+
+        let mut conn = self.db.get_connection()?;
+        let tx = conn.transaction()?;
+
+        let mut selected = input
+            .task_ids
+            .iter()
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .collect::<Vec<_>>();
+        if let Some(goal_id) = input
+            .goal_id
+            .as_deref()
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+        {
+            selected.extend(load_goal_task_ids(&tx, goal_id)?);
+        }
+        let mut selected_ids = HashSet::new();
+        selected.retain(|id| selected_ids.insert(id.clone()));
+        if selected.is_empty() {
+            return Err(AppError::validation("需要提供任务或目标"));
+        }
+
+        let successors = if input.delta_days > 0 {
+            load_successor_map(&tx)?
+        } else {
+            HashMap::new()
+        };
+        let mut order = Vec::new();
+        let mut visited = HashSet::new();
+        let mut queue = selected.into_iter().collect::<VecDeque<_>>();
+        while let Some(task_id) = queue.pop_front() {
+            if !visited.insert(task_id.clone()) {
+                continue;
+            }
+            if let Some(next) = successors.get(&task_id) {
+                queue.extend(next.iter().cloned());
+            }
+            order.push(task_id);
+        }
+
+        let now = Utc::now().to_rfc3339();
+        let mut shifts = Vec::new();
+        let mut flagged_block_ids = Vec::new();
+        for task_id in order {
+            let is_cascade = !selected_ids.contains(&task_id);
+            let row = match TaskRepository::find_by_id(&tx, &task_id)? {
+                Some(row) => row,
+                None if is_cascade => continue,
+                None => return Err(AppError::not_found()),
+            };
+            let mut record = row.into_record()?;
+            if is_cascade && matches!(record.status.as_str(), "done" | "archived") {
+                continue;
+            }
+            if record.due_at.is_none() && record.planned_start_at.is_none() {
+                continue;
+            }
+
+            let previous_due_at = record.due_at.clone();
+            let previous_planned_start_at = record.planned_start_at.clone();
+            record.due_at = shift_datetime(record.due_at.as_deref(), delta)?;
+            record.planned_start_at = shift_datetime(record.planned_start_at.as_deref(), delta)?;
+            record.updated_at = now.clone();
+            TaskRepository::update(&tx, &TaskRow::from_record(&record)?)?;
+
+            flagged_block_ids.extend(reflag_time_blocks(&tx, &record)?);
+            shifts.push(TaskDateShift {
+                task_id: record.id,
+                title: record.title,
+                cascaded: is_cascade,
+                previous_due_at,
+                due_at: record.due_at,
+                previous_planned_start_at,
+                planned_start_at: record.planned_start_at,
+            });
+        }
+
+        if input.preview {
+            tx.rollback()?;
+        } else {
+            tx.commit()?;
+            info!(
+                shifted = shifts.len(),
+                flagged_blocks = flagged_block_ids.len(),
+                delta_days = input.delta_days,
+                "task dates shifted"
+            );
+        }
+
+        Ok(TaskShiftDatesResult {
+            preview: input.preview,
+            shifts,
+            flagged_block_ids,
+        })
+    }
+
+    /// Completed tasks resembling the given title and tags, with how long
+    /// they actually took.
+    pub fn find_similar(&self, query: SimilarTasksQuery) -> AppResult<SimilarTasksResult> {
+        let title = query.title.trim();
+        if title.is_empty() && query.tags.iter().all(|tag| tag.trim().is_empty()) {
+            return Err(AppError::validation("请提供任务标题或标签"));
+        }
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_SIMILAR_LIMIT)
+            .clamp(1, MAX_SIMILAR_LIMIT);
+
+        let result = self.db.with_connection(|conn| {
+            find_similar_tasks(
+                conn,
+                title,
+                &query.tags,
+                query.exclude_task_id.as_deref(),
+                limit,
+            )
+        })?;
+        debug!(matches = result.matches.len(), "similar tasks found");
+        Ok(result)
+    }
+
+    /// Open tasks untouched for at least `min_days` (default 14), grouped by
+    /// age with a suggested disposition for each.
+    pub fn stale_report(&self, min_days: Option<u32>) -> AppResult<StaleTaskReport> {
+        let min_days = min_days.unwrap_or(DEFAULT_STALE_DAYS);
+        if !(1..=MAX_STALE_DAYS).contains(&min_days) {
+            return Err(AppError::validation(format!(
+                "天数需在 1 到 {MAX_STALE_DAYS} 之间"
+            )));
+        }
+        let tasks = self.list_tasks_readonly()?;
+        let report = build_stale_report(&tasks, min_days, Utc::now());
+        debug!(
+            stale = report.tasks.len(),
+            min_days, "stale task report built"
+        );
+        Ok(report)
+    }
+
+    /// Open tasks classified into the urgent/important quadrants.
+    pub fn matrix_snapshot(&self) -> AppResult<TaskMatrixSnapshot> {
+        let tasks = self.list_tasks_readonly()?;
+        let goal_linked = self.db.with_read_connection(load_goal_linked_task_ids)?;
+        Ok(build_matrix_snapshot(&tasks, &goal_linked, Utc::now()))
+    }
+
     pub fn pool(&self) -> &DbPool {
         &self.db
     }
 }
 
+fn load_goal_task_ids(conn: &Connection, goal_id: &str) -> AppResult<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT task_id FROM goal_task_associations WHERE goal_id = ?1 ORDER BY created_at ASC",
+    )?;
+    let ids = stmt
+        .query_map([goal_id], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    if ids.is_empty() {
+        return Err(AppError::validation("该目标下没有关联任务"));
+    }
+    Ok(ids)
+}
+
+fn load_successor_map(conn: &Connection) -> AppResult<HashMap<String, Vec<String>>> {
+    let mut stmt = conn.prepare("SELECT predecessor_id, successor_id FROM task_dependencies")?;
+    let edges = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut map: HashMap<String, Vec<String>> = HashMap::new();
+    for (predecessor, successor) in edges {
+        map.entry(predecessor).or_default().push(successor);
+    }
+    Ok(map)
+}
+
+fn shift_datetime(value: Option<&str>, delta: Duration) -> AppResult<Option<String>> {
+    value
+        .map(|raw| {
+            let parsed = DateTime::parse_from_rfc3339(raw)
+                .map_err(|_| AppError::validation("时间格式非法"))?;
+            parsed
+                .checked_add_signed(delta)
+                .map(|shifted| shifted.to_rfc3339())
+                .ok_or_else(|| AppError::validation("平移后的时间超出范围"))
+        })
+        .transpose()
+}
+
+/// Recomputes the date-shift flags of the task's applied blocks and returns
+/// the ids of blocks whose flags changed.
+fn reflag_time_blocks(conn: &Connection, task: &TaskRecord) -> AppResult<Vec<String>> {
+    let due_at = task
+        .due_at
+        .as_deref()
+        .and_then(|raw| DateTime::parse_from_rfc3339(raw).ok());
+    let planned_start_at = task
+        .planned_start_at
+        .as_deref()
+        .and_then(|raw| DateTime::parse_from_rfc3339(raw).ok());
+
+    let mut changed = Vec::new();
+    for row in PlanningRepository::list_time_blocks_for_task(conn, &task.id)? {
+        if row.status != "planned" {
+            continue;
+        }
+        let mut block = row.into_record()?;
+        let previous = block
+            .conflict_flags
+            .as_ref()
+            .and_then(|value| serde_json::from_value::<Vec<String>>(value.clone()).ok())
+            .unwrap_or_default();
+        let mut flags = previous
+            .iter()
+            .filter(|flag| *flag != FLAG_PAST_DUE && *flag != FLAG_BEFORE_PLANNED_START)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let start = DateTime::parse_from_rfc3339(&block.start_at).ok();
+        let end = DateTime::parse_from_rfc3339(&block.end_at).ok();
+        if let (Some(end), Some(due)) = (end, due_at) {
+            if end > due {
+                flags.push(FLAG_PAST_DUE.to_string());
+            }
+        }
+        if let (Some(start), Some(planned)) = (start, planned_start_at) {
+            if start < planned {
+                flags.push(FLAG_BEFORE_PLANNED_START.to_string());
+            }
+        }
+
+        if flags != previous {
+            block.conflict_flags = if flags.is_empty() {
+                None
+            } else {
+                Some(json!(flags))
+            };
+            PlanningRepository::update_time_block(
+                conn,
+                &PlanningTimeBlockRow::from_record(&block)?,
+            )?;
+            changed.push(block.id);
+        }
+    }
+
+    Ok(changed)
+}
+
 fn build_record_from_create(mut input: TaskCreateInput) -> AppResult<TaskRecord> {
     let title = normalize_title(&input.title)?;
     let description = normalize_optional_string(input.description.take());
@@ -109,10 +658,15 @@ fn build_record_from_create(mut input: TaskCreateInput) -> AppResult<TaskRecord>
     let tags = normalize_string_vec(input.tags.take().unwrap_or_default())?;
     let external_links = normalize_links(input.external_links.take().unwrap_or_default())?;
     let owner_id = normalize_optional_string(input.owner_id.take());
+    let project_id = normalize_optional_string(input.project_id.take());
     let is_recurring = input.is_recurring.unwrap_or(false);
     let recurrence = normalize_recurrence(is_recurring, input.recurrence.take())?;
     let task_type = normalize_optional_string(input.task_type.take());
     let ai = normalize_ai(input.ai.take())?;
+    let color = normalize_color(input.color.take())?;
+    let icon = normalize_icon(input.icon.take())?;
+    let delegated_to = normalize_party(input.delegated_to.take(), "委派对象")?;
+    let waiting_on = normalize_party(input.waiting_on.take(), "等待对象")?;
 
     Ok(TaskRecord {
         id: String::new(),
@@ -133,6 +687,15 @@ fn build_record_from_create(mut input: TaskCreateInput) -> AppResult<TaskRecord>
         recurrence,
         ai,
         external_links,
+        project_id,
+        snoozed_until: None,
+        snooze_notify: false,
+        color,
+        icon,
+        delegated_to,
+        waiting_on,
+        waiting_since: None,
+        follow_up_task_id: None,
         created_at: String::new(),
         updated_at: String::new(),
     })
@@ -211,10 +774,106 @@ fn apply_update(record: &mut TaskRecord, update: TaskUpdateInput) -> AppResult<(
         let values = external_links.unwrap_or_default();
         record.external_links = normalize_links(values)?;
     }
+    if let Some(project_id) = update.project_id {
+        record.project_id = normalize_optional_string(project_id);
+    }
+
+    if let Some(color) = update.color {
+        record.color = normalize_color(color)?;
+    }
+
+    if let Some(icon) = update.icon {
+        record.icon = normalize_icon(icon)?;
+    }
+
+    if let Some(delegated_to) = update.delegated_to {
+        record.delegated_to = normalize_party(delegated_to, "委派对象")?;
+    }
+
+    if let Some(waiting_on) = update.waiting_on {
+        record.waiting_on = normalize_party(waiting_on, "等待对象")?;
+    }
 
     Ok(())
 }
 
+/// Starts the waiting clock when a task enters `waiting` and clears it, with
+/// the check-in link, when it leaves.
+fn sync_waiting(record: &mut TaskRecord, now: &str) {
+    if record.status == WAITING_STATUS {
+        record.waiting_since.get_or_insert_with(|| now.to_string());
+    } else {
+        record.waiting_since = None;
+        record.follow_up_task_id = None;
+    }
+}
+
+/// Check-in task for `task`, due at `now`, in the same project.
+fn build_follow_up(task: &TaskRecord, now: DateTime<Utc>) -> AppResult<TaskRecord> {
+    let mut details = Vec::new();
+    if let Some(delegated_to) = &task.delegated_to {
+        details.push(format!("已委派给 {delegated_to}"));
+    }
+    if let Some(waiting_on) = &task.waiting_on {
+        details.push(format!("正在等待 {waiting_on}"));
+    }
+    if let Some(days) = task
+        .waiting_since
+        .as_deref()
+        .and_then(|since| DateTime::parse_from_rfc3339(since).ok())
+        .map(|since| (now - since.with_timezone(&Utc)).num_days())
+    {
+        details.push(format!("已等待 {days} 天"));
+    }
+    details.push("确认进展后完成此任务".to_string());
+
+    build_record_from_create(TaskCreateInput {
+        title: format!("跟进：{}", task.title).chars().take(160).collect(),
+        description: Some(details.join("，")),
+        priority: Some(task.priority.clone()),
+        due_at: Some(now.to_rfc3339()),
+        estimated_minutes: Some(FOLLOW_UP_MINUTES),
+        project_id: task.project_id.clone(),
+        ..TaskCreateInput::default()
+    })
+}
+
+/// Checks the task's project accepts new tasks and adds its default tags.
+fn apply_project_defaults(conn: &Connection, record: &mut TaskRecord) -> AppResult<()> {
+    let Some(project_id) = record.project_id.as_deref() else {
+        return Ok(());
+    };
+    let project = ensure_project_open(conn, project_id)?;
+    for tag in project.default_tags {
+        if !record
+            .tags
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(&tag))
+        {
+            record.tags.push(tag);
+        }
+    }
+    Ok(())
+}
+
+fn ensure_project_open(conn: &Connection, project_id: &str) -> AppResult<ProjectRecord> {
+    let project = ProjectRepository::find(conn, project_id)?
+        .ok_or_else(|| AppError::validation("所选项目不存在"))?
+        .into_record()?;
+    if project.status == ProjectStatus::Archived {
+        return Err(AppError::validation("项目已归档，无法再添加任务"));
+    }
+    Ok(project)
+}
+
+/// Whether the task is still snoozed at `now`.
+pub fn is_snoozed(task: &TaskRecord, now: DateTime<Utc>) -> bool {
+    task.snoozed_until
+        .as_deref()
+        .and_then(|until| DateTime::parse_from_rfc3339(until).ok())
+        .is_some_and(|until| until > now)
+}
+
 fn validate_record(record: &TaskRecord) -> AppResult<()> {
     if record.is_recurring && record.recurrence.is_none() {
         return Err(AppError::validation("循环任务必须提供重复规则"));
@@ -268,6 +927,19 @@ fn normalize_priority(priority: Option<String>) -> AppResult<String> {
     }
 }
 
+fn normalize_party(value: Option<String>, label: &str) -> AppResult<Option<String>> {
+    let value = normalize_optional_string(value);
+    if value
+        .as_ref()
+        .is_some_and(|value| value.chars().count() > MAX_PARTY_CHARS)
+    {
+        return Err(AppError::validation(format!(
+            "{label}长度需在 {MAX_PARTY_CHARS} 字以内"
+        )));
+    }
+    Ok(value)
+}
+
 fn normalize_optional_string(value: Option<String>) -> Option<String> {
     value.and_then(|val| {
         let trimmed = val.trim().to_string();
@@ -492,4 +1164,332 @@ mod tests {
         let result = service.get_task(&record.id);
         assert!(matches!(result, Err(AppError::NotFound)));
     }
+
+    #[test]
+    fn import_tasks_is_atomic_and_cancellable() {
+        let (service, _dir) = setup_service();
+        let input = |title: &str| TaskCreateInput {
+            title: title.into(),
+            ..Default::default()
+        };
+
+        let imported = service
+            .import_tasks(
+                vec![input("第一项"), input("第二项")],
+                &ProgressReporter::silent(),
+            )
+            .expect("import tasks");
+        assert_eq!(imported.len(), 2);
+
+        let invalid = service.import_tasks(
+            vec![input("第三项"), input("  ")],
+            &ProgressReporter::silent(),
+        );
+        assert!(invalid.is_err());
+
+        let registry = crate::services::progress::OperationRegistry::new();
+        let progress = registry
+            .start(Some("import".into()), None)
+            .expect("start operation");
+        registry.cancel("import");
+        let cancelled = service.import_tasks(vec![input("第四项")], &progress);
+        assert!(matches!(cancelled, Err(AppError::Cancelled)));
+
+        let titles: Vec<String> = service
+            .list_tasks()
+            .expect("list tasks")
+            .into_iter()
+            .map(|task| task.title)
+            .collect();
+        assert_eq!(titles.len(), 2);
+        assert!(!titles.contains(&"第三项".to_string()));
+    }
+
+    #[test]
+    fn shift_dates_cascades_to_successors_and_flags_blocks() {
+        let (service, _dir) = setup_service();
+        let create = |title: &str, due_at: &str| {
+            service
+                .create_task(TaskCreateInput {
+                    title: title.into(),
+                    due_at: Some(due_at.into()),
+                    ..Default::default()
+                })
+                .expect("create task")
+        };
+        let design = create("设计", "2026-03-02T18:00:00+08:00");
+        let build = create("开发", "2026-03-05T18:00:00+08:00");
+
+        service
+            .pool()
+            .with_connection(|conn| {
+                conn.execute_batch(&format!(
+                    "INSERT INTO task_dependencies (id, predecessor_id, successor_id, created_at)
+                         VALUES ('dep-1', '{design}', '{build}', '2026-03-01T00:00:00Z');
+                     INSERT INTO planning_sessions (id, task_ids, generated_at, status)
+                         VALUES ('session-1', '[]', '2026-03-01T00:00:00Z', 'applied');
+                     INSERT INTO planning_options (id, session_id, rank)
+                         VALUES ('option-1', 'session-1', 1);
+                     INSERT INTO planning_time_blocks (id, option_id, task_id, start_at, end_at, conflict_flags, status)
+                         VALUES ('block-1', 'option-1', '{build}', '2026-03-05T09:00:00+08:00',
+                                 '2026-03-05T11:00:00+08:00', '[\"calendar-overlap\"]', 'planned');",
+                    design = design.id,
+                    build = build.id,
+                ))?;
+                Ok(())
+            })
+            .expect("seed dependency and block");
+
+        let preview = service
+            .shift_dates(TaskShiftDatesInput {
+                task_ids: vec![design.id.clone()],
+                delta_days: 7,
+                preview: true,
+                ..Default::default()
+            })
+            .expect("preview shift");
+        assert_eq!(preview.shifts.len(), 2);
+        assert!(preview.shifts[1].cascaded);
+        assert_eq!(
+            preview.shifts[1].due_at.as_deref(),
+            Some("2026-03-12T18:00:00+08:00")
+        );
+        assert_eq!(
+            service.get_task(&build.id).unwrap().due_at.as_deref(),
+            Some("2026-03-05T18:00:00+08:00")
+        );
+
+        let result = service
+            .shift_dates(TaskShiftDatesInput {
+                task_ids: vec![build.id.clone()],
+                delta_days: -4,
+                ..Default::default()
+            })
+            .expect("shift earlier");
+        assert_eq!(result.shifts.len(), 1);
+        assert_eq!(result.flagged_block_ids, vec!["block-1".to_string()]);
+        let flags = service
+            .pool()
+            .with_connection(|conn| {
+                Ok(
+                    PlanningRepository::list_time_blocks_for_task(conn, &build.id)?
+                        .remove(0)
+                        .conflict_flags,
+                )
+            })
+            .unwrap();
+        assert_eq!(flags.as_deref(), Some(r#"["calendar-overlap","past-due"]"#));
+
+        assert!(service
+            .shift_dates(TaskShiftDatesInput {
+                delta_days: 1,
+                ..Default::default()
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn snoozed_tasks_wake_when_their_time_passes() {
+        let (service, _dir) = setup_service();
+        let record = service
+            .create_task(TaskCreateInput {
+                title: "稍后处理".into(),
+                ..Default::default()
+            })
+            .expect("create task");
+
+        let until = Utc::now() + Duration::hours(2);
+        let snoozed = service
+            .snooze_task(&record.id, Some(until.to_rfc3339()), true)
+            .expect("snooze task");
+        assert!(is_snoozed(&snoozed, Utc::now()));
+        assert!(service
+            .snooze_task(&record.id, Some("2020-01-01T00:00:00Z".into()), false)
+            .is_err());
+
+        assert!(service.wake_snoozed(Utc::now()).unwrap().is_empty());
+        let woken = service
+            .wake_snoozed(until + Duration::minutes(1))
+            .expect("wake snoozed");
+        assert_eq!(woken.len(), 1);
+        assert!(woken[0].notify);
+        assert_eq!(woken[0].task.id, record.id);
+
+        let fetched = service.get_task(&record.id).unwrap();
+        assert!(fetched.snoozed_until.is_none());
+        assert!(!fetched.snooze_notify);
+    }
+
+    #[test]
+    fn find_similar_returns_completed_look_alikes() {
+        let (service, _dir) = setup_service();
+        let done = service
+            .create_task(TaskCreateInput {
+                title: "撰写季度报告".into(),
+                status: Some("done".into()),
+                estimated_minutes: Some(120),
+                tags: Some(vec!["report".into()]),
+                ..Default::default()
+            })
+            .expect("create done task");
+        service
+            .create_task(TaskCreateInput {
+                title: "撰写季度报告草稿".into(),
+                ..Default::default()
+            })
+            .expect("create open task");
+        service
+            .create_task(TaskCreateInput {
+                title: "预约牙医".into(),
+                status: Some("done".into()),
+                ..Default::default()
+            })
+            .expect("create unrelated task");
+
+        let result = service
+            .find_similar(SimilarTasksQuery {
+                title: "季度报告".into(),
+                tags: vec!["Report".into()],
+                ..Default::default()
+            })
+            .expect("find similar");
+        assert_eq!(result.matches.len(), 1);
+        assert_eq!(result.matches[0].task_id, done.id);
+        assert_eq!(result.matches[0].actual_minutes, None);
+        assert_eq!(result.typical_minutes, Some(120));
+
+        let excluded = service
+            .find_similar(SimilarTasksQuery {
+                title: "季度报告".into(),
+                exclude_task_id: Some(done.id.clone()),
+                ..Default::default()
+            })
+            .expect("find similar");
+        assert!(excluded.matches.is_empty());
+        assert!(service.find_similar(SimilarTasksQuery::default()).is_err());
+    }
+
+    #[test]
+    fn color_and_icon_round_trip_and_reject_unknown_values() {
+        let (service, _dir) = setup_service();
+        let task = service
+            .create_task(TaskCreateInput {
+                title: "晨跑".into(),
+                color: Some("#22C55E".into()),
+                icon: Some("dumbbell".into()),
+                ..Default::default()
+            })
+            .expect("create task");
+        let fetched = service.get_task(&task.id).expect("get task");
+        assert_eq!(fetched.color.as_deref(), Some("#22c55e"));
+        assert_eq!(fetched.icon.as_deref(), Some("dumbbell"));
+
+        let updated = service
+            .update_task(
+                &task.id,
+                TaskUpdateInput {
+                    color: Some(None),
+                    ..Default::default()
+                },
+            )
+            .expect("clear color");
+        assert_eq!(updated.color, None);
+        assert_eq!(updated.icon.as_deref(), Some("dumbbell"));
+
+        assert!(service
+            .update_task(
+                &task.id,
+                TaskUpdateInput {
+                    icon: Some(Some("skull".into())),
+                    ..Default::default()
+                },
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn waiting_tasks_get_follow_ups_after_the_configured_days() {
+        let (service, _dir) = setup_service();
+        let task = service
+            .create_task(TaskCreateInput {
+                title: "合同审批".into(),
+                status: Some(WAITING_STATUS.into()),
+                priority: Some("high".into()),
+                delegated_to: Some(" 法务 ".into()),
+                waiting_on: Some("签字".into()),
+                ..Default::default()
+            })
+            .expect("create task");
+        assert_eq!(task.delegated_to.as_deref(), Some("法务"));
+        let since = DateTime::parse_from_rfc3339(task.waiting_since.as_deref().unwrap())
+            .unwrap()
+            .with_timezone(&Utc);
+
+        // Default is three days
+        assert!(service
+            .generate_follow_ups(since + Duration::days(2))
+            .unwrap()
+            .is_empty());
+        let created = service
+            .generate_follow_ups(since + Duration::days(3))
+            .unwrap();
+        assert_eq!(created.len(), 1);
+        let follow_up = &created[0];
+        assert_eq!(follow_up.title, "跟进：合同审批");
+        assert_eq!(follow_up.priority, "high");
+        assert!(follow_up
+            .description
+            .as_deref()
+            .unwrap()
+            .contains("已委派给 法务"));
+        assert_eq!(
+            service.get_task(&task.id).unwrap().follow_up_task_id,
+            Some(follow_up.id.clone())
+        );
+
+        // Nothing new while the check-in is open, then again three days
+        // after it was finished
+        assert!(service
+            .generate_follow_ups(since + Duration::days(10))
+            .unwrap()
+            .is_empty());
+        service
+            .update_task(
+                &follow_up.id,
+                TaskUpdateInput {
+                    status: Some("done".into()),
+                    completed_at: Some(Some((since + Duration::days(10)).to_rfc3339())),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert!(service
+            .generate_follow_ups(since + Duration::days(12))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            service
+                .generate_follow_ups(since + Duration::days(13))
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Leaving `waiting` clears the clock
+        let resumed = service
+            .update_task(
+                &task.id,
+                TaskUpdateInput {
+                    status: Some("todo".into()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(resumed.waiting_since, None);
+        assert_eq!(resumed.follow_up_task_id, None);
+        assert!(service
+            .generate_follow_ups(since + Duration::days(30))
+            .unwrap()
+            .is_empty());
+    }
 }
@@ -0,0 +1,328 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use rusqlite::Connection;
+use tracing::info;
+
+use crate::db::repositories::planning_repository::PlanningRepository;
+use crate::db::repositories::project_repository::ProjectRepository;
+use crate::db::repositories::task_repository::TaskRepository;
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::task::TaskRecord;
+use crate::models::timesheet::{
+    Timesheet, TimesheetEntry, TimesheetExportParams, TimesheetExportResult, TimesheetFormat,
+    TimesheetGroupTotal, TimesheetGrouping, TimesheetRoundingMode,
+};
+use crate::services::history_service::parse_range;
+use crate::utils::files::write_atomic;
+use crate::utils::paths::sanitize_file_name;
+
+const TIMESHEET_PREFIX: &str = "timesheet";
+const DEFAULT_ROUNDING_MINUTES: i64 = 15;
+const MAX_ROUNDING_MINUTES: i64 = 60;
+const UNFINISHED_BLOCK_STATUS: &str = "unfinished";
+const NO_PROJECT_GROUP: &str = "无项目";
+const NO_TAG_GROUP: &str = "未分类";
+const CSV_HEADER: &str = "Date,Group,Task,Start,End,Source,Minutes,Billed Minutes,Billed Hours";
+
+pub struct TimesheetService {
+    db: DbPool,
+    exports_dir: PathBuf,
+}
+
+impl TimesheetService {
+    pub fn new(db: DbPool) -> Self {
+        let exports_dir = default_exports_dir(db.path());
+        Self { db, exports_dir }
+    }
+
+    /// Writes the timesheet for the range to the reports directory.
+    pub fn export(&self, params: TimesheetExportParams) -> AppResult<TimesheetExportResult> {
+        let (start, end) = parse_range(&params.start, &params.end)?;
+        let rounding = params.rounding_minutes.unwrap_or(DEFAULT_ROUNDING_MINUTES);
+        if !(1..=MAX_ROUNDING_MINUTES).contains(&rounding) {
+            return Err(AppError::validation(format!(
+                "取整粒度需在 1 到 {MAX_ROUNDING_MINUTES} 分钟之间"
+            )));
+        }
+
+        let now = Utc::now();
+        let timesheet = self.db.with_connection(|conn| {
+            build_timesheet(
+                conn,
+                start,
+                end,
+                rounding,
+                params.rounding_mode,
+                params.group_by,
+                now,
+            )
+        })?;
+
+        std::fs::create_dir_all(&self.exports_dir)?;
+        let filename = format!(
+            "{TIMESHEET_PREFIX}-{}-{}.{}",
+            timesheet.start_date,
+            timesheet.end_date,
+            params.format.file_extension()
+        );
+        let path = self.exports_dir.join(sanitize_file_name(&filename));
+        let content = match params.format {
+            TimesheetFormat::Csv => render_csv(&timesheet),
+            TimesheetFormat::Json => serde_json::to_string_pretty(&timesheet)?,
+        };
+        let checksum = write_atomic(&path, content.as_bytes())?;
+
+        info!(
+            target: "app::timesheet",
+            entries = timesheet.entries.len(),
+            billed_minutes = timesheet.total_billed_minutes,
+            "timesheet exported"
+        );
+        Ok(TimesheetExportResult {
+            file_path: path.to_string_lossy().to_string(),
+            checksum,
+            format: params.format,
+            entry_count: timesheet.entries.len(),
+            groups: timesheet.groups,
+            total_minutes: timesheet.total_minutes,
+            total_billed_minutes: timesheet.total_billed_minutes,
+            generated_at: now.to_rfc3339(),
+        })
+    }
+}
+
+/// Turns the applied time blocks of `start..=end` (UTC days) into timesheet
+/// entries. Tracked times win over planned ones; planned time that has not
+/// happened yet and unfinished blocks are left out.
+pub fn build_timesheet(
+    conn: &Connection,
+    start: NaiveDate,
+    end: NaiveDate,
+    rounding: i64,
+    mode: TimesheetRoundingMode,
+    group_by: TimesheetGrouping,
+    now: DateTime<Utc>,
+) -> AppResult<Timesheet> {
+    let window_start = Utc.from_utc_datetime(&start.and_hms_opt(0, 0, 0).unwrap());
+    let window_end = Utc.from_utc_datetime(&end.and_hms_opt(0, 0, 0).unwrap()) + Duration::days(1);
+
+    let project_names: HashMap<String, String> = ProjectRepository::list(conn, true)?
+        .into_iter()
+        .map(|row| (row.id, row.name))
+        .collect();
+    let mut tasks: HashMap<String, Option<TaskRecord>> = HashMap::new();
+
+    let mut entries = Vec::new();
+    let blocks = PlanningRepository::list_applied_time_blocks_between(
+        conn,
+        &window_start.to_rfc3339(),
+        &window_end.to_rfc3339(),
+    )?;
+    for block in blocks {
+        if block.status == UNFINISHED_BLOCK_STATUS {
+            continue;
+        }
+        let tracked = block.actual_start_at.is_some() && block.actual_end_at.is_some();
+        let (start_at, end_at) = if tracked {
+            (
+                block.actual_start_at.as_deref().and_then(parse_datetime),
+                block.actual_end_at.as_deref().and_then(parse_datetime),
+            )
+        } else {
+            (
+                parse_datetime(&block.start_at),
+                parse_datetime(&block.end_at),
+            )
+        };
+        let (Some(start_at), Some(end_at)) = (start_at, end_at) else {
+            continue;
+        };
+        if end_at <= start_at || (!tracked && end_at > now) {
+            continue;
+        }
+
+        if !tasks.contains_key(&block.task_id) {
+            let task = TaskRepository::find_by_id(conn, &block.task_id)?
+                .map(|row| row.into_record())
+                .transpose()?;
+            tasks.insert(block.task_id.clone(), task);
+        }
+        let Some(task) = tasks.get(&block.task_id).and_then(Option::as_ref) else {
+            continue;
+        };
+
+        let group = match group_by {
+            TimesheetGrouping::Project => task
+                .project_id
+                .as_ref()
+                .and_then(|id| project_names.get(id))
+                .cloned()
+                .unwrap_or_else(|| NO_PROJECT_GROUP.to_string()),
+            TimesheetGrouping::Tag => task
+                .tags
+                .first()
+                .cloned()
+                .unwrap_or_else(|| NO_TAG_GROUP.to_string()),
+        };
+        let minutes = (end_at - start_at).num_minutes();
+        entries.push(TimesheetEntry {
+            date: start_at.format("%Y-%m-%d").to_string(),
+            group,
+            task_id: task.id.clone(),
+            task_title: task.title.clone(),
+            start_at: start_at.to_rfc3339(),
+            end_at: end_at.to_rfc3339(),
+            tracked,
+            minutes,
+            billed_minutes: round_minutes(minutes, rounding, mode),
+        });
+    }
+    entries.sort_by(|a, b| {
+        a.group
+            .cmp(&b.group)
+            .then_with(|| a.start_at.cmp(&b.start_at))
+    });
+
+    let mut totals: BTreeMap<String, (i64, i64)> = BTreeMap::new();
+    for entry in &entries {
+        let total = totals.entry(entry.group.clone()).or_default();
+        total.0 += entry.minutes;
+        total.1 += entry.billed_minutes;
+    }
+    let groups: Vec<TimesheetGroupTotal> = totals
+        .into_iter()
+        .map(|(group, (minutes, billed_minutes))| TimesheetGroupTotal {
+            group,
+            minutes,
+            billed_minutes,
+        })
+        .collect();
+
+    Ok(Timesheet {
+        start_date: start.format("%Y-%m-%d").to_string(),
+        end_date: end.format("%Y-%m-%d").to_string(),
+        rounding_minutes: rounding,
+        rounding_mode: mode,
+        group_by,
+        total_minutes: groups.iter().map(|group| group.minutes).sum(),
+        total_billed_minutes: groups.iter().map(|group| group.billed_minutes).sum(),
+        entries,
+        groups,
+    })
+}
+
+/// Rounds to a multiple of `increment`; `Nearest` rounds halves up.
+pub fn round_minutes(minutes: i64, increment: i64, mode: TimesheetRoundingMode) -> i64 {
+    if increment <= 1 {
+        return minutes;
+    }
+    let steps = match mode {
+        TimesheetRoundingMode::Nearest => (minutes + increment / 2) / increment,
+        TimesheetRoundingMode::Up => (minutes + increment - 1) / increment,
+        TimesheetRoundingMode::Down => minutes / increment,
+    };
+    steps * increment
+}
+
+pub fn render_csv(timesheet: &Timesheet) -> String {
+    let mut csv = String::from(CSV_HEADER);
+    csv.push_str("\r\n");
+    for entry in &timesheet.entries {
+        let fields = [
+            entry.date.clone(),
+            csv_field(&entry.group),
+            csv_field(&entry.task_title),
+            entry.start_at.clone(),
+            entry.end_at.clone(),
+            if entry.tracked { "tracked" } else { "planned" }.to_string(),
+            entry.minutes.to_string(),
+            entry.billed_minutes.to_string(),
+            format!("{:.2}", entry.billed_minutes as f64 / 60.0),
+        ];
+        csv.push_str(&fields.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// Quotes fields with separators or quotes, and neutralises leading
+/// characters that spreadsheets would evaluate as formulas.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{value}")
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+fn parse_datetime(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+fn default_exports_dir(db_path: &Path) -> PathBuf {
+    db_path
+        .parent()
+        .map(|dir| dir.join("reports"))
+        .unwrap_or_else(|| std::env::temp_dir().join("cognical"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_minutes_follows_mode() {
+        use TimesheetRoundingMode::*;
+        assert_eq!(round_minutes(52, 15, Nearest), 45);
+        assert_eq!(round_minutes(53, 15, Nearest), 60);
+        assert_eq!(round_minutes(46, 15, Up), 60);
+        assert_eq!(round_minutes(59, 15, Down), 45);
+        assert_eq!(round_minutes(7, 1, Up), 7);
+    }
+
+    #[test]
+    fn csv_escapes_separators_and_formulas() {
+        let timesheet = Timesheet {
+            start_date: "2026-03-02".into(),
+            end_date: "2026-03-02".into(),
+            rounding_minutes: 15,
+            rounding_mode: TimesheetRoundingMode::Nearest,
+            group_by: TimesheetGrouping::Project,
+            entries: vec![TimesheetEntry {
+                date: "2026-03-02".into(),
+                group: "Client, Inc".into(),
+                task_id: "t-1".into(),
+                task_title: "=SUM(A1)".into(),
+                start_at: "2026-03-02T09:00:00+00:00".into(),
+                end_at: "2026-03-02T09:50:00+00:00".into(),
+                tracked: true,
+                minutes: 50,
+                billed_minutes: 45,
+            }],
+            groups: Vec::new(),
+            total_minutes: 50,
+            total_billed_minutes: 45,
+        };
+
+        let csv = render_csv(&timesheet);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(CSV_HEADER));
+        assert_eq!(
+            lines.next(),
+            Some(
+                "2026-03-02,\"Client, Inc\",'=SUM(A1),2026-03-02T09:00:00+00:00,\
+                 2026-03-02T09:50:00+00:00,tracked,50,45,0.75"
+            )
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! "Today so far": live metrics for the current local day. The dashboard,
+//! the agent's schedule context and the wellness nudges all read this
+//! instead of recomputing from raw tasks.
+
+use chrono::{DateTime, Duration, FixedOffset, NaiveTime, TimeZone, Utc};
+use rusqlite::Connection;
+
+use crate::db::repositories::planning_repository::PlanningRepository;
+use crate::db::repositories::task_repository::TaskRepository;
+use crate::db::repositories::wellness_repository::FocusIdleRepository;
+use crate::error::AppResult;
+use crate::models::analytics::TodaySnapshot;
+use crate::models::planning::PlanningTimeBlockRecord;
+use crate::models::task::TaskRecord;
+
+const OPEN_BLOCK_STATUSES: [&str; 2] = ["planned", "in_progress"];
+
+/// Snapshot of the local day containing `now`.
+pub fn today_snapshot(conn: &Connection, now: DateTime<FixedOffset>) -> AppResult<TodaySnapshot> {
+    let day_start = day_start(now);
+    let day_end = day_start + Duration::days(1);
+
+    let tasks = TaskRepository::list_all(conn)?
+        .into_iter()
+        .map(|row| row.into_record())
+        .collect::<AppResult<Vec<_>>>()?;
+    let blocks = PlanningRepository::list_applied_time_blocks_between(
+        conn,
+        &day_start.with_timezone(&Utc).to_rfc3339(),
+        &day_end.with_timezone(&Utc).to_rfc3339(),
+    )?
+    .into_iter()
+    .map(|row| row.into_record())
+    .collect::<AppResult<Vec<_>>>()?;
+    let idle_minutes = FocusIdleRepository::confirmed_minutes_between(
+        conn,
+        &day_start.to_rfc3339(),
+        &now.to_rfc3339(),
+    )?;
+
+    Ok(build_today_snapshot(&tasks, &blocks, idle_minutes, now))
+}
+
+fn day_start(now: DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+    now.offset()
+        .from_local_datetime(&now.date_naive().and_time(NaiveTime::MIN))
+        .single()
+        .unwrap_or(now)
+}
+
+fn parse(value: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(value).ok()
+}
+
+fn build_today_snapshot(
+    tasks: &[TaskRecord],
+    blocks: &[PlanningTimeBlockRecord],
+    idle_minutes: i64,
+    now: DateTime<FixedOffset>,
+) -> TodaySnapshot {
+    let day_start = day_start(now);
+    let day_end = day_start + Duration::days(1);
+    let mut snapshot = TodaySnapshot {
+        date: now.date_naive().to_string(),
+        computed_at: now.to_rfc3339(),
+        ..TodaySnapshot::default()
+    };
+
+    for task in tasks {
+        let completed_at = task.completed_at.as_deref().and_then(parse);
+        if completed_at.is_some_and(|at| at >= day_start && at <= now) {
+            snapshot.completed_so_far += 1;
+        }
+        let open = completed_at.is_none() && task.status != "done";
+        match task.due_at.as_deref().and_then(parse) {
+            Some(due) if open && due < now => snapshot.overdue += 1,
+            Some(due) if open && due < day_end => snapshot.open_due_today += 1,
+            _ => {}
+        }
+    }
+
+    let mut focus_minutes = 0;
+    let mut first_focus_at: Option<DateTime<FixedOffset>> = None;
+    for block in blocks {
+        let (start, end) = match (&block.actual_start_at, &block.actual_end_at) {
+            (Some(start), Some(end)) => (start, end),
+            _ => (&block.start_at, &block.end_at),
+        };
+        let (Some(start), Some(end)) = (parse(start), parse(end)) else {
+            continue;
+        };
+        let open = OPEN_BLOCK_STATUSES.contains(&block.status.as_str());
+        if end <= start {
+            continue;
+        }
+        if start > now {
+            if open {
+                snapshot.remaining_planned_minutes += (end - start).num_minutes();
+                snapshot.remaining_blocks += 1;
+            }
+            continue;
+        }
+
+        first_focus_at = Some(first_focus_at.map_or(start, |first| first.min(start)));
+        focus_minutes += (end.min(now) - start.max(day_start)).num_minutes().max(0);
+        if end > now {
+            snapshot.current_focus_minutes = (now - start).num_minutes();
+            if open {
+                snapshot.remaining_planned_minutes += (end - now).num_minutes();
+                snapshot.remaining_blocks += 1;
+            }
+        }
+    }
+
+    snapshot.focus_minutes_so_far = (focus_minutes - idle_minutes).max(0);
+    snapshot.first_focus_at = first_focus_at.map(|at| at.to_rfc3339());
+    snapshot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(status: &str, due_at: Option<&str>, completed_at: Option<&str>) -> TaskRecord {
+        TaskRecord {
+            id: "task".to_string(),
+            title: "任务".to_string(),
+            description: None,
+            status: status.to_string(),
+            priority: "medium".to_string(),
+            planned_start_at: None,
+            start_at: None,
+            due_at: due_at.map(str::to_string),
+            completed_at: completed_at.map(str::to_string),
+            estimated_minutes: None,
+            estimated_hours: None,
+            tags: Vec::new(),
+            owner_id: None,
+            task_type: None,
+            is_recurring: false,
+            recurrence: None,
+            ai: None,
+            external_links: Vec::new(),
+            project_id: None,
+            snoozed_until: None,
+            snooze_notify: false,
+            color: None,
+            icon: None,
+            delegated_to: None,
+            waiting_on: None,
+            waiting_since: None,
+            follow_up_task_id: None,
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            updated_at: "2026-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    fn block(id: &str, start_at: &str, end_at: &str, status: &str) -> PlanningTimeBlockRecord {
+        PlanningTimeBlockRecord {
+            id: id.to_string(),
+            option_id: "option".to_string(),
+            task_id: "task".to_string(),
+            start_at: start_at.to_string(),
+            end_at: end_at.to_string(),
+            flexibility: None,
+            confidence: None,
+            conflict_flags: None,
+            applied_at: Some("2026-03-02T07:00:00+08:00".to_string()),
+            actual_start_at: None,
+            actual_end_at: None,
+            status: status.to_string(),
+            prep_note: None,
+        }
+    }
+
+    #[test]
+    fn today_counts_elapsed_and_remaining_time() {
+        let now = parse("2026-03-02T14:30:00+08:00").unwrap();
+        let tasks = vec![
+            task("done", None, Some("2026-03-02T10:00:00+08:00")),
+            task("done", None, Some("2026-03-01T22:00:00+08:00")),
+            task("todo", Some("2026-03-02T18:00:00+08:00"), None),
+            task("todo", Some("2026-03-02T09:00:00+08:00"), None),
+        ];
+        let mut finished = block(
+            "morning",
+            "2026-03-02T09:00:00+08:00",
+            "2026-03-02T10:00:00+08:00",
+            "completed",
+        );
+        finished.actual_start_at = Some("2026-03-02T09:00:00+08:00".to_string());
+        finished.actual_end_at = Some("2026-03-02T10:30:00+08:00".to_string());
+        let blocks = vec![
+            finished,
+            block(
+                "running",
+                "2026-03-02T14:00:00+08:00",
+                "2026-03-02T15:00:00+08:00",
+                "in_progress",
+            ),
+            block(
+                "later",
+                "2026-03-02T16:00:00+08:00",
+                "2026-03-02T17:00:00+08:00",
+                "planned",
+            ),
+        ];
+
+        let snapshot = build_today_snapshot(&tasks, &blocks, 20, now);
+        assert_eq!(snapshot.date, "2026-03-02");
+        assert_eq!(snapshot.completed_so_far, 1);
+        assert_eq!(snapshot.open_due_today, 1);
+        assert_eq!(snapshot.overdue, 1);
+        // 90 + 30 elapsed, less 20 confirmed idle minutes
+        assert_eq!(snapshot.focus_minutes_so_far, 100);
+        assert_eq!(snapshot.current_focus_minutes, 30);
+        assert_eq!(snapshot.remaining_planned_minutes, 90);
+        assert_eq!(snapshot.remaining_blocks, 2);
+        assert_eq!(
+            snapshot.first_focus_at.as_deref(),
+            Some("2026-03-02T09:00:00+08:00")
+        );
+    }
+}
@@ -1,10 +1,11 @@
 use crate::error::{AppError, AppResult};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use tokio::time::{timeout, Duration};
 use tracing::{debug, error, info, warn};
 
@@ -14,6 +15,41 @@ pub type ToolHandler = Arc<
     dyn Fn(JsonValue) -> Pin<Box<dyn Future<Output = AppResult<JsonValue>> + Send>> + Send + Sync,
 >;
 
+/// Where a tool came from. Built-in tools are compiled into the app; runtime
+/// tools are registered declaratively by users or plugins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolOrigin {
+    BuiltIn,
+    Runtime,
+}
+
+/// Tools the agent may call. The global list applies to every conversation
+/// and a conversation's own list narrows it further; a missing list means
+/// no restriction.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolAllowlist {
+    #[serde(default)]
+    pub global: Option<BTreeSet<String>>,
+    #[serde(default)]
+    pub conversations: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl ToolAllowlist {
+    /// Whether `tool_name` may be called, in `conversation_id` if given
+    pub fn allows(&self, conversation_id: Option<&str>, tool_name: &str) -> bool {
+        let allowed_globally = self
+            .global
+            .as_ref()
+            .is_none_or(|tools| tools.contains(tool_name));
+        let allowed_in_conversation = conversation_id
+            .and_then(|id| self.conversations.get(id))
+            .is_none_or(|tools| tools.contains(tool_name));
+        allowed_globally && allowed_in_conversation
+    }
+}
+
 /// Definition of a tool that can be called by the AI
 #[derive(Clone)]
 pub struct ToolDefinition {
@@ -22,6 +58,7 @@ pub struct ToolDefinition {
     /// JSON Schema for parameters (OpenAI function calling format)
     pub parameters: JsonValue,
     pub handler: ToolHandler,
+    pub origin: ToolOrigin,
 }
 
 /// A tool call request from the AI
@@ -43,8 +80,13 @@ pub struct ToolResult {
 }
 
 /// Registry for managing available tools
+///
+/// Built-in tools are registered through `&mut self` while the app state is
+/// assembled; runtime tools can be added and removed later through a shared
+/// reference, so the map sits behind a lock.
 pub struct ToolRegistry {
-    tools: HashMap<String, ToolDefinition>,
+    tools: RwLock<HashMap<String, ToolDefinition>>,
+    allowlist: RwLock<ToolAllowlist>,
     timeout_duration: Duration,
 }
 
@@ -54,7 +96,8 @@ impl ToolRegistry {
     /// database operations, API calls, or file I/O
     pub fn new() -> Self {
         Self {
-            tools: HashMap::new(),
+            tools: RwLock::new(HashMap::new()),
+            allowlist: RwLock::new(ToolAllowlist::default()),
             timeout_duration: Duration::from_secs(15),
         }
     }
@@ -62,7 +105,8 @@ impl ToolRegistry {
     /// Create a new tool registry with aggressive timeout for quick operations
     pub fn with_fast_timeout() -> Self {
         Self {
-            tools: HashMap::new(),
+            tools: RwLock::new(HashMap::new()),
+            allowlist: RwLock::new(ToolAllowlist::default()),
             timeout_duration: Duration::from_secs(3), // Fast operations like validation, simple queries
         }
     }
@@ -70,7 +114,8 @@ impl ToolRegistry {
     /// Create a new tool registry with slow timeout for intensive operations
     pub fn with_slow_timeout() -> Self {
         Self {
-            tools: HashMap::new(),
+            tools: RwLock::new(HashMap::new()),
+            allowlist: RwLock::new(ToolAllowlist::default()),
             timeout_duration: Duration::from_secs(30), // Complex operations, large data processing
         }
     }
@@ -78,7 +123,8 @@ impl ToolRegistry {
     /// Create a new tool registry with custom timeout
     pub fn with_timeout(timeout_ms: u64) -> Self {
         Self {
-            tools: HashMap::new(),
+            tools: RwLock::new(HashMap::new()),
+            allowlist: RwLock::new(ToolAllowlist::default()),
             timeout_duration: Duration::from_millis(timeout_ms),
         }
     }
@@ -100,30 +146,88 @@ impl ToolRegistry {
         description: String,
         parameters: JsonValue,
         handler: ToolHandler,
+    ) -> AppResult<()> {
+        let tools = self.tools.get_mut().expect("tool registry lock poisoned");
+        Self::insert_tool(
+            tools,
+            ToolDefinition {
+                name,
+                description,
+                parameters,
+                handler,
+                origin: ToolOrigin::BuiltIn,
+            },
+        )
+    }
+
+    /// Register a tool after the registry has been shared, e.g. a declarative
+    /// tool loaded from the database. The schema must compile up front so a
+    /// broken definition is rejected instead of failing on first use.
+    pub fn register_runtime_tool(
+        &self,
+        name: String,
+        description: String,
+        parameters: JsonValue,
+        handler: ToolHandler,
+    ) -> AppResult<()> {
+        if let Err(e) = jsonschema::JSONSchema::compile(&parameters) {
+            return Err(AppError::validation(format!(
+                "Invalid schema for tool '{}': {}",
+                name, e
+            )));
+        }
+
+        let mut tools = self.tools.write().expect("tool registry lock poisoned");
+        Self::insert_tool(
+            &mut tools,
+            ToolDefinition {
+                name,
+                description,
+                parameters,
+                handler,
+                origin: ToolOrigin::Runtime,
+            },
+        )
+    }
+
+    /// Remove a runtime tool. Built-in tools cannot be unregistered.
+    pub fn unregister_runtime_tool(&self, name: &str) -> AppResult<()> {
+        let mut tools = self.tools.write().expect("tool registry lock poisoned");
+        match tools.get(name).map(|tool| tool.origin) {
+            Some(ToolOrigin::Runtime) => {
+                tools.remove(name);
+                info!(target: "tool_registry", tool_name = %name, "Runtime tool unregistered");
+                Ok(())
+            }
+            Some(ToolOrigin::BuiltIn) => Err(AppError::validation(format!(
+                "Tool '{}' is built in and cannot be unregistered",
+                name
+            ))),
+            None => Err(AppError::not_found()),
+        }
+    }
+
+    fn insert_tool(
+        tools: &mut HashMap<String, ToolDefinition>,
+        tool_def: ToolDefinition,
     ) -> AppResult<()> {
         // Check if tool already exists
-        if self.tools.contains_key(&name) {
+        if tools.contains_key(&tool_def.name) {
             return Err(AppError::validation(format!(
                 "Tool '{}' is already registered",
-                name
+                tool_def.name
             )));
         }
 
         // Validate that parameters is a valid JSON Schema object
-        if !parameters.is_object() {
+        if !tool_def.parameters.is_object() {
             return Err(AppError::validation(
                 "Tool parameters must be a JSON object (JSON Schema)",
             ));
         }
 
-        let tool_def = ToolDefinition {
-            name: name.clone(),
-            description,
-            parameters,
-            handler,
-        };
-
-        self.tools.insert(name.clone(), tool_def);
+        let name = tool_def.name.clone();
+        tools.insert(name.clone(), tool_def);
         info!(target: "tool_registry", tool_name = %name, "Tool registered successfully");
 
         Ok(())
@@ -131,10 +235,21 @@ impl ToolRegistry {
 
     /// Get all tool schemas in OpenAI function calling format
     ///
-    /// Returns a vector of tool definitions formatted for AI consumption
+    /// Returns a vector of tool definitions formatted for AI consumption.
+    /// Tools outside the global allowlist are left out.
     pub fn get_tool_schemas(&self) -> Vec<JsonValue> {
+        self.tool_schemas_for(None)
+    }
+
+    /// Get the schemas of the tools the agent may call in a conversation, so
+    /// the model is never offered a tool that would be refused
+    pub fn tool_schemas_for(&self, conversation_id: Option<&str>) -> Vec<JsonValue> {
+        let allowlist = self.allowlist();
         self.tools
+            .read()
+            .expect("tool registry lock poisoned")
             .values()
+            .filter(|tool| allowlist.allows(conversation_id, &tool.name))
             .map(|tool| {
                 serde_json::json!({
                     "type": "function",
@@ -150,17 +265,62 @@ impl ToolRegistry {
 
     /// Check if a tool with the given name exists
     pub fn has_tool(&self, name: &str) -> bool {
-        self.tools.contains_key(name)
+        self.read_tools().contains_key(name)
+    }
+
+    /// Get the origin of a registered tool
+    pub fn tool_origin(&self, name: &str) -> Option<ToolOrigin> {
+        self.read_tools().get(name).map(|tool| tool.origin)
+    }
+
+    /// Get the parameter schema of a registered tool
+    pub fn tool_parameters(&self, name: &str) -> Option<JsonValue> {
+        self.read_tools()
+            .get(name)
+            .map(|tool| tool.parameters.clone())
+    }
+
+    /// Get the handler of a registered tool so other tools can delegate to it
+    pub fn tool_handler(&self, name: &str) -> Option<ToolHandler> {
+        self.read_tools().get(name).map(|tool| tool.handler.clone())
     }
 
     /// Get the number of registered tools
     pub fn tool_count(&self) -> usize {
-        self.tools.len()
+        self.read_tools().len()
     }
 
     /// Get a list of all registered tool names
     pub fn tool_names(&self) -> Vec<String> {
-        self.tools.keys().cloned().collect()
+        self.read_tools().keys().cloned().collect()
+    }
+
+    /// Replace the lists of tools the agent may call
+    pub fn set_allowlist(&self, allowlist: ToolAllowlist) {
+        *self
+            .allowlist
+            .write()
+            .expect("tool allowlist lock poisoned") = allowlist;
+    }
+
+    /// Get the lists of tools the agent may call
+    pub fn allowlist(&self) -> ToolAllowlist {
+        self.allowlist
+            .read()
+            .expect("tool allowlist lock poisoned")
+            .clone()
+    }
+
+    /// Check whether a tool may be called, in a conversation if given
+    pub fn is_tool_allowed(&self, conversation_id: Option<&str>, name: &str) -> bool {
+        self.allowlist
+            .read()
+            .expect("tool allowlist lock poisoned")
+            .allows(conversation_id, name)
+    }
+
+    fn read_tools(&self) -> std::sync::RwLockReadGuard<'_, HashMap<String, ToolDefinition>> {
+        self.tools.read().expect("tool registry lock poisoned")
     }
 
     /// Validate a tool call against its schema
@@ -173,12 +333,12 @@ impl ToolRegistry {
     /// * `Err(AppError)` if the tool doesn't exist or parameters are invalid
     pub fn validate_tool_call(&self, tool_call: &ToolCall) -> AppResult<()> {
         // Check if tool exists
-        let tool = self.tools.get(&tool_call.name).ok_or_else(|| {
+        let parameters = self.tool_parameters(&tool_call.name).ok_or_else(|| {
             AppError::validation(format!("Tool '{}' not found in registry", tool_call.name))
         })?;
 
         // Compile the JSON Schema
-        let schema = match jsonschema::JSONSchema::compile(&tool.parameters) {
+        let schema = match jsonschema::JSONSchema::compile(&parameters) {
             Ok(schema) => schema,
             Err(e) => {
                 error!(
@@ -287,8 +447,8 @@ impl ToolRegistry {
         }
 
         // Get the tool handler
-        let tool = match self.tools.get(&tool_name) {
-            Some(tool) => tool,
+        let handler = match self.tool_handler(&tool_name) {
+            Some(handler) => handler,
             None => {
                 // This shouldn't happen after validation, but handle it anyway
                 let error_msg = format!("工具 '{}' 未找到", tool_name);
@@ -308,7 +468,6 @@ impl ToolRegistry {
         };
 
         // Execute the tool with timeout protection
-        let handler = tool.handler.clone();
         let arguments = tool_call.arguments.clone();
 
         match timeout(self.timeout_duration, handler(arguments)).await {
@@ -524,7 +683,57 @@ impl ToolRegistry {
     /// # Returns
     /// * Vector of `ToolResult` in the same order as input
     pub async fn execute_tools(&self, tool_calls: Vec<ToolCall>) -> Vec<ToolResult> {
-        self.execute_tools_with_concurrency(tool_calls, 5).await
+        self.execute_tools_for(None, tool_calls).await
+    }
+
+    /// Execute multiple tool calls made in a conversation
+    ///
+    /// Calls to tools outside the allowlist are refused without running and
+    /// come back as errors in their place.
+    ///
+    /// # Arguments
+    /// * `conversation_id` - Conversation whose allowlist applies, if any
+    /// * `tool_calls` - Vector of tool calls to execute
+    ///
+    /// # Returns
+    /// * Vector of `ToolResult` in the same order as input
+    pub async fn execute_tools_for(
+        &self,
+        conversation_id: Option<&str>,
+        tool_calls: Vec<ToolCall>,
+    ) -> Vec<ToolResult> {
+        let allowlist = self.allowlist();
+        let mut results: Vec<Option<ToolResult>> = vec![None; tool_calls.len()];
+        let mut allowed_indices = Vec::new();
+        let mut allowed_calls = Vec::new();
+
+        for (index, tool_call) in tool_calls.into_iter().enumerate() {
+            if allowlist.allows(conversation_id, &tool_call.name) {
+                allowed_indices.push(index);
+                allowed_calls.push(tool_call);
+                continue;
+            }
+
+            warn!(
+                target: "tool_registry",
+                tool_name = %tool_call.name,
+                tool_call_id = %tool_call.id,
+                conversation_id = ?conversation_id,
+                "Tool call refused by allowlist"
+            );
+            results[index] = Some(ToolResult {
+                tool_call_id: tool_call.id,
+                result: None,
+                error: Some(format!("工具 '{}' 在当前对话中已被禁用", tool_call.name)),
+            });
+        }
+
+        let executed = self.execute_tools_with_concurrency(allowed_calls, 5).await;
+        for (index, result) in allowed_indices.into_iter().zip(executed) {
+            results[index] = Some(result);
+        }
+
+        results.into_iter().flatten().collect()
     }
 
     /// Execute multiple tool calls with custom timeout per tool
@@ -541,7 +750,8 @@ impl ToolRegistry {
         per_tool_timeout: u64,
     ) -> Vec<ToolResult> {
         let custom_registry = ToolRegistry {
-            tools: self.tools.clone(),
+            tools: RwLock::new(self.read_tools().clone()),
+            allowlist: RwLock::new(self.allowlist()),
             timeout_duration: Duration::from_millis(per_tool_timeout),
         };
         custom_registry.execute_tools(tool_calls).await
@@ -551,7 +761,8 @@ impl ToolRegistry {
     /// This creates a shallow clone that shares the tool handlers
     fn clone_for_execution(&self) -> Self {
         Self {
-            tools: self.tools.clone(),
+            tools: RwLock::new(self.read_tools().clone()),
+            allowlist: RwLock::new(self.allowlist()),
             timeout_duration: self.timeout_duration,
         }
     }
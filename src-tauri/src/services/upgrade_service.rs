@@ -0,0 +1,269 @@
+//! Data tasks that run once after an app update, on top of the schema
+//! migrations: backfilling values for new columns, recomputing derived data.
+//! Each step is tracked in `app_upgrade_steps`; a failed step is rolled back
+//! and retried on the next launch.
+
+use chrono::Utc;
+use rusqlite::Connection;
+use tracing::{info, warn};
+
+use crate::db::repositories::settings_repository::SettingsRepository;
+use crate::db::repositories::upgrade_repository::{UpgradeRepository, UpgradeStepRow};
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::upgrade::{
+    AppUpgradeStatus, ChangelogEntry, UpgradeStepState, UpgradeStepStatus,
+};
+use crate::services::behavior_learning::DEFAULT_PREFERENCE_ID;
+
+const KEY_CHANGELOG_SEEN_VERSION: &str = "changelog_seen_version";
+
+pub struct UpgradeStep {
+    /// Stable identifier; never reuse one for different work.
+    pub id: &'static str,
+    pub app_version: &'static str,
+    pub description: &'static str,
+    /// Runs inside a transaction and must be safe to run again after a
+    /// failure.
+    pub run: fn(&Connection) -> AppResult<()>,
+}
+
+/// Registered steps, run in order.
+const UPGRADE_STEPS: &[UpgradeStep] = &[UpgradeStep {
+    id: "0.1.0-preference-profile-names",
+    app_version: "0.1.0",
+    description: "为未命名的偏好配置补全名称",
+    run: backfill_preference_profile_names,
+}];
+
+/// In-app release notes, newest first.
+const CHANGELOG: &[(&str, &[&str])] = &[(
+    "0.1.0",
+    &[
+        "错误信息带有稳定的错误码，并提示是否可以重试",
+        "后台任务崩溃后会自动重启，并在任务列表中显示运行状况",
+        "可在设置中开关实验性功能：AI 洞察、主动建议与增量同步",
+    ],
+)];
+
+pub struct UpgradeService {
+    db: DbPool,
+    steps: &'static [UpgradeStep],
+}
+
+impl UpgradeService {
+    pub fn new(db: DbPool) -> Self {
+        Self {
+            db,
+            steps: UPGRADE_STEPS,
+        }
+    }
+
+    /// Runs every step that has not completed yet and returns how many
+    /// completed now. A failing step does not stop the ones after it.
+    pub fn run_pending(&self) -> AppResult<usize> {
+        self.db.with_connection(|conn| {
+            let done = UpgradeRepository::list(conn)?
+                .into_iter()
+                .filter(|row| row.status == UpgradeStepState::Completed.as_str())
+                .map(|row| row.id)
+                .collect::<Vec<_>>();
+
+            let mut completed = 0;
+            for step in self.steps {
+                if done.iter().any(|id| id == step.id) {
+                    continue;
+                }
+
+                let now = Utc::now().to_rfc3339();
+                let outcome = conn.unchecked_transaction().map_err(AppError::from).and_then(|tx| {
+                    (step.run)(&tx)?;
+                    tx.commit()?;
+                    Ok(())
+                });
+                let row = match outcome {
+                    Ok(()) => {
+                        completed += 1;
+                        info!(target: "app::upgrade", step = step.id, "upgrade step completed");
+                        UpgradeStepRow {
+                            id: step.id.to_string(),
+                            app_version: step.app_version.to_string(),
+                            status: UpgradeStepState::Completed.as_str().to_string(),
+                            error: None,
+                            attempted_at: now.clone(),
+                            completed_at: Some(now),
+                        }
+                    }
+                    Err(err) => {
+                        warn!(target: "app::upgrade", step = step.id, error = %err, "upgrade step failed");
+                        UpgradeStepRow {
+                            id: step.id.to_string(),
+                            app_version: step.app_version.to_string(),
+                            status: UpgradeStepState::Failed.as_str().to_string(),
+                            error: Some(err.to_string()),
+                            attempted_at: now,
+                            completed_at: None,
+                        }
+                    }
+                };
+                UpgradeRepository::upsert(conn, &row)?;
+            }
+
+            Ok(completed)
+        })
+    }
+
+    pub fn status(&self) -> AppResult<AppUpgradeStatus> {
+        self.db.with_read_connection(|conn| {
+            let rows = UpgradeRepository::list(conn)?;
+            let steps = self
+                .steps
+                .iter()
+                .map(|step| {
+                    let row = rows.iter().find(|row| row.id == step.id);
+                    let state = match row {
+                        Some(row) => UpgradeStepState::try_from(row.status.as_str())
+                            .map_err(AppError::validation)?,
+                        None => UpgradeStepState::Pending,
+                    };
+                    Ok(UpgradeStepStatus {
+                        id: step.id.to_string(),
+                        app_version: step.app_version.to_string(),
+                        description: step.description.to_string(),
+                        state,
+                        error: row.and_then(|row| row.error.clone()),
+                        completed_at: row.and_then(|row| row.completed_at.clone()),
+                    })
+                })
+                .collect::<AppResult<Vec<_>>>()?;
+
+            let seen =
+                SettingsRepository::get(conn, KEY_CHANGELOG_SEEN_VERSION)?.map(|row| row.value);
+            let changelog = CHANGELOG
+                .iter()
+                .filter(|(version, _)| {
+                    seen.as_deref()
+                        .is_none_or(|seen| version_key(version) > version_key(seen))
+                })
+                .map(|(version, notes)| ChangelogEntry {
+                    version: version.to_string(),
+                    notes: notes.iter().map(|note| note.to_string()).collect(),
+                })
+                .collect();
+
+            Ok(AppUpgradeStatus {
+                app_version: env!("CARGO_PKG_VERSION").to_string(),
+                steps,
+                changelog,
+            })
+        })
+    }
+
+    /// Hides the changelog entries up to the running version.
+    pub fn acknowledge_changelog(&self) -> AppResult<AppUpgradeStatus> {
+        self.db.with_connection(|conn| {
+            SettingsRepository::upsert(conn, KEY_CHANGELOG_SEEN_VERSION, env!("CARGO_PKG_VERSION"))
+        })?;
+        self.status()
+    }
+}
+
+/// Numeric components of a dotted version, so "0.10.0" sorts after "0.9.0".
+fn version_key(version: &str) -> Vec<u64> {
+    version
+        .split(['.', '-'])
+        .map_while(|part| part.parse().ok())
+        .collect()
+}
+
+/// Profiles saved before they had names fall back to their id at read time;
+/// store that name so renames and exports see it too.
+fn backfill_preference_profile_names(conn: &Connection) -> AppResult<()> {
+    conn.execute(
+        "UPDATE schedule_preferences
+         SET name = CASE WHEN id = ?1 THEN '默认' ELSE id END
+         WHERE name IS NULL OR TRIM(name) = ''",
+        [DEFAULT_PREFERENCE_ID],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failing_step(_conn: &Connection) -> AppResult<()> {
+        Err(AppError::other("回填失败"))
+    }
+
+    const TEST_STEPS: &[UpgradeStep] = &[
+        UpgradeStep {
+            id: "0.1.0-preference-profile-names",
+            app_version: "0.1.0",
+            description: "names",
+            run: backfill_preference_profile_names,
+        },
+        UpgradeStep {
+            id: "0.1.0-broken",
+            app_version: "0.1.0",
+            description: "broken",
+            run: failing_step,
+        },
+    ];
+
+    #[test]
+    fn steps_run_once_and_failures_are_retried() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = DbPool::new(dir.path().join("upgrade.sqlite")).unwrap();
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO schedule_preferences (id, data, updated_at) VALUES ('focus', '{}', '2026-01-01T00:00:00Z')",
+                [],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+        let service = UpgradeService {
+            db: db.clone(),
+            steps: TEST_STEPS,
+        };
+
+        assert_eq!(service.run_pending().unwrap(), 1);
+        let name: String = db
+            .with_read_connection(|conn| {
+                Ok(conn.query_row(
+                    "SELECT name FROM schedule_preferences WHERE id = 'focus'",
+                    [],
+                    |row| row.get(0),
+                )?)
+            })
+            .unwrap();
+        assert_eq!(name, "focus");
+
+        let status = service.status().unwrap();
+        assert_eq!(status.steps[0].state, UpgradeStepState::Completed);
+        assert_eq!(status.steps[1].state, UpgradeStepState::Failed);
+        assert_eq!(status.steps[1].error.as_deref(), Some("回填失败"));
+
+        // Only the failed step is attempted again.
+        assert_eq!(service.run_pending().unwrap(), 0);
+        assert_eq!(
+            service.status().unwrap().steps[1].state,
+            UpgradeStepState::Failed
+        );
+    }
+
+    #[test]
+    fn acknowledged_changelog_entries_are_hidden() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = UpgradeService::new(DbPool::new(dir.path().join("log.sqlite")).unwrap());
+
+        assert_eq!(service.status().unwrap().changelog.len(), CHANGELOG.len());
+        assert!(service
+            .acknowledge_changelog()
+            .unwrap()
+            .changelog
+            .is_empty());
+        assert!(version_key("0.10.0") > version_key("0.9.1"));
+    }
+}
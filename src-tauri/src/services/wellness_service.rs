@@ -1,39 +1,272 @@
+use schemars::JsonSchema;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration as StdDuration;
 
-use chrono::{DateTime, Duration, Timelike, Utc};
-use tracing::{debug, info};
+use chrono::{DateTime, Duration, FixedOffset, Local, Timelike, Utc};
+use tracing::{debug, info, warn};
 
-use crate::db::repositories::task_repository::TaskRepository;
-use crate::db::repositories::wellness_repository::WellnessRepository;
+use crate::db::repositories::planning_repository::PlanningRepository;
+use crate::db::repositories::wellness_repository::{
+    FocusIdleAdjustmentRow, FocusIdleRepository, WellnessRepository,
+};
 use crate::db::DbPool;
-use crate::error::AppResult;
+use crate::error::{AppError, AppResult};
+use crate::models::planning::PlanningTimeBlockRecord;
+use crate::models::settings::{FeedbackCategory, SleepSchedule};
 use crate::models::wellness::{
-    WellnessEventInsert, WellnessEventRecord, WellnessEventResponseUpdate, WellnessResponse,
-    WellnessTriggerReason,
+    FocusIdleAdjustment, FocusSession, IdleAdjustmentStatus, WellnessEventInsert,
+    WellnessEventRecord, WellnessEventResponseUpdate, WellnessResponse, WellnessTriggerReason,
 };
+use crate::services::focus_idle::IdleTracker;
 use crate::services::settings_service::SettingsService;
+use crate::services::today_snapshot;
+use crate::utils::idle::{IdleSource, SystemIdleSource};
 
 const DEFAULT_FOCUS_THRESHOLD_MINUTES: i64 = 90; // 90 minutes of continuous focus
 const DEFAULT_WORK_STREAK_THRESHOLD_HOURS: f64 = 4.0; // 4 hours continuous work
 const DEFAULT_REST_BREAK_MINUTES: i64 = 10; // Recommend 10-minute break
 const MAX_DEFERRAL_COUNT: i64 = 3; // Max times user can snooze
 const SNOOZE_INCREMENT_MINUTES: i64 = 15; // Snooze for 15 minutes
+const LATE_WORK_WARNING_MINUTES: i64 = 120; // Weekly late work worth flagging
+const DEFAULT_FOCUS_SESSION_MINUTES: i64 = 25; // One pomodoro
+const MAX_FOCUS_SESSION_MINUTES: i64 = 240;
+const PENDING_SCAN_LIMIT: usize = 20; // Pending nudges checked for a critical one
+const IDLE_SAMPLE_INTERVAL: StdDuration = StdDuration::from_secs(30);
 
 /// Service for wellness nudges and rest reminders
 pub struct WellnessService {
     db: DbPool,
     settings_service: Arc<SettingsService>,
     nudge_job_running: Arc<AtomicBool>,
+    focus_session: Mutex<Option<FocusSession>>,
+    idle_source: Arc<dyn IdleSource>,
+    /// Idle sampling for the focus session in progress, when enabled.
+    idle_tracker: Arc<Mutex<Option<IdleTracker>>>,
 }
 
 impl WellnessService {
     pub fn new(db: DbPool, settings_service: Arc<SettingsService>) -> Self {
+        Self::with_idle_source(db, settings_service, Arc::new(SystemIdleSource))
+    }
+
+    pub fn with_idle_source(
+        db: DbPool,
+        settings_service: Arc<SettingsService>,
+        idle_source: Arc<dyn IdleSource>,
+    ) -> Self {
         Self {
             db,
             settings_service,
             nudge_job_running: Arc::new(AtomicBool::new(false)),
+            focus_session: Mutex::new(None),
+            idle_source,
+            idle_tracker: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Start a focus session; non-critical nudges are deferred until it ends.
+    pub fn start_focus_session(
+        &self,
+        planned_minutes: Option<i64>,
+        task_id: Option<String>,
+    ) -> AppResult<FocusSession> {
+        let minutes = planned_minutes.unwrap_or(DEFAULT_FOCUS_SESSION_MINUTES);
+        if !(1..=MAX_FOCUS_SESSION_MINUTES).contains(&minutes) {
+            return Err(AppError::validation(format!(
+                "专注时长需在 1 到 {MAX_FOCUS_SESSION_MINUTES} 分钟之间"
+            )));
+        }
+
+        let now = Utc::now();
+        let session = FocusSession {
+            started_at: now.to_rfc3339(),
+            ends_at: (now + Duration::minutes(minutes)).to_rfc3339(),
+            task_id,
+        };
+
+        let mut guard = self
+            .focus_session
+            .lock()
+            .map_err(|_| AppError::other("专注状态不可用"))?;
+        if let Some(previous) = guard.replace(session.clone()) {
+            self.finish_idle_tracking(&previous, now)?;
+        }
+        drop(guard);
+        self.start_idle_tracking(now, now + Duration::minutes(minutes))?;
+
+        info!("Focus session started for {} minutes", minutes);
+        Ok(session)
+    }
+
+    /// End the current focus session and surface any nudge it deferred.
+    pub fn end_focus_session(&self) -> AppResult<Option<WellnessEventRecord>> {
+        let ended = self
+            .focus_session
+            .lock()
+            .map_err(|_| AppError::other("专注状态不可用"))?
+            .take();
+        if let Some(session) = &ended {
+            info!("Focus session ended");
+            self.finish_idle_tracking(session, Utc::now())?;
+        }
+
+        self.check_and_generate_nudge()
+    }
+
+    /// The focus session in progress, if any. Sessions past their end time
+    /// are cleared here.
+    pub fn current_focus_session(&self) -> AppResult<Option<FocusSession>> {
+        let mut guard = self
+            .focus_session
+            .lock()
+            .map_err(|_| AppError::other("专注状态不可用"))?;
+
+        let expired = guard.as_ref().is_some_and(|session| {
+            DateTime::parse_from_rfc3339(&session.ends_at)
+                .map(|ends_at| ends_at.with_timezone(&Utc) <= Utc::now())
+                .unwrap_or(true)
+        });
+        if expired {
+            debug!("Focus session reached its planned end");
+            if let Some(session) = guard.take() {
+                let ended_at = DateTime::parse_from_rfc3339(&session.ends_at)
+                    .map(|ends_at| ends_at.with_timezone(&Utc))
+                    .unwrap_or_else(|_| Utc::now());
+                self.finish_idle_tracking(&session, ended_at)?;
+            }
+        }
+
+        Ok(guard.clone())
+    }
+
+    /// Samples idle time until the session ends, if the user opted in and
+    /// the platform reports idle time.
+    fn start_idle_tracking(
+        &self,
+        started_at: DateTime<Utc>,
+        ends_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        let settings = self.settings_service.get_idle_detection()?;
+        if !settings.enabled || self.idle_source.idle_time().is_none() {
+            return Ok(());
+        }
+
+        *self
+            .idle_tracker
+            .lock()
+            .map_err(|_| AppError::other("专注状态不可用"))? =
+            Some(IdleTracker::new(started_at, settings.threshold_minutes));
+
+        let tracker = Arc::clone(&self.idle_tracker);
+        let source = Arc::clone(&self.idle_source);
+        let sampler = thread::Builder::new()
+            .name("focus-idle-sampler".to_string())
+            .spawn(move || loop {
+                thread::sleep(IDLE_SAMPLE_INTERVAL);
+                let now = Utc::now().min(ends_at);
+                let Ok(mut guard) = tracker.lock() else {
+                    return;
+                };
+                match guard.as_mut() {
+                    Some(tracker) if tracker.session_started_at() == started_at => {
+                        tracker.observe(now, source.idle_time());
+                    }
+                    _ => return,
+                }
+                if now >= ends_at {
+                    return;
+                }
+            });
+        if let Err(err) = sampler {
+            warn!("Idle detection unavailable for this focus session: {}", err);
+        }
+        Ok(())
+    }
+
+    /// Stores the idle time detected during `session` as a pending
+    /// adjustment for the user to confirm.
+    fn finish_idle_tracking(
+        &self,
+        session: &FocusSession,
+        ended_at: DateTime<Utc>,
+    ) -> AppResult<Option<FocusIdleAdjustment>> {
+        let tracker = self
+            .idle_tracker
+            .lock()
+            .map_err(|_| AppError::other("专注状态不可用"))?
+            .take();
+        let Some(tracker) = tracker else {
+            return Ok(None);
+        };
+
+        let periods = tracker.finish(ended_at);
+        let idle_minutes: i64 = periods.iter().map(|period| period.minutes).sum();
+        if idle_minutes == 0 {
+            return Ok(None);
         }
+
+        let mut adjustment = FocusIdleAdjustment {
+            id: 0,
+            session_started_at: session.started_at.clone(),
+            session_ended_at: ended_at.to_rfc3339(),
+            task_id: session.task_id.clone(),
+            idle_minutes,
+            periods,
+            status: IdleAdjustmentStatus::Pending,
+            created_at: Utc::now().to_rfc3339(),
+            resolved_at: None,
+        };
+        let row = FocusIdleAdjustmentRow::from_record(&adjustment)?;
+        adjustment.id = self
+            .db
+            .with_connection(|conn| FocusIdleRepository::insert(conn, &row))?;
+
+        info!(
+            "Detected {} idle minutes during the focus session",
+            idle_minutes
+        );
+        Ok(Some(adjustment))
+    }
+
+    /// Idle time from past focus sessions awaiting confirmation.
+    pub fn pending_idle_adjustments(&self) -> AppResult<Vec<FocusIdleAdjustment>> {
+        self.db
+            .with_read_connection(FocusIdleRepository::list_pending)
+    }
+
+    /// Confirming subtracts the idle minutes from the day's focus time;
+    /// dismissing keeps the focus time as recorded.
+    pub fn resolve_idle_adjustment(
+        &self,
+        id: i64,
+        confirm: bool,
+    ) -> AppResult<FocusIdleAdjustment> {
+        self.db.with_connection(|conn| {
+            let adjustment =
+                FocusIdleRepository::find(conn, id)?.ok_or_else(AppError::not_found)?;
+            if adjustment.status != IdleAdjustmentStatus::Pending {
+                return Err(AppError::validation("该空闲记录已处理"));
+            }
+
+            let status = if confirm {
+                IdleAdjustmentStatus::Confirmed
+            } else {
+                IdleAdjustmentStatus::Dismissed
+            };
+            FocusIdleRepository::update_status(conn, id, status, &Utc::now().to_rfc3339())?;
+            FocusIdleRepository::find(conn, id)?.ok_or_else(AppError::not_found)
+        })
+    }
+
+    /// Oldest pending critical nudge; used while a focus session is active.
+    fn pending_critical_nudge(&self) -> AppResult<Option<WellnessEventRecord>> {
+        let conn = self.db.get_connection()?;
+        let pending = WellnessRepository::list_pending(&conn, PENDING_SCAN_LIMIT)?;
+        Ok(pending
+            .into_iter()
+            .find(|event| event.trigger_reason.is_critical()))
     }
 
     /// Check if current time is within quiet hours
@@ -54,6 +287,15 @@ impl WellnessService {
         Ok(false)
     }
 
+    /// Whether the user turned wellness check-ins off in the feedback
+    /// opt-outs. No nudges are raised or shown while they are.
+    fn check_ins_opted_out(&self) -> AppResult<bool> {
+        Ok(self
+            .settings_service
+            .get_feedback_opt_outs()?
+            .is_opted_out(FeedbackCategory::WellnessCheckIns))
+    }
+
     /// Calculate exponential back-off delay based on deferral count
     fn calculate_backoff_minutes(deferral_count: i64) -> i64 {
         // Exponential back-off: 15, 30, 60, 120 minutes
@@ -62,6 +304,10 @@ impl WellnessService {
 
     /// Generate wellness nudge based on current activity patterns
     pub fn check_and_generate_nudge(&self) -> AppResult<Option<WellnessEventRecord>> {
+        if self.check_ins_opted_out()? {
+            return Ok(None);
+        }
+
         let now = Utc::now();
 
         // Check if in quiet hours
@@ -70,6 +316,11 @@ impl WellnessService {
             return Ok(None);
         }
 
+        if self.current_focus_session()?.is_some() {
+            debug!("Deferring wellness nudges: focus session active");
+            return self.pending_critical_nudge();
+        }
+
         // Check for existing pending nudges
         let conn = self.db.get_connection()?;
         let pending = WellnessRepository::list_pending(&conn, 1)?;
@@ -161,71 +412,92 @@ impl WellnessService {
         Ok(Some(record))
     }
 
-    /// Analyze current work patterns
-    fn analyze_work_pattern(&self) -> AppResult<WorkPattern> {
-        let conn = self.db.get_connection()?;
-        let now = Utc::now();
-
-        // Get recent tasks (last 4 hours)
-        let _start_time = now - Duration::hours(4);
-        let tasks = TaskRepository::list_all(&conn)?;
+    /// Raise a nudge when freshly applied blocks run into the wind-down period
+    /// or sleep time configured in the sleep schedule.
+    pub fn nudge_for_wind_down(
+        &self,
+        blocks: &[PlanningTimeBlockRecord],
+    ) -> AppResult<Option<WellnessEventRecord>> {
+        let schedule = self.settings_service.get_sleep_schedule()?;
+        if !schedule.enabled || self.check_ins_opted_out()? {
+            return Ok(None);
+        }
 
-        // Filter tasks that were worked on today
-        let today_start = now.date_naive().and_hms_opt(0, 0, 0).unwrap();
-        let today_tasks: Vec<_> = tasks
-            .into_iter()
-            .filter(|task| {
-                if let Some(start_at) = &task.start_at {
-                    if let Ok(started) = DateTime::parse_from_rfc3339(start_at) {
-                        let started_naive = started.naive_utc();
-                        return started_naive >= today_start;
-                    }
-                }
-                false
+        let late_blocks = blocks
+            .iter()
+            .filter(|block| {
+                block_interval(block, false)
+                    .map(|(start, end)| late_minutes(&schedule, start, end) > 0)
+                    .unwrap_or(false)
             })
-            .collect();
+            .count();
+        if late_blocks == 0 {
+            return Ok(None);
+        }
 
-        // Calculate work streak (simplified)
-        let work_streak_hours = if !today_tasks.is_empty() {
-            // Estimate based on tasks started today
-            let hours_since_start = if let Some(first_task) = today_tasks.first() {
-                if let Some(start_at) = &first_task.start_at {
-                    if let Ok(started) = DateTime::parse_from_rfc3339(start_at) {
-                        let diff = now.signed_duration_since(started.with_timezone(&Utc));
-                        diff.num_minutes() as f64 / 60.0
-                    } else {
-                        0.0
-                    }
-                } else {
-                    0.0
-                }
-            } else {
-                0.0
-            };
-            hours_since_start.min(8.0) // Cap at 8 hours
-        } else {
-            0.0
+        let conn = self.db.get_connection()?;
+        let insert = WellnessEventInsert {
+            window_start: Utc::now().to_rfc3339(),
+            trigger_reason: WellnessTriggerReason::WindDown,
+            recommended_break_minutes: schedule.wind_down_minutes as i64,
+            suggested_micro_task: Some(format!(
+                "有 {late_blocks} 个时间块排在睡前放松时段，考虑提前完成或移到明天"
+            )),
         };
 
-        // Calculate continuous focus (simplified - based on recent task activity)
-        let continuous_focus_minutes = if !today_tasks.is_empty() {
-            // Estimate based on number of in-progress tasks
-            let active_tasks = today_tasks
-                .iter()
-                .filter(|t| t.status == "in-progress")
-                .count();
+        let id = WellnessRepository::insert(&conn, &insert)?;
+        let record = WellnessRepository::find_by_id(&conn, id)?;
 
-            if active_tasks > 0 {
-                (work_streak_hours * 60.0) as i64
-            } else {
-                0
+        info!(
+            "Generated wind-down nudge for {} late blocks (id: {})",
+            late_blocks, id
+        );
+
+        Ok(Some(record))
+    }
+
+    /// Minutes of applied blocks in the past week that fell into wind-down
+    /// or sleep time. Actual times are preferred over planned ones.
+    fn late_work_minutes(&self, week_start: DateTime<Utc>, now: DateTime<Utc>) -> AppResult<i64> {
+        let schedule = self.settings_service.get_sleep_schedule()?;
+        if !schedule.enabled {
+            return Ok(0);
+        }
+
+        let conn = self.db.get_connection()?;
+        let blocks = PlanningRepository::list_applied_time_blocks_between(
+            &conn,
+            &week_start.to_rfc3339(),
+            &now.to_rfc3339(),
+        )?;
+
+        let mut total = 0;
+        for row in blocks {
+            let block = row.into_record()?;
+            if let Some((start, end)) = block_interval(&block, true) {
+                total += late_minutes(&schedule, start, end);
             }
-        } else {
-            0
-        };
+        }
+
+        Ok(total)
+    }
+
+    /// Analyze current work patterns from the live today snapshot
+    fn analyze_work_pattern(&self) -> AppResult<WorkPattern> {
+        let conn = self.db.get_connection()?;
+        let now = Local::now().fixed_offset();
+        let snapshot = today_snapshot::today_snapshot(&conn, now)?;
+
+        let work_streak_hours = snapshot
+            .first_focus_at
+            .as_deref()
+            .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+            .map(|first| (now - first).num_minutes() as f64 / 60.0)
+            .unwrap_or(0.0)
+            .min(8.0); // Cap at 8 hours
 
         Ok(WorkPattern {
-            continuous_focus_minutes,
+            continuous_focus_minutes: snapshot.current_focus_minutes,
             work_streak_hours,
         })
     }
@@ -265,6 +537,14 @@ impl WellnessService {
 
     /// Get current pending nudge
     pub fn get_pending_nudge(&self) -> AppResult<Option<WellnessEventRecord>> {
+        if self.check_ins_opted_out()? {
+            return Ok(None);
+        }
+
+        if self.current_focus_session()?.is_some() {
+            return self.pending_critical_nudge();
+        }
+
         let conn = self.db.get_connection()?;
         let pending = WellnessRepository::list_pending(&conn, 1)?;
         Ok(pending.into_iter().next())
@@ -313,6 +593,7 @@ impl WellnessService {
                 WellnessTriggerReason::WorkStreak => {
                     max_work_streak_hours = max_work_streak_hours.max(4.0); // DEFAULT_WORK_STREAK_THRESHOLD_HOURS
                 }
+                WellnessTriggerReason::WindDown => {}
             }
         }
 
@@ -331,11 +612,14 @@ impl WellnessService {
         // Simple rhythm score based on compliance
         let focus_rhythm_score = (rest_compliance_rate * 100.0).min(100.0);
 
+        let late_work_minutes = self.late_work_minutes(week_start, now)?;
+
         // Generate recommendations
         let recommendations = self.generate_wellness_recommendations(
             rest_compliance_rate,
             snoozed_count,
             ignored_count,
+            late_work_minutes,
         );
 
         Ok(WeeklySummary {
@@ -350,6 +634,7 @@ impl WellnessService {
             rest_compliance_rate,
             focus_rhythm_score,
             peak_hours: vec![], // TODO: Implement peak hours analysis
+            late_work_minutes,
             recommendations,
         })
     }
@@ -359,6 +644,7 @@ impl WellnessService {
         compliance_rate: f64,
         snoozed_count: i32,
         ignored_count: i32,
+        late_work_minutes: i64,
     ) -> Vec<String> {
         let mut recommendations = Vec::new();
 
@@ -376,6 +662,12 @@ impl WellnessService {
             recommendations.push("注意到您忽略了多个休息提醒，请关注身体健康".to_string());
         }
 
+        if late_work_minutes > LATE_WORK_WARNING_MINUTES {
+            recommendations.push(format!(
+                "本周有 {late_work_minutes} 分钟工作排在睡前或睡眠时间，建议提前收尾"
+            ));
+        }
+
         if recommendations.is_empty() {
             recommendations.push("继续保持良好的工作节奏".to_string());
         }
@@ -395,12 +687,39 @@ impl WellnessService {
     }
 }
 
+fn block_interval(
+    block: &PlanningTimeBlockRecord,
+    prefer_actual: bool,
+) -> Option<(DateTime<FixedOffset>, DateTime<FixedOffset>)> {
+    let (start, end) = match (&block.actual_start_at, &block.actual_end_at) {
+        (Some(start), Some(end)) if prefer_actual => (start, end),
+        _ => (&block.start_at, &block.end_at),
+    };
+    let start = DateTime::parse_from_rfc3339(start).ok()?;
+    let end = DateTime::parse_from_rfc3339(end).ok()?;
+    (start < end).then_some((start, end))
+}
+
+/// Overlap of `[start, end)` with the wind-down and sleep periods.
+fn late_minutes(
+    schedule: &SleepSchedule,
+    start: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+) -> i64 {
+    schedule
+        .wind_down_intervals(start, end)
+        .into_iter()
+        .chain(schedule.sleep_intervals(start, end))
+        .map(|(from, to)| (to.min(end) - from.max(start)).num_minutes().max(0))
+        .sum()
+}
+
 struct WorkPattern {
     continuous_focus_minutes: i64,
     work_streak_hours: f64,
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct WeeklySummary {
     pub week_start: String,
@@ -414,5 +733,7 @@ pub struct WeeklySummary {
     pub rest_compliance_rate: f64,
     pub focus_rhythm_score: f64,
     pub peak_hours: Vec<i32>,
+    /// Minutes of applied blocks inside wind-down or sleep time.
+    pub late_work_minutes: i64,
     pub recommendations: Vec<String>,
 }
@@ -1,17 +1,21 @@
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
-use chrono::{DateTime, Duration, TimeZone, Utc};
-use tracing::{debug, error, info};
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use serde_json::json;
+use tracing::{debug, error, info, warn};
 
 use crate::db::repositories::task_repository::TaskRepository;
 use crate::db::repositories::workload_repository::WorkloadRepository;
 use crate::db::DbPool;
 use crate::error::AppResult;
 use crate::models::workload::{
-    ContributingTaskSummary, WorkloadForecastRecord, WorkloadForecastResponse, WorkloadHorizon,
-    WorkloadRiskLevel,
+    ContributingTaskSummary, WorkloadForecastBucket, WorkloadForecastRecord,
+    WorkloadForecastResponse, WorkloadGranularity, WorkloadHorizon, WorkloadRiskLevel,
 };
+use crate::services::job_queue::{enqueue_retry, JOB_KIND_WORKLOAD_FORECAST};
+use crate::services::job_supervisor::spawn_supervised;
+use crate::services::power_throttle::PowerThrottle;
 use crate::services::task_service::TaskService;
 
 const DEFAULT_CAPACITY_THRESHOLD_HOURS: f64 = 40.0;
@@ -20,6 +24,13 @@ const LOW_CONFIDENCE_THRESHOLD: f64 = 0.4;
 const MIN_HISTORICAL_DAYS: i64 = 7;
 const WARNING_THRESHOLD_MULTIPLIER: f64 = 0.8;
 const CRITICAL_THRESHOLD_MULTIPLIER: f64 = 1.0;
+/// z-score of a two-sided 90% interval.
+const INTERVAL_Z: f64 = 1.645;
+/// Relative spread of an estimated task; widened as confidence drops.
+const ESTIMATE_SPREAD_BASE: f64 = 0.25;
+const ESTIMATE_SPREAD_SCALE: f64 = 0.5;
+/// Tasks without an estimate count as one hour and could be far off.
+const UNESTIMATED_SPREAD: f64 = 1.0;
 
 /// Service for forecasting workload and detecting capacity risks.
 pub struct WorkloadForecastService {
@@ -42,20 +53,29 @@ impl WorkloadForecastService {
     pub fn generate_forecasts(
         &self,
         capacity_threshold_hours: Option<f64>,
+    ) -> AppResult<Vec<WorkloadForecastResponse>> {
+        self.generate_forecasts_with(
+            &WorkloadHorizon::ALL,
+            WorkloadGranularity::Day,
+            capacity_threshold_hours,
+        )
+    }
+
+    /// Generate and store forecasts for `horizons`, bucketed by `granularity`.
+    pub fn generate_forecasts_with(
+        &self,
+        horizons: &[WorkloadHorizon],
+        granularity: WorkloadGranularity,
+        capacity_threshold_hours: Option<f64>,
     ) -> AppResult<Vec<WorkloadForecastResponse>> {
         let threshold = capacity_threshold_hours.unwrap_or(DEFAULT_CAPACITY_THRESHOLD_HOURS);
         let now = Utc::now();
 
-        let horizons = vec![
-            WorkloadHorizon::SevenDays,
-            WorkloadHorizon::FourteenDays,
-            WorkloadHorizon::ThirtyDays,
-        ];
-
         let mut results = Vec::new();
 
         for horizon in horizons {
-            let forecast = self.generate_forecast_for_horizon(horizon, threshold, &now)?;
+            let forecast =
+                self.generate_forecast_for_horizon(*horizon, granularity, threshold, &now)?;
             results.push(forecast);
         }
 
@@ -66,20 +86,15 @@ impl WorkloadForecastService {
     fn generate_forecast_for_horizon(
         &self,
         horizon: WorkloadHorizon,
+        granularity: WorkloadGranularity,
         capacity_threshold: f64,
         now: &DateTime<Utc>,
     ) -> AppResult<WorkloadForecastResponse> {
-        let days = match horizon {
-            WorkloadHorizon::SevenDays => 7,
-            WorkloadHorizon::FourteenDays => 14,
-            WorkloadHorizon::ThirtyDays => 30,
-        };
-
-        let end_date = *now + Duration::days(days);
+        let end_date = *now + Duration::days(horizon.days());
 
         // Fetch pending and in-progress tasks
-        let conn = self.db.get_connection()?;
-        let tasks = TaskRepository::list_all(&conn)?;
+        let read_conn = self.db.get_read_connection()?;
+        let tasks = TaskRepository::list_all(&read_conn)?;
 
         let pending_tasks: Vec<_> = tasks
             .into_iter()
@@ -94,17 +109,43 @@ impl WorkloadForecastService {
             })
             .collect();
 
+        // Calculate confidence based on historical data availability
+        let confidence = self.calculate_confidence(&read_conn)?;
+        let estimate_spread = ESTIMATE_SPREAD_BASE + ESTIMATE_SPREAD_SCALE * (1.0 - confidence);
+
         // Calculate total workload
         let mut total_hours = 0.0;
+        let mut total_variance = 0.0;
         let mut contributing_tasks = Vec::new();
+        let mut daily = empty_daily_buckets(now.date_naive(), end_date.date_naive());
 
         for task in pending_tasks {
-            let hours = task
+            let estimate = task
                 .estimated_hours
-                .or_else(|| task.estimated_minutes.map(|m| m as f64 / 60.0))
-                .unwrap_or(1.0); // Default 1 hour if no estimate
+                .or_else(|| task.estimated_minutes.map(|m| m as f64 / 60.0));
+            let hours = estimate.unwrap_or(1.0); // Default 1 hour if no estimate
+            let spread = if estimate.is_some() {
+                estimate_spread
+            } else {
+                UNESTIMATED_SPREAD
+            };
+            let variance = (hours * spread).powi(2);
 
             total_hours += hours;
+            total_variance += variance;
+
+            // Overdue work still has to happen, so it lands on today.
+            let due_day = task
+                .due_at
+                .as_ref()
+                .and_then(|due| DateTime::parse_from_rfc3339(due).ok())
+                .map(|due| due.with_timezone(&Utc).date_naive().max(now.date_naive()));
+            if let Some(bucket) =
+                due_day.and_then(|day| daily.iter_mut().find(|(date, _, _)| *date == day))
+            {
+                bucket.1 += hours;
+                bucket.2 += variance;
+            }
 
             contributing_tasks.push(ContributingTaskSummary {
                 task_id: task.id.clone(),
@@ -115,8 +156,11 @@ impl WorkloadForecastService {
             });
         }
 
-        // Calculate confidence based on historical data availability
-        let confidence = self.calculate_confidence(&conn)?;
+        let (low_hours, high_hours) = interval(total_hours, total_variance);
+        let buckets: Vec<WorkloadForecastBucket> = daily
+            .into_iter()
+            .map(|(date, hours, variance)| bucket(date, date, hours, variance))
+            .collect();
 
         // Determine risk level
         let risk_level = self.determine_risk_level(total_hours, capacity_threshold, confidence);
@@ -128,12 +172,16 @@ impl WorkloadForecastService {
             risk_level,
             total_hours,
             capacity_threshold,
-            contributing_tasks: contributing_tasks.clone(),
+            contributing_tasks,
             confidence,
+            low_hours,
+            high_hours,
+            buckets,
         };
 
         // Save to database
-        WorkloadRepository::upsert_forecast(&conn, &record)?;
+        self.db
+            .with_background_write(|conn| WorkloadRepository::upsert_forecast(conn, &record))?;
 
         info!(
             target: "app::workload_forecast",
@@ -144,20 +192,34 @@ impl WorkloadForecastService {
             confidence
         );
 
-        Ok(WorkloadForecastResponse {
-            horizon: horizon.as_str().to_string(),
+        Ok(self.to_response(record, granularity))
+    }
+
+    fn to_response(
+        &self,
+        record: WorkloadForecastRecord,
+        granularity: WorkloadGranularity,
+    ) -> WorkloadForecastResponse {
+        let recommendations = self.generate_recommendations(
+            &record.risk_level,
+            record.total_hours,
+            record.high_hours,
+            record.capacity_threshold,
+        );
+        WorkloadForecastResponse {
+            horizon: record.horizon.as_str().to_string(),
             generated_at: record.generated_at,
-            risk_level: risk_level.as_str().to_string(),
-            total_hours,
-            capacity_threshold,
-            contributing_tasks,
-            confidence,
-            recommendations: self.generate_recommendations(
-                &risk_level,
-                total_hours,
-                capacity_threshold,
-            ),
-        })
+            risk_level: record.risk_level.as_str().to_string(),
+            total_hours: record.total_hours,
+            capacity_threshold: record.capacity_threshold,
+            contributing_tasks: record.contributing_tasks,
+            confidence: record.confidence,
+            low_hours: record.low_hours,
+            high_hours: record.high_hours,
+            granularity,
+            buckets: roll_up_buckets(&record.buckets, granularity),
+            recommendations,
+        }
     }
 
     /// Calculate confidence based on historical data availability.
@@ -219,9 +281,10 @@ impl WorkloadForecastService {
         &self,
         risk_level: &WorkloadRiskLevel,
         total_hours: f64,
+        high_hours: f64,
         capacity_threshold: f64,
     ) -> Vec<String> {
-        match risk_level {
+        let mut recommendations = match risk_level {
             WorkloadRiskLevel::Critical => vec![
                 "⚠️ 工作负载严重超载，建议立即采取行动".to_string(),
                 format!(
@@ -253,45 +316,39 @@ impl WorkloadForecastService {
                 ),
                 "继续保持良好的工作节奏！".to_string(),
             ],
+        };
+
+        if *risk_level != WorkloadRiskLevel::Critical && high_hours > capacity_threshold {
+            recommendations.push(format!(
+                "按预测区间上限 {:.1} 小时估算可能超出容量，建议预留缓冲时间",
+                high_hours
+            ));
         }
+
+        recommendations
     }
 
     /// Get the latest forecast for a specific horizon.
     pub fn get_latest_forecast(
         &self,
         horizon: WorkloadHorizon,
+        granularity: WorkloadGranularity,
     ) -> AppResult<Option<WorkloadForecastResponse>> {
         let conn = self.db.get_connection()?;
         let record = WorkloadRepository::latest_for_horizon(&conn, horizon)?;
 
-        Ok(record.map(|r| WorkloadForecastResponse {
-            horizon: r.horizon.as_str().to_string(),
-            generated_at: r.generated_at,
-            risk_level: r.risk_level.as_str().to_string(),
-            total_hours: r.total_hours,
-            capacity_threshold: r.capacity_threshold,
-            contributing_tasks: r.contributing_tasks,
-            confidence: r.confidence,
-            recommendations: self.generate_recommendations(
-                &r.risk_level,
-                r.total_hours,
-                r.capacity_threshold,
-            ),
-        }))
+        Ok(record.map(|r| self.to_response(r, granularity)))
     }
 
     /// Get all latest forecasts.
-    pub fn get_all_latest_forecasts(&self) -> AppResult<Vec<WorkloadForecastResponse>> {
-        let horizons = vec![
-            WorkloadHorizon::SevenDays,
-            WorkloadHorizon::FourteenDays,
-            WorkloadHorizon::ThirtyDays,
-        ];
-
+    pub fn get_all_latest_forecasts(
+        &self,
+        granularity: WorkloadGranularity,
+    ) -> AppResult<Vec<WorkloadForecastResponse>> {
         let mut results = Vec::new();
 
-        for horizon in horizons {
-            if let Some(forecast) = self.get_latest_forecast(horizon)? {
+        for horizon in WorkloadHorizon::ALL {
+            if let Some(forecast) = self.get_latest_forecast(horizon, granularity)? {
                 results.push(forecast);
             }
         }
@@ -307,9 +364,14 @@ impl WorkloadForecastService {
             .is_ok()
         {
             let service = Arc::clone(self);
-            std::thread::spawn(move || {
-                service.run_nightly_job();
-            });
+            if let Err(err) =
+                spawn_supervised(self.db.clone(), "workload-forecast-job", move || {
+                    service.run_nightly_job();
+                })
+            {
+                self.job_started.store(false, Ordering::SeqCst);
+                return Err(err);
+            }
             info!(target: "app::workload_forecast", "Nightly forecast job started");
         }
         Ok(())
@@ -317,6 +379,7 @@ impl WorkloadForecastService {
 
     /// Run the nightly forecast job loop.
     fn run_nightly_job(&self) {
+        let throttle = PowerThrottle::new(self.db.clone());
         loop {
             let now = Utc::now();
             let next_midnight = (now + Duration::days(1))
@@ -335,6 +398,7 @@ impl WorkloadForecastService {
             );
 
             std::thread::sleep(wait_duration);
+            throttle.wait_until_allowed("workload-forecast");
 
             // Run the forecast generation
             match self.generate_forecasts(None) {
@@ -351,8 +415,103 @@ impl WorkloadForecastService {
                         "Nightly forecast failed: {}",
                         err
                     );
+                    let queued = self.db.with_background_write(|conn| {
+                        enqueue_retry(
+                            conn,
+                            JOB_KIND_WORKLOAD_FORECAST,
+                            &json!({}),
+                            &err.to_string(),
+                            Utc::now(),
+                        )
+                    });
+                    if let Err(queue_err) = queued {
+                        warn!(
+                            target: "app::workload_forecast",
+                            error = %queue_err,
+                            "failed to queue forecast retry"
+                        );
+                    }
                 }
             }
         }
     }
 }
+
+fn empty_daily_buckets(start: NaiveDate, end: NaiveDate) -> Vec<(NaiveDate, f64, f64)> {
+    start
+        .iter_days()
+        .take_while(|day| *day <= end)
+        .map(|day| (day, 0.0, 0.0))
+        .collect()
+}
+
+/// 90% interval for a sum of independent task estimates with the given
+/// total variance; hours never go below zero.
+fn interval(expected: f64, variance: f64) -> (f64, f64) {
+    let margin = INTERVAL_Z * variance.sqrt();
+    ((expected - margin).max(0.0), expected + margin)
+}
+
+fn bucket(start: NaiveDate, end: NaiveDate, hours: f64, variance: f64) -> WorkloadForecastBucket {
+    let (low_hours, high_hours) = interval(hours, variance);
+    WorkloadForecastBucket {
+        start: start.to_string(),
+        end: end.to_string(),
+        expected_hours: hours,
+        low_hours,
+        high_hours,
+    }
+}
+
+/// Groups daily buckets into `granularity`. The upper bound is never
+/// clamped, so each day's variance can be recovered from it.
+fn roll_up_buckets(
+    daily: &[WorkloadForecastBucket],
+    granularity: WorkloadGranularity,
+) -> Vec<WorkloadForecastBucket> {
+    match granularity {
+        WorkloadGranularity::Day => daily.to_vec(),
+        WorkloadGranularity::Week => daily
+            .chunks(7)
+            .filter_map(|week| {
+                let first = week.first()?;
+                let last = week.last()?;
+                let hours: f64 = week.iter().map(|day| day.expected_hours).sum();
+                let variance: f64 = week
+                    .iter()
+                    .map(|day| ((day.high_hours - day.expected_hours) / INTERVAL_Z).powi(2))
+                    .sum();
+                let start = NaiveDate::parse_from_str(&first.start, "%Y-%m-%d").ok()?;
+                let end = NaiveDate::parse_from_str(&last.end, "%Y-%m-%d").ok()?;
+                Some(bucket(start, end, hours, variance))
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weekly_buckets_combine_daily_variance() {
+        let start = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+        let daily: Vec<_> = start
+            .iter_days()
+            .take(9)
+            .map(|day| bucket(day, day, 2.0, 1.0))
+            .collect();
+
+        let weekly = roll_up_buckets(&daily, WorkloadGranularity::Week);
+
+        assert_eq!(weekly.len(), 2);
+        assert_eq!(weekly[0].start, "2025-03-03");
+        assert_eq!(weekly[0].end, "2025-03-09");
+        assert!((weekly[0].expected_hours - 14.0).abs() < 1e-9);
+        let margin = INTERVAL_Z * 7f64.sqrt();
+        assert!((weekly[0].high_hours - (14.0 + margin)).abs() < 1e-9);
+        assert!((weekly[0].low_hours - (14.0 - margin)).abs() < 1e-9);
+        assert_eq!(weekly[1].end, "2025-03-11");
+        assert!((weekly[1].expected_hours - 4.0).abs() < 1e-9);
+    }
+}
@@ -473,6 +473,7 @@ pub async fn create_calendar_event_tool(
         start_at: start_at.clone(),
         end_at: end_at.clone(),
         event_type: params.event_type.clone(),
+        recurrence_rule: None,
     };
 
     // TODO: Replace with actual calendar data storage
@@ -615,6 +616,7 @@ pub async fn update_calendar_event_tool(
         start_at: updated_start_at.clone(),
         end_at: updated_end_at.clone(),
         event_type: updated_event_type,
+        recurrence_rule: None,
     };
 
     // Check for conflicts (excluding the event being updated)
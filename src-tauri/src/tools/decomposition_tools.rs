@@ -0,0 +1,84 @@
+use crate::error::{AppError, AppResult};
+use crate::services::ai_service::AiService;
+use serde::Deserialize;
+use serde_json::{json, Value as JsonValue};
+use std::sync::Arc;
+use tracing::debug;
+
+/// Decompose task schema
+pub fn decompose_task_schema() -> JsonValue {
+    json!({
+        "type": "object",
+        "properties": {
+            "task_id": {
+                "type": "string",
+                "description": "ID of the task to break down (required)"
+            },
+            "max_subtasks": {
+                "type": "integer",
+                "minimum": 2,
+                "maximum": 12,
+                "description": "Maximum number of subtasks to propose (optional, default 6)"
+            }
+        },
+        "required": ["task_id"]
+    })
+}
+
+/// Propose subtasks for a task without creating them
+pub async fn decompose_task_tool(
+    ai_service: Arc<AiService>,
+    args: JsonValue,
+) -> AppResult<JsonValue> {
+    debug!("decompose_task_tool invoked");
+
+    #[derive(Debug, Deserialize)]
+    struct DecomposeTaskParams {
+        task_id: String,
+        max_subtasks: Option<usize>,
+    }
+
+    let params: DecomposeTaskParams = serde_json::from_value(args)
+        .map_err(|e| AppError::validation(format!("Failed to parse parameters: {}", e)))?;
+
+    let decomposition = ai_service
+        .decompose_task(&params.task_id, params.max_subtasks)
+        .await?;
+
+    Ok(json!({
+        "success": true,
+        "decomposition": serde_json::to_value(&decomposition)?,
+        "count": decomposition.subtasks.len(),
+        "created": false,
+        "message": "These subtasks are proposals. Show them to the user and create them only after confirmation."
+    }))
+}
+
+/// Register task decomposition tools
+pub fn register_decomposition_tools(
+    registry: &mut crate::services::tool_registry::ToolRegistry,
+    ai_service: Arc<AiService>,
+) -> AppResult<()> {
+    use crate::services::tool_registry::ToolHandler;
+    use std::future::Future;
+    use std::pin::Pin;
+
+    // Register decompose_task tool
+    {
+        let service = Arc::clone(&ai_service);
+        let handler: ToolHandler = Arc::new(move |args: JsonValue| {
+            let service = Arc::clone(&service);
+            Box::pin(async move { decompose_task_tool(service, args).await })
+                as Pin<Box<dyn Future<Output = AppResult<JsonValue>> + Send>>
+        });
+
+        registry.register_tool(
+            "decompose_task".to_string(),
+            "Break a large task into ordered subtasks with time estimates and dependencies. Returns proposals only; nothing is created. Use when the user wants to split up, plan out or get started on a big task, then ask before creating the subtasks.".to_string(),
+            decompose_task_schema(),
+            handler,
+        )?;
+    }
+
+    Ok(())
+}
@@ -74,6 +74,28 @@ pub fn add_task_dependency_schema() -> JsonValue {
     })
 }
 
+/// Get schema for create_dependency_chain tool
+pub fn create_dependency_chain_schema() -> JsonValue {
+    json!({
+        "type": "object",
+        "properties": {
+            "task_ids": {
+                "type": "array",
+                "items": {"type": "string"},
+                "minItems": 2,
+                "description": "Task IDs in execution order; each task depends on the one before it (required)"
+            },
+            "dependency_type": {
+                "type": "string",
+                "enum": ["finish_to_start", "start_to_start", "finish_to_finish", "start_to_finish"],
+                "default": "finish_to_start",
+                "description": "Type of every link in the chain (default: finish_to_start)"
+            }
+        },
+        "required": ["task_ids"]
+    })
+}
+
 /// Get schema for remove_task_dependency tool
 pub fn remove_task_dependency_schema() -> JsonValue {
     json!({
@@ -361,6 +383,55 @@ pub async fn add_task_dependency_tool(
     Ok(result)
 }
 
+/// Chain an ordered list of tasks with sequential dependencies
+pub async fn create_dependency_chain_tool(
+    dependency_service: Arc<DependencyService>,
+    args: JsonValue,
+) -> AppResult<JsonValue> {
+    debug!("create_dependency_chain_tool invoked");
+
+    let params: CreateDependencyChainParams = serde_json::from_value(args)
+        .map_err(|e| AppError::validation(format!("Failed to parse parameters: {}", e)))?;
+
+    let task_ids: Vec<String> = params
+        .task_ids
+        .iter()
+        .map(|id| id.trim().to_string())
+        .collect();
+    if task_ids.iter().any(|id| id.is_empty()) {
+        return Err(AppError::validation("任务ID不能为空"));
+    }
+
+    let dependency_type = match params.dependency_type.as_deref() {
+        Some(value) => Some(value.parse::<DependencyType>().map_err(|_| {
+            AppError::validation(format!(
+                "无效的依赖类型: {}. 有效值: finish_to_start, start_to_start, finish_to_finish, start_to_finish",
+                value
+            ))
+        })?),
+        None => None,
+    };
+
+    let created = dependency_service
+        .add_dependency_chain(&task_ids, dependency_type)
+        .await?;
+
+    let result = json!({
+        "success": true,
+        "task_ids": task_ids,
+        "dependencies": created,
+        "created_count": created.len(),
+        "message": format!(
+            "✅ 成功串联 {} 个任务，新建 {} 条依赖关系",
+            task_ids.len(),
+            created.len()
+        )
+    });
+
+    debug!(created = created.len(), "dependency chain created successfully");
+    Ok(result)
+}
+
 /// Remove a dependency relationship
 pub async fn remove_task_dependency_tool(
     dependency_service: Arc<DependencyService>,
@@ -630,6 +701,12 @@ struct AddTaskDependencyParams {
     dependency_type: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct CreateDependencyChainParams {
+    task_ids: Vec<String>,
+    dependency_type: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct RemoveTaskDependencyParams {
     dependency_id: String,
@@ -723,6 +800,23 @@ pub fn register_dependency_tools(
         )?;
     }
 
+    // Register create_dependency_chain tool
+    {
+        let service = Arc::clone(&dependency_service);
+        let handler: ToolHandler = Arc::new(move |args: JsonValue| {
+            let service = Arc::clone(&service);
+            Box::pin(async move { create_dependency_chain_tool(service, args).await })
+                as Pin<Box<dyn Future<Output = AppResult<JsonValue>> + Send>>
+        });
+
+        registry.register_tool(
+            "create_dependency_chain".to_string(),
+            "Chain an ordered list of tasks so each depends on the previous one, in a single call. Use after breaking a project into sequential steps instead of calling add_task_dependency repeatedly. Validates the whole chain for cycles.".to_string(),
+            create_dependency_chain_schema(),
+            handler,
+        )?;
+    }
+
     // Register remove_task_dependency tool
     {
         let service = Arc::clone(&dependency_service);
@@ -284,6 +284,8 @@ pub async fn create_goal_tool(
         parent_goal_id: params.parent_goal_id,
         priority,
         target_date: None, // TODO: Add target_date support
+        project_id: None,
+        checkin_cadence: None,
     };
 
     let goal = goal_service.create_goal(request)?;
@@ -334,6 +336,8 @@ pub async fn update_goal_tool(
         status,
         priority: params.priority,
         target_date: None,
+        project_id: None,
+        checkin_cadence: None,
     };
 
     let updated_goal = goal_service.update_goal(&params.goal_id, request)?;
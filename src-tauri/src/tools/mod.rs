@@ -1,4 +1,5 @@
 pub mod calendar_tools;
+pub mod decomposition_tools;
 pub mod dependency_tools;
 pub mod goal_tools;
 pub mod recurring_task_tools;
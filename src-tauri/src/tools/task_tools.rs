@@ -29,7 +29,7 @@ pub fn create_task_schema() -> JsonValue {
             },
             "status": {
                 "type": "string",
-                "enum": ["backlog", "todo", "in_progress", "blocked", "done", "archived"],
+                "enum": ["backlog", "todo", "in_progress", "blocked", "waiting", "done", "archived"],
                 "description": "Current status of the task (default: todo)"
             },
             "due_at": {
@@ -47,6 +47,14 @@ pub fn create_task_schema() -> JsonValue {
             "estimated_hours": {
                 "type": "number",
                 "description": "Estimated hours to complete the task"
+            },
+            "delegated_to": {
+                "type": "string",
+                "description": "Person the task is handed to (optional)"
+            },
+            "waiting_on": {
+                "type": "string",
+                "description": "Who or what the task is waiting on; use with status 'waiting' (optional)"
             }
         },
         "required": ["title"]
@@ -77,7 +85,7 @@ pub fn update_task_schema() -> JsonValue {
             },
             "status": {
                 "type": "string",
-                "enum": ["backlog", "todo", "in_progress", "blocked", "done", "archived"],
+                "enum": ["backlog", "todo", "in_progress", "blocked", "waiting", "done", "archived"],
                 "description": "New status"
             },
             "due_at": {
@@ -91,6 +99,14 @@ pub fn update_task_schema() -> JsonValue {
                     "type": "string"
                 },
                 "description": "New tags for the task"
+            },
+            "delegated_to": {
+                "type": "string",
+                "description": "Person the task is handed to"
+            },
+            "waiting_on": {
+                "type": "string",
+                "description": "Who or what the task is waiting on; use with status 'waiting'"
             }
         },
         "required": ["task_id"]
@@ -118,7 +134,7 @@ pub fn list_tasks_schema() -> JsonValue {
         "properties": {
             "status": {
                 "type": "string",
-                "enum": ["backlog", "todo", "in_progress", "blocked", "done", "archived"],
+                "enum": ["backlog", "todo", "in_progress", "blocked", "waiting", "done", "archived"],
                 "description": "Filter tasks by status"
             },
             "priority": {
@@ -145,7 +161,7 @@ pub fn search_tasks_schema() -> JsonValue {
             },
             "status": {
                 "type": "string",
-                "enum": ["backlog", "todo", "in_progress", "blocked", "done", "archived"],
+                "enum": ["backlog", "todo", "in_progress", "blocked", "waiting", "done", "archived"],
                 "description": "Filter results by status"
             },
             "priority": {
@@ -158,6 +174,20 @@ pub fn search_tasks_schema() -> JsonValue {
     })
 }
 
+/// Get the schema for the stale_task_report tool
+pub fn stale_task_report_schema() -> JsonValue {
+    json!({
+        "type": "object",
+        "properties": {
+            "min_days": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Only include open tasks not updated for at least this many days (default: 14)"
+            }
+        }
+    })
+}
+
 /// Parameters for creating a task
 #[derive(Debug, Deserialize)]
 struct CreateTaskParams {
@@ -174,6 +204,10 @@ struct CreateTaskParams {
     tags: Option<Vec<String>>,
     #[serde(default)]
     estimated_hours: Option<f64>,
+    #[serde(default)]
+    delegated_to: Option<String>,
+    #[serde(default)]
+    waiting_on: Option<String>,
 }
 
 /// Parameters for updating a task
@@ -192,6 +226,10 @@ struct UpdateTaskParams {
     due_at: Option<String>,
     #[serde(default)]
     tags: Option<Vec<String>>,
+    #[serde(default)]
+    delegated_to: Option<String>,
+    #[serde(default)]
+    waiting_on: Option<String>,
 }
 
 /// Parameters for deleting a task
@@ -221,6 +259,13 @@ struct SearchTasksParams {
     priority: Option<String>,
 }
 
+/// Parameters for the stale task report
+#[derive(Debug, Deserialize)]
+struct StaleTaskReportParams {
+    #[serde(default)]
+    min_days: Option<u32>,
+}
+
 /// Helper function to extract parameters from JSON
 fn extract_params<T: for<'de> Deserialize<'de>>(args: &JsonValue) -> AppResult<T> {
     serde_json::from_value(args.clone())
@@ -238,6 +283,8 @@ fn format_task_for_ai(task: &crate::models::task::TaskRecord) -> JsonValue {
         "due_at": task.due_at,
         "tags": task.tags,
         "estimated_hours": task.estimated_hours,
+        "delegated_to": task.delegated_to,
+        "waiting_on": task.waiting_on,
         "created_at": task.created_at,
         "updated_at": task.updated_at,
     })
@@ -321,6 +368,8 @@ pub async fn create_task_tool(
         due_at: params.due_at,
         tags: params.tags,
         estimated_hours: params.estimated_hours,
+        delegated_to: params.delegated_to,
+        waiting_on: params.waiting_on,
         ..Default::default()
     };
 
@@ -366,6 +415,8 @@ pub async fn update_task_tool(
         status: params.status,
         due_at: params.due_at.map(Some),
         tags: params.tags.map(Some),
+        delegated_to: params.delegated_to.map(Some),
+        waiting_on: params.waiting_on.map(Some),
         ..Default::default()
     };
 
@@ -555,6 +606,44 @@ pub async fn search_tasks_tool(
     }
 }
 
+/// Report open tasks that have gone untouched
+///
+/// Groups stale tasks by age and suggests archiving, re-estimating or breaking
+/// down each one, so backlog cleanup conversations can act on real data.
+pub async fn stale_task_report_tool(
+    task_service: Arc<TaskService>,
+    args: JsonValue,
+) -> AppResult<JsonValue> {
+    debug!(target: "task_tools", "Building stale task report with args: {}", args);
+
+    let params: StaleTaskReportParams = extract_params(&args)?;
+    let report = task_service.stale_report(params.min_days)?;
+
+    let message = if report.tasks.is_empty() {
+        format!(
+            "No open tasks have gone untouched for {} days or more.",
+            report.min_days
+        )
+    } else {
+        let mut lines = vec![format!(
+            "{} open tasks untouched for {}+ days:",
+            report.tasks.len(),
+            report.min_days
+        )];
+        for bucket in report.buckets.iter().filter(|bucket| bucket.count > 0) {
+            lines.push(format!("- {}: {}", bucket.label, bucket.count));
+        }
+        lines.join("\n")
+    };
+
+    Ok(json!({
+        "success": true,
+        "message": message,
+        "count": report.tasks.len(),
+        "report": report
+    }))
+}
+
 /// Register all task management tools with the tool registry
 ///
 /// # Arguments
@@ -673,6 +762,27 @@ pub fn register_task_tools(
         )?;
     }
 
-    debug!(target: "task_tools", "Registered 5 task management tools");
+    // Register stale_task_report tool
+    {
+        let service = Arc::clone(&task_service);
+        let handler: ToolHandler = Arc::new(move |args: JsonValue| {
+            let service = Arc::clone(&service);
+            Box::pin(async move { stale_task_report_tool(service, args).await })
+                as Pin<Box<dyn Future<Output = AppResult<JsonValue>> + Send>>
+        });
+
+        registry.register_tool(
+            "stale_task_report".to_string(),
+            "Report open tasks that have not been updated for a while, grouped by age, each with a suggested disposition (archive, reestimate or break_down) and the reason. Use when the user wants to 'clean up my backlog', review old or forgotten tasks, or asks what to drop. Act on the suggestions with update_task only after the user agrees.".to_string(),
+            json!({
+                "type": "object",
+                "properties": stale_task_report_schema()["properties"],
+                "required": []
+            }),
+            handler,
+        )?;
+    }
+
+    debug!(target: "task_tools", "Registered 6 task management tools");
     Ok(())
 }
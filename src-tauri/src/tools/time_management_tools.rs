@@ -110,7 +110,7 @@ pub fn update_time_item_schema() -> JsonValue {
             },
             "status": {
                 "type": "string",
-                "enum": ["todo", "in_progress", "done", "blocked"],
+                "enum": ["todo", "in_progress", "done", "blocked", "waiting"],
                 "description": "New status of the time item (optional)"
             }
         },
@@ -0,0 +1,149 @@
+//! Colors and icons tasks and projects may carry, so every view renders them
+//! the same way without its own mapping.
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::error::{AppError, AppResult};
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PaletteColor {
+    pub key: &'static str,
+    /// Lowercase `#rrggbb`, the form stored on records.
+    pub hex: &'static str,
+}
+
+const fn color(key: &'static str, hex: &'static str) -> PaletteColor {
+    PaletteColor { key, hex }
+}
+
+pub const COLOR_PALETTE: [PaletteColor; 18] = [
+    color("slate", "#64748b"),
+    color("red", "#ef4444"),
+    color("orange", "#f97316"),
+    color("amber", "#f59e0b"),
+    color("yellow", "#eab308"),
+    color("lime", "#84cc16"),
+    color("green", "#22c55e"),
+    color("emerald", "#10b981"),
+    color("teal", "#14b8a6"),
+    color("cyan", "#06b6d4"),
+    color("sky", "#0ea5e9"),
+    color("blue", "#3b82f6"),
+    color("indigo", "#6366f1"),
+    color("violet", "#8b5cf6"),
+    color("purple", "#a855f7"),
+    color("fuchsia", "#d946ef"),
+    color("pink", "#ec4899"),
+    color("rose", "#f43f5e"),
+];
+
+/// Lucide icon names.
+pub const ICON_SET: [&str; 30] = [
+    "circle",
+    "briefcase",
+    "book-open",
+    "code",
+    "pen-tool",
+    "phone",
+    "mail",
+    "users",
+    "calendar",
+    "clock",
+    "target",
+    "flag",
+    "star",
+    "heart",
+    "home",
+    "shopping-cart",
+    "dumbbell",
+    "music",
+    "plane",
+    "graduation-cap",
+    "lightbulb",
+    "wrench",
+    "file-text",
+    "coffee",
+    "bug",
+    "rocket",
+    "dollar-sign",
+    "camera",
+    "leaf",
+    "gamepad-2",
+];
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AppearanceOptions {
+    pub colors: Vec<PaletteColor>,
+    pub icons: Vec<&'static str>,
+}
+
+pub fn appearance_options() -> AppearanceOptions {
+    AppearanceOptions {
+        colors: COLOR_PALETTE.to_vec(),
+        icons: ICON_SET.to_vec(),
+    }
+}
+
+/// Accepts a palette color as `#rrggbb` in any case; blank clears it.
+pub fn normalize_color(color: Option<String>) -> AppResult<Option<String>> {
+    let Some(color) = color
+        .map(|value| value.trim().to_ascii_lowercase())
+        .filter(|value| !value.is_empty())
+    else {
+        return Ok(None);
+    };
+    let valid = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|ch| ch.is_ascii_hexdigit());
+    if !valid {
+        return Err(AppError::validation("颜色需为 #RRGGBB 格式"));
+    }
+    if !COLOR_PALETTE.iter().any(|entry| entry.hex == color) {
+        return Err(AppError::validation("颜色需从调色板中选择"));
+    }
+    Ok(Some(color))
+}
+
+/// Accepts an icon from [`ICON_SET`]; blank clears it.
+pub fn normalize_icon(icon: Option<String>) -> AppResult<Option<String>> {
+    let Some(icon) = icon
+        .map(|value| value.trim().to_ascii_lowercase())
+        .filter(|value| !value.is_empty())
+    else {
+        return Ok(None);
+    };
+    if !ICON_SET.contains(&icon.as_str()) {
+        return Err(AppError::validation(format!("不支持的图标: {icon}")));
+    }
+    Ok(Some(icon))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_color_only_accepts_palette_entries() {
+        assert_eq!(
+            normalize_color(Some(" #3B82F6 ".into()))
+                .unwrap()
+                .as_deref(),
+            Some("#3b82f6")
+        );
+        assert_eq!(normalize_color(Some("  ".into())).unwrap(), None);
+        assert!(normalize_color(Some("#123456".into())).is_err());
+        assert!(normalize_color(Some("blue".into())).is_err());
+    }
+
+    #[test]
+    fn normalize_icon_only_accepts_icon_set() {
+        assert_eq!(
+            normalize_icon(Some("Rocket".into())).unwrap().as_deref(),
+            Some("rocket")
+        );
+        assert!(normalize_icon(Some("<script>".into())).is_err());
+    }
+}
@@ -0,0 +1,208 @@
+//! Writes export entries one at a time into a directory, a zip file or a
+//! `.tar.gz` file, so large exports never have to be held in memory.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Component, Path, PathBuf};
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::error::{AppError, AppResult};
+use crate::utils::files::{copy_atomic, file_checksum, write_atomic_with, HashingWriter};
+
+pub enum BundleWriter {
+    Directory(PathBuf),
+    Zip(ZipWriter<File>),
+    TarGz {
+        builder: tar::Builder<GzEncoder<File>>,
+        /// Tar headers need the entry size up front, so generated entries are
+        /// staged here before being appended.
+        staging: PathBuf,
+    },
+}
+
+impl BundleWriter {
+    pub fn directory(root: &Path) -> AppResult<Self> {
+        fs::create_dir_all(root)?;
+        Ok(Self::Directory(root.to_path_buf()))
+    }
+
+    pub fn zip(file: File) -> Self {
+        Self::Zip(ZipWriter::new(file))
+    }
+
+    pub fn tar_gz(file: File, staging: PathBuf) -> Self {
+        Self::TarGz {
+            builder: tar::Builder::new(GzEncoder::new(file, Compression::default())),
+            staging,
+        }
+    }
+
+    /// Adds the entry `relative` with the content produced by `write` and
+    /// returns its SHA-256 checksum, hex encoded.
+    pub fn add_entry<F>(&mut self, relative: &Path, write: F) -> AppResult<String>
+    where
+        F: FnOnce(&mut dyn Write) -> AppResult<()>,
+    {
+        match self {
+            Self::Directory(root) => {
+                let target = root.join(relative);
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                write_atomic_with(&target, |file| {
+                    let mut writer = BufWriter::new(file);
+                    write(&mut writer)?;
+                    writer.into_inner().map_err(|err| err.into_error().into())
+                })
+            }
+            Self::Zip(zip) => {
+                zip.start_file(entry_name(relative)?, zip_options())
+                    .map_err(zip_error)?;
+                let mut writer = HashingWriter::new(zip);
+                write(&mut writer)?;
+                Ok(writer.checksum())
+            }
+            Self::TarGz { builder, staging } => {
+                let checksum = write_atomic_with(staging, |file| {
+                    let mut writer = BufWriter::new(file);
+                    write(&mut writer)?;
+                    writer.into_inner().map_err(|err| err.into_error().into())
+                })?;
+                let appended = builder.append_path_with_name(&*staging, relative);
+                let _ = fs::remove_file(&*staging);
+                appended?;
+                Ok(checksum)
+            }
+        }
+    }
+
+    /// Adds the file at `source` as `relative`, streaming it from disk.
+    pub fn add_file(&mut self, relative: &Path, source: &Path) -> AppResult<String> {
+        match self {
+            Self::Directory(root) => {
+                let target = root.join(relative);
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                copy_atomic(source, &target)
+            }
+            Self::Zip(zip) => {
+                zip.start_file(entry_name(relative)?, zip_options())
+                    .map_err(zip_error)?;
+                let mut writer = HashingWriter::new(zip);
+                io::copy(&mut File::open(source)?, &mut writer)?;
+                Ok(writer.checksum())
+            }
+            Self::TarGz { builder, .. } => {
+                builder.append_path_with_name(source, relative)?;
+                file_checksum(source)
+            }
+        }
+    }
+
+    /// Completes the bundle. Returns the bundle file, or `None` when writing
+    /// into a directory.
+    pub fn finish(self) -> AppResult<Option<File>> {
+        match self {
+            Self::Directory(_) => Ok(None),
+            Self::Zip(zip) => Ok(Some(zip.finish().map_err(zip_error)?)),
+            Self::TarGz { builder, .. } => Ok(Some(builder.into_inner()?.finish()?)),
+        }
+    }
+}
+
+fn zip_options() -> SimpleFileOptions {
+    SimpleFileOptions::default().compression_method(CompressionMethod::Deflated)
+}
+
+/// Zip entry names always use `/`, whatever the platform separator is.
+fn entry_name(relative: &Path) -> AppResult<String> {
+    let parts = relative
+        .components()
+        .map(|component| match component {
+            Component::Normal(part) => Ok(part.to_string_lossy().into_owned()),
+            _ => Err(AppError::other(format!(
+                "无效的导出条目路径: {}",
+                relative.display()
+            ))),
+        })
+        .collect::<AppResult<Vec<_>>>()?;
+    Ok(parts.join("/"))
+}
+
+fn zip_error(err: zip::result::ZipError) -> AppError {
+    AppError::other(format!("无法写入压缩文件: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn write_sample(writer: &mut BundleWriter, source: &Path) -> (String, String) {
+        let generated = writer
+            .add_entry(Path::new("notes/summary.md"), |out| {
+                out.write_all(b"# Summary")?;
+                Ok(())
+            })
+            .unwrap();
+        let copied = writer.add_file(Path::new("raw.md"), source).unwrap();
+        (generated, copied)
+    }
+
+    #[test]
+    fn every_format_holds_the_same_entries_and_checksums() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source.md");
+        fs::write(&source, "raw content").unwrap();
+
+        let mut directory = BundleWriter::directory(&dir.path().join("out")).unwrap();
+        let expected = write_sample(&mut directory, &source);
+        assert!(directory.finish().unwrap().is_none());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("out/notes/summary.md")).unwrap(),
+            "# Summary"
+        );
+
+        let zip_path = dir.path().join("out.zip");
+        let mut zip = BundleWriter::zip(File::create(&zip_path).unwrap());
+        assert_eq!(write_sample(&mut zip, &source), expected);
+        zip.finish().unwrap().unwrap();
+        let mut archive = zip::ZipArchive::new(File::open(&zip_path).unwrap()).unwrap();
+        let mut content = String::new();
+        archive
+            .by_name("notes/summary.md")
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "# Summary");
+
+        let tar_path = dir.path().join("out.tar.gz");
+        let mut tar = BundleWriter::tar_gz(
+            File::create(&tar_path).unwrap(),
+            dir.path().join("out.tar.gz.entry"),
+        );
+        assert_eq!(write_sample(&mut tar, &source), expected);
+        tar.finish().unwrap().unwrap();
+        let mut archive =
+            tar::Archive::new(flate2::read::GzDecoder::new(File::open(&tar_path).unwrap()));
+        let names: Vec<String> = archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                entry
+                    .unwrap()
+                    .path()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        assert_eq!(names, ["notes/summary.md", "raw.md"]);
+        assert!(!dir.path().join("out.tar.gz.entry").exists());
+    }
+}
@@ -31,7 +31,7 @@ impl CryptoVault {
 
     pub fn new(account_id: &str) -> AppResult<Self> {
         Entry::new(KEYRING_SERVICE, account_id)
-            .map_err(|err| AppError::other(format!("无法初始化系统密钥存储: {err}")))?;
+            .map_err(|err| AppError::crypto(format!("无法初始化系统密钥存储: {err}")))?;
         Ok(Self {
             account: account_id.to_string(),
         })
@@ -52,7 +52,7 @@ impl CryptoVault {
         match entry.delete_password() {
             Ok(_) => Ok(()),
             Err(keyring::Error::NoEntry) => Ok(()),
-            Err(err) => Err(AppError::other(format!(
+            Err(err) => Err(AppError::crypto(format!(
                 "无法删除系统密钥存储中的凭据: {err}"
             ))),
         }
@@ -63,7 +63,7 @@ impl CryptoVault {
         match entry.get_password() {
             Ok(secret) => decode_master_secret(&secret),
             Err(keyring::Error::NoEntry) => self.create_master_secret(entry),
-            Err(err) => Err(AppError::other(format!("无法访问系统密钥存储: {err}"))),
+            Err(err) => Err(AppError::crypto(format!("无法访问系统密钥存储: {err}"))),
         }
     }
 
@@ -73,33 +73,33 @@ impl CryptoVault {
         let encoded = Base64.encode(&secret);
         entry
             .set_password(&encoded)
-            .map_err(|err| AppError::other(format!("无法写入系统密钥存储: {err}")))?;
+            .map_err(|err| AppError::crypto(format!("无法写入系统密钥存储: {err}")))?;
         Ok(secret)
     }
 
     fn entry(&self) -> AppResult<Entry> {
         Entry::new(KEYRING_SERVICE, &self.account)
-            .map_err(|err| AppError::other(format!("无法初始化系统密钥存储: {err}")))
+            .map_err(|err| AppError::crypto(format!("无法初始化系统密钥存储: {err}")))
     }
 }
 
 pub(crate) fn encrypt_with_master(master_secret: &[u8], plaintext: &[u8]) -> AppResult<String> {
     if master_secret.len() != KEY_LEN {
-        return Err(AppError::other("主密钥长度无效"));
+        return Err(AppError::crypto("主密钥长度无效"));
     }
 
     let mut salt = [0u8; SALT_LEN];
     OsRng.fill_bytes(&mut salt);
     let key = derive_key(master_secret, &salt);
     let cipher =
-        Aes256Gcm::new_from_slice(&key).map_err(|_| AppError::other("无法初始化加密器"))?;
+        Aes256Gcm::new_from_slice(&key).map_err(|_| AppError::crypto("无法初始化加密器"))?;
 
     let mut nonce = [0u8; NONCE_LEN];
     OsRng.fill_bytes(&mut nonce);
 
     let ciphertext = cipher
         .encrypt(Nonce::from_slice(&nonce), plaintext)
-        .map_err(|_| AppError::other("加密失败"))?;
+        .map_err(|_| AppError::crypto("加密失败"))?;
 
     let mut payload = Vec::with_capacity(SALT_LEN + NONCE_LEN + ciphertext.len());
     payload.extend_from_slice(&salt);
@@ -111,19 +111,19 @@ pub(crate) fn encrypt_with_master(master_secret: &[u8], plaintext: &[u8]) -> App
 
 pub(crate) fn decrypt_with_master(master_secret: &[u8], ciphertext: &str) -> AppResult<Vec<u8>> {
     if master_secret.len() != KEY_LEN {
-        return Err(AppError::other("主密钥长度无效"));
+        return Err(AppError::crypto("主密钥长度无效"));
     }
 
     let encoded = ciphertext
         .strip_prefix(VERSION_PREFIX)
-        .ok_or_else(|| AppError::other("密文格式不受支持"))?;
+        .ok_or_else(|| AppError::crypto("密文格式不受支持"))?;
 
     let decoded = Base64
         .decode(encoded.as_bytes())
-        .map_err(|_| AppError::other("密文损坏，无法解码"))?;
+        .map_err(|_| AppError::crypto("密文损坏，无法解码"))?;
 
     if decoded.len() <= SALT_LEN + NONCE_LEN {
-        return Err(AppError::other("密文数据长度无效"));
+        return Err(AppError::crypto("密文数据长度无效"));
     }
 
     let (salt, rest) = decoded.split_at(SALT_LEN);
@@ -131,11 +131,11 @@ pub(crate) fn decrypt_with_master(master_secret: &[u8], ciphertext: &str) -> App
 
     let key = derive_key(master_secret, salt);
     let cipher =
-        Aes256Gcm::new_from_slice(&key).map_err(|_| AppError::other("无法初始化解密器"))?;
+        Aes256Gcm::new_from_slice(&key).map_err(|_| AppError::crypto("无法初始化解密器"))?;
 
     cipher
         .decrypt(Nonce::from_slice(nonce_bytes), ciphertext_bytes)
-        .map_err(|_| AppError::other("解密失败"))
+        .map_err(|_| AppError::crypto("解密失败"))
 }
 
 fn derive_key(master: &[u8], salt: &[u8]) -> [u8; KEY_LEN] {
@@ -147,9 +147,9 @@ fn derive_key(master: &[u8], salt: &[u8]) -> [u8; KEY_LEN] {
 fn decode_master_secret(encoded: &str) -> AppResult<Vec<u8>> {
     let secret = Base64
         .decode(encoded.as_bytes())
-        .map_err(|_| AppError::other("系统密钥存储中的凭据损坏"))?;
+        .map_err(|_| AppError::crypto("系统密钥存储中的凭据损坏"))?;
     if secret.len() != KEY_LEN {
-        return Err(AppError::other("系统密钥存储中的凭据长度无效"));
+        return Err(AppError::crypto("系统密钥存储中的凭据长度无效"));
     }
     Ok(secret)
 }
@@ -0,0 +1,131 @@
+//! Crash-safe writes for generated files. Content goes to a temporary file
+//! next to the target, is flushed to disk and then renamed over the target,
+//! so readers only ever see the old file or the complete new one.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::error::AppResult;
+
+const TEMP_SUFFIX: &str = ".tmp";
+
+/// Atomically replaces `path` with `bytes` and returns the SHA-256 checksum
+/// of the written file, hex encoded.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> AppResult<String> {
+    write_atomic_with(path, |mut file| {
+        file.write_all(bytes)?;
+        Ok(file)
+    })
+}
+
+/// Like [`write_atomic`] for content produced by a streaming writer. `write`
+/// receives the temporary file and hands it back once everything is written.
+pub fn write_atomic_with<F>(path: &Path, write: F) -> AppResult<String>
+where
+    F: FnOnce(File) -> AppResult<File>,
+{
+    let temp_path = temp_path_for(path);
+    let result = File::create(&temp_path)
+        .map_err(Into::into)
+        .and_then(write)
+        .and_then(|file| file.sync_all().map_err(Into::into))
+        .and_then(|_| fs::rename(&temp_path, path).map_err(Into::into));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result?;
+
+    file_checksum(path)
+}
+
+/// Atomically copies `source` to `target`, returning the checksum of the
+/// copy.
+pub fn copy_atomic(source: &Path, target: &Path) -> AppResult<String> {
+    write_atomic_with(target, |mut file| {
+        io::copy(&mut File::open(source)?, &mut file)?;
+        Ok(file)
+    })
+}
+
+/// SHA-256 of the file at `path`, hex encoded.
+pub fn file_checksum(path: &Path) -> AppResult<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Passes writes through to `inner` while hashing them, for checksums of
+/// content that never exists as a file of its own.
+pub struct HashingWriter<W> {
+    inner: W,
+    hasher: Sha256,
+}
+
+impl<W: Write> HashingWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+        }
+    }
+
+    /// SHA-256 of everything written so far, hex encoded.
+    pub fn checksum(self) -> String {
+        format!("{:x}", self.hasher.finalize())
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn temp_path_for(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(TEMP_SUFFIX);
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_the_target_and_returns_its_checksum() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.md");
+        fs::write(&path, "old").unwrap();
+
+        let checksum = write_atomic(&path, b"new report").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new report");
+        assert_eq!(checksum, format!("{:x}", Sha256::digest(b"new report")));
+        assert!(!temp_path_for(&path).exists());
+    }
+
+    #[test]
+    fn failed_writes_keep_the_previous_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.json");
+        fs::write(&path, "complete").unwrap();
+
+        let result = write_atomic_with(&path, |mut file| {
+            file.write_all(b"partial")?;
+            Err(crate::error::AppError::other("interrupted"))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "complete");
+        assert!(!temp_path_for(&path).exists());
+    }
+}
@@ -0,0 +1,398 @@
+//! Just enough iCalendar (RFC 5545) to exchange tasks as VTODO and time
+//! blocks as VEVENT. Components keep every property as parsed text; callers
+//! read the few they map and ignore the rest.
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::error::{AppError, AppResult};
+
+pub const PRODID: &str = "-//CogniCal//CogniCal//ZH";
+/// Content lines are folded after this many octets.
+const FOLD_OCTETS: usize = 75;
+const UTC_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+const LOCAL_FORMAT: &str = "%Y%m%dT%H%M%S";
+const DATE_FORMAT: &str = "%Y%m%d";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Property {
+    pub name: String,
+    pub params: Vec<(String, String)>,
+    /// Raw value; text values are still escaped.
+    pub value: String,
+}
+
+impl Property {
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Component {
+    pub name: String,
+    pub properties: Vec<Property>,
+    pub children: Vec<Component>,
+}
+
+impl Component {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_ascii_uppercase(),
+            ..Self::default()
+        }
+    }
+
+    pub fn push_value(&mut self, name: &str, value: impl Into<String>) -> &mut Self {
+        self.properties.push(Property {
+            name: name.to_ascii_uppercase(),
+            params: Vec::new(),
+            value: value.into(),
+        });
+        self
+    }
+
+    pub fn push_text(&mut self, name: &str, value: &str) -> &mut Self {
+        self.push_value(name, escape_text(value))
+    }
+
+    pub fn push_datetime(&mut self, name: &str, value: DateTime<Utc>) -> &mut Self {
+        self.push_value(name, format_utc(value))
+    }
+
+    pub fn property(&self, name: &str) -> Option<&Property> {
+        self.properties
+            .iter()
+            .find(|property| property.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Unescaped text value, `None` when missing or blank.
+    pub fn text(&self, name: &str) -> Option<String> {
+        self.property(name)
+            .map(|property| unescape_text(&property.value))
+            .filter(|value| !value.trim().is_empty())
+    }
+
+    /// Comma-separated text values such as `CATEGORIES`, across repeats.
+    pub fn text_list(&self, name: &str) -> Vec<String> {
+        self.properties
+            .iter()
+            .filter(|property| property.name.eq_ignore_ascii_case(name))
+            .flat_map(|property| split_unescaped(&property.value, ','))
+            .map(|value| unescape_text(&value))
+            .filter(|value| !value.trim().is_empty())
+            .collect()
+    }
+
+    pub fn datetime(&self, name: &str) -> Option<DateTime<Utc>> {
+        let property = self.property(name)?;
+        parse_datetime(&property.value, property.param("TZID"))
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Component> {
+        self.children
+            .iter()
+            .find(|child| child.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Content lines of this component, unfolded and without a trailing
+    /// line break.
+    pub fn content_lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("BEGIN:{}", self.name)];
+        for property in &self.properties {
+            let mut line = property.name.clone();
+            for (key, value) in &property.params {
+                line.push(';');
+                line.push_str(key);
+                line.push('=');
+                if value.contains([':', ';', ',']) {
+                    line.push_str(&format!("\"{value}\""));
+                } else {
+                    line.push_str(value);
+                }
+            }
+            line.push(':');
+            line.push_str(&property.value);
+            lines.push(line);
+        }
+        for child in &self.children {
+            lines.extend(child.content_lines());
+        }
+        lines.push(format!("END:{}", self.name));
+        lines
+    }
+}
+
+/// Wraps `components` in a VCALENDAR and renders it with folded CRLF lines.
+pub fn write_calendar(components: &[Component]) -> String {
+    let mut calendar = Component::new("VCALENDAR");
+    calendar
+        .push_value("VERSION", "2.0")
+        .push_value("PRODID", PRODID)
+        .push_value("CALSCALE", "GREGORIAN");
+    calendar.children = components.to_vec();
+    calendar
+        .content_lines()
+        .iter()
+        .map(|line| fold_line(line))
+        .collect::<Vec<_>>()
+        .join("")
+}
+
+/// Top-level components of `input`, normally a single VCALENDAR.
+pub fn parse(input: &str) -> AppResult<Vec<Component>> {
+    let mut stack: Vec<Component> = Vec::new();
+    let mut roots = Vec::new();
+
+    for line in unfold(input) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let property = parse_line(&line)
+            .ok_or_else(|| AppError::validation(format!("无法解析的日历行: {line}")))?;
+        if property.name.eq_ignore_ascii_case("BEGIN") {
+            stack.push(Component::new(property.value.trim()));
+        } else if property.name.eq_ignore_ascii_case("END") {
+            let component = stack
+                .pop()
+                .filter(|component| component.name.eq_ignore_ascii_case(property.value.trim()))
+                .ok_or_else(|| AppError::validation("日历组件的 BEGIN 与 END 不匹配"))?;
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(component),
+                None => roots.push(component),
+            }
+        } else if let Some(current) = stack.last_mut() {
+            current.properties.push(property);
+        }
+    }
+
+    if !stack.is_empty() {
+        return Err(AppError::validation("日历数据不完整"));
+    }
+    Ok(roots)
+}
+
+/// Every component named `name` at any depth, e.g. all VTODOs of a feed.
+pub fn find_components<'a>(roots: &'a [Component], name: &str) -> Vec<&'a Component> {
+    let mut found = Vec::new();
+    let mut pending: Vec<&Component> = roots.iter().collect();
+    while let Some(component) = pending.pop() {
+        if component.name.eq_ignore_ascii_case(name) {
+            found.push(component);
+        }
+        pending.extend(component.children.iter().rev());
+    }
+    found
+}
+
+pub fn format_utc(value: DateTime<Utc>) -> String {
+    value.format(UTC_FORMAT).to_string()
+}
+
+/// Parses a DATE-TIME in UTC, with a TZID, or floating (read as local
+/// time), and a DATE as midnight UTC.
+pub fn parse_datetime(value: &str, tzid: Option<&str>) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Some(utc) = value.strip_suffix('Z') {
+        return NaiveDateTime::parse_from_str(utc, LOCAL_FORMAT)
+            .ok()
+            .map(|naive| Utc.from_utc_datetime(&naive));
+    }
+    if let Ok(naive) = NaiveDateTime::parse_from_str(value, LOCAL_FORMAT) {
+        return match tzid.and_then(|tzid| tzid.parse::<Tz>().ok()) {
+            Some(zone) => zone
+                .from_local_datetime(&naive)
+                .earliest()
+                .map(|at| at.with_timezone(&Utc)),
+            None => Local
+                .from_local_datetime(&naive)
+                .earliest()
+                .map(|at| at.with_timezone(&Utc)),
+        };
+    }
+    NaiveDate::parse_from_str(value, DATE_FORMAT)
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|naive| Utc.from_utc_datetime(&naive))
+}
+
+pub fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
+
+pub fn unescape_text(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            unescaped.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+fn split_unescaped(value: &str, separator: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut escaped = false;
+    for ch in value.chars() {
+        if ch == separator && !escaped {
+            parts.push(String::new());
+            continue;
+        }
+        escaped = ch == '\\' && !escaped;
+        parts.last_mut().unwrap().push(ch);
+    }
+    parts
+}
+
+fn unfold(input: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in input.split('\n') {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        match (raw.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(raw.to_string()),
+        }
+    }
+    lines
+}
+
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut octets = 0;
+    for ch in line.chars() {
+        if octets + ch.len_utf8() > FOLD_OCTETS {
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+        folded.push(ch);
+        octets += ch.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+fn parse_line(line: &str) -> Option<Property> {
+    let mut in_quotes = false;
+    let mut colon = None;
+    for (index, ch) in line.char_indices() {
+        match ch {
+            '"' => in_quotes = !in_quotes,
+            ':' if !in_quotes => {
+                colon = Some(index);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let colon = colon?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    in_quotes = false;
+    for ch in head.chars() {
+        match ch {
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => segments.push(std::mem::take(&mut current)),
+            other => current.push(other),
+        }
+    }
+    segments.push(current);
+
+    let mut segments = segments.into_iter();
+    let name = segments.next()?.trim().to_ascii_uppercase();
+    if name.is_empty() {
+        return None;
+    }
+    let params = segments
+        .filter_map(|segment| {
+            let (key, value) = segment.split_once('=')?;
+            Some((key.trim().to_ascii_uppercase(), value.to_string()))
+        })
+        .collect();
+    Some(Property {
+        name,
+        params,
+        value: value.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_escaped_text_and_long_lines() {
+        let mut todo = Component::new("VTODO");
+        let title =
+            "写周报; 包括 Q3 数据, 以及一段很长很长的说明文字，确保这一行会被折叠成多行输出"
+                .repeat(2);
+        todo.push_value("UID", "task-1")
+            .push_text("SUMMARY", &title)
+            .push_text("DESCRIPTION", "第一行\n第二行")
+            .push_datetime("DUE", Utc.with_ymd_and_hms(2026, 3, 2, 9, 30, 0).unwrap());
+
+        let ics = write_calendar(&[todo]);
+        assert!(ics.lines().all(|line| line.len() <= FOLD_OCTETS + 1));
+
+        let roots = parse(&ics).unwrap();
+        let todos = find_components(&roots, "VTODO");
+        assert_eq!(todos.len(), 1);
+        assert_eq!(todos[0].text("SUMMARY").as_deref(), Some(title.as_str()));
+        assert_eq!(
+            todos[0].text("DESCRIPTION").as_deref(),
+            Some("第一行\n第二行")
+        );
+        assert_eq!(
+            todos[0].datetime("DUE"),
+            Some(Utc.with_ymd_and_hms(2026, 3, 2, 9, 30, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn reads_zoned_dates_params_and_lists() {
+        let ics = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:a\r\n\
+                   DTSTART;TZID=Europe/Berlin:20260115T090000\r\n\
+                   DTEND;VALUE=DATE:20260116\r\n\
+                   CATEGORIES:work,deep\\, focus\r\n\
+                   X-NOTE;X-LABEL=\"a:b\":value\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        let roots = parse(ics).unwrap();
+        let event = find_components(&roots, "VEVENT")[0];
+
+        assert_eq!(
+            event.datetime("DTSTART"),
+            Some(Utc.with_ymd_and_hms(2026, 1, 15, 8, 0, 0).unwrap())
+        );
+        assert_eq!(
+            event.datetime("DTEND"),
+            Some(Utc.with_ymd_and_hms(2026, 1, 16, 0, 0, 0).unwrap())
+        );
+        assert_eq!(event.text_list("CATEGORIES"), vec!["work", "deep, focus"]);
+        assert_eq!(
+            event.property("X-NOTE").unwrap().param("x-label"),
+            Some("a:b")
+        );
+        assert!(parse("BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\nEND:VCALENDAR\r\n").is_err());
+    }
+}
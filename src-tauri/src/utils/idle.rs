@@ -0,0 +1,74 @@
+//! How long the user has been away from keyboard and mouse. Read through
+//! tools the desktop already ships (`xprintidle` on X11, `ioreg` on macOS);
+//! anywhere else idle time is unavailable and focus time is left untouched.
+
+use std::process::Command;
+use std::time::Duration as StdDuration;
+
+pub trait IdleSource: Send + Sync {
+    /// Time since the last user input, or `None` when it cannot be read.
+    fn idle_time(&self) -> Option<StdDuration>;
+}
+
+/// Reads idle time from the operating system.
+#[derive(Debug, Default)]
+pub struct SystemIdleSource;
+
+impl IdleSource for SystemIdleSource {
+    fn idle_time(&self) -> Option<StdDuration> {
+        if cfg!(target_os = "macos") {
+            parse_ioreg_idle(&command_output("ioreg", &["-c", "IOHIDSystem", "-d", "4"])?)
+        } else if cfg!(target_os = "linux") {
+            parse_xprintidle(&command_output("xprintidle", &[])?)
+        } else {
+            None
+        }
+    }
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// `xprintidle` prints milliseconds.
+fn parse_xprintidle(output: &str) -> Option<StdDuration> {
+    output
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(StdDuration::from_millis)
+}
+
+/// `ioreg` reports `"HIDIdleTime" = <nanoseconds>` for the HID system.
+fn parse_ioreg_idle(output: &str) -> Option<StdDuration> {
+    output
+        .lines()
+        .find(|line| line.contains("\"HIDIdleTime\""))
+        .and_then(|line| line.rsplit('=').next())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(StdDuration::from_nanos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idle_time_is_parsed_from_tool_output() {
+        assert_eq!(
+            parse_xprintidle("93512\n"),
+            Some(StdDuration::from_millis(93_512))
+        );
+        assert_eq!(parse_xprintidle("couldn't open display"), None);
+
+        let ioreg = r#"
+    | |   "HIDIdleTime" = 4000000000
+    | |   "HIDKeyboardModifierMappingPairs" = ()"#;
+        assert_eq!(parse_ioreg_idle(ioreg), Some(StdDuration::from_secs(4)));
+        assert_eq!(parse_ioreg_idle("+-o Root"), None);
+    }
+}
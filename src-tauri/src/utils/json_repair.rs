@@ -0,0 +1,171 @@
+use serde_json::Value as JsonValue;
+
+/// Upper bound on truncation points tried when closing a cut-off document.
+const MAX_TRUNCATION_CANDIDATES: usize = 32;
+
+/// Best-effort repair of malformed model output.
+///
+/// Leading prose is skipped, anything after the balanced top-level value is
+/// dropped and trailing commas are removed. A truncated document is closed
+/// at the end, or failing that at the latest element boundary that yields
+/// valid JSON. Returns `None` when no candidate parses.
+pub fn repair_json(raw: &str) -> Option<JsonValue> {
+    let start = raw.find(['{', '['])?;
+    let text = &raw[start..];
+    let scan = scan(text);
+
+    if let Some(end) = scan.closed_at {
+        return parse(&remove_trailing_commas(&text[..end]));
+    }
+
+    let mut whole = text.trim_end().to_string();
+    if scan.in_string {
+        if scan.escaped {
+            whole.pop();
+        }
+        whole.push('"');
+    }
+    whole.push_str(&closers(&scan.stack));
+
+    std::iter::once(whole)
+        .chain(
+            scan.commas
+                .iter()
+                .rev()
+                .take(MAX_TRUNCATION_CANDIDATES)
+                .map(|(position, stack)| format!("{}{}", &text[..*position], closers(stack))),
+        )
+        .find_map(|candidate| parse(&remove_trailing_commas(&candidate)))
+}
+
+struct Scan {
+    closed_at: Option<usize>,
+    in_string: bool,
+    escaped: bool,
+    stack: Vec<char>,
+    /// Commas outside strings with the open containers at that point.
+    commas: Vec<(usize, Vec<char>)>,
+}
+
+fn scan(text: &str) -> Scan {
+    let mut result = Scan {
+        closed_at: None,
+        in_string: false,
+        escaped: false,
+        stack: Vec::new(),
+        commas: Vec::new(),
+    };
+
+    for (index, ch) in text.char_indices() {
+        if result.in_string {
+            if result.escaped {
+                result.escaped = false;
+            } else if ch == '\\' {
+                result.escaped = true;
+            } else if ch == '"' {
+                result.in_string = false;
+            }
+            continue;
+        }
+
+        match ch {
+            '"' => result.in_string = true,
+            '{' | '[' => result.stack.push(ch),
+            '}' | ']' => {
+                result.stack.pop();
+                if result.stack.is_empty() {
+                    result.closed_at = Some(index + 1);
+                    break;
+                }
+            }
+            ',' => result.commas.push((index, result.stack.clone())),
+            _ => {}
+        }
+    }
+
+    result
+}
+
+fn closers(stack: &[char]) -> String {
+    stack
+        .iter()
+        .rev()
+        .map(|open| if *open == '{' { '}' } else { ']' })
+        .collect()
+}
+
+/// Drops commas directly followed (ignoring whitespace) by `}` or `]`.
+fn remove_trailing_commas(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut output = String::with_capacity(text.len());
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (index, ch) in chars.iter().enumerate() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if *ch == '\\' {
+                escaped = true;
+            } else if *ch == '"' {
+                in_string = false;
+            }
+            output.push(*ch);
+            continue;
+        }
+
+        if *ch == ',' {
+            let next = chars[index + 1..].iter().find(|c| !c.is_whitespace());
+            if matches!(next, Some('}') | Some(']')) {
+                continue;
+            }
+        }
+        if *ch == '"' {
+            in_string = true;
+        }
+        output.push(*ch);
+    }
+
+    output
+}
+
+fn parse(candidate: &str) -> Option<JsonValue> {
+    serde_json::from_str(candidate).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn strips_surrounding_prose_and_trailing_commas() {
+        let raw = "Here is the plan:\n{\"items\": [{\"title\": \"a, b\"},], \"telemetry\": null,}\nThanks!";
+
+        assert_eq!(
+            repair_json(raw),
+            Some(json!({"items": [{"title": "a, b"}], "telemetry": null}))
+        );
+    }
+
+    #[test]
+    fn closes_truncated_strings_and_containers() {
+        assert_eq!(
+            repair_json(r#"{"summary": "Prepare the quarterly rev"#),
+            Some(json!({"summary": "Prepare the quarterly rev"}))
+        );
+        assert_eq!(
+            repair_json(r#"{"items": [{"title": "Deep work"}, {"title": "Emai"#),
+            Some(json!({"items": [{"title": "Deep work"}, {"title": "Emai"}]}))
+        );
+    }
+
+    #[test]
+    fn falls_back_to_last_complete_element() {
+        assert_eq!(
+            repair_json(r#"{"missingFields": ["dueAt"], "reasoning": {"confidence": 0."#),
+            Some(json!({"missingFields": ["dueAt"]}))
+        );
+        assert_eq!(repair_json("not-json"), None);
+    }
+}
@@ -1,5 +1,17 @@
+pub mod appearance;
+pub mod bundle;
 pub mod cot;
 pub mod crypto;
+pub mod files;
+pub mod ical;
+pub mod idle;
+pub mod json_repair;
 pub mod logger;
+pub mod paths;
+pub mod power;
 pub mod redact;
+pub mod runtime_profile;
 pub mod semantic;
+#[cfg(desktop)]
+pub mod single_instance;
+pub mod tokens;
@@ -0,0 +1,134 @@
+//! File names that are valid on Windows, macOS and Linux alike. Exports are
+//! copied between machines, so every generated name follows the strictest
+//! rules regardless of the platform it was written on.
+
+use std::path::{Component, Path, PathBuf};
+
+/// Most file systems cap a single name at 255 bytes (ext4, APFS) or 255
+/// UTF-16 units (NTFS). UTF-8 is never shorter than UTF-16, so a byte limit
+/// covers both.
+pub const MAX_FILE_NAME_BYTES: usize = 255;
+/// Extensions longer than this are treated as part of the name when
+/// truncating.
+const MAX_EXTENSION_BYTES: usize = 16;
+const FALLBACK_FILE_NAME: &str = "untitled";
+const RESERVED_WINDOWS_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Makes `name` safe to use as a single path component: characters that are
+/// invalid on any supported platform become `_`, trailing dots and spaces are
+/// dropped, reserved Windows device names get a `_` prefix and long names are
+/// shortened on a character boundary, keeping the extension.
+pub fn sanitize_file_name(name: &str) -> String {
+    let replaced: String = name
+        .chars()
+        .map(|c| if is_invalid_char(c) { '_' } else { c })
+        .collect();
+    let mut sanitized = trim_name(&replaced).to_string();
+    if sanitized.is_empty() || sanitized == "." || sanitized == ".." {
+        return FALLBACK_FILE_NAME.to_string();
+    }
+
+    let stem = sanitized.split('.').next().unwrap_or_default();
+    if RESERVED_WINDOWS_NAMES
+        .iter()
+        .any(|reserved| stem.trim_end().eq_ignore_ascii_case(reserved))
+    {
+        sanitized.insert(0, '_');
+    }
+
+    truncate_file_name(&sanitized)
+}
+
+/// Sanitizes every component of a relative path. Root, prefix and parent
+/// components are dropped so the result always stays below its base.
+pub fn sanitize_relative_path(path: &Path) -> PathBuf {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(sanitize_file_name(&part.to_string_lossy())),
+            _ => None,
+        })
+        .collect()
+}
+
+fn is_invalid_char(c: char) -> bool {
+    c.is_control() || matches!(c, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*')
+}
+
+/// Windows silently strips trailing dots and spaces, so a name ending in one
+/// would not round-trip.
+fn trim_name(name: &str) -> &str {
+    name.trim()
+        .trim_end_matches(|c: char| c == '.' || c.is_whitespace())
+}
+
+fn truncate_file_name(name: &str) -> String {
+    if name.len() <= MAX_FILE_NAME_BYTES {
+        return name.to_string();
+    }
+
+    let extension = name
+        .rfind('.')
+        .filter(|&index| index > 0 && name.len() - index <= MAX_EXTENSION_BYTES)
+        .map_or("", |index| &name[index..]);
+    let stem = &name[..name.len() - extension.len()];
+    let mut end = MAX_FILE_NAME_BYTES - extension.len();
+    while !stem.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    format!("{}{extension}", trim_name(&stem[..end]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_rules_cover_characters_reserved_names_and_trailing_dots() {
+        assert_eq!(
+            sanitize_file_name("report<1>:\"draft\"|?*.md"),
+            "report_1___draft____.md"
+        );
+        assert_eq!(sanitize_file_name("CON"), "_CON");
+        assert_eq!(sanitize_file_name("nul.txt"), "_nul.txt");
+        assert_eq!(sanitize_file_name("lpt9.tar.gz"), "_lpt9.tar.gz");
+        assert_eq!(sanitize_file_name("console.md"), "console.md");
+        assert_eq!(sanitize_file_name("notes. . "), "notes");
+    }
+
+    #[test]
+    fn unix_separators_and_control_characters_are_replaced() {
+        assert_eq!(sanitize_file_name("a/b\\c"), "a_b_c");
+        assert_eq!(sanitize_file_name("line\nbreak\0.md"), "line_break_.md");
+        assert_eq!(sanitize_file_name(".."), FALLBACK_FILE_NAME);
+        assert_eq!(sanitize_file_name("   "), FALLBACK_FILE_NAME);
+        assert_eq!(sanitize_file_name(".hidden"), ".hidden");
+    }
+
+    #[test]
+    fn long_names_keep_the_extension_and_whole_characters() {
+        let name = format!("{}.json", "周报".repeat(100));
+        let sanitized = sanitize_file_name(&name);
+        assert!(sanitized.len() <= MAX_FILE_NAME_BYTES);
+        assert!(sanitized.ends_with(".json"));
+        assert!(sanitized.starts_with("周报周报"));
+
+        let unchanged = "cognical-report-20260101T000000Z.md";
+        assert_eq!(sanitize_file_name(unchanged), unchanged);
+    }
+
+    #[test]
+    fn relative_paths_are_sanitized_per_component() {
+        assert_eq!(
+            sanitize_relative_path(Path::new("2026/AUX/../a:b.md")),
+            PathBuf::from("2026").join("_AUX").join("a_b.md")
+        );
+        assert_eq!(
+            sanitize_relative_path(Path::new("/etc/passwd")),
+            PathBuf::from("etc").join("passwd")
+        );
+    }
+}
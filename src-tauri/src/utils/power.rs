@@ -0,0 +1,140 @@
+//! Where the machine draws power from. Only Linux exposes this without extra
+//! dependencies (`/sys/class/power_supply`); other platforms report
+//! `Unknown`, which never throttles anything.
+
+use std::fs;
+use std::path::PathBuf;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum PowerState {
+    Ac,
+    #[serde(rename_all = "camelCase")]
+    Battery {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        percent: Option<u8>,
+        power_saver: bool,
+    },
+    Unknown,
+}
+
+pub trait PowerSource: Send + Sync {
+    fn state(&self) -> PowerState;
+}
+
+/// Reads the power state from the operating system.
+pub struct SystemPowerSource {
+    sysfs_root: PathBuf,
+}
+
+impl Default for SystemPowerSource {
+    fn default() -> Self {
+        Self {
+            sysfs_root: PathBuf::from("/sys"),
+        }
+    }
+}
+
+impl SystemPowerSource {
+    /// Reads from a copy of the sysfs tree, for tests.
+    pub fn with_sysfs_root(root: impl Into<PathBuf>) -> Self {
+        Self {
+            sysfs_root: root.into(),
+        }
+    }
+
+    fn read(&self, relative: &str) -> Option<String> {
+        fs::read_to_string(self.sysfs_root.join(relative))
+            .ok()
+            .map(|value| value.trim().to_string())
+    }
+
+    fn linux_state(&self) -> PowerState {
+        let Ok(entries) = fs::read_dir(self.sysfs_root.join("class/power_supply")) else {
+            return PowerState::Unknown;
+        };
+
+        let mut on_mains = false;
+        let mut batteries = Vec::new();
+        for entry in entries.flatten() {
+            let read = |file: &str| {
+                fs::read_to_string(entry.path().join(file))
+                    .ok()
+                    .map(|value| value.trim().to_string())
+            };
+            match read("type").as_deref() {
+                Some("Mains") | Some("USB") => on_mains |= read("online").as_deref() == Some("1"),
+                Some("Battery") => batteries.push(
+                    read("capacity")
+                        .and_then(|value| value.parse::<u8>().ok())
+                        .map(|percent| percent.min(100)),
+                ),
+                _ => {}
+            }
+        }
+
+        // Desktops have no battery at all
+        if on_mains || batteries.is_empty() {
+            return PowerState::Ac;
+        }
+        PowerState::Battery {
+            percent: batteries.into_iter().flatten().min(),
+            power_saver: self.read("firmware/acpi/platform_profile").as_deref()
+                == Some("low-power"),
+        }
+    }
+}
+
+impl PowerSource for SystemPowerSource {
+    fn state(&self) -> PowerState {
+        if cfg!(target_os = "linux") {
+            self.linux_state()
+        } else {
+            PowerState::Unknown
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &std::path::Path, relative: &str, value: &str) {
+        let path = root.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, format!("{value}\n")).unwrap();
+    }
+
+    #[test]
+    fn battery_state_is_read_from_sysfs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "class/power_supply/AC/type", "Mains");
+        write(root, "class/power_supply/AC/online", "0");
+        write(root, "class/power_supply/BAT0/type", "Battery");
+        write(root, "class/power_supply/BAT0/capacity", "42");
+        write(root, "firmware/acpi/platform_profile", "low-power");
+
+        let source = SystemPowerSource::with_sysfs_root(root);
+        assert_eq!(
+            source.linux_state(),
+            PowerState::Battery {
+                percent: Some(42),
+                power_saver: true
+            }
+        );
+
+        write(root, "class/power_supply/AC/online", "1");
+        assert_eq!(source.linux_state(), PowerState::Ac);
+
+        let empty = tempfile::tempdir().unwrap();
+        write(empty.path(), "class/power_supply/.keep", "");
+        assert_eq!(
+            SystemPowerSource::with_sysfs_root(empty.path()).linux_state(),
+            PowerState::Ac
+        );
+    }
+}
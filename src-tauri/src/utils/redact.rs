@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use crate::error::AppResult;
 use serde_json::Value as JsonValue;
 
@@ -53,6 +55,72 @@ fn redact_string_value(value: &JsonValue) -> JsonValue {
     }
 }
 
+/// Stable placeholders (`T1`, `T2`, `E1`, ...) that stand in for local ids
+/// and titles in prompts sent to external providers. The mapping never
+/// leaves the process; responses are translated back with it.
+#[derive(Debug, Default, Clone)]
+pub struct PlaceholderMap {
+    counters: HashMap<String, usize>,
+    by_id: HashMap<String, String>,
+    ids: HashMap<String, String>,
+    labels: HashMap<String, String>,
+}
+
+impl PlaceholderMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Placeholder for `id`, allocating the next one for `prefix` on first
+    /// use. `label` is what the placeholder expands to in resolved text.
+    pub fn alias(&mut self, prefix: &str, id: &str, label: Option<&str>) -> String {
+        if let Some(existing) = self.by_id.get(id) {
+            return existing.clone();
+        }
+
+        let counter = self.counters.entry(prefix.to_string()).or_insert(0);
+        *counter += 1;
+        let placeholder = format!("{prefix}{counter}");
+
+        self.by_id.insert(id.to_string(), placeholder.clone());
+        self.ids.insert(placeholder.clone(), id.to_string());
+        if let Some(label) = label.map(str::trim).filter(|label| !label.is_empty()) {
+            self.labels.insert(placeholder.clone(), label.to_string());
+        }
+        placeholder
+    }
+
+    pub fn resolve_id(&self, placeholder: &str) -> Option<&str> {
+        self.ids.get(placeholder.trim()).map(String::as_str)
+    }
+
+    /// Replaces whole-word placeholders in free text with their labels, so
+    /// `T12` is never mistaken for `T1`.
+    pub fn resolve_text(&self, text: &str) -> String {
+        let mut resolved = String::with_capacity(text.len());
+        let mut token = String::new();
+
+        for ch in text.chars() {
+            if ch.is_ascii_alphanumeric() {
+                token.push(ch);
+            } else {
+                self.flush_token(&mut token, &mut resolved);
+                resolved.push(ch);
+            }
+        }
+        self.flush_token(&mut token, &mut resolved);
+        resolved
+    }
+
+    fn flush_token(&self, token: &mut String, out: &mut String) {
+        match self.labels.get(token.as_str()) {
+            Some(label) => out.push_str(label),
+            None => out.push_str(token),
+        }
+        token.clear();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -118,4 +186,26 @@ mod tests {
         // Should remain unchanged
         assert_eq!(redacted, data);
     }
+
+    #[test]
+    fn placeholders_are_stable_and_resolve_locally() {
+        let mut map = PlaceholderMap::new();
+        let first = map.alias("T", "task-a", Some("写季度报告"));
+        let second = map.alias("T", "task-b", None);
+        let event = map.alias("E", "evt-1", None);
+        for n in 3..=12 {
+            map.alias("T", &format!("task-{n}"), Some(&format!("任务{n}")));
+        }
+
+        assert_eq!(first, "T1");
+        assert_eq!(second, "T2");
+        assert_eq!(event, "E1");
+        assert_eq!(map.alias("T", "task-a", Some("ignored")), "T1");
+        assert_eq!(map.resolve_id("T2"), Some("task-b"));
+        assert_eq!(map.resolve_id("T99"), None);
+        assert_eq!(
+            map.resolve_text("Start T1 before T12; T2 after E1."),
+            "Start 写季度报告 before 任务12; T2 after E1."
+        );
+    }
 }
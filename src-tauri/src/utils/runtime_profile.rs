@@ -0,0 +1,53 @@
+//! How much background work the app may do on this platform. Desktop keeps
+//! its own scheduler threads; mobile runs the same work on demand, when the
+//! app comes to the foreground, because the OS suspends idle threads.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Memory documents kept in the search index on mobile. Older ones stay on
+/// disk and are re-indexed if the limit is lifted.
+const MOBILE_MEMORY_INDEX_LIMIT: usize = 500;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RuntimeProfile {
+    Desktop,
+    Mobile,
+}
+
+impl RuntimeProfile {
+    /// Profile of the platform the app was built for.
+    pub fn detect() -> Self {
+        if cfg!(mobile) {
+            RuntimeProfile::Mobile
+        } else {
+            RuntimeProfile::Desktop
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuntimeProfile::Desktop => "desktop",
+            RuntimeProfile::Mobile => "mobile",
+        }
+    }
+
+    /// Whether scheduled jobs get their own long-running threads.
+    pub fn runs_background_threads(&self) -> bool {
+        matches!(self, RuntimeProfile::Desktop)
+    }
+
+    /// Whether commits may skip the per-transaction fsync and be flushed in
+    /// batches at WAL checkpoints.
+    pub fn batches_writes(&self) -> bool {
+        matches!(self, RuntimeProfile::Mobile)
+    }
+
+    pub fn memory_index_limit(&self) -> Option<usize> {
+        match self {
+            RuntimeProfile::Desktop => None,
+            RuntimeProfile::Mobile => Some(MOBILE_MEMORY_INDEX_LIMIT),
+        }
+    }
+}
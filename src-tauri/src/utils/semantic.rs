@@ -21,3 +21,60 @@ pub fn semantic_hash(input: &str, metadata: Option<&JsonValue>) -> String {
     let digest = hasher.finalize();
     STANDARD_NO_PAD.encode(digest)
 }
+
+/// Normalize free text for fuzzy comparison: lower-case, alphanumerics only.
+pub fn normalize_for_match(input: &str) -> String {
+    input
+        .chars()
+        .filter(|ch| ch.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Dice coefficient over character bigrams of the normalized inputs.
+///
+/// Works for both CJK and Latin titles without a tokenizer; returns a value
+/// in `0.0..=1.0` where `1.0` means the normalized strings are identical.
+pub fn text_similarity(a: &str, b: &str) -> f64 {
+    let left: Vec<char> = normalize_for_match(a).chars().collect();
+    let right: Vec<char> = normalize_for_match(b).chars().collect();
+
+    if left.is_empty() || right.is_empty() {
+        return 0.0;
+    }
+    if left == right {
+        return 1.0;
+    }
+    if left.len() < 2 || right.len() < 2 {
+        return 0.0;
+    }
+
+    let mut right_bigrams: Vec<(char, char)> = right.windows(2).map(|w| (w[0], w[1])).collect();
+    let total = (left.len() - 1 + right_bigrams.len()) as f64;
+    let mut shared = 0usize;
+    for window in left.windows(2) {
+        let bigram = (window[0], window[1]);
+        if let Some(pos) = right_bigrams
+            .iter()
+            .position(|candidate| *candidate == bigram)
+        {
+            right_bigrams.swap_remove(pos);
+            shared += 1;
+        }
+    }
+
+    (2 * shared) as f64 / total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn similarity_ignores_case_and_punctuation() {
+        assert_eq!(text_similarity("Send Q3 report!", "send q3 report"), 1.0);
+        assert!(text_similarity("准备季度汇报材料", "准备季度汇报") > 0.8);
+        assert!(text_similarity("Book flights", "Review budget") < 0.3);
+        assert_eq!(text_similarity("", "anything"), 0.0);
+    }
+}
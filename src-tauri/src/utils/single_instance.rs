@@ -0,0 +1,108 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+pub const SECOND_INSTANCE_EVENT: &str = "app://second-instance";
+
+const MAIN_WINDOW_LABEL: &str = "main";
+const CAPTURE_FLAG: &str = "--capture";
+
+/// Launch arguments of a second instance, forwarded to the running one.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchArgs {
+    /// Arguments after the executable path.
+    pub args: Vec<String>,
+    pub cwd: String,
+    /// Arguments that look like URLs, e.g. `cognical://task/123`.
+    pub deep_links: Vec<String>,
+    /// Text passed with `--capture <text>` or `--capture=<text>`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capture_text: Option<String>,
+}
+
+pub fn parse_launch_args(argv: &[String], cwd: &str) -> LaunchArgs {
+    let args: Vec<String> = argv.iter().skip(1).cloned().collect();
+    let mut deep_links = Vec::new();
+    let mut capture_text = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == CAPTURE_FLAG {
+            capture_text = iter.next().cloned();
+        } else if let Some(text) = arg.strip_prefix("--capture=") {
+            capture_text = Some(text.to_string());
+        } else if arg.contains("://") {
+            deep_links.push(arg.clone());
+        }
+    }
+
+    LaunchArgs {
+        args,
+        cwd: cwd.to_string(),
+        deep_links,
+        capture_text: capture_text
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty()),
+    }
+}
+
+/// Called in the running instance when another launch is attempted: brings
+/// the main window to front and hands the arguments to the frontend.
+pub fn handle_second_instance(app: &AppHandle, argv: Vec<String>, cwd: String) {
+    let launch = parse_launch_args(&argv, &cwd);
+    info!(
+        target: "app::single_instance",
+        args = launch.args.len(),
+        deep_links = launch.deep_links.len(),
+        capture = launch.capture_text.is_some(),
+        "second instance launch forwarded"
+    );
+
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW_LABEL) {
+        let focused = window
+            .unminimize()
+            .and_then(|_| window.show())
+            .and_then(|_| window.set_focus());
+        if let Err(error) = focused {
+            warn!(target: "app::single_instance", %error, "failed to bring main window to front");
+        }
+    }
+
+    if let Err(error) = app.emit(SECOND_INSTANCE_EVENT, &launch) {
+        warn!(target: "app::single_instance", %error, "failed to emit second instance event");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argv(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_deep_links_and_capture_text() {
+        let launch = parse_launch_args(
+            &argv(&[
+                "cognical",
+                "cognical://task/42",
+                "--capture",
+                " 明天交报告 ",
+            ]),
+            "/home/user",
+        );
+        assert_eq!(launch.args.len(), 3);
+        assert_eq!(launch.deep_links, vec!["cognical://task/42".to_string()]);
+        assert_eq!(launch.capture_text.as_deref(), Some("明天交报告"));
+
+        let inline = parse_launch_args(&argv(&["cognical", "--capture=买牛奶"]), "/");
+        assert_eq!(inline.capture_text.as_deref(), Some("买牛奶"));
+        assert!(inline.deep_links.is_empty());
+
+        let bare = parse_launch_args(&argv(&["cognical", "--capture"]), "/");
+        assert_eq!(bare.capture_text, None);
+    }
+}
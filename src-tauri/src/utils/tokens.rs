@@ -0,0 +1,191 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Rough token estimate used to keep prompts inside provider limits.
+///
+/// CJK characters are counted as one token each while other text is
+/// approximated at four characters per token, which errs on the side of
+/// overestimating for DeepSeek's tokenizer.
+pub fn estimate_tokens(text: &str) -> usize {
+    let mut cjk = 0usize;
+    let mut other = 0usize;
+    for ch in text.chars() {
+        if is_cjk(ch) {
+            cjk += 1;
+        } else {
+            other += 1;
+        }
+    }
+    cjk + other.div_ceil(4)
+}
+
+const FLOAT_TOLERANCE: f64 = 1e-9;
+
+/// Per-model approximation of how many tokens a character costs.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenizerProfile {
+    pub tokens_per_cjk_char: f64,
+    pub tokens_per_other_char: f64,
+}
+
+impl TokenizerProfile {
+    /// Same ratios as [`estimate_tokens`]; used when the model is unknown.
+    pub const CONSERVATIVE: Self = Self {
+        tokens_per_cjk_char: 1.0,
+        tokens_per_other_char: 0.25,
+    };
+
+    /// DeepSeek's published ratios: ~0.6 tokens per CJK character and ~0.3
+    /// per English character.
+    pub const DEEPSEEK: Self = Self {
+        tokens_per_cjk_char: 0.6,
+        tokens_per_other_char: 0.3,
+    };
+
+    pub fn estimate(&self, text: &str) -> usize {
+        let (cjk, other) = text.chars().fold((0usize, 0usize), |(cjk, other), ch| {
+            if is_cjk(ch) {
+                (cjk + 1, other)
+            } else {
+                (cjk, other + 1)
+            }
+        });
+        let tokens =
+            cjk as f64 * self.tokens_per_cjk_char + other as f64 * self.tokens_per_other_char;
+        // Tolerate float noise so e.g. 10 × 0.3 counts as 3, not 4.
+        (tokens - FLOAT_TOLERANCE).ceil().max(0.0) as usize
+    }
+
+    /// Longest prefix of `text` that fits in `max_tokens`, cut at a character boundary.
+    pub fn truncate<'a>(&self, text: &'a str, max_tokens: usize) -> &'a str {
+        let budget = max_tokens as f64;
+        let mut used = 0.0;
+        for (index, ch) in text.char_indices() {
+            used += if is_cjk(ch) {
+                self.tokens_per_cjk_char
+            } else {
+                self.tokens_per_other_char
+            };
+            if used > budget + FLOAT_TOLERANCE {
+                return &text[..index];
+            }
+        }
+        text
+    }
+}
+
+impl Default for TokenizerProfile {
+    fn default() -> Self {
+        Self::CONSERVATIVE
+    }
+}
+
+/// Split text into chunks that each stay within `max_tokens`.
+///
+/// Paragraph boundaries are preferred, then line boundaries; a single line
+/// that is still too large is cut by characters as a last resort.
+pub fn chunk_by_tokens(text: &str, max_tokens: usize) -> Vec<String> {
+    let max_tokens = max_tokens.max(1);
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    for line in text.lines() {
+        let line = line.trim_end();
+        let pieces = if estimate_tokens(line) > max_tokens {
+            split_line(line, max_tokens)
+        } else {
+            vec![line.to_string()]
+        };
+
+        for piece in pieces {
+            let candidate_tokens = estimate_tokens(&current) + estimate_tokens(&piece) + 1;
+            if !current.trim().is_empty() && candidate_tokens > max_tokens {
+                chunks.push(current.trim().to_string());
+                current.clear();
+            }
+            if !current.is_empty() {
+                current.push('\n');
+            }
+            current.push_str(&piece);
+        }
+    }
+
+    if !current.trim().is_empty() {
+        chunks.push(current.trim().to_string());
+    }
+
+    chunks
+}
+
+fn split_line(line: &str, max_tokens: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    for ch in line.chars() {
+        current.push(ch);
+        if estimate_tokens(&current) >= max_tokens {
+            pieces.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        pieces.push(current);
+    }
+    pieces
+}
+
+fn is_cjk(ch: char) -> bool {
+    matches!(
+        ch as u32,
+        0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xAC00..=0xD7AF | 0xF900..=0xFAFF | 0xFF00..=0xFFEF
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimates_cjk_and_latin_text() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+        assert_eq!(estimate_tokens("会议纪要"), 4);
+    }
+
+    #[test]
+    fn tokenizer_profiles_estimate_and_truncate() {
+        let deepseek = TokenizerProfile::DEEPSEEK;
+        assert_eq!(deepseek.estimate("abcdefghij"), 3);
+        assert_eq!(deepseek.estimate("会议纪要"), 3);
+        assert_eq!(
+            TokenizerProfile::CONSERVATIVE.estimate("会议纪要 abcd"),
+            estimate_tokens("会议纪要 abcd")
+        );
+
+        assert_eq!(deepseek.truncate("会议纪要", 2), "会议纪");
+        assert_eq!(deepseek.truncate("short", 10), "short");
+    }
+
+    #[test]
+    fn chunks_respect_token_budget() {
+        let notes = (0..40)
+            .map(|idx| format!("Item {idx}: follow up with the vendor about pricing"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let chunks = chunk_by_tokens(&notes, 60);
+
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(estimate_tokens(chunk) <= 60);
+        }
+        assert!(chunks[0].starts_with("Item 0:"));
+    }
+
+    #[test]
+    fn oversized_line_is_split_by_characters() {
+        let line = "议".repeat(250);
+        let chunks = chunk_by_tokens(&line, 100);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks.concat(), line);
+    }
+}
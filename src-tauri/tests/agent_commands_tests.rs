@@ -27,6 +27,8 @@ async fn ai_agent_chat_validates_empty_message() {
         AgentChatRequest {
             conversation_id: "test-conv-1".to_string(),
             message: "    ".to_string(),
+            attachments: Vec::new(),
+            incognito: false,
         },
     )
     .await;
@@ -45,6 +47,8 @@ async fn ai_agent_chat_validates_empty_conversation_id() {
         AgentChatRequest {
             conversation_id: "   ".to_string(),
             message: "Hello".to_string(),
+            attachments: Vec::new(),
+            incognito: false,
         },
     )
     .await;
@@ -63,6 +67,8 @@ async fn ai_agent_chat_requires_api_key() {
         AgentChatRequest {
             conversation_id: "test-conv-1".to_string(),
             message: "Create a task for me".to_string(),
+            attachments: Vec::new(),
+            incognito: false,
         },
     )
     .await;
@@ -129,6 +135,7 @@ async fn memory_export_validates_empty_path() {
         &state,
         MemoryExportRequest {
             path: "   ".to_string(),
+            bundle: None,
         },
     )
     .await;
@@ -147,6 +154,7 @@ async fn memory_export_handles_unavailable_memory() {
         &state,
         MemoryExportRequest {
             path: export_path.to_string_lossy().to_string(),
+            bundle: None,
         },
     )
     .await;
@@ -220,6 +228,8 @@ async fn agent_chat_response_includes_metadata() {
         AgentChatRequest {
             conversation_id: "test-conv-1".to_string(),
             message: "Hello, how are you?".to_string(),
+            attachments: Vec::new(),
+            incognito: false,
         },
     )
     .await;
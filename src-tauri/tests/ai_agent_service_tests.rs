@@ -1,10 +1,18 @@
 use cognical_app_lib::db::DbPool;
+use cognical_app_lib::models::task::TaskCreateInput;
 use cognical_app_lib::services::ai_agent_service::{
-    AgentContext, AgentMetadata, AgentResponse, AiAgentService,
+    AgentContext, AgentMetadata, AgentResponse, AiAgentService, ChatAttachment, ChatAttachmentKind,
 };
-use cognical_app_lib::services::ai_service::AiService;
+use cognical_app_lib::services::ai_service::{testing::service_with_base_url, AiService};
+use cognical_app_lib::services::cache_service::CacheService;
+use cognical_app_lib::services::goal_service::GoalService;
+use cognical_app_lib::services::memory_service::MemoryService;
+use cognical_app_lib::services::planning_service::PlanningService;
+use cognical_app_lib::services::settings_service::{SettingsService, SettingsUpdateInput};
+use cognical_app_lib::services::task_service::TaskService;
 
 use cognical_app_lib::services::tool_registry::{ToolCall, ToolRegistry};
+use httpmock::prelude::*;
 use serde_json::json;
 use std::sync::Arc;
 use tempfile::TempDir;
@@ -92,6 +100,8 @@ async fn test_agent_response_serialization() {
             errors: None,
             memory_available: Some(true),
             performance: None,
+            cached: None,
+            unverified_actions: Vec::new(),
         },
     };
 
@@ -195,3 +205,204 @@ async fn test_agent_metadata_with_tools() {
 // 3. Memory service running
 // These tests focus on the structure and basic functionality
 // without requiring external dependencies
+
+#[tokio::test]
+async fn test_attachments_fill_missing_tool_ids() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let db_pool = DbPool::new(temp_dir.path().join("test.db")).expect("Failed to create db pool");
+    let ai_service = Arc::new(AiService::new(db_pool).expect("Failed to create AI service"));
+
+    let mut registry = ToolRegistry::new();
+    registry
+        .register_tool(
+            "link_task".to_string(),
+            "Links a task to a goal".to_string(),
+            json!({
+                "type": "object",
+                "properties": {
+                    "task_id": { "type": "string" },
+                    "goal_id": { "type": "string" }
+                }
+            }),
+            Arc::new(|_args| Box::pin(async move { Ok(json!({})) })),
+        )
+        .expect("Failed to register tool");
+    let agent_service = AiAgentService::new(ai_service, Arc::new(registry));
+
+    let call = |arguments| ToolCall {
+        id: "call_1".to_string(),
+        name: "link_task".to_string(),
+        arguments,
+    };
+    let task = |id: &str| ChatAttachment {
+        kind: ChatAttachmentKind::Task,
+        id: id.to_string(),
+    };
+    let goal = ChatAttachment {
+        kind: ChatAttachmentKind::Goal,
+        id: "goal-1".to_string(),
+    };
+
+    // A single attachment of a kind fills the matching argument
+    let filled =
+        agent_service.fill_attachment_ids(vec![call(json!({}))], &[task("task-1"), goal.clone()]);
+    assert_eq!(filled[0].arguments["task_id"], "task-1");
+    assert_eq!(filled[0].arguments["goal_id"], "goal-1");
+
+    // Arguments chosen by the model are kept
+    let kept = agent_service
+        .fill_attachment_ids(vec![call(json!({"task_id": "task-9"}))], &[task("task-1")]);
+    assert_eq!(kept[0].arguments["task_id"], "task-9");
+
+    // Two attached tasks are ambiguous, so nothing is guessed
+    let ambiguous = agent_service.fill_attachment_ids(
+        vec![call(json!({}))],
+        &[task("task-1"), task("task-2"), goal],
+    );
+    assert!(ambiguous[0].arguments.get("task_id").is_none());
+    assert_eq!(ambiguous[0].arguments["goal_id"], "goal-1");
+}
+
+#[tokio::test]
+async fn test_slash_done_completes_task_without_model() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let db_pool = DbPool::new(temp_dir.path().join("test.db")).expect("Failed to create db pool");
+    let ai_service =
+        Arc::new(AiService::new(db_pool.clone()).expect("Failed to create AI service"));
+    let task_service = Arc::new(TaskService::new(db_pool));
+    let agent_service = AiAgentService::new(ai_service, Arc::new(ToolRegistry::new()))
+        .with_task_history(Arc::clone(&task_service));
+
+    let task = task_service
+        .create_task(TaskCreateInput {
+            title: "Write weekly report".to_string(),
+            ..TaskCreateInput::default()
+        })
+        .expect("create task");
+
+    let response = agent_service
+        .chat("slash-conv", "/done weekly report")
+        .await
+        .expect("slash command should not need an API key");
+    assert_eq!(response.metadata.tools_executed, vec!["/done".to_string()]);
+    assert!(response.tool_calls.is_empty());
+
+    let updated = task_service.get_task(&task.id).expect("get task");
+    assert_eq!(updated.status, "done");
+    assert!(updated.completed_at.is_some());
+
+    let help = agent_service
+        .chat("slash-conv", "/snooze")
+        .await
+        .expect("unknown commands reply with help");
+    assert!(help.message.contains("/today"));
+}
+
+#[tokio::test]
+async fn test_slash_plan_never_calls_the_provider() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let db_pool = DbPool::new(temp_dir.path().join("test.db")).expect("Failed to create db pool");
+    SettingsService::new(db_pool.clone())
+        .expect("settings service")
+        .update(SettingsUpdateInput {
+            plan_critique_enabled: Some(true),
+            ..Default::default()
+        })
+        .expect("enable critique");
+
+    let server = MockServer::start_async().await;
+    let provider = server
+        .mock_async(|when, then| {
+            when.method(POST).path("/v1/chat/completions");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "choices": [{"message": {"content": "{\"issues\": []}"}}],
+                    "usage": {}
+                }));
+        })
+        .await;
+
+    let ai_service =
+        Arc::new(service_with_base_url(db_pool.clone(), &server.base_url()).expect("AI service"));
+    let task_service = Arc::new(TaskService::new(db_pool.clone()));
+    let planning_service = Arc::new(PlanningService::new(
+        db_pool.clone(),
+        Arc::clone(&task_service),
+        Arc::clone(&ai_service),
+    ));
+    let agent_service = AiAgentService::new(ai_service, Arc::new(ToolRegistry::new()))
+        .with_task_history(Arc::clone(&task_service))
+        .with_attachment_sources(Arc::new(GoalService::new(db_pool)), planning_service);
+
+    let tomorrow_noon = chrono::Local::now()
+        .date_naive()
+        .succ_opt()
+        .and_then(|day| day.and_hms_opt(12, 0, 0))
+        .and_then(|at| at.and_local_timezone(chrono::Local).earliest())
+        .expect("tomorrow noon");
+    task_service
+        .create_task(TaskCreateInput {
+            title: "Prepare slides".to_string(),
+            estimated_minutes: Some(60),
+            due_at: Some(tomorrow_noon.to_rfc3339()),
+            ..TaskCreateInput::default()
+        })
+        .expect("create task");
+
+    let response = agent_service
+        .chat("slash-plan", "/plan tomorrow")
+        .await
+        .expect("slash plan should not need the model");
+    assert!(
+        response.message.contains("生成计划"),
+        "{}",
+        response.message
+    );
+    assert_eq!(provider.hits_async().await, 0);
+}
+
+#[tokio::test]
+async fn test_repeated_question_hits_the_response_cache() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let db_pool = DbPool::new(temp_dir.path().join("test.db")).expect("Failed to create db pool");
+
+    let server = MockServer::start_async().await;
+    let provider = server
+        .mock_async(|when, then| {
+            when.method(POST).path("/v1/chat/completions");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "choices": [{"message": {"content": "Start with the report."}}],
+                    "usage": {}
+                }));
+        })
+        .await;
+
+    let ai_service =
+        Arc::new(service_with_base_url(db_pool.clone(), &server.base_url()).expect("AI service"));
+    let memory_service =
+        Arc::new(MemoryService::new(temp_dir.path().join("kb")).expect("memory service"));
+    let agent_service =
+        AiAgentService::new_with_memory(ai_service, Arc::new(ToolRegistry::new()), memory_service)
+            .with_response_cache(
+                CacheService::new(db_pool, chrono::Duration::hours(1)).expect("cache service"),
+            );
+
+    let first = agent_service
+        .chat("cache-hit", "What should I work on first?")
+        .await
+        .expect("first reply");
+    assert!(first.memory_stored);
+    assert_eq!(first.metadata.cached, Some(false));
+
+    // The stored exchange is now part of the conversation's history
+    let second = agent_service
+        .chat("cache-hit", "What should I work on first?")
+        .await
+        .expect("second reply");
+    assert_eq!(second.metadata.cached, Some(true));
+    assert_eq!(second.message, first.message);
+    assert_eq!(provider.hits_async().await, 1);
+}
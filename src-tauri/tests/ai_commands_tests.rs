@@ -1,5 +1,6 @@
 use cognical_app_lib::commands::ai_commands::testing::{
-    ai_generate_recommendations, ai_plan_schedule, ai_status, tasks_parse_ai,
+    ai_extract_action_items, ai_generate_recommendations, ai_plan_schedule, ai_status,
+    tasks_decompose_ai, tasks_parse_ai,
 };
 use cognical_app_lib::commands::AppState;
 use cognical_app_lib::db::DbPool;
@@ -103,3 +104,43 @@ async fn ai_status_impl_reports_missing_key() {
     assert!(status.latency_ms.is_none());
     assert_eq!(status.message.as_deref(), Some("DeepSeek API Key 未配置"));
 }
+
+#[tokio::test]
+async fn ai_extract_action_items_validates_empty_text() {
+    let (_dir, state) = init_state();
+
+    let error = ai_extract_action_items(&state, "  \n ".to_string())
+        .await
+        .expect_err("expected validation error");
+    assert_eq!(error.code, "VALIDATION_ERROR");
+    assert_eq!(error.message, "会议记录内容不能为空");
+}
+
+#[tokio::test]
+async fn ai_extract_action_items_requires_api_key() {
+    let (_dir, state) = init_state();
+
+    let error = ai_extract_action_items(
+        &state,
+        "周会纪要：张三下周五前提交预算草案；李四负责联系供应商。".to_string(),
+    )
+    .await
+    .expect_err("expected missing api key error");
+    assert_eq!(error.code, "MISSING_API_KEY");
+}
+
+#[tokio::test]
+async fn tasks_decompose_ai_validates_task_id() {
+    let (_dir, state) = init_state();
+
+    let error = tasks_decompose_ai(&state, "   ".to_string(), None)
+        .await
+        .expect_err("expected validation error");
+    assert_eq!(error.code, "VALIDATION_ERROR");
+    assert_eq!(error.message, "任务 ID 不能为空");
+
+    let error = tasks_decompose_ai(&state, "missing-task".to_string(), Some(4))
+        .await
+        .expect_err("expected missing task error");
+    assert_eq!(error.code, "NOT_FOUND");
+}
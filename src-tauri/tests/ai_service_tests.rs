@@ -1,8 +1,12 @@
 use cognical_app_lib::error::AiErrorCode;
 use cognical_app_lib::models::ai::{TaskParseContext, TaskParseRequest};
-use cognical_app_lib::services::ai_service::testing::{map_http_error, parse_task_via_http};
+use cognical_app_lib::models::ai_types::{ExtractedActionItemDto, ProposedSubtaskDto};
+use cognical_app_lib::services::ai_service::testing::{
+    map_http_error, merge_action_items, normalize_subtasks, parse_task_via_http,
+};
 use cognical_app_lib::services::prompt_templates::{
-    build_recommendations_payload, build_schedule_payload, build_task_parse_payload,
+    build_action_items_payload, build_decompose_payload, build_recommendations_payload,
+    build_schedule_payload, build_task_parse_payload, resolve_parse_language, ParseLanguage,
 };
 use httpmock::prelude::*;
 use reqwest::StatusCode;
@@ -20,6 +24,8 @@ fn build_task_parse_payload_includes_context_and_expectations() {
             metadata: Some(json!({"draftTitle": "Planning"})),
             existing_task_id: None,
             user_preferences: None,
+            project_id: None,
+            projects: None,
         }),
     };
 
@@ -57,7 +63,43 @@ fn build_task_parse_payload_includes_context_and_expectations() {
             .get("languages")
             .and_then(|v| v.as_array())
             .map(|list| list.len()),
-        Some(2)
+        Some(3)
+    );
+    assert_eq!(obj.get("language").and_then(|v| v.as_str()), Some("zh-CN"));
+}
+
+#[test]
+fn task_parse_language_follows_locale_or_input_script() {
+    let request = |input: &str, locale: Option<&str>| TaskParseRequest {
+        input: input.to_string(),
+        context: locale.map(|locale| TaskParseContext {
+            locale: Some(locale.to_string()),
+            ..Default::default()
+        }),
+    };
+
+    let japanese = build_task_parse_payload(&request("来週の火曜に歯医者", None));
+    assert_eq!(japanese["language"], "ja");
+    assert_eq!(
+        japanese["examples"][0]["input"],
+        "来週の火曜15時までに見積書を送る、2時間くらい"
+    );
+
+    assert_eq!(
+        resolve_parse_language(&request("下周二交报告", None)),
+        ParseLanguage::ZhCn
+    );
+    assert_eq!(
+        resolve_parse_language(&request("Ship it on Friday", None)),
+        ParseLanguage::En
+    );
+    assert_eq!(
+        resolve_parse_language(&request("下周二交报告", Some("ja-JP"))),
+        ParseLanguage::Ja
+    );
+    assert_eq!(
+        resolve_parse_language(&request("Ship it", Some("fr-FR"))),
+        ParseLanguage::En
     );
 }
 
@@ -133,6 +175,127 @@ fn build_schedule_payload_has_expected_shape() {
     );
 }
 
+#[test]
+fn build_action_items_payload_wraps_chunk_context() {
+    let input = json!({
+        "notes": "Alice to send the deck by Friday",
+        "chunkIndex": 1,
+        "chunkCount": 2
+    });
+
+    let payload = build_action_items_payload(&input);
+    assert_eq!(
+        payload.get("operation").and_then(|value| value.as_str()),
+        Some("extractActionItems")
+    );
+    assert_eq!(payload.get("context"), Some(&input));
+    assert_eq!(
+        payload
+            .pointer("/expectations/includeOwners")
+            .and_then(|value| value.as_bool()),
+        Some(true)
+    );
+}
+
+#[test]
+fn merge_action_items_collapses_chunk_duplicates() {
+    let first = vec![ExtractedActionItemDto {
+        title: "Send the Q3 deck".to_string(),
+        owner: Some("Alice".to_string()),
+        ..Default::default()
+    }];
+    let second = vec![
+        ExtractedActionItemDto {
+            title: "send the Q3 deck!".to_string(),
+            due_hint: Some("by Friday".to_string()),
+            ..Default::default()
+        },
+        ExtractedActionItemDto {
+            title: "Book venue for offsite".to_string(),
+            ..Default::default()
+        },
+        ExtractedActionItemDto {
+            title: "   ".to_string(),
+            ..Default::default()
+        },
+    ];
+
+    let merged = merge_action_items(vec![first, second]);
+    assert_eq!(merged.len(), 2);
+    assert_eq!(merged[0].owner.as_deref(), Some("Alice"));
+    assert_eq!(merged[0].due_hint.as_deref(), Some("by Friday"));
+    assert_eq!(merged[1].title, "Book venue for offsite");
+}
+
+#[test]
+fn build_decompose_payload_requests_estimates_and_dependencies() {
+    let input = json!({ "task": { "title": "Write annual report" }, "maxSubtasks": 4 });
+
+    let payload = build_decompose_payload(&input);
+    assert_eq!(
+        payload.get("operation").and_then(|value| value.as_str()),
+        Some("decomposeTask")
+    );
+    assert_eq!(payload.get("context"), Some(&input));
+    assert_eq!(
+        payload
+            .pointer("/expectations/includeDependencies")
+            .and_then(|value| value.as_bool()),
+        Some(true)
+    );
+}
+
+#[test]
+fn normalize_subtasks_reorders_clamps_and_drops_bad_dependencies() {
+    let subtasks = vec![
+        ProposedSubtaskDto {
+            title: " Draft outline ".to_string(),
+            estimated_minutes: Some(2),
+            order: 2,
+            depends_on: vec![5],
+            ..Default::default()
+        },
+        ProposedSubtaskDto {
+            title: "Collect figures".to_string(),
+            estimated_minutes: Some(900),
+            order: 5,
+            ..Default::default()
+        },
+        ProposedSubtaskDto {
+            title: "Review with team".to_string(),
+            order: 7,
+            depends_on: vec![2, 2, 7, 9],
+            ..Default::default()
+        },
+        ProposedSubtaskDto {
+            title: "  ".to_string(),
+            order: 1,
+            ..Default::default()
+        },
+        ProposedSubtaskDto {
+            title: "Polish".to_string(),
+            order: 0,
+            ..Default::default()
+        },
+    ];
+
+    let normalized = normalize_subtasks(subtasks, 3);
+    assert_eq!(normalized.len(), 3);
+
+    assert_eq!(normalized[0].title, "Draft outline");
+    assert_eq!(normalized[0].order, 1);
+    assert_eq!(normalized[0].estimated_minutes, Some(5));
+    // Depending on a later step would make the order impossible.
+    assert!(normalized[0].depends_on.is_empty());
+
+    assert_eq!(normalized[1].title, "Collect figures");
+    assert_eq!(normalized[1].estimated_minutes, Some(480));
+
+    assert_eq!(normalized[2].title, "Review with team");
+    assert_eq!(normalized[2].order, 3);
+    assert_eq!(normalized[2].depends_on, vec![1]);
+}
+
 #[test]
 fn deepseek_http_error_mapping_exposes_retry_semantics() {
     let (error, retryable) = map_http_error(StatusCode::UNAUTHORIZED);
@@ -151,6 +314,7 @@ fn deepseek_http_error_mapping_exposes_retry_semantics() {
     assert!(retryable);
     assert_eq!(error.to_string(), "DeepSeek 请求过于频繁，请稍后重试");
     assert_eq!(error.ai_code(), Some(AiErrorCode::RateLimited));
+    assert_eq!(error.ai_correlation_id(), Some("test-correlation-id"));
 
     let (error, retryable) = map_http_error(StatusCode::from_u16(503).unwrap());
     assert!(retryable);
@@ -305,3 +469,228 @@ async fn deepseek_parse_task_maps_timeouts_to_http_timeout() {
     assert_eq!(error.ai_code(), Some(AiErrorCode::HttpTimeout));
     assert!(error.ai_correlation_id().is_some());
 }
+
+fn is_correction_request(req: &httpmock::prelude::HttpMockRequest) -> bool {
+    req.body
+        .as_ref()
+        .map(|body| {
+            String::from_utf8_lossy(body).contains("did not match the required JSON schema")
+        })
+        .unwrap_or(false)
+}
+
+fn chat_completion(content: serde_json::Value, prompt_tokens: u64) -> serde_json::Value {
+    json!({
+        "choices": [{
+            "message": {"content": serde_json::to_string(&content).expect("json")}
+        }],
+        "usage": {
+            "prompt_tokens": prompt_tokens,
+            "completion_tokens": 1,
+            "total_tokens": prompt_tokens + 1
+        }
+    })
+}
+
+#[tokio::test]
+async fn deepseek_parse_task_retries_once_when_schema_validation_fails() {
+    let server = MockServer::start_async().await;
+
+    let initial = server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/v1/chat/completions")
+                .matches(|req| !is_correction_request(req));
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(chat_completion(
+                    json!({
+                        "payload": {"title": "整理周报"},
+                        "missingFields": "dueAt",
+                        "reasoning": {}
+                    }),
+                    10,
+                ));
+        })
+        .await;
+    let correction = server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/v1/chat/completions")
+                .matches(is_correction_request)
+                .body_contains("/missingFields");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(chat_completion(
+                    json!({
+                        "payload": {"title": "整理周报"},
+                        "missingFields": ["dueAt"],
+                        "reasoning": {"summary": "fixed"}
+                    }),
+                    20,
+                ));
+        })
+        .await;
+
+    let request = TaskParseRequest {
+        input: "整理本周周报".into(),
+        context: None,
+    };
+
+    let dto = parse_task_via_http(&server.base_url(), StdDuration::from_secs(2), request)
+        .await
+        .expect("corrected response is accepted");
+
+    initial.assert_async().await;
+    correction.assert_async().await;
+    assert_eq!(dto.missing_fields, vec!["dueAt".to_string()]);
+    let tokens = dto
+        .reasoning
+        .provider
+        .and_then(|provider| provider.tokens_used)
+        .expect("token usage present");
+    assert_eq!(tokens.get("prompt"), Some(&30));
+}
+
+#[tokio::test]
+async fn deepseek_parse_task_fails_when_correction_is_still_invalid() {
+    let server = MockServer::start_async().await;
+
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(POST).path("/v1/chat/completions");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(chat_completion(json!({"payload": "整理周报"}), 5));
+        })
+        .await;
+
+    let request = TaskParseRequest {
+        input: "整理本周周报".into(),
+        context: None,
+    };
+
+    let error = parse_task_via_http(&server.base_url(), StdDuration::from_secs(2), request)
+        .await
+        .expect_err("schema violations are reported");
+
+    mock.assert_hits_async(2).await;
+    assert_eq!(error.ai_code(), Some(AiErrorCode::InvalidResponse));
+    assert_eq!(error.to_string(), "DeepSeek 响应不符合预期结构");
+}
+
+#[tokio::test]
+async fn deepseek_parse_task_repairs_trailing_commas_and_truncation_locally() {
+    let server = MockServer::start_async().await;
+
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(POST).path("/v1/chat/completions");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "choices": [{
+                        "message": {"content": "```json\n{\"payload\": {\"title\": \"整理周报\",}, \"missingFields\": [], \"reasoning\": {\"summary\": \"整理本周"}
+                    }],
+                    "usage": {}
+                }));
+        })
+        .await;
+
+    let request = TaskParseRequest {
+        input: "整理本周周报".into(),
+        context: None,
+    };
+
+    let dto = parse_task_via_http(&server.base_url(), StdDuration::from_secs(2), request)
+        .await
+        .expect("repaired locally");
+
+    mock.assert_hits_async(1).await;
+    assert_eq!(dto.payload.title.as_deref(), Some("整理周报"));
+    assert_eq!(dto.reasoning.summary.as_deref(), Some("整理本周"));
+}
+
+fn is_json_completion_request(req: &httpmock::prelude::HttpMockRequest) -> bool {
+    req.body
+        .as_ref()
+        .map(|body| String::from_utf8_lossy(body).contains("was truncated or was not valid JSON"))
+        .unwrap_or(false)
+}
+
+#[tokio::test]
+async fn deepseek_parse_task_re_asks_when_json_cannot_be_repaired() {
+    let server = MockServer::start_async().await;
+
+    let initial = server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/v1/chat/completions")
+                .matches(|req| !is_json_completion_request(req));
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "choices": [{"message": {"content": "Sure! The task is 整理周报"}}],
+                    "usage": {}
+                }));
+        })
+        .await;
+    let completion = server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/v1/chat/completions")
+                .matches(is_json_completion_request)
+                .body_contains("Sure! The task is");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(chat_completion(
+                    json!({
+                        "payload": {"title": "整理周报"},
+                        "missingFields": [],
+                        "reasoning": {}
+                    }),
+                    8,
+                ));
+        })
+        .await;
+
+    let request = TaskParseRequest {
+        input: "整理本周周报".into(),
+        context: None,
+    };
+
+    let dto = parse_task_via_http(&server.base_url(), StdDuration::from_secs(2), request)
+        .await
+        .expect("recovered by re-ask");
+
+    initial.assert_async().await;
+    completion.assert_async().await;
+    assert_eq!(dto.payload.title.as_deref(), Some("整理周报"));
+}
+
+#[tokio::test]
+async fn deepseek_rate_limit_keeps_retry_after() {
+    let server = MockServer::start_async().await;
+
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(POST).path("/v1/chat/completions");
+            then.status(429).header("retry-after", "120");
+        })
+        .await;
+
+    let request = TaskParseRequest {
+        input: "Book flights".to_string(),
+        context: None,
+    };
+
+    let error = parse_task_via_http(&server.base_url(), StdDuration::from_secs(2), request)
+        .await
+        .expect_err("rate limited request should fail");
+
+    assert_eq!(error.ai_code(), Some(AiErrorCode::RateLimited));
+    assert_eq!(error.ai_details(), Some(&json!({ "retryAfterSecs": 120 })));
+    assert!(error.is_retryable());
+    // A wait this long is left to the caller instead of retried in place
+    assert_eq!(mock.hits_async().await, 1);
+}
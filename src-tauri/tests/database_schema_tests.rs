@@ -317,4 +317,35 @@ fn test_sample_data_performance() {
         
         Ok(())
     }).expect("performance test");
-}
\ No newline at end of file
+}
+#[test]
+fn test_read_connection_rejects_writes() {
+    let dir = tempdir().expect("temp dir");
+    let pool = DbPool::new(dir.path().join("test.sqlite")).expect("db pool");
+
+    pool.with_connection(|conn| {
+        conn.execute(
+            "INSERT INTO app_settings (key, value) VALUES ('probe', '1')",
+            [],
+        )?;
+        Ok(())
+    })
+    .expect("write through regular connection");
+
+    let value: String = pool
+        .with_read_connection(|conn| {
+            Ok(conn.query_row(
+                "SELECT value FROM app_settings WHERE key = 'probe'",
+                [],
+                |row| row.get(0),
+            )?)
+        })
+        .expect("read through read connection");
+    assert_eq!(value, "1");
+
+    let write = pool.with_read_connection(|conn| {
+        conn.execute("DELETE FROM app_settings", [])?;
+        Ok(())
+    });
+    assert!(write.is_err());
+}
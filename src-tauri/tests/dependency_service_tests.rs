@@ -1,10 +1,10 @@
 use cognical_app_lib::db::DbPool;
 use cognical_app_lib::models::dependency::{DependencyCreateInput, DependencyType};
 use cognical_app_lib::services::dependency_service::DependencyService;
-use tempfile::tempdir;
+use tempfile::{tempdir, TempDir};
 use chrono::Utc;
 
-async fn setup_test_db() -> (DbPool, DependencyService) {
+async fn setup_test_db() -> (TempDir, DbPool, DependencyService) {
     let dir = tempdir().expect("temp dir");
     let db_path = dir.path().join("test.sqlite");
     let pool = DbPool::new(db_path).expect("db pool");
@@ -23,12 +23,12 @@ async fn setup_test_db() -> (DbPool, DependencyService) {
         Ok(())
     }).expect("test data setup");
     
-    (pool, service)
+    (dir, pool, service)
 }
 
 #[tokio::test]
 async fn test_add_dependency_success() {
-    let (_pool, service) = setup_test_db().await;
+    let (_dir, _pool, service) = setup_test_db().await;
     
     let input = DependencyCreateInput {
         predecessor_id: "task1".to_string(),
@@ -49,7 +49,7 @@ async fn test_add_dependency_success() {
 
 #[tokio::test]
 async fn test_add_dependency_prevents_self_dependency() {
-    let (_pool, service) = setup_test_db().await;
+    let (_dir, _pool, service) = setup_test_db().await;
     
     let input = DependencyCreateInput {
         predecessor_id: "task1".to_string(),
@@ -63,7 +63,7 @@ async fn test_add_dependency_prevents_self_dependency() {
 
 #[tokio::test]
 async fn test_add_dependency_prevents_duplicate() {
-    let (_pool, service) = setup_test_db().await;
+    let (_dir, _pool, service) = setup_test_db().await;
     
     let input = DependencyCreateInput {
         predecessor_id: "task1".to_string(),
@@ -82,7 +82,7 @@ async fn test_add_dependency_prevents_duplicate() {
 
 #[tokio::test]
 async fn test_circular_dependency_detection() {
-    let (_pool, service) = setup_test_db().await;
+    let (_dir, _pool, service) = setup_test_db().await;
     
     // Create chain: task1 -> task2 -> task3
     let input1 = DependencyCreateInput {
@@ -112,7 +112,7 @@ async fn test_circular_dependency_detection() {
 
 #[tokio::test]
 async fn test_validate_dependency() {
-    let (_pool, service) = setup_test_db().await;
+    let (_dir, _pool, service) = setup_test_db().await;
     
     // Valid dependency
     let validation = service.validate_dependency("task1", "task2").await.unwrap();
@@ -132,7 +132,7 @@ async fn test_validate_dependency() {
 
 #[tokio::test]
 async fn test_remove_dependency() {
-    let (_pool, service) = setup_test_db().await;
+    let (_dir, _pool, service) = setup_test_db().await;
     
     let input = DependencyCreateInput {
         predecessor_id: "task1".to_string(),
@@ -153,7 +153,7 @@ async fn test_remove_dependency() {
 
 #[tokio::test]
 async fn test_get_task_dependencies() {
-    let (_pool, service) = setup_test_db().await;
+    let (_dir, _pool, service) = setup_test_db().await;
     
     // Create dependencies: task1 -> task2, task1 -> task3
     let input1 = DependencyCreateInput {
@@ -183,7 +183,7 @@ async fn test_get_task_dependencies() {
 
 #[tokio::test]
 async fn test_get_ready_tasks() {
-    let (pool, service) = setup_test_db().await;
+    let (_dir, pool, service) = setup_test_db().await;
     
     // Mark task1 as completed
     pool.with_connection(|conn| {
@@ -220,7 +220,7 @@ async fn test_get_ready_tasks() {
 
 #[tokio::test]
 async fn test_topological_sorting() {
-    let (_pool, service) = setup_test_db().await;
+    let (_dir, _pool, service) = setup_test_db().await;
     
     // Create dependency chain: task1 -> task2 -> task3
     let input1 = DependencyCreateInput {
@@ -257,7 +257,7 @@ async fn test_topological_sorting() {
 
 #[tokio::test]
 async fn test_critical_path_calculation() {
-    let (_pool, service) = setup_test_db().await;
+    let (_dir, _pool, service) = setup_test_db().await;
     
     // Create dependency chain: task1 -> task2 -> task3
     let input1 = DependencyCreateInput {
@@ -284,7 +284,7 @@ async fn test_critical_path_calculation() {
 
 #[tokio::test]
 async fn test_dependency_graph_caching() {
-    let (_pool, service) = setup_test_db().await;
+    let (_dir, _pool, service) = setup_test_db().await;
     
     // First call should build the graph
     let start = std::time::Instant::now();
@@ -307,7 +307,7 @@ async fn test_dependency_graph_caching() {
 
 #[tokio::test]
 async fn test_complex_dependency_graph() {
-    let (_pool, service) = setup_test_db().await;
+    let (_dir, _pool, service) = setup_test_db().await;
     
     // Create complex dependency structure:
     // task1 -> task3
@@ -352,7 +352,7 @@ async fn test_complex_dependency_graph() {
 
 #[tokio::test]
 async fn test_dependency_types() {
-    let (_pool, service) = setup_test_db().await;
+    let (_dir, _pool, service) = setup_test_db().await;
     
     let dependency_types = vec![
         DependencyType::FinishToStart,
@@ -388,7 +388,7 @@ async fn test_dependency_types() {
 
 #[tokio::test]
 async fn test_cache_invalidation() {
-    let (_pool, service) = setup_test_db().await;
+    let (_dir, _pool, service) = setup_test_db().await;
     
     // Build initial graph (should be cached)
     let graph1 = service.get_dependency_graph(None).await.unwrap();
@@ -413,4 +413,48 @@ async fn test_cache_invalidation() {
     // Get graph again (should rebuild and show no dependencies)
     let graph3 = service.get_dependency_graph(None).await.unwrap();
     assert_eq!(graph3.edges.len(), 0);
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_dependency_chain_creation() {
+    let (_dir, _pool, service) = setup_test_db().await;
+
+    // task2 -> task3 already exists and is kept
+    let input = DependencyCreateInput {
+        predecessor_id: "task2".to_string(),
+        successor_id: "task3".to_string(),
+        dependency_type: Some(DependencyType::FinishToStart),
+    };
+    service.add_dependency(input).await.unwrap();
+
+    let chain: Vec<String> = ["task1", "task2", "task3", "task4"]
+        .iter()
+        .map(|id| id.to_string())
+        .collect();
+    let created = service.add_dependency_chain(&chain, None).await.unwrap();
+    assert_eq!(created.len(), 2);
+    assert_eq!(created[0].predecessor_id, "task1");
+    assert_eq!(created[1].successor_id, "task4");
+
+    let graph = service.get_dependency_graph(None).await.unwrap();
+    assert_eq!(graph.edges.len(), 3);
+
+    // Closing the loop back to task1 is rejected and writes nothing
+    let cyclic: Vec<String> = ["task4", "task5", "task1"]
+        .iter()
+        .map(|id| id.to_string())
+        .collect();
+    assert!(service.add_dependency_chain(&cyclic, None).await.is_err());
+    let graph = service.get_dependency_graph(None).await.unwrap();
+    assert_eq!(graph.edges.len(), 3);
+
+    let duplicate: Vec<String> = ["task1", "task5", "task1"]
+        .iter()
+        .map(|id| id.to_string())
+        .collect();
+    assert!(service.add_dependency_chain(&duplicate, None).await.is_err());
+    assert!(service
+        .add_dependency_chain(&["task1".to_string()], None)
+        .await
+        .is_err());
+}
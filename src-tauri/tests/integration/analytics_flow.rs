@@ -7,7 +7,7 @@ use cognical_app_lib::models::analytics::{
     AnalyticsExportFormat, AnalyticsExportParams, AnalyticsGrouping, AnalyticsQueryParams,
     AnalyticsRangeKey,
 };
-use cognical_app_lib::models::task::TaskCreateInput;
+use cognical_app_lib::models::task::{TaskCreateInput, TaskUpdateInput};
 use cognical_app_lib::services::analytics_service::AnalyticsService;
 use cognical_app_lib::services::settings_service::{SettingsService, SettingsUpdateInput};
 use cognical_app_lib::services::task_service::TaskService;
@@ -58,6 +58,11 @@ fn analytics_overview_history_and_settings_flow() {
             task_type: Some("work".into()),
             ai: None,
             external_links: None,
+            project_id: None,
+            color: None,
+            icon: None,
+            delegated_to: None,
+            waiting_on: None,
         })
         .expect("create completed task");
     assert_eq!(completed_task.status, "done");
@@ -81,6 +86,11 @@ fn analytics_overview_history_and_settings_flow() {
             task_type: Some("study".into()),
             ai: None,
             external_links: None,
+            project_id: None,
+            color: None,
+            icon: None,
+            delegated_to: None,
+            waiting_on: None,
         })
         .expect("create pending task");
     assert_eq!(pending_task.status, "todo");
@@ -93,6 +103,8 @@ fn analytics_overview_history_and_settings_flow() {
         from: Some(range_start.to_rfc3339()),
         to: Some(range_end.to_rfc3339()),
         grouping: Some(AnalyticsGrouping::Day),
+        project_id: None,
+        full_recompute: false,
     };
 
     let overview = analytics_service
@@ -116,6 +128,7 @@ fn analytics_overview_history_and_settings_flow() {
             format: AnalyticsExportFormat::Markdown,
             from: params.from.clone(),
             to: params.to.clone(),
+            project_id: None,
         })
         .expect("export report");
     assert_eq!(export.format, AnalyticsExportFormat::Markdown);
@@ -133,6 +146,15 @@ fn analytics_overview_history_and_settings_flow() {
             workday_end_minute: Some(17 * 60),
             theme: Some("dark".into()),
             ai_feedback_opt_out: None,
+            clipboard_watch_enabled: None,
+            ai_privacy_mode: None,
+            ai_debug_log_enabled: None,
+            planning_session_retention_days: None,
+            proactive_suggestions_enabled: None,
+            anomaly_notifications_enabled: None,
+            plan_critique_enabled: None,
+            default_preference_id: None,
+            productivity_score_target: None,
         })
         .expect("update settings");
     assert_eq!(updated_settings.theme, "dark");
@@ -159,3 +181,101 @@ fn analytics_overview_history_and_settings_flow() {
         .all(|c| c == '*'));
     assert_eq!(fetched_masked.len(), "sk-phase3-abcdef123456".len());
 }
+
+#[test]
+fn analytics_daily_aggregates_follow_task_changes() {
+    let dir = tempdir().expect("temp dir");
+    let db_path = dir.path().join("aggregates.sqlite");
+    let pool = DbPool::new(&db_path).expect("db pool");
+    let task_service = Arc::new(TaskService::new(pool.clone()));
+
+    let day = Utc
+        .with_ymd_and_hms(2025, 6, 2, 10, 0, 0)
+        .single()
+        .expect("day");
+    let task = task_service
+        .create_task(TaskCreateInput {
+            title: "Quarterly plan".into(),
+            description: None,
+            status: Some("todo".into()),
+            priority: Some("high".into()),
+            planned_start_at: None,
+            start_at: None,
+            due_at: Some((day + Duration::hours(2)).to_rfc3339()),
+            completed_at: None,
+            estimated_minutes: Some(60),
+            estimated_hours: None,
+            tags: None,
+            owner_id: None,
+            is_recurring: None,
+            recurrence: None,
+            task_type: Some("work".into()),
+            ai: None,
+            external_links: None,
+            project_id: None,
+            color: None,
+            icon: None,
+            delegated_to: None,
+            waiting_on: None,
+        })
+        .expect("create task");
+
+    // Whole UTC days, so the aggregate and exact paths cover the same span.
+    let params = AnalyticsQueryParams {
+        range: AnalyticsRangeKey::SevenDays,
+        from: Some("2025-06-01T00:00:00Z".into()),
+        to: Some("2025-06-03T23:59:59Z".into()),
+        grouping: Some(AnalyticsGrouping::Day),
+        project_id: None,
+        full_recompute: false,
+    };
+
+    let analytics_service =
+        AnalyticsService::new(pool.clone(), Arc::clone(&task_service)).expect("analytics service");
+    let aggregated = analytics_service
+        .fetch_overview(params.clone())
+        .expect("aggregated overview");
+    let recomputed = analytics_service
+        .fetch_overview(AnalyticsQueryParams {
+            full_recompute: true,
+            ..params.clone()
+        })
+        .expect("recomputed overview");
+    assert_eq!(
+        serde_json::to_value(&aggregated.history.points).expect("serialize points"),
+        serde_json::to_value(&recomputed.history.points).expect("serialize points"),
+        "aggregates match a full recompute"
+    );
+    assert_eq!(aggregated.overview.summary.total_completed, 0);
+    assert_eq!(aggregated.overview.summary.overdue_tasks, 1);
+
+    task_service
+        .update_task(
+            &task.id,
+            TaskUpdateInput {
+                status: Some("done".into()),
+                completed_at: Some(Some((day + Duration::hours(1)).to_rfc3339())),
+                ..Default::default()
+            },
+        )
+        .expect("complete task");
+
+    let stale: i64 = pool
+        .with_connection(|conn| {
+            Ok(conn.query_row(
+                "SELECT stale FROM analytics_daily_snapshots WHERE snapshot_date = '2025-06-02'",
+                [],
+                |row| row.get(0),
+            )?)
+        })
+        .expect("read aggregate");
+    assert_eq!(stale, 1, "completion marks the day stale");
+
+    let refreshed =
+        AnalyticsService::new(pool.clone(), Arc::clone(&task_service)).expect("analytics service");
+    let overview = refreshed
+        .fetch_overview(params)
+        .expect("refreshed overview");
+    assert_eq!(overview.overview.summary.total_completed, 1);
+    assert_eq!(overview.overview.summary.overdue_tasks, 0);
+}
@@ -0,0 +1,170 @@
+//! Integration tests for the end-of-day close ritual.
+
+use std::sync::Arc;
+
+use cognical_app_lib::db::DbPool;
+use cognical_app_lib::models::day_log::{DayCloseInput, RolloverAction, RolloverDecision};
+use cognical_app_lib::models::task::TaskCreateInput;
+use cognical_app_lib::services::analytics_service::AnalyticsService;
+use cognical_app_lib::services::day_close_service::DayCloseService;
+use cognical_app_lib::services::productivity_score_service::ProductivityScoreService;
+use cognical_app_lib::services::task_service::TaskService;
+use rusqlite::params;
+use tempfile::{tempdir, TempDir};
+
+fn setup() -> (DbPool, Arc<TaskService>, DayCloseService, TempDir) {
+    let dir = tempdir().expect("temp dir");
+    let pool = DbPool::new(dir.path().join("day_close.sqlite")).expect("db pool");
+
+    let task_service = Arc::new(TaskService::new(pool.clone()));
+    let analytics_service = Arc::new(
+        AnalyticsService::new(pool.clone(), Arc::clone(&task_service)).expect("analytics service"),
+    );
+    let score_service = Arc::new(ProductivityScoreService::new(pool.clone()));
+    let day_close = DayCloseService::new(
+        pool.clone(),
+        Arc::clone(&task_service),
+        analytics_service,
+        score_service,
+    );
+
+    (pool, task_service, day_close, dir)
+}
+
+fn insert_applied_block(pool: &DbPool, task_id: &str, start_at: &str, end_at: &str) {
+    let conn = pool.get_connection().expect("connection");
+    conn.execute(
+        "INSERT OR IGNORE INTO planning_sessions (id, task_ids, generated_at, status) VALUES ('session-1', '[]', ?1, 'applied')",
+        params![start_at],
+    )
+    .expect("insert session");
+    conn.execute(
+        "INSERT OR IGNORE INTO planning_options (id, session_id, rank) VALUES ('option-1', 'session-1', 1)",
+        [],
+    )
+    .expect("insert option");
+    conn.execute(
+        "INSERT INTO planning_time_blocks (id, option_id, task_id, start_at, end_at, applied_at, status) VALUES (?1, 'option-1', ?2, ?3, ?4, ?3, 'planned')",
+        params![format!("block-{task_id}"), task_id, start_at, end_at],
+    )
+    .expect("insert block");
+}
+
+#[test]
+fn close_day_marks_blocks_and_applies_rollover_decisions() {
+    let (pool, task_service, day_close, _dir) = setup();
+
+    let open_task = task_service
+        .create_task(TaskCreateInput {
+            title: "Write quarterly report".into(),
+            status: Some("in_progress".into()),
+            due_at: Some("2025-05-01T17:00:00+00:00".into()),
+            ..Default::default()
+        })
+        .expect("open task");
+    let done_task = task_service
+        .create_task(TaskCreateInput {
+            title: "Reply to vendor".into(),
+            status: Some("done".into()),
+            completed_at: Some("2025-05-01T11:00:00+00:00".into()),
+            ..Default::default()
+        })
+        .expect("done task");
+
+    insert_applied_block(
+        &pool,
+        &open_task.id,
+        "2025-05-01T09:00:00+00:00",
+        "2025-05-01T10:30:00+00:00",
+    );
+    insert_applied_block(
+        &pool,
+        &done_task.id,
+        "2025-05-01T10:30:00+00:00",
+        "2025-05-01T11:00:00+00:00",
+    );
+
+    let first = day_close
+        .close_day(DayCloseInput {
+            date: "2025-05-01".into(),
+            decisions: Vec::new(),
+            reflection: Some("  Deep work slipped after lunch  ".into()),
+        })
+        .expect("first close");
+
+    assert_eq!(first.log.unfinished_blocks, 1);
+    assert_eq!(first.log.completed_tasks, 1);
+    assert_eq!(first.pending_rollovers.len(), 1);
+    assert_eq!(first.pending_rollovers[0].task_id, open_task.id);
+    assert_eq!(first.pending_rollovers[0].planned_minutes, 90);
+    assert!(first.score.is_some());
+    assert_eq!(
+        first.log.productivity_score,
+        first.score.as_ref().map(|score| score.composite_score)
+    );
+
+    let status: String = pool
+        .get_connection()
+        .expect("connection")
+        .query_row(
+            "SELECT status FROM planning_time_blocks WHERE task_id = ?1",
+            params![open_task.id],
+            |row| row.get(0),
+        )
+        .expect("block status");
+    assert_eq!(status, "unfinished");
+
+    let second = day_close
+        .close_day(DayCloseInput {
+            date: "2025-05-01".into(),
+            decisions: vec![RolloverDecision {
+                task_id: open_task.id.clone(),
+                action: RolloverAction::Park,
+                reschedule_to: None,
+            }],
+            reflection: None,
+        })
+        .expect("second close");
+
+    assert!(second.pending_rollovers.is_empty());
+    assert_eq!(second.log.decisions.len(), 1);
+    assert_eq!(
+        second.log.reflection.as_deref(),
+        Some("Deep work slipped after lunch")
+    );
+    assert_eq!(second.log.created_at, first.log.created_at);
+
+    let parked = task_service.get_task(&open_task.id).expect("parked task");
+    assert_eq!(parked.status, "backlog");
+    assert!(parked.tags.contains(&"inbox".to_string()));
+    assert!(parked.planned_start_at.is_none());
+
+    let stored = day_close.get_log("2025-05-01").expect("stored log");
+    assert_eq!(stored.decisions[0].action, RolloverAction::Park);
+    assert_eq!(day_close.list_logs(None).expect("logs").len(), 1);
+}
+
+#[test]
+fn close_day_rejects_invalid_input() {
+    let (_pool, _task_service, day_close, _dir) = setup();
+
+    let bad_date = day_close.close_day(DayCloseInput {
+        date: "05/01/2025".into(),
+        decisions: Vec::new(),
+        reflection: None,
+    });
+    assert!(bad_date.is_err());
+
+    let missing_target = day_close.close_day(DayCloseInput {
+        date: "2025-05-01".into(),
+        decisions: vec![RolloverDecision {
+            task_id: "task-1".into(),
+            action: RolloverAction::Reschedule,
+            reschedule_to: None,
+        }],
+        reflection: None,
+    });
+    assert!(missing_target.is_err());
+
+    assert!(day_close.get_log("2025-05-02").is_err());
+}
@@ -0,0 +1,226 @@
+// Latency budgets for the hot paths on a large, realistic dataset.
+//
+// Budgets are for release builds; debug builds get `DEBUG_BUDGET_FACTOR`
+// times as long. Run with `cargo test --release --test large_dataset` before
+// cutting a release.
+
+use std::sync::Arc;
+use std::time::{Duration as StdDuration, Instant};
+
+use chrono::{Duration, Utc};
+use cognical_app_lib::db::DbPool;
+use cognical_app_lib::models::analytics::{
+    AnalyticsGrouping, AnalyticsQueryParams, AnalyticsRangeKey,
+};
+use cognical_app_lib::services::ai_service::AiService;
+use cognical_app_lib::services::analytics_service::AnalyticsService;
+use cognical_app_lib::services::memory_service::MemoryService;
+use cognical_app_lib::services::planning_service::{GeneratePlanInput, PlanningService};
+use cognical_app_lib::services::task_service::TaskService;
+use rusqlite::params;
+use tempfile::{tempdir, TempDir};
+
+const TASK_COUNT: usize = 10_000;
+const BLOCK_COUNT: usize = 50_000;
+const MEMORY_DOC_COUNT: usize = 5_000;
+/// Blocks are spread over this many days around today.
+const BLOCK_SPAN_DAYS: i64 = 365;
+const PLANNED_TASK_COUNT: usize = 20;
+
+const TASKS_LIST_BUDGET_MS: u64 = 500;
+const ANALYTICS_OVERVIEW_BUDGET_MS: u64 = 1_000;
+const PLANNING_GENERATE_BUDGET_MS: u64 = 1_500;
+const MEMORY_SEARCH_BUDGET_MS: u64 = 300;
+const DEBUG_BUDGET_FACTOR: u32 = 10;
+
+fn budget(release_ms: u64) -> StdDuration {
+    let budget = StdDuration::from_millis(release_ms);
+    if cfg!(debug_assertions) {
+        budget * DEBUG_BUDGET_FACTOR
+    } else {
+        budget
+    }
+}
+
+fn assert_within(label: &str, elapsed: StdDuration, release_ms: u64) {
+    let budget = budget(release_ms);
+    println!("{label}: {elapsed:?} (budget {budget:?})");
+    assert!(
+        elapsed <= budget,
+        "{label} took {elapsed:?}, over the {budget:?} budget"
+    );
+}
+
+/// Seeds `TASK_COUNT` tasks and `BLOCK_COUNT` time blocks with raw SQL in one
+/// transaction. A third of the tasks are done, due dates span two months
+/// around today, and every fifth block is applied.
+fn seed_database() -> (DbPool, TempDir) {
+    let dir = tempdir().expect("temp dir");
+    let pool = DbPool::new(dir.path().join("large.sqlite")).expect("db pool");
+    let now = Utc::now();
+    let created_at = (now - Duration::days(60)).to_rfc3339();
+
+    pool.with_connection(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        {
+            let mut insert_task = tx.prepare(
+                "INSERT INTO tasks (id, title, status, priority, due_at, completed_at,
+                    estimated_minutes, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?8)",
+            )?;
+            for i in 0..TASK_COUNT {
+                let due_at = now + Duration::hours((i % 1_440) as i64 - 720);
+                let done = i % 3 == 0;
+                let completed_at = done.then(|| (due_at - Duration::hours(2)).to_rfc3339());
+                insert_task.execute(params![
+                    format!("task-{i}"),
+                    format!("Large dataset task {i}"),
+                    if done { "done" } else { "todo" },
+                    ["low", "medium", "high"][i % 3],
+                    due_at.to_rfc3339(),
+                    completed_at,
+                    30 + (i % 4) as i64 * 30,
+                    created_at,
+                ])?;
+            }
+
+            tx.execute(
+                "INSERT INTO planning_sessions (id, task_ids, generated_at, status)
+                 VALUES ('seed-session', '[]', ?1, 'applied')",
+                params![created_at],
+            )?;
+            tx.execute(
+                "INSERT INTO planning_options (id, session_id, rank)
+                 VALUES ('seed-option', 'seed-session', 1)",
+                [],
+            )?;
+
+            let mut insert_block = tx.prepare(
+                "INSERT INTO planning_time_blocks (id, option_id, task_id, start_at, end_at,
+                    applied_at, status)
+                 VALUES (?1, 'seed-option', ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            let first_start = now - Duration::days(BLOCK_SPAN_DAYS / 2);
+            let step_minutes = BLOCK_SPAN_DAYS * 24 * 60 / BLOCK_COUNT as i64;
+            for i in 0..BLOCK_COUNT {
+                let start_at = first_start + Duration::minutes(i as i64 * step_minutes);
+                let applied = i % 5 == 0;
+                insert_block.execute(params![
+                    format!("block-{i}"),
+                    format!("task-{}", i % TASK_COUNT),
+                    start_at.to_rfc3339(),
+                    (start_at + Duration::minutes(30)).to_rfc3339(),
+                    applied.then(|| created_at.clone()),
+                    if applied { "applied" } else { "planned" },
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    })
+    .expect("seed large dataset");
+
+    (pool, dir)
+}
+
+#[test]
+fn tasks_list_within_budget() {
+    let (pool, _dir) = seed_database();
+    let task_service = TaskService::new(pool);
+
+    let start = Instant::now();
+    let tasks = task_service.list_tasks().expect("list tasks");
+    let elapsed = start.elapsed();
+
+    assert_eq!(tasks.len(), TASK_COUNT);
+    assert_within("tasks_list", elapsed, TASKS_LIST_BUDGET_MS);
+}
+
+#[test]
+fn analytics_overview_within_budget() {
+    let (pool, _dir) = seed_database();
+    let task_service = Arc::new(TaskService::new(pool.clone()));
+    let analytics_service = AnalyticsService::new(pool, task_service).expect("analytics service");
+    let now = Utc::now();
+    let params = AnalyticsQueryParams {
+        range: AnalyticsRangeKey::ThirtyDays,
+        from: Some((now - Duration::days(30)).to_rfc3339()),
+        to: Some(now.to_rfc3339()),
+        grouping: Some(AnalyticsGrouping::Day),
+        project_id: None,
+        full_recompute: false,
+    };
+
+    let start = Instant::now();
+    let overview = analytics_service
+        .fetch_overview(params)
+        .expect("analytics overview");
+    let elapsed = start.elapsed();
+
+    assert!(overview.overview.summary.total_completed > 0);
+    assert_within(
+        "analytics_overview_fetch",
+        elapsed,
+        ANALYTICS_OVERVIEW_BUDGET_MS,
+    );
+}
+
+#[tokio::test]
+async fn planning_generate_optimizer_within_budget() {
+    let (pool, _dir) = seed_database();
+    let task_service = Arc::new(TaskService::new(pool.clone()));
+    let ai_service = Arc::new(AiService::new(pool.clone()).expect("ai service"));
+    let planning_service = PlanningService::new(pool, task_service, ai_service);
+    let task_ids = (0..PLANNED_TASK_COUNT)
+        .map(|i| format!("task-{}", i * 3 + 1))
+        .collect();
+
+    let start = Instant::now();
+    let session = planning_service
+        .generate_plan(GeneratePlanInput {
+            task_ids,
+            project_id: None,
+            constraints: None,
+            preference_id: None,
+            seed: Some(7),
+            include_later_items: false,
+            privacy_mode: None,
+            optimizer_only: true,
+            offline: false,
+        })
+        .await
+        .expect("generate plan");
+    let elapsed = start.elapsed();
+
+    assert!(!session.options.is_empty());
+    assert_within("planning_generate", elapsed, PLANNING_GENERATE_BUDGET_MS);
+}
+
+#[tokio::test]
+async fn memory_search_within_budget() {
+    let dir = tempdir().expect("temp dir");
+    let memory_service = MemoryService::new(dir.path().join("memory")).expect("memory service");
+    let topics = ["planning", "focus", "health", "reading", "travel"];
+    for i in 0..MEMORY_DOC_COUNT {
+        let topic = topics[i % topics.len()];
+        memory_service
+            .store_conversation(
+                &format!("conv-{}", i % 500),
+                &format!("How should I handle {topic} item {i}?"),
+                &format!("Break {topic} item {i} into smaller steps and schedule them."),
+                vec![topic.to_string()],
+            )
+            .await
+            .expect("store conversation");
+    }
+
+    let start = Instant::now();
+    let context = memory_service
+        .search_memory("planning steps", 10)
+        .await
+        .expect("memory search");
+    let elapsed = start.elapsed();
+
+    assert!(!context.relevant_documents.is_empty());
+    assert_within("memory search", elapsed, MEMORY_SEARCH_BUDGET_MS);
+}
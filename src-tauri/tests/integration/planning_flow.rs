@@ -1,17 +1,30 @@
 use std::sync::Arc;
 
-use chrono::{Duration, FixedOffset, NaiveDate, TimeZone};
+use chrono::{Duration, FixedOffset, NaiveDate, TimeZone, Utc};
 use cognical_app_lib::db::DbPool;
+use cognical_app_lib::models::capacity::{
+    CapacityWizardAnswer, CapacityWizardStep, WeeklyCommitment,
+};
+use cognical_app_lib::models::memory::{MemoryFact, MemoryFactKind};
+use cognical_app_lib::models::project::ProjectCreateInput;
 use cognical_app_lib::models::task::TaskCreateInput;
-use cognical_app_lib::services::ai_service::AiService;
+use cognical_app_lib::services::ai_service::{testing::service_with_base_url, AiService};
+use cognical_app_lib::services::behavior_learning::{BehaviorLearningService, PreferenceSnapshot};
+use cognical_app_lib::services::capacity_wizard::CapacityWizardService;
+use cognical_app_lib::services::memory_service::MemoryService;
 use cognical_app_lib::services::planning_service::{
-    ApplyPlanInput, GeneratePlanInput, PlanningService, ResolveConflictInput, TimeBlockOverride,
+    ApplyPlanInput, GeneratePlanInput, PlanningService, PlanningSessionListFilter,
+    PlanningSessionView, ResolveConflictInput, TimeBlockOverride,
 };
+use cognical_app_lib::services::project_service::ProjectService;
 use cognical_app_lib::services::schedule_optimizer::{
-    ExistingEvent, ScheduleConstraints, TimeWindow,
+    ConflictKind, ConflictSeverity, ExistingEvent, ScheduleConstraints, TimeWindow,
 };
 use cognical_app_lib::services::schedule_utils;
+use cognical_app_lib::services::settings_service::{SettingsService, SettingsUpdateInput};
 use cognical_app_lib::services::task_service::TaskService;
+use httpmock::prelude::*;
+use serde_json::json;
 use tempfile::tempdir;
 
 #[tokio::test]
@@ -60,6 +73,11 @@ async fn planning_generate_apply_resolve_flow() {
             task_type: None,
             ai: None,
             external_links: None,
+            project_id: None,
+            color: None,
+            icon: None,
+            delegated_to: None,
+            waiting_on: None,
         })
         .expect("create task A");
 
@@ -85,6 +103,11 @@ async fn planning_generate_apply_resolve_flow() {
             task_type: None,
             ai: None,
             external_links: None,
+            project_id: None,
+            color: None,
+            icon: None,
+            delegated_to: None,
+            waiting_on: None,
         })
         .expect("create task B");
 
@@ -98,6 +121,7 @@ async fn planning_generate_apply_resolve_flow() {
             start_at: schedule_utils::format_datetime(base_day + Duration::hours(1)),
             end_at: schedule_utils::format_datetime(base_day + Duration::hours(2)),
             event_type: Some("meeting".into()),
+            recurrence_rule: None,
         }],
         max_focus_minutes_per_day: Some(480),
         ..Default::default()
@@ -106,9 +130,14 @@ async fn planning_generate_apply_resolve_flow() {
     let session = planning_service
         .generate_plan(GeneratePlanInput {
             task_ids: vec![task_a.id.clone(), task_b.id.clone()],
+            project_id: None,
             constraints: Some(constraints.clone()),
             preference_id: Some("default".into()),
             seed: Some(11),
+            include_later_items: false,
+            privacy_mode: None,
+            optimizer_only: false,
+            offline: false,
         })
         .await
         .expect("generate plan");
@@ -119,6 +148,31 @@ async fn planning_generate_apply_resolve_flow() {
         .iter()
         .any(|conflict| conflict.conflict_type == "calendar-overlap"));
 
+    let overlapping = session
+        .options
+        .iter()
+        .find(|option| {
+            option
+                .conflicts
+                .iter()
+                .any(|conflict| conflict.conflict_type == "calendar-overlap")
+        })
+        .expect("option with calendar overlap");
+    let hints = &overlapping.display_hints;
+    assert_eq!(hints.blocks.len(), overlapping.blocks.len());
+    assert!(hints.blocks.iter().any(|hint| {
+        hint.severity == Some(ConflictSeverity::High)
+            && hint.kinds.contains(&ConflictKind::CalendarOverlap)
+    }));
+    let day = hints
+        .days
+        .iter()
+        .find(|day| day.date == "2025-05-01")
+        .expect("hint for planned day");
+    assert_eq!(day.severity, Some(ConflictSeverity::High));
+    assert!(day.conflicted_blocks >= 1);
+    assert!(day.block_count >= day.conflicted_blocks);
+
     let option_id = session.options[0].option.id.clone();
     let applied = planning_service
         .apply_option(ApplyPlanInput {
@@ -205,3 +259,668 @@ async fn planning_generate_apply_resolve_flow() {
         "expected planned start to be recorded"
     );
 }
+
+#[tokio::test]
+async fn planning_generate_for_project_uses_unblocked_tasks() {
+    let dir = tempdir().expect("temp dir");
+    let pool = DbPool::new(dir.path().join("planning-project.sqlite")).expect("db pool");
+
+    let task_service = Arc::new(TaskService::new(pool.clone()));
+    let ai_service = Arc::new(AiService::new(pool.clone()).expect("ai service"));
+    let planning_service = PlanningService::new(
+        pool.clone(),
+        Arc::clone(&task_service),
+        Arc::clone(&ai_service),
+    );
+    let project_service = ProjectService::new(pool.clone());
+
+    let project = project_service
+        .create(ProjectCreateInput {
+            name: "Website".into(),
+            ..Default::default()
+        })
+        .expect("create project");
+    let create = |title: &str, status: &str, project_id: Option<String>| {
+        task_service
+            .create_task(TaskCreateInput {
+                title: title.into(),
+                status: Some(status.into()),
+                estimated_minutes: Some(60),
+                project_id,
+                ..Default::default()
+            })
+            .expect("create task")
+    };
+    let ready = create("Draft copy", "todo", Some(project.id.clone()));
+    let blocked = create("Publish", "todo", Some(project.id.clone()));
+    create("Kickoff", "done", Some(project.id.clone()));
+    let outside = create("Unrelated", "todo", None);
+
+    pool.with_connection(|conn| {
+        conn.execute(
+            "INSERT INTO task_dependencies (id, predecessor_id, successor_id, dependency_type, created_at)
+             VALUES ('dep-1', ?1, ?2, 'finish_to_start', '2025-05-01T00:00:00Z')",
+            [&ready.id, &blocked.id],
+        )?;
+        Ok(())
+    })
+    .expect("insert dependency");
+
+    let session = planning_service
+        .generate_plan(GeneratePlanInput {
+            task_ids: Vec::new(),
+            project_id: Some(project.id.clone()),
+            constraints: None,
+            preference_id: None,
+            seed: Some(7),
+            include_later_items: false,
+            privacy_mode: None,
+            optimizer_only: false,
+            offline: false,
+        })
+        .await
+        .expect("generate project plan");
+    assert_eq!(session.session.task_ids, vec![ready.id.clone()]);
+    assert_eq!(
+        session.session.project_id.as_deref(),
+        Some(project.id.as_str())
+    );
+
+    planning_service
+        .generate_plan(GeneratePlanInput {
+            task_ids: vec![outside.id.clone()],
+            project_id: None,
+            constraints: None,
+            preference_id: None,
+            seed: Some(7),
+            include_later_items: false,
+            privacy_mode: None,
+            optimizer_only: false,
+            offline: false,
+        })
+        .await
+        .expect("generate plan without project");
+
+    let project_sessions = planning_service
+        .list_sessions(PlanningSessionListFilter {
+            project_id: Some(project.id.clone()),
+            ..Default::default()
+        })
+        .expect("list project sessions");
+    assert_eq!(project_sessions.total, 1);
+    assert_eq!(project_sessions.items[0].session.id, session.session.id);
+    assert_eq!(
+        planning_service
+            .list_sessions(PlanningSessionListFilter::default())
+            .unwrap()
+            .total,
+        2
+    );
+
+    project_service
+        .archive(&project.id)
+        .expect("archive project");
+    assert!(planning_service
+        .generate_plan(GeneratePlanInput {
+            task_ids: Vec::new(),
+            project_id: Some(project.id.clone()),
+            constraints: None,
+            preference_id: None,
+            seed: None,
+            include_later_items: false,
+            privacy_mode: None,
+            optimizer_only: false,
+            offline: false,
+        })
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn planning_sessions_page_get_and_discard() {
+    let dir = tempdir().expect("temp dir");
+    let pool = DbPool::new(dir.path().join("planning-sessions.sqlite")).expect("db pool");
+
+    let task_service = Arc::new(TaskService::new(pool.clone()));
+    let ai_service = Arc::new(AiService::new(pool.clone()).expect("ai service"));
+    let planning_service = PlanningService::new(
+        pool.clone(),
+        Arc::clone(&task_service),
+        Arc::clone(&ai_service),
+    );
+
+    let task = task_service
+        .create_task(TaskCreateInput {
+            title: "Write report".into(),
+            estimated_minutes: Some(90),
+            ..Default::default()
+        })
+        .expect("create task");
+    let mut session_ids = Vec::new();
+    for seed in 0..3 {
+        let session = planning_service
+            .generate_plan(GeneratePlanInput {
+                task_ids: vec![task.id.clone()],
+                project_id: None,
+                constraints: None,
+                preference_id: None,
+                seed: Some(seed),
+                include_later_items: false,
+                privacy_mode: None,
+                optimizer_only: false,
+                offline: false,
+            })
+            .await
+            .expect("generate plan");
+        session_ids.push(session.session.id);
+    }
+
+    let applied = planning_service
+        .get_session(&session_ids[0])
+        .expect("get session");
+    planning_service
+        .apply_option(ApplyPlanInput {
+            session_id: applied.session.id.clone(),
+            option_id: applied.options[0].option.id.clone(),
+            overrides: Vec::new(),
+        })
+        .expect("apply option");
+
+    let page = planning_service
+        .list_sessions(PlanningSessionListFilter {
+            page: Some(2),
+            page_size: Some(2),
+            ..Default::default()
+        })
+        .expect("list second page");
+    assert_eq!(page.total, 3);
+    assert_eq!(page.items.len(), 1);
+    assert!(!page.items[0].options.is_empty());
+
+    let pending = planning_service
+        .list_sessions(PlanningSessionListFilter {
+            status: Some("pending".into()),
+            ..Default::default()
+        })
+        .expect("list pending");
+    assert_eq!(pending.total, 2);
+
+    planning_service
+        .discard_session(&session_ids[1])
+        .expect("discard pending session");
+    assert!(planning_service.get_session(&session_ids[1]).is_err());
+    assert!(planning_service.discard_session(&session_ids[0]).is_err());
+    assert_eq!(
+        planning_service
+            .list_sessions(PlanningSessionListFilter::default())
+            .unwrap()
+            .total,
+        2
+    );
+}
+
+#[tokio::test]
+async fn stale_pending_sessions_expire_and_lose_drafts() {
+    let dir = tempdir().expect("temp dir");
+    let pool = DbPool::new(dir.path().join("planning-retention.sqlite")).expect("db pool");
+
+    let task_service = Arc::new(TaskService::new(pool.clone()));
+    let ai_service = Arc::new(AiService::new(pool.clone()).expect("ai service"));
+    let planning_service = PlanningService::new(
+        pool.clone(),
+        Arc::clone(&task_service),
+        Arc::clone(&ai_service),
+    );
+
+    let task = task_service
+        .create_task(TaskCreateInput {
+            title: "Quarterly review".into(),
+            estimated_minutes: Some(60),
+            ..Default::default()
+        })
+        .expect("create task");
+    let generate = |seed| GeneratePlanInput {
+        task_ids: vec![task.id.clone()],
+        project_id: None,
+        constraints: None,
+        preference_id: None,
+        seed: Some(seed),
+        include_later_items: false,
+        privacy_mode: None,
+        optimizer_only: false,
+        offline: false,
+    };
+    let stale = planning_service
+        .generate_plan(generate(1))
+        .await
+        .expect("generate stale plan");
+    let applied = planning_service
+        .generate_plan(generate(2))
+        .await
+        .expect("generate applied plan");
+    planning_service
+        .apply_option(ApplyPlanInput {
+            session_id: applied.session.id.clone(),
+            option_id: applied.options[0].option.id.clone(),
+            overrides: Vec::new(),
+        })
+        .expect("apply option");
+
+    let now = Utc::now();
+    assert_eq!(planning_service.expire_stale_sessions(now).unwrap(), 0);
+    assert_eq!(
+        planning_service
+            .expire_stale_sessions(now + Duration::days(15))
+            .unwrap(),
+        1
+    );
+
+    let expired = planning_service.get_session(&stale.session.id).unwrap();
+    assert_eq!(expired.session.status, "expired");
+    assert!(expired.options.is_empty());
+    assert!(planning_service
+        .apply_option(ApplyPlanInput {
+            session_id: stale.session.id.clone(),
+            option_id: stale.options[0].option.id.clone(),
+            overrides: Vec::new(),
+        })
+        .is_err());
+
+    let kept = planning_service.get_session(&applied.session.id).unwrap();
+    assert_eq!(kept.session.status, "applied");
+    assert!(!kept.options.is_empty());
+}
+
+#[tokio::test]
+async fn plan_critique_is_skipped_without_a_provider() {
+    let dir = tempdir().expect("temp dir");
+    let pool = DbPool::new(dir.path().join("planning-critique.sqlite")).expect("db pool");
+    let settings = SettingsService::new(pool.clone()).expect("settings service");
+    let updated = settings
+        .update(SettingsUpdateInput {
+            plan_critique_enabled: Some(true),
+            ..Default::default()
+        })
+        .expect("enable critique");
+    assert_eq!(updated.plan_critique_enabled, Some(true));
+
+    let task_service = Arc::new(TaskService::new(pool.clone()));
+    let ai_service = Arc::new(AiService::new(pool.clone()).expect("ai service"));
+    let planning_service = PlanningService::new(
+        pool.clone(),
+        Arc::clone(&task_service),
+        Arc::clone(&ai_service),
+    );
+    let task = task_service
+        .create_task(TaskCreateInput {
+            title: "Draft proposal".into(),
+            estimated_minutes: Some(90),
+            ..Default::default()
+        })
+        .expect("create task");
+
+    let view = planning_service
+        .generate_plan(GeneratePlanInput {
+            task_ids: vec![task.id.clone()],
+            project_id: None,
+            constraints: None,
+            preference_id: None,
+            seed: Some(7),
+            include_later_items: false,
+            privacy_mode: None,
+            optimizer_only: true,
+            offline: false,
+        })
+        .await
+        .expect("generate plan");
+
+    let top = &view.options[0].option;
+    let notes = top
+        .risk_notes
+        .as_ref()
+        .and_then(|value| value.get("notes"))
+        .and_then(|notes| notes.as_array())
+        .cloned()
+        .unwrap_or_default();
+    assert!(notes
+        .iter()
+        .filter_map(|note| note.as_str())
+        .all(|note| !note.starts_with("AI 审查")));
+}
+
+/// Stub provider answering every chat completion with one critique issue.
+async fn critique_server() -> MockServer {
+    let server = MockServer::start_async().await;
+    let content = json!({
+        "issues": [{"kind": "too_dense", "message": "下午的专注块过于密集"}]
+    })
+    .to_string();
+    server
+        .mock_async(|when, then| {
+            when.method(POST).path("/v1/chat/completions");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "choices": [{"message": {"content": content}}],
+                    "usage": {"prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15}
+                }));
+        })
+        .await;
+    server
+}
+
+fn risk_note_strings(view: &PlanningSessionView) -> Vec<String> {
+    view.options[0]
+        .option
+        .risk_notes
+        .as_ref()
+        .and_then(|value| value.get("notes"))
+        .and_then(|notes| notes.as_array())
+        .map(|notes| {
+            notes
+                .iter()
+                .filter_map(|note| note.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[tokio::test]
+async fn plan_critique_adds_risk_notes_to_optimizer_plans() {
+    let dir = tempdir().expect("temp dir");
+    let pool = DbPool::new(dir.path().join("planning-critique-on.sqlite")).expect("db pool");
+    SettingsService::new(pool.clone())
+        .expect("settings service")
+        .update(SettingsUpdateInput {
+            plan_critique_enabled: Some(true),
+            ..Default::default()
+        })
+        .expect("enable critique");
+    let server = critique_server().await;
+
+    let task_service = Arc::new(TaskService::new(pool.clone()));
+    let ai_service =
+        Arc::new(service_with_base_url(pool.clone(), &server.base_url()).expect("ai service"));
+    let planning_service = PlanningService::new(
+        pool.clone(),
+        Arc::clone(&task_service),
+        Arc::clone(&ai_service),
+    );
+    let task = task_service
+        .create_task(TaskCreateInput {
+            title: "Draft proposal".into(),
+            estimated_minutes: Some(90),
+            ..Default::default()
+        })
+        .expect("create task");
+
+    let generate = |offline| GeneratePlanInput {
+        task_ids: vec![task.id.clone()],
+        project_id: None,
+        constraints: None,
+        preference_id: None,
+        seed: Some(7),
+        include_later_items: false,
+        privacy_mode: None,
+        optimizer_only: true,
+        offline,
+    };
+
+    let reviewed = planning_service
+        .generate_plan(generate(false))
+        .await
+        .expect("generate reviewed plan");
+    assert!(risk_note_strings(&reviewed)
+        .iter()
+        .any(|note| note == "AI 审查：下午的专注块过于密集"));
+
+    let offline = planning_service
+        .generate_plan(generate(true))
+        .await
+        .expect("generate offline plan");
+    assert!(risk_note_strings(&offline)
+        .iter()
+        .all(|note| !note.starts_with("AI 审查")));
+}
+
+#[tokio::test]
+async fn planning_snapshot_includes_memory_facts() {
+    let dir = tempdir().expect("temp dir");
+    let pool = DbPool::new(dir.path().join("planning-facts.sqlite")).expect("db pool");
+    let memory_service =
+        Arc::new(MemoryService::new(dir.path().join("memory")).expect("memory service"));
+    memory_service
+        .set_facts(vec![
+            MemoryFact {
+                kind: MemoryFactKind::Constraint,
+                text: "No meetings before 10am".into(),
+            },
+            MemoryFact {
+                kind: MemoryFactKind::Profile,
+                text: "Second-year biology student".into(),
+            },
+        ])
+        .expect("set facts");
+
+    let task_service = Arc::new(TaskService::new(pool.clone()));
+    let ai_service = Arc::new(AiService::new(pool.clone()).expect("ai service"));
+    let planning_service = PlanningService::new(
+        pool.clone(),
+        Arc::clone(&task_service),
+        Arc::clone(&ai_service),
+    )
+    .with_memory_facts(Arc::clone(&memory_service));
+
+    let task = task_service
+        .create_task(TaskCreateInput {
+            title: "Lab report".into(),
+            estimated_minutes: Some(60),
+            ..Default::default()
+        })
+        .expect("create task");
+    let session = planning_service
+        .generate_plan(GeneratePlanInput {
+            task_ids: vec![task.id.clone()],
+            project_id: None,
+            constraints: None,
+            preference_id: None,
+            seed: Some(3),
+            include_later_items: false,
+            privacy_mode: None,
+            optimizer_only: false,
+            offline: false,
+        })
+        .await
+        .expect("generate plan");
+
+    // Only preference and constraint facts reach the planner
+    let snapshot = session
+        .session
+        .personalization_snapshot
+        .expect("personalization snapshot");
+    assert_eq!(
+        snapshot["userFacts"],
+        serde_json::json!([{ "kind": "constraint", "text": "No meetings before 10am" }])
+    );
+}
+
+#[tokio::test]
+async fn planning_uses_the_default_preference_profile() {
+    let dir = tempdir().expect("temp dir");
+    let pool = DbPool::new(dir.path().join("planning-profiles.sqlite")).expect("db pool");
+    let profile = pool
+        .with_connection(|conn| {
+            let behavior = BehaviorLearningService::new(conn);
+            let profile = behavior.create_profile("Exam week", Some("default"), "default")?;
+            behavior.save_preferences(
+                &profile.id,
+                &PreferenceSnapshot {
+                    buffer_minutes_between_blocks: 30,
+                    ..Default::default()
+                },
+            )?;
+            Ok(profile)
+        })
+        .expect("create profile");
+    assert!(!profile.is_default);
+
+    let settings = SettingsService::new(pool.clone()).expect("settings service");
+    assert!(settings
+        .update(SettingsUpdateInput {
+            default_preference_id: Some("missing".into()),
+            ..Default::default()
+        })
+        .is_err());
+    settings
+        .update(SettingsUpdateInput {
+            default_preference_id: Some(profile.id.clone()),
+            ..Default::default()
+        })
+        .expect("set default profile");
+
+    let profiles = pool
+        .with_connection(|conn| {
+            let behavior = BehaviorLearningService::new(conn);
+            assert!(behavior.delete_profile(&profile.id, &profile.id).is_err());
+            assert!(behavior
+                .create_profile("exam WEEK", None, &profile.id)
+                .is_err());
+            behavior.list_profiles(&profile.id)
+        })
+        .expect("list profiles");
+    let names: Vec<_> = profiles
+        .iter()
+        .map(|profile| (profile.name.as_str(), profile.is_default))
+        .collect();
+    assert_eq!(names, vec![("默认", false), ("Exam week", true)]);
+
+    let task_service = Arc::new(TaskService::new(pool.clone()));
+    let ai_service = Arc::new(AiService::new(pool.clone()).expect("ai service"));
+    let planning_service = PlanningService::new(
+        pool.clone(),
+        Arc::clone(&task_service),
+        Arc::clone(&ai_service),
+    );
+    let task = task_service
+        .create_task(TaskCreateInput {
+            title: "Revise chapter 3".into(),
+            estimated_minutes: Some(60),
+            ..Default::default()
+        })
+        .expect("create task");
+    let input = |preference_id: Option<&str>| GeneratePlanInput {
+        task_ids: vec![task.id.clone()],
+        project_id: None,
+        constraints: None,
+        preference_id: preference_id.map(str::to_string),
+        seed: Some(5),
+        include_later_items: false,
+        privacy_mode: None,
+        optimizer_only: true,
+        offline: false,
+    };
+
+    let session = planning_service
+        .generate_plan(input(None))
+        .await
+        .expect("generate plan");
+    let snapshot = session
+        .session
+        .personalization_snapshot
+        .expect("personalization snapshot");
+    assert_eq!(snapshot["bufferMinutesBetweenBlocks"], 30);
+
+    assert!(planning_service
+        .generate_plan(input(Some("missing")))
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn capacity_wizard_saves_answers_and_plans_the_week() {
+    let dir = tempdir().expect("temp dir");
+    let pool = DbPool::new(dir.path().join("capacity.sqlite")).expect("db pool");
+
+    let task_service = Arc::new(TaskService::new(pool.clone()));
+    let ai_service = Arc::new(AiService::new(pool.clone()).expect("ai service"));
+    let planning_service = Arc::new(PlanningService::new(
+        pool.clone(),
+        Arc::clone(&task_service),
+        Arc::clone(&ai_service),
+    ));
+    let settings = Arc::new(SettingsService::new(pool.clone()).expect("settings service"));
+    let wizard = CapacityWizardService::new(pool.clone(), planning_service, Arc::clone(&settings));
+
+    let task = task_service
+        .create_task(TaskCreateInput {
+            title: "Thesis chapter".into(),
+            estimated_minutes: Some(90),
+            ..Default::default()
+        })
+        .expect("create task");
+
+    let state = wizard.start().expect("start wizard");
+    assert_eq!(state.step, CapacityWizardStep::Commitments);
+    assert!(wizard
+        .answer(
+            &state.id,
+            CapacityWizardAnswer::Priorities {
+                task_ids: vec![task.id.clone()],
+            },
+        )
+        .is_err());
+
+    let standup = WeeklyCommitment {
+        title: "Standup".into(),
+        weekday: 1,
+        start_minute: 9 * 60,
+        end_minute: 9 * 60 + 30,
+    };
+    wizard
+        .answer(
+            &state.id,
+            CapacityWizardAnswer::Commitments {
+                commitments: vec![standup.clone()],
+            },
+        )
+        .expect("answer commitments");
+    wizard
+        .answer(
+            &state.id,
+            CapacityWizardAnswer::FocusHours {
+                weekly_focus_hours: 20,
+                focus_start_minute: Some(8 * 60),
+                focus_end_minute: Some(12 * 60),
+            },
+        )
+        .expect("answer focus hours");
+    let state = wizard
+        .answer(
+            &state.id,
+            CapacityWizardAnswer::Priorities {
+                task_ids: vec![task.id.clone(), task.id.clone()],
+            },
+        )
+        .expect("answer priorities");
+    assert_eq!(state.step, CapacityWizardStep::Review);
+    assert_eq!(state.priority_task_ids, vec![task.id.clone()]);
+
+    let result = wizard.finish(&state.id).await.expect("finish wizard");
+    assert_eq!(result.max_focus_minutes_per_day, 240);
+    assert_eq!(result.plan.session.task_ids, vec![task.id.clone()]);
+    assert!(!result.plan.options.is_empty());
+
+    let profile = settings.get_capacity_profile().expect("capacity profile");
+    assert_eq!(profile.commitments, vec![standup]);
+    assert_eq!(profile.weekly_focus_hours, Some(20));
+    let preferences = pool
+        .with_connection(|conn| {
+            BehaviorLearningService::new(conn).load_preferences(&result.preference_id)
+        })
+        .expect("load preferences");
+    assert_eq!(preferences.focus_start_minute, Some(8 * 60));
+    assert_eq!(preferences.focus_end_minute, Some(12 * 60));
+
+    // A finished wizard is closed
+    assert!(wizard.finish(&state.id).await.is_err());
+}
@@ -9,6 +9,8 @@
 use chrono::Utc;
 use cognical_app_lib::db::repositories::wellness_repository::WellnessRepository;
 use cognical_app_lib::db::DbPool;
+use cognical_app_lib::models::planning::PlanningTimeBlockRecord;
+use cognical_app_lib::models::settings::SleepSchedule;
 use cognical_app_lib::models::wellness::{WellnessEventInsert, WellnessTriggerReason};
 use cognical_app_lib::services::settings_service::SettingsService;
 use cognical_app_lib::services::wellness_service::WellnessService;
@@ -16,15 +18,38 @@ use std::sync::Arc;
 use tempfile::{tempdir, TempDir};
 
 fn setup_test_env() -> (DbPool, Arc<WellnessService>, TempDir) {
+    let (db, _settings_service, wellness_service, temp_dir) = setup_test_env_with_settings();
+    (db, wellness_service, temp_dir)
+}
+
+fn setup_test_env_with_settings() -> (DbPool, Arc<SettingsService>, Arc<WellnessService>, TempDir) {
     let temp_dir = tempdir().expect("Failed to create temp directory");
     let db_path = temp_dir.path().join("test.db");
     let db = DbPool::new(&db_path).expect("Failed to create test database");
 
     let settings_service =
         Arc::new(SettingsService::new(db.clone()).expect("Failed to create SettingsService"));
-    let wellness_service = Arc::new(WellnessService::new(db.clone(), settings_service));
+    let wellness_service = Arc::new(WellnessService::new(db.clone(), settings_service.clone()));
 
-    (db, wellness_service, temp_dir)
+    (db, settings_service, wellness_service, temp_dir)
+}
+
+fn time_block(id: &str, start_at: &str, end_at: &str) -> PlanningTimeBlockRecord {
+    PlanningTimeBlockRecord {
+        id: id.to_string(),
+        option_id: "option-1".to_string(),
+        task_id: "task-1".to_string(),
+        start_at: start_at.to_string(),
+        end_at: end_at.to_string(),
+        flexibility: None,
+        confidence: None,
+        conflict_flags: None,
+        applied_at: None,
+        actual_start_at: None,
+        actual_end_at: None,
+        status: "planned".to_string(),
+        prep_note: None,
+    }
 }
 
 #[test]
@@ -114,3 +139,102 @@ fn test_pending_nudge() {
         result.err()
     );
 }
+
+#[test]
+fn test_wind_down_nudge_for_late_blocks() {
+    let (_db, settings_service, wellness_service, _temp_dir) = setup_test_env_with_settings();
+    let evening = time_block(
+        "block-evening",
+        "2025-05-01T22:30:00+08:00",
+        "2025-05-01T23:00:00+08:00",
+    );
+    let afternoon = time_block(
+        "block-afternoon",
+        "2025-05-01T14:00:00+08:00",
+        "2025-05-01T15:00:00+08:00",
+    );
+
+    // Disabled schedules never nudge
+    let result = wellness_service
+        .nudge_for_wind_down(&[evening.clone()])
+        .expect("wind-down check should not error");
+    assert!(result.is_none());
+
+    settings_service
+        .update_sleep_schedule(SleepSchedule {
+            enabled: true,
+            ..Default::default()
+        })
+        .expect("sleep schedule should save");
+
+    let result = wellness_service
+        .nudge_for_wind_down(&[afternoon.clone()])
+        .expect("wind-down check should not error");
+    assert!(result.is_none(), "daytime blocks should not nudge");
+
+    let nudge = wellness_service
+        .nudge_for_wind_down(&[evening, afternoon])
+        .expect("wind-down check should not error")
+        .expect("late block should nudge");
+    assert_eq!(nudge.trigger_reason, WellnessTriggerReason::WindDown);
+    assert_eq!(nudge.recommended_break_minutes, 60);
+}
+
+#[test]
+fn test_focus_session_defers_non_critical_nudges() {
+    let (db, wellness_service, _temp_dir) = setup_test_env();
+
+    let conn = db.get_connection().expect("connection");
+    WellnessRepository::insert(
+        &conn,
+        &WellnessEventInsert {
+            window_start: Utc::now().to_rfc3339(),
+            trigger_reason: WellnessTriggerReason::FocusStreak,
+            recommended_break_minutes: 10,
+            suggested_micro_task: None,
+        },
+    )
+    .expect("insert focus nudge");
+
+    let session = wellness_service
+        .start_focus_session(Some(50), None)
+        .expect("start focus session");
+    assert_eq!(
+        wellness_service.current_focus_session().unwrap(),
+        Some(session)
+    );
+    assert!(
+        wellness_service.get_pending_nudge().unwrap().is_none(),
+        "focus streak nudge waits for the session to end"
+    );
+    assert!(wellness_service
+        .check_and_generate_nudge()
+        .unwrap()
+        .is_none());
+
+    WellnessRepository::insert(
+        &conn,
+        &WellnessEventInsert {
+            window_start: Utc::now().to_rfc3339(),
+            trigger_reason: WellnessTriggerReason::WindDown,
+            recommended_break_minutes: 30,
+            suggested_micro_task: None,
+        },
+    )
+    .expect("insert wind-down nudge");
+    let critical = wellness_service
+        .get_pending_nudge()
+        .unwrap()
+        .expect("critical nudge still surfaces");
+    assert_eq!(critical.trigger_reason, WellnessTriggerReason::WindDown);
+
+    wellness_service.end_focus_session().expect("end session");
+    assert!(wellness_service.current_focus_session().unwrap().is_none());
+    let pending = wellness_service
+        .get_pending_nudge()
+        .unwrap()
+        .expect("deferred nudge returns");
+    assert_eq!(pending.trigger_reason, WellnessTriggerReason::FocusStreak);
+
+    assert!(wellness_service.start_focus_session(Some(0), None).is_err());
+}
@@ -2,7 +2,7 @@ use chrono::{Duration, Utc};
 use cognical_app_lib::db::repositories::workload_repository::WorkloadRepository;
 use cognical_app_lib::db::DbPool;
 use cognical_app_lib::models::task::TaskCreateInput;
-use cognical_app_lib::models::workload::WorkloadHorizon;
+use cognical_app_lib::models::workload::{WorkloadGranularity, WorkloadHorizon};
 use cognical_app_lib::services::task_service::TaskService;
 use cognical_app_lib::services::workload_forecast_service::WorkloadForecastService;
 use std::sync::Arc;
@@ -42,6 +42,11 @@ fn test_workload_forecast_generation() {
                 recurrence: None,
                 ai: None,
                 external_links: None,
+                project_id: None,
+                color: None,
+                icon: None,
+                delegated_to: None,
+                waiting_on: None,
             })
             .unwrap();
     }
@@ -116,6 +121,11 @@ fn test_workload_forecast_risk_levels() {
                 recurrence: None,
                 ai: None,
                 external_links: None,
+                project_id: None,
+                color: None,
+                icon: None,
+                delegated_to: None,
+                waiting_on: None,
             })
             .unwrap();
     }
@@ -177,7 +187,9 @@ fn test_get_all_latest_forecasts() {
     forecast_service.generate_forecasts(Some(40.0)).unwrap();
 
     // Retrieve all latest forecasts
-    let all_latest = forecast_service.get_all_latest_forecasts().unwrap();
+    let all_latest = forecast_service
+        .get_all_latest_forecasts(WorkloadGranularity::Day)
+        .unwrap();
 
     // Should return 3 forecasts (7d, 14d, 30d)
     assert_eq!(all_latest.len(), 3);
@@ -187,3 +199,71 @@ fn test_get_all_latest_forecasts() {
     assert!(all_latest.iter().any(|f| f.horizon == "14d"));
     assert!(all_latest.iter().any(|f| f.horizon == "30d"));
 }
+
+#[test]
+fn test_workload_forecast_intervals_and_weekly_buckets() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let db_path = temp_file.path();
+    let pool = DbPool::new(db_path).unwrap();
+
+    let task_service = Arc::new(TaskService::new(pool.clone()));
+    let forecast_service = WorkloadForecastService::new(pool.clone(), task_service.clone());
+
+    let now = Utc::now();
+    for (i, estimate) in [Some(4.0), None, Some(6.0)].into_iter().enumerate() {
+        task_service
+            .create_task(TaskCreateInput {
+                title: format!("Interval Task {}", i),
+                description: None,
+                status: Some("todo".to_string()),
+                priority: Some("medium".to_string()),
+                planned_start_at: None,
+                start_at: None,
+                due_at: Some((now + Duration::days(i as i64 * 5 + 1)).to_rfc3339()),
+                completed_at: None,
+                estimated_minutes: None,
+                estimated_hours: estimate,
+                tags: None,
+                owner_id: None,
+                task_type: None,
+                is_recurring: None,
+                recurrence: None,
+                ai: None,
+                external_links: None,
+                project_id: None,
+                color: None,
+                icon: None,
+                delegated_to: None,
+                waiting_on: None,
+            })
+            .unwrap();
+    }
+
+    let forecasts = forecast_service
+        .generate_forecasts_with(
+            &[WorkloadHorizon::FourteenDays],
+            WorkloadGranularity::Week,
+            Some(40.0),
+        )
+        .unwrap();
+    assert_eq!(forecasts.len(), 1);
+
+    let forecast = &forecasts[0];
+    assert_eq!(forecast.horizon, "14d");
+    assert_eq!(forecast.total_hours, 11.0);
+    assert!(forecast.low_hours < forecast.total_hours);
+    assert!(forecast.high_hours > forecast.total_hours);
+    assert!(forecast.low_hours >= 0.0);
+
+    // 15 days from today through the horizon end, in seven-day groups.
+    assert_eq!(forecast.buckets.len(), 3);
+    let bucketed: f64 = forecast.buckets.iter().map(|b| b.expected_hours).sum();
+    assert!((bucketed - forecast.total_hours).abs() < 1e-9);
+
+    let daily = forecast_service
+        .get_latest_forecast(WorkloadHorizon::FourteenDays, WorkloadGranularity::Day)
+        .unwrap()
+        .expect("stored 14-day forecast");
+    assert_eq!(daily.buckets.len(), 15);
+    assert_eq!(daily.high_hours, forecast.high_hours);
+}
@@ -1,5 +1,5 @@
 use cognical_app_lib::models::memory::{
-    MemoryExportFormat, MemoryExportOptions, MemorySearchQuery,
+    MemoryExportBundle, MemoryExportFormat, MemoryExportOptions, MemoryFact, MemoryFactKind, MemorySearchQuery,
 };
 use cognical_app_lib::services::memory_service::MemoryService;
 use chrono::{Duration, Utc};
@@ -37,6 +37,58 @@ async fn test_store_conversation_success() {
     assert_eq!(stats.total_topics, 2);
 }
 
+#[tokio::test]
+async fn test_incognito_conversation_is_never_stored() {
+    let (service, _temp_dir) = setup_test_memory_service().await;
+
+    service
+        .store_conversation("before", "Earlier question", "Earlier answer", Vec::new())
+        .await
+        .expect("Failed to store conversation");
+    service.mark_incognito("private");
+    assert!(service.is_incognito("private"));
+
+    let result = service
+        .store_conversation("private", "A sensitive question", "An answer", Vec::new())
+        .await;
+    assert!(result.is_err());
+
+    let stats = service.get_memory_stats().expect("Failed to get stats");
+    assert_eq!(stats.total_documents, 1);
+    let docs = service
+        .search_by_conversation_id("private")
+        .await
+        .expect("Failed to search by conversation");
+    assert!(docs.is_empty());
+}
+
+#[tokio::test]
+async fn test_index_limit_keeps_newest_documents_on_disk_and_indexes_the_rest() {
+    let temp_dir = tempdir().expect("Failed to create temp directory");
+    let memory_dir = temp_dir.path().join("memory");
+    let service = MemoryService::new_with_index_limit(memory_dir.clone(), Some(2))
+        .expect("Failed to create memory service");
+
+    for conversation in ["first", "second", "third"] {
+        service
+            .store_conversation(conversation, "Question", "Answer", Vec::new())
+            .await
+            .expect("Failed to store conversation");
+    }
+
+    let stats = service.get_memory_stats().expect("Failed to get stats");
+    assert_eq!(stats.total_documents, 2);
+    assert!(service
+        .search_by_conversation_id("first")
+        .await
+        .expect("Failed to search by conversation")
+        .is_empty());
+
+    // Lifting the limit brings the trimmed document back from disk
+    let unlimited = MemoryService::new(memory_dir).expect("Failed to reopen memory service");
+    assert_eq!(unlimited.get_memory_stats().unwrap().total_documents, 3);
+}
+
 #[tokio::test]
 async fn test_search_memory_basic() {
     let (service, _temp_dir) = setup_test_memory_service().await;
@@ -177,6 +229,7 @@ async fn test_export_memory_archive() {
         include_metadata: true,
         date_range: None,
         format: MemoryExportFormat::Archive,
+        bundle: None,
     };
     
     let result = service.export_memory_archive(&export_options).await;
@@ -207,6 +260,7 @@ async fn test_export_as_json() {
         include_metadata: false,
         date_range: None,
         format: MemoryExportFormat::Json,
+        bundle: None,
     };
     
     let result = service.export_memory_archive(&export_options).await;
@@ -239,6 +293,7 @@ async fn test_export_as_markdown() {
         include_metadata: false,
         date_range: None,
         format: MemoryExportFormat::Markdown,
+        bundle: None,
     };
     
     let result = service.export_memory_archive(&export_options).await;
@@ -253,6 +308,53 @@ async fn test_export_as_markdown() {
     assert!(md_content.contains("md_test"));
 }
 
+#[tokio::test]
+async fn test_export_as_zip_bundle() {
+    let (service, temp_dir) = setup_test_memory_service().await;
+
+    for i in 0..3 {
+        let _ = service
+            .store_conversation(
+                &format!("zip_test_{}", i),
+                "Zip export question",
+                "Zip export answer",
+                vec!["export".to_string()],
+            )
+            .await;
+    }
+
+    let bundle_path = temp_dir.path().join("exports").join("memory.zip");
+    let export_options = MemoryExportOptions {
+        output_path: bundle_path.clone(),
+        include_metadata: true,
+        date_range: None,
+        format: MemoryExportFormat::Archive,
+        bundle: Some(MemoryExportBundle::Zip),
+    };
+
+    let files = service
+        .export_memory_archive(&export_options)
+        .await
+        .expect("zip export");
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].path, bundle_path);
+
+    let mut archive =
+        zip::ZipArchive::new(fs::File::open(&bundle_path).expect("open bundle")).expect("read zip");
+    let names: Vec<String> = archive.file_names().map(str::to_string).collect();
+    assert_eq!(names.iter().filter(|name| name.ends_with(".md")).count(), 3);
+    assert!(names.contains(&"metadata.json".to_string()));
+
+    let mut metadata = String::new();
+    std::io::Read::read_to_string(
+        &mut archive.by_name("metadata.json").expect("metadata entry"),
+        &mut metadata,
+    )
+    .expect("read metadata");
+    let metadata: serde_json::Value = serde_json::from_str(&metadata).expect("metadata json");
+    assert_eq!(metadata.as_array().map(Vec::len), Some(3));
+}
+
 #[tokio::test]
 async fn test_cleanup_old_memories() {
     let (service, _temp_dir) = setup_test_memory_service().await;
@@ -449,4 +551,244 @@ async fn test_relevance_scoring() {
                 >= context.relevant_documents[i + 1].metadata.relevance_score
         );
     }
-}
\ No newline at end of file
+}
+
+fn find_document_file(dir: &std::path::Path, doc_id: &str) -> Option<std::path::PathBuf> {
+    for entry in fs::read_dir(dir).ok()? {
+        let path = entry.ok()?.path();
+        if path.is_dir() {
+            if let Some(found) = find_document_file(&path, doc_id) {
+                return Some(found);
+            }
+        } else if path.file_stem().and_then(|stem| stem.to_str()) == Some(doc_id) {
+            return Some(path);
+        }
+    }
+    None
+}
+
+#[tokio::test]
+async fn test_index_snapshot_survives_restart() {
+    let temp_dir = tempdir().expect("Failed to create temp directory");
+    let memory_dir = temp_dir.path().join("memory");
+
+    let service = MemoryService::new(memory_dir.clone()).expect("Failed to create memory service");
+    let budget_id = service
+        .store_conversation(
+            "budget",
+            "Plan the quarterly budget review",
+            "Block two hours for the budget review",
+            vec!["budget".to_string()],
+        )
+        .await
+        .expect("store budget");
+    let garden_id = service
+        .store_conversation(
+            "garden",
+            "When should I water the tomatoes?",
+            "Water tomatoes early in the morning",
+            vec!["gardening".to_string()],
+        )
+        .await
+        .expect("store garden");
+    drop(service);
+
+    // First restart reads every file and writes the snapshot
+    let service = MemoryService::new(memory_dir.clone()).expect("Failed to reopen memory service");
+    assert_eq!(service.get_memory_stats().unwrap().total_documents, 2);
+    assert!(memory_dir.join(".index-snapshot.json").exists());
+    drop(service);
+
+    // Change one document and delete the other between runs
+    let budget_path = find_document_file(&memory_dir, &budget_id).expect("budget file");
+    let mut content = fs::read_to_string(&budget_path).unwrap();
+    content.push_str("\nRemember the zucchini invoice.\n");
+    fs::write(&budget_path, content).unwrap();
+    fs::remove_file(find_document_file(&memory_dir, &garden_id).expect("garden file")).unwrap();
+
+    let service = MemoryService::new(memory_dir.clone()).expect("Failed to reopen memory service");
+    assert_eq!(service.get_memory_stats().unwrap().total_documents, 1);
+    let context = service.search_memory("zucchini", 5).await.unwrap();
+    assert_eq!(context.relevant_documents.len(), 1);
+    assert_eq!(context.relevant_documents[0].id, budget_id);
+    assert!(context.relevant_documents[0].content.contains("zucchini"));
+}
+
+#[tokio::test]
+async fn test_created_at_comes_from_document_not_load_time() {
+    let temp_dir = tempdir().expect("Failed to create temp directory");
+    let memory_dir = temp_dir.path().join("memory");
+    let legacy_dir = memory_dir.join("2024").join("03");
+    fs::create_dir_all(&legacy_dir).unwrap();
+    // Written before the frontmatter carried a full timestamp
+    fs::write(
+        legacy_dir.join("legacy-doc.md"),
+        "---\ndate: 2024-03-05\ntopics:\n- planning\nparticipants:\n- user\n- assistant\nsummary: Old planning chat\nrelevance_score: 1.0\nconversation_id: legacy\n---\n\n# Conversation Summary: Old planning chat\n",
+    )
+    .unwrap();
+
+    let service = MemoryService::new(memory_dir.clone()).expect("Failed to create memory service");
+    let stored_id = service
+        .store_conversation(
+            "fresh",
+            "New question",
+            "New answer",
+            vec!["planning".to_string()],
+        )
+        .await
+        .expect("store");
+    drop(service);
+
+    let service = MemoryService::new(memory_dir).expect("Failed to reopen memory service");
+    let legacy = service
+        .get_documents_by_date_range("2024-03-05", "2024-03-05")
+        .await
+        .unwrap();
+    assert_eq!(legacy.len(), 1);
+    assert_eq!(legacy[0].created_at.date_naive().to_string(), "2024-03-05");
+
+    let today = Utc::now().format("%Y-%m-%d").to_string();
+    let fresh = service
+        .get_documents_by_date_range(&today, &today)
+        .await
+        .unwrap();
+    let fresh = fresh
+        .iter()
+        .find(|doc| doc.id == stored_id)
+        .expect("fresh doc");
+    assert_eq!(fresh.metadata.created_at, Some(fresh.created_at));
+
+    // Year-old memories are now eligible for archival after a restart
+    assert_eq!(service.archive_old_memories(30).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_topic_curation_rewrites_documents() {
+    let temp_dir = tempdir().expect("Failed to create temp directory");
+    let memory_dir = temp_dir.path().join("memory");
+    let service = MemoryService::new(memory_dir.clone()).expect("Failed to create memory service");
+
+    let first = service
+        .store_conversation(
+            "c1",
+            "Plan the sprint",
+            "Split it into two weeks",
+            vec!["sprint".to_string(), "planning".to_string()],
+        )
+        .await
+        .unwrap();
+    service
+        .store_conversation(
+            "c2",
+            "Review the sprint backlog",
+            "Sort by priority",
+            vec!["Sprint".to_string()],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        service
+            .merge_topics(&["Sprint".to_string()], "sprint")
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        service.rename_topic("sprint", "iteration").await.unwrap(),
+        2
+    );
+    assert_eq!(service.delete_topic("planning").await.unwrap(), 1);
+    assert!(service.rename_topic("iteration", " ").await.is_err());
+
+    let topics = service.list_topics();
+    assert_eq!(topics.len(), 1);
+    assert_eq!(topics[0].topic, "iteration");
+    assert_eq!(topics[0].document_count, 2);
+
+    // Topic changes are written to the frontmatter and survive a restart
+    drop(service);
+    let service = MemoryService::new(memory_dir).expect("Failed to reopen memory service");
+    let documents = service.search_by_conversation_id("c1").await.unwrap();
+    assert_eq!(documents[0].id, first);
+    assert_eq!(documents[0].metadata.topics, vec!["iteration".to_string()]);
+    assert!(documents[0].content.contains("Split it into two weeks"));
+}
+
+#[tokio::test]
+async fn test_pinned_and_ignored_memories() {
+    let temp_dir = tempdir().expect("Failed to create temp directory");
+    let memory_dir = temp_dir.path().join("memory");
+    let service = MemoryService::new(memory_dir.clone()).expect("Failed to create memory service");
+
+    let pinned = service
+        .store_conversation("c1", "Quarterly budget numbers", "Budget is fixed", vec![])
+        .await
+        .unwrap();
+    let ignored = service
+        .store_conversation("c2", "Budget rumor", "Ignore this budget rumor", vec![])
+        .await
+        .unwrap();
+
+    service.set_pinned(&pinned, true).await.unwrap();
+    service.set_ignored(&ignored, true).await.unwrap();
+    assert!(service.set_pinned("missing", true).await.is_err());
+
+    let context = service.search_memory("budget", 10).await.unwrap();
+    assert!(context
+        .relevant_documents
+        .iter()
+        .all(|doc| doc.id != ignored));
+
+    // Every memory is past a zero-day cutoff; only the pinned one is kept
+    assert_eq!(service.cleanup_old_memories(0).await.unwrap(), 1);
+    let stats = service.get_memory_stats().unwrap();
+    assert_eq!(stats.total_documents, 1);
+
+    drop(service);
+    let service = MemoryService::new(memory_dir).expect("Failed to reopen memory service");
+    let documents = service.search_by_conversation_id("c1").await.unwrap();
+    assert!(documents[0].metadata.pinned);
+}
+
+#[tokio::test]
+async fn test_memory_facts_round_trip() {
+    let temp_dir = tempdir().expect("Failed to create temp directory");
+    let memory_dir = temp_dir.path().join("memory");
+    let service = MemoryService::new(memory_dir.clone()).expect("Failed to create memory service");
+
+    assert!(service.get_facts().unwrap().facts.is_empty());
+
+    let constraint = MemoryFact {
+        kind: MemoryFactKind::Constraint,
+        text: "  No meetings before 10am ".to_string(),
+    };
+    let saved = service
+        .set_facts(vec![
+            constraint.clone(),
+            constraint,
+            MemoryFact {
+                kind: MemoryFactKind::Profile,
+                text: " ".to_string(),
+            },
+        ])
+        .unwrap();
+    assert_eq!(saved.facts.len(), 1);
+    assert_eq!(saved.facts[0].text, "No meetings before 10am");
+    assert_eq!(
+        saved.format_for_prompt().as_deref(),
+        Some("- Constraint: No meetings before 10am")
+    );
+
+    let too_long = MemoryFact {
+        kind: MemoryFactKind::Preference,
+        text: "x".repeat(501),
+    };
+    assert!(service.set_facts(vec![too_long]).is_err());
+
+    // Facts are not conversation documents and survive a restart
+    drop(service);
+    let service = MemoryService::new(memory_dir).expect("Failed to reopen memory service");
+    assert_eq!(service.get_memory_stats().unwrap().total_documents, 0);
+    assert_eq!(service.get_facts().unwrap(), saved);
+}
@@ -330,3 +330,26 @@ async fn test_search_tasks_tool_with_filters() {
     assert_eq!(result_json["success"], true);
     assert_eq!(result_json["count"], 2);
 }
+
+#[tokio::test]
+async fn test_stale_task_report_tool() {
+    let (service, _dir) = setup_test_service();
+
+    create_task_tool(service.clone(), json!({"title": "Fresh task"}))
+        .await
+        .unwrap();
+
+    let result = stale_task_report_tool(service.clone(), json!({}))
+        .await
+        .expect("stale_task_report_tool should succeed");
+    assert_eq!(result["success"], true);
+    assert_eq!(result["count"], 0);
+    assert_eq!(result["report"]["minDays"], 14);
+    assert!(result["message"]
+        .as_str()
+        .unwrap()
+        .contains("No open tasks"));
+
+    let result = stale_task_report_tool(service.clone(), json!({"min_days": 0})).await;
+    assert!(result.is_err(), "min_days below 1 should be rejected");
+}
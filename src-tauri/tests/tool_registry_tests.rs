@@ -1,4 +1,7 @@
-use cognical_app_lib::services::tool_registry::{ToolCall, ToolRegistry};
+use cognical_app_lib::db::DbPool;
+use cognical_app_lib::models::custom_tool::CustomToolDefinition;
+use cognical_app_lib::services::custom_tool_service::CustomToolService;
+use cognical_app_lib::services::tool_registry::{ToolCall, ToolOrigin, ToolRegistry};
 use serde_json::json;
 use std::sync::Arc;
 
@@ -500,3 +503,176 @@ async fn test_execute_multiple_tools() {
     assert!(results[0].error.is_none());
     assert!(results[1].error.is_none());
 }
+
+fn registry_with_builtin_echo() -> ToolRegistry {
+    let mut registry = ToolRegistry::new();
+    registry
+        .register_tool(
+            "create_note".to_string(),
+            "Built-in note tool".to_string(),
+            json!({
+                "type": "object",
+                "properties": {
+                    "title": {"type": "string"},
+                    "tags": {"type": "array", "items": {"type": "string"}}
+                },
+                "required": ["title"]
+            }),
+            create_echo_handler(),
+        )
+        .unwrap();
+    registry
+}
+
+fn reading_tool_definition() -> CustomToolDefinition {
+    CustomToolDefinition {
+        name: "log_reading".to_string(),
+        description: "Save an article to read later".to_string(),
+        parameters: json!({
+            "type": "object",
+            "properties": {"title": {"type": "string"}},
+            "required": ["title"]
+        }),
+        target_tool: "create_note".to_string(),
+        fixed_arguments: Some(json!({"tags": ["reading"]})),
+    }
+}
+
+#[test]
+fn test_runtime_tool_registration_through_shared_registry() {
+    let registry = Arc::new(registry_with_builtin_echo());
+
+    registry
+        .register_runtime_tool(
+            "runtime_echo".to_string(),
+            "Runtime echo".to_string(),
+            json!({"type": "object"}),
+            create_echo_handler(),
+        )
+        .unwrap();
+
+    assert_eq!(
+        registry.tool_origin("runtime_echo"),
+        Some(ToolOrigin::Runtime)
+    );
+    assert_eq!(
+        registry.tool_origin("create_note"),
+        Some(ToolOrigin::BuiltIn)
+    );
+    assert!(registry.unregister_runtime_tool("create_note").is_err());
+    assert!(registry.unregister_runtime_tool("runtime_echo").is_ok());
+    assert!(!registry.has_tool("runtime_echo"));
+
+    let invalid_schema = registry.register_runtime_tool(
+        "broken".to_string(),
+        "Broken schema".to_string(),
+        json!({"type": "object", "properties": {"x": {"type": "not-a-type"}}}),
+        create_echo_handler(),
+    );
+    assert!(invalid_schema.is_err());
+}
+
+#[tokio::test]
+async fn test_custom_tool_forwards_and_reloads_after_restart() {
+    let dir = tempfile::tempdir().unwrap();
+    let pool = DbPool::new(dir.path().join("tools.sqlite")).unwrap();
+
+    let registry = Arc::new(registry_with_builtin_echo());
+    let service = CustomToolService::new(pool.clone(), Arc::clone(&registry));
+
+    let mut clashing = reading_tool_definition();
+    clashing.name = "create_note".to_string();
+    assert!(service.register(clashing).is_err());
+
+    let mut unknown_target = reading_tool_definition();
+    unknown_target.target_tool = "missing_tool".to_string();
+    assert!(service.register(unknown_target).is_err());
+
+    service.register(reading_tool_definition()).unwrap();
+    let result = registry
+        .execute_tool(ToolCall {
+            id: "call_1".to_string(),
+            name: "log_reading".to_string(),
+            arguments: json!({"title": "Designing Data-Intensive Applications"}),
+        })
+        .await;
+    assert!(result.error.is_none(), "{:?}", result.error);
+    assert_eq!(result.result.unwrap()["echoed"]["tags"], json!(["reading"]));
+
+    // A fresh registry (simulating the next app start) picks the tool back up
+    let restarted = Arc::new(registry_with_builtin_echo());
+    let reloaded = CustomToolService::new(pool, Arc::clone(&restarted));
+    assert_eq!(reloaded.load_persisted().unwrap(), 1);
+    assert_eq!(
+        restarted.tool_origin("log_reading"),
+        Some(ToolOrigin::Runtime)
+    );
+
+    reloaded.unregister("log_reading").unwrap();
+    assert!(!restarted.has_tool("log_reading"));
+    assert!(reloaded.list().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_conversation_allowlist_hides_and_refuses_tools() {
+    let dir = tempfile::tempdir().unwrap();
+    let pool = DbPool::new(dir.path().join("tools.sqlite")).unwrap();
+
+    let build_registry = || {
+        let mut registry = registry_with_builtin_echo();
+        registry
+            .register_tool(
+                "list_notes".to_string(),
+                "Read-only note listing".to_string(),
+                json!({"type": "object", "properties": {}}),
+                create_echo_handler(),
+            )
+            .unwrap();
+        Arc::new(registry)
+    };
+    let registry = build_registry();
+    let service = CustomToolService::new(pool.clone(), Arc::clone(&registry));
+
+    assert!(service
+        .set_conversation_allowlist("demo", Some(vec!["missing_tool".to_string()]))
+        .is_err());
+    service
+        .set_conversation_allowlist("demo", Some(vec!["list_notes".to_string()]))
+        .unwrap();
+
+    assert_eq!(registry.get_tool_schemas().len(), 2);
+    let demo_schemas = registry.tool_schemas_for(Some("demo"));
+    assert_eq!(demo_schemas.len(), 1);
+    assert_eq!(demo_schemas[0]["function"]["name"], "list_notes");
+
+    let calls = vec![
+        ToolCall {
+            id: "call_write".to_string(),
+            name: "create_note".to_string(),
+            arguments: json!({"title": "Demo"}),
+        },
+        ToolCall {
+            id: "call_read".to_string(),
+            name: "list_notes".to_string(),
+            arguments: json!({}),
+        },
+    ];
+    let results = registry
+        .execute_tools_for(Some("demo"), calls.clone())
+        .await;
+    assert_eq!(results[0].tool_call_id, "call_write");
+    assert!(results[0].error.is_some());
+    assert_eq!(results[1].tool_call_id, "call_read");
+    assert!(results[1].error.is_none(), "{:?}", results[1].error);
+
+    // Other conversations are not restricted
+    let results = registry.execute_tools_for(Some("other"), calls).await;
+    assert!(results.iter().all(|result| result.error.is_none()));
+
+    // The allowlist is restored on the next start
+    let restarted = build_registry();
+    let reloaded = CustomToolService::new(pool, Arc::clone(&restarted));
+    reloaded.load_allowlist().unwrap();
+    assert!(!restarted.is_tool_allowed(Some("demo"), "create_note"));
+    assert!(restarted.is_tool_allowed(Some("demo"), "list_notes"));
+}